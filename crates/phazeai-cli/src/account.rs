@@ -0,0 +1,75 @@
+//! `phazeai login`, `logout` and `whoami`: the PhazeAI Cloud account.
+
+use anyhow::Result;
+use phazeai_cloud::{entitlements, login::LOGIN_TIMEOUT, CloudCredentials, LoginFlow};
+
+/// Sign in through the browser and store the tokens.
+pub async fn login(open_browser: bool) -> Result<()> {
+    let flow = LoginFlow::start().await?;
+    let url = flow.url();
+    if !open_browser || !open_url(&url) {
+        println!("Open this page to sign in:\n  {url}");
+    } else {
+        println!("Opened your browser to sign in. If nothing happened, open:\n  {url}");
+    }
+    println!("Waiting for the browser...");
+    let creds = flow.finish(LOGIN_TIMEOUT).await?;
+    println!(
+        "Signed in as {} ({}).",
+        creds.email.as_deref().unwrap_or("unknown"),
+        entitlements::current().tier.display_name()
+    );
+    Ok(())
+}
+
+pub fn logout() -> Result<()> {
+    let mut creds = CloudCredentials::load();
+    if !creds.is_authenticated() {
+        println!("Not signed in.");
+        return Ok(());
+    }
+    creds.logout()?;
+    println!("Signed out of PhazeAI Cloud.");
+    Ok(())
+}
+
+pub async fn whoami() -> Result<()> {
+    let client = phazeai_cloud::session_client("").await?;
+    let account = client.validate().await?;
+    let current = entitlements::refresh(&client)
+        .await
+        .unwrap_or_else(|_| entitlements::current());
+    println!("{}", account.email);
+    println!("Plan: {}", current.tier.display_name());
+    println!(
+        "Credits: {} of {} left",
+        account.credits_remaining, account.credits_limit
+    );
+    let features: Vec<&str> = current
+        .enabled()
+        .into_iter()
+        .map(|f| f.display_name())
+        .collect();
+    if !features.is_empty() {
+        println!("Features: {}", features.join(", "));
+    }
+    Ok(())
+}
+
+/// Open `url` in the default browser; `false` if no opener could be started.
+fn open_url(url: &str) -> bool {
+    let mut cmd = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    cmd.arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .is_ok()
+}
@@ -4,11 +4,19 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use phazeai_cloud::{CloudCredentials, TeamPromptContext};
 use phazeai_core::{
+    agent::recording::RunRecorder,
     collect_git_info,
-    context::{ConversationMetadata, ConversationStore, SavedConversation, SavedMessage},
+    config::{DocsSettings, LlmProvider},
+    context::{
+        budget, docs, titling, Citation, ContextBudget, ContextOverflow, ContextPart,
+        ContextSource, ConversationMetadata, ConversationStore, DocChunk, DocsIndex,
+        SavedConversation, SavedMessage,
+    },
+    llm::{StreamMeter, TaskType, UsageTracker},
     tools::{ToolApprovalManager, ToolApprovalMode},
-    Agent, AgentEvent, Settings, SystemPromptBuilder,
+    Agent, AgentEvent, MemoryStore, Settings, SystemPromptBuilder,
 };
 use ratatui::{
     backend::CrosstermBackend,
@@ -22,7 +30,7 @@ use ratatui::{
     Terminal,
 };
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
@@ -34,6 +42,7 @@ use syntect::parsing::SyntaxSet;
 
 use crate::commands::{self, CommandResult};
 use crate::companion::Companion;
+use crate::headless::{self, RunSummary};
 use crate::theme::Theme;
 
 // ── Single-prompt mode ──────────────────────────────────────────────────
@@ -42,11 +51,18 @@ pub async fn run_single_prompt(
     settings: &Settings,
     prompt: &str,
     extra_instructions: Option<&str>,
+    record: Option<&Path>,
 ) -> Result<()> {
     let llm = settings.build_llm_client()?;
     let system_prompt = build_system_prompt(extra_instructions);
 
-    let mut agent = Agent::new(llm).with_system_prompt(system_prompt);
+    let mut agent = with_team_context(
+        Agent::new(llm)
+            .with_system_prompt(system_prompt)
+            .with_memory(workspace_memory())
+            .with_limits(&settings.agent),
+    );
+    agent = with_workspace_docs(agent, &settings.docs).await;
 
     // Try to start sidecar for semantic search
     if let Some(client) = try_start_sidecar().await {
@@ -54,8 +70,19 @@ pub async fn run_single_prompt(
         agent.register_tool(Box::new(phazeai_sidecar::SemanticSearchTool::new(
             client.clone(),
         )));
+        agent.register_tool(Box::new(phazeai_sidecar::FindSimilarCodeTool::new(
+            client.clone(),
+        )));
         agent.register_tool(Box::new(phazeai_sidecar::BuildIndexTool::new(client)));
     }
+    for tool in phazeai_sidecar::plugin_sidecar_tools(&settings.plugin_sidecars).await {
+        agent.register_tool(tool);
+    }
+
+    let recorder = record.map(|_| start_recording(settings, prompt));
+    if let Some(rec) = &recorder {
+        agent = agent.with_recorder(rec.clone());
+    }
 
     let (event_tx, mut event_rx) = mpsc::unbounded_channel::<AgentEvent>();
 
@@ -64,9 +91,19 @@ pub async fn run_single_prompt(
         async move { agent.run_with_events(prompt, event_tx).await }
     });
 
+    let mut reply = String::new();
+    let mut sources = Vec::new();
     while let Some(event) = event_rx.recv().await {
         match event {
-            AgentEvent::TextDelta(text) => print!("{text}"),
+            AgentEvent::TextDelta(text) => {
+                print!("{text}");
+                reply.push_str(&text);
+            }
+            AgentEvent::DocSources(chunks) => sources = chunks,
+            AgentEvent::Citations(citations) => {
+                let cited: Vec<String> = citations.iter().map(|c| c.location()).collect();
+                eprintln!("\n[cited: {}]", cited.join(", "));
+            }
             AgentEvent::ToolStart { name } => eprintln!("\n[tool: {name}]"),
             AgentEvent::ToolResult {
                 name,
@@ -76,16 +113,137 @@ pub async fn run_single_prompt(
                 let icon = if success { "ok" } else { "err" };
                 eprintln!("[{name}: {icon}] {summary}");
             }
-            AgentEvent::Complete { .. } => println!(),
+            AgentEvent::Complete { .. } => {
+                if let Some(footer) = docs::sources_footer(&reply, &sources) {
+                    print!("{footer}");
+                }
+                println!()
+            }
+            AgentEvent::StreamResumed { attempt, reason } => {
+                eprintln!("\n[connection dropped: {reason}; resuming (attempt {attempt})]")
+            }
             AgentEvent::Error(e) => eprintln!("\nError: {e}"),
             _ => {}
         }
     }
 
-    agent_handle.await??;
+    let result = agent_handle.await?;
+    if let (Some(rec), Some(path)) = (&recorder, record) {
+        save_recording(rec, path);
+    }
+    result?;
     Ok(())
 }
 
+fn start_recording(settings: &Settings, prompt: &str) -> Arc<RunRecorder> {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let rec = RunRecorder::new(prompt, cwd);
+    rec.set_model(format!(
+        "{:?}/{}",
+        settings.llm.provider, settings.llm.model
+    ));
+    rec
+}
+
+/// Write the bundle; an empty `path` means the default location.
+fn save_recording(rec: &RunRecorder, path: &Path) {
+    let recording = rec.snapshot();
+    let path = if path.as_os_str().is_empty() {
+        recording.default_path()
+    } else {
+        path.to_path_buf()
+    };
+    match recording.save(&path) {
+        Ok(()) => eprintln!("Recorded run to {}", path.display()),
+        Err(e) => eprintln!("Failed to save recording to {}: {e}", path.display()),
+    }
+}
+
+/// Headless mode for scripts and CI: run one prompt and stream every
+/// `AgentEvent` to stdout as newline-delimited JSON. Returns the exit code.
+pub async fn run_json(
+    settings: &Settings,
+    prompt: &str,
+    extra_instructions: Option<&str>,
+    record: Option<&Path>,
+) -> Result<i32> {
+    use std::io::Write;
+
+    let mut out = io::stdout().lock();
+    let mut emit = |value: serde_json::Value| -> Result<()> {
+        writeln!(out, "{value}")?;
+        out.flush()?;
+        Ok(())
+    };
+
+    let llm = match settings.build_llm_client() {
+        Ok(llm) => llm,
+        Err(e) => {
+            let summary = RunSummary {
+                error: Some(e.to_string()),
+                ..Default::default()
+            };
+            emit(summary.to_json())?;
+            return Ok(summary.exit_code());
+        }
+    };
+
+    let mut agent = with_team_context(
+        Agent::new(llm)
+            .with_system_prompt(build_system_prompt(extra_instructions))
+            .with_memory(workspace_memory())
+            .with_limits(&settings.agent),
+    );
+    agent = with_workspace_docs(agent, &settings.docs).await;
+    if let Some(client) = try_start_sidecar().await {
+        let client = Arc::new(client);
+        agent.register_tool(Box::new(phazeai_sidecar::SemanticSearchTool::new(
+            client.clone(),
+        )));
+        agent.register_tool(Box::new(phazeai_sidecar::FindSimilarCodeTool::new(
+            client.clone(),
+        )));
+        agent.register_tool(Box::new(phazeai_sidecar::BuildIndexTool::new(client)));
+    }
+    for tool in phazeai_sidecar::plugin_sidecar_tools(&settings.plugin_sidecars).await {
+        agent.register_tool(tool);
+    }
+
+    let recorder = record.map(|_| start_recording(settings, prompt));
+    if let Some(rec) = &recorder {
+        agent = agent.with_recorder(rec.clone());
+    }
+
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<AgentEvent>();
+    let agent_handle = tokio::spawn({
+        let prompt = prompt.to_string();
+        async move { agent.run_with_events(prompt, event_tx).await }
+    });
+
+    while let Some(event) = event_rx.recv().await {
+        emit(headless::event_to_json(&event))?;
+    }
+
+    let result = agent_handle.await?;
+    if let (Some(rec), Some(path)) = (&recorder, record) {
+        save_recording(rec, path);
+    }
+    let summary = match result {
+        Ok(resp) => RunSummary {
+            iterations: resp.iterations,
+            input_tokens: resp.total_input_tokens,
+            output_tokens: resp.total_output_tokens,
+            error: None,
+        },
+        Err(e) => RunSummary {
+            error: Some(e.to_string()),
+            ..Default::default()
+        },
+    };
+    emit(summary.to_json())?;
+    Ok(summary.exit_code())
+}
+
 // ── Interactive TUI ─────────────────────────────────────────────────────
 
 #[derive(Clone)]
@@ -93,6 +251,8 @@ struct ChatMessage {
     role: MessageRole,
     content: String,
     timestamp: String,
+    /// For replies, the file ranges they are grounded in.
+    citations: Vec<Citation>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -120,6 +280,11 @@ enum ChatItem {
         /// When true, only show the header line (for future collapsible UI)
         collapsed: bool,
     },
+    /// The model's thinking before its reply; collapses once the reply starts.
+    Thinking {
+        text: String,
+        collapsed: bool,
+    },
 }
 
 /// What we're waiting on from the user during tool approval
@@ -158,6 +323,22 @@ struct AppState {
     total_tokens_in: u64,
     total_tokens_out: u64,
     estimated_cost: f64,
+    /// Throughput of the response being streamed (or the last one).
+    stream_meter: Option<StreamMeter>,
+    /// Dropped streams resumed during the current request.
+    stream_resumes: u32,
+    /// Documentation passages given to the model for the current request.
+    doc_sources: Vec<DocChunk>,
+    /// Time to first token per provider this session, shown by /status.
+    usage: UsageTracker,
+    /// Estimated size of the agent's system prompt.
+    system_prompt_tokens: usize,
+    /// Files sent with /add since the history was last cleared, with their
+    /// estimated size.
+    added_files: Vec<(String, usize)>,
+    /// Input already warned about as too big for the context window; sending
+    /// it again goes ahead.
+    overflow_confirmed: Option<String>,
 
     // Display
     should_quit: bool,
@@ -167,6 +348,9 @@ struct AppState {
     // Conversation management
     conversation_id: String,
     conversation_store: ConversationStore,
+    /// Tools switched off for the session, restored from a resumed
+    /// conversation and saved with each conversation.
+    disabled_tools: Vec<String>,
 
     // Session Picker UI
     show_session_picker: bool,
@@ -222,6 +406,7 @@ impl AppState {
                     cwd
                 ),
                 timestamp: now_str(),
+                citations: Vec::new(),
             })],
             scroll_offset: 0,
             total_content_lines: 0,
@@ -235,6 +420,13 @@ impl AppState {
             iterations: 0,
             total_tokens_in: 0,
             total_tokens_out: 0,
+            stream_meter: None,
+            stream_resumes: 0,
+            doc_sources: Vec::new(),
+            usage: UsageTracker::default(),
+            system_prompt_tokens: 0,
+            added_files: Vec::new(),
+            overflow_confirmed: None,
             estimated_cost: 0.0,
 
             should_quit: false,
@@ -243,6 +435,7 @@ impl AppState {
 
             conversation_id: conv_id,
             conversation_store: store,
+            disabled_tools: Vec::new(),
 
             show_session_picker: false,
             session_picker_list: Vec::new(),
@@ -260,11 +453,39 @@ impl AppState {
         }
     }
 
+    /// List the documentation passages the reply to the last request cites
+    /// under its final message.
+    fn append_sources_footer(&mut self, sources: &[DocChunk]) {
+        let reply: String = self
+            .messages
+            .iter()
+            .rev()
+            .map_while(|item| match item {
+                ChatItem::Message(m) if m.role == MessageRole::User => None,
+                ChatItem::Message(m) if m.role == MessageRole::Assistant => {
+                    Some(m.content.as_str())
+                }
+                _ => Some(""),
+            })
+            .collect();
+        let Some(footer) = docs::sources_footer(&reply, sources) else {
+            return;
+        };
+        let last = self.messages.iter_mut().rev().find_map(|item| match item {
+            ChatItem::Message(m) if m.role == MessageRole::Assistant => Some(m),
+            _ => None,
+        });
+        if let Some(m) = last {
+            m.content.push_str(&footer);
+        }
+    }
+
     fn add_message(&mut self, role: MessageRole, content: String) {
         self.messages.push(ChatItem::Message(ChatMessage {
             role,
             content,
             timestamp: now_str(),
+            citations: Vec::new(),
         }));
         self.scroll_to_bottom();
     }
@@ -347,8 +568,8 @@ impl AppState {
         let messages: Vec<SavedMessage> = self
             .messages
             .iter()
-            .map(|item| match item {
-                ChatItem::Message(m) => SavedMessage {
+            .filter_map(|item| match item {
+                ChatItem::Message(m) => Some(SavedMessage {
                     role: match m.role {
                         MessageRole::User => "user".into(),
                         MessageRole::Assistant => "assistant".into(),
@@ -361,7 +582,8 @@ impl AppState {
                     content: m.content.clone(),
                     timestamp: m.timestamp.clone(),
                     tool_name: None,
-                },
+                    citations: m.citations.clone(),
+                }),
                 ChatItem::ToolCard {
                     name,
                     output,
@@ -373,13 +595,15 @@ impl AppState {
                         Some(false) => "err",
                         None => "running",
                     };
-                    SavedMessage {
+                    Some(SavedMessage {
                         role: "tool".into(),
                         content: format!("[{name}: {icon}] {output}"),
                         timestamp: now_str(),
                         tool_name: Some(name.clone()),
-                    }
+                        citations: Vec::new(),
+                    })
                 }
+                ChatItem::Thinking { .. } => None,
             })
             .collect();
 
@@ -413,6 +637,9 @@ impl AppState {
             message_count: messages.len(),
             model: self.model_name.clone(),
             project_dir: cwd,
+            tags: Vec::new(),
+            title_source: Default::default(),
+            disabled_tools: self.disabled_tools.clone(),
         };
 
         let conversation = SavedConversation {
@@ -423,10 +650,39 @@ impl AppState {
 
         if let Err(e) = self.conversation_store.save(&conversation) {
             tracing::warn!("Failed to save conversation: {e}");
+            return;
+        }
+        if titling::wants_auto_title(&conversation) {
+            spawn_auto_title(conversation.metadata.id);
         }
     }
 }
 
+/// " (tools off: …)" for a conversation with tools switched off.
+fn tools_off_note(disabled_tools: &[String]) -> String {
+    if disabled_tools.is_empty() {
+        String::new()
+    } else {
+        format!(" (tools off: {})", disabled_tools.join(", "))
+    }
+}
+
+/// Title and tag a conversation in the background with the quick-answer model.
+fn spawn_auto_title(id: String) {
+    tokio::spawn(async move {
+        let settings = Settings::load();
+        let result = async {
+            let llm = settings.build_client_for_task(TaskType::QuickAnswer)?;
+            let store = ConversationStore::new()?;
+            titling::auto_title(&store, llm.as_ref(), &id).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to title conversation {id}: {e}");
+        }
+    });
+}
+
 pub async fn run_tui(
     settings: Settings,
     theme_name: &str,
@@ -450,6 +706,7 @@ pub async fn run_tui(
             if let Some(meta) = recent.first() {
                 if let Ok(conv) = state.conversation_store.load(&meta.id) {
                     state.conversation_id = conv.metadata.id.clone();
+                    state.disabled_tools = conv.metadata.disabled_tools.clone();
                     for msg in &conv.messages {
                         let role = match msg.role.as_str() {
                             "user" => MessageRole::User,
@@ -461,6 +718,7 @@ pub async fn run_tui(
                             role: role.clone(),
                             content: msg.content.clone(),
                             timestamp: msg.timestamp.clone(),
+                            citations: msg.citations.clone(),
                         }));
                         // Collect user/assistant pairs for agent context
                         if msg.role == "user" || msg.role == "assistant" {
@@ -469,7 +727,11 @@ pub async fn run_tui(
                     }
                     state.add_message(
                         MessageRole::System,
-                        format!("Resumed: {}", conv.metadata.title),
+                        format!(
+                            "Resumed: {}{}",
+                            conv.metadata.title,
+                            tools_off_note(&conv.metadata.disabled_tools)
+                        ),
                     );
                     state.scroll_to_bottom();
                 }
@@ -481,6 +743,7 @@ pub async fn run_tui(
             if let Some(meta) = recent.iter().find(|m| m.id.starts_with(id)) {
                 if let Ok(conv) = state.conversation_store.load(&meta.id) {
                     state.conversation_id = conv.metadata.id.clone();
+                    state.disabled_tools = conv.metadata.disabled_tools.clone();
                     for msg in &conv.messages {
                         let role = match msg.role.as_str() {
                             "user" => MessageRole::User,
@@ -492,6 +755,7 @@ pub async fn run_tui(
                             role: role.clone(),
                             content: msg.content.clone(),
                             timestamp: msg.timestamp.clone(),
+                            citations: msg.citations.clone(),
                         }));
                         if msg.role == "user" || msg.role == "assistant" {
                             restore_messages.push((msg.role.clone(), msg.content.clone()));
@@ -499,7 +763,11 @@ pub async fn run_tui(
                     }
                     state.add_message(
                         MessageRole::System,
-                        format!("Resumed: {}", conv.metadata.title),
+                        format!(
+                            "Resumed: {}{}",
+                            conv.metadata.title,
+                            tools_off_note(&conv.metadata.disabled_tools)
+                        ),
                     );
                     state.scroll_to_bottom();
                 } else {
@@ -518,6 +786,7 @@ pub async fn run_tui(
     }
 
     let system_prompt = build_system_prompt(extra_instructions);
+    state.system_prompt_tokens = budget::estimate_tokens(&system_prompt);
 
     let llm = match settings.build_llm_client() {
         Ok(llm) => Some(llm),
@@ -581,11 +850,20 @@ pub async fn run_tui(
         });
 
         let cancel_token = state.cancel_token.clone();
+        let disabled_tools = state.disabled_tools.clone();
+        let limits = settings.agent.clone();
+        let plugin_sidecars = settings.plugin_sidecars.clone();
+        let docs_settings = settings.docs.clone();
         let handle = tokio::spawn(async move {
-            let mut agent = Agent::new(llm)
-                .with_system_prompt(system_prompt)
-                .with_approval(approval_fn)
-                .with_cancel_token(cancel_token.clone());
+            let mut agent = with_team_context(
+                Agent::new(llm)
+                    .with_system_prompt(system_prompt)
+                    .with_memory(workspace_memory())
+                    .with_approval(approval_fn)
+                    .with_cancel_token(cancel_token.clone())
+                    .with_limits(&limits),
+            );
+            agent = with_workspace_docs(agent, &docs_settings).await;
 
             // Connect to MCP servers
             let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
@@ -602,9 +880,22 @@ pub async fn run_tui(
                 agent.register_tool(Box::new(phazeai_sidecar::SemanticSearchTool::new(
                     client.clone(),
                 )));
+                agent.register_tool(Box::new(phazeai_sidecar::FindSimilarCodeTool::new(
+                    client.clone(),
+                )));
                 agent.register_tool(Box::new(phazeai_sidecar::BuildIndexTool::new(client)));
             }
 
+            // Tools from plugin sidecars (Node, Go, … helpers) in the settings.
+            for tool in phazeai_sidecar::plugin_sidecar_tools(&plugin_sidecars).await {
+                agent.register_tool(tool);
+            }
+
+            // Keep the resumed conversation's tools switched off.
+            if !disabled_tools.is_empty() {
+                agent = agent.with_tool_filter(|t| !disabled_tools.iter().any(|d| d == t.name()));
+            }
+
             // Load any restored history
             if !restore_msgs.is_empty() {
                 agent.load_history(restore_msgs).await;
@@ -641,12 +932,8 @@ pub async fn run_tui(
                     }
                 });
 
-                if let Err(e) = agent_fut.await {
-                    let err_str = e.to_string();
-                    if err_str != "Cancelled" {
-                        let _ = event_tx.send(AgentEvent::Error(err_str));
-                    }
-                }
+                // Failures (timeouts included) arrive as AgentEvent::Error.
+                let _ = agent_fut.await;
 
                 forward.abort();
             }
@@ -720,6 +1007,46 @@ fn apply_mode_prefix(mode: &str, input: &str) -> String {
     }
 }
 
+/// Project memory for the current directory.
+fn workspace_memory() -> MemoryStore {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    MemoryStore::for_workspace(&cwd)
+}
+
+/// Search the project documentation configured under `[docs]` for each
+/// request, bringing the index up to date first.
+async fn with_workspace_docs(agent: Agent, settings: &DocsSettings) -> Agent {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let settings = settings.clone();
+    let index = tokio::task::spawn_blocking(move || DocsIndex::for_workspace(&cwd, &settings))
+        .await
+        .ok()
+        .flatten();
+    match index {
+        Some(index) => agent.with_docs(Arc::new(index)),
+        None => agent,
+    }
+}
+
+/// Consult the shared team context when signed in to PhazeAI Cloud,
+/// refreshing entitlements and the cached snapshot in the background.
+fn with_team_context(agent: Agent) -> Agent {
+    let creds = CloudCredentials::load();
+    if !creds.is_authenticated() {
+        return agent;
+    }
+    tokio::spawn(async move {
+        let result = match phazeai_cloud::session_client("").await {
+            Ok(client) => phazeai_cloud::sync_account(&client).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::debug!("PhazeAI Cloud sync failed: {e}");
+        }
+    });
+    agent.with_prompt_context(Arc::new(TeamPromptContext))
+}
+
 fn build_system_prompt(extra_instructions: Option<&str>) -> String {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
 
@@ -1358,6 +1685,46 @@ fn render_tool_card_lines(
     lines
 }
 
+fn render_thinking_lines(text: &str, collapsed: bool, theme: &Theme) -> Vec<Line<'static>> {
+    let collapse_icon = if collapsed { "▸" } else { "▾" };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!("   {collapse_icon} "),
+            Style::default().fg(theme.dim),
+        ),
+        Span::styled(
+            "Thinking…".to_string(),
+            Style::default()
+                .fg(theme.muted)
+                .add_modifier(Modifier::ITALIC),
+        ),
+    ])];
+
+    if !collapsed {
+        // The latest lines, so the section follows the model as it thinks
+        let total_lines = text.lines().count();
+        if total_lines > 10 {
+            lines.push(Line::from(Span::styled(
+                format!("     │ {} earlier lines", total_lines - 10),
+                Style::default().fg(theme.dim),
+            )));
+        }
+        for content_line in text.lines().skip(total_lines.saturating_sub(10)) {
+            lines.push(Line::from(vec![
+                Span::styled("     │ ".to_string(), Style::default().fg(theme.dim)),
+                Span::styled(
+                    content_line.to_string(),
+                    Style::default()
+                        .fg(theme.muted)
+                        .add_modifier(Modifier::ITALIC),
+                ),
+            ]));
+        }
+    }
+
+    lines
+}
+
 fn build_chat_lines(
     messages: &[ChatItem],
     is_processing: bool,
@@ -1373,6 +1740,13 @@ fn build_chat_lines(
                     chat_lines.push(Line::raw(""));
                 }
                 chat_lines.extend(render_message_lines(msg, theme));
+                if !msg.citations.is_empty() {
+                    let cited: Vec<String> = msg.citations.iter().map(|c| c.location()).collect();
+                    chat_lines.push(Line::from(Span::styled(
+                        format!("  ↳ {}", cited.join(" · ")),
+                        Style::default().fg(theme.muted),
+                    )));
+                }
                 chat_lines.push(Line::raw(""));
             }
             ChatItem::ToolCard {
@@ -1386,6 +1760,9 @@ fn build_chat_lines(
                     name, args, output, *success, *collapsed, theme,
                 ));
             }
+            ChatItem::Thinking { text, collapsed } => {
+                chat_lines.extend(render_thinking_lines(text, *collapsed, theme));
+            }
         }
     }
 
@@ -1551,6 +1928,13 @@ fn draw_status_bar(f: &mut ratatui::Frame, area: Rect, state: &AppState, theme:
             format!("{frame} {}", state.status_text),
             Style::default().fg(theme.accent),
         ));
+        if let Some(meter) = &state.stream_meter {
+            spans.push(sep.clone());
+            spans.push(Span::styled(
+                meter.summary(),
+                Style::default().fg(theme.muted),
+            ));
+        }
     } else {
         spans.push(Span::styled(
             state.status_text.clone(),
@@ -1662,6 +2046,10 @@ fn draw_session_picker(f: &mut ratatui::Frame, area: Rect, state: &AppState) {
 fn handle_agent_event(state: &mut AppState, event: AgentEvent) {
     match event {
         AgentEvent::Thinking { iteration } => {
+            if iteration == 1 {
+                state.stream_meter = Some(StreamMeter::start());
+                state.stream_resumes = 0;
+            }
             state.iterations = iteration;
             state.status_text = format!("Thinking... (step {iteration})");
             state.companion.on_thinking();
@@ -1675,6 +2063,12 @@ fn handle_agent_event(state: &mut AppState, event: AgentEvent) {
             state.companion.on_approval();
         }
         AgentEvent::TextDelta(text) => {
+            if let Some(meter) = &mut state.stream_meter {
+                meter.on_text(&text);
+            }
+            if let Some(ChatItem::Thinking { collapsed, .. }) = state.messages.last_mut() {
+                *collapsed = true;
+            }
             if let Some(ChatItem::Message(m)) = state.messages.last_mut() {
                 if m.role == MessageRole::Assistant {
                     m.content.push_str(&text);
@@ -1684,6 +2078,18 @@ fn handle_agent_event(state: &mut AppState, event: AgentEvent) {
             }
             state.add_message(MessageRole::Assistant, text);
         }
+        AgentEvent::ReasoningDelta(text) => {
+            state.status_text = "Thinking...".into();
+            if let Some(ChatItem::Thinking { text: thinking, .. }) = state.messages.last_mut() {
+                thinking.push_str(&text);
+            } else {
+                state.messages.push(ChatItem::Thinking {
+                    text,
+                    collapsed: false,
+                });
+            }
+            state.scroll_to_bottom();
+        }
         AgentEvent::ToolStart { name } => {
             state.status_text = match name.as_str() {
                 "bash" => "Running bash...".into(),
@@ -1706,10 +2112,16 @@ fn handle_agent_event(state: &mut AppState, event: AgentEvent) {
             state.finish_tool_card(&name, summary, success);
             state.pending_approval = None;
         }
+        AgentEvent::FirstToken { latency } => {
+            state.usage.track_first_token(&state.provider_name, latency);
+        }
         AgentEvent::TokenUsage {
             input_tokens,
             output_tokens,
         } => {
+            if let Some(meter) = &mut state.stream_meter {
+                meter.set_output_tokens(output_tokens);
+            }
             state.total_tokens_in += input_tokens;
             state.total_tokens_out += output_tokens;
             state.estimated_cost += estimate_cost(
@@ -1719,17 +2131,54 @@ fn handle_agent_event(state: &mut AppState, event: AgentEvent) {
                 output_tokens,
             );
         }
+        AgentEvent::DocSources(chunks) => state.doc_sources = chunks,
+        AgentEvent::Citations(citations) => {
+            let last = state.messages.iter_mut().rev().find_map(|item| match item {
+                ChatItem::Message(m) if m.role == MessageRole::Assistant => Some(m),
+                _ => None,
+            });
+            if let Some(m) = last {
+                m.citations = citations;
+            }
+        }
         AgentEvent::Complete { iterations } => {
             state.is_processing = false;
-            state.status_text = format!("Done ({iterations} steps)");
+            let sources = std::mem::take(&mut state.doc_sources);
+            if !sources.is_empty() {
+                state.append_sources_footer(&sources);
+            }
+            state.status_text = match &state.stream_meter {
+                Some(meter) => format!("Done ({iterations} steps) · {}", meter.summary()),
+                None => format!("Done ({iterations} steps)"),
+            };
+            if state.stream_resumes > 0 {
+                let times = match state.stream_resumes {
+                    1 => "once".to_string(),
+                    n => format!("{n} times"),
+                };
+                state.add_message(
+                    MessageRole::System,
+                    format!("↻ Recovered: the connection dropped {times}; the reply was resumed where it stopped."),
+                );
+            }
             state.scroll_to_bottom();
             state.companion.on_complete();
             if state.messages.len().is_multiple_of(10) {
                 state.save_conversation();
             }
         }
+        AgentEvent::StreamResumed { attempt, reason } => {
+            state.stream_resumes += 1;
+            state.status_text = format!("Connection dropped ({reason}), resuming #{attempt}...");
+        }
+        // cancel_request already told the user.
+        AgentEvent::Error(e) if e == "Cancelled" => {
+            state.is_processing = false;
+            state.doc_sources.clear();
+        }
         AgentEvent::Error(e) => {
             state.is_processing = false;
+            state.doc_sources.clear();
             state.add_message(MessageRole::System, format!("Error: {e}"));
             state.status_text = "Error".into();
             state.companion.on_error();
@@ -1740,6 +2189,107 @@ fn handle_agent_event(state: &mut AppState, event: AgentEvent) {
     }
 }
 
+/// Estimate the next request — system prompt, the conversation so far, files
+/// sent with /add and `input` — against the current model's context window.
+/// Models whose window isn't known pass.
+fn check_context(state: &AppState, input: &str) -> Result<(), ContextOverflow> {
+    let Some(provider) = LlmProvider::from_name(&state.provider_name) else {
+        return Ok(());
+    };
+    let Some(window) = budget::context_window(&provider.to_provider_id(), &state.model_name) else {
+        return Ok(());
+    };
+    let mut parts = ContextBudget::new();
+    parts.parts.push(ContextPart {
+        source: ContextSource::SystemPrompt,
+        label: "System prompt".into(),
+        tokens: state.system_prompt_tokens,
+    });
+    let mut history = String::new();
+    let mut count = 0;
+    for item in &state.messages {
+        match item {
+            ChatItem::Message(m) if m.role == MessageRole::System => {}
+            ChatItem::Message(m) => {
+                history.push_str(&m.content);
+                count += 1;
+            }
+            ChatItem::ToolCard { args, output, .. } => {
+                history.push_str(args);
+                history.push_str(output);
+                count += 1;
+            }
+            ChatItem::Thinking { .. } => {}
+        }
+    }
+    parts.add(
+        ContextSource::History,
+        format!("Conversation history ({count} messages)"),
+        &history,
+    );
+    for (path, tokens) in &state.added_files {
+        parts.parts.push(ContextPart {
+            source: ContextSource::Attachment(path.clone()),
+            label: format!("{path} (/add)"),
+            tokens: *tokens,
+        });
+    }
+    parts.add(ContextSource::Prompt, "Your message", input);
+    parts.check(window, Settings::load().llm.max_tokens as usize)
+}
+
+/// What's filling the context window and what to do about it.
+fn overflow_note(state: &AppState, overflow: &ContextOverflow) -> String {
+    let mut note = format!("⚠ {}. Biggest parts:\n", overflow.summary());
+    for part in overflow
+        .contributors
+        .iter()
+        .filter(|p| p.tokens > 0)
+        .take(5)
+    {
+        note.push_str(&format!("  {:<40} ~{} tok\n", part.label, part.tokens));
+    }
+    let current = LlmProvider::from_name(&state.provider_name).map(|p| p.to_provider_id());
+    let registry = Settings::load().build_provider_registry();
+    let switches: Vec<String> = budget::larger_models(&registry, overflow.estimated)
+        .into_iter()
+        .take(3)
+        .map(|(provider, model)| {
+            let window = model.context_window / 1000;
+            if Some(&provider) == current.as_ref() {
+                format!("/model {} ({window}K)", model.id)
+            } else {
+                let name = format!("{provider:?}").to_lowercase();
+                format!("/provider {name} + /model {} ({window}K)", model.id)
+            }
+        })
+        .collect();
+    note.push_str("Summarize the history with /compact");
+    if !switches.is_empty() {
+        note.push_str(&format!(", switch model ({})", switches.join(", ")));
+    }
+    note.push_str(", or press Enter to send anyway.");
+    note
+}
+
+/// Stop the running request: the agent drops its in-flight model call or
+/// tool, and a pending approval is denied.
+fn cancel_request(state: &mut AppState) {
+    state.cancel_token.store(true, Ordering::Relaxed);
+    let sender = state
+        .approval_tx
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    if let Some(tx) = sender {
+        let _ = tx.send(false);
+    }
+    state.pending_approval = None;
+    state.is_processing = false;
+    state.status_text = "Cancelled".into();
+    state.add_message(MessageRole::System, "Request cancelled.".into());
+}
+
 fn handle_key(
     state: &mut AppState,
     key: KeyEvent,
@@ -1788,24 +2338,15 @@ fn handle_key(
         // Quit
         (KeyModifiers::CONTROL, KeyCode::Char('c')) => {
             if state.is_processing {
-                state.cancel_token.store(true, Ordering::Relaxed);
-                let sender = state
-                    .approval_tx
-                    .lock()
-                    .unwrap_or_else(|e| e.into_inner())
-                    .take();
-                if let Some(tx) = sender {
-                    let _ = tx.send(false);
-                }
-                state.pending_approval = None;
-                state.is_processing = false;
-                state.status_text = "Cancelled".into();
-                state.add_message(MessageRole::System, "Request cancelled.".into());
+                cancel_request(state);
             } else {
                 state.should_quit = true;
             }
         }
 
+        // Stop the running request
+        (_, KeyCode::Esc) if state.is_processing => cancel_request(state),
+
         // Toggle file tree
         (KeyModifiers::CONTROL, KeyCode::Char('b')) => {
             state.show_files = !state.show_files;
@@ -1847,6 +2388,7 @@ fn handle_key(
             state.messages.clear();
             state.conversation_id = ConversationStore::generate_id();
             let _ = user_input_tx.send(WorkerCommand::ClearHistory);
+            state.added_files.clear();
             state.add_message(MessageRole::System, "New conversation started.".into());
             state.scroll_offset = 0;
             state.iterations = 0;
@@ -1876,6 +2418,16 @@ fn handle_key(
             }
 
             let input = state.input.clone();
+            // Hold back a message too big for the model; Enter again sends it.
+            if !input.starts_with('/') && state.overflow_confirmed.as_ref() != Some(&input) {
+                if let Err(overflow) = check_context(state, &input) {
+                    let note = overflow_note(state, &overflow);
+                    state.add_message(MessageRole::System, note);
+                    state.overflow_confirmed = Some(input);
+                    return;
+                }
+            }
+            state.overflow_confirmed = None;
             state.input.clear();
             state.cursor_pos = 0;
             state.push_history(input.clone());
@@ -2020,7 +2572,9 @@ fn handle_key(
 
         (KeyModifiers::CONTROL, KeyCode::Char('t')) => {
             for item in state.messages.iter_mut().rev() {
-                if let ChatItem::ToolCard { collapsed, .. } = item {
+                if let ChatItem::ToolCard { collapsed, .. } | ChatItem::Thinking { collapsed, .. } =
+                    item
+                {
                     *collapsed = !*collapsed;
                     break;
                 }
@@ -2147,8 +2701,22 @@ fn handle_command_result(
                 "groq" => phazeai_core::config::LlmProvider::Groq,
                 "together" => phazeai_core::config::LlmProvider::Together,
                 "openrouter" | "or" => phazeai_core::config::LlmProvider::OpenRouter,
+                "xai" | "x.ai" | "grok" => phazeai_core::config::LlmProvider::Xai,
+                "mistral" | "mistralai" | "la-plateforme" => {
+                    phazeai_core::config::LlmProvider::Mistral
+                }
+                "huggingface" | "hf" | "tgi" => phazeai_core::config::LlmProvider::HuggingFace,
+                "bedrock" | "aws" | "aws-bedrock" => phazeai_core::config::LlmProvider::Bedrock,
                 "lmstudio" | "lm-studio" => phazeai_core::config::LlmProvider::LmStudio,
+                "llamacpp" | "llama.cpp" | "llama-cpp" => {
+                    phazeai_core::config::LlmProvider::LlamaCpp
+                }
+                "vllm" => phazeai_core::config::LlmProvider::Vllm,
                 "gemini" => phazeai_core::config::LlmProvider::Gemini,
+                "vertex" | "vertex-ai" | "gcp" => phazeai_core::config::LlmProvider::Vertex,
+                "phazecloud" | "phaze-cloud" | "cloud" => {
+                    phazeai_core::config::LlmProvider::PhazeCloud
+                }
                 _ => {
                     state.add_message(MessageRole::System, format!("Unknown provider: {provider}"));
                     return;
@@ -2230,6 +2798,7 @@ fn handle_command_result(
                 role: MessageRole::System,
                 content: summary,
                 timestamp: now_str(),
+                citations: Vec::new(),
             }));
 
             // Keep the last 6 messages
@@ -2241,6 +2810,7 @@ fn handle_command_result(
             state.messages = new_messages;
 
             let _ = user_input_tx.send(WorkerCommand::ClearHistory);
+            state.added_files.clear();
 
             let mut replay = String::new();
             for item in &state.messages {
@@ -2284,6 +2854,9 @@ fn handle_command_result(
             Ok(conv) => {
                 state.messages.clear();
                 state.conversation_id = conv.metadata.id;
+                // Saved with the conversation; the running agent keeps its tools
+                // until the conversation is resumed with --resume.
+                state.disabled_tools = conv.metadata.disabled_tools;
                 for msg in conv.messages {
                     let role = match msg.role.as_str() {
                         "user" => MessageRole::User,
@@ -2295,11 +2868,16 @@ fn handle_command_result(
                         role,
                         content: msg.content,
                         timestamp: msg.timestamp,
+                        citations: msg.citations,
                     }));
                 }
                 state.add_message(
                     MessageRole::System,
-                    format!("Loaded conversation: {}", conv.metadata.title),
+                    format!(
+                        "Loaded conversation: {}{}",
+                        conv.metadata.title,
+                        tools_off_note(&state.disabled_tools)
+                    ),
                 );
                 state.scroll_to_bottom();
             }
@@ -2317,10 +2895,12 @@ fn handle_command_result(
                 } else {
                     let mut list = String::from("Recent conversations:\n");
                     for c in &convs {
+                        let tags: String = c.tags.iter().map(|t| format!(" #{t}")).collect();
                         list.push_str(&format!(
-                            "  {} | {} | {} msgs | {}\n",
+                            "  {} | {}{} | {} msgs | {}\n",
                             &c.id[..8.min(c.id.len())],
                             c.title,
+                            tags,
                             c.message_count,
                             c.updated_at,
                         ));
@@ -2347,6 +2927,7 @@ fn handle_command_result(
             state.estimated_cost = 0.0;
             state.iterations = 0;
             let _ = user_input_tx.send(WorkerCommand::ClearHistory);
+            state.added_files.clear();
             state.add_message(MessageRole::System, "New conversation started.".into());
             state.scroll_offset = 0;
         }
@@ -2373,7 +2954,7 @@ fn handle_command_result(
             let cwd = std::env::current_dir()
                 .map(|p| p.display().to_string())
                 .unwrap_or_else(|_| "?".into());
-            let status = format!(
+            let mut status = format!(
                 "Status:\n\
                  Provider: {}\n\
                  Model: {}\n\
@@ -2391,6 +2972,26 @@ fn handle_command_result(
                 state.messages.len(),
                 state.iterations,
             );
+            for (provider, stats) in &state.usage.first_token {
+                status.push_str(&format!(
+                    "\nFirst token ({provider}): avg {}ms, min {}ms, max {}ms over {} requests",
+                    stats.average_ms(),
+                    stats.min_ms,
+                    stats.max_ms,
+                    stats.samples
+                ));
+            }
+            if let Some(quota) = phazeai_cloud::hosted::usage().quota {
+                status.push_str(&format!(
+                    "\nCloud quota: {}/{} requests left{}",
+                    quota.remaining,
+                    quota.limit,
+                    quota
+                        .resets_at
+                        .map(|at| format!(" (resets {at})"))
+                        .unwrap_or_default()
+                ));
+            }
             state.add_message(MessageRole::System, status);
         }
         CommandResult::ShowDiff => {
@@ -2551,15 +3152,27 @@ fn handle_command_result(
                 s if s.contains("groq") => phazeai_core::ProviderId::Groq,
                 s if s.contains("together") => phazeai_core::ProviderId::Together,
                 s if s.contains("openrouter") => phazeai_core::ProviderId::OpenRouter,
+                s if s.contains("xai") || s.contains("grok") => phazeai_core::ProviderId::Xai,
+                s if s.contains("mistral") => phazeai_core::ProviderId::Mistral,
+                s if s.contains("hugging") => phazeai_core::ProviderId::HuggingFace,
+                s if s.contains("bedrock") => phazeai_core::ProviderId::Bedrock,
+                s if s.contains("vertex") => phazeai_core::ProviderId::Vertex,
                 s if s.contains("lm studio") || s.contains("lmstudio") => {
                     phazeai_core::ProviderId::LmStudio
                 }
+                s if s.contains("llama.cpp") || s.contains("llamacpp") => {
+                    phazeai_core::ProviderId::LlamaCpp
+                }
+                s if s.contains("vllm") => phazeai_core::ProviderId::Vllm,
                 _ => phazeai_core::ProviderId::Claude,
             };
 
             let models = phazeai_core::ProviderRegistry::known_models(&provider);
 
-            if models.is_empty() {
+            if provider == phazeai_core::ProviderId::HuggingFace {
+                state.add_message(MessageRole::System,
+                    "Hugging Face models are listed from the hub: run `phazeai models hub` to see your account's models and Inference Endpoints.".into());
+            } else if models.is_empty() {
                 state.add_message(MessageRole::System,
                     format!("No static model list for {}. Local models must be discovered.\nTip: Use /discover to scan for local models.", provider));
             } else {
@@ -2601,23 +3214,23 @@ fn handle_command_result(
                 }
             }
 
-            // Check LM Studio
-            let lm_port = phazeai_core::constants::endpoints::LMSTUDIO_PORT;
-            let lm_addr = format!("127.0.0.1:{}", lm_port);
-            let lm_check = lm_addr
-                .parse::<std::net::SocketAddr>()
-                .ok()
-                .and_then(|addr| {
-                    std::net::TcpStream::connect_timeout(
-                        &addr,
-                        std::time::Duration::from_millis(500),
-                    )
-                    .ok()
-                });
-            if lm_check.is_some() {
-                msg.push_str(&format!("  LM Studio: running on port {}\n", lm_port));
-            } else {
-                msg.push_str("  LM Studio: not detected\n");
+            // Check the OpenAI-compatible local servers
+            use phazeai_core::constants::endpoints;
+            for (name, port) in [
+                ("LM Studio", endpoints::LMSTUDIO_PORT),
+                ("llama.cpp", endpoints::LLAMACPP_PORT),
+                ("vLLM", endpoints::VLLM_PORT),
+            ] {
+                let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+                let check = std::net::TcpStream::connect_timeout(
+                    &addr,
+                    std::time::Duration::from_millis(500),
+                );
+                if check.is_ok() {
+                    msg.push_str(&format!("  {name}: running on port {port}\n"));
+                } else {
+                    msg.push_str(&format!("  {name}: not detected\n"));
+                }
             }
 
             msg.push_str("\nUse /provider ollama and /model <name> to use a local model.");
@@ -2733,6 +3346,9 @@ fn handle_command_result(
                         MessageRole::System,
                         format!("Added file: {path_str} ({char_count} chars)"),
                     );
+                    state
+                        .added_files
+                        .push((path_str.clone(), budget::estimate_tokens(&context_msg)));
                     // Send file contents as a context message to the agent
                     if !state.is_processing {
                         let _ = user_input_tx.send(WorkerCommand::UserMessage(format!(
@@ -2767,21 +3383,7 @@ fn handle_command_result(
                 let _ = user_input_tx.send(WorkerCommand::UserMessage(agent_input));
             }
         }
-        CommandResult::Cancel => {
-            state.cancel_token.store(true, Ordering::Relaxed);
-            let sender = state
-                .approval_tx
-                .lock()
-                .unwrap_or_else(|e| e.into_inner())
-                .take();
-            if let Some(tx) = sender {
-                let _ = tx.send(false);
-            }
-            state.pending_approval = None;
-            state.is_processing = false;
-            state.status_text = "Cancelled".into();
-            state.add_message(MessageRole::System, "Request cancelled.".into());
-        }
+        CommandResult::Cancel => cancel_request(state),
         CommandResult::Grep(pattern) => {
             let root = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
             let output = std::process::Command::new("rg")
@@ -3182,6 +3784,7 @@ fn build_compaction_summary(items: &[ChatItem]) -> String {
                 };
                 tool_results.push(brief);
             }
+            ChatItem::Thinking { .. } => {}
         }
     }
 
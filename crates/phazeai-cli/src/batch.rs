@@ -0,0 +1,197 @@
+//! `phazeai batch`: summaries or doc comments for a whole workspace through
+//! Anthropic's or OpenAI's batch API, at half the price of asking file by
+//! file. Jobs run on the provider for up to a day; `collect` writes their
+//! results back and `list` shows what each cost and saved.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use phazeai_core::agent::batch::{self, BatchJob, BatchJobStore, BatchTask};
+use phazeai_core::llm::BatchClient;
+use phazeai_core::Settings;
+
+/// How often `collect --wait` asks whether the batches are done.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Subcommand)]
+pub enum BatchCommand {
+    /// Send every source file (or those under PATHS) as a batch job
+    Submit {
+        /// summarize (into .phazeai/summaries) or docstrings (into the files)
+        task: String,
+        /// Files or directories to include (default: the whole workspace)
+        paths: Vec<PathBuf>,
+    },
+    /// List jobs with their state, tokens, cost and savings
+    List,
+    /// Show how far a job's batches are (default: the newest job)
+    Status { id: Option<String> },
+    /// Write back a finished job's results (default: the newest job)
+    Collect {
+        id: Option<String>,
+        /// Wait for the batches to finish first
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Stop a job's batches; finished requests can still be collected
+    Cancel { id: Option<String> },
+}
+
+pub async fn run(settings: &Settings, cmd: BatchCommand) -> Result<()> {
+    let root = std::env::current_dir()?;
+    let store = BatchJobStore::open(&root);
+    match cmd {
+        BatchCommand::Submit { task, paths } => {
+            let task = BatchTask::parse(&task)
+                .with_context(|| format!("unknown task '{task}'; use summarize or docstrings"))?;
+            let provider = settings.llm.provider.to_provider_id();
+            let registry = settings.build_provider_registry();
+            let config = registry
+                .get_config(&provider)
+                .with_context(|| format!("the {provider} provider is disabled"))?;
+            let client = BatchClient::from_provider(config, &settings.llm.model)?;
+            let job = batch::submit(&client, task, &root, &paths).await?;
+            println!(
+                "Submitted {} ({} files in {} batch{}) to {} / {}",
+                job.id,
+                job.items.len(),
+                job.batches.len(),
+                if job.batches.len() == 1 { "" } else { "es" },
+                job.provider.name(),
+                job.model
+            );
+            println!("Batches finish within 24 hours; then run: phazeai batch collect");
+            Ok(())
+        }
+        BatchCommand::List => {
+            let jobs = store.list();
+            if jobs.is_empty() {
+                eprintln!("No batch jobs. Start one with: phazeai batch submit summarize");
+                return Ok(());
+            }
+            let (mut cost, mut savings) = (0.0, 0.0);
+            for job in &jobs {
+                let state = if job.is_collected() {
+                    format!(
+                        "{} written, {} failed",
+                        job.written.len(),
+                        job.failures.len()
+                    )
+                } else {
+                    "submitted".to_string()
+                };
+                println!(
+                    "{:<28} {:<10} {:>5} files  {:<26}  {}",
+                    job.id,
+                    job.task.as_str(),
+                    job.items.len(),
+                    state,
+                    job.model
+                );
+                if job.is_collected() {
+                    println!(
+                        "    {} in / {} out tokens  ${:.4}  (saved ${:.4})",
+                        job.input_tokens, job.output_tokens, job.cost, job.savings
+                    );
+                }
+                cost += job.cost;
+                savings += job.savings;
+            }
+            println!("Total: ${cost:.4} at batch prices, ${savings:.4} saved");
+            Ok(())
+        }
+        BatchCommand::Status { id } => {
+            let job = store.find(id.as_deref().unwrap_or(""))?;
+            let client = client_for(settings, &job)?;
+            println!(
+                "{} ({}, {} files)",
+                job.id,
+                job.task.as_str(),
+                job.items.len()
+            );
+            for status in batch::statuses(&client, &job).await? {
+                println!(
+                    "  {:<40} {:<12} {}/{} succeeded, {} failed",
+                    status.id,
+                    status.state.label(),
+                    status.succeeded,
+                    status.total,
+                    status.failed
+                );
+            }
+            if job.is_collected() {
+                println!("  collected: {} written", job.written.len());
+            }
+            Ok(())
+        }
+        BatchCommand::Collect { id, wait } => {
+            let mut job = store.find(id.as_deref().unwrap_or(""))?;
+            if job.is_collected() {
+                bail!("{} was already collected", job.id);
+            }
+            let client = client_for(settings, &job)?;
+            if wait {
+                wait_for(&client, &job).await?;
+            }
+            batch::collect(&client, &mut job, &root).await?;
+            print_collected(&job, &root);
+            Ok(())
+        }
+        BatchCommand::Cancel { id } => {
+            let job = store.find(id.as_deref().unwrap_or(""))?;
+            let client = client_for(settings, &job)?;
+            for id in &job.batches {
+                client.cancel(id).await?;
+            }
+            println!("Cancelling {}; collect it to keep what finished", job.id);
+            Ok(())
+        }
+    }
+}
+
+/// A client for the provider and model the job was submitted to.
+fn client_for(settings: &Settings, job: &BatchJob) -> Result<BatchClient> {
+    let registry = settings.build_provider_registry();
+    let config = registry
+        .get_config(&job.provider)
+        .with_context(|| format!("the {} provider is disabled", job.provider))?;
+    Ok(BatchClient::from_provider(config, &job.model)?)
+}
+
+async fn wait_for(client: &BatchClient, job: &BatchJob) -> Result<()> {
+    loop {
+        let statuses = batch::statuses(client, job).await?;
+        if statuses.iter().all(|s| s.state.is_finished()) {
+            return Ok(());
+        }
+        let done: usize = statuses.iter().map(|s| s.succeeded + s.failed).sum();
+        let total: usize = statuses.iter().map(|s| s.total).sum();
+        eprintln!("{done}/{total} requests done; checking again in a minute");
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn print_collected(job: &BatchJob, root: &Path) {
+    for failure in &job.failures {
+        eprintln!("  {}: {}", failure.path.display(), failure.reason);
+    }
+    println!(
+        "{}: wrote {} files, {} failed",
+        job.id,
+        job.written.len(),
+        job.failures.len()
+    );
+    println!(
+        "{} in / {} out tokens: ${:.4} at batch prices, ${:.4} saved",
+        job.input_tokens, job.output_tokens, job.cost, job.savings
+    );
+    match job.task {
+        BatchTask::Summarize => println!(
+            "Summaries are in {}",
+            root.join(batch::SUMMARIES_DIR).display()
+        ),
+        BatchTask::Docstrings => println!("Review the new doc comments with: git diff"),
+    }
+}
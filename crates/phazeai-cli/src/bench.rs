@@ -0,0 +1,125 @@
+//! `phazeai bench`: run the workspace's benchmarks, record them per branch
+//! and flag the ones that got slower.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use phazeai_core::bench::{self, BenchHistory};
+use phazeai_core::git::GitOps;
+
+const HOOK: &str =
+    "#!/bin/sh\n# Installed by `phazeai bench --install-hook`.\nexec phazeai bench --check\n";
+
+/// Run the suites whose name contains `suite` (all of them without one),
+/// comparing each benchmark with its baseline. With `check`, a regression
+/// past `threshold` percent fails the command and the run isn't recorded, so
+/// the baseline stays at the last good numbers.
+pub fn run(suite: Option<String>, list: bool, check: bool, threshold: f64) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let root = GitOps::find_root(&cwd).unwrap_or(cwd);
+    let suites: Vec<_> = bench::discover(&root)
+        .into_iter()
+        .filter(|s| suite.as_ref().is_none_or(|f| s.name.contains(f.as_str())))
+        .collect();
+    if suites.is_empty() {
+        println!("No criterion or pytest-benchmark suites found.");
+        return Ok(());
+    }
+    if list {
+        for s in &suites {
+            println!(
+                "{:<16}  {:<24}  {}",
+                s.kind.label(),
+                s.name,
+                s.file.display()
+            );
+        }
+        return Ok(());
+    }
+
+    let threshold = threshold / 100.0;
+    let (branch, commit) = bench::git_head(&root);
+    let mut history = BenchHistory::open_workspace(&root)?;
+    let mut regressions = 0;
+    for s in &suites {
+        eprintln!("bench: running {} ({})", s.name, s.kind.label());
+        let results =
+            bench::run_suite(&root, s).with_context(|| format!("benchmark {} failed", s.name))?;
+        let comparisons = history.compare(&branch, &s.name, &results)?;
+        let regressed = comparisons
+            .iter()
+            .filter(|c| c.is_regression(threshold))
+            .count();
+        for c in &comparisons {
+            println!(
+                "{:>12}  {:>8}  {}{}",
+                bench::format_duration(c.result.mean_ns),
+                c.change()
+                    .map(|change| format!("{:+.1}%", change * 100.0))
+                    .unwrap_or_else(|| "new".to_string()),
+                c.result.benchmark,
+                if c.is_regression(threshold) {
+                    "  REGRESSED"
+                } else {
+                    ""
+                }
+            );
+        }
+        if !(check && regressed > 0) {
+            history.record(&branch, commit.as_deref(), &s.name, &results)?;
+        }
+        regressions += regressed;
+    }
+    if regressions > 0 {
+        let message = format!(
+            "{regressions} benchmark(s) regressed by more than {:.0}% on {branch}",
+            threshold * 100.0
+        );
+        if check {
+            bail!(message);
+        }
+        println!("\n{message}");
+    }
+    Ok(())
+}
+
+/// Write a pre-commit hook that runs `phazeai bench --check`, leaving an
+/// existing hook of someone else's alone.
+pub fn install_hook() -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let root = GitOps::find_root(&cwd).context("not inside a git repository")?;
+    let path = hook_path(&root)?;
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        if !existing.contains("phazeai bench") {
+            bail!(
+                "{} already exists; add `phazeai bench --check` to it by hand",
+                path.display()
+            );
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, HOOK).with_context(|| format!("writing {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    println!("Installed {}", path.display());
+    Ok(())
+}
+
+/// Where git looks for the pre-commit hook, honouring `core.hooksPath` and
+/// worktrees.
+fn hook_path(root: &Path) -> Result<PathBuf> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks/pre-commit"])
+        .current_dir(root)
+        .output()
+        .context("running git failed")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(root.join(String::from_utf8_lossy(&output.stdout).trim()))
+}
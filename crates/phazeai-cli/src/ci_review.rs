@@ -0,0 +1,139 @@
+//! `phazeai ci-review`: review the current branch's diff for CI pipelines.
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use phazeai_core::analysis::review::{self, ReviewFinding};
+use phazeai_core::analysis::Severity;
+use phazeai_core::git::GitOps;
+use phazeai_core::Settings;
+use std::path::{Path, PathBuf};
+
+/// Diffs larger than this are truncated before being sent to the model.
+const MAX_LLM_DIFF_CHARS: usize = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReviewFormat {
+    /// Human-readable `path:line: severity: message`
+    Text,
+    /// GitHub Actions workflow annotations
+    Github,
+    /// SARIF 2.1.0 JSON
+    Sarif,
+}
+
+pub struct CiReviewOptions {
+    pub base: String,
+    pub format: ReviewFormat,
+    pub sarif_output: Option<PathBuf>,
+    pub fail_on: FailOn,
+    pub use_llm: bool,
+}
+
+/// Minimum severity that fails the run; `FailOn(None)` never fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailOn(pub Option<Severity>);
+
+/// Parse a `--fail-on` value: a severity name or `never`.
+pub fn parse_fail_on(s: &str) -> Result<FailOn, String> {
+    if s.eq_ignore_ascii_case("never") || s.eq_ignore_ascii_case("none") {
+        return Ok(FailOn(None));
+    }
+    Severity::parse(s)
+        .map(|sev| FailOn(Some(sev)))
+        .ok_or_else(|| format!("expected error, warning, info or never, got '{s}'"))
+}
+
+/// Run the review and print the report. Returns the process exit code.
+pub async fn run(settings: &Settings, opts: CiReviewOptions) -> Result<i32> {
+    let cwd = std::env::current_dir()?;
+    let Some(root) = GitOps::find_root(&cwd) else {
+        bail!("ci-review must run inside a git repository");
+    };
+    let git = GitOps::new(&root);
+    let diff = git
+        .diff_against(&opts.base)
+        .await
+        .map_err(|e| anyhow::anyhow!("git diff against '{}' failed: {e}", opts.base))?;
+
+    let changed = review::parse_diff_added_lines(&diff);
+    let mut findings = review::lint_changed_lines(&root, &changed);
+
+    if opts.use_llm && !changed.is_empty() {
+        let llm = settings.build_llm_client()?;
+        let diff_for_llm = truncate_chars(&diff, MAX_LLM_DIFF_CHARS);
+        let hotspots = review::changed_hotspots(&root, &changed);
+        let ai = review::llm_review(llm.as_ref(), diff_for_llm, &hotspots)
+            .await
+            .context("AI review failed")?;
+        // Drop findings that point outside the changed lines.
+        findings.extend(ai.into_iter().filter(|f| {
+            changed
+                .get(&f.path)
+                .is_some_and(|lines| lines.contains(&f.line))
+        }));
+    }
+    findings.sort_by(|a, b| {
+        (&a.path, a.line, std::cmp::Reverse(a.severity)).cmp(&(
+            &b.path,
+            b.line,
+            std::cmp::Reverse(b.severity),
+        ))
+    });
+    findings.dedup_by(|a, b| a.path == b.path && a.line == b.line && a.message == b.message);
+
+    if let Some(path) = &opts.sarif_output {
+        write_sarif(path, &findings)?;
+    }
+    match opts.format {
+        ReviewFormat::Text => print!("{}", to_text(&findings, changed.len())),
+        ReviewFormat::Github => print!("{}", review::to_github_annotations(&findings)),
+        ReviewFormat::Sarif => println!(
+            "{}",
+            serde_json::to_string_pretty(&review::to_sarif(&findings))?
+        ),
+    }
+
+    let failing = opts
+        .fail_on
+        .0
+        .map(|min| findings.iter().filter(|f| f.severity >= min).count())
+        .unwrap_or(0);
+    if failing > 0 {
+        eprintln!(
+            "ci-review: {failing} finding(s) at or above '{}'",
+            opts.fail_on.0.map(|s| s.as_str()).unwrap_or("never")
+        );
+        return Ok(1);
+    }
+    Ok(0)
+}
+
+fn write_sarif(path: &Path, findings: &[ReviewFinding]) -> Result<()> {
+    let json = serde_json::to_string_pretty(&review::to_sarif(findings))?;
+    std::fs::write(path, json).with_context(|| format!("writing {}", path.display()))
+}
+
+fn to_text(findings: &[ReviewFinding], files: usize) -> String {
+    let mut out = String::new();
+    for f in findings {
+        out.push_str(&format!(
+            "{}:{}: {}: {}\n",
+            f.path,
+            f.line,
+            f.severity.as_str(),
+            f.message
+        ));
+    }
+    out.push_str(&format!(
+        "{} finding(s) in {files} changed file(s)\n",
+        findings.len()
+    ));
+    out
+}
+
+fn truncate_chars(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((idx, _)) => &s[..idx],
+        None => s,
+    }
+}
@@ -164,6 +164,8 @@ pub fn handle_command(input: &str) -> CommandResult {
                 }
             }
         }
+        "/memory" => memory_command(arg),
+        "/docs" => docs_command(arg),
         "/version" => CommandResult::Message(format!("PhazeAI CLI v{}", env!("CARGO_PKG_VERSION"))),
         "/models" => CommandResult::ListModels,
         "/discover" => CommandResult::DiscoverModels,
@@ -240,6 +242,132 @@ pub fn handle_command(input: &str) -> CommandResult {
     }
 }
 
+/// `/memory`, `/memory add <fact>`, `/memory forget <n>` on the project memory.
+fn memory_command(arg: &str) -> CommandResult {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let store = phazeai_core::MemoryStore::for_workspace(&cwd);
+    let (sub, rest) = arg.split_once(' ').unwrap_or((arg, ""));
+    let rest = rest.trim();
+    let result = match sub {
+        "" | "list" => store.list().map(|memories| {
+            if memories.is_empty() {
+                "No project memories yet. The agent saves facts with its memory tool, \
+                 or use /memory add <fact>."
+                    .to_string()
+            } else {
+                let lines: Vec<String> = memories
+                    .iter()
+                    .map(|m| format!("  {:>3}. {}", m.id, m.text))
+                    .collect();
+                format!(
+                    "Project memory ({}):\n{}",
+                    store.path().display(),
+                    lines.join("\n")
+                )
+            }
+        }),
+        "add" if !rest.is_empty() => store.add(rest).map(|added| {
+            if added {
+                "Remembered.".to_string()
+            } else {
+                "Already remembered.".to_string()
+            }
+        }),
+        "forget" | "rm" => match rest.parse::<usize>() {
+            Ok(id) => store.remove(id).map(|removed| match removed {
+                Some(m) => format!("Forgot: {}", m.text),
+                None => format!("No memory #{id}. See /memory."),
+            }),
+            Err(_) => Ok("Usage: /memory forget <n>".to_string()),
+        },
+        _ => Ok("Usage: /memory [list | add <fact> | forget <n>]".to_string()),
+    };
+    CommandResult::Message(result.unwrap_or_else(|e| format!("Memory error: {e}")))
+}
+
+/// `/docs`, `/docs add <path>`, `/docs remove <path>`, `/docs reindex` on
+/// the documentation sources the agent searches.
+fn docs_command(arg: &str) -> CommandResult {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let mut settings = phazeai_core::Settings::load();
+    let (sub, rest) = arg.split_once(' ').unwrap_or((arg, ""));
+    let rest = rest.trim();
+    let message = match sub {
+        "" | "list" => {
+            if settings.docs.sources.is_empty() {
+                return CommandResult::Message(
+                    "No documentation sources. Add a folder or file with /docs add <path>.".into(),
+                );
+            }
+            let mut lines = vec![format!("Sources: {}", settings.docs.sources.join(", "))];
+            match phazeai_core::context::DocsIndex::open(&cwd, &settings.docs).and_then(|index| {
+                index.sync()?;
+                index.files()
+            }) {
+                Ok(files) => lines.extend(files.iter().map(|f| match &f.error {
+                    Some(e) => format!("  {}  (skipped: {e})", f.path),
+                    None => format!("  {}  ({} passages)", f.path, f.chunks),
+                })),
+                Err(e) => lines.push(format!("Docs index error: {e}")),
+            }
+            lines.join("\n")
+        }
+        "add" if !rest.is_empty() => {
+            if !settings.docs.sources.iter().any(|s| s == rest) {
+                settings.docs.sources.push(rest.to_string());
+            }
+            save_docs_sources(&cwd, &settings)
+        }
+        "remove" | "rm" if !rest.is_empty() => {
+            let before = settings.docs.sources.len();
+            settings.docs.sources.retain(|s| s != rest);
+            if settings.docs.sources.len() == before {
+                format!("{rest} is not a documentation source. See /docs.")
+            } else {
+                save_docs_sources(&cwd, &settings)
+            }
+        }
+        "reindex" => {
+            match phazeai_core::context::DocsIndex::open(&cwd, &settings.docs).and_then(|index| {
+                index.clear()?;
+                index.sync()
+            }) {
+                Ok(sync) => format!("Reindexed {} documents.", sync.indexed),
+                Err(e) => format!("Docs index error: {e}"),
+            }
+        }
+        _ => "Usage: /docs [list | add <path> | remove <path> | reindex]".to_string(),
+    };
+    CommandResult::Message(message)
+}
+
+/// Save the sources in `settings` and bring the index up to date with them.
+fn save_docs_sources(cwd: &std::path::Path, settings: &phazeai_core::Settings) -> String {
+    let sources = settings.docs.sources.clone();
+    if let Err(e) = phazeai_core::Settings::update(|s| s.docs.sources = sources) {
+        return format!("Failed to save settings: {e}");
+    }
+    match phazeai_core::context::DocsIndex::open(cwd, &settings.docs).and_then(|index| index.sync())
+    {
+        Ok(sync) => {
+            let mut message = format!(
+                "Documentation sources: {}. Indexed {} documents; new sessions search them.",
+                if settings.docs.sources.is_empty() {
+                    "none".to_string()
+                } else {
+                    settings.docs.sources.join(", ")
+                },
+                sync.indexed
+            );
+            for (path, reason) in sync.failed {
+                message.push_str(&format!("\n  skipped {path}: {reason}"));
+            }
+            message
+        }
+        Err(e) => format!("Docs index error: {e}"),
+    }
+}
+
 fn show_help() -> CommandResult {
     let help_text = "\
 ╭─ PhazeAI CLI Commands ─────────────────────────────────────────╮
@@ -283,6 +411,8 @@ fn show_help() -> CommandResult {
     /pwd                      Show current directory
     /cd <dir>                 Change directory
     /context                  Show loaded project context
+    /memory                   List project memory (add <fact>, forget <n>)
+    /docs                     List docs sources (add <path>, remove <path>, reindex)
 
   SESSION CONTROL
     /retry                    Resend the last user message
@@ -298,6 +428,7 @@ fn show_help() -> CommandResult {
     Ctrl+U                    Kill line
     Ctrl+W                    Delete word backward
     Ctrl+C                    Copy (or Abort if running/empty)
+    Esc                       Stop the running request
 
   OTHER
     /help, /h                 Show this help message
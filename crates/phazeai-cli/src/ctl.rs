@@ -0,0 +1,85 @@
+//! `phazeai ctl`: drive a running IDE over its control socket.
+
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use phazeai_core::ipc::{self, methods};
+use serde_json::{json, Value};
+
+#[derive(Subcommand)]
+pub enum CtlCommand {
+    /// Check that the IDE is listening
+    Ping,
+    /// Open a file, optionally at a line (`path` or `path:line`)
+    Open { target: String },
+    /// List command palette actions
+    Commands,
+    /// Run a command palette action by its label
+    Run { label: String },
+    /// Send a prompt to the chat panel
+    Chat { prompt: String },
+    /// Print LSP diagnostics, optionally for one file
+    Diagnostics { path: Option<String> },
+    /// Send a raw JSON-RPC call
+    Call {
+        method: String,
+        /// JSON params object
+        params: Option<String>,
+    },
+}
+
+/// Split `path:line` into its parts; a non-numeric suffix stays in the path.
+pub fn parse_target(target: &str) -> (String, Option<u32>) {
+    if let Some((path, line)) = target.rsplit_once(':') {
+        if let Ok(line) = line.parse::<u32>() {
+            return (path.to_string(), Some(line));
+        }
+    }
+    (target.to_string(), None)
+}
+
+fn absolute(path: &str) -> String {
+    let p = std::path::Path::new(path);
+    if p.is_absolute() {
+        return path.to_string();
+    }
+    std::env::current_dir()
+        .map(|cwd| cwd.join(p).to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+pub fn run(cmd: CtlCommand) -> Result<()> {
+    let (method, params) = match cmd {
+        CtlCommand::Ping => (methods::PING.to_string(), Value::Null),
+        CtlCommand::Open { target } => {
+            let (path, line) = parse_target(&target);
+            (
+                methods::OPEN_FILE.to_string(),
+                json!({ "path": absolute(&path), "line": line }),
+            )
+        }
+        CtlCommand::Commands => (methods::LIST_COMMANDS.to_string(), Value::Null),
+        CtlCommand::Run { label } => (methods::RUN_COMMAND.to_string(), json!({ "label": label })),
+        CtlCommand::Chat { prompt } => {
+            (methods::SEND_CHAT.to_string(), json!({ "prompt": prompt }))
+        }
+        CtlCommand::Diagnostics { path } => (
+            methods::GET_DIAGNOSTICS.to_string(),
+            json!({ "path": path.as_deref().map(absolute) }),
+        ),
+        CtlCommand::Call { method, params } => {
+            let params = match params {
+                Some(p) => serde_json::from_str(&p).map_err(|e| anyhow!("invalid params: {e}"))?,
+                None => Value::Null,
+            };
+            (method, params)
+        }
+    };
+
+    let result = ipc::call(&ipc::socket_path(), &method, params)?;
+    match result {
+        Value::Bool(true) | Value::Null => {}
+        Value::String(s) => println!("{s}"),
+        other => println!("{}", serde_json::to_string_pretty(&other)?),
+    }
+    Ok(())
+}
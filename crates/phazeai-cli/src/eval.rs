@@ -0,0 +1,85 @@
+//! `phazeai eval`: run a prompt suite against several models and compare.
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use phazeai_core::eval::{self, EvalSuite, EvalTarget, PreparedTarget};
+use phazeai_core::Settings;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EvalFormat {
+    /// Comparison table
+    Text,
+    /// Full results, including model output, as JSON
+    Json,
+}
+
+/// Run `suite` and print the report. Returns 0 when every case passed on
+/// every target, 1 otherwise.
+pub async fn run(
+    settings: &Settings,
+    suite: &Path,
+    targets: &[String],
+    judge: Option<&str>,
+    format: EvalFormat,
+) -> Result<i32> {
+    let suite = EvalSuite::load(suite)?;
+
+    // Command-line targets replace the suite's; with neither, evaluate the
+    // configured model (and its routes).
+    let specs: Vec<EvalTarget> = if !targets.is_empty() {
+        targets
+            .iter()
+            .map(|t| EvalTarget::parse(t))
+            .collect::<Result<_, _>>()?
+    } else if !suite.targets.is_empty() {
+        suite.targets.clone()
+    } else {
+        vec![EvalTarget::parse("default")?]
+    };
+
+    let mut prepared = Vec::with_capacity(specs.len());
+    for spec in &specs {
+        let client = spec
+            .build_client(settings)
+            .map_err(|e| anyhow!("target {}: {e}", spec.label()))?;
+        prepared.push(PreparedTarget {
+            label: spec.label(),
+            client,
+            system_prompt: spec.system_prompt.clone(),
+        });
+    }
+
+    let judge_spec = match judge {
+        Some(j) => Some(EvalTarget::parse(j)?),
+        None => suite.judge.clone(),
+    };
+    let judge_client = judge_spec
+        .unwrap_or_else(|| EvalTarget::parse("default").expect("default target"))
+        .build_client(settings)
+        .map_err(|e| anyhow!("judge: {e}"))?;
+
+    eprintln!(
+        "Running {} cases × {} targets",
+        suite.cases.len(),
+        prepared.len()
+    );
+    let report = eval::run_suite(&suite, &prepared, Some(judge_client.as_ref()), |r| {
+        let status = match &r.error {
+            Some(e) => format!("error: {e}"),
+            None if r.passed() => "pass".to_string(),
+            None => "fail".to_string(),
+        };
+        eprintln!(
+            "  [{}] {} — {status} ({}ms)",
+            r.target, r.case, r.latency_ms
+        );
+    })
+    .await;
+
+    match format {
+        EvalFormat::Text => print!("{}", report.to_table()),
+        EvalFormat::Json => println!("{}", serde_json::to_string_pretty(&report.to_json())?),
+    }
+    Ok(if report.all_passed() { 0 } else { 1 })
+}
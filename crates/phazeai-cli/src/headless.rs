@@ -0,0 +1,141 @@
+//! Newline-delimited JSON encoding of agent events for `phazeai run --json`.
+//!
+//! Every line on stdout is one object with a `"type"` field. The stream always
+//! ends with a single `{"type":"exit",...}` record carrying the exit status and
+//! cumulative token usage, so consumers never need to parse stderr.
+
+use phazeai_core::context::docs;
+use phazeai_core::AgentEvent;
+use serde_json::{json, Value};
+
+/// Encode one agent event as a JSON object.
+pub fn event_to_json(event: &AgentEvent) -> Value {
+    match event {
+        AgentEvent::Thinking { iteration } => json!({
+            "type": "thinking",
+            "iteration": iteration,
+        }),
+        AgentEvent::TextDelta(text) => json!({
+            "type": "text_delta",
+            "text": text,
+        }),
+        AgentEvent::ReasoningDelta(text) => json!({
+            "type": "reasoning_delta",
+            "text": text,
+        }),
+        AgentEvent::FirstToken { latency } => json!({
+            "type": "first_token",
+            "latency_ms": latency.as_millis() as u64,
+        }),
+        AgentEvent::ToolApprovalRequest { name, params } => json!({
+            "type": "tool_approval_request",
+            "name": name,
+            "params": params,
+        }),
+        AgentEvent::ToolStart { name } => json!({
+            "type": "tool_start",
+            "name": name,
+        }),
+        AgentEvent::ToolResult {
+            name,
+            success,
+            summary,
+        } => json!({
+            "type": "tool_result",
+            "name": name,
+            "success": success,
+            "summary": summary,
+        }),
+        AgentEvent::DocSources(chunks) => json!({
+            "type": "doc_sources",
+            "sources": chunks
+                .iter()
+                .enumerate()
+                .map(|(i, chunk)| json!({
+                    "label": docs::label(i),
+                    "path": chunk.path,
+                    "heading": chunk.heading,
+                    "line": chunk.line,
+                    "page": chunk.page,
+                }))
+                .collect::<Vec<_>>(),
+        }),
+        AgentEvent::Citations(citations) => json!({
+            "type": "citations",
+            "citations": citations,
+        }),
+        AgentEvent::Complete { iterations } => json!({
+            "type": "complete",
+            "iterations": iterations,
+        }),
+        AgentEvent::StreamResumed { attempt, reason } => json!({
+            "type": "stream_resumed",
+            "attempt": attempt,
+            "reason": reason,
+        }),
+        AgentEvent::TokenUsage {
+            input_tokens,
+            output_tokens,
+        } => json!({
+            "type": "token_usage",
+            "input_tokens": input_tokens,
+            "output_tokens": output_tokens,
+        }),
+        AgentEvent::Error(message) => json!({
+            "type": "error",
+            "message": message,
+        }),
+        AgentEvent::BrowserFetchStart { url } => json!({
+            "type": "browser_fetch_start",
+            "url": url,
+        }),
+        AgentEvent::BrowserFetchComplete {
+            url,
+            title,
+            content,
+        } => json!({
+            "type": "browser_fetch_complete",
+            "url": url,
+            "title": title,
+            "content_length": content.len(),
+        }),
+        AgentEvent::BrowserFetchError { url, error } => json!({
+            "type": "browser_fetch_error",
+            "url": url,
+            "error": error,
+        }),
+    }
+}
+
+/// Final summary of a headless run.
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub iterations: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub error: Option<String>,
+}
+
+impl RunSummary {
+    /// Process exit code: 0 on success, 1 when the agent failed.
+    pub fn exit_code(&self) -> i32 {
+        if self.error.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// The trailing `exit` record.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "type": "exit",
+            "status": if self.error.is_some() { "error" } else { "success" },
+            "code": self.exit_code(),
+            "iterations": self.iterations,
+            "input_tokens": self.input_tokens,
+            "output_tokens": self.output_tokens,
+            "error": self.error,
+        })
+    }
+}
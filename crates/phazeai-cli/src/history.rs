@@ -0,0 +1,153 @@
+//! `phazeai history`: query saved conversations.
+
+use anyhow::Result;
+use clap::{Subcommand, ValueEnum};
+use phazeai_core::{ConversationStore, SearchQuery};
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum HistoryCommand {
+    /// Full-text search over conversation titles and messages
+    Search {
+        /// Words to find (all must match); quote a phrase to match it exactly.
+        /// May be omitted when filtering by --tag.
+        query: Option<String>,
+
+        /// Only conversations with a model containing this
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Only conversations started in this directory
+        #[arg(long, value_name = "DIR", conflicts_with = "here")]
+        workspace: Option<PathBuf>,
+
+        /// Only conversations started in the current directory
+        #[arg(long)]
+        here: bool,
+
+        /// Only conversations with this tag (repeatable)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Only conversations updated on or after this date (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        since: Option<String>,
+
+        /// Only conversations updated on or before this date (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        until: Option<String>,
+
+        /// Maximum number of conversations to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Set a conversation's title (automatic titling won't replace it)
+    Rename {
+        /// Conversation ID or unique prefix
+        id: String,
+        title: String,
+    },
+    /// Replace a conversation's tags (none clears them)
+    Tag {
+        /// Conversation ID or unique prefix
+        id: String,
+        tags: Vec<String>,
+    },
+    /// Write a conversation out as Markdown or JSON, with the file ranges
+    /// each reply cites
+    Export {
+        /// Conversation ID or unique prefix
+        id: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+        format: ExportFormat,
+
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The messages, with a list of citations under each reply
+    Markdown,
+    /// The saved conversation, citations included
+    Json,
+}
+
+pub fn run(cmd: HistoryCommand) -> Result<()> {
+    let store = ConversationStore::new()?;
+    match cmd {
+        HistoryCommand::Search {
+            query,
+            model,
+            workspace,
+            here,
+            tags,
+            since,
+            until,
+            limit,
+        } => {
+            let cwd = std::env::current_dir()?;
+            let workspace = if here {
+                Some(cwd)
+            } else {
+                workspace.map(|w| if w.is_absolute() { w } else { cwd.join(w) })
+            };
+            let query = SearchQuery {
+                text: query.unwrap_or_default(),
+                model,
+                workspace: workspace.map(|w| w.display().to_string()),
+                since,
+                until,
+                tags,
+                limit,
+            };
+
+            let hits = store.search_messages(&query)?;
+            if hits.is_empty() {
+                eprintln!("No matching conversations.");
+                return Ok(());
+            }
+            for hit in hits {
+                let meta = &hit.metadata;
+                let short_id = meta.id.get(..8).unwrap_or(&meta.id);
+                let date = meta.updated_at.get(..10).unwrap_or(&meta.updated_at);
+                let tags: String = meta.tags.iter().map(|t| format!(" #{t}")).collect();
+                println!(
+                    "{short_id}  {date}  {:<24} {}{tags}",
+                    meta.model, meta.title
+                );
+                if !hit.snippet.is_empty() {
+                    println!("    {}: {}", hit.role, hit.snippet.replace('\n', " "));
+                }
+            }
+            eprintln!("Resume with: phazeai --resume <id>");
+            Ok(())
+        }
+        HistoryCommand::Rename { id, title } => {
+            let id = store.resolve_id(&id)?;
+            store.rename(&id, &title)?;
+            Ok(())
+        }
+        HistoryCommand::Tag { id, tags } => {
+            let id = store.resolve_id(&id)?;
+            store.set_tags(&id, &tags)?;
+            Ok(())
+        }
+        HistoryCommand::Export { id, format, output } => {
+            let conversation = store.load(&store.resolve_id(&id)?)?;
+            let text = match format {
+                ExportFormat::Markdown => conversation.to_markdown(),
+                ExportFormat::Json => serde_json::to_string_pretty(&conversation)? + "\n",
+            };
+            match output {
+                Some(path) => std::fs::write(path, text)?,
+                None => print!("{text}"),
+            }
+            Ok(())
+        }
+    }
+}
@@ -11,6 +11,9 @@ pub mod commands;
 #[path = "companion.rs"]
 pub mod companion;
 
+#[path = "headless.rs"]
+pub mod headless;
+
 #[path = "theme.rs"]
 pub mod theme;
 
@@ -1,10 +1,30 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
+mod account;
 mod app;
+mod batch;
+mod bench;
+mod ci_review;
 mod commands;
 mod companion;
+mod ctl;
+mod eval;
+mod headless;
+mod history;
+mod metrics;
+mod models;
+mod onboard;
+mod org;
+mod replay;
+mod run_config;
+mod schedule;
+mod sync;
+mod team;
 mod theme;
+mod update;
+mod update_deps;
+mod workspace;
 
 #[derive(Parser)]
 #[command(name = "phazeai")]
@@ -19,7 +39,7 @@ struct Cli {
     #[arg(short, long)]
     model: Option<String>,
 
-    /// LLM provider (claude, openai, ollama)
+    /// LLM provider (claude, openai, ollama, xai/grok, mistral, ...)
     #[arg(long)]
     provider: Option<String>,
 
@@ -38,22 +58,279 @@ struct Cli {
     /// Path to custom instructions file
     #[arg(long)]
     instructions: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the agent non-interactively on a single prompt
+    Run {
+        /// The prompt (stdin is appended when piped)
+        prompt: Option<String>,
+
+        /// Stream agent events to stdout as newline-delimited JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Record the run into a replay bundle under .phazeai/recordings/
+        #[arg(long)]
+        record: bool,
+
+        /// Record the run into a replay bundle at this path
+        #[arg(long, value_name = "PATH")]
+        record_to: Option<std::path::PathBuf>,
+    },
+    /// Re-run a recorded session in a sandbox and report divergences
+    Replay {
+        /// Recording bundle written by `run --record`
+        bundle: std::path::PathBuf,
+
+        /// Sandbox directory (default: a fresh temp dir)
+        #[arg(long)]
+        sandbox: Option<std::path::PathBuf>,
+
+        /// Keep the sandbox after the replay finishes
+        #[arg(long)]
+        keep: bool,
+    },
+    /// Control a running IDE over its local socket
+    Ctl {
+        #[command(subcommand)]
+        command: ctl::CtlCommand,
+    },
+    /// Search saved conversations
+    History {
+        #[command(subcommand)]
+        command: history::HistoryCommand,
+    },
+    /// Manage recurring agent jobs run by `phazeai daemon`
+    Schedule {
+        #[command(subcommand)]
+        command: schedule::ScheduleCommand,
+    },
+    /// Run scheduled agent jobs in the foreground until interrupted
+    Daemon,
+    /// Sign in to PhazeAI Cloud in the browser
+    Login {
+        /// Print the sign-in link instead of opening a browser
+        #[arg(long)]
+        no_browser: bool,
+    },
+    /// Sign out of PhazeAI Cloud
+    Logout,
+    /// Show the signed-in PhazeAI Cloud account and plan
+    Whoami,
+    /// View and manage the shared team context (Team plan)
+    Team {
+        #[command(subcommand)]
+        command: team::TeamCommand,
+    },
+    /// Manage organization seats and policies (Team plan admins)
+    Org {
+        #[command(subcommand)]
+        command: org::OrgCommand,
+    },
+    /// Sync conversations with PhazeAI Cloud, optionally end-to-end encrypted
+    Sync {
+        #[command(subcommand)]
+        command: sync::SyncCommand,
+    },
+    /// Review the diff against a base branch for CI (SARIF / GitHub annotations)
+    CiReview {
+        /// Base ref to diff against (merge base with HEAD)
+        #[arg(long, default_value = "origin/main")]
+        base: String,
+
+        /// Report format written to stdout
+        #[arg(long, value_enum, default_value_t = ci_review::ReviewFormat::Text)]
+        format: ci_review::ReviewFormat,
+
+        /// Also write a SARIF report to this file
+        #[arg(long)]
+        sarif: Option<std::path::PathBuf>,
+
+        /// Exit nonzero when a finding is at or above this severity (error, warning, info, never)
+        #[arg(long, default_value = "error", value_parser = ci_review::parse_fail_on)]
+        fail_on: ci_review::FailOn,
+
+        /// Only run the static checks, skip the AI review
+        #[arg(long)]
+        no_llm: bool,
+    },
+    /// Score a TOML suite of prompts against one or more provider/model targets
+    Eval {
+        /// Eval suite file
+        suite: std::path::PathBuf,
+
+        /// Target as provider/model, or `default` for the configured model and routes (repeatable)
+        #[arg(long = "target", value_name = "PROVIDER/MODEL")]
+        targets: Vec<String>,
+
+        /// Model that grades llm_grade assertions (default: the configured model)
+        #[arg(long, value_name = "PROVIDER/MODEL")]
+        judge: Option<String>,
+
+        /// Report format written to stdout
+        #[arg(long, value_enum, default_value_t = eval::EvalFormat::Text)]
+        format: eval::EvalFormat,
+    },
+    /// Write or update ARCHITECTURE.md, an overview of the codebase for newcomers
+    Onboard {
+        /// Rewrite every section, even ones whose files haven't changed
+        #[arg(long)]
+        force: bool,
+    },
+    /// List the workspace's most complex, longest and most deeply nested functions
+    Metrics {
+        /// How many functions to list
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+    },
+    /// Find outdated dependencies, summarize their breaking changes and bump the manifests
+    UpdateDeps {
+        /// Write the manifest changes instead of only printing the diff
+        #[arg(long)]
+        apply: bool,
+        /// Skip the release-note summaries, which need a model
+        #[arg(long)]
+        no_summary: bool,
+    },
+    /// Manage the custom Ollama models built from Modelfiles, phaze-beast among
+    /// them, and list your Hugging Face, Vertex AI, xAI and Mistral models
+    Models {
+        #[command(subcommand)]
+        command: models::ModelsCommand,
+    },
+    /// Run the workspace's criterion and pytest-benchmark suites and flag regressions
+    Bench {
+        /// Only run suites whose name contains this
+        suite: Option<String>,
+        /// List the suites instead of running them
+        #[arg(long)]
+        list: bool,
+        /// Exit with an error, without recording the run, if a benchmark regressed
+        #[arg(long)]
+        check: bool,
+        /// Percent slowdown from the baseline that counts as a regression
+        #[arg(long, default_value_t = 10.0)]
+        threshold: f64,
+        /// Install a git pre-commit hook that runs `phazeai bench --check`
+        #[arg(long)]
+        install_hook: bool,
+    },
+    /// Summarize or document every file through a batch API, at half price
+    Batch {
+        #[command(subcommand)]
+        command: batch::BatchCommand,
+    },
+    /// Run a configuration of .phazeai/launch.toml after its pre-tasks, or list them
+    RunConfig {
+        /// The configuration to run (default: list them)
+        name: Option<String>,
+    },
+    /// Download the newest release and install it the next time phazeai starts
+    Update {
+        /// Only say whether there is a newer release
+        #[arg(long)]
+        check: bool,
+        /// Switch to this release channel first (stable or beta)
+        #[arg(long)]
+        channel: Option<String>,
+    },
+    /// Back up or restore the workspace's .phazeai state and conversations
+    Workspace {
+        #[command(subcommand)]
+        command: workspace::WorkspaceCommand,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Install an update staged by `phazeai update` before doing anything else.
+    update::apply_staged();
+
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
         )
         .with_target(false)
+        // Keep stdout clean for `run --json` and piped output.
+        .with_writer(std::io::stderr)
         .init();
 
     // Anonymous telemetry — single fire-and-forget ping, no personal data
     phazeai_core::telemetry::report_launch(phazeai_core::telemetry::AppKind::Cli);
 
     let cli = Cli::parse();
+    // Offer PhazeAI Cloud hosted models when signed in; apply the org policy.
+    phazeai_cloud::init();
+
+    if let Some(Command::Ctl { command }) = cli.command {
+        return ctl::run(command);
+    }
+    if let Some(Command::History { command }) = cli.command {
+        return history::run(command);
+    }
+    if let Some(Command::Update { check, channel }) = cli.command {
+        return update::run(channel, check).await;
+    }
+    if let Some(Command::Workspace { command }) = cli.command {
+        return workspace::run(command);
+    }
+    if let Some(Command::Metrics { top }) = cli.command {
+        return metrics::run(top);
+    }
+    if let Some(Command::RunConfig { name }) = cli.command {
+        let code = run_config::run(name)?;
+        std::process::exit(code);
+    }
+    if let Some(Command::Bench {
+        suite,
+        list,
+        check,
+        threshold,
+        install_hook,
+    }) = cli.command
+    {
+        return if install_hook {
+            bench::install_hook()
+        } else {
+            bench::run(suite, list, check, threshold)
+        };
+    }
+    if let Some(Command::Schedule { command }) = cli.command {
+        return schedule::run(command).await;
+    }
+    if let Some(Command::Daemon) = cli.command {
+        return schedule::daemon().await;
+    }
+    match cli.command {
+        Some(Command::Login { no_browser }) => return account::login(!no_browser).await,
+        Some(Command::Logout) => return account::logout(),
+        Some(Command::Whoami) => return account::whoami().await,
+        _ => {}
+    }
+    if let Some(Command::Team { command }) = cli.command {
+        return team::run(command).await;
+    }
+    if let Some(Command::Org { command }) = cli.command {
+        return org::run(command).await;
+    }
+    if let Some(Command::Sync { command }) = cli.command {
+        return sync::run(command).await;
+    }
+    if let Some(Command::Replay {
+        bundle,
+        sandbox,
+        keep,
+    }) = cli.command
+    {
+        let code = replay::run(&bundle, sandbox, keep).await?;
+        std::process::exit(code);
+    }
 
     let mut settings = phazeai_core::Settings::load();
 
@@ -61,16 +338,8 @@ async fn main() -> Result<()> {
         settings.llm.model = model.clone();
     }
     if let Some(ref provider) = cli.provider {
-        settings.llm.provider = match provider.to_lowercase().as_str() {
-            "openai" | "gpt" => phazeai_core::config::LlmProvider::OpenAI,
-            "ollama" | "local" => phazeai_core::config::LlmProvider::Ollama,
-            "groq" => phazeai_core::config::LlmProvider::Groq,
-            "together" => phazeai_core::config::LlmProvider::Together,
-            "openrouter" | "or" => phazeai_core::config::LlmProvider::OpenRouter,
-            "lmstudio" | "lm-studio" | "lm_studio" => phazeai_core::config::LlmProvider::LmStudio,
-            "claude" | "anthropic" => phazeai_core::config::LlmProvider::Claude,
-            _ => phazeai_core::config::LlmProvider::Claude,
-        };
+        settings.llm.provider = phazeai_core::config::LlmProvider::from_name(provider)
+            .unwrap_or(phazeai_core::config::LlmProvider::Claude);
     }
 
     let extra_instructions = if let Some(ref instructions_path) = cli.instructions {
@@ -79,23 +348,50 @@ async fn main() -> Result<()> {
         None
     };
 
-    // Auto-provision phaze-beast if needed
-    if settings.llm.provider == phazeai_core::config::LlmProvider::Ollama
-        && settings.llm.model == "phaze-beast"
+    if let Some(Command::Models { command }) = cli.command {
+        return models::run(&settings, command).await;
+    }
+    if let Some(Command::Batch { command }) = cli.command {
+        return batch::run(&settings, command).await;
+    }
+
+    // Build the selected custom model (phaze-beast by default) if its
+    // Modelfile is new or was edited.
+    models::ensure_selected(&settings).await;
+
+    if let Some(Command::CiReview {
+        base,
+        format,
+        sarif,
+        fail_on,
+        no_llm,
+    }) = cli.command
     {
-        let base_url = settings
-            .llm
-            .base_url
-            .clone()
-            .unwrap_or_else(|| "http://localhost:11434".to_string());
-
-        if let Ok(manager) = phazeai_core::llm::OllamaManager::new(&base_url) {
-            if let Err(e) = manager.ensure_phaze_beast().await {
-                tracing::warn!(
-                    "Failed to auto-provision phaze-beast: {e}. Falling back to existing models."
-                );
-            }
-        }
+        let opts = ci_review::CiReviewOptions {
+            base,
+            format,
+            sarif_output: sarif,
+            fail_on,
+            use_llm: !no_llm,
+        };
+        let code = ci_review::run(&settings, opts).await?;
+        std::process::exit(code);
+    }
+    if let Some(Command::Eval {
+        suite,
+        targets,
+        judge,
+        format,
+    }) = cli.command
+    {
+        let code = eval::run(&settings, &suite, &targets, judge.as_deref(), format).await?;
+        std::process::exit(code);
+    }
+    if let Some(Command::Onboard { force }) = cli.command {
+        return onboard::run(&settings, force).await;
+    }
+    if let Some(Command::UpdateDeps { apply, no_summary }) = cli.command {
+        return update_deps::run(&settings, apply, !no_summary).await;
     }
 
     use std::io::{IsTerminal, Read};
@@ -106,16 +402,56 @@ async fn main() -> Result<()> {
         }
     }
 
-    if let Some(prompt) = cli.prompt {
+    if let Some(Command::Run {
+        prompt,
+        json,
+        record,
+        record_to,
+    }) = cli.command
+    {
+        // An empty path selects the default bundle location.
+        let record = record_to.or_else(|| record.then(std::path::PathBuf::new));
+        let final_prompt = match prompt {
+            Some(p) if !stdin_data.is_empty() => {
+                format!("{}\n\n<stdin>\n{}\n</stdin>", p, stdin_data)
+            }
+            Some(p) => p,
+            None if !stdin_data.is_empty() => stdin_data,
+            None => anyhow::bail!("`phazeai run` needs a prompt argument or piped stdin"),
+        };
+        if json {
+            let code = app::run_json(
+                &settings,
+                &final_prompt,
+                extra_instructions.as_deref(),
+                record.as_deref(),
+            )
+            .await?;
+            std::process::exit(code);
+        }
+        app::run_single_prompt(
+            &settings,
+            &final_prompt,
+            extra_instructions.as_deref(),
+            record.as_deref(),
+        )
+        .await?;
+    } else if let Some(prompt) = cli.prompt {
         let final_prompt = if stdin_data.is_empty() {
             prompt
         } else {
             format!("{}\n\n<stdin>\n{}\n</stdin>", prompt, stdin_data)
         };
-        app::run_single_prompt(&settings, &final_prompt, extra_instructions.as_deref()).await?;
+        app::run_single_prompt(
+            &settings,
+            &final_prompt,
+            extra_instructions.as_deref(),
+            None,
+        )
+        .await?;
     } else if !stdin_data.is_empty() {
         // Run single prompt implicitly if stdin is provided but no prompt string
-        app::run_single_prompt(&settings, &stdin_data, extra_instructions.as_deref()).await?;
+        app::run_single_prompt(&settings, &stdin_data, extra_instructions.as_deref(), None).await?;
     } else {
         app::run_tui(
             settings,
@@ -0,0 +1,42 @@
+//! `phazeai metrics`: the workspace's most complex functions.
+
+use anyhow::Result;
+use phazeai_core::analysis::metrics;
+use phazeai_core::git::GitOps;
+
+/// Measure the repository (or the current directory outside git) and print
+/// its `top` hotspots, most complex first.
+pub fn run(top: usize) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let root = GitOps::find_root(&cwd).unwrap_or(cwd);
+    let files = metrics::measure_workspace(&root);
+    let functions: usize = files.iter().map(|f| f.functions.len()).sum();
+    let lines: usize = files.iter().map(|f| f.lines_of_code).sum();
+    println!(
+        "{} files, {functions} functions, {lines} lines of code",
+        files.len()
+    );
+
+    let hotspots = metrics::hotspots(&files, top);
+    if hotspots.is_empty() {
+        println!("No hotspots.");
+        return Ok(());
+    }
+    println!(
+        "\n{:>10}  {:>6}  {:>7}  FUNCTION",
+        "COMPLEXITY", "LINES", "NESTING"
+    );
+    for h in &hotspots {
+        let f = &h.function;
+        println!(
+            "{:>10}  {:>6}  {:>7}  {} ({}:{})",
+            f.complexity,
+            f.length(),
+            f.nesting,
+            f.name,
+            h.path.display(),
+            f.line
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,289 @@
+//! `phazeai models`: the custom Ollama models built from Modelfiles in the
+//! config directory, `phaze-beast` among them, the Hugging Face models and
+//! Inference Endpoints of the configured token's account, the Gemini
+//! models Vertex AI serves to the configured Google Cloud project, and the
+//! models the xAI and Mistral keys can use.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use phazeai_core::config::LlmProvider;
+use phazeai_core::llm::{
+    CustomModel, HuggingFaceHub, MistralClient, ModelInfo, ModelStore, OllamaManager,
+    ProviderConfig, VertexClient, XaiClient,
+};
+use phazeai_core::{progress, ProviderId, Settings};
+
+#[derive(Subcommand)]
+pub enum ModelsCommand {
+    /// List the custom models and whether each is built from its current Modelfile
+    List,
+    /// Print a model's Modelfile
+    Show { name: String },
+    /// Print a model's Modelfile path, creating it from phaze-beast's (or --from's) if new
+    Edit {
+        name: String,
+        /// Start a new model from this model's Modelfile
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Build models whose Modelfile changed since their last build (all, or just NAME)
+    Build {
+        name: Option<String>,
+        /// Rebuild even if the Modelfile hasn't changed
+        #[arg(long)]
+        force: bool,
+    },
+    /// List the text-generation models and Inference Endpoints of your Hugging Face account
+    Hub,
+    /// List the Gemini models Vertex AI serves in your project's location
+    Vertex,
+    /// List the Grok models your xAI key can use, with their prices
+    Xai,
+    /// List the chat models your Mistral key can use, fine-tuned ones included
+    Mistral,
+}
+
+pub async fn run(settings: &Settings, cmd: ModelsCommand) -> Result<()> {
+    let store = ModelStore::open_default();
+    match cmd {
+        ModelsCommand::List => {
+            for name in store.names() {
+                match store.load(&name) {
+                    Ok(model) => println!(
+                        "{name:<24}  {:<10}  FROM {}",
+                        store.status(&model).label(),
+                        model.base
+                    ),
+                    Err(e) => println!("{name:<24}  invalid     {e}"),
+                }
+            }
+            println!("\nModelfiles: {}", ModelStore::default_dir().display());
+        }
+        ModelsCommand::Show { name } => {
+            ensure_known(&store, &name)?;
+            print!("{}", store.read(&name)?);
+        }
+        ModelsCommand::Edit { name, from } => {
+            let path = store.edit(&name, from.as_deref())?;
+            println!("{}", path.display());
+        }
+        ModelsCommand::Build { name, force } => {
+            let names = match name {
+                Some(name) => {
+                    ensure_known(&store, &name)?;
+                    vec![name]
+                }
+                None => store.names(),
+            };
+            let manager = OllamaManager::new(&settings.ollama_base_url())?;
+            for name in names {
+                let model = store.load(&name)?;
+                build(&manager, &store, &model, force).await?;
+            }
+        }
+        ModelsCommand::Hub => hub(settings).await?,
+        ModelsCommand::Vertex => vertex(settings).await?,
+        ModelsCommand::Xai => {
+            let config = enabled_config(settings, ProviderId::Xai)?;
+            let client = XaiClient::from_provider(&config, &config.default_model)?;
+            let models = client
+                .list_models()
+                .await
+                .context("listing xAI models failed")?;
+            print_priced("xAI models:", &models);
+        }
+        ModelsCommand::Mistral => {
+            let config = enabled_config(settings, ProviderId::Mistral)?;
+            let client = MistralClient::from_provider(&config, &config.default_model)?;
+            let models = client
+                .list_models()
+                .await
+                .context("listing Mistral models failed")?;
+            print_priced("Mistral models:", &models);
+        }
+    }
+    Ok(())
+}
+
+/// Print the account's models and endpoints, with the settings entry that
+/// points the `huggingface` provider at each running endpoint.
+async fn hub(settings: &Settings) -> Result<()> {
+    let registry = settings.build_provider_registry();
+    let config = registry
+        .get_config(&ProviderId::HuggingFace)
+        .context("the huggingface provider is disabled")?;
+    let hub = HuggingFaceHub::from_config(config)?;
+    let account = hub.whoami().await.context("checking the token failed")?;
+    println!("Hugging Face account: {}", account.name);
+
+    for namespace in account.namespaces() {
+        let models = hub
+            .models(namespace)
+            .await
+            .with_context(|| format!("listing {namespace}'s models failed"))?;
+        if models.is_empty() {
+            continue;
+        }
+        println!("\nModels in {namespace}:");
+        for m in &models {
+            println!("  {}", m.id);
+        }
+    }
+
+    let mut running = Vec::new();
+    for namespace in account.namespaces() {
+        // Listing needs the token's inference permissions; skip namespaces
+        // it can't see rather than fail the whole listing.
+        let endpoints = match hub.inference_endpoints(namespace).await {
+            Ok(endpoints) => endpoints,
+            Err(e) => {
+                tracing::debug!("Inference Endpoints for {namespace}: {e}");
+                continue;
+            }
+        };
+        if endpoints.is_empty() {
+            continue;
+        }
+        println!("\nInference Endpoints in {namespace}:");
+        for ep in endpoints {
+            println!("  {:<24}  {:<14}  {}", ep.name, ep.state, ep.model);
+            if ep.is_running() {
+                running.extend(ep.provider_entry());
+            }
+        }
+    }
+
+    if let Some(entry) = running.first() {
+        println!(
+            "\nTo use an endpoint, add to settings.toml and set provider = \"huggingface\":\n\n\
+             [[providers]]\nname = \"{}\"\nenabled = true\napi_key_env = \"{}\"\nbase_url = \"{}\"\ndefault_model = \"{}\"",
+            entry.name, entry.api_key_env, entry.base_url, entry.default_model
+        );
+    }
+    Ok(())
+}
+
+/// Print the Gemini models available through Vertex AI, by the IDs
+/// `provider = "vertex"` takes.
+async fn vertex(settings: &Settings) -> Result<()> {
+    let registry = settings.build_provider_registry();
+    let config = registry
+        .get_config(&ProviderId::Vertex)
+        .context("the vertex provider is disabled")?;
+    let client = VertexClient::from_provider(config, &config.default_model)?;
+    let project = client.project();
+    println!(
+        "Vertex AI models for {} in {}:",
+        project.project, project.location
+    );
+    for m in client
+        .list_models()
+        .await
+        .context("listing Vertex AI models failed")?
+    {
+        println!("  {:<36}  ctx:{}", m.id, m.context_window);
+    }
+    Ok(())
+}
+
+fn enabled_config(settings: &Settings, id: ProviderId) -> Result<ProviderConfig> {
+    settings
+        .build_provider_registry()
+        .get_config(&id)
+        .cloned()
+        .with_context(|| format!("the {id} provider is disabled"))
+}
+
+fn print_priced(title: &str, models: &[ModelInfo]) {
+    println!("{title}");
+    for m in models {
+        let price = if m.input_cost_per_m > 0.0 {
+            format!(
+                "${:.2}/${:.2} per M",
+                m.input_cost_per_m, m.output_cost_per_m
+            )
+        } else {
+            String::new()
+        };
+        let tools = if m.supports_tools { "" } else { "  no tools" };
+        println!(
+            "  {:<36}  ctx:{:<8}  {price}{tools}",
+            m.id, m.context_window
+        );
+    }
+}
+
+/// Build `model` if needed, echoing the server's progress to stderr.
+async fn build(
+    manager: &OllamaManager,
+    store: &ModelStore,
+    model: &CustomModel,
+    force: bool,
+) -> Result<()> {
+    let events = progress::global().subscribe();
+    let done = Arc::new(AtomicBool::new(false));
+    let echo = {
+        let done = done.clone();
+        let title = format!("Building {}", model.name);
+        std::thread::spawn(move || {
+            let mut last = String::new();
+            while !done.load(Ordering::Relaxed) {
+                if events.recv_timeout(Duration::from_millis(200)).is_err() {
+                    continue;
+                }
+                let tasks = progress::global().tasks();
+                let Some(task) = tasks.iter().find(|t| t.title == title) else {
+                    continue;
+                };
+                let label = task.label();
+                if label != last {
+                    eprintln!("{label}");
+                    last = label;
+                }
+            }
+        })
+    };
+    let built = manager.build_custom_model(store, model, force).await;
+    done.store(true, Ordering::Relaxed);
+    let _ = echo.join();
+    match built.with_context(|| format!("building {} failed", model.name))? {
+        true => println!("Built {}", model.name),
+        false => println!("{} is up to date", model.name),
+    }
+    Ok(())
+}
+
+/// Rebuild the configured model if it's a custom one whose Modelfile
+/// changed. Failures are logged; the existing model is used instead.
+pub async fn ensure_selected(settings: &Settings) {
+    if settings.llm.provider != LlmProvider::Ollama {
+        return;
+    }
+    let store = ModelStore::open_default();
+    if !store.names().contains(&settings.llm.model) {
+        return;
+    }
+    let result = async {
+        let manager = OllamaManager::new(&settings.ollama_base_url())?;
+        let model = store.load(&settings.llm.model)?;
+        manager.build_custom_model(&store, &model, false).await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!(
+            "Failed to build {}: {e}. Falling back to existing models.",
+            settings.llm.model
+        );
+    }
+}
+
+fn ensure_known(store: &ModelStore, name: &str) -> Result<()> {
+    if !store.names().iter().any(|n| n == name) {
+        bail!("no Modelfile for {name}; create one with `phazeai models edit {name}`");
+    }
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! `phazeai onboard`: write or update the workspace's ARCHITECTURE.md.
+
+use anyhow::{Context, Result};
+use phazeai_core::analysis::onboard;
+use phazeai_core::git::GitOps;
+use phazeai_core::Settings;
+
+/// Bring ARCHITECTURE.md at the repository root (or the current directory
+/// outside git) up to date and say which sections were rewritten.
+pub async fn run(settings: &Settings, force: bool) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let root = GitOps::find_root(&cwd).unwrap_or(cwd);
+    let llm = settings.build_llm_client()?;
+    eprintln!("onboard: reading {}", root.display());
+    let report = onboard::onboard(llm.as_ref(), &root, force)
+        .await
+        .context("writing the architecture summary failed")?;
+
+    let titles = |sections: &[onboard::Section]| {
+        sections
+            .iter()
+            .map(|s| s.title())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if report.regenerated.is_empty() {
+        println!("{} is up to date.", report.path.display());
+    } else {
+        println!(
+            "Updated {}: {}",
+            report.path.display(),
+            titles(&report.regenerated)
+        );
+        if !report.unchanged.is_empty() {
+            println!("Unchanged: {}", titles(&report.unchanged));
+        }
+    }
+    Ok(())
+}
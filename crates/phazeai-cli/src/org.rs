@@ -0,0 +1,134 @@
+//! `phazeai org`: organization seats, invitations and policies on PhazeAI Cloud.
+
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use phazeai_cloud::{entitlements, Feature, OrgPolicy, Organization, TeamRole};
+
+#[derive(Subcommand)]
+pub enum OrgCommand {
+    /// Show the organization, seat count and policy
+    Show,
+    /// List seats and their usage this billing period (admins only)
+    Seats,
+    /// Invite a member by email (admins only)
+    Invite {
+        email: String,
+        /// Role for the new member: viewer, member or admin
+        #[arg(long, default_value = "member")]
+        role: String,
+    },
+    /// Revoke a member's access or cancel an invitation (admins only)
+    Revoke { email: String },
+    /// Show the policy, or change it with the flags below (admins only)
+    Policy {
+        /// Comma-separated providers members may use (e.g. claude,phazecloud)
+        #[arg(long, value_delimiter = ',', conflicts_with = "allow_all")]
+        allow: Option<Vec<String>>,
+        /// Allow every provider
+        #[arg(long)]
+        allow_all: bool,
+        /// Delete saved conversations after this many days
+        #[arg(long, conflicts_with = "keep_forever")]
+        retention_days: Option<u32>,
+        /// Keep saved conversations indefinitely
+        #[arg(long)]
+        keep_forever: bool,
+    },
+}
+
+fn print_policy(policy: &OrgPolicy) {
+    let providers = if policy.allowed_providers.is_empty() {
+        "all".to_string()
+    } else {
+        policy.allowed_providers.join(", ")
+    };
+    println!("Allowed providers: {providers}");
+    match policy.retention_days {
+        Some(days) => println!("History retention: {days} days"),
+        None => println!("History retention: kept indefinitely"),
+    }
+}
+
+fn print_org(org: &Organization) {
+    println!("{}  (your role: {})", org.name, org.role.name());
+    println!(
+        "Seats: {} of {} used ({} free)",
+        org.seats_used,
+        org.seats_total,
+        org.seats_free()
+    );
+    print_policy(&org.policy);
+}
+
+pub async fn run(cmd: OrgCommand) -> Result<()> {
+    let client = phazeai_cloud::session_client("").await?;
+    if let Err(e) = entitlements::refresh(&client).await {
+        tracing::debug!("Entitlement check failed: {e}");
+    }
+    Feature::Organization.require()?;
+    let org = client.organization().await?;
+
+    match cmd {
+        OrgCommand::Show => print_org(&org),
+        OrgCommand::Seats => {
+            org.role.require_admin()?;
+            for seat in client.seats().await? {
+                println!(
+                    "  {:<8} {:<8} {:<32} {:>6} requests {:>10} tokens  {}",
+                    seat.status.name(),
+                    seat.role.name(),
+                    seat.email,
+                    seat.requests_used,
+                    seat.tokens_used,
+                    seat.last_active.as_deref().unwrap_or("never active")
+                );
+            }
+        }
+        OrgCommand::Invite { email, role } => {
+            org.role.require_admin()?;
+            let role = TeamRole::parse(&role)
+                .ok_or_else(|| anyhow!("unknown role '{role}' (viewer, member, admin)"))?;
+            let seat = client.invite_member(email.trim(), role).await?;
+            println!("Invited {} as {}.", seat.email, seat.role.name());
+        }
+        OrgCommand::Revoke { email } => {
+            org.role.require_admin()?;
+            client.revoke_seat(email.trim()).await?;
+            println!("Revoked access for {}.", email.trim());
+        }
+        OrgCommand::Policy {
+            allow,
+            allow_all,
+            retention_days,
+            keep_forever,
+        } => {
+            let mut policy = org.policy.clone();
+            if allow_all {
+                policy.allowed_providers.clear();
+            } else if let Some(allow) = allow {
+                policy.allowed_providers = allow
+                    .iter()
+                    .map(|p| p.trim().to_lowercase())
+                    .filter(|p| !p.is_empty())
+                    .collect();
+            }
+            if keep_forever {
+                policy.retention_days = None;
+            } else if retention_days.is_some() {
+                policy.retention_days = retention_days;
+            }
+            if policy != org.policy {
+                org.role.require_admin()?;
+                policy = client.set_org_policy(&policy).await?;
+                println!("Updated the organization policy.");
+            }
+            policy.save()?;
+            let pruned = policy.apply()?;
+            print_policy(&policy);
+            if pruned > 0 {
+                println!("Deleted {pruned} local conversations past the retention period.");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! `phazeai replay`: re-run a recorded agent session in a sandbox.
+
+use anyhow::Result;
+use phazeai_core::agent::recording::{self, RunRecording};
+use std::path::{Path, PathBuf};
+
+/// Longest expected/actual excerpt printed per divergence.
+const MAX_EXCERPT: usize = 400;
+
+/// Replay `bundle` and print a report. Returns the exit code:
+/// 0 when the replay matches the recording, 1 otherwise.
+pub async fn run(bundle: &Path, sandbox: Option<PathBuf>, keep: bool) -> Result<i32> {
+    let recording = RunRecording::load(bundle)?;
+    let sandbox = sandbox
+        .unwrap_or_else(|| std::env::temp_dir().join(format!("phazeai-replay-{}", recording.id)));
+    eprintln!(
+        "Replaying {} ({} model turns, {} tool calls) in {}",
+        bundle.display(),
+        recording.model_turns.len(),
+        recording.tool_calls.len(),
+        sandbox.display()
+    );
+
+    let report = recording::replay(&recording, &sandbox).await?;
+
+    for d in &report.divergences {
+        println!("✗ tool #{} {} — {} differs", d.index + 1, d.tool, d.what);
+        println!("  expected: {}", excerpt(&d.expected));
+        println!("  actual:   {}", excerpt(&d.actual));
+    }
+    if let Some(err) = &report.error {
+        println!("✗ agent loop failed: {err}");
+    }
+    if report.is_faithful() {
+        println!(
+            "✓ replay matches recording ({} tool calls)",
+            report.tool_calls
+        );
+    }
+
+    if keep {
+        eprintln!("Sandbox kept at {}", report.sandbox.display());
+    } else {
+        let _ = std::fs::remove_dir_all(&report.sandbox);
+    }
+    Ok(if report.is_faithful() { 0 } else { 1 })
+}
+
+fn excerpt(s: &str) -> String {
+    let one_line = s.replace('\n', "⏎");
+    match one_line.char_indices().nth(MAX_EXCERPT) {
+        Some((idx, _)) => format!("{}…", &one_line[..idx]),
+        None => one_line,
+    }
+}
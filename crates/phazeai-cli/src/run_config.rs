@@ -0,0 +1,43 @@
+//! `phazeai run-config`: run a configuration of `.phazeai/launch.toml`.
+
+use anyhow::{bail, Result};
+use phazeai_core::git::GitOps;
+use phazeai_core::project::run_config::{RunConfigs, RUN_CONFIG_FILE};
+
+/// Run `name` after its pre-tasks with the terminal attached, stopping at
+/// the first that fails; with no name, list the configurations. Returns the
+/// exit code of the last command run.
+pub fn run(name: Option<String>) -> Result<i32> {
+    let cwd = std::env::current_dir()?;
+    let root = GitOps::find_root(&cwd).unwrap_or(cwd);
+    let configs = RunConfigs::load(&root)?;
+
+    let Some(name) = name else {
+        if configs.configurations.is_empty() {
+            println!("No run configurations; add them to {RUN_CONFIG_FILE}.");
+        }
+        for config in &configs.configurations {
+            let pre = config
+                .pre_task
+                .as_deref()
+                .map(|t| format!("  (after {t})"))
+                .unwrap_or_default();
+            println!("{:<20} {}{pre}", config.name, config.command_line());
+        }
+        return Ok(0);
+    };
+
+    for config in configs.plan(&name)? {
+        eprintln!("> [{}] {}", config.name, config.command_line());
+        let status = match config.command(&root).status() {
+            Ok(status) => status,
+            Err(e) => bail!("could not run {}: {e}", config.command),
+        };
+        if !status.success() {
+            let code = status.code().unwrap_or(1);
+            eprintln!("[{}] exited with code {code}", config.name);
+            return Ok(code);
+        }
+    }
+    Ok(0)
+}
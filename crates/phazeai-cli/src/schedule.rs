@@ -0,0 +1,224 @@
+//! `phazeai schedule` and `phazeai daemon`: recurring agent jobs.
+//!
+//! Schedules are stored in the config file. The daemon re-reads them every
+//! minute, so edits from the CLI or the IDE apply without a restart, and
+//! saves each run to conversation history tagged `scheduled`.
+
+use anyhow::{bail, Context, Result};
+use chrono::{Local, NaiveDateTime, Timelike};
+use clap::Subcommand;
+use phazeai_core::agent::schedule::{run_scheduled, SCHEDULED_TAG};
+use phazeai_core::agent::{AgentFactory, CronSchedule, ScheduledJob};
+use phazeai_core::tools::BashTool;
+use phazeai_core::{Agent, ConversationStore, Settings, ToolRegistry};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Subcommand)]
+pub enum ScheduleCommand {
+    /// List schedules and when each runs next
+    List,
+    /// Add a schedule
+    Add {
+        /// Unique name for the schedule
+        name: String,
+
+        /// Cron expression (minute hour day month weekday) or @hourly, @nightly, @weekly...
+        #[arg(long)]
+        cron: String,
+
+        /// What the agent should do
+        #[arg(long)]
+        prompt: String,
+
+        /// Directory the agent works in (default: the current directory)
+        #[arg(long, value_name = "DIR")]
+        workspace: Option<PathBuf>,
+
+        /// Run each time in a new git worktree on a phaze/<task> branch
+        #[arg(long)]
+        worktree: bool,
+
+        /// Let the run use a tool besides read-only ones, e.g. write_file or bash (repeatable)
+        #[arg(long = "allow", value_name = "TOOL")]
+        allowed_tools: Vec<String>,
+    },
+    /// Delete a schedule
+    Remove { name: String },
+    /// Resume a disabled schedule
+    Enable { name: String },
+    /// Stop running a schedule without deleting it
+    Disable { name: String },
+    /// Run a schedule now and save the result to history
+    Run { name: String },
+}
+
+pub async fn run(cmd: ScheduleCommand) -> Result<()> {
+    let settings = Settings::load();
+    match cmd {
+        ScheduleCommand::List => {
+            if settings.schedules.is_empty() {
+                eprintln!("No schedules. Add one with: phazeai schedule add <name> --cron ... --prompt ...");
+                return Ok(());
+            }
+            let now = Local::now().naive_local();
+            for job in &settings.schedules {
+                let next = match job.schedule() {
+                    Err(e) => format!("invalid: {e}"),
+                    Ok(_) if !job.enabled => "disabled".to_string(),
+                    Ok(_) => job
+                        .next_run(now)
+                        .map(|t| format!("next {}", t.format("%Y-%m-%d %H:%M")))
+                        .unwrap_or_else(|| "never".to_string()),
+                };
+                let worktree = if job.use_worktree { " [worktree]" } else { "" };
+                let allowed = if job.allowed_tools.is_empty() {
+                    " [read-only]".to_string()
+                } else {
+                    format!(" [allows {}]", job.allowed_tools.join(", "))
+                };
+                println!(
+                    "{:<20} {:<14} {next}{worktree}{allowed}",
+                    job.name, job.cron
+                );
+                println!("    {} — {}", job.workspace.display(), job.prompt);
+            }
+            Ok(())
+        }
+        ScheduleCommand::Add {
+            name,
+            cron,
+            prompt,
+            workspace,
+            worktree,
+            allowed_tools,
+        } => {
+            CronSchedule::parse(&cron).map_err(anyhow::Error::msg)?;
+            if settings.schedules.iter().any(|j| j.name == name) {
+                bail!("A schedule named '{name}' already exists");
+            }
+            let cwd = std::env::current_dir()?;
+            let workspace = match workspace {
+                Some(w) if w.is_absolute() => w,
+                Some(w) => cwd.join(w),
+                None => cwd,
+            };
+            let job = ScheduledJob {
+                name,
+                cron,
+                prompt,
+                workspace,
+                use_worktree: worktree,
+                enabled: true,
+                allowed_tools,
+            };
+            Settings::update(|s| s.schedules.push(job))?;
+            eprintln!("Saved. Schedules run while `phazeai daemon` is running.");
+            Ok(())
+        }
+        ScheduleCommand::Remove { name } => {
+            find(&settings, &name)?;
+            Settings::update(|s| s.schedules.retain(|j| j.name != name))?;
+            Ok(())
+        }
+        ScheduleCommand::Enable { name } => set_enabled(&settings, &name, true),
+        ScheduleCommand::Disable { name } => set_enabled(&settings, &name, false),
+        ScheduleCommand::Run { name } => {
+            let job = find(&settings, &name)?.clone();
+            run_and_report(&job, &agent_factory(), &settings.llm.model).await
+        }
+    }
+}
+
+fn find<'a>(settings: &'a Settings, name: &str) -> Result<&'a ScheduledJob> {
+    settings
+        .schedules
+        .iter()
+        .find(|j| j.name == name)
+        .with_context(|| format!("No schedule named '{name}'"))
+}
+
+fn set_enabled(settings: &Settings, name: &str, enabled: bool) -> Result<()> {
+    find(settings, name)?;
+    Settings::update(|s| {
+        for job in s.schedules.iter_mut().filter(|j| j.name == name) {
+            job.enabled = enabled;
+        }
+    })?;
+    Ok(())
+}
+
+/// Agents for scheduled runs, built from the current config with the shell
+/// rooted in the run's directory.
+fn agent_factory() -> AgentFactory {
+    Arc::new(|dir: &Path| {
+        let settings = Settings::load();
+        let client = settings.build_llm_client()?;
+        let mut tools = ToolRegistry::default();
+        tools.register(Box::new(BashTool::new(dir.to_path_buf())));
+        Ok(Agent::new(client)
+            .with_tools(tools)
+            .with_limits(&settings.agent))
+    })
+}
+
+async fn run_and_report(job: &ScheduledJob, factory: &AgentFactory, model: &str) -> Result<()> {
+    let store = ConversationStore::new()?;
+    let run = run_scheduled(job, factory, model, &store).await?;
+    let id = &run.conversation.metadata.id;
+    let short_id = id.get(..8).unwrap_or(id);
+    match run.error {
+        Some(e) => eprintln!("[{}] failed: {e} (saved as {short_id})", job.name),
+        None => eprintln!("[{}] done (saved as {short_id})", job.name),
+    }
+    Ok(())
+}
+
+/// Run due schedules until interrupted. Jobs start at the top of the minute;
+/// a job still running when it comes due again is skipped that time.
+pub async fn daemon() -> Result<()> {
+    let factory = agent_factory();
+    let running: Arc<Mutex<HashSet<String>>> = Arc::default();
+    eprintln!(
+        "phazeai daemon: running {} schedule(s); results are saved to history (phazeai history search --tag {SCHEDULED_TAG})",
+        Settings::load().schedules.iter().filter(|j| j.enabled).count()
+    );
+    let mut last_tick: Option<NaiveDateTime> = None;
+    loop {
+        let now = Local::now().naive_local();
+        let minute = now
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(now);
+        if last_tick != Some(minute) {
+            last_tick = Some(minute);
+            // Re-read so schedules added or edited since the last tick apply.
+            let settings = Settings::load();
+            for job in settings.schedules.into_iter().filter(|j| j.is_due(minute)) {
+                if !running.lock().unwrap().insert(job.name.clone()) {
+                    tracing::warn!("Skipping {}: previous run still in progress", job.name);
+                    continue;
+                }
+                eprintln!("[{}] starting", job.name);
+                let factory = Arc::clone(&factory);
+                let running = Arc::clone(&running);
+                let model = settings.llm.model.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = run_and_report(&job, &factory, &model).await {
+                        eprintln!("[{}] could not save result: {e}", job.name);
+                    }
+                    running.lock().unwrap().remove(&job.name);
+                });
+            }
+        }
+        let wait = 60 - Local::now().second().min(59);
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(wait.into())) => {}
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("phazeai daemon: stopping");
+                return Ok(());
+            }
+        }
+    }
+}
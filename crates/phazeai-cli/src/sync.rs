@@ -0,0 +1,133 @@
+//! `phazeai sync`: conversation sync with PhazeAI Cloud, optionally end-to-end
+//! encrypted.
+
+use std::io::Write;
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use phazeai_cloud::{e2e::MIN_PASSPHRASE_LEN, entitlements, sync, Privacy};
+use phazeai_core::context::ConversationStore;
+
+/// Read the passphrase from here instead of prompting (for scripts).
+const PASSPHRASE_ENV: &str = "PHAZEAI_SYNC_PASSPHRASE";
+
+#[derive(Subcommand)]
+pub enum SyncCommand {
+    /// Upload and download conversations now
+    Now,
+    /// Show whether synced conversations are end-to-end encrypted
+    Status,
+    /// Encrypt synced conversations with a passphrase, or unlock them on this machine
+    Encrypt,
+    /// Re-encrypt every synced conversation with a new passphrase
+    RotateKey,
+    /// Turn off end-to-end encryption; synced conversations become server-readable
+    Decrypt,
+}
+
+/// Prompt for a passphrase without echoing it.
+fn read_passphrase(prompt: &str) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    eprint!("{prompt}: ");
+    std::io::stderr().flush()?;
+    crossterm::terminal::enable_raw_mode()?;
+    let mut passphrase = String::new();
+    let result = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Enter => break Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break Err(anyhow::anyhow!("cancelled"))
+                }
+                KeyCode::Char(c) => passphrase.push(c),
+                KeyCode::Backspace => {
+                    passphrase.pop();
+                }
+                KeyCode::Esc => break Err(anyhow::anyhow!("cancelled")),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    crossterm::terminal::disable_raw_mode()?;
+    eprintln!();
+    result.map(|_| passphrase)
+}
+
+/// A new passphrase, typed twice.
+fn read_new_passphrase() -> Result<String> {
+    let passphrase = read_passphrase(&format!(
+        "New passphrase (at least {MIN_PASSPHRASE_LEN} characters)"
+    ))?;
+    if std::env::var(PASSPHRASE_ENV).is_err() && read_passphrase("Repeat it")? != passphrase {
+        bail!("the passphrases don't match");
+    }
+    Ok(passphrase)
+}
+
+pub async fn run(cmd: SyncCommand) -> Result<()> {
+    let client = phazeai_cloud::session_client("").await?;
+    if let Err(e) = entitlements::refresh(&client).await {
+        tracing::debug!("Entitlement check failed: {e}");
+    }
+    let store = ConversationStore::new()?;
+
+    match cmd {
+        SyncCommand::Now => {
+            let report = sync::sync_conversations(&client, &store).await?;
+            println!(
+                "Synced conversations ({}): {report}.",
+                Privacy::current().describe()
+            );
+        }
+        SyncCommand::Status => {
+            let privacy = Privacy::current();
+            println!("Conversations: {}", privacy.describe());
+            if !privacy.is_encrypted() {
+                println!(
+                    "  PhazeAI Cloud can read them. Run `phazeai sync encrypt` to change that."
+                );
+            }
+            if let Some(params) = client.sync_key_params().await? {
+                let unlocked =
+                    matches!(&privacy, Privacy::EndToEnd { key_id } if *key_id == params.key_id);
+                if !unlocked {
+                    println!(
+                        "  The account uses key {}; run `phazeai sync encrypt` to unlock it here.",
+                        params.key_id
+                    );
+                }
+            }
+            println!("Account, plan, team context and org policy: server-readable");
+        }
+        SyncCommand::Encrypt => {
+            let passphrase = match client.sync_key_params().await? {
+                Some(params) => read_passphrase(&format!("Passphrase for key {}", params.key_id))?,
+                None => read_new_passphrase()?,
+            };
+            let (key, report) = sync::enable_encryption(&client, &store, &passphrase).await?;
+            println!(
+                "Synced conversations are end-to-end encrypted with key {} ({report}).",
+                key.id()
+            );
+            println!("Keep the passphrase safe: PhazeAI can't recover it.");
+        }
+        SyncCommand::RotateKey => {
+            let passphrase = read_new_passphrase()?;
+            let (key, report) = sync::rotate_key(&client, &store, &passphrase).await?;
+            println!(
+                "Re-encrypted synced conversations with key {} ({report}).",
+                key.id()
+            );
+        }
+        SyncCommand::Decrypt => {
+            let report = sync::disable_encryption(&client, &store).await?;
+            println!("Synced conversations are now server-readable ({report}).");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! `phazeai team`: view and manage the shared team context on PhazeAI Cloud.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use clap::Subcommand;
+use phazeai_cloud::{entitlements, team, TeamCache, TeamRole};
+
+#[derive(Subcommand)]
+pub enum TeamCommand {
+    /// Show the team's memories, prompt profiles and pinned documents
+    Show,
+    /// Fetch the latest team context now
+    Sync,
+    /// Save a shared memory for every member's agent
+    Remember { text: String },
+    /// Delete a shared memory by id
+    Forget { id: String },
+    /// Pin a file as a context document for every member's agent
+    Pin {
+        file: PathBuf,
+        /// Document title (default: the file name)
+        #[arg(long)]
+        title: Option<String>,
+    },
+    /// Unpin a context document by id
+    Unpin { id: String },
+    /// Manage prompt profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+    /// List team members and their roles
+    Members,
+    /// Change a member's role: viewer, member or admin (admins only)
+    Role { email: String, role: String },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileCommand {
+    /// Use this profile's instructions for agents on this machine
+    Use { name: String },
+    /// Create or replace a profile from a file of instructions (admins only)
+    Set { name: String, file: PathBuf },
+    /// Delete a profile (admins only)
+    Rm { name: String },
+}
+
+fn short(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+fn print_context(cache: &TeamCache) {
+    let ctx = &cache.context;
+    println!(
+        "{}  (you: {}, {})",
+        ctx.team_name,
+        ctx.email,
+        ctx.role.name()
+    );
+
+    println!("\nMemories ({}):", ctx.memories.len());
+    for m in &ctx.memories {
+        println!("  {}  {}  — {}", short(&m.id), m.text, m.author);
+    }
+
+    let active = cache
+        .active_profile
+        .as_deref()
+        .unwrap_or(team::DEFAULT_PROFILE);
+    println!("\nPrompt profiles ({}):", ctx.profiles.len());
+    for p in &ctx.profiles {
+        let marker = if p.name.eq_ignore_ascii_case(active) {
+            "*"
+        } else {
+            " "
+        };
+        let first_line = p.instructions.lines().next().unwrap_or_default();
+        println!("  {marker} {:<16} {first_line}", p.name);
+    }
+
+    println!("\nPinned documents ({}):", ctx.documents.len());
+    for d in &ctx.documents {
+        println!(
+            "  {}  {}  ({} chars) — {}",
+            short(&d.id),
+            d.title,
+            d.content.len(),
+            d.pinned_by
+        );
+    }
+}
+
+/// Resolve an id or unique id prefix among `ids`.
+fn resolve<'a>(prefix: &str, ids: impl Iterator<Item = &'a str>) -> Result<String> {
+    let matches: Vec<&str> = ids.filter(|id| id.starts_with(prefix)).collect();
+    match matches.as_slice() {
+        [id] => Ok(id.to_string()),
+        [] => Err(anyhow!("no item with id '{prefix}'")),
+        _ => Err(anyhow!("id '{prefix}' is ambiguous")),
+    }
+}
+
+pub async fn run(cmd: TeamCommand) -> Result<()> {
+    let client = phazeai_cloud::session_client("").await?;
+    // Plan changes take effect immediately; offline, the cached entitlements apply.
+    if let Err(e) = entitlements::refresh(&client).await {
+        tracing::debug!("Entitlement check failed: {e}");
+    }
+    // Every command starts from a fresh snapshot so role checks and ids are current.
+    let cache = team::sync(&client).await?;
+    let ctx = &cache.context;
+    let changes_team = !matches!(
+        cmd,
+        TeamCommand::Show
+            | TeamCommand::Sync
+            | TeamCommand::Members
+            | TeamCommand::Profile {
+                command: ProfileCommand::Use { .. }
+            }
+    );
+
+    match cmd {
+        TeamCommand::Show => print_context(&cache),
+        TeamCommand::Sync => println!(
+            "Synced {}: {} memories, {} profiles, {} pinned documents.",
+            ctx.team_name,
+            ctx.memories.len(),
+            ctx.profiles.len(),
+            ctx.documents.len()
+        ),
+        TeamCommand::Remember { text } => {
+            ctx.role.require_write()?;
+            let memory = client.add_team_memory(text.trim()).await?;
+            println!("Saved team memory {}.", short(&memory.id));
+        }
+        TeamCommand::Forget { id } => {
+            let id = resolve(&id, ctx.memories.iter().map(|m| m.id.as_str()))?;
+            let memory = ctx.memories.iter().find(|m| m.id == id);
+            if !memory.is_some_and(|m| ctx.role.can_remove(&m.author, &ctx.email)) {
+                ctx.role.require_admin()?;
+            }
+            client.delete_team_memory(&id).await?;
+            println!("Deleted team memory {}.", short(&id));
+        }
+        TeamCommand::Pin { file, title } => {
+            ctx.role.require_write()?;
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("reading {}", file.display()))?;
+            let title = title.unwrap_or_else(|| {
+                file.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| file.display().to_string())
+            });
+            let doc = client.pin_document(&title, &content).await?;
+            println!("Pinned \"{}\" ({}).", doc.title, short(&doc.id));
+        }
+        TeamCommand::Unpin { id } => {
+            let id = resolve(&id, ctx.documents.iter().map(|d| d.id.as_str()))?;
+            let doc = ctx.documents.iter().find(|d| d.id == id);
+            if !doc.is_some_and(|d| ctx.role.can_remove(&d.pinned_by, &ctx.email)) {
+                ctx.role.require_admin()?;
+            }
+            client.unpin_document(&id).await?;
+            println!("Unpinned {}.", short(&id));
+        }
+        TeamCommand::Profile { command } => match command {
+            ProfileCommand::Use { name } => {
+                let profile = ctx
+                    .profile(&name)
+                    .ok_or_else(|| anyhow!("no prompt profile named '{name}'"))?;
+                let mut cache = cache.clone();
+                cache.active_profile = Some(profile.name.clone());
+                cache.save()?;
+                println!(
+                    "Agents on this machine now use the '{}' profile.",
+                    profile.name
+                );
+            }
+            ProfileCommand::Set { name, file } => {
+                ctx.role.require_admin()?;
+                let instructions = std::fs::read_to_string(&file)
+                    .with_context(|| format!("reading {}", file.display()))?;
+                let profile = client.put_prompt_profile(&name, &instructions).await?;
+                println!("Saved prompt profile '{}'.", profile.name);
+            }
+            ProfileCommand::Rm { name } => {
+                ctx.role.require_admin()?;
+                client.delete_prompt_profile(&name).await?;
+                println!("Deleted prompt profile '{name}'.");
+            }
+        },
+        TeamCommand::Members => {
+            for member in client.team_members().await? {
+                println!("  {:<8} {}", member.role.name(), member.email);
+            }
+        }
+        TeamCommand::Role { email, role } => {
+            ctx.role.require_admin()?;
+            let role = TeamRole::parse(&role)
+                .ok_or_else(|| anyhow!("unknown role '{role}' (viewer, member, admin)"))?;
+            let member = client.set_member_role(&email, role).await?;
+            println!("{} is now {}.", member.email, member.role.name());
+        }
+    }
+
+    // Pick up the change in the local snapshot right away.
+    if changes_team {
+        team::sync(&client).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! `phazeai update`: look for a newer release on the configured channel and
+//! stage it, to be installed the next time `phazeai` starts.
+
+use anyhow::{anyhow, bail, Result};
+use phazeai_core::update::{self, Channel, CURRENT_VERSION};
+use phazeai_core::Settings;
+
+/// The name of this binary's assets in the release feed.
+pub const BINARY: &str = "phazeai";
+
+/// Check for an update and, unless `check_only`, download and stage it.
+/// `channel` switches the configured channel first.
+pub async fn run(channel: Option<String>, check_only: bool) -> Result<()> {
+    let mut settings = Settings::load();
+    if let Some(name) = channel {
+        let channel = Channel::from_name(&name)
+            .ok_or_else(|| anyhow!("unknown channel '{name}' (expected stable or beta)"))?;
+        if channel != settings.updates.channel {
+            settings = Settings::update(|s| s.updates.channel = channel)?;
+            println!("Switched to the {} channel.", channel.label());
+        }
+    }
+
+    let channel = settings.updates.channel;
+    let Some(release) = update::check(&settings.updates).await? else {
+        println!(
+            "phazeai {CURRENT_VERSION} is the newest release on the {} channel.",
+            channel.label()
+        );
+        return Ok(());
+    };
+    println!(
+        "phazeai {} is available on the {} channel (you have {CURRENT_VERSION}).",
+        release.version,
+        channel.label()
+    );
+    if !release.notes.trim().is_empty() {
+        println!("\n{}\n", release.notes.trim());
+    }
+    if check_only {
+        return Ok(());
+    }
+
+    let target = update::target();
+    let Some(asset) = release.asset(BINARY, &target) else {
+        bail!(
+            "release {} has no {BINARY} build for {target}",
+            release.version
+        );
+    };
+    let bytes = update::download(&release, asset).await?;
+    update::stage(
+        &update::updates_dir(),
+        &release.version,
+        release.channel,
+        asset,
+        &bytes,
+    )?;
+    println!(
+        "Verified and staged {}; it's installed the next time phazeai starts.",
+        release.version
+    );
+    Ok(())
+}
+
+/// Install an update staged by an earlier `phazeai update` and start it in
+/// place of this process. Call before anything else.
+pub fn apply_staged() {
+    let (Ok(exe), Some(key)) = (std::env::current_exe(), update::release_key()) else {
+        return;
+    };
+    match update::apply_staged(&update::updates_dir(), BINARY, &exe, CURRENT_VERSION, &key) {
+        Ok(Some(staged)) => {
+            eprintln!("Updated phazeai to {}.", staged.version);
+            let e = update::restart(&exe);
+            eprintln!("Couldn't restart phazeai: {e}");
+            std::process::exit(1);
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Couldn't install the staged update: {e}"),
+    }
+}
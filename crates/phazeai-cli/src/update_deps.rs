@@ -0,0 +1,109 @@
+//! `phazeai update-deps`: list outdated dependencies, summarize the
+//! breaking changes that affect this codebase, and bump the manifests.
+
+use anyhow::{Context, Result};
+use phazeai_core::audit::{self, Ecosystem, Outdated};
+use phazeai_core::git::GitOps;
+use phazeai_core::Settings;
+
+/// Usage sites shown to the model per dependency.
+const MAX_USAGES: usize = 40;
+
+/// Check the workspace's dependencies, explain the breaking updates and
+/// print the manifest bumps as a diff, writing them with `apply`.
+pub async fn run(settings: &Settings, apply: bool, summarize: bool) -> Result<()> {
+    let cwd = std::env::current_dir()?;
+    let root = GitOps::find_root(&cwd).unwrap_or(cwd);
+    eprintln!("update-deps: checking {}", root.display());
+    let outdated = audit::find_outdated(&root)
+        .await
+        .context("checking the registries failed")?;
+    if outdated.is_empty() {
+        println!("All dependencies are up to date.");
+        return Ok(());
+    }
+
+    let width = outdated
+        .iter()
+        .map(|o| o.dependency.name.len())
+        .max()
+        .unwrap_or(0);
+    for o in &outdated {
+        println!(
+            "{:<width$}  {:>10} -> {:<10}  {:<10}  {}",
+            o.dependency.name,
+            o.current,
+            o.latest,
+            if o.breaking { "breaking" } else { "compatible" },
+            o.dependency.manifest.display(),
+        );
+    }
+
+    let breaking: Vec<&Outdated> = outdated.iter().filter(|o| o.breaking).collect();
+    if summarize && !breaking.is_empty() {
+        let llm = settings.build_llm_client()?;
+        for o in &breaking {
+            let notes = audit::fetch_release_notes(o).await.unwrap_or_else(|e| {
+                tracing::warn!("release notes for {}: {e}", o.dependency.name);
+                None
+            });
+            let usages = audit::usage_sites(&root, &o.dependency, MAX_USAGES);
+            let summary =
+                audit::summarize_breaking_changes(llm.as_ref(), o, notes.as_deref(), &usages)
+                    .await
+                    .with_context(|| format!("summarizing {} failed", o.dependency.name))?;
+            println!(
+                "\n== {} {} -> {} ({} usage sites) ==\n{summary}",
+                o.dependency.name,
+                o.current,
+                o.latest,
+                usages.len()
+            );
+        }
+    }
+
+    let compatible: Vec<&str> = outdated
+        .iter()
+        .filter(|o| !o.breaking)
+        .map(|o| o.dependency.name.as_str())
+        .collect();
+    if !compatible.is_empty() {
+        let cargo = outdated
+            .iter()
+            .any(|o| !o.breaking && o.dependency.ecosystem == Ecosystem::CratesIo);
+        let npm = outdated
+            .iter()
+            .any(|o| !o.breaking && o.dependency.ecosystem == Ecosystem::Npm);
+        println!(
+            "\n{} compatible update(s) need only a lockfile update: {}",
+            compatible.len(),
+            match (cargo, npm) {
+                (true, true) => "`cargo update` and `npm update`",
+                (true, false) => "`cargo update`",
+                _ => "`npm update`",
+            }
+        );
+    }
+
+    let edits = audit::bump_edits(&root, &outdated);
+    if edits.is_empty() {
+        return Ok(());
+    }
+    println!();
+    for edit in &edits {
+        print!("{}", edit.unified_diff());
+    }
+    if apply {
+        for edit in &edits {
+            std::fs::write(root.join(&edit.path), &edit.after)
+                .with_context(|| format!("writing {}", edit.path.display()))?;
+        }
+        println!(
+            "\nBumped {} manifest(s); update the lockfiles and fix the call sites above.",
+            edits.len()
+        );
+    } else {
+        println!("\nRe-run with --apply to write these changes.");
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! `phazeai workspace`: back up the workspace's `.phazeai` state and its
+//! conversations to one archive, and restore it.
+
+use anyhow::Result;
+use clap::Subcommand;
+use phazeai_core::git::GitOps;
+use phazeai_core::project::backup;
+use phazeai_core::ConversationStore;
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum WorkspaceCommand {
+    /// Write memories, tasks, run configurations, recordings and the
+    /// workspace's conversations to a zip archive
+    Export {
+        /// The archive to write (default: <workspace>-phazeai-<date>.zip here)
+        out: Option<PathBuf>,
+    },
+    /// Restore an archive written by `phazeai workspace export` into this workspace
+    Restore {
+        archive: PathBuf,
+        /// Replace files and conversations that already exist
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+fn workspace_root() -> Result<PathBuf> {
+    let cwd = std::env::current_dir()?;
+    Ok(GitOps::find_root(&cwd).unwrap_or(cwd))
+}
+
+fn default_archive(root: &Path) -> PathBuf {
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "workspace".into());
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("{name}-phazeai-{stamp}.zip"))
+}
+
+pub fn run(command: WorkspaceCommand) -> Result<()> {
+    let root = workspace_root()?;
+    let store = ConversationStore::new()?;
+    match command {
+        WorkspaceCommand::Export { out } => {
+            let out = out.unwrap_or_else(|| default_archive(&root));
+            let manifest = backup::export(&root, &store, &out)?;
+            println!(
+                "Backed up {} files and {} conversations from {} to {}",
+                manifest.files.len(),
+                manifest.conversations.len(),
+                root.display(),
+                out.display()
+            );
+        }
+        WorkspaceCommand::Restore { archive, force } => {
+            let manifest = backup::read_manifest(&archive)?;
+            let report = backup::restore(&archive, &root, &store, force)?;
+            println!(
+                "Restored {} files and {} conversations from {} (taken {} in {})",
+                report.files,
+                report.conversations,
+                archive.display(),
+                manifest.created_at,
+                manifest.workspace.display()
+            );
+            if !report.skipped.is_empty() {
+                println!(
+                    "Kept {} that already exist (--force replaces them):",
+                    report.skipped.len()
+                );
+                for name in &report.skipped {
+                    println!("  {name}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use phazeai_cli::headless::{event_to_json, RunSummary};
+use phazeai_core::context::{Citation, DocChunk};
+use phazeai_core::AgentEvent;
+
+#[test]
+fn test_text_delta_event_json() {
+    let v = event_to_json(&AgentEvent::TextDelta("hello".into()));
+    assert_eq!(v["type"], "text_delta");
+    assert_eq!(v["text"], "hello");
+}
+
+#[test]
+fn test_tool_result_event_json() {
+    let v = event_to_json(&AgentEvent::ToolResult {
+        name: "bash".into(),
+        success: false,
+        summary: "exit 2".into(),
+    });
+    assert_eq!(v["type"], "tool_result");
+    assert_eq!(v["name"], "bash");
+    assert_eq!(v["success"], false);
+    assert_eq!(v["summary"], "exit 2");
+}
+
+#[test]
+fn test_token_usage_event_json() {
+    let v = event_to_json(&AgentEvent::TokenUsage {
+        input_tokens: 120,
+        output_tokens: 45,
+    });
+    assert_eq!(v["type"], "token_usage");
+    assert_eq!(v["input_tokens"], 120);
+    assert_eq!(v["output_tokens"], 45);
+}
+
+#[test]
+fn test_first_token_event_json() {
+    let v = event_to_json(&AgentEvent::FirstToken {
+        latency: std::time::Duration::from_millis(840),
+    });
+    assert_eq!(v["type"], "first_token");
+    assert_eq!(v["latency_ms"], 840);
+}
+
+#[test]
+fn test_stream_resumed_event_json() {
+    let v = event_to_json(&AgentEvent::StreamResumed {
+        attempt: 2,
+        reason: "connection reset".into(),
+    });
+    assert_eq!(v["type"], "stream_resumed");
+    assert_eq!(v["attempt"], 2);
+    assert_eq!(v["reason"], "connection reset");
+}
+
+#[test]
+fn test_reasoning_delta_event_json() {
+    let v = event_to_json(&AgentEvent::ReasoningDelta("Checking the callers".into()));
+    assert_eq!(v["type"], "reasoning_delta");
+    assert_eq!(v["text"], "Checking the callers");
+}
+
+#[test]
+fn test_doc_sources_event_json() {
+    let v = event_to_json(&AgentEvent::DocSources(vec![DocChunk {
+        path: "docs/auth.md".into(),
+        heading: "Login › Tokens".into(),
+        line: 42,
+        page: None,
+        text: "Tokens expire after an hour.".into(),
+    }]));
+    assert_eq!(v["type"], "doc_sources");
+    assert_eq!(v["sources"][0]["label"], "D1");
+    assert_eq!(v["sources"][0]["path"], "docs/auth.md");
+    assert_eq!(v["sources"][0]["line"], 42);
+    assert!(v["sources"][0]["page"].is_null());
+}
+
+#[test]
+fn test_citations_event_json() {
+    let v = event_to_json(&AgentEvent::Citations(vec![Citation::new(
+        "src/lib.rs",
+        120,
+        160,
+    )]));
+    assert_eq!(v["type"], "citations");
+    assert_eq!(v["citations"][0]["path"], "src/lib.rs");
+    assert_eq!(v["citations"][0]["start_line"], 120);
+    assert_eq!(v["citations"][0]["end_line"], 160);
+}
+
+#[test]
+fn test_event_json_is_single_line() {
+    let v = event_to_json(&AgentEvent::TextDelta("line one\nline two".into()));
+    assert!(!v.to_string().contains('\n'));
+}
+
+#[test]
+fn test_exit_record_success() {
+    let summary = RunSummary {
+        iterations: 3,
+        input_tokens: 10,
+        output_tokens: 20,
+        error: None,
+    };
+    assert_eq!(summary.exit_code(), 0);
+    let v = summary.to_json();
+    assert_eq!(v["type"], "exit");
+    assert_eq!(v["status"], "success");
+    assert_eq!(v["code"], 0);
+    assert_eq!(v["iterations"], 3);
+    assert!(v["error"].is_null());
+}
+
+#[test]
+fn test_exit_record_error() {
+    let summary = RunSummary {
+        error: Some("Cancelled".into()),
+        ..Default::default()
+    };
+    assert_eq!(summary.exit_code(), 1);
+    let v = summary.to_json();
+    assert_eq!(v["status"], "error");
+    assert_eq!(v["code"], 1);
+    assert_eq!(v["error"], "Cancelled");
+}
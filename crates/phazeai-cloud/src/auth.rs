@@ -1,16 +1,73 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use phazeai_core::secrets;
 use serde::{Deserialize, Serialize};
 
+use crate::cloud_api_url;
+
+/// Access tokens are renewed when they have less than this left.
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(2 * 60);
+
+/// Where the refresh token is filed in the OS keychain.
+const KEYCHAIN_SERVICE: &str = "phazeai-cloud";
+const KEYCHAIN_ACCOUNT: &str = "refresh-token";
+
 /// Returns the browser URL for OAuth sign-in.
 pub fn login_url() -> &'static str {
     "https://app.phazeai.com/signin"
 }
 
-/// Stored credentials (persisted to ~/.config/phazeai/cloud.toml).
+/// Stored credentials (persisted to ~/.config/phazeai/cloud.toml, readable
+/// only by the current user). The refresh token is kept in the OS keychain
+/// and only written to the file when there's no keychain to use. Loading
+/// never touches the keychain, which can block; [`CloudCredentials::refresh`]
+/// fetches the token from it off the calling thread when it's needed.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CloudCredentials {
     pub email: Option<String>,
-    /// API token from https://app.phazeai.com/settings/tokens
+    /// Access token from browser sign-in, or an API token from
+    /// https://app.phazeai.com/settings/tokens
     pub api_token: Option<String>,
+    /// Renews `api_token` after browser sign-in; API tokens have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// The refresh token is in the OS keychain rather than `refresh_token`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub refresh_token_in_keychain: bool,
+    /// Unix seconds when `api_token` expires; `None` if it doesn't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+/// Response of the `/auth/token` endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Lifetime of `access_token` in seconds.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+impl TokenResponse {
+    /// Credentials for this response, received at `now` (Unix seconds). A
+    /// refresh response without a new refresh token keeps `previous`'s.
+    pub fn into_credentials(self, previous: &CloudCredentials, now: u64) -> CloudCredentials {
+        let rotated = self.refresh_token.is_some();
+        CloudCredentials {
+            email: self.email.or_else(|| previous.email.clone()),
+            api_token: Some(self.access_token),
+            refresh_token: self
+                .refresh_token
+                .or_else(|| previous.refresh_token.clone()),
+            refresh_token_in_keychain: !rotated && previous.refresh_token_in_keychain,
+            expires_at: self.expires_in.map(|secs| now + secs),
+        }
+    }
 }
 
 impl CloudCredentials {
@@ -23,12 +80,34 @@ impl CloudCredentials {
         }
     }
 
+    /// Write the credentials, moving a refresh token into the OS keychain
+    /// when there is one to use. Blocks on the keychain.
     pub fn save(&self) -> anyhow::Result<()> {
         let path = credentials_path();
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        std::fs::write(path, toml::to_string_pretty(self)?)?;
+        let mut on_disk = self.clone();
+        match &self.refresh_token {
+            Some(token) => {
+                let stored = secrets::store_secret(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, token);
+                if let Err(e) = &stored {
+                    tracing::debug!("keeping the PhazeAI Cloud refresh token in cloud.toml: {e}");
+                }
+                on_disk.refresh_token_in_keychain = stored.is_ok();
+                if stored.is_ok() {
+                    on_disk.refresh_token = None;
+                }
+            }
+            // Loaded but not fetched yet: the keychain still has it.
+            None if self.refresh_token_in_keychain => {}
+            None => {
+                if let Err(e) = secrets::forget_secret(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT) {
+                    tracing::debug!("PhazeAI Cloud refresh token left in the keychain: {e}");
+                }
+            }
+        }
+        write_private(&path, &toml::to_string_pretty(&on_disk)?)?;
         Ok(())
     }
 
@@ -39,12 +118,107 @@ impl CloudCredentials {
             .unwrap_or(false)
     }
 
-    /// Clear the stored token and persist the change.
+    /// Whether the access token expires within [`REFRESH_MARGIN`] of `now`
+    /// (Unix seconds) and can be renewed.
+    pub fn needs_refresh(&self, now: u64) -> bool {
+        (self.refresh_token.is_some() || self.refresh_token_in_keychain)
+            && self
+                .expires_at
+                .is_some_and(|at| at <= now + REFRESH_MARGIN.as_secs())
+    }
+
+    /// Exchange the refresh token for a new access token and save it. A
+    /// rejected refresh token signs out, since the session is over.
+    pub async fn refresh(&mut self) -> anyhow::Result<()> {
+        if self.refresh_token.is_none() && self.refresh_token_in_keychain {
+            self.refresh_token =
+                tokio::task::spawn_blocking(|| secrets::secret(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT))
+                    .await??;
+        }
+        let Some(refresh_token) = self.refresh_token.clone() else {
+            bail!("no refresh token; sign in to PhazeAI Cloud again");
+        };
+        let body = serde_json::json!({
+            "grant_type": "refresh_token",
+            "refresh_token": refresh_token,
+        });
+        let resp = reqwest::Client::new()
+            .post(format!("{}/auth/token", cloud_api_url()))
+            .json(&body)
+            .send()
+            .await?;
+        if matches!(resp.status().as_u16(), 400 | 401) {
+            self.logout()?;
+            bail!("PhazeAI Cloud session expired; sign in again");
+        }
+        let token: TokenResponse = resp
+            .error_for_status()?
+            .json()
+            .await
+            .context("reading token response")?;
+        *self = token.into_credentials(self, now_secs());
+        let creds = self.clone();
+        tokio::task::spawn_blocking(move || creds.save()).await?
+    }
+
+    /// Clear the stored tokens, cached entitlements and team context, and
+    /// persist the change. Hosted models stop being offered as a provider.
     pub fn logout(&mut self) -> anyhow::Result<()> {
         self.api_token = None;
+        self.refresh_token = None;
+        self.refresh_token_in_keychain = false;
+        self.expires_at = None;
         self.email = None;
-        self.save()
+        crate::entitlements::clear()?;
+        crate::team::TeamCache::clear()?;
+        crate::org::OrgPolicy::clear()?;
+        crate::e2e::SyncKey::clear()?;
+        self.save()?;
+        crate::hosted::register_provider();
+        Ok(())
+    }
+}
+
+/// Write `content` to `path` with owner-only permissions where supported.
+pub(crate) fn write_private(path: &std::path::Path, content: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        // `mode` only applies to new files; tighten older ones too.
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(content.as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        std::fs::write(path, content)
+    }
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Stored credentials, renewed first if the access token is about to expire.
+/// A failed renewal keeps the current token; requests fail with 401 if it
+/// has really expired.
+pub async fn load_fresh() -> CloudCredentials {
+    let mut creds = CloudCredentials::load();
+    if creds.needs_refresh(now_secs()) {
+        if let Err(e) = creds.refresh().await {
+            tracing::debug!("PhazeAI Cloud token refresh failed: {e}");
+        }
     }
+    creds
 }
 
 fn credentials_path() -> std::path::PathBuf {
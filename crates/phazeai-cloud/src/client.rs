@@ -1,16 +1,17 @@
 use anyhow::Result;
 use futures::channel::mpsc;
 use phazeai_core::{
-    error::PhazeError, llm::OpenAIClient, tools::ToolDefinition, LlmClient, LlmResponse, Message,
-    StreamEvent,
+    error::PhazeError, tools::ToolDefinition, LlmClient, LlmResponse, Message, StreamEvent,
 };
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::{auth::CloudCredentials, cloud_api_url};
+use crate::{
+    auth::CloudCredentials, cloud_api_url, entitlements::Entitlements, hosted::CloudLlmClient,
+};
 
 /// HTTP client for the PhazeAI Cloud API.
-/// Delegates LLM calls to an `OpenAIClient` pointed at the cloud backend.
+/// Delegates LLM calls to a [`CloudLlmClient`] for `model`.
 #[derive(Clone)]
 pub struct CloudClient {
     http: Client,
@@ -32,15 +33,16 @@ impl CloudClient {
         })
     }
 
-    /// Build an `OpenAIClient` delegating to the cloud backend.
-    fn openai_client(&self) -> OpenAIClient {
-        // cloud_api_url() returns e.g. "https://api.phazeai.com/v1".
-        // OpenAIClient appends "/v1/chat/completions", so strip the trailing "/v1".
-        let base = cloud_api_url();
-        let base = base.trim_end_matches("/v1").to_string();
-        OpenAIClient::new(&self.token)
-            .with_base_url(base)
-            .with_model(&self.model)
+    /// A hosted-model client using this account's token.
+    pub fn llm_client(&self) -> CloudLlmClient {
+        CloudLlmClient::new(&self.token).with_model(&self.model)
+    }
+
+    /// An authenticated request to `path` under the cloud API base URL.
+    pub(crate) fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.http
+            .request(method, format!("{}{path}", cloud_api_url()))
+            .bearer_auth(&self.token)
     }
 
     /// Validate credentials against the cloud API and return account info.
@@ -59,6 +61,20 @@ impl CloudClient {
     }
 }
 
+impl CloudClient {
+    /// The account's tier and feature flags. Most callers want
+    /// [`entitlements::refresh`](crate::entitlements::refresh), which also caches them.
+    pub async fn entitlements(&self) -> Result<Entitlements> {
+        Ok(self
+            .request(reqwest::Method::GET, "/account/entitlements")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Entitlements>()
+            .await?)
+    }
+}
+
 #[async_trait::async_trait]
 impl LlmClient for CloudClient {
     async fn chat(
@@ -66,7 +82,7 @@ impl LlmClient for CloudClient {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<LlmResponse, PhazeError> {
-        self.openai_client().chat(messages, tools).await
+        self.llm_client().chat(messages, tools).await
     }
 
     async fn chat_stream(
@@ -74,13 +90,15 @@ impl LlmClient for CloudClient {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
-        self.openai_client().chat_stream(messages, tools).await
+        self.llm_client().chat_stream(messages, tools).await
     }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AccountInfo {
     pub email: String,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
     pub tier: String,
     pub credits_remaining: u64,
     pub credits_limit: u64,
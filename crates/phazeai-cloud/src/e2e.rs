@@ -0,0 +1,198 @@
+//! End-to-end encryption for cloud-synced data.
+//!
+//! A [`SyncKey`] is derived from a passphrase with Argon2id and encrypts with
+//! XChaCha20-Poly1305, so the server only ever stores ciphertext. The salt and
+//! an encrypted check value ([`KeyParams`]) are stored on the server, which
+//! lets another machine derive the same key from the same passphrase and
+//! reject a wrong one. The passphrase itself never leaves the machine; the
+//! derived key is kept owner-only in `~/.config/phazeai/sync-key.json`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+
+/// The only algorithm written so far; stored so it can change later.
+pub const ALGORITHM: &str = "xchacha20poly1305";
+/// Shortest passphrase accepted for a new key.
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+/// Plaintext of [`KeyParams::check`].
+const CHECK_PLAINTEXT: &[u8] = b"phazeai-e2e-check";
+
+/// Ciphertext as uploaded: which key and nonce, base64-encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedBlob {
+    pub key_id: String,
+    pub alg: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// A symmetric key for encrypting synced data.
+#[derive(Clone)]
+pub struct SyncKey {
+    id: String,
+    key: [u8; 32],
+}
+
+impl std::fmt::Debug for SyncKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncKey").field("id", &self.id).finish()
+    }
+}
+
+impl SyncKey {
+    /// Derive the key for `passphrase` and `salt` (Argon2id, default cost).
+    pub fn derive(passphrase: &str, salt: &[u8], id: impl Into<String>) -> Result<Self> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| anyhow!("deriving the sync key: {e}"))?;
+        Ok(Self { id: id.into(), key })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Encrypt `plaintext`, binding it to `context` (e.g. the conversation id)
+    /// so a blob can't be swapped in for another item.
+    pub fn encrypt(&self, plaintext: &[u8], context: &str) -> Result<EncryptedBlob> {
+        let cipher = XChaCha20Poly1305::new((&self.key).into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: context.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("encryption failed"))?;
+        Ok(EncryptedBlob {
+            key_id: self.id.clone(),
+            alg: ALGORITHM.to_string(),
+            nonce: B64.encode(nonce),
+            ciphertext: B64.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt a blob made by [`encrypt`](Self::encrypt) with the same `context`.
+    pub fn decrypt(&self, blob: &EncryptedBlob, context: &str) -> Result<Vec<u8>> {
+        if blob.key_id != self.id {
+            bail!(
+                "encrypted with key {} but the current key is {}",
+                blob.key_id,
+                self.id
+            );
+        }
+        if blob.alg != ALGORITHM {
+            bail!("unsupported encryption algorithm '{}'", blob.alg);
+        }
+        let nonce = B64.decode(&blob.nonce).context("bad nonce")?;
+        if nonce.len() != 24 {
+            bail!("bad nonce length");
+        }
+        let ciphertext = B64.decode(&blob.ciphertext).context("bad ciphertext")?;
+        XChaCha20Poly1305::new((&self.key).into())
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: context.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("decryption failed (wrong key or tampered data)"))
+    }
+
+    /// The key saved on this machine, if end-to-end encryption is set up.
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(key_path()).ok()?;
+        let stored: StoredKey = serde_json::from_str(&content).ok()?;
+        let key = B64.decode(stored.key).ok()?.try_into().ok()?;
+        Some(Self { id: stored.id, key })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = key_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let stored = StoredKey {
+            id: self.id.clone(),
+            key: B64.encode(self.key),
+        };
+        crate::auth::write_private(&path, &serde_json::to_string_pretty(&stored)?)?;
+        Ok(())
+    }
+
+    /// Forget the key on this machine.
+    pub fn clear() -> Result<()> {
+        match std::fs::remove_file(key_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    id: String,
+    key: String,
+}
+
+fn key_path() -> std::path::PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("phazeai")
+        .join("sync-key.json")
+}
+
+/// The public half of a sync key, kept on the server: enough to derive the
+/// key again from the passphrase and to tell whether the passphrase is right.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyParams {
+    pub key_id: String,
+    /// Argon2id salt, base64.
+    pub salt: String,
+    /// [`CHECK_PLAINTEXT`] encrypted with the key.
+    pub check: EncryptedBlob,
+}
+
+impl KeyParams {
+    /// A new key from `passphrase` with a fresh salt and id.
+    pub fn generate(passphrase: &str) -> Result<(Self, SyncKey)> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            bail!("the passphrase must be at least {MIN_PASSPHRASE_LEN} characters");
+        }
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let mut id = [0u8; 6];
+        OsRng.fill_bytes(&mut id);
+        let id: String = id.iter().map(|b| format!("{b:02x}")).collect();
+
+        let key = SyncKey::derive(passphrase, &salt, id.clone())?;
+        let check = key.encrypt(CHECK_PLAINTEXT, &id)?;
+        Ok((
+            Self {
+                key_id: id,
+                salt: B64.encode(salt),
+                check,
+            },
+            key,
+        ))
+    }
+
+    /// Derive the key from `passphrase`, failing if it's the wrong passphrase.
+    pub fn unlock(&self, passphrase: &str) -> Result<SyncKey> {
+        let salt = B64.decode(&self.salt).context("bad key salt")?;
+        let key = SyncKey::derive(passphrase, &salt, self.key_id.clone())?;
+        match key.decrypt(&self.check, &self.key_id) {
+            Ok(check) if check == CHECK_PLAINTEXT => Ok(key),
+            _ => bail!("wrong passphrase for sync key {}", self.key_id),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Subscription entitlements and feature gating.
+//!
+//! The account's tier and feature flags come from the cloud API and are cached
+//! in `~/.config/phazeai/entitlements.json`. Paid features keep working from
+//! the cache for [`OFFLINE_GRACE`] after the last successful check, so a
+//! flaky connection doesn't lock anyone out; after that they switch off until
+//! the next refresh. Feature modules ask [`Feature::is_enabled`] (or
+//! [`Feature::require`] for a user-facing error) before doing anything.
+
+use std::collections::BTreeSet;
+use std::sync::{LazyLock, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::CloudClient;
+use crate::subscription::Tier;
+
+/// How long cached entitlements stay valid without reaching the cloud API.
+pub const OFFLINE_GRACE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Where to send users who hit a gated feature.
+pub const UPGRADE_URL: &str = "https://app.phazeai.com/billing";
+
+/// A paid capability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// PhazeAI-hosted models without your own API key.
+    HostedModels,
+    /// Settings and conversation sync across machines.
+    CloudSync,
+    /// Shared team memories, prompt profiles and pinned documents.
+    TeamContext,
+    /// Live pair programming sessions.
+    PairProgramming,
+    /// Organisation audit log.
+    AuditLog,
+    /// Organization seats, invitations and policies.
+    Organization,
+    /// Single sign-on.
+    Sso,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 7] = [
+        Feature::HostedModels,
+        Feature::CloudSync,
+        Feature::TeamContext,
+        Feature::PairProgramming,
+        Feature::AuditLog,
+        Feature::Organization,
+        Feature::Sso,
+    ];
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Feature::HostedModels => "Hosted models",
+            Feature::CloudSync => "Cloud sync",
+            Feature::TeamContext => "Shared team context",
+            Feature::PairProgramming => "Pair programming",
+            Feature::AuditLog => "Audit log",
+            Feature::Organization => "Organization management",
+            Feature::Sso => "Single sign-on",
+        }
+    }
+
+    /// The cheapest tier that includes this feature.
+    pub fn min_tier(self) -> Tier {
+        match self {
+            Feature::HostedModels | Feature::CloudSync => Tier::Cloud,
+            Feature::TeamContext
+            | Feature::PairProgramming
+            | Feature::AuditLog
+            | Feature::Organization => Tier::Team,
+            Feature::Sso => Tier::Enterprise,
+        }
+    }
+
+    /// Whether the current (cached) entitlements include this feature.
+    pub fn is_enabled(self) -> bool {
+        current().has(self)
+    }
+
+    /// `Ok` when enabled, otherwise an error that explains how to upgrade.
+    pub fn require(self) -> Result<(), UpgradeRequired> {
+        if self.is_enabled() {
+            Ok(())
+        } else {
+            Err(UpgradeRequired {
+                feature: self,
+                current: current().tier,
+            })
+        }
+    }
+}
+
+/// A gated feature was used on a plan that doesn't include it.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error(
+    "{} requires the {} plan (current plan: {}). Upgrade at {UPGRADE_URL}",
+    feature.display_name(),
+    feature.min_tier().display_name(),
+    current.display_name()
+)]
+pub struct UpgradeRequired {
+    pub feature: Feature,
+    pub current: Tier,
+}
+
+/// What the signed-in account may use.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entitlements {
+    pub tier: Tier,
+    /// Feature flags granted by the server on top of (or instead of) the
+    /// tier defaults, e.g. trials and beta access.
+    #[serde(default)]
+    pub features: BTreeSet<Feature>,
+    /// Flags the server turned off for this account despite the tier.
+    #[serde(default)]
+    pub disabled: BTreeSet<Feature>,
+    /// Unix seconds of the last successful check against the cloud API.
+    #[serde(default)]
+    pub checked_at: u64,
+}
+
+impl Entitlements {
+    pub fn has(&self, feature: Feature) -> bool {
+        if self.disabled.contains(&feature) {
+            return false;
+        }
+        self.features.contains(&feature) || self.tier >= feature.min_tier()
+    }
+
+    /// Everything this account can use.
+    pub fn enabled(&self) -> Vec<Feature> {
+        Feature::ALL.into_iter().filter(|f| self.has(*f)).collect()
+    }
+
+    /// These entitlements as of `now` (Unix seconds): unchanged within the
+    /// offline grace period, self-hosted once it has run out.
+    pub fn effective_at(&self, now: u64) -> Self {
+        if now.saturating_sub(self.checked_at) <= OFFLINE_GRACE.as_secs() {
+            self.clone()
+        } else {
+            Self::default()
+        }
+    }
+
+    fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(cache_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let path = cache_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// In-memory copy of the cache; `None` until first read.
+static CURRENT: LazyLock<RwLock<Option<Entitlements>>> = LazyLock::new(Default::default);
+
+fn cache_path() -> std::path::PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("phazeai")
+        .join("entitlements.json")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// The entitlements in effect now: the last known ones while within the
+/// offline grace period, otherwise self-hosted.
+pub fn current() -> Entitlements {
+    let cached = CURRENT.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let known = match cached {
+        Some(e) => e,
+        None => {
+            let loaded = Entitlements::load().unwrap_or_default();
+            *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(loaded.clone());
+            loaded
+        }
+    };
+    known.effective_at(now_secs())
+}
+
+/// Store freshly fetched entitlements for [`current`] and the on-disk cache.
+pub fn set(mut entitlements: Entitlements) -> anyhow::Result<()> {
+    entitlements.checked_at = now_secs();
+    entitlements.save()?;
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(entitlements);
+    Ok(())
+}
+
+/// Forget cached entitlements (e.g. on sign-out).
+pub fn clear() -> anyhow::Result<()> {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(Entitlements::default());
+    match std::fs::remove_file(cache_path()) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Fetch the account's entitlements and cache them. On failure the cached
+/// entitlements stay in effect for the rest of their grace period.
+pub async fn refresh(client: &CloudClient) -> anyhow::Result<Entitlements> {
+    let entitlements = client.entitlements().await?;
+    set(entitlements.clone())?;
+    Ok(current())
+}
@@ -0,0 +1,236 @@
+//! Hosted models through PhazeAI Cloud (Cloud tier).
+//!
+//! [`CloudLlmClient`] sends OpenAI-style chat completions to the cloud API
+//! with the signed-in session token, so no provider API key is needed. Every
+//! response carries the account's request quota in headers; the latest values
+//! and the tokens used are kept in a process-wide [`UsageTracker`] for the
+//! status displays ([`usage`]). [`register_provider`] makes the hosted models
+//! selectable as [`ProviderId::PhazeCloud`] wherever a provider registry is built.
+
+use std::sync::{Arc, LazyLock, Mutex};
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use phazeai_core::{
+    constants::models,
+    error::PhazeError,
+    llm::{
+        provider::{self, ClientFactory},
+        OpenAIClient, ProviderConfig, ProviderId, Quota, UsageTracker,
+    },
+    tools::ToolDefinition,
+    LlmClient, LlmResponse, Message, StreamEvent,
+};
+use reqwest::header::HeaderMap;
+
+use crate::{
+    auth::{self, CloudCredentials},
+    cloud_api_url,
+    entitlements::{Feature, UPGRADE_URL},
+};
+
+/// Requests allowed in the current billing period.
+pub const QUOTA_LIMIT_HEADER: &str = "x-phazeai-quota-limit";
+/// Requests left in the current billing period.
+pub const QUOTA_REMAINING_HEADER: &str = "x-phazeai-quota-remaining";
+/// When the quota resets (RFC 3339).
+pub const QUOTA_RESET_HEADER: &str = "x-phazeai-quota-reset";
+
+/// Hosted-model usage for this process.
+static USAGE: LazyLock<Mutex<UsageTracker>> = LazyLock::new(Default::default);
+
+/// Tokens used and the latest quota reported by PhazeAI Cloud in this process.
+pub fn usage() -> UsageTracker {
+    USAGE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn record(f: impl FnOnce(&mut UsageTracker)) {
+    f(&mut USAGE.lock().unwrap_or_else(|e| e.into_inner()));
+}
+
+/// The quota sent with a cloud API response, if the headers are present.
+pub fn parse_quota(headers: &HeaderMap) -> Option<Quota> {
+    let number =
+        |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.trim().parse().ok() };
+    Some(Quota {
+        limit: number(QUOTA_LIMIT_HEADER)?,
+        remaining: number(QUOTA_REMAINING_HEADER)?,
+        resets_at: headers
+            .get(QUOTA_RESET_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+    })
+}
+
+/// The cloud API root that `OpenAIClient` appends `/v1/chat/completions` to.
+fn proxy_base_url() -> String {
+    cloud_api_url()
+        .trim_end_matches('/')
+        .trim_end_matches("/v1")
+        .to_string()
+}
+
+/// Chat completions against PhazeAI-hosted models.
+#[derive(Clone)]
+pub struct CloudLlmClient {
+    token: String,
+    model: String,
+    base_url: String,
+    /// Use the stored sign-in session, renewing its token before requests.
+    session: bool,
+}
+
+impl CloudLlmClient {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            model: models::DEFAULT_PHAZECLOUD_MODEL.to_string(),
+            base_url: proxy_base_url(),
+            session: false,
+        }
+    }
+
+    /// A client for the signed-in account, or an error when signed out.
+    pub fn from_credentials(creds: &CloudCredentials) -> anyhow::Result<Self> {
+        match &creds.api_token {
+            Some(token) if creds.is_authenticated() => Ok(Self {
+                session: true,
+                ..Self::new(token)
+            }),
+            _ => anyhow::bail!("not signed in to PhazeAI Cloud; run `phazeai login`"),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn inner(&self) -> OpenAIClient {
+        let mut token = self.token.clone();
+        if self.session {
+            token = auth::load_fresh().await.api_token.unwrap_or(token);
+        }
+        OpenAIClient::new(token)
+            .with_base_url(&self.base_url)
+            .with_model(&self.model)
+            .on_response_headers(Arc::new(|headers| {
+                if let Some(quota) = parse_quota(headers) {
+                    record(|u| u.track_quota(quota));
+                }
+            }))
+    }
+
+    /// Explain a failed request when the quota has run out; the proxy's own
+    /// error body is less helpful than the numbers it already sent.
+    fn explain(err: PhazeError) -> PhazeError {
+        match usage().quota {
+            Some(quota) if quota.is_exhausted() => PhazeError::Llm(format!(
+                "PhazeAI Cloud quota used up ({} requests this period{}). Upgrade at {UPGRADE_URL}",
+                quota.limit,
+                quota
+                    .resets_at
+                    .map(|at| format!(", resets {at}"))
+                    .unwrap_or_default()
+            )),
+            _ => err,
+        }
+    }
+}
+
+fn require_hosted_models() -> Result<(), PhazeError> {
+    Feature::HostedModels
+        .require()
+        .map_err(|e| PhazeError::Llm(e.to_string()))
+}
+
+#[async_trait::async_trait]
+impl LlmClient for CloudLlmClient {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        require_hosted_models()?;
+        let response = self
+            .inner()
+            .await
+            .chat(messages, tools)
+            .await
+            .map_err(Self::explain)?;
+        let (input, output) = response
+            .usage
+            .as_ref()
+            .map(|u| (u.input_tokens, u.output_tokens))
+            .unwrap_or_default();
+        record(|u| u.track(input, output));
+        Ok(response)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        require_hosted_models()?;
+        let mut events = self
+            .inner()
+            .await
+            .chat_stream(messages, tools)
+            .await
+            .map_err(Self::explain)?;
+
+        // Pass events through, counting the request and its reported tokens.
+        let (tx, rx) = mpsc::unbounded();
+        tokio::spawn(async move {
+            let mut tokens = (0, 0);
+            while let Some(event) = events.next().await {
+                if let StreamEvent::Usage(u) = &event {
+                    tokens = (u.input_tokens, u.output_tokens);
+                }
+                let done = matches!(event, StreamEvent::Done);
+                if tx.unbounded_send(event).is_err() || done {
+                    break;
+                }
+            }
+            record(|u| u.track(tokens.0, tokens.1));
+        });
+        Ok(rx)
+    }
+}
+
+/// Add [`ProviderId::PhazeCloud`] to provider registries when signed in, or
+/// remove it when not. Call at startup and after signing in or out. Returns
+/// whether the hosted models are now available.
+pub fn register_provider() -> bool {
+    let Ok(client) = CloudLlmClient::from_credentials(&CloudCredentials::load()) else {
+        provider::unregister_provider(&ProviderId::PhazeCloud);
+        return false;
+    };
+    let config = ProviderConfig {
+        id: ProviderId::PhazeCloud,
+        enabled: true,
+        api_key_env: String::new(),
+        base_url: client.base_url.clone(),
+        default_model: client.model.clone(),
+    };
+    // Read the token per client so renewed access tokens are picked up.
+    let factory: ClientFactory = Arc::new(|config, model| {
+        let client = CloudLlmClient::from_credentials(&CloudCredentials::load())
+            .map_err(|e| PhazeError::Config(e.to_string()))?
+            .with_base_url(&config.base_url)
+            .with_model(model);
+        Ok(Box::new(client))
+    });
+    provider::register_provider(config, factory);
+    true
+}
@@ -11,14 +11,64 @@
 
 pub mod auth;
 pub mod client;
+pub mod e2e;
+pub mod entitlements;
+pub mod hosted;
+pub mod login;
+pub mod org;
 pub mod subscription;
+pub mod sync;
+pub mod team;
 
 pub use auth::{login_url, CloudCredentials, CloudSession};
 pub use client::CloudClient;
+pub use e2e::SyncKey;
+pub use entitlements::{Entitlements, Feature, UpgradeRequired};
+pub use hosted::CloudLlmClient;
+pub use login::LoginFlow;
+pub use org::{OrgPolicy, Organization, Seat, SeatStatus};
 pub use subscription::Tier;
+pub use sync::{Privacy, SyncReport};
+pub use team::{TeamCache, TeamContext, TeamPromptContext, TeamRole};
 
 /// Cloud API base URL. Points to our hosted backend.
 /// Override with PHAZEAI_CLOUD_URL env var for self-hosted enterprise deployments.
 pub fn cloud_api_url() -> String {
     std::env::var("PHAZEAI_CLOUD_URL").unwrap_or_else(|_| "https://api.phazeai.com/v1".to_string())
 }
+
+/// Set up cloud features for this process: make the hosted models available
+/// when signed in and apply the cached organization policy. Call at startup.
+pub fn init() {
+    hosted::register_provider();
+    match org::apply_cached() {
+        Ok(0) => {}
+        Ok(n) => tracing::info!("Deleted {n} conversations past the org retention period"),
+        Err(e) => tracing::warn!("Failed to apply the organization policy: {e}"),
+    }
+}
+
+/// A client for the signed-in account, renewing the access token first if it
+/// is about to expire.
+pub async fn session_client(model: &str) -> anyhow::Result<CloudClient> {
+    let creds = auth::load_fresh().await;
+    if !creds.is_authenticated() {
+        anyhow::bail!("not signed in to PhazeAI Cloud; run `phazeai login`");
+    }
+    CloudClient::new(&creds, model)
+}
+
+/// Refresh what the signed-in account may use, then the team context,
+/// organization policy and synced conversations if the plan includes them. A failed entitlement check keeps the cached ones (see
+/// [`entitlements::OFFLINE_GRACE`]).
+pub async fn sync_account(client: &CloudClient) -> anyhow::Result<()> {
+    let entitlements = entitlements::refresh(client).await;
+    if Feature::TeamContext.is_enabled() {
+        team::refresh_if_stale(client).await?;
+    }
+    if Feature::Organization.is_enabled() {
+        org::sync_policy(client).await?;
+    }
+    sync::sync_if_stale(client).await?;
+    entitlements.map(|_| ())
+}
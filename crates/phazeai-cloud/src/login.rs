@@ -0,0 +1,207 @@
+//! Browser sign-in for PhazeAI Cloud.
+//!
+//! [`LoginFlow::start`] listens on a random localhost port and builds the
+//! sign-in URL for the browser. After signing in, the browser is redirected to
+//! `http://127.0.0.1:<port>/callback?code=...&state=...`; [`LoginFlow::finish`]
+//! accepts that request, checks `state`, and exchanges the one-time code at
+//! `/auth/token` for an access token and a refresh token. The credentials are
+//! saved, and the account's entitlements and hosted models are loaded.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::auth::{self, login_url, CloudCredentials, TokenResponse};
+use crate::{cloud_api_url, entitlements, hosted, CloudClient};
+
+/// How long to wait for the browser to come back.
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Largest callback request read, in bytes.
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+const DONE_PAGE: &str = "<!doctype html><html><body style=\"font-family:sans-serif\">\
+<h2>Signed in to PhazeAI</h2><p>You can close this tab and return to PhazeAI.</p></body></html>";
+
+/// A sign-in in progress: the localhost callback server and its `state`.
+pub struct LoginFlow {
+    listener: TcpListener,
+    state: String,
+    redirect_uri: String,
+}
+
+impl LoginFlow {
+    /// Start listening for the browser callback.
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("starting the sign-in callback server")?;
+        let port = listener.local_addr()?.port();
+        Ok(Self {
+            listener,
+            state: uuid::Uuid::new_v4().simple().to_string(),
+            redirect_uri: format!("http://127.0.0.1:{port}/callback"),
+        })
+    }
+
+    /// The page to open in the browser.
+    pub fn url(&self) -> String {
+        format!(
+            "{}?client=phazeai-ide&redirect_uri={}&state={}",
+            login_url(),
+            encode(&self.redirect_uri),
+            self.state
+        )
+    }
+
+    /// Wait up to `timeout` for the browser callback, then exchange its code
+    /// for credentials and save them.
+    pub async fn finish(self, timeout: Duration) -> Result<CloudCredentials> {
+        let code = tokio::time::timeout(timeout, self.accept_code())
+            .await
+            .map_err(|_| anyhow!("timed out waiting for the browser sign-in"))??;
+
+        let body = serde_json::json!({
+            "grant_type": "authorization_code",
+            "code": code,
+            "redirect_uri": self.redirect_uri,
+        });
+        let token: TokenResponse = reqwest::Client::new()
+            .post(format!("{}/auth/token", cloud_api_url()))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()
+            .context("exchanging the sign-in code")?
+            .json()
+            .await?;
+        let creds = token.into_credentials(&CloudCredentials::default(), auth::now_secs());
+        let saved = creds.clone();
+        tokio::task::spawn_blocking(move || saved.save()).await??;
+        hosted::register_provider();
+        if let Ok(client) = CloudClient::new(&creds, "") {
+            if let Err(e) = entitlements::refresh(&client).await {
+                tracing::debug!("Entitlement check after sign-in failed: {e}");
+            }
+        }
+        Ok(creds)
+    }
+
+    /// Serve requests until the callback arrives; other paths (favicon...) get a 404.
+    async fn accept_code(&self) -> Result<String> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+            let Some(target) = read_request_target(&mut stream).await? else {
+                continue;
+            };
+            match parse_callback(&target, &self.state) {
+                None => respond(&mut stream, "404 Not Found", "Not found").await,
+                Some(Ok(code)) => {
+                    respond(&mut stream, "200 OK", DONE_PAGE).await;
+                    return Ok(code);
+                }
+                Some(Err(e)) => {
+                    respond(
+                        &mut stream,
+                        "400 Bad Request",
+                        &format!("Sign-in failed: {e}"),
+                    )
+                    .await;
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// The request target of an HTTP GET (`/callback?...`), or `None` for
+/// anything else.
+async fn read_request_target(stream: &mut TcpStream) -> Result<Option<String>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 2048];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") && buf.len() < MAX_REQUEST_BYTES {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let request = String::from_utf8_lossy(&buf);
+    let mut parts = request.lines().next().unwrap_or_default().split(' ');
+    Ok(match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    })
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Check a callback request target: `None` if it isn't `/callback`,
+/// otherwise the authorization code or why the sign-in failed.
+pub fn parse_callback(target: &str, expected_state: &str) -> Option<Result<String>> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/callback" {
+        return None;
+    }
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| decode(v))
+    };
+    Some((|| {
+        if let Some(error) = param("error") {
+            bail!("{error}");
+        }
+        if param("state").as_deref() != Some(expected_state) {
+            bail!("state mismatch; start the sign-in again");
+        }
+        param("code").ok_or_else(|| anyhow!("no authorization code in the callback"))
+    })())
+}
+
+/// Percent-encode a query parameter value.
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Decode a percent-encoded query parameter value (`+` is a space).
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
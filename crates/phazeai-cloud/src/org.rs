@@ -0,0 +1,222 @@
+//! Organization administration (Team tier).
+//!
+//! Admins invite members by email, see each seat and its usage, revoke access
+//! and set org-wide policies. Every member's machine fetches the
+//! [`OrgPolicy`], caches it in `~/.config/phazeai/org-policy.json` and applies
+//! it locally: providers outside the allow list can't be used, and saved
+//! conversations older than the retention period are deleted. As with the team
+//! context, the server enforces roles; the checks here only fail early.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use phazeai_core::{
+    config::LlmProvider,
+    context::ConversationStore,
+    llm::{provider, ProviderId},
+};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::client::CloudClient;
+use crate::entitlements::Feature;
+use crate::team::{segment, send, send_json, TeamRole};
+
+/// Whether a seat is in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SeatStatus {
+    #[default]
+    Active,
+    /// Invited by email; the invitation hasn't been accepted yet.
+    Invited,
+    /// Access revoked; the seat is free for someone else.
+    Revoked,
+}
+
+impl SeatStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Invited => "invited",
+            Self::Revoked => "revoked",
+        }
+    }
+}
+
+/// One member's seat and what they used this billing period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Seat {
+    pub email: String,
+    #[serde(default)]
+    pub role: TeamRole,
+    #[serde(default)]
+    pub status: SeatStatus,
+    #[serde(default)]
+    pub requests_used: u64,
+    #[serde(default)]
+    pub tokens_used: u64,
+    /// RFC 3339; `None` if the member hasn't used PhazeAI yet.
+    #[serde(default)]
+    pub last_active: Option<String>,
+}
+
+/// Rules the organization's admins set for every member.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrgPolicy {
+    /// Provider names (`claude`, `openai`, `ollama`, `phazecloud`...) members
+    /// may use. Empty allows every provider.
+    #[serde(default)]
+    pub allowed_providers: Vec<String>,
+    /// Delete saved conversations after this many days; `None` keeps them.
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+}
+
+impl OrgPolicy {
+    /// The allowed providers, or `None` when every provider is allowed.
+    pub fn provider_ids(&self) -> Option<Vec<ProviderId>> {
+        if self.allowed_providers.is_empty() {
+            return None;
+        }
+        Some(
+            self.allowed_providers
+                .iter()
+                .map(|name| provider_id(name))
+                .collect(),
+        )
+    }
+
+    pub fn allows(&self, id: &ProviderId) -> bool {
+        self.provider_ids()
+            .is_none_or(|allowed| allowed.contains(id))
+    }
+
+    pub fn retention(&self) -> Option<Duration> {
+        self.retention_days
+            .map(|days| Duration::from_secs(u64::from(days) * 24 * 60 * 60))
+    }
+
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(cache_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = cache_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Forget the cached policy and lift its restrictions (e.g. on sign-out).
+    pub fn clear() -> Result<()> {
+        provider::set_allowed_providers(None);
+        match std::fs::remove_file(cache_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enforce the policy in this process: restrict providers and delete
+    /// conversations past the retention period. Returns how many
+    /// conversations were deleted.
+    pub fn apply(&self) -> Result<usize> {
+        provider::set_allowed_providers(self.provider_ids());
+        let Some(retention) = self.retention() else {
+            return Ok(0);
+        };
+        Ok(ConversationStore::new()?.prune_older_than(retention)?)
+    }
+}
+
+/// The [`ProviderId`] for a provider name in a policy; unknown names are
+/// custom providers from the settings file.
+pub fn provider_id(name: &str) -> ProviderId {
+    LlmProvider::from_name(name.trim())
+        .map(|p| p.to_provider_id())
+        .unwrap_or_else(|| ProviderId::Custom(name.trim().to_string()))
+}
+
+/// The organization as seen by the signed-in member.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    /// The signed-in member's role.
+    #[serde(default)]
+    pub role: TeamRole,
+    /// Seats paid for.
+    #[serde(default)]
+    pub seats_total: u32,
+    /// Seats taken by active and invited members.
+    #[serde(default)]
+    pub seats_used: u32,
+    #[serde(default)]
+    pub policy: OrgPolicy,
+}
+
+impl Organization {
+    pub fn seats_free(&self) -> u32 {
+        self.seats_total.saturating_sub(self.seats_used)
+    }
+}
+
+fn cache_path() -> std::path::PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("phazeai")
+        .join("org-policy.json")
+}
+
+/// Fetch the organization's policy, cache it and apply it. Offline, the
+/// cached policy stays in force.
+pub async fn sync_policy(client: &CloudClient) -> Result<OrgPolicy> {
+    Feature::Organization.require()?;
+    let policy = client.org_policy().await?;
+    policy.save()?;
+    policy.apply()?;
+    Ok(policy)
+}
+
+/// Apply the cached policy, if any. Call at startup.
+pub fn apply_cached() -> Result<usize> {
+    match OrgPolicy::load() {
+        Some(policy) => policy.apply(),
+        None => Ok(0),
+    }
+}
+
+impl CloudClient {
+    pub async fn organization(&self) -> Result<Organization> {
+        send_json(self.request(Method::GET, "/org")).await
+    }
+
+    pub async fn org_policy(&self) -> Result<OrgPolicy> {
+        send_json(self.request(Method::GET, "/org/policy")).await
+    }
+
+    /// Every seat with its usage this billing period (admin only).
+    pub async fn seats(&self) -> Result<Vec<Seat>> {
+        send_json(self.request(Method::GET, "/org/seats")).await
+    }
+
+    /// Invite `email` to the organization; takes a seat (admin only).
+    pub async fn invite_member(&self, email: &str, role: TeamRole) -> Result<Seat> {
+        let body = serde_json::json!({ "email": email, "role": role });
+        send_json(self.request(Method::POST, "/org/invites").json(&body)).await
+    }
+
+    /// Revoke a member's access or cancel an invitation (admin only).
+    pub async fn revoke_seat(&self, email: &str) -> Result<()> {
+        let path = format!("/org/seats/{}", segment(email));
+        send(self.request(Method::DELETE, &path)).await.map(|_| ())
+    }
+
+    /// Replace the organization's policy (admin only).
+    pub async fn set_org_policy(&self, policy: &OrgPolicy) -> Result<OrgPolicy> {
+        send_json(self.request(Method::PUT, "/org/policy").json(policy)).await
+    }
+}
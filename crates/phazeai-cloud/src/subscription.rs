@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-/// PhazeAI subscription tier.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+/// PhazeAI subscription tier, ordered from least to most included.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
+)]
+#[serde(rename_all = "snake_case")]
 pub enum Tier {
     /// Self-hosted / bring-your-own-key. Free, no cloud account needed.
     #[default]
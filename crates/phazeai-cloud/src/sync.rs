@@ -0,0 +1,402 @@
+//! Conversation sync (Cloud tier).
+//!
+//! Saved conversations are uploaded to PhazeAI Cloud and downloaded on the
+//! account's other machines; the copy updated last wins. By default they are
+//! stored server-readable. With end-to-end encryption turned on
+//! ([`enable_encryption`]) every conversation is uploaded as an
+//! [`EncryptedBlob`] that only the account's passphrase can open, and
+//! [`Privacy::current`] says which mode this machine is in.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use phazeai_core::context::{ConversationStore, SavedConversation};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::client::CloudClient;
+use crate::e2e::{EncryptedBlob, KeyParams, SyncKey};
+use crate::entitlements::Feature;
+use crate::team::segment;
+
+/// Background syncs ([`sync_if_stale`]) run at most this often.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+static LAST_SYNC: LazyLock<Mutex<Option<Instant>>> = LazyLock::new(Default::default);
+
+/// Who can read synced conversations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Privacy {
+    /// Stored as plain JSON; PhazeAI Cloud can read them.
+    ServerReadable,
+    /// Encrypted on this machine with the key `key_id`.
+    EndToEnd { key_id: String },
+}
+
+impl Privacy {
+    /// This machine's mode: end-to-end when a sync key is saved.
+    pub fn current() -> Self {
+        match SyncKey::load() {
+            Some(key) => Self::EndToEnd {
+                key_id: key.id().to_string(),
+            },
+            None => Self::ServerReadable,
+        }
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, Self::EndToEnd { .. })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::ServerReadable => "server-readable".to_string(),
+            Self::EndToEnd { key_id } => format!("end-to-end encrypted (key {key_id})"),
+        }
+    }
+}
+
+/// A synced conversation as listed by the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteEntry {
+    pub id: String,
+    pub updated_at: String,
+    /// The key it is encrypted with; `None` for server-readable copies.
+    #[serde(default)]
+    pub key_id: Option<String>,
+}
+
+/// A conversation as uploaded: plaintext or encrypted, never both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedConversation {
+    pub id: String,
+    pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation: Option<SavedConversation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted: Option<EncryptedBlob>,
+}
+
+impl SyncedConversation {
+    /// Prepare a conversation for upload, encrypted when `key` is given.
+    pub fn seal(conversation: &SavedConversation, key: Option<&SyncKey>) -> Result<Self> {
+        let id = conversation.metadata.id.clone();
+        let updated_at = conversation.metadata.updated_at.clone();
+        Ok(match key {
+            Some(key) => Self {
+                encrypted: Some(key.encrypt(&serde_json::to_vec(conversation)?, &id)?),
+                conversation: None,
+                id,
+                updated_at,
+            },
+            None => Self {
+                conversation: Some(conversation.clone()),
+                encrypted: None,
+                id,
+                updated_at,
+            },
+        })
+    }
+
+    /// The conversation, decrypting it with `key` if it was encrypted.
+    pub fn open(self, key: Option<&SyncKey>) -> Result<SavedConversation> {
+        match (self.conversation, self.encrypted, key) {
+            (Some(conversation), _, _) => Ok(conversation),
+            (None, Some(blob), Some(key)) => {
+                Ok(serde_json::from_slice(&key.decrypt(&blob, &self.id)?)?)
+            }
+            (None, Some(blob), None) => bail!(
+                "conversation {} is end-to-end encrypted (key {}); unlock sync with your passphrase",
+                self.id,
+                blob.key_id
+            ),
+            (None, None, _) => bail!("conversation {} has no content", self.id),
+        }
+    }
+}
+
+/// What a sync did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    /// Remote copies that couldn't be decrypted with the current key.
+    pub locked: usize,
+}
+
+impl std::fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} uploaded, {} downloaded",
+            self.uploaded, self.downloaded
+        )?;
+        if self.locked > 0 {
+            write!(f, ", {} locked by an older key", self.locked)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether RFC 3339 timestamp `a` is later than `b`.
+fn is_newer(a: &str, b: &str) -> bool {
+    use chrono::DateTime;
+    match (
+        DateTime::parse_from_rfc3339(a),
+        DateTime::parse_from_rfc3339(b),
+    ) {
+        (Ok(a), Ok(b)) => a > b,
+        _ => a > b,
+    }
+}
+
+/// Sync conversations in both directions with this machine's settings.
+/// Refuses to upload anything when the account's encryption setting on the
+/// server doesn't match this machine's, rather than leak plaintext or use a
+/// stale key.
+pub async fn sync_conversations(
+    client: &CloudClient,
+    store: &ConversationStore,
+) -> Result<SyncReport> {
+    Feature::CloudSync.require()?;
+    let key = SyncKey::load();
+    match (client.sync_key_params().await?, &key) {
+        (Some(params), None) => bail!(
+            "synced conversations are end-to-end encrypted (key {}); \
+             unlock them with your passphrase (`phazeai sync encrypt`)",
+            params.key_id
+        ),
+        (Some(params), Some(key)) if params.key_id != key.id() => bail!(
+            "the sync key was rotated on another machine (now {}); \
+             unlock with the new passphrase (`phazeai sync encrypt`)",
+            params.key_id
+        ),
+        (None, Some(key)) => bail!(
+            "end-to-end encryption was turned off on another machine; \
+             run `phazeai sync encrypt` to turn it back on or `phazeai sync decrypt` \
+             to sync server-readable (local key {})",
+            key.id()
+        ),
+        _ => {}
+    }
+    let report = sync_with(client, store, key.as_ref(), false).await?;
+    *LAST_SYNC.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    Ok(report)
+}
+
+/// [`sync_conversations`] unless one ran in the last [`SYNC_INTERVAL`].
+pub async fn sync_if_stale(client: &CloudClient) -> Result<()> {
+    let recent = LAST_SYNC
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some_and(|at| at.elapsed() < SYNC_INTERVAL);
+    if recent || !Feature::CloudSync.is_enabled() {
+        return Ok(());
+    }
+    let report = sync_conversations(client, &ConversationStore::new()?).await?;
+    tracing::debug!("Conversation sync: {report}");
+    Ok(())
+}
+
+/// Upload local conversations that are new, newer or under a different key
+/// (all of them with `reupload`), and download remote ones that are newer.
+async fn sync_with(
+    client: &CloudClient,
+    store: &ConversationStore,
+    key: Option<&SyncKey>,
+    reupload: bool,
+) -> Result<SyncReport> {
+    let mut remote: HashMap<String, RemoteEntry> = client
+        .synced_conversations()
+        .await?
+        .into_iter()
+        .map(|e| (e.id.clone(), e))
+        .collect();
+    let key_id = key.map(|k| k.id().to_string());
+    let mut report = SyncReport::default();
+
+    for meta in store.list_recent(usize::MAX)? {
+        let upload = match remote.remove(&meta.id) {
+            None => true,
+            Some(entry) if is_newer(&entry.updated_at, &meta.updated_at) => {
+                download(client, store, key, &entry.id, &mut report).await?;
+                false
+            }
+            Some(entry) => {
+                reupload || is_newer(&meta.updated_at, &entry.updated_at) || entry.key_id != key_id
+            }
+        };
+        if upload {
+            let conversation = store.load(&meta.id)?;
+            client
+                .upload_conversation(&SyncedConversation::seal(&conversation, key)?)
+                .await?;
+            report.uploaded += 1;
+        }
+    }
+    for id in remote.into_keys() {
+        download(client, store, key, &id, &mut report).await?;
+    }
+    Ok(report)
+}
+
+async fn download(
+    client: &CloudClient,
+    store: &ConversationStore,
+    key: Option<&SyncKey>,
+    id: &str,
+    report: &mut SyncReport,
+) -> Result<()> {
+    match client.synced_conversation(id).await?.open(key) {
+        Ok(conversation) => {
+            store.save(&conversation)?;
+            report.downloaded += 1;
+        }
+        Err(e) => {
+            tracing::warn!("Skipping synced conversation {id}: {e}");
+            report.locked += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Turn on end-to-end encryption with `passphrase`. If the account already
+/// has a sync key (set up on another machine) the passphrase unlocks it;
+/// otherwise a new key is created and every conversation is re-uploaded
+/// encrypted.
+pub async fn enable_encryption(
+    client: &CloudClient,
+    store: &ConversationStore,
+    passphrase: &str,
+) -> Result<(SyncKey, SyncReport)> {
+    Feature::CloudSync.require()?;
+    let (key, reupload) = match client.sync_key_params().await? {
+        Some(params) => (params.unlock(passphrase)?, false),
+        None => {
+            // Bring down server-readable copies before they are replaced.
+            let before = sync_with(client, store, None, false).await?;
+            tracing::debug!("Synced before enabling encryption: {before}");
+            let (params, key) = KeyParams::generate(passphrase)?;
+            client.put_sync_key(&params).await?;
+            (key, true)
+        }
+    };
+    key.save()?;
+    let report = sync_with(client, store, Some(&key), reupload).await?;
+    Ok((key, report))
+}
+
+/// Replace the sync key with one from `new_passphrase` and re-encrypt every
+/// conversation with it. Needs the current key on this machine.
+pub async fn rotate_key(
+    client: &CloudClient,
+    store: &ConversationStore,
+    new_passphrase: &str,
+) -> Result<(SyncKey, SyncReport)> {
+    Feature::CloudSync.require()?;
+    let Some(old) = SyncKey::load() else {
+        bail!("end-to-end encryption isn't turned on; run `phazeai sync encrypt` first");
+    };
+    let (key, report) = reencrypt(client, store, &old, new_passphrase).await?;
+    key.save().context(
+        "the account now uses the new sync key; run `phazeai sync encrypt` with the new passphrase",
+    )?;
+    Ok((key, report))
+}
+
+/// Re-encrypt every conversation under a new key from `new_passphrase`,
+/// then make it the account's key. Until every conversation is uploaded
+/// under the new key, `old` stays the account's key; if that fails, what was
+/// re-encrypted so far is put back under `old`.
+pub async fn reencrypt(
+    client: &CloudClient,
+    store: &ConversationStore,
+    old: &SyncKey,
+    new_passphrase: &str,
+) -> Result<(SyncKey, SyncReport)> {
+    let (params, key) = KeyParams::generate(new_passphrase)?;
+    // Download everything the old key can open so it is re-encrypted too.
+    sync_with(client, store, Some(old), false).await?;
+    let switched = async {
+        let report = sync_with(client, store, Some(&key), true).await?;
+        client.put_sync_key(&params).await?;
+        Ok::<_, anyhow::Error>(report)
+    }
+    .await;
+    match switched {
+        Ok(report) => Ok((key, report)),
+        Err(e) => {
+            if let Err(undo) = sync_with(client, store, Some(old), false).await {
+                tracing::warn!("Re-uploading under sync key {} failed: {undo}", old.id());
+            }
+            Err(e.context(format!(
+                "re-encrypting conversations failed; sync key {} is still in use",
+                old.id()
+            )))
+        }
+    }
+}
+
+/// Turn off end-to-end encryption: download what the current key can open,
+/// then re-upload every conversation server-readable.
+pub async fn disable_encryption(
+    client: &CloudClient,
+    store: &ConversationStore,
+) -> Result<SyncReport> {
+    Feature::CloudSync.require()?;
+    if let Some(key) = SyncKey::load() {
+        sync_with(client, store, Some(&key), false).await?;
+    }
+    client.delete_sync_key().await?;
+    SyncKey::clear()?;
+    sync_with(client, store, None, true).await
+}
+
+async fn send(req: RequestBuilder) -> Result<reqwest::Response> {
+    Ok(req.send().await?.error_for_status()?)
+}
+
+async fn send_json<T: DeserializeOwned>(req: RequestBuilder) -> Result<T> {
+    Ok(send(req).await?.json::<T>().await?)
+}
+
+impl CloudClient {
+    pub async fn synced_conversations(&self) -> Result<Vec<RemoteEntry>> {
+        send_json(self.request(Method::GET, "/sync/conversations")).await
+    }
+
+    pub async fn synced_conversation(&self, id: &str) -> Result<SyncedConversation> {
+        let path = format!("/sync/conversations/{}", segment(id));
+        send_json(self.request(Method::GET, &path)).await
+    }
+
+    pub async fn upload_conversation(&self, conversation: &SyncedConversation) -> Result<()> {
+        let path = format!("/sync/conversations/{}", segment(&conversation.id));
+        send(self.request(Method::PUT, &path).json(conversation))
+            .await
+            .map(|_| ())
+    }
+
+    /// The account's sync key parameters, or `None` if sync isn't encrypted.
+    pub async fn sync_key_params(&self) -> Result<Option<KeyParams>> {
+        let resp = self.request(Method::GET, "/sync/key").send().await?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(resp.error_for_status()?.json().await?))
+    }
+
+    pub async fn put_sync_key(&self, params: &KeyParams) -> Result<()> {
+        send(self.request(Method::PUT, "/sync/key").json(params))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn delete_sync_key(&self) -> Result<()> {
+        send(self.request(Method::DELETE, "/sync/key"))
+            .await
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,379 @@
+//! Shared team agent context (Team tier).
+//!
+//! A team keeps a knowledge base in PhazeAI Cloud: shared memories, prompt
+//! profiles and pinned context documents. Every member's agent consults it
+//! through [`TeamPromptContext`]. Members with write access add memories and
+//! pin documents; admins also manage profiles and member roles. The server
+//! enforces roles; the checks here only give an early, readable error.
+//!
+//! The last fetched snapshot is cached on disk so agents keep the team context
+//! when offline and don't wait on the network at the start of every turn.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Result};
+use phazeai_core::context::memory::{self, Memory, PromptContext};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::client::CloudClient;
+use crate::entitlements::Feature;
+
+/// Snapshots older than this are refreshed by [`refresh_if_stale`].
+pub const CACHE_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+/// Pinned document text added to the prompt per turn, in characters.
+const MAX_DOCUMENT_CHARS: usize = 8_000;
+/// Profile used when none has been chosen with `phazeai team profile use`.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// A member's permissions within the team.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TeamRole {
+    /// Agents consult the team context; no edits.
+    #[default]
+    Viewer,
+    /// Adds memories and pins documents, removes their own.
+    Member,
+    /// Manages everything, including prompt profiles and roles.
+    Admin,
+}
+
+impl TeamRole {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "viewer" | "read" => Some(Self::Viewer),
+            "member" | "write" => Some(Self::Member),
+            "admin" | "owner" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Member => "member",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn can_write(self) -> bool {
+        self >= Self::Member
+    }
+
+    pub fn is_admin(self) -> bool {
+        self == Self::Admin
+    }
+
+    /// Whether this role may delete an item added by `author` when signed in as `me`.
+    pub fn can_remove(self, author: &str, me: &str) -> bool {
+        self.is_admin() || (self.can_write() && author.eq_ignore_ascii_case(me))
+    }
+
+    /// Fail unless this role may add to the knowledge base.
+    pub fn require_write(self) -> Result<()> {
+        if !self.can_write() {
+            bail!("your team role ({}) is read-only", self.name());
+        }
+        Ok(())
+    }
+
+    /// Fail unless this role is admin.
+    pub fn require_admin(self) -> Result<()> {
+        if !self.is_admin() {
+            bail!("only team admins can do this (your role: {})", self.name());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamMemory {
+    pub id: String,
+    pub text: String,
+    /// Email of the member who saved it.
+    pub author: String,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// Named system prompt instructions shared by the team (e.g. "default", "review").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptProfile {
+    pub name: String,
+    pub instructions: String,
+    #[serde(default)]
+    pub updated_by: String,
+}
+
+/// A document every member's agent reads (architecture notes, style guide...).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedDocument {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+    pub pinned_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamMember {
+    pub email: String,
+    pub role: TeamRole,
+}
+
+/// The team knowledge base as seen by the signed-in member.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TeamContext {
+    pub team_id: String,
+    pub team_name: String,
+    /// The signed-in member's role.
+    pub role: TeamRole,
+    /// The signed-in member's email.
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub memories: Vec<TeamMemory>,
+    #[serde(default)]
+    pub profiles: Vec<PromptProfile>,
+    #[serde(default)]
+    pub documents: Vec<PinnedDocument>,
+}
+
+impl TeamContext {
+    pub fn profile(&self, name: &str) -> Option<&PromptProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The system prompt section for a request: the chosen prompt profile,
+    /// the shared memories most relevant to `query`, and pinned documents up
+    /// to a size budget.
+    pub fn prompt_section(&self, query: &str, profile: Option<&str>) -> Option<String> {
+        let profile = self.profile(profile.unwrap_or(DEFAULT_PROFILE));
+        let memories: Vec<Memory> = self
+            .memories
+            .iter()
+            .enumerate()
+            .map(|(i, m)| Memory {
+                id: i + 1,
+                text: m.text.clone(),
+            })
+            .collect();
+        let memories = memory::rank(memories, query, memory::MAX_PROMPT_MEMORIES);
+        if profile.is_none() && memories.is_empty() && self.documents.is_empty() {
+            return None;
+        }
+
+        let mut section = format!(
+            "## Team Context ({})\n\
+             Shared by your team on PhazeAI Cloud. Team conventions apply to everyone's work; \
+             project instructions and the user's requests take precedence where they conflict.\n",
+            self.team_name
+        );
+        if let Some(profile) = profile {
+            section.push_str(&format!(
+                "\n### Team Instructions ({})\n{}\n",
+                profile.name,
+                profile.instructions.trim()
+            ));
+        }
+        if !memories.is_empty() {
+            section.push_str("\n### Team Memory\n");
+            for m in &memories {
+                section.push_str(&format!("- {}\n", m.text));
+            }
+        }
+        let mut budget = MAX_DOCUMENT_CHARS;
+        for doc in &self.documents {
+            if budget == 0 {
+                section.push_str(&format!(
+                    "\n(Pinned document \"{}\" omitted: context budget reached.)\n",
+                    doc.title
+                ));
+                continue;
+            }
+            let content = doc.content.trim();
+            let cut = content.floor_char_boundary(budget.min(content.len()));
+            budget -= cut;
+            section.push_str(&format!("\n### Pinned: {}\n{}", doc.title, &content[..cut]));
+            if cut < content.len() {
+                section.push_str("\n[truncated]");
+            }
+            section.push('\n');
+        }
+        Some(section)
+    }
+}
+
+/// The last fetched [`TeamContext`] plus local choices, in `~/.config/phazeai/team.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TeamCache {
+    pub context: TeamContext,
+    /// Unix seconds of the last successful fetch.
+    pub fetched_at: u64,
+    /// Prompt profile this machine's agents use; `None` uses [`DEFAULT_PROFILE`].
+    pub active_profile: Option<String>,
+}
+
+impl TeamCache {
+    pub fn load() -> Option<Self> {
+        let content = std::fs::read_to_string(cache_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = cache_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Forget the cached team context (e.g. on sign-out).
+    pub fn clear() -> Result<()> {
+        match std::fs::remove_file(cache_path()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        now_secs().saturating_sub(self.fetched_at) < CACHE_MAX_AGE.as_secs()
+    }
+
+    pub fn prompt_section(&self, query: &str) -> Option<String> {
+        self.context
+            .prompt_section(query, self.active_profile.as_deref())
+    }
+}
+
+fn cache_path() -> std::path::PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("phazeai")
+        .join("team.json")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Fetch the team context and update the cache, keeping the local profile choice.
+pub async fn sync(client: &CloudClient) -> Result<TeamCache> {
+    Feature::TeamContext.require()?;
+    let context = client.team_context().await?;
+    let mut cache = TeamCache::load().unwrap_or_default();
+    if cache.context.team_id != context.team_id {
+        cache.active_profile = None;
+    }
+    cache.context = context;
+    cache.fetched_at = now_secs();
+    cache.save()?;
+    Ok(cache)
+}
+
+/// Re-fetch the team context when the cache is missing or older than
+/// [`CACHE_MAX_AGE`]. Errors leave the old snapshot in place.
+pub async fn refresh_if_stale(client: &CloudClient) -> Result<()> {
+    if !Feature::TeamContext.is_enabled() || TeamCache::load().is_some_and(|c| c.is_fresh()) {
+        return Ok(());
+    }
+    sync(client).await.map(|_| ())
+}
+
+/// Adds the cached team context to every agent turn. Reads the cache each
+/// turn, so a background refresh is picked up without rebuilding the agent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TeamPromptContext;
+
+impl PromptContext for TeamPromptContext {
+    fn section(&self, query: &str) -> Option<String> {
+        if !Feature::TeamContext.is_enabled() {
+            return None;
+        }
+        TeamCache::load()?.prompt_section(query)
+    }
+}
+
+/// Percent-encode one URL path segment.
+pub(crate) fn segment(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+pub(crate) async fn send(req: RequestBuilder) -> Result<reqwest::Response> {
+    let resp = req.send().await?;
+    match resp.status() {
+        StatusCode::FORBIDDEN => bail!("your team role doesn't allow this"),
+        StatusCode::NOT_FOUND => bail!("not found (is this account on a Team plan?)"),
+        _ => Ok(resp.error_for_status()?),
+    }
+}
+
+pub(crate) async fn send_json<T: DeserializeOwned>(req: RequestBuilder) -> Result<T> {
+    Ok(send(req).await?.json::<T>().await?)
+}
+
+impl CloudClient {
+    pub async fn team_context(&self) -> Result<TeamContext> {
+        send_json(self.request(Method::GET, "/team/context")).await
+    }
+
+    pub async fn add_team_memory(&self, text: &str) -> Result<TeamMemory> {
+        let body = serde_json::json!({ "text": text });
+        send_json(self.request(Method::POST, "/team/memories").json(&body)).await
+    }
+
+    pub async fn delete_team_memory(&self, id: &str) -> Result<()> {
+        let path = format!("/team/memories/{}", segment(id));
+        send(self.request(Method::DELETE, &path)).await.map(|_| ())
+    }
+
+    /// Create or replace the prompt profile `name` (admin only).
+    pub async fn put_prompt_profile(
+        &self,
+        name: &str,
+        instructions: &str,
+    ) -> Result<PromptProfile> {
+        let path = format!("/team/profiles/{}", segment(name));
+        let body = serde_json::json!({ "instructions": instructions });
+        send_json(self.request(Method::PUT, &path).json(&body)).await
+    }
+
+    pub async fn delete_prompt_profile(&self, name: &str) -> Result<()> {
+        let path = format!("/team/profiles/{}", segment(name));
+        send(self.request(Method::DELETE, &path)).await.map(|_| ())
+    }
+
+    pub async fn pin_document(&self, title: &str, content: &str) -> Result<PinnedDocument> {
+        let body = serde_json::json!({ "title": title, "content": content });
+        send_json(self.request(Method::POST, "/team/documents").json(&body)).await
+    }
+
+    pub async fn unpin_document(&self, id: &str) -> Result<()> {
+        let path = format!("/team/documents/{}", segment(id));
+        send(self.request(Method::DELETE, &path)).await.map(|_| ())
+    }
+
+    pub async fn team_members(&self) -> Result<Vec<TeamMember>> {
+        send_json(self.request(Method::GET, "/team/members")).await
+    }
+
+    /// Change a member's role (admin only).
+    pub async fn set_member_role(&self, email: &str, role: TeamRole) -> Result<TeamMember> {
+        let path = format!("/team/members/{}", segment(email));
+        let body = serde_json::json!({ "role": role });
+        send_json(self.request(Method::PUT, &path).json(&body)).await
+    }
+}
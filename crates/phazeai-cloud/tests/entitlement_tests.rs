@@ -0,0 +1,56 @@
+use phazeai_cloud::entitlements::OFFLINE_GRACE;
+use phazeai_cloud::{Entitlements, Feature, Tier, UpgradeRequired};
+
+#[test]
+fn tier_defaults_and_server_flags_decide_features() {
+    let cloud = Entitlements {
+        tier: Tier::Cloud,
+        ..Default::default()
+    };
+    assert!(cloud.has(Feature::HostedModels));
+    assert!(!cloud.has(Feature::TeamContext));
+
+    // A trial flag grants a Team feature; a disabled flag revokes a tier one.
+    let trial: Entitlements = serde_json::from_str(
+        r#"{"tier": "cloud", "features": ["team_context"], "disabled": ["cloud_sync"]}"#,
+    )
+    .unwrap();
+    assert!(trial.has(Feature::TeamContext));
+    assert!(!trial.has(Feature::CloudSync));
+    assert_eq!(
+        trial.enabled(),
+        vec![Feature::HostedModels, Feature::TeamContext]
+    );
+
+    assert!(Entitlements::default().enabled().is_empty());
+    let enterprise = Entitlements {
+        tier: Tier::Enterprise,
+        ..Default::default()
+    };
+    assert_eq!(enterprise.enabled(), Feature::ALL.to_vec());
+}
+
+#[test]
+fn cached_entitlements_expire_after_offline_grace() {
+    let team = Entitlements {
+        tier: Tier::Team,
+        checked_at: 1_000,
+        ..Default::default()
+    };
+    let grace = OFFLINE_GRACE.as_secs();
+    assert_eq!(team.effective_at(1_000 + grace).tier, Tier::Team);
+    let expired = team.effective_at(1_001 + grace);
+    assert_eq!(expired.tier, Tier::SelfHosted);
+    assert!(!expired.has(Feature::HostedModels));
+}
+
+#[test]
+fn upgrade_error_names_feature_and_plan() {
+    let err = UpgradeRequired {
+        feature: Feature::AuditLog,
+        current: Tier::Cloud,
+    };
+    let msg = err.to_string();
+    assert!(msg.starts_with("Audit log requires the Team plan (current plan: PhazeAI Cloud)"));
+    assert!(msg.contains("app.phazeai.com/billing"));
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use phazeai_cloud::hosted::{self, parse_quota};
+use phazeai_cloud::CloudLlmClient;
+use phazeai_core::llm::provider::{self, ClientFactory};
+use phazeai_core::llm::{ProviderConfig, ProviderId, ProviderRegistry, UsageTracker};
+use reqwest::header::{HeaderMap, HeaderValue};
+
+#[test]
+fn quota_headers_are_parsed_and_tracked() {
+    let mut headers = HeaderMap::new();
+    headers.insert(hosted::QUOTA_LIMIT_HEADER, HeaderValue::from_static("500"));
+    headers.insert(
+        hosted::QUOTA_REMAINING_HEADER,
+        HeaderValue::from_static(" 0"),
+    );
+    headers.insert(
+        hosted::QUOTA_RESET_HEADER,
+        HeaderValue::from_static("2026-11-01T00:00:00Z"),
+    );
+    let quota = parse_quota(&headers).unwrap();
+    assert_eq!((quota.limit, quota.remaining, quota.used()), (500, 0, 500));
+    assert!(quota.is_exhausted());
+    assert_eq!(quota.resets_at.as_deref(), Some("2026-11-01T00:00:00Z"));
+
+    let mut tracker = UsageTracker::default();
+    tracker.track_quota(quota.clone());
+    assert_eq!(tracker.quota, Some(quota));
+
+    // Both numbers are required; responses without them leave the quota alone.
+    headers.remove(hosted::QUOTA_LIMIT_HEADER);
+    assert!(parse_quota(&headers).is_none());
+}
+
+#[test]
+fn registered_cloud_provider_builds_clients_from_any_registry() {
+    let config = ProviderConfig {
+        id: ProviderId::PhazeCloud,
+        enabled: true,
+        api_key_env: String::new(),
+        base_url: "http://127.0.0.1:9".into(),
+        default_model: "phaze-fast".into(),
+    };
+    let factory: ClientFactory = Arc::new(|config, model| {
+        Ok(Box::new(
+            CloudLlmClient::new("session-token")
+                .with_base_url(&config.base_url)
+                .with_model(model),
+        ))
+    });
+    provider::register_provider(config, factory);
+
+    let mut registry = ProviderRegistry::new();
+    let config = registry.get_config(&ProviderId::PhazeCloud).unwrap();
+    assert!(config.is_available(), "no API key needed");
+    registry.set_provider(ProviderId::PhazeCloud);
+    assert_eq!(registry.active_model(), "phaze-fast");
+    assert!(registry.build_active_client().is_ok());
+
+    provider::unregister_provider(&ProviderId::PhazeCloud);
+    let registry = ProviderRegistry::new();
+    assert!(registry.get_config(&ProviderId::PhazeCloud).is_none());
+    let err = registry
+        .build_client_for(
+            &ProviderConfig {
+                id: ProviderId::PhazeCloud,
+                enabled: true,
+                api_key_env: String::new(),
+                base_url: String::new(),
+                default_model: String::new(),
+            },
+            "phaze-fast",
+        )
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("not signed in"));
+}
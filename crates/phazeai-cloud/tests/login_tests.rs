@@ -0,0 +1,73 @@
+use phazeai_cloud::auth::{CloudCredentials, TokenResponse, REFRESH_MARGIN};
+use phazeai_cloud::login::parse_callback;
+
+#[test]
+fn callback_yields_code_only_for_matching_state() {
+    let code = parse_callback("/callback?code=abc%2F123&state=s1", "s1").unwrap();
+    assert_eq!(code.unwrap(), "abc/123");
+
+    let err = parse_callback("/callback?code=abc&state=other", "s1").unwrap();
+    assert!(err.unwrap_err().to_string().contains("state mismatch"));
+
+    let denied = parse_callback("/callback?error=access+denied&state=s1", "s1").unwrap();
+    assert_eq!(denied.unwrap_err().to_string(), "access denied");
+
+    assert!(parse_callback("/favicon.ico", "s1").is_none());
+}
+
+#[test]
+fn token_responses_become_refreshable_credentials() {
+    let token: TokenResponse = serde_json::from_str(
+        r#"{"access_token": "at1", "refresh_token": "rt1", "expires_in": 3600, "email": "a@b.co"}"#,
+    )
+    .unwrap();
+    let creds = token.into_credentials(&CloudCredentials::default(), 1_000);
+    assert!(creds.is_authenticated());
+    assert_eq!(creds.expires_at, Some(4_600));
+    assert!(!creds.needs_refresh(1_000));
+    assert!(creds.needs_refresh(4_600 - REFRESH_MARGIN.as_secs()));
+
+    // A refresh that doesn't rotate the refresh token keeps the old one and the email.
+    let renewed: TokenResponse =
+        serde_json::from_str(r#"{"access_token": "at2", "expires_in": 3600}"#).unwrap();
+    let renewed = renewed.into_credentials(&creds, 4_500);
+    assert_eq!(renewed.api_token.as_deref(), Some("at2"));
+    assert_eq!(renewed.refresh_token.as_deref(), Some("rt1"));
+    assert_eq!(renewed.email.as_deref(), Some("a@b.co"));
+
+    // Pasted API tokens never expire, so they are never refreshed.
+    let api_token = CloudCredentials {
+        api_token: Some("pat".into()),
+        ..Default::default()
+    };
+    assert!(!api_token.needs_refresh(u64::MAX / 2));
+}
+
+#[test]
+fn credentials_with_the_refresh_token_in_the_keychain_stay_refreshable() {
+    // As loaded from cloud.toml: the token itself isn't read until a refresh.
+    let loaded: CloudCredentials = toml::from_str(
+        "api_token = \"at1\"\nrefresh_token_in_keychain = true\nexpires_at = 4600\n",
+    )
+    .unwrap();
+    assert!(loaded.refresh_token.is_none());
+    assert!(loaded.needs_refresh(4_600 - REFRESH_MARGIN.as_secs()));
+
+    let renewed: TokenResponse =
+        serde_json::from_str(r#"{"access_token": "at2", "expires_in": 3600}"#).unwrap();
+    assert!(
+        renewed
+            .into_credentials(&loaded, 4_500)
+            .refresh_token_in_keychain
+    );
+    // A rotated token replaces the one in the keychain on the next save.
+    let rotated: TokenResponse =
+        serde_json::from_str(r#"{"access_token": "at3", "refresh_token": "rt2"}"#).unwrap();
+    let rotated = rotated.into_credentials(&loaded, 4_500);
+    assert!(!rotated.refresh_token_in_keychain);
+    assert_eq!(rotated.refresh_token.as_deref(), Some("rt2"));
+
+    assert!(!toml::to_string(&CloudCredentials::default())
+        .unwrap()
+        .contains("refresh_token_in_keychain"));
+}
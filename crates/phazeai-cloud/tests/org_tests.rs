@@ -0,0 +1,75 @@
+use phazeai_cloud::org::provider_id;
+use phazeai_cloud::{OrgPolicy, Organization, Seat, SeatStatus, TeamRole};
+use phazeai_core::llm::{provider, ProviderConfig, ProviderId, ProviderRegistry};
+
+#[test]
+fn empty_policy_allows_every_provider() {
+    let policy = OrgPolicy::default();
+    assert_eq!(policy.provider_ids(), None);
+    assert!(policy.allows(&ProviderId::Claude));
+    assert!(policy.allows(&ProviderId::Custom("internal".into())));
+    assert_eq!(policy.retention(), None);
+}
+
+#[test]
+fn policy_maps_provider_names() {
+    assert_eq!(provider_id("anthropic"), ProviderId::Claude);
+    assert_eq!(provider_id(" PhazeCloud "), ProviderId::PhazeCloud);
+    assert_eq!(
+        provider_id("internal"),
+        ProviderId::Custom("internal".into())
+    );
+
+    let policy = OrgPolicy {
+        allowed_providers: vec!["ollama".into(), "phazecloud".into()],
+        retention_days: Some(30),
+    };
+    assert!(policy.allows(&ProviderId::Ollama));
+    assert!(policy.allows(&ProviderId::PhazeCloud));
+    assert!(!policy.allows(&ProviderId::OpenAI));
+    assert_eq!(
+        policy.retention(),
+        Some(std::time::Duration::from_secs(30 * 24 * 60 * 60))
+    );
+}
+
+#[test]
+fn organization_parses_with_missing_fields() {
+    let org: Organization = serde_json::from_str(
+        r#"{"id":"o1","name":"Acme","role":"admin","seats_total":5,"seats_used":3,
+            "policy":{"allowed_providers":["claude"]}}"#,
+    )
+    .unwrap();
+    assert_eq!(org.role, TeamRole::Admin);
+    assert_eq!(org.seats_free(), 2);
+    assert_eq!(org.policy.allowed_providers, vec!["claude"]);
+    assert_eq!(org.policy.retention_days, None);
+
+    let seat: Seat =
+        serde_json::from_str(r#"{"email":"bo@example.com","status":"invited"}"#).unwrap();
+    assert_eq!(seat.status, SeatStatus::Invited);
+    assert_eq!(seat.role, TeamRole::Viewer);
+    assert_eq!(seat.requests_used, 0);
+}
+
+#[test]
+fn blocked_providers_cannot_build_clients() {
+    let registry = ProviderRegistry::new();
+    let ollama = ProviderConfig {
+        id: ProviderId::Ollama,
+        enabled: true,
+        api_key_env: String::new(),
+        base_url: "http://localhost:11434".into(),
+        default_model: "llama3".into(),
+    };
+
+    provider::set_allowed_providers(Some(vec![ProviderId::Claude]));
+    let err = registry
+        .build_client_for(&ollama, "llama3")
+        .err()
+        .expect("ollama is blocked");
+    assert!(err.to_string().contains("organization's policy"));
+
+    provider::set_allowed_providers(None);
+    assert!(registry.build_client_for(&ollama, "llama3").is_ok());
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use phazeai_cloud::e2e::KeyParams;
+use phazeai_cloud::sync::{self, RemoteEntry, SyncedConversation};
+use phazeai_cloud::{CloudClient, CloudCredentials};
+use phazeai_core::context::{ConversationStore, SavedConversation, SavedMessage};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn conversation() -> SavedConversation {
+    let mut c = SavedConversation::new(
+        "c1".into(),
+        "Lifetimes".into(),
+        "claude-sonnet".into(),
+        None,
+        None,
+    );
+    c.add_message(SavedMessage::user("Why does this borrow fail?".into()));
+    c
+}
+
+#[test]
+fn passphrase_unlocks_the_same_key() {
+    let (params, key) = KeyParams::generate("correct horse battery").unwrap();
+    assert_eq!(params.key_id, key.id());
+    assert!(KeyParams::generate("short").is_err());
+
+    let unlocked = params.unlock("correct horse battery").unwrap();
+    let blob = key.encrypt(b"secret", "c1").unwrap();
+    assert_eq!(unlocked.decrypt(&blob, "c1").unwrap(), b"secret");
+
+    let err = params.unlock("wrong horse battery").unwrap_err();
+    assert!(err.to_string().contains("wrong passphrase"));
+}
+
+#[test]
+fn encrypted_conversations_hide_content_and_detect_tampering() {
+    let (_, key) = KeyParams::generate("correct horse battery").unwrap();
+    let sealed = SyncedConversation::seal(&conversation(), Some(&key)).unwrap();
+    assert!(sealed.conversation.is_none());
+    let json = serde_json::to_string(&sealed).unwrap();
+    assert!(!json.contains("borrow"));
+
+    // Only the key opens it, and only under its own id.
+    assert!(sealed.clone().open(None).is_err());
+    let mut moved = sealed.clone();
+    moved.id = "c2".into();
+    assert!(moved.open(Some(&key)).is_err());
+    let mut tampered = sealed.clone();
+    let blob = tampered.encrypted.as_mut().unwrap();
+    blob.ciphertext = blob.ciphertext.chars().rev().collect();
+    assert!(tampered.open(Some(&key)).is_err());
+
+    let opened = sealed.open(Some(&key)).unwrap();
+    assert_eq!(opened.messages[0].content, "Why does this borrow fail?");
+}
+
+#[test]
+fn server_readable_conversations_are_plain_json() {
+    let sealed = SyncedConversation::seal(&conversation(), None).unwrap();
+    assert!(sealed.encrypted.is_none());
+    assert!(serde_json::to_string(&sealed).unwrap().contains("borrow"));
+    assert_eq!(sealed.open(None).unwrap().metadata.title, "Lifetimes");
+}
+
+/// A stand-in for the sync API, failing the `fail_upload`th conversation
+/// upload (counting from 1; 0 never fails).
+#[derive(Default)]
+struct FakeSync {
+    conversations: HashMap<String, SyncedConversation>,
+    key: Option<KeyParams>,
+    uploads: usize,
+    fail_upload: usize,
+}
+
+impl FakeSync {
+    fn handle(&mut self, method: &str, path: &str, body: &str) -> (u16, String) {
+        match (method, path.strip_prefix("/sync/conversations")) {
+            ("GET", Some("")) => {
+                let entries: Vec<RemoteEntry> = self
+                    .conversations
+                    .values()
+                    .map(|c| RemoteEntry {
+                        id: c.id.clone(),
+                        updated_at: c.updated_at.clone(),
+                        key_id: c.encrypted.as_ref().map(|b| b.key_id.clone()),
+                    })
+                    .collect();
+                (200, serde_json::to_string(&entries).unwrap())
+            }
+            ("GET", Some(id)) => match self.conversations.get(&id[1..]) {
+                Some(c) => (200, serde_json::to_string(c).unwrap()),
+                None => (404, String::new()),
+            },
+            ("PUT", Some(_)) => {
+                self.uploads += 1;
+                if self.uploads == self.fail_upload {
+                    return (500, String::new());
+                }
+                let c: SyncedConversation = serde_json::from_str(body).unwrap();
+                self.conversations.insert(c.id.clone(), c);
+                (200, "{}".into())
+            }
+            ("PUT", None) if path == "/sync/key" => {
+                self.key = Some(serde_json::from_str(body).unwrap());
+                (200, "{}".into())
+            }
+            _ => (404, String::new()),
+        }
+    }
+}
+
+/// Serve `fake` on a local port, one request per connection.
+async fn serve(fake: Arc<Mutex<FakeSync>>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            let (head, body) = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|l| {
+                            l.to_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() >= length || n == 0 {
+                        break (head.to_string(), body.to_string());
+                    }
+                }
+            };
+            let mut request_line = head.split_whitespace();
+            let method = request_line.next().unwrap_or_default();
+            let path = request_line.next().unwrap_or_default();
+            let (status, body) = fake.lock().unwrap().handle(method, path, &body);
+            let response = format!(
+                "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn key_rotation_keeps_the_old_key_until_everything_is_re_encrypted() {
+    let dir = TempDir::new().unwrap();
+    let store = ConversationStore::with_dir(dir.path().to_path_buf()).unwrap();
+    let (old_params, old) = KeyParams::generate("correct horse battery").unwrap();
+    let mut fake = FakeSync {
+        key: Some(old_params.clone()),
+        // The second upload under the new key fails.
+        fail_upload: 2,
+        ..Default::default()
+    };
+    for id in ["c1", "c2", "c3"] {
+        let mut c = SavedConversation::new(id.into(), id.into(), "m".into(), None, None);
+        c.add_message(SavedMessage::user(format!("about {id}")));
+        store.save(&c).unwrap();
+        let saved = store.load(id).unwrap();
+        let sealed = SyncedConversation::seal(&saved, Some(&old)).unwrap();
+        fake.conversations.insert(id.into(), sealed);
+    }
+    let fake = Arc::new(Mutex::new(fake));
+    std::env::set_var("PHAZEAI_CLOUD_URL", serve(fake.clone()).await);
+    let creds = CloudCredentials {
+        api_token: Some("token".into()),
+        ..Default::default()
+    };
+    let client = CloudClient::new(&creds, "").unwrap();
+
+    let err = sync::reencrypt(&client, &store, &old, "staple battery horse")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("still in use"), "{err:#}");
+    {
+        let fake = fake.lock().unwrap();
+        assert_eq!(fake.key.as_ref(), Some(&old_params));
+        // What went up under the new key was put back under the old one.
+        for c in fake.conversations.values() {
+            assert_eq!(c.encrypted.as_ref().unwrap().key_id, old.id());
+        }
+    }
+
+    fake.lock().unwrap().fail_upload = 0;
+    let (key, report) = sync::reencrypt(&client, &store, &old, "staple battery horse")
+        .await
+        .unwrap();
+    assert_eq!(report.uploaded, 3);
+    let fake = fake.lock().unwrap();
+    assert_eq!(fake.key.as_ref().unwrap().key_id, key.id());
+    for c in fake.conversations.values() {
+        let opened = c.clone().open(Some(&key)).unwrap();
+        assert_eq!(opened.messages[0].content, format!("about {}", c.id));
+    }
+}
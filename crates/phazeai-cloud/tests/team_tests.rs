@@ -0,0 +1,80 @@
+use phazeai_cloud::team::{PinnedDocument, PromptProfile, TeamMemory};
+use phazeai_cloud::{TeamContext, TeamRole};
+
+fn team() -> TeamContext {
+    let memory = |id: &str, text: &str| TeamMemory {
+        id: id.into(),
+        text: text.into(),
+        author: "ana@example.com".into(),
+        created_at: String::new(),
+    };
+    TeamContext {
+        team_id: "t1".into(),
+        team_name: "Acme".into(),
+        role: TeamRole::Member,
+        email: "bo@example.com".into(),
+        memories: vec![
+            memory("m1", "Services talk over gRPC, not REST"),
+            memory("m2", "We use sqlx 0.8, never diesel"),
+        ],
+        profiles: vec![
+            PromptProfile {
+                name: "default".into(),
+                instructions: "Write tests for every bug fix.".into(),
+                updated_by: String::new(),
+            },
+            PromptProfile {
+                name: "review".into(),
+                instructions: "Be strict about error handling.".into(),
+                updated_by: String::new(),
+            },
+        ],
+        documents: vec![PinnedDocument {
+            id: "d1".into(),
+            title: "ARCHITECTURE.md".into(),
+            content: "x".repeat(20_000),
+            pinned_by: "ana@example.com".into(),
+        }],
+    }
+}
+
+#[test]
+fn team_prompt_section_uses_profile_memories_and_documents() {
+    let ctx = team();
+    let section = ctx.prompt_section("add a sqlx query", None).unwrap();
+    assert!(section.starts_with("## Team Context (Acme)"));
+    assert!(section.contains("Write tests for every bug fix."));
+    assert!(!section.contains("strict about error handling"));
+    // The relevant memory comes first.
+    let sqlx = section.find("sqlx 0.8").unwrap();
+    let grpc = section.find("gRPC").unwrap();
+    assert!(sqlx < grpc);
+    // Pinned documents are cut to the prompt budget.
+    assert!(section.contains("### Pinned: ARCHITECTURE.md"));
+    assert!(section.contains("[truncated]"));
+    assert!(section.len() < 10_000);
+
+    let review = ctx.prompt_section("", Some("Review")).unwrap();
+    assert!(review.contains("Be strict about error handling."));
+
+    assert!(TeamContext::default().prompt_section("", None).is_none());
+}
+
+#[test]
+fn team_roles_gate_writes_and_removals() {
+    assert!(!TeamRole::Viewer.can_write());
+    assert!(TeamRole::Viewer.require_write().is_err());
+    assert!(TeamRole::Member.require_write().is_ok());
+    assert!(TeamRole::Member.require_admin().is_err());
+
+    assert!(TeamRole::Member.can_remove("Bo@example.com", "bo@example.com"));
+    assert!(!TeamRole::Member.can_remove("ana@example.com", "bo@example.com"));
+    assert!(!TeamRole::Viewer.can_remove("bo@example.com", "bo@example.com"));
+    assert!(TeamRole::Admin.can_remove("ana@example.com", "bo@example.com"));
+
+    assert_eq!(TeamRole::parse("Admin"), Some(TeamRole::Admin));
+    assert_eq!(TeamRole::parse("owner"), Some(TeamRole::Admin));
+    assert_eq!(TeamRole::parse("guest"), None);
+    let role: TeamRole = serde_json::from_str("\"member\"").unwrap();
+    assert_eq!(role, TeamRole::Member);
+}
@@ -101,6 +101,9 @@ async fn main() -> Result<(), PhazeError> {
                 } => {
                     println!("\n🏁 PIPELINE COMPLETE!");
                 }
+                MultiAgentEvent::WorktreeCreated { path, branch } => {
+                    println!("\n🌿 Worktree: {} ({})", path.display(), branch);
+                }
                 MultiAgentEvent::Error(e) => {
                     println!("\n❌ Agent Error: {}", e);
                 }
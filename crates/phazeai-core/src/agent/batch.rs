@@ -0,0 +1,453 @@
+//! Workspace-wide jobs run through a batch API.
+//!
+//! A [`BatchTask`] turns every source file into one [`BatchRequest`]; the
+//! requests go out as one or more batches, and a [`BatchJob`] in
+//! `.phazeai/batches/` remembers which file each result belongs to. Once the
+//! batches have ended, [`collect`] writes the results back (a summary next
+//! to the workspace, or the file with its new doc comments) and records what
+//! the job cost at batch prices.
+
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::analysis::Language;
+use crate::error::PhazeError;
+use crate::llm::batch::{chunk_requests, BatchClient, BatchRequest, BatchStatus};
+use crate::llm::{Message, ProviderId, ProviderRegistry, UsageTracker};
+
+/// Where jobs are kept, under the workspace.
+pub const BATCHES_DIR: &str = ".phazeai/batches";
+/// Where [`BatchTask::Summarize`] writes, under the workspace.
+pub const SUMMARIES_DIR: &str = ".phazeai/summaries";
+
+/// Files larger than this are left out of a summary job.
+const MAX_SUMMARY_FILE_BYTES: u64 = 100_000;
+/// Files larger than this are left out of a docstring job; the reply has to
+/// hold the whole file.
+const MAX_DOCSTRING_FILE_BYTES: u64 = 28_000;
+const SUMMARY_MAX_TOKENS: u32 = 1024;
+/// Most output tokens asked for; some models take no more.
+const MAX_REPLY_TOKENS: u32 = 16_384;
+
+const SUMMARIZE_PROMPT: &str = "You summarize one source file for a \
+developer new to the project. In three to six sentences of plain prose, say \
+what the file is for, its main types and functions, and what it relies on. \
+Name real items from the file and never invent ones. No preamble, no \
+headings.";
+
+const DOCSTRINGS_PROMPT: &str = "You add documentation comments to one \
+source file. Give every public function, method, type and module that has \
+none a short doc comment in the language's usual style: `///` and `//!` in \
+Rust, docstrings in Python, JSDoc in JavaScript and TypeScript, `//` comments \
+in Go. Change nothing else; every existing line, comment and blank line \
+stays as it is. Reply with the whole file in a single fenced code block.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchTask {
+    /// A short summary of every source file, in [`SUMMARIES_DIR`].
+    Summarize,
+    /// Doc comments for everything public that lacks one, written into the
+    /// files themselves.
+    Docstrings,
+}
+
+impl BatchTask {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BatchTask::Summarize => "summarize",
+            BatchTask::Docstrings => "docstrings",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "summarize" | "summaries" => Some(BatchTask::Summarize),
+            "docstrings" | "docs" => Some(BatchTask::Docstrings),
+            _ => None,
+        }
+    }
+
+    fn max_file_bytes(self) -> u64 {
+        match self {
+            BatchTask::Summarize => MAX_SUMMARY_FILE_BYTES,
+            BatchTask::Docstrings => MAX_DOCSTRING_FILE_BYTES,
+        }
+    }
+
+    /// The request for one file.
+    fn request(self, custom_id: String, path: &Path, code: &str) -> BatchRequest {
+        let (prompt, max_tokens) = match self {
+            BatchTask::Summarize => (SUMMARIZE_PROMPT, SUMMARY_MAX_TOKENS),
+            // Room for the whole file again, with its new comments.
+            BatchTask::Docstrings => (
+                DOCSTRINGS_PROMPT,
+                ((code.len() / 2) as u32 + 2048).min(MAX_REPLY_TOKENS),
+            ),
+        };
+        BatchRequest {
+            custom_id,
+            messages: vec![
+                Message::system(prompt),
+                Message::user(format!("File: {}\n\n```\n{code}\n```", path.display())),
+            ],
+            max_tokens,
+        }
+    }
+}
+
+/// One file of a job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub custom_id: String,
+    /// Relative to the workspace.
+    pub path: PathBuf,
+    /// SHA-256 of the file as submitted; a file edited since is left alone.
+    pub digest: String,
+}
+
+/// A file whose result couldn't be used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchFailure {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A submitted job, saved as `.phazeai/batches/<id>.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchJob {
+    pub id: String,
+    pub task: BatchTask,
+    pub provider: ProviderId,
+    pub model: String,
+    /// RFC 3339.
+    pub created_at: String,
+    /// The provider's ids of the job's batches.
+    pub batches: Vec<String>,
+    pub items: Vec<BatchItem>,
+    /// RFC 3339; unset until the results have been collected.
+    #[serde(default)]
+    pub collected_at: Option<String>,
+    /// Files written from the results.
+    #[serde(default)]
+    pub written: Vec<PathBuf>,
+    #[serde(default)]
+    pub failures: Vec<BatchFailure>,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// What the job cost, in dollars, at batch prices.
+    #[serde(default)]
+    pub cost: f64,
+    /// What it would have cost on top of that without batching.
+    #[serde(default)]
+    pub savings: f64,
+}
+
+impl BatchJob {
+    pub fn is_collected(&self) -> bool {
+        self.collected_at.is_some()
+    }
+}
+
+fn digest(code: &str) -> String {
+    hex::encode(Sha256::digest(code.as_bytes()))
+}
+
+/// The source files under `paths` (files or directories relative to
+/// `root`; all of it when empty) git wouldn't ignore, and their requests.
+pub fn work_items(
+    task: BatchTask,
+    root: &Path,
+    paths: &[PathBuf],
+) -> (Vec<BatchItem>, Vec<BatchRequest>) {
+    let mut items = Vec::new();
+    let mut requests = Vec::new();
+    let starts: Vec<PathBuf> = if paths.is_empty() {
+        vec![root.to_path_buf()]
+    } else {
+        paths.iter().map(|p| root.join(p)).collect()
+    };
+    for start in starts {
+        let walker = WalkBuilder::new(&start)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(|e| !matches!(e.file_name().to_str(), Some("node_modules" | "target")))
+            .build();
+        for entry in walker.flatten() {
+            if !entry.file_type().is_some_and(|t| t.is_file())
+                || !entry
+                    .metadata()
+                    .is_ok_and(|m| m.len() <= task.max_file_bytes())
+            {
+                continue;
+            }
+            let ext = entry
+                .path()
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("");
+            if Language::from_extension(ext) == Language::Other {
+                continue;
+            }
+            let Ok(code) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            if code.trim().is_empty() {
+                continue;
+            }
+            let path = entry
+                .path()
+                .strip_prefix(root)
+                .unwrap_or(entry.path())
+                .to_path_buf();
+            let custom_id = format!("file-{}", items.len() + 1);
+            requests.push(task.request(custom_id.clone(), &path, &code));
+            items.push(BatchItem {
+                custom_id,
+                path,
+                digest: digest(&code),
+            });
+        }
+    }
+    (items, requests)
+}
+
+/// Submit `task` for the files under `paths`, in as many batches as the
+/// provider needs, and save the job.
+pub async fn submit(
+    client: &BatchClient,
+    task: BatchTask,
+    root: &Path,
+    paths: &[PathBuf],
+) -> Result<BatchJob, PhazeError> {
+    let (items, requests) = work_items(task, root, paths);
+    if items.is_empty() {
+        return Err(PhazeError::Other("No source files to send".into()));
+    }
+    let mut batches = Vec::new();
+    for chunk in chunk_requests(requests, client.limits()) {
+        batches.push(client.submit(&chunk).await?);
+    }
+    let now = chrono::Local::now();
+    let job = BatchJob {
+        id: format!("{}-{}", now.format("%Y%m%d-%H%M%S"), task.as_str()),
+        task,
+        provider: client.provider().clone(),
+        model: client.model().to_string(),
+        created_at: now.to_rfc3339(),
+        batches,
+        items,
+        collected_at: None,
+        written: Vec::new(),
+        failures: Vec::new(),
+        input_tokens: 0,
+        output_tokens: 0,
+        cost: 0.0,
+        savings: 0.0,
+    };
+    BatchJobStore::open(root).save(&job)?;
+    Ok(job)
+}
+
+/// Where each of the job's batches is.
+pub async fn statuses(
+    client: &BatchClient,
+    job: &BatchJob,
+) -> Result<Vec<BatchStatus>, PhazeError> {
+    let mut statuses = Vec::new();
+    for id in &job.batches {
+        statuses.push(client.status(id).await?);
+    }
+    Ok(statuses)
+}
+
+/// Write back the results of a job whose batches have all finished, record
+/// its usage and cost, and save it.
+pub async fn collect(
+    client: &BatchClient,
+    job: &mut BatchJob,
+    root: &Path,
+) -> Result<(), PhazeError> {
+    let statuses = statuses(client, job).await?;
+    if let Some(running) = statuses.iter().find(|s| !s.state.is_finished()) {
+        return Err(PhazeError::Other(format!(
+            "Batch {} is still running ({}/{} done)",
+            running.id,
+            running.succeeded + running.failed,
+            running.total
+        )));
+    }
+    let mut usage = UsageTracker::default();
+    let mut written = Vec::new();
+    let mut failures = Vec::new();
+    let mut answered = std::collections::HashSet::new();
+    for status in &statuses {
+        for result in client.results(status).await? {
+            let Some(item) = job.items.iter().find(|i| i.custom_id == result.custom_id) else {
+                continue;
+            };
+            answered.insert(item.custom_id.clone());
+            if let Some(u) = &result.usage {
+                usage.track_batch(u.input_tokens, u.output_tokens);
+            }
+            match result
+                .outcome
+                .and_then(|reply| integrate(job.task, root, item, &reply))
+            {
+                Ok(path) => written.push(path),
+                Err(reason) => failures.push(BatchFailure {
+                    path: item.path.clone(),
+                    reason,
+                }),
+            }
+        }
+    }
+    for item in &job.items {
+        if !answered.contains(&item.custom_id) {
+            failures.push(BatchFailure {
+                path: item.path.clone(),
+                reason: "no result".into(),
+            });
+        }
+    }
+
+    let model = ProviderRegistry::known_models(&job.provider)
+        .into_iter()
+        .filter(|m| job.model.starts_with(&m.id))
+        .max_by_key(|m| m.id.len());
+    job.input_tokens = usage.batch_input_tokens;
+    job.output_tokens = usage.batch_output_tokens;
+    job.cost = model.as_ref().map_or(0.0, |m| usage.estimated_cost(m));
+    job.savings = model.as_ref().map_or(0.0, |m| usage.batch_savings(m));
+    job.written = written;
+    job.failures = failures;
+    job.collected_at = Some(chrono::Local::now().to_rfc3339());
+    BatchJobStore::open(root).save(job)
+}
+
+/// Put one reply where it belongs, returning the path written, relative
+/// to `root`.
+pub fn integrate(
+    task: BatchTask,
+    root: &Path,
+    item: &BatchItem,
+    reply: &str,
+) -> Result<PathBuf, String> {
+    let reply = reply.trim();
+    if reply.is_empty() {
+        return Err("empty reply".into());
+    }
+    let write = |path: &Path, content: &str| -> Result<(), String> {
+        let full = root.join(path);
+        if let Some(parent) = full.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(full, content).map_err(|e| e.to_string())
+    };
+    match task {
+        BatchTask::Summarize => {
+            let mut name = item.path.clone().into_os_string();
+            name.push(".md");
+            let path = Path::new(SUMMARIES_DIR).join(name);
+            write(&path, &format!("# {}\n\n{reply}\n", item.path.display()))?;
+            Ok(path)
+        }
+        BatchTask::Docstrings => {
+            let original =
+                std::fs::read_to_string(root.join(&item.path)).map_err(|e| e.to_string())?;
+            if digest(&original) != item.digest {
+                return Err("changed since the job was submitted".into());
+            }
+            let mut updated = fenced_code(reply).to_string();
+            if original.ends_with('\n') && !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            if !only_adds_lines(&original, &updated) {
+                return Err("the reply changed existing lines".into());
+            }
+            write(&item.path, &updated)?;
+            Ok(item.path.clone())
+        }
+    }
+}
+
+/// The contents of the first fenced code block in `reply`, or all of it.
+fn fenced_code(reply: &str) -> &str {
+    let Some(start) = reply.find("```") else {
+        return reply;
+    };
+    let after = &reply[start + 3..];
+    let Some(body_start) = after.find('\n') else {
+        return reply;
+    };
+    let body = &after[body_start + 1..];
+    match body.rfind("```") {
+        Some(end) => &body[..end],
+        None => body,
+    }
+}
+
+/// Whether `updated` is `original` with lines added and none changed.
+fn only_adds_lines(original: &str, updated: &str) -> bool {
+    let mut updated = updated.lines();
+    original
+        .lines()
+        .all(|line| updated.by_ref().any(|u| u.trim_end() == line.trim_end()))
+}
+
+/// The jobs of one workspace.
+pub struct BatchJobStore {
+    dir: PathBuf,
+}
+
+impl BatchJobStore {
+    pub fn open(root: &Path) -> Self {
+        Self {
+            dir: root.join(BATCHES_DIR),
+        }
+    }
+
+    pub fn save(&self, job: &BatchJob) -> Result<(), PhazeError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(job)?;
+        std::fs::write(self.dir.join(format!("{}.json", job.id)), json)?;
+        Ok(())
+    }
+
+    /// Every job, newest first.
+    pub fn list(&self) -> Vec<BatchJob> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut jobs: Vec<BatchJob> = entries
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+            .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        jobs
+    }
+
+    /// The job whose id starts with `prefix`; the newest one if that's empty.
+    pub fn find(&self, prefix: &str) -> Result<BatchJob, PhazeError> {
+        let mut matching = self
+            .list()
+            .into_iter()
+            .filter(|job| job.id.starts_with(prefix));
+        let job = matching
+            .next()
+            .ok_or_else(|| PhazeError::Other(format!("No batch job '{prefix}'")))?;
+        if !prefix.is_empty() && matching.next().is_some() {
+            return Err(PhazeError::Other(format!(
+                "More than one batch job starts with '{prefix}'"
+            )));
+        }
+        Ok(job)
+    }
+}
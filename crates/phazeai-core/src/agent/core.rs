@@ -1,7 +1,15 @@
+use super::recording::{RecordingLlm, RunRecorder};
+use crate::config::AgentSettings;
+use crate::context::citations::{self, Citation};
+use crate::context::docs::{self, DocChunk, DocsIndex};
+use crate::context::memory::{MemoryStore, PromptContext};
 use crate::context::ConversationHistory;
 use crate::error::PhazeError;
-use crate::llm::{FunctionCall, LlmClient, Message, StreamEvent, ToolCall};
-use crate::tools::{ToolDefinition, ToolRegistry};
+use crate::llm::{
+    FunctionCall, ImageAttachment, LlmClient, Message, StreamEvent, ToolCall, STREAM_CLOSED_EARLY,
+};
+use crate::project::BoardStore;
+use crate::tools::{Tool, ToolDefinition, ToolRegistry};
 use futures::StreamExt;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -9,8 +17,38 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+/// How often a pending model or tool call checks for cancellation.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How many times one model response may be resumed after its stream drops.
+const MAX_STREAM_RESUMES: u32 = 3;
+
+/// Wait before resuming; multiplied by the attempt number.
+const RESUME_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Sent after the partial reply when resuming a dropped stream.
+const RESUME_PROMPT: &str = "Your previous reply was cut off by a network error. \
+     Continue exactly where it stopped, without repeating any of it.";
+
+/// The request that resumes a reply whose stream dropped after `partial`.
+/// None of the supported providers offer a resume token, so the model is
+/// shown its partial reply and asked to go on.
+fn resume_request(messages: &[Message], partial: &str) -> Vec<Message> {
+    let mut request = messages.to_vec();
+    request.push(Message::assistant(partial));
+    request.push(Message::user(RESUME_PROMPT));
+    request
+}
+
+/// Why [`Agent::interruptible`] stopped waiting.
+enum Interrupt {
+    Cancelled,
+    TimedOut,
+}
+
 /// Events emitted during agent execution - the shared CLI/IDE interface.
 #[derive(Debug, Clone)]
 pub enum AgentEvent {
@@ -18,6 +56,13 @@ pub enum AgentEvent {
         iteration: usize,
     },
     TextDelta(String),
+    /// Summarized reasoning from a reasoning model, streamed before its answer.
+    ReasoningDelta(String),
+    /// The model's first streamed token (text or tool call) for the current
+    /// step arrived `latency` after the request was sent.
+    FirstToken {
+        latency: std::time::Duration,
+    },
     ToolApprovalRequest {
         name: String,
         params: Value,
@@ -30,9 +75,21 @@ pub enum AgentEvent {
         success: bool,
         summary: String,
     },
+    /// Documentation passages added to the prompt for this request, in
+    /// label order (`D1`, `D2`, …); the reply cites them by label.
+    DocSources(Vec<DocChunk>),
+    /// The file ranges the finished reply is grounded in: what its tools
+    /// retrieved and the documentation it cited. Sent just before `Complete`.
+    Citations(Vec<Citation>),
     Complete {
         iterations: usize,
     },
+    /// The model's stream dropped mid-response; the agent is reconnecting
+    /// and will continue the reply from the text received so far.
+    StreamResumed {
+        attempt: u32,
+        reason: String,
+    },
     TokenUsage {
         input_tokens: u64,
         output_tokens: u64,
@@ -61,6 +118,9 @@ pub struct AgentResponse {
     /// Cumulative token usage across all LLM calls in this run.
     pub total_input_tokens: u64,
     pub total_output_tokens: u64,
+    /// How many times a dropped stream was resumed; the reply is "recovered"
+    /// when this is nonzero.
+    pub resumed: u32,
 }
 
 /// Callback invoked before tool execution. Returns true to approve, false to deny.
@@ -85,6 +145,16 @@ pub struct Agent {
     approval_fn: Option<ApprovalFn>,
     /// Optional cancellation token — set to `true` to abort the running loop.
     cancel_token: Option<Arc<AtomicBool>>,
+    /// Longest one `run_with_events` call may take.
+    turn_timeout: Option<Duration>,
+    /// Longest one tool call may take; a timed-out call fails but the turn goes on.
+    tool_timeout: Option<Duration>,
+    /// Captures model turns and tool calls for later replay.
+    recorder: Option<Arc<RunRecorder>>,
+    /// Sources appended to the system prompt on each run (project memory, team context).
+    prompt_context: Vec<Arc<dyn PromptContext>>,
+    /// Project documentation searched for passages relevant to each request.
+    docs: Option<Arc<DocsIndex>>,
 }
 
 impl Agent {
@@ -97,11 +167,17 @@ impl Agent {
             max_context_tokens: 32768, // Default budget
             approval_fn: None,
             cancel_token: None,
+            turn_timeout: None,
+            tool_timeout: None,
+            recorder: None,
+            prompt_context: Vec::new(),
+            docs: None,
         }
     }
 
     /// Attach a cancellation token. Set the `AtomicBool` to `true` from any
-    /// thread to abort the agent loop after the current LLM/tool step.
+    /// thread to abort the agent loop; an in-flight model request or tool
+    /// call is dropped within [`CANCEL_POLL_INTERVAL`].
     pub fn with_cancel_token(mut self, token: Arc<AtomicBool>) -> Self {
         self.cancel_token = Some(token);
         self
@@ -120,6 +196,71 @@ impl Agent {
             .unwrap_or(false)
     }
 
+    pub fn with_turn_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.turn_timeout = timeout;
+        self
+    }
+
+    pub fn with_tool_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.tool_timeout = timeout;
+        self
+    }
+
+    /// Apply the turn and tool timeouts from the config.
+    pub fn with_limits(self, limits: &AgentSettings) -> Self {
+        self.with_turn_timeout(limits.turn_timeout())
+            .with_tool_timeout(limits.tool_timeout())
+    }
+
+    /// Await `fut` unless the run is cancelled or `deadline` passes first.
+    /// Giving up drops `fut`, which aborts its HTTP request or child process.
+    async fn interruptible<F: Future>(
+        &self,
+        fut: F,
+        deadline: Option<Instant>,
+    ) -> Result<F::Output, Interrupt> {
+        tokio::pin!(fut);
+        loop {
+            let mut wait = CANCEL_POLL_INTERVAL;
+            if let Some(deadline) = deadline {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if left.is_zero() {
+                    return Err(Interrupt::TimedOut);
+                }
+                wait = wait.min(left);
+            }
+            tokio::select! {
+                out = &mut fut => return Ok(out),
+                _ = tokio::time::sleep(wait) => {
+                    if self.is_cancelled() {
+                        return Err(Interrupt::Cancelled);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Report why the turn stopped and turn it into the run's error.
+    fn stop_turn(
+        &self,
+        why: Interrupt,
+        event_tx: &tokio::sync::mpsc::UnboundedSender<AgentEvent>,
+    ) -> PhazeError {
+        let error = match why {
+            Interrupt::Cancelled => PhazeError::Cancelled,
+            Interrupt::TimedOut => PhazeError::Timeout(format!(
+                "request took longer than {}s",
+                self.turn_timeout.unwrap_or_default().as_secs()
+            )),
+        };
+        let message = match &error {
+            PhazeError::Cancelled => "Cancelled".to_string(),
+            e => e.to_string(),
+        };
+        let _ = event_tx.send(AgentEvent::Error(message));
+        error
+    }
+
     pub fn with_tools(mut self, tools: ToolRegistry) -> Self {
         self.tools = tools;
         self
@@ -135,6 +276,96 @@ impl Agent {
         self
     }
 
+    /// Record this agent's model traffic and tool calls into `recorder`.
+    /// Call after any `swap_llm`, since the current client gets wrapped.
+    pub fn with_recorder(self, recorder: Arc<RunRecorder>) -> Self {
+        Self {
+            llm: Box::new(RecordingLlm::new(self.llm, recorder.clone())),
+            recorder: Some(recorder),
+            ..self
+        }
+    }
+
+    /// Inject the memories most relevant to each request into the system
+    /// prompt and let the `memory` tool write to `store`.
+    pub fn with_memory(mut self, store: MemoryStore) -> Self {
+        self.tools
+            .register(Box::new(crate::tools::MemoryTool::new(store.clone())));
+        self.with_prompt_context(Arc::new(store))
+    }
+
+    /// Point the `task_board` tool, when the agent has it, at `store`; cards
+    /// it creates link to `conversation`.
+    pub fn with_task_board(mut self, store: BoardStore, conversation: Option<String>) -> Self {
+        if self.tools.get("task_board").is_some() {
+            let mut tool = crate::tools::TaskBoardTool::new(store);
+            if let Some(id) = conversation {
+                tool = tool.with_conversation(id);
+            }
+            self.tools.register(Box::new(tool));
+        }
+        self
+    }
+
+    /// Point the `http_request` tool, when the agent has it, at the request
+    /// files of the workspace at `root`.
+    pub fn with_http_requests(mut self, root: &std::path::Path) -> Self {
+        if self.tools.get("http_request").is_some() {
+            self.tools
+                .register(Box::new(crate::tools::HttpRequestTool::new(root)));
+        }
+        self
+    }
+
+    /// Point the `delete_path`, `move_path` and `file_history` tools, when
+    /// the agent has them, at the trash and journal of the workspace `root`.
+    pub fn with_file_journal(mut self, root: &std::path::Path) -> Self {
+        let journal = crate::project::FileJournal::for_workspace(root);
+        if self.tools.get("delete_path").is_some() {
+            self.tools
+                .register(Box::new(crate::tools::DeletePathTool::new(journal.clone())));
+        }
+        if self.tools.get("move_path").is_some() {
+            self.tools
+                .register(Box::new(crate::tools::MovePathTool::new(journal.clone())));
+        }
+        if self.tools.get("file_history").is_some() {
+            self.tools
+                .register(Box::new(crate::tools::FileHistoryTool::new(journal)));
+        }
+        self
+    }
+
+    /// Give the `sql_query` tool, when the agent has it, the database
+    /// `profiles`; SQLite paths are relative to `root`.
+    pub fn with_databases(
+        mut self,
+        profiles: &[crate::db::DatabaseProfile],
+        root: &std::path::Path,
+    ) -> Self {
+        if self.tools.get("sql_query").is_some() {
+            self.tools
+                .register(Box::new(crate::tools::SqlQueryTool::new(
+                    profiles.to_vec(),
+                    root,
+                )));
+        }
+        self
+    }
+
+    /// Append `source`'s section for each request to the system prompt.
+    pub fn with_prompt_context(mut self, source: Arc<dyn PromptContext>) -> Self {
+        self.prompt_context.push(source);
+        self
+    }
+
+    /// Add the passages of `index` most relevant to each request to the
+    /// system prompt, announced with [`AgentEvent::DocSources`].
+    pub fn with_docs(mut self, index: Arc<DocsIndex>) -> Self {
+        self.docs = Some(index);
+        self
+    }
+
     pub fn with_approval(mut self, f: ApprovalFn) -> Self {
         self.approval_fn = Some(f);
         self
@@ -165,6 +396,12 @@ impl Agent {
         self.tools.register(tool);
     }
 
+    /// Drop the tools `keep` rejects; see [`ToolRegistry::with_filter`].
+    pub fn with_tool_filter(mut self, keep: impl Fn(&dyn Tool) -> bool) -> Self {
+        self.tools = std::mem::replace(&mut self.tools, ToolRegistry::new()).with_filter(keep);
+        self
+    }
+
     pub fn register_mcp_tools(
         &mut self,
         manager: std::sync::Arc<std::sync::Mutex<crate::mcp::McpManager>>,
@@ -187,16 +424,48 @@ impl Agent {
         &self,
         user_input: impl Into<String>,
         event_tx: tokio::sync::mpsc::UnboundedSender<AgentEvent>,
+    ) -> Result<AgentResponse, PhazeError> {
+        self.run_with_images(user_input, Vec::new(), event_tx).await
+    }
+
+    /// [`Agent::run_with_events`], sending `images` along with the user's
+    /// message for a vision model to look at.
+    pub async fn run_with_images(
+        &self,
+        user_input: impl Into<String>,
+        images: Vec<ImageAttachment>,
+        event_tx: tokio::sync::mpsc::UnboundedSender<AgentEvent>,
     ) -> Result<AgentResponse, PhazeError> {
         let user_input = user_input.into();
         let mut iterations = 0;
         let mut tool_executions = Vec::new();
         let mut total_input_tokens: u64 = 0;
         let mut total_output_tokens: u64 = 0;
+        let mut total_resumes = 0;
+        let deadline = self.turn_timeout.map(|t| Instant::now() + t);
+
+        let mut sections: Vec<String> = self
+            .prompt_context
+            .iter()
+            .filter_map(|source| source.section(&user_input))
+            .collect();
+        let mut doc_chunks = Vec::new();
+        if let Some(ref index) = self.docs {
+            doc_chunks = index.retrieve(&user_input);
+            if let Some(section) = docs::prompt_section(&doc_chunks) {
+                sections.push(section);
+                let _ = event_tx.send(AgentEvent::DocSources(doc_chunks.clone()));
+            }
+        }
+        let mut retrieved = Vec::new();
 
         {
             let mut conversation = self.conversation.lock().await;
-            conversation.add_user_message(&user_input);
+            if !self.prompt_context.is_empty() || self.docs.is_some() {
+                conversation
+                    .set_memory_context((!sections.is_empty()).then(|| sections.join("\n\n")));
+            }
+            conversation.add_message(Message::user(&user_input).with_images(images));
         }
 
         loop {
@@ -230,61 +499,126 @@ impl Agent {
             let tool_definitions: Vec<ToolDefinition> = self.tools.definitions();
 
             // Use streaming API to get real-time token deltas
-            let mut stream = self
-                .llm
-                .chat_stream(&messages, &tool_definitions)
-                .await
-                .inspect_err(|e| {
-                    let _ = event_tx.send(AgentEvent::Error(e.to_string()));
-                })?;
-
-            // Accumulate response content and tool calls from stream
+            let requested = Instant::now();
+            let mut first_token_seen = false;
             let mut content = String::new();
             let mut tool_calls: Vec<ToolCall> = Vec::new();
-            let mut current_tool_calls: HashMap<String, (String, String)> = HashMap::new(); // id -> (name, arguments)
-
-            while let Some(event) = stream.next().await {
-                if self.is_cancelled() {
-                    let _ = event_tx.send(AgentEvent::Error("Cancelled".to_string()));
-                    return Err(PhazeError::Cancelled);
-                }
-                match event {
-                    StreamEvent::TextDelta(delta) => {
-                        content.push_str(&delta);
-                        let _ = event_tx.send(AgentEvent::TextDelta(delta));
-                    }
-                    StreamEvent::ToolCallStart { id, name } => {
-                        current_tool_calls.insert(id.clone(), (name, String::new()));
+            let mut reasoning = Vec::new();
+            let mut resumes = 0;
+
+            loop {
+                let request = if content.is_empty() {
+                    messages.clone()
+                } else {
+                    resume_request(&messages, &content)
+                };
+                let mut current_tool_calls: HashMap<String, (String, String)> = HashMap::new(); // id -> (name, arguments)
+                let mut dropped = None;
+
+                match self
+                    .interruptible(self.llm.chat_stream(&request, &tool_definitions), deadline)
+                    .await
+                    .map_err(|why| self.stop_turn(why, &event_tx))?
+                {
+                    // Still offline while resuming: try again.
+                    Err(e) if resumes > 0 => dropped = Some(e.to_string()),
+                    Err(e) => {
+                        let _ = event_tx.send(AgentEvent::Error(e.to_string()));
+                        return Err(e);
                     }
-                    StreamEvent::ToolCallDelta {
-                        id,
-                        arguments_delta,
-                    } => {
-                        if let Some((_, args)) = current_tool_calls.get_mut(&id) {
-                            args.push_str(&arguments_delta);
+                    Ok(mut stream) => loop {
+                        let Some(event) = self
+                            .interruptible(stream.next(), deadline)
+                            .await
+                            .map_err(|why| self.stop_turn(why, &event_tx))?
+                        else {
+                            dropped = Some(STREAM_CLOSED_EARLY.to_string());
+                            break;
+                        };
+                        if self.is_cancelled() {
+                            let _ = event_tx.send(AgentEvent::Error("Cancelled".to_string()));
+                            return Err(PhazeError::Cancelled);
                         }
-                    }
-                    StreamEvent::ToolCallEnd { id } => {
-                        if let Some((name, arguments)) = current_tool_calls.remove(&id) {
-                            tool_calls.push(ToolCall {
-                                id: id.clone(),
-                                call_type: "function".to_string(),
-                                function: FunctionCall { name, arguments },
+                        if !first_token_seen
+                            && matches!(
+                                event,
+                                StreamEvent::TextDelta(_)
+                                    | StreamEvent::ReasoningDelta(_)
+                                    | StreamEvent::ToolCallStart { .. }
+                            )
+                        {
+                            first_token_seen = true;
+                            let _ = event_tx.send(AgentEvent::FirstToken {
+                                latency: requested.elapsed(),
                             });
                         }
-                    }
-                    StreamEvent::Usage(u) => {
-                        total_input_tokens += u.input_tokens as u64;
-                        total_output_tokens += u.output_tokens as u64;
-                    }
-                    StreamEvent::Done => {
-                        break;
-                    }
-                    StreamEvent::Error(err) => {
-                        let _ = event_tx.send(AgentEvent::Error(err.clone()));
-                        return Err(PhazeError::Llm(err));
-                    }
+                        match event {
+                            StreamEvent::TextDelta(delta) => {
+                                content.push_str(&delta);
+                                let _ = event_tx.send(AgentEvent::TextDelta(delta));
+                            }
+                            StreamEvent::ReasoningDelta(delta) => {
+                                let _ = event_tx.send(AgentEvent::ReasoningDelta(delta));
+                            }
+                            StreamEvent::Reasoning(item) => reasoning.push(item),
+                            StreamEvent::ToolCallStart { id, name } => {
+                                current_tool_calls.insert(id.clone(), (name, String::new()));
+                            }
+                            StreamEvent::ToolCallDelta {
+                                id,
+                                arguments_delta,
+                            } => {
+                                if let Some((_, args)) = current_tool_calls.get_mut(&id) {
+                                    args.push_str(&arguments_delta);
+                                }
+                            }
+                            StreamEvent::ToolCallEnd { id } => {
+                                if let Some((name, arguments)) = current_tool_calls.remove(&id) {
+                                    tool_calls.push(ToolCall {
+                                        id: id.clone(),
+                                        call_type: "function".to_string(),
+                                        function: FunctionCall { name, arguments },
+                                    });
+                                }
+                            }
+                            StreamEvent::Usage(u) => {
+                                total_input_tokens += u.input_tokens as u64;
+                                total_output_tokens += u.output_tokens as u64;
+                            }
+                            StreamEvent::Done => break,
+                            StreamEvent::Error(err) => {
+                                let _ = event_tx.send(AgentEvent::Error(err.clone()));
+                                return Err(PhazeError::Llm(err));
+                            }
+                            StreamEvent::Dropped(reason) => {
+                                dropped = Some(reason);
+                                break;
+                            }
+                        }
+                    },
                 }
+
+                let Some(reason) = dropped else {
+                    break;
+                };
+                if resumes == MAX_STREAM_RESUMES {
+                    let err = format!("Stream dropped: {reason}");
+                    let _ = event_tx.send(AgentEvent::Error(err.clone()));
+                    return Err(PhazeError::Llm(err));
+                }
+                // The text so far is kept and continued; tool calls and
+                // reasoning come again with the resumed reply.
+                tool_calls.clear();
+                reasoning.clear();
+                resumes += 1;
+                total_resumes += 1;
+                let _ = event_tx.send(AgentEvent::StreamResumed {
+                    attempt: resumes,
+                    reason,
+                });
+                self.interruptible(tokio::time::sleep(RESUME_BACKOFF * resumes), deadline)
+                    .await
+                    .map_err(|why| self.stop_turn(why, &event_tx))?;
             }
 
             // Check if we have tool calls to execute
@@ -292,10 +626,10 @@ impl Agent {
                 // Add assistant message with tool calls to conversation
                 {
                     let mut conversation = self.conversation.lock().await;
-                    conversation.add_message(Message::assistant_with_tools(
-                        content.clone(),
-                        tool_calls.clone(),
-                    ));
+                    conversation.add_message(
+                        Message::assistant_with_tools(content.clone(), tool_calls.clone())
+                            .with_reasoning(reasoning),
+                    );
                 }
 
                 // Execute each tool call
@@ -316,7 +650,11 @@ impl Agent {
                             params: params.clone(),
                         });
 
-                        let approved = (approval_fn)(tool_name.clone(), params.clone()).await;
+                        // Waiting on the user doesn't count against the turn timeout.
+                        let approved = self
+                            .interruptible((approval_fn)(tool_name.clone(), params.clone()), None)
+                            .await
+                            .map_err(|why| self.stop_turn(why, &event_tx))?;
                         if !approved {
                             let _ = event_tx.send(AgentEvent::ToolResult {
                                 name: tool_name.clone(),
@@ -331,6 +669,16 @@ impl Agent {
                                     "Error: Tool execution denied by user",
                                 );
                             }
+                            if let Some(rec) = &self.recorder {
+                                rec.record_tool(
+                                    tool_name,
+                                    params.clone(),
+                                    false,
+                                    "Error: Tool execution denied by user",
+                                    true,
+                                    Vec::new(),
+                                );
+                            }
                             tool_executions.push(ToolExecution {
                                 tool_name: tool_name.clone(),
                                 params,
@@ -345,7 +693,48 @@ impl Agent {
                         name: tool_name.clone(),
                     });
 
-                    let (success, result_str) = self.execute_tool(tool_call).await;
+                    let before = self.recorder.as_ref().map(|rec| {
+                        rec.capture_files(&tool_call.parse_arguments().unwrap_or(Value::Null))
+                    });
+                    let tool_deadline = match (self.tool_timeout, deadline) {
+                        (Some(t), Some(d)) => Some(d.min(Instant::now() + t)),
+                        (Some(t), None) => Some(Instant::now() + t),
+                        (None, d) => d,
+                    };
+                    let (success, result_str) = match self
+                        .interruptible(self.execute_tool(tool_call), tool_deadline)
+                        .await
+                    {
+                        Ok(outcome) => outcome,
+                        Err(Interrupt::TimedOut) if deadline.is_none_or(|d| Instant::now() < d) => {
+                            (
+                                false,
+                                format!(
+                                    "Error: {tool_name} timed out after {}s",
+                                    self.tool_timeout.unwrap_or_default().as_secs()
+                                ),
+                            )
+                        }
+                        Err(why) => return Err(self.stop_turn(why, &event_tx)),
+                    };
+                    if let (Some(rec), Some(before)) = (&self.recorder, before) {
+                        rec.record_tool(
+                            tool_name,
+                            tool_call.parse_arguments().unwrap_or(Value::Null),
+                            success,
+                            &result_str,
+                            false,
+                            before,
+                        );
+                    }
+
+                    if success {
+                        retrieved.extend(citations::from_tool_result(
+                            tool_name,
+                            &tool_call.parse_arguments().unwrap_or(Value::Null),
+                            &result_str,
+                        ));
+                    }
 
                     let summary = if success {
                         truncate_str(&result_str, 200)
@@ -381,11 +770,21 @@ impl Agent {
                 input_tokens: total_input_tokens,
                 output_tokens: total_output_tokens,
             });
+            retrieved.extend(
+                docs::cited(&content, &doc_chunks)
+                    .into_iter()
+                    .filter_map(|(_, chunk)| citations::from_doc_chunk(chunk)),
+            );
+            let cited = citations::merge(std::mem::take(&mut retrieved));
+            if !cited.is_empty() {
+                let _ = event_tx.send(AgentEvent::Citations(cited));
+            }
             let _ = event_tx.send(AgentEvent::Complete { iterations });
 
             {
                 let mut conversation = self.conversation.lock().await;
-                conversation.add_assistant_message(&content);
+                conversation
+                    .add_message(Message::assistant(content.clone()).with_reasoning(reasoning));
             }
 
             return Ok(AgentResponse {
@@ -394,6 +793,7 @@ impl Agent {
                 iterations,
                 total_input_tokens,
                 total_output_tokens,
+                resumed: total_resumes,
             });
         }
     }
@@ -0,0 +1,385 @@
+//! Background agent jobs.
+//!
+//! A [`JobQueue`] runs agent tasks on its own runtime thread, a few at a time,
+//! while the caller carries on. Each job can get its own git worktree so its
+//! edits never touch the workspace. Tool calls that need approval park the
+//! job until [`JobQueue::respond`] is called; every change is reported
+//! through the listener so a UI can re-read [`JobQueue::jobs`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{mpsc, oneshot, Semaphore};
+
+use super::{Agent, AgentEvent};
+use crate::error::PhazeError;
+use crate::git::GitOps;
+use crate::tools::ToolApprovalManager;
+
+pub type JobId = u64;
+
+/// Builds the agent for a job, given the directory it works in.
+pub type AgentFactory = Arc<dyn Fn(&Path) -> Result<Agent, PhazeError> + Send + Sync>;
+
+/// Called after any job changes.
+pub type JobListener = Arc<dyn Fn() + Send + Sync>;
+
+/// Log lines kept per job; older ones are dropped.
+const MAX_LOG_LINES: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    /// Parked until the user approves or denies a tool call.
+    AwaitingApproval {
+        tool: String,
+        /// What the tool is about to do, for the approval prompt.
+        detail: String,
+    },
+    Succeeded,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Succeeded | JobStatus::Failed(_) | JobStatus::Cancelled
+        )
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::AwaitingApproval { .. } => "needs approval",
+            JobStatus::Succeeded => "done",
+            JobStatus::Failed(_) => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// One queued agent task.
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: JobId,
+    pub prompt: String,
+    pub workspace: PathBuf,
+    /// Run in a new worktree on a `phaze/<task>` branch.
+    pub use_worktree: bool,
+    pub status: JobStatus,
+    /// Where the agent works: the workspace, or its worktree once created.
+    pub workdir: Option<PathBuf>,
+    pub branch: Option<String>,
+    /// Progress, one line per step or tool call.
+    pub log: Vec<String>,
+    /// The agent's final reply.
+    pub output: String,
+}
+
+struct Shared {
+    jobs: Mutex<Vec<Job>>,
+    approvals: Mutex<HashMap<JobId, oneshot::Sender<bool>>>,
+    cancels: Mutex<HashMap<JobId, Arc<AtomicBool>>>,
+    factory: AgentFactory,
+    listener: JobListener,
+}
+
+impl Shared {
+    fn update(&self, id: JobId, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.lock().unwrap().iter_mut().find(|j| j.id == id) {
+            f(job);
+        }
+        (self.listener)();
+    }
+
+    fn log(&self, id: JobId, line: impl Into<String>) {
+        let line = line.into();
+        self.update(id, |job| {
+            job.log.push(line);
+            if job.log.len() > MAX_LOG_LINES {
+                job.log.remove(0);
+            }
+        });
+    }
+
+    fn job(&self, id: JobId) -> Option<Job> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|j| j.id == id)
+            .cloned()
+    }
+}
+
+/// Runs agent jobs in the background, `max_concurrent` at a time, in the
+/// order they were submitted. Cheap to clone; clones share the queue.
+#[derive(Clone)]
+pub struct JobQueue {
+    shared: Arc<Shared>,
+    submit_tx: mpsc::UnboundedSender<JobId>,
+    next_id: Arc<Mutex<JobId>>,
+}
+
+impl JobQueue {
+    /// Start the queue's runtime thread.
+    pub fn new(max_concurrent: usize, factory: AgentFactory, listener: JobListener) -> Self {
+        let shared = Arc::new(Shared {
+            jobs: Mutex::new(Vec::new()),
+            approvals: Mutex::new(HashMap::new()),
+            cancels: Mutex::new(HashMap::new()),
+            factory,
+            listener,
+        });
+        let (submit_tx, mut submit_rx) = mpsc::unbounded_channel::<JobId>();
+        let dispatch = Arc::clone(&shared);
+        let slots = Arc::new(Semaphore::new(max_concurrent.max(1)));
+        std::thread::Builder::new()
+            .name("phaze-jobs".into())
+            .spawn(move || {
+                let Ok(rt) = tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(2)
+                    .enable_all()
+                    .build()
+                else {
+                    return;
+                };
+                rt.block_on(async move {
+                    // Waiting for a slot before taking the next id keeps it FIFO.
+                    while let Some(id) = submit_rx.recv().await {
+                        let Ok(permit) = Arc::clone(&slots).acquire_owned().await else {
+                            return;
+                        };
+                        let shared = Arc::clone(&dispatch);
+                        tokio::spawn(async move {
+                            run_job(&shared, id).await;
+                            drop(permit);
+                        });
+                    }
+                });
+            })
+            .expect("failed to spawn job queue thread");
+        Self {
+            shared,
+            submit_tx,
+            next_id: Arc::new(Mutex::new(1)),
+        }
+    }
+
+    /// Queue `prompt` to run in `workspace`, or in a new worktree of the
+    /// workspace's repository when `use_worktree` is set.
+    pub fn submit(
+        &self,
+        prompt: impl Into<String>,
+        workspace: PathBuf,
+        use_worktree: bool,
+    ) -> JobId {
+        let id = {
+            let mut next = self.next_id.lock().unwrap();
+            let id = *next;
+            *next += 1;
+            id
+        };
+        self.shared.jobs.lock().unwrap().push(Job {
+            id,
+            prompt: prompt.into(),
+            workspace,
+            use_worktree,
+            status: JobStatus::Queued,
+            workdir: None,
+            branch: None,
+            log: Vec::new(),
+            output: String::new(),
+        });
+        (self.shared.listener)();
+        let _ = self.submit_tx.send(id);
+        id
+    }
+
+    /// Snapshot of every job, oldest first.
+    pub fn jobs(&self) -> Vec<Job> {
+        self.shared.jobs.lock().unwrap().clone()
+    }
+
+    /// Answer a job's pending approval request.
+    pub fn respond(&self, id: JobId, approved: bool) {
+        if let Some(tx) = self.shared.approvals.lock().unwrap().remove(&id) {
+            let _ = tx.send(approved);
+        }
+    }
+
+    /// Stop a job: queued jobs never start, running ones stop after their
+    /// current step, and a pending approval is denied.
+    pub fn cancel(&self, id: JobId) {
+        if let Some(token) = self.shared.cancels.lock().unwrap().get(&id) {
+            token.store(true, Ordering::Relaxed);
+        }
+        self.respond(id, false);
+        self.shared.update(id, |job| {
+            if job.status == JobStatus::Queued {
+                job.status = JobStatus::Cancelled;
+            }
+        });
+    }
+
+    /// Drop finished jobs from the list.
+    pub fn clear_finished(&self) {
+        self.shared
+            .jobs
+            .lock()
+            .unwrap()
+            .retain(|j| !j.status.is_finished());
+        (self.shared.listener)();
+    }
+}
+
+async fn run_job(shared: &Arc<Shared>, id: JobId) {
+    let Some(job) = shared.job(id) else {
+        return;
+    };
+    if job.status != JobStatus::Queued {
+        return;
+    }
+    let cancel = Arc::new(AtomicBool::new(false));
+    shared
+        .cancels
+        .lock()
+        .unwrap()
+        .insert(id, Arc::clone(&cancel));
+    shared.update(id, |job| job.status = JobStatus::Running);
+
+    let result = execute(shared, &job, Arc::clone(&cancel)).await;
+    let status = match result {
+        _ if cancel.load(Ordering::Relaxed) => JobStatus::Cancelled,
+        Ok(()) => JobStatus::Succeeded,
+        Err(e) => JobStatus::Failed(e.to_string()),
+    };
+    shared.cancels.lock().unwrap().remove(&id);
+    shared.approvals.lock().unwrap().remove(&id);
+    shared.update(id, |job| job.status = status);
+}
+
+/// Where a job's agent works, and the prompt to give it there.
+pub(crate) struct Workdir {
+    pub path: PathBuf,
+    /// The task branch, when working in a new worktree.
+    pub branch: Option<String>,
+    pub prompt: String,
+}
+
+/// Resolve the directory to run `prompt` in: `workspace` itself, or a new
+/// task worktree of its repository with the prompt told to stay inside it.
+pub(crate) async fn prepare_workdir(
+    workspace: &Path,
+    use_worktree: bool,
+    prompt: &str,
+) -> Result<Workdir, PhazeError> {
+    if !use_worktree {
+        return Ok(Workdir {
+            path: workspace.to_path_buf(),
+            branch: None,
+            prompt: prompt.to_string(),
+        });
+    }
+    let root = GitOps::find_root(workspace).ok_or_else(|| {
+        PhazeError::Other(format!(
+            "{} is not in a git repository",
+            workspace.display()
+        ))
+    })?;
+    let worktree = GitOps::new(root)
+        .create_task_worktree(prompt)
+        .await
+        .map_err(|e| PhazeError::Other(format!("Failed to create worktree: {e}")))?;
+    let branch = worktree.branch.unwrap_or_default();
+    Ok(Workdir {
+        prompt: format!(
+            "You are working in a git worktree at {} on branch {branch}. \
+             Keep every file you read, write or run inside it.\n\n{prompt}",
+            worktree.path.display()
+        ),
+        path: worktree.path,
+        branch: Some(branch),
+    })
+}
+
+async fn execute(
+    shared: &Arc<Shared>,
+    job: &Job,
+    cancel: Arc<AtomicBool>,
+) -> Result<(), PhazeError> {
+    let id = job.id;
+    let Workdir {
+        path: workdir,
+        branch,
+        prompt,
+    } = prepare_workdir(&job.workspace, job.use_worktree, &job.prompt).await?;
+    if let Some(branch) = branch {
+        shared.log(id, format!("Worktree {} ({branch})", workdir.display()));
+        shared.update(id, |job| job.branch = Some(branch));
+    }
+    shared.update(id, |job| job.workdir = Some(workdir.clone()));
+
+    let approval_shared = Arc::clone(shared);
+    let agent = (shared.factory)(&workdir)?
+        .with_cancel_token(cancel)
+        .with_approval(Box::new(move |tool, params| {
+            let shared = Arc::clone(&approval_shared);
+            Box::pin(async move {
+                let manager = ToolApprovalManager::default();
+                if !manager.needs_approval(&tool, &params) {
+                    return true;
+                }
+                let (tx, rx) = oneshot::channel();
+                shared.approvals.lock().unwrap().insert(id, tx);
+                let detail = manager.format_approval_prompt(&tool, &params);
+                shared.update(id, |job| {
+                    job.status = JobStatus::AwaitingApproval {
+                        tool: tool.clone(),
+                        detail,
+                    }
+                });
+                let approved = rx.await.unwrap_or(false);
+                shared.update(id, |job| job.status = JobStatus::Running);
+                shared.log(
+                    id,
+                    format!("{} {tool}", if approved { "Approved" } else { "Denied" }),
+                );
+                approved
+            })
+        }));
+
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let progress = async {
+        while let Some(event) = event_rx.recv().await {
+            match event {
+                AgentEvent::Thinking { iteration } => shared.log(id, format!("Step {iteration}")),
+                AgentEvent::ToolStart { name } => shared.log(id, format!("▶ {name}")),
+                AgentEvent::ToolResult {
+                    name,
+                    success,
+                    summary,
+                } => {
+                    let mark = if success { "✓" } else { "✗" };
+                    let summary = summary.lines().next().unwrap_or_default().to_string();
+                    shared.log(id, format!("{mark} {name}: {summary}"));
+                }
+                AgentEvent::StreamResumed { reason, .. } => {
+                    shared.log(id, format!("↻ Connection dropped ({reason}), resuming"))
+                }
+                AgentEvent::Error(e) => shared.log(id, format!("Error: {e}")),
+                _ => {}
+            }
+        }
+    };
+    let (result, ()) = tokio::join!(agent.run_with_events(prompt, event_tx), progress);
+    let response = result?;
+    shared.update(id, |job| job.output = response.content);
+    Ok(())
+}
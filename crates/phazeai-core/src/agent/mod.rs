@@ -1,7 +1,14 @@
+pub mod batch;
 mod core;
+pub mod jobs;
 pub mod multi_agent;
+pub mod recording;
+pub mod schedule;
 
+pub use batch::{BatchJob, BatchJobStore, BatchTask};
 pub use core::{Agent, AgentEvent, AgentResponse, ApprovalFn};
+pub use jobs::{AgentFactory, Job, JobId, JobListener, JobQueue, JobStatus};
 pub use multi_agent::{
     AgentRole, AgentRoleResult, AgentTask, MultiAgentEvent, MultiAgentOrchestrator, PipelineResult,
 };
+pub use schedule::{CronSchedule, ScheduledJob, ScheduledRun};
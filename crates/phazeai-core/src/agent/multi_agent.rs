@@ -1,5 +1,5 @@
 use crate::error::PhazeError;
-use crate::llm::{LlmClient, Message, Role};
+use crate::llm::{LlmClient, Message};
 /// Multi-agent orchestrator for PhazeAI.
 /// Runs planner, coder, and reviewer agents ALL locally through Ollama.
 /// Features a self-healing iterative refinement loop: after the Coder writes
@@ -95,6 +95,11 @@ pub enum MultiAgentEvent {
         code: String,
         review: String,
     },
+    /// The pipeline got its own worktree on a fresh task branch
+    WorktreeCreated {
+        path: std::path::PathBuf,
+        branch: String,
+    },
     /// Something went wrong
     Error(String),
 }
@@ -113,6 +118,8 @@ pub struct MultiAgentOrchestrator {
     role_clients: std::collections::HashMap<AgentRole, Arc<dyn LlmClient>>,
     /// Project root path for running build checks
     project_root: Option<String>,
+    /// Run each pipeline in a new git worktree on its own task branch
+    use_worktree: bool,
 }
 
 impl MultiAgentOrchestrator {
@@ -123,6 +130,7 @@ impl MultiAgentOrchestrator {
             max_refinement_iterations: 5,
             role_clients: std::collections::HashMap::new(),
             project_root: None,
+            use_worktree: false,
         }
     }
 
@@ -151,6 +159,15 @@ impl MultiAgentOrchestrator {
         self
     }
 
+    /// Run each pipeline in its own git worktree, on a new `phaze/<task>`
+    /// branch next to the project root's repository, so build checks and
+    /// edits never touch the user's working tree. The worktree is left in
+    /// place for review; see [`PipelineResult::worktree`].
+    pub fn with_worktree(mut self, enabled: bool) -> Self {
+        self.use_worktree = enabled;
+        self
+    }
+
     /// Convenience: get the appropriate client for a role (falls back to default).
     fn client_for_role(&self, role: &AgentRole) -> &Arc<dyn LlmClient> {
         self.role_clients.get(role).unwrap_or(&self.llm)
@@ -163,20 +180,57 @@ impl MultiAgentOrchestrator {
         task: AgentTask,
         event_tx: Option<tokio::sync::mpsc::UnboundedSender<MultiAgentEvent>>,
     ) -> Result<PipelineResult, PhazeError> {
-        if self.full_pipeline {
-            self.execute_full_pipeline(task, event_tx).await
+        let worktree = if self.use_worktree {
+            Some(self.create_worktree(&task, &event_tx).await?)
+        } else {
+            None
+        };
+        let root = match &worktree {
+            Some(path) => Some(path.to_string_lossy().into_owned()),
+            None => self.project_root.clone(),
+        };
+        let mut result = if self.full_pipeline {
+            self.execute_full_pipeline(task, root.as_deref(), event_tx)
+                .await?
         } else {
             // Single-shot: just run the coder directly
             let output = self.run_role(AgentRole::Coder, &task, None).await?;
-            Ok(PipelineResult {
+            PipelineResult {
                 plan: String::new(),
                 code: output.output.clone(),
                 review: String::new(),
                 final_output: output.output,
                 refinement_iterations: 0,
                 clean_build: false,
-            })
-        }
+                worktree: None,
+            }
+        };
+        result.worktree = worktree;
+        Ok(result)
+    }
+
+    /// Create the task worktree for [`with_worktree`](Self::with_worktree).
+    async fn create_worktree(
+        &self,
+        task: &AgentTask,
+        event_tx: &Option<tokio::sync::mpsc::UnboundedSender<MultiAgentEvent>>,
+    ) -> Result<std::path::PathBuf, PhazeError> {
+        let start = self.project_root.as_deref().unwrap_or(".");
+        let repo = crate::git::GitOps::find_root(std::path::Path::new(start)).ok_or_else(|| {
+            PhazeError::Other(format!("Worktree mode needs a git repository: {start}"))
+        })?;
+        let worktree = crate::git::GitOps::new(repo)
+            .create_task_worktree(&task.user_request)
+            .await
+            .map_err(|e| PhazeError::Other(format!("Failed to create worktree: {e}")))?;
+        Self::emit(
+            event_tx,
+            MultiAgentEvent::WorktreeCreated {
+                path: worktree.path.clone(),
+                branch: worktree.branch.clone().unwrap_or_default(),
+            },
+        );
+        Ok(worktree.path)
     }
 
     /// Full pipeline: Planner → Coder → Build Check → Fix Loop → Reviewer
@@ -189,11 +243,12 @@ impl MultiAgentOrchestrator {
     async fn execute_full_pipeline(
         &self,
         mut task: AgentTask,
+        project_root: Option<&str>,
         event_tx: Option<tokio::sync::mpsc::UnboundedSender<MultiAgentEvent>>,
     ) -> Result<PipelineResult, PhazeError> {
         // Auto-generate repo map if project root is set and no repo map provided
         if task.repo_map.is_none() {
-            if let Some(ref root) = self.project_root {
+            if let Some(root) = project_root {
                 let generator = crate::context::RepoMapGenerator::new(root)
                     .with_max_files(200)
                     .with_max_tokens(2048);
@@ -240,7 +295,7 @@ impl MultiAgentOrchestrator {
         for iteration in 1..=self.max_refinement_iterations {
             iterations_used = iteration;
 
-            let build_result = self.run_build_check().await;
+            let build_result = self.run_build_check(project_root).await;
 
             let (success, error_count, warning_count, raw_output) = match build_result {
                 Ok(check) => check,
@@ -310,10 +365,22 @@ impl MultiAgentOrchestrator {
             MultiAgentEvent::AgentStarted(AgentRole::Reviewer),
         );
 
-        let review_context = format!(
+        let mut review_context = format!(
             "## Plan\n{}\n\n## Implementation (after {} refinement iterations, build clean: {})\n{}",
             plan_result.output, iterations_used, clean_build, code_result.output
         );
+        // The project's most complex functions, so the review can say
+        // whether the change made one of them worse.
+        if let Some(root) = project_root {
+            let files = crate::analysis::metrics::measure_workspace(std::path::Path::new(root));
+            let hotspots = crate::analysis::metrics::hotspots(&files, MAX_REVIEW_HOTSPOTS);
+            if !hotspots.is_empty() {
+                review_context.push_str(&format!(
+                    "\n\n## Complexity hotspots\n{}",
+                    crate::analysis::metrics::describe_hotspots(&hotspots)
+                ));
+            }
+        }
         let review_result = self
             .run_role(AgentRole::Reviewer, &task, Some(&review_context))
             .await?;
@@ -330,6 +397,7 @@ impl MultiAgentOrchestrator {
             final_output: code_result.output,
             refinement_iterations: iterations_used,
             clean_build,
+            worktree: None,
         };
 
         Self::emit(
@@ -382,20 +450,7 @@ impl MultiAgentOrchestrator {
         user_msg.push_str("## User Request\n");
         user_msg.push_str(&task.user_request);
 
-        let messages = vec![
-            Message {
-                role: Role::System,
-                content: system_prompt,
-                tool_calls: None,
-                tool_call_id: None,
-            },
-            Message {
-                role: Role::User,
-                content: user_msg,
-                tool_calls: None,
-                tool_call_id: None,
-            },
-        ];
+        let messages = vec![Message::system(system_prompt), Message::user(user_msg)];
 
         let client = self.client_for_role(&role);
         let response = client
@@ -411,10 +466,13 @@ impl MultiAgentOrchestrator {
         })
     }
 
-    /// Run a build check against the project to detect errors and warnings.
+    /// Run a build check in `project_root` to detect errors and warnings.
     /// Returns (success, error_count, warning_count, raw_output).
-    async fn run_build_check(&self) -> Result<(bool, usize, usize, String), PhazeError> {
-        let project_dir = self.project_root.clone().unwrap_or_else(|| ".".to_string());
+    async fn run_build_check(
+        &self,
+        project_root: Option<&str>,
+    ) -> Result<(bool, usize, usize, String), PhazeError> {
+        let project_dir = project_root.unwrap_or(".").to_string();
 
         let project_path = std::path::Path::new(&project_dir);
 
@@ -493,6 +551,8 @@ pub struct PipelineResult {
     pub refinement_iterations: usize,
     /// Whether the final code produced a clean build
     pub clean_build: bool,
+    /// Worktree the pipeline ran in, when worktree mode is on
+    pub worktree: Option<std::path::PathBuf>,
 }
 
 // ── Agent Role Prompts ──────────────────────────────────────
@@ -530,6 +590,9 @@ Your output should be:
 
 Write production-quality code. The REVIEWER agent will check your work."#;
 
+/// Hotspots listed for the Reviewer at most.
+const MAX_REVIEW_HOTSPOTS: usize = 10;
+
 const REVIEWER_PROMPT: &str = r#"You are the REVIEWER agent in PhazeAI's multi-agent system.
 Your job is to review the CODER's implementation for issues.
 
@@ -537,6 +600,7 @@ You will receive:
 - The original plan
 - The code implementation
 - The repo map and relevant files
+- The project's complexity hotspots, when known
 
 Check for:
 1. Correctness: Does the code implement the plan correctly?
@@ -544,6 +608,7 @@ Check for:
 3. Security: Any injection vectors, unsafe operations, secret leaks?
 4. Style: Does it match the existing codebase style?
 5. Performance: Any obvious inefficiencies?
+6. Complexity: Does it add branches to a hotspot that should be split up instead?
 
 Output a brief review:
 - ✅ APPROVED if the code looks good
@@ -0,0 +1,760 @@
+//! Recording and deterministic replay of agent runs.
+//!
+//! A [`RunRecorder`] attached with [`Agent::with_recorder`] captures every
+//! model request with its streamed response, every tool call with its full
+//! output, and the before/after contents of files named in tool params.
+//! The resulting [`RunRecording`] is a self-contained JSON bundle.
+//!
+//! [`replay`] feeds the recorded model turns back through a [`ReplayLlm`]
+//! while the tools run for real inside a sandbox copy of the workspace, then
+//! reports where tool results diverge from the recording. Shell commands are
+//! re-executed but their side effects are not captured, only their output.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::core::Agent;
+use crate::error::PhazeError;
+use crate::llm::{
+    FunctionCall, LlmClient, LlmResponse, Message, ReasoningItem, Role, StreamEvent, ToolCall,
+    Usage,
+};
+use crate::tools::{ToolDefinition, ToolRegistry};
+
+pub const RECORDING_VERSION: u32 = 1;
+
+/// Files larger than this are recorded without contents.
+const MAX_CAPTURE_BYTES: u64 = 1024 * 1024;
+
+/// Param keys that name files a tool may read or modify.
+const PATH_PARAMS: &[&str] = &["path", "source", "destination"];
+
+/// Serializable mirror of [`StreamEvent`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordedStreamEvent {
+    TextDelta {
+        text: String,
+    },
+    ReasoningDelta {
+        text: String,
+    },
+    Reasoning {
+        item: ReasoningItem,
+    },
+    ToolCallStart {
+        id: String,
+        name: String,
+    },
+    ToolCallDelta {
+        id: String,
+        arguments_delta: String,
+    },
+    ToolCallEnd {
+        id: String,
+    },
+    Usage {
+        input_tokens: u32,
+        output_tokens: u32,
+    },
+    Done,
+    Error {
+        message: String,
+    },
+    Dropped {
+        reason: String,
+    },
+}
+
+impl From<&StreamEvent> for RecordedStreamEvent {
+    fn from(ev: &StreamEvent) -> Self {
+        match ev {
+            StreamEvent::TextDelta(text) => Self::TextDelta { text: text.clone() },
+            StreamEvent::ReasoningDelta(text) => Self::ReasoningDelta { text: text.clone() },
+            StreamEvent::Reasoning(item) => Self::Reasoning { item: item.clone() },
+            StreamEvent::ToolCallStart { id, name } => Self::ToolCallStart {
+                id: id.clone(),
+                name: name.clone(),
+            },
+            StreamEvent::ToolCallDelta {
+                id,
+                arguments_delta,
+            } => Self::ToolCallDelta {
+                id: id.clone(),
+                arguments_delta: arguments_delta.clone(),
+            },
+            StreamEvent::ToolCallEnd { id } => Self::ToolCallEnd { id: id.clone() },
+            StreamEvent::Usage(u) => Self::Usage {
+                input_tokens: u.input_tokens,
+                output_tokens: u.output_tokens,
+            },
+            StreamEvent::Done => Self::Done,
+            StreamEvent::Error(message) => Self::Error {
+                message: message.clone(),
+            },
+            StreamEvent::Dropped(reason) => Self::Dropped {
+                reason: reason.clone(),
+            },
+        }
+    }
+}
+
+impl RecordedStreamEvent {
+    fn to_stream_event(&self) -> StreamEvent {
+        match self {
+            Self::TextDelta { text } => StreamEvent::TextDelta(text.clone()),
+            Self::ReasoningDelta { text } => StreamEvent::ReasoningDelta(text.clone()),
+            Self::Reasoning { item } => StreamEvent::Reasoning(item.clone()),
+            Self::ToolCallStart { id, name } => StreamEvent::ToolCallStart {
+                id: id.clone(),
+                name: name.clone(),
+            },
+            Self::ToolCallDelta {
+                id,
+                arguments_delta,
+            } => StreamEvent::ToolCallDelta {
+                id: id.clone(),
+                arguments_delta: arguments_delta.clone(),
+            },
+            Self::ToolCallEnd { id } => StreamEvent::ToolCallEnd { id: id.clone() },
+            Self::Usage {
+                input_tokens,
+                output_tokens,
+            } => StreamEvent::Usage(Usage {
+                input_tokens: *input_tokens,
+                output_tokens: *output_tokens,
+            }),
+            Self::Done => StreamEvent::Done,
+            Self::Error { message } => StreamEvent::Error(message.clone()),
+            Self::Dropped { reason } => StreamEvent::Dropped(reason.clone()),
+        }
+    }
+}
+
+/// One model request and the response that came back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelTurn {
+    pub messages: Vec<Message>,
+    pub events: Vec<RecordedStreamEvent>,
+}
+
+/// State of one file before and after a tool call.
+/// `None` means the file did not exist, or was binary or too large to capture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileEffect {
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRecord {
+    pub name: String,
+    pub params: Value,
+    pub success: bool,
+    /// Full tool output as sent back to the model.
+    pub output: String,
+    /// The user denied this call in the approval prompt.
+    #[serde(default)]
+    pub denied: bool,
+    #[serde(default)]
+    pub effects: Vec<FileEffect>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecording {
+    pub version: u32,
+    pub id: String,
+    pub created_at: String,
+    pub prompt: String,
+    pub workspace: PathBuf,
+    #[serde(default)]
+    pub model: String,
+    pub model_turns: Vec<ModelTurn>,
+    pub tool_calls: Vec<ToolRecord>,
+}
+
+impl RunRecording {
+    pub fn load(path: &Path) -> Result<Self, PhazeError> {
+        let text = std::fs::read_to_string(path)?;
+        let rec: Self = serde_json::from_str(&text)?;
+        if rec.version > RECORDING_VERSION {
+            return Err(PhazeError::Other(format!(
+                "recording version {} is newer than supported ({RECORDING_VERSION})",
+                rec.version
+            )));
+        }
+        Ok(rec)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PhazeError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Default bundle location: `<workspace>/.phazeai/recordings/<id>.json`.
+    pub fn default_path(&self) -> PathBuf {
+        self.workspace
+            .join(".phazeai")
+            .join("recordings")
+            .join(format!("{}.json", self.id))
+    }
+
+    /// The error the recorded run ended with, if its last model turn failed.
+    pub fn final_error(&self) -> Option<&str> {
+        match self.model_turns.last()?.events.last()? {
+            RecordedStreamEvent::Error { message } => Some(message),
+            _ => None,
+        }
+    }
+
+    /// System prompt sent with the first model request, if any.
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.model_turns
+            .first()?
+            .messages
+            .iter()
+            .find(|m| m.role == Role::System)
+            .map(|m| m.content.as_str())
+    }
+}
+
+/// Collects a [`RunRecording`] while an agent runs.
+pub struct RunRecorder {
+    recording: Mutex<RunRecording>,
+}
+
+impl RunRecorder {
+    pub fn new(prompt: impl Into<String>, workspace: impl Into<PathBuf>) -> Arc<Self> {
+        Arc::new(Self {
+            recording: Mutex::new(RunRecording {
+                version: RECORDING_VERSION,
+                id: uuid::Uuid::new_v4().to_string(),
+                created_at: chrono::Utc::now().to_rfc3339(),
+                prompt: prompt.into(),
+                workspace: workspace.into(),
+                model: String::new(),
+                model_turns: Vec::new(),
+                tool_calls: Vec::new(),
+            }),
+        })
+    }
+
+    pub fn set_model(&self, model: impl Into<String>) {
+        if let Ok(mut rec) = self.recording.lock() {
+            rec.model = model.into();
+        }
+    }
+
+    /// A copy of everything recorded so far.
+    pub fn snapshot(&self) -> RunRecording {
+        self.recording
+            .lock()
+            .map(|r| r.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    fn begin_turn(&self, messages: &[Message]) -> usize {
+        let mut rec = self.recording.lock().unwrap_or_else(|e| e.into_inner());
+        rec.model_turns.push(ModelTurn {
+            messages: messages.to_vec(),
+            events: Vec::new(),
+        });
+        rec.model_turns.len() - 1
+    }
+
+    fn push_event(&self, turn: usize, ev: RecordedStreamEvent) {
+        if let Ok(mut rec) = self.recording.lock() {
+            if let Some(t) = rec.model_turns.get_mut(turn) {
+                t.events.push(ev);
+            }
+        }
+    }
+
+    /// Record a failed request so replay fails at the same point.
+    fn push_error(&self, turn: usize, err: &PhazeError) {
+        self.push_event(
+            turn,
+            RecordedStreamEvent::Error {
+                message: err.to_string(),
+            },
+        );
+    }
+
+    fn workspace(&self) -> PathBuf {
+        self.recording
+            .lock()
+            .map(|r| r.workspace.clone())
+            .unwrap_or_default()
+    }
+
+    /// Capture the current contents of every file named in `params`.
+    pub(crate) fn capture_files(&self, params: &Value) -> Vec<(String, Option<String>)> {
+        let workspace = self.workspace();
+        PATH_PARAMS
+            .iter()
+            .filter_map(|k| params.get(*k).and_then(Value::as_str))
+            .map(|p| (p.to_string(), read_capped(&workspace.join(p))))
+            .collect()
+    }
+
+    pub(crate) fn record_tool(
+        &self,
+        name: &str,
+        params: Value,
+        success: bool,
+        output: &str,
+        denied: bool,
+        before: Vec<(String, Option<String>)>,
+    ) {
+        let workspace = self.workspace();
+        let effects = before
+            .into_iter()
+            .map(|(path, before)| {
+                let after = read_capped(&workspace.join(&path));
+                FileEffect {
+                    path,
+                    before,
+                    after,
+                }
+            })
+            .collect();
+        if let Ok(mut rec) = self.recording.lock() {
+            rec.tool_calls.push(ToolRecord {
+                name: name.to_string(),
+                params,
+                success,
+                output: output.to_string(),
+                denied,
+                effects,
+            });
+        }
+    }
+}
+
+fn read_capped(path: &Path) -> Option<String> {
+    let meta = std::fs::metadata(path).ok()?;
+    if !meta.is_file() || meta.len() > MAX_CAPTURE_BYTES {
+        return None;
+    }
+    std::fs::read_to_string(path).ok()
+}
+
+/// Wraps a live client and records each request/response pair.
+pub struct RecordingLlm {
+    inner: Box<dyn LlmClient>,
+    recorder: Arc<RunRecorder>,
+}
+
+impl RecordingLlm {
+    pub fn new(inner: Box<dyn LlmClient>, recorder: Arc<RunRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmClient for RecordingLlm {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        let turn = self.recorder.begin_turn(messages);
+        let resp = self
+            .inner
+            .chat(messages, tools)
+            .await
+            .inspect_err(|e| self.recorder.push_error(turn, e))?;
+        for ev in response_to_events(&resp) {
+            self.recorder.push_event(turn, ev);
+        }
+        Ok(resp)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<futures::channel::mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        let turn = self.recorder.begin_turn(messages);
+        let mut upstream = self
+            .inner
+            .chat_stream(messages, tools)
+            .await
+            .inspect_err(|e| self.recorder.push_error(turn, e))?;
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let recorder = self.recorder.clone();
+        tokio::spawn(async move {
+            while let Some(ev) = upstream.next().await {
+                recorder.push_event(turn, RecordedStreamEvent::from(&ev));
+                if tx.unbounded_send(ev).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(rx)
+    }
+}
+
+fn response_to_events(resp: &LlmResponse) -> Vec<RecordedStreamEvent> {
+    let mut events = Vec::new();
+    if !resp.message.content.is_empty() {
+        events.push(RecordedStreamEvent::TextDelta {
+            text: resp.message.content.clone(),
+        });
+    }
+    for call in resp.message.tool_calls.iter().flatten() {
+        events.push(RecordedStreamEvent::ToolCallStart {
+            id: call.id.clone(),
+            name: call.function.name.clone(),
+        });
+        events.push(RecordedStreamEvent::ToolCallDelta {
+            id: call.id.clone(),
+            arguments_delta: call.function.arguments.clone(),
+        });
+        events.push(RecordedStreamEvent::ToolCallEnd {
+            id: call.id.clone(),
+        });
+    }
+    if let Some(u) = &resp.usage {
+        events.push(RecordedStreamEvent::Usage {
+            input_tokens: u.input_tokens,
+            output_tokens: u.output_tokens,
+        });
+    }
+    events.push(RecordedStreamEvent::Done);
+    events
+}
+
+/// Plays back recorded model turns in order, ignoring the request contents.
+/// Occurrences of the original workspace path are rewritten to `sandbox`.
+pub struct ReplayLlm {
+    turns: Vec<ModelTurn>,
+    next: AtomicUsize,
+    rewrite: Option<(String, String)>,
+}
+
+impl ReplayLlm {
+    pub fn new(recording: &RunRecording, sandbox: Option<&Path>) -> Self {
+        let rewrite = sandbox.map(|s| {
+            (
+                recording.workspace.to_string_lossy().to_string(),
+                s.to_string_lossy().to_string(),
+            )
+        });
+        Self {
+            turns: recording.model_turns.clone(),
+            next: AtomicUsize::new(0),
+            rewrite,
+        }
+    }
+
+    fn next_turn(&self) -> Result<Vec<StreamEvent>, PhazeError> {
+        let idx = self.next.fetch_add(1, Ordering::SeqCst);
+        let turn = self.turns.get(idx).ok_or_else(|| {
+            PhazeError::Llm(format!(
+                "replay diverged: agent made model request #{} but only {} were recorded",
+                idx + 1,
+                self.turns.len()
+            ))
+        })?;
+        Ok(turn
+            .events
+            .iter()
+            .map(|ev| {
+                let mut ev = ev.to_stream_event();
+                if let (
+                    Some((from, to)),
+                    StreamEvent::ToolCallDelta {
+                        arguments_delta, ..
+                    },
+                ) = (&self.rewrite, &mut ev)
+                {
+                    *arguments_delta = arguments_delta.replace(from.as_str(), to);
+                }
+                ev
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmClient for ReplayLlm {
+    async fn chat(
+        &self,
+        _messages: &[Message],
+        _tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        let mut content = String::new();
+        let mut calls: Vec<ToolCall> = Vec::new();
+        let mut usage = None;
+        let mut reasoning = Vec::new();
+        for ev in self.next_turn()? {
+            match ev {
+                StreamEvent::TextDelta(t) => content.push_str(&t),
+                StreamEvent::Reasoning(item) => reasoning.push(item),
+                StreamEvent::ToolCallStart { id, name } => calls.push(ToolCall {
+                    id,
+                    call_type: "function".to_string(),
+                    function: FunctionCall {
+                        name,
+                        arguments: String::new(),
+                    },
+                }),
+                StreamEvent::ToolCallDelta {
+                    id,
+                    arguments_delta,
+                } => {
+                    if let Some(c) = calls.iter_mut().find(|c| c.id == id) {
+                        c.function.arguments.push_str(&arguments_delta);
+                    }
+                }
+                StreamEvent::Usage(u) => usage = Some(u),
+                StreamEvent::Error(e) => return Err(PhazeError::Llm(e)),
+                StreamEvent::Dropped(reason) => {
+                    return Err(PhazeError::Llm(format!("Stream dropped: {reason}")))
+                }
+                StreamEvent::ReasoningDelta(_)
+                | StreamEvent::ToolCallEnd { .. }
+                | StreamEvent::Done => {}
+            }
+        }
+        let message = if calls.is_empty() {
+            Message::assistant(content)
+        } else {
+            Message::assistant_with_tools(content, calls)
+        }
+        .with_reasoning(reasoning);
+        Ok(LlmResponse { message, usage })
+    }
+
+    async fn chat_stream(
+        &self,
+        _messages: &[Message],
+        _tools: &[ToolDefinition],
+    ) -> Result<futures::channel::mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        for ev in self.next_turn()? {
+            let _ = tx.unbounded_send(ev);
+        }
+        Ok(rx)
+    }
+}
+
+/// A place where the replayed run differs from the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// Index of the tool call (0-based).
+    pub index: usize,
+    pub tool: String,
+    /// What differs: `name`, `success`, `output`, `file:<path>` or `count`.
+    pub what: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    pub sandbox: PathBuf,
+    pub tool_calls: usize,
+    pub final_text: String,
+    /// Set when the replayed agent loop itself failed.
+    pub error: Option<String>,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    pub fn is_faithful(&self) -> bool {
+        self.error.is_none() && self.divergences.is_empty()
+    }
+}
+
+/// Copy the non-ignored files of `src` into `dst` (respects `.gitignore`).
+pub fn copy_workspace(src: &Path, dst: &Path) -> Result<usize, PhazeError> {
+    let mut copied = 0;
+    for entry in ignore::WalkBuilder::new(src)
+        .hidden(false)
+        .build()
+        .flatten()
+    {
+        let path = entry.path();
+        let Ok(rel) = path.strip_prefix(src) else {
+            continue;
+        };
+        if rel.as_os_str().is_empty() || rel.starts_with(".git") {
+            continue;
+        }
+        let target = dst.join(rel);
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            std::fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_some_and(|t| t.is_file()) {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(path, &target)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Put every file a recorded tool touched back into its pre-run state.
+fn restore_pre_state(recording: &RunRecording, sandbox: &Path) -> Result<(), PhazeError> {
+    let mut seen = std::collections::HashSet::new();
+    for effect in recording.tool_calls.iter().flat_map(|t| &t.effects) {
+        if !seen.insert(effect.path.clone()) {
+            continue;
+        }
+        let path = Path::new(&effect.path);
+        let rel = match path.strip_prefix(&recording.workspace) {
+            Ok(rel) => rel,
+            // Never touch files outside the workspace.
+            Err(_) if path.is_absolute() => continue,
+            Err(_) => path,
+        };
+        let target = sandbox.join(rel);
+        match &effect.before {
+            Some(content) => {
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&target, content)?;
+            }
+            None if target.is_file() => std::fs::remove_file(&target)?,
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Compare recorded tool calls against a replay. `sandbox` paths in the
+/// replayed output are mapped back to the recorded workspace first.
+pub fn compare_tool_calls(
+    expected: &[ToolRecord],
+    actual: &[ToolRecord],
+    workspace: &Path,
+    sandbox: &Path,
+) -> Vec<Divergence> {
+    let ws = workspace.to_string_lossy();
+    let sb = sandbox.to_string_lossy();
+    let normalize = |s: &str| s.replace(sb.as_ref(), ws.as_ref());
+    let mut out = Vec::new();
+
+    for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+        let mut push = |what: String, expected: String, actual: String| {
+            out.push(Divergence {
+                index: i,
+                tool: e.name.clone(),
+                what,
+                expected,
+                actual,
+            })
+        };
+        if e.name != a.name {
+            push("name".into(), e.name.clone(), a.name.clone());
+            continue;
+        }
+        if e.success != a.success {
+            push(
+                "success".into(),
+                e.success.to_string(),
+                a.success.to_string(),
+            );
+        }
+        let actual_output = normalize(&a.output);
+        if e.output != actual_output {
+            push("output".into(), e.output.clone(), actual_output);
+        }
+        for (ee, ae) in e.effects.iter().zip(&a.effects) {
+            if ee.after != ae.after {
+                push(
+                    format!("file:{}", ee.path),
+                    ee.after.clone().unwrap_or_default(),
+                    ae.after.clone().unwrap_or_default(),
+                );
+            }
+        }
+    }
+    if expected.len() != actual.len() {
+        out.push(Divergence {
+            index: expected.len().min(actual.len()),
+            tool: String::new(),
+            what: "count".into(),
+            expected: expected.len().to_string(),
+            actual: actual.len().to_string(),
+        });
+    }
+    out
+}
+
+/// Re-run a recording inside `sandbox` and report divergences.
+///
+/// The sandbox is populated with a copy of the recorded workspace, with
+/// touched files reset to their recorded pre-run contents. The process
+/// working directory is switched to the sandbox for the duration of the run.
+pub async fn replay(recording: &RunRecording, sandbox: &Path) -> Result<ReplayReport, PhazeError> {
+    std::fs::create_dir_all(sandbox)?;
+    let sandbox = sandbox.canonicalize()?;
+    if recording.workspace.is_dir() {
+        copy_workspace(&recording.workspace, &sandbox)?;
+    }
+    restore_pre_state(recording, &sandbox)?;
+
+    let prev_cwd = std::env::current_dir()?;
+    std::env::set_current_dir(&sandbox)?;
+
+    let denials: Arc<Vec<bool>> = Arc::new(recording.tool_calls.iter().map(|t| t.denied).collect());
+    let approval_idx = Arc::new(AtomicUsize::new(0));
+    let recorder = RunRecorder::new(recording.prompt.clone(), sandbox.clone());
+
+    // Tools are constructed after the cwd switch so the shell starts in the sandbox.
+    let mut agent = Agent::new(Box::new(ReplayLlm::new(recording, Some(&sandbox))))
+        .with_tools(ToolRegistry::default())
+        .with_approval(Box::new(move |_name, _params| {
+            let i = approval_idx.fetch_add(1, Ordering::SeqCst);
+            let approved = !denials.get(i).copied().unwrap_or(false);
+            Box::pin(async move { approved })
+        }));
+    if let Some(system) = recording.system_prompt() {
+        agent = agent.with_system_prompt(system);
+    }
+    let agent = agent.with_recorder(recorder.clone());
+
+    let result = agent.run(recording.prompt.clone()).await;
+    let _ = std::env::set_current_dir(prev_cwd);
+
+    let replayed = recorder.snapshot();
+    let divergences = compare_tool_calls(
+        &recording.tool_calls,
+        &replayed.tool_calls,
+        &recording.workspace,
+        &sandbox,
+    );
+    let (final_text, error) = match result {
+        Ok(resp) => (resp.content, None),
+        // A recorded failure that replays the same way is a faithful replay.
+        Err(e)
+            if recording
+                .final_error()
+                .is_some_and(|m| e.to_string().contains(m)) =>
+        {
+            (String::new(), None)
+        }
+        Err(e) => (String::new(), Some(e.to_string())),
+    };
+    Ok(ReplayReport {
+        sandbox,
+        tool_calls: replayed.tool_calls.len(),
+        final_text,
+        error,
+        divergences,
+    })
+}
@@ -0,0 +1,301 @@
+//! Scheduled agent jobs.
+//!
+//! A [`ScheduledJob`] pairs a prompt with a cron expression, e.g. a nightly
+//! "summarize new TODOs". Schedules live in [`Settings`](crate::Settings);
+//! `phazeai daemon` checks them every minute and runs the due ones with
+//! [`run_scheduled`], which saves each run to conversation history.
+//!
+//! Nobody is there to approve tool calls, so a run may only read unless
+//! its schedule lists the tools it may use in `allowed_tools`.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use super::jobs::{prepare_workdir, AgentFactory, Workdir};
+use super::ApprovalFn;
+use crate::context::{ConversationStore, SavedConversation, SavedMessage, TitleSource};
+use crate::error::PhazeError;
+use crate::tools::{ToolApprovalManager, ToolPermission};
+
+/// Tag on every conversation saved by a scheduled run.
+pub const SCHEDULED_TAG: &str = "scheduled";
+
+/// How far ahead [`CronSchedule::next_after`] looks before giving up, enough
+/// for a February 29th schedule.
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+fn default_true() -> bool {
+    true
+}
+
+/// An agent prompt that runs on a cron schedule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    /// Unique name, used on the command line.
+    pub name: String,
+    /// Five-field cron expression or an alias such as `@nightly`.
+    pub cron: String,
+    pub prompt: String,
+    pub workspace: PathBuf,
+    /// Run in a new worktree on a `phaze/<task>` branch.
+    #[serde(default)]
+    pub use_worktree: bool,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Tools the run may use besides read-only ones, e.g. `write_file` or
+    /// `bash`. Destructive commands are refused regardless.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+}
+
+impl ScheduledJob {
+    pub fn schedule(&self) -> Result<CronSchedule, String> {
+        CronSchedule::parse(&self.cron)
+    }
+
+    /// Whether the job should run in the minute starting at `at`.
+    pub fn is_due(&self, at: NaiveDateTime) -> bool {
+        self.enabled && self.schedule().is_ok_and(|s| s.matches(at))
+    }
+
+    /// When the job runs next after `after`; `None` when it's disabled or its
+    /// schedule is invalid or never fires.
+    pub fn next_run(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        if !self.enabled {
+            return None;
+        }
+        self.schedule().ok()?.next_after(after)
+    }
+}
+
+/// A parsed cron expression: `minute hour day-of-month month day-of-week`.
+///
+/// Fields take `*`, numbers, ranges (`1-5`), lists (`1,15`) and steps
+/// (`*/15`, `0-30/10`); months and weekdays also take three-letter names.
+/// As in cron, when both day fields are restricted a day matching either
+/// one fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let expanded = match expr.to_ascii_lowercase().as_str() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" | "@nightly" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            a if a.starts_with('@') => return Err(format!("Unknown schedule alias {expr}")),
+            _ => expr,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "Expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAYS, "weekday")?;
+        // Both 0 and 7 are Sunday.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], "minute")?,
+            hours: parse_field(hour, 0, 23, &[], "hour")?,
+            days: parse_field(day, 1, 31, &[], "day")?,
+            months: parse_field(month, 1, 12, &MONTHS, "month")?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// Whether the schedule fires in the minute starting at `t`.
+    pub fn matches(&self, t: NaiveDateTime) -> bool {
+        self.minutes & (1 << t.minute()) != 0
+            && self.hours & (1 << t.hour()) != 0
+            && self.day_matches(t)
+    }
+
+    fn day_matches(&self, t: NaiveDateTime) -> bool {
+        if self.months & (1 << t.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << t.day()) != 0;
+        let weekday = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+
+    /// The first minute after `after` that the schedule fires in.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(MAX_LOOKAHEAD_DAYS);
+        while t <= limit {
+            if !self.day_matches(t) {
+                t = (t.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+/// Parse one cron field into a bitmask of the values it allows.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], what: &str) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let lower = text.to_ascii_lowercase();
+        let n = match names.iter().position(|n| *n == lower) {
+            // Month names count from 1, weekday names from 0.
+            Some(i) => i as u32 + min,
+            None => text
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid {what} '{text}'"))?,
+        };
+        if n < min || n > max {
+            return Err(format!("{what} {n} is out of range {min}-{max}"));
+        }
+        Ok(n)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(|| format!("Invalid step in {what} '{part}'"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value(a)?, value(b)?)
+        } else {
+            let start = value(range)?;
+            // `5/10` means every 10th value from 5.
+            (start, if part.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(format!("Empty {what} range '{range}'"));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            mask |= 1 << n;
+        }
+    }
+    Ok(mask)
+}
+
+/// Approval for unattended runs: read-only tools, and the tools in
+/// `allowed` unless what they'd do is destructive. Shell commands are only
+/// guessed to be read-only from how they start, so they need `bash`
+/// allowed either way.
+pub fn unattended_approval(allowed: &[String]) -> ApprovalFn {
+    let allowed: HashSet<String> = allowed.iter().cloned().collect();
+    Box::new(move |tool, params| {
+        let approved = match ToolApprovalManager::default().classify_tool(&tool, &params) {
+            ToolPermission::Destructive => false,
+            ToolPermission::ReadOnly if tool != "bash" => true,
+            _ => allowed.contains(&tool),
+        };
+        Box::pin(async move { approved })
+    })
+}
+
+/// A finished scheduled run.
+#[derive(Debug, Clone)]
+pub struct ScheduledRun {
+    /// The run as saved to history.
+    pub conversation: SavedConversation,
+    /// Why the run failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Run `job` once, unattended, with the agent `factory` builds, and save the
+/// prompt and reply (or the failure) to `store` tagged [`SCHEDULED_TAG`].
+/// Only failing to save is an error.
+pub async fn run_scheduled(
+    job: &ScheduledJob,
+    factory: &AgentFactory,
+    model: &str,
+    store: &ConversationStore,
+) -> Result<ScheduledRun, PhazeError> {
+    let started = chrono::Local::now();
+    let mut conversation = SavedConversation::new(
+        ConversationStore::generate_id(),
+        format!("{} ({})", job.name, started.format("%Y-%m-%d %H:%M")),
+        model.to_string(),
+        Some(job.workspace.display().to_string()),
+        None,
+    );
+    conversation.metadata.title_source = TitleSource::Generated;
+    conversation.metadata.tags = vec![SCHEDULED_TAG.to_string()];
+    conversation.add_message(SavedMessage::user(job.prompt.clone()));
+
+    let outcome = async {
+        let Workdir {
+            path,
+            branch,
+            prompt,
+        } = prepare_workdir(&job.workspace, job.use_worktree, &job.prompt).await?;
+        if let Some(branch) = branch {
+            conversation.add_message(SavedMessage::system(format!(
+                "Ran in worktree {} on branch {branch}",
+                path.display()
+            )));
+        }
+        let agent = factory(&path)?.with_approval(unattended_approval(&job.allowed_tools));
+        agent.run(prompt).await
+    }
+    .await;
+
+    let error = match outcome {
+        Ok(response) => {
+            conversation.add_message(SavedMessage::assistant(response.content));
+            None
+        }
+        Err(e) => {
+            let error = e.to_string();
+            conversation.add_message(SavedMessage::assistant(format!(
+                "Scheduled run failed: {error}"
+            )));
+            conversation.metadata.tags.push("failed".to_string());
+            Some(error)
+        }
+    };
+    store.save(&conversation)?;
+    Ok(ScheduledRun {
+        conversation,
+        error,
+    })
+}
@@ -0,0 +1,75 @@
+//! Short explanations of shell commands, shown when hovering a command in the
+//! terminal or in chat.
+//!
+//! Explanations come from a cheap model (the `quick_answer` route when
+//! configured) and are cached per normalized command string for the life of
+//! the process, so hovering the same command again is free.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+
+/// Longest command sent for explanation, in characters.
+const MAX_COMMAND_CHARS: usize = 500;
+/// Explanations kept in the cache before it is cleared.
+const MAX_CACHED: usize = 512;
+
+static CACHE: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(Default::default);
+
+const EXPLAIN_PROMPT: &str = "You explain shell commands to developers. \
+Say in one sentence what the command does, then list each flag or argument \
+that matters as `flag: meaning`, one per line. Mention anything destructive. \
+No preamble, at most 8 lines.";
+
+/// The command as it is cached and explained: trimmed, without a leading
+/// `$ ` prompt and with runs of whitespace collapsed. `None` for empty or
+/// overly long input.
+pub fn normalize_command(command: &str) -> Option<String> {
+    let command = command.trim();
+    let command = match command.strip_prefix('$') {
+        Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => rest,
+        _ => command,
+    };
+    let normalized = command.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!normalized.is_empty() && normalized.chars().count() <= MAX_COMMAND_CHARS)
+        .then_some(normalized)
+}
+
+/// The command typed on a terminal prompt line, i.e. the text after the
+/// prompt's `$ `, `# `, `% `, `> ` or `❯ ` terminator.
+pub fn command_from_prompt_line(line: &str) -> Option<String> {
+    let end = ["$ ", "# ", "% ", "> ", "❯ "]
+        .iter()
+        .filter_map(|t| line.find(t).map(|i| i + t.len()))
+        .min()?;
+    normalize_command(&line[end..])
+}
+
+/// A cached explanation for `command`, if it was explained before.
+pub fn cached_explanation(command: &str) -> Option<String> {
+    let key = normalize_command(command)?;
+    CACHE.lock().unwrap().get(&key).cloned()
+}
+
+/// Explain `command` with `llm`, or return the cached explanation.
+pub async fn explain_command(llm: &dyn LlmClient, command: &str) -> Result<String, PhazeError> {
+    let key =
+        normalize_command(command).ok_or_else(|| PhazeError::Other("nothing to explain".into()))?;
+    if let Some(cached) = CACHE.lock().unwrap().get(&key) {
+        return Ok(cached.clone());
+    }
+    let messages = vec![Message::system(EXPLAIN_PROMPT), Message::user(key.clone())];
+    let response = llm.chat(&messages, &[]).await?;
+    let explanation = response.message.content.trim().to_string();
+    if explanation.is_empty() {
+        return Err(PhazeError::Llm("empty explanation".into()));
+    }
+    let mut cache = CACHE.lock().unwrap();
+    if cache.len() >= MAX_CACHED {
+        cache.clear();
+    }
+    cache.insert(key, explanation.clone());
+    Ok(explanation)
+}
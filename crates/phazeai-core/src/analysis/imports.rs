@@ -0,0 +1,733 @@
+//! The workspace's import graph: which source files depend on which.
+//!
+//! [`scan_workspace`] reads every Rust, Python, JS/TS and Go file git
+//! wouldn't ignore, along with the manifests (`Cargo.toml`, `package.json`,
+//! `pyproject.toml`, `go.mod`) that group them into packages, and
+//! [`ImportGraph::build`] resolves each file's `mod`/`use`, `import`,
+//! `require` and `from` statements to the files they name. Imports of
+//! anything outside the workspace (std, third-party packages) are left out.
+//! [`ImportGraph::layout`] places the modules on a grid for drawing, and
+//! [`ImportGraph::describe`] renders a graph for the agent.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::progress;
+
+/// Files larger than this are skipped (generated or vendored code).
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+
+/// Source files read before the scan stops.
+pub const MAX_MODULES: usize = 3000;
+
+/// Package of files no manifest claims.
+pub const NO_PACKAGE: &str = "(workspace)";
+
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// A package: the directory of a manifest, and the name it declares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    /// Relative to the workspace root; empty for the root itself.
+    pub dir: PathBuf,
+    pub name: String,
+    /// What code imports it as when that isn't `name`: a Rust crate's
+    /// `[lib] name`, or the `module` path from `go.mod` that Go imports
+    /// are prefixed with.
+    pub import_name: Option<String>,
+}
+
+/// A source file in the graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    /// Relative to the workspace root.
+    pub path: PathBuf,
+    /// Name of the package it belongs to, or [`NO_PACKAGE`].
+    pub package: String,
+}
+
+/// Modules and the imports between them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportGraph {
+    /// Sorted by path.
+    pub modules: Vec<Module>,
+    /// `(importer, imported)` indices into `modules`, sorted, no repeats.
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// The package a manifest declares, if it declares one: `file_name` is the
+/// manifest's (e.g. `Cargo.toml`), `content` its text. A Cargo workspace
+/// root without a `[package]` isn't a package.
+pub fn parse_manifest(dir: &Path, file_name: &str, content: &str) -> Option<Package> {
+    let toml_name = |tables: &[&str]| {
+        let value: toml::Value = toml::from_str(content).ok()?;
+        tables.iter().find_map(|table| {
+            let mut v = &value;
+            for key in table.split('.') {
+                v = v.get(key)?;
+            }
+            v.get("name")?.as_str().map(str::to_string)
+        })
+    };
+    let (name, import_name) = match file_name {
+        "Cargo.toml" => (toml_name(&["package"])?, toml_name(&["lib"])),
+        "pyproject.toml" => (toml_name(&["project", "tool.poetry"])?, None),
+        "package.json" => {
+            let value: serde_json::Value = serde_json::from_str(content).ok()?;
+            (value.get("name")?.as_str()?.to_string(), None)
+        }
+        "go.mod" => {
+            let module = content
+                .lines()
+                .find_map(|l| l.trim().strip_prefix("module "))?
+                .trim()
+                .trim_matches('"')
+                .to_string();
+            let name = module.rsplit('/').next().unwrap_or(&module).to_string();
+            (name, Some(module))
+        }
+        _ => return None,
+    };
+    Some(Package {
+        dir: dir.to_path_buf(),
+        name,
+        import_name,
+    })
+}
+
+/// The import graph of every source file under `root` that git wouldn't
+/// ignore. Stops reading after [`MAX_MODULES`] files.
+pub fn scan_workspace(root: &Path) -> ImportGraph {
+    let task = progress::global()
+        .task("Building import graph")
+        .unit("files")
+        .cancellable()
+        .start();
+    let mut sources = Vec::new();
+    let mut packages = Vec::new();
+    let walker = WalkBuilder::new(root).require_git(false).build();
+    for entry in walker.flatten() {
+        if task.is_cancelled() || sources.len() >= MAX_MODULES {
+            break;
+        }
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let file_name = relative.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let is_manifest = matches!(
+            file_name,
+            "Cargo.toml" | "pyproject.toml" | "package.json" | "go.mod"
+        );
+        if !is_manifest && language(relative).is_none() {
+            continue;
+        }
+        if !entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        if is_manifest {
+            let dir = relative.parent().unwrap_or(Path::new(""));
+            packages.extend(parse_manifest(dir, file_name, &content));
+        } else {
+            task.advance(1);
+            sources.push((relative.to_path_buf(), content));
+        }
+    }
+    ImportGraph::build(&sources, &packages)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Lang {
+    Rust,
+    Python,
+    Js,
+    Go,
+}
+
+fn language(path: &Path) -> Option<Lang> {
+    match path.extension()?.to_str()? {
+        "rs" => Some(Lang::Rust),
+        "py" => Some(Lang::Python),
+        "go" => Some(Lang::Go),
+        ext if JS_EXTENSIONS.contains(&ext) => Some(Lang::Js),
+        _ => None,
+    }
+}
+
+impl ImportGraph {
+    /// Resolve the imports of `sources` (relative path, content) among
+    /// themselves; `packages` say which package each file belongs to.
+    pub fn build(sources: &[(PathBuf, String)], packages: &[Package]) -> Self {
+        let mut sources: Vec<&(PathBuf, String)> = sources.iter().collect();
+        sources.sort_by(|a, b| a.0.cmp(&b.0));
+        sources.dedup_by(|a, b| a.0 == b.0);
+        let index: HashMap<&Path, usize> = sources
+            .iter()
+            .enumerate()
+            .map(|(i, (path, _))| (path.as_path(), i))
+            .collect();
+        let resolver = Resolver {
+            files: index.keys().copied().collect(),
+            packages,
+        };
+
+        let modules = sources
+            .iter()
+            .map(|(path, _)| Module {
+                path: path.clone(),
+                package: resolver
+                    .package_of(path)
+                    .map_or(NO_PACKAGE.to_string(), |p| p.name.clone()),
+            })
+            .collect();
+        let mut edges = BTreeSet::new();
+        for (from, (path, content)) in sources.iter().enumerate() {
+            for target in resolver.imports(path, content) {
+                if let Some(&to) = index.get(target.as_path()) {
+                    if to != from {
+                        edges.insert((from, to));
+                    }
+                }
+            }
+        }
+        Self {
+            modules,
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// Names of the packages with modules in the graph, sorted.
+    pub fn packages(&self) -> Vec<String> {
+        let names: BTreeSet<&str> = self.modules.iter().map(|m| m.package.as_str()).collect();
+        names.into_iter().map(str::to_string).collect()
+    }
+
+    /// The graph of the modules `keep` accepts and the imports among them.
+    pub fn subgraph(&self, keep: impl Fn(&Module) -> bool) -> Self {
+        let mut renumbered = vec![None; self.modules.len()];
+        let mut modules = Vec::new();
+        for (i, module) in self.modules.iter().enumerate() {
+            if keep(module) {
+                renumbered[i] = Some(modules.len());
+                modules.push(module.clone());
+            }
+        }
+        let edges = self
+            .edges
+            .iter()
+            .filter_map(|&(a, b)| Some((renumbered[a]?, renumbered[b]?)))
+            .collect();
+        Self { modules, edges }
+    }
+
+    /// `module`, what it imports and what imports it.
+    pub fn neighborhood(&self, module: usize) -> Self {
+        let mut near: HashSet<&Path> = HashSet::from([self.modules[module].path.as_path()]);
+        for &(a, b) in &self.edges {
+            if a == module {
+                near.insert(&self.modules[b].path);
+            } else if b == module {
+                near.insert(&self.modules[a].path);
+            }
+        }
+        self.subgraph(|m| near.contains(m.path.as_path()))
+    }
+
+    /// A `(column, row)` cell for each module: a module's column is one past
+    /// the furthest column of anything it imports, so imports point left,
+    /// and within a column modules are ordered by package and path. Imports
+    /// that close a cycle are ignored for the placement.
+    pub fn layout(&self) -> Vec<(u32, u32)> {
+        let n = self.modules.len();
+        let mut imports: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for &(a, b) in &self.edges {
+            imports[a].push(b);
+        }
+        // Depth-first, placing each module after everything it imports.
+        let mut column: Vec<Option<u32>> = vec![None; n];
+        let mut on_path = vec![false; n];
+        for start in 0..n {
+            if column[start].is_some() {
+                continue;
+            }
+            let mut stack = vec![(start, 0)];
+            on_path[start] = true;
+            while let Some(top) = stack.last_mut() {
+                let node = top.0;
+                if let Some(&dep) = imports[node].get(top.1) {
+                    top.1 += 1;
+                    if column[dep].is_none() && !on_path[dep] {
+                        on_path[dep] = true;
+                        stack.push((dep, 0));
+                    }
+                    continue;
+                }
+                let col = imports[node]
+                    .iter()
+                    .filter_map(|&dep| column[dep])
+                    .map(|c| c + 1)
+                    .max()
+                    .unwrap_or(0);
+                column[node] = Some(col);
+                on_path[node] = false;
+                stack.pop();
+            }
+        }
+
+        let mut by_column: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        for (i, col) in column.iter().enumerate() {
+            by_column.entry(col.unwrap_or(0)).or_default().push(i);
+        }
+        let mut cells = vec![(0, 0); n];
+        for (col, mut members) in by_column {
+            members.sort_by(|&a, &b| {
+                let (a, b) = (&self.modules[a], &self.modules[b]);
+                (&a.package, &a.path).cmp(&(&b.package, &b.path))
+            });
+            for (row, i) in members.into_iter().enumerate() {
+                cells[i] = (col, row as u32);
+            }
+        }
+        cells
+    }
+
+    /// The graph as text for the agent: each module with the modules it
+    /// imports, grouped by package.
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        let mut package = None;
+        for (i, module) in self.modules.iter().enumerate() {
+            if package != Some(&module.package) {
+                package = Some(&module.package);
+                out.push_str(&format!("package {}:\n", module.package));
+            }
+            let imports: Vec<String> = self
+                .edges
+                .iter()
+                .filter(|(a, _)| *a == i)
+                .map(|&(_, b)| self.modules[b].path.display().to_string())
+                .collect();
+            if imports.is_empty() {
+                out.push_str(&format!("  {}\n", module.path.display()));
+            } else {
+                out.push_str(&format!(
+                    "  {} -> {}\n",
+                    module.path.display(),
+                    imports.join(", ")
+                ));
+            }
+        }
+        out
+    }
+}
+
+struct Resolver<'a> {
+    files: HashSet<&'a Path>,
+    packages: &'a [Package],
+}
+
+impl Resolver<'_> {
+    /// The package with the deepest directory containing `path`.
+    fn package_of(&self, path: &Path) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|p| path.starts_with(&p.dir))
+            .max_by_key(|p| p.dir.components().count())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.contains(path)
+    }
+
+    /// Files `path`'s imports name, whether or not they exist.
+    fn imports(&self, path: &Path, content: &str) -> Vec<PathBuf> {
+        match language(path) {
+            Some(Lang::Rust) => self.rust_imports(path, content),
+            Some(Lang::Python) => self.python_imports(path, content),
+            Some(Lang::Js) => self.js_imports(path, content),
+            Some(Lang::Go) => self.go_imports(content),
+            None => Vec::new(),
+        }
+    }
+
+    // ── Rust ──────────────────────────────────────────────────
+
+    fn rust_imports(&self, path: &Path, content: &str) -> Vec<PathBuf> {
+        let package = self.package_of(path);
+        let src = package.map_or_else(|| PathBuf::from("src"), |p| p.dir.join("src"));
+        let own_dir = rust_module_dir(path);
+        let mut out = Vec::new();
+        for statement in rust_statements(content) {
+            if let Some(name) = statement
+                .strip_prefix("mod ")
+                .filter(|rest| !rest.contains('{'))
+            {
+                let name = name.trim_end_matches(';').trim();
+                out.extend(self.rust_file(&own_dir, &[name.to_string()]));
+                continue;
+            }
+            let Some(tree) = statement.split_once("use ").map(|(_, t)| t) else {
+                continue;
+            };
+            for segments in expand_use_tree(tree.trim_end_matches(';')) {
+                let Some((first, rest)) = segments.split_first() else {
+                    continue;
+                };
+                let base = match first.as_str() {
+                    "crate" if path.starts_with(&src) => src.clone(),
+                    "self" => own_dir.clone(),
+                    "super" => {
+                        let mut base = own_dir.parent().map(Path::to_path_buf);
+                        let mut rest = rest;
+                        while rest.first().is_some_and(|s| s == "super") {
+                            base = base.and_then(|b| b.parent().map(Path::to_path_buf));
+                            rest = &rest[1..];
+                        }
+                        if let Some(base) = base {
+                            out.extend(self.rust_file(&base, rest));
+                        }
+                        continue;
+                    }
+                    other => {
+                        let crate_name = |p: &&Package| match &p.import_name {
+                            Some(lib) => lib == other,
+                            None => p.name.replace('-', "_") == other,
+                        };
+                        match self.packages.iter().find(crate_name) {
+                            Some(p) => p.dir.join("src"),
+                            None => continue,
+                        }
+                    }
+                };
+                out.extend(self.rust_file(&base, rest));
+            }
+        }
+        out
+    }
+
+    /// The file of the longest prefix of `segments` that is a module under
+    /// `dir`, or else of the module `dir` holds the children of.
+    fn rust_file(&self, dir: &Path, segments: &[String]) -> Option<PathBuf> {
+        (0..=segments.len()).rev().find_map(|n| {
+            let mut module = dir.to_path_buf();
+            module.extend(&segments[..n]);
+            let mut candidates = vec![module.with_extension("rs"), module.join("mod.rs")];
+            if n == 0 {
+                candidates.extend([module.join("lib.rs"), module.join("main.rs")]);
+            }
+            candidates.into_iter().find(|f| self.exists(f))
+        })
+    }
+
+    // ── Python ────────────────────────────────────────────────
+
+    fn python_imports(&self, path: &Path, content: &str) -> Vec<PathBuf> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let package_dir = self
+            .package_of(path)
+            .map_or(PathBuf::new(), |p| p.dir.clone());
+        let roots = [package_dir.join("src"), package_dir, PathBuf::new()];
+        let mut out = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("import ") {
+                for module in rest.split(',') {
+                    let module = module.split(" as ").next().unwrap_or("").trim();
+                    out.extend(self.python_module(&roots, module));
+                }
+            } else if let Some((from, names)) = line
+                .strip_prefix("from ")
+                .and_then(|rest| rest.split_once(" import "))
+            {
+                let from = from.trim();
+                let dots = from.chars().take_while(|&c| c == '.').count();
+                let names: Vec<&str> = names
+                    .trim_matches(|c| c == '(' || c == ')' || c == ' ')
+                    .split(',')
+                    .map(|n| n.split(" as ").next().unwrap_or("").trim())
+                    .filter(|n| !n.is_empty() && *n != "*")
+                    .collect();
+                let bases: Vec<PathBuf> = if dots > 0 {
+                    let mut base = Some(dir);
+                    for _ in 1..dots {
+                        base = base.and_then(Path::parent);
+                    }
+                    base.map(|b| vec![b.to_path_buf()]).unwrap_or_default()
+                } else {
+                    roots.to_vec()
+                };
+                let module = &from[dots..];
+                // `from pkg import sub` imports the submodule when there is one.
+                let mut found = false;
+                for name in &names {
+                    let dotted = if module.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{module}.{name}")
+                    };
+                    if let Some(file) = self.python_exact(&bases, &dotted) {
+                        out.push(file);
+                        found = true;
+                    }
+                }
+                if !found && !module.is_empty() {
+                    out.extend(self.python_module(&bases, module));
+                }
+            }
+        }
+        out
+    }
+
+    /// The file of the longest prefix of dotted `module` under one of `roots`.
+    fn python_module(&self, roots: &[PathBuf], module: &str) -> Option<PathBuf> {
+        let parts: Vec<&str> = module.split('.').collect();
+        (1..=parts.len())
+            .rev()
+            .find_map(|n| self.python_exact(roots, &parts[..n].join(".")))
+    }
+
+    fn python_exact(&self, roots: &[PathBuf], module: &str) -> Option<PathBuf> {
+        roots.iter().find_map(|root| {
+            let mut file = root.clone();
+            file.extend(module.split('.'));
+            [file.with_extension("py"), file.join("__init__.py")]
+                .into_iter()
+                .find(|f| self.exists(f))
+        })
+    }
+
+    // ── JavaScript / TypeScript ───────────────────────────────
+
+    fn js_imports(&self, path: &Path, content: &str) -> Vec<PathBuf> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        js_specifiers(content)
+            .into_iter()
+            .filter(|s| s.starts_with("./") || s.starts_with("../"))
+            .filter_map(|s| {
+                let target = normalize(&dir.join(s));
+                // `./x.js` in TypeScript names `./x.ts`.
+                let stem = match target.extension().and_then(|e| e.to_str()) {
+                    Some(ext) if JS_EXTENSIONS.contains(&ext) => target.with_extension(""),
+                    _ => target.clone(),
+                };
+                std::iter::once(target.clone())
+                    .chain(JS_EXTENSIONS.iter().map(|ext| stem.with_extension(ext)))
+                    .chain(
+                        JS_EXTENSIONS
+                            .iter()
+                            .map(|ext| target.join("index").with_extension(ext)),
+                    )
+                    .find(|f| self.exists(f))
+            })
+            .collect()
+    }
+
+    // ── Go ────────────────────────────────────────────────────
+
+    /// Every file of each imported package inside the workspace.
+    fn go_imports(&self, content: &str) -> Vec<PathBuf> {
+        let dirs: Vec<PathBuf> = go_import_paths(content)
+            .iter()
+            .filter_map(|import| {
+                self.packages.iter().find_map(|p| {
+                    let module = p.import_name.as_deref()?;
+                    let rest = import
+                        .strip_prefix(module)
+                        .filter(|rest| rest.is_empty() || rest.starts_with('/'))?;
+                    Some(p.dir.join(rest.trim_start_matches('/')))
+                })
+            })
+            .collect();
+        self.files
+            .iter()
+            .filter(|f| {
+                f.extension().is_some_and(|e| e == "go")
+                    && !f.to_string_lossy().ends_with("_test.go")
+                    && f.parent().is_some_and(|p| dirs.iter().any(|d| d == p))
+            })
+            .map(|f| f.to_path_buf())
+            .collect()
+    }
+}
+
+/// The directory a Rust file's child modules live in: its own for
+/// `mod.rs`, `lib.rs` and `main.rs`, else one named after it.
+fn rust_module_dir(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    match path.file_stem().and_then(|s| s.to_str()) {
+        Some("mod" | "lib" | "main") | None => dir.to_path_buf(),
+        Some(stem) => dir.join(stem),
+    }
+}
+
+/// `mod` and `use` statements, each joined onto one line, with visibility
+/// and attributes left in place.
+fn rust_statements(content: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut pending: Option<String> = None;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if let Some(statement) = pending.as_mut() {
+            statement.push(' ');
+            statement.push_str(line);
+        } else {
+            let item = strip_visibility(line);
+            if item.starts_with("mod ") && !item.contains('{') {
+                out.push(item.to_string());
+                continue;
+            }
+            if !item.starts_with("use ") {
+                continue;
+            }
+            pending = Some(item.to_string());
+        }
+        if line.ends_with(';') {
+            out.extend(pending.take());
+        }
+    }
+    out
+}
+
+/// `line` without a leading `pub`, `pub(crate)` and the like.
+fn strip_visibility(line: &str) -> &str {
+    if let Some(rest) = line.strip_prefix("pub(") {
+        return rest.split_once(')').map_or(line, |(_, r)| r.trim_start());
+    }
+    line.strip_prefix("pub ").unwrap_or(line)
+}
+
+/// The paths a `use` tree names, e.g. `crate::{a, b::{c, d}}` gives
+/// `crate::a`, `crate::b::c` and `crate::b::d`, split into segments.
+fn expand_use_tree(tree: &str) -> Vec<Vec<String>> {
+    // `as` renames are kept as `@` so whitespace can go.
+    let tree: String = tree
+        .replace(" as ", "@")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let mut out = Vec::new();
+    expand_into(&tree, &mut Vec::new(), &mut out);
+    out
+}
+
+fn expand_into(tree: &str, prefix: &mut Vec<String>, out: &mut Vec<Vec<String>>) {
+    let tree = tree.trim_start_matches("::");
+    let Some(open) = tree.find('{') else {
+        let path = tree.split('@').next().unwrap_or(tree);
+        let mut segments = prefix.clone();
+        segments.extend(
+            path.split("::")
+                .filter(|s| !s.is_empty() && *s != "*" && *s != "self")
+                .map(str::to_string),
+        );
+        out.push(segments);
+        return;
+    };
+    let head: Vec<String> = tree[..open]
+        .split("::")
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    let inner = tree[open + 1..]
+        .strip_suffix('}')
+        .unwrap_or(&tree[open + 1..]);
+    let depth = prefix.len();
+    prefix.extend(head);
+    let mut level = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '{' => level += 1,
+            '}' => level -= 1,
+            ',' if level == 0 => {
+                expand_into(&inner[start..i], prefix, out);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < inner.len() {
+        expand_into(&inner[start..], prefix, out);
+    }
+    prefix.truncate(depth);
+}
+
+/// Module specifiers in `import ... from '...'`, `import '...'`,
+/// `export ... from '...'`, `require('...')` and `import('...')`.
+fn js_specifiers(content: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for line in content.lines().map(str::trim) {
+        let is_import = line.starts_with("import ")
+            || line.starts_with("export ")
+            || line.contains("require(")
+            || line.contains("import(")
+            || line.starts_with("} from ");
+        if !is_import {
+            continue;
+        }
+        for quote in ['\'', '"', '`'] {
+            let mut rest = line;
+            while let Some(start) = rest.find(quote) {
+                let after = &rest[start + 1..];
+                let Some(end) = after.find(quote) else {
+                    break;
+                };
+                let before = rest[..start].trim_end();
+                if before.ends_with("from")
+                    || before.ends_with("import")
+                    || before.ends_with("require(")
+                    || before.ends_with("import(")
+                {
+                    out.push(after[..end].to_string());
+                }
+                rest = &after[end + 1..];
+            }
+        }
+    }
+    out
+}
+
+/// Import paths of a Go file, from single imports and `import ( ... )`.
+fn go_import_paths(content: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut in_block = false;
+    for line in content.lines().map(str::trim) {
+        let spec = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "import (" {
+            in_block = true;
+            continue;
+        } else if let Some(rest) = line.strip_prefix("import ") {
+            rest
+        } else {
+            continue;
+        };
+        if let Some(path) = spec.split('"').nth(1) {
+            out.push(path.to_string());
+        }
+    }
+    out
+}
+
+/// `path` with `.` and `..` resolved lexically.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
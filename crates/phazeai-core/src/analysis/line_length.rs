@@ -0,0 +1,302 @@
+//! The max-line-length check: lines wider than the project's limit (the
+//! `.editorconfig` `max_line_length`), with a quick fix where one is safe.
+//!
+//! Widths are in columns, a tab advancing to the next tab stop. A line that
+//! is only a line comment is fixed by rewrapping its paragraph of comment
+//! lines. In Rust, a string literal that runs past the limit is split with
+//! `\` line continuations, which leave its value unchanged. Other long lines
+//! are reported without a fix.
+
+use super::workspace_lint::Removal;
+use crate::editing::TypingRules;
+
+/// Source of the findings in the Problems panel.
+pub const LINE_LENGTH_SOURCE: &str = "Line length";
+
+/// Comment text starting with one of these isn't rewrapped with the lines
+/// around it: Markdown fences, tables and headings.
+const BLOCK_MARKERS: [&str; 3] = ["```", "|", "#"];
+
+/// Bullets that start a list item; its wrapped lines hang under its text,
+/// indented by `HANGING`.
+const BULLETS: [&str; 2] = ["- ", "* "];
+const HANGING: &str = "  ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LongLine {
+    /// 1-based.
+    pub line: usize,
+    /// In columns.
+    pub width: usize,
+    pub fix: Option<Removal>,
+}
+
+/// The lines of `content` wider than `max` columns. `ext`, the file's
+/// extension, decides the comment syntax and whether strings can be split.
+pub fn long_lines(content: &str, max: usize, tab_width: usize, ext: &str) -> Vec<LongLine> {
+    let raw: Vec<&str> = content.split_inclusive('\n').collect();
+    let lines: Vec<&str> = raw
+        .iter()
+        .map(|l| l.trim_end_matches(['\n', '\r']))
+        .collect();
+    let token = TypingRules::for_extension(ext).comment_token();
+    let mut found = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let width = column_width(line, tab_width);
+        if width <= max {
+            continue;
+        }
+        let wrapped = match token.and_then(|t| comment_line(line, t).map(|c| (t, c))) {
+            Some((token, _)) => {
+                rewrap_comment(&lines, i, token, max, tab_width).map(|(start, end, new)| {
+                    (start, end, new, format!("Rewrap comment at {max} columns"))
+                })
+            }
+            None if ext == "rs" => split_string(line, max, tab_width)
+                .map(|new| (i, i + 1, new, format!("Wrap string at {max} columns"))),
+            None => None,
+        };
+        let fix = wrapped.map(|(start, end, new, title)| {
+            let ending = if raw[end - 1].ends_with("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            };
+            let mut replacement = new.join(ending);
+            // The last line keeps whatever ended it, nothing at the end of the file.
+            replacement.push_str(&raw[end - 1][lines[end - 1].len()..]);
+            Removal {
+                title,
+                start_line: start,
+                end_line: end,
+                replacement,
+            }
+        });
+        found.push(LongLine {
+            line: i + 1,
+            width,
+            fix,
+        });
+    }
+    found
+}
+
+/// The columns `line` takes, tabs advancing to the next multiple of
+/// `tab_width`.
+pub fn column_width(line: &str, tab_width: usize) -> usize {
+    let tab_width = tab_width.max(1);
+    line.chars().fold(0, |col, c| {
+        if c == '\t' {
+            col + tab_width - col % tab_width
+        } else {
+            col + 1
+        }
+    })
+}
+
+/// A line that is only a line comment, as its prefix (indent, marker such
+/// as `//`, `///` or `#`, and one space) and its text.
+fn comment_line<'a>(line: &'a str, token: &str) -> Option<(&'a str, &'a str)> {
+    let code = line.trim_start();
+    let after_token = code.strip_prefix(token)?;
+    // A shebang isn't a comment to rewrap.
+    if token == "#" && after_token.starts_with('!') {
+        return None;
+    }
+    let after_marker = after_token.trim_start_matches(['/', '!', '#']);
+    let text = after_marker.strip_prefix(' ').unwrap_or(after_marker);
+    Some((&line[..line.len() - text.len()], text))
+}
+
+/// What a comment line's text is to the paragraph around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// Starts a list item.
+    Bullet,
+    /// Continues a list item, indented under its text.
+    Hanging,
+    /// Prose.
+    Plain,
+    /// Blank, indented code, or a Markdown fence, table or heading.
+    Break,
+}
+
+fn role(text: &str) -> Role {
+    let starts_block = |t: &str| {
+        t.trim().is_empty()
+            || t.starts_with(char::is_whitespace)
+            || BLOCK_MARKERS.iter().any(|m| t.starts_with(m))
+    };
+    if BULLETS.iter().any(|b| text.starts_with(b)) {
+        Role::Bullet
+    } else if text
+        .strip_prefix(HANGING)
+        .is_some_and(|rest| !starts_block(rest) && !BULLETS.iter().any(|b| rest.starts_with(b)))
+    {
+        Role::Hanging
+    } else if starts_block(text) {
+        Role::Break
+    } else {
+        Role::Plain
+    }
+}
+
+/// The paragraph of comment lines around line `at`, refilled to `max`
+/// columns: its first line, the line after its last, and the new lines.
+fn rewrap_comment(
+    lines: &[&str],
+    at: usize,
+    token: &str,
+    max: usize,
+    tab_width: usize,
+) -> Option<(usize, usize, Vec<String>)> {
+    let (prefix, _) = comment_line(lines[at], token)?;
+    let role_of = |i: usize| {
+        comment_line(lines[i], token)
+            .filter(|(p, _)| *p == prefix)
+            .map_or(Role::Break, |(_, t)| role(t))
+    };
+    // A list item is its bullet line and the lines hanging under it.
+    let (start, next) = match role_of(at) {
+        Role::Break => return None,
+        Role::Bullet => (at, Role::Hanging),
+        Role::Hanging => {
+            let mut start = at;
+            while start > 0 && role_of(start - 1) == Role::Hanging {
+                start -= 1;
+            }
+            if start == 0 || role_of(start - 1) != Role::Bullet {
+                return None;
+            }
+            (start - 1, Role::Hanging)
+        }
+        Role::Plain => {
+            let mut start = at;
+            while start > 0 && role_of(start - 1) == Role::Plain {
+                start -= 1;
+            }
+            (start, Role::Plain)
+        }
+    };
+    let mut end = at + 1;
+    while end < lines.len() && role_of(end) == next {
+        end += 1;
+    }
+    let hanging = if next == Role::Hanging { HANGING } else { "" };
+
+    let words: Vec<&str> = (start..end)
+        .filter_map(|i| comment_line(lines[i], token))
+        .flat_map(|(_, t)| t.split_whitespace())
+        .collect();
+    let rest_prefix = format!("{prefix}{hanging}");
+    let mut wrapped = Vec::new();
+    let mut current = String::new();
+    for word in words {
+        if current.is_empty() {
+            current = format!("{prefix}{word}");
+        } else if column_width(&current, tab_width) + 1 + word.chars().count() <= max {
+            current.push(' ');
+            current.push_str(word);
+        } else {
+            wrapped.push(std::mem::take(&mut current));
+            current = format!("{rest_prefix}{word}");
+        }
+    }
+    wrapped.push(current);
+    let unchanged = wrapped
+        .iter()
+        .map(String::as_str)
+        .eq(lines[start..end].iter().copied());
+    (!unchanged).then_some((start, end, wrapped))
+}
+
+/// `line` with the Rust string literal that crosses `max` split over as
+/// many lines as it takes, using `\` continuations so its value is the
+/// same. `None` when there's no such literal or no space to split at.
+fn split_string(line: &str, max: usize, tab_width: usize) -> Option<Vec<String>> {
+    let (open, close) = string_crossing(line, max, tab_width)?;
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let unit = if indent.starts_with('\t') {
+        "\t"
+    } else {
+        "    "
+    };
+    let continuation = format!("{indent}{unit}");
+
+    let mut lines = Vec::new();
+    let mut current = line.to_string();
+    let (mut body, mut close) = (open + 1, close);
+    while column_width(&current, tab_width) > max {
+        // Split after a space, before a character that isn't whitespace:
+        // the continuation drops the next line's leading whitespace.
+        let split = current[..close]
+            .char_indices()
+            .rfind(|&(i, c)| {
+                i > body
+                    && c != ' '
+                    && c != '\t'
+                    && current[..i].ends_with(' ')
+                    && !current[..i].ends_with("\\ ")
+                    && column_width(&current[..i], tab_width) < max
+            })
+            .map(|(i, _)| i);
+        let Some(split) = split else {
+            break;
+        };
+        lines.push(format!("{}\\", &current[..split]));
+        let rest = format!("{continuation}{}", &current[split..]);
+        body = continuation.len();
+        close = close - split + continuation.len();
+        current = rest;
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.push(current);
+    Some(lines)
+}
+
+/// The byte offsets of the quotes of the first plain string literal on
+/// `line` that starts within `max` columns and ends past them. Raw strings
+/// and lines where a literal doesn't close are left alone.
+fn string_crossing(line: &str, max: usize, tab_width: usize) -> Option<(usize, usize)> {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'/') => return None,
+            // Char literals that could hold a quote: `'"'` and `'\"'`.
+            b'\'' if bytes.get(i + 1) == Some(&b'\\') => {
+                i += 4;
+                continue;
+            }
+            b'\'' if bytes.get(i + 2) == Some(&b'\'') => {
+                i += 3;
+                continue;
+            }
+            b'"' => {
+                let raw = line[..i].trim_end_matches('#').ends_with('r');
+                if raw {
+                    return None;
+                }
+                let mut j = i + 1;
+                while j < bytes.len() && bytes[j] != b'"' {
+                    j += if bytes[j] == b'\\' { 2 } else { 1 };
+                }
+                if j >= bytes.len() {
+                    return None;
+                }
+                if column_width(&line[..i], tab_width) < max
+                    && column_width(&line[..=j], tab_width) > max
+                {
+                    return Some((i, j));
+                }
+                i = j + 1;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
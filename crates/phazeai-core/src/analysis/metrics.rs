@@ -0,0 +1,517 @@
+//! Per-function code metrics: cyclomatic complexity, length and nesting
+//! depth.
+//!
+//! [`measure`] finds the functions of one file (Rust, Python, Go,
+//! JavaScript/TypeScript) with line patterns, after blanking strings and
+//! comments, and counts their decision points: branches, loops, match or
+//! switch arms, `catch`/`except` and short-circuit operators. Braced
+//! languages end a function at its closing brace, Python at the first line
+//! indented no deeper than the `def`. [`measure_workspace`] runs it over
+//! every source file git wouldn't ignore, and [`hotspots`] picks the
+//! functions worth a reviewer's attention.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+
+use super::linter::Language;
+use crate::progress;
+
+/// Complexity from which a function is marked warm, and hot.
+pub const COMPLEX: usize = 10;
+pub const VERY_COMPLEX: usize = 20;
+/// Length, in lines, from which a function is a hotspot.
+pub const LONG_FUNCTION: usize = 80;
+/// Nesting depth from which a function is a hotspot.
+pub const DEEP_NESTING: usize = 5;
+
+/// Files larger than this are skipped (generated or vendored code).
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+/// Source files measured before the scan stops.
+const MAX_FILES: usize = 5000;
+
+static RE_RUST_FN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:const|async|unsafe|extern(?:\s+\S+)?)\s+)*fn\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+});
+static RE_GO_FN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^func\s+(?:\([^)]*\)\s*)?([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+static RE_JS_FN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*([A-Za-z_$][A-Za-z0-9_$]*)").unwrap()
+});
+static RE_JS_ARROW: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:export\s+)?(?:const|let|var)\s+([A-Za-z_$][A-Za-z0-9_$]*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:\([^)]*\)|[A-Za-z_$][A-Za-z0-9_$]*)\s*(?::[^=]+)?=>\s*\{").unwrap()
+});
+static RE_JS_METHOD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s+(?:(?:public|private|protected|static|async|override|readonly|get|set)\s+)*\*?([A-Za-z_$][A-Za-z0-9_$]*)\s*\([^)]*\)\s*(?::[^{]+)?\{\s*$").unwrap()
+});
+static RE_PY_FN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*)(?:async\s+)?def\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+
+// `&&` and `||` count after an operand, so `&&x` and closures `|| ..` don't.
+static RE_RUST_BRANCH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:if|while|for)\b|[\w)\]?]\s*(?:&&|\|\|)|=>").unwrap());
+static RE_RUST_MATCH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bmatch\b").unwrap());
+static RE_GO_BRANCH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:if|for|case)\b|&&|\|\|").unwrap());
+static RE_JS_BRANCH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:if|for|while|case|catch)\b|&&|\|\||\?\?| \? ").unwrap());
+static RE_PY_BRANCH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:if|elif|for|while|except|and|or)\b").unwrap());
+
+/// Control-flow keywords a JavaScript method pattern would mistake for a name.
+const JS_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "with", "function", "return",
+];
+
+/// How much a function's complexity stands out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Heat {
+    Cool,
+    /// [`COMPLEX`] or more.
+    Warm,
+    /// [`VERY_COMPLEX`] or more.
+    Hot,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunctionMetrics {
+    pub name: String,
+    /// 1-based first line (the signature) and last line.
+    pub line: usize,
+    pub end_line: usize,
+    /// Cyclomatic complexity: one plus the decision points.
+    pub complexity: usize,
+    /// Deepest block nesting inside the body; a flat body is 0.
+    pub nesting: usize,
+}
+
+impl FunctionMetrics {
+    /// Lines from the signature to the end of the body.
+    pub fn length(&self) -> usize {
+        self.end_line + 1 - self.line
+    }
+
+    pub fn heat(&self) -> Heat {
+        if self.complexity >= VERY_COMPLEX {
+            Heat::Hot
+        } else if self.complexity >= COMPLEX {
+            Heat::Warm
+        } else {
+            Heat::Cool
+        }
+    }
+
+    /// Complex, long or deeply nested enough to be worth a second look.
+    pub fn is_hotspot(&self) -> bool {
+        self.complexity >= COMPLEX || self.length() >= LONG_FUNCTION || self.nesting >= DEEP_NESTING
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetrics {
+    /// Relative to the workspace root.
+    pub path: PathBuf,
+    /// Lines with code on them, not counting blank and comment lines.
+    pub lines_of_code: usize,
+    pub functions: Vec<FunctionMetrics>,
+}
+
+impl FileMetrics {
+    pub fn max_complexity(&self) -> usize {
+        self.functions
+            .iter()
+            .map(|f| f.complexity)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// A hotspot function and the file it is in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotspot {
+    pub path: PathBuf,
+    pub function: FunctionMetrics,
+}
+
+/// The functions of `code` with their metrics, in source order.
+pub fn measure(code: &str, language: Language) -> Vec<FunctionMetrics> {
+    let lines = code_lines(code, language);
+    match language {
+        Language::Python => measure_indented(&lines),
+        Language::Other => Vec::new(),
+        _ => measure_braced(&lines, language),
+    }
+}
+
+/// [`measure`] every source file under `root` git wouldn't ignore, in path
+/// order. Files without functions are left out.
+pub fn measure_workspace(root: &Path) -> Vec<FileMetrics> {
+    let task = progress::global()
+        .task("Measuring complexity")
+        .unit("files")
+        .cancellable()
+        .start();
+    let mut files = Vec::new();
+    let walker = WalkBuilder::new(root)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some("node_modules" | "target")))
+        .build();
+    let mut seen = 0;
+    for entry in walker.flatten() {
+        if task.is_cancelled() || seen >= MAX_FILES {
+            break;
+        }
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let ext = entry
+            .path()
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let language = match ext {
+            "mjs" | "cjs" => Language::JavaScript,
+            _ => Language::from_extension(ext),
+        };
+        if language == Language::Other || !entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES)
+        {
+            continue;
+        }
+        let Ok(code) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        seen += 1;
+        task.advance(1);
+        let functions = measure(&code, language);
+        if functions.is_empty() {
+            continue;
+        }
+        files.push(FileMetrics {
+            path: entry
+                .path()
+                .strip_prefix(root)
+                .unwrap_or(entry.path())
+                .to_path_buf(),
+            lines_of_code: code_lines(&code, language)
+                .iter()
+                .filter(|l| !l.trim().is_empty())
+                .count(),
+            functions,
+        });
+    }
+    files
+}
+
+/// The `limit` hotspots of `files`, most complex (then longest) first.
+pub fn hotspots(files: &[FileMetrics], limit: usize) -> Vec<Hotspot> {
+    let mut found: Vec<Hotspot> = files
+        .iter()
+        .flat_map(|file| {
+            file.functions
+                .iter()
+                .filter(|f| f.is_hotspot())
+                .map(|f| Hotspot {
+                    path: file.path.clone(),
+                    function: f.clone(),
+                })
+        })
+        .collect();
+    found.sort_by(|a, b| {
+        (b.function.complexity, b.function.length())
+            .cmp(&(a.function.complexity, a.function.length()))
+            .then_with(|| (&a.path, a.function.line).cmp(&(&b.path, b.function.line)))
+    });
+    found.truncate(limit);
+    found
+}
+
+/// Hotspots as a Markdown list, one line each, for a prompt.
+pub fn describe_hotspots(hotspots: &[Hotspot]) -> String {
+    hotspots
+        .iter()
+        .map(|h| {
+            format!(
+                "- `{}` ({}:{}): complexity {}, {} lines, nesting {}\n",
+                h.function.name,
+                h.path.display(),
+                h.function.line,
+                h.function.complexity,
+                h.function.length(),
+                h.function.nesting
+            )
+        })
+        .collect()
+}
+
+/// `code`'s lines with string and character literals emptied and comments
+/// removed, so braces and keywords in them aren't counted.
+fn code_lines(code: &str, language: Language) -> Vec<String> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Code,
+        Block,
+        /// Closing quote, and for Rust raw strings the `#`s after it.
+        Str(char, usize),
+        /// Python triple-quoted string.
+        Triple(char),
+    }
+    let python = language == Language::Python;
+    let rust = language == Language::Rust;
+    let mut state = State::Code;
+    let mut out = Vec::new();
+    for line in code.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        let mut kept = String::with_capacity(line.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            match state {
+                State::Block => {
+                    if c == '*' && next == Some('/') {
+                        state = State::Code;
+                        i += 1;
+                    }
+                }
+                State::Triple(q) => {
+                    if c == q && next == Some(q) && chars.get(i + 2) == Some(&q) {
+                        kept.push(q);
+                        state = State::Code;
+                        i += 2;
+                    }
+                }
+                State::Str(q, hashes) => {
+                    if c == '\\' && hashes == 0 && q != '`' {
+                        i += 1;
+                    } else if c == q
+                        && chars[i + 1..].iter().take_while(|&&h| h == '#').count() >= hashes
+                    {
+                        kept.push(q);
+                        state = State::Code;
+                        i += hashes;
+                    }
+                }
+                State::Code => {
+                    if python && c == '#' || !python && c == '/' && next == Some('/') {
+                        break;
+                    } else if !python && c == '/' && next == Some('*') {
+                        state = State::Block;
+                        i += 1;
+                    } else if python
+                        && (c == '"' || c == '\'')
+                        && next == Some(c)
+                        && chars.get(i + 2) == Some(&c)
+                    {
+                        kept.push(c);
+                        state = State::Triple(c);
+                        i += 2;
+                    } else if rust && c == 'r' && matches!(next, Some('"' | '#')) {
+                        let hashes = chars[i + 1..].iter().take_while(|&&h| h == '#').count();
+                        if chars.get(i + 1 + hashes) == Some(&'"') {
+                            kept.push('"');
+                            state = State::Str('"', hashes);
+                            i += hashes + 1;
+                        } else {
+                            kept.push(c);
+                        }
+                    } else if rust && c == '\'' {
+                        // A char literal ('x', '\n', '\u{1F600}'), or a lifetime.
+                        let end = if next == Some('\\') {
+                            chars[i + 2..]
+                                .iter()
+                                .position(|&e| e == '\'')
+                                .map(|p| i + 2 + p)
+                        } else {
+                            Some(i + 2).filter(|&e| chars.get(e) == Some(&'\''))
+                        };
+                        match end {
+                            Some(end) => {
+                                kept.push_str("''");
+                                i = end;
+                            }
+                            None => kept.push(c),
+                        }
+                    } else if c == '"' || c == '`' || c == '\'' {
+                        kept.push(c);
+                        state = State::Str(c, 0);
+                    } else {
+                        kept.push(c);
+                    }
+                }
+            }
+            i += 1;
+        }
+        // Only raw and template strings and block comments go on past the
+        // end of a line; an unterminated quote is a typo.
+        if let State::Str(q, hashes) = state {
+            if !(q == '`' || rust && (hashes > 0 || q == '"')) {
+                state = State::Code;
+            }
+        }
+        out.push(kept);
+    }
+    out
+}
+
+/// The name of the function `line` starts, in a braced language.
+fn braced_function_name(line: &str, language: Language) -> Option<String> {
+    let re: &[&Regex] = match language {
+        Language::Rust => &[&RE_RUST_FN],
+        Language::Go => &[&RE_GO_FN],
+        _ => &[&RE_JS_FN, &RE_JS_ARROW, &RE_JS_METHOD],
+    };
+    re.iter()
+        .find_map(|re| re.captures(line))
+        .map(|c| c[1].to_string())
+        .filter(|name| !JS_KEYWORDS.contains(&name.as_str()))
+}
+
+/// Decision points in a function's blanked body.
+fn decisions(body: &str, language: Language) -> usize {
+    match language {
+        // A match with n arms is n - 1 decisions: count the arms' `=>`,
+        // less one per `match`.
+        Language::Rust => RE_RUST_BRANCH
+            .find_iter(body)
+            .count()
+            .saturating_sub(RE_RUST_MATCH.find_iter(body).count()),
+        Language::Go => RE_GO_BRANCH.find_iter(body).count(),
+        Language::Python => RE_PY_BRANCH.find_iter(body).count(),
+        _ => RE_JS_BRANCH.find_iter(body).count(),
+    }
+}
+
+fn measure_braced(lines: &[String], language: Language) -> Vec<FunctionMetrics> {
+    let mut functions = Vec::new();
+    for (start, line) in lines.iter().enumerate() {
+        let Some(name) = braced_function_name(line, language) else {
+            continue;
+        };
+        // The body opens at the first `{` after the name; a `;` before it
+        // is a declaration without one (a trait method, an extern fn).
+        let mut depth = 0usize;
+        let mut nesting = 0;
+        let mut body = String::new();
+        let mut end = None;
+        'lines: for (i, text) in lines.iter().enumerate().skip(start) {
+            for c in text.chars() {
+                match c {
+                    ';' if depth == 0 => break 'lines,
+                    '{' => {
+                        nesting = nesting.max(depth);
+                        depth += 1;
+                    }
+                    '}' if depth > 0 => {
+                        depth -= 1;
+                        if depth == 0 {
+                            end = Some(i);
+                            break 'lines;
+                        }
+                    }
+                    _ => {}
+                }
+                if depth > 0 {
+                    body.push(c);
+                }
+            }
+            body.push('\n');
+        }
+        if let Some(end) = end {
+            functions.push(FunctionMetrics {
+                name,
+                line: start + 1,
+                end_line: end + 1,
+                complexity: 1 + decisions(&body, language),
+                nesting,
+            });
+        }
+    }
+    functions
+}
+
+fn indent_of(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// How many more brackets `line` opens than it closes.
+fn bracket_balance(line: &str) -> i32 {
+    line.chars()
+        .map(|c| match c {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+fn measure_indented(lines: &[String]) -> Vec<FunctionMetrics> {
+    let mut functions = Vec::new();
+    for (start, line) in lines.iter().enumerate() {
+        let Some(caps) = RE_PY_FN.captures(line) else {
+            continue;
+        };
+        let def_indent = indent_of(line);
+        // The signature goes on until its brackets close.
+        let mut open = 0;
+        let mut signature_end = start;
+        for (i, text) in lines.iter().enumerate().skip(start) {
+            open += bracket_balance(text);
+            signature_end = i;
+            if open <= 0 {
+                break;
+            }
+        }
+        // A one-line body follows the colon: `def f(): return 1`.
+        let last = &lines[signature_end];
+        let colon = last.rfind(')').map_or(0, |p| p + 1);
+        let mut body = last[colon..]
+            .find(':')
+            .map(|p| last[colon + p + 1..].to_string())
+            .unwrap_or_default();
+        body.push('\n');
+
+        let mut end = signature_end;
+        let mut nesting = 0;
+        // Indents of the blocks the current statement is in, the body's first.
+        let mut blocks: Vec<usize> = Vec::new();
+        // Open brackets carry a statement onto the next lines.
+        let mut open = 0;
+        for (i, text) in lines.iter().enumerate().skip(signature_end + 1) {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let indent = indent_of(text);
+            if open <= 0 {
+                if indent <= def_indent {
+                    break;
+                }
+                while blocks.len() > 1 && blocks.last().is_some_and(|&b| b >= indent) {
+                    blocks.pop();
+                }
+                if blocks.last() != Some(&indent) {
+                    blocks.push(indent);
+                }
+                nesting = nesting.max(blocks.len() - 1);
+                open = 0;
+            }
+            open += bracket_balance(text);
+            body.push_str(text);
+            body.push('\n');
+            end = i;
+        }
+        functions.push(FunctionMetrics {
+            name: caps[2].to_string(),
+            line: start + 1,
+            end_line: end + 1,
+            complexity: 1 + decisions(&body, Language::Python),
+            nesting,
+        });
+    }
+    functions
+}
@@ -1,7 +1,24 @@
+pub mod explain;
+pub mod imports;
+pub mod line_length;
 mod linter;
+pub mod metrics;
+pub mod onboard;
 pub mod outline;
+pub mod review;
+pub mod security;
+pub mod tasks;
+pub mod todos;
+pub mod workspace_lint;
 
-pub use linter::{CodeAnalysis, CodeMetrics, Issue, Linter, Severity};
+pub use imports::ImportGraph;
+pub use line_length::{long_lines, LongLine, LINE_LENGTH_SOURCE};
+pub use linter::{CodeAnalysis, CodeMetrics, Issue, Language, Linter, Severity};
 pub use outline::{
-    extract_symbols_generic, generate_repo_map, symbols_to_repo_map, CodeSymbol, SymbolKind,
+    extract_symbols_generic, generate_repo_map, is_public_declaration, symbols_to_repo_map,
+    CodeSymbol, SymbolKind,
 };
+pub use security::{SecurityFinding, SecurityRule, SECURITY_SOURCE};
+pub use tasks::PlanTask;
+pub use todos::{TodoComment, TodoFilter};
+pub use workspace_lint::{FindingKind, Removal, WorkspaceFinding, ANALYSIS_SOURCE};
@@ -0,0 +1,527 @@
+//! Architecture summaries for onboarding, written by `phazeai onboard` and
+//! the IDE's "Generate Architecture Summary" command.
+//!
+//! [`collect_inputs`] gathers what a newcomer reads first: the README,
+//! package manifests, build and CI files and entry points, along with the
+//! repo map and the import graph. The model writes each [`Section`] of
+//! `ARCHITECTURE.md` from the part of those inputs it needs, and the file
+//! keeps a fingerprint of that part next to the section. Running [`onboard`]
+//! again only rewrites sections whose inputs changed; the others, hand edits
+//! included, are left as they are.
+
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use super::imports;
+use crate::context::RepoMapGenerator;
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+use crate::progress;
+
+/// The summary's file name, at the workspace root.
+pub const ARCHITECTURE_FILE: &str = "ARCHITECTURE.md";
+
+/// Longest input sent for one section, in characters.
+const MAX_SECTION_INPUT_CHARS: usize = 40_000;
+/// Longest excerpt of a single key file, in characters.
+const MAX_FILE_CHARS: usize = 6_000;
+/// Key files of each kind read before the rest are skipped.
+const MAX_FILES_PER_KIND: usize = 24;
+/// Token budget for the repo map.
+const REPO_MAP_TOKENS: usize = 6_000;
+
+const MARKER_PREFIX: &str = "<!-- phazeai:section ";
+
+const DEFAULT_PREAMBLE: &str = "# Architecture\n\n\
+<!-- Generated by `phazeai onboard`. Each section below is rewritten when the \
+files it was written from change; run it again to bring the summary up to date. \
+Edits to a section are kept until then. -->";
+
+const ONBOARD_PROMPT: &str = "You write the ARCHITECTURE.md of a software \
+project for developers joining it. You are writing one section of it from \
+the project files below. Be concrete: name real directories, files, types \
+and commands, and never invent ones that aren't shown. Write GitHub-flavored \
+Markdown without a top-level heading; use `###` subheadings, short \
+paragraphs and lists. No preamble.";
+
+/// A section of the architecture summary, in document order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Overview,
+    Modules,
+    DataFlow,
+    EntryPoints,
+    BuildAndTest,
+}
+
+impl Section {
+    pub const ALL: [Section; 5] = [
+        Section::Overview,
+        Section::Modules,
+        Section::DataFlow,
+        Section::EntryPoints,
+        Section::BuildAndTest,
+    ];
+
+    /// Identifier in the section's marker comment.
+    pub fn id(self) -> &'static str {
+        match self {
+            Section::Overview => "overview",
+            Section::Modules => "modules",
+            Section::DataFlow => "data-flow",
+            Section::EntryPoints => "entry-points",
+            Section::BuildAndTest => "build-and-test",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.id() == id)
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Section::Overview => "Overview",
+            Section::Modules => "Modules",
+            Section::DataFlow => "Data flow",
+            Section::EntryPoints => "Entry points",
+            Section::BuildAndTest => "Building and testing",
+        }
+    }
+
+    fn instructions(self) -> &'static str {
+        match self {
+            Section::Overview => {
+                "Write the Overview: what the project is, who uses it, and \
+                 the packages it is split into, each in a sentence."
+            }
+            Section::Modules => {
+                "Write the Modules section: the main directories and modules, \
+                 what each is responsible for and its most important types. \
+                 Group them by package."
+            }
+            Section::DataFlow => {
+                "Write the Data flow section: starting from the entry points, \
+                 how a request, command or event travels through the modules, \
+                 and which modules depend on which."
+            }
+            Section::EntryPoints => {
+                "Write the Entry points section: each binary, library root or \
+                 script a user or another program starts from, with its path \
+                 and what it sets up."
+            }
+            Section::BuildAndTest => {
+                "Write the Building and testing section: the exact commands to \
+                 build, run, test and lint the project, the toolchains they \
+                 need, and what CI runs."
+            }
+        }
+    }
+}
+
+/// The project files the summary is written from. Paths are relative to the
+/// workspace root; file contents are truncated excerpts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OnboardInputs {
+    /// READMEs.
+    pub docs: Vec<(String, String)>,
+    /// Package manifests (`Cargo.toml`, `package.json`, ...).
+    pub manifests: Vec<(String, String)>,
+    /// Build scripts, CI workflows and contributing guides.
+    pub build_files: Vec<(String, String)>,
+    /// Binary and library roots (`src/main.rs`, `main.go`, `index.ts`, ...).
+    pub entry_points: Vec<(String, String)>,
+    pub repo_map: String,
+    /// The import graph as [`imports::ImportGraph::describe`] renders it.
+    pub imports: String,
+}
+
+impl OnboardInputs {
+    /// The text `section` is written from. Its fingerprint decides whether
+    /// the section is out of date.
+    pub fn for_section(&self, section: Section) -> String {
+        let mut out = String::new();
+        let files = |out: &mut String, heading: &str, files: &[(String, String)]| {
+            if files.is_empty() {
+                return;
+            }
+            out.push_str(&format!("# {heading}\n\n"));
+            for (path, content) in files {
+                out.push_str(&format!("## {path}\n```\n{}\n```\n\n", content.trim_end()));
+            }
+        };
+        let text = |out: &mut String, heading: &str, text: &str| {
+            if !text.trim().is_empty() {
+                out.push_str(&format!("# {heading}\n\n{}\n\n", text.trim_end()));
+            }
+        };
+        match section {
+            Section::Overview => {
+                files(&mut out, "Documentation", &self.docs);
+                files(&mut out, "Manifests", &self.manifests);
+            }
+            Section::Modules => {
+                files(&mut out, "Manifests", &self.manifests);
+                text(&mut out, "Repo map", &self.repo_map);
+            }
+            Section::DataFlow => {
+                files(&mut out, "Entry points", &self.entry_points);
+                text(&mut out, "Import graph", &self.imports);
+            }
+            Section::EntryPoints => {
+                files(&mut out, "Manifests", &self.manifests);
+                files(&mut out, "Entry points", &self.entry_points);
+            }
+            Section::BuildAndTest => {
+                files(&mut out, "Documentation", &self.docs);
+                files(&mut out, "Manifests", &self.manifests);
+                files(&mut out, "Build files", &self.build_files);
+            }
+        }
+        truncate(&out, MAX_SECTION_INPUT_CHARS)
+    }
+}
+
+/// Read the summary's inputs from the workspace at `root`. Files are visited
+/// in name order so the same tree always gives the same inputs.
+pub fn collect_inputs(root: &Path) -> OnboardInputs {
+    let mut inputs = OnboardInputs::default();
+    // Hidden files are walked for `.github/workflows` and `.gitlab-ci.yml`.
+    let walker = WalkBuilder::new(root)
+        .require_git(false)
+        .hidden(false)
+        .filter_entry(|e| e.file_name() != ".git")
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let Some(kind) = key_file_kind(relative) else {
+            continue;
+        };
+        let list = match kind {
+            KeyFile::Doc => &mut inputs.docs,
+            KeyFile::Manifest => &mut inputs.manifests,
+            KeyFile::Build => &mut inputs.build_files,
+            KeyFile::EntryPoint => &mut inputs.entry_points,
+        };
+        if list.len() >= MAX_FILES_PER_KIND {
+            continue;
+        }
+        if let Ok(content) = std::fs::read_to_string(entry.path()) {
+            let path = relative.to_string_lossy().replace('\\', "/");
+            list.push((path, truncate(&content, MAX_FILE_CHARS)));
+        }
+    }
+    inputs.repo_map = RepoMapGenerator::new(root)
+        .with_max_tokens(REPO_MAP_TOKENS)
+        .generate();
+    inputs.imports = imports::scan_workspace(root).describe();
+    inputs
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum KeyFile {
+    Doc,
+    Manifest,
+    Build,
+    EntryPoint,
+}
+
+fn key_file_kind(path: &Path) -> Option<KeyFile> {
+    let name = path.file_name()?.to_str()?;
+    let parent = path.parent().unwrap_or(Path::new(""));
+    let at_root = parent.as_os_str().is_empty();
+    let parent_name = parent.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let lower = name.to_ascii_lowercase();
+    if lower.starts_with("readme") && path.components().count() <= 3 {
+        return Some(KeyFile::Doc);
+    }
+    match name {
+        "Cargo.toml" | "package.json" | "pyproject.toml" | "setup.py" | "go.mod" | "pom.xml"
+        | "build.gradle" | "build.gradle.kts" => return Some(KeyFile::Manifest),
+        "Makefile" | "justfile" | "Justfile" | "CMakeLists.txt" | "Dockerfile"
+        | "CONTRIBUTING.md" | "tox.ini" | "noxfile.py" | ".gitlab-ci.yml"
+            if at_root =>
+        {
+            return Some(KeyFile::Build)
+        }
+        _ => {}
+    }
+    if parent == Path::new(".github/workflows")
+        && (name.ends_with(".yml") || name.ends_with(".yaml"))
+    {
+        return Some(KeyFile::Build);
+    }
+    let entry_point = match name {
+        "main.rs" | "lib.rs" => parent_name == "src",
+        "main.go" | "__main__.py" | "manage.py" => true,
+        "main.py" | "app.py" | "index.ts" | "index.js" | "main.ts" | "main.js" => {
+            at_root || parent_name == "src"
+        }
+        _ => false,
+    };
+    (entry_point || parent_name == "bin" && name.ends_with(".rs")).then_some(KeyFile::EntryPoint)
+}
+
+/// At most `max` characters of `text`, marking where it was cut.
+fn truncate(text: &str, max: usize) -> String {
+    if text.len() <= max {
+        return text.to_string();
+    }
+    format!("{}\n… (truncated)", &text[..text.floor_char_boundary(max)])
+}
+
+/// A stable fingerprint of `text` (64-bit FNV-1a, as hex). Stored in the
+/// summary, so it must not change between builds or platforms.
+pub fn fingerprint(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in text.bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// The fingerprint recorded for `section` when written from `inputs`. The
+/// instructions are part of it, so changing them rewrites the section.
+pub fn section_fingerprint(section: Section, inputs: &OnboardInputs) -> String {
+    fingerprint(&format!(
+        "{}\n{}",
+        section.instructions(),
+        inputs.for_section(section)
+    ))
+}
+
+/// A section as stored in the summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummarySection {
+    pub section: Section,
+    /// Fingerprint of the inputs it was written from.
+    pub fingerprint: String,
+    /// Markdown under the section's heading.
+    pub body: String,
+}
+
+/// An `ARCHITECTURE.md` as [`onboard`] writes it: free text up to the first
+/// section, then each section under its marker comment and heading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub preamble: String,
+    pub sections: Vec<SummarySection>,
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Self {
+            preamble: DEFAULT_PREAMBLE.to_string(),
+            sections: Vec::new(),
+        }
+    }
+}
+
+impl Summary {
+    /// Split `doc` into its sections. Text before the first marker is the
+    /// preamble; markers for unknown sections are dropped with their text.
+    pub fn parse(doc: &str) -> Self {
+        let mut summary = Summary {
+            preamble: String::new(),
+            sections: Vec::new(),
+        };
+        let mut current: Option<(Option<Section>, String, Vec<&str>)> = None;
+        let mut preamble = Vec::new();
+        let finish = |summary: &mut Summary,
+                      current: Option<(Option<Section>, String, Vec<&str>)>| {
+            if let Some((Some(section), fingerprint, lines)) = current {
+                let mut lines = lines.as_slice();
+                while lines.first().is_some_and(|l| l.trim().is_empty()) {
+                    lines = &lines[1..];
+                }
+                if lines.first().is_some_and(|l| l.starts_with("## ")) {
+                    lines = &lines[1..];
+                }
+                summary.sections.push(SummarySection {
+                    section,
+                    fingerprint,
+                    body: lines.join("\n").trim().to_string(),
+                });
+            }
+        };
+        for line in doc.lines() {
+            let marker = line
+                .trim()
+                .strip_prefix(MARKER_PREFIX)
+                .and_then(|rest| rest.strip_suffix("-->"));
+            match marker {
+                Some(marker) => {
+                    finish(&mut summary, current.take());
+                    let mut parts = marker.split_whitespace();
+                    let section = parts.next().and_then(Section::from_id);
+                    let fingerprint = parts.next().unwrap_or("").to_string();
+                    current = Some((section, fingerprint, Vec::new()));
+                }
+                None => match &mut current {
+                    Some((_, _, lines)) => lines.push(line),
+                    None => preamble.push(line),
+                },
+            }
+        }
+        finish(&mut summary, current);
+        summary.preamble = preamble.join("\n").trim().to_string();
+        summary
+    }
+
+    pub fn section(&self, section: Section) -> Option<&SummarySection> {
+        self.sections.iter().find(|s| s.section == section)
+    }
+
+    /// Replace `section`'s text, keeping sections in document order.
+    pub fn set(&mut self, section: Section, fingerprint: String, body: String) {
+        self.sections.retain(|s| s.section != section);
+        self.sections.push(SummarySection {
+            section,
+            fingerprint,
+            body,
+        });
+        self.sections
+            .sort_by_key(|s| Section::ALL.iter().position(|&x| x == s.section));
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        if !self.preamble.is_empty() {
+            out.push_str(&self.preamble);
+            out.push_str("\n\n");
+        }
+        for s in &self.sections {
+            out.push_str(&format!(
+                "{MARKER_PREFIX}{} {} -->\n## {}\n\n{}\n\n",
+                s.section.id(),
+                s.fingerprint,
+                s.section.title(),
+                s.body
+            ));
+        }
+        format!("{}\n", out.trim_end())
+    }
+
+    /// Sections missing from the summary or written from other inputs.
+    pub fn stale_sections(&self, inputs: &OnboardInputs) -> Vec<Section> {
+        Section::ALL
+            .into_iter()
+            .filter(|&section| {
+                self.section(section)
+                    .is_none_or(|s| s.fingerprint != section_fingerprint(section, inputs))
+            })
+            .collect()
+    }
+}
+
+/// The model's answer as a section body: without code fences around the
+/// whole answer or a repeated section heading.
+pub fn clean_section_body(text: &str) -> String {
+    let mut text = text.trim();
+    if let Some(inner) = text
+        .strip_prefix("```markdown")
+        .or_else(|| text.strip_prefix("```md"))
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|t| t.strip_suffix("```"))
+    {
+        text = inner.trim();
+    }
+    if let Some(first) = text.lines().next() {
+        if first.starts_with("# ") || first.starts_with("## ") {
+            text = text[first.len()..].trim_start();
+        }
+    }
+    text.to_string()
+}
+
+/// Ask the model for `section`, showing it the current text to revise.
+pub async fn write_section(
+    llm: &dyn LlmClient,
+    section: Section,
+    inputs: &OnboardInputs,
+    current: Option<&str>,
+) -> Result<String, PhazeError> {
+    let mut request = format!(
+        "{}\n\n{}",
+        section.instructions(),
+        inputs.for_section(section)
+    );
+    if let Some(current) = current.filter(|c| !c.trim().is_empty()) {
+        request.push_str(&format!(
+            "# Current text of this section\n\nThe files changed since it was \
+             written. Keep what is still accurate and fix the rest.\n\n{current}\n"
+        ));
+    }
+    let messages = vec![Message::system(ONBOARD_PROMPT), Message::user(request)];
+    let response = llm.chat(&messages, &[]).await?;
+    let body = clean_section_body(&response.message.content);
+    if body.is_empty() {
+        return Err(PhazeError::Llm(format!(
+            "empty answer for the {} section",
+            section.title()
+        )));
+    }
+    Ok(body)
+}
+
+/// What [`onboard`] did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnboardReport {
+    pub path: PathBuf,
+    pub regenerated: Vec<Section>,
+    pub unchanged: Vec<Section>,
+}
+
+/// Bring `ARCHITECTURE.md` under `root` up to date, rewriting every section
+/// when `force` is set and only stale ones otherwise. The file is saved
+/// after each section, so an interrupted run keeps what it finished.
+pub async fn onboard(
+    llm: &dyn LlmClient,
+    root: &Path,
+    force: bool,
+) -> Result<OnboardReport, PhazeError> {
+    let path = root.join(ARCHITECTURE_FILE);
+    let mut summary = match std::fs::read_to_string(&path) {
+        Ok(doc) => Summary::parse(&doc),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Summary::default(),
+        Err(e) => return Err(e.into()),
+    };
+    let inputs = collect_inputs(root);
+    let stale = if force {
+        Section::ALL.to_vec()
+    } else {
+        summary.stale_sections(&inputs)
+    };
+
+    let task = progress::global()
+        .task("Writing architecture summary")
+        .unit("sections")
+        .total(stale.len() as u64)
+        .cancellable()
+        .start();
+    for &section in &stale {
+        if task.is_cancelled() {
+            return Err(PhazeError::Cancelled);
+        }
+        task.set_message(section.title());
+        let current = summary.section(section).map(|s| s.body.clone());
+        let body = write_section(llm, section, &inputs, current.as_deref()).await?;
+        summary.set(section, section_fingerprint(section, &inputs), body);
+        std::fs::write(&path, summary.render())?;
+        task.advance(1);
+    }
+
+    Ok(OnboardReport {
+        path,
+        unchanged: Section::ALL
+            .into_iter()
+            .filter(|s| !stale.contains(s))
+            .collect(),
+        regenerated: stale,
+    })
+}
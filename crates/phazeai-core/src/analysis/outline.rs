@@ -70,6 +70,57 @@ pub fn extract_symbols_generic(source: &str, extension: &str) -> Vec<CodeSymbol>
     symbols
 }
 
+/// Whether a symbol is part of its file's public API, judged from `line`, the
+/// line declaring it, and `parent`, the line declaring the symbol it is
+/// nested in (if any).
+///
+/// Rust items need a plain `pub` (not `pub(crate)`), except the items of
+/// traits, trait impls and enums, which share their parent's visibility.
+/// JS/TS needs `export` at the top level; class members are public unless
+/// `private`, `protected` or `#`-named. Go names start with a capital, Python
+/// names don't start with `_` (dunders aside), and Java/C# need `public`
+/// outside interfaces. Kotlin, Swift and Scala are public unless marked
+/// otherwise; C/C++ unless `static` at file level. Anything else counts as
+/// public.
+pub fn is_public_declaration(
+    extension: &str,
+    line: &str,
+    name: &str,
+    parent: Option<&str>,
+) -> bool {
+    let line = line.trim();
+    // The words before the name: keywords and modifiers.
+    let head: Vec<&str> = match line.find(name) {
+        Some(i) if !name.is_empty() => line[..i].split_whitespace().collect(),
+        _ => line.split_whitespace().collect(),
+    };
+    let has = |word: &str| head.contains(&word);
+    let parent = parent.map(str::trim);
+    let parent_is =
+        |keyword: &str| parent.is_some_and(|p| p.split_whitespace().any(|w| w == keyword));
+    match extension {
+        "rs" => {
+            has("pub")
+                || line.starts_with("impl")
+                || parent_is("trait")
+                || parent_is("enum")
+                || (parent_is("for") && parent.is_some_and(|p| p.starts_with("impl")))
+        }
+        "js" | "jsx" | "ts" | "tsx" | "mjs" => match parent {
+            None => has("export"),
+            Some(_) => !(has("private") || has("protected") || name.starts_with('#')),
+        },
+        "go" => name.chars().next().is_some_and(char::is_uppercase),
+        "py" => !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__")),
+        "java" | "cs" => has("public") || parent_is("interface"),
+        "kt" | "kts" | "swift" | "scala" => {
+            !(has("private") || has("protected") || has("internal") || has("fileprivate"))
+        }
+        "c" | "cpp" | "cc" | "cxx" | "h" | "hpp" => parent.is_some() || !has("static"),
+        _ => true,
+    }
+}
+
 /// Generate a compact repo map string from symbols (like Aider does).
 /// This gives the LLM a high-level view of a file without sending all the code.
 pub fn symbols_to_repo_map(path: &Path, symbols: &[CodeSymbol]) -> String {
@@ -0,0 +1,280 @@
+//! Diff review pipeline used by `phazeai ci-review`.
+//!
+//! Findings come from two sources: the static [`Linter`] run over changed
+//! files (restricted to added lines), and an optional LLM pass over the diff
+//! whose JSON answer is parsed by [`parse_llm_findings`]. The LLM is told
+//! which complexity hotspots the diff touches ([`changed_hotspots`]).
+//! Results can be rendered as SARIF 2.1.0 or GitHub Actions workflow
+//! annotations.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use super::linter::{Language, Linter, Severity};
+use super::metrics::{self, FileMetrics, Hotspot};
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewFinding {
+    pub path: String,
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+    /// Stable identifier for SARIF (`lint/...` or `ai-review`).
+    pub rule: String,
+}
+
+/// Added line numbers (new-file side) per path, parsed from a unified diff.
+pub fn parse_diff_added_lines(diff: &str) -> BTreeMap<String, BTreeSet<usize>> {
+    let mut out: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+    let mut current: Option<String> = None;
+    let mut new_line = 0usize;
+
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            current = path
+                .strip_prefix("b/")
+                .filter(|_| path != "/dev/null")
+                .map(str::to_string);
+        } else if line.starts_with("--- ") {
+            continue;
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            // "@@ -a,b +c,d @@ ..."
+            new_line = hunk
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(0);
+        } else if let Some(path) = &current {
+            if line.starts_with('+') {
+                out.entry(path.clone()).or_default().insert(new_line);
+                new_line += 1;
+            } else if line.starts_with(' ') {
+                new_line += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Run the static linter over each changed file, keeping issues on added lines.
+pub fn lint_changed_lines(
+    root: &Path,
+    changed: &BTreeMap<String, BTreeSet<usize>>,
+) -> Vec<ReviewFinding> {
+    let mut findings = Vec::new();
+    for (path, lines) in changed {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let language = Language::from_extension(ext);
+        let Ok(code) = std::fs::read_to_string(root.join(path)) else {
+            continue;
+        };
+        for issue in Linter::analyze(&code, language).issues {
+            if lines.contains(&issue.line) {
+                findings.push(ReviewFinding {
+                    path: path.clone(),
+                    line: issue.line,
+                    severity: issue.severity,
+                    message: issue.message,
+                    rule: "lint".to_string(),
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// The complexity hotspots among the functions the diff adds lines to.
+pub fn changed_hotspots(root: &Path, changed: &BTreeMap<String, BTreeSet<usize>>) -> Vec<Hotspot> {
+    let mut files = Vec::new();
+    for (path, lines) in changed {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let Ok(code) = std::fs::read_to_string(root.join(path)) else {
+            continue;
+        };
+        let functions: Vec<_> = metrics::measure(&code, Language::from_extension(ext))
+            .into_iter()
+            .filter(|f| lines.range(f.line..=f.end_line).next().is_some())
+            .collect();
+        files.push(FileMetrics {
+            path: path.into(),
+            lines_of_code: 0,
+            functions,
+        });
+    }
+    metrics::hotspots(&files, usize::MAX)
+}
+
+const REVIEW_PROMPT: &str = r#"You are a strict code reviewer running in CI.
+Review ONLY the lines added in the diff below. Report real problems: bugs,
+security issues, data loss, races, broken error handling. Skip style nits.
+
+Answer with a JSON array and nothing else. Each item:
+{"path": "<file path as in the diff>", "line": <new-file line number>,
+ "severity": "error" | "warning" | "info", "message": "<one sentence>"}
+Answer [] if there is nothing worth reporting."#;
+
+/// Ask the model to review `diff`, pointing out the `hotspots` it touches,
+/// and parse its findings.
+pub async fn llm_review(
+    llm: &dyn LlmClient,
+    diff: &str,
+    hotspots: &[Hotspot],
+) -> Result<Vec<ReviewFinding>, PhazeError> {
+    let mut request = format!("```diff\n{diff}\n```");
+    if !hotspots.is_empty() {
+        request.push_str(&format!(
+            "\n\nThe diff changes these complex functions; flag changes that make \
+             them harder to follow:\n{}",
+            metrics::describe_hotspots(hotspots)
+        ));
+    }
+    let messages = vec![Message::system(REVIEW_PROMPT), Message::user(request)];
+    let response = llm.chat(&messages, &[]).await?;
+    Ok(parse_llm_findings(&response.message.content))
+}
+
+/// Extract findings from a model answer. Tolerates surrounding prose or a
+/// fenced code block around the JSON array; malformed items are skipped.
+pub fn parse_llm_findings(text: &str) -> Vec<ReviewFinding> {
+    let (Some(start), Some(end)) = (text.find('['), text.rfind(']')) else {
+        return Vec::new();
+    };
+    if end < start {
+        return Vec::new();
+    }
+    let Ok(Value::Array(items)) = serde_json::from_str::<Value>(&text[start..=end]) else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(ReviewFinding {
+                path: item.get("path")?.as_str()?.to_string(),
+                line: item.get("line").and_then(Value::as_u64).unwrap_or(1).max(1) as usize,
+                severity: item
+                    .get("severity")
+                    .and_then(Value::as_str)
+                    .and_then(Severity::parse)
+                    .unwrap_or(Severity::Warning),
+                message: item.get("message")?.as_str()?.trim().to_string(),
+                rule: "ai-review".to_string(),
+            })
+        })
+        .filter(|f| !f.message.is_empty())
+        .collect()
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+/// Render findings as a SARIF 2.1.0 log.
+pub fn to_sarif(findings: &[ReviewFinding]) -> Value {
+    let rules: BTreeSet<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|f| {
+            json!({
+                "ruleId": f.rule,
+                "level": sarif_level(f.severity),
+                "message": { "text": f.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": f.path },
+                        "region": { "startLine": f.line },
+                    }
+                }],
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "PhazeAI Review",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Render findings as GitHub Actions workflow commands (`::error file=...::msg`).
+pub fn to_github_annotations(findings: &[ReviewFinding]) -> String {
+    findings
+        .iter()
+        .map(|f| {
+            let level = match f.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "notice",
+            };
+            // Workflow commands need %, CR and LF escaped in the message.
+            let message = f
+                .message
+                .replace('%', "%25")
+                .replace('\r', "%0D")
+                .replace('\n', "%0A");
+            format!("::{level} file={},line={}::{message}\n", f.path, f.line)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_added_lines_from_hunks() {
+        let diff = "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,2 +1,3 @@\n fn a() {}\n+fn b() {}\n fn c() {}\n@@ -10,0 +11,2 @@\n+x\n+y\n";
+        let lines = parse_diff_added_lines(diff);
+        let a: Vec<usize> = lines["src/a.rs"].iter().copied().collect();
+        assert_eq!(a, vec![2, 11, 12]);
+    }
+
+    #[test]
+    fn test_deleted_file_is_ignored() {
+        let diff = "--- a/gone.rs\n+++ /dev/null\n@@ -1,1 +0,0 @@\n-fn gone() {}\n";
+        assert!(parse_diff_added_lines(diff).is_empty());
+    }
+
+    #[test]
+    fn test_parse_llm_findings_with_fence() {
+        let text = "Here you go:\n```json\n[{\"path\":\"a.rs\",\"line\":3,\"severity\":\"error\",\"message\":\"bad\"},{\"line\":1}]\n```";
+        let findings = parse_llm_findings(text);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Error);
+        assert_eq!(findings[0].line, 3);
+    }
+
+    #[test]
+    fn test_github_annotation_escapes_newlines() {
+        let out = to_github_annotations(&[ReviewFinding {
+            path: "a.rs".into(),
+            line: 4,
+            severity: Severity::Warning,
+            message: "two\nlines".into(),
+            rule: "ai-review".into(),
+        }]);
+        assert_eq!(out, "::warning file=a.rs,line=4::two%0Alines\n");
+    }
+}
@@ -0,0 +1,403 @@
+//! Security checks for the [`Linter`]: secrets committed to the workspace
+//! and code patterns that let untrusted input do damage.
+//!
+//! [`Linter::security_scan`] reads every text file git wouldn't ignore,
+//! dotfiles such as `.env` included, and runs [`scan_file`] over each. Every
+//! file is searched for credentials: provider key formats (AWS, GitHub,
+//! Slack, Google, Stripe, OpenAI, Anthropic), private key blocks, and
+//! high-entropy values assigned to names like `api_key` or `password`.
+//! Source files are also checked for SQL built by formatting or
+//! concatenation, shell commands built with `format!` and friends, and, in
+//! Rust, `unwrap()` on environment, argument, stdin or request input.
+//!
+//! The checks are line-based heuristics: SQL is only recognised in upper
+//! case, and values that look like placeholders (`your-key-here`,
+//! `EXAMPLE`) are not reported. Messages never repeat a secret in full.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+
+use super::linter::{Linter, Severity};
+use super::workspace_lint::is_test_path;
+use crate::progress;
+
+/// Source of the findings in the Problems panel.
+pub const SECURITY_SOURCE: &str = "Security";
+
+/// Files larger than this are skipped (generated or vendored code).
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+/// Files read before the scan stops.
+const MAX_FILES: usize = 5000;
+/// Lines a call is followed over when looking for what it is passed.
+const MAX_STATEMENT_LINES: usize = 6;
+/// Bits per character below which an assigned value reads as a word or a
+/// placeholder rather than a generated secret.
+const MIN_SECRET_ENTROPY: f64 = 3.0;
+
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "go", "ts", "tsx", "js", "jsx", "mjs", "cjs", "java", "kt", "rb", "php", "cs",
+];
+/// Checksums and hashes that look like secrets but aren't.
+const SKIPPED_FILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.sum",
+    "poetry.lock",
+];
+/// Values that mark an assignment as documentation rather than a secret.
+const PLACEHOLDERS: &[&str] = &[
+    "example",
+    "sample",
+    "dummy",
+    "placeholder",
+    "changeme",
+    "change_me",
+    "your",
+    "fake",
+    "redacted",
+    "xxxx",
+    "****",
+    "....",
+];
+
+/// Provider credential formats, most specific first.
+static SECRET_PATTERNS: LazyLock<Vec<(&'static str, Regex)>> = LazyLock::new(|| {
+    [
+        (
+            "private key",
+            r"-----BEGIN (?:[A-Z]+ )*PRIVATE KEY(?: BLOCK)?-----",
+        ),
+        ("AWS access key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+        (
+            "GitHub token",
+            r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{60,})",
+        ),
+        ("Slack token", r"\bxox[abposr]-[A-Za-z0-9-]{10,}"),
+        ("Google API key", r"\bAIza[0-9A-Za-z_-]{35}"),
+        ("Stripe secret key", r"\b[sr]k_live_[0-9A-Za-z]{20,}"),
+        ("Anthropic API key", r"\bsk-ant-[A-Za-z0-9_-]{20,}"),
+        ("OpenAI API key", r"\bsk-(?:proj-)?[A-Za-z0-9_-]{20,}"),
+    ]
+    .into_iter()
+    .map(|(name, re)| (name, Regex::new(re).unwrap()))
+    .collect()
+});
+/// `api_key = "…"`, `"password": "…"` and the like in code and config.
+static RE_SECRET_ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)([\w.-]*(?:api[_-]?key|secret|passw(?:or)?d|token|access[_-]?key)[\w.-]*)["']?\s*(?::|=|:=|=>)\s*["']([^"'\s]{12,})["']"#).unwrap()
+});
+/// `NAME=value` in `.env` files, where values aren't quoted.
+static RE_ENV_ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^(?:export\s+)?([A-Z0-9_]*(?:KEY|SECRET|TOKEN|PASSWORD|PASSWD)[A-Z0-9_]*)\s*=\s*["']?([^"'\s#]{8,})"#).unwrap()
+});
+/// A string literal that starts a SQL statement.
+static RE_SQL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"["'`]\s*(?:SELECT\s.*\bFROM\b|INSERT\s+INTO\b|UPDATE\s+\S+\s+SET\b|DELETE\s+FROM\b|DROP\s+TABLE\b)"#).unwrap()
+});
+/// Building a string out of values: formatting macros and methods,
+/// f-strings, template literals, `%` and `+`.
+static RE_INTERPOLATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"format!\(|Sprintf\(|String\.format\(|\bf["']|\.format\(|["']\s*%\s*[\w(]|["'`]\s*\+\s*[\w(]|[\w)]\s*\+\s*["'`]|\$\{|\.concat\("#).unwrap()
+});
+/// A process started through a shell, or with a formatted program name.
+static RE_SHELL_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"Command::new\(\s*(?:"(?:sh|bash|zsh|cmd|cmd\.exe|powershell|pwsh)"|format!)|exec\.Command\(\s*"(?:sh|bash|zsh|cmd)"|\bos\.(?:system|popen)\(|\bsubprocess\.\w+\(.*shell\s*=\s*True|(?:^|[^.\w]|child_process\.)exec(?:Sync)?\(\s*[`"'\w]|Runtime\.getRuntime\(\)\.exec\("#).unwrap()
+});
+static RE_UNWRAP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\.(?:unwrap|expect)\(").unwrap());
+/// A double-quoted string literal.
+static RE_STRING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""(?:[^"\\]|\\.)*""#).unwrap());
+/// Values the program doesn't control: environment, arguments, stdin,
+/// and the usual names for a request and its parts.
+static RE_UNTRUSTED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"env::(?:var|var_os|args)\b|\bargs\(\)|\bstdin\(\)|\bread_line\(|\b(?:req|request|query|params|headers|form|payload|body)\b").unwrap()
+});
+
+/// What a [`SecurityFinding`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecurityRule {
+    /// A credential in the files.
+    Secret,
+    /// SQL built by formatting or concatenating values.
+    SqlConcatenation,
+    /// A shell command built by formatting or concatenating values.
+    CommandInjection,
+    /// `unwrap()` or `expect()` on input the program doesn't control.
+    UntrustedUnwrap,
+}
+
+impl SecurityRule {
+    /// Stable identifier, used as the diagnostic code.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Secret => "hardcoded-secret",
+            Self::SqlConcatenation => "sql-concatenation",
+            Self::CommandInjection => "command-injection",
+            Self::UntrustedUnwrap => "unwrap-untrusted-input",
+        }
+    }
+
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::Secret => Severity::Error,
+            Self::SqlConcatenation | Self::CommandInjection => Severity::Warning,
+            Self::UntrustedUnwrap => Severity::Info,
+        }
+    }
+}
+
+/// A security problem at one line of a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityFinding {
+    /// Relative to the workspace root.
+    pub path: PathBuf,
+    /// 1-based.
+    pub line: usize,
+    /// 0-based.
+    pub column: usize,
+    pub rule: SecurityRule,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Linter {
+    /// Run the security checks over the workspace at `root`.
+    pub fn security_scan(root: &Path) -> Vec<SecurityFinding> {
+        let task = progress::global()
+            .task("Scanning for security problems")
+            .unit("files")
+            .cancellable()
+            .start();
+        let mut findings = Vec::new();
+        let mut files = 0;
+        let walker = WalkBuilder::new(root)
+            .require_git(false)
+            .hidden(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(|e| {
+                !matches!(
+                    e.file_name().to_str(),
+                    Some(".git" | "node_modules" | "target")
+                )
+            })
+            .build();
+        for entry in walker.flatten() {
+            if task.is_cancelled() || files >= MAX_FILES {
+                break;
+            }
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let file_name = relative.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if SKIPPED_FILES.contains(&file_name) || file_name.ends_with(".min.js") {
+                continue;
+            }
+            if !entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES) {
+                continue;
+            }
+            // Binary files aren't UTF-8, or carry NULs when they happen to be.
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            if content.contains('\0') {
+                continue;
+            }
+            files += 1;
+            task.advance(1);
+            findings.extend(scan_file(relative, &content));
+        }
+        findings
+    }
+}
+
+/// The security findings in one file; `path` is reported as given and
+/// decides which checks apply.
+pub fn scan_file(path: &Path, content: &str) -> Vec<SecurityFinding> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let is_env = file_name == ".env" || file_name.starts_with(".env.");
+    let is_source = SOURCE_EXTENSIONS.contains(&ext);
+    let is_test = is_test_path(path);
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut findings = Vec::new();
+    let mut report = |line: usize, column: usize, rule: SecurityRule, message: String| {
+        findings.push(SecurityFinding {
+            path: path.to_path_buf(),
+            line: line + 1,
+            column,
+            rule,
+            severity: rule.severity(),
+            message,
+        });
+    };
+    for (i, line) in lines.iter().enumerate() {
+        if let Some((column, what, value)) = find_secret(line, is_env) {
+            report(
+                i,
+                column,
+                SecurityRule::Secret,
+                format!(
+                    "Possible {what} committed ({}); move it to the environment or a secret store and rotate it",
+                    redact(value)
+                ),
+            );
+            continue;
+        }
+        if !is_source || is_comment(line) {
+            continue;
+        }
+        if let Some(m) = RE_SQL.find(line) {
+            // `format!(` may end the line before the literal.
+            let opened = i
+                .checked_sub(1)
+                .map(|p| lines[p])
+                .filter(|p| p.ends_with('('));
+            let call = format!("{}{}", opened.unwrap_or(""), statement(&lines, i));
+            if RE_INTERPOLATION.is_match(&call) {
+                report(
+                    i,
+                    m.start(),
+                    SecurityRule::SqlConcatenation,
+                    "SQL built from values is open to injection; use bound parameters".to_string(),
+                );
+                continue;
+            }
+        }
+        if let Some(m) = RE_SHELL_COMMAND.find(line) {
+            let call = statement(&lines, i);
+            if m.as_str().ends_with("format!") || RE_INTERPOLATION.is_match(&call) {
+                report(
+                    i,
+                    m.start(),
+                    SecurityRule::CommandInjection,
+                    "Shell command built from values is open to injection; pass arguments separately without a shell".to_string(),
+                );
+                continue;
+            }
+        }
+        if ext == "rs" && !is_test {
+            if let Some(m) = RE_UNWRAP.find(line) {
+                // What is unwrapped: the right of an assignment, not its name.
+                let expr = &line[..m.start()];
+                let expr = expr.rsplit_once('=').map_or(expr, |(_, e)| e);
+                if RE_UNTRUSTED.is_match(&RE_STRING.replace_all(expr, "\"\"")) {
+                    report(
+                        i,
+                        m.start() + 1,
+                        SecurityRule::UntrustedUnwrap,
+                        "Unwrapping untrusted input panics when it is malformed; handle the error"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+    }
+    findings
+}
+
+/// The column, kind and value of a credential on `line`.
+fn find_secret(line: &str, is_env: bool) -> Option<(usize, &'static str, &str)> {
+    for (what, re) in SECRET_PATTERNS.iter() {
+        if let Some(m) = re.find(line) {
+            if !is_placeholder(m.as_str()) {
+                return Some((m.start(), what, m.as_str()));
+            }
+        }
+    }
+    let (re, min_entropy) = if is_env {
+        (&*RE_ENV_ASSIGNMENT, 0.0)
+    } else {
+        (&*RE_SECRET_ASSIGNMENT, MIN_SECRET_ENTROPY)
+    };
+    let caps = re.captures(line)?;
+    let value = caps.get(2)?;
+    if is_placeholder(value.as_str())
+        || looks_like_name(value.as_str())
+        || entropy(value.as_str()) < min_entropy
+    {
+        return None;
+    }
+    Some((value.start(), "secret", value.as_str()))
+}
+
+fn is_placeholder(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    PLACEHOLDERS.iter().any(|p| lower.contains(p))
+        || value.contains("${")
+        || value.contains("{{")
+        || value.starts_with('<')
+        || value.starts_with('$')
+        || value
+            .chars()
+            .all(|c| c == value.chars().next().unwrap_or(c))
+}
+
+/// Whether an assigned value is a path, a URL or the name of something
+/// (`"OPENAI_API_KEY"`, `"hardcoded-secret"`) rather than a credential,
+/// which mixes letters and digits.
+fn looks_like_name(value: &str) -> bool {
+    !value.chars().any(|c| c.is_ascii_digit())
+        || !value.chars().any(|c| c.is_ascii_alphabetic())
+        || value.contains("://")
+        || value.starts_with('/')
+        || value.starts_with('.')
+        || (value.contains('_')
+            && value
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+}
+
+/// Shannon entropy of `value` in bits per character.
+fn entropy(value: &str) -> f64 {
+    let mut counts = std::collections::HashMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+    }
+    let len = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// The first four characters of a secret, so it can be recognised without
+/// being repeated.
+fn redact(value: &str) -> String {
+    let shown: String = value.chars().take(4).collect();
+    format!("{shown}…")
+}
+
+fn is_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with('*')
+}
+
+/// Line `i` and the lines after it up to where its parentheses close and
+/// its method chain ends, so a call's arguments are seen when they're split
+/// over lines.
+fn statement(lines: &[&str], i: usize) -> String {
+    let mut depth = 0i32;
+    let mut out = String::new();
+    let mut rest = lines.iter().skip(i).take(MAX_STATEMENT_LINES).peekable();
+    while let Some(line) = rest.next() {
+        out.push_str(line);
+        out.push('\n');
+        depth += line.matches('(').count() as i32 - line.matches(')').count() as i32;
+        let chained = rest
+            .peek()
+            .is_some_and(|next| next.trim_start().starts_with('.'));
+        if depth <= 0 && !chained {
+            break;
+        }
+    }
+    out
+}
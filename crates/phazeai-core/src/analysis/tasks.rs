@@ -0,0 +1,246 @@
+//! Action items pulled out of a conversation or a selection.
+//!
+//! [`extract_tasks`] asks a model for the action items in some text and
+//! [`add_to_plan`] appends the new ones to the workspace's `plan.md` as a
+//! Markdown checklist, which the IDE's Tasks panel lists. Lines of the plan
+//! that aren't checklist items are left alone, so it can hold notes too.
+//! A task filed as a GitHub issue keeps the issue's URL on its line.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+
+/// The plan, relative to the workspace root.
+pub const PLAN_FILE: &str = "plan.md";
+
+/// Only the end of longer text is sent; in a conversation that's where the
+/// decisions are.
+const MAX_SOURCE_CHARS: usize = 24_000;
+
+/// Separates a task's title from its detail on a plan line.
+const DETAIL_SEPARATOR: &str = " — ";
+
+/// One item of the plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanTask {
+    pub title: String,
+    /// What needs doing beyond the title; may be empty.
+    pub detail: String,
+    pub done: bool,
+    /// URL of the GitHub issue the task was filed as.
+    pub issue: Option<String>,
+}
+
+impl PlanTask {
+    pub fn new(title: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            detail: detail.into(),
+            done: false,
+            issue: None,
+        }
+    }
+
+    /// The task as a plan line: `- [ ] Title — detail (issue URL)`.
+    pub fn to_line(&self) -> String {
+        let mut line = format!("- [{}] {}", if self.done { "x" } else { " " }, self.title);
+        if !self.detail.is_empty() {
+            line.push_str(DETAIL_SEPARATOR);
+            line.push_str(&self.detail);
+        }
+        if let Some(url) = &self.issue {
+            line.push_str(&format!(" ({url})"));
+        }
+        line
+    }
+
+    /// The task on a checklist line of the plan.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let rest = line.trim_start().strip_prefix("- [")?;
+        let (mark, rest) = rest.split_once(']')?;
+        let done = match mark {
+            " " => false,
+            "x" | "X" => true,
+            _ => return None,
+        };
+        let mut rest = rest.trim();
+        let mut issue = None;
+        if let Some(open) = rest.rfind(" (http") {
+            if let Some(url) = rest[open + 2..].strip_suffix(')') {
+                issue = Some(url.to_string());
+                rest = rest[..open].trim_end();
+            }
+        }
+        let (title, detail) = rest.split_once(DETAIL_SEPARATOR).unwrap_or((rest, ""));
+        if title.trim().is_empty() {
+            return None;
+        }
+        Some(Self {
+            title: title.trim().to_string(),
+            detail: detail.trim().to_string(),
+            done,
+            issue,
+        })
+    }
+
+    /// The body of a GitHub issue for the task.
+    pub fn issue_body(&self) -> String {
+        let mut body = self.detail.clone();
+        if !body.is_empty() {
+            body.push_str("\n\n");
+        }
+        body.push_str(&format!("_From `{PLAN_FILE}`._"));
+        body
+    }
+}
+
+/// The tasks in the text of a plan, in order.
+pub fn parse_plan(text: &str) -> Vec<PlanTask> {
+    text.lines().filter_map(PlanTask::parse_line).collect()
+}
+
+/// The tasks in the workspace's plan; none when there is no plan yet.
+pub fn read_plan(root: &Path) -> Vec<PlanTask> {
+    std::fs::read_to_string(root.join(PLAN_FILE))
+        .map(|text| parse_plan(&text))
+        .unwrap_or_default()
+}
+
+/// Append the tasks the plan doesn't have yet (by title, ignoring case),
+/// creating the plan if needed. Returns how many were added.
+pub fn add_to_plan(root: &Path, tasks: &[PlanTask]) -> Result<usize, PhazeError> {
+    let path = root.join(PLAN_FILE);
+    let mut text = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut known: Vec<String> = parse_plan(&text)
+        .into_iter()
+        .map(|t| t.title.to_lowercase())
+        .collect();
+    let mut added = 0;
+    for task in tasks {
+        let key = task.title.to_lowercase();
+        if known.contains(&key) {
+            continue;
+        }
+        if text.trim().is_empty() {
+            text = "# Plan\n\n".to_string();
+        } else if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&task.to_line());
+        text.push('\n');
+        known.push(key);
+        added += 1;
+    }
+    if added > 0 {
+        std::fs::write(&path, text)?;
+    }
+    Ok(added)
+}
+
+/// Replace the plan's task titled `title` with `task`, keeping its place
+/// and every other line. Returns whether the task was found.
+pub fn update_in_plan(root: &Path, title: &str, task: &PlanTask) -> Result<bool, PhazeError> {
+    let path = root.join(PLAN_FILE);
+    let text = std::fs::read_to_string(&path)?;
+    let mut found = false;
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match PlanTask::parse_line(line) {
+            Some(existing) if !found && existing.title == title => {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                lines.push(format!("{indent}{}", task.to_line()));
+                found = true;
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    if found {
+        let mut text = lines.join("\n");
+        text.push('\n');
+        std::fs::write(&path, text)?;
+    }
+    Ok(found)
+}
+
+#[derive(Deserialize)]
+struct ExtractedTask {
+    title: String,
+    #[serde(default)]
+    detail: String,
+}
+
+/// The tasks in a model's reply: a JSON array of `{title, detail}`, or,
+/// when the reply holds no such array, its Markdown list items.
+pub fn parse_extracted_tasks(reply: &str) -> Vec<PlanTask> {
+    let json = match (reply.find('['), reply.rfind(']')) {
+        (Some(start), Some(end)) if start < end => {
+            serde_json::from_str::<Vec<ExtractedTask>>(&reply[start..=end]).ok()
+        }
+        _ => None,
+    };
+    // `- [ ]` is an empty JSON array too.
+    let json = json.filter(|items| !items.is_empty());
+    let tasks: Vec<PlanTask> = match json {
+        Some(items) => items
+            .into_iter()
+            .map(|t| PlanTask::new(t.title.trim(), t.detail.trim()))
+            .collect(),
+        None => reply.lines().filter_map(list_item).collect(),
+    };
+    let mut unique: Vec<PlanTask> = Vec::new();
+    for task in tasks {
+        let title = task.title.to_lowercase();
+        if !task.title.is_empty() && !unique.iter().any(|t| t.title.to_lowercase() == title) {
+            unique.push(task);
+        }
+    }
+    unique
+}
+
+/// A task from a Markdown list item: `- [ ] …`, `- …`, `* …` or `1. …`.
+fn list_item(line: &str) -> Option<PlanTask> {
+    if let Some(task) = PlanTask::parse_line(line) {
+        return Some(PlanTask::new(task.title, task.detail));
+    }
+    let line = line.trim_start();
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            (digits > 0)
+                .then(|| line[digits..].strip_prefix(". "))
+                .flatten()
+        })?;
+    let rest = rest.trim().trim_matches('*').trim();
+    let (title, detail) = rest
+        .split_once(DETAIL_SEPARATOR)
+        .or_else(|| rest.split_once(": "))
+        .unwrap_or((rest, ""));
+    (!title.trim().is_empty()).then(|| PlanTask::new(title.trim(), detail.trim()))
+}
+
+const EXTRACT_PROMPT: &str = "You turn discussions and code into a task list. \
+Find the action items in the text: work someone agreed or still needs to do, \
+follow-ups, and TODO / FIXME notes. Skip work the text says is already done. \
+Give each a short imperative title and, when the text says more, a one-sentence \
+detail naming the files or functions involved. Reply with only a JSON array, \
+one object per task: {\"title\": \"...\", \"detail\": \"...\"}. Reply with [] \
+when there are none.";
+
+/// Ask `llm` for the action items in `text`.
+pub async fn extract_tasks(llm: &dyn LlmClient, text: &str) -> Result<Vec<PlanTask>, PhazeError> {
+    let mut start = text.len().saturating_sub(MAX_SOURCE_CHARS);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let messages = vec![
+        Message::system(EXTRACT_PROMPT),
+        Message::user(&text[start..]),
+    ];
+    let response = llm.chat(&messages, &[]).await?;
+    Ok(parse_extracted_tasks(&response.message.content))
+}
@@ -0,0 +1,233 @@
+//! TODO / FIXME / HACK comments across a workspace.
+//!
+//! [`scan_workspace`] walks the tree the way ripgrep does — honoring
+//! `.gitignore`, skipping hidden, binary and very large files — and collects
+//! tagged comments. [`attribute_authors`] fills in who last changed each line
+//! from `git blame`, and [`todos_context`] renders the list for the agent.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::git::GitOps;
+use crate::progress;
+
+/// Tags recognized in comments, matched case-sensitively as whole words.
+pub const TODO_TAGS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// Files larger than this are skipped (generated or vendored code).
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Comments collected before the scan stops.
+pub const MAX_TODOS: usize = 5000;
+
+/// Markers that start a comment, for the languages the IDE highlights.
+const COMMENT_MARKERS: &[&str] = &["//", "/*", "#", "--", "<!--", ";;"];
+
+/// A tagged comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoComment {
+    /// Path relative to the workspace root.
+    pub path: PathBuf,
+    /// 0-based line.
+    pub line: u32,
+    pub tag: &'static str,
+    /// The comment after the tag, without `(owner):` and closing markers.
+    pub text: String,
+    /// Who last changed the line, from `git blame`; `None` until attributed,
+    /// or when the line isn't committed.
+    pub author: Option<String>,
+}
+
+/// The tag and text of a tagged comment on `line`, if there is one. The tag
+/// has to open a comment that isn't inside a string, as in `// TODO: ...`,
+/// `# FIXME(ana) ...` or `/* HACK */`.
+pub fn parse_todo(line: &str) -> Option<(&'static str, String)> {
+    comment_starts(line).into_iter().find_map(|start| {
+        let body = line[start..].trim_start_matches(['/', '*', '#', '-', '!', '<', ';', ' ', '\t']);
+        let tag = TODO_TAGS.iter().find(|t| body.starts_with(**t))?;
+        let rest = &body[tag.len()..];
+        if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            return None;
+        }
+        Some((*tag, clean_text(rest)))
+    })
+}
+
+/// Byte offsets of comment markers outside string literals. A `#` can be
+/// an attribute or a raw string rather than a comment, so every marker is
+/// a candidate.
+fn comment_starts(line: &str) -> Vec<usize> {
+    let trimmed = line.trim_start();
+    // Continuation lines of block comments.
+    if trimmed.starts_with('*') {
+        return vec![line.len() - trimmed.len()];
+    }
+    let mut starts = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        } else if COMMENT_MARKERS.iter().any(|m| line[i..].starts_with(m)) {
+            starts.push(i);
+        }
+    }
+    starts
+}
+
+/// Drop an `(owner)`, the separator after the tag and closing comment markers.
+fn clean_text(rest: &str) -> String {
+    let mut text = rest.trim_start();
+    if text.starts_with('(') {
+        if let Some(end) = text.find(')') {
+            text = &text[end + 1..];
+        }
+    }
+    let text = text.trim_start_matches([':', '-', ' ', '\t']);
+    let text = text.trim_end();
+    let text = text
+        .strip_suffix("-->")
+        .or_else(|| text.strip_suffix("*/"))
+        .unwrap_or(text);
+    text.trim_end().to_string()
+}
+
+/// Tagged comments in one file's `content`.
+pub fn scan_text(path: &Path, content: &str) -> Vec<TodoComment> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let (tag, text) = parse_todo(line)?;
+            Some(TodoComment {
+                path: path.to_path_buf(),
+                line: i as u32,
+                tag,
+                text,
+                author: None,
+            })
+        })
+        .collect()
+}
+
+/// Tagged comments in every file under `root` that git wouldn't ignore,
+/// sorted by path and line. Stops after [`MAX_TODOS`].
+pub fn scan_workspace(root: &Path) -> Vec<TodoComment> {
+    let mut out = Vec::new();
+    let task = progress::global()
+        .task("Scanning TODOs")
+        .unit("files")
+        .cancellable()
+        .start();
+    let walker = WalkBuilder::new(root).require_git(false).build();
+    for entry in walker.flatten() {
+        if task.is_cancelled() {
+            break;
+        }
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        task.advance(1);
+        if !entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES) {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(entry.path()) else {
+            continue;
+        };
+        if bytes.iter().take(8192).any(|&b| b == 0) {
+            continue;
+        }
+        let Ok(content) = String::from_utf8(bytes) else {
+            continue;
+        };
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        out.extend(scan_text(relative, &content));
+        if out.len() >= MAX_TODOS {
+            out.truncate(MAX_TODOS);
+            break;
+        }
+    }
+    out.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+    out
+}
+
+/// Fill in each comment's author from `git blame`, one blame per file.
+/// Files git doesn't know about are left unattributed.
+pub async fn attribute_authors(root: &Path, todos: &mut [TodoComment]) {
+    let git = GitOps::new(root);
+    let files: BTreeSet<PathBuf> = todos.iter().map(|t| t.path.clone()).collect();
+    for file in files {
+        let Ok(blame) = git.blame(&root.join(&file)).await else {
+            continue;
+        };
+        for todo in todos.iter_mut().filter(|t| t.path == file) {
+            todo.author = blame
+                .get(todo.line as usize)
+                .filter(|b| !b.is_uncommitted())
+                .map(|b| b.author.clone());
+        }
+    }
+}
+
+/// Narrows the list in the TODO panel.
+#[derive(Debug, Clone, Default)]
+pub struct TodoFilter {
+    pub tag: Option<String>,
+    pub author: Option<String>,
+    /// Case-insensitive text to find in the comment or path.
+    pub text: String,
+}
+
+impl TodoFilter {
+    pub fn matches(&self, todo: &TodoComment) -> bool {
+        if self.tag.as_deref().is_some_and(|t| t != todo.tag) {
+            return false;
+        }
+        if self.author.is_some() && self.author != todo.author {
+            return false;
+        }
+        let needle = self.text.trim().to_lowercase();
+        needle.is_empty()
+            || todo.text.to_lowercase().contains(&needle)
+            || todo.path.to_string_lossy().to_lowercase().contains(&needle)
+    }
+}
+
+/// Distinct authors of `todos`, sorted.
+pub fn todo_authors(todos: &[TodoComment]) -> Vec<String> {
+    let authors: BTreeSet<&String> = todos.iter().filter_map(|t| t.author.as_ref()).collect();
+    authors.into_iter().cloned().collect()
+}
+
+/// The comments as a context block for the agent, listing at most `limit`.
+pub fn todos_context(todos: &[TodoComment], limit: usize) -> String {
+    let mut out = format!("## Open TODO comments ({})\n", todos.len());
+    for todo in todos.iter().take(limit) {
+        out.push_str(&format!(
+            "- {}:{} {}: {}",
+            todo.path.display(),
+            todo.line + 1,
+            todo.tag,
+            todo.text
+        ));
+        if let Some(author) = &todo.author {
+            out.push_str(&format!(" ({author})"));
+        }
+        out.push('\n');
+    }
+    if todos.len() > limit {
+        out.push_str(&format!("- … and {} more\n", todos.len() - limit));
+    }
+    out
+}
@@ -0,0 +1,664 @@
+//! Cross-file checks for the [`Linter`]: public items nothing references,
+//! dependencies no source file uses, and blocks of code copied between
+//! files.
+//!
+//! [`Linter::analyze_workspace`] reads every source file and manifest git
+//! wouldn't ignore and runs the three checks over them. References are
+//! counted as identifier occurrences across the workspace, so an item used
+//! only through a macro or by another program is reported too; the IDE asks
+//! the language server before showing unused items. Findings that can be
+//! removed carry a [`Removal`] that deletes them.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::linter::{Linter, Severity};
+use crate::progress;
+
+/// Source of the findings in the Problems panel.
+pub const ANALYSIS_SOURCE: &str = "Analysis";
+
+/// Files larger than this are skipped (generated or vendored code).
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+/// Source files read before the scan stops.
+const MAX_FILES: usize = 3000;
+/// Significant lines (not blank, not braces, not comments or imports) a
+/// copied block needs before it is reported.
+pub const MIN_DUPLICATE_LINES: usize = 8;
+
+const SOURCE_EXTENSIONS: &[&str] = &["rs", "py", "go", "ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+static RE_IDENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z_$][A-Za-z0-9_$]*").unwrap());
+static RE_RUST_ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^pub (?:async |unsafe |const |extern \S+ )*(?:fn|struct|enum|trait|type|const|static|union) (?:mut )?([A-Za-z_][A-Za-z0-9_]*)").unwrap()
+});
+static RE_PY_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:async def|def|class) ([A-Za-z][A-Za-z0-9_]*)").unwrap());
+static RE_JS_ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^export (?:declare )?(?:async )?(?:function\*?|class|const|let|var|interface|type|enum|abstract class) ([A-Za-z_$][A-Za-z0-9_$]*)").unwrap()
+});
+static RE_GO_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:func|type|var|const) ([A-Z][A-Za-z0-9_]*)").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FindingKind {
+    UnusedItem,
+    UnusedDependency,
+    DuplicateCode,
+}
+
+/// A quick fix: replace lines `start_line..end_line` (0-based, end
+/// exclusive) of the finding's file with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Removal {
+    pub title: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub replacement: String,
+}
+
+impl Removal {
+    fn delete(title: String, start_line: usize, end_line: usize) -> Self {
+        Self {
+            title,
+            start_line,
+            end_line,
+            replacement: String::new(),
+        }
+    }
+
+    /// `content` with the fix applied. Line endings are kept.
+    pub fn apply(&self, content: &str) -> String {
+        let mut out = String::with_capacity(content.len());
+        for (i, line) in content.split_inclusive('\n').enumerate() {
+            if i == self.start_line {
+                out.push_str(&self.replacement);
+            }
+            if i < self.start_line || i >= self.end_line {
+                out.push_str(line);
+            }
+        }
+        if self.start_line >= content.split_inclusive('\n').count() {
+            out.push_str(&self.replacement);
+        }
+        out
+    }
+}
+
+/// A problem found by looking at more than one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceFinding {
+    /// Relative to the workspace root.
+    pub path: PathBuf,
+    /// 1-based.
+    pub line: usize,
+    /// 0-based column of the item's name, for asking the language server.
+    pub column: usize,
+    /// 1-based, inclusive.
+    pub end_line: usize,
+    pub severity: Severity,
+    pub kind: FindingKind,
+    pub message: String,
+    pub fix: Option<Removal>,
+}
+
+impl Linter {
+    /// Run the cross-file checks over the workspace at `root`.
+    pub fn analyze_workspace(root: &Path) -> Vec<WorkspaceFinding> {
+        let task = progress::global()
+            .task("Analyzing workspace")
+            .unit("files")
+            .cancellable()
+            .start();
+        let mut sources = Vec::new();
+        let mut manifests = Vec::new();
+        let walker = WalkBuilder::new(root)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(|e| !matches!(e.file_name().to_str(), Some("node_modules" | "target")))
+            .build();
+        for entry in walker.flatten() {
+            if task.is_cancelled() || sources.len() >= MAX_FILES {
+                break;
+            }
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let file_name = relative.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let is_manifest = matches!(file_name, "Cargo.toml" | "package.json" | "go.mod");
+            let ext = relative.extension().and_then(|e| e.to_str()).unwrap_or("");
+            if !is_manifest && !SOURCE_EXTENSIONS.contains(&ext) {
+                continue;
+            }
+            if !entry.metadata().is_ok_and(|m| m.len() <= MAX_FILE_BYTES) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            if is_manifest {
+                manifests.push((relative.to_path_buf(), content));
+            } else {
+                task.advance(1);
+                sources.push((relative.to_path_buf(), content));
+            }
+        }
+
+        let mut findings = unused_public_items(&sources);
+        for (manifest, content) in &manifests {
+            findings.extend(unused_dependencies(manifest, content, &sources));
+        }
+        findings.extend(duplicate_blocks(&sources));
+        findings.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        findings
+    }
+}
+
+/// Whether `path` is test, example or benchmark code, whose items aren't
+/// API and whose copies are expected.
+pub(super) fn is_test_path(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    path.components().any(|c| {
+        matches!(
+            c.as_os_str().to_str(),
+            Some("tests" | "test" | "__tests__" | "examples" | "benches" | "testdata")
+        )
+    }) || name.ends_with("_test.go")
+        || name.starts_with("test_")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+}
+
+/// Public top-level items of `files` whose name appears nowhere else in
+/// them. Only items declared at column 0 are considered.
+pub fn unused_public_items(files: &[(PathBuf, String)]) -> Vec<WorkspaceFinding> {
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for (_, content) in files {
+        for ident in RE_IDENT.find_iter(content) {
+            *uses.entry(ident.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (path, content) in files {
+        if is_test_path(path) {
+            continue;
+        }
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let pattern: &Regex = match ext {
+            "rs" => &RE_RUST_ITEM,
+            "py" => &RE_PY_ITEM,
+            "go" => &RE_GO_ITEM,
+            _ if SOURCE_EXTENSIONS.contains(&ext) => &RE_JS_ITEM,
+            _ => continue,
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        for (i, line) in lines.iter().enumerate() {
+            let Some(name) = pattern.captures(line).and_then(|c| c.get(1)) else {
+                continue;
+            };
+            let ident = name.as_str();
+            if uses.get(ident).copied().unwrap_or(0) > 1
+                || is_entry_point_name(ident)
+                || is_exempt(&lines, i, ext)
+            {
+                continue;
+            }
+            let (start, end) = item_extent(&lines, i, ext);
+            findings.push(WorkspaceFinding {
+                path: path.clone(),
+                line: i + 1,
+                column: name.start(),
+                end_line: end,
+                severity: Severity::Warning,
+                kind: FindingKind::UnusedItem,
+                message: format!("`{ident}` is public but never used in the workspace"),
+                fix: Some(Removal::delete(format!("Remove `{ident}`"), start, end)),
+            });
+        }
+    }
+    findings
+}
+
+/// Names called by a runtime or test harness rather than by code.
+fn is_entry_point_name(name: &str) -> bool {
+    name == "main"
+        || name == "init"
+        || name.starts_with("test")
+        || ["Test", "Benchmark", "Example", "Fuzz"].iter().any(|p| {
+            name.strip_prefix(p)
+                .is_some_and(|rest| rest.starts_with(char::is_uppercase))
+        })
+}
+
+/// Whether the attributes or decorators above line `i` say something else
+/// calls the item: a test harness, FFI, a web framework route.
+fn is_exempt(lines: &[&str], i: usize, ext: &str) -> bool {
+    lines[..i]
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+        .take_while(|l| is_leading_line(l, ext))
+        .any(|l| {
+            (l.starts_with("#[") || l.starts_with('@'))
+                && !l.starts_with("#[derive")
+                && !l.starts_with("#[must_use")
+                && !l.starts_with("#[inline")
+                && !l.starts_with("#[deprecated")
+        })
+}
+
+/// Doc comments, attributes and decorators that belong to the item below.
+fn is_leading_line(trimmed: &str, ext: &str) -> bool {
+    match ext {
+        "rs" => trimmed.starts_with("///") || trimmed.starts_with("#["),
+        "py" => trimmed.starts_with('@'),
+        "go" => trimmed.starts_with("//"),
+        _ => {
+            trimmed.starts_with('@')
+                || trimmed.starts_with("/**")
+                || trimmed.starts_with('*')
+                || trimmed.starts_with("///")
+        }
+    }
+}
+
+/// Lines `start..end` (0-based, end exclusive) spanned by the item declared
+/// on line `decl`, with its doc comments and attributes, and the blank line
+/// after it when one is left above (all of them at the top of the file).
+fn item_extent(lines: &[&str], decl: usize, ext: &str) -> (usize, usize) {
+    let mut start = decl;
+    while start > 0 && is_leading_line(lines[start - 1].trim(), ext) {
+        start -= 1;
+    }
+
+    let mut end = decl + 1;
+    if ext == "py" {
+        let indent = |l: &str| l.len() - l.trim_start().len();
+        let own = indent(lines[decl]);
+        while end < lines.len() && (lines[end].trim().is_empty() || indent(lines[end]) > own) {
+            end += 1;
+        }
+        while end > decl + 1 && lines[end - 1].trim().is_empty() {
+            end -= 1;
+        }
+    } else {
+        let mut depth = 0i32;
+        let mut opened = false;
+        'lines: for (j, line) in lines.iter().enumerate().skip(decl) {
+            let code = line.split("//").next().unwrap_or("");
+            for c in code.chars() {
+                match c {
+                    '{' | '(' | '[' => {
+                        depth += 1;
+                        opened |= c == '{';
+                    }
+                    '}' | ')' | ']' => depth -= 1,
+                    ';' if depth == 0 => {
+                        end = j + 1;
+                        break 'lines;
+                    }
+                    _ => {}
+                }
+            }
+            end = j + 1;
+            if opened && depth <= 0 {
+                break;
+            }
+            // Rust items end at a `;` or a closing brace; Go and JS/TS ones
+            // without a body can end at the line, as in `type ID int`.
+            if ext != "rs" && !opened && depth == 0 {
+                let continues = code.trim_end().ends_with(['=', ',', '|', '&', '>'])
+                    || lines.get(j + 1).is_some_and(|n| {
+                        n.trim_start().starts_with(['|', '&', '.', '?', ':', '{'])
+                    });
+                if !continues {
+                    break;
+                }
+            }
+        }
+    }
+
+    let blank = |l: &&str| l.trim().is_empty();
+    if start == 0 {
+        while lines.get(end).is_some_and(blank) {
+            end += 1;
+        }
+    } else if blank(&lines[start - 1]) && lines.get(end).is_some_and(blank) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Dependencies declared in `manifest` (a `Cargo.toml`, `package.json` or
+/// `go.mod` at a path relative to the workspace root) that no source file
+/// of its package mentions.
+pub fn unused_dependencies(
+    manifest: &Path,
+    content: &str,
+    sources: &[(PathBuf, String)],
+) -> Vec<WorkspaceFinding> {
+    let dir = manifest.parent().unwrap_or(Path::new(""));
+    let in_package = |ext: &[&str]| {
+        sources
+            .iter()
+            .filter(move |(p, _)| {
+                p.starts_with(dir)
+                    && p.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| ext.contains(&e))
+            })
+            .collect::<Vec<_>>()
+    };
+    let file_name = manifest.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let lines: Vec<&str> = content.lines().collect();
+    let mut findings = Vec::new();
+    let mut report = |line: usize, name: &str, fix: Option<Removal>| {
+        findings.push(WorkspaceFinding {
+            path: manifest.to_path_buf(),
+            line: line + 1,
+            column: lines[line].find(name).unwrap_or(0),
+            end_line: fix.as_ref().map_or(line + 1, |f| f.end_line.max(line + 1)),
+            severity: Severity::Warning,
+            kind: FindingKind::UnusedDependency,
+            message: format!("Dependency `{name}` is not used by any source file"),
+            fix,
+        });
+    };
+
+    match file_name {
+        "Cargo.toml" => {
+            let rust = in_package(&["rs"]);
+            if rust.is_empty() {
+                return findings;
+            }
+            let idents: HashSet<&str> = rust
+                .iter()
+                .flat_map(|(_, c)| RE_IDENT.find_iter(c).map(|m| m.as_str()))
+                .collect();
+            let build_idents: HashSet<&str> = rust
+                .iter()
+                .filter(|(p, _)| p == &dir.join("build.rs"))
+                .flat_map(|(_, c)| RE_IDENT.find_iter(c).map(|m| m.as_str()))
+                .collect();
+            for (line, section, name) in cargo_dependencies(&lines) {
+                let ident = name.replace('-', "_");
+                let used = if section == "build-dependencies" {
+                    build_idents.contains(ident.as_str())
+                } else {
+                    idents.contains(ident.as_str())
+                };
+                if !used {
+                    let fix = cargo_removal(&lines, line, &name);
+                    report(line, &name, fix);
+                }
+            }
+        }
+        "package.json" => {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+                return findings;
+            };
+            let Some(deps) = json.get("dependencies").and_then(|d| d.as_object()) else {
+                return findings;
+            };
+            let js = in_package(&["js", "jsx", "ts", "tsx", "mjs", "cjs"]);
+            let scripts = json
+                .get("scripts")
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+            for name in deps.keys() {
+                let mentioned = js.iter().any(|(_, c)| {
+                    ['\'', '"', '`'].iter().any(|q| {
+                        c.contains(&format!("{q}{name}{q}")) || c.contains(&format!("{q}{name}/"))
+                    })
+                });
+                if mentioned || scripts.contains(name.as_str()) {
+                    continue;
+                }
+                if let Some(line) = json_dependency_line(&lines, name) {
+                    let fix = json_removal(&lines, line, name);
+                    report(line, name, Some(fix));
+                }
+            }
+        }
+        "go.mod" => {
+            let go = in_package(&["go"]);
+            if go.is_empty() {
+                return findings;
+            }
+            let mut in_block = false;
+            for (i, line) in lines.iter().enumerate() {
+                let trimmed = line.trim();
+                if trimmed.starts_with("require (") {
+                    in_block = true;
+                    continue;
+                }
+                if in_block && trimmed == ")" {
+                    in_block = false;
+                    continue;
+                }
+                let spec = if in_block {
+                    trimmed
+                } else if let Some(rest) = trimmed.strip_prefix("require ") {
+                    rest.trim()
+                } else {
+                    continue;
+                };
+                if spec.is_empty() || spec.starts_with("//") || spec.contains("// indirect") {
+                    continue;
+                }
+                let Some(module) = spec.split_whitespace().next() else {
+                    continue;
+                };
+                let quoted = format!("\"{module}");
+                if !go.iter().any(|(_, c)| c.contains(&quoted)) {
+                    let fix = Removal::delete(format!("Remove `{module}`"), i, i + 1);
+                    report(i, module, Some(fix));
+                }
+            }
+        }
+        _ => {}
+    }
+    findings
+}
+
+/// `(line, section, name)` of each entry in a Cargo manifest's
+/// `[dependencies]`, `[dev-dependencies]` and `[build-dependencies]`,
+/// including `[dependencies.name]` tables.
+fn cargo_dependencies(lines: &[&str]) -> Vec<(usize, &'static str, String)> {
+    const SECTIONS: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+    let mut out = Vec::new();
+    let mut section: Option<&'static str> = None;
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if let Some(header) = trimmed.strip_prefix('[').and_then(|h| h.split(']').next()) {
+            section = SECTIONS.iter().copied().find(|s| header == *s);
+            if let Some((table, name)) = header.split_once('.') {
+                if let Some(s) = SECTIONS.iter().copied().find(|s| table == *s) {
+                    out.push((i, s, name.trim_matches('"').to_string()));
+                }
+            }
+            continue;
+        }
+        let Some(s) = section else {
+            continue;
+        };
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some((key, _)) = trimmed.split_once('=') {
+            let key = key.trim().trim_matches('"');
+            if !key.is_empty() && !key.contains(['.', ' ']) {
+                out.push((i, s, key.to_string()));
+            }
+        }
+    }
+    out
+}
+
+/// Deletes a Cargo dependency: its line when the entry fits on one, or the
+/// whole `[dependencies.name]` table. Multi-line inline tables get none.
+fn cargo_removal(lines: &[&str], line: usize, name: &str) -> Option<Removal> {
+    let title = format!("Remove `{name}`");
+    let trimmed = lines[line].trim();
+    if trimmed.starts_with('[') {
+        let mut end = line + 1;
+        while end < lines.len() && !lines[end].trim().starts_with('[') {
+            end += 1;
+        }
+        while end > line + 1 && lines[end - 1].trim().is_empty() {
+            end -= 1;
+        }
+        return Some(Removal::delete(title, line, end));
+    }
+    let balanced =
+        |open: char, close: char| trimmed.matches(open).count() == trimmed.matches(close).count();
+    (balanced('{', '}') && balanced('[', ']')).then(|| Removal::delete(title, line, line + 1))
+}
+
+/// Line of `name`'s entry in a `package.json`'s `"dependencies"` object.
+fn json_dependency_line(lines: &[&str], name: &str) -> Option<usize> {
+    let start = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with("\"dependencies\""))?;
+    let key = format!("\"{name}\"");
+    lines
+        .iter()
+        .enumerate()
+        .skip(start + 1)
+        .take_while(|(_, l)| !l.trim_start().starts_with('}'))
+        .find(|(_, l)| l.trim_start().starts_with(&key))
+        .map(|(i, _)| i)
+}
+
+/// Deletes a `package.json` entry, moving the comma off the entry above
+/// when the last one goes.
+fn json_removal(lines: &[&str], line: usize, name: &str) -> Removal {
+    let title = format!("Remove `{name}`");
+    let last = !lines[line].trim_end().ends_with(',');
+    let above = line - 1;
+    if last && lines[above].trim_end().ends_with(',') {
+        let kept = lines[above].trim_end().trim_end_matches(',');
+        return Removal {
+            title,
+            start_line: above,
+            end_line: line + 1,
+            replacement: format!("{kept}\n"),
+        };
+    }
+    Removal::delete(title, line, line + 1)
+}
+
+/// A line that says nothing on its own: blank, a lone brace, a comment or
+/// an import.
+fn is_insignificant(trimmed: &str) -> bool {
+    trimmed.chars().all(|c| "{}()[];,".contains(c))
+        || [
+            "//", "#", "/*", "*", "use ", "import ", "from ", "package ", "mod ",
+        ]
+        .iter()
+        .any(|p| trimmed.starts_with(p))
+}
+
+/// Blocks of at least [`MIN_DUPLICATE_LINES`] significant lines that also
+/// appear earlier in `files`, reported at the later copy. Whitespace and
+/// insignificant lines are ignored when comparing.
+pub fn duplicate_blocks(files: &[(PathBuf, String)]) -> Vec<WorkspaceFinding> {
+    let window = MIN_DUPLICATE_LINES;
+    // Per file: (0-based line, trimmed text) of each significant line.
+    let significant: Vec<Vec<(usize, &str)>> = files
+        .iter()
+        .map(|(path, content)| {
+            if is_test_path(path) {
+                return Vec::new();
+            }
+            content
+                .lines()
+                .enumerate()
+                .map(|(i, l)| (i, l.trim()))
+                .filter(|(_, l)| !is_insignificant(l))
+                .collect()
+        })
+        .collect();
+    let key = |f: usize, i: usize| {
+        let mut hasher = DefaultHasher::new();
+        for (_, l) in &significant[f][i..i + window] {
+            l.hash(&mut hasher);
+        }
+        hasher.finish()
+    };
+    let same = |(f, i): (usize, usize), (g, j): (usize, usize)| {
+        significant[f][i..i + window]
+            .iter()
+            .map(|(_, l)| l)
+            .eq(significant[g][j..j + window].iter().map(|(_, l)| l))
+    };
+
+    let mut first: HashMap<u64, (usize, usize)> = HashMap::new();
+    let mut findings = Vec::new();
+    for f in 0..files.len() {
+        let lines = &significant[f];
+        // Copies found here: (first window, last window, original's first).
+        let mut copies = Vec::new();
+        let mut run: Option<(usize, (usize, usize))> = None;
+        // Windows before this one overlap a copy already found.
+        let mut covered = 0;
+        for i in 0..(lines.len() + 1).saturating_sub(window) {
+            let h = key(f, i);
+            let earlier = match first.get(&h) {
+                Some(&(g, j)) if (g != f || j + window <= i) && same((f, i), (g, j)) => {
+                    Some((g, j))
+                }
+                _ => None,
+            };
+            first.entry(h).or_insert((f, i));
+            match (run, earlier) {
+                // The copy goes on where the last window's did.
+                (Some((start, (g, j))), Some((g2, j2))) if g2 == g && j2 == j + (i - start) => {}
+                (Some((start, original)), _) => {
+                    copies.push((start, i - 1, original));
+                    covered = i - 1 + window;
+                    run = None;
+                }
+                (None, _) => {}
+            }
+            if run.is_none() && i >= covered {
+                run = earlier.map(|original| (i, original));
+            }
+        }
+        if let Some((start, original)) = run {
+            copies.push((start, lines.len() - window, original));
+        }
+
+        for (start, end, (g, j)) in copies {
+            let (first_line, last_line) = (lines[start].0, lines[end + window - 1].0);
+            let original = &significant[g];
+            findings.push(WorkspaceFinding {
+                path: files[f].0.clone(),
+                line: first_line + 1,
+                column: 0,
+                end_line: last_line + 1,
+                severity: Severity::Info,
+                kind: FindingKind::DuplicateCode,
+                message: format!(
+                    "{} lines duplicate {}:{}–{}",
+                    last_line - first_line + 1,
+                    files[g].0.display(),
+                    original[j].0 + 1,
+                    original[j + end - start + window - 1].0 + 1
+                ),
+                fix: None,
+            });
+        }
+    }
+    findings
+}
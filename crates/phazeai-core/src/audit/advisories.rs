@@ -0,0 +1,421 @@
+//! A local copy of the OSV advisories (RustSec for crates, GitHub's for
+//! npm) that concern the workspace's packages.
+//!
+//! Audits only read the copy, so they work offline. It is refreshed from
+//! the OSV API for the packages the workspace pins, or imported from OSV
+//! records on disk: a directory of `.json` files, an ecosystem's
+//! `all.zip` export, or a single record.
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::lockfile::{Ecosystem, Package};
+use crate::constants::{endpoints, paths};
+use crate::error::PhazeError;
+use crate::progress;
+
+/// File the database is kept in, under the config directory.
+const DB_FILE: &str = "advisories.json";
+/// Packages per OSV batch query (the API's limit is 1000).
+const QUERY_BATCH: usize = 500;
+/// Advisory records fetched at once during a refresh.
+const FETCH_CONCURRENCY: usize = 8;
+
+/// One vulnerability report for one package.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Advisory {
+    /// OSV identifier (`RUSTSEC-…`, `GHSA-…`).
+    pub id: String,
+    /// Other identifiers, such as the CVE.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub summary: String,
+    pub ecosystem: Ecosystem,
+    pub package: String,
+    /// The database's rating (`CRITICAL`, `HIGH`, `MODERATE`, `LOW`), or
+    /// RustSec's informational kind (`unmaintained`, `unsound`).
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// Affected version intervals.
+    #[serde(default)]
+    pub ranges: Vec<AffectedRange>,
+    /// Affected versions listed one by one.
+    #[serde(default)]
+    pub versions: Vec<String>,
+}
+
+/// Versions from `introduced` up to `fixed` (exclusive) or `last_affected`
+/// (inclusive); with neither, every later version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AffectedRange {
+    pub introduced: String,
+    #[serde(default)]
+    pub fixed: Option<String>,
+    #[serde(default)]
+    pub last_affected: Option<String>,
+}
+
+impl Advisory {
+    /// Whether `version` of the package is affected.
+    pub fn affects(&self, version: &str) -> bool {
+        self.versions.iter().any(|v| v == version)
+            || self.ranges.iter().any(|r| {
+                compare_versions(version, &r.introduced) != Ordering::Less
+                    && match (&r.fixed, &r.last_affected) {
+                        (Some(fixed), _) => compare_versions(version, fixed) == Ordering::Less,
+                        (None, Some(last)) => compare_versions(version, last) != Ordering::Greater,
+                        (None, None) => true,
+                    }
+            })
+    }
+
+    /// The versions that fix it, lowest first.
+    pub fn fixed_versions(&self) -> Vec<String> {
+        let mut fixed: Vec<String> = self.ranges.iter().filter_map(|r| r.fixed.clone()).collect();
+        fixed.sort_by(|a, b| compare_versions(a, b));
+        fixed.dedup();
+        fixed
+    }
+
+    /// The lowest fixing version above `version`.
+    pub fn fixed_after(&self, version: &str) -> Option<String> {
+        self.fixed_versions()
+            .into_iter()
+            .find(|f| compare_versions(f, version) == Ordering::Greater)
+    }
+
+    /// The advisories an OSV record makes, one per affected package in a
+    /// supported ecosystem. Withdrawn records make none.
+    pub fn from_osv(record: &serde_json::Value) -> Vec<Advisory> {
+        if record.get("withdrawn").is_some_and(|w| !w.is_null()) {
+            return Vec::new();
+        }
+        let text = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_str()).map(str::to_string);
+        let Some(id) = text(record.get("id")) else {
+            return Vec::new();
+        };
+        let summary = text(record.get("summary"))
+            .or_else(|| {
+                text(record.get("details")).map(|d| d.lines().next().unwrap_or("").to_string())
+            })
+            .unwrap_or_default();
+        let aliases: Vec<String> = record
+            .get("aliases")
+            .and_then(|a| a.as_array())
+            .map(|a| a.iter().filter_map(|v| text(Some(v))).collect())
+            .unwrap_or_default();
+        let severity = text(record.pointer("/database_specific/severity"));
+
+        let affected = record.get("affected").and_then(|a| a.as_array());
+        affected
+            .into_iter()
+            .flatten()
+            .filter_map(|affected| {
+                let ecosystem =
+                    Ecosystem::from_osv_name(affected.pointer("/package/ecosystem")?.as_str()?)?;
+                let package = text(affected.pointer("/package/name"))?;
+                let ranges = affected
+                    .get("ranges")
+                    .and_then(|r| r.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter(|r| r.get("type").and_then(|t| t.as_str()) != Some("GIT"))
+                    .flat_map(|r| osv_intervals(r.get("events")))
+                    .collect();
+                let versions = affected
+                    .get("versions")
+                    .and_then(|v| v.as_array())
+                    .map(|v| v.iter().filter_map(|v| text(Some(v))).collect())
+                    .unwrap_or_default();
+                let severity = severity.clone().or_else(|| {
+                    text(affected.pointer("/database_specific/informational")).or_else(|| {
+                        text(affected.pointer("/ecosystem_specific/affects/informational"))
+                    })
+                });
+                Some(Advisory {
+                    id: id.clone(),
+                    aliases: aliases.clone(),
+                    summary: summary.clone(),
+                    ecosystem,
+                    package,
+                    severity,
+                    ranges,
+                    versions,
+                })
+            })
+            .collect()
+    }
+}
+
+/// An OSV range's `introduced` / `fixed` / `last_affected` events as
+/// intervals.
+fn osv_intervals(events: Option<&serde_json::Value>) -> Vec<AffectedRange> {
+    let mut ranges = Vec::new();
+    let mut open: Option<String> = None;
+    for event in events.and_then(|e| e.as_array()).into_iter().flatten() {
+        let field = |name: &str| event.get(name).and_then(|v| v.as_str()).map(str::to_string);
+        if let Some(introduced) = field("introduced") {
+            if let Some(start) = open.replace(introduced) {
+                ranges.push(AffectedRange {
+                    introduced: start,
+                    fixed: None,
+                    last_affected: None,
+                });
+            }
+        } else if let Some(fixed) = field("fixed") {
+            ranges.push(AffectedRange {
+                introduced: open.take().unwrap_or_else(|| "0".to_string()),
+                fixed: Some(fixed),
+                last_affected: None,
+            });
+        } else if let Some(last) = field("last_affected") {
+            ranges.push(AffectedRange {
+                introduced: open.take().unwrap_or_else(|| "0".to_string()),
+                fixed: None,
+                last_affected: Some(last),
+            });
+        }
+    }
+    if let Some(start) = open {
+        ranges.push(AffectedRange {
+            introduced: start,
+            fixed: None,
+            last_affected: None,
+        });
+    }
+    ranges
+}
+
+/// Compare two semver-style versions: numeric parts numerically, a
+/// pre-release before its release, build metadata ignored. `"0"` is below
+/// everything.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn split(v: &str) -> (Vec<u64>, Option<&str>) {
+        let v = v.trim().trim_start_matches('v');
+        let v = v.split('+').next().unwrap_or(v);
+        let (release, pre) = match v.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (v, None),
+        };
+        let parts = release.split('.').map(|p| p.parse().unwrap_or(0)).collect();
+        (parts, pre)
+    }
+    let ((pa, pre_a), (pb, pre_b)) = (split(a), split(b));
+    for i in 0..pa.len().max(pb.len()) {
+        let order = pa.get(i).unwrap_or(&0).cmp(pb.get(i).unwrap_or(&0));
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    match (pre_a, pre_b) {
+        (None, None) => Ordering::Equal,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(x), Some(y)) => {
+            for (x, y) in x.split('.').zip(y.split('.')) {
+                let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            x.split('.').count().cmp(&y.split('.').count())
+        }
+    }
+}
+
+/// The advisories kept on disk.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdvisoryDb {
+    /// When it was last refreshed or imported into.
+    #[serde(default)]
+    pub updated: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub advisories: Vec<Advisory>,
+}
+
+impl AdvisoryDb {
+    /// `~/.config/phazeai/advisories.json`.
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(paths::CONFIG_DIR)
+            .join(DB_FILE)
+    }
+
+    /// The database at `path`; empty when there is none yet or it can't be
+    /// read.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PhazeError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Add `advisories`, replacing older copies of the same advisory for
+    /// the same package. Returns how many were new.
+    pub fn merge(&mut self, advisories: Vec<Advisory>) -> usize {
+        let mut added = 0;
+        for advisory in advisories {
+            match self.advisories.iter_mut().find(|a| {
+                a.id == advisory.id
+                    && a.ecosystem == advisory.ecosystem
+                    && a.package == advisory.package
+            }) {
+                Some(existing) => *existing = advisory,
+                None => {
+                    self.advisories.push(advisory);
+                    added += 1;
+                }
+            }
+        }
+        self.updated = Some(Utc::now());
+        added
+    }
+
+    /// The advisories affecting `package`'s pinned version.
+    pub fn affecting<'a>(&'a self, package: &'a Package) -> impl Iterator<Item = &'a Advisory> {
+        self.advisories.iter().filter(move |a| {
+            a.ecosystem == package.ecosystem
+                && a.package == package.name
+                && a.affects(&package.version)
+        })
+    }
+
+    /// Merge the OSV records at `path`: a directory of `.json` files
+    /// (searched recursively), a `.zip` of them such as an ecosystem's
+    /// `all.zip`, or one `.json` record. Returns how many advisories were
+    /// new.
+    pub fn import(&mut self, path: &Path) -> Result<usize, PhazeError> {
+        let mut records = Vec::new();
+        if path.is_dir() {
+            for entry in ignore::WalkBuilder::new(path)
+                .standard_filters(false)
+                .build()
+            {
+                let entry = entry.map_err(|e| PhazeError::Other(e.to_string()))?;
+                if entry.path().extension().is_some_and(|e| e == "json") {
+                    records.push(std::fs::read_to_string(entry.path())?);
+                }
+            }
+        } else if path.extension().is_some_and(|e| e == "zip") {
+            let file = std::fs::File::open(path)?;
+            let mut archive = zip::ZipArchive::new(file)
+                .map_err(|e| PhazeError::Other(format!("{}: {e}", path.display())))?;
+            for i in 0..archive.len() {
+                let mut entry = archive
+                    .by_index(i)
+                    .map_err(|e| PhazeError::Other(format!("{}: {e}", path.display())))?;
+                if entry.name().ends_with(".json") {
+                    let mut record = String::new();
+                    std::io::Read::read_to_string(&mut entry, &mut record)?;
+                    records.push(record);
+                }
+            }
+        } else {
+            records.push(std::fs::read_to_string(path)?);
+        }
+
+        let mut advisories = Vec::new();
+        for record in records {
+            // Files that aren't OSV records are skipped, not fatal.
+            if let Ok(record) = serde_json::from_str::<serde_json::Value>(&record) {
+                advisories.extend(Advisory::from_osv(&record));
+            }
+        }
+        Ok(self.merge(advisories))
+    }
+
+    /// Fetch from the OSV API every advisory, for any version, about the
+    /// packages in `packages`. Returns how many advisories were new.
+    pub async fn refresh(&mut self, packages: &[Package]) -> Result<usize, PhazeError> {
+        let names: BTreeSet<(Ecosystem, &str)> = packages
+            .iter()
+            .map(|p| (p.ecosystem, p.name.as_str()))
+            .collect();
+        let names: Vec<_> = names.into_iter().collect();
+        let task = progress::global()
+            .task("Refreshing advisories")
+            .unit("packages")
+            .total(names.len() as u64)
+            .cancellable()
+            .start();
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .user_agent("PhazeAI/1.0")
+            .build()?;
+
+        let mut ids = BTreeSet::new();
+        for batch in names.chunks(QUERY_BATCH) {
+            if task.is_cancelled() {
+                return Err(PhazeError::Cancelled);
+            }
+            let queries: Vec<_> = batch
+                .iter()
+                .map(|(ecosystem, name)| {
+                    serde_json::json!({
+                        "package": { "name": name, "ecosystem": ecosystem.osv_name() }
+                    })
+                })
+                .collect();
+            let response: serde_json::Value = client
+                .post(format!("{}/v1/querybatch", endpoints::OSV_API_URL))
+                .json(&serde_json::json!({ "queries": queries }))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let results = response.get("results").and_then(|r| r.as_array());
+            for result in results.into_iter().flatten() {
+                let vulns = result.get("vulns").and_then(|v| v.as_array());
+                for vuln in vulns.into_iter().flatten() {
+                    if let Some(id) = vuln.get("id").and_then(|i| i.as_str()) {
+                        ids.insert(id.to_string());
+                    }
+                }
+            }
+            task.advance(batch.len() as u64);
+        }
+
+        let ids: Vec<String> = ids.into_iter().collect();
+        task.set_message(format!("{} advisories", ids.len()));
+        let mut advisories = Vec::new();
+        for chunk in ids.chunks(FETCH_CONCURRENCY) {
+            if task.is_cancelled() {
+                return Err(PhazeError::Cancelled);
+            }
+            let fetches = chunk.iter().map(|id| {
+                let request = client.get(format!("{}/v1/vulns/{id}", endpoints::OSV_API_URL));
+                async move {
+                    request
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .json::<serde_json::Value>()
+                        .await
+                }
+            });
+            for record in futures::future::join_all(fetches).await {
+                advisories.extend(Advisory::from_osv(&record?));
+            }
+        }
+        Ok(self.merge(advisories))
+    }
+}
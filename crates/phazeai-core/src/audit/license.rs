@@ -0,0 +1,172 @@
+//! Grouping the workspace's packages by license.
+
+use std::collections::BTreeMap;
+
+use super::lockfile::Package;
+
+/// How a license constrains the code that uses it, most constraining first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LicenseKind {
+    /// GPL, AGPL and the like: derived work must use the same license.
+    Copyleft,
+    /// No license found, or one that isn't recognised.
+    Unknown,
+    /// LGPL, MPL, EPL: changes to the package itself must be shared.
+    WeakCopyleft,
+    Permissive,
+}
+
+impl LicenseKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Copyleft => "copyleft",
+            Self::Unknown => "unknown",
+            Self::WeakCopyleft => "weak copyleft",
+            Self::Permissive => "permissive",
+        }
+    }
+}
+
+const PERMISSIVE: &[&str] = &[
+    "MIT",
+    "MIT-0",
+    "Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "0BSD",
+    "ISC",
+    "Zlib",
+    "Unlicense",
+    "CC0-1.0",
+    "BSL-1.0",
+    "Unicode-DFS-2016",
+    "Unicode-3.0",
+    "WTFPL",
+    "Python-2.0",
+    "BlueOak-1.0.0",
+    "CC-BY-4.0",
+    "CC-BY-3.0",
+];
+
+/// Classify an SPDX expression. With `OR` (or the older `/`) the package
+/// may be used under the most permissive choice; with `AND` every license
+/// applies, so the most constraining one counts.
+pub fn classify(license: Option<&str>) -> LicenseKind {
+    let Some(license) = license else {
+        return LicenseKind::Unknown;
+    };
+    let spaced = license
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut pos = 0;
+    let kind = classify_or(&tokens, &mut pos);
+    if pos == tokens.len() {
+        kind
+    } else {
+        LicenseKind::Unknown
+    }
+}
+
+fn classify_or(tokens: &[&str], pos: &mut usize) -> LicenseKind {
+    let mut kind = classify_and(tokens, pos);
+    while tokens.get(*pos) == Some(&"OR") {
+        *pos += 1;
+        kind = kind.max(classify_and(tokens, pos));
+    }
+    kind
+}
+
+fn classify_and(tokens: &[&str], pos: &mut usize) -> LicenseKind {
+    let mut kind = classify_license(tokens, pos);
+    while tokens.get(*pos) == Some(&"AND") {
+        *pos += 1;
+        kind = kind.min(classify_license(tokens, pos));
+    }
+    kind
+}
+
+/// A license, its `WITH` exception (which doesn't change its kind), or a
+/// parenthesised expression.
+fn classify_license(tokens: &[&str], pos: &mut usize) -> LicenseKind {
+    let Some(&token) = tokens.get(*pos) else {
+        return LicenseKind::Unknown;
+    };
+    *pos += 1;
+    let kind = if token == "(" {
+        let kind = classify_or(tokens, pos);
+        if tokens.get(*pos) == Some(&")") {
+            *pos += 1;
+        }
+        kind
+    } else {
+        classify_term(token)
+    };
+    if tokens.get(*pos) == Some(&"WITH") {
+        *pos += 2;
+    }
+    kind
+}
+
+fn classify_term(term: &str) -> LicenseKind {
+    let upper = term.to_uppercase();
+    if upper.contains("LGPL")
+        || upper.starts_with("MPL")
+        || upper.starts_with("EPL")
+        || upper.starts_with("CDDL")
+    {
+        LicenseKind::WeakCopyleft
+    } else if upper.contains("GPL")
+        || upper.starts_with("SSPL")
+        || upper.starts_with("EUPL")
+        || upper.starts_with("OSL")
+    {
+        LicenseKind::Copyleft
+    } else if PERMISSIVE
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case(term.trim_end_matches('+')))
+    {
+        LicenseKind::Permissive
+    } else {
+        LicenseKind::Unknown
+    }
+}
+
+/// The packages using one license.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseGroup {
+    /// The license expression, or `"unknown"`.
+    pub license: String,
+    pub kind: LicenseKind,
+    /// `name version`, sorted.
+    pub packages: Vec<String>,
+}
+
+/// `packages` grouped by license, the most constraining kinds first and,
+/// within a kind, the most used licenses first.
+pub fn license_summary(packages: &[Package]) -> Vec<LicenseGroup> {
+    let mut groups: BTreeMap<Option<&str>, Vec<String>> = BTreeMap::new();
+    for p in packages {
+        groups
+            .entry(p.license.as_deref())
+            .or_default()
+            .push(format!("{} {}", p.name, p.version));
+    }
+    let mut summary: Vec<LicenseGroup> = groups
+        .into_iter()
+        .map(|(license, mut packages)| {
+            packages.sort();
+            packages.dedup();
+            LicenseGroup {
+                license: license.unwrap_or("unknown").to_string(),
+                kind: classify(license),
+                packages,
+            }
+        })
+        .collect();
+    summary.sort_by(|a, b| {
+        (a.kind, b.packages.len(), &a.license).cmp(&(b.kind, a.packages.len(), &b.license))
+    });
+    summary
+}
@@ -0,0 +1,221 @@
+//! The packages a workspace's lockfiles pin, and their licenses.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+
+/// Package registry a dependency comes from, named as OSV names it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Ecosystem {
+    #[serde(rename = "crates.io")]
+    CratesIo,
+    #[serde(rename = "npm")]
+    Npm,
+}
+
+impl Ecosystem {
+    /// The ecosystem's name in OSV records.
+    pub fn osv_name(self) -> &'static str {
+        match self {
+            Self::CratesIo => "crates.io",
+            Self::Npm => "npm",
+        }
+    }
+
+    pub fn from_osv_name(name: &str) -> Option<Self> {
+        match name {
+            "crates.io" => Some(Self::CratesIo),
+            "npm" => Some(Self::Npm),
+            _ => None,
+        }
+    }
+}
+
+/// A package version pinned by a lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Package {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    /// SPDX expression, when the lockfile or the installed package says.
+    pub license: Option<String>,
+    /// The lockfile, relative to the workspace root.
+    pub lockfile: PathBuf,
+}
+
+/// Registry packages in a `Cargo.lock`, as `(name, version)`. Workspace
+/// members, path and git dependencies have no `source` from a registry
+/// and aren't published advisories' subjects.
+pub fn parse_cargo_lock(content: &str) -> Vec<(String, String)> {
+    #[derive(Deserialize)]
+    struct Lock {
+        #[serde(default)]
+        package: Vec<Entry>,
+    }
+    #[derive(Deserialize)]
+    struct Entry {
+        name: String,
+        version: String,
+        source: Option<String>,
+    }
+    let Ok(lock) = toml::from_str::<Lock>(content) else {
+        return Vec::new();
+    };
+    lock.package
+        .into_iter()
+        .filter(|p| {
+            p.source
+                .as_deref()
+                .is_some_and(|s| s.starts_with("registry+") || s.starts_with("sparse+"))
+        })
+        .map(|p| (p.name, p.version))
+        .collect()
+}
+
+/// Installed packages in a `package-lock.json`, as `(name, version,
+/// license)`. Lockfile versions 2 and 3 list them under `packages` by
+/// `node_modules/` path; version 1 nests them under `dependencies`.
+pub fn parse_package_lock(content: &str) -> Vec<(String, String, Option<String>)> {
+    let Ok(lock) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    if let Some(packages) = lock.get("packages").and_then(|p| p.as_object()) {
+        for (path, entry) in packages {
+            let Some((_, name)) = path.rsplit_once("node_modules/") else {
+                continue; // the root project
+            };
+            if entry.get("link").and_then(|l| l.as_bool()) == Some(true) {
+                continue;
+            }
+            let Some(version) = entry.get("version").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let license = entry
+                .get("license")
+                .and_then(|l| l.as_str())
+                .map(str::to_string);
+            out.push((name.to_string(), version.to_string(), license));
+        }
+    } else if let Some(deps) = lock.get("dependencies") {
+        v1_dependencies(deps, &mut out);
+    }
+    out
+}
+
+fn v1_dependencies(deps: &serde_json::Value, out: &mut Vec<(String, String, Option<String>)>) {
+    let Some(deps) = deps.as_object() else {
+        return;
+    };
+    for (name, entry) in deps {
+        if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
+            // Local and git dependencies are pinned by path or URL.
+            if !version.contains(':') {
+                out.push((name.clone(), version.to_string(), None));
+            }
+        }
+        if let Some(nested) = entry.get("dependencies") {
+            v1_dependencies(nested, out);
+        }
+    }
+}
+
+/// Every package pinned by the `Cargo.lock` and `package-lock.json` files
+/// under `root`, with the licenses that can be found without a network:
+/// from the lockfile, the installed `node_modules`, or cargo's registry
+/// sources. Lockfiles are read even when gitignored, as libraries often
+/// leave `Cargo.lock` out of git.
+pub fn workspace_packages(root: &Path) -> Vec<Package> {
+    let walker = WalkBuilder::new(root)
+        .git_ignore(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some("node_modules" | "target")))
+        .build();
+    let cargo_sources = cargo_registry_sources();
+    let mut packages = BTreeSet::new();
+    for entry in walker.flatten() {
+        let name = entry.file_name().to_str().unwrap_or("");
+        if !matches!(name, "Cargo.lock" | "package-lock.json") {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let lockfile = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_path_buf();
+        let dir = entry.path().parent().unwrap_or(root);
+        if name == "Cargo.lock" {
+            for (name, version) in parse_cargo_lock(&content) {
+                let license = crate_license(&cargo_sources, &name, &version);
+                packages.insert(Package {
+                    ecosystem: Ecosystem::CratesIo,
+                    name,
+                    version,
+                    license,
+                    lockfile: lockfile.clone(),
+                });
+            }
+        } else {
+            for (name, version, license) in parse_package_lock(&content) {
+                let license = license.or_else(|| npm_license(dir, &name));
+                packages.insert(Package {
+                    ecosystem: Ecosystem::Npm,
+                    name,
+                    version,
+                    license,
+                    lockfile: lockfile.clone(),
+                });
+            }
+        }
+    }
+    packages.into_iter().collect()
+}
+
+/// Cargo's unpacked registry directories (`~/.cargo/registry/src/*`).
+fn cargo_registry_sources() -> Vec<PathBuf> {
+    let home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".cargo")));
+    let Some(src) = home.map(|h| h.join("registry").join("src")) else {
+        return Vec::new();
+    };
+    std::fs::read_dir(src)
+        .map(|dirs| dirs.flatten().map(|d| d.path()).collect())
+        .unwrap_or_default()
+}
+
+fn crate_license(sources: &[PathBuf], name: &str, version: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Manifest {
+        package: ManifestPackage,
+    }
+    #[derive(Deserialize)]
+    struct ManifestPackage {
+        license: Option<String>,
+        #[serde(rename = "license-file")]
+        license_file: Option<String>,
+    }
+    sources.iter().find_map(|src| {
+        let manifest = std::fs::read_to_string(src.join(format!("{name}-{version}/Cargo.toml")));
+        let package = toml::from_str::<Manifest>(&manifest.ok()?).ok()?.package;
+        package
+            .license
+            .or_else(|| package.license_file.map(|f| format!("SEE LICENSE IN {f}")))
+    })
+}
+
+fn npm_license(dir: &Path, name: &str) -> Option<String> {
+    let manifest =
+        std::fs::read_to_string(dir.join("node_modules").join(name).join("package.json")).ok()?;
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).ok()?;
+    match manifest.get("license")? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        // The old `{ "type": "MIT" }` form.
+        other => other.get("type")?.as_str().map(str::to_string),
+    }
+}
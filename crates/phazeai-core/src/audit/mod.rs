@@ -0,0 +1,122 @@
+//! Dependency audit: the packages the workspace's lockfiles pin, checked
+//! against a local copy of the RustSec / OSV advisories, and grouped by
+//! license. Also the update check: which declared dependencies have newer
+//! releases, their release notes, and the manifest bumps that take them.
+
+mod advisories;
+mod license;
+mod lockfile;
+mod requirements;
+mod updates;
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+pub use advisories::{compare_versions, Advisory, AdvisoryDb, AffectedRange};
+pub use license::{classify, license_summary, LicenseGroup, LicenseKind};
+pub use lockfile::{parse_cargo_lock, parse_package_lock, workspace_packages, Ecosystem, Package};
+pub use requirements::{
+    bump_requirement, cargo_dependencies, manifest_dependencies, npm_dependencies,
+    requirement_allows, Dependency,
+};
+pub use updates::{
+    bump_edits, changelog_between, fetch_release_notes, find_outdated, github_repo, latest_release,
+    summarize_breaking_changes, usage_sites, ManifestEdit, Outdated, Release,
+};
+
+/// A pinned package an advisory affects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vulnerability {
+    pub package: Package,
+    pub advisory: Advisory,
+}
+
+impl Vulnerability {
+    /// The lowest version that fixes it, if one has been released.
+    pub fn fixed_in(&self) -> Option<String> {
+        self.advisory.fixed_after(&self.package.version)
+    }
+
+    /// Ranks `CRITICAL` highest and informational advisories lowest.
+    pub fn severity_rank(&self) -> u8 {
+        match self
+            .advisory
+            .severity
+            .as_deref()
+            .map(str::to_uppercase)
+            .as_deref()
+        {
+            Some("CRITICAL") => 4,
+            Some("HIGH") => 3,
+            Some("MODERATE" | "MEDIUM") => 2,
+            Some("LOW") => 1,
+            // Unrated advisories are still vulnerabilities.
+            None => 2,
+            Some(_) => 0,
+        }
+    }
+}
+
+/// What [`audit`] found.
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    pub packages: Vec<Package>,
+    /// Most severe first.
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub licenses: Vec<LicenseGroup>,
+    /// When the advisory database was last refreshed.
+    pub db_updated: Option<DateTime<Utc>>,
+    pub db_advisories: usize,
+}
+
+/// Audit the lockfiles under `root` against `db`. Reads no network.
+pub fn audit(root: &Path, db: &AdvisoryDb) -> AuditReport {
+    let packages = workspace_packages(root);
+    let mut vulnerabilities: Vec<Vulnerability> = packages
+        .iter()
+        .flat_map(|package| {
+            db.affecting(package).map(|advisory| Vulnerability {
+                package: package.clone(),
+                advisory: advisory.clone(),
+            })
+        })
+        .collect();
+    vulnerabilities.sort_by(|a, b| {
+        b.severity_rank()
+            .cmp(&a.severity_rank())
+            .then_with(|| a.package.cmp(&b.package))
+            .then_with(|| a.advisory.id.cmp(&b.advisory.id))
+    });
+    AuditReport {
+        licenses: license_summary(&packages),
+        packages,
+        vulnerabilities,
+        db_updated: db.updated,
+        db_advisories: db.advisories.len(),
+    }
+}
+
+/// The vulnerabilities as a list for the agent: one line per advisory with
+/// the package, its lockfile, what the advisory says and where it's fixed.
+pub fn describe_vulnerabilities(vulnerabilities: &[Vulnerability]) -> String {
+    let mut out = String::new();
+    for v in vulnerabilities {
+        let (p, a) = (&v.package, &v.advisory);
+        let severity = a.severity.as_deref().unwrap_or("unrated");
+        let fix = match v.fixed_in() {
+            Some(version) => format!("fixed in {version}"),
+            None => "no fixed version".to_string(),
+        };
+        out.push_str(&format!(
+            "- {} {} ({}, {}): {} [{severity}] {}; {fix}\n",
+            p.name,
+            p.version,
+            p.ecosystem.osv_name(),
+            p.lockfile.display(),
+            a.id,
+            a.summary
+        ));
+    }
+    out
+}
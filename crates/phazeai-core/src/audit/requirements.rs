@@ -0,0 +1,350 @@
+//! Version requirements declared in `Cargo.toml` and `package.json`, what
+//! they allow, and how to bump them.
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+
+use super::advisories::compare_versions;
+use super::lockfile::Ecosystem;
+
+static RE_INLINE_VERSION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bversion\s*=\s*"([^"]*)""#).unwrap());
+static RE_INLINE_PACKAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bpackage\s*=\s*"([^"]*)""#).unwrap());
+static RE_NOT_REGISTRY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(path|git)\s*=|\bworkspace\s*=\s*true").unwrap());
+static RE_NPM_SECTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*"(dependencies|devDependencies|optionalDependencies)"\s*:\s*\{\s*$"#).unwrap()
+});
+static RE_NPM_ENTRY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*"([^"]+)"\s*:\s*"([^"]*)""#).unwrap());
+static RE_OPERATOR_SPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"([<>=~^]+)\s+").unwrap());
+
+/// A registry dependency a manifest declares.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub ecosystem: Ecosystem,
+    /// The registry name, which differs from the manifest key when a
+    /// Cargo dependency is renamed with `package = "..."`.
+    pub name: String,
+    pub requirement: String,
+    /// The manifest, relative to the workspace root.
+    pub manifest: PathBuf,
+    /// 0-based line holding the requirement.
+    pub line: usize,
+}
+
+/// Registry dependencies in a `Cargo.toml`, as `(line, name, requirement)`.
+/// Covers `[dependencies]`-style tables (dev, build, target-specific and
+/// `[workspace.dependencies]`), inline tables and `[dependencies.foo]`
+/// tables; path, git and `workspace = true` dependencies are skipped.
+pub fn cargo_dependencies(content: &str) -> Vec<(usize, String, String)> {
+    // A `[dependencies.foo]` table being read: its name, version line and
+    // whether it points somewhere other than the registry.
+    struct Table {
+        name: String,
+        version: Option<(usize, String)>,
+        local: bool,
+    }
+    fn flush(table: Option<Table>, out: &mut Vec<(usize, String, String)>) {
+        if let Some(Table {
+            name,
+            version: Some((line, requirement)),
+            local: false,
+        }) = table
+        {
+            out.push((line, name, requirement));
+        }
+    }
+    fn is_dependency_table(header: &str) -> bool {
+        let last = header.rsplit('.').next().unwrap_or(header).trim();
+        matches!(
+            last,
+            "dependencies"
+                | "dev-dependencies"
+                | "build-dependencies"
+                | "dev_dependencies"
+                | "build_dependencies"
+        )
+    }
+
+    let mut out = Vec::new();
+    let mut in_list = false;
+    let mut table: Option<Table> = None;
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.split(" #").next().unwrap_or(line).trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if trimmed.starts_with('[') {
+            flush(table.take(), &mut out);
+            let header = trimmed.trim_matches(['[', ']']).trim();
+            in_list = is_dependency_table(header);
+            if !in_list {
+                if let Some((parent, name)) = header.rsplit_once('.') {
+                    if is_dependency_table(parent) {
+                        table = Some(Table {
+                            name: name.trim().trim_matches('"').to_string(),
+                            version: None,
+                            local: false,
+                        });
+                    }
+                }
+            }
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim().trim_matches('"'), value.trim());
+        if let Some(table) = table.as_mut() {
+            match key {
+                "version" => {
+                    table.version = Some((i, value.trim_matches('"').to_string()));
+                }
+                "package" => table.name = value.trim_matches('"').to_string(),
+                "path" | "git" => table.local = true,
+                "workspace" if value == "true" => table.local = true,
+                _ => {}
+            }
+        } else if in_list {
+            if let Some(requirement) = value.strip_prefix('"') {
+                let requirement = requirement.split('"').next().unwrap_or("");
+                out.push((i, key.to_string(), requirement.to_string()));
+            } else if value.starts_with('{') && !RE_NOT_REGISTRY.is_match(value) {
+                let Some(version) = RE_INLINE_VERSION.captures(value) else {
+                    continue;
+                };
+                let name = RE_INLINE_PACKAGE
+                    .captures(value)
+                    .map_or(key, |c| c.get(1).map_or(key, |m| m.as_str()));
+                out.push((i, name.to_string(), version[1].to_string()));
+            }
+        }
+    }
+    flush(table, &mut out);
+    out
+}
+
+/// Registry dependencies in a `package.json`, as `(line, name,
+/// requirement)`, from `dependencies`, `devDependencies` and
+/// `optionalDependencies`. Expects the usual one-entry-per-line layout.
+/// Tags, URLs, `file:`/`workspace:`/`npm:` specs and GitHub shorthands are
+/// skipped.
+pub fn npm_dependencies(content: &str) -> Vec<(usize, String, String)> {
+    let mut out = Vec::new();
+    let mut in_section = false;
+    for (i, line) in content.lines().enumerate() {
+        if RE_NPM_SECTION.is_match(line) {
+            in_section = true;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if line.trim_start().starts_with('}') {
+            in_section = false;
+            continue;
+        }
+        let Some(entry) = RE_NPM_ENTRY.captures(line) else {
+            continue;
+        };
+        let requirement = &entry[2];
+        if requirement.contains([':', '/']) || !requirement.chars().any(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        out.push((i, entry[1].to_string(), requirement.to_string()));
+    }
+    out
+}
+
+/// Every registry dependency declared by the manifests under `root`.
+pub fn manifest_dependencies(root: &Path) -> Vec<Dependency> {
+    let walker = WalkBuilder::new(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some("node_modules" | "target")))
+        .build();
+    let mut out = Vec::new();
+    for entry in walker.flatten() {
+        let ecosystem = match entry.file_name().to_str() {
+            Some("Cargo.toml") => Ecosystem::CratesIo,
+            Some("package.json") => Ecosystem::Npm,
+            _ => continue,
+        };
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let manifest = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_path_buf();
+        let declared = match ecosystem {
+            Ecosystem::CratesIo => cargo_dependencies(&content),
+            Ecosystem::Npm => npm_dependencies(&content),
+        };
+        for (line, name, requirement) in declared {
+            out.push(Dependency {
+                ecosystem,
+                name,
+                requirement,
+                manifest: manifest.clone(),
+                line,
+            });
+        }
+    }
+    out
+}
+
+/// A version in a requirement, with the parts it leaves out or writes as
+/// `*`/`x` unset.
+#[derive(Debug, Clone, Copy)]
+struct Partial {
+    parts: [u64; 3],
+    given: usize,
+}
+
+impl Partial {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches('v');
+        let release = text.split(['-', '+']).next().unwrap_or(text);
+        let mut parts = [0; 3];
+        let mut given = 0;
+        for part in release.split('.').filter(|p| !p.is_empty()) {
+            if matches!(part, "*" | "x" | "X") || given == 3 {
+                break;
+            }
+            parts[given] = part.parse().ok()?;
+            given += 1;
+        }
+        Some(Self { parts, given })
+    }
+
+    fn version(self) -> String {
+        let [major, minor, patch] = self.parts;
+        format!("{major}.{minor}.{patch}")
+    }
+
+    /// The first version past every one the given parts match.
+    fn next(self) -> Option<String> {
+        let [major, minor, _] = self.parts;
+        match self.given {
+            0 => None,
+            1 => Some(format!("{}.0.0", major + 1)),
+            _ => Some(format!("{major}.{}.0", minor + 1)),
+        }
+    }
+}
+
+/// Whether a single comparator (`^1.2`, `>=3`, `~0.4.1`, `1.x`) allows
+/// `version`, or `None` when it isn't understood.
+fn comparator_allows(ecosystem: Ecosystem, comparator: &str, version: &str) -> Option<bool> {
+    let op_len = comparator
+        .find(|c: char| !matches!(c, '<' | '>' | '=' | '~' | '^'))
+        .unwrap_or(comparator.len());
+    let (op, rest) = comparator.split_at(op_len);
+    let partial = Partial::parse(rest)?;
+    let cmp = |bound: &str| compare_versions(version, bound);
+    let below = |bound: Option<String>| bound.is_none_or(|b| cmp(&b) == Ordering::Less);
+    let op = match op {
+        "" if ecosystem == Ecosystem::CratesIo => "^",
+        "" => "=",
+        "~>" => "~",
+        op => op,
+    };
+    Some(match op {
+        "=" if partial.given == 3 => cmp(rest) == Ordering::Equal,
+        "=" => cmp(&partial.version()) != Ordering::Less && below(partial.next()),
+        "^" => {
+            let [major, minor, patch] = partial.parts;
+            let upper = match partial.given {
+                0 => None,
+                _ if major > 0 || partial.given == 1 => Some(format!("{}.0.0", major + 1)),
+                _ if minor > 0 || partial.given == 2 => Some(format!("0.{}.0", minor + 1)),
+                _ => Some(format!("0.0.{}", patch + 1)),
+            };
+            cmp(&partial.version()) != Ordering::Less && below(upper)
+        }
+        "~" => cmp(&partial.version()) != Ordering::Less && below(partial.next()),
+        ">=" => cmp(&partial.version()) != Ordering::Less,
+        ">" if partial.given == 3 => cmp(rest) == Ordering::Greater,
+        ">" => partial.next().is_some_and(|n| cmp(&n) != Ordering::Less),
+        "<" => cmp(&partial.version()) == Ordering::Less,
+        "<=" if partial.given == 3 => cmp(rest) != Ordering::Greater,
+        "<=" => below(partial.next()),
+        _ => return None,
+    })
+}
+
+/// Whether `requirement` allows `version`, with Cargo's rules (a bare
+/// version is a caret requirement, comparators are comma-separated) or
+/// npm's (a bare version is exact; `||`, hyphen ranges and x-ranges).
+/// `None` when the requirement isn't understood.
+pub fn requirement_allows(ecosystem: Ecosystem, requirement: &str, version: &str) -> Option<bool> {
+    let requirement = RE_OPERATOR_SPACE.replace_all(requirement.trim(), "$1");
+    let alternatives: Vec<&str> = match ecosystem {
+        Ecosystem::CratesIo => vec![&requirement],
+        Ecosystem::Npm => requirement.split("||").collect(),
+    };
+    let mut allowed = false;
+    for alternative in alternatives {
+        let alternative = alternative.trim();
+        let comparators: Vec<String> = match ecosystem {
+            Ecosystem::CratesIo => alternative
+                .split(',')
+                .map(|c| c.trim().to_string())
+                .collect(),
+            Ecosystem::Npm => match alternative.split_once(" - ") {
+                Some((low, high)) => vec![format!(">={}", low.trim()), {
+                    let high = high.trim();
+                    match Partial::parse(high)?.given {
+                        3 => format!("<={high}"),
+                        _ => format!("<{}", Partial::parse(high)?.next()?),
+                    }
+                }],
+                None => alternative.split_whitespace().map(str::to_string).collect(),
+            },
+        };
+        let mut all = true;
+        for comparator in comparators.iter().filter(|c| !c.is_empty()) {
+            all &= comparator_allows(ecosystem, comparator, version)?;
+        }
+        allowed |= all;
+    }
+    Some(allowed)
+}
+
+/// The requirement rewritten to allow `latest`, keeping its operator and
+/// precision (`^0.11` → `^0.12`, `1.2.3` → `2.0.1`). `None` for
+/// requirements with several comparators or wildcards, which are left for
+/// the developer to rewrite.
+pub fn bump_requirement(requirement: &str, latest: &str) -> Option<String> {
+    let requirement = requirement.trim();
+    let op_len = requirement
+        .find(|c: char| !matches!(c, '=' | '~' | '^'))
+        .unwrap_or(requirement.len());
+    let (op, rest) = requirement.split_at(op_len);
+    if rest.is_empty() || !rest.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let given = Partial::parse(rest)?.given;
+    let latest = Partial::parse(latest)?;
+    if given == 0 || latest.given < given {
+        return None;
+    }
+    let parts: Vec<String> = latest.parts[..given].iter().map(u64::to_string).collect();
+    let bumped = format!("{op}{}", parts.join("."));
+    (bumped != requirement).then_some(bumped)
+}
+
+/// The lowest version a single-comparator requirement names, filled out
+/// to three parts (`^0.11` → `0.11.0`).
+pub(super) fn base_version(requirement: &str) -> Option<String> {
+    let rest = requirement.trim().trim_start_matches(['=', '~', '^', '>']);
+    let partial = Partial::parse(rest)?;
+    (partial.given > 0).then(|| partial.version())
+}
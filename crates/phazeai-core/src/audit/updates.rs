@@ -0,0 +1,483 @@
+//! Outdated dependencies: the newest registry release of every declared
+//! dependency, the release notes since the locked version, the places the
+//! codebase uses the package, and the manifest edits that bump it.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::advisories::compare_versions;
+use super::lockfile::{workspace_packages, Ecosystem};
+use super::requirements::{
+    base_version, bump_requirement, manifest_dependencies, requirement_allows, Dependency,
+};
+use crate::constants::endpoints;
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+use crate::progress;
+
+/// How many registry lookups run at once.
+const LOOKUP_CONCURRENCY: usize = 8;
+/// Release notes past this many characters are cut, keeping the oldest
+/// entries, which are the ones the upgrade crosses first.
+const MAX_NOTES: usize = 12_000;
+
+static RE_GITHUB_REPO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"github\.com[/:]([A-Za-z0-9_.-]+)/([A-Za-z0-9_.-]+?)(?:\.git)?(?:[/#?]|$)").unwrap()
+});
+static RE_VERSION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"v?(\d+\.\d+(?:\.\d+)?(?:-[0-9A-Za-z.]+)?)").unwrap());
+
+const SUMMARY_PROMPT: &str = "You are helping a developer upgrade a dependency across a breaking \
+version. From the release notes, list only the breaking changes, removals and behaviour changes \
+that plausibly affect the usage sites shown, then one line per site that needs editing and what to \
+change. Say so plainly when nothing shown is affected. Be concise; plain text, no preamble.";
+
+/// A dependency whose registry has a newer stable release than the
+/// workspace uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outdated {
+    pub dependency: Dependency,
+    /// The locked version, or the requirement's own when nothing is locked.
+    pub current: String,
+    pub latest: String,
+    /// The requirement doesn't allow `latest`, so taking it means editing
+    /// the manifest; otherwise a lockfile update is enough.
+    pub breaking: bool,
+    /// Source repository URL from the registry.
+    pub repository: Option<String>,
+}
+
+impl Outdated {
+    /// The manifest requirement that allows `latest`, for breaking updates.
+    pub fn bumped_requirement(&self) -> Option<String> {
+        if !self.breaking {
+            return None;
+        }
+        bump_requirement(&self.dependency.requirement, &self.latest)
+    }
+}
+
+/// A registry's newest stable release of a package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub version: String,
+    pub repository: Option<String>,
+}
+
+fn http_client() -> Result<reqwest::Client, PhazeError> {
+    // crates.io rejects requests without a user agent.
+    Ok(reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .user_agent("PhazeAI/1.0 (dependency updates)")
+        .build()?)
+}
+
+/// The newest stable release on crates.io or npm, `None` when the registry
+/// doesn't know the package.
+pub async fn latest_release(
+    client: &reqwest::Client,
+    ecosystem: Ecosystem,
+    name: &str,
+) -> Result<Option<Release>, PhazeError> {
+    let url = match ecosystem {
+        Ecosystem::CratesIo => format!("{}/api/v1/crates/{name}", endpoints::CRATES_IO_API_URL),
+        Ecosystem::Npm => format!(
+            "{}/{}/latest",
+            endpoints::NPM_REGISTRY_URL,
+            name.replace('/', "%2F")
+        ),
+    };
+    let response = client.get(url).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body: serde_json::Value = response.error_for_status()?.json().await?;
+    let (version, repository) = match ecosystem {
+        Ecosystem::CratesIo => {
+            let krate = &body["crate"];
+            let version = krate["max_stable_version"]
+                .as_str()
+                .or_else(|| krate["max_version"].as_str());
+            (version, krate["repository"].as_str())
+        }
+        Ecosystem::Npm => {
+            let repository = match &body["repository"] {
+                serde_json::Value::String(url) => Some(url.as_str()),
+                other => other["url"].as_str(),
+            };
+            (body["version"].as_str(), repository)
+        }
+    };
+    Ok(version.map(|version| Release {
+        version: version.to_string(),
+        repository: repository.map(str::to_string),
+    }))
+}
+
+/// Every declared dependency under `root` with a newer stable release,
+/// breaking updates first.
+pub async fn find_outdated(root: &Path) -> Result<Vec<Outdated>, PhazeError> {
+    let dependencies: Vec<Dependency> = manifest_dependencies(root)
+        .into_iter()
+        .filter(|d| requirement_allows(d.ecosystem, &d.requirement, "0.0.0").is_some())
+        .collect();
+    let locked = workspace_packages(root);
+    let mut names: Vec<(Ecosystem, &str)> = dependencies
+        .iter()
+        .map(|d| (d.ecosystem, d.name.as_str()))
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let task = progress::global()
+        .task("Checking for updates")
+        .unit("packages")
+        .total(names.len() as u64)
+        .cancellable()
+        .start();
+    let client = http_client()?;
+    let mut releases = BTreeMap::new();
+    for chunk in names.chunks(LOOKUP_CONCURRENCY) {
+        if task.is_cancelled() {
+            return Err(PhazeError::Cancelled);
+        }
+        let lookups = chunk
+            .iter()
+            .map(|&(ecosystem, name)| latest_release(&client, ecosystem, name));
+        for (key, release) in chunk.iter().zip(futures::future::join_all(lookups).await) {
+            if let Some(release) = release? {
+                releases.insert(*key, release);
+            }
+        }
+        task.advance(chunk.len() as u64);
+    }
+
+    let mut outdated = Vec::new();
+    for dependency in &dependencies {
+        let Some(release) = releases.get(&(dependency.ecosystem, dependency.name.as_str())) else {
+            continue;
+        };
+        // The highest locked version the requirement allows is the one
+        // this manifest resolves to.
+        let current = locked
+            .iter()
+            .filter(|p| p.ecosystem == dependency.ecosystem && p.name == dependency.name)
+            .filter(|p| {
+                requirement_allows(dependency.ecosystem, &dependency.requirement, &p.version)
+                    == Some(true)
+            })
+            .map(|p| p.version.clone())
+            .max_by(|a, b| compare_versions(a, b))
+            .or_else(|| base_version(&dependency.requirement));
+        let Some(current) = current else {
+            continue;
+        };
+        if compare_versions(&release.version, &current) != Ordering::Greater {
+            continue;
+        }
+        let breaking = requirement_allows(
+            dependency.ecosystem,
+            &dependency.requirement,
+            &release.version,
+        ) == Some(false);
+        outdated.push(Outdated {
+            dependency: dependency.clone(),
+            current,
+            latest: release.version.clone(),
+            breaking,
+            repository: release.repository.clone(),
+        });
+    }
+    outdated.sort_by(|a, b| {
+        b.breaking
+            .cmp(&a.breaking)
+            .then_with(|| a.dependency.name.cmp(&b.dependency.name))
+            .then_with(|| a.dependency.manifest.cmp(&b.dependency.manifest))
+    });
+    Ok(outdated)
+}
+
+/// `(owner, repo)` of a GitHub repository URL, in any of the forms
+/// registries record (`git+https://….git`, `git@github.com:…`).
+pub fn github_repo(url: &str) -> Option<(String, String)> {
+    let captures = RE_GITHUB_REPO.captures(url)?;
+    Some((captures[1].to_string(), captures[2].to_string()))
+}
+
+/// Whether a version is past `from` and no later than `to`.
+fn in_upgrade(version: &str, from: &str, to: &str) -> bool {
+    compare_versions(version, from) == Ordering::Greater
+        && compare_versions(version, to) != Ordering::Greater
+}
+
+fn truncate_notes(mut notes: String) -> String {
+    if notes.len() > MAX_NOTES {
+        let mut end = MAX_NOTES;
+        while !notes.is_char_boundary(end) {
+            end -= 1;
+        }
+        notes.truncate(end);
+        notes.push_str("\n[…release notes truncated]");
+    }
+    notes
+}
+
+/// The entries of a Markdown changelog for versions past `from` up to
+/// `to`, oldest first. Entries are the sections under headings that name
+/// a version, at the level the first such heading uses.
+pub fn changelog_between(changelog: &str, from: &str, to: &str) -> Option<String> {
+    let heading = |line: &str| {
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if level == 0 {
+            return None;
+        }
+        let version = RE_VERSION.captures(&line[level..])?.get(1)?.as_str();
+        Some((level, version.to_string()))
+    };
+    let level = changelog.lines().find_map(heading)?.0;
+    let mut entries: Vec<String> = Vec::new();
+    let mut current: Option<String> = None;
+    for line in changelog.lines() {
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        if hashes > 0 && hashes <= level {
+            if let Some(entry) = current.take() {
+                entries.push(entry);
+            }
+            if let Some((_, version)) = heading(line).filter(|(l, _)| *l == level) {
+                if in_upgrade(&version, from, to) {
+                    current = Some(String::new());
+                }
+            }
+        }
+        if let Some(entry) = current.as_mut() {
+            entry.push_str(line);
+            entry.push('\n');
+        }
+    }
+    entries.extend(current);
+    if entries.is_empty() {
+        return None;
+    }
+    // Changelogs list the newest release first.
+    entries.reverse();
+    Some(truncate_notes(entries.concat()))
+}
+
+/// Whether a release tag belongs to `name`: `v1.2.0`, `1.2.0`,
+/// `name-1.2.0` and `name@1.2.0`, but not another package's tag in the
+/// same repository.
+fn tag_version<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let found = RE_VERSION.captures(tag)?.get(1)?;
+    let prefix = tag[..found.start()].trim_end_matches(['v', '-', '@', '/', '_']);
+    let short = name.rsplit('/').next().unwrap_or(name);
+    (prefix.is_empty() || prefix == name || prefix == short).then_some(found.as_str())
+}
+
+/// Release notes between the current and latest versions: the GitHub
+/// releases in that range, or the repository's `CHANGELOG.md` when it
+/// publishes none. `GH_TOKEN` / `GITHUB_TOKEN` raise GitHub's rate limit.
+pub async fn fetch_release_notes(outdated: &Outdated) -> Result<Option<String>, PhazeError> {
+    let Some((owner, repo)) = outdated.repository.as_deref().and_then(github_repo) else {
+        return Ok(None);
+    };
+    let client = http_client()?;
+    let token = std::env::var("GH_TOKEN")
+        .or_else(|_| std::env::var("GITHUB_TOKEN"))
+        .ok();
+    let mut request = client
+        .get(format!(
+            "{}/repos/{owner}/{repo}/releases?per_page=100",
+            endpoints::GITHUB_API_URL
+        ))
+        .header("Accept", "application/vnd.github+json");
+    if let Some(token) = &token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await?;
+    if response.status().is_success() {
+        let releases: Vec<serde_json::Value> = response.json().await?;
+        let mut notes = String::new();
+        // Releases come newest first; the notes read oldest first.
+        for release in releases.iter().rev() {
+            let tag = release["tag_name"].as_str().unwrap_or("");
+            let Some(version) = tag_version(tag, &outdated.dependency.name) else {
+                continue;
+            };
+            if !in_upgrade(version, &outdated.current, &outdated.latest) {
+                continue;
+            }
+            notes.push_str(&format!("## {tag}\n"));
+            notes.push_str(release["body"].as_str().unwrap_or("").trim());
+            notes.push_str("\n\n");
+        }
+        if !notes.is_empty() {
+            return Ok(Some(truncate_notes(notes)));
+        }
+    }
+    for file in ["CHANGELOG.md", "CHANGES.md", "RELEASES.md"] {
+        let response = client
+            .get(format!(
+                "{}/{owner}/{repo}/HEAD/{file}",
+                endpoints::GITHUB_RAW_URL
+            ))
+            .send()
+            .await?;
+        if response.status().is_success() {
+            let changelog = response.text().await?;
+            return Ok(changelog_between(
+                &changelog,
+                &outdated.current,
+                &outdated.latest,
+            ));
+        }
+    }
+    Ok(None)
+}
+
+/// Lines under `root` that use the dependency, as `path:line: code`, at
+/// most `limit` of them: `name::` paths and `use name` in Rust, imports
+/// and `require` calls in JavaScript and TypeScript.
+pub fn usage_sites(root: &Path, dependency: &Dependency, limit: usize) -> Vec<String> {
+    let (pattern, extensions): (String, &[&str]) = match dependency.ecosystem {
+        Ecosystem::CratesIo => {
+            let ident = regex::escape(&dependency.name.replace('-', "_"));
+            (format!(r"\b{ident}::|\buse\s+{ident}\b"), &["rs"])
+        }
+        Ecosystem::Npm => {
+            let name = regex::escape(&dependency.name);
+            (
+                format!(r#"(from\s+|require\(\s*|import\(\s*)['"]{name}(/[^'"]*)?['"]"#),
+                &["js", "jsx", "ts", "tsx", "mjs", "cjs", "vue", "svelte"],
+            )
+        }
+    };
+    let Ok(pattern) = Regex::new(&pattern) else {
+        return Vec::new();
+    };
+    let walker = ignore::WalkBuilder::new(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    let mut sites = Vec::new();
+    for entry in walker.flatten() {
+        let path = entry.path();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if !extensions.contains(&extension) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        for (i, line) in content.lines().enumerate() {
+            if pattern.is_match(line) {
+                sites.push(format!("{}:{}: {}", relative.display(), i + 1, line.trim()));
+                if sites.len() >= limit {
+                    return sites;
+                }
+            }
+        }
+    }
+    sites
+}
+
+/// Ask the model which of the update's breaking changes the usage sites
+/// run into.
+pub async fn summarize_breaking_changes(
+    llm: &dyn LlmClient,
+    outdated: &Outdated,
+    notes: Option<&str>,
+    usages: &[String],
+) -> Result<String, PhazeError> {
+    let mut request = format!(
+        "Dependency: {} ({}), upgrading {} -> {}\n\n",
+        outdated.dependency.name,
+        outdated.dependency.ecosystem.osv_name(),
+        outdated.current,
+        outdated.latest
+    );
+    match notes {
+        Some(notes) => request.push_str(&format!("Release notes:\n{notes}\n\n")),
+        None => request.push_str(
+            "No release notes were found; go by what you know of these versions and say so.\n\n",
+        ),
+    }
+    if usages.is_empty() {
+        request.push_str("No usage sites were found.\n");
+    } else {
+        request.push_str("Usage sites:\n");
+        for site in usages {
+            request.push_str(site);
+            request.push('\n');
+        }
+    }
+    let messages = vec![Message::system(SUMMARY_PROMPT), Message::user(request)];
+    let response = llm.chat(&messages, &[]).await?;
+    Ok(response.message.content.trim().to_string())
+}
+
+/// A manifest's content before and after bumping requirements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEdit {
+    /// Relative to the workspace root.
+    pub path: PathBuf,
+    pub before: String,
+    pub after: String,
+}
+
+impl ManifestEdit {
+    /// The edit as a unified diff with `a/` and `b/` paths, as `git diff`
+    /// prints it.
+    pub fn unified_diff(&self) -> String {
+        let path = self.path.display();
+        similar::TextDiff::from_lines(&self.before, &self.after)
+            .unified_diff()
+            .context_radius(2)
+            .header(&format!("a/{path}"), &format!("b/{path}"))
+            .to_string()
+    }
+}
+
+/// The manifest edits that take each breaking update's requirement to its
+/// latest version. Only the requirement on the recorded line is replaced,
+/// so comments and formatting survive; updates whose requirement can't be
+/// bumped mechanically are left out.
+pub fn bump_edits(root: &Path, updates: &[Outdated]) -> Vec<ManifestEdit> {
+    let mut by_manifest: BTreeMap<&Path, Vec<(&Outdated, String)>> = BTreeMap::new();
+    for update in updates {
+        if let Some(bumped) = update.bumped_requirement() {
+            by_manifest
+                .entry(update.dependency.manifest.as_path())
+                .or_default()
+                .push((update, bumped));
+        }
+    }
+    let mut edits = Vec::new();
+    for (manifest, bumps) in by_manifest {
+        let Ok(before) = std::fs::read_to_string(root.join(manifest)) else {
+            continue;
+        };
+        let mut lines: Vec<String> = before.split_inclusive('\n').map(str::to_string).collect();
+        for (update, bumped) in bumps {
+            let Some(line) = lines.get_mut(update.dependency.line) else {
+                continue;
+            };
+            let quoted = format!("\"{}\"", update.dependency.requirement);
+            if let Some(at) = line.find(&quoted) {
+                line.replace_range(at..at + quoted.len(), &format!("\"{bumped}\""));
+            }
+        }
+        let after = lines.concat();
+        if after != before {
+            edits.push(ManifestEdit {
+                path: manifest.to_path_buf(),
+                before,
+                after,
+            });
+        }
+    }
+    edits
+}
@@ -0,0 +1,189 @@
+//! Benchmark results over time, per branch, in `.phazeai/bench.db`.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{BenchResult, Comparison};
+use crate::error::PhazeError;
+
+const SCHEMA_VERSION: i64 = 1;
+
+/// Branches a new branch's first run is compared against.
+const DEFAULT_BRANCHES: &str = "('main', 'master')";
+
+/// One recorded timing of a benchmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub run_id: i64,
+    pub commit: Option<String>,
+    /// RFC 3339.
+    pub recorded_at: String,
+    pub mean_ns: f64,
+}
+
+/// A benchmark's recent samples on a branch, oldest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trend {
+    pub suite: String,
+    pub benchmark: String,
+    pub samples: Vec<Sample>,
+}
+
+impl Trend {
+    pub fn latest(&self) -> Option<&Sample> {
+        self.samples.last()
+    }
+
+    /// The latest sample's change from the one before, as a fraction.
+    pub fn change(&self) -> Option<f64> {
+        let [.., previous, latest] = self.samples.as_slice() else {
+            return None;
+        };
+        (previous.mean_ns > 0.0).then(|| latest.mean_ns / previous.mean_ns - 1.0)
+    }
+}
+
+pub struct BenchHistory {
+    conn: Connection,
+}
+
+impl BenchHistory {
+    pub fn open(path: &Path) -> Result<Self, PhazeError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS runs (
+                 id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                 branch      TEXT NOT NULL,
+                 commit_id   TEXT,
+                 suite       TEXT NOT NULL,
+                 recorded_at TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS results (
+                 run_id    INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+                 benchmark TEXT NOT NULL,
+                 mean_ns   REAL NOT NULL,
+                 low_ns    REAL NOT NULL,
+                 high_ns   REAL NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS runs_by_branch ON runs(branch, suite);
+             CREATE INDEX IF NOT EXISTS results_by_run ON results(run_id);
+             PRAGMA user_version = {SCHEMA_VERSION};"
+        ))?;
+        Ok(Self { conn })
+    }
+
+    /// The workspace's history, creating `.phazeai/bench.db` if needed.
+    pub fn open_workspace(root: &Path) -> Result<Self, PhazeError> {
+        let dir = root.join(".phazeai");
+        std::fs::create_dir_all(&dir)?;
+        Self::open(&dir.join("bench.db"))
+    }
+
+    /// Pair each result with its baseline: the latest earlier run of the
+    /// benchmark on `branch`, else on the default branch. Call before
+    /// [`record`](Self::record) so a run isn't its own baseline.
+    pub fn compare(
+        &self,
+        branch: &str,
+        suite: &str,
+        results: &[BenchResult],
+    ) -> Result<Vec<Comparison>, PhazeError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT r.mean_ns FROM results r JOIN runs u ON u.id = r.run_id
+             WHERE u.suite = ?2 AND r.benchmark = ?3
+               AND (u.branch = ?1 OR u.branch IN {DEFAULT_BRANCHES})
+             ORDER BY u.branch = ?1 DESC, u.id DESC LIMIT 1"
+        ))?;
+        results
+            .iter()
+            .map(|result| {
+                let baseline_ns = stmt
+                    .query_row(params![branch, suite, result.benchmark], |row| row.get(0))
+                    .optional()?;
+                Ok(Comparison {
+                    suite: suite.to_string(),
+                    result: result.clone(),
+                    baseline_ns,
+                })
+            })
+            .collect()
+    }
+
+    /// Store a suite's run, returning its id.
+    pub fn record(
+        &mut self,
+        branch: &str,
+        commit: Option<&str>,
+        suite: &str,
+        results: &[BenchResult],
+    ) -> Result<i64, PhazeError> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (branch, commit_id, suite, recorded_at) VALUES (?1, ?2, ?3, ?4)",
+            params![branch, commit, suite, chrono::Utc::now().to_rfc3339()],
+        )?;
+        let run_id = tx.last_insert_rowid();
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO results (run_id, benchmark, mean_ns, low_ns, high_ns)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for r in results {
+                insert.execute(params![run_id, r.benchmark, r.mean_ns, r.low_ns, r.high_ns])?;
+            }
+        }
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// Every benchmark run on `branch`, with its last `limit` samples.
+    pub fn trends(&self, branch: &str, limit: usize) -> Result<Vec<Trend>, PhazeError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT u.suite, r.benchmark, u.id, u.commit_id, u.recorded_at, r.mean_ns
+             FROM results r JOIN runs u ON u.id = r.run_id
+             WHERE u.branch = ?1
+             ORDER BY u.suite, r.benchmark, u.id",
+        )?;
+        let rows = stmt.query_map(params![branch], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                Sample {
+                    run_id: row.get(2)?,
+                    commit: row.get(3)?,
+                    recorded_at: row.get(4)?,
+                    mean_ns: row.get(5)?,
+                },
+            ))
+        })?;
+        let mut trends: Vec<Trend> = Vec::new();
+        for row in rows {
+            let (suite, benchmark, sample) = row?;
+            match trends.last_mut() {
+                Some(t) if t.suite == suite && t.benchmark == benchmark => t.samples.push(sample),
+                _ => trends.push(Trend {
+                    suite,
+                    benchmark,
+                    samples: vec![sample],
+                }),
+            }
+        }
+        for trend in &mut trends {
+            let excess = trend.samples.len().saturating_sub(limit);
+            trend.samples.drain(..excess);
+        }
+        Ok(trends)
+    }
+
+    /// Branches with recorded runs, most recently run first.
+    pub fn branches(&self) -> Result<Vec<String>, PhazeError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT branch FROM runs GROUP BY branch ORDER BY max(id) DESC")?;
+        let branches = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(branches)
+    }
+}
@@ -0,0 +1,343 @@
+//! Benchmarks: finding the workspace's criterion and pytest-benchmark
+//! suites, running them, and tracking their results per branch in a local
+//! database so a change that slows one down is caught.
+
+mod history;
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::PhazeError;
+use crate::progress;
+
+pub use history::{BenchHistory, Sample, Trend};
+
+/// Slowdown, as a fraction of the baseline, past which a benchmark counts
+/// as regressed.
+pub const DEFAULT_THRESHOLD: f64 = 0.10;
+
+static RE_PYTEST_BENCH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"def\s+test\w*\s*\([^)]*\bbenchmark\b").unwrap());
+
+/// The harness a suite is written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BenchKind {
+    /// A `benches/*.rs` target using criterion, run with `cargo bench`.
+    Criterion,
+    /// A pytest file with tests taking the `benchmark` fixture.
+    PytestBenchmark,
+}
+
+impl BenchKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Criterion => "criterion",
+            Self::PytestBenchmark => "pytest-benchmark",
+        }
+    }
+}
+
+/// A runnable set of benchmarks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BenchSuite {
+    pub kind: BenchKind,
+    /// The cargo bench target, or the pytest file's path.
+    pub name: String,
+    /// Where the suite runs from: the package directory for criterion, the
+    /// workspace root for pytest. Relative to the workspace root.
+    pub dir: PathBuf,
+    /// The suite's source file, relative to the workspace root.
+    pub file: PathBuf,
+}
+
+/// One benchmark's timing from a run, in nanoseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub benchmark: String,
+    pub mean_ns: f64,
+    /// Criterion's confidence interval for the mean, or the mean plus or
+    /// minus one standard deviation for pytest-benchmark.
+    pub low_ns: f64,
+    pub high_ns: f64,
+}
+
+/// A run's result next to the baseline it's judged against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub suite: String,
+    pub result: BenchResult,
+    /// The latest earlier mean on the same branch, or on `main` / `master`
+    /// for a branch's first run.
+    pub baseline_ns: Option<f64>,
+}
+
+impl Comparison {
+    /// Change from the baseline as a fraction: 0.25 is 25% slower.
+    pub fn change(&self) -> Option<f64> {
+        let baseline = self.baseline_ns.filter(|b| *b > 0.0)?;
+        Some(self.result.mean_ns / baseline - 1.0)
+    }
+
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.change().is_some_and(|c| c > threshold)
+    }
+}
+
+/// Every criterion and pytest-benchmark suite under `root`.
+pub fn discover(root: &Path) -> Vec<BenchSuite> {
+    let walker = WalkBuilder::new(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some("node_modules" | "target")))
+        .build();
+    let mut suites = Vec::new();
+    for entry in walker.flatten() {
+        let path = entry.path();
+        let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("rs") => {
+                // `benches/name.rs` or `benches/name/main.rs`.
+                let Some(parent) = path.parent() else {
+                    continue;
+                };
+                let (name, benches) = match path.file_stem().and_then(|s| s.to_str()) {
+                    Some("main") if parent.parent().is_some_and(|p| p.ends_with("benches")) => {
+                        (parent.file_name().and_then(|n| n.to_str()), parent.parent())
+                    }
+                    stem if parent.ends_with("benches") => (stem, Some(parent)),
+                    _ => continue,
+                };
+                let (Some(name), Some(benches)) = (name, benches) else {
+                    continue;
+                };
+                let Ok(code) = std::fs::read_to_string(path) else {
+                    continue;
+                };
+                if !code.contains("criterion_main!") {
+                    continue;
+                }
+                let package = benches.parent().unwrap_or(root);
+                suites.push(BenchSuite {
+                    kind: BenchKind::Criterion,
+                    name: name.to_string(),
+                    dir: package.strip_prefix(root).unwrap_or(package).to_path_buf(),
+                    file: relative,
+                });
+            }
+            Some("py") => {
+                let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                if !(stem.starts_with("test_") || stem.ends_with("_test")) {
+                    continue;
+                }
+                let Ok(code) = std::fs::read_to_string(path) else {
+                    continue;
+                };
+                if !RE_PYTEST_BENCH.is_match(&code) {
+                    continue;
+                }
+                suites.push(BenchSuite {
+                    kind: BenchKind::PytestBenchmark,
+                    name: relative.to_string_lossy().replace('\\', "/"),
+                    dir: PathBuf::new(),
+                    file: relative,
+                });
+            }
+            _ => {}
+        }
+    }
+    suites
+}
+
+/// Nanoseconds in a criterion time unit.
+fn unit_ns(unit: &str) -> Option<f64> {
+    Some(match unit {
+        "ps" => 0.001,
+        "ns" => 1.0,
+        "µs" | "us" | "μs" => 1e3,
+        "ms" => 1e6,
+        "s" => 1e9,
+        _ => return None,
+    })
+}
+
+/// The timings in criterion's console output: a `time: [low mean high]`
+/// line per benchmark, after its name on the same line or, for long names,
+/// the line before.
+pub fn parse_criterion_output(output: &str) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    let mut previous = "";
+    for line in output.lines() {
+        let Some((name, rest)) = line.split_once("time:") else {
+            if !line.trim().is_empty() {
+                previous = line;
+            }
+            continue;
+        };
+        let name = match name.trim() {
+            "" => previous.trim(),
+            name => name,
+        };
+        let values: Vec<f64> = rest
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .filter_map(|pair| match pair {
+                [value, unit] => Some(value.parse::<f64>().ok()? * unit_ns(unit)?),
+                _ => None,
+            })
+            .collect();
+        if let ([low, mean, high], false) = (values.as_slice(), name.is_empty()) {
+            results.push(BenchResult {
+                benchmark: name.to_string(),
+                mean_ns: *mean,
+                low_ns: *low,
+                high_ns: *high,
+            });
+        }
+        previous = line;
+    }
+    results
+}
+
+/// The timings in a `--benchmark-json` report, which are in seconds.
+pub fn parse_pytest_benchmark_json(json: &str) -> Vec<BenchResult> {
+    let Ok(report) = serde_json::from_str::<serde_json::Value>(json) else {
+        return Vec::new();
+    };
+    let benchmarks = report["benchmarks"].as_array();
+    benchmarks
+        .into_iter()
+        .flatten()
+        .filter_map(|b| {
+            let name = b["name"].as_str()?;
+            let mean = b["stats"]["mean"].as_f64()? * 1e9;
+            let stddev = b["stats"]["stddev"].as_f64().unwrap_or(0.0) * 1e9;
+            Some(BenchResult {
+                benchmark: name.to_string(),
+                mean_ns: mean,
+                low_ns: (mean - stddev).max(0.0),
+                high_ns: mean + stddev,
+            })
+        })
+        .collect()
+}
+
+/// Run a suite and read its timings, blocking until it finishes. Reported
+/// on the progress bus and cancellable there.
+pub fn run_suite(root: &Path, suite: &BenchSuite) -> Result<Vec<BenchResult>, PhazeError> {
+    let task = progress::global()
+        .task(format!("Benchmarking {}", suite.name))
+        .cancellable()
+        .start();
+    let report = std::env::temp_dir().join(format!(
+        "phazeai-bench-{}-{}.json",
+        std::process::id(),
+        task.id()
+    ));
+    let mut command = match suite.kind {
+        BenchKind::Criterion => {
+            let mut c = Command::new("cargo");
+            c.args(["bench", "--bench", &suite.name, "--", "--noplot"]);
+            c
+        }
+        BenchKind::PytestBenchmark => {
+            let mut c = Command::new(if cfg!(windows) { "python" } else { "python3" });
+            c.args(["-m", "pytest", "-q", "--benchmark-only"])
+                .arg(format!("--benchmark-json={}", report.display()))
+                .arg(&suite.file);
+            c
+        }
+    };
+    let mut child = command
+        .current_dir(root.join(&suite.dir))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| PhazeError::Other(format!("starting {} failed: {e}", suite.kind.label())))?;
+    // Both pipes are drained as they fill, so neither can block the run.
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        pipe.map(|mut pipe| {
+            std::thread::spawn(move || {
+                let mut text = String::new();
+                let _ = pipe.read_to_string(&mut text);
+                text
+            })
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if task.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(PhazeError::Cancelled);
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    };
+    let collect = |h: Option<std::thread::JoinHandle<String>>| {
+        h.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+    let (stdout, stderr) = (collect(stdout), collect(stderr));
+    let results = match suite.kind {
+        BenchKind::Criterion => parse_criterion_output(&stdout),
+        BenchKind::PytestBenchmark => {
+            let json = std::fs::read_to_string(&report).unwrap_or_default();
+            let _ = std::fs::remove_file(&report);
+            parse_pytest_benchmark_json(&json)
+        }
+    };
+    if !status.success() || results.is_empty() {
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        let tail: Vec<&str> = tail.into_iter().rev().collect();
+        return Err(PhazeError::Other(format!(
+            "{} {} exited with {status} and no timings: {}",
+            suite.kind.label(),
+            suite.name,
+            tail.join("\n")
+        )));
+    }
+    Ok(results)
+}
+
+/// The checked-out branch (`HEAD` when detached) and short commit.
+pub fn git_head(root: &Path) -> (String, Option<String>) {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !text.is_empty()).then_some(text)
+    };
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]).unwrap_or_else(|| "HEAD".to_string());
+    (branch, git(&["rev-parse", "--short", "HEAD"]))
+}
+
+/// A duration in the unit criterion would print it in (`26.25 µs`).
+pub fn format_duration(ns: f64) -> String {
+    let (value, unit) = if ns >= 1e9 {
+        (ns / 1e9, "s")
+    } else if ns >= 1e6 {
+        (ns / 1e6, "ms")
+    } else if ns >= 1e3 {
+        (ns / 1e3, "µs")
+    } else {
+        (ns, "ns")
+    };
+    format!("{value:.2} {unit}")
+}
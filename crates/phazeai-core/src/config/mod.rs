@@ -1,9 +1,19 @@
-use crate::constants::{defaults, paths};
+use crate::agent::ScheduledJob;
+use crate::constants::{defaults, endpoints, paths};
+use crate::db::DatabaseProfile;
 use crate::llm::model_router::{ModelRoute, ModelRouter, TaskType};
 use crate::llm::provider::{ProviderConfig, ProviderId, ProviderRegistry};
+use crate::llm::responses::ReasoningEffort;
+use crate::llm::vertex::VertexProject;
+use crate::memory::MemoryBudgets;
+use crate::project::watcher::WatchOptions;
+use crate::update::UpdateSettings;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
+
+pub mod store;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
@@ -14,6 +24,32 @@ pub struct Settings {
     pub providers: Vec<ProviderEntry>,
     #[serde(default)]
     pub model_routes: HashMap<TaskType, ModelRoute>,
+    /// Agent jobs run by `phazeai daemon`.
+    #[serde(default)]
+    pub schedules: Vec<ScheduledJob>,
+    #[serde(default)]
+    pub agent: AgentSettings,
+    /// The Google Cloud project Vertex AI bills to.
+    #[serde(default)]
+    pub vertex: VertexSettings,
+    /// Helper processes whose tools the agent can call.
+    #[serde(default)]
+    pub plugin_sidecars: Vec<PluginSidecarConfig>,
+    /// Documentation the agent looks things up in.
+    #[serde(default)]
+    pub docs: DocsSettings,
+    /// Connections for the DB tab and the `sql_query` tool.
+    #[serde(default)]
+    pub databases: Vec<DatabaseProfile>,
+    /// How workspaces are watched for changes.
+    #[serde(default)]
+    pub watcher: WatchOptions,
+    /// What the IDE's caches may hold.
+    #[serde(default)]
+    pub memory: MemoryBudgets,
+    /// The release channel and whether to look for updates.
+    #[serde(default)]
+    pub updates: UpdateSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +59,15 @@ pub struct LlmSettings {
     pub api_key_env: String,
     pub base_url: Option<String>,
     pub max_tokens: u32,
+    /// How long OpenAI's reasoning models (o-series, GPT-5) think:
+    /// `minimal`, `low`, `medium` or `high`. Unset is the model's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// Tokens Claude may spend on extended thinking before it answers (at
+    /// least 1024). Unset leaves thinking off; a model route's own
+    /// `thinking_budget` takes precedence for its task type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -34,11 +79,43 @@ pub enum LlmProvider {
     Groq,
     Together,
     OpenRouter,
+    Xai,
+    Mistral,
     LmStudio,
+    LlamaCpp,
+    Vllm,
+    HuggingFace,
+    Bedrock,
     Gemini,
+    Vertex,
+    /// PhazeAI Cloud hosted models (requires signing in).
+    PhazeCloud,
 }
 
 impl LlmProvider {
+    /// Parse a provider name as typed on the command line (`claude`, `gpt`, `lm-studio`...).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "claude" | "anthropic" => LlmProvider::Claude,
+            "openai" | "gpt" => LlmProvider::OpenAI,
+            "ollama" | "local" => LlmProvider::Ollama,
+            "groq" => LlmProvider::Groq,
+            "together" => LlmProvider::Together,
+            "openrouter" | "or" => LlmProvider::OpenRouter,
+            "xai" | "x.ai" | "grok" => LlmProvider::Xai,
+            "mistral" | "mistralai" | "la-plateforme" => LlmProvider::Mistral,
+            "lmstudio" | "lm-studio" | "lm_studio" => LlmProvider::LmStudio,
+            "llamacpp" | "llama.cpp" | "llama-cpp" | "llama-server" => LlmProvider::LlamaCpp,
+            "vllm" => LlmProvider::Vllm,
+            "huggingface" | "hf" | "tgi" => LlmProvider::HuggingFace,
+            "bedrock" | "aws" | "aws-bedrock" => LlmProvider::Bedrock,
+            "gemini" => LlmProvider::Gemini,
+            "vertex" | "vertex-ai" | "vertexai" | "gcp" => LlmProvider::Vertex,
+            "phazecloud" | "phaze-cloud" | "cloud" => LlmProvider::PhazeCloud,
+            _ => return None,
+        })
+    }
+
     pub fn to_provider_id(&self) -> ProviderId {
         match self {
             LlmProvider::Claude => ProviderId::Claude,
@@ -47,8 +124,16 @@ impl LlmProvider {
             LlmProvider::Groq => ProviderId::Groq,
             LlmProvider::Together => ProviderId::Together,
             LlmProvider::OpenRouter => ProviderId::OpenRouter,
+            LlmProvider::Xai => ProviderId::Xai,
+            LlmProvider::Mistral => ProviderId::Mistral,
             LlmProvider::LmStudio => ProviderId::LmStudio,
+            LlmProvider::LlamaCpp => ProviderId::LlamaCpp,
+            LlmProvider::Vllm => ProviderId::Vllm,
+            LlmProvider::HuggingFace => ProviderId::HuggingFace,
+            LlmProvider::Bedrock => ProviderId::Bedrock,
             LlmProvider::Gemini => ProviderId::Gemini,
+            LlmProvider::Vertex => ProviderId::Vertex,
+            LlmProvider::PhazeCloud => ProviderId::PhazeCloud,
         }
     }
 }
@@ -68,6 +153,17 @@ pub struct ProviderEntry {
 pub struct EditorSettings {
     pub theme: String,
     pub font_size: f32,
+    /// Editor font family; falls back to the built-in monospace stack if missing.
+    pub font_family: String,
+    /// Render programming ligatures (`->`, `!=`, `=>`) where the font supports them.
+    pub font_ligatures: bool,
+    /// Editor line height as a multiple of the font size.
+    pub line_height: f32,
+    pub terminal_font_family: String,
+    pub terminal_font_size: f32,
+    /// Chat font family; empty uses the regular UI font.
+    pub chat_font_family: String,
+    pub chat_font_size: f32,
     pub tab_size: u32,
     pub show_line_numbers: bool,
     pub auto_save: bool,
@@ -76,6 +172,30 @@ pub struct EditorSettings {
     pub inlay_hints: bool,
     pub code_lens: bool,
     pub organize_imports_on_save: bool,
+    /// Give a file with mixed line endings one kind when it's saved.
+    pub normalize_line_endings_on_save: bool,
+    /// Columns to draw vertical rulers at, besides the `.editorconfig`
+    /// `max_line_length`.
+    pub rulers: Vec<u32>,
+    /// Report lines over the `.editorconfig` `max_line_length` in the
+    /// Problems panel.
+    pub line_length_lint: bool,
+    /// Switch between the light and dark UI theme with the OS appearance.
+    pub follow_system_theme: bool,
+    /// Syntax highlighting theme chosen per UI theme (UI theme name → syntect theme).
+    /// UI themes without an entry use their built-in default.
+    pub syntax_themes: HashMap<String, String>,
+    /// UI language as a locale code (`de`, `pt_BR`); empty follows `LANG`.
+    pub locale: String,
+    /// Listen on the local control socket used by `phazeai ctl`.
+    pub control_socket: bool,
+    /// Explain shell commands hovered in the terminal or chat with the
+    /// quick-answer model.
+    pub explain_commands_on_hover: bool,
+    /// Show who last changed the current line at its end.
+    pub inline_blame: bool,
+    /// Show the blame gutter (author and date beside every line).
+    pub blame_gutter: bool,
 }
 
 impl Default for EditorSettings {
@@ -83,6 +203,13 @@ impl Default for EditorSettings {
         Self {
             theme: defaults::THEME.to_string(),
             font_size: defaults::FONT_SIZE,
+            font_family: defaults::FONT_FAMILY.to_string(),
+            font_ligatures: true,
+            line_height: defaults::LINE_HEIGHT,
+            terminal_font_family: defaults::FONT_FAMILY.to_string(),
+            terminal_font_size: defaults::TERMINAL_FONT_SIZE,
+            chat_font_family: String::new(),
+            chat_font_size: defaults::CHAT_FONT_SIZE,
             tab_size: defaults::TAB_SIZE,
             show_line_numbers: true,
             auto_save: true,
@@ -91,10 +218,94 @@ impl Default for EditorSettings {
             inlay_hints: true,
             code_lens: true,
             organize_imports_on_save: false,
+            normalize_line_endings_on_save: false,
+            rulers: Vec::new(),
+            line_length_lint: false,
+            follow_system_theme: false,
+            syntax_themes: HashMap::new(),
+            locale: String::new(),
+            control_socket: true,
+            explain_commands_on_hover: false,
+            inline_blame: true,
+            blame_gutter: false,
+        }
+    }
+}
+
+/// Limits on agent runs; `0` turns a limit off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentSettings {
+    /// Longest one request may run, model calls and tools included.
+    pub turn_timeout_secs: u64,
+    /// Longest a single tool call may run before it fails.
+    pub tool_timeout_secs: u64,
+}
+
+impl Default for AgentSettings {
+    fn default() -> Self {
+        Self {
+            turn_timeout_secs: defaults::TURN_TIMEOUT_SECS,
+            tool_timeout_secs: defaults::TOOL_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// Where Vertex AI requests go. An empty project falls back to
+/// `GOOGLE_CLOUD_PROJECT` or the credentials' project.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VertexSettings {
+    pub project: String,
+    /// A region such as `us-central1`, or `global`.
+    pub location: String,
+}
+
+impl Default for VertexSettings {
+    fn default() -> Self {
+        Self {
+            project: String::new(),
+            location: endpoints::VERTEX_DEFAULT_LOCATION.to_string(),
+        }
+    }
+}
+
+/// Documentation folders and files (docs/, wiki exports, ADRs, PDFs) whose
+/// passages most relevant to each request are added to the prompt, to be
+/// cited in the reply.
+///
+/// ```toml
+/// [docs]
+/// sources = ["docs", "adr", "~/exports/wiki"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocsSettings {
+    /// Relative to the workspace, absolute, or under `~`.
+    pub sources: Vec<String>,
+    /// Passages added to each request; `0` turns retrieval off.
+    pub max_chunks: usize,
+}
+
+impl Default for DocsSettings {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            max_chunks: defaults::DOCS_MAX_CHUNKS,
         }
     }
 }
 
+impl AgentSettings {
+    pub fn turn_timeout(&self) -> Option<Duration> {
+        (self.turn_timeout_secs > 0).then(|| Duration::from_secs(self.turn_timeout_secs))
+    }
+
+    pub fn tool_timeout(&self) -> Option<Duration> {
+        (self.tool_timeout_secs > 0).then(|| Duration::from_secs(self.tool_timeout_secs))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SidecarSettings {
     pub enabled: bool,
@@ -102,6 +313,30 @@ pub struct SidecarSettings {
     pub auto_start: bool,
 }
 
+/// A helper process in any language — Node, Go, … — that speaks the
+/// sidecar's JSON-RPC protocol over stdio and provides tools to the agent.
+///
+/// ```toml
+/// [[plugin_sidecars]]
+/// name = "jira"
+/// command = "node"
+/// args = ["/opt/jira-helper/index.js"]
+/// tools = ["create_issue"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginSidecarConfig {
+    /// Names its tools for the agent: `sidecar__<name>__<tool>`.
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Which of the tools it provides to use; empty for all of them.
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -111,6 +346,8 @@ impl Default for Settings {
                 api_key_env: "".to_string(),
                 base_url: None,
                 max_tokens: defaults::MAX_TOKENS,
+                reasoning_effort: None,
+                thinking_budget: None,
             },
             editor: EditorSettings::default(),
             sidecar: SidecarSettings {
@@ -120,6 +357,15 @@ impl Default for Settings {
             },
             providers: Vec::new(),
             model_routes: HashMap::new(),
+            schedules: Vec::new(),
+            agent: AgentSettings::default(),
+            vertex: VertexSettings::default(),
+            plugin_sidecars: Vec::new(),
+            docs: DocsSettings::default(),
+            databases: Vec::new(),
+            watcher: WatchOptions::default(),
+            memory: MemoryBudgets::default(),
+            updates: UpdateSettings::default(),
         }
     }
 }
@@ -134,25 +380,34 @@ impl Settings {
 
     pub fn load() -> Self {
         let config_path = Self::config_path();
-        if config_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&config_path) {
-                if let Ok(config) = toml::from_str(&content) {
-                    return config;
-                }
-            }
-        }
-        Self::default()
+        store::read(&config_path).unwrap_or_else(|e| {
+            tracing::warn!("{e}; using the default settings");
+            Self::default()
+        })
+    }
+
+    /// Apply `change` to the config as it is on disk and save what it
+    /// changed, leaving other writers' changes in place. Returns the
+    /// settings as saved.
+    pub fn update(
+        change: impl FnOnce(&mut Settings),
+    ) -> Result<Settings, crate::error::PhazeError> {
+        store::update(&Self::config_path(), change)
     }
 
+    /// Save all of these settings. Anything changed on disk since they were
+    /// loaded is overwritten, so prefer [`Settings::update`].
     pub fn save(&self) -> Result<(), crate::error::PhazeError> {
-        let config_path = Self::config_path();
-        if let Some(parent) = config_path.parent() {
-            std::fs::create_dir_all(parent)?;
+        store::update(&Self::config_path(), |settings| *settings = self.clone()).map(|_| ())
+    }
+
+    /// The Ollama server custom models are built on: the configured one
+    /// when Ollama is the provider, else the local default.
+    pub fn ollama_base_url(&self) -> String {
+        match (&self.llm.provider, &self.llm.base_url) {
+            (LlmProvider::Ollama, Some(url)) => url.clone(),
+            _ => endpoints::OLLAMA_BASE_URL.to_string(),
         }
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| crate::error::PhazeError::Config(e.to_string()))?;
-        std::fs::write(&config_path, content)?;
-        Ok(())
     }
 
     /// Get the API key from the environment variable specified in settings.
@@ -164,6 +419,16 @@ impl Settings {
     pub fn build_provider_registry(&self) -> ProviderRegistry {
         let mut registry = ProviderRegistry::new();
 
+        if !self.vertex.project.is_empty() {
+            if let Some(vertex) = registry.get_config(&ProviderId::Vertex) {
+                let project = VertexProject::new(&self.vertex.project, &self.vertex.location);
+                registry.configure(ProviderConfig {
+                    base_url: project.base_url(),
+                    ..vertex.clone()
+                });
+            }
+        }
+
         // Apply any custom provider configs from settings
         for entry in &self.providers {
             let id = match entry.name.to_lowercase().as_str() {
@@ -173,8 +438,16 @@ impl Settings {
                 "groq" => ProviderId::Groq,
                 "together" => ProviderId::Together,
                 "openrouter" => ProviderId::OpenRouter,
+                "xai" | "grok" => ProviderId::Xai,
+                "mistral" => ProviderId::Mistral,
                 "lmstudio" | "lm_studio" => ProviderId::LmStudio,
+                "llamacpp" | "llama.cpp" => ProviderId::LlamaCpp,
+                "vllm" => ProviderId::Vllm,
+                "huggingface" | "hf" | "tgi" => ProviderId::HuggingFace,
+                "bedrock" | "aws" => ProviderId::Bedrock,
                 "gemini" => ProviderId::Gemini,
+                "vertex" | "vertex-ai" => ProviderId::Vertex,
+                "phazecloud" => ProviderId::PhazeCloud,
                 other => ProviderId::Custom(other.to_string()),
             };
             let config = ProviderConfig {
@@ -184,12 +457,31 @@ impl Settings {
                 base_url: entry.base_url.clone(),
                 default_model: entry.default_model.clone(),
             };
+            // An entry for a built-in provider (e.g. a TGI endpoint for
+            // `huggingface`) replaces its defaults, keeping any left empty.
+            if !matches!(id, ProviderId::Custom(_)) {
+                if let Some(builtin) = registry.get_config(&id) {
+                    let mut merged = config.clone();
+                    for (field, default) in [
+                        (&mut merged.api_key_env, &builtin.api_key_env),
+                        (&mut merged.base_url, &builtin.base_url),
+                        (&mut merged.default_model, &builtin.default_model),
+                    ] {
+                        if field.is_empty() {
+                            *field = default.clone();
+                        }
+                    }
+                    registry.configure(merged);
+                }
+            }
             registry.add_custom_provider(entry.name.clone(), config);
         }
 
         // Set active provider from legacy settings
         let provider_id = self.llm.provider.to_provider_id();
         registry.set_active(provider_id, self.llm.model.clone());
+        registry.set_reasoning_effort(self.llm.reasoning_effort);
+        registry.set_thinking_budget(self.llm.thinking_budget);
 
         registry
     }
@@ -208,4 +500,48 @@ impl Settings {
             Ok(Box::new(router))
         }
     }
+
+    /// Build a client for one kind of background task: the configured route
+    /// for `task` if there is one, otherwise the default model. A route with
+    /// a draft model gets a [`SpeculativeClient`](crate::llm::SpeculativeClient).
+    pub fn build_client_for_task(
+        &self,
+        task: TaskType,
+    ) -> Result<Box<dyn crate::llm::LlmClient>, crate::error::PhazeError> {
+        let mut settings = self.clone();
+        let route = self.model_routes.get(&task);
+        if let Some(route) = route {
+            if let Some(provider) = LlmProvider::from_name(&route.provider) {
+                settings.llm.provider = provider;
+                settings.llm.model = route.model.clone();
+            }
+            if route.thinking_budget.is_some() {
+                settings.llm.thinking_budget = route.thinking_budget;
+            }
+        }
+        settings.model_routes.clear();
+        let client = settings.build_llm_client()?;
+        Ok(match route {
+            Some(route) => {
+                ModelRouter::with_draft(route, &settings.build_provider_registry(), client)
+            }
+            None => client,
+        })
+    }
+
+    /// A raw fill-in-the-middle client for inline completions, when the
+    /// completion route (or the default model) is on a llama.cpp or vLLM
+    /// server that can take one. `None` means completions go through chat.
+    pub fn build_fim_client(&self) -> Option<crate::llm::FimClient> {
+        let (provider, model) = match self.model_routes.get(&TaskType::Completion) {
+            Some(route) => (
+                LlmProvider::from_name(&route.provider)?,
+                route.model.as_str(),
+            ),
+            None => (self.llm.provider.clone(), self.llm.model.as_str()),
+        };
+        let registry = self.build_provider_registry();
+        let config = registry.get_config(&provider.to_provider_id())?;
+        crate::llm::FimClient::for_provider(config, model)
+    }
 }
@@ -0,0 +1,297 @@
+//! The one way `config.toml` gets written.
+//!
+//! The IDE saves from background threads and the CLI may be running beside
+//! it, so a write takes a lock file next to the config, reads what's on disk
+//! at that moment, applies its change, and only writes back the keys the
+//! change touched, leaving comments, key order, keys this build doesn't know
+//! and everyone else's changes alone. The file is replaced by a rename, so a
+//! reader never sees half of it, and a config that doesn't parse is reported
+//! rather than overwritten with defaults.
+//!
+//! [`subscribe`] hears about every change, whether written through here or
+//! by another process.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, Once};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::Settings;
+use crate::error::PhazeError;
+
+/// How long a writer waits for the lock before giving up.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+/// A lock file older than this was left by a writer that died.
+const STALE_LOCK: Duration = Duration::from_secs(30);
+/// How often [`subscribe`] looks for changes made by other processes.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Threads of this process queue here rather than spinning on the lock file.
+static WRITE: Mutex<()> = Mutex::new(());
+static SUBSCRIBERS: Mutex<Vec<Sender<ConfigChange>>> = Mutex::new(Vec::new());
+/// The config as last written or seen, so the poller only reports changes.
+static LAST_SEEN: Mutex<Option<String>> = Mutex::new(None);
+static POLLER: Once = Once::new();
+
+/// A change to the config, as [`subscribe`] reports it.
+#[derive(Debug, Clone)]
+pub struct ConfigChange {
+    /// The settings after the change.
+    pub settings: Settings,
+    /// Made by another process or by hand, rather than through [`update`]
+    /// in this one.
+    pub external: bool,
+}
+
+/// Held while writing the config; the lock file goes when it's dropped.
+struct ConfigLock {
+    path: PathBuf,
+}
+
+impl ConfigLock {
+    fn acquire(config: &Path) -> Result<Self, PhazeError> {
+        let path = lock_path(config);
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if is_stale(&path) {
+                        let _ = std::fs::remove_file(&path);
+                        continue;
+                    }
+                    if Instant::now() >= deadline {
+                        return Err(PhazeError::Config(format!(
+                            "{} is locked by another writer; delete {} if none is running",
+                            config.display(),
+                            path.display()
+                        )));
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+impl Drop for ConfigLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn lock_path(config: &Path) -> PathBuf {
+    let mut name = config.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    config.with_file_name(name)
+}
+
+fn is_stale(lock: &Path) -> bool {
+    std::fs::metadata(lock)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > STALE_LOCK)
+}
+
+fn parse(path: &Path, text: &str) -> Result<Settings, PhazeError> {
+    toml::from_str(text).map_err(|e| PhazeError::Config(format!("{}: {e}", path.display())))
+}
+
+/// The settings in the config at `path`: the defaults when there's none,
+/// an error when it doesn't parse.
+pub fn read(path: &Path) -> Result<Settings, PhazeError> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => parse(path, &text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn to_table(settings: &Settings) -> Result<toml::Table, PhazeError> {
+    toml::Table::try_from(settings).map_err(|e| PhazeError::Config(e.to_string()))
+}
+
+/// `value` as it's written under `key`: tables as `[key]` sections and
+/// arrays of tables as `[[key]]`, like a whole-file write would have them.
+fn to_item(key: &str, value: &toml::Value) -> Result<toml_edit::Item, PhazeError> {
+    let mut wrapper = toml::Table::new();
+    wrapper.insert(key.to_string(), value.clone());
+    let mut doc =
+        toml_edit::ser::to_document(&wrapper).map_err(|e| PhazeError::Config(e.to_string()))?;
+    Ok(expand(doc.remove(key).unwrap_or_default()))
+}
+
+/// The serializer writes nested tables inline; turn them into sections.
+fn expand(item: toml_edit::Item) -> toml_edit::Item {
+    use toml_edit::{ArrayOfTables, Item, Value};
+    match item {
+        Item::Value(Value::InlineTable(table)) => {
+            let mut table = table.into_table();
+            for (_, item) in table.iter_mut() {
+                *item = expand(std::mem::take(item));
+            }
+            Item::Table(table)
+        }
+        Item::Value(Value::Array(array))
+            if !array.is_empty() && array.iter().all(|v| v.is_inline_table()) =>
+        {
+            let mut tables = ArrayOfTables::new();
+            for value in array {
+                if let Item::Table(table) = expand(Item::Value(value)) {
+                    tables.push(table);
+                }
+            }
+            Item::ArrayOfTables(tables)
+        }
+        item => item,
+    }
+}
+
+/// Write into `doc` what changed between `before` and `after`, recursing
+/// into sections so sibling keys, and their comments, stay as they are.
+fn patch(
+    doc: &mut toml_edit::Table,
+    before: &toml::Table,
+    after: &toml::Table,
+) -> Result<(), PhazeError> {
+    for (key, value) in after {
+        let old = before.get(key);
+        if old == Some(value) {
+            continue;
+        }
+        if let (Some(toml::Value::Table(old)), toml::Value::Table(new)) = (old, value) {
+            if let Some(section) = doc.get_mut(key).and_then(|i| i.as_table_mut()) {
+                patch(section, old, new)?;
+                continue;
+            }
+        }
+        doc.insert(key, to_item(key, value)?);
+    }
+    for key in before.keys().filter(|k| !after.contains_key(*k)) {
+        doc.remove(key);
+    }
+    Ok(())
+}
+
+/// Write `text` to `path` by way of a file beside it, so the config is
+/// never seen half-written.
+fn replace(path: &Path, text: &str) -> Result<(), PhazeError> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    let temp = path.with_file_name(name);
+    std::fs::write(&temp, text)?;
+    std::fs::rename(&temp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        PhazeError::from(e)
+    })
+}
+
+/// Apply `change` to the config at `path` as it is on disk now, and write
+/// back what it changed. Returns the settings as saved.
+pub fn update(path: &Path, change: impl FnOnce(&mut Settings)) -> Result<Settings, PhazeError> {
+    let _queued = WRITE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let _lock = ConfigLock::acquire(path)?;
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let (settings, written) = match text {
+        Some(text) => {
+            let mut settings = parse(path, &text)?;
+            let before = to_table(&settings)?;
+            change(&mut settings);
+            let after = to_table(&settings)?;
+            if after == before {
+                return Ok(settings);
+            }
+            let mut doc = text
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| PhazeError::Config(format!("{}: {e}", path.display())))?;
+            patch(doc.as_table_mut(), &before, &after)?;
+            (settings, doc.to_string())
+        }
+        None => {
+            let mut settings = Settings::default();
+            change(&mut settings);
+            let text =
+                toml::to_string_pretty(&settings).map_err(|e| PhazeError::Config(e.to_string()))?;
+            (settings, text)
+        }
+    };
+    replace(path, &written)?;
+    if path == Settings::config_path() {
+        *LAST_SEEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(written);
+    }
+    notify(&settings, false);
+    Ok(settings)
+}
+
+fn notify(settings: &Settings, external: bool) {
+    SUBSCRIBERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|tx| {
+            tx.send(ConfigChange {
+                settings: settings.clone(),
+                external,
+            })
+            .is_ok()
+        });
+}
+
+/// Hear about every change to the config: each write made through
+/// [`update`] in this process, and, checked every couple of seconds, edits
+/// by other processes or by hand.
+pub fn subscribe() -> Receiver<ConfigChange> {
+    let (tx, rx) = mpsc::channel();
+    SUBSCRIBERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(tx);
+    POLLER.call_once(|| {
+        let path = Settings::config_path();
+        {
+            let mut last = LAST_SEEN.lock().unwrap_or_else(|e| e.into_inner());
+            if last.is_none() {
+                *last = std::fs::read_to_string(&path).ok();
+            }
+        }
+        std::thread::spawn(move || poll(&path));
+    });
+    rx
+}
+
+fn poll(path: &Path) {
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let Ok(text) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        {
+            let mut last = LAST_SEEN.lock().unwrap_or_else(|e| e.into_inner());
+            if last.as_deref() == Some(text.as_str()) {
+                continue;
+            }
+            *last = Some(text.clone());
+        }
+        // A hand edit that doesn't parse yet is left for the next one.
+        if let Ok(settings) = parse(path, &text) {
+            notify(&settings, true);
+        }
+    }
+}
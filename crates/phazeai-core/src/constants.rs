@@ -26,6 +26,20 @@ pub mod models {
     pub const DEFAULT_TOGETHER_MODEL: &str = "deepseek-r1-distill-llama-70b";
     pub const DEFAULT_OPENROUTER_MODEL: &str = "anthropic/claude-sonnet-4-5";
     pub const DEFAULT_LMSTUDIO_MODEL: &str = "local-model";
+    /// llama-server serves whatever model it was started with and ignores the name
+    pub const DEFAULT_LLAMACPP_MODEL: &str = "local-model";
+    /// vLLM needs the served model's name; discovery fills it in
+    pub const DEFAULT_VLLM_MODEL: &str = "Qwen/Qwen2.5-Coder-7B-Instruct";
+    /// xAI's flagship Grok model
+    pub const DEFAULT_XAI_MODEL: &str = "grok-4";
+    /// Mistral La Plateforme's largest general model
+    pub const DEFAULT_MISTRAL_MODEL: &str = "mistral-large-latest";
+    /// Hugging Face Inference Providers model; a TGI endpoint ignores the name
+    pub const DEFAULT_HUGGINGFACE_MODEL: &str = "Qwen/Qwen2.5-Coder-32B-Instruct";
+    /// Bedrock's cross-region inference profile for Claude Sonnet 4.5 in the US
+    pub const DEFAULT_BEDROCK_MODEL: &str = "us.anthropic.claude-sonnet-4-5-20250929-v1:0";
+    /// Gemini on Vertex AI, by its OpenAI-compatible endpoint's `publisher/model` ID
+    pub const DEFAULT_VERTEX_MODEL: &str = "google/gemini-2.5-flash";
     /// Default Gemini model — 2.5 Flash: fast, cheap, 1M context, optional thinking
     pub const DEFAULT_GEMINI_MODEL: &str = "gemini-2.5-flash";
     pub const GEMINI_PRO_MODEL: &str = "gemini-2.5-pro";
@@ -35,6 +49,9 @@ pub mod models {
     pub const GEMINI_15_PRO_MODEL: &str = "gemini-1.5-pro";
     pub const GEMINI_15_FLASH_MODEL: &str = "gemini-1.5-flash";
     pub const GEMINI_EMBEDDING_MODEL: &str = "text-embedding-004";
+    /// Default PhazeAI Cloud hosted model (Cloud tier, no API key)
+    pub const DEFAULT_PHAZECLOUD_MODEL: &str = "phaze-beast-70b";
+    pub const PHAZECLOUD_FAST_MODEL: &str = "phaze-fast";
 }
 
 // ─── API Endpoints ────────────────────────────────────────────────────────────
@@ -45,16 +62,54 @@ pub mod endpoints {
     pub const GROQ_BASE_URL: &str = "https://api.groq.com/openai";
     pub const TOGETHER_BASE_URL: &str = "https://api.together.xyz";
     pub const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api";
+    /// xAI API (OpenAI-compatible, plus `/v1/language-models`)
+    pub const XAI_BASE_URL: &str = "https://api.x.ai";
+    /// Mistral La Plateforme (OpenAI-compatible)
+    pub const MISTRAL_BASE_URL: &str = "https://api.mistral.ai";
     pub const OLLAMA_BASE_URL: &str = "http://localhost:11434";
     pub const LMSTUDIO_BASE_URL: &str = "http://localhost:1234";
     pub const LMSTUDIO_PORT: u16 = 1234;
+    /// llama.cpp `llama-server` (OpenAI-compatible, plus `/infill` and `/health`)
+    pub const LLAMACPP_BASE_URL: &str = "http://localhost:8080";
+    pub const LLAMACPP_PORT: u16 = 8080;
+    /// vLLM's OpenAI-compatible server (`vllm serve`)
+    pub const VLLM_BASE_URL: &str = "http://localhost:8000";
+    pub const VLLM_PORT: u16 = 8000;
+    /// Hugging Face Inference Providers router (OpenAI-compatible); a
+    /// dedicated Inference Endpoint or TGI server replaces it as base_url
+    pub const HUGGINGFACE_BASE_URL: &str = "https://router.huggingface.co";
+    /// Hugging Face Hub API (account and model listing)
+    pub const HUGGINGFACE_HUB_URL: &str = "https://huggingface.co";
+    /// Hugging Face Inference Endpoints management API
+    pub const HUGGINGFACE_ENDPOINTS_URL: &str = "https://api.endpoints.huggingface.cloud";
+    /// Region for Bedrock when neither the environment nor the AWS profile sets one
+    pub const AWS_DEFAULT_REGION: &str = "us-east-1";
+    /// EC2 instance metadata service (instance role credentials)
+    pub const AWS_IMDS_URL: &str = "http://169.254.169.254";
+    /// Vertex AI location when neither settings nor GOOGLE_CLOUD_LOCATION set one
+    pub const VERTEX_DEFAULT_LOCATION: &str = "us-central1";
+    /// Google OAuth token endpoint (service account and user credentials)
+    pub const GOOGLE_OAUTH_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+    /// Google Cloud metadata server (credentials on GCE, Cloud Run, GKE)
+    pub const GCE_METADATA_URL: &str = "http://metadata.google.internal";
     /// Google Gemini OpenAI-compatible endpoint (works with existing OpenAIClient)
     pub const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta/openai/";
     /// Gemini native REST endpoint (for context caching, thinking mode, grounding)
     pub const GEMINI_NATIVE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
     /// Pi Ollama LAN endpoint (configured via setup script)
     pub const PI_OLLAMA_LAN_URL: &str = "http://192.168.1.155:8080";
+    /// PhazeAI Cloud hosted-model proxy (OpenAI-compatible)
+    pub const PHAZECLOUD_BASE_URL: &str = "https://api.phazeai.com";
+    /// Release feed the self-updater reads
+    pub const RELEASE_FEED_URL: &str = "https://api.phazeai.com/v1/releases";
     pub const SEARCH_ENGINE_URL: &str = "https://html.duckduckgo.com/html/?q={}";
+    /// OSV vulnerability database (RustSec and GitHub advisories).
+    pub const OSV_API_URL: &str = "https://api.osv.dev";
+    /// Registries and source hosts the dependency update check reads.
+    pub const CRATES_IO_API_URL: &str = "https://crates.io";
+    pub const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org";
+    pub const GITHUB_API_URL: &str = "https://api.github.com";
+    pub const GITHUB_RAW_URL: &str = "https://raw.githubusercontent.com";
 }
 
 // ─── Default Settings ─────────────────────────────────────────────────────────
@@ -62,11 +117,18 @@ pub mod endpoints {
 pub mod defaults {
     pub const THEME: &str = "Midnight Blue";
     pub const FONT_SIZE: f32 = 14.0;
+    pub const FONT_FAMILY: &str = "JetBrains Mono";
+    pub const LINE_HEIGHT: f32 = 1.5;
+    pub const TERMINAL_FONT_SIZE: f32 = 13.0;
+    pub const CHAT_FONT_SIZE: f32 = 13.0;
     pub const TAB_SIZE: u32 = 4;
     pub const MAX_TOKENS: u32 = 8192;
     pub const CONTEXT_WINDOW: u32 = 8192;
     pub const PYTHON_PATH: &str = "python3";
     pub const DEFAULT_MODEL: &str = super::models::PHAZE_BEAST;
+    pub const TURN_TIMEOUT_SECS: u64 = 30 * 60;
+    pub const TOOL_TIMEOUT_SECS: u64 = 10 * 60;
+    pub const DOCS_MAX_CHUNKS: usize = 4;
 }
 
 // ─── Modelfile Hyperparameters ────────────────────────────────────────────────
@@ -140,6 +202,8 @@ pub mod paths {
     pub const CONFIG_FILE: &str = "config.toml";
     pub const IDE_STATE_FILE: &str = "ide_state.json";
     pub const CONVERSATIONS_DIR: &str = "conversations";
+    /// Custom Ollama Modelfiles, under the config directory.
+    pub const MODELS_DIR: &str = "models";
     pub const INSTRUCTION_FILES: &[&str] = &[
         "CLAUDE.md",
         ".phazeai/instructions.md",
@@ -201,6 +265,7 @@ pub mod ui {
     pub const Z_INLINE_EDIT: i32 = 400;
     pub const Z_RENAME: i32 = 420;
     pub const Z_TOAST: i32 = 450;
+    pub const Z_NOTIFICATIONS: i32 = 455;
     pub const Z_WS_SYMBOLS: i32 = 460;
     pub const Z_BRANCH_PICKER: i32 = 470;
     pub const Z_PEEK_DEF: i32 = 485;
@@ -0,0 +1,143 @@
+//! Pre-flight check of a request against the model's context window.
+//!
+//! Callers list what goes into a request — system prompt, history,
+//! attachments, the prompt itself — in a [`ContextBudget`];
+//! [`ContextBudget::check`] reports an overflow with the biggest contributors
+//! so the user can drop an attachment, summarize history or pick a model
+//! with a larger window ([`larger_models`]) before anything is sent.
+
+use crate::llm::{ModelInfo, ProviderId, ProviderRegistry};
+
+/// Rough token count: about 3 characters per token, as
+/// [`ConversationHistory::estimate_tokens`](super::ConversationHistory::estimate_tokens)
+/// assumes.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(3)
+}
+
+/// What a part of the request is, and so what can be done about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextSource {
+    SystemPrompt,
+    /// Earlier messages; can be summarized.
+    History,
+    /// A file or command output sent along; can be dropped. Holds the
+    /// caller's key for it, e.g. the mentioned path.
+    Attachment(String),
+    Prompt,
+}
+
+/// One part of a request and its estimated size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextPart {
+    pub source: ContextSource,
+    pub label: String,
+    pub tokens: usize,
+}
+
+/// Everything a request will send, part by part.
+#[derive(Debug, Clone, Default)]
+pub struct ContextBudget {
+    pub parts: Vec<ContextPart>,
+}
+
+impl ContextBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, source: ContextSource, label: impl Into<String>, text: &str) {
+        self.parts.push(ContextPart {
+            source,
+            label: label.into(),
+            tokens: estimate_tokens(text),
+        });
+    }
+
+    pub fn total(&self) -> usize {
+        self.parts.iter().map(|p| p.tokens).sum()
+    }
+
+    /// Check the request fits in `context_window` with room for a reply of
+    /// up to `max_output` tokens (capped at a quarter of the window, so a
+    /// large output setting doesn't rule out small local models).
+    pub fn check(&self, context_window: usize, max_output: usize) -> Result<(), ContextOverflow> {
+        let limit = context_window.saturating_sub(max_output.min(context_window / 4));
+        let estimated = self.total();
+        if estimated <= limit {
+            return Ok(());
+        }
+        let mut contributors = self.parts.clone();
+        contributors.sort_by_key(|p| std::cmp::Reverse(p.tokens));
+        Err(ContextOverflow {
+            estimated,
+            limit,
+            context_window,
+            contributors,
+        })
+    }
+}
+
+/// A request that won't fit the model's context window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextOverflow {
+    /// Estimated prompt tokens.
+    pub estimated: usize,
+    /// Prompt tokens that fit once the reply is reserved.
+    pub limit: usize,
+    pub context_window: usize,
+    /// Every part of the request, biggest first.
+    pub contributors: Vec<ContextPart>,
+}
+
+impl ContextOverflow {
+    /// Tokens to cut for the request to fit.
+    pub fn excess(&self) -> usize {
+        self.estimated.saturating_sub(self.limit)
+    }
+
+    /// One line for a status bar or error.
+    pub fn summary(&self) -> String {
+        format!(
+            "Request is ~{} tokens but the model's {}-token window leaves room for ~{}",
+            self.estimated, self.context_window, self.limit
+        )
+    }
+}
+
+/// Context window of `model` from `provider`, when known: listed models
+/// use their catalog size, local ones an estimate from the model name.
+pub fn context_window(provider: &ProviderId, model: &str) -> Option<usize> {
+    if let Some(info) = ProviderRegistry::known_models(provider)
+        .into_iter()
+        .find(|m| m.id == model)
+    {
+        return Some(info.context_window);
+    }
+    provider
+        .is_local()
+        .then(|| crate::llm::discovery::estimate_context_window(model))
+}
+
+/// Models from available providers whose window fits `tokens`, smallest
+/// (and then cheapest) first.
+pub fn larger_models(registry: &ProviderRegistry, tokens: usize) -> Vec<(ProviderId, ModelInfo)> {
+    let mut models: Vec<(ProviderId, ModelInfo)> = registry
+        .available_providers()
+        .into_iter()
+        .flat_map(|config| {
+            ProviderRegistry::known_models(&config.id)
+                .into_iter()
+                .map(|m| (config.id.clone(), m))
+        })
+        .filter(|(_, m)| m.context_window * 3 / 4 >= tokens)
+        .collect();
+    models.sort_by(|(_, a), (_, b)| {
+        a.context_window.cmp(&b.context_window).then(
+            a.input_cost_per_m
+                .partial_cmp(&b.input_cost_per_m)
+                .unwrap_or(std::cmp::Ordering::Equal),
+        )
+    });
+    models
+}
@@ -1,9 +1,12 @@
+use super::docs::{self, DocsIndex};
+
 /// Builds a context string from system prompt, context files, and user query.
 pub struct ContextBuilder {
     system_prompt: String,
     context_files: Vec<(String, String)>,
     user_query: String,
     repo_map: Option<String>,
+    docs: Option<String>,
 }
 
 impl ContextBuilder {
@@ -13,6 +16,7 @@ impl ContextBuilder {
             context_files: Vec::new(),
             user_query: String::new(),
             repo_map: None,
+            docs: None,
         }
     }
 
@@ -39,6 +43,13 @@ impl ContextBuilder {
         self
     }
 
+    /// Add the documentation passages most relevant to the user query, set
+    /// with [`with_user_query`](Self::with_user_query) beforehand.
+    pub fn with_docs(mut self, index: &DocsIndex) -> Self {
+        self.docs = docs::prompt_section(&index.retrieve(&self.user_query));
+        self
+    }
+
     pub fn build(self) -> String {
         let mut context = String::new();
 
@@ -52,6 +63,11 @@ impl ContextBuilder {
             context.push_str("\n\n");
         }
 
+        if let Some(ref docs) = self.docs {
+            context.push_str(docs);
+            context.push('\n');
+        }
+
         if !self.context_files.is_empty() {
             context.push_str("## Context Files:\n\n");
             for (path, content) in self.context_files {
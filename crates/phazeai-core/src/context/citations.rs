@@ -0,0 +1,183 @@
+//! Citations: the file ranges an answer is grounded in.
+//!
+//! While it works the agent collects every range its tools retrieved — what
+//! `read_file` returned, snippets from search tools that report line ranges,
+//! and the documentation passages the reply cites — and sends them with
+//! [`AgentEvent::Citations`](crate::AgentEvent::Citations) once the reply is
+//! done. The IDE shows them as chips under the reply that open the range;
+//! saved conversations keep them with the message.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::docs::DocChunk;
+
+/// At most this many ranges are kept per reply.
+pub const MAX_CITATIONS: usize = 12;
+
+/// Ranges of the same file at most this many lines apart are merged.
+const MERGE_GAP: u32 = 3;
+
+/// A range of lines in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    /// As the tool reported it: relative to the workspace, or absolute.
+    pub path: String,
+    /// 1-based, inclusive.
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+impl Citation {
+    pub fn new(path: impl Into<String>, start_line: u32, end_line: u32) -> Self {
+        let start_line = start_line.max(1);
+        Self {
+            path: path.into(),
+            start_line,
+            end_line: end_line.max(start_line),
+        }
+    }
+
+    /// `path:120-160`, or `path:120` for a single line.
+    pub fn location(&self) -> String {
+        if self.start_line == self.end_line {
+            format!("{}:{}", self.path, self.start_line)
+        } else {
+            format!("{}:{}-{}", self.path, self.start_line, self.end_line)
+        }
+    }
+
+    /// The short form shown on a chip: the file name and range, `file.rs:120-160`.
+    pub fn label(&self) -> String {
+        let name = Path::new(&self.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone());
+        Citation {
+            path: name,
+            ..self.clone()
+        }
+        .location()
+    }
+
+    /// The file, with relative paths taken from `root`.
+    pub fn resolve(&self, root: &Path) -> std::path::PathBuf {
+        match self
+            .path
+            .strip_prefix("~/")
+            .and_then(|rest| dirs::home_dir().map(|h| h.join(rest)))
+        {
+            Some(home) => home,
+            None => root.join(&self.path),
+        }
+    }
+}
+
+/// The ranges a successful tool call retrieved. `result` is the tool's
+/// output as sent to the model.
+pub fn from_tool_result(tool: &str, params: &Value, result: &str) -> Vec<Citation> {
+    let Ok(result) = serde_json::from_str::<Value>(result) else {
+        return Vec::new();
+    };
+    if tool == "read_file" {
+        let path = result.get("path").and_then(Value::as_str);
+        let shown = result
+            .get("lines_shown")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        let start = params
+            .get("offset")
+            .and_then(Value::as_u64)
+            .unwrap_or(1)
+            .max(1);
+        return match path {
+            Some(path) if shown > 0 => {
+                vec![Citation::new(
+                    path,
+                    start as u32,
+                    (start + shown - 1) as u32,
+                )]
+            }
+            _ => Vec::new(),
+        };
+    }
+    let mut citations = Vec::new();
+    collect_ranges(&result, &mut citations);
+    citations
+}
+
+/// Search results anywhere in `value` that name a file and a line range.
+fn collect_ranges(value: &Value, out: &mut Vec<Citation>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_ranges(item, out)),
+        Value::Object(map) => {
+            let path = map
+                .get("file")
+                .or_else(|| map.get("path"))
+                .and_then(Value::as_str);
+            let line = |key: &str| map.get(key).and_then(Value::as_u64);
+            if let (Some(path), Some(start), Some(end)) =
+                (path, line("start_line"), line("end_line"))
+            {
+                out.push(Citation::new(path, start as u32, end as u32));
+            } else {
+                map.values().for_each(|v| collect_ranges(v, out));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The range of a documentation passage; PDFs have no lines to open.
+pub fn from_doc_chunk(chunk: &DocChunk) -> Option<Citation> {
+    if chunk.page.is_some() {
+        return None;
+    }
+    let lines = chunk.text.lines().count().max(1) as u32;
+    Some(Citation::new(
+        chunk.path.clone(),
+        chunk.line,
+        chunk.line + lines - 1,
+    ))
+}
+
+/// Merge overlapping and nearby ranges of the same file. Files keep the
+/// order they were first retrieved in, their ranges go top to bottom, and at
+/// most [`MAX_CITATIONS`] ranges are kept.
+pub fn merge(citations: Vec<Citation>) -> Vec<Citation> {
+    let mut files: Vec<(String, Vec<Citation>)> = Vec::new();
+    for citation in citations {
+        match files.iter_mut().find(|(path, _)| *path == citation.path) {
+            Some((_, ranges)) => ranges.push(citation),
+            None => files.push((citation.path.clone(), vec![citation])),
+        }
+    }
+    let mut merged = Vec::new();
+    for (_, mut ranges) in files {
+        ranges.sort_by_key(|c| c.start_line);
+        let mut ranges = ranges.into_iter();
+        let Some(mut current) = ranges.next() else {
+            continue;
+        };
+        for next in ranges {
+            if next.start_line <= current.end_line.saturating_add(MERGE_GAP) {
+                current.end_line = current.end_line.max(next.end_line);
+            } else {
+                merged.push(std::mem::replace(&mut current, next));
+            }
+        }
+        merged.push(current);
+    }
+    merged.truncate(MAX_CITATIONS);
+    merged
+}
+
+/// The ranges as a Markdown list, for exports.
+pub fn markdown_list(citations: &[Citation]) -> String {
+    citations
+        .iter()
+        .map(|c| format!("- `{}`\n", c.location()))
+        .collect()
+}
@@ -0,0 +1,569 @@
+//! Retrieval over project documentation: the folders and files listed as
+//! `[docs] sources` — docs/, wiki exports, ADRs, PDFs.
+//!
+//! Sources are cut into passages at headings (PDFs page by page) and indexed
+//! with SQLite FTS5 in `.phazeai/docs.db`; [`DocsIndex::sync`] reindexes files
+//! whose size or modification time changed. Before each request the agent
+//! adds the passages most relevant to it to the system prompt as `[D1]`,
+//! `[D2]`, … and the reply cites them by those labels; [`sources_footer`]
+//! lists the ones it cited under the reply.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::UNIX_EPOCH;
+
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::config::DocsSettings;
+use crate::error::PhazeError;
+
+/// The index, under the workspace root.
+pub const DOCS_DB: &str = ".phazeai/docs.db";
+
+/// Bumped whenever the tables or the chunking change; older indexes are rebuilt.
+const SCHEMA_VERSION: i64 = 1;
+
+/// Files picked up inside a source folder. Files listed as sources
+/// themselves are read whatever their extension.
+pub const DOC_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "mdx", "txt", "rst", "adoc", "asciidoc", "org", "pdf",
+];
+
+/// Files larger than this are skipped.
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// A passage this long is cut at its next paragraph break.
+const CHUNK_MAX_CHARS: usize = 1_500;
+
+/// Longest passage text put in the prompt.
+const PROMPT_CHUNK_CHARS: usize = 2_000;
+
+/// At most this many words of a request are looked up.
+const MAX_QUERY_TERMS: usize = 32;
+
+/// Words that say nothing about which passage is wanted.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "and", "any", "are", "because", "but", "can", "could", "did", "does",
+    "doing", "for", "from", "has", "have", "here", "how", "into", "its", "just", "like", "make",
+    "more", "need", "not", "now", "our", "please", "should", "some", "than", "that", "the",
+    "their", "them", "then", "there", "these", "this", "those", "use", "using", "want", "was",
+    "were", "what", "when", "where", "which", "while", "who", "why", "will", "with", "would",
+    "you", "your",
+];
+
+/// A citation: `[D1]`, or several labels in one pair of brackets, `[D1, D3]`.
+static CITATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(D\d+(?:\s*,\s*D\d+)*)\]").expect("valid regex"));
+
+/// One indexed passage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocChunk {
+    /// The file, relative to the workspace when inside it.
+    pub path: String,
+    /// Headings the passage sits under, outermost first, joined with ` › `.
+    pub heading: String,
+    /// 1-based line the passage starts at.
+    pub line: u32,
+    /// For PDFs, the 1-based page the passage is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    pub text: String,
+}
+
+impl DocChunk {
+    /// Where the passage is: `docs/auth.md:42 › Login › Tokens`, or
+    /// `specs/api.pdf p. 3` for a PDF.
+    pub fn location(&self) -> String {
+        let mut location = match self.page {
+            Some(page) => format!("{} p. {page}", self.path),
+            None => format!("{}:{}", self.path, self.line),
+        };
+        if !self.heading.is_empty() {
+            location.push_str(" › ");
+            location.push_str(&self.heading);
+        }
+        location
+    }
+}
+
+/// The citation label of the `i`th retrieved passage: `D1`, `D2`, …
+pub fn label(i: usize) -> String {
+    format!("D{}", i + 1)
+}
+
+/// The heading `line` opens, as (level, title). Markdown uses `#`, AsciiDoc
+/// `=` and Org `*`.
+fn heading(line: &str, marker: char) -> Option<(usize, String)> {
+    let level = line.chars().take_while(|&c| c == marker).count();
+    let title = line[level..]
+        .strip_prefix(' ')?
+        .trim()
+        .trim_end_matches(marker);
+    ((1..=6).contains(&level) && !title.trim().is_empty())
+        .then(|| (level, title.trim().to_string()))
+}
+
+/// Cut a text document into passages: one per section, with long sections
+/// cut again at paragraph breaks. `marker` is the heading character, or
+/// `None` for text without headings.
+pub fn chunk_text(path: &str, text: &str, marker: Option<char>) -> Vec<DocChunk> {
+    let mut chunks = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut body = String::new();
+    let mut start: Option<u32> = None;
+    let mut in_fence = false;
+
+    let flush = |chunks: &mut Vec<DocChunk>,
+                 body: &mut String,
+                 start: &mut Option<u32>,
+                 headings: &[(usize, String)]| {
+        if !body.trim().is_empty() {
+            chunks.push(DocChunk {
+                path: path.to_string(),
+                heading: headings
+                    .iter()
+                    .map(|(_, title)| title.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" › "),
+                line: start.unwrap_or(1),
+                page: None,
+                text: body.trim_end().to_string(),
+            });
+        }
+        body.clear();
+        *start = None;
+    };
+
+    for (i, line) in text.lines().enumerate() {
+        let number = i as u32 + 1;
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if !in_fence {
+            if let Some((level, title)) = marker.and_then(|m| heading(line, m)) {
+                flush(&mut chunks, &mut body, &mut start, &headings);
+                headings.retain(|(l, _)| *l < level);
+                headings.push((level, title));
+                start = Some(number);
+                continue;
+            }
+            if line.trim().is_empty() && body.len() >= CHUNK_MAX_CHARS {
+                flush(&mut chunks, &mut body, &mut start, &headings);
+                continue;
+            }
+        }
+        if body.is_empty() && line.trim().is_empty() {
+            continue;
+        }
+        start.get_or_insert(number);
+        body.push_str(line);
+        body.push('\n');
+    }
+    flush(&mut chunks, &mut body, &mut start, &headings);
+    chunks
+}
+
+/// Cut a PDF, given as the text of each page, into passages.
+pub fn chunk_pages(path: &str, pages: &[String]) -> Vec<DocChunk> {
+    pages
+        .iter()
+        .enumerate()
+        .flat_map(|(i, text)| {
+            chunk_text(path, text, None)
+                .into_iter()
+                .map(move |chunk| DocChunk {
+                    page: Some(i as u32 + 1),
+                    ..chunk
+                })
+        })
+        .collect()
+}
+
+/// Passages of the file at `file`, shown as `path`.
+fn read_chunks(file: &Path, path: &str) -> Result<Vec<DocChunk>, String> {
+    let ext = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if ext == "pdf" {
+        // pdf-extract panics on some malformed files.
+        let file = file.to_path_buf();
+        let pages = std::panic::catch_unwind(move || pdf_extract::extract_text_by_pages(&file))
+            .map_err(|_| "could not read the PDF".to_string())?
+            .map_err(|e| format!("could not read the PDF: {e}"))?;
+        return Ok(chunk_pages(path, &pages));
+    }
+    let bytes = std::fs::read(file).map_err(|e| e.to_string())?;
+    let text = String::from_utf8(bytes).map_err(|_| "not UTF-8 text".to_string())?;
+    let marker = match ext.as_str() {
+        "adoc" | "asciidoc" => Some('='),
+        "org" => Some('*'),
+        "rst" | "txt" => None,
+        _ => Some('#'),
+    };
+    Ok(chunk_text(path, &text, marker))
+}
+
+/// `source` as a path: relative to `root`, absolute, or under `~`.
+fn resolve_source(root: &Path, source: &str) -> PathBuf {
+    if let Some(rest) = source.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    root.join(source)
+}
+
+/// How a file is shown: relative to the workspace when inside it, under `~`
+/// when in the home directory, absolute otherwise.
+fn display_path(root: &Path, file: &Path) -> String {
+    if let Ok(rel) = file.strip_prefix(root) {
+        return rel.to_string_lossy().replace('\\', "/");
+    }
+    if let Some(rel) =
+        dirs::home_dir().and_then(|home| file.strip_prefix(home).ok().map(Path::to_path_buf))
+    {
+        return format!("~/{}", rel.to_string_lossy().replace('\\', "/"));
+    }
+    file.to_string_lossy().to_string()
+}
+
+/// Every document under `sources`, as (file, shown path), in order.
+pub fn source_files(root: &Path, sources: &[String]) -> Vec<(PathBuf, String)> {
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for source in sources {
+        let base = resolve_source(root, source);
+        let mut found = Vec::new();
+        if base.is_file() {
+            found.push(base);
+        } else if base.is_dir() {
+            for entry in ignore::WalkBuilder::new(&base).build().flatten() {
+                let path = entry.path();
+                let is_doc = path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| DOC_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+                if is_doc && path.is_file() {
+                    found.push(path.to_path_buf());
+                }
+            }
+            found.sort();
+        }
+        for file in found {
+            if seen.insert(file.clone()) {
+                let shown = display_path(root, &file);
+                files.push((file, shown));
+            }
+        }
+    }
+    files
+}
+
+/// Turn a request into an FTS5 query matching passages with any of its
+/// telling words; ranking puts those with the most, and rarest, first.
+pub fn retrieval_query(prompt: &str) -> Option<String> {
+    let mut seen = HashSet::new();
+    let terms: Vec<String> = prompt
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(str::to_lowercase)
+        .filter(|w| w.chars().count() >= 3 && !STOPWORDS.contains(&w.as_str()))
+        .filter(|w| seen.insert(w.clone()))
+        .take(MAX_QUERY_TERMS)
+        .map(|w| format!("\"{w}\""))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+/// The system prompt section for `chunks`, labelled for citing, or `None`
+/// when there are none.
+pub fn prompt_section(chunks: &[DocChunk]) -> Option<String> {
+    if chunks.is_empty() {
+        return None;
+    }
+    let mut section = String::from(
+        "## Project Documentation\n\
+         Passages from the project's documentation that may bear on this request. When your \
+         reply relies on one, cite its label in brackets after the statement, e.g. [D1]. Cite \
+         only passages you used.\n",
+    );
+    for (i, chunk) in chunks.iter().enumerate() {
+        let text: String = chunk.text.chars().take(PROMPT_CHUNK_CHARS).collect();
+        section.push_str(&format!(
+            "\n[{}] {}\n{}\n",
+            label(i),
+            chunk.location(),
+            text
+        ));
+    }
+    Some(section)
+}
+
+/// The passages `reply` cites, with their labels, in label order. Both
+/// `[D1][D3]` and `[D1, D3]` count.
+pub fn cited<'a>(reply: &str, chunks: &'a [DocChunk]) -> Vec<(String, &'a DocChunk)> {
+    let labels: HashSet<&str> = CITATION
+        .captures_iter(reply)
+        .filter_map(|cap| cap.get(1))
+        .flat_map(|m| m.as_str().split(',').map(str::trim))
+        .collect();
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| (label(i), chunk))
+        .filter(|(l, _)| labels.contains(l.as_str()))
+        .collect()
+}
+
+/// A list of the passages `reply` cites, to show under it, or `None` when
+/// it cites none.
+pub fn sources_footer(reply: &str, chunks: &[DocChunk]) -> Option<String> {
+    let cited = cited(reply, chunks);
+    if cited.is_empty() {
+        return None;
+    }
+    let mut footer = String::from("\n\n---\n**Sources**\n");
+    for (label, chunk) in cited {
+        footer.push_str(&format!("- [{label}] {}\n", chunk.location()));
+    }
+    Some(footer)
+}
+
+/// A file in the index, for listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedDoc {
+    pub path: String,
+    pub chunks: usize,
+    /// Why the file could not be indexed.
+    pub error: Option<String>,
+}
+
+/// What [`DocsIndex::sync`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocsSync {
+    /// Files (re)indexed because they were new or had changed.
+    pub indexed: usize,
+    /// Files dropped because they, or their source, are gone.
+    pub removed: usize,
+    /// Files that could not be read, with the reason.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Handle to a workspace's documentation index.
+pub struct DocsIndex {
+    conn: Mutex<Connection>,
+    root: PathBuf,
+    settings: DocsSettings,
+}
+
+impl DocsIndex {
+    pub fn open(root: &Path, settings: &DocsSettings) -> Result<Self, PhazeError> {
+        let db = root.join(DOCS_DB);
+        if let Some(dir) = db.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(&db)?;
+        // The index is a cache of the sources: on a schema change drop it
+        // and let `sync` rebuild it.
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            conn.execute_batch(
+                "DROP TABLE IF EXISTS doc_files;
+                 DROP TABLE IF EXISTS doc_chunks;",
+            )?;
+        }
+        conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS doc_files (
+                 file     TEXT PRIMARY KEY,
+                 path     TEXT NOT NULL,
+                 modified INTEGER NOT NULL,
+                 size     INTEGER NOT NULL,
+                 chunks   INTEGER NOT NULL,
+                 error    TEXT
+             );
+             CREATE VIRTUAL TABLE IF NOT EXISTS doc_chunks USING fts5(
+                 file UNINDEXED,
+                 path UNINDEXED,
+                 heading,
+                 text,
+                 line UNINDEXED,
+                 page UNINDEXED,
+                 tokenize = 'porter unicode61'
+             );
+             PRAGMA user_version = {SCHEMA_VERSION};"
+        ))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            root: root.to_path_buf(),
+            settings: settings.clone(),
+        })
+    }
+
+    /// The up-to-date index of `root`'s documentation, or `None` when no
+    /// sources are set or retrieval is off.
+    pub fn for_workspace(root: &Path, settings: &DocsSettings) -> Option<Self> {
+        if settings.sources.is_empty() || settings.max_chunks == 0 {
+            return None;
+        }
+        let index = match Self::open(root, settings) {
+            Ok(index) => index,
+            Err(e) => {
+                tracing::warn!("Failed to open the docs index: {e}");
+                return None;
+            }
+        };
+        match index.sync() {
+            Ok(sync) => {
+                for (path, reason) in &sync.failed {
+                    tracing::warn!("Skipped {path} in the docs index: {reason}");
+                }
+            }
+            Err(e) => tracing::warn!("Failed to update the docs index: {e}"),
+        }
+        Some(index)
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Index new and changed files under the sources, and drop files that
+    /// are gone.
+    pub fn sync(&self) -> Result<DocsSync, PhazeError> {
+        let mut result = DocsSync::default();
+        let files = source_files(&self.root, &self.settings.sources);
+        let mut conn = self.conn();
+
+        let indexed: HashMap<String, (i64, i64)> = {
+            let mut stmt = conn.prepare("SELECT file, modified, size FROM doc_files")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+            rows.collect::<Result<_, _>>()?
+        };
+
+        for (file, path) in &files {
+            let Ok(meta) = std::fs::metadata(file) else {
+                continue;
+            };
+            let modified = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_millis() as i64);
+            let size = meta.len() as i64;
+            let key = file.to_string_lossy().to_string();
+            if indexed.get(&key) == Some(&(modified, size)) {
+                continue;
+            }
+
+            let chunks = if meta.len() > MAX_FILE_BYTES {
+                Err(format!("larger than {} MB", MAX_FILE_BYTES / (1024 * 1024)))
+            } else {
+                read_chunks(file, path)
+            };
+            let error = chunks.as_ref().err().cloned();
+            let chunks = chunks.unwrap_or_default();
+
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM doc_chunks WHERE file = ?1", params![key])?;
+            {
+                let mut insert = tx.prepare(
+                    "INSERT INTO doc_chunks (file, path, heading, text, line, page)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+                for chunk in &chunks {
+                    insert.execute(params![
+                        key,
+                        chunk.path,
+                        chunk.heading,
+                        chunk.text,
+                        chunk.line,
+                        chunk.page
+                    ])?;
+                }
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO doc_files (file, path, modified, size, chunks, error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![key, path, modified, size, chunks.len() as i64, error],
+            )?;
+            tx.commit()?;
+
+            match error {
+                Some(reason) => result.failed.push((path.clone(), reason)),
+                None => result.indexed += 1,
+            }
+        }
+
+        let live: HashSet<String> = files
+            .iter()
+            .map(|(file, _)| file.to_string_lossy().to_string())
+            .collect();
+        for file in indexed.keys().filter(|f| !live.contains(*f)) {
+            conn.execute("DELETE FROM doc_chunks WHERE file = ?1", params![file])?;
+            conn.execute("DELETE FROM doc_files WHERE file = ?1", params![file])?;
+            result.removed += 1;
+        }
+        Ok(result)
+    }
+
+    /// Up to `limit` passages for `text`, most relevant first. Heading
+    /// matches weigh double.
+    pub fn search(&self, text: &str, limit: usize) -> Result<Vec<DocChunk>, PhazeError> {
+        let Some(query) = retrieval_query(text) else {
+            return Ok(Vec::new());
+        };
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT path, heading, line, page, text FROM doc_chunks
+             WHERE doc_chunks MATCH ?1
+             ORDER BY bm25(doc_chunks, 0.0, 0.0, 2.0, 1.0, 0.0, 0.0) LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![query, limit as i64], |row| {
+            Ok(DocChunk {
+                path: row.get(0)?,
+                heading: row.get(1)?,
+                line: row.get(2)?,
+                page: row.get(3)?,
+                text: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// The passages to add to the prompt for `prompt`.
+    pub fn retrieve(&self, prompt: &str) -> Vec<DocChunk> {
+        self.search(prompt, self.settings.max_chunks)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to search the docs index: {e}");
+                Vec::new()
+            })
+    }
+
+    /// Every indexed file, in path order.
+    pub fn files(&self) -> Result<Vec<IndexedDoc>, PhazeError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT path, chunks, error FROM doc_files ORDER BY path")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(IndexedDoc {
+                path: row.get(0)?,
+                chunks: row.get::<_, i64>(1)? as usize,
+                error: row.get(2)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Forget everything indexed, so the next `sync` reads every file again.
+    pub fn clear(&self) -> Result<(), PhazeError> {
+        self.conn().execute_batch(
+            "DELETE FROM doc_chunks;
+             DELETE FROM doc_files;",
+        )?;
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@ pub struct ConversationHistory {
     messages: VecDeque<Message>,
     max_messages: usize,
     system_prompt: Option<String>,
+    /// Per-turn context (project memory, team context) appended to the system prompt.
+    memory_context: Option<String>,
 }
 
 impl ConversationHistory {
@@ -13,6 +15,7 @@ impl ConversationHistory {
             messages: VecDeque::new(),
             max_messages: 100,
             system_prompt: None,
+            memory_context: None,
         }
     }
 
@@ -34,6 +37,20 @@ impl ConversationHistory {
         self.system_prompt.as_deref()
     }
 
+    /// Replace the per-turn context sent after the system prompt.
+    pub fn set_memory_context(&mut self, memory: Option<String>) {
+        self.memory_context = memory;
+    }
+
+    /// The system prompt with the memory section appended, as sent to the model.
+    fn full_system_prompt(&self) -> Option<String> {
+        match (&self.system_prompt, &self.memory_context) {
+            (Some(system), Some(memory)) => Some(format!("{system}\n\n{memory}")),
+            (Some(only), None) | (None, Some(only)) => Some(only.clone()),
+            (None, None) => None,
+        }
+    }
+
     pub fn add_user_message(&mut self, content: impl Into<String>) {
         self.messages.push_back(Message::user(content));
         self.trim_if_needed();
@@ -70,7 +87,7 @@ impl ConversationHistory {
     /// Get all messages including system prompt as a system message.
     pub fn get_messages(&self) -> Vec<Message> {
         let mut messages = Vec::new();
-        if let Some(ref system) = self.system_prompt {
+        if let Some(system) = self.full_system_prompt() {
             messages.push(Message::system(system));
         }
         messages.extend(self.messages.iter().cloned());
@@ -117,7 +134,7 @@ impl ConversationHistory {
         let mut total = 0;
 
         // System prompt tokens — use chars/3 for code-heavy content
-        if let Some(ref system) = self.system_prompt {
+        if let Some(system) = self.full_system_prompt() {
             total += system.len().div_ceil(3);
         }
 
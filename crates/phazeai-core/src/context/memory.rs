@@ -0,0 +1,227 @@
+//! Per-project long-term memory for the agent.
+//!
+//! Durable facts ("we use sqlx 0.8, never diesel") live in
+//! `.phazeai/memory.md` at the project root, one `- ` bullet per fact, so they
+//! can be reviewed, edited and committed like any other file. Other lines in
+//! the file are left alone. The agent saves facts with the `memory` tool, and
+//! the most relevant ones are added to the system prompt on every turn.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::error::PhazeError;
+
+/// Memory file, relative to the project root.
+pub const MEMORY_FILE: &str = ".phazeai/memory.md";
+/// Memories injected into the system prompt per turn.
+pub const MAX_PROMPT_MEMORIES: usize = 20;
+/// Longest fact kept, in characters.
+const MAX_MEMORY_CHARS: usize = 500;
+
+const HEADER: &str = "# Project memory\n\n\
+Facts the PhazeAI agent keeps about this project, one `- ` bullet each.\n\
+Edit or delete them freely.\n\n";
+
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "was", "our", "use",
+    "with", "this", "that", "from", "have", "has", "into", "when", "what", "how", "why", "its",
+    "they", "them", "then", "than", "there", "their", "should", "would", "could", "please",
+];
+
+/// One remembered fact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memory {
+    /// 1-based position in the file, as shown by `list`.
+    pub id: usize,
+    pub text: String,
+}
+
+/// The memory file of one project.
+#[derive(Debug, Clone)]
+pub struct MemoryStore {
+    path: PathBuf,
+}
+
+impl MemoryStore {
+    pub fn for_workspace(root: &Path) -> Self {
+        Self {
+            path: root.join(MEMORY_FILE),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All memories, oldest first. A missing file has none.
+    pub fn list(&self) -> Result<Vec<Memory>, PhazeError> {
+        Ok(parse(&self.read()?))
+    }
+
+    /// Save a fact. Returns `false` when the same fact is already stored.
+    pub fn add(&self, text: &str) -> Result<bool, PhazeError> {
+        let text = clean(text);
+        if text.is_empty() {
+            return Err(PhazeError::Other("memory text is empty".into()));
+        }
+        let content = self.read()?;
+        let key = text.to_lowercase();
+        if parse(&content).iter().any(|m| m.text.to_lowercase() == key) {
+            return Ok(false);
+        }
+        let mut content = if content.trim().is_empty() {
+            HEADER.to_string()
+        } else {
+            content
+        };
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(&format!("- {text}\n"));
+        self.write(&content)?;
+        Ok(true)
+    }
+
+    /// Delete memory `id` and return it, or `None` if there is no such memory.
+    pub fn remove(&self, id: usize) -> Result<Option<Memory>, PhazeError> {
+        let content = self.read()?;
+        let mut seen = 0;
+        let mut removed = None;
+        let mut kept = String::with_capacity(content.len());
+        for line in content.lines() {
+            if let Some(text) = bullet(line) {
+                seen += 1;
+                if seen == id {
+                    removed = Some(Memory {
+                        id,
+                        text: text.to_string(),
+                    });
+                    continue;
+                }
+            }
+            kept.push_str(line);
+            kept.push('\n');
+        }
+        if removed.is_some() {
+            self.write(&kept)?;
+        }
+        Ok(removed)
+    }
+
+    /// Up to `limit` memories, most relevant to `query` first.
+    pub fn relevant(&self, query: &str, limit: usize) -> Result<Vec<Memory>, PhazeError> {
+        Ok(rank(self.list()?, query, limit))
+    }
+
+    fn read(&self) -> Result<String, PhazeError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write(&self, content: &str) -> Result<(), PhazeError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+fn bullet(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let text = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))?
+        .trim();
+    (!text.is_empty()).then_some(text)
+}
+
+fn parse(content: &str) -> Vec<Memory> {
+    content
+        .lines()
+        .filter_map(bullet)
+        .enumerate()
+        .map(|(i, text)| Memory {
+            id: i + 1,
+            text: text.to_string(),
+        })
+        .collect()
+}
+
+/// Collapse a fact onto one line so it stays a single bullet.
+fn clean(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = text.trim_start_matches(['-', '*', ' ']);
+    text.chars().take(MAX_MEMORY_CHARS).collect()
+}
+
+fn terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(str::to_lowercase)
+        .filter(|w| w.len() > 2 && !STOP_WORDS.contains(&w.as_str()))
+        // Crude stemming so "migrations" finds "migration".
+        .map(|w| match w.strip_suffix('s') {
+            Some(stem) if stem.len() > 2 && !stem.ends_with('s') => stem.to_string(),
+            _ => w,
+        })
+        .collect()
+}
+
+/// Order `memories` by how many of `query`'s words they share, rarer words
+/// counting more; ties go to the newer memory. All memories are kept up to
+/// `limit`, so a small memory file is always injected in full.
+pub fn rank(memories: Vec<Memory>, query: &str, limit: usize) -> Vec<Memory> {
+    let query = terms(query);
+    let memory_terms: Vec<HashSet<String>> = memories.iter().map(|m| terms(&m.text)).collect();
+    let n = memories.len() as f64;
+    let score = |t: &HashSet<String>| -> f64 {
+        t.intersection(&query)
+            .map(|w| {
+                let df = memory_terms.iter().filter(|m| m.contains(w)).count() as f64;
+                (1.0 + n / df).ln()
+            })
+            .sum()
+    };
+    let mut scored: Vec<(f64, Memory)> = memory_terms.iter().map(score).zip(memories).collect();
+    scored.sort_by(|(a, ma), (b, mb)| b.total_cmp(a).then(mb.id.cmp(&ma.id)));
+    scored.into_iter().take(limit).map(|(_, m)| m).collect()
+}
+
+/// Context added to the system prompt on every turn, chosen for the request
+/// at hand. Project memory is one source; shared team context is another.
+pub trait PromptContext: Send + Sync {
+    /// The section to append for `query`, or `None` to add nothing.
+    fn section(&self, query: &str) -> Option<String>;
+}
+
+impl PromptContext for MemoryStore {
+    fn section(&self, query: &str) -> Option<String> {
+        match self.relevant(query, MAX_PROMPT_MEMORIES) {
+            Ok(memories) => prompt_section(&memories),
+            Err(e) => {
+                tracing::warn!("Failed to read project memory: {e}");
+                None
+            }
+        }
+    }
+}
+
+/// The system prompt section for `memories`, or `None` when there are none.
+pub fn prompt_section(memories: &[Memory]) -> Option<String> {
+    if memories.is_empty() {
+        return None;
+    }
+    let mut section = format!(
+        "## Project Memory\n\
+         Facts saved in earlier sessions ({MEMORY_FILE}). Follow them unless the user says \
+         otherwise. Use the `memory` tool to save new durable facts about this project or to \
+         forget ones that are outdated.\n"
+    );
+    for m in memories {
+        section.push_str(&format!("- {}\n", m.text));
+    }
+    Some(section)
+}
@@ -1,11 +1,24 @@
+pub mod budget;
 mod builder;
+pub mod citations;
+pub mod docs;
 mod history;
+pub mod memory;
 pub mod persistence;
 pub mod repo_map;
+pub mod search;
 pub mod system_prompt;
+pub mod titling;
 
+pub use budget::{ContextBudget, ContextOverflow, ContextPart, ContextSource};
 pub use builder::ContextBuilder;
+pub use citations::Citation;
+pub use docs::{DocChunk, DocsIndex, DocsSync};
 pub use history::ConversationHistory;
-pub use persistence::{ConversationMetadata, ConversationStore, SavedConversation, SavedMessage};
+pub use memory::{Memory, MemoryStore, PromptContext};
+pub use persistence::{
+    ConversationMetadata, ConversationStore, SavedConversation, SavedMessage, TitleSource,
+};
 pub use repo_map::RepoMapGenerator;
+pub use search::{SearchHit, SearchQuery};
 pub use system_prompt::{collect_git_info, ProjectType, SystemPromptBuilder};
@@ -1,3 +1,5 @@
+use super::citations::{self, Citation};
+use super::search::{SearchHit, SearchIndex, SearchQuery};
 use crate::error::PhazeError;
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
@@ -15,6 +17,27 @@ pub struct ConversationMetadata {
     pub message_count: usize,
     pub model: String,
     pub project_dir: Option<String>,
+    /// Topic tags (lowercase, dash-separated), generated or set by the user.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub title_source: TitleSource,
+    /// Tools switched off for this conversation; resuming it keeps them off.
+    #[serde(default)]
+    pub disabled_tools: Vec<String>,
+}
+
+/// Where a conversation title came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleSource {
+    /// Truncated first user message (what every save writes by default).
+    #[default]
+    FirstMessage,
+    /// Written by the titling model after the first few turns.
+    Generated,
+    /// Edited by the user; never replaced automatically.
+    User,
 }
 
 /// A complete saved conversation
@@ -32,6 +55,9 @@ pub struct SavedMessage {
     pub content: String,
     pub timestamp: String,
     pub tool_name: Option<String>,
+    /// For replies, the file ranges they are grounded in.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
 }
 
 /// Index of all conversations
@@ -75,6 +101,11 @@ impl ConversationStore {
         self.base_dir.join("index.json")
     }
 
+    /// Get path to the full-text search index
+    fn search_index_path(&self) -> PathBuf {
+        self.base_dir.join("search.db")
+    }
+
     /// Get path to a conversation file
     fn conversation_path(&self, id: &str) -> PathBuf {
         self.base_dir.join(format!("{}.json", id))
@@ -139,7 +170,49 @@ impl ConversationStore {
     }
 
     /// Save a conversation to disk
+    ///
+    /// Callers rebuild metadata from scratch on every save, so a generated or
+    /// user-edited title already on disk is kept when the incoming copy still
+    /// has the first-message default, and existing tags are kept when the
+    /// incoming copy has none.
     pub fn save(&self, conversation: &SavedConversation) -> Result<(), PhazeError> {
+        let index = self.load_index()?;
+        let carried;
+        let conversation = match index
+            .conversations
+            .iter()
+            .find(|m| m.id == conversation.metadata.id)
+        {
+            Some(existing) => {
+                let keep_title = conversation.metadata.title_source == TitleSource::FirstMessage
+                    && existing.title_source != TitleSource::FirstMessage;
+                let keep_tags = conversation.metadata.tags.is_empty() && !existing.tags.is_empty();
+                if keep_title || keep_tags {
+                    let mut c = conversation.clone();
+                    if keep_title {
+                        c.metadata.title = existing.title.clone();
+                        c.metadata.title_source = existing.title_source;
+                    }
+                    if keep_tags {
+                        c.metadata.tags = existing.tags.clone();
+                    }
+                    carried = c;
+                    &carried
+                } else {
+                    conversation
+                }
+            }
+            None => conversation,
+        };
+        self.write(conversation, index)
+    }
+
+    /// Write a conversation exactly as given and update both indexes.
+    fn write(
+        &self,
+        conversation: &SavedConversation,
+        mut index: ConversationIndex,
+    ) -> Result<(), PhazeError> {
         // Save the conversation file
         let path = self.conversation_path(&conversation.metadata.id);
         let contents = serde_json::to_string_pretty(conversation)
@@ -157,9 +230,6 @@ impl ConversationStore {
             PhazeError::Config(format!("Failed to rename conversation file: {}", e))
         })?;
 
-        // Update the index
-        let mut index = self.load_index()?;
-
         // Remove existing entry if present
         index
             .conversations
@@ -175,6 +245,17 @@ impl ConversationStore {
 
         self.save_index(&index)?;
 
+        // The JSON files are the source of truth; a stale index is repaired
+        // by the next search, so indexing failures don't fail the save.
+        if let Err(e) =
+            SearchIndex::open(&self.search_index_path()).and_then(|mut i| i.index(conversation))
+        {
+            tracing::warn!(
+                "Failed to index conversation {}: {e}",
+                conversation.metadata.id
+            );
+        }
+
         Ok(())
     }
 
@@ -222,9 +303,35 @@ impl ConversationStore {
         index.conversations.retain(|m| m.id != id);
         self.save_index(&index)?;
 
+        if let Err(e) = SearchIndex::open(&self.search_index_path()).and_then(|i| i.remove(id)) {
+            tracing::warn!("Failed to remove conversation {id} from search index: {e}");
+        }
+
         Ok(())
     }
 
+    /// Delete conversations last updated more than `max_age` ago (e.g. for a
+    /// data retention policy). Returns how many were deleted; conversations
+    /// with an unreadable timestamp are kept.
+    pub fn prune_older_than(&self, max_age: std::time::Duration) -> Result<usize, PhazeError> {
+        use chrono::{DateTime, Utc};
+        let Ok(max_age) = chrono::Duration::from_std(max_age) else {
+            return Ok(0);
+        };
+        let cutoff = Utc::now() - max_age;
+        let expired: Vec<String> = self
+            .load_index()?
+            .conversations
+            .into_iter()
+            .filter(|m| DateTime::parse_from_rfc3339(&m.updated_at).is_ok_and(|t| t < cutoff))
+            .map(|m| m.id)
+            .collect();
+        for id in &expired {
+            self.delete(id)?;
+        }
+        Ok(expired.len())
+    }
+
     /// Search conversations by title
     pub fn search(&self, query: &str) -> Result<Vec<ConversationMetadata>, PhazeError> {
         let index = self.load_index()?;
@@ -236,6 +343,49 @@ impl ConversationStore {
             .filter(|m| m.title.to_lowercase().contains(&query_lower))
             .collect())
     }
+
+    /// Set a user-chosen title; it won't be replaced by automatic titling.
+    pub fn rename(&self, id: &str, title: &str) -> Result<(), PhazeError> {
+        let mut conversation = self.load(id)?;
+        conversation.metadata.title = title.trim().to_string();
+        conversation.metadata.title_source = TitleSource::User;
+        self.write(&conversation, self.load_index()?)
+    }
+
+    /// Replace a conversation's tags. Tags are normalised and deduplicated.
+    pub fn set_tags(&self, id: &str, tags: &[String]) -> Result<(), PhazeError> {
+        let mut conversation = self.load(id)?;
+        conversation.metadata.tags = super::titling::normalize_tags(tags);
+        self.write(&conversation, self.load_index()?)
+    }
+
+    /// Resolve a full conversation ID from a unique prefix.
+    pub fn resolve_id(&self, prefix: &str) -> Result<String, PhazeError> {
+        let index = self.load_index()?;
+        let matches: Vec<&ConversationMetadata> = index
+            .conversations
+            .iter()
+            .filter(|m| m.id.starts_with(prefix))
+            .collect();
+        match matches.as_slice() {
+            [one] => Ok(one.id.clone()),
+            [] => Err(PhazeError::Config(format!(
+                "Conversation not found: {prefix}"
+            ))),
+            _ => Err(PhazeError::Config(format!(
+                "Conversation ID prefix '{prefix}' is ambiguous"
+            ))),
+        }
+    }
+
+    /// Full-text search over titles and message content, with filters.
+    /// Conversations missing from the index are indexed first.
+    pub fn search_messages(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, PhazeError> {
+        let mut search = SearchIndex::open(&self.search_index_path())?;
+        let index = self.load_index()?;
+        search.sync(&index.conversations, |id| self.load(id))?;
+        search.search(query)
+    }
 }
 
 impl Default for ConversationStore {
@@ -269,6 +419,9 @@ impl SavedConversation {
                 message_count: 0,
                 model,
                 project_dir,
+                tags: Vec::new(),
+                title_source: TitleSource::FirstMessage,
+                disabled_tools: Vec::new(),
             },
             messages: Vec::new(),
             system_prompt,
@@ -282,6 +435,32 @@ impl SavedConversation {
         self.metadata.updated_at = ConversationStore::timestamp();
     }
 
+    /// The conversation as a Markdown document: the title, then each user
+    /// and assistant message, with a reply's citations listed under it.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.metadata.title);
+        out.push_str(&format!(
+            "_{} · {}_\n",
+            self.metadata.model, self.metadata.created_at
+        ));
+        for message in &self.messages {
+            let heading = match message.role.as_str() {
+                "user" => "User",
+                "assistant" => "Assistant",
+                _ => continue,
+            };
+            out.push_str(&format!(
+                "\n## {heading}\n\n{}\n",
+                message.content.trim_end()
+            ));
+            if !message.citations.is_empty() {
+                out.push_str("\n**Citations**\n\n");
+                out.push_str(&citations::markdown_list(&message.citations));
+            }
+        }
+        out
+    }
+
     /// Generate a title from the first user message
     pub fn generate_title_from_first_message(&mut self) {
         if let Some(first_user_msg) = self.messages.iter().find(|m| m.role == "user") {
@@ -304,9 +483,16 @@ impl SavedMessage {
             content,
             timestamp: ConversationStore::timestamp(),
             tool_name,
+            citations: Vec::new(),
         }
     }
 
+    /// Attach the file ranges the message is grounded in.
+    pub fn with_citations(mut self, citations: Vec<Citation>) -> Self {
+        self.citations = citations;
+        self
+    }
+
     /// Create a user message
     pub fn user(content: String) -> Self {
         Self::new("user".to_string(), content, None)
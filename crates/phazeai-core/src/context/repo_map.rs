@@ -8,6 +8,7 @@ use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use crate::analysis::{extract_symbols_generic, symbols_to_repo_map, CodeSymbol};
+use crate::progress;
 
 /// File-level symbol summary
 #[derive(Debug, Clone)]
@@ -64,9 +65,14 @@ impl RepoMapGenerator {
 
         let mut results = Vec::new();
         let mut file_count = 0;
+        let task = progress::global()
+            .task("Building repo map")
+            .unit("files")
+            .cancellable()
+            .start();
 
         for entry in walker.flatten() {
-            if file_count >= self.max_files {
+            if file_count >= self.max_files || task.is_cancelled() {
                 break;
             }
 
@@ -100,6 +106,7 @@ impl RepoMapGenerator {
                 continue;
             }
 
+            task.advance(1);
             let content = match std::fs::read_to_string(path) {
                 Ok(c) => c,
                 Err(_) => continue,
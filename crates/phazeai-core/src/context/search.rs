@@ -0,0 +1,338 @@
+//! Full-text search over saved conversations (SQLite FTS5).
+//!
+//! The index lives next to the conversation files as `search.db` and is kept
+//! current by [`ConversationStore::save`](super::ConversationStore::save) and
+//! `delete`. Conversations saved before the index existed are picked up by
+//! [`SearchIndex::sync`], which compares `updated_at` against the index.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+use super::persistence::{ConversationMetadata, SavedConversation};
+use crate::error::PhazeError;
+
+/// Bumped whenever the tables change; older indexes are rebuilt.
+const SCHEMA_VERSION: i64 = 2;
+
+/// Default number of conversations returned by a search.
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// A search over conversation titles and message text.
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Words to look for; every word must appear. `"quoted phrases"` match exactly.
+    pub text: String,
+    /// Only conversations whose model contains this (case-insensitive).
+    pub model: Option<String>,
+    /// Only conversations started in this project directory.
+    pub workspace: Option<String>,
+    /// Only conversations updated on or after this date (`YYYY-MM-DD` or RFC 3339).
+    pub since: Option<String>,
+    /// Only conversations updated on or before this date.
+    pub until: Option<String>,
+    /// Only conversations carrying all of these tags.
+    pub tags: Vec<String>,
+    /// Maximum number of conversations; 0 uses [`DEFAULT_SEARCH_LIMIT`].
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Parse search-box input, where `#tag` words become tag filters.
+    pub fn from_input(input: &str) -> Self {
+        let mut text = Vec::new();
+        let mut tags = Vec::new();
+        for word in input.split_whitespace() {
+            match word.strip_prefix('#') {
+                Some(tag) if !tag.is_empty() => tags.push(tag.to_string()),
+                _ => text.push(word),
+            }
+        }
+        Self {
+            text: text.join(" "),
+            tags,
+            ..Self::default()
+        }
+    }
+}
+
+/// One matching conversation with its best-ranked excerpt.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub metadata: ConversationMetadata,
+    /// Role of the best-matching message (`title` when the title matched).
+    pub role: String,
+    /// Excerpt around the match with hits wrapped in `[` `]`.
+    pub snippet: String,
+}
+
+/// Turn free text into an FTS5 query: each word (or quoted phrase) becomes a
+/// quoted term so punctuation like `::` or `-` can't break the query syntax.
+pub fn fts_query(text: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('"') {
+        terms.extend(words(&rest[..start]));
+        let after = &rest[start + 1..];
+        let (phrase, tail) = after.split_once('"').unwrap_or((after, ""));
+        let phrase = phrase.trim();
+        if !phrase.is_empty() {
+            terms.push(format!("\"{}\"", phrase.replace('"', "")));
+        }
+        rest = tail;
+    }
+    terms.extend(words(rest));
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .map(|w| format!("\"{w}\""))
+        .collect()
+}
+
+/// Handle to the on-disk search index.
+pub struct SearchIndex {
+    conn: Connection,
+}
+
+impl SearchIndex {
+    pub fn open(path: &Path) -> Result<Self, PhazeError> {
+        let conn = Connection::open(path)?;
+        // The index is a cache of the JSON files: on a schema change drop it
+        // and let `sync` rebuild it.
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            conn.execute_batch(
+                "DROP TABLE IF EXISTS conversations;
+                 DROP TABLE IF EXISTS messages;",
+            )?;
+        }
+        conn.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS conversations (
+                 id            TEXT PRIMARY KEY,
+                 title         TEXT NOT NULL,
+                 created_at    TEXT NOT NULL,
+                 updated_at    TEXT NOT NULL,
+                 model         TEXT NOT NULL,
+                 project_dir   TEXT,
+                 message_count INTEGER NOT NULL,
+                 tags          TEXT NOT NULL DEFAULT '[]',
+                 title_source  TEXT NOT NULL DEFAULT 'first_message'
+             );
+             CREATE VIRTUAL TABLE IF NOT EXISTS messages USING fts5(
+                 conv_id UNINDEXED,
+                 role UNINDEXED,
+                 content,
+                 tokenize = 'porter unicode61'
+             );
+             PRAGMA user_version = {SCHEMA_VERSION};"
+        ))?;
+        Ok(Self { conn })
+    }
+
+    /// Replace everything indexed for `conversation`.
+    pub fn index(&mut self, conversation: &SavedConversation) -> Result<(), PhazeError> {
+        let meta = &conversation.metadata;
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM messages WHERE conv_id = ?1", params![meta.id])?;
+        tx.execute(
+            "INSERT OR REPLACE INTO conversations
+                 (id, title, created_at, updated_at, model, project_dir, message_count,
+                  tags, title_source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                meta.id,
+                meta.title,
+                meta.created_at,
+                meta.updated_at,
+                meta.model,
+                meta.project_dir,
+                meta.message_count as i64,
+                serde_json::to_string(&meta.tags)?,
+                serde_json::to_value(meta.title_source)?
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string()
+            ],
+        )?;
+        {
+            let mut insert =
+                tx.prepare("INSERT INTO messages (conv_id, role, content) VALUES (?1, ?2, ?3)")?;
+            // Tags ride along with the title so free text finds them too.
+            let heading = std::iter::once(meta.title.as_str())
+                .chain(meta.tags.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" ");
+            insert.execute(params![meta.id, "title", heading])?;
+            for msg in &conversation.messages {
+                if !msg.content.trim().is_empty() {
+                    insert.execute(params![meta.id, msg.role, msg.content])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), PhazeError> {
+        self.conn
+            .execute("DELETE FROM messages WHERE conv_id = ?1", params![id])?;
+        self.conn
+            .execute("DELETE FROM conversations WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Bring the index in line with `listed`: index conversations that are
+    /// missing or newer than their indexed copy (loaded via `load`), and drop
+    /// ones that no longer exist. Returns how many were (re)indexed.
+    pub fn sync(
+        &mut self,
+        listed: &[ConversationMetadata],
+        load: impl Fn(&str) -> Result<SavedConversation, PhazeError>,
+    ) -> Result<usize, PhazeError> {
+        let mut reindexed = 0;
+        for meta in listed {
+            let indexed: Option<String> = self
+                .conn
+                .query_row(
+                    "SELECT updated_at FROM conversations WHERE id = ?1",
+                    params![meta.id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if indexed.as_deref() == Some(meta.updated_at.as_str()) {
+                continue;
+            }
+            if let Ok(conversation) = load(&meta.id) {
+                self.index(&conversation)?;
+                reindexed += 1;
+            }
+        }
+
+        let live: HashSet<&str> = listed.iter().map(|m| m.id.as_str()).collect();
+        let stale: Vec<String> = {
+            let mut stmt = self.conn.prepare("SELECT id FROM conversations")?;
+            let ids = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            ids.filter_map(Result::ok)
+                .filter(|id| !live.contains(id.as_str()))
+                .collect()
+        };
+        for id in stale {
+            self.remove(&id)?;
+        }
+        Ok(reindexed)
+    }
+
+    /// Best match per conversation, most relevant first. A query with tags
+    /// but no text lists the tagged conversations, most recent first.
+    pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, PhazeError> {
+        let fts = fts_query(&query.text);
+        if fts.is_none() && query.tags.is_empty() {
+            return Ok(Vec::new());
+        }
+        let limit = if query.limit == 0 {
+            DEFAULT_SEARCH_LIMIT
+        } else {
+            query.limit
+        };
+        let model = query
+            .model
+            .as_ref()
+            .map(|m| format!("%{}%", m.to_lowercase()));
+        // Date-only bounds compare as prefixes of the RFC 3339 timestamps;
+        // pad `until` so the whole day is included.
+        let until = query.until.as_ref().map(|u| {
+            if u.len() == 10 {
+                format!("{u}T99")
+            } else {
+                u.clone()
+            }
+        });
+        let tags = serde_json::to_string(&super::titling::normalize_tags(&query.tags))?;
+
+        const COLUMNS: &str = "c.id, c.title, c.created_at, c.updated_at, c.model,
+            c.project_dir, c.message_count, c.tags, c.title_source";
+        // ?5 is a JSON array of tags that must all be present.
+        const FILTERS: &str = "(?1 IS NULL OR lower(c.model) LIKE ?1)
+            AND (?2 IS NULL OR c.project_dir = ?2)
+            AND (?3 IS NULL OR c.updated_at >= ?3)
+            AND (?4 IS NULL OR c.updated_at <= ?4)
+            AND (SELECT count(*) FROM json_each(?5) t
+                 WHERE t.value IN (SELECT value FROM json_each(c.tags)))
+                = json_array_length(?5)";
+
+        let sql = if fts.is_some() {
+            format!(
+                "SELECT {COLUMNS}, m.role, snippet(messages, 2, '[', ']', '…', 12)
+                 FROM messages m JOIN conversations c ON c.id = m.conv_id
+                 WHERE messages MATCH ?6 AND {FILTERS}
+                 ORDER BY bm25(messages) LIMIT ?7"
+            )
+        } else {
+            format!(
+                "SELECT {COLUMNS}, 'title', ''
+                 FROM conversations c
+                 WHERE ?6 IS NULL AND {FILTERS}
+                 ORDER BY c.updated_at DESC LIMIT ?7"
+            )
+        };
+        let mut stmt = self.conn.prepare(&sql)?;
+        // Fetch extra rows so several hits in one conversation don't starve the limit.
+        let rows = stmt.query_map(
+            params![
+                model,
+                query.workspace,
+                query.since,
+                until,
+                tags,
+                fts,
+                (limit * 10) as i64
+            ],
+            |row| {
+                let tags: String = row.get(7)?;
+                let source: String = row.get(8)?;
+                Ok(SearchHit {
+                    metadata: ConversationMetadata {
+                        id: row.get(0)?,
+                        title: row.get(1)?,
+                        created_at: row.get(2)?,
+                        updated_at: row.get(3)?,
+                        model: row.get(4)?,
+                        project_dir: row.get(5)?,
+                        message_count: row.get::<_, i64>(6)? as usize,
+                        tags: serde_json::from_str(&tags).unwrap_or_default(),
+                        title_source: serde_json::from_value(Value::String(source))
+                            .unwrap_or_default(),
+                        disabled_tools: Vec::new(),
+                    },
+                    role: row.get(9)?,
+                    snippet: row.get(10)?,
+                })
+            },
+        )?;
+
+        let mut seen = HashSet::new();
+        let mut hits = Vec::new();
+        for hit in rows {
+            let hit = hit?;
+            if seen.insert(hit.metadata.id.clone()) {
+                hits.push(hit);
+                if hits.len() == limit {
+                    break;
+                }
+            }
+        }
+        Ok(hits)
+    }
+}
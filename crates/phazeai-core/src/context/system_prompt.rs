@@ -314,7 +314,8 @@ You have 17 powerful tools at your disposal:
 - `edit_file`: Targeted search-and-replace for minimal diffs.
 - `find_path`: Regex-based file search (like `find` or `fd`).
 - `glob`: Search files using glob patterns.
-- `copy_path` / `move_path` / `delete_path`: Manage file lifecycle.
+- `copy_path` / `move_path` / `delete_path`: Manage file lifecycle. Deletes go to the workspace trash; `move_path` takes many moves at once.
+- `file_history`: List deletes and moves, and undo one to restore the previous layout.
 - `create_directory`: Recursive directory creation.
 
 ### Research & Analysis
@@ -322,6 +323,7 @@ You have 17 powerful tools at your disposal:
 - `list_files`: Non-recursive list of a directory.
 - `diagnostics`: Run `cargo check` or linters and parse structured errors.
 - `now`: Get current time/date for context.
+- `memory`: Save durable facts about this project (conventions, chosen libraries, decisions) for future sessions.
 
 ### Execution & External
 - `bash`: Run any terminal command. Use for builds, tests, and env setup.
@@ -0,0 +1,166 @@
+//! Automatic conversation titles and topic tags.
+//!
+//! After [`AUTO_TITLE_AFTER_TURNS`] user turns a cheap model (the
+//! `quick_answer` route when configured) summarises the conversation into a
+//! short title and a few tags. Titles the user edited are never replaced, and
+//! tags the user set are kept.
+
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+
+use serde_json::Value;
+
+use super::persistence::{ConversationStore, SavedConversation, TitleSource};
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+
+/// User turns before a conversation gets a generated title.
+pub const AUTO_TITLE_AFTER_TURNS: usize = 2;
+/// Tags kept per conversation.
+pub const MAX_TAGS: usize = 5;
+/// Longest generated title, in characters.
+const MAX_TITLE_CHARS: usize = 60;
+/// Transcript budget sent to the titling model, in characters.
+const MAX_TRANSCRIPT_CHARS: usize = 6_000;
+
+/// Conversations with a titling request in flight; saves can fire faster
+/// than the model answers.
+static IN_FLIGHT: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Default::default);
+
+const TITLE_PROMPT: &str = r#"You label saved coding-assistant conversations.
+Read the transcript and answer with JSON and nothing else:
+{"title": "<3-8 word title, no trailing punctuation>", "tags": ["<topic>", ...]}
+Use 1-5 short lowercase topic tags such as a language, library or task
+("rust", "borrow-checker", "sql", "refactoring")."#;
+
+/// Whether `conversation` is due for a generated title.
+pub fn wants_auto_title(conversation: &SavedConversation) -> bool {
+    conversation.metadata.title_source == TitleSource::FirstMessage
+        && conversation
+            .messages
+            .iter()
+            .filter(|m| m.role == "user")
+            .count()
+            >= AUTO_TITLE_AFTER_TURNS
+}
+
+/// Lowercase, dash-separate, dedupe and cap a list of tags.
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.iter()
+        .filter_map(|t| normalize_tag(t))
+        .filter(|t| seen.insert(t.clone()))
+        .take(MAX_TAGS)
+        .collect()
+}
+
+fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag
+        .trim()
+        .trim_start_matches('#')
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '_')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let tag: String = tag
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '+' | '#' | '.'))
+        .take(32)
+        .collect();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Extract `(title, tags)` from the model answer.
+pub fn parse_title_response(text: &str) -> Option<(String, Vec<String>)> {
+    let (start, end) = (text.find('{')?, text.rfind('}')?);
+    if end < start {
+        return None;
+    }
+    let v: Value = serde_json::from_str(&text[start..=end]).ok()?;
+    let title = v.get("title")?.as_str()?.trim();
+    let title = title.trim_end_matches(['.', '!', '?']).trim_matches('"');
+    if title.is_empty() {
+        return None;
+    }
+    let title: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    let tags: Vec<String> = v
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|a| {
+            a.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    Some((title, normalize_tags(&tags)))
+}
+
+fn transcript(conversation: &SavedConversation) -> String {
+    let mut out = String::new();
+    for m in conversation
+        .messages
+        .iter()
+        .filter(|m| m.role == "user" || m.role == "assistant")
+    {
+        let line = format!("{}: {}\n\n", m.role, m.content.trim());
+        if out.len() + line.len() > MAX_TRANSCRIPT_CHARS {
+            let room = MAX_TRANSCRIPT_CHARS.saturating_sub(out.len());
+            out.push_str(&line[..line.floor_char_boundary(room)]);
+            break;
+        }
+        out.push_str(&line);
+    }
+    out
+}
+
+/// Ask `llm` for a title and tags for `conversation`.
+pub async fn generate_title_and_tags(
+    llm: &dyn LlmClient,
+    conversation: &SavedConversation,
+) -> Result<(String, Vec<String>), PhazeError> {
+    let messages = vec![
+        Message::system(TITLE_PROMPT),
+        Message::user(transcript(conversation)),
+    ];
+    let response = llm.chat(&messages, &[]).await?;
+    parse_title_response(&response.message.content).ok_or_else(|| {
+        PhazeError::Llm(format!(
+            "unusable title response: {}",
+            response.message.content.trim()
+        ))
+    })
+}
+
+/// Generate and store a title and tags for conversation `id` if it is due.
+/// Returns whether the conversation was updated.
+pub async fn auto_title(
+    store: &ConversationStore,
+    llm: &dyn LlmClient,
+    id: &str,
+) -> Result<bool, PhazeError> {
+    let conversation = store.load(id)?;
+    if !wants_auto_title(&conversation) {
+        return Ok(false);
+    }
+    if !IN_FLIGHT.lock().unwrap().insert(id.to_string()) {
+        return Ok(false);
+    }
+    let generated = generate_title_and_tags(llm, &conversation).await;
+    IN_FLIGHT.lock().unwrap().remove(id);
+    let (title, tags) = generated?;
+
+    // Re-read: the conversation may have been saved or edited meanwhile.
+    let mut conversation = store.load(id)?;
+    if conversation.metadata.title_source == TitleSource::User {
+        return Ok(false);
+    }
+    conversation.metadata.title = title;
+    conversation.metadata.title_source = TitleSource::Generated;
+    if conversation.metadata.tags.is_empty() {
+        conversation.metadata.tags = tags;
+    }
+    store.save(&conversation)?;
+    Ok(true)
+}
@@ -0,0 +1,38 @@
+//! Database passwords in the OS keychain, through [`crate::secrets`], keyed
+//! by profile name.
+
+use crate::error::PhazeError;
+use crate::secrets::{forget_secret, secret, store_secret};
+
+/// The keychain service the passwords are filed under.
+const SERVICE: &str = "phazeai-db";
+
+pub fn store_password(profile: &str, password: &str) -> Result<(), PhazeError> {
+    store_secret(SERVICE, profile, password)
+}
+
+/// The profile's password; `None` when it has none saved.
+pub fn password(profile: &str) -> Result<Option<String>, PhazeError> {
+    secret(SERVICE, profile)
+}
+
+pub fn forget_password(profile: &str) -> Result<(), PhazeError> {
+    forget_secret(SERVICE, profile)
+}
+
+/// `url` with the profile's saved password, when it names a user and has
+/// no password of its own.
+pub fn url_with_password(profile: &str, url: &str) -> Result<String, PhazeError> {
+    let mut parsed = reqwest::Url::parse(url)
+        .map_err(|e| PhazeError::Config(format!("bad connection string for {profile}: {e}")))?;
+    if parsed.username().is_empty() || parsed.password().is_some() {
+        return Ok(url.to_string());
+    }
+    match password(profile)? {
+        Some(password) => {
+            let _ = parsed.set_password(Some(&password));
+            Ok(parsed.to_string())
+        }
+        None => Ok(url.to_string()),
+    }
+}
@@ -0,0 +1,400 @@
+//! Database client: SQLite files out of the box, PostgreSQL and MySQL /
+//! MariaDB by connection string.
+//!
+//! Connections are [`DatabaseProfile`]s saved in the settings:
+//!
+//! ```toml
+//! [[databases]]
+//! name = "app"
+//! url = "data/app.sqlite3"          # relative to the workspace
+//!
+//! [[databases]]
+//! name = "staging"
+//! url = "postgres://reader@db.internal:5432/shop"
+//! read_only = true
+//! ```
+//!
+//! Passwords stay out of the settings file: [`keychain`] keeps them in the
+//! OS keychain under the profile's name, and [`Database::open`] puts them
+//! into the URL. The IDE's DB tab browses tables and runs queries through
+//! here, and so does the agent's `sql_query` tool, which only runs
+//! [`is_read_only`] statements unless the profile sets `agent_writes`.
+
+pub mod keychain;
+mod mysql;
+mod postgres;
+mod sqlite;
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PhazeError;
+
+/// Rows a query keeps; the rest are dropped and the result marked truncated.
+pub const MAX_ROWS: usize = 1000;
+
+/// A saved connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseProfile {
+    pub name: String,
+    /// `postgres://user@host/db`, `mysql://user@host/db`, or the path of a
+    /// SQLite file, optionally prefixed with `sqlite:`. Without a password.
+    pub url: String,
+    /// Only run read-only statements, from the DB tab as well as the agent.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Let the agent's `sql_query` tool change data too.
+    #[serde(default)]
+    pub agent_writes: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbKind {
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl DbKind {
+    /// The kind of database `url` names; anything without a known scheme is
+    /// a SQLite file.
+    pub fn of(url: &str) -> Self {
+        let scheme = url.split_once("://").map(|(s, _)| s.to_ascii_lowercase());
+        match scheme.as_deref() {
+            Some("postgres" | "postgresql") => DbKind::Postgres,
+            Some("mysql" | "mariadb") => DbKind::MySql,
+            _ => DbKind::Sqlite,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DbKind::Sqlite => "SQLite",
+            DbKind::Postgres => "PostgreSQL",
+            DbKind::MySql => "MySQL",
+        }
+    }
+}
+
+/// A table or view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    /// The Postgres schema or MySQL database; `None` for SQLite.
+    pub schema: Option<String>,
+    pub name: String,
+    pub is_view: bool,
+}
+
+impl TableInfo {
+    /// `schema.name`, or the name alone.
+    pub fn qualified_name(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{schema}.{}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    pub primary_key: bool,
+}
+
+/// What a statement returned: its rows, or how many rows it changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    /// Each value as text; `None` for NULL.
+    pub rows: Vec<Vec<Option<String>>>,
+    /// Rows changed, for a statement that returns none.
+    pub affected: Option<u64>,
+    /// Whether rows past [`MAX_ROWS`] were dropped.
+    pub truncated: bool,
+    pub elapsed: Duration,
+}
+
+impl QueryResult {
+    /// `42 rows · 12 ms`, `3 rows affected · 4 ms`.
+    pub fn summary(&self) -> String {
+        let what = match self.affected {
+            Some(n) if self.columns.is_empty() => format!("{n} row(s) affected"),
+            _ if self.truncated => format!("first {} rows", self.rows.len()),
+            _ => format!("{} row(s)", self.rows.len()),
+        };
+        format!("{what} · {} ms", self.elapsed.as_millis())
+    }
+
+    /// Keep the first `max` rows, marking the result truncated when any go.
+    pub(crate) fn push_row(&mut self, row: Vec<Option<String>>, max: usize) {
+        if self.rows.len() < max {
+            self.rows.push(row);
+        } else {
+            self.truncated = true;
+        }
+    }
+}
+
+/// SQL keywords that write data or schema, or change the session.
+const WRITE_KEYWORDS: &[&str] = &[
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "MERGE",
+    "UPSERT",
+    "CREATE",
+    "ALTER",
+    "DROP",
+    "TRUNCATE",
+    "RENAME",
+    "GRANT",
+    "REVOKE",
+    "ATTACH",
+    "DETACH",
+    "VACUUM",
+    "REINDEX",
+    "ANALYZE",
+    "COPY",
+    "CALL",
+    "EXEC",
+    "EXECUTE",
+    "DO",
+    "LOCK",
+    "SET",
+    "RESET",
+    "INTO",
+    "COMMIT",
+    "ROLLBACK",
+    "BEGIN",
+    "SAVEPOINT",
+    "LOAD",
+    "HANDLER",
+    "REFRESH",
+    "CLUSTER",
+    "NOTIFY",
+    "LISTEN",
+];
+
+/// Statements that start read-only.
+const READ_KEYWORDS: &[&str] = &[
+    "SELECT", "WITH", "EXPLAIN", "SHOW", "DESCRIBE", "DESC", "VALUES", "TABLE", "PRAGMA",
+];
+
+/// The words of `sql` outside string literals, quoted identifiers and
+/// comments, upper-cased, and whether it holds more than one statement.
+fn sql_words(sql: &str) -> (Vec<String>, bool) {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut statements = 0;
+    let mut in_statement = false;
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut flush = |word: &mut String| {
+            if !word.is_empty() {
+                words.push(std::mem::take(word).to_ascii_uppercase());
+            }
+        };
+        match c {
+            '\'' | '"' | '`' => {
+                flush(&mut word);
+                in_statement = true;
+                while let Some(d) = chars.next() {
+                    if d == c {
+                        // A doubled quote is an escaped one.
+                        if chars.peek() == Some(&c) {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                flush(&mut word);
+                for d in chars.by_ref() {
+                    if d == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                flush(&mut word);
+                chars.next();
+                let mut prev = ' ';
+                for d in chars.by_ref() {
+                    if prev == '*' && d == '/' {
+                        break;
+                    }
+                    prev = d;
+                }
+            }
+            ';' => {
+                flush(&mut word);
+                if in_statement {
+                    statements += 1;
+                    in_statement = false;
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                word.push(c);
+                in_statement = true;
+            }
+            c => {
+                flush(&mut word);
+                if !c.is_whitespace() {
+                    in_statement = true;
+                }
+            }
+        }
+    }
+    if !word.is_empty() {
+        words.push(word.to_ascii_uppercase());
+    }
+    if in_statement {
+        statements += 1;
+    }
+    (words, statements > 1)
+}
+
+/// Whether `sql` is a single statement that only reads: it starts with
+/// `SELECT`, `WITH`, `EXPLAIN`, `SHOW`, … and has no keyword that writes
+/// anywhere, `SELECT … INTO` and data-modifying CTEs included. Errs on the
+/// side of refusing; the connection is read-only as well.
+pub fn is_read_only(sql: &str) -> bool {
+    let (words, several) = sql_words(sql);
+    let Some(first) = words.first() else {
+        return false;
+    };
+    // `PRAGMA name = value` sets it.
+    let sets_pragma = first == "PRAGMA" && sql.contains('=');
+    !several
+        && !sets_pragma
+        && READ_KEYWORDS.contains(&first.as_str())
+        && !words.iter().any(|w| WRITE_KEYWORDS.contains(&w.as_str()))
+}
+
+/// Text of a value no driver formats for us.
+pub(crate) fn blob_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => format!("<{} bytes>", bytes.len()),
+    }
+}
+
+/// An open profile: where it connects and whether it may write.
+#[derive(Debug, Clone)]
+pub struct Database {
+    pub name: String,
+    pub kind: DbKind,
+    /// With the password from the keychain, when there is one.
+    url: String,
+    /// The file, for SQLite.
+    path: PathBuf,
+    pub read_only: bool,
+}
+
+impl Database {
+    /// `profile`, with a SQLite path relative to `root`.
+    pub fn open(profile: &DatabaseProfile, root: &Path) -> Result<Self, PhazeError> {
+        let kind = DbKind::of(&profile.url);
+        let (url, path) = match kind {
+            DbKind::Sqlite => {
+                let file = profile.url.trim();
+                let file = file
+                    .strip_prefix("sqlite://")
+                    .or_else(|| file.strip_prefix("sqlite:"))
+                    .unwrap_or(file);
+                (String::new(), root.join(file))
+            }
+            DbKind::Postgres | DbKind::MySql => (
+                keychain::url_with_password(&profile.name, &profile.url)?,
+                PathBuf::new(),
+            ),
+        };
+        Ok(Self {
+            name: profile.name.clone(),
+            kind,
+            url,
+            path,
+            read_only: profile.read_only,
+        })
+    }
+
+    /// Refuse writes from here on, whatever the profile says.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Its tables and views, by schema and name.
+    pub async fn tables(&self) -> Result<Vec<TableInfo>, PhazeError> {
+        match self.kind {
+            DbKind::Sqlite => {
+                let path = self.path.clone();
+                blocking(move || sqlite::tables(&path)).await
+            }
+            DbKind::Postgres => postgres::tables(&self.url).await,
+            DbKind::MySql => mysql::tables(&self.url).await,
+        }
+    }
+
+    pub async fn columns(&self, table: &TableInfo) -> Result<Vec<ColumnInfo>, PhazeError> {
+        match self.kind {
+            DbKind::Sqlite => {
+                let (path, name) = (self.path.clone(), table.name.clone());
+                blocking(move || sqlite::columns(&path, &name)).await
+            }
+            DbKind::Postgres => postgres::columns(&self.url, table).await,
+            DbKind::MySql => mysql::columns(&self.url, table).await,
+        }
+    }
+
+    /// Run `sql`, keeping at most `max_rows` rows. On a read-only database
+    /// only [`is_read_only`] statements run, in a read-only transaction.
+    pub async fn query(&self, sql: &str, max_rows: usize) -> Result<QueryResult, PhazeError> {
+        if self.read_only && !is_read_only(sql) {
+            return Err(PhazeError::Other(format!(
+                "{} is read-only: only a single SELECT, WITH, EXPLAIN, SHOW or PRAGMA \
+                 statement that changes nothing can run",
+                self.name
+            )));
+        }
+        let started = Instant::now();
+        let mut result = match self.kind {
+            DbKind::Sqlite => {
+                let (path, sql, read_only) = (self.path.clone(), sql.to_string(), self.read_only);
+                blocking(move || sqlite::query(&path, &sql, read_only, max_rows)).await?
+            }
+            DbKind::Postgres => postgres::query(&self.url, sql, self.read_only, max_rows).await?,
+            DbKind::MySql => mysql::query(&self.url, sql, self.read_only, max_rows).await?,
+        };
+        result.elapsed = started.elapsed();
+        Ok(result)
+    }
+
+    /// A query showing the first rows of `table`.
+    pub fn preview_sql(&self, table: &TableInfo) -> String {
+        let quote = |name: &str| match self.kind {
+            DbKind::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        };
+        let name = match &table.schema {
+            Some(schema) => format!("{}.{}", quote(schema), quote(&table.name)),
+            None => quote(&table.name),
+        };
+        format!("SELECT * FROM {name} LIMIT 100")
+    }
+}
+
+/// Run blocking SQLite work off the async runtime.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, PhazeError> + Send + 'static,
+) -> Result<T, PhazeError> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| PhazeError::Other(format!("database task failed: {e}")))?
+}
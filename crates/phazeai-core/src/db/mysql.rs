@@ -0,0 +1,141 @@
+//! MySQL and MariaDB, through `mysql_async`.
+
+use mysql_async::prelude::Queryable;
+use mysql_async::{Conn, Row, Value};
+
+use super::{blob_text, ColumnInfo, QueryResult, TableInfo};
+use crate::error::PhazeError;
+
+fn mysql_error(e: mysql_async::Error) -> PhazeError {
+    PhazeError::Other(format!("MySQL: {e}"))
+}
+
+/// `mariadb://` is ours; the driver only takes `mysql://`.
+async fn connect(url: &str) -> Result<Conn, PhazeError> {
+    let url = match url.strip_prefix("mariadb://") {
+        Some(rest) => format!("mysql://{rest}"),
+        None => url.to_string(),
+    };
+    Conn::from_url(url).await.map_err(mysql_error)
+}
+
+fn text(value: &Value) -> Option<String> {
+    Some(match value {
+        Value::NULL => return None,
+        Value::Bytes(bytes) => blob_text(bytes),
+        Value::Int(i) => i.to_string(),
+        Value::UInt(u) => u.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Double(d) => d.to_string(),
+        Value::Date(y, mo, d, h, mi, s, us) => {
+            let mut date = format!("{y:04}-{mo:02}-{d:02} {h:02}:{mi:02}:{s:02}");
+            if *us > 0 {
+                date.push_str(&format!(".{us:06}"));
+            }
+            date
+        }
+        Value::Time(negative, days, h, mi, s, us) => {
+            let hours = u32::from(*h) + days * 24;
+            let sign = if *negative { "-" } else { "" };
+            let mut time = format!("{sign}{hours:02}:{mi:02}:{s:02}");
+            if *us > 0 {
+                time.push_str(&format!(".{us:06}"));
+            }
+            time
+        }
+    })
+}
+
+fn row_text(row: &Row) -> Vec<Option<String>> {
+    (0..row.len())
+        .map(|i| row.as_ref(i).and_then(text))
+        .collect()
+}
+
+/// Column `i` of a row from an information_schema query.
+fn field(row: &Row, i: usize) -> String {
+    row.as_ref(i).and_then(text).unwrap_or_default()
+}
+
+pub(super) async fn tables(url: &str) -> Result<Vec<TableInfo>, PhazeError> {
+    let mut conn = connect(url).await?;
+    let rows: Vec<Row> = conn
+        .query(
+            "SELECT table_schema, table_name, table_type FROM information_schema.tables
+             WHERE table_schema = DATABASE() ORDER BY table_name",
+        )
+        .await
+        .map_err(mysql_error)?;
+    let _ = conn.disconnect().await;
+    Ok(rows
+        .iter()
+        .map(|row| TableInfo {
+            schema: Some(field(row, 0)),
+            name: field(row, 1),
+            is_view: field(row, 2) == "VIEW",
+        })
+        .collect())
+}
+
+pub(super) async fn columns(url: &str, table: &TableInfo) -> Result<Vec<ColumnInfo>, PhazeError> {
+    let mut conn = connect(url).await?;
+    let rows: Vec<Row> = conn
+        .exec(
+            "SELECT column_name, column_type, is_nullable, column_key
+             FROM information_schema.columns
+             WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ?
+             ORDER BY ordinal_position",
+            (table.schema.clone(), table.name.clone()),
+        )
+        .await
+        .map_err(mysql_error)?;
+    let _ = conn.disconnect().await;
+    Ok(rows
+        .iter()
+        .map(|row| ColumnInfo {
+            name: field(row, 0),
+            data_type: field(row, 1),
+            nullable: field(row, 2) == "YES",
+            primary_key: field(row, 3) == "PRI",
+        })
+        .collect())
+}
+
+pub(super) async fn query(
+    url: &str,
+    sql: &str,
+    read_only: bool,
+    max_rows: usize,
+) -> Result<QueryResult, PhazeError> {
+    let mut conn = connect(url).await?;
+    if read_only {
+        conn.query_drop("START TRANSACTION READ ONLY")
+            .await
+            .map_err(mysql_error)?;
+    }
+    let result = run(&mut conn, sql, max_rows).await;
+    if read_only {
+        let _ = conn.query_drop("ROLLBACK").await;
+    }
+    let _ = conn.disconnect().await;
+    result
+}
+
+async fn run(conn: &mut Conn, sql: &str, max_rows: usize) -> Result<QueryResult, PhazeError> {
+    let mut rows = conn.query_iter(sql).await.map_err(mysql_error)?;
+    let mut result = QueryResult {
+        columns: rows
+            .columns()
+            .map(|columns| columns.iter().map(|c| c.name_str().into_owned()).collect())
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    let fetched: Vec<Row> = rows.collect().await.map_err(mysql_error)?;
+    if result.columns.is_empty() {
+        result.affected = Some(rows.affected_rows());
+    }
+    for row in &fetched {
+        result.push_row(row_text(row), max_rows);
+    }
+    Ok(result)
+}
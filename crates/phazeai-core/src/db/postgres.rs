@@ -0,0 +1,125 @@
+//! PostgreSQL, through `tokio-postgres`. Queries go over the simple query
+//! protocol, which returns every value as text.
+
+use tokio_postgres::{Client, NoTls, SimpleQueryMessage};
+
+use super::{ColumnInfo, QueryResult, TableInfo};
+use crate::error::PhazeError;
+
+fn pg_error(e: tokio_postgres::Error) -> PhazeError {
+    PhazeError::Other(format!("PostgreSQL: {e}"))
+}
+
+/// Connect, driving the connection on a task of its own until the client
+/// is dropped.
+async fn connect(url: &str) -> Result<Client, PhazeError> {
+    let (client, connection) = tokio_postgres::connect(url, NoTls)
+        .await
+        .map_err(pg_error)?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            tracing::debug!("PostgreSQL connection closed: {e}");
+        }
+    });
+    Ok(client)
+}
+
+pub(super) async fn tables(url: &str) -> Result<Vec<TableInfo>, PhazeError> {
+    let client = connect(url).await?;
+    let rows = client
+        .query(
+            "SELECT table_schema::text, table_name::text, table_type = 'VIEW'
+             FROM information_schema.tables
+             WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
+             ORDER BY 1, 2",
+            &[],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(rows
+        .iter()
+        .map(|row| TableInfo {
+            schema: Some(row.get(0)),
+            name: row.get(1),
+            is_view: row.get(2),
+        })
+        .collect())
+}
+
+pub(super) async fn columns(url: &str, table: &TableInfo) -> Result<Vec<ColumnInfo>, PhazeError> {
+    let client = connect(url).await?;
+    let schema = table.schema.as_deref().unwrap_or("public");
+    let rows = client
+        .query(
+            "SELECT c.column_name::text, c.data_type::text, c.is_nullable = 'YES',
+                    EXISTS (
+                        SELECT 1 FROM information_schema.table_constraints t
+                        JOIN information_schema.key_column_usage k
+                          ON k.constraint_name = t.constraint_name
+                         AND k.table_schema = t.table_schema
+                        WHERE t.constraint_type = 'PRIMARY KEY'
+                          AND t.table_schema = c.table_schema
+                          AND t.table_name = c.table_name
+                          AND k.column_name = c.column_name
+                    )
+             FROM information_schema.columns c
+             WHERE c.table_schema = $1 AND c.table_name = $2
+             ORDER BY c.ordinal_position",
+            &[&schema, &table.name],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(rows
+        .iter()
+        .map(|row| ColumnInfo {
+            name: row.get(0),
+            data_type: row.get(1),
+            nullable: row.get(2),
+            primary_key: row.get(3),
+        })
+        .collect())
+}
+
+pub(super) async fn query(
+    url: &str,
+    sql: &str,
+    read_only: bool,
+    max_rows: usize,
+) -> Result<QueryResult, PhazeError> {
+    let client = connect(url).await?;
+    if read_only {
+        client
+            .batch_execute("BEGIN READ ONLY")
+            .await
+            .map_err(pg_error)?;
+    }
+    let messages = client.simple_query(sql).await.map_err(pg_error);
+    if read_only {
+        let _ = client.batch_execute("ROLLBACK").await;
+    }
+
+    // Several statements each reply; the last one's result is shown.
+    let mut result = QueryResult::default();
+    for message in messages? {
+        match message {
+            SimpleQueryMessage::RowDescription(columns) => {
+                result = QueryResult {
+                    columns: columns.iter().map(|c| c.name().to_string()).collect(),
+                    ..Default::default()
+                };
+            }
+            SimpleQueryMessage::Row(row) => {
+                if result.columns.is_empty() {
+                    result.columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                }
+                let values = (0..row.len())
+                    .map(|i| row.get(i).map(String::from))
+                    .collect();
+                result.push_row(values, max_rows);
+            }
+            SimpleQueryMessage::CommandComplete(n) => result.affected = Some(n),
+            _ => {}
+        }
+    }
+    Ok(result)
+}
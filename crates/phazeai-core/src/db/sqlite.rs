@@ -0,0 +1,102 @@
+//! SQLite files, through `rusqlite`.
+
+use std::path::Path;
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+
+use super::{blob_text, ColumnInfo, QueryResult, TableInfo};
+use crate::error::PhazeError;
+
+/// Open an existing file; a missing one is an error rather than created.
+fn connect(path: &Path, read_only: bool) -> Result<Connection, PhazeError> {
+    if !path.is_file() {
+        return Err(PhazeError::Other(format!(
+            "no SQLite database at {}",
+            path.display()
+        )));
+    }
+    let access = if read_only {
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+    } else {
+        OpenFlags::SQLITE_OPEN_READ_WRITE
+    };
+    let conn = Connection::open_with_flags(path, access | OpenFlags::SQLITE_OPEN_NO_MUTEX)?;
+    if read_only {
+        conn.pragma_update(None, "query_only", true)?;
+    }
+    Ok(conn)
+}
+
+pub(super) fn tables(path: &Path) -> Result<Vec<TableInfo>, PhazeError> {
+    let conn = connect(path, true)?;
+    let mut stmt = conn.prepare(
+        "SELECT name, type FROM sqlite_master
+         WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )?;
+    let tables = stmt
+        .query_map([], |row| {
+            Ok(TableInfo {
+                schema: None,
+                name: row.get(0)?,
+                is_view: row.get::<_, String>(1)? == "view",
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(tables)
+}
+
+pub(super) fn columns(path: &Path, table: &str) -> Result<Vec<ColumnInfo>, PhazeError> {
+    let conn = connect(path, true)?;
+    let mut stmt =
+        conn.prepare("SELECT name, type, \"notnull\", pk FROM pragma_table_info(?1) ORDER BY cid")?;
+    let columns = stmt
+        .query_map([table], |row| {
+            Ok(ColumnInfo {
+                name: row.get(0)?,
+                data_type: row.get(1)?,
+                nullable: row.get::<_, i64>(2)? == 0,
+                primary_key: row.get::<_, i64>(3)? > 0,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(columns)
+}
+
+fn text(value: ValueRef<'_>) -> Option<String> {
+    match value {
+        ValueRef::Null => None,
+        ValueRef::Integer(i) => Some(i.to_string()),
+        ValueRef::Real(f) => Some(f.to_string()),
+        ValueRef::Text(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+        ValueRef::Blob(bytes) => Some(blob_text(bytes)),
+    }
+}
+
+pub(super) fn query(
+    path: &Path,
+    sql: &str,
+    read_only: bool,
+    max_rows: usize,
+) -> Result<QueryResult, PhazeError> {
+    let conn = connect(path, read_only)?;
+    let mut stmt = conn.prepare(sql)?;
+    let mut result = QueryResult {
+        columns: stmt.column_names().into_iter().map(String::from).collect(),
+        ..Default::default()
+    };
+    if result.columns.is_empty() {
+        result.affected = Some(stmt.execute([])? as u64);
+        return Ok(result);
+    }
+    let width = result.columns.len();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let values = (0..width)
+            .map(|i| row.get_ref(i).map(text))
+            .collect::<Result<_, _>>()?;
+        result.push_row(values, max_rows);
+    }
+    Ok(result)
+}
@@ -0,0 +1,420 @@
+//! Applying a chat reply's code block to a file: the blocks of a Markdown
+//! reply and the path each one names, the file a block is most likely for,
+//! and that file's text with the block in place.
+//!
+//! A block is taken as the whole file when it starts and ends as the file
+//! does, or is most of its length. Otherwise each part of it between
+//! elision lines (`// ...`, `# … existing code`) replaces the lines it
+//! overlaps: from the first line that appears only once in the file to the
+//! last, widened to the end of any `{ … }` or indented block opened on one
+//! of them and over matching lines on either side. A part shown at another
+//! indentation than the file's, such as a method without its `impl`, is
+//! reindented to fit.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::folding::fold_regions;
+use crate::tools::SHELL_FENCES;
+
+/// Files larger than this aren't read when looking for a block's target.
+const MAX_CANDIDATE_BYTES: u64 = 512 * 1024;
+/// Files read at most when looking for a block's target.
+const MAX_CANDIDATES: usize = 2000;
+/// The share of a block's lines a file must have to be taken as its target.
+const MIN_SIMILARITY: f64 = 0.4;
+
+/// A fenced code block from a reply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// The fence's language, lowercase; empty when it has none.
+    pub lang: String,
+    /// The file the block names: after the fence's language
+    /// (```` ```rust src/main.rs ````, `rust:src/main.rs`, `title="…"`), as
+    /// a comment on its first line (`// src/main.rs`, `# file: app.py`), or
+    /// in code or bold on the line before it.
+    pub path: Option<String>,
+    /// The code, without a first line naming the file.
+    pub body: String,
+}
+
+/// The code blocks of a Markdown reply, in order. Shell blocks, which are
+/// run rather than applied, and empty blocks are left out.
+pub fn code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut before: Option<&str> = None;
+    let mut current: Option<(&str, Vec<&str>, Option<&str>)> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        match current.take() {
+            None => {
+                if let Some(info) = trimmed.strip_prefix("```") {
+                    current = Some((info, Vec::new(), before));
+                } else if !trimmed.is_empty() {
+                    before = Some(trimmed);
+                }
+            }
+            Some((info, body, prose)) if trimmed.starts_with("```") => {
+                before = None;
+                if let Some(block) = code_block(info, &body, prose) {
+                    blocks.push(block);
+                }
+            }
+            Some((info, mut body, prose)) => {
+                body.push(line);
+                current = Some((info, body, prose));
+            }
+        }
+    }
+    blocks
+}
+
+fn code_block(info: &str, body: &[&str], prose: Option<&str>) -> Option<CodeBlock> {
+    let mut words = info.split_whitespace();
+    let first = words.next().unwrap_or("");
+    let (mut lang, mut path) = match first.split_once(':') {
+        Some((lang, path)) if looks_like_path(path) => (lang, Some(path)),
+        _ if looks_like_path(first) => ("", Some(first)),
+        _ => (first, None),
+    };
+    for word in words {
+        let value = ["title=", "file=", "path=", "filename="]
+            .iter()
+            .find_map(|key| word.strip_prefix(key))
+            .map_or(word, |v| v.trim_matches(['"', '\'']));
+        if path.is_none() && looks_like_path(value) {
+            path = Some(value);
+        }
+    }
+    if lang.is_empty() {
+        lang = path
+            .and_then(|p| p.rsplit_once('.'))
+            .map_or("", |(_, ext)| ext);
+    }
+    let lang = lang.to_ascii_lowercase();
+    if SHELL_FENCES.contains(&lang.as_str()) {
+        return None;
+    }
+
+    let mut body = body;
+    if let Some(named) = body.first().and_then(|l| comment_path(l)) {
+        path = path.or(Some(named));
+        body = &body[1..];
+    }
+    if path.is_none() {
+        path = prose.and_then(prose_path);
+    }
+    let body = body.join("\n");
+    if body.trim().is_empty() {
+        return None;
+    }
+    Some(CodeBlock {
+        lang,
+        path: path.map(str::to_string),
+        body,
+    })
+}
+
+/// A file name or path: one word with an extension of letters, or with a
+/// `/`.
+fn looks_like_path(word: &str) -> bool {
+    let allowed = |c: char| c.is_alphanumeric() || "/\\._-@+".contains(c);
+    if word.is_empty() || !word.chars().all(allowed) {
+        return false;
+    }
+    let name = word.rsplit(['/', '\\']).next().unwrap_or("");
+    let has_extension = name.rsplit_once('.').is_some_and(|(_, ext)| {
+        ext.chars().any(|c| c.is_ascii_alphabetic()) && ext.chars().all(|c| c.is_alphanumeric())
+    });
+    has_extension || (word.contains('/') && !name.is_empty())
+}
+
+/// The path a code block's first line names, when it's only a comment
+/// giving one.
+fn comment_path(line: &str) -> Option<&str> {
+    let text = strip_comment(line)?;
+    let text = text.trim_end_matches("-->").trim_end_matches("*/").trim();
+    let text = ["file:", "File:", "path:", "Path:", "filename:"]
+        .iter()
+        .find_map(|label| text.strip_prefix(label))
+        .unwrap_or(text)
+        .trim();
+    looks_like_path(text).then_some(text)
+}
+
+/// The last path in code or bold on a line of prose.
+fn prose_path(line: &str) -> Option<&str> {
+    let spans = line.split('`').skip(1).step_by(2);
+    let bold = line.split("**").skip(1).step_by(2);
+    spans
+        .chain(bold)
+        .map(|s| s.trim_matches('`'))
+        .filter(|s| looks_like_path(s))
+        .last()
+}
+
+/// The text after a line comment's marker, `None` for a line that isn't one.
+fn strip_comment(line: &str) -> Option<&str> {
+    let code = line.trim_start();
+    ["//", "#", "--", "/*", "<!--", ";"]
+        .iter()
+        .find_map(|marker| code.strip_prefix(marker))
+        .map(|rest| rest.trim_start_matches(['/', '!', '*']).trim())
+}
+
+/// A line standing for code left out, such as `// ...` or
+/// `# … rest unchanged`.
+fn is_elision(line: &str) -> bool {
+    let text = strip_comment(line).unwrap_or(line.trim());
+    text.starts_with("...") || text.starts_with('…')
+}
+
+/// The extensions of files a fence's language is written in.
+fn fence_extensions(lang: &str) -> &'static [&'static str] {
+    match lang {
+        "rust" | "rs" => &["rs"],
+        "python" | "py" => &["py", "pyi"],
+        "javascript" | "js" | "jsx" => &["js", "jsx", "mjs", "cjs"],
+        "typescript" | "ts" | "tsx" => &["ts", "tsx"],
+        "c" | "h" => &["c", "h"],
+        "cpp" | "c++" | "cxx" => &["cpp", "cc", "cxx", "hpp", "h"],
+        "csharp" | "cs" => &["cs"],
+        "golang" | "go" => &["go"],
+        "ruby" | "rb" => &["rb"],
+        "kotlin" | "kt" => &["kt", "kts"],
+        "yaml" | "yml" => &["yaml", "yml"],
+        "markdown" | "md" => &["md"],
+        _ => &[],
+    }
+}
+
+/// The file `hint` names in the workspace at `root` whose files are
+/// `files`: the file at that path, or else the one whose path ends with it
+/// that's nearest the root. A hint naming no file in the workspace is a new
+/// file at that path under `root`. The hint comes from the model, so one
+/// that is absolute or climbs out with `..` names nothing: `None`.
+pub fn resolve_hint(hint: &str, root: &Path, files: &[PathBuf]) -> Option<PathBuf> {
+    let hint = hint.replace('\\', "/");
+    let hint = hint.trim_start_matches("./");
+    let relative = Path::new(hint);
+    let inside = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside || relative.file_name().is_none() {
+        return None;
+    }
+    let exact = root.join(hint);
+    if files.contains(&exact) {
+        return Some(exact);
+    }
+    let nearest = files
+        .iter()
+        .filter(|f| f.ends_with(hint))
+        .min_by_key(|f| f.components().count())
+        .cloned();
+    Some(nearest.unwrap_or(exact))
+}
+
+/// The share of `block`'s lines with code in them that `text` has too,
+/// compared without their indentation.
+pub fn similarity(block: &str, text: &str) -> f64 {
+    let code = |line: &&str| line.chars().any(char::is_alphanumeric) && !is_elision(line);
+    let lines: std::collections::HashSet<&str> = text.lines().map(str::trim).collect();
+    let wanted: std::collections::HashSet<&str> =
+        block.lines().map(str::trim).filter(code).collect();
+    if wanted.is_empty() {
+        return 0.0;
+    }
+    let found = wanted.iter().filter(|line| lines.contains(*line)).count();
+    found as f64 / wanted.len() as f64
+}
+
+/// The file `block` is for: the one its path names in the workspace, or
+/// else the file most like it, `open` files first and then the workspace's
+/// `files` in the block's language. `None` when no file has enough of its
+/// lines.
+pub fn find_target(
+    block: &CodeBlock,
+    root: &Path,
+    files: &[PathBuf],
+    open: &[PathBuf],
+) -> Option<PathBuf> {
+    let _span = tracing::debug_span!("find_apply_target").entered();
+    let hinted = block
+        .path
+        .as_deref()
+        .and_then(|hint| resolve_hint(hint, root, files));
+    if hinted.is_some() {
+        return hinted;
+    }
+    let extensions = fence_extensions(&block.lang);
+    let in_language = |path: &&PathBuf| {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        block.lang.is_empty() || ext == block.lang || extensions.contains(&ext)
+    };
+    let small = |path: &&PathBuf| {
+        std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() <= MAX_CANDIDATE_BYTES)
+    };
+    let mut best: Option<(f64, &PathBuf)> = None;
+    let candidates = open
+        .iter()
+        .chain(files.iter().filter(in_language))
+        .filter(small)
+        .take(MAX_CANDIDATES);
+    for path in candidates {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let score = similarity(&block.body, &text);
+        if score >= MIN_SIMILARITY && best.is_none_or(|(b, _)| score > b) {
+            best = Some((score, path));
+        }
+    }
+    best.map(|(_, path)| path.clone())
+}
+
+/// `current`, the text of a file with extension `ext`, with `body` put in
+/// place as the module docs describe. `None` when a part of `body` has no
+/// line found only once in `current` to place it by.
+pub fn apply_block(current: &str, body: &str, ext: &str) -> Option<String> {
+    let ending = if current.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let trailing = current.is_empty() || current.ends_with('\n');
+    let finish = |lines: &[String]| {
+        let mut text = lines.join(ending);
+        if trailing && !text.is_empty() {
+            text.push_str(ending);
+        }
+        text
+    };
+    let body: Vec<&str> = body.lines().collect();
+    let pieces: Vec<&[&str]> = body
+        .split(|line| is_elision(line))
+        .map(trim_blank)
+        .filter(|piece| !piece.is_empty())
+        .collect();
+    let whole = |piece: &[&str]| piece.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+    if current.trim().is_empty() {
+        return Some(finish(&whole(&body)));
+    }
+    let mut lines: Vec<String> = current.lines().map(str::to_string).collect();
+    let elided = body.iter().any(|line| is_elision(line));
+    if let [piece] = pieces.as_slice() {
+        if !elided && covers(&lines, piece) {
+            return Some(finish(&whole(piece)));
+        }
+    }
+    for piece in pieces {
+        let (start, end, indent) = place(&lines, piece, ext)?;
+        let replacement: Vec<String> = piece.iter().map(|line| indent.apply(line)).collect();
+        lines.splice(start..=end, replacement);
+    }
+    Some(finish(&lines))
+}
+
+/// `lines` without blank lines at either end.
+fn trim_blank<'a, 'b>(lines: &'b [&'a str]) -> &'b [&'a str] {
+    let start = lines.iter().take_while(|l| is_blank(l)).count();
+    let end = lines.len() - lines.iter().rev().take_while(|l| is_blank(l)).count();
+    &lines[start..end.max(start)]
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// The first line of `lines` with anything on it, trimmed.
+fn first_code<'a>(mut lines: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    lines.find(|l| !is_blank(l)).map(str::trim)
+}
+
+/// Whether `piece` is the whole of `lines`: it starts and ends with the
+/// same code, or has most of its lines.
+fn covers(lines: &[String], piece: &[&str]) -> bool {
+    let same_ends = first_code(lines.iter().map(String::as_str))
+        == first_code(piece.iter().copied())
+        && first_code(lines.iter().rev().map(String::as_str))
+            == first_code(piece.iter().rev().copied());
+    let file_lines = lines.iter().filter(|l| !is_blank(l)).count();
+    same_ends || piece.iter().filter(|l| !is_blank(l)).count() * 5 >= file_lines * 4
+}
+
+/// How a piece's lines are reindented to the file's.
+enum Indent<'a> {
+    Add(&'a str),
+    Remove(&'a str),
+}
+
+impl Indent<'_> {
+    fn apply(&self, line: &str) -> String {
+        match self {
+            _ if is_blank(line) => String::new(),
+            Indent::Add(extra) => format!("{extra}{line}"),
+            Indent::Remove(extra) => line.strip_prefix(extra).unwrap_or(line).to_string(),
+        }
+    }
+}
+
+/// The lines of `lines` that `piece` replaces, first and last, and how to
+/// reindent it.
+fn place<'a>(
+    lines: &'a [String],
+    piece: &[&'a str],
+    ext: &str,
+) -> Option<(usize, usize, Indent<'a>)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in lines {
+        *counts.entry(line.trim()).or_default() += 1;
+    }
+    let mut anchors: Vec<(usize, usize)> = Vec::new();
+    for (i, line) in piece.iter().enumerate() {
+        let code = line.trim();
+        if !code.chars().any(char::is_alphanumeric) || counts.get(code) != Some(&1) {
+            continue;
+        }
+        let j = lines.iter().position(|l| l.trim() == code)?;
+        if anchors.last().is_none_or(|&(_, last)| j > last) {
+            anchors.push((i, j));
+        }
+    }
+    let &(mut i0, mut j0) = anchors.first()?;
+    let indent_of = |line: &'a str| &line[..line.len() - line.trim_start().len()];
+    let (file_indent, piece_indent) = (indent_of(&lines[j0]), indent_of(piece[i0]));
+    let indent = match file_indent.strip_prefix(piece_indent) {
+        Some(extra) => Indent::Add(extra),
+        None => Indent::Remove(piece_indent.strip_prefix(file_indent).unwrap_or("")),
+    };
+    while i0 > 0 && j0 > 0 && piece[i0 - 1].trim() == lines[j0 - 1].trim() {
+        i0 -= 1;
+        j0 -= 1;
+    }
+
+    // The last anchor, or the end of a block opened on an anchor line when
+    // the piece closes it too.
+    let piece_regions = fold_regions(&piece.join("\n"), ext);
+    let file_regions = fold_regions(&lines.join("\n"), ext);
+    let region_end = |regions: &[(usize, usize)], start: usize| {
+        regions.iter().find(|r| r.0 == start).map(|r| r.1)
+    };
+    let (mut i1, mut j1) = anchors
+        .iter()
+        .filter_map(|&(i, j)| {
+            Some((
+                region_end(&piece_regions, i)?,
+                region_end(&file_regions, j)?,
+            ))
+        })
+        .chain(anchors.last().copied())
+        .max_by_key(|&(_, j)| j)?;
+    while i1 + 1 < piece.len()
+        && j1 + 1 < lines.len()
+        && piece[i1 + 1].trim() == lines[j1 + 1].trim()
+    {
+        i1 += 1;
+        j1 += 1;
+    }
+    Some((j0, j1, indent))
+}
@@ -0,0 +1,157 @@
+//! Line bookmarks with optional notes.
+//!
+//! Bookmarks are kept sorted by file and line so the Bookmarks panel can list
+//! them grouped by file and [`Bookmarks::next`] / [`Bookmarks::previous`] can
+//! walk them in order, wrapping around at either end. The IDE stores one
+//! [`Bookmarks`] per workspace in the session file.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A bookmarked line (0-based) with an optional note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub path: PathBuf,
+    pub line: u32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+impl Bookmark {
+    fn key(&self) -> (&Path, u32) {
+        (&self.path, self.line)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<Bookmark>", into = "Vec<Bookmark>")]
+pub struct Bookmarks {
+    items: Vec<Bookmark>,
+}
+
+impl From<Vec<Bookmark>> for Bookmarks {
+    /// Sorts, and keeps the first of any bookmarks on the same line, so a
+    /// hand-edited session file can't break the ordering.
+    fn from(mut items: Vec<Bookmark>) -> Self {
+        items.sort_by(|a, b| a.key().cmp(&b.key()));
+        items.dedup_by(|a, b| a.key() == b.key());
+        Self { items }
+    }
+}
+
+impl From<Bookmarks> for Vec<Bookmark> {
+    fn from(bookmarks: Bookmarks) -> Self {
+        bookmarks.items
+    }
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// All bookmarks, ordered by file then line.
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.items.iter()
+    }
+
+    /// Add a bookmark on `line`, or remove the one already there. Returns
+    /// whether the line is bookmarked afterwards.
+    pub fn toggle(&mut self, path: &Path, line: u32) -> bool {
+        match self.position(path, line) {
+            Ok(i) => {
+                self.items.remove(i);
+                false
+            }
+            Err(i) => {
+                self.items.insert(
+                    i,
+                    Bookmark {
+                        path: path.to_path_buf(),
+                        line,
+                        note: String::new(),
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    pub fn get(&self, path: &Path, line: u32) -> Option<&Bookmark> {
+        self.position(path, line).ok().map(|i| &self.items[i])
+    }
+
+    /// Set the note of the bookmark on `line`. Returns false if the line
+    /// isn't bookmarked.
+    pub fn set_note(&mut self, path: &Path, line: u32, note: &str) -> bool {
+        match self.position(path, line) {
+            Ok(i) => {
+                self.items[i].note = note.trim().to_string();
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path, line: u32) -> bool {
+        match self.position(path, line) {
+            Ok(i) => {
+                self.items.remove(i);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Bookmarked lines in `path`, in order.
+    pub fn lines_in(&self, path: &Path) -> Vec<u32> {
+        self.items
+            .iter()
+            .filter(|b| b.path == path)
+            .map(|b| b.line)
+            .collect()
+    }
+
+    /// Bookmarks grouped by file, files and lines in order.
+    pub fn by_file(&self) -> Vec<(PathBuf, Vec<Bookmark>)> {
+        let mut groups: Vec<(PathBuf, Vec<Bookmark>)> = Vec::new();
+        for b in &self.items {
+            match groups.last_mut() {
+                Some((path, list)) if *path == b.path => list.push(b.clone()),
+                _ => groups.push((b.path.clone(), vec![b.clone()])),
+            }
+        }
+        groups
+    }
+
+    /// The first bookmark after `line` in `path`, continuing into later files
+    /// and wrapping around to the first bookmark.
+    pub fn next(&self, path: &Path, line: u32) -> Option<&Bookmark> {
+        self.items
+            .iter()
+            .find(|b| b.key() > (path, line))
+            .or_else(|| self.items.first())
+    }
+
+    /// The last bookmark before `line` in `path`, continuing into earlier
+    /// files and wrapping around to the last bookmark.
+    pub fn previous(&self, path: &Path, line: u32) -> Option<&Bookmark> {
+        self.items
+            .iter()
+            .rev()
+            .find(|b| b.key() < (path, line))
+            .or_else(|| self.items.last())
+    }
+
+    fn position(&self, path: &Path, line: u32) -> Result<usize, usize> {
+        self.items.binary_search_by(|b| b.key().cmp(&(path, line)))
+    }
+}
@@ -0,0 +1,202 @@
+//! Comparing two texts line by line, for the compare view: the runs of
+//! changed lines, the rows showing them side by side or inline, the words
+//! that differ within a changed line, and copying a change to the other
+//! side.
+//!
+//! Lines keep their endings, so copying a change carries them across and
+//! a line differing only in its ending counts as changed.
+
+use std::ops::Range;
+
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+/// One of the two texts compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn other(self) -> Self {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+/// A run of changed lines: the `left` lines stand where the right text
+/// has its `right` lines. One of the two is empty when only one side has
+/// lines there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub left: Range<usize>,
+    pub right: Range<usize>,
+}
+
+/// A row of the compare view: a line of either text or of both, 0-based,
+/// and the index of the change it shows, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Row {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    pub change: Option<usize>,
+}
+
+/// The lines of `text` as they're compared, each with its line ending.
+pub fn lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// The changes from `left` to `right`, in order.
+pub fn changes(left: &str, right: &str) -> Vec<Change> {
+    let _span = tracing::trace_span!("compare_changes").entered();
+    let (left, right) = (lines(left), lines(right));
+    let mut changes: Vec<Change> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, &left, &right) {
+        if matches!(op, DiffOp::Equal { .. }) {
+            continue;
+        }
+        let (_, old, new) = op.as_tag_tuple();
+        // A deletion next to an insertion is one change.
+        match changes.last_mut() {
+            Some(last) if last.left.end == old.start && last.right.end == new.start => {
+                last.left.end = old.end;
+                last.right.end = new.end;
+            }
+            _ => changes.push(Change {
+                left: old,
+                right: new,
+            }),
+        }
+    }
+    changes
+}
+
+/// Rows pairing the lines of texts with `left_len` and `right_len` lines:
+/// unchanged lines with each other, and within a change, its left lines
+/// with its right ones in order, the shorter side padded.
+pub fn side_by_side(changes: &[Change], left_len: usize, right_len: usize) -> Vec<Row> {
+    rows(changes, left_len, right_len, |rows, k, change| {
+        for j in 0..change.left.len().max(change.right.len()) {
+            rows.push(Row {
+                left: (j < change.left.len()).then(|| change.left.start + j),
+                right: (j < change.right.len()).then(|| change.right.start + j),
+                change: Some(k),
+            });
+        }
+    })
+}
+
+/// Rows listing the lines in one column: unchanged lines once, and each
+/// change's left lines followed by its right ones.
+pub fn inline(changes: &[Change], left_len: usize, right_len: usize) -> Vec<Row> {
+    rows(changes, left_len, right_len, |rows, k, change| {
+        rows.extend(change.left.clone().map(|l| Row {
+            left: Some(l),
+            right: None,
+            change: Some(k),
+        }));
+        rows.extend(change.right.clone().map(|r| Row {
+            left: None,
+            right: Some(r),
+            change: Some(k),
+        }));
+    })
+}
+
+fn rows(
+    changes: &[Change],
+    left_len: usize,
+    right_len: usize,
+    mut push_change: impl FnMut(&mut Vec<Row>, usize, &Change),
+) -> Vec<Row> {
+    let mut rows = Vec::with_capacity(left_len.max(right_len));
+    let push_equal = |rows: &mut Vec<Row>, left: Range<usize>, right_start: usize| {
+        rows.extend(left.enumerate().map(|(j, l)| Row {
+            left: Some(l),
+            right: Some(right_start + j),
+            change: None,
+        }));
+    };
+    let (mut l, mut r) = (0, 0);
+    for (k, change) in changes.iter().enumerate() {
+        push_equal(&mut rows, l..change.left.start, r);
+        push_change(&mut rows, k, change);
+        (l, r) = (change.left.end, change.right.end);
+    }
+    // What follows the last change is the same on both sides.
+    push_equal(&mut rows, l..left_len, r);
+    rows
+}
+
+/// The byte ranges of `old` and of `new` that differ, compared a word,
+/// a run of spaces or a punctuation mark at a time. Adjacent ranges are
+/// joined, and line endings left out.
+pub fn intraline(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old = tokens(old.trim_end_matches(['\n', '\r']));
+    let new = tokens(new.trim_end_matches(['\n', '\r']));
+    let span = |tokens: &[(usize, &str)], range: Range<usize>| match tokens.get(range.clone()) {
+        Some([first, .., last]) => first.0..last.0 + last.1.len(),
+        Some([only]) => only.0..only.0 + only.1.len(),
+        _ => 0..0,
+    };
+    let push = |ranges: &mut Vec<Range<usize>>, range: Range<usize>| match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ if range.is_empty() => {}
+        _ => ranges.push(range),
+    };
+    let old_words: Vec<&str> = old.iter().map(|t| t.1).collect();
+    let new_words: Vec<&str> = new.iter().map(|t| t.1).collect();
+    let (mut old_ranges, mut new_ranges) = (Vec::new(), Vec::new());
+    for op in capture_diff_slices(Algorithm::Myers, &old_words, &new_words) {
+        if matches!(op, DiffOp::Equal { .. }) {
+            continue;
+        }
+        let (_, o, n) = op.as_tag_tuple();
+        push(&mut old_ranges, span(&old, o));
+        push(&mut new_ranges, span(&new, n));
+    }
+    (old_ranges, new_ranges)
+}
+
+/// `line` as words, runs of whitespace and single other characters, each
+/// with its byte offset.
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut tokens: Vec<(usize, &str)> = Vec::new();
+    for (i, c) in line.char_indices() {
+        match tokens.last_mut() {
+            Some((start, token))
+                if class(c) != 2 && token.chars().next().map(class) == Some(class(c)) =>
+            {
+                *token = &line[*start..i + c.len_utf8()];
+            }
+            _ => tokens.push((i, &line[i..i + c.len_utf8()])),
+        }
+    }
+    tokens
+}
+
+/// The text of side `to` with `change`'s lines there replaced by the other
+/// side's.
+pub fn copy_change(left: &str, right: &str, change: &Change, to: Side) -> String {
+    let (from, from_range, target, target_range) = match to {
+        Side::Right => (left, &change.left, right, &change.right),
+        Side::Left => (right, &change.right, left, &change.left),
+    };
+    let (from, target) = (lines(from), lines(target));
+    let mut text = target[..target_range.start].concat();
+    text.push_str(&from[from_range.clone()].concat());
+    text.push_str(&target[target_range.end..].concat());
+    text
+}
@@ -0,0 +1,218 @@
+//! What the editor draws from a document's text besides the text itself:
+//! fold ranges, bracket pairs with their depth, and the guides joining
+//! brackets on distant lines.
+//!
+//! [`Derived::compute`] works all of them out at once for one revision of
+//! the text, and a [`DerivedCache`] keeps the latest so a revision is never
+//! worked out twice. The editor bumps its revision on every edit and only
+//! asks once the edits pause, so a burst of typing costs one pass.
+
+use std::sync::Arc;
+
+/// Larger texts get no fold ranges.
+pub const MAX_FOLD_BYTES: usize = 500_000;
+/// Larger texts get no bracket pairs or guides.
+pub const MAX_BRACKET_BYTES: usize = 300_000;
+
+/// The derived state of one revision of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Derived {
+    pub revision: u64,
+    /// `(start_line, end_line)` of each foldable region, as
+    /// [`fold_regions`](super::folding::fold_regions) finds them.
+    pub fold_ranges: Vec<(usize, usize)>,
+    /// `(open_byte, close_byte, depth)` of each `()`, `[]` and `{}` pair.
+    pub bracket_pairs: Vec<(usize, usize, usize)>,
+    /// `(open_line, open_col, close_line, depth)` of the pairs more than a
+    /// line apart, for drawing indent guides between them.
+    pub bracket_guides: Vec<(usize, usize, usize, usize)>,
+}
+
+impl Derived {
+    /// `ext` is the file's extension, for the folds that depend on the
+    /// language.
+    pub fn compute(revision: u64, text: &str, ext: &str) -> Self {
+        let _span = tracing::debug_span!("derive", revision, bytes = text.len()).entered();
+        let fold_ranges = if text.len() <= MAX_FOLD_BYTES {
+            super::folding::fold_regions(text, ext)
+        } else {
+            Vec::new()
+        };
+        let (bracket_pairs, bracket_guides) = if text.len() <= MAX_BRACKET_BYTES {
+            let pairs = bracket_pairs(text);
+            let guides = bracket_guides(text, &pairs);
+            (pairs, guides)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        Self {
+            revision,
+            fold_ranges,
+            bracket_pairs,
+            bracket_guides,
+        }
+    }
+}
+
+/// The [`Derived`] state of a document's latest revision.
+#[derive(Debug, Default)]
+pub struct DerivedCache {
+    ext: String,
+    latest: Option<Arc<Derived>>,
+}
+
+impl DerivedCache {
+    /// A cache for a document whose file has extension `ext`.
+    pub fn new(ext: &str) -> Self {
+        Self {
+            ext: ext.to_string(),
+            latest: None,
+        }
+    }
+
+    /// The state of `revision`, worked out from `text` unless it already
+    /// has been.
+    pub fn get(&mut self, revision: u64, text: impl FnOnce() -> String) -> Arc<Derived> {
+        match &self.latest {
+            Some(derived) if derived.revision == revision => derived.clone(),
+            _ => {
+                let derived = Arc::new(Derived::compute(revision, &text(), &self.ext));
+                self.latest = Some(derived.clone());
+                derived
+            }
+        }
+    }
+
+    pub fn latest(&self) -> Option<Arc<Derived>> {
+        self.latest.clone()
+    }
+}
+
+/// Foldable regions found by brace matching: `(start_line, end_line)` where
+/// `start_line` has the `{` and `end_line` the matching `}`, sorted by start.
+/// Blocks on a single line aren't foldable.
+pub fn fold_ranges(text: &str) -> Vec<(usize, usize)> {
+    let _span = tracing::trace_span!("fold_ranges").entered();
+    let mut stack: Vec<usize> = Vec::new();
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut in_string = false;
+    let mut string_char = '"';
+    let mut prev_char = '\0';
+
+    for (line_idx, line) in text.lines().enumerate() {
+        for ch in line.chars() {
+            if in_string {
+                if ch == string_char && prev_char != '\\' {
+                    in_string = false;
+                }
+            } else {
+                match ch {
+                    '"' | '\'' => {
+                        in_string = true;
+                        string_char = ch;
+                    }
+                    '{' => stack.push(line_idx),
+                    '}' => {
+                        if let Some(start) = stack.pop() {
+                            if line_idx > start {
+                                ranges.push((start, line_idx));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            prev_char = ch;
+        }
+        // Strings don't span lines in this simplified parser.
+        in_string = false;
+        prev_char = '\0';
+    }
+    ranges.sort_by_key(|&(s, _)| s);
+    ranges
+}
+
+/// Every `{}`, `()` and `[]` pair as `(open_byte, close_byte, depth)`, depth
+/// 0-based, skipping strings and `//` comments.
+pub fn bracket_pairs(text: &str) -> Vec<(usize, usize, usize)> {
+    let _span = tracing::trace_span!("bracket_pairs").entered();
+    let mut stack: Vec<(usize, usize)> = Vec::new(); // (byte_pos, depth)
+    let mut result: Vec<(usize, usize, usize)> = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut string_char = '"';
+    let mut in_line_comment = false;
+    let mut prev = '\0';
+
+    for (byte_pos, ch) in text.char_indices() {
+        if in_line_comment {
+            if ch == '\n' {
+                in_line_comment = false;
+            }
+        } else if in_string {
+            if ch == string_char && prev != '\\' {
+                in_string = false;
+            }
+        } else {
+            match ch {
+                '/' if prev == '/' => {
+                    in_line_comment = true;
+                }
+                '"' | '\'' => {
+                    in_string = true;
+                    string_char = ch;
+                }
+                '(' | '[' | '{' => {
+                    stack.push((byte_pos, depth));
+                    depth += 1;
+                }
+                ')' | ']' | '}' => {
+                    if let Some((open_pos, open_depth)) = stack.pop() {
+                        result.push((open_pos, byte_pos, open_depth));
+                        depth = open_depth;
+                    }
+                }
+                _ => {}
+            }
+        }
+        prev = ch;
+    }
+    result
+}
+
+/// The guides for `pairs` of `text`: `(open_line, open_col, close_line,
+/// depth)` for each pair whose brackets are more than a line apart, the
+/// column in bytes.
+pub fn bracket_guides(
+    text: &str,
+    pairs: &[(usize, usize, usize)],
+) -> Vec<(usize, usize, usize, usize)> {
+    let _span = tracing::trace_span!("bracket_guides", pairs = pairs.len()).entered();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset) - 1;
+    pairs
+        .iter()
+        .filter_map(|&(open, close, depth)| {
+            let open_line = line_of(open);
+            let close_line = line_of(close);
+            (close_line > open_line + 1).then(|| {
+                let open_col = open - line_starts[open_line];
+                (open_line, open_col, close_line, depth)
+            })
+        })
+        .collect()
+}
+
+/// The 0-based lines of diagnostics given as `(1-based line, severity)`,
+/// sorted by line.
+pub fn diag_lines<S>(diags: impl IntoIterator<Item = (u32, S)>) -> Vec<(usize, S)> {
+    let _span = tracing::trace_span!("diag_lines").entered();
+    let mut lines: Vec<(usize, S)> = diags
+        .into_iter()
+        .map(|(line, severity)| (line.saturating_sub(1) as usize, severity))
+        .collect();
+    lines.sort_by_key(|&(line, _)| line);
+    lines
+}
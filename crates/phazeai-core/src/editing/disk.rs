@@ -0,0 +1,173 @@
+//! Saving files safely: [`write_atomic`] replaces a file in full or not at
+//! all, and a [`DiskStamp`] taken when a file is read or saved tells whether
+//! something else has changed it since, so the editor can offer to reload
+//! or merge instead of writing over it.
+
+use std::ffi::OsString;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// Keeps temporary files of concurrent writes to one file apart.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Replace `path` with `bytes` by writing a temporary file beside it and
+/// renaming that over the original, so a crash leaves the old contents or
+/// the new, never a truncated file. The file keeps its permissions, its
+/// owner and group where the user may set them, and a symlink keeps
+/// pointing at it.
+///
+/// A file with other hard links is written in place instead, since a
+/// rename would leave the other links with the old contents; that write
+/// isn't atomic, and a crash part way through can leave it truncated.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let target = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if hard_linked(&target) {
+        return write_in_place(&target, bytes);
+    }
+    let Some(name) = target.file_name() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a file", path.display()),
+        ));
+    };
+    let mut temp_name = OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp = target.with_file_name(temp_name);
+
+    let file = create_temp(&temp, &target)?;
+    let result = write_and_rename(file, &temp, &target, bytes);
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Create `temp`, failing if something is already there, with `target`'s
+/// permission bits from the start so the new contents are never readable
+/// by more users than the old.
+fn create_temp(temp: &Path, target: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if let Ok(metadata) = std::fs::metadata(target) {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(metadata.permissions().mode() & 0o777);
+    }
+    #[cfg(not(unix))]
+    let _ = target;
+    options.open(temp)
+}
+
+fn write_and_rename(
+    mut file: std::fs::File,
+    temp: &Path,
+    target: &Path,
+    bytes: &[u8],
+) -> std::io::Result<()> {
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    drop(file);
+    if let Ok(metadata) = std::fs::metadata(target) {
+        std::fs::set_permissions(temp, metadata.permissions())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // Only root may give a file away, so this is best effort.
+            let _ = std::os::unix::fs::chown(temp, Some(metadata.uid()), Some(metadata.gid()));
+        }
+    }
+    std::fs::rename(temp, target)
+}
+
+#[cfg(unix)]
+fn hard_linked(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.nlink() > 1)
+}
+
+#[cfg(not(unix))]
+fn hard_linked(_path: &Path) -> bool {
+    false
+}
+
+fn write_in_place(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// How a file compares with a [`DiskStamp`] of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiskChange {
+    Unchanged,
+    Modified,
+    Deleted,
+}
+
+/// A file's contents as of a read or write: its modification time and
+/// length, which are cheap to compare, and a hash of the bytes for when
+/// those differ but the contents may not (a `touch`, a checkout of the same
+/// revision).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+fn hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl DiskStamp {
+    /// The stamp of `path` as it is now.
+    pub fn of(path: &Path) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::written(path, &bytes)
+    }
+
+    /// The stamp of `path` just after `bytes` were read from or written
+    /// to it.
+    pub fn written(path: &Path, bytes: &[u8]) -> std::io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: bytes.len() as u64,
+            hash: hash(bytes),
+        })
+    }
+
+    /// Whether `path` still holds what it did when this stamp was taken.
+    pub fn check(&self, path: &Path) -> DiskChange {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return DiskChange::Deleted,
+            // Unreadable now; a save will report why.
+            Err(_) => return DiskChange::Unchanged,
+        };
+        if metadata.len() == self.len && metadata.modified().ok() == self.modified {
+            return DiskChange::Unchanged;
+        }
+        match std::fs::read(path) {
+            Ok(bytes) if bytes.len() as u64 == self.len && hash(&bytes) == self.hash => {
+                DiskChange::Unchanged
+            }
+            Ok(_) => DiskChange::Modified,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => DiskChange::Deleted,
+            Err(_) => DiskChange::Unchanged,
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Text encodings the editor can open and save files in.
+//!
+//! Files are read as bytes and decoded: a byte-order mark decides, then text
+//! that looks like UTF-16 without a mark, then valid UTF-8, and anything else
+//! opens as Latin-1, which every byte sequence is. Saving encodes back into
+//! the file's encoding, byte-order mark or not, so a Latin-1 or UTF-16 file
+//! stays one unless it's converted on purpose.
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    /// UTF-16 without a byte-order mark.
+    Utf16Le,
+    Utf16LeBom,
+    Utf16Be,
+    Utf16BeBom,
+    /// ISO-8859-1: each byte is the character with that code point.
+    Latin1,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 7] = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf8Bom,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16LeBom,
+        TextEncoding::Utf16Be,
+        TextEncoding::Utf16BeBom,
+        TextEncoding::Latin1,
+    ];
+
+    /// As shown in the status bar.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 with BOM",
+            TextEncoding::Utf16Le => "UTF-16 LE",
+            TextEncoding::Utf16LeBom => "UTF-16 LE with BOM",
+            TextEncoding::Utf16Be => "UTF-16 BE",
+            TextEncoding::Utf16BeBom => "UTF-16 BE with BOM",
+            TextEncoding::Latin1 => "Latin-1",
+        }
+    }
+
+    /// The encoding `bytes` are most likely in.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(UTF8_BOM) {
+            TextEncoding::Utf8Bom
+        } else if bytes.starts_with(UTF16_LE_BOM) {
+            TextEncoding::Utf16LeBom
+        } else if bytes.starts_with(UTF16_BE_BOM) {
+            TextEncoding::Utf16BeBom
+        } else if let Some(utf16) = unmarked_utf16(bytes) {
+            // Before UTF-8, which ASCII in UTF-16 (NULs and all) also is.
+            utf16
+        } else if std::str::from_utf8(bytes).is_ok() {
+            TextEncoding::Utf8
+        } else {
+            TextEncoding::Latin1
+        }
+    }
+
+    /// `bytes` as text, without the byte-order mark; `None` when they
+    /// aren't valid in this encoding.
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        match self {
+            TextEncoding::Utf8 => String::from_utf8(bytes.to_vec()).ok(),
+            TextEncoding::Utf8Bom => {
+                String::from_utf8(bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes).to_vec()).ok()
+            }
+            TextEncoding::Utf16Le | TextEncoding::Utf16LeBom => decode_utf16(
+                bytes.strip_prefix(UTF16_LE_BOM).unwrap_or(bytes),
+                u16::from_le_bytes,
+            ),
+            TextEncoding::Utf16Be | TextEncoding::Utf16BeBom => decode_utf16(
+                bytes.strip_prefix(UTF16_BE_BOM).unwrap_or(bytes),
+                u16::from_be_bytes,
+            ),
+            TextEncoding::Latin1 => Some(bytes.iter().map(|&b| b as char).collect()),
+        }
+    }
+
+    /// `text` in this encoding, with a byte-order mark for the encodings
+    /// that have one. Fails, naming the first character, when Latin-1 can't
+    /// hold it.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, String> {
+        let utf16 = |bom: &[u8], unit: fn(u16) -> [u8; 2]| {
+            bom.iter()
+                .copied()
+                .chain(text.encode_utf16().flat_map(unit))
+                .collect()
+        };
+        match self {
+            TextEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+            TextEncoding::Utf8Bom => Ok([UTF8_BOM, text.as_bytes()].concat()),
+            TextEncoding::Utf16Le => Ok(utf16(&[], u16::to_le_bytes)),
+            TextEncoding::Utf16LeBom => Ok(utf16(UTF16_LE_BOM, u16::to_le_bytes)),
+            TextEncoding::Utf16Be => Ok(utf16(&[], u16::to_be_bytes)),
+            TextEncoding::Utf16BeBom => Ok(utf16(UTF16_BE_BOM, u16::to_be_bytes)),
+            TextEncoding::Latin1 => text
+                .chars()
+                .map(|c| {
+                    u8::try_from(u32::from(c))
+                        .map_err(|_| format!("Latin-1 has no '{c}' (U+{:04X})", u32::from(c)))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// UTF-16 without a byte-order mark, told by the zero high bytes of ASCII
+/// characters all falling on one side.
+fn unmarked_utf16(bytes: &[u8]) -> Option<TextEncoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = bytes.len() / 2;
+    let zeros_at = |offset: usize| {
+        bytes
+            .iter()
+            .skip(offset)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    let encoding = if odd * 10 >= pairs * 3 && even == 0 {
+        TextEncoding::Utf16Le
+    } else if even * 10 >= pairs * 3 && odd == 0 {
+        TextEncoding::Utf16Be
+    } else {
+        return None;
+    };
+    encoding.decode(bytes).map(|_| encoding)
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Option<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .ok()
+}
+
+/// `bytes` decoded in the encoding they're detected to be in.
+pub fn decode_text(bytes: &[u8]) -> (String, TextEncoding) {
+    let encoding = TextEncoding::detect(bytes);
+    match encoding.decode(bytes) {
+        Some(text) => (text, encoding),
+        None => (
+            TextEncoding::Latin1.decode(bytes).unwrap_or_default(),
+            TextEncoding::Latin1,
+        ),
+    }
+}
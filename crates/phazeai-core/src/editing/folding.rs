@@ -0,0 +1,264 @@
+//! Fold ranges beyond `{ … }` blocks, and the folds remembered per file.
+//!
+//! [`fold_regions`] adds runs of comment lines and `/* … */` comments,
+//! `#region` / `#endregion` markers, indented Python blocks and groups of
+//! imports to the brace blocks of [`fold_ranges`]. The editor folds by start
+//! line, so where ranges start on the same line only the widest is kept.
+//!
+//! [`FoldMemory`] keeps each file's folded start lines between sessions.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::derived::fold_ranges;
+use super::TypingRules;
+
+/// Every foldable `(start_line, end_line)` of `text`, sorted by start.
+/// `ext`, the file's extension, decides the comment syntax, whether
+/// indentation makes blocks and what an import looks like.
+pub fn fold_regions(text: &str, ext: &str) -> Vec<(usize, usize)> {
+    let _span = tracing::trace_span!("fold_regions").entered();
+    let ext = ext.to_ascii_lowercase();
+    let ext = ext.as_str();
+    let lines: Vec<&str> = text.lines().collect();
+    let token = TypingRules::for_extension(ext).comment_token();
+    let mut ranges = fold_ranges(text);
+    ranges.extend(region_markers(&lines, token));
+    if let Some(token) = token {
+        ranges.extend(comment_blocks(&lines, token));
+    }
+    if matches!(ext, "py" | "pyi") {
+        ranges.extend(indent_blocks(&lines));
+    }
+    ranges.extend(import_groups(&lines, ext));
+
+    ranges.sort_by_key(|&(start, end)| (start, std::cmp::Reverse(end)));
+    ranges.dedup_by_key(|r| r.0);
+    ranges
+}
+
+/// The nesting level of each of `ranges` (as [`fold_regions`] returns them),
+/// 1 for ranges no other range contains.
+pub fn fold_levels(ranges: &[(usize, usize)]) -> Vec<usize> {
+    let mut open: Vec<usize> = Vec::new();
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            // A range starting on the line another ends on, as after
+            // `} else {`, follows it rather than nesting in it.
+            while open.last().is_some_and(|&outer_end| outer_end <= start) {
+                open.pop();
+            }
+            open.push(end);
+            open.len()
+        })
+        .collect()
+}
+
+/// The start lines of the `ranges` at nesting `level`, 1-based.
+pub fn starts_at_level(ranges: &[(usize, usize)], level: usize) -> Vec<usize> {
+    ranges
+        .iter()
+        .zip(fold_levels(ranges))
+        .filter(|&(_, l)| l == level)
+        .map(|(&(start, _), _)| start)
+        .collect()
+}
+
+/// `#region` … `#endregion` pairs, the markers written as line comments
+/// (`// #region`, `# region`) or, as in C#, on their own.
+fn region_markers(lines: &[&str], token: Option<&str>) -> Vec<(usize, usize)> {
+    let mut open = Vec::new();
+    let mut ranges = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        match region_marker(line, token) {
+            Some(Marker::Start) => open.push(i),
+            Some(Marker::End) => {
+                if let Some(start) = open.pop() {
+                    ranges.push((start, i));
+                }
+            }
+            None => {}
+        }
+    }
+    ranges
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    Start,
+    End,
+}
+
+fn region_marker(line: &str, token: Option<&str>) -> Option<Marker> {
+    let mut rest = line.trim_start();
+    if let Some(after) = token.and_then(|t| rest.strip_prefix(t)) {
+        rest = after.trim_start();
+    }
+    let rest = rest.strip_prefix('#').unwrap_or(rest);
+    let word_ends = |after: &str| !after.starts_with(|c: char| c.is_alphanumeric() || c == '_');
+    if let Some(after) = rest.strip_prefix("endregion") {
+        word_ends(after).then_some(Marker::End)
+    } else if let Some(after) = rest.strip_prefix("region") {
+        word_ends(after).then_some(Marker::Start)
+    } else {
+        None
+    }
+}
+
+/// Runs of two or more lines that are only a line comment, and `/* … */`
+/// comments over several lines when `token` is `//`. Region markers and
+/// shebangs aren't part of a run.
+fn comment_blocks(lines: &[&str], token: &str) -> Vec<(usize, usize)> {
+    let is_comment = |line: &str| {
+        let code = line.trim_start();
+        code.starts_with(token)
+            && !code.starts_with("#!")
+            && region_marker(line, Some(token)).is_none()
+    };
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if is_comment(lines[i]) {
+            let start = i;
+            while i + 1 < lines.len() && is_comment(lines[i + 1]) {
+                i += 1;
+            }
+            if i > start {
+                ranges.push((start, i));
+            }
+        } else if token == "//" && lines[i].trim_start().starts_with("/*") {
+            let start = i;
+            let opened = &lines[i].trim_start()[2..];
+            if !opened.contains("*/") {
+                match (i + 1..lines.len()).find(|&j| lines[j].contains("*/")) {
+                    Some(end) => {
+                        ranges.push((start, end));
+                        i = end;
+                    }
+                    None => break,
+                }
+            }
+        }
+        i += 1;
+    }
+    ranges
+}
+
+/// Lines followed by more indented ones, as Python's blocks are: from the
+/// line to the last more indented line before one that isn't. Blank lines
+/// inside a block don't end it.
+fn indent_blocks(lines: &[&str]) -> Vec<(usize, usize)> {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let blank = |line: &str| line.trim().is_empty();
+    let mut ranges = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if blank(line) {
+            continue;
+        }
+        let level = indent(line);
+        let mut end = i;
+        for (j, next) in lines.iter().enumerate().skip(i + 1) {
+            if blank(next) {
+                continue;
+            }
+            if indent(next) <= level {
+                break;
+            }
+            end = j;
+        }
+        if end > i {
+            ranges.push((i, end));
+        }
+    }
+    ranges
+}
+
+/// Runs of import statements, blank lines allowed between them, spanning
+/// more than one line. A statement runs on while it has brackets open, as
+/// in `use std::{…};` or `from x import (…)`.
+fn import_groups(lines: &[&str], ext: &str) -> Vec<(usize, usize)> {
+    let keywords: &[&str] = match ext {
+        "rs" => &["use ", "pub use ", "pub(crate) use ", "extern crate "],
+        "py" | "pyi" => &["import ", "from "],
+        "c" | "h" | "cc" | "cpp" | "hpp" => &["#include"],
+        "cs" => &["using "],
+        _ => &["import "],
+    };
+    let mut ranges = Vec::new();
+    let mut group: Option<(usize, usize)> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let code = lines[i].trim_start();
+        if keywords.iter().any(|k| code.starts_with(k)) {
+            let start = i;
+            let mut depth = bracket_depth(lines[i]);
+            while depth > 0 && i + 1 < lines.len() {
+                i += 1;
+                depth += bracket_depth(lines[i]);
+            }
+            group = Some((group.map_or(start, |(s, _)| s), i));
+        } else if !code.is_empty() {
+            ranges.extend(group.take().filter(|(s, e)| e > s));
+        }
+        i += 1;
+    }
+    ranges.extend(group.filter(|(s, e)| e > s));
+    ranges
+}
+
+/// Opening minus closing brackets on `line`.
+fn bracket_depth(line: &str) -> i32 {
+    line.chars()
+        .map(|c| match c {
+            '(' | '[' | '{' => 1,
+            ')' | ']' | '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// The folded start lines of each file, as kept in the session file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FoldMemory {
+    files: BTreeMap<PathBuf, Vec<u32>>,
+}
+
+impl FoldMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The folded start lines of `path`, in order.
+    pub fn get(&self, path: &Path) -> &[u32] {
+        self.files.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Remember `lines` as the folded starts of `path`, forgetting the file
+    /// when there are none. Returns whether anything changed.
+    pub fn set(&mut self, path: &Path, lines: impl IntoIterator<Item = u32>) -> bool {
+        let mut lines: Vec<u32> = lines.into_iter().collect();
+        lines.sort_unstable();
+        lines.dedup();
+        if self.get(path) == lines.as_slice() {
+            return false;
+        }
+        if lines.is_empty() {
+            self.files.remove(path);
+        } else {
+            self.files.insert(path.to_path_buf(), lines);
+        }
+        true
+    }
+
+    /// Forget the files `keep` returns false for.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Path) -> bool) {
+        self.files.retain(|path, _| keep(path));
+    }
+}
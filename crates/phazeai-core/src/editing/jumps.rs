@@ -0,0 +1,141 @@
+//! Navigation history for the editor: a jump list like vim's `Ctrl+O` /
+//! `Ctrl+I` or VS Code's Go Back / Go Forward.
+//!
+//! The editor reports every cursor move with [`JumpList::observe`]; moves to
+//! another file or of [`JUMP_LINES`] or more lines are jumps and remember the
+//! location left behind. [`JumpList::back`] and [`JumpList::forward`] walk the
+//! list without recording the moves they cause.
+
+use std::path::PathBuf;
+
+/// Lines the cursor has to move within a file for the move to be a jump.
+pub const JUMP_LINES: u32 = 10;
+
+/// Locations kept; the oldest are dropped first.
+const MAX_JUMPS: usize = 100;
+
+/// A cursor position: file, 0-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: u32,
+    pub col: u32,
+}
+
+impl Location {
+    pub fn new(path: impl Into<PathBuf>, line: u32, col: u32) -> Self {
+        Self {
+            path: path.into(),
+            line,
+            col,
+        }
+    }
+
+    fn same_line(&self, other: &Location) -> bool {
+        self.path == other.path && self.line == other.line
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JumpList {
+    entries: Vec<Location>,
+    /// Where `back`/`forward` have walked to; `entries.len()` when at the
+    /// newest location.
+    pos: usize,
+    /// Where `back`/`forward` sent the cursor; moves on the way there aren't
+    /// recorded.
+    expected: Option<Location>,
+}
+
+impl JumpList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether moving the cursor from `from` to `to` is a jump.
+    pub fn is_jump(from: &Location, to: &Location) -> bool {
+        from.path != to.path || from.line.abs_diff(to.line) >= JUMP_LINES
+    }
+
+    /// Report a cursor move, recording `from` if the move was a jump.
+    pub fn observe(&mut self, from: &Location, to: &Location) {
+        if let Some(target) = &self.expected {
+            if to.path == target.path {
+                // Landed, or the user moved on from wherever the file opened.
+                if to.line == target.line || !Self::is_jump(from, to) {
+                    self.expected = None;
+                }
+                return;
+            }
+            self.expected = None;
+        }
+        if Self::is_jump(from, to) {
+            self.record(from.clone());
+        }
+    }
+
+    /// Remember `location` as the newest entry. Anything ahead of the current
+    /// position is dropped, as is an older entry for the same line.
+    pub fn record(&mut self, location: Location) {
+        self.entries.truncate(self.pos);
+        self.entries.retain(|e| !e.same_line(&location));
+        self.entries.push(location);
+        if self.entries.len() > MAX_JUMPS {
+            self.entries.remove(0);
+        }
+        self.pos = self.entries.len();
+    }
+
+    /// The location before `current`, if any. The first step back also
+    /// remembers `current` so `forward` can return to it.
+    pub fn back(&mut self, current: Location) -> Option<Location> {
+        if self.pos == self.entries.len() {
+            if !self.entries.last().is_some_and(|e| e.same_line(&current)) {
+                self.entries.push(current);
+            }
+            self.pos = self.entries.len() - 1;
+        }
+        if self.pos == 0 {
+            return None;
+        }
+        self.pos -= 1;
+        self.go(self.pos)
+    }
+
+    /// The location `back` last came from, if any.
+    pub fn forward(&mut self) -> Option<Location> {
+        if self.pos + 1 >= self.entries.len() {
+            return None;
+        }
+        self.pos += 1;
+        self.go(self.pos)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.pos > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.pos + 1 < self.entries.len()
+    }
+
+    /// Visited locations, most recent first, one per line.
+    pub fn recent(&self, limit: usize) -> Vec<Location> {
+        let mut out: Vec<Location> = Vec::new();
+        for entry in self.entries.iter().rev() {
+            if !out.iter().any(|o| o.same_line(entry)) {
+                out.push(entry.clone());
+            }
+            if out.len() == limit {
+                break;
+            }
+        }
+        out
+    }
+
+    fn go(&mut self, index: usize) -> Option<Location> {
+        let location = self.entries.get(index)?.clone();
+        self.expected = Some(location.clone());
+        Some(location)
+    }
+}
@@ -0,0 +1,47 @@
+//! Finding which line endings a text uses and converting between them.
+
+use crate::project::editorconfig::LineEnding;
+
+/// The line endings of a text as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndings {
+    /// Every line ends the same way; a text without line breaks is `LF`.
+    Uniform(LineEnding),
+    /// Both kinds; `most` is the more common one (`LF` on a tie).
+    Mixed { most: LineEnding },
+}
+
+impl LineEndings {
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        match (crlf, lf) {
+            (0, _) => LineEndings::Uniform(LineEnding::Lf),
+            (_, 0) => LineEndings::Uniform(LineEnding::Crlf),
+            (crlf, lf) => LineEndings::Mixed {
+                most: if crlf > lf {
+                    LineEnding::Crlf
+                } else {
+                    LineEnding::Lf
+                },
+            },
+        }
+    }
+
+    /// `LF`, `CRLF` or `Mixed`, as shown in the status bar.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEndings::Uniform(ending) => ending.label(),
+            LineEndings::Mixed { .. } => "Mixed",
+        }
+    }
+}
+
+/// `text` with every line ending `ending`.
+pub fn convert_line_endings(text: &str, ending: LineEnding) -> String {
+    let lf = text.replace("\r\n", "\n");
+    match ending {
+        LineEnding::Lf => lf,
+        LineEnding::Crlf => lf.replace('\n', "\r\n"),
+    }
+}
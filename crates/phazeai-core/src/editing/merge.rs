@@ -0,0 +1,134 @@
+//! Line-based three-way merge, for a buffer edited in the editor while the
+//! file under it changed on disk.
+//!
+//! Both sides are diffed against the text they started from; changes to
+//! different lines are combined, and where both sides changed the same or
+//! adjacent lines differently, both versions are kept between conflict
+//! markers as git writes them.
+
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+pub const MARKER_OURS: &str = "<<<<<<< editor";
+pub const MARKER_SEPARATOR: &str = "=======";
+pub const MARKER_THEIRS: &str = ">>>>>>> disk";
+
+/// The result of [`merge3`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merged {
+    pub text: String,
+    /// Places where both versions were kept between markers.
+    pub conflicts: usize,
+}
+
+/// A changed range of the base and what one side has there instead.
+#[derive(Debug, Clone, Copy)]
+struct Hunk {
+    base: (usize, usize),
+    side: (usize, usize),
+}
+
+fn hunks(base: &[&str], side: &[&str]) -> Vec<Hunk> {
+    capture_diff_slices(Algorithm::Myers, base, side)
+        .into_iter()
+        .filter(|op| !matches!(op, DiffOp::Equal { .. }))
+        .map(|op| {
+            let (_, old, new) = op.as_tag_tuple();
+            Hunk {
+                base: (old.start, old.end),
+                side: (new.start, new.end),
+            }
+        })
+        .collect()
+}
+
+/// The lines of one side standing in for `base[start..end]`, given the
+/// side's hunks within that range.
+fn side_range(hunks: &[Hunk], start: usize, end: usize, offset: isize) -> (usize, usize) {
+    match (hunks.first(), hunks.last()) {
+        (Some(first), Some(last)) => (
+            first.side.0 - (first.base.0 - start),
+            last.side.1 + (end - last.base.1),
+        ),
+        _ => (
+            (start as isize + offset) as usize,
+            (end as isize + offset) as usize,
+        ),
+    }
+}
+
+fn push_lines(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        out.push_str(line);
+    }
+}
+
+fn push_block(out: &mut String, marker: &str, lines: &[&str]) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(marker);
+    out.push('\n');
+    push_lines(out, lines);
+}
+
+/// Merge `ours` and `theirs`, both edited from `base`.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Merged {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let ours_hunks = hunks(&base, &ours);
+    let theirs_hunks = hunks(&base, &theirs);
+
+    let mut out = String::new();
+    let mut conflicts = 0;
+    let (mut i, mut j) = (0, 0);
+    let (mut ours_offset, mut theirs_offset) = (0isize, 0isize);
+    let mut pos = 0;
+    while i < ours_hunks.len() || j < theirs_hunks.len() {
+        let start = match (ours_hunks.get(i), theirs_hunks.get(j)) {
+            (Some(a), Some(b)) => a.base.0.min(b.base.0),
+            (Some(a), None) => a.base.0,
+            (None, Some(b)) => b.base.0,
+            (None, None) => unreachable!(),
+        };
+        // Every hunk of either side touching the group joins it.
+        let (ours_from, theirs_from) = (i, j);
+        let mut end = start;
+        loop {
+            if let Some(h) = ours_hunks.get(i).filter(|h| h.base.0 <= end) {
+                end = end.max(h.base.1);
+                i += 1;
+            } else if let Some(h) = theirs_hunks.get(j).filter(|h| h.base.0 <= end) {
+                end = end.max(h.base.1);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+        push_lines(&mut out, &base[pos..start]);
+
+        let ours_group = &ours_hunks[ours_from..i];
+        let theirs_group = &theirs_hunks[theirs_from..j];
+        let (os, oe) = side_range(ours_group, start, end, ours_offset);
+        let (ts, te) = side_range(theirs_group, start, end, theirs_offset);
+        let (ours_lines, theirs_lines) = (&ours[os..oe], &theirs[ts..te]);
+        if theirs_group.is_empty() || ours_lines == theirs_lines {
+            push_lines(&mut out, ours_lines);
+        } else if ours_group.is_empty() {
+            push_lines(&mut out, theirs_lines);
+        } else {
+            conflicts += 1;
+            push_block(&mut out, MARKER_OURS, ours_lines);
+            push_block(&mut out, MARKER_SEPARATOR, theirs_lines);
+            push_block(&mut out, MARKER_THEIRS, &[]);
+        }
+        ours_offset = oe as isize - end as isize;
+        theirs_offset = te as isize - end as isize;
+        pos = end;
+    }
+    push_lines(&mut out, &base[pos..]);
+    Merged {
+        text: out,
+        conflicts,
+    }
+}
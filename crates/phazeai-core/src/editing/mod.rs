@@ -0,0 +1,27 @@
+//! Editor logic that doesn't need the UI: typing assists, the navigation
+//! jump list, line bookmarks, the state derived from a document's text and
+//! its fold regions, comparing two texts, applying a chat reply's code
+//! blocks, encodings and line endings, and saving safely alongside whatever
+//! else writes the file.
+
+pub mod apply;
+pub mod bookmarks;
+pub mod compare;
+pub mod derived;
+pub mod disk;
+pub mod encoding;
+pub mod folding;
+pub mod jumps;
+pub mod line_endings;
+pub mod merge;
+pub mod typing;
+
+pub use bookmarks::{Bookmark, Bookmarks};
+pub use derived::{Derived, DerivedCache};
+pub use disk::{write_atomic, DiskChange, DiskStamp};
+pub use encoding::{decode_text, TextEncoding};
+pub use folding::{fold_regions, FoldMemory};
+pub use jumps::{JumpList, Location};
+pub use line_endings::{convert_line_endings, LineEndings};
+pub use merge::{merge3, Merged};
+pub use typing::{closing_bracket, EnterIndent, TypingRules};
@@ -0,0 +1,216 @@
+//! Typing assists for the editor: auto-closing pairs, indentation on Enter
+//! and wrapping a selection in brackets or quotes.
+//!
+//! These are text rules keyed by file extension; the editor view decides
+//! when a key was typed and applies the edits.
+
+/// Python statements after which the next line dedents.
+const PYTHON_BLOCK_ENDERS: [&str; 5] = ["return", "pass", "break", "continue", "raise"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lang {
+    Rust,
+    Python,
+    /// `//` comments and `'` strings or chars: C, Go, Java, JS/TS...
+    CLike,
+    /// `#` comments: shell, TOML, YAML, Ruby...
+    Hash,
+    /// Prose, where `'` is an apostrophe: Markdown, plain text.
+    Prose,
+    Other,
+}
+
+/// The closing bracket for an opening one.
+pub fn closing_bracket(open: char) -> Option<char> {
+    match open {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// Typing rules for one language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypingRules {
+    lang: Lang,
+}
+
+/// What to insert after Enter: `indent` on the new line, and when the cursor
+/// was between a pair, `closing` — the indent of the line the closer moves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnterIndent {
+    pub indent: String,
+    pub closing: Option<String>,
+}
+
+impl TypingRules {
+    pub fn for_extension(ext: &str) -> Self {
+        let lang = match ext.to_ascii_lowercase().as_str() {
+            "rs" => Lang::Rust,
+            "py" | "pyi" => Lang::Python,
+            "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "go" | "java" | "kt" | "swift" | "js"
+            | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "dart" | "scala" | "php" | "css" | "scss"
+            | "json" => Lang::CLike,
+            "sh" | "bash" | "zsh" | "fish" | "toml" | "yaml" | "yml" | "rb" | "pl" | "r"
+            | "conf" | "ini" | "dockerfile" | "makefile" => Lang::Hash,
+            "md" | "markdown" | "txt" | "rst" | "adoc" | "" => Lang::Prose,
+            _ => Lang::Other,
+        };
+        Self { lang }
+    }
+
+    /// The character to insert after `open` was typed, with `before` the
+    /// line's text before it and `after` the character after the cursor.
+    ///
+    /// Brackets close unless they'd land before a word. Quotes also stay
+    /// open when they end a string, follow a word character or backslash,
+    /// or are typed in a comment; `'` never closes in prose, nor for Rust
+    /// lifetimes (`&'a`, `<'a>`).
+    pub fn auto_close(&self, open: char, before: &str, after: Option<char>) -> Option<char> {
+        let blocked_after = after.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        if let Some(close) = closing_bracket(open) {
+            return (!blocked_after).then_some(close);
+        }
+        let quote = match open {
+            '"' => true,
+            '\'' => !matches!(self.lang, Lang::Prose),
+            '`' => matches!(self.lang, Lang::CLike | Lang::Hash | Lang::Prose),
+            _ => false,
+        };
+        if !quote || blocked_after || after == Some(open) {
+            return None;
+        }
+        let prev = before.chars().last();
+        if prev.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '\\' || c == open) {
+            return None;
+        }
+        if open == '\''
+            && self.lang == Lang::Rust
+            && (matches!(prev, Some('&' | '<'))
+                || before.matches('<').count() > before.matches('>').count())
+        {
+            return None;
+        }
+        if self.in_comment(before) || unescaped_count(before, open) % 2 == 1 {
+            return None;
+        }
+        Some(open)
+    }
+
+    /// Whether typing `ch` right before the same character should step over
+    /// it instead, as after an auto-inserted closer.
+    pub fn types_over(&self, ch: char, after: Option<char>) -> bool {
+        matches!(ch, ')' | ']' | '}' | '"' | '\'' | '`') && after == Some(ch)
+    }
+
+    /// Whether Backspace after `open` should also delete `close`: the cursor
+    /// sat between an empty pair.
+    pub fn deletes_pair(&self, open: char, close: char) -> bool {
+        closing_bracket(open) == Some(close) || (open == close && matches!(open, '"' | '\'' | '`'))
+    }
+
+    /// The closer when `open` is typed over a selection, wrapping it.
+    pub fn surround(&self, open: char) -> Option<char> {
+        match open {
+            '"' | '\'' | '`' => Some(open),
+            '*' | '_' if self.lang == Lang::Prose => Some(open),
+            _ => closing_bracket(open),
+        }
+    }
+
+    /// Indentation for a new line after Enter, from the `previous` line and
+    /// `rest`, the text that moved to the new line with the cursor; `unit` is
+    /// one indent level.
+    ///
+    /// Keeps the previous line's indent, adds a level after an opening
+    /// bracket (a Python `:`, a Rust `=>` match arm without a body), and
+    /// drops one after Python's `return`, `pass`, `break`, `continue` and
+    /// `raise`.
+    pub fn enter(&self, previous: &str, rest: &str, unit: &str) -> EnterIndent {
+        let code = self.strip_comment(previous).trim_end();
+        let base = &previous[..previous.len() - previous.trim_start().len()];
+        let last = code.chars().last();
+        let opens = last.is_some_and(|c| closing_bracket(c).is_some())
+            || (self.lang == Lang::Python && last == Some(':'))
+            || (self.lang == Lang::Rust && code.ends_with("=>"));
+        if opens {
+            let closing = last
+                .and_then(closing_bracket)
+                .filter(|close| rest.trim_start().starts_with(*close))
+                .map(|_| base.to_string());
+            return EnterIndent {
+                indent: format!("{base}{unit}"),
+                closing,
+            };
+        }
+        let first_word = code.split_whitespace().next().unwrap_or("");
+        let indent = if self.lang == Lang::Python && PYTHON_BLOCK_ENDERS.contains(&first_word) {
+            base.strip_suffix(unit)
+                .or_else(|| base.strip_suffix('\t'))
+                .unwrap_or(base)
+        } else {
+            base
+        };
+        EnterIndent {
+            indent: indent.to_string(),
+            closing: None,
+        }
+    }
+
+    /// The token that starts a line comment, if the language has one.
+    pub fn comment_token(&self) -> Option<&'static str> {
+        match self.lang {
+            Lang::Rust | Lang::CLike => Some("//"),
+            Lang::Python | Lang::Hash => Some("#"),
+            Lang::Prose | Lang::Other => None,
+        }
+    }
+
+    /// Whether the end of `line` is inside a line comment.
+    fn in_comment(&self, line: &str) -> bool {
+        self.comment_start(line).is_some()
+    }
+
+    fn strip_comment<'a>(&self, line: &'a str) -> &'a str {
+        match self.comment_start(line) {
+            Some(at) => &line[..at],
+            None => line,
+        }
+    }
+
+    /// Where a line comment starts in `line`, ignoring tokens in strings.
+    fn comment_start(&self, line: &str) -> Option<usize> {
+        let token = self.comment_token()?;
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+        for (i, c) in line.char_indices() {
+            match quote {
+                Some(_) if escaped => escaped = false,
+                Some(_) if c == '\\' => escaped = true,
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' => quote = Some(c),
+                None if line[i..].starts_with(token) => return Some(i),
+                None => {}
+            }
+        }
+        None
+    }
+}
+
+/// Occurrences of `quote` in `text` not preceded by a backslash.
+fn unescaped_count(text: &str, quote: char) -> usize {
+    let mut count = 0;
+    let mut escaped = false;
+    for c in text.chars() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            count += 1;
+        }
+    }
+    count
+}
@@ -20,6 +20,9 @@ pub enum PhazeError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
     #[error("Agent exceeded maximum iterations ({0})")]
     MaxIterations(usize),
 
@@ -31,6 +34,9 @@ pub enum PhazeError {
 
     #[error("Agent execution cancelled")]
     Cancelled,
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
 }
 
 impl PhazeError {
@@ -0,0 +1,697 @@
+//! Prompt and model-route evaluation used by `phazeai eval`.
+//!
+//! A suite is a TOML file of `[[case]]` entries, each a prompt plus a list
+//! of `[[case.assert]]` checks. Every case runs once per [`EvalTarget`]
+//! (a provider/model pair, or the configured default including
+//! `model_routes`) and the results are collected into an [`EvalReport`]
+//! that renders as a comparison table or JSON.
+//!
+//! ```toml
+//! system_prompt = "You are a terse Rust expert."
+//!
+//! [[target]]
+//! provider = "ollama"
+//! model = "qwen2.5-coder:7b"
+//!
+//! [[case]]
+//! name = "hello"
+//! prompt = "Write a Rust hello world. Code only."
+//!
+//! [[case.assert]]
+//! type = "regex"
+//! value = 'fn\s+main'
+//!
+//! [[case.assert]]
+//! type = "llm_grade"
+//! rubric = "Compiles and prints a greeting."
+//! ```
+
+use std::path::Path;
+use std::time::Instant;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::{LlmProvider, Settings};
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+
+/// Default minimum judge score (0.0–1.0) for an `llm_grade` assertion to pass.
+const DEFAULT_GRADE_THRESHOLD: f64 = 0.7;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalSuite {
+    /// System prompt for every case unless the target or case overrides it.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Targets used when none are given on the command line.
+    #[serde(default, rename = "target")]
+    pub targets: Vec<EvalTarget>,
+    /// Model that grades `llm_grade` assertions (default: the configured model).
+    #[serde(default)]
+    pub judge: Option<EvalTarget>,
+    #[serde(default, rename = "case")]
+    pub cases: Vec<EvalCase>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalCase {
+    pub name: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    #[serde(default, rename = "assert")]
+    pub assertions: Vec<Assertion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    /// Whole answer equals `value` after trimming whitespace.
+    Exact {
+        value: String,
+    },
+    Contains {
+        value: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    NotContains {
+        value: String,
+        #[serde(default)]
+        ignore_case: bool,
+    },
+    Regex {
+        value: String,
+    },
+    /// Ask the judge model to score the answer against `rubric`.
+    LlmGrade {
+        rubric: String,
+        #[serde(default = "default_grade_threshold")]
+        threshold: f64,
+    },
+}
+
+fn default_grade_threshold() -> f64 {
+    DEFAULT_GRADE_THRESHOLD
+}
+
+impl Assertion {
+    /// Short description used in reports.
+    pub fn describe(&self) -> String {
+        match self {
+            Assertion::Exact { value } => format!("exact {value:?}"),
+            Assertion::Contains { value, .. } => format!("contains {value:?}"),
+            Assertion::NotContains { value, .. } => format!("not_contains {value:?}"),
+            Assertion::Regex { value } => format!("regex /{value}/"),
+            Assertion::LlmGrade { threshold, .. } => format!("llm_grade ≥ {threshold:.2}"),
+        }
+    }
+}
+
+/// A provider/model combination to evaluate.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EvalTarget {
+    /// Column label; defaults to `provider/model`.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Overrides the suite system prompt for this target.
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Keep the configured `model_routes` so the router picks the model per case.
+    #[serde(default)]
+    pub routes: bool,
+}
+
+impl EvalTarget {
+    /// Parse a command-line target: `provider/model`, `provider`, or
+    /// `default` (the configured model with its routes).
+    pub fn parse(spec: &str) -> Result<Self, PhazeError> {
+        let spec = spec.trim();
+        if spec.eq_ignore_ascii_case("default") || spec.eq_ignore_ascii_case("routes") {
+            return Ok(Self {
+                name: Some("default".to_string()),
+                provider: None,
+                model: None,
+                system_prompt: None,
+                routes: true,
+            });
+        }
+        // Only the first '/' separates the provider: OpenRouter models contain more.
+        let (provider, model) = match spec.split_once('/') {
+            Some((p, m)) => (p, Some(m.to_string())),
+            None => (spec, None),
+        };
+        if LlmProvider::from_name(provider).is_none() {
+            return Err(PhazeError::Config(format!(
+                "unknown provider '{provider}' in target '{spec}'"
+            )));
+        }
+        Ok(Self {
+            name: None,
+            provider: Some(provider.to_string()),
+            model: model.filter(|m| !m.is_empty()),
+            system_prompt: None,
+            routes: false,
+        })
+    }
+
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match (&self.provider, &self.model) {
+            (Some(p), Some(m)) => format!("{p}/{m}"),
+            (Some(p), None) => p.clone(),
+            (None, Some(m)) => m.clone(),
+            (None, None) => "default".to_string(),
+        }
+    }
+
+    /// Build a client for this target on top of `base` settings.
+    pub fn build_client(&self, base: &Settings) -> Result<Box<dyn LlmClient>, PhazeError> {
+        let mut settings = base.clone();
+        if let Some(provider) = &self.provider {
+            settings.llm.provider = LlmProvider::from_name(provider)
+                .ok_or_else(|| PhazeError::Config(format!("unknown provider '{provider}'")))?;
+        }
+        if let Some(model) = &self.model {
+            settings.llm.model = model.clone();
+        }
+        if !self.routes {
+            settings.model_routes.clear();
+        }
+        settings.build_llm_client()
+    }
+}
+
+impl EvalSuite {
+    pub fn parse(text: &str) -> Result<Self, PhazeError> {
+        let suite: Self = toml::from_str(text).map_err(|e| PhazeError::Config(e.to_string()))?;
+        if suite.cases.is_empty() {
+            return Err(PhazeError::Config(
+                "eval suite has no [[case]] entries".into(),
+            ));
+        }
+        for case in &suite.cases {
+            for assertion in &case.assertions {
+                if let Assertion::Regex { value } = assertion {
+                    Regex::new(value).map_err(|e| {
+                        PhazeError::Config(format!("case '{}': invalid regex: {e}", case.name))
+                    })?;
+                }
+            }
+        }
+        Ok(suite)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PhazeError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssertionResult {
+    pub assertion: String,
+    pub passed: bool,
+    /// 0.0–1.0; deterministic checks score 0 or 1.
+    pub score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AssertionResult {
+    fn binary(assertion: &Assertion, passed: bool) -> Self {
+        Self {
+            assertion: assertion.describe(),
+            passed,
+            score: if passed { 1.0 } else { 0.0 },
+            detail: None,
+        }
+    }
+}
+
+/// Evaluate a deterministic assertion. Returns `None` for `llm_grade`,
+/// which needs a judge model (see [`grade`]).
+pub fn check(assertion: &Assertion, output: &str) -> Option<AssertionResult> {
+    let passed = match assertion {
+        Assertion::Exact { value } => output.trim() == value.trim(),
+        Assertion::Contains { value, ignore_case } => contains(output, value, *ignore_case),
+        Assertion::NotContains { value, ignore_case } => !contains(output, value, *ignore_case),
+        Assertion::Regex { value } => Regex::new(value).is_ok_and(|re| re.is_match(output)),
+        Assertion::LlmGrade { .. } => return None,
+    };
+    Some(AssertionResult::binary(assertion, passed))
+}
+
+fn contains(haystack: &str, needle: &str, ignore_case: bool) -> bool {
+    if ignore_case {
+        haystack.to_lowercase().contains(&needle.to_lowercase())
+    } else {
+        haystack.contains(needle)
+    }
+}
+
+const JUDGE_PROMPT: &str = r#"You grade answers produced by another AI model.
+Score how well the ANSWER satisfies the RUBRIC for the given PROMPT, from 0
+(fails completely) to 10 (fully satisfies it). Judge only against the rubric.
+
+Answer with JSON and nothing else: {"score": <0-10>, "reason": "<one sentence>"}"#;
+
+/// Grade `output` with the judge model. The score is normalised to 0.0–1.0.
+pub async fn grade(
+    judge: &dyn LlmClient,
+    rubric: &str,
+    prompt: &str,
+    output: &str,
+) -> Result<(f64, String), PhazeError> {
+    let messages = vec![
+        Message::system(JUDGE_PROMPT),
+        Message::user(format!(
+            "PROMPT:\n{prompt}\n\nRUBRIC:\n{rubric}\n\nANSWER:\n{output}"
+        )),
+    ];
+    let response = judge.chat(&messages, &[]).await?;
+    parse_grade(&response.message.content).ok_or_else(|| {
+        PhazeError::Other(format!(
+            "judge answer has no score: {}",
+            response.message.content.trim()
+        ))
+    })
+}
+
+/// Extract `(score 0.0–1.0, reason)` from a judge answer. Accepts the JSON
+/// object asked for, or falls back to a bare `N/10` in prose.
+pub fn parse_grade(text: &str) -> Option<(f64, String)> {
+    if let (Some(start), Some(end)) = (text.find('{'), text.rfind('}')) {
+        if start < end {
+            if let Ok(v) = serde_json::from_str::<Value>(&text[start..=end]) {
+                if let Some(score) = v.get("score").and_then(Value::as_f64) {
+                    let reason = v
+                        .get("reason")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string();
+                    return Some(((score / 10.0).clamp(0.0, 1.0), reason));
+                }
+            }
+        }
+    }
+    let re = Regex::new(r"(\d+(?:\.\d+)?)\s*/\s*10\b").ok()?;
+    let score: f64 = re.captures(text)?.get(1)?.as_str().parse().ok()?;
+    Some(((score / 10.0).clamp(0.0, 1.0), text.trim().to_string()))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub case: String,
+    pub target: String,
+    pub output: String,
+    /// Set when the model call itself failed; the case counts as failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub assertions: Vec<AssertionResult>,
+    pub latency_ms: u64,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.assertions.iter().all(|a| a.passed)
+    }
+
+    /// Mean assertion score; a case without assertions scores 1 if it ran.
+    pub fn score(&self) -> f64 {
+        if self.error.is_some() {
+            return 0.0;
+        }
+        if self.assertions.is_empty() {
+            return 1.0;
+        }
+        self.assertions.iter().map(|a| a.score).sum::<f64>() / self.assertions.len() as f64
+    }
+}
+
+/// Run one case against one model and score it.
+pub async fn run_case(
+    client: &dyn LlmClient,
+    judge: Option<&dyn LlmClient>,
+    target: &str,
+    system_prompt: Option<&str>,
+    case: &EvalCase,
+) -> CaseResult {
+    let mut messages = Vec::new();
+    if let Some(system) = case.system_prompt.as_deref().or(system_prompt) {
+        messages.push(Message::system(system));
+    }
+    messages.push(Message::user(&case.prompt));
+
+    let started = Instant::now();
+    let response = client.chat(&messages, &[]).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let mut result = CaseResult {
+        case: case.name.clone(),
+        target: target.to_string(),
+        output: String::new(),
+        error: None,
+        assertions: Vec::new(),
+        latency_ms,
+        input_tokens: 0,
+        output_tokens: 0,
+    };
+    let response = match response {
+        Ok(r) => r,
+        Err(e) => {
+            result.error = Some(e.to_string());
+            return result;
+        }
+    };
+    if let Some(usage) = &response.usage {
+        result.input_tokens = usage.input_tokens;
+        result.output_tokens = usage.output_tokens;
+    }
+    result.output = response.message.content;
+
+    for assertion in &case.assertions {
+        if let Some(r) = check(assertion, &result.output) {
+            result.assertions.push(r);
+            continue;
+        }
+        let Assertion::LlmGrade { rubric, threshold } = assertion else {
+            continue;
+        };
+        let (score, detail) = match judge {
+            Some(judge) => match grade(judge, rubric, &case.prompt, &result.output).await {
+                Ok((score, reason)) => (score, reason),
+                Err(e) => (0.0, format!("judge failed: {e}")),
+            },
+            None => (0.0, "no judge model configured".to_string()),
+        };
+        result.assertions.push(AssertionResult {
+            assertion: assertion.describe(),
+            passed: score >= *threshold,
+            score,
+            detail: Some(detail).filter(|d| !d.is_empty()),
+        });
+    }
+    result
+}
+
+/// A target ready to run: its label, client and system prompt override.
+pub struct PreparedTarget {
+    pub label: String,
+    pub client: Box<dyn LlmClient>,
+    pub system_prompt: Option<String>,
+}
+
+/// Run every case against every target, in suite order.
+pub async fn run_suite(
+    suite: &EvalSuite,
+    targets: &[PreparedTarget],
+    judge: Option<&dyn LlmClient>,
+    mut on_result: impl FnMut(&CaseResult),
+) -> EvalReport {
+    let mut results = Vec::new();
+    for target in targets {
+        let system = target
+            .system_prompt
+            .as_deref()
+            .or(suite.system_prompt.as_deref());
+        for case in &suite.cases {
+            let result = run_case(target.client.as_ref(), judge, &target.label, system, case).await;
+            on_result(&result);
+            results.push(result);
+        }
+    }
+    EvalReport {
+        cases: suite.cases.iter().map(|c| c.name.clone()).collect(),
+        targets: targets.iter().map(|t| t.label.clone()).collect(),
+        results,
+    }
+}
+
+/// Renders one summary cell from a target's results.
+type SummaryCell = fn(&[&CaseResult]) -> String;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub cases: Vec<String>,
+    pub targets: Vec<String>,
+    pub results: Vec<CaseResult>,
+}
+
+impl EvalReport {
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(CaseResult::passed)
+    }
+
+    fn result(&self, case: &str, target: &str) -> Option<&CaseResult> {
+        self.results
+            .iter()
+            .find(|r| r.case == case && r.target == target)
+    }
+
+    fn for_target<'a>(&'a self, target: &'a str) -> impl Iterator<Item = &'a CaseResult> + 'a {
+        self.results.iter().filter(move |r| r.target == target)
+    }
+
+    /// Comparison table: one row per case, one column per target, followed
+    /// by pass rate, mean score, mean latency and token totals.
+    pub fn to_table(&self) -> String {
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut header = vec!["case".to_string()];
+        header.extend(self.targets.iter().cloned());
+        rows.push(header);
+
+        for case in &self.cases {
+            let mut row = vec![case.clone()];
+            for target in &self.targets {
+                row.push(match self.result(case, target) {
+                    Some(r) if r.error.is_some() => "error".to_string(),
+                    Some(r) => {
+                        let mark = if r.passed() { "✓" } else { "✗" };
+                        format!("{mark} {:.2}", r.score())
+                    }
+                    None => "-".to_string(),
+                });
+            }
+            rows.push(row);
+        }
+
+        let summary: [(&str, SummaryCell); 4] = [
+            ("pass rate", |rs| {
+                let passed = rs.iter().filter(|r| r.passed()).count();
+                format!("{passed}/{}", rs.len())
+            }),
+            ("mean score", |rs| {
+                let total: f64 = rs.iter().map(|r| r.score()).sum();
+                format!("{:.2}", total / rs.len().max(1) as f64)
+            }),
+            ("mean latency", |rs| {
+                let total: u64 = rs.iter().map(|r| r.latency_ms).sum();
+                format!("{}ms", total / rs.len().max(1) as u64)
+            }),
+            ("tokens in/out", |rs| {
+                let input: u32 = rs.iter().map(|r| r.input_tokens).sum();
+                let output: u32 = rs.iter().map(|r| r.output_tokens).sum();
+                format!("{input}/{output}")
+            }),
+        ];
+        for (label, cell) in summary {
+            let mut row = vec![label.to_string()];
+            for target in &self.targets {
+                let rs: Vec<&CaseResult> = self.for_target(target).collect();
+                row.push(cell(&rs));
+            }
+            rows.push(row);
+        }
+
+        render_table(&rows, self.cases.len() + 1)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let summary: Vec<Value> = self
+            .targets
+            .iter()
+            .map(|target| {
+                let rs: Vec<&CaseResult> = self.for_target(target).collect();
+                let n = rs.len().max(1) as f64;
+                json!({
+                    "target": target,
+                    "passed": rs.iter().filter(|r| r.passed()).count(),
+                    "total": rs.len(),
+                    "mean_score": rs.iter().map(|r| r.score()).sum::<f64>() / n,
+                    "mean_latency_ms": rs.iter().map(|r| r.latency_ms as f64).sum::<f64>() / n,
+                })
+            })
+            .collect();
+        json!({ "summary": summary, "results": self.results })
+    }
+}
+
+/// Left-aligned plain-text table with a rule under the header and before
+/// row `summary_start`.
+fn render_table(rows: &[Vec<String>], summary_start: usize) -> String {
+    let cols = rows.first().map_or(0, Vec::len);
+    let widths: Vec<usize> = (0..cols)
+        .map(|c| rows.iter().map(|r| r[c].chars().count()).max().unwrap_or(0))
+        .collect();
+    let rule = widths
+        .iter()
+        .map(|w| "─".repeat(*w))
+        .collect::<Vec<_>>()
+        .join("─┼─");
+
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        if i == 1 || (i == summary_start && i < rows.len()) {
+            out.push_str(&rule);
+            out.push('\n');
+        }
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!("{cell}{}", " ".repeat(w - cell.chars().count())))
+            .collect::<Vec<_>>()
+            .join(" │ ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{LlmResponse, StreamEvent, Usage};
+    use crate::tools::ToolDefinition;
+
+    /// Answers every prompt with the same text.
+    struct Canned(&'static str);
+
+    #[async_trait::async_trait]
+    impl LlmClient for Canned {
+        async fn chat(
+            &self,
+            _messages: &[Message],
+            _tools: &[ToolDefinition],
+        ) -> Result<LlmResponse, PhazeError> {
+            Ok(LlmResponse {
+                message: Message::assistant(self.0),
+                usage: Some(Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                }),
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _messages: &[Message],
+            _tools: &[ToolDefinition],
+        ) -> Result<futures::channel::mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+            Err(PhazeError::Other("not streamed".into()))
+        }
+    }
+
+    const SUITE: &str = r#"
+[[case]]
+name = "hello"
+prompt = "Write hello world in Rust"
+
+[[case.assert]]
+type = "regex"
+value = 'fn\s+main'
+
+[[case.assert]]
+type = "not_contains"
+value = "unsafe"
+
+[[case.assert]]
+type = "llm_grade"
+rubric = "Prints a greeting"
+threshold = 0.5
+"#;
+
+    #[test]
+    fn test_parse_suite_and_targets() {
+        let suite = EvalSuite::parse(SUITE).unwrap();
+        assert_eq!(suite.cases.len(), 1);
+        assert_eq!(suite.cases[0].assertions.len(), 3);
+        assert!(EvalSuite::parse(
+            "[[case]]\nname='x'\nprompt='y'\n[[case.assert]]\ntype='regex'\nvalue='('"
+        )
+        .is_err());
+
+        let t = EvalTarget::parse("openrouter/anthropic/claude-3.5-sonnet").unwrap();
+        assert_eq!(t.provider.as_deref(), Some("openrouter"));
+        assert_eq!(t.model.as_deref(), Some("anthropic/claude-3.5-sonnet"));
+        assert!(EvalTarget::parse("default").unwrap().routes);
+        assert!(EvalTarget::parse("nope/model").is_err());
+    }
+
+    #[test]
+    fn test_deterministic_checks() {
+        let exact = Assertion::Exact { value: "42".into() };
+        assert!(check(&exact, " 42\n").unwrap().passed);
+        let contains = Assertion::Contains {
+            value: "HELLO".into(),
+            ignore_case: true,
+        };
+        assert!(check(&contains, "hello world").unwrap().passed);
+        let grade = Assertion::LlmGrade {
+            rubric: "x".into(),
+            threshold: 0.5,
+        };
+        assert!(check(&grade, "anything").is_none());
+    }
+
+    #[test]
+    fn test_parse_grade() {
+        let (score, reason) =
+            parse_grade("```json\n{\"score\": 8, \"reason\": \"ok\"}\n```").unwrap();
+        assert!((score - 0.8).abs() < 1e-9);
+        assert_eq!(reason, "ok");
+        let (score, _) = parse_grade("I'd give this 3/10.").unwrap();
+        assert!((score - 0.3).abs() < 1e-9);
+        assert!(parse_grade("looks fine").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_suite_scores_and_table() {
+        let suite = EvalSuite::parse(SUITE).unwrap();
+        let targets = vec![
+            PreparedTarget {
+                label: "good".into(),
+                client: Box::new(Canned("fn main() { println!(\"hi\"); }")),
+                system_prompt: None,
+            },
+            PreparedTarget {
+                label: "bad".into(),
+                client: Box::new(Canned("unsafe { main() }")),
+                system_prompt: None,
+            },
+        ];
+        let judge = Canned(r#"{"score": 6, "reason": "greets"}"#);
+        let report = run_suite(&suite, &targets, Some(&judge), |_| {}).await;
+
+        assert_eq!(report.results.len(), 2);
+        assert!(report.results[0].passed());
+        assert!(!report.results[1].passed());
+        assert!(!report.all_passed());
+
+        let table = report.to_table();
+        assert!(table.contains("good"));
+        assert!(table.contains("✗ 0.20"));
+        assert!(table.contains("1/1"));
+        assert_eq!(report.to_json()["summary"][1]["passed"], 0);
+    }
+}
@@ -0,0 +1,141 @@
+//! Per-line `git blame`, parsed from `--porcelain` output.
+//!
+//! Blame is loaded lazily by the editor and cached per file until the file
+//! changes on disk, so moving the cursor or scrolling never re-runs git. The
+//! least recently blamed files are dropped once the cache outgrows its
+//! [`Subsystem::Blame`] budget.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+use super::GitOps;
+use crate::memory::{self, BudgetLru, Subsystem};
+
+type Cached = (Option<SystemTime>, Arc<Vec<BlameLine>>);
+
+static CACHE: LazyLock<Mutex<BudgetLru<PathBuf, Cached>>> = LazyLock::new(Default::default);
+
+/// Who last changed one line of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameLine {
+    /// Full commit hash; all zeros for lines that aren't committed yet.
+    pub commit: String,
+    pub author: String,
+    /// Author time, in seconds since the Unix epoch.
+    pub author_time: i64,
+    /// First line of the commit message.
+    pub summary: String,
+}
+
+impl BlameLine {
+    pub fn is_uncommitted(&self) -> bool {
+        self.commit.bytes().all(|b| b == b'0')
+    }
+
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(8)]
+    }
+
+    /// Roughly what the line takes up in memory.
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.commit.len() + self.author.len() + self.summary.len()
+    }
+}
+
+#[derive(Default, Clone)]
+struct CommitInfo {
+    author: String,
+    author_time: i64,
+    summary: String,
+}
+
+/// Parse `git blame --porcelain` output into one entry per line of the file.
+/// Commit details are only printed the first time a commit appears, so they
+/// are remembered by hash.
+pub fn parse_blame_porcelain(text: &str) -> Vec<BlameLine> {
+    let mut commits: HashMap<String, CommitInfo> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current: Option<String> = None;
+    for line in text.lines() {
+        if line.starts_with('\t') {
+            if let Some(commit) = current.take() {
+                let info = commits.get(&commit).cloned().unwrap_or_default();
+                lines.push(BlameLine {
+                    commit,
+                    author: info.author,
+                    author_time: info.author_time,
+                    summary: info.summary,
+                });
+            }
+            continue;
+        }
+        let Some(commit) = current.as_ref() else {
+            let hash = line.split(' ').next().unwrap_or_default();
+            if hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                commits.entry(hash.to_string()).or_default();
+                current = Some(hash.to_string());
+            }
+            continue;
+        };
+        let info = commits.entry(commit.clone()).or_default();
+        if let Some(author) = line.strip_prefix("author ") {
+            info.author = author.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            info.author_time = time.trim().parse().unwrap_or_default();
+        } else if let Some(summary) = line.strip_prefix("summary ") {
+            info.summary = summary.to_string();
+        }
+    }
+    // Output is trimmed, which can swallow the tab line of an empty last line.
+    if let Some(commit) = current {
+        let info = commits.remove(&commit).unwrap_or_default();
+        lines.push(BlameLine {
+            commit,
+            author: info.author,
+            author_time: info.author_time,
+            summary: info.summary,
+        });
+    }
+    lines
+}
+
+/// `timestamp` relative to `now` (both Unix seconds), e.g. "3 days ago".
+pub fn relative_time(timestamp: i64, now: i64) -> String {
+    let secs = (now - timestamp).max(0);
+    let (n, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (secs / 60, "minute"),
+        3600..=86_399 => (secs / 3600, "hour"),
+        86_400..=2_591_999 => (secs / 86_400, "day"),
+        2_592_000..=31_535_999 => (secs / 2_592_000, "month"),
+        _ => (secs / 31_536_000, "year"),
+    };
+    let s = if n == 1 { "" } else { "s" };
+    format!("{n} {unit}{s} ago")
+}
+
+impl GitOps {
+    /// Blame for every line of `path` as last saved, cached until the file's
+    /// modification time changes.
+    pub async fn blame(&self, path: &Path) -> Result<Arc<Vec<BlameLine>>, String> {
+        let mtime = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let Some((cached_mtime, lines)) = CACHE.lock().unwrap().get(path) {
+            if *cached_mtime == mtime {
+                return Ok(Arc::clone(lines));
+            }
+        }
+        let file = path.to_string_lossy();
+        let output = self.run_git(&["blame", "--porcelain", "--", &file]).await?;
+        let lines = Arc::new(parse_blame_porcelain(&output));
+        let bytes = lines.iter().map(BlameLine::size).sum();
+        let ledger = memory::global();
+        let mut cache = CACHE.lock().unwrap();
+        cache.insert(path.to_path_buf(), (mtime, Arc::clone(&lines)), bytes);
+        let evicted = cache.evict_to(ledger.budget(Subsystem::Blame));
+        ledger.evicted(Subsystem::Blame, evicted.len());
+        ledger.report(Subsystem::Blame, cache.bytes(), cache.len());
+        Ok(lines)
+    }
+}
@@ -0,0 +1,56 @@
+//! Filing GitHub issues for the repository's `origin`.
+//!
+//! The token comes from `GH_TOKEN` or `GITHUB_TOKEN`, as for the Actions
+//! panel; creating issues needs one with access to the repository.
+
+use super::GitOps;
+use crate::audit::github_repo;
+use crate::constants::endpoints;
+use crate::error::PhazeError;
+
+/// The token used for the GitHub API, if one is set.
+pub fn github_token() -> Option<String> {
+    std::env::var("GH_TOKEN")
+        .or_else(|_| std::env::var("GITHUB_TOKEN"))
+        .ok()
+        .filter(|t| !t.trim().is_empty())
+}
+
+impl GitOps {
+    /// `(owner, repo)` of the `origin` remote when it's on GitHub.
+    pub async fn github_remote(&self) -> Option<(String, String)> {
+        let url = self.run_git(&["remote", "get-url", "origin"]).await.ok()?;
+        github_repo(&url)
+    }
+
+    /// Open an issue on the `origin` repository. Returns its URL.
+    pub async fn create_github_issue(&self, title: &str, body: &str) -> Result<String, PhazeError> {
+        let (owner, repo) = self
+            .github_remote()
+            .await
+            .ok_or_else(|| PhazeError::Other("origin is not a GitHub repository".to_string()))?;
+        let token = github_token().ok_or_else(|| {
+            PhazeError::Config("set GH_TOKEN or GITHUB_TOKEN to file GitHub issues".to_string())
+        })?;
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .user_agent("PhazeAI/1.0 (issues)")
+            .build()?;
+        let response = client
+            .post(format!(
+                "{}/repos/{owner}/{repo}/issues",
+                endpoints::GITHUB_API_URL
+            ))
+            .header("Accept", "application/vnd.github+json")
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "title": title, "body": body }))
+            .send()
+            .await?
+            .error_for_status()?;
+        let issue: serde_json::Value = response.json().await?;
+        issue["html_url"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| PhazeError::Other("GitHub didn't return the new issue".to_string()))
+    }
+}
@@ -0,0 +1,96 @@
+//! Commit history for the repository or a single file, and the actions the
+//! history view offers on a commit.
+
+use std::path::Path;
+
+use super::GitOps;
+
+/// `git log` format: fields split by unit separators, records by record
+/// separators, so subjects may contain any printable text.
+pub(super) const LOG_FORMAT: &str = "--format=%H%x1f%h%x1f%an%x1f%at%x1f%s%x1e";
+
+/// One commit in a history listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    /// Author time, in seconds since the Unix epoch.
+    pub author_time: i64,
+    pub subject: String,
+}
+
+/// Parse `git log` output written with [`LOG_FORMAT`].
+pub fn parse_log(text: &str) -> Vec<CommitSummary> {
+    text.split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_matches(['\n', '\r']).split('\x1f');
+            let hash = fields.next()?.trim().to_string();
+            if hash.is_empty() {
+                return None;
+            }
+            Some(CommitSummary {
+                hash,
+                short_hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                author_time: fields.next()?.trim().parse().unwrap_or_default(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+impl GitOps {
+    /// The last `limit` commits, newest first; only those touching `path`
+    /// (following renames) when it is given.
+    pub async fn history(
+        &self,
+        limit: usize,
+        path: Option<&Path>,
+    ) -> Result<Vec<CommitSummary>, String> {
+        let count = format!("-{limit}");
+        let file = path.map(|p| p.to_string_lossy().into_owned());
+        let mut args = vec!["log", LOG_FORMAT, &count];
+        if let Some(file) = &file {
+            args.extend(["--follow", "--", file]);
+        }
+        Ok(parse_log(&self.run_git(&args).await?))
+    }
+
+    /// The message and patch of `commit` (`git show`), limited to `path`
+    /// when it is given.
+    pub async fn show_commit(&self, commit: &str, path: Option<&Path>) -> Result<String, String> {
+        let file = path.map(|p| p.to_string_lossy().into_owned());
+        let mut args = vec![
+            "show",
+            "--no-color",
+            "--no-ext-diff",
+            "--format=fuller",
+            commit,
+        ];
+        if let Some(file) = &file {
+            args.extend(["--", file]);
+        }
+        self.run_git(&args).await
+    }
+
+    /// Check out `rev`, detaching `HEAD` when it isn't a branch.
+    pub async fn checkout(&self, rev: &str) -> Result<String, String> {
+        self.run_git(&["checkout", rev]).await
+    }
+
+    /// Restore `path` in the working tree and index to its content at `rev`.
+    pub async fn restore_file(&self, rev: &str, path: &Path) -> Result<String, String> {
+        let file = path.to_string_lossy();
+        self.run_git(&["checkout", rev, "--", &file]).await
+    }
+
+    pub async fn cherry_pick(&self, commit: &str) -> Result<String, String> {
+        self.run_git(&["cherry-pick", commit]).await
+    }
+
+    /// Commit the inverse of `commit` with the default message.
+    pub async fn revert(&self, commit: &str) -> Result<String, String> {
+        self.run_git(&["revert", "--no-edit", commit]).await
+    }
+}
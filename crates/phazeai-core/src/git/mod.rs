@@ -1,3 +1,18 @@
+mod blame;
+mod github;
+mod history;
 mod ops;
+mod rebase;
+mod worktree;
 
+pub use blame::{parse_blame_porcelain, relative_time, BlameLine};
+pub use github::github_token;
+pub use history::{parse_log, CommitSummary};
 pub use ops::{FileState, FileStatus, GitOps, GitStatus};
+pub use rebase::{
+    parse_plan_suggestion, suggest_plan, todo_list, validate_plan, ConflictSide, RebaseAction,
+    RebaseState, RebaseStep,
+};
+pub use worktree::{
+    parse_worktree_list, task_branch_name, worktree_path, Worktree, TASK_BRANCH_PREFIX,
+};
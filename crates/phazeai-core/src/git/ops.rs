@@ -38,6 +38,10 @@ impl GitOps {
         }
     }
 
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
     /// Find the git root from a given path by walking up.
     pub fn find_root(start: &Path) -> Option<PathBuf> {
         let mut current = start.to_path_buf();
@@ -51,9 +55,19 @@ impl GitOps {
         }
     }
 
-    async fn run_git(&self, args: &[&str]) -> Result<String, String> {
+    pub(super) async fn run_git(&self, args: &[&str]) -> Result<String, String> {
+        self.run_git_env(args, &[]).await
+    }
+
+    /// [`run_git`](Self::run_git) with extra environment variables.
+    pub(super) async fn run_git_env(
+        &self,
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<String, String> {
         let output = Command::new("git")
             .args(args)
+            .envs(envs.iter().copied())
             .current_dir(&self.repo_root)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -120,6 +134,13 @@ impl GitOps {
         }
     }
 
+    /// Diff of `HEAD` against its merge base with `base` (`git diff base...HEAD`).
+    pub async fn diff_against(&self, base: &str) -> Result<String, String> {
+        let range = format!("{base}...HEAD");
+        self.run_git(&["diff", "--no-color", "--no-ext-diff", &range])
+            .await
+    }
+
     pub async fn add(&self, paths: &[&str]) -> Result<(), String> {
         let mut args = vec!["add"];
         args.extend(paths);
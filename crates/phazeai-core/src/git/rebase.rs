@@ -0,0 +1,373 @@
+//! Interactive rebase without an editor.
+//!
+//! A plan of [`RebaseStep`]s is written as git's todo list and handed to
+//! `git rebase -i` through `GIT_SEQUENCE_EDITOR`. New commit messages are
+//! applied by `exec git commit --amend` lines after each commit group, so git
+//! never waits on an editor. When a step stops on conflicts the rebase stays
+//! in progress until it is continued, skipped or aborted.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::history::LOG_FORMAT;
+use super::{parse_log, CommitSummary, GitOps};
+use crate::error::PhazeError;
+use crate::llm::{LlmClient, Message};
+
+/// Environment that keeps git from opening an editor while rebasing.
+const NO_EDITOR: (&str, &str) = ("GIT_EDITOR", "true");
+
+/// What to do with one commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebaseAction {
+    Pick,
+    /// Keep the commit with a new message.
+    Reword,
+    /// Meld into the previous commit, combining the messages.
+    Squash,
+    /// Meld into the previous commit, keeping its message.
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub const ALL: [RebaseAction; 5] = [
+        Self::Pick,
+        Self::Reword,
+        Self::Squash,
+        Self::Fixup,
+        Self::Drop,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pick => "pick",
+            Self::Reword => "reword",
+            Self::Squash => "squash",
+            Self::Fixup => "fixup",
+            Self::Drop => "drop",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|a| a.as_str().eq_ignore_ascii_case(s.trim()))
+    }
+
+    /// Squash and fixup meld into the commit before them.
+    pub fn melds(self) -> bool {
+        matches!(self, Self::Squash | Self::Fixup)
+    }
+}
+
+/// One line of a rebase plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebaseStep {
+    pub action: RebaseAction,
+    pub hash: String,
+    pub subject: String,
+    /// Message for the commit this step ends up in. On a squash group the
+    /// last message set wins; `None` keeps what git would write.
+    pub message: Option<String>,
+}
+
+impl RebaseStep {
+    pub fn pick(commit: &CommitSummary) -> Self {
+        Self {
+            action: RebaseAction::Pick,
+            hash: commit.hash.clone(),
+            subject: commit.subject.clone(),
+            message: None,
+        }
+    }
+}
+
+/// Where a rebase stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebaseState {
+    /// No rebase in progress.
+    Idle,
+    /// Stopped mid-way; `conflicts` lists unmerged paths (empty when it
+    /// stopped for another reason, e.g. a failing `exec`).
+    Stopped { conflicts: Vec<String> },
+}
+
+/// Which side of a conflict to keep. While rebasing, "ours" is the branch
+/// being rebased onto and "theirs" is the commit being replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictSide {
+    Upstream,
+    Commit,
+}
+
+/// Why a plan can't be run.
+pub fn validate_plan(steps: &[RebaseStep]) -> Result<(), String> {
+    let mut kept = steps.iter().filter(|s| s.action != RebaseAction::Drop);
+    match kept.next() {
+        None => Err("the plan drops every commit".to_string()),
+        Some(first) if first.action.melds() => Err(format!(
+            "{} {} has no earlier commit to meld into",
+            first.action.as_str(),
+            &first.hash[..first.hash.len().min(8)]
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Quote `s` for the POSIX shell git runs `exec` lines with.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// The new message of each commit group in `steps`, keyed by the index of
+/// the group's last step (where its amend goes).
+fn group_messages(steps: &[RebaseStep]) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut group_message: Option<&str> = None;
+    for (i, step) in steps.iter().enumerate() {
+        if step.action == RebaseAction::Drop {
+            continue;
+        }
+        if !step.action.melds() {
+            group_message = None;
+        }
+        if let Some(message) = step.message.as_deref() {
+            group_message = Some(message);
+        }
+        let group_ends = !steps[i + 1..]
+            .iter()
+            .find(|s| s.action != RebaseAction::Drop)
+            .is_some_and(|s| s.action.melds());
+        if let (true, Some(message)) = (group_ends, group_message) {
+            out.push((i, message.to_string()));
+        }
+    }
+    out
+}
+
+/// The todo list for `steps`. `message_file(i)` names the file holding the
+/// new message of the group that ends at step `i`.
+pub fn todo_list(steps: &[RebaseStep], message_file: impl Fn(usize) -> PathBuf) -> String {
+    let amends: Vec<usize> = group_messages(steps).into_iter().map(|(i, _)| i).collect();
+    let mut todo = String::new();
+    for (i, step) in steps.iter().enumerate() {
+        // Rewording is done by the amend, so git picks the commit as is.
+        let action = match step.action {
+            RebaseAction::Reword => RebaseAction::Pick,
+            a => a,
+        };
+        todo.push_str(&format!(
+            "{} {} {}\n",
+            action.as_str(),
+            step.hash,
+            step.subject
+        ));
+        if amends.contains(&i) {
+            todo.push_str(&format!(
+                "exec git commit --amend --only --allow-empty --quiet -F {}\n",
+                shell_quote(&message_file(i).to_string_lossy())
+            ));
+        }
+    }
+    todo
+}
+
+#[derive(Deserialize)]
+struct SuggestedStep {
+    hash: String,
+    action: String,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Turn a model's JSON plan into steps for `commits` (oldest first). The
+/// model's order is kept; unknown hashes are ignored and commits it left
+/// out are picked where they were. Falls back to picking everything when the
+/// reply isn't a usable plan.
+pub fn parse_plan_suggestion(reply: &str, commits: &[CommitSummary]) -> Vec<RebaseStep> {
+    let all_picks = || commits.iter().map(RebaseStep::pick).collect::<Vec<_>>();
+    let (Some(start), Some(end)) = (reply.find('['), reply.rfind(']')) else {
+        return all_picks();
+    };
+    let Ok(suggested) = serde_json::from_str::<Vec<SuggestedStep>>(&reply[start..=end]) else {
+        return all_picks();
+    };
+    let mut steps: Vec<RebaseStep> = Vec::new();
+    for s in suggested {
+        let hash = s.hash.trim();
+        let Some(commit) = commits
+            .iter()
+            .find(|c| hash.len() >= 4 && c.hash.starts_with(hash))
+        else {
+            continue;
+        };
+        if steps.iter().any(|st| st.hash == commit.hash) {
+            continue;
+        }
+        let mut step = RebaseStep::pick(commit);
+        step.action = RebaseAction::parse(&s.action).unwrap_or(RebaseAction::Pick);
+        step.message = s
+            .message
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty() && step.action != RebaseAction::Fixup);
+        steps.push(step);
+    }
+    for (i, commit) in commits.iter().enumerate() {
+        if !steps.iter().any(|s| s.hash == commit.hash) {
+            steps.insert(i.min(steps.len()), RebaseStep::pick(commit));
+        }
+    }
+    if validate_plan(&steps).is_err() {
+        return all_picks();
+    }
+    steps
+}
+
+const SUGGEST_PROMPT: &str = "You clean up git history before it is pushed. \
+Given commits oldest first as `hash subject`, propose an interactive rebase plan: \
+group related commits by moving them next to each other and squashing or fixing \
+them up into the first of the group, drop nothing, and give each kept commit a \
+clear imperative message when its subject is vague (`wip`, `fix`, `more`). \
+Reply with only a JSON array in the order the commits should end up, one object \
+per commit: {\"hash\": \"...\", \"action\": \"pick|reword|squash|fixup\", \
+\"message\": \"new message or empty\"}.";
+
+/// Ask `llm` for squash groupings and reworded messages for `commits`.
+pub async fn suggest_plan(
+    llm: &dyn LlmClient,
+    commits: &[CommitSummary],
+) -> Result<Vec<RebaseStep>, PhazeError> {
+    let listing = commits
+        .iter()
+        .map(|c| format!("{} {}", c.short_hash, c.subject))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let messages = vec![Message::system(SUGGEST_PROMPT), Message::user(listing)];
+    let response = llm.chat(&messages, &[]).await?;
+    Ok(parse_plan_suggestion(&response.message.content, commits))
+}
+
+impl GitOps {
+    /// Commits a rebase onto `base` would rewrite, oldest first; every
+    /// commit when `base` is `None` (`--root`).
+    pub async fn rebase_commits(&self, base: Option<&str>) -> Result<Vec<CommitSummary>, String> {
+        let range = base.map(|b| format!("{b}..HEAD"));
+        let mut args = vec!["log", "--reverse", LOG_FORMAT];
+        args.push(range.as_deref().unwrap_or("HEAD"));
+        Ok(parse_log(&self.run_git(&args).await?))
+    }
+
+    /// A sensible default base: where the branch left its upstream, else
+    /// ten commits back, else the root.
+    pub async fn default_rebase_base(&self) -> Option<String> {
+        if let Ok(base) = self.run_git(&["merge-base", "HEAD", "@{upstream}"]).await {
+            return Some(base);
+        }
+        let count: usize = self
+            .run_git(&["rev-list", "--count", "HEAD"])
+            .await
+            .ok()?
+            .parse()
+            .ok()?;
+        (count > 10).then(|| "HEAD~10".to_string())
+    }
+
+    /// Run `steps` as `git rebase -i` onto `base` (`None` for `--root`).
+    pub async fn start_rebase(
+        &self,
+        base: Option<&str>,
+        steps: &[RebaseStep],
+    ) -> Result<RebaseState, String> {
+        validate_plan(steps)?;
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let dir = std::env::temp_dir().join(format!("phazeai-rebase-{nanos}"));
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let message_file = |i: usize| dir.join(format!("message-{i}"));
+        for (i, message) in group_messages(steps) {
+            std::fs::write(message_file(i), message).map_err(|e| e.to_string())?;
+        }
+        let todo_path = dir.join("todo");
+        std::fs::write(&todo_path, todo_list(steps, message_file)).map_err(|e| e.to_string())?;
+
+        let sequence_editor = format!("cp {}", shell_quote(&todo_path.to_string_lossy()));
+        let mut args = vec!["rebase", "-i"];
+        args.push(base.unwrap_or("--root"));
+        let result = self
+            .run_git_env(
+                &args,
+                &[("GIT_SEQUENCE_EDITOR", &sequence_editor), NO_EDITOR],
+            )
+            .await;
+        self.after_rebase_step(result).await
+    }
+
+    /// A rebase command's result: stopping on a conflict is not an error.
+    async fn after_rebase_step(
+        &self,
+        result: Result<String, String>,
+    ) -> Result<RebaseState, String> {
+        match (result, self.rebase_state().await) {
+            (Err(e), RebaseState::Idle) => Err(e),
+            (_, state) => Ok(state),
+        }
+    }
+
+    pub async fn rebase_state(&self) -> RebaseState {
+        let mut in_progress = false;
+        for dir in ["rebase-merge", "rebase-apply"] {
+            if let Ok(path) = self.run_git(&["rev-parse", "--git-path", dir]).await {
+                in_progress |= self.repo_root().join(path).exists();
+            }
+        }
+        if !in_progress {
+            return RebaseState::Idle;
+        }
+        let conflicts = self
+            .run_git(&["diff", "--name-only", "--diff-filter=U"])
+            .await
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        RebaseState::Stopped { conflicts }
+    }
+
+    /// Continue after conflicts were resolved and staged.
+    pub async fn rebase_continue(&self) -> Result<RebaseState, String> {
+        let result = self
+            .run_git_env(&["rebase", "--continue"], &[NO_EDITOR])
+            .await;
+        self.after_rebase_step(result).await
+    }
+
+    /// Drop the commit the rebase stopped on and go on.
+    pub async fn rebase_skip(&self) -> Result<RebaseState, String> {
+        let result = self.run_git_env(&["rebase", "--skip"], &[NO_EDITOR]).await;
+        self.after_rebase_step(result).await
+    }
+
+    /// Give up and put the branch back where it was before the rebase.
+    pub async fn rebase_abort(&self) -> Result<(), String> {
+        self.run_git(&["rebase", "--abort"]).await.map(|_| ())
+    }
+
+    /// Resolve a conflicted `path` by keeping one side, and stage it.
+    pub async fn resolve_conflict(&self, path: &str, side: ConflictSide) -> Result<(), String> {
+        let flag = match side {
+            ConflictSide::Upstream => "--ours",
+            ConflictSide::Commit => "--theirs",
+        };
+        self.run_git(&["checkout", flag, "--", path]).await?;
+        self.mark_resolved(path).await
+    }
+
+    /// Stage `path` after its conflict markers were resolved by hand.
+    pub async fn mark_resolved(&self, path: &str) -> Result<(), String> {
+        self.run_git(&["add", "--", path]).await.map(|_| ())
+    }
+}
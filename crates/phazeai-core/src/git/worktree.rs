@@ -0,0 +1,172 @@
+//! `git worktree` management, so agent tasks can edit and build on their own
+//! branch in a separate checkout without touching the user's working tree.
+
+use std::path::{Path, PathBuf};
+
+use super::GitOps;
+
+/// Prefix of the branches created for agent tasks.
+pub const TASK_BRANCH_PREFIX: &str = "phaze/";
+
+/// Words of the task description kept in its branch name.
+const MAX_SLUG_WORDS: usize = 6;
+const MAX_SLUG_LEN: usize = 40;
+
+/// One checkout of the repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worktree {
+    pub path: PathBuf,
+    /// Commit checked out; empty for a bare repository.
+    pub head: String,
+    /// Short branch name, `None` when `HEAD` is detached.
+    pub branch: Option<String>,
+    /// The repository's own checkout, which can't be removed.
+    pub is_main: bool,
+    pub locked: bool,
+    /// Its directory is gone; `git worktree prune` will drop it.
+    pub prunable: bool,
+}
+
+/// Parse `git worktree list --porcelain`. The first entry is the main
+/// worktree.
+pub fn parse_worktree_list(text: &str) -> Vec<Worktree> {
+    let mut worktrees: Vec<Worktree> = Vec::new();
+    for line in text.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        if key == "worktree" {
+            worktrees.push(Worktree {
+                path: PathBuf::from(value),
+                head: String::new(),
+                branch: None,
+                is_main: worktrees.is_empty(),
+                locked: false,
+                prunable: false,
+            });
+            continue;
+        }
+        let Some(wt) = worktrees.last_mut() else {
+            continue;
+        };
+        match key {
+            "HEAD" => wt.head = value.to_string(),
+            "branch" => {
+                let name = value.strip_prefix("refs/heads/").unwrap_or(value);
+                wt.branch = Some(name.to_string());
+            }
+            "locked" => wt.locked = true,
+            "prunable" => wt.prunable = true,
+            _ => {}
+        }
+    }
+    worktrees
+}
+
+/// Branch name for an agent task: [`TASK_BRANCH_PREFIX`] followed by a slug
+/// of the first few words of `task`, e.g. `phaze/add-retry-to-http-client`.
+pub fn task_branch_name(task: &str) -> String {
+    let slug = task
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(MAX_SLUG_WORDS)
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+    let mut slug = slug.as_str();
+    if slug.len() > MAX_SLUG_LEN {
+        slug = slug[..MAX_SLUG_LEN].trim_end_matches('-');
+    }
+    if slug.is_empty() {
+        slug = "task";
+    }
+    format!("{TASK_BRANCH_PREFIX}{slug}")
+}
+
+/// Where the worktree for `branch` goes: a `<repo>-worktrees` directory next
+/// to the repository, so it stays out of the workspace's explorer and search.
+pub fn worktree_path(repo_root: &Path, branch: &str) -> PathBuf {
+    let name = repo_root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repo".to_string());
+    let parent = repo_root.parent().unwrap_or(repo_root);
+    parent
+        .join(format!("{name}-worktrees"))
+        .join(branch.replace('/', "-"))
+}
+
+impl GitOps {
+    /// Every worktree of the repository, the main one first.
+    pub async fn worktrees(&self) -> Result<Vec<Worktree>, String> {
+        let output = self.run_git(&["worktree", "list", "--porcelain"]).await?;
+        Ok(parse_worktree_list(&output))
+    }
+
+    /// Check out a new `branch`, started from `base` (default `HEAD`), in a
+    /// new worktree at `path`.
+    pub async fn add_worktree(
+        &self,
+        path: &Path,
+        branch: &str,
+        base: Option<&str>,
+    ) -> Result<(), String> {
+        let dir = path.to_string_lossy();
+        let mut args = vec!["worktree", "add", "-b", branch, &dir];
+        args.extend(base);
+        self.run_git(&args).await.map(|_| ())
+    }
+
+    /// Create a worktree on a fresh task branch named after `task`, next to
+    /// the repository. A numeric suffix keeps branch and directory unique.
+    pub async fn create_task_worktree(&self, task: &str) -> Result<Worktree, String> {
+        let base_name = task_branch_name(task);
+        let mut branch = base_name.clone();
+        let mut n = 1;
+        loop {
+            let taken = self
+                .run_git(&[
+                    "rev-parse",
+                    "--verify",
+                    "--quiet",
+                    &format!("refs/heads/{branch}"),
+                ])
+                .await
+                .is_ok();
+            if !taken && !worktree_path(self.repo_root(), &branch).exists() {
+                break;
+            }
+            n += 1;
+            branch = format!("{base_name}-{n}");
+        }
+        let path = worktree_path(self.repo_root(), &branch);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        self.add_worktree(&path, &branch, None).await?;
+        let head = self.run_git(&["rev-parse", "HEAD"]).await?;
+        Ok(Worktree {
+            path,
+            head,
+            branch: Some(branch),
+            is_main: false,
+            locked: false,
+            prunable: false,
+        })
+    }
+
+    /// Remove the worktree at `path`; its branch is kept. `force` discards
+    /// uncommitted changes in it.
+    pub async fn remove_worktree(&self, path: &Path, force: bool) -> Result<(), String> {
+        let dir = path.to_string_lossy();
+        let mut args = vec!["worktree", "remove"];
+        if force {
+            args.push("--force");
+        }
+        args.push(&dir);
+        self.run_git(&args).await.map(|_| ())
+    }
+
+    /// Forget worktrees whose directories were deleted by hand.
+    pub async fn prune_worktrees(&self) -> Result<(), String> {
+        self.run_git(&["worktree", "prune"]).await.map(|_| ())
+    }
+}
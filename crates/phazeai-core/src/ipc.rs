@@ -0,0 +1,231 @@
+//! Local control API: JSON-RPC 2.0 over a Unix domain socket.
+//!
+//! The running IDE listens on [`socket_path`]; `phazeai ctl` and other
+//! scripts connect, write one request per line and read one response per
+//! line. Method names and their params are documented on [`methods`].
+
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::PhazeError;
+
+/// Methods understood by the IDE control server.
+pub mod methods {
+    /// `{}` → `"pong"`
+    pub const PING: &str = "ping";
+    /// `{"path": str, "line"?: u32}` → `true`
+    pub const OPEN_FILE: &str = "openFile";
+    /// `{}` → `[str]` command palette labels
+    pub const LIST_COMMANDS: &str = "listCommands";
+    /// `{"label": str}` → `true`; runs a command palette action by label
+    pub const RUN_COMMAND: &str = "runCommand";
+    /// `{"prompt": str}` → `true`; sends a prompt to the chat panel
+    pub const SEND_CHAT: &str = "sendChat";
+    /// `{"path"?: str}` → `[{path, line, col, severity, message}]`
+    pub const GET_DIAGNOSTICS: &str = "getDiagnostics";
+    /// `{}` → `true`; brings the IDE window to the front
+    pub const FOCUS_WINDOW: &str = "focusWindow";
+}
+
+/// JSON-RPC error codes.
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    #[serde(default = "jsonrpc_version")]
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+fn jsonrpc_version() -> String {
+    "2.0".to_string()
+}
+
+impl RpcResponse {
+    pub fn from_result(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(v) => (Some(v), None),
+            Err(e) => (None, Some(e)),
+        };
+        Self {
+            jsonrpc: jsonrpc_version(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+/// Handles one request. Called on a connection thread, so implementations
+/// must hand work to the UI thread themselves.
+pub type RpcHandler = Arc<dyn Fn(&RpcRequest) -> Result<Value, RpcError> + Send + Sync>;
+
+/// Socket location: `$PHAZEAI_SOCKET`, else `$XDG_RUNTIME_DIR/phazeai.sock`,
+/// else `~/.config/phazeai/phazeai.sock`.
+pub fn socket_path() -> PathBuf {
+    if let Some(p) = std::env::var_os("PHAZEAI_SOCKET") {
+        return PathBuf::from(p);
+    }
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        return PathBuf::from(dir).join("phazeai.sock");
+    }
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("phazeai")
+        .join("phazeai.sock")
+}
+
+/// Answer one raw request line.
+pub fn handle_line(line: &str, handler: &RpcHandler) -> RpcResponse {
+    match serde_json::from_str::<RpcRequest>(line) {
+        Ok(req) => RpcResponse::from_result(req.id.clone(), handler(&req)),
+        Err(e) => RpcResponse::from_result(
+            Value::Null,
+            Err(RpcError::new(PARSE_ERROR, format!("Parse error: {e}"))),
+        ),
+    }
+}
+
+/// Start listening on `path` in a background thread.
+///
+/// A stale socket file left by a crashed instance is replaced; if another
+/// instance is still answering on it, this fails with `AddrInUse`.
+#[cfg(unix)]
+pub fn serve(path: PathBuf, handler: RpcHandler) -> std::io::Result<()> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("{} is in use by another instance", path.display()),
+            ));
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    }
+
+    std::thread::Builder::new()
+        .name("phazeai-ipc".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = handler.clone();
+                std::thread::spawn(move || serve_connection(stream, handler));
+            }
+        })?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn serve(_path: PathBuf, _handler: RpcHandler) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the control socket is only available on Unix platforms",
+    ))
+}
+
+#[cfg(unix)]
+fn serve_connection(stream: std::os::unix::net::UnixStream, handler: RpcHandler) {
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut writer = stream;
+    for line in BufReader::new(read_half).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let resp = handle_line(&line, &handler);
+        let Ok(json) = serde_json::to_string(&resp) else {
+            break;
+        };
+        if writeln!(writer, "{json}").is_err() {
+            break;
+        }
+    }
+}
+
+/// Send one request to the server at `path` and wait for its response.
+#[cfg(unix)]
+pub fn call(path: &std::path::Path, method: &str, params: Value) -> Result<Value, PhazeError> {
+    use std::os::unix::net::UnixStream;
+
+    let stream = UnixStream::connect(path).map_err(|e| {
+        PhazeError::Other(format!(
+            "Cannot connect to {} ({e}). Is the IDE running?",
+            path.display()
+        ))
+    })?;
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(30)))?;
+    let req = RpcRequest {
+        jsonrpc: jsonrpc_version(),
+        id: Value::from(1),
+        method: method.to_string(),
+        params,
+    };
+    let mut writer = stream.try_clone()?;
+    writeln!(writer, "{}", serde_json::to_string(&req)?)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let resp: RpcResponse = serde_json::from_str(&line)?;
+    match resp.error {
+        Some(e) => Err(PhazeError::Other(format!("{} ({})", e.message, e.code))),
+        None => Ok(resp.result.unwrap_or(Value::Null)),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn call(_path: &std::path::Path, _method: &str, _params: Value) -> Result<Value, PhazeError> {
+    Err(PhazeError::Other(
+        "the control socket is only available on Unix platforms".into(),
+    ))
+}
@@ -1,25 +1,41 @@
 pub mod agent;
 pub mod analysis;
+pub mod audit;
+pub mod bench;
 pub mod companion;
 pub mod config;
 pub mod constants;
 pub mod context;
+pub mod db;
+pub mod editing;
 pub mod error;
+pub mod eval;
 pub mod ext_host;
 pub mod git;
+pub mod ipc;
 pub mod llm;
 pub mod lsp;
 pub mod mcp;
+pub mod memory;
+pub mod notifications;
+pub mod output;
+pub mod preview;
+pub mod profiling;
+pub mod progress;
 pub mod project;
+pub mod rest;
+pub mod secrets;
 pub mod telemetry;
 pub mod tools;
+pub mod update;
 
 // Re-export key types
 pub use agent::{Agent, AgentEvent, AgentResponse, ApprovalFn};
 pub use config::Settings;
 pub use context::{
     collect_git_info, ContextBuilder, ConversationHistory, ConversationMetadata, ConversationStore,
-    ProjectType, RepoMapGenerator, SavedConversation, SavedMessage, SystemPromptBuilder,
+    MemoryStore, ProjectType, RepoMapGenerator, SavedConversation, SavedMessage, SearchHit,
+    SearchQuery, SystemPromptBuilder,
 };
 pub use error::PhazeError;
 pub use llm::{
@@ -0,0 +1,519 @@
+//! AWS plumbing for the Bedrock provider: credentials found the way the AWS
+//! CLI finds them (environment, shared profile, EC2 instance role), the
+//! region, Signature Version 4 request signing, and the binary event stream
+//! Bedrock streams responses in.
+//!
+//! Only what Bedrock needs: static and instance-role credentials, not SSO
+//! or assumed roles (`aws sso login` and `credential_process` users can
+//! export the resulting keys into the environment).
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::constants::endpoints;
+use crate::error::PhazeError;
+
+/// Instance-role credentials are refreshed this long before they expire.
+const REFRESH_MARGIN: chrono::TimeDelta = chrono::TimeDelta::minutes(5);
+
+/// The metadata service answers in microseconds on EC2; elsewhere the
+/// address doesn't route and this bounds the wait.
+const IMDS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// An access key pair, with the session token and expiry of temporary ones.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("expires_at", &self.expires_at)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Credentials {
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+            expires_at: None,
+        }
+    }
+
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Option<Self> {
+        let id = non_empty_env("AWS_ACCESS_KEY_ID")?;
+        let secret = non_empty_env("AWS_SECRET_ACCESS_KEY")?;
+        Some(Self {
+            session_token: non_empty_env("AWS_SESSION_TOKEN"),
+            ..Self::new(id, secret)
+        })
+    }
+
+    /// Keys for `profile` from the shared credentials file, falling back to
+    /// the config file, which may hold them too.
+    pub fn from_profile(credentials_file: &str, config_file: &str, profile: &str) -> Option<Self> {
+        let from = |section: Option<&HashMap<String, String>>| {
+            let section = section?;
+            Some(Self {
+                session_token: section.get("aws_session_token").cloned(),
+                ..Self::new(
+                    section.get("aws_access_key_id")?,
+                    section.get("aws_secret_access_key")?,
+                )
+            })
+        };
+        from(parse_ini(credentials_file).get(profile))
+            .or_else(|| from(parse_ini(config_file).get(&config_section(profile))))
+    }
+
+    /// Parse the metadata service's `security-credentials/<role>` document.
+    pub fn from_imds(body: &str) -> Result<Self, PhazeError> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Document {
+            code: String,
+            access_key_id: String,
+            secret_access_key: String,
+            token: String,
+            expiration: DateTime<Utc>,
+        }
+        let doc: Document = serde_json::from_str(body)?;
+        if doc.code != "Success" {
+            return Err(PhazeError::Config(format!(
+                "Instance role credentials unavailable: {}",
+                doc.code
+            )));
+        }
+        Ok(Self {
+            session_token: Some(doc.token),
+            expires_at: Some(doc.expiration),
+            ..Self::new(doc.access_key_id, doc.secret_access_key)
+        })
+    }
+
+    /// Usable at `now`, with time to spare for a request.
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|at| now + REFRESH_MARGIN < at)
+    }
+}
+
+/// Finds credentials in the environment, then the shared profile, then the
+/// EC2 instance role. Instance-role credentials are cached until shortly
+/// before they expire; the others are read again each time so rotated keys
+/// are picked up.
+pub struct CredentialChain {
+    http: reqwest::Client,
+    imds_url: Option<String>,
+    cached: tokio::sync::Mutex<Option<Credentials>>,
+}
+
+static SHARED_CHAIN: LazyLock<Arc<CredentialChain>> =
+    LazyLock::new(|| Arc::new(CredentialChain::new()));
+
+impl Default for CredentialChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CredentialChain {
+    /// A chain that asks the metadata service at `AWS_EC2_METADATA_SERVICE_ENDPOINT`
+    /// (or its usual address), unless `AWS_EC2_METADATA_DISABLED` is `true`.
+    pub fn new() -> Self {
+        let imds_url = if non_empty_env("AWS_EC2_METADATA_DISABLED")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+        {
+            None
+        } else {
+            Some(
+                non_empty_env("AWS_EC2_METADATA_SERVICE_ENDPOINT")
+                    .unwrap_or_else(|| endpoints::AWS_IMDS_URL.to_string()),
+            )
+        };
+        Self::with_imds_url(imds_url)
+    }
+
+    /// A chain with another metadata service address, or none.
+    pub fn with_imds_url(imds_url: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(IMDS_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            imds_url: imds_url.map(|u| u.trim_end_matches('/').to_string()),
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// The chain every Bedrock client shares, so instance-role credentials
+    /// are fetched once.
+    pub fn shared() -> Arc<Self> {
+        SHARED_CHAIN.clone()
+    }
+
+    pub async fn credentials(&self) -> Result<Credentials, PhazeError> {
+        if let Some(creds) = Credentials::from_env() {
+            return Ok(creds);
+        }
+        let files = SharedFiles::read();
+        if let Some(creds) =
+            Credentials::from_profile(&files.credentials, &files.config, &profile_name())
+        {
+            return Ok(creds);
+        }
+        let Some(imds_url) = &self.imds_url else {
+            return Err(no_credentials());
+        };
+        let mut cached = self.cached.lock().await;
+        if let Some(creds) = cached.as_ref().filter(|c| c.is_fresh(Utc::now())) {
+            return Ok(creds.clone());
+        }
+        let creds = self.fetch_imds(imds_url).await.map_err(|e| {
+            tracing::debug!("EC2 instance metadata: {e}");
+            no_credentials()
+        })?;
+        *cached = Some(creds.clone());
+        Ok(creds)
+    }
+
+    /// IMDSv2: a session token first, then the role's credentials with it.
+    async fn fetch_imds(&self, base: &str) -> Result<Credentials, PhazeError> {
+        let token = self
+            .http
+            .put(format!("{base}/latest/api/token"))
+            .header("x-aws-ec2-metadata-token-ttl-seconds", "21600")
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let path = format!("{base}/latest/meta-data/iam/security-credentials/");
+        let get = |url: String| {
+            self.http
+                .get(url)
+                .header("x-aws-ec2-metadata-token", &token)
+                .send()
+        };
+        let roles = get(path.clone()).await?.error_for_status()?.text().await?;
+        let role = roles
+            .lines()
+            .next()
+            .filter(|r| !r.trim().is_empty())
+            .ok_or_else(|| PhazeError::Config("No IAM role attached to this instance".into()))?;
+        let body = get(format!("{path}{}", role.trim()))
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Credentials::from_imds(&body)
+    }
+
+    /// Whether credentials are set up without asking the metadata service,
+    /// for listing Bedrock as available.
+    pub fn configured_locally() -> bool {
+        let files = SharedFiles::read();
+        Credentials::from_env().is_some()
+            || Credentials::from_profile(&files.credentials, &files.config, &profile_name())
+                .is_some()
+    }
+}
+
+fn no_credentials() -> PhazeError {
+    PhazeError::Config(
+        "No AWS credentials: set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, add a profile \
+         to ~/.aws/credentials (AWS_PROFILE selects it), or run on an instance with an IAM role"
+            .into(),
+    )
+}
+
+/// The shared credentials and config files' contents (empty if missing).
+struct SharedFiles {
+    credentials: String,
+    config: String,
+}
+
+impl SharedFiles {
+    fn read() -> Self {
+        let path = |env: &str, name: &str| {
+            non_empty_env(env)
+                .map(PathBuf::from)
+                .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join(name)))
+        };
+        let read = |p: Option<PathBuf>| {
+            p.and_then(|p| std::fs::read_to_string(p).ok())
+                .unwrap_or_default()
+        };
+        Self {
+            credentials: read(path("AWS_SHARED_CREDENTIALS_FILE", "credentials")),
+            config: read(path("AWS_CONFIG_FILE", "config")),
+        }
+    }
+}
+
+/// `AWS_PROFILE`, or `default`.
+pub fn profile_name() -> String {
+    non_empty_env("AWS_PROFILE").unwrap_or_else(|| "default".into())
+}
+
+/// The region: `AWS_REGION`, `AWS_DEFAULT_REGION`, the profile's `region`,
+/// or us-east-1.
+pub fn resolve_region() -> String {
+    non_empty_env("AWS_REGION")
+        .or_else(|| non_empty_env("AWS_DEFAULT_REGION"))
+        .or_else(|| region_from_profile(&SharedFiles::read().config, &profile_name()))
+        .unwrap_or_else(|| endpoints::AWS_DEFAULT_REGION.to_string())
+}
+
+/// The `region` set for `profile` in a config file.
+pub fn region_from_profile(config_file: &str, profile: &str) -> Option<String> {
+    parse_ini(config_file)
+        .get(&config_section(profile))?
+        .get("region")
+        .cloned()
+}
+
+/// The config file names profiles `[profile name]`, except `[default]`.
+fn config_section(profile: &str) -> String {
+    if profile == "default" {
+        profile.to_string()
+    } else {
+        format!("profile {profile}")
+    }
+}
+
+/// Sections of an AWS-style INI file, keys lowercased.
+fn parse_ini(text: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+        } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            if let Some(entries) = sections.get_mut(section) {
+                entries.insert(key.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+    }
+    sections
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+// ── Signature Version 4 ─────────────────────────────────────────────────
+
+/// Signs requests to one AWS service in one region.
+pub struct SigV4<'a> {
+    pub credentials: &'a Credentials,
+    pub region: &'a str,
+    pub service: &'a str,
+}
+
+impl SigV4<'_> {
+    /// The headers to add to a `method` request for `url` with `payload`
+    /// as its body, signed as of `now`: `x-amz-date`, the session token if
+    /// any, and `authorization`. Only `host` and these are signed, so other
+    /// headers can be added freely.
+    pub fn sign(
+        &self,
+        method: &str,
+        url: &reqwest::Url,
+        payload: &[u8],
+        now: DateTime<Utc>,
+    ) -> Vec<(&'static str, String)> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = &amz_date[..8];
+
+        let mut host = url.host_str().unwrap_or_default().to_string();
+        if let Some(port) = url.port() {
+            host = format!("{host}:{port}");
+        }
+        let mut headers = vec![("host", host), ("x-amz-date", amz_date.clone())];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+
+        let canonical_request = format!(
+            "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+            canonical_uri(url.path()),
+            canonical_query(url),
+            hex::encode(Sha256::digest(payload)),
+        );
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac(
+            format!("AWS4{}", self.credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region, self.service, "aws4_request"] {
+            key = hmac(&key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+        headers.remove(0); // reqwest sets host itself
+        headers.push((
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                self.credentials.access_key_id
+            ),
+        ));
+        headers
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Every path segment encoded again, as all services but S3 expect: a
+/// model ID's `:` is sent as `%3A` and signed as `%253A`.
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() {
+        return "/".into();
+    }
+    path.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(url: &reqwest::Url) -> String {
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| {
+            (
+                urlencoding::encode(&k).into_owned(),
+                urlencoding::encode(&v).into_owned(),
+            )
+        })
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+// ── Event stream ────────────────────────────────────────────────────────
+
+/// One message of an `application/vnd.amazon.eventstream` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventMessage {
+    /// String-valued headers (`:event-type`, `:message-type`...); others are skipped.
+    pub headers: HashMap<String, String>,
+    pub payload: Vec<u8>,
+}
+
+impl EventMessage {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// Take the first complete message off the front of `buf`; `Ok(None)`
+/// until enough bytes have arrived. Checksums aren't verified, TLS already
+/// guards the bytes.
+pub fn decode_event(buf: &mut Vec<u8>) -> Result<Option<EventMessage>, PhazeError> {
+    const PRELUDE: usize = 12;
+    const TRAILER: usize = 4;
+    if buf.len() < PRELUDE {
+        return Ok(None);
+    }
+    let total = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+    let headers_len = u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) as usize;
+    if total < PRELUDE + headers_len + TRAILER {
+        return Err(malformed("message shorter than its headers"));
+    }
+    if buf.len() < total {
+        return Ok(None);
+    }
+    let message: Vec<u8> = buf.drain(..total).collect();
+    let headers = parse_event_headers(&message[PRELUDE..PRELUDE + headers_len])?;
+    Ok(Some(EventMessage {
+        headers,
+        payload: message[PRELUDE + headers_len..total - TRAILER].to_vec(),
+    }))
+}
+
+fn parse_event_headers(mut bytes: &[u8]) -> Result<HashMap<String, String>, PhazeError> {
+    fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8], PhazeError> {
+        if bytes.len() < n {
+            return Err(malformed("truncated header"));
+        }
+        let (head, rest) = bytes.split_at(n);
+        *bytes = rest;
+        Ok(head)
+    }
+    let mut headers = HashMap::new();
+    while !bytes.is_empty() {
+        let name_len = take(&mut bytes, 1)?[0] as usize;
+        let name = String::from_utf8_lossy(take(&mut bytes, name_len)?).into_owned();
+        let value_type = take(&mut bytes, 1)?[0];
+        let fixed = match value_type {
+            0 | 1 => Some(0), // true, false
+            2 => Some(1),     // byte
+            3 => Some(2),     // short
+            4 => Some(4),     // int
+            5 | 8 => Some(8), // long, timestamp
+            9 => Some(16),    // uuid
+            6 | 7 => None,    // byte array, string
+            other => return Err(malformed(&format!("unknown header type {other}"))),
+        };
+        match fixed {
+            Some(n) => {
+                take(&mut bytes, n)?;
+            }
+            None => {
+                let len = take(&mut bytes, 2)?;
+                let value = take(&mut bytes, u16::from_be_bytes([len[0], len[1]]) as usize)?;
+                if value_type == 7 {
+                    headers.insert(name, String::from_utf8_lossy(value).into_owned());
+                }
+            }
+        }
+    }
+    Ok(headers)
+}
+
+fn malformed(what: &str) -> PhazeError {
+    PhazeError::Llm(format!("Malformed AWS event stream: {what}"))
+}
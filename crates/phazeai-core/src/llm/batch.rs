@@ -0,0 +1,483 @@
+//! Batch APIs, for large jobs that can wait.
+//!
+//! Anthropic's Message Batches and OpenAI's Batch API run requests in the
+//! background, within a day, at half the usual price. A [`BatchClient`]
+//! submits [`BatchRequest`]s, polls the batch's [`BatchStatus`] and reads
+//! the [`BatchResult`]s once it has ended. A job too large for one batch is
+//! split with [`chunk_requests`].
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::PhazeError;
+use crate::llm::provider::{ProviderConfig, ProviderId};
+use crate::llm::traits::*;
+
+/// Fraction of the usual price batched requests cost.
+pub const BATCH_DISCOUNT: f64 = 0.5;
+
+/// How long a batch may take before the provider expires it.
+const COMPLETION_WINDOW: &str = "24h";
+
+/// The most one batch may hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchLimits {
+    pub max_requests: usize,
+    /// Total size of the requests as sent.
+    pub max_bytes: usize,
+}
+
+/// One request of a batch: a conversation to answer without tools.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequest {
+    /// Names the request's result. Letters, digits, `-` and `_` only, at
+    /// most 64 of them, and unique within the batch.
+    pub custom_id: String,
+    pub messages: Vec<Message>,
+    pub max_tokens: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchState {
+    /// Being validated, run or cancelled.
+    InProgress,
+    /// Every request has a result.
+    Ended,
+    /// The batch was rejected as a whole; no request ran.
+    Failed,
+    /// The window ran out; requests that finished in time have results.
+    Expired,
+    /// Cancelled; requests that finished before have results.
+    Cancelled,
+}
+
+impl BatchState {
+    pub fn is_finished(self) -> bool {
+        self != BatchState::InProgress
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BatchState::InProgress => "in progress",
+            BatchState::Ended => "ended",
+            BatchState::Failed => "failed",
+            BatchState::Expired => "expired",
+            BatchState::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchStatus {
+    pub id: String,
+    pub state: BatchState,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Where the results are: Anthropic's results URL, or OpenAI's output
+    /// and error file ids. Empty until there are results.
+    pub result_files: Vec<String>,
+}
+
+/// The outcome of one [`BatchRequest`].
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    pub custom_id: String,
+    /// The reply, or why there is none.
+    pub outcome: Result<String, String>,
+    pub usage: Option<Usage>,
+}
+
+/// Splits `requests` into batches within `limits`, in order.
+pub fn chunk_requests(requests: Vec<BatchRequest>, limits: BatchLimits) -> Vec<Vec<BatchRequest>> {
+    let mut chunks: Vec<Vec<BatchRequest>> = Vec::new();
+    let mut bytes = 0;
+    for request in requests {
+        let size = serde_json::to_string(&request).map_or(0, |s| s.len());
+        match chunks.last_mut() {
+            Some(chunk)
+                if chunk.len() < limits.max_requests && bytes + size <= limits.max_bytes =>
+            {
+                chunk.push(request);
+                bytes += size;
+            }
+            _ => {
+                chunks.push(vec![request]);
+                bytes = size;
+            }
+        }
+    }
+    chunks
+}
+
+/// The body of an Anthropic Message Batches request.
+pub fn anthropic_batch_body(model: &str, requests: &[BatchRequest]) -> Value {
+    let requests: Vec<Value> = requests
+        .iter()
+        .map(|r| {
+            let system: Vec<&str> = r
+                .messages
+                .iter()
+                .filter(|m| m.role == Role::System)
+                .map(|m| m.content.as_str())
+                .collect();
+            let messages: Vec<Value> = r
+                .messages
+                .iter()
+                .filter(|m| m.role != Role::System)
+                .map(|m| json!({ "role": m.role, "content": m.content }))
+                .collect();
+            let mut params = json!({
+                "model": model,
+                "max_tokens": r.max_tokens,
+                "messages": messages,
+            });
+            if !system.is_empty() {
+                params["system"] = Value::String(system.join("\n\n"));
+            }
+            json!({ "custom_id": r.custom_id, "params": params })
+        })
+        .collect();
+    json!({ "requests": requests })
+}
+
+/// The input file of an OpenAI batch: one chat completion per line.
+pub fn openai_batch_jsonl(model: &str, requests: &[BatchRequest]) -> String {
+    requests
+        .iter()
+        .map(|r| {
+            let messages: Vec<Value> = r
+                .messages
+                .iter()
+                .map(|m| json!({ "role": m.role, "content": m.content }))
+                .collect();
+            let line = json!({
+                "custom_id": r.custom_id,
+                "method": "POST",
+                "url": "/v1/chat/completions",
+                "body": {
+                    "model": model,
+                    "messages": messages,
+                    "max_completion_tokens": r.max_tokens,
+                },
+            });
+            format!("{line}\n")
+        })
+        .collect()
+}
+
+fn count(value: &Value) -> usize {
+    value.as_u64().unwrap_or(0) as usize
+}
+
+/// A Message Batches object as a [`BatchStatus`].
+pub fn parse_anthropic_status(batch: &Value) -> BatchStatus {
+    let counts = &batch["request_counts"];
+    let failed = count(&counts["errored"]) + count(&counts["canceled"]) + count(&counts["expired"]);
+    let succeeded = count(&counts["succeeded"]);
+    let state = match batch["processing_status"].as_str() {
+        Some("ended") if count(&counts["expired"]) > 0 => BatchState::Expired,
+        Some("ended") if count(&counts["canceled"]) > 0 => BatchState::Cancelled,
+        Some("ended") => BatchState::Ended,
+        _ => BatchState::InProgress,
+    };
+    BatchStatus {
+        id: batch["id"].as_str().unwrap_or_default().to_string(),
+        state,
+        total: succeeded + failed + count(&counts["processing"]),
+        succeeded,
+        failed,
+        result_files: batch["results_url"]
+            .as_str()
+            .map(str::to_string)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// An OpenAI batch object as a [`BatchStatus`].
+pub fn parse_openai_status(batch: &Value) -> BatchStatus {
+    let counts = &batch["request_counts"];
+    let state = match batch["status"].as_str() {
+        Some("completed") => BatchState::Ended,
+        Some("failed") => BatchState::Failed,
+        Some("expired") => BatchState::Expired,
+        Some("cancelled") => BatchState::Cancelled,
+        _ => BatchState::InProgress,
+    };
+    BatchStatus {
+        id: batch["id"].as_str().unwrap_or_default().to_string(),
+        state,
+        total: count(&counts["total"]),
+        succeeded: count(&counts["completed"]),
+        failed: count(&counts["failed"]),
+        result_files: ["output_file_id", "error_file_id"]
+            .iter()
+            .filter_map(|key| batch[*key].as_str().map(str::to_string))
+            .collect(),
+    }
+}
+
+/// The lines of an Anthropic results file.
+pub fn parse_anthropic_results(jsonl: &str) -> Vec<BatchResult> {
+    jsonl
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .map(|line| {
+            let result = &line["result"];
+            let message = &result["message"];
+            let outcome = match result["type"].as_str() {
+                Some("succeeded") => Ok(message["content"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|block| block["type"] == "text")
+                    .filter_map(|block| block["text"].as_str())
+                    .collect()),
+                Some("errored") => Err(result["error"]["error"]["message"]
+                    .as_str()
+                    .unwrap_or("request failed")
+                    .to_string()),
+                Some(other) => Err(other.to_string()),
+                None => Err("no result".to_string()),
+            };
+            BatchResult {
+                custom_id: line["custom_id"].as_str().unwrap_or_default().to_string(),
+                outcome,
+                usage: serde_json::from_value(message["usage"].clone()).ok(),
+            }
+        })
+        .collect()
+}
+
+/// The lines of an OpenAI output or error file.
+pub fn parse_openai_results(jsonl: &str) -> Vec<BatchResult> {
+    jsonl
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .map(|line| {
+            let response = &line["response"];
+            let body = &response["body"];
+            let outcome = if let Some(message) = line["error"]["message"].as_str() {
+                Err(message.to_string())
+            } else if response["status_code"] != 200 {
+                Err(body["error"]["message"]
+                    .as_str()
+                    .unwrap_or("request failed")
+                    .to_string())
+            } else {
+                Ok(body["choices"][0]["message"]["content"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string())
+            };
+            BatchResult {
+                custom_id: line["custom_id"].as_str().unwrap_or_default().to_string(),
+                outcome,
+                usage: body["usage"]["prompt_tokens"].as_u64().map(|input| Usage {
+                    input_tokens: input as u32,
+                    output_tokens: body["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Submits and reads batches on Anthropic or OpenAI.
+pub struct BatchClient {
+    client: reqwest::Client,
+    provider: ProviderId,
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl BatchClient {
+    /// Whether `provider` has a batch API this client speaks.
+    pub fn supports(provider: &ProviderId) -> bool {
+        matches!(provider, ProviderId::Claude | ProviderId::OpenAI)
+    }
+
+    /// A client with the provider's key and base URL.
+    pub fn from_provider(config: &ProviderConfig, model: &str) -> Result<Self, PhazeError> {
+        if !Self::supports(&config.id) {
+            return Err(PhazeError::Config(format!(
+                "{} has no batch API; use Claude or OpenAI",
+                config.id.name()
+            )));
+        }
+        let api_key = config.api_key().ok_or_else(|| {
+            PhazeError::Config(format!(
+                "Set {} environment variable for {}",
+                config.api_key_env,
+                config.id.name()
+            ))
+        })?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            provider: config.id.clone(),
+            api_key,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+        })
+    }
+
+    pub fn provider(&self) -> &ProviderId {
+        &self.provider
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn limits(&self) -> BatchLimits {
+        match self.provider {
+            ProviderId::Claude => BatchLimits {
+                max_requests: 100_000,
+                max_bytes: 256 * 1024 * 1024,
+            },
+            _ => BatchLimits {
+                max_requests: 50_000,
+                max_bytes: 200 * 1024 * 1024,
+            },
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match self.provider {
+            ProviderId::Claude => request
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01"),
+            _ => request.bearer_auth(&self.api_key),
+        }
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        what: &str,
+    ) -> Result<String, PhazeError> {
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(PhazeError::Llm(format!(
+                "{} batch {what} failed ({status}): {body}",
+                self.provider.name()
+            )));
+        }
+        Ok(body)
+    }
+
+    /// Submit `requests` as one batch, returning its id.
+    pub async fn submit(&self, requests: &[BatchRequest]) -> Result<String, PhazeError> {
+        let body = match self.provider {
+            ProviderId::Claude => {
+                let url = format!("{}/v1/messages/batches", self.base_url);
+                let request = self
+                    .request(reqwest::Method::POST, &url)
+                    .json(&anthropic_batch_body(&self.model, requests));
+                self.send(request, "submission").await?
+            }
+            _ => {
+                let file_id = self
+                    .upload(&openai_batch_jsonl(&self.model, requests))
+                    .await?;
+                let url = format!("{}/v1/batches", self.base_url);
+                let request = self.request(reqwest::Method::POST, &url).json(&json!({
+                    "input_file_id": file_id,
+                    "endpoint": "/v1/chat/completions",
+                    "completion_window": COMPLETION_WINDOW,
+                }));
+                self.send(request, "submission").await?
+            }
+        };
+        let batch: Value = serde_json::from_str(&body)?;
+        batch["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| PhazeError::Llm(format!("Batch submission returned no id: {body}")))
+    }
+
+    /// Upload an OpenAI batch input file, returning its id.
+    async fn upload(&self, jsonl: &str) -> Result<String, PhazeError> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let boundary = format!("phazeai-batch-{nanos:x}");
+        let body = format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"purpose\"\r\n\r\n\
+             batch\r\n\
+             --{boundary}\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"batch.jsonl\"\r\n\
+             Content-Type: application/jsonl\r\n\r\n\
+             {jsonl}\r\n\
+             --{boundary}--\r\n"
+        );
+        let url = format!("{}/v1/files", self.base_url);
+        let request = self
+            .request(reqwest::Method::POST, &url)
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body);
+        let file: Value = serde_json::from_str(&self.send(request, "upload").await?)?;
+        file["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| PhazeError::Llm("Batch upload returned no file id".into()))
+    }
+
+    pub async fn status(&self, id: &str) -> Result<BatchStatus, PhazeError> {
+        let url = match self.provider {
+            ProviderId::Claude => format!("{}/v1/messages/batches/{id}", self.base_url),
+            _ => format!("{}/v1/batches/{id}", self.base_url),
+        };
+        let body = self
+            .send(self.request(reqwest::Method::GET, &url), "status")
+            .await?;
+        let batch: Value = serde_json::from_str(&body)?;
+        Ok(match self.provider {
+            ProviderId::Claude => parse_anthropic_status(&batch),
+            _ => parse_openai_status(&batch),
+        })
+    }
+
+    /// The results of a finished batch; none if it has no results yet.
+    pub async fn results(&self, status: &BatchStatus) -> Result<Vec<BatchResult>, PhazeError> {
+        let mut results = Vec::new();
+        for file in &status.result_files {
+            let url = match self.provider {
+                ProviderId::Claude => file.clone(),
+                _ => format!("{}/v1/files/{file}/content", self.base_url),
+            };
+            let jsonl = self
+                .send(self.request(reqwest::Method::GET, &url), "results")
+                .await?;
+            results.extend(match self.provider {
+                ProviderId::Claude => parse_anthropic_results(&jsonl),
+                _ => parse_openai_results(&jsonl),
+            });
+        }
+        Ok(results)
+    }
+
+    /// Ask the provider to stop the batch. Requests already done keep their
+    /// results.
+    pub async fn cancel(&self, id: &str) -> Result<(), PhazeError> {
+        let url = match self.provider {
+            ProviderId::Claude => format!("{}/v1/messages/batches/{id}/cancel", self.base_url),
+            _ => format!("{}/v1/batches/{id}/cancel", self.base_url),
+        };
+        self.send(self.request(reqwest::Method::POST, &url), "cancellation")
+            .await?;
+        Ok(())
+    }
+}
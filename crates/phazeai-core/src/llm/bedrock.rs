@@ -0,0 +1,402 @@
+//! Amazon Bedrock through its Converse API, which takes one request shape
+//! for every model family it hosts (Anthropic, Meta Llama...), tools
+//! included. Requests are signed with SigV4 using the [`CredentialChain`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::channel::mpsc;
+use serde_json::{json, Value};
+
+use crate::error::PhazeError;
+use crate::llm::aws::{self, CredentialChain, SigV4};
+use crate::llm::provider::ProviderConfig;
+use crate::llm::traits::*;
+use crate::tools::ToolDefinition;
+
+const SERVICE: &str = "bedrock";
+
+/// The Bedrock runtime endpoint of `region`.
+pub fn runtime_url(region: &str) -> String {
+    format!("https://bedrock-runtime.{region}.amazonaws.com")
+}
+
+/// The region and endpoint a provider `base_url` stands for: empty for the
+/// resolved AWS region, a bare region name (`eu-central-1`), or a full URL
+/// such as a VPC endpoint, whose region is read from its host.
+pub fn endpoint_for(base_url: &str) -> (String, String) {
+    let base_url = base_url.trim().trim_end_matches('/');
+    if base_url.is_empty() {
+        let region = aws::resolve_region();
+        let url = runtime_url(&region);
+        return (region, url);
+    }
+    if !base_url.contains("://") {
+        return (base_url.to_string(), runtime_url(base_url));
+    }
+    let host = base_url
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.split(['/', ':']).next())
+        .unwrap_or_default();
+    let mut labels = host.split('.');
+    let region = labels
+        .by_ref()
+        .find(|l| l.starts_with("bedrock-runtime"))
+        .and_then(|_| labels.next())
+        .map(str::to_string)
+        .unwrap_or_else(aws::resolve_region);
+    (region, base_url.to_string())
+}
+
+pub struct BedrockClient {
+    http: reqwest::Client,
+    credentials: Arc<CredentialChain>,
+    region: String,
+    base_url: String,
+    model: String,
+    max_tokens: Option<u32>,
+}
+
+impl BedrockClient {
+    /// A client for `model` in the resolved AWS region.
+    pub fn new(model: impl Into<String>) -> Self {
+        Self::for_config("", model)
+    }
+
+    /// A client for `model` at a provider `base_url` (see [`endpoint_for`]).
+    pub fn for_config(base_url: &str, model: impl Into<String>) -> Self {
+        let (region, base_url) = endpoint_for(base_url);
+        Self {
+            http: reqwest::Client::new(),
+            credentials: CredentialChain::shared(),
+            region,
+            base_url,
+            model: model.into(),
+            max_tokens: None,
+        }
+    }
+
+    pub fn from_provider(config: &ProviderConfig, model: &str) -> Self {
+        let client = Self::for_config(&config.base_url, model);
+        // Llama models on Bedrock reject more than their 2048-token output
+        // limit, so only Anthropic models get a larger one.
+        if model.contains("anthropic.") {
+            client.with_max_tokens(8192)
+        } else {
+            client
+        }
+    }
+
+    pub fn with_max_tokens(mut self, max: u32) -> Self {
+        self.max_tokens = Some(max);
+        self
+    }
+
+    pub fn with_credentials(mut self, credentials: Arc<CredentialChain>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// The Converse request body. Consecutive messages with the same role
+    /// are merged, since Bedrock requires the roles to alternate (the agent
+    /// sends each tool result as its own user message).
+    pub fn request_body(&self, messages: &[Message], tools: &[ToolDefinition]) -> Value {
+        let system: Vec<Value> = messages
+            .iter()
+            .filter(|m| m.role == Role::System && !m.content.is_empty())
+            .map(|m| json!({ "text": m.content }))
+            .collect();
+
+        let mut turns: Vec<(&str, Vec<Value>)> = Vec::new();
+        for m in messages.iter().filter(|m| m.role != Role::System) {
+            let role = if m.role == Role::Assistant {
+                "assistant"
+            } else {
+                "user"
+            };
+            let mut content = Vec::new();
+            if let Some(id) = &m.tool_call_id {
+                content.push(json!({
+                    "toolResult": {
+                        "toolUseId": id,
+                        "content": [{ "text": m.content }],
+                    }
+                }));
+            } else if !m.content.is_empty() {
+                content.push(json!({ "text": m.content }));
+            }
+            for tc in m.tool_calls.iter().flatten() {
+                let input: Value = serde_json::from_str(&tc.function.arguments)
+                    .unwrap_or(Value::Object(Default::default()));
+                content.push(json!({
+                    "toolUse": {
+                        "toolUseId": tc.id,
+                        "name": tc.function.name,
+                        "input": input,
+                    }
+                }));
+            }
+            if content.is_empty() {
+                // Bedrock rejects blank text blocks.
+                continue;
+            }
+            match turns.last_mut() {
+                Some((last, blocks)) if *last == role => blocks.extend(content),
+                _ => turns.push((role, content)),
+            }
+        }
+
+        let mut body = json!({
+            "messages": turns
+                .into_iter()
+                .map(|(role, content)| json!({ "role": role, "content": content }))
+                .collect::<Vec<_>>(),
+        });
+        if !system.is_empty() {
+            body["system"] = Value::Array(system);
+        }
+        if let Some(max) = self.max_tokens {
+            body["inferenceConfig"] = json!({ "maxTokens": max });
+        }
+        if !tools.is_empty() {
+            let specs: Vec<Value> = tools
+                .iter()
+                .map(|t| {
+                    json!({
+                        "toolSpec": {
+                            "name": t.name,
+                            "description": t.description,
+                            "inputSchema": { "json": t.parameters },
+                        }
+                    })
+                })
+                .collect();
+            body["toolConfig"] = json!({ "tools": specs });
+        }
+        body
+    }
+
+    /// Sign and send `body` to the model's `action` (`converse` or
+    /// `converse-stream`).
+    async fn send(&self, action: &str, body: &Value) -> Result<reqwest::Response, PhazeError> {
+        let url = format!(
+            "{}/model/{}/{action}",
+            self.base_url,
+            urlencoding::encode(&self.model)
+        );
+        let url = reqwest::Url::parse(&url)
+            .map_err(|e| PhazeError::Config(format!("Invalid Bedrock endpoint {url}: {e}")))?;
+        let payload = serde_json::to_vec(body)?;
+        let credentials = self.credentials.credentials().await?;
+        let signer = SigV4 {
+            credentials: &credentials,
+            region: &self.region,
+            service: SERVICE,
+        };
+        let mut request = self
+            .http
+            .post(url.clone())
+            .header("content-type", "application/json");
+        for (name, value) in signer.sign("POST", &url, &payload, chrono::Utc::now()) {
+            request = request.header(name, value);
+        }
+        let response = request.body(payload).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&text)
+                .ok()
+                .and_then(|v| v["message"].as_str().map(str::to_string))
+                .unwrap_or(text);
+            return Err(PhazeError::Llm(format!(
+                "Bedrock error ({status}): {message}"
+            )));
+        }
+        Ok(response)
+    }
+}
+
+/// The reply in a Converse response.
+pub fn parse_converse_response(body: &Value) -> Result<LlmResponse, PhazeError> {
+    let content = body["output"]["message"]["content"]
+        .as_array()
+        .ok_or_else(|| PhazeError::Llm("Bedrock response has no message".into()))?;
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in content {
+        if let Some(t) = block["text"].as_str() {
+            text.push_str(t);
+        } else if let Some(tool) = block.get("toolUse") {
+            tool_calls.push(ToolCall {
+                id: tool["toolUseId"].as_str().unwrap_or_default().to_string(),
+                call_type: "function".to_string(),
+                function: FunctionCall {
+                    name: tool["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: tool["input"].to_string(),
+                },
+            });
+        }
+    }
+    let message = if tool_calls.is_empty() {
+        Message::assistant(text)
+    } else {
+        Message::assistant_with_tools(text, tool_calls)
+    };
+    Ok(LlmResponse {
+        message,
+        usage: parse_usage(&body["usage"]),
+    })
+}
+
+fn parse_usage(usage: &Value) -> Option<Usage> {
+    Some(Usage {
+        input_tokens: usage["inputTokens"].as_u64()? as u32,
+        output_tokens: usage["outputTokens"].as_u64()? as u32,
+    })
+}
+
+/// Turns ConverseStream events into [`StreamEvent`]s.
+#[derive(Default)]
+pub struct StreamDecoder {
+    /// Content block index → tool use id, for the deltas that follow.
+    tool_blocks: HashMap<u64, String>,
+    stopped: bool,
+}
+
+impl StreamDecoder {
+    /// The events for one stream message; [`StreamEvent::Done`] comes after
+    /// the usage metadata that ends the stream.
+    pub fn decode(&mut self, message: &aws::EventMessage) -> Vec<StreamEvent> {
+        let payload: Value = serde_json::from_slice(&message.payload).unwrap_or_default();
+        if message.header(":message-type") == Some("exception") {
+            let kind = message.header(":exception-type").unwrap_or("exception");
+            let text = payload["message"].as_str().unwrap_or("stream failed");
+            return vec![StreamEvent::Error(format!("Bedrock {kind}: {text}"))];
+        }
+        let index = payload["contentBlockIndex"].as_u64().unwrap_or(0);
+        match message.header(":event-type") {
+            Some("contentBlockStart") => {
+                let tool = &payload["start"]["toolUse"];
+                let Some(id) = tool["toolUseId"].as_str() else {
+                    return vec![];
+                };
+                self.tool_blocks.insert(index, id.to_string());
+                vec![StreamEvent::ToolCallStart {
+                    id: id.to_string(),
+                    name: tool["name"].as_str().unwrap_or_default().to_string(),
+                }]
+            }
+            Some("contentBlockDelta") => {
+                let delta = &payload["delta"];
+                if let Some(text) = delta["text"].as_str() {
+                    vec![StreamEvent::TextDelta(text.to_string())]
+                } else if let (Some(input), Some(id)) = (
+                    delta["toolUse"]["input"].as_str(),
+                    self.tool_blocks.get(&index),
+                ) {
+                    vec![StreamEvent::ToolCallDelta {
+                        id: id.clone(),
+                        arguments_delta: input.to_string(),
+                    }]
+                } else {
+                    vec![]
+                }
+            }
+            Some("contentBlockStop") => match self.tool_blocks.remove(&index) {
+                Some(id) => vec![StreamEvent::ToolCallEnd { id }],
+                None => vec![],
+            },
+            Some("messageStop") => {
+                self.stopped = true;
+                vec![]
+            }
+            Some("metadata") => {
+                let mut events: Vec<_> = parse_usage(&payload["usage"])
+                    .map(StreamEvent::Usage)
+                    .into_iter()
+                    .collect();
+                events.push(StreamEvent::Done);
+                events
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The event for a stream that closed without its metadata.
+    pub fn finish(&self) -> StreamEvent {
+        if self.stopped {
+            StreamEvent::Done
+        } else {
+            StreamEvent::Dropped(STREAM_CLOSED_EARLY.to_string())
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmClient for BedrockClient {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        let response = self
+            .send("converse", &self.request_body(messages, tools))
+            .await?;
+        let body: Value = response.json().await?;
+        parse_converse_response(&body)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        let response = self
+            .send("converse-stream", &self.request_body(messages, tools))
+            .await?;
+
+        let (tx, rx) = mpsc::unbounded();
+        let mut stream = response.bytes_stream();
+        tokio::spawn(async move {
+            use futures::StreamExt;
+            let mut buffer = Vec::new();
+            let mut decoder = StreamDecoder::default();
+            while let Some(chunk) = stream.next().await {
+                let chunk = match chunk {
+                    Ok(c) => c,
+                    Err(e) => {
+                        let _ = tx.unbounded_send(StreamEvent::Dropped(e.to_string()));
+                        return;
+                    }
+                };
+                buffer.extend_from_slice(&chunk);
+                loop {
+                    let message = match aws::decode_event(&mut buffer) {
+                        Ok(Some(message)) => message,
+                        Ok(None) => break,
+                        Err(e) => {
+                            let _ = tx.unbounded_send(StreamEvent::Error(e.to_string()));
+                            return;
+                        }
+                    };
+                    for event in decoder.decode(&message) {
+                        let last = matches!(event, StreamEvent::Done | StreamEvent::Error(_));
+                        let _ = tx.unbounded_send(event);
+                        if last {
+                            return;
+                        }
+                    }
+                }
+            }
+            let _ = tx.unbounded_send(decoder.finish());
+        });
+
+        Ok(rx)
+    }
+}
@@ -11,8 +11,12 @@ pub struct ClaudeClient {
     model: String,
     base_url: String,
     max_tokens: u32,
+    thinking_budget: Option<u32>,
 }
 
+/// The smallest thinking budget the API takes.
+pub const MIN_THINKING_BUDGET: u32 = 1024;
+
 impl ClaudeClient {
     pub fn new(api_key: impl Into<String>) -> Self {
         Self {
@@ -21,6 +25,7 @@ impl ClaudeClient {
             model: crate::constants::models::DEFAULT_CLAUDE_MODEL.to_string(),
             base_url: crate::constants::endpoints::CLAUDE_BASE_URL.to_string(),
             max_tokens: 8192,
+            thinking_budget: None,
         }
     }
 
@@ -39,7 +44,34 @@ impl ClaudeClient {
         self
     }
 
-    fn build_request_body(
+    /// Turn on extended thinking with up to `budget` tokens of it, raised to
+    /// [`MIN_THINKING_BUDGET`]. `None` or `Some(0)` leaves it off. The
+    /// answer still gets `max_tokens` on top of the budget.
+    pub fn with_thinking_budget(mut self, budget: Option<u32>) -> Self {
+        self.thinking_budget = budget
+            .filter(|&b| b > 0)
+            .map(|b| b.max(MIN_THINKING_BUDGET));
+        self
+    }
+
+    /// Whether this request can think. The API wants an assistant turn that
+    /// is still waiting on its tool results to start with its thinking, so
+    /// a tool loop begun without thinking (by another model, or before it
+    /// was turned on) has to finish without it.
+    fn thinking_budget_for(&self, messages: &[Message]) -> Option<u32> {
+        let budget = self.thinking_budget?;
+        let pending = messages
+            .iter()
+            .rev()
+            .find(|m| m.tool_call_id.is_none())
+            .filter(|m| m.role == Role::Assistant && m.tool_calls.is_some());
+        match pending {
+            Some(m) if !m.reasoning.iter().any(is_thinking) => None,
+            _ => Some(budget),
+        }
+    }
+
+    pub fn build_request_body(
         &self,
         messages: &[Message],
         tools: &[ToolDefinition],
@@ -53,6 +85,8 @@ impl ClaudeClient {
             .collect::<Vec<_>>()
             .join("\n\n");
 
+        let thinking_budget = self.thinking_budget_for(messages);
+
         let conv_messages: Vec<Value> = messages
             .iter()
             .filter(|m| m.role != Role::System)
@@ -66,15 +100,32 @@ impl ClaudeClient {
                             "content": m.content,
                         }]
                     })
-                } else if let Some(ref tool_calls) = m.tool_calls {
-                    let mut content: Vec<Value> = Vec::new();
+                } else if m.tool_calls.is_some() || m.reasoning.iter().any(is_thinking) {
+                    // Thinking goes back first and unchanged; its signature
+                    // covers it.
+                    let mut content: Vec<Value> = m
+                        .reasoning
+                        .iter()
+                        .filter_map(|item| match item.kind {
+                            ReasoningKind::Thinking => Some(serde_json::json!({
+                                "type": "thinking",
+                                "thinking": item.summary.concat(),
+                                "signature": item.encrypted_content.as_deref().unwrap_or(""),
+                            })),
+                            ReasoningKind::RedactedThinking => Some(serde_json::json!({
+                                "type": "redacted_thinking",
+                                "data": item.encrypted_content.as_deref().unwrap_or(""),
+                            })),
+                            ReasoningKind::OpenAi => None,
+                        })
+                        .collect();
                     if !m.content.is_empty() {
                         content.push(serde_json::json!({
                             "type": "text",
                             "text": m.content,
                         }));
                     }
-                    for tc in tool_calls {
+                    for tc in m.tool_calls.iter().flatten() {
                         let args: Value = serde_json::from_str(&tc.function.arguments)
                             .unwrap_or(Value::Object(Default::default()));
                         content.push(serde_json::json!({
@@ -88,6 +139,29 @@ impl ClaudeClient {
                         "role": "assistant",
                         "content": content,
                     })
+                } else if !m.images.is_empty() {
+                    let mut content: Vec<Value> = m
+                        .images
+                        .iter()
+                        .map(|image| {
+                            serde_json::json!({
+                                "type": "image",
+                                "source": {
+                                    "type": "base64",
+                                    "media_type": image.media_type,
+                                    "data": image.data,
+                                },
+                            })
+                        })
+                        .collect();
+                    content.push(serde_json::json!({
+                        "type": "text",
+                        "text": m.content,
+                    }));
+                    serde_json::json!({
+                        "role": m.role,
+                        "content": content,
+                    })
                 } else {
                     serde_json::json!({
                         "role": m.role,
@@ -103,6 +177,14 @@ impl ClaudeClient {
             "messages": conv_messages,
         });
 
+        if let Some(budget) = thinking_budget {
+            body["max_tokens"] = Value::from(self.max_tokens + budget);
+            body["thinking"] = serde_json::json!({
+                "type": "enabled",
+                "budget_tokens": budget,
+            });
+        }
+
         if !system_prompt.is_empty() {
             body["system"] = Value::String(system_prompt);
         }
@@ -129,6 +211,10 @@ impl ClaudeClient {
     }
 }
 
+fn is_thinking(item: &ReasoningItem) -> bool {
+    item.kind != ReasoningKind::OpenAi
+}
+
 #[derive(Debug, Deserialize)]
 struct ClaudeApiResponse {
     content: Vec<ClaudeContent>,
@@ -147,6 +233,37 @@ struct ClaudeContent {
     name: Option<String>,
     #[serde(default)]
     input: Value,
+    #[serde(default)]
+    thinking: String,
+    #[serde(default)]
+    signature: Option<String>,
+    #[serde(default)]
+    data: Option<String>,
+}
+
+impl ClaudeContent {
+    /// A thinking or redacted thinking block as a [`ReasoningItem`].
+    fn reasoning(&self) -> Option<ReasoningItem> {
+        let (kind, summary, encrypted_content) = match self.content_type.as_str() {
+            "thinking" => (
+                ReasoningKind::Thinking,
+                vec![self.thinking.clone()],
+                self.signature.clone(),
+            ),
+            "redacted_thinking" => (
+                ReasoningKind::RedactedThinking,
+                Vec::new(),
+                self.data.clone(),
+            ),
+            _ => return None,
+        };
+        Some(ReasoningItem {
+            id: String::new(),
+            kind,
+            summary,
+            encrypted_content,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -209,11 +326,18 @@ impl LlmClient for ClaudeClient {
             })
             .collect();
 
+        let reasoning = api_response
+            .content
+            .iter()
+            .filter_map(ClaudeContent::reasoning)
+            .collect();
+
         let message = if tool_calls.is_empty() {
             Message::assistant(content)
         } else {
             Message::assistant_with_tools(content, tool_calls)
-        };
+        }
+        .with_reasoning(reasoning);
 
         Ok(LlmResponse {
             message,
@@ -260,13 +384,16 @@ impl LlmClient for ClaudeClient {
             // Maps content_block index → tool_use id, so delta events can find their tool call
             let mut tool_block_ids: std::collections::HashMap<u64, String> =
                 std::collections::HashMap::new();
+            // Thinking blocks being streamed, by content_block index
+            let mut thinking_blocks: std::collections::HashMap<u64, ClaudeContent> =
+                std::collections::HashMap::new();
 
             while let Some(chunk) = stream.next().await {
                 let chunk = match chunk {
                     Ok(c) => c,
                     Err(e) => {
-                        let _ = tx.unbounded_send(StreamEvent::Error(e.to_string()));
-                        break;
+                        let _ = tx.unbounded_send(StreamEvent::Dropped(e.to_string()));
+                        return;
                     }
                 };
 
@@ -303,6 +430,38 @@ impl LlmClient for ClaudeClient {
                                                 ));
                                             }
                                         }
+                                        Some("thinking_delta") => {
+                                            if let Some(text) =
+                                                delta.get("thinking").and_then(|t| t.as_str())
+                                            {
+                                                let index = event
+                                                    .get("index")
+                                                    .and_then(|i| i.as_u64())
+                                                    .unwrap_or(0);
+                                                if let Some(block) = thinking_blocks.get_mut(&index)
+                                                {
+                                                    block.thinking.push_str(text);
+                                                }
+                                                let _ = tx.unbounded_send(
+                                                    StreamEvent::ReasoningDelta(text.to_string()),
+                                                );
+                                            }
+                                        }
+                                        Some("signature_delta") => {
+                                            let index = event
+                                                .get("index")
+                                                .and_then(|i| i.as_u64())
+                                                .unwrap_or(0);
+                                            if let (Some(block), Some(signature)) = (
+                                                thinking_blocks.get_mut(&index),
+                                                delta.get("signature").and_then(|s| s.as_str()),
+                                            ) {
+                                                block
+                                                    .signature
+                                                    .get_or_insert_with(String::new)
+                                                    .push_str(signature);
+                                            }
+                                        }
                                         Some("input_json_delta") => {
                                             if let Some(partial) =
                                                 delta.get("partial_json").and_then(|t| t.as_str())
@@ -327,7 +486,19 @@ impl LlmClient for ClaudeClient {
                             }
                             Some("content_block_start") => {
                                 if let Some(cb) = event.get("content_block") {
-                                    if cb.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
+                                    let block_type = cb.get("type").and_then(|t| t.as_str());
+                                    if matches!(block_type, Some("thinking" | "redacted_thinking"))
+                                    {
+                                        let index = event
+                                            .get("index")
+                                            .and_then(|i| i.as_u64())
+                                            .unwrap_or(0);
+                                        if let Ok(block) =
+                                            serde_json::from_value::<ClaudeContent>(cb.clone())
+                                        {
+                                            thinking_blocks.insert(index, block);
+                                        }
+                                    } else if block_type == Some("tool_use") {
                                         let index = event
                                             .get("index")
                                             .and_then(|i| i.as_u64())
@@ -355,6 +526,11 @@ impl LlmClient for ClaudeClient {
                                     event.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
                                 if let Some(id) = tool_block_ids.remove(&index) {
                                     let _ = tx.unbounded_send(StreamEvent::ToolCallEnd { id });
+                                } else if let Some(item) = thinking_blocks
+                                    .remove(&index)
+                                    .and_then(|block| block.reasoning())
+                                {
+                                    let _ = tx.unbounded_send(StreamEvent::Reasoning(item));
                                 }
                             }
                             Some("message_delta") => {
@@ -390,7 +566,8 @@ impl LlmClient for ClaudeClient {
                 }
             }
 
-            let _ = tx.unbounded_send(StreamEvent::Done);
+            // message_stop returns above; getting here means the connection closed.
+            let _ = tx.unbounded_send(StreamEvent::Dropped(STREAM_CLOSED_EARLY.to_string()));
         });
 
         Ok(rx)
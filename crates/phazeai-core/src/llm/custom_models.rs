@@ -0,0 +1,509 @@
+//! Custom Ollama models: named Modelfiles kept in the config directory
+//! (`models/<name>.Modelfile`), parsed, checked against what the local
+//! server supports, and rebuilt when they change.
+//!
+//! `phaze-beast` is one of them, seeded from the bundled Modelfile the first
+//! time it's needed; editing its file customizes it like any other.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde_json::{json, Map, Value};
+
+use crate::analysis::onboard::fingerprint;
+use crate::constants::{models, paths};
+use crate::error::PhazeError;
+
+const MODELFILE_EXTENSION: &str = "Modelfile";
+/// Fingerprints of the Modelfiles as last built, by model name.
+const BUILDS_FILE: &str = "builds.json";
+
+/// The bundled `phaze-beast` Modelfile.
+pub const PHAZE_BEAST_MODELFILE: &str = include_str!("../../resources/Modelfile-Phaze-Lite");
+
+/// The first release whose `/api/create` takes the model as JSON fields
+/// (`from`, `system`, `parameters`, …); older servers take the Modelfile.
+pub const STRUCTURED_CREATE_VERSION: OllamaVersion = OllamaVersion(0, 5, 5);
+/// The first release with tool calling, which templates reach as `.Tools`.
+pub const TOOLS_TEMPLATE_VERSION: OllamaVersion = OllamaVersion(0, 3, 0);
+
+/// What a `PARAMETER` takes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ParameterKind {
+    Float,
+    Int,
+    Bool,
+    Text,
+}
+
+const PARAMETERS: &[(&str, ParameterKind)] = &[
+    ("temperature", ParameterKind::Float),
+    ("top_p", ParameterKind::Float),
+    ("min_p", ParameterKind::Float),
+    ("typical_p", ParameterKind::Float),
+    ("repeat_penalty", ParameterKind::Float),
+    ("presence_penalty", ParameterKind::Float),
+    ("frequency_penalty", ParameterKind::Float),
+    ("mirostat_eta", ParameterKind::Float),
+    ("mirostat_tau", ParameterKind::Float),
+    ("tfs_z", ParameterKind::Float),
+    ("num_ctx", ParameterKind::Int),
+    ("num_predict", ParameterKind::Int),
+    ("top_k", ParameterKind::Int),
+    ("seed", ParameterKind::Int),
+    ("repeat_last_n", ParameterKind::Int),
+    ("mirostat", ParameterKind::Int),
+    ("num_gpu", ParameterKind::Int),
+    ("num_batch", ParameterKind::Int),
+    ("num_thread", ParameterKind::Int),
+    ("num_keep", ParameterKind::Int),
+    ("main_gpu", ParameterKind::Int),
+    ("use_mmap", ParameterKind::Bool),
+    ("use_mlock", ParameterKind::Bool),
+    ("numa", ParameterKind::Bool),
+    ("low_vram", ParameterKind::Bool),
+    ("penalize_newline", ParameterKind::Bool),
+    ("stop", ParameterKind::Text),
+];
+
+fn parameter_kind(name: &str) -> Option<ParameterKind> {
+    PARAMETERS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, kind)| *kind)
+}
+
+/// An Ollama server version, as `/api/version` reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OllamaVersion(pub u32, pub u32, pub u32);
+
+impl OllamaVersion {
+    /// `0.5.7`, `v0.3.14` or `0.6.0-rc2`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let core = text.trim().trim_start_matches('v');
+        let core = core.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|p| p.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+        let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+        Some(Self(major, minor, patch))
+    }
+
+    /// Source builds report `0.0.0`; they're assumed to support everything.
+    pub fn is_development(self) -> bool {
+        self == Self(0, 0, 0)
+    }
+
+    pub fn supports(self, required: OllamaVersion) -> bool {
+        self.is_development() || self >= required
+    }
+}
+
+impl fmt::Display for OllamaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+/// A model described by a Modelfile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomModel {
+    pub name: String,
+    /// The `FROM` model.
+    pub base: String,
+    pub system: Option<String>,
+    pub template: Option<String>,
+    pub license: Option<String>,
+    /// `PARAMETER` lines in order; `stop` may repeat.
+    pub parameters: Vec<(String, String)>,
+    /// `MESSAGE` lines: role and content.
+    pub messages: Vec<(String, String)>,
+    /// The Modelfile as written.
+    pub source: String,
+}
+
+/// A directive's argument: a `"""` block that may span lines, a quoted
+/// string, or the rest of the line.
+fn take_argument<'a>(
+    rest: &'a str,
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    line_no: usize,
+) -> Result<String, PhazeError> {
+    let rest = rest.trim();
+    if let Some(block) = rest.strip_prefix("\"\"\"") {
+        if let Some(end) = block.find("\"\"\"") {
+            return Ok(block[..end].to_string());
+        }
+        let mut text = block.to_string();
+        for (_, line) in lines.by_ref() {
+            if let Some(end) = line.find("\"\"\"") {
+                text.push('\n');
+                text.push_str(&line[..end]);
+                return Ok(text.trim_start_matches('\n').to_string());
+            }
+            text.push('\n');
+            text.push_str(line);
+        }
+        return Err(PhazeError::Config(format!(
+            "line {line_no}: unterminated \"\"\" block"
+        )));
+    }
+    match rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+        Some(quoted) => Ok(quoted.replace("\\\"", "\"")),
+        None => Ok(rest.to_string()),
+    }
+}
+
+impl CustomModel {
+    /// Parse the Modelfile `source` for the model `name`.
+    pub fn parse(name: &str, source: &str) -> Result<Self, PhazeError> {
+        let mut model = Self {
+            name: name.to_string(),
+            base: String::new(),
+            system: None,
+            template: None,
+            license: None,
+            parameters: Vec::new(),
+            messages: Vec::new(),
+            source: source.to_string(),
+        };
+        let mut lines = source.lines().enumerate().map(|(i, l)| (i + 1, l));
+        while let Some((line_no, line)) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (directive, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match directive.to_ascii_uppercase().as_str() {
+                "FROM" => model.base = take_argument(rest, &mut lines, line_no)?,
+                "SYSTEM" => model.system = Some(take_argument(rest, &mut lines, line_no)?),
+                "TEMPLATE" => model.template = Some(take_argument(rest, &mut lines, line_no)?),
+                "LICENSE" => model.license = Some(take_argument(rest, &mut lines, line_no)?),
+                "PARAMETER" => {
+                    let (key, value) =
+                        rest.trim().split_once(char::is_whitespace).ok_or_else(|| {
+                            PhazeError::Config(format!("line {line_no}: PARAMETER needs a value"))
+                        })?;
+                    let value = take_argument(value, &mut lines, line_no)?;
+                    model.parameters.push((key.to_ascii_lowercase(), value));
+                }
+                "MESSAGE" => {
+                    let (role, content) =
+                        rest.trim().split_once(char::is_whitespace).ok_or_else(|| {
+                            PhazeError::Config(format!("line {line_no}: MESSAGE needs content"))
+                        })?;
+                    let content = take_argument(content, &mut lines, line_no)?;
+                    model.messages.push((role.to_ascii_lowercase(), content));
+                }
+                other => {
+                    return Err(PhazeError::Config(format!(
+                        "line {line_no}: unsupported instruction {other}"
+                    )))
+                }
+            }
+        }
+        if model.base.is_empty() {
+            return Err(PhazeError::Config(format!(
+                "{name}: the Modelfile has no FROM line"
+            )));
+        }
+        Ok(model)
+    }
+
+    /// Mistakes Ollama would reject the model for.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !is_valid_name(&self.name) {
+            problems.push(format!(
+                "\"{}\" isn't a valid model name: use lowercase letters, digits, '.', '-' and '_'",
+                self.name
+            ));
+        }
+        for (key, value) in &self.parameters {
+            let valid = match parameter_kind(key) {
+                None => {
+                    problems.push(format!("unknown parameter {key}"));
+                    continue;
+                }
+                Some(ParameterKind::Float) => value.parse::<f64>().is_ok(),
+                Some(ParameterKind::Int) => value.parse::<i64>().is_ok(),
+                Some(ParameterKind::Bool) => value.parse::<bool>().is_ok(),
+                Some(ParameterKind::Text) => true,
+            };
+            if !valid {
+                problems.push(format!("parameter {key} has an invalid value \"{value}\""));
+            }
+        }
+        for (role, _) in &self.messages {
+            if !matches!(role.as_str(), "system" | "user" | "assistant") {
+                problems.push(format!(
+                    "MESSAGE role must be system, user or assistant, not {role}"
+                ));
+            }
+        }
+        problems
+    }
+
+    /// The oldest server that can build and run the model, with the feature
+    /// that needs it.
+    pub fn required_version(&self) -> Option<(OllamaVersion, &'static str)> {
+        self.template
+            .as_deref()
+            .filter(|t| t.contains(".Tools"))
+            .map(|_| {
+                (
+                    TOOLS_TEMPLATE_VERSION,
+                    "a template using .Tools (tool calling)",
+                )
+            })
+    }
+
+    /// Error out if `version` can't build the model.
+    pub fn check_server(&self, version: OllamaVersion) -> Result<(), PhazeError> {
+        match self.required_version() {
+            Some((required, feature)) if !version.supports(required) => {
+                Err(PhazeError::Llm(format!(
+                    "{} uses {feature}, which needs Ollama {required} or newer; the server is {version}",
+                    self.name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Changes whenever the Modelfile does.
+    pub fn fingerprint(&self) -> String {
+        fingerprint(&self.source)
+    }
+
+    /// The `/api/create` request for a server at `version`.
+    pub fn create_request(&self, version: OllamaVersion) -> Value {
+        if !version.supports(STRUCTURED_CREATE_VERSION) {
+            return json!({ "name": self.name, "modelfile": self.source, "stream": true });
+        }
+        let mut parameters = Map::new();
+        for (key, value) in &self.parameters {
+            let typed = match parameter_kind(key) {
+                Some(ParameterKind::Float) => value.parse::<f64>().ok().map(Value::from),
+                Some(ParameterKind::Int) => value.parse::<i64>().ok().map(Value::from),
+                Some(ParameterKind::Bool) => value.parse::<bool>().ok().map(Value::from),
+                _ => None,
+            };
+            match typed {
+                Some(typed) => {
+                    parameters.insert(key.clone(), typed);
+                }
+                None => {
+                    let stops = parameters
+                        .entry(key.clone())
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(stops) = stops {
+                        stops.push(Value::from(value.clone()));
+                    }
+                }
+            }
+        }
+        let mut request = json!({
+            "model": self.name,
+            "from": self.base,
+            "stream": true,
+        });
+        let fields = [
+            ("system", self.system.clone().map(Value::from)),
+            ("template", self.template.clone().map(Value::from)),
+            ("license", self.license.clone().map(Value::from)),
+            (
+                "parameters",
+                (!parameters.is_empty()).then_some(Value::Object(parameters)),
+            ),
+            (
+                "messages",
+                (!self.messages.is_empty()).then(|| {
+                    self.messages
+                        .iter()
+                        .map(|(role, content)| json!({ "role": role, "content": content }))
+                        .collect()
+                }),
+            ),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                request[key] = value;
+            }
+        }
+        request
+    }
+}
+
+/// A status line streamed back while a model is created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateProgress {
+    pub status: String,
+    /// Bytes pulled so far and in all, while pulling the base model.
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+}
+
+/// Read a line of the `/api/create` stream: its progress, nothing for a
+/// blank line, or the server's error.
+pub fn parse_create_line(line: &str) -> Result<Option<CreateProgress>, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let value: Value = serde_json::from_str(line).map_err(|_| line.to_string())?;
+    if let Some(error) = value["error"].as_str() {
+        return Err(error.to_string());
+    }
+    Ok(Some(CreateProgress {
+        status: value["status"].as_str().unwrap_or_default().to_string(),
+        completed: value["completed"].as_u64(),
+        total: value["total"].as_u64(),
+    }))
+}
+
+/// Ollama model names: lowercase letters, digits, `.`, `-` and `_`, with an
+/// optional `:tag`.
+fn is_valid_name(name: &str) -> bool {
+    let (model, tag) = name.split_once(':').unwrap_or((name, "latest"));
+    let ok = |s: &str| {
+        !s.is_empty()
+            && s.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '-' | '_')
+            })
+    };
+    ok(model) && ok(tag)
+}
+
+/// Whether a model's Modelfile matches what was last built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildStatus {
+    NotBuilt,
+    /// Edited since it was built.
+    Changed,
+    Built,
+}
+
+impl BuildStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NotBuilt => "not built",
+            Self::Changed => "changed",
+            Self::Built => "built",
+        }
+    }
+}
+
+/// The directory of custom Modelfiles.
+#[derive(Debug, Clone)]
+pub struct ModelStore {
+    dir: PathBuf,
+}
+
+impl ModelStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `<config>/phazeai/models`.
+    pub fn default_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(paths::CONFIG_DIR)
+            .join(paths::MODELS_DIR)
+    }
+
+    pub fn open_default() -> Self {
+        Self::new(Self::default_dir())
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        // `:` separates the tag but isn't allowed in Windows file names.
+        self.dir
+            .join(format!("{}.{MODELFILE_EXTENSION}", name.replace(':', "@")))
+    }
+
+    /// Names of the models with a Modelfile, `phaze-beast` always among
+    /// them, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some(MODELFILE_EXTENSION) {
+                    return None;
+                }
+                let stem = path.file_stem()?.to_str()?;
+                Some(stem.replace('@', ":"))
+            })
+            .collect();
+        if !names.iter().any(|n| n == models::PHAZE_BEAST) {
+            names.push(models::PHAZE_BEAST.to_string());
+        }
+        names.sort();
+        names
+    }
+
+    /// The model's Modelfile as text; `phaze-beast` falls back to the
+    /// bundled one until it's been edited.
+    pub fn read(&self, name: &str) -> Result<String, PhazeError> {
+        match std::fs::read_to_string(self.path(name)) {
+            Ok(source) => Ok(source),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && name == models::PHAZE_BEAST => {
+                Ok(PHAZE_BEAST_MODELFILE.to_string())
+            }
+            Err(e) => Err(PhazeError::Config(format!(
+                "reading the Modelfile for {name}: {e}"
+            ))),
+        }
+    }
+
+    pub fn load(&self, name: &str) -> Result<CustomModel, PhazeError> {
+        CustomModel::parse(name, &self.read(name)?)
+    }
+
+    /// The model's Modelfile path, written from the bundled `phaze-beast` or
+    /// `template`'s Modelfile if it doesn't exist yet, ready to edit.
+    pub fn edit(&self, name: &str, template: Option<&str>) -> Result<PathBuf, PhazeError> {
+        let path = self.path(name);
+        if !path.exists() {
+            let source = match template {
+                Some(template) => self.read(template)?,
+                None => self.read(models::PHAZE_BEAST)?,
+            };
+            std::fs::create_dir_all(&self.dir)?;
+            std::fs::write(&path, source)?;
+        }
+        Ok(path)
+    }
+
+    fn builds(&self) -> HashMap<String, String> {
+        std::fs::read_to_string(self.dir.join(BUILDS_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self, model: &CustomModel) -> BuildStatus {
+        match self.builds().get(&model.name) {
+            None => BuildStatus::NotBuilt,
+            Some(built) if *built == model.fingerprint() => BuildStatus::Built,
+            Some(_) => BuildStatus::Changed,
+        }
+    }
+
+    /// Remember that the model was built from its current Modelfile.
+    pub fn record_build(&self, model: &CustomModel) -> Result<(), PhazeError> {
+        let mut builds = self.builds();
+        builds.insert(model.name.clone(), model.fingerprint());
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(
+            self.dir.join(BUILDS_FILE),
+            serde_json::to_string_pretty(&builds)?,
+        )?;
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
+use crate::constants::endpoints;
 use crate::error::PhazeError;
 use crate::llm::provider::{ModelInfo, ProviderId};
 use ollama_rs::Ollama;
 use serde::Deserialize;
 
-/// Discover locally available models from Ollama and LM Studio.
+/// Discover locally available models from Ollama, LM Studio, llama.cpp and vLLM.
 pub struct LocalDiscovery;
 
 impl LocalDiscovery {
@@ -46,40 +47,7 @@ impl LocalDiscovery {
 
     /// Check if LM Studio is running and list models.
     pub async fn lm_studio_models(base_url: &str) -> Result<Vec<ModelInfo>, PhazeError> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(3))
-            .build()?;
-
-        let url = format!("{}/v1/models", base_url);
-        let response = client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| PhazeError::Llm(format!("LM Studio not reachable: {e}")))?;
-
-        if !response.status().is_success() {
-            return Err(PhazeError::Llm("LM Studio returned error".into()));
-        }
-
-        let body: OpenAIModelsResponse = response
-            .json()
-            .await
-            .map_err(|e| PhazeError::Llm(format!("Failed to parse LM Studio response: {e}")))?;
-
-        let models = body
-            .data
-            .into_iter()
-            .map(|m| ModelInfo {
-                name: m.id.clone(),
-                id: m.id,
-                context_window: 4096, // LM Studio doesn't expose this
-                supports_tools: true,
-                input_cost_per_m: 0.0,
-                output_cost_per_m: 0.0,
-            })
-            .collect();
-
-        Ok(models)
+        openai_models(base_url, "LM Studio").await
     }
 
     /// Check if LM Studio is running.
@@ -97,26 +65,117 @@ impl LocalDiscovery {
         }
     }
 
+    /// The model a llama.cpp `llama-server` was started with.
+    pub async fn llama_cpp_models(base_url: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+        openai_models(base_url, "llama.cpp").await
+    }
+
+    /// Check if `llama-server` is up with its model loaded: `/health` answers
+    /// 503 while loading.
+    pub async fn llama_cpp_available(base_url: &str) -> bool {
+        health_ok(base_url).await
+    }
+
+    /// The models a vLLM server is serving.
+    pub async fn vllm_models(base_url: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+        openai_models(base_url, "vLLM").await
+    }
+
+    /// Check if vLLM is up; `/health` answers once the engine is ready.
+    pub async fn vllm_available(base_url: &str) -> bool {
+        health_ok(base_url).await
+    }
+
     /// Discover all available local providers and their models.
     pub async fn discover_all() -> Vec<(ProviderId, Vec<ModelInfo>)> {
         let mut results = Vec::new();
 
         // Check Ollama
-        if let Ok(models) = Self::ollama_models("http://localhost:11434").await {
+        if let Ok(models) = Self::ollama_models(endpoints::OLLAMA_BASE_URL).await {
             if !models.is_empty() {
                 results.push((ProviderId::Ollama, models));
             }
         }
 
         // Check LM Studio
-        if let Ok(models) = Self::lm_studio_models("http://localhost:1234").await {
+        if let Ok(models) = Self::lm_studio_models(endpoints::LMSTUDIO_BASE_URL).await {
             if !models.is_empty() {
                 results.push((ProviderId::LmStudio, models));
             }
         }
 
+        // Check llama.cpp and vLLM, only once they report healthy
+        if Self::llama_cpp_available(endpoints::LLAMACPP_BASE_URL).await {
+            if let Ok(models) = Self::llama_cpp_models(endpoints::LLAMACPP_BASE_URL).await {
+                results.push((ProviderId::LlamaCpp, models));
+            }
+        }
+        if Self::vllm_available(endpoints::VLLM_BASE_URL).await {
+            if let Ok(models) = Self::vllm_models(endpoints::VLLM_BASE_URL).await {
+                results.push((ProviderId::Vllm, models));
+            }
+        }
+
         results
     }
+
+    /// Models from an OpenAI-compatible `/v1/models` body. vLLM reports each
+    /// model's context length (`max_model_len`) and llama.cpp its training
+    /// context (`meta.n_ctx_train`); otherwise it's estimated from the name.
+    pub fn parse_openai_models(body: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+        let body: OpenAIModelsResponse = serde_json::from_str(body)?;
+        Ok(body
+            .data
+            .into_iter()
+            .map(|m| ModelInfo {
+                context_window: m
+                    .max_model_len
+                    .or(m.meta.and_then(|meta| meta.n_ctx_train))
+                    .unwrap_or_else(|| estimate_context_window(&m.id)),
+                supports_tools: true,
+                name: m.id.clone(),
+                id: m.id,
+                input_cost_per_m: 0.0,
+                output_cost_per_m: 0.0,
+            })
+            .filter(|m| !m.id.is_empty())
+            .collect())
+    }
+}
+
+async fn openai_models(base_url: &str, server: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(3))
+        .build()?;
+
+    let url = format!("{}/v1/models", base_url.trim_end_matches('/'));
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| PhazeError::Llm(format!("{server} not reachable: {e}")))?;
+
+    if !response.status().is_success() {
+        return Err(PhazeError::Llm(format!("{server} returned error")));
+    }
+
+    let body = response
+        .text()
+        .await
+        .map_err(|e| PhazeError::Llm(format!("Failed to read {server} response: {e}")))?;
+    LocalDiscovery::parse_openai_models(&body)
+        .map_err(|e| PhazeError::Llm(format!("Failed to parse {server} response: {e}")))
+}
+
+async fn health_ok(base_url: &str) -> bool {
+    let Ok(client) = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .build()
+    else {
+        return false;
+    };
+    let url = format!("{}/health", base_url.trim_end_matches('/'));
+    matches!(client.get(&url).send().await, Ok(r) if r.status().is_success())
 }
 
 #[derive(Debug, Deserialize)]
@@ -127,6 +186,16 @@ struct OpenAIModelsResponse {
 #[derive(Debug, Deserialize)]
 struct OpenAIModelEntry {
     id: String,
+    #[serde(default)]
+    max_model_len: Option<usize>,
+    #[serde(default)]
+    meta: Option<ModelMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelMeta {
+    #[serde(default)]
+    n_ctx_train: Option<usize>,
 }
 
 fn format_model_name(name: &str, size: u64) -> String {
@@ -138,7 +207,7 @@ fn format_model_name(name: &str, size: u64) -> String {
     }
 }
 
-fn estimate_context_window(name: &str) -> usize {
+pub(crate) fn estimate_context_window(name: &str) -> usize {
     let lower = name.to_lowercase();
     if lower.contains("128k") {
         128_000
@@ -0,0 +1,180 @@
+//! Fill-in-the-middle completions for the editor's inline suggestions, on
+//! local servers that take raw prompts instead of only chat.
+//!
+//! llama.cpp's `llama-server` has an `/infill` endpoint that applies the
+//! loaded model's own FIM tokens. vLLM only has `/v1/completions`, so the
+//! prompt is built here in the model family's [`FimFormat`]. Other providers
+//! have no raw completion endpoint; the editor asks them through chat.
+
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::error::PhazeError;
+use crate::llm::provider::{ProviderConfig, ProviderId};
+
+/// Completions longer than this are cut off by the server.
+pub const MAX_TOKENS: u32 = 128;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a model family marks the prefix, suffix and gap in a FIM prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FimFormat {
+    /// Code Llama: `<PRE> prefix <SUF>suffix <MID>`.
+    CodeLlama,
+    /// StarCoder and StableCode: `<fim_prefix>`, `<fim_suffix>`, `<fim_middle>`.
+    StarCoder,
+    /// Qwen2.5-Coder and CodeGemma: the `<|fim_…|>` tokens.
+    Qwen,
+    /// DeepSeek Coder: `<｜fim▁begin｜>`, `<｜fim▁hole｜>`, `<｜fim▁end｜>`.
+    DeepSeek,
+    /// Codestral: suffix first, `[SUFFIX]…[PREFIX]…`.
+    Codestral,
+}
+
+impl FimFormat {
+    /// The format for a model, from its name; `None` for models without
+    /// FIM training (or not known to have it).
+    pub fn detect(model: &str) -> Option<Self> {
+        let lower = model.to_lowercase();
+        if lower.contains("codellama") || lower.contains("code-llama") {
+            Some(Self::CodeLlama)
+        } else if lower.contains("starcoder") || lower.contains("stable-code") {
+            Some(Self::StarCoder)
+        } else if (lower.contains("qwen") && lower.contains("coder")) || lower.contains("codegemma")
+        {
+            Some(Self::Qwen)
+        } else if lower.contains("deepseek-coder") || lower.contains("deepseek_coder") {
+            Some(Self::DeepSeek)
+        } else if lower.contains("codestral") {
+            Some(Self::Codestral)
+        } else {
+            None
+        }
+    }
+
+    pub fn prompt(&self, prefix: &str, suffix: &str) -> String {
+        match self {
+            Self::CodeLlama => format!("<PRE> {prefix} <SUF>{suffix} <MID>"),
+            Self::StarCoder => format!("<fim_prefix>{prefix}<fim_suffix>{suffix}<fim_middle>"),
+            Self::Qwen => format!("<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>"),
+            Self::DeepSeek => format!("<｜fim▁begin｜>{prefix}<｜fim▁hole｜>{suffix}<｜fim▁end｜>"),
+            Self::Codestral => format!("[SUFFIX]{suffix}[PREFIX]{prefix}"),
+        }
+    }
+
+    /// Tokens that end the completion, besides the model's end of text.
+    pub fn stop(&self) -> &'static [&'static str] {
+        match self {
+            Self::CodeLlama => &["<EOT>"],
+            Self::StarCoder => &["<|endoftext|>", "<file_sep>"],
+            Self::Qwen => &[
+                "<|endoftext|>",
+                "<|fim_pad|>",
+                "<|file_sep|>",
+                "<|repo_name|>",
+            ],
+            Self::DeepSeek => &["<｜end▁of▁sentence｜>"],
+            Self::Codestral => &["</s>"],
+        }
+    }
+}
+
+/// Where a FIM request goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FimEndpoint {
+    /// llama.cpp's `/infill`.
+    Infill,
+    /// `/v1/completions` with a prompt in this format.
+    Completions(FimFormat),
+}
+
+/// Sends FIM requests to one local server.
+pub struct FimClient {
+    http: reqwest::Client,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+    endpoint: FimEndpoint,
+}
+
+impl FimClient {
+    /// A client for `model` on `config`'s server, or `None` if the server has
+    /// no raw completion endpoint or the model's FIM format isn't known.
+    pub fn for_provider(config: &ProviderConfig, model: &str) -> Option<Self> {
+        let endpoint = match config.id {
+            ProviderId::LlamaCpp => FimEndpoint::Infill,
+            ProviderId::Vllm => FimEndpoint::Completions(FimFormat::detect(model)?),
+            _ => return None,
+        };
+        let http = reqwest::Client::builder().timeout(TIMEOUT).build().ok()?;
+        Some(Self {
+            http,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: config.api_key(),
+            endpoint,
+        })
+    }
+
+    pub fn endpoint(&self) -> FimEndpoint {
+        self.endpoint
+    }
+
+    /// The URL and JSON body of a request to fill in between `prefix` and
+    /// `suffix`.
+    pub fn request(&self, prefix: &str, suffix: &str) -> (String, Value) {
+        match self.endpoint {
+            FimEndpoint::Infill => (
+                format!("{}/infill", self.base_url),
+                json!({
+                    "input_prefix": prefix,
+                    "input_suffix": suffix,
+                    "n_predict": MAX_TOKENS,
+                    "stream": false,
+                }),
+            ),
+            FimEndpoint::Completions(format) => (
+                format!("{}/v1/completions", self.base_url),
+                json!({
+                    "model": self.model,
+                    "prompt": format.prompt(prefix, suffix),
+                    "max_tokens": MAX_TOKENS,
+                    "temperature": 0.0,
+                    "stop": format.stop(),
+                    "stream": false,
+                }),
+            ),
+        }
+    }
+
+    /// The completion text in a response body.
+    pub fn parse_response(&self, body: &Value) -> Option<String> {
+        let text = match self.endpoint {
+            FimEndpoint::Infill => body["content"].as_str(),
+            FimEndpoint::Completions(_) => body["choices"][0]["text"].as_str(),
+        };
+        text.map(str::to_string)
+    }
+
+    /// The text to insert between `prefix` and `suffix`.
+    pub async fn complete(&self, prefix: &str, suffix: &str) -> Result<String, PhazeError> {
+        let (url, body) = self.request(prefix, suffix);
+        let mut request = self.http.post(&url).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(PhazeError::Llm(format!(
+                "FIM request failed ({status}): {text}"
+            )));
+        }
+        let body: Value = response.json().await?;
+        self.parse_response(&body)
+            .ok_or_else(|| PhazeError::Llm("FIM response has no completion text".into()))
+    }
+}
@@ -0,0 +1,212 @@
+//! The Hugging Face Hub side of the `huggingface` provider: who a token
+//! belongs to, the text-generation models they and their organizations
+//! publish, and their Inference Endpoints.
+//!
+//! Chat itself needs nothing Hugging Face specific: Inference Providers and
+//! TGI (which every Inference Endpoint runs for LLMs) both speak the OpenAI
+//! chat API, so the provider uses [`OpenAIClient`](super::OpenAIClient) with
+//! the router or an endpoint's URL as its base URL.
+
+use serde::Deserialize;
+
+use crate::config::ProviderEntry;
+use crate::constants::endpoints;
+use crate::error::PhazeError;
+use crate::llm::discovery::estimate_context_window;
+use crate::llm::provider::{ModelInfo, ProviderConfig, ProviderId};
+
+/// Models listed per account or organization.
+const MODEL_LIMIT: usize = 100;
+
+/// The account a token belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HubAccount {
+    pub name: String,
+    pub orgs: Vec<String>,
+}
+
+impl HubAccount {
+    /// The account's own namespace, then its organizations'.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.orgs.iter().map(String::as_str))
+    }
+}
+
+/// A dedicated Inference Endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferenceEndpoint {
+    pub name: String,
+    pub namespace: String,
+    /// The model repository it serves.
+    pub model: String,
+    /// `running`, `paused`, `scaledToZero`, `initializing`...
+    pub state: String,
+    /// Set once the endpoint has been deployed.
+    pub url: Option<String>,
+}
+
+impl InferenceEndpoint {
+    pub fn is_running(&self) -> bool {
+        self.state == "running"
+    }
+
+    /// The `[[providers]]` entry that sends the `huggingface` provider's
+    /// requests to this endpoint.
+    pub fn provider_entry(&self) -> Option<ProviderEntry> {
+        Some(ProviderEntry {
+            name: "huggingface".into(),
+            enabled: true,
+            api_key_env: ProviderId::HuggingFace.default_api_key_env().into(),
+            base_url: self.url.clone()?,
+            default_model: self.model.clone(),
+        })
+    }
+}
+
+/// Talks to the Hub and Inference Endpoints APIs with a user access token.
+pub struct HuggingFaceHub {
+    http: reqwest::Client,
+    token: String,
+    hub_url: String,
+    endpoints_url: String,
+}
+
+impl HuggingFaceHub {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            token: token.into(),
+            hub_url: endpoints::HUGGINGFACE_HUB_URL.into(),
+            endpoints_url: endpoints::HUGGINGFACE_ENDPOINTS_URL.into(),
+        }
+    }
+
+    /// Using the token from the provider's `api_key_env`.
+    pub fn from_config(config: &ProviderConfig) -> Result<Self, PhazeError> {
+        let token = config.api_key().ok_or_else(|| {
+            PhazeError::Config(format!(
+                "Set {} to a Hugging Face access token",
+                config.api_key_env
+            ))
+        })?;
+        Ok(Self::new(token))
+    }
+
+    pub fn with_urls(mut self, hub_url: &str, endpoints_url: &str) -> Self {
+        self.hub_url = hub_url.trim_end_matches('/').into();
+        self.endpoints_url = endpoints_url.trim_end_matches('/').into();
+        self
+    }
+
+    pub async fn whoami(&self) -> Result<HubAccount, PhazeError> {
+        let body = self.get(&format!("{}/api/whoami-v2", self.hub_url)).await?;
+        parse_account(&body)
+    }
+
+    /// Text-generation models published under `namespace`, private ones
+    /// included when the token can see them.
+    pub async fn models(&self, namespace: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+        let url = format!(
+            "{}/api/models?author={namespace}&pipeline_tag=text-generation&sort=lastModified&limit={MODEL_LIMIT}",
+            self.hub_url
+        );
+        parse_models(&self.get(&url).await?)
+    }
+
+    /// The Inference Endpoints in `namespace`.
+    pub async fn inference_endpoints(
+        &self,
+        namespace: &str,
+    ) -> Result<Vec<InferenceEndpoint>, PhazeError> {
+        let url = format!("{}/v2/endpoint/{namespace}", self.endpoints_url);
+        parse_endpoints(namespace, &self.get(&url).await?)
+    }
+
+    async fn get(&self, url: &str) -> Result<String, PhazeError> {
+        let response = self.http.get(url).bearer_auth(&self.token).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(PhazeError::Llm(format!(
+                "Hugging Face API error ({status}): {body}"
+            )));
+        }
+        Ok(body)
+    }
+}
+
+/// Parse a `whoami-v2` response.
+pub fn parse_account(body: &str) -> Result<HubAccount, PhazeError> {
+    #[derive(Deserialize)]
+    struct WhoAmI {
+        name: String,
+        #[serde(default)]
+        orgs: Vec<Org>,
+    }
+    #[derive(Deserialize)]
+    struct Org {
+        name: String,
+    }
+    let who: WhoAmI = serde_json::from_str(body)?;
+    Ok(HubAccount {
+        name: who.name,
+        orgs: who.orgs.into_iter().map(|o| o.name).collect(),
+    })
+}
+
+/// Parse a model search response.
+pub fn parse_models(body: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+    #[derive(Deserialize)]
+    struct HubModel {
+        id: String,
+    }
+    let models: Vec<HubModel> = serde_json::from_str(body)?;
+    Ok(models
+        .into_iter()
+        .map(|m| ModelInfo {
+            name: m.id.clone(),
+            context_window: estimate_context_window(&m.id),
+            id: m.id,
+            supports_tools: true,
+            // Billed by Hugging Face per provider or endpoint uptime.
+            input_cost_per_m: 0.0,
+            output_cost_per_m: 0.0,
+        })
+        .collect())
+}
+
+/// Parse an Inference Endpoints list response.
+pub fn parse_endpoints(namespace: &str, body: &str) -> Result<Vec<InferenceEndpoint>, PhazeError> {
+    #[derive(Deserialize)]
+    struct List {
+        items: Vec<Item>,
+    }
+    #[derive(Deserialize)]
+    struct Item {
+        name: String,
+        model: Model,
+        status: Status,
+    }
+    #[derive(Deserialize)]
+    struct Model {
+        repository: String,
+    }
+    #[derive(Deserialize)]
+    struct Status {
+        state: String,
+        #[serde(default)]
+        url: Option<String>,
+    }
+    let list: List = serde_json::from_str(body)?;
+    Ok(list
+        .items
+        .into_iter()
+        .map(|item| InferenceEndpoint {
+            name: item.name,
+            namespace: namespace.to_string(),
+            model: item.model.repository,
+            state: item.status.state,
+            url: item.status.url.filter(|u| !u.is_empty()),
+        })
+        .collect())
+}
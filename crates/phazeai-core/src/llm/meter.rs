@@ -0,0 +1,91 @@
+//! Live throughput of a streaming response, for the "tokens/sec" readouts in
+//! the chat header and the TUI status line.
+
+use std::time::{Duration, Instant};
+
+/// Measures one streamed response from the moment it's requested.
+///
+/// Output tokens are estimated from the streamed text (about 3 characters per
+/// token, as [`ConversationHistory`](crate::context::ConversationHistory)
+/// estimates) until the provider reports the real count.
+#[derive(Debug, Clone)]
+pub struct StreamMeter {
+    started: Instant,
+    first_token: Option<Duration>,
+    chars: usize,
+    reported_tokens: Option<u64>,
+}
+
+impl Default for StreamMeter {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl StreamMeter {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            first_token: None,
+            chars: 0,
+            reported_tokens: None,
+        }
+    }
+
+    /// Count streamed text; the first call marks time-to-first-token.
+    pub fn on_text(&mut self, text: &str) {
+        if self.first_token.is_none() {
+            self.first_token = Some(self.started.elapsed());
+        }
+        self.chars += text.len();
+    }
+
+    /// Use the provider's own output token count from now on.
+    pub fn set_output_tokens(&mut self, tokens: u64) {
+        self.reported_tokens = Some(tokens);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn first_token(&self) -> Option<Duration> {
+        self.first_token
+    }
+
+    pub fn output_tokens(&self) -> u64 {
+        self.reported_tokens
+            .unwrap_or_else(|| self.chars.div_ceil(3) as u64)
+    }
+
+    /// Generation rate since the first token; 0 before it arrives.
+    pub fn tokens_per_sec(&self) -> f64 {
+        self.rate_at(self.elapsed())
+    }
+
+    fn rate_at(&self, elapsed: Duration) -> f64 {
+        let Some(first) = self.first_token else {
+            return 0.0;
+        };
+        let generating = elapsed.saturating_sub(first).as_secs_f64();
+        if generating < 0.05 {
+            return 0.0;
+        }
+        self.output_tokens() as f64 / generating
+    }
+
+    /// One-line readout, e.g. `4.2s · 318 tok · 96 tok/s · first token 0.9s`.
+    pub fn summary(&self) -> String {
+        let elapsed = self.elapsed();
+        let Some(first) = self.first_token else {
+            return format!("{:.1}s · waiting for first token", elapsed.as_secs_f64());
+        };
+        format!(
+            "{:.1}s · {} tok · {:.0} tok/s · first token {:.1}s",
+            elapsed.as_secs_f64(),
+            self.output_tokens(),
+            self.rate_at(elapsed),
+            first.as_secs_f64()
+        )
+    }
+}
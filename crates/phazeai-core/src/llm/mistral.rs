@@ -0,0 +1,149 @@
+//! Mistral's La Plateforme.
+//!
+//! Chat is OpenAI-compatible and goes through
+//! [`OpenAIClient`](super::OpenAIClient); Mistral sends usage with the last
+//! streamed chunk on its own and rejects fields it doesn't know, so the
+//! client leaves `stream_options` out. `/v1/models` lists the models the
+//! key can use, fine-tuned ones included, with their capabilities and
+//! context lengths.
+
+use futures::channel::mpsc;
+use serde::Deserialize;
+
+use crate::constants::{endpoints, models};
+use crate::error::PhazeError;
+use crate::llm::provider::{ModelInfo, ProviderConfig, ProviderId, ProviderRegistry};
+use crate::llm::traits::*;
+use crate::llm::OpenAIClient;
+use crate::tools::ToolDefinition;
+
+pub struct MistralClient {
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl MistralClient {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: endpoints::MISTRAL_BASE_URL.to_string(),
+            model: models::DEFAULT_MISTRAL_MODEL.to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// A client with the provider's key and base URL.
+    pub fn from_provider(config: &ProviderConfig, model: &str) -> Result<Self, PhazeError> {
+        let api_key = config.api_key().ok_or_else(|| {
+            PhazeError::Config(format!(
+                "Set {} environment variable for Mistral",
+                config.api_key_env
+            ))
+        })?;
+        Ok(Self::new(api_key)
+            .with_model(model)
+            .with_base_url(&config.base_url))
+    }
+
+    fn openai(&self) -> OpenAIClient {
+        OpenAIClient::new(&self.api_key)
+            .with_model(&self.model)
+            .with_base_url(&self.base_url)
+    }
+
+    /// The chat models the key can use.
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, PhazeError> {
+        let response = reqwest::Client::new()
+            .get(format!("{}/v1/models", self.base_url))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(PhazeError::Llm(format!(
+                "Mistral model listing failed ({status}): {body}"
+            )));
+        }
+        parse_models(&body)
+    }
+}
+
+/// The chat models in a `/v1/models` listing, without deprecated ones.
+/// Prices are the known models' for the same family (`mistral-large-2411`
+/// is `mistral-large-latest`'s); others are unknown and count as free.
+pub fn parse_models(body: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+    #[derive(Deserialize)]
+    struct List {
+        #[serde(default)]
+        data: Vec<Model>,
+    }
+    #[derive(Deserialize)]
+    struct Model {
+        id: String,
+        #[serde(default)]
+        capabilities: Capabilities,
+        max_context_length: Option<usize>,
+        deprecation: Option<String>,
+    }
+    #[derive(Default, Deserialize)]
+    struct Capabilities {
+        #[serde(default)]
+        completion_chat: bool,
+        #[serde(default)]
+        function_calling: bool,
+    }
+    let list: List = serde_json::from_str(body)?;
+    let known = ProviderRegistry::known_models(&ProviderId::Mistral);
+    Ok(list
+        .data
+        .into_iter()
+        .filter(|m| m.capabilities.completion_chat && m.deprecation.is_none())
+        .map(|m| {
+            let family = known
+                .iter()
+                .filter(|k| m.id.starts_with(k.id.trim_end_matches("-latest")))
+                .max_by_key(|k| k.id.len());
+            ModelInfo {
+                name: m.id.clone(),
+                context_window: m
+                    .max_context_length
+                    .or(family.map(|k| k.context_window))
+                    .unwrap_or(32_768),
+                supports_tools: m.capabilities.function_calling,
+                input_cost_per_m: family.map_or(0.0, |k| k.input_cost_per_m),
+                output_cost_per_m: family.map_or(0.0, |k| k.output_cost_per_m),
+                id: m.id,
+            }
+        })
+        .collect())
+}
+
+#[async_trait::async_trait]
+impl LlmClient for MistralClient {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        self.openai().chat(messages, tools).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        self.openai().chat_stream(messages, tools).await
+    }
+}
@@ -1,17 +1,42 @@
+pub mod aws;
+pub mod batch;
+pub mod bedrock;
 mod claude;
+pub mod custom_models;
 pub mod discovery;
+pub mod fim;
+pub mod huggingface;
+mod meter;
+pub mod mistral;
 pub mod model_router;
 mod ollama;
 pub mod ollama_manager;
 mod openai;
 pub mod provider;
+pub mod responses;
+pub mod speculative;
 mod traits;
+pub mod vertex;
+pub mod xai;
 
+pub use batch::{BatchClient, BatchRequest, BatchResult, BatchState, BatchStatus};
+pub use bedrock::BedrockClient;
 pub use claude::ClaudeClient;
+pub use custom_models::{BuildStatus, CustomModel, ModelStore, OllamaVersion};
 pub use discovery::LocalDiscovery;
+pub use fim::{FimClient, FimFormat};
+pub use huggingface::HuggingFaceHub;
+pub use meter::StreamMeter;
+pub use mistral::MistralClient;
 pub use model_router::{ModelRoute, ModelRouter, TaskType};
 pub use ollama::OllamaClient;
 pub use ollama_manager::OllamaManager;
-pub use openai::OpenAIClient;
-pub use provider::{ModelInfo, ProviderConfig, ProviderId, ProviderRegistry, UsageTracker};
+pub use openai::{OpenAIClient, ResponseHeadersHook};
+pub use provider::{
+    LatencyStats, ModelInfo, ProviderConfig, ProviderId, ProviderRegistry, Quota, UsageTracker,
+};
+pub use responses::{ReasoningEffort, ResponsesClient};
+pub use speculative::SpeculativeClient;
 pub use traits::*;
+pub use vertex::VertexClient;
+pub use xai::XaiClient;
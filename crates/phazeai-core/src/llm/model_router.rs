@@ -1,5 +1,6 @@
 use crate::error::PhazeError;
 use crate::llm::provider::{ProviderId, ProviderRegistry};
+use crate::llm::speculative::SpeculativeClient;
 use crate::llm::traits::*;
 use crate::tools::ToolDefinition;
 use futures::channel::mpsc;
@@ -20,6 +21,8 @@ pub enum TaskType {
     CodeReview,
     /// Simple factual answers, quick lookups
     QuickAnswer,
+    /// Inline completions in the editor (ghost text); never classified from chat
+    Completion,
 }
 
 impl TaskType {
@@ -31,6 +34,7 @@ impl TaskType {
             TaskType::CodeGeneration,
             TaskType::CodeReview,
             TaskType::QuickAnswer,
+            TaskType::Completion,
         ]
     }
 
@@ -41,6 +45,7 @@ impl TaskType {
             TaskType::CodeGeneration => "code_generation",
             TaskType::CodeReview => "code_review",
             TaskType::QuickAnswer => "quick_answer",
+            TaskType::Completion => "completion",
         }
     }
 
@@ -110,6 +115,14 @@ impl TaskType {
 pub struct ModelRoute {
     pub provider: String,
     pub model: String,
+    /// A smaller, faster model that drafts each reply for this one to accept
+    /// or correct (see [`SpeculativeClient`]). Tool calls skip it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub draft: Option<Box<ModelRoute>>,
+    /// Claude's extended thinking budget for this task type, in place of
+    /// `[llm] thinking_budget`; 0 turns thinking off for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<u32>,
 }
 
 /// Routes different task types to different LLM provider/model pairs.
@@ -137,7 +150,13 @@ impl ModelRouter {
         for (task_type, route) in route_configs {
             let provider_id = Self::parse_provider_id(&route.provider);
             if let Some(config) = registry.get_config(&provider_id) {
-                match registry.build_client_for(config, &route.model) {
+                let built = match route.thinking_budget {
+                    Some(budget) => {
+                        registry.build_client_with_thinking(config, &route.model, Some(budget))
+                    }
+                    None => registry.build_client_for(config, &route.model),
+                };
+                match built {
                     Ok(client) => {
                         tracing::info!(
                             "Model route: {:?} -> {} / {}",
@@ -145,7 +164,7 @@ impl ModelRouter {
                             route.provider,
                             route.model
                         );
-                        routes.insert(*task_type, client);
+                        routes.insert(*task_type, Self::with_draft(route, registry, client));
                     }
                     Err(e) => {
                         tracing::warn!(
@@ -188,6 +207,48 @@ impl ModelRouter {
         self.routes.len()
     }
 
+    /// Wrap `client`, built for `route`, so that `route.draft` drafts its
+    /// replies. A draft model that fails to build is skipped with a warning.
+    pub fn with_draft(
+        route: &ModelRoute,
+        registry: &ProviderRegistry,
+        client: Box<dyn LlmClient>,
+    ) -> Box<dyn LlmClient> {
+        let Some(draft) = &route.draft else {
+            return client;
+        };
+        let provider_id = Self::parse_provider_id(&draft.provider);
+        let built = match registry.get_config(&provider_id) {
+            Some(config) => registry.build_client_for(config, &draft.model),
+            None => Err(PhazeError::Config(format!(
+                "provider '{}' not found",
+                draft.provider
+            ))),
+        };
+        match built {
+            Ok(draft_client) => {
+                tracing::info!(
+                    "Draft model for {} / {}: {} / {}",
+                    route.provider,
+                    route.model,
+                    draft.provider,
+                    draft.model
+                );
+                Box::new(SpeculativeClient::new(draft_client, client))
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to build draft model {} / {}: {}. Using {} alone.",
+                    draft.provider,
+                    draft.model,
+                    e,
+                    route.model
+                );
+                client
+            }
+        }
+    }
+
     fn parse_provider_id(name: &str) -> ProviderId {
         match name.to_lowercase().as_str() {
             "claude" | "anthropic" => ProviderId::Claude,
@@ -196,8 +257,16 @@ impl ModelRouter {
             "groq" => ProviderId::Groq,
             "together" => ProviderId::Together,
             "openrouter" => ProviderId::OpenRouter,
+            "xai" | "grok" => ProviderId::Xai,
+            "mistral" => ProviderId::Mistral,
             "lmstudio" | "lm_studio" => ProviderId::LmStudio,
+            "llamacpp" | "llama.cpp" | "llama-cpp" => ProviderId::LlamaCpp,
+            "vllm" => ProviderId::Vllm,
+            "huggingface" | "hf" | "tgi" => ProviderId::HuggingFace,
+            "bedrock" | "aws" => ProviderId::Bedrock,
             "gemini" => ProviderId::Gemini,
+            "vertex" | "vertex-ai" => ProviderId::Vertex,
+            "phazecloud" | "phaze-cloud" | "cloud" => ProviderId::PhazeCloud,
             other => ProviderId::Custom(other.to_string()),
         }
     }
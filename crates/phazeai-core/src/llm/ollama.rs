@@ -7,6 +7,7 @@ use ollama_rs::generation::chat::{
     ChatMessage as OllamaChatMessage, ChatMessageRequest, ChatToolCall, ChatToolFunction,
     MessageRole,
 };
+use ollama_rs::generation::images::Image;
 use ollama_rs::Ollama;
 use serde_json::Value;
 
@@ -77,7 +78,17 @@ impl OllamaClient {
                         Role::Assistant => MessageRole::Assistant,
                         Role::System => MessageRole::System,
                     };
-                    OllamaChatMessage::new(role, m.content.clone())
+                    let msg = OllamaChatMessage::new(role, m.content.clone());
+                    if m.images.is_empty() {
+                        msg
+                    } else {
+                        msg.with_images(
+                            m.images
+                                .iter()
+                                .map(|image| Image::from_base64(image.data.clone()))
+                                .collect(),
+                        )
+                    }
                 }
             })
             .collect()
@@ -230,7 +241,8 @@ impl LlmClient for OllamaClient {
                 }
             }
 
-            let _ = tx.unbounded_send(StreamEvent::Done);
+            // A final `done` chunk returns above.
+            let _ = tx.unbounded_send(StreamEvent::Dropped(STREAM_CLOSED_EARLY.to_string()));
         });
 
         Ok(rx)
@@ -1,4 +1,8 @@
+use crate::constants::models;
 use crate::error::PhazeError;
+use crate::llm::custom_models::{self, BuildStatus, CustomModel, ModelStore, OllamaVersion};
+use crate::progress;
+use futures::StreamExt;
 use ollama_rs::models::create::CreateModelRequest;
 use ollama_rs::Ollama;
 use std::path::Path;
@@ -6,7 +10,8 @@ use tracing::info;
 
 pub struct OllamaManager {
     ollama: Ollama,
-    _base_url: String,
+    http: reqwest::Client,
+    base_url: String,
 }
 
 impl OllamaManager {
@@ -15,7 +20,8 @@ impl OllamaManager {
             .map_err(|e| PhazeError::Llm(format!("Invalid Ollama URL: {e}")))?;
         Ok(Self {
             ollama,
-            _base_url: base_url.to_string(),
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
         })
     }
 
@@ -28,7 +34,7 @@ impl OllamaManager {
             .map_err(|e| {
                 PhazeError::Llm(format!(
                     "Ollama is not responding at {}. Is it running at that address? Error: {}",
-                    self._base_url, e
+                    self.base_url, e
                 ))
             })
     }
@@ -46,11 +52,115 @@ impl OllamaManager {
             .any(|m| m.name == model_name || m.name.starts_with(&format!("{}:", model_name))))
     }
 
-    /// Specifically ensure Phaze-Beast is provisioned with optimized settings
+    /// The server's version, from `/api/version`.
+    pub async fn server_version(&self) -> Result<OllamaVersion, PhazeError> {
+        let body: serde_json::Value = self
+            .http
+            .get(format!("{}/api/version", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let version = body["version"].as_str().unwrap_or_default();
+        OllamaVersion::parse(version)
+            .ok_or_else(|| PhazeError::Llm(format!("Unrecognized Ollama version \"{version}\"")))
+    }
+
+    /// Make sure Phaze-Beast is built from its Modelfile, rebuilding it if
+    /// the Modelfile was edited.
     pub async fn ensure_phaze_beast(&self) -> Result<(), PhazeError> {
-        let modelfile = include_str!("../../resources/Modelfile-Phaze-Lite");
-        self.ensure_model_from_content("phaze-beast", modelfile)
-            .await
+        let store = ModelStore::open_default();
+        let model = store.load(models::PHAZE_BEAST)?;
+        self.build_custom_model(&store, &model, false).await?;
+        Ok(())
+    }
+
+    /// Build a custom model unless it's already built from its current
+    /// Modelfile (or `force`). Checks the Modelfile and the server's version
+    /// first, and reports the build on the progress bus, where it can be
+    /// cancelled. Returns whether it built.
+    pub async fn build_custom_model(
+        &self,
+        store: &ModelStore,
+        model: &CustomModel,
+        force: bool,
+    ) -> Result<bool, PhazeError> {
+        let problems = model.problems();
+        if !problems.is_empty() {
+            return Err(PhazeError::Config(format!(
+                "{}: {}",
+                model.name,
+                problems.join("; ")
+            )));
+        }
+        if !force
+            && store.status(model) == BuildStatus::Built
+            && self.model_exists(&model.name).await?
+        {
+            return Ok(false);
+        }
+        let version = self.server_version().await?;
+        model.check_server(version)?;
+
+        info!("Building {} from {}...", model.name, model.base);
+        let task = progress::global()
+            .task(format!("Building {}", model.name))
+            .unit("%")
+            .cancellable()
+            .start();
+        let response = self
+            .http
+            .post(format!("{}/api/create", self.base_url))
+            .json(&model.create_request(version))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = custom_models::parse_create_line(&body)
+                .err()
+                .unwrap_or(body);
+            return Err(PhazeError::Llm(format!(
+                "Failed to build {}: {message}",
+                model.name
+            )));
+        }
+        let mut stream = response.bytes_stream();
+        let mut pending = Vec::new();
+        let report = |line: &[u8]| -> Result<(), PhazeError> {
+            let line = String::from_utf8_lossy(line);
+            match custom_models::parse_create_line(&line) {
+                Ok(Some(status)) => {
+                    task.set_message(status.status);
+                    match status.total.filter(|t| *t > 0) {
+                        Some(total) => {
+                            task.set_total(Some(100));
+                            task.set_done(status.completed.unwrap_or(0) * 100 / total);
+                        }
+                        None => task.set_total(None),
+                    }
+                    Ok(())
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(PhazeError::Llm(format!(
+                    "Failed to build {}: {e}",
+                    model.name
+                ))),
+            }
+        };
+        while let Some(chunk) = stream.next().await {
+            if task.is_cancelled() {
+                return Err(PhazeError::Cancelled);
+            }
+            pending.extend_from_slice(&chunk?);
+            while let Some(end) = pending.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                report(&line)?;
+            }
+        }
+        report(&pending)?;
+        store.record_build(model)?;
+        Ok(true)
     }
 
     /// Provision a model from strings (useful for bundled resources).
@@ -130,10 +240,10 @@ impl OllamaManager {
 
         let mut provisioned = Vec::new();
 
-        if !self.model_exists("phaze-beast").await? {
-            info!("Provisioning phaze-beast...");
-            self.ensure_phaze_beast().await?;
-            provisioned.push("phaze-beast".to_string());
+        let store = ModelStore::open_default();
+        let beast = store.load(models::PHAZE_BEAST)?;
+        if self.build_custom_model(&store, &beast, false).await? {
+            provisioned.push(models::PHAZE_BEAST.to_string());
         }
 
         if !self.model_exists("phaze-coder").await? {
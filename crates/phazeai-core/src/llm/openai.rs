@@ -4,12 +4,19 @@ use crate::tools::ToolDefinition;
 use futures::channel::mpsc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
+
+/// Called with the headers of every API response, before the body is read.
+pub type ResponseHeadersHook = Arc<dyn Fn(&reqwest::header::HeaderMap) + Send + Sync>;
 
 pub struct OpenAIClient {
     client: reqwest::Client,
     api_key: String,
     model: String,
     base_url: String,
+    chat_path: String,
+    stream_usage: bool,
+    on_response_headers: Option<ResponseHeadersHook>,
 }
 
 impl OpenAIClient {
@@ -19,6 +26,9 @@ impl OpenAIClient {
             api_key: api_key.into(),
             model: crate::constants::models::DEFAULT_OPENAI_MODEL.to_string(),
             base_url: crate::constants::endpoints::OPENAI_BASE_URL.to_string(),
+            chat_path: "/v1/chat/completions".to_string(),
+            stream_usage: false,
+            on_response_headers: None,
         }
     }
 
@@ -32,6 +42,28 @@ impl OpenAIClient {
         self
     }
 
+    /// For servers whose chat endpoint isn't at `/v1/chat/completions`
+    /// under the base URL (e.g. Vertex AI's `/chat/completions`).
+    pub fn with_chat_path(mut self, path: impl Into<String>) -> Self {
+        self.chat_path = path.into();
+        self
+    }
+
+    /// Ask for token usage at the end of streamed responses
+    /// (`stream_options.include_usage`). Off by default: servers that
+    /// send usage anyway (Mistral) or reject unknown fields don't need it.
+    pub fn with_stream_usage(mut self, enabled: bool) -> Self {
+        self.stream_usage = enabled;
+        self
+    }
+
+    /// Inspect response headers, e.g. for rate limit or quota information
+    /// that OpenAI-compatible proxies send alongside the body.
+    pub fn on_response_headers(mut self, hook: ResponseHeadersHook) -> Self {
+        self.on_response_headers = Some(hook);
+        self
+    }
+
     fn build_tool_defs(&self, tools: &[ToolDefinition]) -> Vec<Value> {
         tools
             .iter()
@@ -83,8 +115,27 @@ struct OpenAIFunction {
 
 #[derive(Debug, Deserialize)]
 struct OpenAIUsage {
+    #[serde(default)]
     prompt_tokens: u32,
+    #[serde(default)]
     completion_tokens: u32,
+    #[serde(default)]
+    total_tokens: Option<u32>,
+}
+
+impl From<OpenAIUsage> for Usage {
+    /// Reasoning models (xAI's Grok among them) leave their reasoning
+    /// tokens out of `completion_tokens` but bill them as output; the
+    /// total still counts them.
+    fn from(u: OpenAIUsage) -> Self {
+        let reasoning_inclusive = u
+            .total_tokens
+            .map_or(0, |total| total.saturating_sub(u.prompt_tokens));
+        Usage {
+            input_tokens: u.prompt_tokens,
+            output_tokens: u.completion_tokens.max(reasoning_inclusive),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -95,6 +146,23 @@ struct OpenAIRequest {
     tools: Option<Vec<Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<Value>,
+}
+
+/// A message's content: its text, or text and images as content parts.
+fn message_content(m: &Message) -> Value {
+    if m.images.is_empty() {
+        return Value::from(m.content.as_str());
+    }
+    let mut parts = vec![serde_json::json!({ "type": "text", "text": m.content })];
+    parts.extend(m.images.iter().map(|image| {
+        serde_json::json!({
+            "type": "image_url",
+            "image_url": { "url": image.data_url() },
+        })
+    }));
+    Value::Array(parts)
 }
 
 #[async_trait::async_trait]
@@ -104,7 +172,7 @@ impl LlmClient for OpenAIClient {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<LlmResponse, PhazeError> {
-        let url = format!("{}/v1/chat/completions", self.base_url);
+        let url = format!("{}{}", self.base_url, self.chat_path);
 
         let oai_messages: Vec<Value> = messages
             .iter()
@@ -137,7 +205,7 @@ impl LlmClient for OpenAIClient {
                 } else {
                     serde_json::json!({
                         "role": m.role,
-                        "content": m.content,
+                        "content": message_content(m),
                     })
                 }
             })
@@ -152,6 +220,7 @@ impl LlmClient for OpenAIClient {
                 Some(self.build_tool_defs(tools))
             },
             stream: None,
+            stream_options: None,
         };
 
         let response = self
@@ -161,6 +230,9 @@ impl LlmClient for OpenAIClient {
             .json(&request_body)
             .send()
             .await?;
+        if let Some(hook) = &self.on_response_headers {
+            hook(response.headers());
+        }
 
         let status = response.status();
         let response_text = response.text().await?;
@@ -204,10 +276,7 @@ impl LlmClient for OpenAIClient {
 
         Ok(LlmResponse {
             message,
-            usage: api_response.usage.map(|u| Usage {
-                input_tokens: u.prompt_tokens,
-                output_tokens: u.completion_tokens,
-            }),
+            usage: api_response.usage.map(Usage::from),
         })
     }
 
@@ -216,7 +285,7 @@ impl LlmClient for OpenAIClient {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
-        let url = format!("{}/v1/chat/completions", self.base_url);
+        let url = format!("{}{}", self.base_url, self.chat_path);
 
         let oai_messages: Vec<Value> = messages
             .iter()
@@ -249,7 +318,7 @@ impl LlmClient for OpenAIClient {
                 } else {
                     serde_json::json!({
                         "role": m.role,
-                        "content": m.content,
+                        "content": message_content(m),
                     })
                 }
             })
@@ -264,6 +333,9 @@ impl LlmClient for OpenAIClient {
                 Some(self.build_tool_defs(tools))
             },
             stream: Some(true),
+            stream_options: self
+                .stream_usage
+                .then(|| serde_json::json!({ "include_usage": true })),
         };
 
         let response = self
@@ -273,6 +345,9 @@ impl LlmClient for OpenAIClient {
             .json(&request_body)
             .send()
             .await?;
+        if let Some(hook) = &self.on_response_headers {
+            hook(response.headers());
+        }
 
         if !response.status().is_success() {
             let status = response.status();
@@ -292,13 +367,15 @@ impl LlmClient for OpenAIClient {
             // Maps tool_call index → id, since OpenAI only sends id on the first delta chunk
             let mut tool_call_ids: std::collections::HashMap<u64, String> =
                 std::collections::HashMap::new();
+            // Some compatible servers end with a finish_reason but no [DONE].
+            let mut finished = false;
 
             while let Some(chunk) = stream.next().await {
                 let chunk = match chunk {
                     Ok(c) => c,
                     Err(e) => {
-                        let _ = tx.unbounded_send(StreamEvent::Error(e.to_string()));
-                        break;
+                        let _ = tx.unbounded_send(StreamEvent::Dropped(e.to_string()));
+                        return;
                     }
                 };
 
@@ -308,11 +385,10 @@ impl LlmClient for OpenAIClient {
                     let line = buffer[..line_end].trim().to_string();
                     buffer = buffer[line_end + 1..].to_string();
 
-                    if line.is_empty() || !line.starts_with("data: ") {
+                    // The space after `data:` is optional; TGI leaves it out.
+                    let Some(data) = line.strip_prefix("data:").map(str::trim_start) else {
                         continue;
-                    }
-
-                    let data = &line[6..];
+                    };
                     if data == "[DONE]" {
                         // Finalize any tool calls that didn't get an explicit end event
                         for (_, id) in tool_call_ids.drain() {
@@ -323,26 +399,34 @@ impl LlmClient for OpenAIClient {
                     }
 
                     if let Ok(event) = serde_json::from_str::<Value>(data) {
+                        // TGI reports generation errors as a stream event.
+                        if let Some(error) = event.get("error").filter(|e| !e.is_null()) {
+                            let message = error
+                                .get("message")
+                                .and_then(|m| m.as_str())
+                                .or(error.as_str())
+                                .unwrap_or("stream error");
+                            let _ = tx.unbounded_send(StreamEvent::Error(message.to_string()));
+                            return;
+                        }
+
                         // OpenAI stream_options: { include_usage: true } emits usage
                         // on the final chunk (choices=[]) — capture it here.
-                        if let Some(usage) = event.get("usage") {
-                            let input = usage
-                                .get("prompt_tokens")
-                                .and_then(|v| v.as_u64())
-                                .unwrap_or(0) as u32;
-                            let output = usage
-                                .get("completion_tokens")
-                                .and_then(|v| v.as_u64())
-                                .unwrap_or(0) as u32;
-                            if input > 0 || output > 0 {
-                                let _ = tx.unbounded_send(StreamEvent::Usage(crate::llm::Usage {
-                                    input_tokens: input,
-                                    output_tokens: output,
-                                }));
+                        if let Some(usage) = event
+                            .get("usage")
+                            .and_then(|u| serde_json::from_value::<OpenAIUsage>(u.clone()).ok())
+                        {
+                            let usage = Usage::from(usage);
+                            if usage.input_tokens > 0 || usage.output_tokens > 0 {
+                                let _ = tx.unbounded_send(StreamEvent::Usage(usage));
                             }
                         }
 
                         if let Some(choices) = event.get("choices").and_then(|c| c.as_array()) {
+                            finished |= choices
+                                .first()
+                                .and_then(|c| c.get("finish_reason"))
+                                .is_some_and(|r| r.is_string());
                             if let Some(delta) = choices.first().and_then(|c| c.get("delta")) {
                                 if let Some(content) = delta.get("content").and_then(|c| c.as_str())
                                 {
@@ -399,7 +483,11 @@ impl LlmClient for OpenAIClient {
                 }
             }
 
-            let _ = tx.unbounded_send(StreamEvent::Done);
+            let _ = tx.unbounded_send(if finished {
+                StreamEvent::Done
+            } else {
+                StreamEvent::Dropped(STREAM_CLOSED_EARLY.to_string())
+            });
         });
 
         Ok(rx)
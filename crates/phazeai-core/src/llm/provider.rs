@@ -1,6 +1,9 @@
 use crate::constants::endpoints;
+use crate::error::PhazeError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
 
 /// Identifies a specific LLM provider.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -12,8 +15,24 @@ pub enum ProviderId {
     Groq,
     Together,
     OpenRouter,
+    /// xAI's Grok models.
+    Xai,
+    /// Mistral's La Plateforme.
+    Mistral,
     LmStudio,
+    /// A raw llama.cpp `llama-server`.
+    LlamaCpp,
+    /// A vLLM OpenAI-compatible server.
+    Vllm,
+    /// Hugging Face Inference Providers, an Inference Endpoint or any TGI server.
+    HuggingFace,
+    /// Amazon Bedrock, with AWS credentials instead of an API key.
+    Bedrock,
     Gemini,
+    /// Gemini on Google Cloud Vertex AI, with Google Cloud credentials.
+    Vertex,
+    /// Hosted models through PhazeAI Cloud; available once signed in.
+    PhazeCloud,
     Custom(String),
 }
 
@@ -26,18 +45,31 @@ impl ProviderId {
             Self::Groq => "Groq",
             Self::Together => "Together.ai",
             Self::OpenRouter => "OpenRouter",
+            Self::Xai => "xAI Grok",
+            Self::Mistral => "Mistral",
             Self::LmStudio => "LM Studio (Local)",
+            Self::LlamaCpp => "llama.cpp (Local)",
+            Self::Vllm => "vLLM (Local)",
+            Self::HuggingFace => "Hugging Face",
+            Self::Bedrock => "AWS Bedrock",
             Self::Gemini => "Google Gemini",
+            Self::Vertex => "Google Vertex AI",
+            Self::PhazeCloud => "PhazeAI Cloud",
             Self::Custom(name) => name,
         }
     }
 
     pub fn is_local(&self) -> bool {
-        matches!(self, Self::Ollama | Self::LmStudio)
+        matches!(
+            self,
+            Self::Ollama | Self::LmStudio | Self::LlamaCpp | Self::Vllm
+        )
     }
 
     pub fn needs_api_key(&self) -> bool {
-        !self.is_local()
+        // PhazeAI Cloud authenticates with the signed-in session token,
+        // Bedrock and Vertex AI with their cloud's credentials.
+        !self.is_local() && !matches!(self, Self::PhazeCloud | Self::Bedrock | Self::Vertex)
     }
 
     pub fn default_base_url(&self) -> &str {
@@ -48,8 +80,18 @@ impl ProviderId {
             Self::Groq => endpoints::GROQ_BASE_URL,
             Self::Together => endpoints::TOGETHER_BASE_URL,
             Self::OpenRouter => endpoints::OPENROUTER_BASE_URL,
+            Self::Xai => endpoints::XAI_BASE_URL,
+            Self::Mistral => endpoints::MISTRAL_BASE_URL,
             Self::LmStudio => endpoints::LMSTUDIO_BASE_URL,
+            Self::LlamaCpp => endpoints::LLAMACPP_BASE_URL,
+            Self::Vllm => endpoints::VLLM_BASE_URL,
+            Self::HuggingFace => endpoints::HUGGINGFACE_BASE_URL,
+            // The AWS region's endpoint; see `bedrock::endpoint_for`.
+            Self::Bedrock => "",
             Self::Gemini => endpoints::GEMINI_BASE_URL,
+            // The project's; see `vertex::VertexProject::resolve`.
+            Self::Vertex => "",
+            Self::PhazeCloud => endpoints::PHAZECLOUD_BASE_URL,
             Self::Custom(_) => "",
         }
     }
@@ -62,8 +104,18 @@ impl ProviderId {
             Self::Groq => "GROQ_API_KEY",
             Self::Together => "TOGETHER_API_KEY",
             Self::OpenRouter => "OPENROUTER_API_KEY",
+            Self::Xai => "XAI_API_KEY",
+            Self::Mistral => "MISTRAL_API_KEY",
             Self::LmStudio => "",
+            // Both servers take an optional key (`--api-key`); these are the
+            // variables they read it from themselves.
+            Self::LlamaCpp => "LLAMA_API_KEY",
+            Self::Vllm => "VLLM_API_KEY",
+            Self::HuggingFace => "HF_TOKEN",
+            Self::Bedrock => "",
             Self::Gemini => "GEMINI_API_KEY",
+            Self::Vertex => "",
+            Self::PhazeCloud => "",
             Self::Custom(_) => "",
         }
     }
@@ -73,11 +125,18 @@ impl ProviderId {
             Self::Claude,
             Self::OpenAI,
             Self::Gemini,
+            Self::Vertex,
             Self::Ollama,
             Self::Groq,
             Self::Together,
             Self::OpenRouter,
+            Self::Xai,
+            Self::Mistral,
+            Self::HuggingFace,
+            Self::Bedrock,
             Self::LmStudio,
+            Self::LlamaCpp,
+            Self::Vllm,
         ]
     }
 }
@@ -133,6 +192,11 @@ impl ProviderConfig {
         if !self.enabled {
             return false;
         }
+        match self.id {
+            ProviderId::Bedrock => return super::aws::CredentialChain::configured_locally(),
+            ProviderId::Vertex => return super::vertex::GoogleCredentials::configured_locally(),
+            _ => {}
+        }
         if self.id.needs_api_key() {
             self.api_key().is_some()
         } else {
@@ -141,11 +205,69 @@ impl ProviderConfig {
     }
 }
 
+/// Builds a client for a provider whose implementation lives outside this
+/// crate, from its config and the model to use.
+pub type ClientFactory = Arc<
+    dyn Fn(&ProviderConfig, &str) -> Result<Box<dyn super::LlmClient>, PhazeError> + Send + Sync,
+>;
+
+/// Providers registered with [`register_provider`].
+static EXTERNAL_PROVIDERS: LazyLock<RwLock<HashMap<ProviderId, (ProviderConfig, ClientFactory)>>> =
+    LazyLock::new(Default::default);
+
+/// Make a provider implemented in another crate (e.g. PhazeAI Cloud hosted
+/// models) available: every [`ProviderRegistry`] created afterwards lists it
+/// and builds its clients with `factory`. Registering the same id again
+/// replaces it.
+pub fn register_provider(config: ProviderConfig, factory: ClientFactory) {
+    EXTERNAL_PROVIDERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(config.id.clone(), (config, factory));
+}
+
+/// Remove a provider added with [`register_provider`] (e.g. on sign-out).
+pub fn unregister_provider(id: &ProviderId) {
+    EXTERNAL_PROVIDERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(id);
+}
+
+fn external_factory(id: &ProviderId) -> Option<ClientFactory> {
+    EXTERNAL_PROVIDERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(id)
+        .map(|(_, factory)| factory.clone())
+}
+
+/// Providers an organization policy allows; `None` allows all.
+static ALLOWED_PROVIDERS: LazyLock<RwLock<Option<Vec<ProviderId>>>> =
+    LazyLock::new(Default::default);
+
+/// Restrict which providers clients may be built for (e.g. an organization's
+/// policy from PhazeAI Cloud). `None` lifts the restriction.
+pub fn set_allowed_providers(allowed: Option<Vec<ProviderId>>) {
+    *ALLOWED_PROVIDERS.write().unwrap_or_else(|e| e.into_inner()) = allowed;
+}
+
+/// Whether the current policy lets clients be built for `id`.
+pub fn is_provider_allowed(id: &ProviderId) -> bool {
+    ALLOWED_PROVIDERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_none_or(|allowed| allowed.contains(id))
+}
+
 /// Manages all configured providers and provides model listing.
 pub struct ProviderRegistry {
     providers: HashMap<ProviderId, ProviderConfig>,
     active_provider: ProviderId,
     active_model: String,
+    reasoning_effort: Option<super::ReasoningEffort>,
+    thinking_budget: Option<u32>,
 }
 
 impl Default for ProviderRegistry {
@@ -169,11 +291,20 @@ impl ProviderRegistry {
             };
             providers.insert(id, config);
         }
+        for (id, (config, _)) in EXTERNAL_PROVIDERS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+        {
+            providers.insert(id.clone(), config.clone());
+        }
 
         Self {
             providers,
             active_provider: ProviderId::Claude,
             active_model: crate::constants::models::DEFAULT_CLAUDE_MODEL.to_string(),
+            reasoning_effort: None,
+            thinking_budget: None,
         }
     }
 
@@ -198,6 +329,18 @@ impl ProviderRegistry {
         self.active_model = model;
     }
 
+    /// Effort for the reasoning models clients are built for; `None` is
+    /// the model's default.
+    pub fn set_reasoning_effort(&mut self, effort: Option<super::ReasoningEffort>) {
+        self.reasoning_effort = effort;
+    }
+
+    /// Extended thinking budget for the Claude clients built; `None` leaves
+    /// thinking off.
+    pub fn set_thinking_budget(&mut self, budget: Option<u32>) {
+        self.thinking_budget = budget;
+    }
+
     pub fn set_provider(&mut self, provider: ProviderId) {
         if let Some(config) = self.providers.get(&provider) {
             self.active_model = config.default_model.clone();
@@ -232,16 +375,24 @@ impl ProviderRegistry {
         self.providers.insert(ProviderId::Custom(name), config);
     }
 
+    /// Replace a built-in provider's config, e.g. to point it at another server.
+    pub fn configure(&mut self, config: ProviderConfig) {
+        self.providers.insert(config.id.clone(), config);
+    }
+
     /// Build an LLM client for the currently active provider/model.
     pub fn build_active_client(
         &self,
     ) -> Result<Box<dyn super::LlmClient>, crate::error::PhazeError> {
-        let config = self.active_config().ok_or_else(|| {
-            crate::error::PhazeError::Config(format!(
-                "Provider {:?} not configured",
-                self.active_provider
-            ))
-        })?;
+        let config = self
+            .active_config()
+            .ok_or_else(|| match self.active_provider {
+                ProviderId::PhazeCloud => not_signed_in(),
+                _ => crate::error::PhazeError::Config(format!(
+                    "Provider {:?} not configured",
+                    self.active_provider
+                )),
+            })?;
 
         self.build_client_for(config, &self.active_model)
     }
@@ -252,6 +403,26 @@ impl ProviderRegistry {
         config: &ProviderConfig,
         model: &str,
     ) -> Result<Box<dyn super::LlmClient>, crate::error::PhazeError> {
+        self.build_client_with_thinking(config, model, self.thinking_budget)
+    }
+
+    /// [`build_client_for`](Self::build_client_for) with its own Claude
+    /// thinking budget in place of the registry's.
+    pub fn build_client_with_thinking(
+        &self,
+        config: &ProviderConfig,
+        model: &str,
+        thinking_budget: Option<u32>,
+    ) -> Result<Box<dyn super::LlmClient>, crate::error::PhazeError> {
+        if !is_provider_allowed(&config.id) {
+            return Err(crate::error::PhazeError::Config(format!(
+                "{} is blocked by your organization's policy",
+                config.id.name()
+            )));
+        }
+        if let Some(factory) = external_factory(&config.id) {
+            return factory(config, model);
+        }
         match config.id {
             ProviderId::Claude => {
                 let api_key = config.api_key().ok_or_else(|| {
@@ -263,13 +434,35 @@ impl ProviderRegistry {
                 let client = super::ClaudeClient::new(api_key)
                     .with_model(model)
                     .with_base_url(&config.base_url)
-                    .with_max_tokens(8192);
+                    .with_max_tokens(8192)
+                    .with_thinking_budget(thinking_budget);
                 Ok(Box::new(client))
             }
             ProviderId::Ollama => {
                 let client = super::OllamaClient::new(model).with_base_url(&config.base_url);
                 Ok(Box::new(client))
             }
+            // Reasoning models keep their reasoning only through the Responses API.
+            ProviderId::OpenAI if super::responses::is_reasoning_model(model) => {
+                let api_key = config.api_key().ok_or_else(|| {
+                    crate::error::PhazeError::Config(format!(
+                        "Set {} environment variable for OpenAI",
+                        config.api_key_env
+                    ))
+                })?;
+                let client = super::ResponsesClient::new(api_key)
+                    .with_model(model)
+                    .with_base_url(&config.base_url)
+                    .with_reasoning_effort(self.reasoning_effort);
+                Ok(Box::new(client))
+            }
+            ProviderId::Xai => Ok(Box::new(super::XaiClient::from_provider(config, model)?)),
+            ProviderId::Mistral => Ok(Box::new(super::MistralClient::from_provider(
+                config, model,
+            )?)),
+            ProviderId::Bedrock => Ok(Box::new(super::BedrockClient::from_provider(config, model))),
+            ProviderId::Vertex => Ok(Box::new(super::VertexClient::from_provider(config, model)?)),
+            ProviderId::PhazeCloud => Err(not_signed_in()),
             // All other providers use OpenAI-compatible API
             _ => {
                 let api_key = if config.id.needs_api_key() {
@@ -281,7 +474,7 @@ impl ProviderRegistry {
                         ))
                     })?
                 } else {
-                    String::new()
+                    config.api_key().unwrap_or_default()
                 };
                 let client = super::OpenAIClient::new(api_key)
                     .with_model(model)
@@ -337,6 +530,30 @@ impl ProviderRegistry {
                     input_cost_per_m: 0.15,
                     output_cost_per_m: 0.60,
                 },
+                ModelInfo {
+                    id: "gpt-5".into(),
+                    name: "GPT-5 (reasoning)".into(),
+                    context_window: 400_000,
+                    supports_tools: true,
+                    input_cost_per_m: 1.25,
+                    output_cost_per_m: 10.0,
+                },
+                ModelInfo {
+                    id: "o3".into(),
+                    name: "o3 (reasoning)".into(),
+                    context_window: 200_000,
+                    supports_tools: true,
+                    input_cost_per_m: 2.0,
+                    output_cost_per_m: 8.0,
+                },
+                ModelInfo {
+                    id: "o4-mini".into(),
+                    name: "o4-mini (reasoning)".into(),
+                    context_window: 200_000,
+                    supports_tools: true,
+                    input_cost_per_m: 1.10,
+                    output_cost_per_m: 4.40,
+                },
                 ModelInfo {
                     id: "o1".into(),
                     name: "o1".into(),
@@ -424,6 +641,124 @@ impl ProviderRegistry {
                     output_cost_per_m: 0.28,
                 },
             ],
+            ProviderId::Xai => vec![
+                ModelInfo {
+                    id: crate::constants::models::DEFAULT_XAI_MODEL.into(),
+                    name: "Grok 4".into(),
+                    context_window: 256_000,
+                    supports_tools: true,
+                    input_cost_per_m: 3.0,
+                    output_cost_per_m: 15.0,
+                },
+                ModelInfo {
+                    id: "grok-code-fast-1".into(),
+                    name: "Grok Code Fast".into(),
+                    context_window: 256_000,
+                    supports_tools: true,
+                    input_cost_per_m: 0.20,
+                    output_cost_per_m: 1.50,
+                },
+                ModelInfo {
+                    id: "grok-3".into(),
+                    name: "Grok 3".into(),
+                    context_window: 131_072,
+                    supports_tools: true,
+                    input_cost_per_m: 3.0,
+                    output_cost_per_m: 15.0,
+                },
+                ModelInfo {
+                    id: "grok-3-mini".into(),
+                    name: "Grok 3 Mini (thinking)".into(),
+                    context_window: 131_072,
+                    supports_tools: true,
+                    input_cost_per_m: 0.30,
+                    output_cost_per_m: 0.50,
+                },
+            ],
+            ProviderId::Mistral => vec![
+                ModelInfo {
+                    id: crate::constants::models::DEFAULT_MISTRAL_MODEL.into(),
+                    name: "Mistral Large".into(),
+                    context_window: 131_072,
+                    supports_tools: true,
+                    input_cost_per_m: 2.0,
+                    output_cost_per_m: 6.0,
+                },
+                ModelInfo {
+                    id: "mistral-medium-latest".into(),
+                    name: "Mistral Medium".into(),
+                    context_window: 131_072,
+                    supports_tools: true,
+                    input_cost_per_m: 0.40,
+                    output_cost_per_m: 2.0,
+                },
+                ModelInfo {
+                    id: "mistral-small-latest".into(),
+                    name: "Mistral Small".into(),
+                    context_window: 131_072,
+                    supports_tools: true,
+                    input_cost_per_m: 0.10,
+                    output_cost_per_m: 0.30,
+                },
+                ModelInfo {
+                    id: "codestral-latest".into(),
+                    name: "Codestral".into(),
+                    context_window: 256_000,
+                    supports_tools: true,
+                    input_cost_per_m: 0.30,
+                    output_cost_per_m: 0.90,
+                },
+                ModelInfo {
+                    id: "devstral-medium-latest".into(),
+                    name: "Devstral Medium".into(),
+                    context_window: 131_072,
+                    supports_tools: true,
+                    input_cost_per_m: 0.40,
+                    output_cost_per_m: 2.0,
+                },
+            ],
+            ProviderId::Bedrock => vec![
+                ModelInfo {
+                    id: crate::constants::models::DEFAULT_BEDROCK_MODEL.into(),
+                    name: "Claude Sonnet 4.5 (Bedrock)".into(),
+                    context_window: 200_000,
+                    supports_tools: true,
+                    input_cost_per_m: 3.0,
+                    output_cost_per_m: 15.0,
+                },
+                ModelInfo {
+                    id: "us.anthropic.claude-haiku-4-5-20251001-v1:0".into(),
+                    name: "Claude Haiku 4.5 (Bedrock)".into(),
+                    context_window: 200_000,
+                    supports_tools: true,
+                    input_cost_per_m: 1.0,
+                    output_cost_per_m: 5.0,
+                },
+                ModelInfo {
+                    id: "anthropic.claude-3-5-sonnet-20241022-v2:0".into(),
+                    name: "Claude 3.5 Sonnet v2 (Bedrock)".into(),
+                    context_window: 200_000,
+                    supports_tools: true,
+                    input_cost_per_m: 3.0,
+                    output_cost_per_m: 15.0,
+                },
+                ModelInfo {
+                    id: "us.meta.llama3-3-70b-instruct-v1:0".into(),
+                    name: "Llama 3.3 70B (Bedrock)".into(),
+                    context_window: 128_000,
+                    supports_tools: true,
+                    input_cost_per_m: 0.72,
+                    output_cost_per_m: 0.72,
+                },
+                ModelInfo {
+                    id: "us.meta.llama3-1-8b-instruct-v1:0".into(),
+                    name: "Llama 3.1 8B (Bedrock)".into(),
+                    context_window: 128_000,
+                    supports_tools: true,
+                    input_cost_per_m: 0.22,
+                    output_cost_per_m: 0.22,
+                },
+            ],
             ProviderId::Gemini => vec![
                 ModelInfo {
                     id: "gemini-2.5-pro".into(),
@@ -474,15 +809,57 @@ impl ProviderRegistry {
                     output_cost_per_m: 0.30,
                 },
             ],
-            ProviderId::LmStudio | ProviderId::Ollama => {
+            // The Gemini API's models under Vertex AI's IDs and rates.
+            ProviderId::Vertex => ["gemini-2.5-pro", "gemini-2.5-flash", "gemini-2.0-flash"]
+                .into_iter()
+                .filter_map(|id| {
+                    let m = Self::known_models(&ProviderId::Gemini)
+                        .into_iter()
+                        .find(|m| m.id == id)?;
+                    Some(ModelInfo {
+                        id: format!("google/{}", m.id),
+                        name: format!("{} (Vertex AI)", m.name),
+                        ..m
+                    })
+                })
+                .collect(),
+            ProviderId::PhazeCloud => vec![
+                ModelInfo {
+                    id: crate::constants::models::DEFAULT_PHAZECLOUD_MODEL.into(),
+                    name: "Phaze Beast 70B (hosted)".into(),
+                    context_window: 128_000,
+                    supports_tools: true,
+                    // Included in the plan; metered by request quota instead.
+                    input_cost_per_m: 0.0,
+                    output_cost_per_m: 0.0,
+                },
+                ModelInfo {
+                    id: crate::constants::models::PHAZECLOUD_FAST_MODEL.into(),
+                    name: "Phaze Fast (hosted)".into(),
+                    context_window: 32_768,
+                    supports_tools: true,
+                    input_cost_per_m: 0.0,
+                    output_cost_per_m: 0.0,
+                },
+            ],
+            ProviderId::LmStudio | ProviderId::Ollama | ProviderId::LlamaCpp | ProviderId::Vllm => {
                 // Dynamic - must query the server
                 vec![]
             }
+            // Dynamic - listed from the hub for the token's account
+            ProviderId::HuggingFace => vec![],
             ProviderId::Custom(_) => vec![],
         }
     }
 }
 
+fn not_signed_in() -> PhazeError {
+    PhazeError::Config(
+        "PhazeAI Cloud is not signed in; run `phazeai login` or sign in from the Account tab"
+            .into(),
+    )
+}
+
 fn default_model_for(id: &ProviderId) -> &str {
     use crate::constants::models;
     match id {
@@ -492,18 +869,84 @@ fn default_model_for(id: &ProviderId) -> &str {
         ProviderId::Groq => models::DEFAULT_GROQ_MODEL,
         ProviderId::Together => models::DEFAULT_TOGETHER_MODEL,
         ProviderId::OpenRouter => models::DEFAULT_OPENROUTER_MODEL,
+        ProviderId::Xai => models::DEFAULT_XAI_MODEL,
+        ProviderId::Mistral => models::DEFAULT_MISTRAL_MODEL,
         ProviderId::LmStudio => models::DEFAULT_LMSTUDIO_MODEL,
+        ProviderId::LlamaCpp => models::DEFAULT_LLAMACPP_MODEL,
+        ProviderId::Vllm => models::DEFAULT_VLLM_MODEL,
+        ProviderId::HuggingFace => models::DEFAULT_HUGGINGFACE_MODEL,
+        ProviderId::Bedrock => models::DEFAULT_BEDROCK_MODEL,
         ProviderId::Gemini => models::DEFAULT_GEMINI_MODEL,
+        ProviderId::Vertex => models::DEFAULT_VERTEX_MODEL,
+        ProviderId::PhazeCloud => models::DEFAULT_PHAZECLOUD_MODEL,
         ProviderId::Custom(_) => "default",
     }
 }
 
+/// Request quota reported by a metered provider (PhazeAI Cloud) with each response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Quota {
+    /// Requests allowed in the current period.
+    pub limit: u64,
+    /// Requests left in the current period.
+    pub remaining: u64,
+    /// When the period resets, as sent by the provider.
+    pub resets_at: Option<String>,
+}
+
+impl Quota {
+    pub fn used(&self) -> u64 {
+        self.limit.saturating_sub(self.remaining)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+/// Time-to-first-token samples for one provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    pub samples: u64,
+    pub total_ms: u64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+}
+
+impl LatencyStats {
+    pub fn record(&mut self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        self.min_ms = if self.samples == 0 {
+            ms
+        } else {
+            self.min_ms.min(ms)
+        };
+        self.max_ms = self.max_ms.max(ms);
+        self.total_ms += ms;
+        self.samples += 1;
+    }
+
+    pub fn average_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.samples).unwrap_or(0)
+    }
+}
+
 /// Token usage tracking for cost estimation.
 #[derive(Debug, Clone, Default)]
 pub struct UsageTracker {
     pub total_input_tokens: u64,
     pub total_output_tokens: u64,
     pub request_count: u64,
+    /// Latest quota reported by the provider, if it meters requests.
+    pub quota: Option<Quota>,
+    /// Time to first token by provider name, to compare providers.
+    pub first_token: BTreeMap<String, LatencyStats>,
+    /// Tokens of batched requests, which cost [`BATCH_DISCOUNT`] of the
+    /// usual price. Not in the totals above.
+    ///
+    /// [`BATCH_DISCOUNT`]: super::batch::BATCH_DISCOUNT
+    pub batch_input_tokens: u64,
+    pub batch_output_tokens: u64,
 }
 
 impl UsageTracker {
@@ -513,10 +956,40 @@ impl UsageTracker {
         self.request_count += 1;
     }
 
+    /// Record how long `provider` took to stream its first token.
+    pub fn track_first_token(&mut self, provider: &str, latency: Duration) {
+        self.first_token
+            .entry(provider.to_string())
+            .or_default()
+            .record(latency);
+    }
+
+    /// Record a request that ran in a batch.
+    pub fn track_batch(&mut self, input: u32, output: u32) {
+        self.batch_input_tokens += input as u64;
+        self.batch_output_tokens += output as u64;
+        self.request_count += 1;
+    }
+
+    /// Record the quota from the latest response; it replaces the previous one.
+    pub fn track_quota(&mut self, quota: Quota) {
+        self.quota = Some(quota);
+    }
+
     pub fn estimated_cost(&self, model: &ModelInfo) -> f64 {
         let input_cost = (self.total_input_tokens as f64 / 1_000_000.0) * model.input_cost_per_m;
         let output_cost = (self.total_output_tokens as f64 / 1_000_000.0) * model.output_cost_per_m;
-        input_cost + output_cost
+        input_cost + output_cost + self.batch_list_price(model) * super::batch::BATCH_DISCOUNT
+    }
+
+    /// What batching saved over sending the same requests one at a time.
+    pub fn batch_savings(&self, model: &ModelInfo) -> f64 {
+        self.batch_list_price(model) * (1.0 - super::batch::BATCH_DISCOUNT)
+    }
+
+    fn batch_list_price(&self, model: &ModelInfo) -> f64 {
+        (self.batch_input_tokens as f64 / 1_000_000.0) * model.input_cost_per_m
+            + (self.batch_output_tokens as f64 / 1_000_000.0) * model.output_cost_per_m
     }
 
     pub fn reset(&mut self) {
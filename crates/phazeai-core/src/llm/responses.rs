@@ -0,0 +1,431 @@
+//! OpenAI's Responses API, for the reasoning models (the o-series and GPT-5).
+//!
+//! Through chat/completions a reasoning model's reasoning is thrown away
+//! after every request. The Responses API returns it as reasoning items: a
+//! summary the chat panel shows, and, with `store: false`, the reasoning
+//! itself, encrypted. The encrypted items go back with the assistant message
+//! on the next request, so a run of tool calls keeps its train of thought
+//! without OpenAI storing the conversation.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures::channel::mpsc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::constants::{endpoints, models};
+use crate::error::PhazeError;
+use crate::llm::traits::*;
+use crate::tools::ToolDefinition;
+
+/// How long a reasoning model thinks before it answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Minimal => "minimal",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// Whether `model` is an OpenAI reasoning model (`o3`, `o4-mini`,
+/// `gpt-5`...), which the OpenAI provider sends through the Responses API.
+pub fn is_reasoning_model(model: &str) -> bool {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let mut chars = model.chars();
+    let o_series = chars.next() == Some('o') && chars.next().is_some_and(|c| c.is_ascii_digit());
+    o_series || (model.starts_with("gpt-5") && !model.starts_with("gpt-5-chat"))
+}
+
+pub struct ResponsesClient {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+    base_url: String,
+    reasoning_effort: Option<ReasoningEffort>,
+    /// Cleared when the organization can't get reasoning summaries
+    /// (they need a verified organization).
+    summaries: AtomicBool,
+}
+
+impl ResponsesClient {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_key: api_key.into(),
+            model: models::DEFAULT_OPENAI_MODEL.to_string(),
+            base_url: endpoints::OPENAI_BASE_URL.to_string(),
+            reasoning_effort: None,
+            summaries: AtomicBool::new(true),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// `None` leaves the effort to the model's default (medium).
+    pub fn with_reasoning_effort(mut self, effort: Option<ReasoningEffort>) -> Self {
+        self.reasoning_effort = effort;
+        self
+    }
+
+    /// The `/v1/responses` request for a conversation.
+    pub fn request_body(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        stream: bool,
+    ) -> Value {
+        let mut instructions = Vec::new();
+        let mut input = Vec::new();
+        for m in messages {
+            if let Some(call_id) = &m.tool_call_id {
+                input.push(json!({
+                    "type": "function_call_output",
+                    "call_id": call_id,
+                    "output": m.content,
+                }));
+                continue;
+            }
+            match m.role {
+                Role::System => instructions.push(m.content.as_str()),
+                Role::User => input.push(json!({ "role": "user", "content": m.content })),
+                Role::Assistant => {
+                    // Only OpenAI can read its encrypted reasoning; other
+                    // providers' reasoning can't be sent back.
+                    for item in &m.reasoning {
+                        let (ReasoningKind::OpenAi, Some(encrypted)) =
+                            (item.kind, &item.encrypted_content)
+                        else {
+                            continue;
+                        };
+                        let summary: Vec<Value> = item
+                            .summary
+                            .iter()
+                            .map(|text| json!({ "type": "summary_text", "text": text }))
+                            .collect();
+                        input.push(json!({
+                            "type": "reasoning",
+                            "id": item.id,
+                            "summary": summary,
+                            "encrypted_content": encrypted,
+                        }));
+                    }
+                    if !m.content.is_empty() {
+                        input.push(json!({ "role": "assistant", "content": m.content }));
+                    }
+                    for call in m.tool_calls.iter().flatten() {
+                        input.push(json!({
+                            "type": "function_call",
+                            "call_id": call.id,
+                            "name": call.function.name,
+                            "arguments": call.function.arguments,
+                        }));
+                    }
+                }
+            }
+        }
+
+        let mut body = json!({
+            "model": self.model,
+            "input": input,
+            "stream": stream,
+            "store": false,
+        });
+        if !instructions.is_empty() {
+            body["instructions"] = json!(instructions.join("\n\n"));
+        }
+        if !tools.is_empty() {
+            body["tools"] = tools
+                .iter()
+                .map(|t| {
+                    json!({
+                        "type": "function",
+                        "name": t.name,
+                        "description": t.description,
+                        "parameters": t.parameters,
+                        // Strict mode wants every property required; ours aren't.
+                        "strict": false,
+                    })
+                })
+                .collect();
+        }
+        if is_reasoning_model(&self.model) {
+            let mut reasoning = json!({});
+            if let Some(effort) = self.reasoning_effort {
+                reasoning["effort"] = json!(effort.as_str());
+            }
+            if self.summaries.load(Ordering::Relaxed) {
+                reasoning["summary"] = json!("auto");
+            }
+            body["reasoning"] = reasoning;
+            body["include"] = json!(["reasoning.encrypted_content"]);
+        }
+        body
+    }
+
+    async fn send(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        stream: bool,
+    ) -> Result<reqwest::Response, PhazeError> {
+        loop {
+            let response = self
+                .client
+                .post(format!("{}/v1/responses", self.base_url))
+                .bearer_auth(&self.api_key)
+                .json(&self.request_body(messages, tools, stream))
+                .send()
+                .await?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            let text = response.text().await.unwrap_or_default();
+            if status == reqwest::StatusCode::BAD_REQUEST
+                && text.contains("summar")
+                && self.summaries.swap(false, Ordering::Relaxed)
+            {
+                tracing::debug!("reasoning summaries unavailable, retrying without: {text}");
+                continue;
+            }
+            return Err(PhazeError::Llm(format!(
+                "OpenAI API error ({status}): {text}"
+            )));
+        }
+    }
+}
+
+/// A reasoning output item as a [`ReasoningItem`].
+fn reasoning_item(item: &Value) -> ReasoningItem {
+    ReasoningItem {
+        id: item["id"].as_str().unwrap_or_default().to_string(),
+        kind: ReasoningKind::OpenAi,
+        summary: item["summary"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s["text"].as_str().map(str::to_string))
+            .collect(),
+        encrypted_content: item["encrypted_content"].as_str().map(str::to_string),
+    }
+}
+
+fn parse_usage(usage: &Value) -> Option<Usage> {
+    Some(Usage {
+        input_tokens: usage["input_tokens"].as_u64()? as u32,
+        // Reasoning tokens are part of the output count.
+        output_tokens: usage["output_tokens"].as_u64()? as u32,
+    })
+}
+
+/// A complete (non-streamed) response.
+pub fn parse_response(body: &Value) -> Result<LlmResponse, PhazeError> {
+    if let Some(message) = body["error"]["message"].as_str() {
+        return Err(PhazeError::Llm(message.to_string()));
+    }
+    let mut content = String::new();
+    let mut tool_calls = Vec::new();
+    let mut reasoning = Vec::new();
+    for item in body["output"].as_array().into_iter().flatten() {
+        match item["type"].as_str() {
+            Some("reasoning") => reasoning.push(reasoning_item(item)),
+            Some("message") => {
+                for part in item["content"].as_array().into_iter().flatten() {
+                    if part["type"] == "output_text" {
+                        content.push_str(part["text"].as_str().unwrap_or_default());
+                    }
+                }
+            }
+            Some("function_call") => tool_calls.push(ToolCall {
+                id: item["call_id"].as_str().unwrap_or_default().to_string(),
+                call_type: "function".to_string(),
+                function: FunctionCall {
+                    name: item["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: item["arguments"].as_str().unwrap_or("{}").to_string(),
+                },
+            }),
+            _ => {}
+        }
+    }
+    let message = if tool_calls.is_empty() {
+        Message::assistant(content)
+    } else {
+        Message::assistant_with_tools(content, tool_calls)
+    };
+    Ok(LlmResponse {
+        message: message.with_reasoning(reasoning),
+        usage: parse_usage(&body["usage"]),
+    })
+}
+
+/// Turns Responses API stream events into [`StreamEvent`]s.
+#[derive(Default)]
+pub struct StreamDecoder {
+    /// Function call item id → call id, for the argument deltas that follow.
+    calls: HashMap<String, String>,
+    /// Whether reasoning summary text has been streamed yet.
+    reasoned: bool,
+    finished: bool,
+}
+
+impl StreamDecoder {
+    /// The events for one stream event's JSON data.
+    pub fn decode(&mut self, event: &Value) -> Vec<StreamEvent> {
+        let delta = || event["delta"].as_str().unwrap_or_default().to_string();
+        match event["type"].as_str().unwrap_or_default() {
+            "response.output_text.delta" => vec![StreamEvent::TextDelta(delta())],
+            // Separate one summary part (or reasoning step) from the next.
+            "response.reasoning_summary_part.added" if self.reasoned => {
+                vec![StreamEvent::ReasoningDelta("\n\n".into())]
+            }
+            "response.reasoning_summary_text.delta" => {
+                self.reasoned = true;
+                vec![StreamEvent::ReasoningDelta(delta())]
+            }
+            "response.output_item.added" if event["item"]["type"] == "function_call" => {
+                let item = &event["item"];
+                let call_id = item["call_id"].as_str().unwrap_or_default().to_string();
+                let item_id = item["id"].as_str().unwrap_or_default().to_string();
+                self.calls.insert(item_id, call_id.clone());
+                vec![StreamEvent::ToolCallStart {
+                    id: call_id,
+                    name: item["name"].as_str().unwrap_or_default().to_string(),
+                }]
+            }
+            "response.function_call_arguments.delta" => {
+                match event["item_id"].as_str().and_then(|id| self.calls.get(id)) {
+                    Some(call_id) => vec![StreamEvent::ToolCallDelta {
+                        id: call_id.clone(),
+                        arguments_delta: delta(),
+                    }],
+                    None => vec![],
+                }
+            }
+            "response.output_item.done" => {
+                let item = &event["item"];
+                match item["type"].as_str() {
+                    Some("reasoning") => vec![StreamEvent::Reasoning(reasoning_item(item))],
+                    Some("function_call") => item["id"]
+                        .as_str()
+                        .and_then(|id| self.calls.remove(id))
+                        .map(|id| StreamEvent::ToolCallEnd { id })
+                        .into_iter()
+                        .collect(),
+                    _ => vec![],
+                }
+            }
+            // `incomplete` ends a response cut short by the output limit.
+            "response.completed" | "response.incomplete" => {
+                self.finished = true;
+                let mut events: Vec<_> = parse_usage(&event["response"]["usage"])
+                    .map(StreamEvent::Usage)
+                    .into_iter()
+                    .collect();
+                events.push(StreamEvent::Done);
+                events
+            }
+            "response.failed" => vec![StreamEvent::Error(
+                event["response"]["error"]["message"]
+                    .as_str()
+                    .unwrap_or("response failed")
+                    .to_string(),
+            )],
+            "error" => vec![StreamEvent::Error(
+                event["message"]
+                    .as_str()
+                    .unwrap_or("stream error")
+                    .to_string(),
+            )],
+            _ => vec![],
+        }
+    }
+
+    /// The event for a stream that ended without a `response.completed`.
+    pub fn finish(&self) -> StreamEvent {
+        if self.finished {
+            StreamEvent::Done
+        } else {
+            StreamEvent::Dropped(STREAM_CLOSED_EARLY.to_string())
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmClient for ResponsesClient {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        let body: Value = self.send(messages, tools, false).await?.json().await?;
+        parse_response(&body)
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        let response = self.send(messages, tools, true).await?;
+
+        let (tx, rx) = mpsc::unbounded();
+        let mut stream = response.bytes_stream();
+        tokio::spawn(async move {
+            use futures::StreamExt;
+            let mut buffer = String::new();
+            let mut decoder = StreamDecoder::default();
+            while let Some(chunk) = stream.next().await {
+                let chunk = match chunk {
+                    Ok(c) => c,
+                    Err(e) => {
+                        let _ = tx.unbounded_send(StreamEvent::Dropped(e.to_string()));
+                        return;
+                    }
+                };
+                buffer.push_str(&String::from_utf8_lossy(&chunk));
+                while let Some(line_end) = buffer.find('\n') {
+                    let line = buffer[..line_end].trim().to_string();
+                    buffer.drain(..=line_end);
+                    // The event name is repeated as the data's `type`.
+                    let Some(data) = line.strip_prefix("data:").map(str::trim_start) else {
+                        continue;
+                    };
+                    let Ok(event) = serde_json::from_str::<Value>(data) else {
+                        continue;
+                    };
+                    for event in decoder.decode(&event) {
+                        let last = matches!(event, StreamEvent::Done | StreamEvent::Error(_));
+                        let _ = tx.unbounded_send(event);
+                        if last {
+                            return;
+                        }
+                    }
+                }
+            }
+            let _ = tx.unbounded_send(decoder.finish());
+        });
+
+        Ok(rx)
+    }
+}
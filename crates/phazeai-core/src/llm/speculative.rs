@@ -0,0 +1,231 @@
+//! Draft-and-verify decoding for local setups with a big and a small model.
+//!
+//! The small model drafts the whole reply; the big model then reads it and
+//! either answers [`ACCEPT`] — a handful of tokens instead of the full reply —
+//! or writes the corrected reply itself. With a draft model that's usually
+//! right, replies arrive at close to the small model's speed with the big
+//! model's quality. Requests with tools skip the draft: a drafted tool call
+//! can't be checked without running it.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+
+use crate::error::PhazeError;
+use crate::llm::traits::*;
+use crate::tools::ToolDefinition;
+
+/// What the verifying model answers when the draft stands as is.
+pub const ACCEPT: &str = "ACCEPT";
+
+const VERIFY_PROMPT: &str = "A faster model drafted your previous reply. \
+If it is a correct and complete reply to the conversation before it, answer with \
+exactly ACCEPT and nothing else. Otherwise answer with the corrected reply only, \
+in the format the draft should have had, with no commentary.";
+
+/// The verifying model's answer to a draft.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Accept,
+    Refine(String),
+}
+
+impl Verdict {
+    /// An empty reply counts as acceptance: the target raised no objection,
+    /// and the draft beats no reply at all.
+    pub fn parse(reply: &str) -> Self {
+        let reply = reply.trim();
+        if reply.is_empty() {
+            return Verdict::Accept;
+        }
+        let word = reply
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or("");
+        if word.eq_ignore_ascii_case(ACCEPT) && reply.len() <= ACCEPT.len() + 1 {
+            Verdict::Accept
+        } else {
+            Verdict::Refine(reply.to_string())
+        }
+    }
+}
+
+/// The conversation the verifying model sees: the original one, the draft
+/// as its own reply, and the request to check it.
+pub fn verify_messages(messages: &[Message], draft: &str) -> Vec<Message> {
+    let mut verify = messages.to_vec();
+    verify.push(Message::assistant(draft));
+    verify.push(Message::user(VERIFY_PROMPT));
+    verify
+}
+
+/// Drafts with one client and verifies with another.
+pub struct SpeculativeClient {
+    draft: Box<dyn LlmClient>,
+    target: Box<dyn LlmClient>,
+    drafted: AtomicU64,
+    accepted: AtomicU64,
+}
+
+impl SpeculativeClient {
+    pub fn new(draft: Box<dyn LlmClient>, target: Box<dyn LlmClient>) -> Self {
+        Self {
+            draft,
+            target,
+            drafted: AtomicU64::new(0),
+            accepted: AtomicU64::new(0),
+        }
+    }
+
+    /// Drafts verified so far, and how many of them the target accepted.
+    pub fn acceptance(&self) -> (u64, u64) {
+        (
+            self.drafted.load(Ordering::Relaxed),
+            self.accepted.load(Ordering::Relaxed),
+        )
+    }
+
+    /// The small model's reply, or `None` (logged) to go straight to the
+    /// target when it fails or has nothing to say.
+    async fn draft(&self, messages: &[Message]) -> Option<String> {
+        match self.draft.chat(messages, &[]).await {
+            Ok(resp) if !resp.message.content.trim().is_empty() => {
+                self.drafted.fetch_add(1, Ordering::Relaxed);
+                Some(resp.message.content)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Draft model failed: {e}. Using the target model alone.");
+                None
+            }
+        }
+    }
+
+    fn record(&self, verdict: &Verdict) {
+        if *verdict == Verdict::Accept {
+            self.accepted.fetch_add(1, Ordering::Relaxed);
+        }
+        let (drafted, accepted) = self.acceptance();
+        tracing::debug!("Speculative draft {verdict:?}; {accepted}/{drafted} accepted");
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmClient for SpeculativeClient {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        if !tools.is_empty() {
+            return self.target.chat(messages, tools).await;
+        }
+        let Some(draft) = self.draft(messages).await else {
+            return self.target.chat(messages, tools).await;
+        };
+        let resp = self
+            .target
+            .chat(&verify_messages(messages, &draft), &[])
+            .await?;
+        let verdict = Verdict::parse(&resp.message.content);
+        self.record(&verdict);
+        let content = match verdict {
+            Verdict::Accept => draft,
+            Verdict::Refine(reply) => reply,
+        };
+        Ok(LlmResponse {
+            message: Message::assistant(content),
+            usage: resp.usage,
+        })
+    }
+
+    /// Streams the verification, holding back only its first few characters:
+    /// once they spell [`ACCEPT`] the draft is sent and the rest dropped,
+    /// otherwise the target's correction streams through as it arrives.
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        if !tools.is_empty() {
+            return self.target.chat_stream(messages, tools).await;
+        }
+        let Some(draft) = self.draft(messages).await else {
+            return self.target.chat_stream(messages, tools).await;
+        };
+        let mut verify = self
+            .target
+            .chat_stream(&verify_messages(messages, &draft), &[])
+            .await?;
+
+        // Hold the verification back until it spells ACCEPT or can't.
+        let mut held = String::new();
+        let mut pending = Vec::new();
+        let mut finished = false;
+        let verdict = loop {
+            match verify.next().await {
+                Some(StreamEvent::TextDelta(text)) => {
+                    held.push_str(&text);
+                    let start = held.trim_start();
+                    let upper = start.to_ascii_uppercase();
+                    if !(ACCEPT.starts_with(upper.as_str()) || upper.starts_with(ACCEPT)) {
+                        break Verdict::Refine(start.to_string());
+                    }
+                    if start.len() > ACCEPT.len() + 1 {
+                        break Verdict::parse(start);
+                    }
+                }
+                Some(event @ StreamEvent::Usage(_)) => pending.push(event),
+                // How the target judged the draft isn't the answer's reasoning.
+                Some(StreamEvent::ReasoningDelta(_) | StreamEvent::Reasoning(_)) => {}
+                Some(event) => {
+                    // Done, or an error before the verdict: pass it along
+                    // after whatever the target said.
+                    finished = true;
+                    let done = matches!(event, StreamEvent::Done);
+                    pending.push(event);
+                    break if done {
+                        Verdict::parse(&held)
+                    } else {
+                        Verdict::Refine(held.trim_start().to_string())
+                    };
+                }
+                None => {
+                    finished = true;
+                    break Verdict::parse(&held);
+                }
+            }
+        };
+        self.record(&verdict);
+
+        let (tx, rx) = mpsc::unbounded();
+        let text = match verdict {
+            Verdict::Accept => {
+                // The rest of the verification is at most punctuation.
+                finished = true;
+                if !pending.iter().any(|e| matches!(e, StreamEvent::Done)) {
+                    pending.push(StreamEvent::Done);
+                }
+                draft
+            }
+            Verdict::Refine(text) => text,
+        };
+        if !text.is_empty() {
+            let _ = tx.unbounded_send(StreamEvent::TextDelta(text));
+        }
+        for event in pending {
+            let _ = tx.unbounded_send(event);
+        }
+        if !finished {
+            tokio::spawn(async move {
+                while let Some(event) = verify.next().await {
+                    if tx.unbounded_send(event).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(rx)
+    }
+}
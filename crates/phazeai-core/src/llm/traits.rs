@@ -18,6 +18,13 @@ pub struct Message {
     pub tool_calls: Option<Vec<ToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// A reasoning model's reasoning behind an assistant message, sent back
+    /// with it on later requests so the model keeps its chain of thought.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasoning: Vec<ReasoningItem>,
+    /// Images sent along with a user message, for vision models.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<ImageAttachment>,
 }
 
 impl Message {
@@ -27,6 +34,8 @@ impl Message {
             content: content.into(),
             tool_calls: None,
             tool_call_id: None,
+            reasoning: Vec::new(),
+            images: Vec::new(),
         }
     }
 
@@ -36,6 +45,8 @@ impl Message {
             content: content.into(),
             tool_calls: None,
             tool_call_id: None,
+            reasoning: Vec::new(),
+            images: Vec::new(),
         }
     }
 
@@ -45,6 +56,8 @@ impl Message {
             content: content.into(),
             tool_calls: None,
             tool_call_id: None,
+            reasoning: Vec::new(),
+            images: Vec::new(),
         }
     }
 
@@ -54,6 +67,8 @@ impl Message {
             content: content.into(),
             tool_calls: Some(tool_calls),
             tool_call_id: None,
+            reasoning: Vec::new(),
+            images: Vec::new(),
         }
     }
 
@@ -63,8 +78,96 @@ impl Message {
             content: content.into(),
             tool_calls: None,
             tool_call_id: Some(tool_call_id.into()),
+            reasoning: Vec::new(),
+            images: Vec::new(),
         }
     }
+
+    pub fn with_reasoning(mut self, reasoning: Vec<ReasoningItem>) -> Self {
+        self.reasoning = reasoning;
+        self
+    }
+
+    pub fn with_images(mut self, images: Vec<ImageAttachment>) -> Self {
+        self.images = images;
+        self
+    }
+}
+
+/// An image for a vision model, base64-encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageAttachment {
+    /// `image/png`, `image/jpeg`, `image/gif` or `image/webp`.
+    pub media_type: String,
+    pub data: String,
+}
+
+impl ImageAttachment {
+    pub fn new(media_type: impl Into<String>, bytes: &[u8]) -> Self {
+        use base64::Engine;
+        Self {
+            media_type: media_type.into(),
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        }
+    }
+
+    /// The image in `path`, typed by its extension.
+    pub fn from_file(path: &std::path::Path) -> Result<Self, PhazeError> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let media_type = match ext.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => {
+                return Err(PhazeError::Other(format!(
+                    "{} is not a PNG, JPEG, GIF or WebP image",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self::new(media_type, &std::fs::read(path)?))
+    }
+
+    /// The image as a `data:` URL.
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
+
+/// One reasoning step of a reasoning model: an OpenAI o-series or GPT-5
+/// reasoning item, or a Claude thinking block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReasoningItem {
+    pub id: String,
+    /// Whose reasoning this is; a provider can only take its own back.
+    #[serde(default)]
+    pub kind: ReasoningKind,
+    /// Readable summaries of the reasoning, when the model was asked for them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub summary: Vec<String>,
+    /// The reasoning itself, encrypted by the provider. Only the provider
+    /// can read it; passing it back lets a stateless request continue from it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_content: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningKind {
+    /// An OpenAI Responses API reasoning item.
+    #[default]
+    OpenAi,
+    /// A Claude thinking block: the thinking is the one `summary` entry and
+    /// its signature the `encrypted_content`.
+    Thinking,
+    /// A Claude thinking block flagged by Anthropic's safety systems, whose
+    /// `encrypted_content` is all that's left of it.
+    RedactedThinking,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,10 +202,18 @@ pub struct Usage {
     pub output_tokens: u32,
 }
 
+/// Reason given with [`StreamEvent::Dropped`] when a stream ends without
+/// the provider's end marker.
+pub(crate) const STREAM_CLOSED_EARLY: &str = "connection closed before the response finished";
+
 /// Events emitted during streaming LLM responses.
 #[derive(Debug, Clone)]
 pub enum StreamEvent {
     TextDelta(String),
+    /// Summarized reasoning, streamed before the answer.
+    ReasoningDelta(String),
+    /// A finished reasoning item, to keep with the assistant message.
+    Reasoning(ReasoningItem),
     ToolCallStart {
         id: String,
         name: String,
@@ -118,6 +229,10 @@ pub enum StreamEvent {
     Usage(Usage),
     Done,
     Error(String),
+    /// The connection broke before the response finished (a network error,
+    /// or the stream closed without the provider's end marker). What arrived
+    /// so far is valid; the agent resumes from it.
+    Dropped(String),
 }
 
 /// The LLM client trait. Implementations provide both blocking and streaming chat.
@@ -0,0 +1,542 @@
+//! Google Vertex AI: Gemini under a Google Cloud project's own quota and
+//! billing, instead of a Gemini API key.
+//!
+//! Vertex serves Gemini through an OpenAI-compatible endpoint, so chat goes
+//! through [`OpenAIClient`](super::OpenAIClient) with an OAuth access token
+//! as its key. Tokens come from Application Default Credentials, as the
+//! Google Cloud SDKs find them: the service account key file named by
+//! `GOOGLE_APPLICATION_CREDENTIALS`, the user credentials written by
+//! `gcloud auth application-default login`, or the metadata server of the
+//! VM, Cloud Run service or GKE pod this runs on.
+
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::channel::mpsc;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::constants::endpoints;
+use crate::error::PhazeError;
+use crate::llm::provider::{ModelInfo, ProviderConfig, ProviderId, ProviderRegistry};
+use crate::llm::traits::*;
+use crate::tools::ToolDefinition;
+
+/// The OAuth scope Vertex AI requests need.
+const SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+
+/// Access tokens are refreshed this long before they expire.
+const REFRESH_MARGIN: TimeDelta = TimeDelta::minutes(5);
+
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where Vertex AI requests go: a project and the location serving it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexProject {
+    pub project: String,
+    /// A region such as `us-central1`, or `global`.
+    pub location: String,
+}
+
+impl VertexProject {
+    pub fn new(project: impl Into<String>, location: impl Into<String>) -> Self {
+        let location = location.into();
+        Self {
+            project: project.into(),
+            location: if location.is_empty() {
+                endpoints::VERTEX_DEFAULT_LOCATION.to_string()
+            } else {
+                location
+            },
+        }
+    }
+
+    pub fn host(&self) -> String {
+        if self.location == "global" {
+            "aiplatform.googleapis.com".into()
+        } else {
+            format!("{}-aiplatform.googleapis.com", self.location)
+        }
+    }
+
+    /// The provider `base_url` for this project and location.
+    pub fn base_url(&self) -> String {
+        format!(
+            "https://{}/v1/projects/{}/locations/{}",
+            self.host(),
+            self.project,
+            self.location
+        )
+    }
+
+    /// The project and location in a [`base_url`](Self::base_url).
+    pub fn from_base_url(url: &str) -> Option<Self> {
+        let path = url.split("/projects/").nth(1)?;
+        let mut parts = path.trim_end_matches('/').split('/');
+        let project = parts.next().filter(|p| !p.is_empty())?;
+        let location = match (parts.next(), parts.next()) {
+            (Some("locations"), Some(location)) => location,
+            _ => "",
+        };
+        Some(Self::new(project, location))
+    }
+
+    /// From the provider's `base_url` if set, else `GOOGLE_CLOUD_PROJECT`
+    /// (or `CLOUDSDK_CORE_PROJECT`) or the credentials' own project, in
+    /// `GOOGLE_CLOUD_LOCATION` or the default location.
+    pub fn resolve(base_url: &str, credentials: &GoogleCredentials) -> Result<Self, PhazeError> {
+        if let Some(project) = Self::from_base_url(base_url) {
+            return Ok(project);
+        }
+        let project = non_empty_env("GOOGLE_CLOUD_PROJECT")
+            .or_else(|| non_empty_env("CLOUDSDK_CORE_PROJECT"))
+            .or_else(|| credentials.project_id().map(str::to_string))
+            .ok_or_else(|| {
+                PhazeError::Config(
+                    "No Google Cloud project for Vertex AI: set project under [vertex] in \
+                     settings.toml or GOOGLE_CLOUD_PROJECT"
+                        .into(),
+                )
+            })?;
+        Ok(Self::new(
+            project,
+            non_empty_env("GOOGLE_CLOUD_LOCATION").unwrap_or_default(),
+        ))
+    }
+}
+
+/// Application Default Credentials.
+#[derive(Clone, PartialEq, Eq)]
+pub enum GoogleCredentials {
+    /// A service account key file.
+    ServiceAccount {
+        client_email: String,
+        private_key: String,
+        token_uri: String,
+        project_id: Option<String>,
+    },
+    /// `gcloud auth application-default login`.
+    AuthorizedUser {
+        client_id: String,
+        client_secret: String,
+        refresh_token: String,
+        quota_project_id: Option<String>,
+    },
+    /// The metadata server, on Google Cloud compute.
+    Metadata,
+}
+
+impl std::fmt::Debug for GoogleCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ServiceAccount { client_email, .. } => f
+                .debug_struct("ServiceAccount")
+                .field("client_email", client_email)
+                .finish_non_exhaustive(),
+            Self::AuthorizedUser { client_id, .. } => f
+                .debug_struct("AuthorizedUser")
+                .field("client_id", client_id)
+                .finish_non_exhaustive(),
+            Self::Metadata => f.write_str("Metadata"),
+        }
+    }
+}
+
+impl GoogleCredentials {
+    /// Parse a credentials file.
+    pub fn from_json(body: &str) -> Result<Self, PhazeError> {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(rename = "type")]
+            kind: String,
+            #[serde(default)]
+            client_email: String,
+            #[serde(default)]
+            private_key: String,
+            #[serde(default)]
+            token_uri: String,
+            project_id: Option<String>,
+            #[serde(default)]
+            client_id: String,
+            #[serde(default)]
+            client_secret: String,
+            #[serde(default)]
+            refresh_token: String,
+            quota_project_id: Option<String>,
+        }
+        let file: File = serde_json::from_str(body)?;
+        match file.kind.as_str() {
+            "service_account" => Ok(Self::ServiceAccount {
+                client_email: file.client_email,
+                private_key: file.private_key,
+                token_uri: if file.token_uri.is_empty() {
+                    endpoints::GOOGLE_OAUTH_TOKEN_URL.to_string()
+                } else {
+                    file.token_uri
+                },
+                project_id: file.project_id,
+            }),
+            "authorized_user" => Ok(Self::AuthorizedUser {
+                client_id: file.client_id,
+                client_secret: file.client_secret,
+                refresh_token: file.refresh_token,
+                quota_project_id: file.quota_project_id,
+            }),
+            other => Err(PhazeError::Config(format!(
+                "Unsupported Google credentials type: {other}"
+            ))),
+        }
+    }
+
+    /// The key file named by `GOOGLE_APPLICATION_CREDENTIALS`, else gcloud's
+    /// application default credentials, else the metadata server.
+    pub fn find() -> Result<Self, PhazeError> {
+        if let Some(path) = non_empty_env("GOOGLE_APPLICATION_CREDENTIALS") {
+            let body = std::fs::read_to_string(&path).map_err(|e| {
+                PhazeError::Config(format!(
+                    "Reading GOOGLE_APPLICATION_CREDENTIALS ({path}): {e}"
+                ))
+            })?;
+            return Self::from_json(&body);
+        }
+        match well_known_file().and_then(|p| std::fs::read_to_string(p).ok()) {
+            Some(body) => Self::from_json(&body),
+            None => Ok(Self::Metadata),
+        }
+    }
+
+    /// Whether credentials are set up without asking the metadata server,
+    /// for listing Vertex AI as available.
+    pub fn configured_locally() -> bool {
+        non_empty_env("GOOGLE_APPLICATION_CREDENTIALS").is_some()
+            || well_known_file().is_some_and(|p| p.exists())
+    }
+
+    /// The project the credentials belong to or bill to, if they say.
+    pub fn project_id(&self) -> Option<&str> {
+        match self {
+            Self::ServiceAccount { project_id, .. } => project_id.as_deref(),
+            Self::AuthorizedUser {
+                quota_project_id, ..
+            } => quota_project_id.as_deref(),
+            Self::Metadata => None,
+        }
+    }
+}
+
+/// Where `gcloud auth application-default login` writes its credentials.
+fn well_known_file() -> Option<PathBuf> {
+    let dir = match non_empty_env("CLOUDSDK_CONFIG") {
+        Some(dir) => PathBuf::from(dir),
+        None if cfg!(windows) => PathBuf::from(non_empty_env("APPDATA")?).join("gcloud"),
+        None => dirs::home_dir()?.join(".config").join("gcloud"),
+    };
+    Some(dir.join("application_default_credentials.json"))
+}
+
+/// A service account's signed token request (RFC 7523), valid for an hour
+/// from `now`.
+pub fn jwt_assertion(
+    client_email: &str,
+    private_key_pem: &str,
+    token_uri: &str,
+    now: DateTime<Utc>,
+) -> Result<String, PhazeError> {
+    use ring::rand::SystemRandom;
+    use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+
+    let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "RS256", "typ": "JWT" }).to_string());
+    let claims = URL_SAFE_NO_PAD.encode(
+        json!({
+            "iss": client_email,
+            "scope": SCOPE,
+            "aud": token_uri,
+            "iat": now.timestamp(),
+            "exp": now.timestamp() + 3600,
+        })
+        .to_string(),
+    );
+    let message = format!("{header}.{claims}");
+
+    let der: String = private_key_pem
+        .lines()
+        .filter(|l| !l.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    let der = STANDARD
+        .decode(der)
+        .map_err(|e| PhazeError::Config(format!("Service account private key: {e}")))?;
+    let key = RsaKeyPair::from_pkcs8(&der)
+        .map_err(|e| PhazeError::Config(format!("Service account private key: {e}")))?;
+    let mut signature = vec![0; key.public().modulus_len()];
+    key.sign(
+        &RSA_PKCS1_SHA256,
+        &SystemRandom::new(),
+        message.as_bytes(),
+        &mut signature,
+    )
+    .map_err(|_| PhazeError::Config("Signing the service account token request failed".into()))?;
+    Ok(format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// An OAuth access token and when it expires.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl AccessToken {
+    /// Parse a token endpoint (or metadata server) response received at `now`.
+    pub fn parse(body: &str, now: DateTime<Utc>) -> Result<Self, PhazeError> {
+        #[derive(Deserialize)]
+        struct Response {
+            access_token: String,
+            expires_in: i64,
+        }
+        let response: Response = serde_json::from_str(body)?;
+        Ok(Self {
+            token: response.access_token,
+            expires_at: now + TimeDelta::seconds(response.expires_in),
+        })
+    }
+
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        now + REFRESH_MARGIN < self.expires_at
+    }
+}
+
+/// Exchanges credentials for access tokens, reusing each until shortly
+/// before it expires.
+pub struct TokenSource {
+    http: reqwest::Client,
+    credentials: GoogleCredentials,
+    cached: tokio::sync::Mutex<Option<AccessToken>>,
+}
+
+static SHARED_SOURCE: LazyLock<RwLock<Option<Arc<TokenSource>>>> = LazyLock::new(Default::default);
+
+impl TokenSource {
+    pub fn new(credentials: GoogleCredentials) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            credentials,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// The source every Vertex client shares, from Application Default
+    /// Credentials found on first use.
+    pub fn shared() -> Result<Arc<Self>, PhazeError> {
+        if let Some(source) = SHARED_SOURCE
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            return Ok(source.clone());
+        }
+        let source = Arc::new(Self::new(GoogleCredentials::find()?));
+        *SHARED_SOURCE.write().unwrap_or_else(|e| e.into_inner()) = Some(source.clone());
+        Ok(source)
+    }
+
+    pub fn credentials(&self) -> &GoogleCredentials {
+        &self.credentials
+    }
+
+    pub async fn token(&self) -> Result<String, PhazeError> {
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref().filter(|t| t.is_fresh(Utc::now())) {
+            return Ok(token.token.clone());
+        }
+        let token = self.fetch().await?;
+        let value = token.token.clone();
+        *cached = Some(token);
+        Ok(value)
+    }
+
+    async fn fetch(&self) -> Result<AccessToken, PhazeError> {
+        let now = Utc::now();
+        let request = match &self.credentials {
+            GoogleCredentials::ServiceAccount {
+                client_email,
+                private_key,
+                token_uri,
+                ..
+            } => {
+                let assertion = jwt_assertion(client_email, private_key, token_uri, now)?;
+                self.http.post(token_uri).form(&[
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                    ("assertion", assertion.as_str()),
+                ])
+            }
+            GoogleCredentials::AuthorizedUser {
+                client_id,
+                client_secret,
+                refresh_token,
+                ..
+            } => self.http.post(endpoints::GOOGLE_OAUTH_TOKEN_URL).form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+                ("refresh_token", refresh_token.as_str()),
+            ]),
+            GoogleCredentials::Metadata => {
+                let host = non_empty_env("GCE_METADATA_HOST")
+                    .map(|h| format!("http://{h}"))
+                    .unwrap_or_else(|| endpoints::GCE_METADATA_URL.to_string());
+                self.http
+                    .get(format!(
+                        "{host}/computeMetadata/v1/instance/service-accounts/default/token"
+                    ))
+                    .query(&[("scopes", SCOPE)])
+                    .header("Metadata-Flavor", "Google")
+                    .timeout(METADATA_TIMEOUT)
+            }
+        };
+        let response = request.send().await.map_err(|e| {
+            if self.credentials == GoogleCredentials::Metadata {
+                PhazeError::Config(format!(
+                    "No Google Cloud credentials: set GOOGLE_APPLICATION_CREDENTIALS to a service \
+                     account key, run `gcloud auth application-default login`, or run on Google \
+                     Cloud ({e})"
+                ))
+            } else {
+                e.into()
+            }
+        })?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(PhazeError::Config(format!(
+                "Google OAuth token request failed ({status}): {body}"
+            )));
+        }
+        AccessToken::parse(&body, now)
+    }
+}
+
+/// Gemini (and other publishers' models) on Vertex AI.
+pub struct VertexClient {
+    tokens: Arc<TokenSource>,
+    project: VertexProject,
+    model: String,
+}
+
+impl VertexClient {
+    pub fn new(tokens: Arc<TokenSource>, project: VertexProject, model: impl Into<String>) -> Self {
+        Self {
+            tokens,
+            project,
+            model: model.into(),
+        }
+    }
+
+    /// A client with the shared credentials, for the project in the
+    /// provider's `base_url` or found as [`VertexProject::resolve`] does.
+    pub fn from_provider(config: &ProviderConfig, model: &str) -> Result<Self, PhazeError> {
+        let tokens = TokenSource::shared()?;
+        let project = VertexProject::resolve(&config.base_url, tokens.credentials())?;
+        Ok(Self::new(tokens, project, model))
+    }
+
+    pub fn project(&self) -> &VertexProject {
+        &self.project
+    }
+
+    async fn openai(&self) -> Result<super::OpenAIClient, PhazeError> {
+        Ok(super::OpenAIClient::new(self.tokens.token().await?)
+            .with_model(&self.model)
+            .with_base_url(format!("{}/endpoints/openapi", self.project.base_url()))
+            .with_chat_path("/chat/completions"))
+    }
+
+    /// Google's Gemini models, as served in the project's location.
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, PhazeError> {
+        let url = format!(
+            "https://{}/v1beta1/publishers/google/models?pageSize=200",
+            self.project.host()
+        );
+        let response = reqwest::Client::new()
+            .get(url)
+            .bearer_auth(self.tokens.token().await?)
+            .header("x-goog-user-project", &self.project.project)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(PhazeError::Llm(format!(
+                "Vertex AI model listing failed ({status}): {body}"
+            )));
+        }
+        parse_publisher_models(&body)
+    }
+}
+
+/// The Gemini models in a publisher model listing, with the `google/` IDs
+/// the OpenAI-compatible endpoint takes. Context windows and prices are the
+/// Gemini API's where it lists the model (Vertex charges the same).
+pub fn parse_publisher_models(body: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct List {
+        #[serde(default)]
+        publisher_models: Vec<PublisherModel>,
+    }
+    #[derive(Deserialize)]
+    struct PublisherModel {
+        name: String,
+    }
+    let list: List = serde_json::from_str(body)?;
+    let gemini = ProviderRegistry::known_models(&ProviderId::Gemini);
+    Ok(list
+        .publisher_models
+        .into_iter()
+        .filter_map(|m| {
+            let name = m.name.rsplit('/').next()?.to_string();
+            if !name.starts_with("gemini") || name.contains("embedding") {
+                return None;
+            }
+            let known = gemini
+                .iter()
+                .filter(|k| name.starts_with(&k.id))
+                .max_by_key(|k| k.id.len());
+            Some(ModelInfo {
+                id: format!("google/{name}"),
+                context_window: known.map_or(1_000_000, |k| k.context_window),
+                supports_tools: true,
+                input_cost_per_m: known.map_or(0.0, |k| k.input_cost_per_m),
+                output_cost_per_m: known.map_or(0.0, |k| k.output_cost_per_m),
+                name,
+            })
+        })
+        .collect())
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+#[async_trait::async_trait]
+impl LlmClient for VertexClient {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        self.openai().await?.chat(messages, tools).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        self.openai().await?.chat_stream(messages, tools).await
+    }
+}
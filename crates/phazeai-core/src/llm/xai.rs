@@ -0,0 +1,141 @@
+//! xAI's Grok models.
+//!
+//! The xAI API is OpenAI-compatible, so chat goes through
+//! [`OpenAIClient`](super::OpenAIClient). What differs is model discovery:
+//! `/v1/language-models` lists the models the key can use with their prices,
+//! and Grok's reasoning tokens, which streamed responses only report when
+//! asked for usage.
+
+use futures::channel::mpsc;
+use serde::Deserialize;
+
+use crate::constants::{endpoints, models};
+use crate::error::PhazeError;
+use crate::llm::provider::{ModelInfo, ProviderConfig, ProviderId, ProviderRegistry};
+use crate::llm::traits::*;
+use crate::llm::OpenAIClient;
+use crate::tools::ToolDefinition;
+
+/// Context window for models the listing has and the known models don't.
+const DEFAULT_CONTEXT_WINDOW: usize = 131_072;
+
+pub struct XaiClient {
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl XaiClient {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            base_url: endpoints::XAI_BASE_URL.to_string(),
+            model: models::DEFAULT_XAI_MODEL.to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// A client with the provider's key and base URL.
+    pub fn from_provider(config: &ProviderConfig, model: &str) -> Result<Self, PhazeError> {
+        let api_key = config.api_key().ok_or_else(|| {
+            PhazeError::Config(format!(
+                "Set {} environment variable for xAI",
+                config.api_key_env
+            ))
+        })?;
+        Ok(Self::new(api_key)
+            .with_model(model)
+            .with_base_url(&config.base_url))
+    }
+
+    fn openai(&self) -> OpenAIClient {
+        OpenAIClient::new(&self.api_key)
+            .with_model(&self.model)
+            .with_base_url(&self.base_url)
+            .with_stream_usage(true)
+    }
+
+    /// The language models the key can use.
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, PhazeError> {
+        let response = reqwest::Client::new()
+            .get(format!("{}/v1/language-models", self.base_url))
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(PhazeError::Llm(format!(
+                "xAI model listing failed ({status}): {body}"
+            )));
+        }
+        parse_language_models(&body)
+    }
+}
+
+/// The models in a `/v1/language-models` listing. Prices are the listing's
+/// (in cents per 100M tokens); context windows are the known models'.
+pub fn parse_language_models(body: &str) -> Result<Vec<ModelInfo>, PhazeError> {
+    #[derive(Deserialize)]
+    struct List {
+        #[serde(default)]
+        models: Vec<LanguageModel>,
+    }
+    #[derive(Deserialize)]
+    struct LanguageModel {
+        id: String,
+        #[serde(default)]
+        prompt_text_token_price: f64,
+        #[serde(default)]
+        completion_text_token_price: f64,
+    }
+    let list: List = serde_json::from_str(body)?;
+    let known = ProviderRegistry::known_models(&ProviderId::Xai);
+    Ok(list
+        .models
+        .into_iter()
+        .map(|m| {
+            let context_window = known
+                .iter()
+                .filter(|k| m.id.starts_with(&k.id))
+                .max_by_key(|k| k.id.len())
+                .map_or(DEFAULT_CONTEXT_WINDOW, |k| k.context_window);
+            ModelInfo {
+                name: m.id.clone(),
+                id: m.id,
+                context_window,
+                supports_tools: true,
+                input_cost_per_m: m.prompt_text_token_price / 10_000.0,
+                output_cost_per_m: m.completion_text_token_price / 10_000.0,
+            }
+        })
+        .collect())
+}
+
+#[async_trait::async_trait]
+impl LlmClient for XaiClient {
+    async fn chat(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        self.openai().chat(messages, tools).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<mpsc::UnboundedReceiver<StreamEvent>, PhazeError> {
+        self.openai().chat_stream(messages, tools).await
+    }
+}
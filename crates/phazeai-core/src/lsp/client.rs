@@ -4,15 +4,18 @@ use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Read, Write};
 use std::path::Path;
 use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use lsp_types::*;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tokio::sync::mpsc;
 
+use crate::progress::{self, ProgressTask};
+
 /// Convert a filesystem path to a file:// URI string
 fn path_to_uri(path: &Path) -> Result<Uri, String> {
     let abs = if path.is_absolute() {
@@ -29,8 +32,9 @@ fn path_to_uri(path: &Path) -> Result<Uri, String> {
 /// Events emitted by the LSP client to the IDE
 #[derive(Debug, Clone)]
 pub enum LspEvent {
-    /// Diagnostics (errors, warnings) for a file
+    /// Diagnostics (errors, warnings) for a file, from the named server
     Diagnostics {
+        server: String,
         uri: Uri,
         diagnostics: Vec<Diagnostic>,
     },
@@ -50,17 +54,24 @@ pub enum LspEvent {
     Initialized(String),
     /// Server exited
     Shutdown,
+    /// The named server's process ended, whether shut down or crashed
+    Exited(String),
     /// Log message from server
     Log(String),
 }
 
+/// Requests awaiting a response, by id, with when each was sent.
+type Pending = Arc<Mutex<HashMap<u64, (Instant, tokio::sync::oneshot::Sender<Value>)>>>;
+
 /// A single LSP client connected to one language server.
 pub struct LspClient {
     id_counter: AtomicU64,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
-    pending: Arc<Mutex<HashMap<u64, tokio::sync::oneshot::Sender<Value>>>>,
+    pending: Pending,
     server_name: String,
-    child: Option<Child>,
+    child: Mutex<Option<Child>>,
+    /// Set by the reader thread when the server's stdout closes.
+    closed: Arc<AtomicBool>,
     event_tx: mpsc::UnboundedSender<LspEvent>,
     capabilities: Arc<Mutex<Option<ServerCapabilities>>>,
 }
@@ -86,8 +97,8 @@ impl LspClient {
         let stdout = child.stdout.take().ok_or("No stdout")?;
 
         let writer: Arc<Mutex<Box<dyn Write + Send>>> = Arc::new(Mutex::new(Box::new(stdin)));
-        let pending: Arc<Mutex<HashMap<u64, tokio::sync::oneshot::Sender<Value>>>> =
-            Arc::new(Mutex::new(HashMap::new()));
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
 
         let server_name = server_cmd.to_string();
 
@@ -96,7 +107,8 @@ impl LspClient {
             writer: writer.clone(),
             pending: pending.clone(),
             server_name: server_name.clone(),
-            child: Some(child),
+            child: Mutex::new(Some(child)),
+            closed: closed.clone(),
             event_tx: event_tx.clone(),
             capabilities: Arc::new(Mutex::new(None)),
         };
@@ -105,12 +117,52 @@ impl LspClient {
         let event_tx_clone = event_tx.clone();
         let pending_clone = pending.clone();
         thread::spawn(move || {
-            Self::reader_loop(stdout, event_tx_clone, pending_clone);
+            Self::reader_loop(stdout, &server_name, &event_tx_clone, &pending_clone);
+            closed.store(true, Ordering::Release);
+            let _ = event_tx_clone.send(LspEvent::Exited(server_name));
         });
 
         Ok(client)
     }
 
+    /// The command the server was started with.
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    /// Whether the server process has ended (or closed its output, which
+    /// amounts to the same for us).
+    pub fn has_exited(&self) -> bool {
+        if self.closed.load(Ordering::Acquire) {
+            return true;
+        }
+        match self.child.lock() {
+            Ok(mut child) => match child.as_mut() {
+                Some(child) => !matches!(child.try_wait(), Ok(None)),
+                None => true,
+            },
+            Err(_) => true,
+        }
+    }
+
+    /// Whether a request has gone unanswered for longer than `timeout`.
+    pub fn is_unresponsive(&self, timeout: Duration) -> bool {
+        self.pending
+            .lock()
+            .is_ok_and(|pending| pending.values().any(|(sent, _)| sent.elapsed() > timeout))
+    }
+
+    /// Kill the server process without the shutdown handshake, for a server
+    /// that no longer responds.
+    pub fn kill(&self) {
+        if let Ok(mut child) = self.child.lock() {
+            if let Some(child) = child.as_mut() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+
     /// Send the LSP initialize request
     #[allow(deprecated)]
     pub async fn initialize(&self, workspace_root: &Path) -> Result<(), String> {
@@ -137,8 +189,25 @@ impl LspClient {
                     definition: Some(GotoCapability {
                         ..Default::default()
                     }),
+                    code_lens: Some(CodeLensClientCapabilities {
+                        ..Default::default()
+                    }),
+                    document_highlight: Some(DocumentHighlightClientCapabilities {
+                        ..Default::default()
+                    }),
                     ..Default::default()
                 }),
+                // rust-analyzer only sends the lenses whose commands the
+                // client says it can run.
+                experimental: Some(json!({
+                    "commands": {
+                        "commands": [
+                            "rust-analyzer.runSingle",
+                            "rust-analyzer.debugSingle",
+                            "rust-analyzer.showReferences",
+                        ]
+                    }
+                })),
                 ..Default::default()
             },
             ..Default::default()
@@ -173,16 +242,41 @@ impl LspClient {
         })
     }
 
-    /// Notify the server that a file changed
+    /// How the server wants document changes sent, once initialized.
+    pub fn sync_kind(&self) -> TextDocumentSyncKind {
+        match self.capabilities.lock() {
+            Ok(caps) => caps
+                .as_ref()
+                .map_or(TextDocumentSyncKind::FULL, super::sync::sync_kind),
+            Err(_) => TextDocumentSyncKind::FULL,
+        }
+    }
+
+    /// Notify the server that a file changed, sending its whole text
     pub fn did_change(&self, path: &Path, version: i32, text: &str) -> Result<(), String> {
-        let uri = path_to_uri(path)?;
-        self.send_notification::<notification::DidChangeTextDocument>(DidChangeTextDocumentParams {
-            text_document: VersionedTextDocumentIdentifier { uri, version },
-            content_changes: vec![TextDocumentContentChangeEvent {
+        self.did_change_ranges(
+            path,
+            version,
+            vec![TextDocumentContentChangeEvent {
                 range: None,
                 range_length: None,
                 text: text.to_string(),
             }],
+        )
+    }
+
+    /// Notify the server that a file changed, sending only the changed
+    /// ranges (for servers with incremental sync)
+    pub fn did_change_ranges(
+        &self,
+        path: &Path,
+        version: i32,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    ) -> Result<(), String> {
+        let uri = path_to_uri(path)?;
+        self.send_notification::<notification::DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier { uri, version },
+            content_changes: changes,
         })
     }
 
@@ -411,6 +505,71 @@ impl LspClient {
         }
     }
 
+    /// Whether the server offers code lenses, and if so whether it sends
+    /// them without commands, to be filled in by `codeLens/resolve`.
+    pub fn code_lens_support(&self) -> Option<bool> {
+        let caps = self.capabilities.lock().ok()?;
+        let options = caps.as_ref()?.code_lens_provider?;
+        Some(options.resolve_provider.unwrap_or(false))
+    }
+
+    /// Request the code lenses of a document (textDocument/codeLens).
+    pub async fn code_lens(&self, path: &Path) -> Result<Vec<CodeLens>, String> {
+        let uri = path_to_uri(path)?;
+        let params = CodeLensParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let result = self
+            .send_request::<request::CodeLensRequest>(params)
+            .await?;
+        Ok(result.unwrap_or_default())
+    }
+
+    /// Fill in the command of a code lens sent without one (codeLens/resolve).
+    pub async fn resolve_code_lens(&self, lens: CodeLens) -> Result<CodeLens, String> {
+        self.send_request::<request::CodeLensResolve>(lens).await
+    }
+
+    /// Whether the server can find the occurrences of a symbol in a document.
+    pub fn supports_document_highlight(&self) -> bool {
+        let Ok(caps) = self.capabilities.lock() else {
+            return false;
+        };
+        match caps
+            .as_ref()
+            .and_then(|c| c.document_highlight_provider.as_ref())
+        {
+            Some(OneOf::Left(enabled)) => *enabled,
+            Some(OneOf::Right(_)) => true,
+            None => false,
+        }
+    }
+
+    /// Request the occurrences of the symbol at a position, each marked as
+    /// read or written where the server knows (textDocument/documentHighlight).
+    pub async fn document_highlight(
+        &self,
+        path: &Path,
+        line: u32,
+        character: u32,
+    ) -> Result<Vec<DocumentHighlight>, String> {
+        let uri = path_to_uri(path)?;
+        let params = DocumentHighlightParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position { line, character },
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let result = self
+            .send_request::<request::DocumentHighlightRequest>(params)
+            .await?;
+        Ok(result.unwrap_or_default())
+    }
+
     /// Request folding ranges for a document (textDocument/foldingRange).
     /// Request inlay hints for a range of lines (textDocument/inlayHint).
     pub async fn inlay_hints(
@@ -554,9 +713,7 @@ impl LspClient {
     pub async fn shutdown(&mut self) -> Result<(), String> {
         let _ = self.send_request::<request::Shutdown>(()).await;
         self.send_notification::<notification::Exit>(())?;
-        if let Some(ref mut child) = self.child {
-            let _ = child.kill();
-        }
+        self.kill();
         let _ = self.event_tx.send(LspEvent::Shutdown);
         Ok(())
     }
@@ -584,7 +741,7 @@ impl LspClient {
         let (tx, rx) = tokio::sync::oneshot::channel();
         {
             let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
-            pending.insert(id, tx);
+            pending.insert(id, (Instant::now(), tx));
         }
 
         self.write_message(&msg)?;
@@ -628,10 +785,14 @@ impl LspClient {
     /// Read loop: parse LSP messages from stdout and dispatch them
     fn reader_loop(
         stdout: impl Read + Send + 'static,
-        event_tx: mpsc::UnboundedSender<LspEvent>,
-        pending: Arc<Mutex<HashMap<u64, tokio::sync::oneshot::Sender<Value>>>>,
+        server_name: &str,
+        event_tx: &mpsc::UnboundedSender<LspEvent>,
+        pending: &Pending,
     ) {
         let mut reader = BufReader::new(stdout);
+        // Work-done progress the server reports, by token, mirrored on the
+        // progress bus until it ends (or the server exits).
+        let mut work: HashMap<String, ProgressTask> = HashMap::new();
 
         while let Ok(content_length) = Self::read_content_length(&mut reader) {
             // Read the JSON body
@@ -655,14 +816,14 @@ impl LspClient {
                 // It's a response to one of our requests
                 if let Some(result) = msg.get("result") {
                     if let Ok(mut pending) = pending.lock() {
-                        if let Some(tx) = pending.remove(&id) {
+                        if let Some((_, tx)) = pending.remove(&id) {
                             let _ = tx.send(result.clone());
                         }
                     }
                 } else if let Some(error) = msg.get("error") {
                     tracing::warn!("LSP error for request {}: {:?}", id, error);
                     if let Ok(mut pending) = pending.lock() {
-                        if let Some(tx) = pending.remove(&id) {
+                        if let Some((_, tx)) = pending.remove(&id) {
                             let _ = tx.send(Value::Null);
                         }
                     }
@@ -676,6 +837,7 @@ impl LspClient {
                                 serde_json::from_value::<PublishDiagnosticsParams>(params.clone())
                             {
                                 let _ = event_tx.send(LspEvent::Diagnostics {
+                                    server: server_name.to_string(),
                                     uri: diag_params.uri,
                                     diagnostics: diag_params.diagnostics,
                                 });
@@ -697,6 +859,25 @@ impl LspClient {
                             let kind = value["kind"].as_str().unwrap_or("").to_string();
                             let message = value["message"].as_str().map(|s| s.to_string());
                             let percentage = value["percentage"].as_u64().map(|p| p as u32);
+                            let token = params["token"].to_string();
+                            if kind == "begin" {
+                                let title = value["title"].as_str().unwrap_or("Language server");
+                                let mut task = progress::global().task(title);
+                                if percentage.is_some() {
+                                    task = task.unit("%").total(100);
+                                }
+                                work.insert(token.clone(), task.start());
+                            }
+                            if kind == "end" {
+                                work.remove(&token);
+                            } else if let Some(task) = work.get(&token) {
+                                if let Some(p) = percentage {
+                                    task.set_done(p as u64);
+                                }
+                                if let Some(message) = &message {
+                                    task.set_message(message.clone());
+                                }
+                            }
                             // Encode as a special log message that lsp_bridge can detect
                             let log_msg = if kind == "end" {
                                 "__progress_end__".to_string()
@@ -720,6 +901,11 @@ impl LspClient {
                 }
             }
         }
+        // Nothing will answer the requests still waiting; fail them rather
+        // than leave their callers hanging.
+        if let Ok(mut pending) = pending.lock() {
+            pending.clear();
+        }
     }
 
     /// Parse the Content-Length header from LSP message stream
@@ -748,8 +934,6 @@ impl LspClient {
 
 impl Drop for LspClient {
     fn drop(&mut self) {
-        if let Some(ref mut child) = self.child {
-            let _ = child.kill();
-        }
+        self.kill();
     }
 }
@@ -0,0 +1,143 @@
+//! Project-wide diagnostics from the language servers.
+//!
+//! Servers publish diagnostics for any file in the project — rust-analyzer
+//! reports `cargo check` results for the whole workspace — not only the files
+//! open in the editor. [`DiagnosticStore`] keeps the latest set each server
+//! published for each file, whether or not the file is open, and drops a
+//! server's diagnostics when it exits or restarts, since they no longer apply.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, Uri};
+
+/// The file a `file://` URI names, with percent-escapes decoded.
+pub fn uri_to_path(uri: &Uri) -> PathBuf {
+    let text = uri.as_str();
+    let path = text.strip_prefix("file://").unwrap_or(text);
+    match urlencoding::decode(path) {
+        Ok(decoded) => PathBuf::from(decoded.into_owned()),
+        Err(_) => PathBuf::from(path),
+    }
+}
+
+/// Errors and warnings in a file, or in everything under a directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiagnosticCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl DiagnosticCounts {
+    pub fn is_empty(&self) -> bool {
+        self.errors == 0 && self.warnings == 0
+    }
+
+    fn add(&mut self, other: DiagnosticCounts) {
+        self.errors += other.errors;
+        self.warnings += other.warnings;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticStore {
+    /// file → server → what the server last published for it.
+    files: BTreeMap<PathBuf, BTreeMap<String, Vec<Diagnostic>>>,
+}
+
+impl DiagnosticStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace what `server` reported for `path`; an empty list clears it.
+    /// Returns whether anything changed.
+    pub fn publish(&mut self, server: &str, path: PathBuf, diagnostics: Vec<Diagnostic>) -> bool {
+        if diagnostics.is_empty() {
+            let Some(by_server) = self.files.get_mut(&path) else {
+                return false;
+            };
+            let removed = by_server.remove(server).is_some();
+            if by_server.is_empty() {
+                self.files.remove(&path);
+            }
+            return removed;
+        }
+        let by_server = self.files.entry(path).or_default();
+        by_server.insert(server.to_string(), diagnostics.clone()) != Some(diagnostics)
+    }
+
+    /// Forget everything `server` reported, e.g. when it exits or restarts.
+    /// Returns whether anything was removed.
+    pub fn clear_server(&mut self, server: &str) -> bool {
+        let mut removed = false;
+        self.files.retain(|_, by_server| {
+            removed |= by_server.remove(server).is_some();
+            !by_server.is_empty()
+        });
+        removed
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Files with diagnostics.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Diagnostics for `path` from every server.
+    pub fn get(&self, path: &Path) -> impl Iterator<Item = &Diagnostic> {
+        self.files
+            .get(path)
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, d)| d)
+    }
+
+    /// Every diagnostic with its file, files in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &Diagnostic)> {
+        self.files.iter().flat_map(|(path, by_server)| {
+            by_server
+                .values()
+                .flatten()
+                .map(move |d| (path.as_path(), d))
+        })
+    }
+
+    /// Errors and warnings per file, and per directory between each file and
+    /// `root` (`root` itself excluded), for badges in a file tree.
+    pub fn counts(&self, root: &Path) -> HashMap<PathBuf, DiagnosticCounts> {
+        let mut counts: HashMap<PathBuf, DiagnosticCounts> = HashMap::new();
+        for (path, by_server) in &self.files {
+            let mut file = DiagnosticCounts::default();
+            for d in by_server.values().flatten() {
+                match d.severity {
+                    // Servers that leave severity out mean an error.
+                    Some(DiagnosticSeverity::ERROR) | None => file.errors += 1,
+                    Some(DiagnosticSeverity::WARNING) => file.warnings += 1,
+                    _ => {}
+                }
+            }
+            if file.is_empty() {
+                continue;
+            }
+            counts.entry(path.clone()).or_default().add(file);
+            if !path.starts_with(root) {
+                continue;
+            }
+            for dir in path.ancestors().skip(1) {
+                if dir == root {
+                    break;
+                }
+                counts.entry(dir.to_path_buf()).or_default().add(file);
+            }
+        }
+        counts
+    }
+}
@@ -0,0 +1,97 @@
+//! Language server health: what state each server is in, and how long to
+//! wait before restarting one that died.
+//!
+//! [`LspManager`](super::LspManager) restarts a server that exits or stops
+//! answering requests, waiting longer after each consecutive failure as
+//! [`Backoff`] dictates, and gives up on a server that keeps failing until
+//! the user restarts it by hand.
+
+use std::time::Duration;
+
+/// Exponential backoff between restarts: the delay doubles after each
+/// consecutive failure, up to a cap, and runs out after a number of attempts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    max_attempts: u32,
+    attempt: u32,
+}
+
+impl Default for Backoff {
+    /// 1s, 2s, 4s, 8s, 16s, then give up.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60), 5)
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration, max_attempts: u32) -> Self {
+        Self {
+            initial,
+            max,
+            max_attempts,
+            attempt: 0,
+        }
+    }
+
+    /// Consecutive failures so far.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Count a failure and return how long to wait before the next attempt,
+    /// or `None` when there are no attempts left.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.attempt >= self.max_attempts {
+            return None;
+        }
+        let delay = self
+            .initial
+            .saturating_mul(2u32.saturating_pow(self.attempt))
+            .min(self.max);
+        self.attempt += 1;
+        Some(delay)
+    }
+
+    /// Start over, e.g. after the server ran fine for a while.
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerState {
+    Starting,
+    Running,
+    /// Died; the `attempt`th restart is scheduled.
+    Restarting {
+        attempt: u32,
+    },
+    /// Gave up restarting, or could not start at all.
+    Failed(String),
+}
+
+impl ServerState {
+    pub fn label(&self) -> String {
+        match self {
+            ServerState::Starting => "starting".to_string(),
+            ServerState::Running => "running".to_string(),
+            ServerState::Restarting { attempt } => format!("restarting (attempt {attempt})"),
+            ServerState::Failed(reason) => format!("failed: {reason}"),
+        }
+    }
+}
+
+/// A language server as shown in the status bar and the Language Servers
+/// panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerStatus {
+    /// The server's command, e.g. `rust-analyzer`.
+    pub server: String,
+    /// Language ids it serves.
+    pub languages: Vec<String>,
+    pub state: ServerState,
+    /// Open documents in its languages.
+    pub open_files: usize,
+}
@@ -0,0 +1,448 @@
+//! Code lenses: the actions shown above functions.
+//!
+//! A file's lenses are merged from the language server's
+//! `textDocument/codeLens`, translated by [`from_server`], and from the IDE's
+//! own sources for what the server leaves out: [`runnables`] finds `main` and
+//! test functions to Run or Debug, [`reference_title`] labels the reference
+//! counts of symbols, and [`explain_lens`] puts an "Explain" lens, which asks
+//! the AI about the function, above functions complex enough to need one.
+//!
+//! Run and Debug lenses carry a shell command for the integrated terminal.
+//! Debugging uses the language's command-line debugger: cargo targets run
+//! under `rust-gdb` (`rust-lldb` on macOS), Python under `pdb`, Go under
+//! `dlv` and Jest under the Node inspector.
+
+use std::path::{Path, PathBuf};
+
+use lsp_types::{CodeLens, Position};
+use serde_json::Value;
+
+/// Branches a function needs before it gets an "Explain" lens.
+pub const EXPLAIN_COMPLEXITY: u32 = 10;
+
+/// What clicking a lens does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LensAction {
+    /// Run this command in the terminal.
+    Run(String),
+    /// Run this command, which starts the target under a debugger, in the
+    /// terminal.
+    Debug(String),
+    /// List the references to the symbol at this (0-based) position.
+    References(Position),
+    /// List the implementations of the symbol at this (0-based) position.
+    Implementations(Position),
+    /// Ask the AI to explain the function `symbol` on these (0-based,
+    /// inclusive) lines.
+    Explain {
+        symbol: String,
+        start_line: u32,
+        end_line: u32,
+    },
+    /// Nothing; the lens only informs.
+    None,
+}
+
+impl LensAction {
+    /// Where lenses sharing a line go, left to right.
+    pub fn order(&self) -> u8 {
+        match self {
+            LensAction::Run(_) => 0,
+            LensAction::Debug(_) => 1,
+            LensAction::References(_) => 2,
+            LensAction::Implementations(_) => 3,
+            LensAction::None => 4,
+            LensAction::Explain { .. } => 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lens {
+    /// 0-based line the lens is shown above.
+    pub line: u32,
+    pub title: String,
+    pub action: LensAction,
+}
+
+impl Lens {
+    fn new(line: u32, title: &str, action: LensAction) -> Self {
+        Self {
+            line,
+            title: title.to_string(),
+            action,
+        }
+    }
+}
+
+/// Translate a resolved server lens. rust-analyzer's runnables become Run and
+/// Debug commands and reference or implementation counts list them; other
+/// lenses are kept for their title when they have no command, and dropped
+/// when they name a command only their own editor extension can run.
+pub fn from_server(lens: &CodeLens) -> Option<Lens> {
+    let command = lens.command.as_ref()?;
+    let line = lens.range.start.line;
+    let argument = |i: usize| command.arguments.as_ref().and_then(|args| args.get(i));
+    let action = match command.command.as_str() {
+        "rust-analyzer.runSingle" => LensAction::Run(runnable_command(argument(0)?, false)?),
+        "rust-analyzer.debugSingle" => LensAction::Debug(runnable_command(argument(0)?, true)?),
+        id if id.ends_with("showReferences") || is_count(&command.title) => {
+            let position = argument(1)
+                .and_then(|p| serde_json::from_value(p.clone()).ok())
+                .unwrap_or(lens.range.start);
+            if command.title.contains("implementation") {
+                LensAction::Implementations(position)
+            } else {
+                LensAction::References(position)
+            }
+        }
+        "" => LensAction::None,
+        _ => return None,
+    };
+    Some(Lens::new(line, &command.title, action))
+}
+
+/// Whether `title` is a count such as "3 references" or "1 implementation".
+fn is_count(title: &str) -> bool {
+    let mut words = title.split_whitespace();
+    matches!(
+        (words.next(), words.next(), words.next()),
+        (Some(n), Some(_), None) if n.parse::<usize>().is_ok()
+    )
+}
+
+/// The command line for a rust-analyzer runnable, under the debugger when
+/// `debug` is set.
+fn runnable_command(runnable: &Value, debug: bool) -> Option<String> {
+    let args = runnable.get("args")?;
+    let strings = |key: &str| -> Vec<String> {
+        args.get(key)
+            .and_then(Value::as_array)
+            .map(|a| {
+                a.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let cwd = args
+        .get("cwd")
+        .or_else(|| args.get("workspaceRoot"))
+        .and_then(Value::as_str);
+    let words = match runnable.get("kind").and_then(Value::as_str) {
+        Some("cargo") => {
+            let cargo = args
+                .get("overrideCargo")
+                .and_then(Value::as_str)
+                .unwrap_or("cargo");
+            let mut words = vec![cargo.to_string()];
+            if debug {
+                words.extend(["--config".to_string(), cargo_debug_config().to_string()]);
+            }
+            words.extend(strings("cargoArgs"));
+            words.extend(strings("cargoExtraArgs"));
+            let executable = strings("executableArgs");
+            if !executable.is_empty() {
+                words.push("--".to_string());
+                words.extend(executable);
+            }
+            words
+        }
+        // Custom runners can't be put under the debugger.
+        Some("shell") if !debug => {
+            let mut words = vec![args.get("program")?.as_str()?.to_string()];
+            words.extend(strings("args"));
+            words
+        }
+        _ => return None,
+    };
+    let line = words
+        .iter()
+        .map(|w| shell_word(w))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(match cwd {
+        Some(cwd) => format!("cd {} && {line}", shell_word(cwd)),
+        None => line,
+    })
+}
+
+/// The `--config` that makes cargo start its targets under the debugger.
+fn cargo_debug_config() -> &'static str {
+    if cfg!(target_os = "macos") {
+        r#"target.'cfg(all())'.runner="rust-lldb --""#
+    } else {
+        r#"target.'cfg(all())'.runner="rust-gdb --args""#
+    }
+}
+
+/// Quote `s` for a POSIX shell, unless it is plain enough not to need it.
+fn shell_word(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if plain {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
+
+/// "1 reference", "3 references".
+pub fn reference_title(count: usize) -> String {
+    if count == 1 {
+        "1 reference".to_string()
+    } else {
+        format!("{count} references")
+    }
+}
+
+/// Rough cyclomatic complexity of `code`: one plus its branches and
+/// short-circuit operators.
+pub fn complexity(code: &str) -> u32 {
+    const BRANCHES: &[&str] = &[
+        "if", "elif", "for", "while", "loop", "match", "case", "catch", "except", "select",
+    ];
+    let words = code
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| BRANCHES.contains(w))
+        .count();
+    let operators = code.matches("&&").count() + code.matches("||").count();
+    1 + (words + operators) as u32
+}
+
+/// An "Explain" lens for the function `symbol` on lines `start_line..=end_line`
+/// of `text`, when it is complex enough to need one.
+pub fn explain_lens(symbol: &str, start_line: u32, end_line: u32, text: &str) -> Option<Lens> {
+    let body: String = text
+        .lines()
+        .skip(start_line as usize)
+        .take((end_line.saturating_sub(start_line) + 1) as usize)
+        .collect::<Vec<_>>()
+        .join("\n");
+    (complexity(&body) >= EXPLAIN_COMPLEXITY).then(|| {
+        Lens::new(
+            start_line,
+            "Explain",
+            LensAction::Explain {
+                symbol: symbol.to_string(),
+                start_line,
+                end_line,
+            },
+        )
+    })
+}
+
+/// Run and Debug lenses for the `main` and test functions in `text`, the
+/// contents of `path`, for Rust, Python, Go and Jest tests in JavaScript or
+/// TypeScript.
+pub fn runnables(path: &Path, text: &str) -> Vec<Lens> {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "rs" => rust_runnables(path, text),
+        "py" => python_runnables(path, text),
+        "go" => go_runnables(path, text),
+        "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" => jest_runnables(path, text),
+        _ => Vec::new(),
+    }
+}
+
+/// Run and Debug lenses on `line`.
+fn run_debug(line: u32, run: String, debug: String) -> [Lens; 2] {
+    [
+        Lens::new(line, "Run", LensAction::Run(run)),
+        Lens::new(line, "Debug", LensAction::Debug(debug)),
+    ]
+}
+
+/// The name of the function declared on `line`, if it declares one.
+fn rust_fn_name(line: &str) -> Option<&str> {
+    let (before, after) = line.split_once("fn ")?;
+    let qualifiers_only = before.split_whitespace().all(|w| {
+        matches!(w, "pub" | "async" | "unsafe" | "const" | "extern") || w.starts_with("pub(")
+    });
+    let name = after.split(['(', '<', ' ']).next()?;
+    (qualifiers_only && !name.is_empty()).then_some(name)
+}
+
+fn rust_runnables(path: &Path, text: &str) -> Vec<Lens> {
+    let Some(manifest) = find_up(path, "Cargo.toml") else {
+        return Vec::new();
+    };
+    let crate_dir = manifest.parent().unwrap_or(Path::new(""));
+    let relative = path.strip_prefix(crate_dir).unwrap_or(path);
+    let parts: Vec<&str> = relative.iter().filter_map(|p| p.to_str()).collect();
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    // Which target the file belongs to, when it isn't the library's.
+    let target = match parts.as_slice() {
+        ["tests", _] => vec!["--test".to_string(), stem.to_string()],
+        ["src", "bin", _] => vec!["--bin".to_string(), stem.to_string()],
+        ["src", "bin", bin, "main.rs"] => vec!["--bin".to_string(), bin.to_string()],
+        ["examples", _] => vec!["--example".to_string(), stem.to_string()],
+        _ => Vec::new(),
+    };
+    let cargo = |debug: bool, subcommand: &str, rest: &[&str]| {
+        let mut words = vec!["cargo".to_string()];
+        if debug {
+            words.extend(["--config".to_string(), cargo_debug_config().to_string()]);
+        }
+        words.extend([
+            subcommand.to_string(),
+            "--manifest-path".to_string(),
+            manifest.to_string_lossy().into_owned(),
+        ]);
+        words.extend(target.iter().cloned());
+        words.extend(rest.iter().map(|w| w.to_string()));
+        words
+            .iter()
+            .map(|w| shell_word(w))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut lenses = Vec::new();
+    let mut item_start: Option<u32> = None;
+    let mut is_test = false;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        // The lens goes above the item's doc comment and attributes.
+        if trimmed.starts_with("///") || trimmed.starts_with("#[") {
+            item_start.get_or_insert(i as u32);
+            is_test |= trimmed == "#[test]"
+                || trimmed.starts_with("#[tokio::test")
+                || trimmed.starts_with("#[async_std::test");
+            continue;
+        }
+        if let Some(name) = rust_fn_name(trimmed) {
+            let at = item_start.unwrap_or(i as u32);
+            if is_test {
+                lenses.extend(run_debug(
+                    at,
+                    cargo(false, "test", &["--", name]),
+                    cargo(true, "test", &["--", name]),
+                ));
+            } else if name == "main"
+                && !line.starts_with(char::is_whitespace)
+                && matches!(parts.first(), Some(&"src" | &"examples"))
+            {
+                lenses.extend(run_debug(
+                    at,
+                    cargo(false, "run", &[]),
+                    cargo(true, "run", &[]),
+                ));
+            }
+        }
+        if !trimmed.is_empty() {
+            item_start = None;
+            is_test = false;
+        }
+    }
+    lenses
+}
+
+fn python_runnables(path: &Path, text: &str) -> Vec<Lens> {
+    let file = path.to_string_lossy();
+    let pytest = |debug: bool, id: &str| {
+        let trace = if debug { " --trace" } else { "" };
+        format!("python3 -m pytest{trace} {}", shell_word(id))
+    };
+    let mut lenses = Vec::new();
+    let mut class: Option<&str> = None;
+    for (i, line) in text.lines().enumerate() {
+        let indented = line.starts_with(char::is_whitespace);
+        let trimmed = line.trim();
+        if !indented && !trimmed.is_empty() {
+            class = trimmed
+                .strip_prefix("class ")
+                .and_then(|rest| rest.split(['(', ':']).next())
+                .map(str::trim);
+        }
+        let def = trimmed
+            .strip_prefix("async def ")
+            .or_else(|| trimmed.strip_prefix("def "));
+        if let Some(name) = def.and_then(|d| d.split('(').next()) {
+            if !name.starts_with("test") {
+                continue;
+            }
+            let id = match class.filter(|_| indented) {
+                Some(class) => format!("{file}::{class}::{name}"),
+                None if !indented => format!("{file}::{name}"),
+                None => continue,
+            };
+            lenses.extend(run_debug(i as u32, pytest(false, &id), pytest(true, &id)));
+        } else if !indented
+            && (trimmed.starts_with("if __name__ == \"__main__\"")
+                || trimmed.starts_with("if __name__ == '__main__'"))
+        {
+            let file = shell_word(&file);
+            lenses.extend(run_debug(
+                i as u32,
+                format!("python3 {file}"),
+                format!("python3 -m pdb {file}"),
+            ));
+        }
+    }
+    lenses
+}
+
+fn go_runnables(path: &Path, text: &str) -> Vec<Lens> {
+    let dir = shell_word(&path.parent().unwrap_or(Path::new(".")).to_string_lossy());
+    let is_main_package = text.lines().any(|l| l.trim() == "package main");
+    let mut lenses = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let Some(rest) = line.strip_prefix("func ") else {
+            continue;
+        };
+        let name = rest.split('(').next().unwrap_or("");
+        if name.starts_with("Test") && rest.contains("*testing.T") {
+            let pattern = shell_word(&format!("^{name}$"));
+            lenses.extend(run_debug(
+                i as u32,
+                format!("cd {dir} && go test -run {pattern} ."),
+                format!("cd {dir} && dlv test . -- -test.run {pattern}"),
+            ));
+        } else if name == "main" && is_main_package {
+            lenses.extend(run_debug(
+                i as u32,
+                format!("cd {dir} && go run ."),
+                format!("cd {dir} && dlv debug ."),
+            ));
+        }
+    }
+    lenses
+}
+
+fn jest_runnables(path: &Path, text: &str) -> Vec<Lens> {
+    let file = shell_word(&path.to_string_lossy());
+    let mut lenses = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let Some(rest) = ["test(", "it("]
+            .iter()
+            .find_map(|call| trimmed.strip_prefix(call))
+        else {
+            continue;
+        };
+        let Some(quote) = rest.chars().next().filter(|c| "'\"`".contains(*c)) else {
+            continue;
+        };
+        let Some(name) = rest[1..].split(quote).next() else {
+            continue;
+        };
+        let name = shell_word(name);
+        lenses.extend(run_debug(
+            i as u32,
+            format!("npx jest {file} -t {name}"),
+            format!("npx --node-options=--inspect-brk jest --runInBand {file} -t {name}"),
+        ));
+    }
+    lenses
+}
+
+/// The nearest `name` in `path`'s directory or one above it.
+fn find_up(path: &Path, name: &str) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
@@ -1,13 +1,27 @@
 /// LSP Manager — auto-detects and spawns the right language server
 /// for a given project type. Inspired by Lapce's plugin catalog.
+///
+/// Servers that exit or stop answering are restarted with backoff; edits
+/// made meanwhile are kept and replayed to the new server.
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use lsp_types::TextDocumentSyncKind;
 use tokio::sync::mpsc;
 
 use super::client::{LspClient, LspEvent};
+use super::health::{Backoff, ServerState, ServerStatus};
+use super::sync::{incremental_change, AppliedEdits};
 use crate::project::workspace::ProjectType;
 
+/// A server with a request pending this long is considered hung and killed.
+const UNRESPONSIVE_AFTER: Duration = Duration::from_secs(60);
+/// How long a server gets to answer `initialize`.
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(30);
+/// A server that ran this long before dying starts its backoff over.
+const STABLE_AFTER: Duration = Duration::from_secs(120);
+
 /// Known language server configurations
 #[derive(Debug, Clone)]
 pub struct LspServerConfig {
@@ -16,9 +30,31 @@ pub struct LspServerConfig {
     pub language_ids: Vec<String>,
 }
 
-/// Manages multiple LSP clients for different languages in a workspace.
+/// Health of one server, and the languages it serves.
+struct ServerHealth {
+    config: LspServerConfig,
+    state: ServerState,
+    backoff: Backoff,
+    started: Instant,
+    /// When a scheduled restart is due.
+    retry_at: Option<Instant>,
+}
+
+/// The latest text of a document the servers were told is open.
+struct OpenDoc {
+    version: i32,
+    text: String,
+}
+
+/// Manages the language servers of a workspace: one process per server,
+/// shared by every language it handles (typescript-language-server serves
+/// TypeScript and JavaScript), with requests routed by a file's language.
 pub struct LspManager {
+    /// Running clients, by server command.
     clients: HashMap<String, std::sync::Arc<LspClient>>,
+    /// Every server started so far, by command.
+    servers: HashMap<String, ServerHealth>,
+    open_docs: HashMap<PathBuf, OpenDoc>,
     workspace_root: PathBuf,
     event_tx: mpsc::UnboundedSender<LspEvent>,
 }
@@ -27,6 +63,8 @@ impl LspManager {
     pub fn new(workspace_root: PathBuf, event_tx: mpsc::UnboundedSender<LspEvent>) -> Self {
         Self {
             clients: HashMap::new(),
+            servers: HashMap::new(),
+            open_docs: HashMap::new(),
             workspace_root,
             event_tx,
         }
@@ -82,24 +120,67 @@ impl LspManager {
             .collect()
     }
 
-    /// Start the appropriate LSP server for a file based on its extension
+    /// Start the appropriate LSP server for a file based on its extension,
+    /// unless the server for its language is already up. A server waiting to
+    /// be restarted counts as up; one that was given up on is an error until
+    /// it is restarted by hand.
     pub async fn ensure_server_for_file(&mut self, path: &Path) -> Result<(), String> {
         let language_id = Self::language_id_from_path(path);
 
-        if self.clients.contains_key(&language_id) {
-            return Ok(());
+        if let Some(server) = self.server_for_language(&language_id) {
+            return match &self.servers[server].state {
+                ServerState::Failed(reason) => Err(reason.clone()),
+                _ => Ok(()),
+            };
         }
 
         let configs = Self::detect_available_servers();
         let config = configs
-            .iter()
+            .into_iter()
             .find(|c| c.language_ids.contains(&language_id))
             .ok_or_else(|| format!("No LSP server available for language: {}", language_id))?;
 
+        let server = config.command.clone();
+        self.servers.insert(
+            server.clone(),
+            ServerHealth {
+                config,
+                state: ServerState::Starting,
+                backoff: Backoff::default(),
+                started: Instant::now(),
+                retry_at: None,
+            },
+        );
+        let result = self.start_server(&server).await;
+        if result.is_err() {
+            self.schedule_restart(&server);
+        }
+        result
+    }
+
+    /// The server that handles `language_id`, if one was started.
+    fn server_for_language(&self, language_id: &str) -> Option<&str> {
+        self.servers
+            .iter()
+            .find(|(_, h)| h.config.language_ids.iter().any(|l| l == language_id))
+            .map(|(server, _)| server.as_str())
+    }
+
+    /// Spawn and initialize `server`, then reopen the documents it had open
+    /// with their latest text.
+    async fn start_server(&mut self, server: &str) -> Result<(), String> {
+        let Some(health) = self.servers.get_mut(server) else {
+            return Err(format!("Unknown LSP server: {server}"));
+        };
+        let config = health.config.clone();
+        health.state = ServerState::Starting;
+        health.started = Instant::now();
+        health.retry_at = None;
+
         tracing::info!(
-            "Starting LSP server '{}' for language '{}'",
+            "Starting LSP server '{}' for {}",
             config.command,
-            language_id
+            config.language_ids.join(", ")
         );
 
         let client = LspClient::start(
@@ -109,22 +190,169 @@ impl LspManager {
             self.event_tx.clone(),
         )?;
 
-        client.initialize(&self.workspace_root).await?;
+        match tokio::time::timeout(INITIALIZE_TIMEOUT, client.initialize(&self.workspace_root))
+            .await
+        {
+            Ok(result) => result?,
+            Err(_) => {
+                client.kill();
+                return Err(format!("{} did not initialize", config.command));
+            }
+        }
 
+        for (path, doc) in &self.open_docs {
+            let language_id = Self::language_id_from_path(path);
+            if config.language_ids.contains(&language_id) {
+                if let Err(e) = client.did_open(path, &language_id, &doc.text) {
+                    tracing::warn!("LSP didOpen failed: {}", e);
+                }
+            }
+        }
+        if let Some(health) = self.servers.get_mut(server) {
+            health.state = ServerState::Running;
+        }
         self.clients
-            .insert(language_id, std::sync::Arc::new(client));
+            .insert(server.to_string(), std::sync::Arc::new(client));
         Ok(())
     }
 
+    /// Drop the client of `server` and schedule its restart, or give up when
+    /// its backoff has run out.
+    fn schedule_restart(&mut self, server: &str) {
+        if let Some(client) = self.clients.remove(server) {
+            client.kill();
+        }
+        let Some(health) = self.servers.get_mut(server) else {
+            return;
+        };
+        if health.state == ServerState::Running && health.started.elapsed() >= STABLE_AFTER {
+            health.backoff.reset();
+        }
+        match health.backoff.next_delay() {
+            Some(delay) => {
+                tracing::warn!("LSP server '{}' is down; restarting in {:?}", server, delay);
+                health.state = ServerState::Restarting {
+                    attempt: health.backoff.attempt(),
+                };
+                health.retry_at = Some(Instant::now() + delay);
+            }
+            None => {
+                tracing::error!("LSP server '{}' keeps failing; giving up", server);
+                health.state = ServerState::Failed(format!(
+                    "exited {} times in a row",
+                    health.backoff.attempt() + 1
+                ));
+                health.retry_at = None;
+            }
+        }
+    }
+
+    /// The process of `server` ended. If a newer process of it is running
+    /// (the event is about one replaced since) it is left alone. Returns
+    /// whether the server's state changed.
+    pub fn server_exited(&mut self, server: &str) -> bool {
+        let dead = self.clients.get(server).is_some_and(|c| c.has_exited());
+        if dead {
+            self.schedule_restart(server);
+        }
+        dead
+    }
+
+    /// Look for servers that exited or hang on a request, and schedule their
+    /// restart. Returns whether any server's state changed.
+    pub fn check_health(&mut self) -> bool {
+        let unhealthy: Vec<String> = self
+            .clients
+            .iter()
+            .filter(|(_, c)| c.has_exited() || c.is_unresponsive(UNRESPONSIVE_AFTER))
+            .map(|(server, _)| server.clone())
+            .collect();
+        for server in &unhealthy {
+            self.schedule_restart(server);
+        }
+        !unhealthy.is_empty()
+    }
+
+    /// Restart the servers whose backoff has elapsed. Returns whether any
+    /// server's state changed.
+    pub async fn poll_restarts(&mut self) -> bool {
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .servers
+            .iter()
+            .filter(|(_, h)| h.retry_at.is_some_and(|at| at <= now))
+            .map(|(server, _)| server.clone())
+            .collect();
+        for server in &due {
+            if let Err(e) = self.start_server(server).await {
+                tracing::warn!("Restarting LSP server '{}' failed: {}", server, e);
+                self.schedule_restart(server);
+            }
+        }
+        !due.is_empty()
+    }
+
+    /// Restart `server` now, even if it was given up on, with a fresh
+    /// backoff.
+    pub async fn restart_server(&mut self, server: &str) {
+        if let Some(client) = self.clients.remove(server) {
+            client.kill();
+        }
+        let Some(health) = self.servers.get_mut(server) else {
+            return;
+        };
+        health.backoff.reset();
+        if let Err(e) = self.start_server(server).await {
+            tracing::warn!("Restarting LSP server '{}' failed: {}", server, e);
+            self.schedule_restart(server);
+        }
+    }
+
+    /// Restart every server now, including ones that were given up on.
+    pub async fn restart_all(&mut self) {
+        let servers: Vec<String> = self.servers.keys().cloned().collect();
+        for server in &servers {
+            self.restart_server(server).await;
+        }
+    }
+
+    /// Every server started so far with its state, by command.
+    pub fn statuses(&self) -> Vec<ServerStatus> {
+        let mut statuses: Vec<ServerStatus> = self
+            .servers
+            .iter()
+            .map(|(server, h)| ServerStatus {
+                server: server.clone(),
+                languages: h.config.language_ids.clone(),
+                state: h.state.clone(),
+                open_files: self
+                    .open_docs
+                    .keys()
+                    .filter(|p| {
+                        h.config
+                            .language_ids
+                            .contains(&Self::language_id_from_path(p))
+                    })
+                    .count(),
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.server.cmp(&b.server));
+        statuses
+    }
+
+    /// Every running client, e.g. to ask them all for workspace symbols.
+    pub fn clients(&self) -> impl Iterator<Item = &std::sync::Arc<LspClient>> {
+        self.clients.values()
+    }
+
     /// Get the LSP client for a given language
     pub fn client_for_language(&self, language_id: &str) -> Option<&std::sync::Arc<LspClient>> {
-        self.clients.get(language_id)
+        self.clients.get(self.server_for_language(language_id)?)
     }
 
     /// Get the LSP client for a file based on its extension
     pub fn client_for_file(&self, path: &Path) -> Option<&std::sync::Arc<LspClient>> {
-        let lang_id = Self::language_id_from_path(path);
-        self.clients.get(&lang_id)
+        self.client_for_language(&Self::language_id_from_path(path))
     }
 
     /// Same as `client_for_file` but path-based (alias for app.rs compatibility)
@@ -132,30 +360,72 @@ impl LspManager {
         self.client_for_file(path)
     }
 
-    /// Notify all relevant servers that a file was opened
-    pub fn did_open(&self, path: &Path, text: &str) {
+    /// Notify the file's server that it was opened
+    pub fn did_open(&mut self, path: &Path, text: &str) {
+        self.open_docs.insert(
+            path.to_path_buf(),
+            OpenDoc {
+                version: 0,
+                text: text.to_string(),
+            },
+        );
         let language_id = Self::language_id_from_path(path);
-        if let Some(client) = self.clients.get(&language_id) {
+        if let Some(client) = self.client_for_language(&language_id) {
             if let Err(e) = client.did_open(path, &language_id, text) {
                 tracing::warn!("LSP didOpen failed: {}", e);
             }
         }
     }
 
-    /// Notify all relevant servers that a file changed
-    pub fn did_change(&self, path: &Path, version: i32, text: &str) {
-        let language_id = Self::language_id_from_path(path);
-        if let Some(client) = self.clients.get(&language_id) {
-            if let Err(e) = client.did_change(path, version, text) {
-                tracing::warn!("LSP didChange failed: {}", e);
-            }
+    /// Notify the file's server that it changed: just the changed spans for
+    /// servers with incremental sync, the whole text for the rest. The spans
+    /// come from `edits` when they apply to the text the server last saw, and
+    /// from comparing the two texts otherwise. While the server is restarting
+    /// only the latest text is kept, and sent when the server reopens the file.
+    pub fn did_change(
+        &mut self,
+        path: &Path,
+        version: i32,
+        text: &str,
+        edits: Option<AppliedEdits>,
+    ) {
+        let doc = self
+            .open_docs
+            .entry(path.to_path_buf())
+            .or_insert_with(|| OpenDoc {
+                version,
+                text: String::new(),
+            });
+        if version < doc.version {
+            return;
+        }
+        doc.version = version;
+        // What the server last saw, to diff against.
+        let old = std::mem::replace(&mut doc.text, text.to_string());
+        let Some(client) = self.client_for_file(path) else {
+            return;
+        };
+        let result = match client.sync_kind() {
+            TextDocumentSyncKind::NONE => Ok(()),
+            TextDocumentSyncKind::INCREMENTAL => match edits {
+                Some(edits) if edits.base_len == old.len() && !edits.changes.is_empty() => {
+                    client.did_change_ranges(path, version, edits.changes)
+                }
+                _ => match incremental_change(&old, text) {
+                    Some(change) => client.did_change_ranges(path, version, vec![change]),
+                    None => Ok(()),
+                },
+            },
+            _ => client.did_change(path, version, text),
+        };
+        if let Err(e) = result {
+            tracing::warn!("LSP didChange failed: {}", e);
         }
     }
 
-    /// Notify all relevant servers that a file was saved (textDocument/didSave)
+    /// Notify the file's server that it was saved (textDocument/didSave)
     pub fn did_save(&self, path: &Path) {
-        let language_id = Self::language_id_from_path(path);
-        if let Some(client) = self.clients.get(&language_id) {
+        if let Some(client) = self.client_for_file(path) {
             if let Err(e) = client.did_save(path, None) {
                 tracing::warn!("LSP didSave failed: {}", e);
             }
@@ -164,8 +434,9 @@ impl LspManager {
 
     /// Shutdown all language servers
     pub async fn shutdown_all(&mut self) {
-        for (lang, arc_client) in self.clients.drain() {
-            tracing::info!("Shutting down LSP server for {}", lang);
+        self.servers.clear();
+        for (server, arc_client) in self.clients.drain() {
+            tracing::info!("Shutting down LSP server '{}'", server);
             // Try to unwrap the Arc to get exclusive access for shutdown
             match std::sync::Arc::try_unwrap(arc_client) {
                 Ok(mut client) => {
@@ -174,8 +445,8 @@ impl LspManager {
                 Err(_) => {
                     // Other references exist; best-effort: send exit notification
                     tracing::warn!(
-                        "Could not shutdown LSP client for '{}': Arc still shared",
-                        lang
+                        "Could not shutdown LSP client '{}': Arc still shared",
+                        server
                     );
                 }
             }
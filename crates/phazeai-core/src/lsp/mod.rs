@@ -1,5 +1,13 @@
 pub mod client;
+pub mod diagnostics;
+pub mod health;
+pub mod lens;
 pub mod manager;
+pub mod sync;
 
 pub use client::{LspClient, LspEvent};
+pub use diagnostics::{uri_to_path, DiagnosticCounts, DiagnosticStore};
+pub use health::{Backoff, ServerState, ServerStatus};
+pub use lens::{Lens, LensAction};
 pub use manager::LspManager;
+pub use sync::{byte_column, incremental_change, sync_kind, utf16_column, AppliedEdits};
@@ -0,0 +1,118 @@
+//! Document sync: what to send in `textDocument/didChange`.
+//!
+//! Servers that accept incremental sync are sent only the spans that changed
+//! since the text they last saw rather than the whole document on every edit.
+//! The editor describes its own edits as [`AppliedEdits`]; when it can't, or
+//! they don't fit the text the server has, the span is found by trimming the
+//! common prefix and suffix of the old and new text.
+//! Positions are in UTF-16 code units, the protocol's default encoding;
+//! [`utf16_column`] and [`byte_column`] convert columns between those and
+//! the byte offsets the editor works in.
+
+use lsp_types::{
+    Position, Range, ServerCapabilities, TextDocumentContentChangeEvent,
+    TextDocumentSyncCapability, TextDocumentSyncKind,
+};
+
+/// Edits as the editor applied them, in order, with positions in UTF-16 code
+/// units, and the length in bytes of the text the first one applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedEdits {
+    pub base_len: usize,
+    pub changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+impl AppliedEdits {
+    /// These edits followed by `next`, which must apply to their result.
+    pub fn then(mut self, next: AppliedEdits) -> Self {
+        self.changes.extend(next.changes);
+        self
+    }
+}
+
+/// How the server wants document changes, per its capabilities. Servers
+/// that don't say get the whole text.
+pub fn sync_kind(capabilities: &ServerCapabilities) -> TextDocumentSyncKind {
+    match &capabilities.text_document_sync {
+        Some(TextDocumentSyncCapability::Kind(kind)) => *kind,
+        Some(TextDocumentSyncCapability::Options(options)) => {
+            options.change.unwrap_or(TextDocumentSyncKind::FULL)
+        }
+        None => TextDocumentSyncKind::FULL,
+    }
+}
+
+/// The single change turning `old` into `new`, or `None` when they are the
+/// same.
+pub fn incremental_change(old: &str, new: &str) -> Option<TextDocumentContentChangeEvent> {
+    if old == new {
+        return None;
+    }
+    let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+
+    let mut prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    // Don't split a CRLF: the position between its two bytes isn't one.
+    if prefix > 0 && old_bytes[prefix - 1] == b'\r' && old_bytes.get(prefix) == Some(&b'\n') {
+        prefix -= 1;
+    }
+
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old_bytes
+        .iter()
+        .rev()
+        .zip(new_bytes.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    let old_end = old.len() - suffix;
+    if old_end > 0 && old_bytes[old_end - 1] == b'\r' && old_bytes.get(old_end) == Some(&b'\n') {
+        suffix -= 1;
+    }
+
+    let start = position_at(old, prefix);
+    let end = position_at(old, old.len() - suffix);
+    Some(TextDocumentContentChangeEvent {
+        range: Some(Range { start, end }),
+        range_length: None,
+        text: new[prefix..new.len() - suffix].to_string(),
+    })
+}
+
+/// The position of byte `offset` in `text`, with the column in UTF-16 code
+/// units.
+fn position_at(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: utf16_column(&before[line_start..], offset - line_start),
+    }
+}
+
+/// The UTF-16 column of byte `byte` in `line`.
+pub fn utf16_column(line: &str, byte: usize) -> u32 {
+    line[..byte.min(line.len())].encode_utf16().count() as u32
+}
+
+/// The byte offset in `line` of UTF-16 column `character`. A column inside a
+/// surrogate pair lands after the character; one past the end, at the end.
+pub fn byte_column(line: &str, character: u32) -> usize {
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= character as usize {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
@@ -0,0 +1,363 @@
+//! Memory accounting for what the IDE keeps around: open and recently closed
+//! documents, syntax highlighting state, the file index and cached blame.
+//!
+//! Each [`Subsystem`] reports what it holds to a [`MemoryLedger`]; [`global`]
+//! is the one the MEMORY tab shows. Each subsystem lets go of what it used
+//! least recently once its budget from `[memory]` in the config is exceeded,
+//! most of them keeping it in a [`BudgetLru`]. Sizes are estimates — the
+//! bytes of text and strings held, not allocator overhead.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+static GLOBAL: LazyLock<MemoryLedger> = LazyLock::new(MemoryLedger::default);
+
+/// The ledger of the IDE's process.
+pub fn global() -> &'static MemoryLedger {
+    &GLOBAL
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Subsystem {
+    /// The text of open tabs, and of closed ones kept for reopening.
+    Documents,
+    /// Parser checkpoints of the open tabs' syntax highlighting.
+    Highlighting,
+    /// The workspace's file list, for the file picker.
+    FileIndex,
+    /// `git blame` of the files it was asked for.
+    Blame,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 4] = [
+        Self::Documents,
+        Self::Highlighting,
+        Self::FileIndex,
+        Self::Blame,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Documents => "Documents",
+            Self::Highlighting => "Syntax highlighting",
+            Self::FileIndex => "File index",
+            Self::Blame => "Git blame",
+        }
+    }
+
+    /// What happens once it's over budget.
+    pub fn policy(self) -> &'static str {
+        match self {
+            Self::Documents => "closed tabs evicted, least recently used first",
+            Self::Highlighting => "least recently drawn tabs reparsed when shown",
+            Self::FileIndex => "least recently changed folders left out of the picker",
+            Self::Blame => "files evicted, least recently used first",
+        }
+    }
+}
+
+/// Budgets per subsystem, in megabytes.
+///
+/// ```toml
+/// [memory]
+/// documents_mb = 128
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryBudgets {
+    /// Open tabs always stay; this bounds the closed ones kept as well.
+    pub documents_mb: u64,
+    /// Checkpoints of the tab being drawn always stay.
+    pub highlighting_mb: u64,
+    /// Files directly in the workspace root always stay.
+    pub file_index_mb: u64,
+    pub blame_mb: u64,
+}
+
+impl Default for MemoryBudgets {
+    fn default() -> Self {
+        Self {
+            documents_mb: 256,
+            highlighting_mb: 64,
+            file_index_mb: 64,
+            blame_mb: 32,
+        }
+    }
+}
+
+impl MemoryBudgets {
+    /// The budget of `subsystem` in bytes.
+    pub fn bytes(&self, subsystem: Subsystem) -> usize {
+        let mb = match subsystem {
+            Subsystem::Documents => self.documents_mb,
+            Subsystem::Highlighting => self.highlighting_mb,
+            Subsystem::FileIndex => self.file_index_mb,
+            Subsystem::Blame => self.blame_mb,
+        };
+        (mb as usize).saturating_mul(1024 * 1024)
+    }
+}
+
+/// What a subsystem holds, as last reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub bytes: usize,
+    pub entries: usize,
+    /// Entries evicted since launch.
+    pub evicted: u64,
+}
+
+/// One row of the MEMORY tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageRow {
+    pub subsystem: Subsystem,
+    pub usage: Usage,
+    pub budget: usize,
+}
+
+impl UsageRow {
+    pub fn over_budget(&self) -> bool {
+        self.usage.bytes > self.budget
+    }
+
+    /// How full the budget is, 0.0 to 1.0 and past it when over.
+    pub fn fill(&self) -> f64 {
+        if self.budget == 0 {
+            return if self.usage.bytes == 0 { 0.0 } else { 1.0 };
+        }
+        self.usage.bytes as f64 / self.budget as f64
+    }
+}
+
+/// What each subsystem holds and may hold.
+#[derive(Debug, Default)]
+pub struct MemoryLedger {
+    usage: Mutex<HashMap<Subsystem, Usage>>,
+    budgets: Mutex<MemoryBudgets>,
+}
+
+impl MemoryLedger {
+    pub fn set_budgets(&self, budgets: MemoryBudgets) {
+        if let Ok(mut b) = self.budgets.lock() {
+            *b = budgets;
+        }
+    }
+
+    /// The budget of `subsystem` in bytes.
+    pub fn budget(&self, subsystem: Subsystem) -> usize {
+        self.budgets
+            .lock()
+            .map(|b| b.bytes(subsystem))
+            .unwrap_or_else(|_| MemoryBudgets::default().bytes(subsystem))
+    }
+
+    /// Note that `subsystem` now holds `bytes` in `entries`.
+    pub fn report(&self, subsystem: Subsystem, bytes: usize, entries: usize) {
+        if let Ok(mut usage) = self.usage.lock() {
+            let usage = usage.entry(subsystem).or_default();
+            usage.bytes = bytes;
+            usage.entries = entries;
+        }
+    }
+
+    /// Note that `subsystem` took on (or, negative, let go of) `bytes` in
+    /// `entries`.
+    pub fn adjust(&self, subsystem: Subsystem, bytes: isize, entries: isize) {
+        if let Ok(mut usage) = self.usage.lock() {
+            let usage = usage.entry(subsystem).or_default();
+            usage.bytes = usage.bytes.saturating_add_signed(bytes);
+            usage.entries = usage.entries.saturating_add_signed(entries);
+        }
+    }
+
+    pub fn evicted(&self, subsystem: Subsystem, entries: usize) {
+        if let Ok(mut usage) = self.usage.lock() {
+            usage.entry(subsystem).or_default().evicted += entries as u64;
+        }
+    }
+
+    pub fn usage(&self, subsystem: Subsystem) -> Usage {
+        self.usage
+            .lock()
+            .ok()
+            .and_then(|u| u.get(&subsystem).copied())
+            .unwrap_or_default()
+    }
+
+    /// Every subsystem with its usage and budget.
+    pub fn rows(&self) -> Vec<UsageRow> {
+        Subsystem::ALL
+            .iter()
+            .map(|&subsystem| UsageRow {
+                subsystem,
+                usage: self.usage(subsystem),
+                budget: self.budget(subsystem),
+            })
+            .collect()
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.usage
+            .lock()
+            .map(|u| u.values().map(|u| u.bytes).sum())
+            .unwrap_or(0)
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    bytes: usize,
+    used: u64,
+}
+
+/// A map whose entries have a size, evicting the least recently used ones
+/// to stay within a budget.
+pub struct BudgetLru<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    bytes: usize,
+    clock: u64,
+}
+
+impl<K, V> Default for BudgetLru<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            bytes: 0,
+            clock: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V> BudgetLru<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Add or replace `key`, as the most recently used; returns the value it
+    /// replaced.
+    pub fn insert(&mut self, key: K, value: V, bytes: usize) -> Option<V> {
+        let used = self.tick();
+        self.bytes += bytes;
+        let old = self.entries.insert(key, Entry { value, bytes, used })?;
+        self.bytes -= old.bytes;
+        Some(old.value)
+    }
+
+    /// The value of `key`, marking it used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let used = self.tick();
+        let entry = self.entries.get_mut(key)?;
+        entry.used = used;
+        Some(&entry.value)
+    }
+
+    /// The value of `key`, leaving it as recently used as it was.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|e| &e.value)
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.contains_key(key)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.entries.remove(key)?;
+        self.bytes -= entry.bytes;
+        Some(entry.value)
+    }
+
+    /// Evict the least recently used entries until at most `budget` bytes
+    /// are held, returning them oldest first.
+    pub fn evict_to(&mut self, budget: usize) -> Vec<(K, V)> {
+        if self.bytes <= budget {
+            return Vec::new();
+        }
+        let mut by_age: Vec<(u64, K)> = self
+            .entries
+            .iter()
+            .map(|(k, e)| (e.used, k.clone()))
+            .collect();
+        by_age.sort_unstable_by_key(|&(used, _)| used);
+        let mut evicted = Vec::new();
+        for (_, key) in by_age {
+            if self.bytes <= budget {
+                break;
+            }
+            if let Some(value) = self.remove(&key) {
+                evicted.push((key, value));
+            }
+        }
+        evicted
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0;
+    }
+
+    /// Bytes held.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// `bytes` as people read it: "512 B", "3.4 KB", "118.0 MB".
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// The resident set size in a `/proc/<pid>/status` file.
+pub fn parse_vm_rss(status: &str) -> Option<usize> {
+    let line = status.lines().find_map(|l| l.strip_prefix("VmRSS:"))?;
+    let kb: usize = line.split_whitespace().next()?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// How much of the process is in RAM; Linux only.
+pub fn resident_bytes() -> Option<usize> {
+    parse_vm_rss(&std::fs::read_to_string("/proc/self/status").ok()?)
+}
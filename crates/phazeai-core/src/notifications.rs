@@ -0,0 +1,209 @@
+//! Notification center: the history behind the IDE's toasts.
+//!
+//! Every message the IDE shows is recorded as a [`Notification`] with a
+//! [`Severity`]. Progress notifications follow a long operation — indexing, a
+//! build, a model pull, a cloud sync — and stay on screen until it finishes.
+//! The center keeps the latest [`MAX_HISTORY`] notifications and counts the
+//! unread ones for the status-bar bell.
+
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+/// Notifications kept in the history; older ones are dropped.
+pub const MAX_HISTORY: usize = 200;
+/// Toasts stacked on screen at once.
+pub const MAX_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Guess the severity of a message shown without one, from its wording.
+    pub fn of_message(message: &str) -> Self {
+        let lower = message.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| lower.contains(w));
+        if has(&["error", "failed", "could not", "cannot", "unable to"]) {
+            Severity::Error
+        } else if has(&["warning", "not found", "no changes"]) {
+            Severity::Warning
+        } else if has(&["saved", "finished", "succeeded", "complete", "ready"]) {
+            Severity::Success
+        } else {
+            Severity::Info
+        }
+    }
+}
+
+/// How far a long operation has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub done: u64,
+    /// `None` when the amount of work isn't known up front.
+    pub total: Option<u64>,
+}
+
+impl Progress {
+    /// Completed share of the work, from 0.0 to 1.0, when the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.done as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u64,
+    pub severity: Severity,
+    pub message: String,
+    /// `Some` while the operation runs.
+    pub progress: Option<Progress>,
+    pub time: SystemTime,
+    pub read: bool,
+    /// Still shown as a toast.
+    pub visible: bool,
+}
+
+impl Notification {
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NotificationCenter {
+    items: VecDeque<Notification>,
+    next_id: u64,
+}
+
+impl NotificationCenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, severity: Severity, message: String, progress: Option<Progress>) -> u64 {
+        self.next_id += 1;
+        self.items.push_back(Notification {
+            id: self.next_id,
+            severity,
+            message,
+            progress,
+            time: SystemTime::now(),
+            read: false,
+            visible: true,
+        });
+        // Drop the oldest notification that has finished; running ones stay
+        // so their operation can still finish them.
+        if self.items.len() > MAX_HISTORY {
+            if let Some(i) = self.items.iter().position(|n| !n.is_running()) {
+                self.items.remove(i);
+            }
+        }
+        self.next_id
+    }
+
+    /// Record a message. Returns its id.
+    pub fn notify(&mut self, severity: Severity, message: impl Into<String>) -> u64 {
+        self.push(severity, message.into(), None)
+    }
+
+    /// Record the start of a long operation; it stays on screen until
+    /// [`finish`](Self::finish) is called with the returned id.
+    pub fn start_progress(&mut self, message: impl Into<String>, total: Option<u64>) -> u64 {
+        self.push(
+            Severity::Info,
+            message.into(),
+            Some(Progress { done: 0, total }),
+        )
+    }
+
+    /// Move a running operation along, optionally with a new message.
+    pub fn update_progress(&mut self, id: u64, progress: Progress, message: Option<&str>) {
+        if let Some(n) = self.get_mut(id).filter(|n| n.is_running()) {
+            n.progress = Some(progress);
+            if let Some(message) = message {
+                n.message = message.to_string();
+            }
+        }
+    }
+
+    /// End a running operation with its outcome. The notification is unread
+    /// again so the bell shows the result.
+    pub fn finish(&mut self, id: u64, severity: Severity, message: impl Into<String>) {
+        let message = message.into();
+        match self.get_mut(id) {
+            Some(n) => {
+                n.progress = None;
+                n.severity = severity;
+                n.message = message;
+                n.time = SystemTime::now();
+                n.read = false;
+                n.visible = true;
+            }
+            None => {
+                self.notify(severity, message);
+            }
+        }
+    }
+
+    /// Take a notification off screen; it stays in the history.
+    pub fn dismiss(&mut self, id: u64) {
+        if let Some(n) = self.get_mut(id) {
+            n.visible = false;
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Notification> {
+        self.items.iter().find(|n| n.id == id)
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut Notification> {
+        self.items.iter_mut().find(|n| n.id == id)
+    }
+
+    /// The notifications shown as toasts, newest first, at most [`MAX_TOASTS`].
+    pub fn toasts(&self) -> Vec<Notification> {
+        self.items
+            .iter()
+            .rev()
+            .filter(|n| n.visible)
+            .take(MAX_TOASTS)
+            .cloned()
+            .collect()
+    }
+
+    /// Every notification kept, newest first.
+    pub fn history(&self) -> impl Iterator<Item = &Notification> {
+        self.items.iter().rev()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn unread(&self) -> usize {
+        self.items.iter().filter(|n| !n.read).count()
+    }
+
+    pub fn mark_all_read(&mut self) {
+        for n in &mut self.items {
+            n.read = true;
+        }
+    }
+
+    /// Empty the history, keeping operations that are still running.
+    pub fn clear(&mut self) {
+        self.items.retain(|n| n.is_running());
+    }
+}
@@ -0,0 +1,459 @@
+//! Output panel channels.
+//!
+//! [`OutputLog`] keeps one ring buffer of lines per named channel — Build,
+//! Run, LSP, Agent, Extensions, Sidecar, or any other name a producer writes
+//! to.
+//! SGR escape codes are parsed into [`StyledSpan`]s as lines arrive (a style
+//! carries over to the next line, as it does in a terminal) and every other
+//! escape sequence is dropped. [`find_links`] finds the file locations and
+//! web addresses the terminal and Output panel make clickable.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+pub const BUILD: &str = "Build";
+pub const RUN: &str = "Run";
+pub const LSP: &str = "LSP";
+pub const AGENT: &str = "Agent";
+pub const EXTENSIONS: &str = "Extensions";
+pub const SIDECAR: &str = "Sidecar";
+
+/// Lines a channel keeps before dropping the oldest.
+pub const MAX_LINES: usize = 10_000;
+
+/// A foreground color set by an SGR sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    /// xterm 256-color index; 0–7 are the basic colors, 8–15 the bright ones.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Text attributes in effect at a point in the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnsiStyle {
+    pub fg: Option<AnsiColor>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl AnsiStyle {
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply the parameters of one `ESC [ … m` sequence. Background colors
+    /// are skipped over; the panel draws on its own background.
+    fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u32> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let byte = |i: usize| codes.get(i).map(|&c| c.min(255) as u8);
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                c @ 30..=37 => self.fg = Some(AnsiColor::Indexed((c - 30) as u8)),
+                c @ 90..=97 => self.fg = Some(AnsiColor::Indexed((c - 90 + 8) as u8)),
+                39 => self.fg = None,
+                c @ (38 | 48) => {
+                    let color = match codes.get(i + 1) {
+                        Some(5) => {
+                            let color = byte(i + 2).map(AnsiColor::Indexed);
+                            i += 2;
+                            color
+                        }
+                        Some(2) => {
+                            let color = match (byte(i + 2), byte(i + 3), byte(i + 4)) {
+                                (Some(r), Some(g), Some(b)) => Some(AnsiColor::Rgb(r, g, b)),
+                                _ => None,
+                            };
+                            i += 4;
+                            color
+                        }
+                        _ => None,
+                    };
+                    if c == 38 {
+                        self.fg = color;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+/// A run of styled text; `range` is a byte range into [`OutputLine::text`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub range: Range<usize>,
+    pub style: AnsiStyle,
+}
+
+/// One line of output with escape codes removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputLine {
+    /// Unique within its channel, so views can key rows across trimming.
+    pub id: u64,
+    pub text: String,
+    /// Styled runs, in order; text outside them is plain.
+    pub spans: Vec<StyledSpan>,
+}
+
+/// Strip escape sequences from `raw`, returning the visible text and the
+/// styled runs in it. `style` is the style in effect before the line and is
+/// left as the style in effect after it. A carriage return starts the line
+/// over, the way progress bars redraw themselves.
+pub fn parse_ansi(raw: &str, style: &mut AnsiStyle) -> (String, Vec<StyledSpan>) {
+    let mut text = String::with_capacity(raw.len());
+    let mut spans: Vec<StyledSpan> = Vec::new();
+    let mut run_start = 0;
+    let mut run_style = *style;
+
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                match chars.next() {
+                    Some('[') => {
+                        let mut params = String::new();
+                        for c in chars.by_ref() {
+                            if ('@'..='~').contains(&c) {
+                                if c == 'm' {
+                                    style.apply_sgr(&params);
+                                }
+                                break;
+                            }
+                            params.push(c);
+                        }
+                    }
+                    // OSC: ends at BEL or ESC \.
+                    Some(']') => {
+                        while let Some(c) = chars.next() {
+                            if c == '\x07' {
+                                break;
+                            }
+                            if c == '\x1b' {
+                                chars.next();
+                                break;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            '\r' => {
+                text.clear();
+                spans.clear();
+                run_start = 0;
+                run_style = *style;
+                continue;
+            }
+            '\t' => {}
+            c if c.is_control() => continue,
+            _ => {}
+        }
+        if *style != run_style {
+            push_span(&mut spans, run_start..text.len(), run_style);
+            run_start = text.len();
+            run_style = *style;
+        }
+        text.push(c);
+    }
+    push_span(&mut spans, run_start..text.len(), run_style);
+    (text, spans)
+}
+
+fn push_span(spans: &mut Vec<StyledSpan>, range: Range<usize>, style: AnsiStyle) {
+    if !range.is_empty() && !style.is_plain() {
+        spans.push(StyledSpan { range, style });
+    }
+}
+
+/// A named channel's lines.
+#[derive(Debug, Clone)]
+pub struct OutputChannel {
+    name: String,
+    lines: VecDeque<OutputLine>,
+    /// Style left open by the last line.
+    style: AnsiStyle,
+    next_id: u64,
+    dropped: u64,
+}
+
+impl OutputChannel {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            lines: VecDeque::new(),
+            style: AnsiStyle::default(),
+            next_id: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Lines, oldest first.
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &OutputLine> + ExactSizeIterator {
+        self.lines.iter()
+    }
+
+    /// Lines dropped from the front since the channel was last cleared.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Every output channel, in the order they were first written to.
+#[derive(Debug, Clone)]
+pub struct OutputLog {
+    channels: Vec<OutputChannel>,
+    capacity: usize,
+}
+
+impl Default for OutputLog {
+    fn default() -> Self {
+        Self::with_capacity(MAX_LINES)
+    }
+}
+
+impl OutputLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A log whose channels each keep at most `capacity` lines. Build, LSP
+    /// and Agent exist from the start so they can be picked before anything
+    /// has been written to them.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            channels: [BUILD, LSP, AGENT]
+                .into_iter()
+                .map(OutputChannel::new)
+                .collect(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn channel_names(&self) -> Vec<String> {
+        self.channels.iter().map(|c| c.name.clone()).collect()
+    }
+
+    pub fn channel(&self, name: &str) -> Option<&OutputChannel> {
+        self.channels.iter().find(|c| c.name == name)
+    }
+
+    fn channel_mut(&mut self, name: &str) -> &mut OutputChannel {
+        let i = match self.channels.iter().position(|c| c.name == name) {
+            Some(i) => i,
+            None => {
+                self.channels.push(OutputChannel::new(name));
+                self.channels.len() - 1
+            }
+        };
+        &mut self.channels[i]
+    }
+
+    /// Append each line of `text` to `channel`, creating the channel if it
+    /// doesn't exist yet and dropping its oldest lines past the capacity.
+    pub fn append(&mut self, channel: &str, text: &str) {
+        let capacity = self.capacity;
+        let channel = self.channel_mut(channel);
+        for raw in text.lines() {
+            let (text, spans) = parse_ansi(raw, &mut channel.style);
+            channel.lines.push_back(OutputLine {
+                id: channel.next_id,
+                text,
+                spans,
+            });
+            channel.next_id += 1;
+        }
+        while channel.lines.len() > capacity {
+            channel.lines.pop_front();
+            channel.dropped += 1;
+        }
+    }
+
+    /// Empty `channel`, keeping it in the list.
+    pub fn clear(&mut self, channel: &str) {
+        if let Some(channel) = self.channels.iter_mut().find(|c| c.name == channel) {
+            channel.lines.clear();
+            channel.style = AnsiStyle::default();
+            channel.dropped = 0;
+        }
+    }
+}
+
+/// A reference to a file location in a line of output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLink {
+    /// Byte range of the whole reference in the line.
+    pub range: Range<usize>,
+    pub path: PathBuf,
+    /// 1-based, as printed.
+    pub line: u32,
+    pub column: Option<u32>,
+}
+
+impl FileLink {
+    /// The file the link points at: the path itself when absolute, otherwise
+    /// relative to `root`. `None` if no such file exists.
+    pub fn resolve(&self, root: &Path) -> Option<PathBuf> {
+        let path = if self.path.is_absolute() {
+            self.path.clone()
+        } else {
+            root.join(&self.path)
+        };
+        path.is_file().then_some(path)
+    }
+}
+
+/// A file location or a web address found in a line of output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    File(FileLink),
+    Url { range: Range<usize>, url: String },
+}
+
+impl Link {
+    /// Byte range of the link in the line.
+    pub fn range(&self) -> &Range<usize> {
+        match self {
+            Link::File(file) => &file.range,
+            Link::Url { range, .. } => range,
+        }
+    }
+}
+
+/// Path with an extension: `src/main.rs`, `./a/b.go`, `C:\x\y.cs`.
+const PATH: &str = r"(?P<path>(?:\b[A-Za-z]:)?[\w.~/\\-]*\.[A-Za-z]\w*)";
+
+/// Compiler and tool formats for a file location, each with `path`, `line`
+/// and an optional `col` group.
+static FILE_LINKS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        // rustc, gcc, clang, go, eslint, tsc --pretty: `path:line[:col]`
+        format!(r"{PATH}:(?P<line>\d+)(?::(?P<col>\d+))?"),
+        // MSVC, C#, tsc: `path(line[,col])`
+        format!(r"{PATH}\((?P<line>\d+)(?:,\s*(?P<col>\d+))?\)"),
+        // Python tracebacks: `File "path", line N`
+        r#"File "(?P<path>[^"]+)", line (?P<line>\d+)"#.to_string(),
+    ]
+    .iter()
+    .map(|re| Regex::new(re).expect("valid regex"))
+    .collect()
+});
+
+static URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\b(?:https?|file)://[^\s<>"'`()\[\]{}]+"#).expect("valid regex")
+});
+
+/// The file locations in `text`, in order, in any of the formats compilers
+/// print them: `src/main.rs:10:5`, `Program.cs(10,5)`, or
+/// `File "app.py", line 10`. Hosts in URLs (`http://host.com:8080`) aren't
+/// locations.
+pub fn file_links(text: &str) -> Vec<FileLink> {
+    let mut links: Vec<FileLink> = FILE_LINKS
+        .iter()
+        .flat_map(|re| re.captures_iter(text))
+        .filter_map(|caps| {
+            let whole = caps.get(0)?;
+            if text[..whole.start()].ends_with(':') {
+                return None;
+            }
+            Some(FileLink {
+                range: whole.range(),
+                path: PathBuf::from(caps.name("path")?.as_str()),
+                line: caps.name("line")?.as_str().parse().ok()?,
+                column: caps.name("col").and_then(|c| c.as_str().parse().ok()),
+            })
+        })
+        .collect();
+    links.sort_by_key(|l| l.range.start);
+    let mut end = 0;
+    links.retain(|l| {
+        let keep = l.range.start >= end;
+        if keep {
+            end = l.range.end;
+        }
+        keep
+    });
+    links
+}
+
+/// Every link in `text`, in order: web addresses, and the file locations
+/// [`file_links`] finds outside them. A `file://` URL is a link to the
+/// file's first line.
+pub fn find_links(text: &str) -> Vec<Link> {
+    let mut links: Vec<Link> = URL
+        .find_iter(text)
+        .map(|m| {
+            let url = m.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            let range = m.start()..m.start() + url.len();
+            match url.strip_prefix("file://") {
+                Some(path) => Link::File(FileLink {
+                    range,
+                    path: PathBuf::from(path),
+                    line: 1,
+                    column: None,
+                }),
+                None => Link::Url {
+                    range,
+                    url: url.to_string(),
+                },
+            }
+        })
+        .collect();
+    let urls: Vec<Range<usize>> = links.iter().map(|l| l.range().clone()).collect();
+    links.extend(
+        file_links(text)
+            .into_iter()
+            .filter(|f| {
+                !urls
+                    .iter()
+                    .any(|u| f.range.start < u.end && u.start < f.range.end)
+            })
+            .map(Link::File),
+    );
+    links.sort_by_key(|l| l.range().start);
+    links
+}
+
+/// The command that builds the project at `root`, picked from the manifest
+/// files at its top level.
+pub fn build_command(root: &Path) -> Option<Vec<String>> {
+    let command: &[&str] = if root.join("Cargo.toml").is_file() {
+        &["cargo", "build", "--color=always"]
+    } else if root.join("package.json").is_file() {
+        &["npm", "run", "build"]
+    } else if root.join("go.mod").is_file() {
+        &["go", "build", "./..."]
+    } else if root.join("Makefile").is_file() {
+        &["make"]
+    } else {
+        return None;
+    };
+    Some(command.iter().map(|s| s.to_string()).collect())
+}
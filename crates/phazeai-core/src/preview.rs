@@ -0,0 +1,203 @@
+//! Previewing a frontend dev server.
+//!
+//! The IDE's PREVIEW tab opens the page a dev server serves on localhost in
+//! a webview window sized to a [`DevicePreset`], reloads it when a file the
+//! page is built from is saved ([`reloads_preview`]), and captures it with a
+//! headless Chrome at the same size ([`capture`]) so the screenshot can go to
+//! a vision model along with a question about the UI.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::PhazeError;
+
+/// A viewport size to preview at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DevicePreset {
+    pub name: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub const DEVICE_PRESETS: &[DevicePreset] = &[
+    DevicePreset {
+        name: "Desktop",
+        width: 1440,
+        height: 900,
+    },
+    DevicePreset {
+        name: "Laptop",
+        width: 1280,
+        height: 800,
+    },
+    DevicePreset {
+        name: "Tablet",
+        width: 768,
+        height: 1024,
+    },
+    DevicePreset {
+        name: "Mobile",
+        width: 390,
+        height: 844,
+    },
+];
+
+/// Ports dev servers listen on by default: Create React App and Next.js,
+/// Vite, webpack-dev-server, Angular, Django, Flask, Astro, Parcel.
+pub const COMMON_DEV_PORTS: &[u16] = &[3000, 5173, 8080, 4200, 8000, 5000, 4321, 1234];
+
+/// How long a headless capture may take, page load included.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The URL for what the user typed: a port (`5173`), a host and port with
+/// an optional path (`localhost:3000/admin`), or a full URL.
+pub fn preview_url(target: &str) -> Option<String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return None;
+    }
+    if target.starts_with("http://") || target.starts_with("https://") {
+        return Some(target.to_string());
+    }
+    if let Ok(port) = target.trim_start_matches(':').parse::<u16>() {
+        return Some(format!("http://localhost:{port}/"));
+    }
+    let host = target.split('/').next().unwrap_or("");
+    if host.contains(':') || host == "localhost" {
+        return Some(format!("http://{target}"));
+    }
+    None
+}
+
+/// Those of `ports` something on this machine is listening on.
+pub fn listening_ports(ports: &[u16]) -> Vec<u16> {
+    ports
+        .iter()
+        .copied()
+        .filter(|port| {
+            let addr = std::net::SocketAddr::from(([127, 0, 0, 1], *port));
+            std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(150)).is_ok()
+        })
+        .collect()
+}
+
+/// Extensions of the files a page is built from.
+const PAGE_EXTENSIONS: &[&str] = &[
+    "html", "htm", "css", "scss", "sass", "less", "js", "jsx", "mjs", "ts", "tsx", "vue", "svelte",
+    "astro", "json", "svg", "md", "mdx",
+];
+
+/// Directories of build output and dependencies, which dev servers write
+/// to themselves.
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    ".git",
+    "dist",
+    "build",
+    ".next",
+    ".svelte-kit",
+];
+
+/// Whether saving `path` should reload the preview.
+pub fn reloads_preview(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    PAGE_EXTENSIONS.contains(&ext.as_str())
+        && !path.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .is_some_and(|name| IGNORED_DIRS.contains(&name))
+        })
+}
+
+/// Browsers that can take a headless screenshot, by executable name.
+const BROWSERS: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "microsoft-edge",
+    "brave-browser",
+    "chrome",
+];
+
+/// Where browsers live when they aren't on `PATH`.
+const BROWSER_PATHS: &[&str] = &[
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
+];
+
+/// A Chromium-based browser to capture with: `PHAZEAI_BROWSER` when set,
+/// else the first one found.
+pub fn find_browser() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("PHAZEAI_BROWSER") {
+        return Some(PathBuf::from(path));
+    }
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    BROWSERS
+        .iter()
+        .find_map(|name| {
+            std::env::split_paths(&path_var)
+                .map(|dir| dir.join(name))
+                .find(|candidate| candidate.is_file())
+        })
+        .or_else(|| {
+            BROWSER_PATHS
+                .iter()
+                .map(PathBuf::from)
+                .find(|candidate| candidate.is_file())
+        })
+}
+
+/// Arguments for a headless screenshot of `url` at `preset`'s size into `out`.
+pub fn capture_args(url: &str, preset: DevicePreset, out: &Path) -> Vec<String> {
+    vec![
+        "--headless=new".to_string(),
+        "--disable-gpu".to_string(),
+        "--hide-scrollbars".to_string(),
+        "--no-first-run".to_string(),
+        "--force-device-scale-factor=1".to_string(),
+        // Give scripts time to render before the shot.
+        "--virtual-time-budget=3000".to_string(),
+        format!("--window-size={},{}", preset.width, preset.height),
+        format!("--screenshot={}", out.display()),
+        url.to_string(),
+    ]
+}
+
+/// Screenshot `url` at `preset`'s size into the PNG `out`.
+pub async fn capture(url: &str, preset: DevicePreset, out: &Path) -> Result<(), PhazeError> {
+    let browser = find_browser().ok_or_else(|| {
+        PhazeError::Other(
+            "No Chrome, Chromium or Edge found to capture the preview with; \
+             install one or set PHAZEAI_BROWSER"
+                .into(),
+        )
+    })?;
+    if let Some(dir) = out.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let _ = std::fs::remove_file(out);
+    let run = tokio::process::Command::new(&browser)
+        .args(capture_args(url, preset, out))
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(CAPTURE_TIMEOUT, run)
+        .await
+        .map_err(|_| PhazeError::Timeout(format!("capturing {url}")))??;
+    if !out.is_file() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PhazeError::Other(format!(
+            "{} didn't capture {url}: {}",
+            browser.display(),
+            stderr.lines().last().unwrap_or("no output")
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,658 @@
+//! CPU profiles as flame graphs: running `cargo flamegraph`, `perf` or
+//! `py-spy` on a target, folding their samples into collapsed stacks
+//! (`main;parse;lex 42`), laying the stacks out as a flame graph, and
+//! finding the source of a hot frame to hand to the agent.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use ignore::WalkBuilder;
+use regex::Regex;
+
+use crate::analysis::{metrics, Language};
+use crate::error::PhazeError;
+use crate::progress;
+
+/// Samples per second asked of `perf` and `py-spy`; a prime, so sampling
+/// doesn't fall in step with periodic work.
+const SAMPLE_RATE: u32 = 997;
+/// Source lines of a located frame attached to a prompt.
+const MAX_SOURCE_LINES: usize = 200;
+/// Lines shown either side of a frame's line when it isn't in a function
+/// the metrics recognize.
+const CONTEXT_LINES: usize = 20;
+/// Callees listed in a prompt.
+const MAX_CALLEES: usize = 8;
+
+static RE_PY_FRAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.*) \((.+):(\d+)\)$").unwrap());
+static RE_OFFSET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\+0x[0-9a-fA-F]+$").unwrap());
+static RE_RUST_HASH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"::h[0-9a-f]{16}$").unwrap());
+
+/// The tool that samples the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Profiler {
+    /// `cargo flamegraph` with cargo target flags (`--bin app`,
+    /// `--test core_tests`, `--example demo`); Linux, as it leaves the
+    /// `perf.data` it recorded in the workspace.
+    CargoFlamegraph,
+    /// `perf record` on a command line.
+    Perf,
+    /// `py-spy record` on a Python command line.
+    PySpy,
+}
+
+impl Profiler {
+    pub const ALL: [Profiler; 3] = [Self::CargoFlamegraph, Self::Perf, Self::PySpy];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::CargoFlamegraph => "cargo flamegraph",
+            Self::Perf => "perf",
+            Self::PySpy => "py-spy",
+        }
+    }
+
+    /// What the target is for this profiler, as an example.
+    pub fn target_hint(self) -> &'static str {
+        match self {
+            Self::CargoFlamegraph => "--bin app -- args  /  --test core_tests",
+            Self::Perf => "./target/release/app args",
+            Self::PySpy => "python script.py args",
+        }
+    }
+
+    /// The shell command that profiles `target`, writing into `out`.
+    fn command(self, target: &str, out: &Path) -> String {
+        let out = out.display();
+        match self {
+            Self::CargoFlamegraph => {
+                format!("cargo flamegraph --output '{out}/flamegraph.svg' {target}")
+            }
+            Self::Perf => {
+                format!("perf record -F {SAMPLE_RATE} -g -o '{out}/perf.data' -- {target}")
+            }
+            Self::PySpy => {
+                format!("py-spy record -f raw -r {SAMPLE_RATE} -o '{out}/profile.txt' -- {target}")
+            }
+        }
+    }
+}
+
+/// A frame of the merged call tree and the samples taken in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlameNode {
+    pub name: String,
+    /// Samples with this frame on the stack.
+    pub total: u64,
+    /// Samples with this frame on top of the stack.
+    pub self_samples: u64,
+    /// Callees, by name.
+    pub children: Vec<FlameNode>,
+}
+
+impl FlameNode {
+    fn child(&mut self, name: &str) -> &mut FlameNode {
+        let index = match self.children.iter().position(|c| c.name == name) {
+            Some(i) => i,
+            None => {
+                self.children.push(FlameNode {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[index]
+    }
+
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| a.name.cmp(&b.name));
+        self.children.iter_mut().for_each(Self::sort);
+    }
+}
+
+/// A frame's box in the flame graph, root at depth 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameBox {
+    /// Child indices from the laid-out root down to this frame.
+    pub path: Vec<usize>,
+    pub depth: usize,
+    pub name: String,
+    /// Samples left of the box within the laid-out root; its width is
+    /// `total`.
+    pub start: u64,
+    pub total: u64,
+    pub self_samples: u64,
+}
+
+/// A function's samples over the whole profile, however it was called.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionStats {
+    pub name: String,
+    pub self_samples: u64,
+    /// Samples with the function anywhere on the stack, counting a
+    /// recursive stack once.
+    pub total: u64,
+}
+
+/// A sampled profile, merged into a call tree under a root frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub root: FlameNode,
+}
+
+impl Profile {
+    /// Parse collapsed stacks: one `frame;frame;frame count` line per
+    /// distinct stack, outermost frame first, as `stackcollapse-*` scripts
+    /// and `py-spy --format raw` write them. Malformed lines are skipped.
+    pub fn from_collapsed(text: &str) -> Self {
+        let mut root = FlameNode {
+            name: "all".to_string(),
+            ..Default::default()
+        };
+        for line in text.lines() {
+            let Some((stack, count)) = line.trim_end().rsplit_once(' ') else {
+                continue;
+            };
+            let Ok(count) = count.parse::<u64>() else {
+                continue;
+            };
+            root.total += count;
+            let mut node = &mut root;
+            for frame in stack.split(';').filter(|f| !f.is_empty()) {
+                node = node.child(frame);
+                node.total += count;
+            }
+            node.self_samples += count;
+        }
+        root.sort();
+        Self { root }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.root.total
+    }
+
+    /// The frame at `path` (child indices from the root).
+    pub fn node(&self, path: &[usize]) -> Option<&FlameNode> {
+        path.iter()
+            .try_fold(&self.root, |node, &i| node.children.get(i))
+    }
+
+    /// The names of the frames from the root's first callee down to the
+    /// frame at `path`.
+    pub fn stack(&self, path: &[usize]) -> Vec<&str> {
+        let mut node = &self.root;
+        let mut names = Vec::new();
+        for &i in path {
+            let Some(child) = node.children.get(i) else {
+                break;
+            };
+            names.push(child.name.as_str());
+            node = child;
+        }
+        names
+    }
+
+    /// Boxes for the frame at `focus` and its callees, skipping frames
+    /// narrower than `min_fraction` of it, which couldn't be drawn or read.
+    /// The focused frame is at depth 0 and `start` 0.
+    pub fn layout(&self, focus: &[usize], min_fraction: f64) -> Vec<FrameBox> {
+        let Some(top) = self.node(focus) else {
+            return Vec::new();
+        };
+        let min = (top.total as f64 * min_fraction).ceil().max(1.0) as u64;
+        let mut boxes = Vec::new();
+        let mut pending = vec![(top, focus.to_vec(), 0usize, 0u64)];
+        while let Some((node, path, depth, start)) = pending.pop() {
+            let mut offset = start;
+            for (i, child) in node.children.iter().enumerate() {
+                if child.total >= min {
+                    let mut child_path = path.clone();
+                    child_path.push(i);
+                    pending.push((child, child_path, depth + 1, offset));
+                }
+                offset += child.total;
+            }
+            boxes.push(FrameBox {
+                path,
+                depth,
+                name: node.name.clone(),
+                start,
+                total: node.total,
+                self_samples: node.self_samples,
+            });
+        }
+        boxes.sort_by_key(|b| (b.depth, b.start));
+        boxes
+    }
+
+    /// Every function's samples, summed over the places it was called from.
+    fn function_stats(&self) -> HashMap<&str, (u64, u64)> {
+        fn visit<'a>(
+            node: &'a FlameNode,
+            on_stack: &mut Vec<&'a str>,
+            stats: &mut HashMap<&'a str, (u64, u64)>,
+        ) {
+            let entry = stats.entry(node.name.as_str()).or_default();
+            entry.0 += node.self_samples;
+            if !on_stack.contains(&node.name.as_str()) {
+                entry.1 += node.total;
+            }
+            on_stack.push(&node.name);
+            for child in &node.children {
+                visit(child, on_stack, stats);
+            }
+            on_stack.pop();
+        }
+        let mut stats = HashMap::new();
+        let mut on_stack = Vec::new();
+        for child in &self.root.children {
+            visit(child, &mut on_stack, &mut stats);
+        }
+        stats
+    }
+
+    /// The `limit` functions with the most samples of their own.
+    pub fn hottest(&self, limit: usize) -> Vec<FunctionStats> {
+        let mut hottest: Vec<FunctionStats> = self
+            .function_stats()
+            .into_iter()
+            .filter(|(_, (own, _))| *own > 0)
+            .map(|(name, (self_samples, total))| FunctionStats {
+                name: name.to_string(),
+                self_samples,
+                total,
+            })
+            .collect();
+        hottest.sort_by(|a, b| {
+            b.self_samples
+                .cmp(&a.self_samples)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        hottest.truncate(limit);
+        hottest
+    }
+}
+
+/// Fold `perf script` output into collapsed stacks. Each sample is a header
+/// line naming the command, then one indented `address symbol+offset
+/// (module)` line per frame, innermost first; the command becomes the
+/// outermost frame, as `stackcollapse-perf.pl` does.
+pub fn collapse_perf_script(script: &str) -> String {
+    let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+    let mut frames: Vec<String> = Vec::new();
+    let mut command: Option<String> = None;
+    let mut flush = |command: &mut Option<String>, frames: &mut Vec<String>| {
+        if let Some(command) = command.take() {
+            let mut stack = command;
+            for frame in frames.iter().rev() {
+                stack.push(';');
+                stack.push_str(frame);
+            }
+            *stacks.entry(stack).or_default() += 1;
+        }
+        frames.clear();
+    };
+    for line in script.lines() {
+        if line.trim().is_empty() {
+            flush(&mut command, &mut frames);
+        } else if line.starts_with(char::is_whitespace) {
+            if command.is_some() {
+                frames.push(perf_frame(line.trim()));
+            }
+        } else if !line.starts_with('#') {
+            flush(&mut command, &mut frames);
+            command = line.split_whitespace().next().map(|c| c.replace(';', ":"));
+        }
+    }
+    flush(&mut command, &mut frames);
+    stacks
+        .into_iter()
+        .map(|(stack, count)| format!("{stack} {count}\n"))
+        .collect()
+}
+
+/// The symbol of a `perf script` frame line, without its address, offset
+/// and module.
+fn perf_frame(line: &str) -> String {
+    let line = match line.rsplit_once(" (") {
+        Some((rest, module)) if module.ends_with(')') => rest,
+        _ => line,
+    };
+    let symbol = match line.split_once(char::is_whitespace) {
+        Some((address, symbol)) if address.chars().all(|c| c.is_ascii_hexdigit()) => symbol,
+        _ => line,
+    };
+    let symbol = RE_OFFSET.replace(symbol.trim(), "");
+    if symbol.is_empty() {
+        "[unknown]".to_string()
+    } else {
+        symbol.replace(';', ":")
+    }
+}
+
+/// Read a saved profile: collapsed stacks, or `perf script` output (told
+/// apart by its indented frame lines).
+pub fn load_profile(path: &Path) -> Result<Profile, PhazeError> {
+    let text = std::fs::read_to_string(path)?;
+    let profile = if text.lines().any(|l| l.starts_with(char::is_whitespace)) {
+        Profile::from_collapsed(&collapse_perf_script(&text))
+    } else {
+        Profile::from_collapsed(&text)
+    };
+    if profile.total() == 0 {
+        return Err(PhazeError::Other(format!(
+            "{} has no samples in a format PhazeAI reads",
+            path.display()
+        )));
+    }
+    Ok(profile)
+}
+
+/// `perf script` over a recording, folded.
+fn perf_script(data: &Path) -> Result<String, PhazeError> {
+    let output = Command::new("perf")
+        .args(["script", "--no-inline", "-i"])
+        .arg(data)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| PhazeError::Other(format!("running perf script failed: {e}")))?;
+    if !output.status.success() {
+        return Err(PhazeError::Other(format!(
+            "perf script could not read {}",
+            data.display()
+        )));
+    }
+    Ok(collapse_perf_script(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Profile `target` with `profiler` from `root`, blocking until it exits.
+/// Reported on the progress bus and cancellable there, which kills the
+/// profiler.
+pub fn run_profile(root: &Path, profiler: Profiler, target: &str) -> Result<Profile, PhazeError> {
+    let out = std::env::temp_dir().join(format!("phazeai-profile-{}", std::process::id()));
+    std::fs::create_dir_all(&out)?;
+    let task = progress::global()
+        .task(format!("Profiling with {}", profiler.label()))
+        .cancellable()
+        .start();
+    task.set_message(target.to_string());
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(profiler.command(target, &out))
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| PhazeError::Other(format!("starting {} failed: {e}", profiler.label())))?;
+    // Drained as it's written, so a chatty target can't fill the pipe.
+    let stderr = child.stderr.take().map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = pipe.read_to_string(&mut text);
+            text
+        })
+    });
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if task.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            let _ = std::fs::remove_dir_all(&out);
+            return Err(PhazeError::Cancelled);
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    };
+    let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+    if !status.success() {
+        let _ = std::fs::remove_dir_all(&out);
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        let tail: Vec<&str> = tail.into_iter().rev().collect();
+        return Err(PhazeError::Other(format!(
+            "{} exited with {status}: {}",
+            profiler.label(),
+            tail.join("\n")
+        )));
+    }
+
+    task.set_message("reading samples");
+    let collapsed = match profiler {
+        Profiler::PySpy => {
+            std::fs::read_to_string(out.join("profile.txt")).map_err(PhazeError::from)
+        }
+        Profiler::Perf => perf_script(&out.join("perf.data")),
+        Profiler::CargoFlamegraph => perf_script(&root.join("perf.data")),
+    };
+    let _ = std::fs::remove_dir_all(&out);
+    let profile = Profile::from_collapsed(&collapsed?);
+    if profile.total() == 0 {
+        return Err(PhazeError::Other(
+            "the profiler recorded no samples; did the target run long enough?".to_string(),
+        ));
+    }
+    Ok(profile)
+}
+
+/// What a frame's name says about where its code is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameFunction {
+    /// The function's own name (`parse` of `app::lexer::Lexer::parse`).
+    pub name: String,
+    /// The path segments before it.
+    pub qualifiers: Vec<String>,
+    /// File and 1-based line, for profilers that record them (py-spy).
+    pub location: Option<(PathBuf, usize)>,
+}
+
+/// Split a frame name into the function and where it's from: py-spy's
+/// `name (file.py:12)`, or a demangled Rust / C++ path with its generic
+/// arguments, closure markers, hash suffix and offset removed.
+pub fn frame_function(frame: &str) -> FrameFunction {
+    if let Some(c) = RE_PY_FRAME.captures(frame) {
+        return FrameFunction {
+            name: c[1].trim().to_string(),
+            qualifiers: Vec::new(),
+            location: c[3].parse().ok().map(|line| (PathBuf::from(&c[2]), line)),
+        };
+    }
+    let frame = RE_OFFSET.replace(frame.trim(), "");
+    let frame = RE_RUST_HASH.replace(&frame, "");
+    // Generic arguments and `<T as Trait>` qualifiers.
+    let mut plain = String::new();
+    let mut depth = 0usize;
+    for c in frame.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            _ if depth == 0 => plain.push(c),
+            _ => {}
+        }
+    }
+    let plain = plain.split('(').next().unwrap_or(&plain);
+    let mut segments: Vec<String> = plain
+        .split("::")
+        .map(str::trim)
+        .filter(|s| !s.is_empty() && !s.starts_with("{{"))
+        .map(str::to_string)
+        .collect();
+    let name = segments.pop().unwrap_or_default();
+    FrameFunction {
+        name,
+        qualifiers: segments,
+        location: None,
+    }
+}
+
+/// A frame's source in the workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Relative to the workspace root.
+    pub path: PathBuf,
+    /// 1-based first and last line of `code`.
+    pub line: usize,
+    pub end_line: usize,
+    pub code: String,
+}
+
+fn language_of(path: &Path) -> Language {
+    Language::from_extension(path.extension().and_then(|e| e.to_str()).unwrap_or(""))
+}
+
+fn source_lines(path: PathBuf, code: &str, line: usize, end_line: usize) -> SourceLocation {
+    let end_line = end_line.min(line + MAX_SOURCE_LINES - 1);
+    let code = code
+        .lines()
+        .skip(line - 1)
+        .take(end_line + 1 - line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    SourceLocation {
+        path,
+        line,
+        end_line,
+        code,
+    }
+}
+
+/// Find the source of `frame` under `root`: the function around the
+/// recorded line when the profiler gives one, otherwise a function of that
+/// name, preferring files whose path matches the frame's module path.
+/// `None` for frames in dependencies, the standard library or the kernel.
+pub fn locate_frame(root: &Path, frame: &str) -> Option<SourceLocation> {
+    let function = frame_function(frame);
+    if let Some((file, line)) = &function.location {
+        let path = if file.is_absolute() {
+            file.strip_prefix(root).ok()?.to_path_buf()
+        } else {
+            file.clone()
+        };
+        let code = std::fs::read_to_string(root.join(&path)).ok()?;
+        let enclosing = metrics::measure(&code, language_of(&path))
+            .into_iter()
+            .filter(|f| f.line <= *line && *line <= f.end_line)
+            .min_by_key(|f| f.length());
+        let (start, end) = match enclosing {
+            Some(f) => (f.line, f.end_line),
+            None => (
+                line.saturating_sub(CONTEXT_LINES).max(1),
+                line + CONTEXT_LINES,
+            ),
+        };
+        return Some(source_lines(path, &code, start, end));
+    }
+    if function.name.is_empty()
+        || !function
+            .name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    let qualifiers: Vec<String> = function
+        .qualifiers
+        .iter()
+        .map(|q| q.replace('_', "-"))
+        .chain(function.qualifiers.iter().cloned())
+        .collect();
+
+    let walker = WalkBuilder::new(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some("node_modules" | "target")))
+        .build();
+    let mut best: Option<(usize, SourceLocation)> = None;
+    for entry in walker.flatten() {
+        let path = entry.path();
+        let language = language_of(path);
+        if language == Language::Other {
+            continue;
+        }
+        let Ok(code) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        if !code.contains(&function.name) {
+            continue;
+        }
+        let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        // Module path segments that name a directory or the file.
+        let score = relative
+            .iter()
+            .filter_map(|c| c.to_str())
+            .map(|c| c.trim_end_matches(".rs"))
+            .filter(|c| qualifiers.iter().any(|q| q == c))
+            .count();
+        if best.as_ref().is_some_and(|(s, _)| *s >= score) {
+            continue;
+        }
+        if let Some(f) = metrics::measure(&code, language)
+            .into_iter()
+            .find(|f| f.name == function.name)
+        {
+            best = Some((score, source_lines(relative, &code, f.line, f.end_line)));
+        }
+    }
+    best.map(|(_, location)| location)
+}
+
+/// A prompt asking the agent to optimize the frame at `path` of `profile`,
+/// with its share of the samples, how it was reached, its hottest callees
+/// and its source when found.
+pub fn optimize_prompt(
+    profile: &Profile,
+    path: &[usize],
+    source: Option<&SourceLocation>,
+) -> Option<String> {
+    let node = profile.node(path)?;
+    let all = profile.total().max(1) as f64;
+    let percent = |n: u64| n as f64 * 100.0 / all;
+    let mut prompt = format!(
+        "Optimize this hot frame from a CPU profile.\n\nFrame: {}\nSamples: {} of {} ({:.1}%), \
+         {} ({:.1}%) in the frame itself\nCall path: {}\n",
+        node.name,
+        node.total,
+        profile.total(),
+        percent(node.total),
+        node.self_samples,
+        percent(node.self_samples),
+        profile.stack(path).join(" → "),
+    );
+    let mut callees: Vec<&FlameNode> = node.children.iter().collect();
+    callees.sort_by_key(|c| std::cmp::Reverse(c.total));
+    if !callees.is_empty() {
+        prompt.push_str("Hottest callees:\n");
+        for callee in callees.into_iter().take(MAX_CALLEES) {
+            prompt.push_str(&format!(
+                "- {} ({:.1}%)\n",
+                callee.name,
+                percent(callee.total)
+            ));
+        }
+    }
+    match source {
+        Some(source) => prompt.push_str(&format!(
+            "\nSource ({}:{}-{}):\n```\n{}\n```\n\nSay what makes it expensive, then propose \
+             concrete edits that keep its behaviour.",
+            source.path.display(),
+            source.line,
+            source.end_line,
+            source.code
+        )),
+        None => prompt.push_str(
+            "\nThe frame's source isn't in the workspace (a dependency, the standard library or \
+             the system). Find the workspace code on the call path that leads to it and propose \
+             edits that call it less or avoid it.",
+        ),
+    }
+    Some(prompt)
+}
@@ -0,0 +1,298 @@
+//! Progress bus for background work.
+//!
+//! Long-running work — semantic indexing, repo-map generation, TODO scans,
+//! language-server warm-up — reports through a [`ProgressBus`] so the IDE can
+//! show one status item for all of it ("Indexing 1,243/5,012 files") and
+//! cancel what can be cancelled. A producer starts a [`ProgressTask`],
+//! advances it, checks [`ProgressTask::is_cancelled`] between steps, and
+//! finishes it or simply drops it. [`global`] is the bus the IDE listens to.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Count updates of one task are published at most this often.
+pub const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+static GLOBAL: LazyLock<ProgressBus> = LazyLock::new(ProgressBus::new);
+
+/// The process-wide bus shown in the IDE's status bar.
+pub fn global() -> &'static ProgressBus {
+    &GLOBAL
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Done,
+    Cancelled,
+    Failed(String),
+}
+
+/// Sent to subscribers; read [`ProgressBus::tasks`] for the current state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    Started(u64),
+    Updated(u64),
+    Finished { id: u64, outcome: Outcome },
+}
+
+/// A running task as the bus last saw it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSnapshot {
+    pub id: u64,
+    pub title: String,
+    /// What is counted, e.g. "files". A `%` unit shows `done` as a percentage.
+    pub unit: String,
+    pub done: u64,
+    pub total: Option<u64>,
+    pub message: Option<String>,
+    pub cancellable: bool,
+    pub cancel_requested: bool,
+}
+
+impl TaskSnapshot {
+    /// Status text, e.g. "Indexing 1,243/5,012 files".
+    pub fn label(&self) -> String {
+        let count = match self.total {
+            _ if self.unit == "%" => format!("{}%", self.done),
+            Some(total) => format!("{}/{}", thousands(self.done), thousands(total)),
+            None if self.done > 0 => thousands(self.done),
+            None => return format!("{}…", self.title),
+        };
+        if self.unit.is_empty() || self.unit == "%" {
+            format!("{} {count}", self.title)
+        } else {
+            format!("{} {count} {}", self.title, self.unit)
+        }
+    }
+}
+
+/// One line for every running task: the oldest task's label, plus how many
+/// others are running.
+pub fn summary(tasks: &[TaskSnapshot]) -> Option<String> {
+    let first = tasks.first()?;
+    Some(match tasks.len() {
+        1 => first.label(),
+        n => format!("{} (+{} more)", first.label(), n - 1),
+    })
+}
+
+/// `n` with comma thousands separators.
+fn thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+struct Entry {
+    snapshot: TaskSnapshot,
+    cancel: Arc<AtomicBool>,
+    last_sent: Instant,
+}
+
+#[derive(Default)]
+struct Bus {
+    tasks: BTreeMap<u64, Entry>,
+    next_id: u64,
+    subscribers: Vec<Sender<ProgressEvent>>,
+}
+
+impl Bus {
+    fn publish(&mut self, event: ProgressEvent) {
+        self.subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ProgressBus {
+    inner: Arc<Mutex<Bus>>,
+}
+
+impl ProgressBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Describe a task to start.
+    pub fn task(&self, title: impl Into<String>) -> TaskBuilder<'_> {
+        TaskBuilder {
+            bus: self,
+            title: title.into(),
+            unit: String::new(),
+            total: None,
+            cancellable: false,
+        }
+    }
+
+    /// Receive every event from now on.
+    pub fn subscribe(&self) -> Receiver<ProgressEvent> {
+        let (tx, rx) = channel();
+        self.inner.lock().unwrap().subscribers.push(tx);
+        rx
+    }
+
+    /// Running tasks, oldest first.
+    pub fn tasks(&self) -> Vec<TaskSnapshot> {
+        let bus = self.inner.lock().unwrap();
+        bus.tasks.values().map(|e| e.snapshot.clone()).collect()
+    }
+
+    /// Ask task `id` to stop. Returns false if it isn't running or can't be
+    /// cancelled.
+    pub fn cancel(&self, id: u64) -> bool {
+        let mut bus = self.inner.lock().unwrap();
+        let Some(entry) = bus.tasks.get_mut(&id).filter(|e| e.snapshot.cancellable) else {
+            return false;
+        };
+        entry.cancel.store(true, Ordering::Relaxed);
+        entry.snapshot.cancel_requested = true;
+        bus.publish(ProgressEvent::Updated(id));
+        true
+    }
+}
+
+pub struct TaskBuilder<'a> {
+    bus: &'a ProgressBus,
+    title: String,
+    unit: String,
+    total: Option<u64>,
+    cancellable: bool,
+}
+
+impl TaskBuilder<'_> {
+    pub fn unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = unit.into();
+        self
+    }
+
+    pub fn total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// The producer checks [`ProgressTask::is_cancelled`] and stops early.
+    pub fn cancellable(mut self) -> Self {
+        self.cancellable = true;
+        self
+    }
+
+    pub fn start(self) -> ProgressTask {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut bus = self.bus.inner.lock().unwrap();
+        bus.next_id += 1;
+        let id = bus.next_id;
+        bus.tasks.insert(
+            id,
+            Entry {
+                snapshot: TaskSnapshot {
+                    id,
+                    title: self.title,
+                    unit: self.unit,
+                    done: 0,
+                    total: self.total,
+                    message: None,
+                    cancellable: self.cancellable,
+                    cancel_requested: false,
+                },
+                cancel: cancel.clone(),
+                last_sent: Instant::now(),
+            },
+        );
+        bus.publish(ProgressEvent::Started(id));
+        ProgressTask {
+            bus: self.bus.clone(),
+            id,
+            cancel,
+            finished: false,
+        }
+    }
+}
+
+/// A running task. Dropping it finishes it.
+pub struct ProgressTask {
+    bus: ProgressBus,
+    id: u64,
+    cancel: Arc<AtomicBool>,
+    finished: bool,
+}
+
+impl ProgressTask {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Change the task. `always` publishes the change even if the last
+    /// update went out less than [`UPDATE_INTERVAL`] ago.
+    fn update(&self, always: bool, f: impl FnOnce(&mut TaskSnapshot)) {
+        let mut bus = self.bus.inner.lock().unwrap();
+        let Some(entry) = bus.tasks.get_mut(&self.id) else {
+            return;
+        };
+        f(&mut entry.snapshot);
+        if !always && entry.last_sent.elapsed() < UPDATE_INTERVAL {
+            return;
+        }
+        entry.last_sent = Instant::now();
+        bus.publish(ProgressEvent::Updated(self.id));
+    }
+
+    pub fn advance(&self, n: u64) {
+        self.update(false, |t| t.done += n);
+    }
+
+    pub fn set_done(&self, done: u64) {
+        self.update(false, |t| t.done = done);
+    }
+
+    pub fn set_total(&self, total: Option<u64>) {
+        self.update(true, |t| t.total = total);
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        self.update(true, |t| t.message = Some(message));
+    }
+
+    fn end(&mut self, outcome: Outcome) {
+        if std::mem::replace(&mut self.finished, true) {
+            return;
+        }
+        let mut bus = self.bus.inner.lock().unwrap();
+        bus.tasks.remove(&self.id);
+        bus.publish(ProgressEvent::Finished {
+            id: self.id,
+            outcome,
+        });
+    }
+
+    /// Finish the task: done, or cancelled if that was asked for. The same
+    /// as dropping it.
+    pub fn finish(self) {}
+
+    pub fn fail(mut self, error: impl Into<String>) {
+        self.end(Outcome::Failed(error.into()));
+    }
+}
+
+impl Drop for ProgressTask {
+    fn drop(&mut self) {
+        let outcome = if self.is_cancelled() {
+            Outcome::Cancelled
+        } else {
+            Outcome::Done
+        };
+        self.end(outcome);
+    }
+}
@@ -0,0 +1,270 @@
+//! Workspace backups: the workspace's `.phazeai` directory (memories, the
+//! task board, run configurations, env profiles, recordings, instructions)
+//! and the saved conversations started in it, in one zip archive.
+//!
+//! [`export`] writes the archive with a [`BackupManifest`] describing it;
+//! [`restore`] puts it back into a workspace, on another machine or after
+//! something got corrupted, pointing the conversations at their new
+//! location. Files already there are kept unless asked to overwrite them.
+
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::{ConversationStore, SavedConversation};
+use crate::error::PhazeError;
+
+/// Bumped whenever the archive layout changes.
+pub const BACKUP_FORMAT: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+/// Where the `.phazeai` files go in the archive.
+const WORKSPACE_PREFIX: &str = "workspace/";
+const CONVERSATIONS_PREFIX: &str = "conversations/";
+/// Left out: indexes rebuilt from their sources, and SQLite's journals.
+const SKIPPED: &[&str] = &["docs.db"];
+const SKIPPED_SUFFIXES: &[&str] = &["-wal", "-shm", "-journal"];
+/// Left out with everything in it: the trash, whose journal points at paths
+/// in this checkout.
+const SKIPPED_DIRS: &[&str] = &["trash"];
+
+/// What an archive holds, stored in it as `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: u32,
+    /// The version of PhazeAI that wrote it.
+    pub version: String,
+    pub created_at: String,
+    /// The workspace it was taken from.
+    pub workspace: PathBuf,
+    /// Paths under `.phazeai`, with `/` separators.
+    pub files: Vec<String>,
+    /// Ids of the conversations.
+    pub conversations: Vec<String>,
+}
+
+/// What [`restore`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub files: usize,
+    pub conversations: usize,
+    /// Files and conversation ids left alone because they already exist.
+    pub skipped: Vec<String>,
+}
+
+fn zip_error(path: &Path, e: impl std::fmt::Display) -> PhazeError {
+    PhazeError::Other(format!("{}: {e}", path.display()))
+}
+
+fn skipped(name: &str) -> bool {
+    SKIPPED.contains(&name) || SKIPPED_SUFFIXES.iter().any(|s| name.ends_with(s))
+}
+
+/// The files under `dir` to back up, relative to it with `/` separators,
+/// sorted. Symlinks aren't followed, and the trash is left out.
+pub fn backup_files(dir: &Path) -> Result<Vec<String>, PhazeError> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = match std::fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let kind = entry.file_type()?;
+            let path = entry.path();
+            if kind.is_dir() {
+                let name = entry.file_name();
+                if current != dir || !SKIPPED_DIRS.contains(&&*name.to_string_lossy()) {
+                    pending.push(path);
+                }
+            } else if kind.is_file() && !skipped(&entry.file_name().to_string_lossy()) {
+                let relative = path.strip_prefix(dir).unwrap_or(&path);
+                let parts: Vec<String> = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                files.push(parts.join("/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether `project_dir`, as saved with a conversation, is `root` or inside it.
+fn in_workspace(project_dir: Option<&str>, root: &Path) -> bool {
+    let Some(dir) = project_dir else {
+        return false;
+    };
+    let dir = Path::new(dir);
+    let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    dir.starts_with(root) || canonical(dir).starts_with(canonical(root))
+}
+
+/// Back up the workspace at `root`, and the conversations in `store`
+/// started in it, to the archive `out`.
+pub fn export(
+    root: &Path,
+    store: &ConversationStore,
+    out: &Path,
+) -> Result<BackupManifest, PhazeError> {
+    let state_dir = root.join(".phazeai");
+    let files = backup_files(&state_dir)?;
+    let conversations: Vec<SavedConversation> = store
+        .list_recent(usize::MAX)?
+        .into_iter()
+        .filter(|m| in_workspace(m.project_dir.as_deref(), root))
+        .filter_map(|m| store.load(&m.id).ok())
+        .collect();
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        workspace: root.to_path_buf(),
+        files,
+        conversations: conversations
+            .iter()
+            .map(|c| c.metadata.id.clone())
+            .collect(),
+    };
+
+    if let Some(parent) = out.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(out)?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut add = |name: String, bytes: &[u8]| -> Result<(), PhazeError> {
+        zip.start_file(name, options)
+            .map_err(|e| zip_error(out, e))?;
+        zip.write_all(bytes)?;
+        Ok(())
+    };
+    add(
+        MANIFEST.to_string(),
+        serde_json::to_string_pretty(&manifest)?.as_bytes(),
+    )?;
+    for file in &manifest.files {
+        let bytes = std::fs::read(state_dir.join(file))?;
+        add(format!("{WORKSPACE_PREFIX}{file}"), &bytes)?;
+    }
+    for conversation in &conversations {
+        add(
+            format!("{CONVERSATIONS_PREFIX}{}.json", conversation.metadata.id),
+            serde_json::to_string_pretty(conversation)?.as_bytes(),
+        )?;
+    }
+    zip.finish().map_err(|e| zip_error(out, e))?;
+    Ok(manifest)
+}
+
+/// `name` from an archive as a path under the directory it's restored to;
+/// `None` for anything that would land outside it.
+fn contained(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let mut parts = path.components().peekable();
+    parts.peek()?;
+    parts
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| path.to_path_buf())
+}
+
+fn open_archive(archive: &Path) -> Result<zip::ZipArchive<std::fs::File>, PhazeError> {
+    zip::ZipArchive::new(std::fs::File::open(archive)?).map_err(|e| zip_error(archive, e))
+}
+
+fn read_entry(
+    zip: &mut zip::ZipArchive<std::fs::File>,
+    archive: &Path,
+    index: usize,
+) -> Result<(String, Vec<u8>), PhazeError> {
+    let mut entry = zip.by_index(index).map_err(|e| zip_error(archive, e))?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Ok((entry.name().to_string(), bytes))
+}
+
+/// The manifest of the archive at `archive`.
+pub fn read_manifest(archive: &Path) -> Result<BackupManifest, PhazeError> {
+    let mut zip = open_archive(archive)?;
+    let mut entry = zip.by_name(MANIFEST).map_err(|_| {
+        PhazeError::Other(format!("{} isn't a workspace backup", archive.display()))
+    })?;
+    let mut manifest = String::new();
+    entry.read_to_string(&mut manifest)?;
+    let manifest: BackupManifest = serde_json::from_str(&manifest)?;
+    if manifest.format > BACKUP_FORMAT {
+        return Err(PhazeError::Other(format!(
+            "{} was written by a newer PhazeAI ({}); update to restore it",
+            archive.display(),
+            manifest.version
+        )));
+    }
+    Ok(manifest)
+}
+
+/// The conversation's `project_dir` moved from the backed-up workspace
+/// `from` to `to`.
+fn moved(project_dir: Option<&str>, from: &Path, to: &Path) -> String {
+    project_dir
+        .and_then(|dir| Path::new(dir).strip_prefix(from).ok())
+        .map(|rest| to.join(rest))
+        .unwrap_or_else(|| to.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Restore the archive at `archive` into the workspace at `root` and its
+/// conversations into `store`. What's already there is kept, and listed
+/// as skipped, unless `overwrite`.
+pub fn restore(
+    archive: &Path,
+    root: &Path,
+    store: &ConversationStore,
+    overwrite: bool,
+) -> Result<RestoreReport, PhazeError> {
+    let manifest = read_manifest(archive)?;
+    let state_dir = root.join(".phazeai");
+    let mut zip = open_archive(archive)?;
+    let mut report = RestoreReport::default();
+    for index in 0..zip.len() {
+        let (name, bytes) = read_entry(&mut zip, archive, index)?;
+        if let Some(file) = name.strip_prefix(WORKSPACE_PREFIX) {
+            let Some(relative) = contained(file) else {
+                continue;
+            };
+            let target = state_dir.join(&relative);
+            if target.exists() && !overwrite {
+                report.skipped.push(file.to_string());
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, bytes)?;
+            report.files += 1;
+        } else if name.starts_with(CONVERSATIONS_PREFIX) {
+            let mut conversation: SavedConversation = serde_json::from_slice(&bytes)?;
+            let id = conversation.metadata.id.clone();
+            // The id names the conversation's file in the store.
+            if contained(&id).is_none_or(|p| p.components().count() != 1) {
+                continue;
+            }
+            if store.load(&id).is_ok() && !overwrite {
+                report.skipped.push(id);
+                continue;
+            }
+            conversation.metadata.project_dir = Some(moved(
+                conversation.metadata.project_dir.as_deref(),
+                &manifest.workspace,
+                root,
+            ));
+            store.save(&conversation)?;
+            report.conversations += 1;
+        }
+    }
+    Ok(report)
+}
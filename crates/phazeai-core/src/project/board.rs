@@ -0,0 +1,325 @@
+//! The project task board, kept in `.phazeai/tasks.json`.
+//!
+//! Cards sit in columns ("To Do", "In Progress", "Done" to start with) and
+//! keep their order within a column. A card can link to places in files and
+//! to the conversations it came from, and collects progress notes — the agent
+//! reports on its work through the `task_board` tool, and the IDE's BOARD tab
+//! shows and rearranges the same file. Every change reads the file, applies
+//! itself and writes it back, so the two don't overwrite each other.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PhazeError;
+
+/// The board, relative to the workspace root.
+pub const BOARD_FILE: &str = ".phazeai/tasks.json";
+
+/// Columns of a new board. Closing a card moves it to the last column.
+pub const DEFAULT_COLUMNS: &[&str] = &["To Do", "In Progress", "Done"];
+
+/// Progress notes kept per card; older ones are dropped.
+const MAX_NOTES: usize = 50;
+
+/// Something a card points at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CardLink {
+    /// A file, relative to the workspace, at a 1-based line.
+    File {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line: Option<u32>,
+    },
+    /// A saved chat conversation.
+    Conversation { id: String },
+}
+
+impl CardLink {
+    /// `path:line`, `path`, or `conversation <id>`.
+    pub fn label(&self) -> String {
+        match self {
+            CardLink::File {
+                path,
+                line: Some(line),
+            } => format!("{path}:{line}"),
+            CardLink::File { path, line: None } => path.clone(),
+            CardLink::Conversation { id } => format!("conversation {id}"),
+        }
+    }
+}
+
+/// A timestamped progress note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CardNote {
+    /// RFC 3339.
+    pub at: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Card {
+    pub id: u64,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub column: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<CardLink>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<CardNote>,
+    /// RFC 3339.
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Card {
+    /// Link `link` unless the card already has it.
+    pub fn link(&mut self, link: CardLink) {
+        if !self.links.contains(&link) {
+            self.links.push(link);
+        }
+    }
+}
+
+/// Columns and the cards in them. Cards are stored in display order; a
+/// column shows its cards in the order they appear here.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Board {
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub cards: Vec<Card>,
+    #[serde(default = "first_id")]
+    pub next_id: u64,
+}
+
+fn first_id() -> u64 {
+    1
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_COLUMNS.iter().map(|c| c.to_string()).collect(),
+            cards: Vec::new(),
+            next_id: first_id(),
+        }
+    }
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+impl Board {
+    /// The cards of `column`, top to bottom.
+    pub fn cards_in<'a>(&'a self, column: &'a str) -> impl Iterator<Item = &'a Card> + 'a {
+        self.cards.iter().filter(move |c| c.column == column)
+    }
+
+    pub fn card(&self, id: u64) -> Option<&Card> {
+        self.cards.iter().find(|c| c.id == id)
+    }
+
+    /// The card, marked as updated now.
+    pub fn card_mut(&mut self, id: u64) -> Result<&mut Card, PhazeError> {
+        let card = self
+            .cards
+            .iter_mut()
+            .find(|c| c.id == id)
+            .ok_or_else(|| PhazeError::Other(format!("no card #{id} on the board")))?;
+        card.updated_at = now();
+        Ok(card)
+    }
+
+    /// The column named `name`, ignoring case.
+    pub fn column(&self, name: &str) -> Result<&str, PhazeError> {
+        let name = name.trim();
+        self.columns
+            .iter()
+            .find(|c| c.eq_ignore_ascii_case(name))
+            .map(String::as_str)
+            .ok_or_else(|| {
+                PhazeError::Other(format!(
+                    "no column \"{name}\"; the board has {}",
+                    self.columns.join(", ")
+                ))
+            })
+    }
+
+    /// Add a card at the bottom of `column`, or of the first column.
+    pub fn add_card(
+        &mut self,
+        title: &str,
+        description: &str,
+        column: Option<&str>,
+    ) -> Result<&mut Card, PhazeError> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(PhazeError::Other("a card needs a title".into()));
+        }
+        let column = match column {
+            Some(name) => self.column(name)?.to_string(),
+            None => self
+                .columns
+                .first()
+                .cloned()
+                .ok_or_else(|| PhazeError::Other("the board has no columns".into()))?,
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        let at = now();
+        self.cards.push(Card {
+            id,
+            title: title.to_string(),
+            description: description.trim().to_string(),
+            column,
+            links: Vec::new(),
+            notes: Vec::new(),
+            created_at: at.clone(),
+            updated_at: at,
+        });
+        Ok(self.cards.last_mut().expect("just pushed"))
+    }
+
+    /// Move a card into `column`, just above card `before` when that's in
+    /// the column, else to the bottom.
+    pub fn move_card(
+        &mut self,
+        id: u64,
+        column: &str,
+        before: Option<u64>,
+    ) -> Result<(), PhazeError> {
+        let column = self.column(column)?.to_string();
+        let from = self
+            .cards
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| PhazeError::Other(format!("no card #{id} on the board")))?;
+        let mut card = self.cards.remove(from);
+        card.column = column.clone();
+        card.updated_at = now();
+        let at = before
+            .filter(|b| *b != id)
+            .and_then(|b| {
+                self.cards
+                    .iter()
+                    .position(|c| c.id == b && c.column == column)
+            })
+            .or_else(|| {
+                self.cards
+                    .iter()
+                    .rposition(|c| c.column == column)
+                    .map(|i| i + 1)
+            })
+            .unwrap_or(self.cards.len());
+        self.cards.insert(at, card);
+        Ok(())
+    }
+
+    /// Move a card to the last column.
+    pub fn close_card(&mut self, id: u64) -> Result<(), PhazeError> {
+        let done = self
+            .columns
+            .last()
+            .cloned()
+            .ok_or_else(|| PhazeError::Other("the board has no columns".into()))?;
+        self.move_card(id, &done, None)
+    }
+
+    /// Record progress on a card.
+    pub fn add_note(&mut self, id: u64, text: &str) -> Result<(), PhazeError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(());
+        }
+        let card = self.card_mut(id)?;
+        card.notes.push(CardNote {
+            at: card.updated_at.clone(),
+            text: text.to_string(),
+        });
+        let excess = card.notes.len().saturating_sub(MAX_NOTES);
+        card.notes.drain(..excess);
+        Ok(())
+    }
+
+    pub fn remove_card(&mut self, id: u64) -> Result<Card, PhazeError> {
+        let at = self
+            .cards
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| PhazeError::Other(format!("no card #{id} on the board")))?;
+        Ok(self.cards.remove(at))
+    }
+
+    /// Add a column at the right, unless there is one by that name.
+    pub fn add_column(&mut self, name: &str) -> Result<(), PhazeError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(PhazeError::Other("a column needs a name".into()));
+        }
+        if self.column(name).is_err() {
+            self.columns.push(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Remove an empty column.
+    pub fn remove_column(&mut self, name: &str) -> Result<(), PhazeError> {
+        let name = self.column(name)?.to_string();
+        if self.cards_in(&name).next().is_some() {
+            return Err(PhazeError::Other(format!(
+                "move the cards out of \"{name}\" first"
+            )));
+        }
+        self.columns.retain(|c| *c != name);
+        Ok(())
+    }
+}
+
+/// The board file of one workspace.
+#[derive(Debug, Clone)]
+pub struct BoardStore {
+    path: PathBuf,
+}
+
+impl BoardStore {
+    pub fn for_workspace(root: &Path) -> Self {
+        Self {
+            path: root.join(BOARD_FILE),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The board; a new one when there's no file yet.
+    pub fn load(&self) -> Result<Board, PhazeError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Board::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, board: &Board) -> Result<(), PhazeError> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(board)?)?;
+        Ok(())
+    }
+
+    /// Load the board, change it with `f` and save it when `f` succeeds.
+    pub fn update<T>(
+        &self,
+        f: impl FnOnce(&mut Board) -> Result<T, PhazeError>,
+    ) -> Result<T, PhazeError> {
+        let mut board = self.load()?;
+        let out = f(&mut board)?;
+        self.save(&board)?;
+        Ok(out)
+    }
+}
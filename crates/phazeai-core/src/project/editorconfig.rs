@@ -0,0 +1,341 @@
+//! `.editorconfig` support and indentation detection for the editor.
+//!
+//! [`EditorConfig::for_file`] resolves the properties that apply to a file —
+//! closer files win, and a file with `root = true` ends the search. When a
+//! file has no indentation configured, [`detect_indent`] guesses it from the
+//! content; [`EditorConfig::indent`] combines the two with the global
+//! default.
+
+use std::ops::Range;
+use std::path::Path;
+
+use globset::GlobBuilder;
+
+/// Lines sampled by [`detect_indent`].
+const DETECT_LINES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Spaces,
+    Tabs,
+}
+
+/// One level of indentation: its style and width in columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Indent {
+    pub style: IndentStyle,
+    pub size: u32,
+}
+
+impl Indent {
+    pub fn spaces(size: u32) -> Self {
+        Self {
+            style: IndentStyle::Spaces,
+            size,
+        }
+    }
+
+    pub fn tabs(size: u32) -> Self {
+        Self {
+            style: IndentStyle::Tabs,
+            size,
+        }
+    }
+
+    /// The text inserted for one level.
+    pub fn unit(&self) -> String {
+        match self.style {
+            IndentStyle::Tabs => "\t".to_string(),
+            IndentStyle::Spaces => " ".repeat(self.size as usize),
+        }
+    }
+
+    /// Status bar text, e.g. `Spaces: 4` or `Tab Size: 4`.
+    pub fn label(&self) -> String {
+        match self.style {
+            IndentStyle::Spaces => format!("Spaces: {}", self.size),
+            IndentStyle::Tabs => format!("Tab Size: {}", self.size),
+        }
+    }
+}
+
+/// Where the indentation in effect for a file came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentSource {
+    EditorConfig,
+    /// Guessed from the file's content.
+    Detected,
+    /// The global `tab_size` setting.
+    Default,
+    /// Picked by the user for this file.
+    User,
+}
+
+/// The indentation used for a file and why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EffectiveIndent {
+    pub indent: Indent,
+    pub source: IndentSource,
+}
+
+impl EffectiveIndent {
+    /// Status bar text, e.g. `Spaces: 2 (.editorconfig)`.
+    pub fn label(&self) -> String {
+        let from = match self.source {
+            IndentSource::EditorConfig => " (.editorconfig)",
+            IndentSource::Detected => " (detected)",
+            IndentSource::Default | IndentSource::User => "",
+        };
+        format!("{}{from}", self.indent.label())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    /// `LF` or `CRLF`, as shown in the status bar.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::Crlf => "CRLF",
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+/// The `.editorconfig` properties that apply to one file; `None` is unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorConfig {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<u32>,
+    pub tab_width: Option<u32>,
+    pub end_of_line: Option<LineEnding>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    /// Columns a line may take; `off` leaves it unset.
+    pub max_line_length: Option<u32>,
+}
+
+impl EditorConfig {
+    /// Read every `.editorconfig` from `path`'s directory up to the first one
+    /// marked `root = true` (or the filesystem root) and resolve the
+    /// properties for `path`.
+    pub fn for_file(path: &Path) -> Self {
+        let mut files = Vec::new();
+        for dir in path.ancestors().skip(1) {
+            let Ok(content) = std::fs::read_to_string(dir.join(".editorconfig")) else {
+                continue;
+            };
+            let root = is_root(&content);
+            files.push((dir.to_path_buf(), content));
+            if root {
+                break;
+            }
+        }
+        let mut config = Self::default();
+        // Outermost first, so closer files override.
+        for (dir, content) in files.iter().rev() {
+            if let Ok(relative) = path.strip_prefix(dir) {
+                config.apply(content, &relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+        config
+    }
+
+    /// Apply the sections of one `.editorconfig` that match `relative`, the
+    /// file's path relative to that `.editorconfig`.
+    pub fn apply(&mut self, content: &str, relative: &str) {
+        let mut matching = false;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                matching = section_matches(section, relative);
+                continue;
+            }
+            if !matching {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            // Anything unrecognized, including `unset`, clears the property.
+            let value = value.trim().to_lowercase();
+            let flag = match value.as_str() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            };
+            match key.trim().to_lowercase().as_str() {
+                "indent_style" => {
+                    self.indent_style = match value.as_str() {
+                        "tab" => Some(IndentStyle::Tabs),
+                        "space" => Some(IndentStyle::Spaces),
+                        _ => None,
+                    }
+                }
+                // `indent_size = tab` means "use tab_width".
+                "indent_size" => self.indent_size = value.parse().ok().filter(|n| *n > 0),
+                "tab_width" => self.tab_width = value.parse().ok().filter(|n| *n > 0),
+                "end_of_line" => {
+                    self.end_of_line = match value.as_str() {
+                        "crlf" => Some(LineEnding::Crlf),
+                        "lf" | "cr" => Some(LineEnding::Lf),
+                        _ => None,
+                    }
+                }
+                "trim_trailing_whitespace" => self.trim_trailing_whitespace = flag,
+                "insert_final_newline" => self.insert_final_newline = flag,
+                "max_line_length" => self.max_line_length = value.parse().ok().filter(|n| *n > 0),
+                _ => {}
+            }
+        }
+    }
+
+    /// The indentation to use for a file with `text`: configured here if
+    /// set, otherwise detected from the content, otherwise `default`.
+    pub fn indent(&self, text: &str, default: Indent) -> EffectiveIndent {
+        let configured_size = match self.indent_style {
+            Some(IndentStyle::Tabs) => self.tab_width.or(self.indent_size),
+            _ => self.indent_size.or(self.tab_width),
+        };
+        if self.indent_style.is_none() && configured_size.is_none() {
+            return match detect_indent(text, default.size) {
+                Some(indent) => EffectiveIndent {
+                    indent,
+                    source: IndentSource::Detected,
+                },
+                None => EffectiveIndent {
+                    indent: default,
+                    source: IndentSource::Default,
+                },
+            };
+        }
+        let detected = detect_indent(text, default.size);
+        let style = self
+            .indent_style
+            .or(detected.map(|d| d.style))
+            .unwrap_or(default.style);
+        let size = configured_size
+            .or(detected.filter(|d| d.style == style).map(|d| d.size))
+            .unwrap_or(default.size);
+        EffectiveIndent {
+            indent: Indent { style, size },
+            source: IndentSource::EditorConfig,
+        }
+    }
+
+    /// Edits that bring `text` in line with `trim_trailing_whitespace` and
+    /// `insert_final_newline`: byte ranges and their replacements, in order
+    /// and non-overlapping. Apply them from last to first.
+    pub fn whitespace_fixes(&self, text: &str) -> Vec<(Range<usize>, &'static str)> {
+        let mut fixes = Vec::new();
+        if self.trim_trailing_whitespace == Some(true) {
+            let mut start = 0;
+            for line in text.split_inclusive('\n') {
+                let content = line.trim_end_matches(['\n', '\r']);
+                let trimmed = content.trim_end_matches([' ', '\t']);
+                if trimmed.len() < content.len() {
+                    fixes.push((start + trimmed.len()..start + content.len(), ""));
+                }
+                start += line.len();
+            }
+        }
+        if self.insert_final_newline == Some(true) && !text.is_empty() && !text.ends_with('\n') {
+            let ending = self.end_of_line.unwrap_or(LineEnding::Lf).as_str();
+            fixes.push((text.len()..text.len(), ending));
+        }
+        fixes
+    }
+}
+
+/// Whether a `.editorconfig` sets `root = true` before its first section.
+fn is_root(content: &str) -> bool {
+    content
+        .lines()
+        .map(str::trim)
+        .take_while(|l| !l.starts_with('['))
+        .filter_map(|l| l.split_once('='))
+        .any(|(k, v)| {
+            k.trim().eq_ignore_ascii_case("root") && v.trim().eq_ignore_ascii_case("true")
+        })
+}
+
+/// Whether a section glob matches `relative`. Globs without a `/` match the
+/// file name in any directory; others are relative to the `.editorconfig`.
+fn section_matches(section: &str, relative: &str) -> bool {
+    let pattern = if let Some(anchored) = section.strip_prefix('/') {
+        anchored.to_string()
+    } else if section.contains('/') {
+        section.to_string()
+    } else {
+        format!("**/{section}")
+    };
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .map(|glob| glob.compile_matcher().is_match(relative))
+        .unwrap_or(false)
+}
+
+/// Guess a file's indentation from its leading whitespace: tabs if most
+/// indented lines start with one, otherwise the most common step between
+/// consecutive lines' indents. Tabs get `tab_size` columns. `None` when
+/// nothing is indented.
+pub fn detect_indent(text: &str, tab_size: u32) -> Option<Indent> {
+    let mut tab_lines = 0usize;
+    let mut space_lines = 0usize;
+    let mut steps = [0usize; 9];
+    let mut previous = 0usize;
+    for line in text.lines().take(DETECT_LINES) {
+        let rest = line.trim_start_matches([' ', '\t']);
+        if rest.is_empty() {
+            continue;
+        }
+        let leading = &line[..line.len() - rest.len()];
+        if leading.starts_with('\t') {
+            tab_lines += 1;
+            continue;
+        }
+        if leading.contains('\t') {
+            continue;
+        }
+        // The ` * ` lines of block comments are off by one.
+        if rest.starts_with('*') && leading.len() % 2 == 1 {
+            continue;
+        }
+        let width = leading.len();
+        if width > 0 {
+            space_lines += 1;
+        }
+        if width > previous {
+            if let Some(count) = steps.get_mut(width - previous) {
+                *count += 1;
+            }
+        }
+        previous = width;
+    }
+    if tab_lines == 0 && space_lines == 0 {
+        return None;
+    }
+    if tab_lines > space_lines {
+        return Some(Indent::tabs(tab_size));
+    }
+    // Most common step of 2 or more; ties go to the smaller one.
+    let step = (2..steps.len())
+        .filter(|s| steps[*s] > 0)
+        .max_by(|a, b| steps[*a].cmp(&steps[*b]).then(b.cmp(a)))?;
+    Some(Indent::spaces(step as u32))
+}
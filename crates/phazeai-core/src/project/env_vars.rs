@@ -0,0 +1,409 @@
+//! The workspace's environment variables, from its `.env` files.
+//!
+//! `.env` holds the variables every profile shares, and `.env.<profile>`
+//! (`.env.dev`, `.env.test`, `.env.prod`, …) those of one profile, with a
+//! `.local` variant of each for values kept out of version control. The
+//! active profile is kept in `.phazeai/env.toml`. The IDE injects the
+//! resolved variables into terminals, build tasks and the sidecar, and shows
+//! them in its ENV tab; values that look like secrets are masked there and
+//! [`EnvVars::redact`]ed from logs.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::PhazeError;
+
+/// The active profile, relative to the workspace root.
+pub const ENV_SETTINGS_FILE: &str = ".phazeai/env.toml";
+
+/// Shown instead of a secret value.
+pub const MASK: &str = "••••••••";
+
+/// Parts of variable names that mark their values as secrets.
+const SECRET_NAME_PARTS: &[&str] = &[
+    "SECRET",
+    "TOKEN",
+    "PASSWORD",
+    "PASSWD",
+    "API_KEY",
+    "APIKEY",
+    "ACCESS_KEY",
+    "PRIVATE",
+    "CREDENTIAL",
+    "AUTH",
+    "SESSION",
+    "COOKIE",
+    "SIGNING",
+    "SALT",
+    "DSN",
+];
+
+/// Secret values shorter than this aren't redacted from logs; they would
+/// match too much ordinary text.
+const MIN_REDACTED_LEN: usize = 4;
+
+/// One `KEY=value` line of a `.env` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvEntry {
+    pub key: String,
+    pub value: String,
+    /// 0-based.
+    pub line: usize,
+    /// The last line, for a quoted value spanning several.
+    pub end_line: usize,
+}
+
+/// Unescape the inside of a double-quoted value.
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// `value` with `${NAME}` replaced from `known`, else the process
+/// environment, else nothing.
+fn expand(value: &str, known: &[EnvEntry]) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let name = &rest[start + 2..start + len];
+        match known.iter().rev().find(|e| e.key == name) {
+            Some(entry) => out.push_str(&entry.value),
+            None => out.push_str(&std::env::var(name).unwrap_or_default()),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The variables of a `.env` file, in order. Lines may start with
+/// `export`; values may be `"double-quoted"` (with escapes and `${VAR}`
+/// expansion, spanning lines), `'single-quoted'` (literal) or bare (with
+/// expansion and a ` #` comment cut off).
+pub fn parse_dotenv(text: &str) -> Vec<EnvEntry> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut entries: Vec<EnvEntry> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line_no = i;
+        let line = lines[i].trim();
+        i += 1;
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, raw)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            continue;
+        }
+        let raw = raw.trim_start();
+        let value = if let Some(quoted) = raw.strip_prefix('"') {
+            // A double-quoted value runs to the next unescaped quote.
+            let mut text = quoted.to_string();
+            loop {
+                if let Some(end) = closing_quote(&text) {
+                    text.truncate(end);
+                    break;
+                }
+                match lines.get(i) {
+                    Some(next) => {
+                        text.push('\n');
+                        text.push_str(next);
+                        i += 1;
+                    }
+                    None => break,
+                }
+            }
+            expand(&unescape(&text), &entries)
+        } else if let Some(quoted) = raw.strip_prefix('\'') {
+            quoted.split('\'').next().unwrap_or("").to_string()
+        } else {
+            let bare = match raw.find(" #") {
+                Some(comment) => &raw[..comment],
+                None => raw,
+            };
+            expand(bare.trim_end(), &entries)
+        };
+        entries.push(EnvEntry {
+            key: key.to_string(),
+            value,
+            line: line_no,
+            end_line: i - 1,
+        });
+    }
+    entries
+}
+
+/// The byte index of the first unescaped `"` in `text`.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Whether the variable `key`'s value should be masked.
+pub fn is_secret(key: &str, value: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    SECRET_NAME_PARTS.iter().any(|part| key.contains(part))
+        // A connection string with a password in it.
+        || value
+            .split_once("://")
+            .and_then(|(_, rest)| rest.split_once('@'))
+            .is_some_and(|(userinfo, _)| userinfo.contains(':'))
+}
+
+/// `value` as a `.env` line would hold it: bare when that reads back the
+/// same, else single-quoted, else double-quoted.
+fn quote(value: &str) -> String {
+    let plain =
+        !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || "\"'#$\\".contains(c));
+    if plain {
+        return value.to_string();
+    }
+    if !value.contains(['\'', '\n']) {
+        return format!("'{value}'");
+    }
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// The `.env` files that apply to `profile`, lowest precedence first.
+pub fn env_files(root: &Path, profile: Option<&str>) -> Vec<PathBuf> {
+    let mut names = vec![".env".to_string(), ".env.local".to_string()];
+    if let Some(profile) = profile {
+        names.push(format!(".env.{profile}"));
+        names.push(format!(".env.{profile}.local"));
+    }
+    names.into_iter().map(|name| root.join(name)).collect()
+}
+
+/// The profiles the workspace has a `.env.<profile>` file for, sorted.
+pub fn profiles(root: &Path) -> Vec<String> {
+    let Ok(dir) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = dir
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let profile = name.strip_prefix(".env.")?;
+            let profile = profile.strip_suffix(".local").unwrap_or(profile);
+            let template = ["local", "example", "sample", "template", "dist"].contains(&profile);
+            (!template && !profile.is_empty()).then(|| profile.to_string())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// A resolved variable and the file it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+    pub source: PathBuf,
+    pub secret: bool,
+}
+
+impl EnvVar {
+    /// The value, or [`MASK`] for a secret.
+    pub fn display_value(&self) -> &str {
+        if self.secret {
+            MASK
+        } else {
+            &self.value
+        }
+    }
+}
+
+/// The variables of one profile, later files overriding earlier ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvVars {
+    pub profile: Option<String>,
+    pub vars: Vec<EnvVar>,
+}
+
+impl EnvVars {
+    /// The workspace's variables for `profile`; missing files are skipped.
+    pub fn resolve(root: &Path, profile: Option<&str>) -> Result<Self, PhazeError> {
+        let mut vars: Vec<EnvVar> = Vec::new();
+        for path in env_files(root, profile) {
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for entry in parse_dotenv(&text) {
+                let var = EnvVar {
+                    secret: is_secret(&entry.key, &entry.value),
+                    key: entry.key,
+                    value: entry.value,
+                    source: path.clone(),
+                };
+                match vars.iter_mut().find(|v| v.key == var.key) {
+                    Some(existing) => *existing = var,
+                    None => vars.push(var),
+                }
+            }
+        }
+        vars.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(Self {
+            profile: profile.map(String::from),
+            vars,
+        })
+    }
+
+    /// The variables of the workspace's active profile. Errors reading the
+    /// files leave them out, so a broken `.env` doesn't stop a terminal.
+    pub fn for_workspace(root: &Path) -> Self {
+        let profile = EnvSettings::load(root).profile;
+        Self::resolve(root, profile.as_deref()).unwrap_or_default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.vars
+            .iter()
+            .find(|v| v.key == key)
+            .map(|v| v.value.as_str())
+    }
+
+    /// `(key, value)` pairs to set on a process.
+    pub fn pairs(&self) -> Vec<(String, String)> {
+        self.vars
+            .iter()
+            .map(|v| (v.key.clone(), v.value.clone()))
+            .collect()
+    }
+
+    /// `text` with every secret value replaced by [`MASK`].
+    pub fn redact(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        let mut secrets: Vec<&str> = self
+            .vars
+            .iter()
+            .filter(|v| v.secret && v.value.len() >= MIN_REDACTED_LEN)
+            .map(|v| v.value.as_str())
+            .collect();
+        // Longest first, so a secret containing another is masked whole.
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        for secret in secrets {
+            if out.contains(secret) {
+                out = out.replace(secret, MASK);
+            }
+        }
+        out
+    }
+}
+
+/// Set `key` to `value` in the `.env` file at `path`, rewriting its line
+/// in place or appending one; the file is created if need be.
+pub fn set_var(path: &Path, key: &str, value: &str) -> Result<(), PhazeError> {
+    if key.is_empty() || key.contains(|c: char| c.is_whitespace() || c == '=') {
+        return Err(PhazeError::Other(format!(
+            "\"{key}\" isn't a variable name"
+        )));
+    }
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let entries = parse_dotenv(&text);
+    let new_line = format!("{key}={}", quote(value));
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    match entries.iter().rev().find(|e| e.key == key) {
+        Some(entry) => {
+            lines.splice(entry.line..=entry.end_line, [new_line]);
+        }
+        None => lines.push(new_line),
+    }
+    write_lines(path, &lines)
+}
+
+/// Remove every `key` line from the `.env` file at `path`.
+pub fn remove_var(path: &Path, key: &str) -> Result<(), PhazeError> {
+    let text = std::fs::read_to_string(path)?;
+    let entries: Vec<EnvEntry> = parse_dotenv(&text)
+        .into_iter()
+        .filter(|e| e.key == key)
+        .collect();
+    let lines: Vec<String> = text
+        .lines()
+        .enumerate()
+        .filter(|(i, _)| !entries.iter().any(|e| (e.line..=e.end_line).contains(i)))
+        .map(|(_, line)| line.to_string())
+        .collect();
+    write_lines(path, &lines)
+}
+
+fn write_lines(path: &Path, lines: &[String]) -> Result<(), PhazeError> {
+    let mut text = lines.join("\n");
+    text.push('\n');
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+/// The workspace's env settings: which profile is active.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvSettings {
+    /// `None` for `.env` alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+impl EnvSettings {
+    pub fn load(root: &Path) -> Self {
+        std::fs::read_to_string(root.join(ENV_SETTINGS_FILE))
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, root: &Path) -> Result<(), PhazeError> {
+        let path = root.join(ENV_SETTINGS_FILE);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(|e| PhazeError::Config(e.to_string()))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
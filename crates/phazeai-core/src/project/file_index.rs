@@ -0,0 +1,261 @@
+//! In-memory index of a workspace's files, for the Ctrl+P file picker.
+//!
+//! [`walk_workspace`] lists files with the `ignore` crate's parallel walker,
+//! honoring `.gitignore`, `.ignore` and `.git/info/exclude` and skipping
+//! hidden files, with no cap on the number of files. A [`FileIndex`] keeps the
+//! result and is kept current from [`FileWatcher`](super::FileWatcher) events
+//! with [`FileIndex::apply`], so the workspace is only walked once. Past its
+//! memory budget, [`FileIndex::evict_to`] lets go of the files of the
+//! top-level folders that changed least recently; a folder is listed again
+//! once something in it changes.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use ignore::gitignore::Gitignore;
+use ignore::{WalkBuilder, WalkState};
+
+use super::FileChangeEvent;
+use crate::progress::ProgressTask;
+
+/// Ignore files read in every directory, as the walker does.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+/// Every file under `dir` that isn't ignored, in no particular order.
+/// `progress` is advanced once per file.
+fn walk(dir: &Path, progress: Option<&ProgressTask>) -> Vec<PathBuf> {
+    let (tx, rx) = mpsc::channel();
+    WalkBuilder::new(dir)
+        .require_git(false)
+        .build_parallel()
+        .run(|| {
+            let tx = tx.clone();
+            Box::new(move |entry| {
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if !entry.file_type().is_some_and(|t| t.is_file()) {
+                    return WalkState::Continue;
+                }
+                if let Some(task) = progress {
+                    if task.is_cancelled() {
+                        return WalkState::Quit;
+                    }
+                    task.advance(1);
+                }
+                let _ = tx.send(entry.into_path());
+                WalkState::Continue
+            })
+        });
+    drop(tx);
+    rx.into_iter().collect()
+}
+
+/// Roughly what `path` takes up in memory.
+fn path_bytes(path: &Path) -> usize {
+    std::mem::size_of::<PathBuf>() + path.as_os_str().len()
+}
+
+/// Every file in the workspace at `root` that isn't ignored, sorted.
+pub fn walk_workspace(root: &Path, progress: Option<&ProgressTask>) -> Vec<PathBuf> {
+    let mut files = walk(root, progress);
+    files.sort();
+    files
+}
+
+#[derive(Debug, Clone)]
+pub struct FileIndex {
+    root: PathBuf,
+    files: BTreeSet<PathBuf>,
+    /// Parsed ignore files by directory, read on demand for changed paths.
+    ignores: HashMap<PathBuf, Vec<Gitignore>>,
+    /// When each top-level folder last changed, by a clock ticked per change.
+    changed: HashMap<PathBuf, u64>,
+    clock: u64,
+    /// Top-level folders whose files were evicted.
+    evicted: BTreeSet<PathBuf>,
+}
+
+impl FileIndex {
+    /// Walk `root` and index its files.
+    pub fn build(root: &Path, progress: Option<&ProgressTask>) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            files: walk(root, progress).into_iter().collect(),
+            ignores: HashMap::new(),
+            changed: HashMap::new(),
+            clock: 0,
+            evicted: BTreeSet::new(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.files.contains(path)
+    }
+
+    /// Indexed files, sorted.
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter()
+    }
+
+    /// Roughly what the indexed paths take up in memory.
+    pub fn bytes(&self) -> usize {
+        self.files.iter().map(|p| path_bytes(p)).sum()
+    }
+
+    /// Top-level folders whose files were evicted, sorted.
+    pub fn evicted(&self) -> impl Iterator<Item = &PathBuf> {
+        self.evicted.iter()
+    }
+
+    /// The top-level folder `path` is in or is, so that deleting or renaming
+    /// an evicted folder itself reaches it; `None` for files directly in the
+    /// root, which are never evicted.
+    fn segment(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let mut components = relative.components();
+        let first = self.root.join(components.next()?);
+        let folder = components.next().is_some() || self.evicted.contains(&first) || first.is_dir();
+        folder.then_some(first)
+    }
+
+    /// Let go of the files of the top-level folders that changed least
+    /// recently until at most `budget` bytes are held. Returns how many
+    /// folders were evicted.
+    pub fn evict_to(&mut self, budget: usize) -> usize {
+        let mut held = self.bytes();
+        if held <= budget {
+            return 0;
+        }
+        let mut segments: HashMap<PathBuf, usize> = HashMap::new();
+        for file in &self.files {
+            if let Some(segment) = self.segment(file) {
+                *segments.entry(segment).or_default() += path_bytes(file);
+            }
+        }
+        let mut by_age: Vec<(u64, PathBuf, usize)> = segments
+            .into_iter()
+            .map(|(segment, bytes)| {
+                let changed = self.changed.get(&segment).copied().unwrap_or(0);
+                (changed, segment, bytes)
+            })
+            .collect();
+        by_age.sort_unstable();
+        let mut evicted = 0;
+        for (_, segment, bytes) in by_age {
+            if held <= budget {
+                break;
+            }
+            self.files.retain(|f| !f.starts_with(&segment));
+            self.changed.remove(&segment);
+            self.evicted.insert(segment);
+            held -= bytes;
+            evicted += 1;
+        }
+        evicted
+    }
+
+    /// The ignore files of `dir`, parsed once.
+    fn dir_ignores(&mut self, dir: &Path) -> &[Gitignore] {
+        self.ignores.entry(dir.to_path_buf()).or_insert_with(|| {
+            IGNORE_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .filter(|file| file.is_file())
+                .map(|file| Gitignore::new(file).0)
+                .collect()
+        })
+    }
+
+    /// Whether the walker would skip `path`: outside the root, hidden, or
+    /// matched by an ignore file in the root or a directory above it.
+    fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        let relative = relative.to_path_buf();
+        if relative
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+        {
+            return true;
+        }
+        let mut dir = self.root.clone();
+        for component in relative.parent().into_iter().flat_map(|p| p.components()) {
+            let ignored = self
+                .dir_ignores(&dir)
+                .iter()
+                .any(|gi| gi.matched_path_or_any_parents(path, is_dir).is_ignore());
+            if ignored {
+                return true;
+            }
+            dir.push(component);
+        }
+        self.dir_ignores(&dir)
+            .iter()
+            .any(|gi| gi.matched_path_or_any_parents(path, is_dir).is_ignore())
+    }
+
+    /// Bring the index up to date with a change to `event.path`, whatever
+    /// the kind of change: the path is looked at as it is now. Returns
+    /// whether the index changed.
+    pub fn apply(&mut self, event: &FileChangeEvent) -> bool {
+        let path = event.path.as_path();
+        if let Some(segment) = self.segment(path) {
+            self.clock += 1;
+            self.changed.insert(segment.clone(), self.clock);
+            if self.evicted.remove(&segment) {
+                // Something in an evicted folder changed: list it again.
+                self.files.extend(walk(&segment, None));
+                return true;
+            }
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if IGNORE_FILES.contains(&name) && path.starts_with(&self.root) {
+            // Ignore rules changed: walk again rather than guess what they
+            // now hide or reveal.
+            let fresh = Self::build(&self.root, None);
+            let changed = fresh.files != self.files;
+            *self = fresh;
+            return changed;
+        }
+
+        if path.is_dir() {
+            if self.is_ignored(path, true) {
+                return false;
+            }
+            let before = self.files.len();
+            self.files.extend(walk(path, None));
+            self.files.len() != before
+        } else if path.is_file() {
+            !self.files.contains(path)
+                && !self.is_ignored(path, false)
+                && self.files.insert(path.to_path_buf())
+        } else {
+            // Gone: the path was a file or a directory of files.
+            let removed: Vec<PathBuf> = self
+                .files
+                .range(path.to_path_buf()..)
+                .take_while(|f| f.starts_with(path))
+                .cloned()
+                .collect();
+            for file in &removed {
+                self.files.remove(file);
+            }
+            !removed.is_empty()
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Deletes and moves that can be taken back.
+//!
+//! Deleting moves files and directories into the workspace's
+//! `.phazeai/trash` rather than removing them, and each delete or batch of
+//! moves is recorded in `.phazeai/trash/journal.json` as one operation.
+//! Undoing an operation puts every path it touched back where it was: the
+//! last one, or any older one still in the journal whose places are free.
+//! The agent's `delete_path`, `move_path` and `file_history` tools and the
+//! explorer's Delete all go through here.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::editing::write_atomic;
+use crate::error::PhazeError;
+
+/// The trash, relative to the workspace root.
+pub const TRASH_DIR: &str = ".phazeai/trash";
+const JOURNAL_FILE: &str = "journal.json";
+
+/// Operations kept in the journal. The trash of older ones is emptied.
+const MAX_OPERATIONS: usize = 50;
+
+/// One path that an operation moved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileStep {
+    /// `from` was moved or renamed to `to`.
+    Moved { from: PathBuf, to: PathBuf },
+    /// `from` went to the trash, at `trashed` within it.
+    Trashed { from: PathBuf, trashed: PathBuf },
+}
+
+/// A delete or a batch of moves, undone as a whole.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileOperation {
+    pub id: u64,
+    /// e.g. "Delete src/old.rs" or "Move 12 paths".
+    pub summary: String,
+    /// RFC 3339.
+    pub at: String,
+    pub steps: Vec<FileStep>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Journal {
+    next_id: u64,
+    operations: Vec<FileOperation>,
+}
+
+/// The trash and operation journal of one workspace.
+#[derive(Debug, Clone)]
+pub struct FileJournal {
+    trash: PathBuf,
+}
+
+impl FileJournal {
+    pub fn for_workspace(root: &Path) -> Self {
+        Self {
+            trash: root.join(TRASH_DIR),
+        }
+    }
+
+    pub fn trash_dir(&self) -> &Path {
+        &self.trash
+    }
+
+    fn load(&self) -> Result<Journal, PhazeError> {
+        match std::fs::read_to_string(self.trash.join(JOURNAL_FILE)) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Journal::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, journal: &Journal) -> Result<(), PhazeError> {
+        std::fs::create_dir_all(&self.trash)?;
+        write_atomic(
+            &self.trash.join(JOURNAL_FILE),
+            serde_json::to_string_pretty(journal)?.as_bytes(),
+        )?;
+        Ok(())
+    }
+
+    /// The operations that can still be undone, oldest first.
+    pub fn history(&self) -> Result<Vec<FileOperation>, PhazeError> {
+        Ok(self.load()?.operations)
+    }
+
+    /// Move `paths` to the trash as one operation.
+    pub fn delete(&self, paths: &[PathBuf]) -> Result<FileOperation, PhazeError> {
+        let paths = paths
+            .iter()
+            .map(|p| absolute(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (i, path) in paths.iter().enumerate() {
+            if std::fs::symlink_metadata(path).is_err() {
+                return Err(PhazeError::Other(format!(
+                    "{} does not exist",
+                    path.display()
+                )));
+            }
+            if self.trash.starts_with(path) || path.starts_with(&self.trash) {
+                return Err(PhazeError::Other(format!(
+                    "{} holds the trash; it can't be moved into it",
+                    path.display()
+                )));
+            }
+            if paths[..i]
+                .iter()
+                .any(|p| path.starts_with(p) || p.starts_with(path))
+            {
+                return Err(PhazeError::Other(format!(
+                    "{} is deleted twice",
+                    path.display()
+                )));
+            }
+        }
+        let summary = match paths.as_slice() {
+            [path] => format!("Delete {}", path.display()),
+            _ => format!("Delete {} paths", paths.len()),
+        };
+        self.record(summary, |id| {
+            let mut taken = Vec::new();
+            paths
+                .iter()
+                .map(|from| {
+                    let trashed = trash_name(id, from, &mut taken);
+                    FileStep::Trashed {
+                        from: from.clone(),
+                        trashed,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Move each `(from, to)` as one operation. A destination that exists is
+    /// replaced, and goes to the trash with the rest of the operation.
+    pub fn move_paths(&self, moves: &[(PathBuf, PathBuf)]) -> Result<FileOperation, PhazeError> {
+        let moves = moves
+            .iter()
+            .map(|(from, to)| Ok((absolute(from)?, absolute(to)?)))
+            .collect::<Result<Vec<_>, PhazeError>>()?;
+        for (i, (from, to)) in moves.iter().enumerate() {
+            if std::fs::symlink_metadata(from).is_err() {
+                return Err(PhazeError::Other(format!(
+                    "{} does not exist",
+                    from.display()
+                )));
+            }
+            if to.starts_with(from) {
+                return Err(PhazeError::Other(format!(
+                    "{} can't be moved into itself",
+                    from.display()
+                )));
+            }
+            if moves[..i].iter().any(|(f, _)| f == from) {
+                return Err(PhazeError::Other(format!(
+                    "{} is moved twice",
+                    from.display()
+                )));
+            }
+            if moves[..i].iter().any(|(_, t)| t == to) {
+                return Err(PhazeError::Other(format!(
+                    "Two paths are moved to {}",
+                    to.display()
+                )));
+            }
+        }
+        let summary = match moves.as_slice() {
+            [(from, to)] => format!("Move {} to {}", from.display(), to.display()),
+            _ => format!("Move {} paths", moves.len()),
+        };
+        let ordered = in_order(moves)?;
+        self.record(summary, |id| {
+            let mut taken = Vec::new();
+            let mut steps = Vec::new();
+            for (_, to) in &ordered {
+                let freed = ordered.iter().any(|(from, _)| from == to);
+                if !freed && std::fs::symlink_metadata(to).is_ok() {
+                    steps.push(FileStep::Trashed {
+                        from: to.clone(),
+                        trashed: trash_name(id, to, &mut taken),
+                    });
+                }
+            }
+            steps.extend(
+                ordered
+                    .into_iter()
+                    .map(|(from, to)| FileStep::Moved { from, to }),
+            );
+            steps
+        })
+    }
+
+    /// Carry out the steps `plan` makes for a new operation and journal it.
+    /// When a step fails, the ones before it are undone.
+    fn record(
+        &self,
+        summary: String,
+        plan: impl FnOnce(u64) -> Vec<FileStep>,
+    ) -> Result<FileOperation, PhazeError> {
+        let mut journal = self.load()?;
+        let id = journal.next_id.max(1);
+        let steps = plan(id);
+        for (done, step) in steps.iter().enumerate() {
+            let (from, to) = self.endpoints(step);
+            if let Err(e) = relocate(&from, &to) {
+                for step in steps[..done].iter().rev() {
+                    let (from, to) = self.endpoints(step);
+                    let _ = relocate(&to, &from);
+                }
+                let _ = std::fs::remove_dir_all(self.trash.join(id.to_string()));
+                return Err(PhazeError::Other(format!(
+                    "{}: {e}; nothing was changed",
+                    from.display()
+                )));
+            }
+        }
+        let operation = FileOperation {
+            id,
+            summary,
+            at: chrono::Utc::now().to_rfc3339(),
+            steps,
+        };
+        journal.next_id = id + 1;
+        journal.operations.push(operation.clone());
+        let dropped = journal.operations.len().saturating_sub(MAX_OPERATIONS);
+        for old in journal.operations.drain(..dropped) {
+            let _ = std::fs::remove_dir_all(self.trash.join(old.id.to_string()));
+        }
+        self.save(&journal)?;
+        Ok(operation)
+    }
+
+    /// Where a step moved its path from and to.
+    fn endpoints(&self, step: &FileStep) -> (PathBuf, PathBuf) {
+        match step {
+            FileStep::Moved { from, to } => (from.clone(), to.clone()),
+            FileStep::Trashed { from, trashed } => (from.clone(), self.trash.join(trashed)),
+        }
+    }
+
+    /// Put back what operation `id` moved, or the last operation's when
+    /// `id` is `None`. Nothing moves unless every path can go back.
+    pub fn undo(&self, id: Option<u64>) -> Result<FileOperation, PhazeError> {
+        let mut journal = self.load()?;
+        let index = match id {
+            Some(id) => journal
+                .operations
+                .iter()
+                .position(|op| op.id == id)
+                .ok_or_else(|| PhazeError::Other(format!("No file operation {id} to undo")))?,
+            None => journal
+                .operations
+                .len()
+                .checked_sub(1)
+                .ok_or_else(|| PhazeError::Other("No file operation to undo".into()))?,
+        };
+        let operation = journal.operations[index].clone();
+        for step in &operation.steps {
+            let (from, to) = self.endpoints(step);
+            if std::fs::symlink_metadata(&to).is_err() {
+                return Err(PhazeError::Other(format!(
+                    "Can't undo \"{}\": {} is gone",
+                    operation.summary,
+                    to.display()
+                )));
+            }
+            let vacated = operation
+                .steps
+                .iter()
+                .any(|other| self.endpoints(other).1 == from);
+            if !vacated && std::fs::symlink_metadata(&from).is_ok() {
+                return Err(PhazeError::Other(format!(
+                    "Can't undo \"{}\": {} exists again",
+                    operation.summary,
+                    from.display()
+                )));
+            }
+        }
+        for (done, step) in operation.steps.iter().enumerate().rev() {
+            let (from, to) = self.endpoints(step);
+            if let Err(e) = relocate(&to, &from) {
+                for step in &operation.steps[done + 1..] {
+                    let (from, to) = self.endpoints(step);
+                    let _ = relocate(&from, &to);
+                }
+                return Err(PhazeError::Other(format!(
+                    "Can't undo \"{}\": {}: {e}",
+                    operation.summary,
+                    from.display()
+                )));
+            }
+        }
+        journal.operations.remove(index);
+        let _ = std::fs::remove_dir_all(self.trash.join(operation.id.to_string()));
+        self.save(&journal)?;
+        Ok(operation)
+    }
+}
+
+/// `moves` ordered so that a path is moved away before another takes its
+/// place (`b -> c` before `a -> b`).
+fn in_order(mut pending: Vec<(PathBuf, PathBuf)>) -> Result<Vec<(PathBuf, PathBuf)>, PhazeError> {
+    let mut ordered = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let ready = pending
+            .iter()
+            .position(|(_, to)| !pending.iter().any(|(from, _)| from == to))
+            .ok_or_else(|| {
+                PhazeError::Other(
+                    "The moves go in a circle; move one of the paths somewhere else first".into(),
+                )
+            })?;
+        ordered.push(pending.remove(ready));
+    }
+    Ok(ordered)
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, PhazeError> {
+    Ok(std::path::absolute(path)?)
+}
+
+/// Where `path` goes in operation `id`'s part of the trash: under its own
+/// name, numbered when the operation already trashed one by that name.
+fn trash_name(id: u64, path: &Path, taken: &mut Vec<PathBuf>) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "root".into());
+    let mut candidate = PathBuf::from(id.to_string()).join(&name);
+    let mut n = 1;
+    while taken.contains(&candidate) {
+        n += 1;
+        candidate = PathBuf::from(id.to_string()).join(format!("{name}.{n}"));
+    }
+    taken.push(candidate.clone());
+    candidate
+}
+
+/// Move `from` to `to`, creating `to`'s parent, copying and removing when
+/// they are on different filesystems. The copy never overwrites anything
+/// already at `to`; if it fails, only what it created is removed.
+fn relocate(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+        result => return result,
+    }
+    let mut created = Vec::new();
+    if let Err(e) = copy_recursive(from, to, &mut created) {
+        for path in created.iter().rev() {
+            let _ = std::fs::remove_dir(path).or_else(|_| std::fs::remove_file(path));
+        }
+        return Err(e);
+    }
+    remove(from)
+}
+
+/// Copy `from` to `to`, which must not exist, pushing each path it creates
+/// onto `created` (parents before children).
+fn copy_recursive(from: &Path, to: &Path, created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let meta = std::fs::symlink_metadata(from)?;
+    let kind = meta.file_type();
+    if kind.is_dir() {
+        std::fs::create_dir(to)?;
+        created.push(to.to_path_buf());
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()), created)?;
+        }
+        return Ok(());
+    }
+    #[cfg(unix)]
+    if kind.is_symlink() {
+        std::os::unix::fs::symlink(std::fs::read_link(from)?, to)?;
+        created.push(to.to_path_buf());
+        return Ok(());
+    }
+    let mut out = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(to)?;
+    created.push(to.to_path_buf());
+    std::io::copy(&mut std::fs::File::open(from)?, &mut out)?;
+    out.set_permissions(meta.permissions())
+}
+
+fn remove(path: &Path) -> std::io::Result<()> {
+    if std::fs::symlink_metadata(path)?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
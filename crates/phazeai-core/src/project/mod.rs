@@ -1,5 +1,20 @@
+pub mod backup;
+pub mod board;
+pub mod editorconfig;
+pub mod env_vars;
+pub mod file_index;
+pub mod file_ops;
+pub mod run_config;
+pub mod search;
 pub mod watcher;
 pub mod workspace;
 
-pub use watcher::{FileChangeEvent, FileChangeKind, FileWatcher};
+pub use backup::{BackupManifest, RestoreReport};
+pub use board::{Board, BoardStore, Card, CardLink, CardNote};
+pub use editorconfig::{EditorConfig, EffectiveIndent, Indent, IndentSource, IndentStyle};
+pub use env_vars::{EnvSettings, EnvVar, EnvVars};
+pub use file_index::{walk_workspace, FileIndex};
+pub use file_ops::{FileJournal, FileOperation, FileStep};
+pub use run_config::{RunConfig, RunConfigs};
+pub use watcher::{FileChangeEvent, FileChangeKind, FileWatcher, PollMode, WatchOptions};
 pub use workspace::{find_workspace_root, WorkspaceInfo};
@@ -0,0 +1,169 @@
+//! Run configurations: the named commands of `.phazeai/launch.toml`.
+//!
+//! ```toml
+//! [[configurations]]
+//! name = "server"
+//! command = "cargo"
+//! args = ["run", "--bin", "server"]
+//! cwd = "crates/server"
+//! env = { RUST_LOG = "debug" }
+//! pre_task = "migrate"
+//! ```
+//!
+//! A configuration runs in the workspace root unless `cwd` says otherwise,
+//! with the workspace's [`EnvVars`] and then its own `env` set. `pre_task`
+//! names another configuration that has to succeed first. The IDE runs them
+//! from the top bar and `phazeai run-config <name>` from a shell.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use super::env_vars::EnvVars;
+use crate::error::PhazeError;
+
+/// The run configurations, relative to the workspace root.
+pub const RUN_CONFIG_FILE: &str = ".phazeai/launch.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Relative to the workspace root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// The configuration to run first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_task: Option<String>,
+}
+
+impl RunConfig {
+    /// The command and its arguments as a shell would show them.
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.command)
+            .chain(&self.args)
+            .map(|arg| {
+                if arg.is_empty() || arg.contains(char::is_whitespace) {
+                    format!("'{arg}'")
+                } else {
+                    arg.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The directory the command runs in.
+    pub fn cwd(&self, root: &Path) -> PathBuf {
+        match &self.cwd {
+            Some(cwd) => root.join(cwd),
+            None => root.to_path_buf(),
+        }
+    }
+
+    /// The process to start, its environment and directory set; stdio is
+    /// left to the caller.
+    pub fn command(&self, root: &Path) -> Command {
+        let mut command = Command::new(&self.command);
+        command
+            .args(&self.args)
+            .current_dir(self.cwd(root))
+            .envs(EnvVars::for_workspace(root).pairs())
+            .envs(&self.env);
+        command
+    }
+}
+
+/// The workspace's run configurations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunConfigs {
+    #[serde(default)]
+    pub configurations: Vec<RunConfig>,
+}
+
+impl RunConfigs {
+    /// The configurations of `root`, none when it has no `launch.toml`.
+    pub fn load(root: &Path) -> Result<Self, PhazeError> {
+        let text = match std::fs::read_to_string(root.join(RUN_CONFIG_FILE)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let configs: Self = toml::from_str(&text)
+            .map_err(|e| PhazeError::Config(format!("{RUN_CONFIG_FILE}: {e}")))?;
+        for (i, config) in configs.configurations.iter().enumerate() {
+            if config.name.trim().is_empty() || config.command.trim().is_empty() {
+                return Err(PhazeError::Config(format!(
+                    "{RUN_CONFIG_FILE}: configuration {} needs a name and a command",
+                    i + 1
+                )));
+            }
+            if configs.configurations[..i]
+                .iter()
+                .any(|c| c.name == config.name)
+            {
+                return Err(PhazeError::Config(format!(
+                    "{RUN_CONFIG_FILE}: two configurations are named \"{}\"",
+                    config.name
+                )));
+            }
+        }
+        Ok(configs)
+    }
+
+    pub fn save(&self, root: &Path) -> Result<(), PhazeError> {
+        let path = root.join(RUN_CONFIG_FILE);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let text = toml::to_string(self).map_err(|e| PhazeError::Config(e.to_string()))?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.configurations
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect()
+    }
+
+    /// The configuration named `name`, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&RunConfig> {
+        self.configurations
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// `name` after the chain of pre-tasks it needs, in the order to run
+    /// them.
+    pub fn plan(&self, name: &str) -> Result<Vec<&RunConfig>, PhazeError> {
+        let mut chain: Vec<&RunConfig> = Vec::new();
+        let mut next = Some(name);
+        while let Some(name) = next {
+            let config = self.get(name).ok_or_else(|| {
+                let known = match self.names() {
+                    names if names.is_empty() => format!("{RUN_CONFIG_FILE} has none"),
+                    names => format!("there are: {}", names.join(", ")),
+                };
+                PhazeError::Config(format!("No run configuration \"{name}\"; {known}"))
+            })?;
+            if chain.iter().any(|c| c.name == config.name) {
+                return Err(PhazeError::Config(format!(
+                    "Run configuration \"{}\" is its own pre-task",
+                    config.name
+                )));
+            }
+            chain.push(config);
+            next = config.pre_task.as_deref();
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+}
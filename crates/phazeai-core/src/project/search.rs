@@ -0,0 +1,183 @@
+//! Workspace text search for the Search panel.
+//!
+//! [`search_workspace`] walks the workspace with the `ignore` crate's parallel
+//! walker, honoring `.gitignore` and `.ignore` and skipping hidden and binary
+//! files, and matches every line of each file against a regex built by
+//! [`SearchOptions::matcher`]. Results are sent file by file as they are found,
+//! so the panel can show them while the search runs, and the search stops as
+//! soon as it is cancelled.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+
+use ignore::overrides::OverrideBuilder;
+use ignore::{WalkBuilder, WalkState};
+use regex::{Regex, RegexBuilder};
+
+/// The search stops once it has found this many matching lines.
+pub const MAX_MATCHES: usize = 2_000;
+
+/// A file with a NUL byte in its first this many bytes is binary.
+const BINARY_PROBE: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// The query is a regex rather than literal text.
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Lines of context kept before and after each match.
+    pub context: usize,
+    /// Globs a file must match, e.g. `*.rs` or `src/**`. Empty for all files.
+    pub include: Vec<String>,
+    /// Globs of files to leave out, e.g. `target/` or `*.lock`.
+    pub exclude: Vec<String>,
+}
+
+impl SearchOptions {
+    /// The regex matching `query` under these options.
+    pub fn matcher(&self, query: &str) -> Result<Regex, regex::Error> {
+        let pattern = if self.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        let pattern = if self.whole_word {
+            format!(r"\b(?:{pattern})\b")
+        } else {
+            pattern
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+    }
+
+    /// Split a comma-separated glob list as typed in the panel.
+    pub fn globs(list: &str) -> Vec<String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// A matching line, with the context around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMatch {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+    /// Byte ranges of the matches in `text`.
+    pub ranges: Vec<Range<usize>>,
+    /// Context lines before the match, not shared with the previous match.
+    pub before: Vec<String>,
+    /// Context lines after the match, up to the next match.
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    pub matches: Vec<LineMatch>,
+}
+
+/// The lines of `text` matching `re`. Each match keeps up to `context` lines
+/// on either side; lines already shown with one match aren't repeated with
+/// the next.
+pub fn search_text(text: &str, re: &Regex, context: usize) -> Vec<LineMatch> {
+    let lines: Vec<&str> = text.lines().collect();
+    let hits: Vec<usize> = (0..lines.len())
+        .filter(|&i| re.is_match(lines[i]))
+        .collect();
+
+    let mut shown_to = 0;
+    hits.iter()
+        .enumerate()
+        .map(|(n, &i)| {
+            let next = hits.get(n + 1).copied().unwrap_or(lines.len());
+            let before_from = i.saturating_sub(context).max(shown_to);
+            let after_to = (i + 1 + context).min(next);
+            shown_to = after_to;
+            LineMatch {
+                line: i + 1,
+                text: lines[i].to_string(),
+                ranges: re.find_iter(lines[i]).map(|m| m.range()).collect(),
+                before: lines[before_from..i]
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect(),
+                after: lines[i + 1..after_to]
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// The matches in the file at `path`, or `None` when it has none or is
+/// binary or unreadable.
+pub fn search_file(path: &Path, re: &Regex, context: usize) -> Option<FileMatches> {
+    let bytes = std::fs::read(path).ok()?;
+    if bytes[..bytes.len().min(BINARY_PROBE)].contains(&0) {
+        return None;
+    }
+    let matches = search_text(&String::from_utf8_lossy(&bytes), re, context);
+    (!matches.is_empty()).then(|| FileMatches {
+        path: path.to_path_buf(),
+        matches,
+    })
+}
+
+/// Search every file under `root` that isn't ignored or excluded, sending
+/// each file with matches to `results` as soon as it has been searched.
+/// Stops when `cancel` is set, when `results` is dropped, or after
+/// [`MAX_MATCHES`] matching lines.
+pub fn search_workspace(
+    root: &Path,
+    re: &Regex,
+    options: &SearchOptions,
+    cancel: &AtomicBool,
+    results: Sender<FileMatches>,
+) {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.include {
+        let _ = overrides.add(glob);
+    }
+    for glob in &options.exclude {
+        let _ = overrides.add(&format!("!{glob}"));
+    }
+    let mut walker = WalkBuilder::new(root);
+    walker.require_git(false);
+    if let Ok(overrides) = overrides.build() {
+        walker.overrides(overrides);
+    }
+
+    let found = AtomicUsize::new(0);
+    walker.build_parallel().run(|| {
+        let results = results.clone();
+        let found = &found;
+        Box::new(move |entry| {
+            if cancel.load(Ordering::Relaxed) || found.load(Ordering::Relaxed) >= MAX_MATCHES {
+                return WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                return WalkState::Continue;
+            }
+            let Some(file) = search_file(entry.path(), re, options.context) else {
+                return WalkState::Continue;
+            };
+            found.fetch_add(file.matches.len(), Ordering::Relaxed);
+            match results.send(file) {
+                Ok(()) => WalkState::Continue,
+                Err(_) => WalkState::Quit,
+            }
+        })
+    });
+}
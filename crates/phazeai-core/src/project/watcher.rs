@@ -1,59 +1,455 @@
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+//! File system watching.
+//!
+//! A [`FileWatcher`] watches a directory tree with the platform's native
+//! watcher, or by polling when the tree is on a network filesystem (NFS,
+//! SMB, sshfs, …) whose changes native watchers don't see. Bursts of events,
+//! like a build rewriting `target/`, are coalesced into one event per path
+//! before they are handed out. Subsystems that care about the same tree
+//! share one watcher through [`FileWatcher::shared`] and each
+//! [`subscribe`](FileWatcher::subscribe) with the globs they want.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use notify::{Config, Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 
-/// Watches a directory for file system changes and sends events through a channel.
+use crate::config::Settings;
+use crate::error::PhazeError;
+
+/// Filesystem types whose changes native watchers miss.
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb",
+    "smb2",
+    "smb3",
+    "smbfs",
+    "afs",
+    "9p",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "gpfs",
+    "davfs",
+    "webdav",
+    "ncpfs",
+    "coda",
+    "afpfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "fuse.s3fs",
+    "fuse.gcsfuse",
+];
+
+/// When to poll instead of using the native watcher.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PollMode {
+    /// Poll trees on network filesystems.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How a [`FileWatcher`] watches.
+///
+/// ```toml
+/// [watcher]
+/// follow_symlinks = false
+/// poll = "always"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchOptions {
+    /// Report changes under symlinked directories.
+    pub follow_symlinks: bool,
+    pub poll: PollMode,
+    pub poll_interval_ms: u64,
+    /// A burst ends once no event has come for this long.
+    pub coalesce_ms: u64,
+    /// A burst is handed out after this long even if it hasn't ended.
+    pub max_delay_ms: u64,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            follow_symlinks: true,
+            poll: PollMode::Auto,
+            poll_interval_ms: 2000,
+            coalesce_ms: 50,
+            max_delay_ms: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChangeEvent {
+    pub path: std::path::PathBuf,
+    pub kind: FileChangeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// Merges the events of a burst into one per path, in the order the paths
+/// first changed.
+#[derive(Debug, Default)]
+pub struct Coalescer {
+    // `None` for a path created and removed again within the burst.
+    events: Vec<(PathBuf, Option<FileChangeKind>)>,
+    index: HashMap<PathBuf, usize>,
+}
+
+impl Coalescer {
+    pub fn push(&mut self, event: FileChangeEvent) {
+        use FileChangeKind::*;
+        let Some(&i) = self.index.get(&event.path) else {
+            self.index.insert(event.path.clone(), self.events.len());
+            self.events.push((event.path, Some(event.kind)));
+            return;
+        };
+        let slot = &mut self.events[i].1;
+        *slot = match (slot.take(), event.kind) {
+            (None, next) => Some(next),
+            (Some(Created), Removed) => None,
+            (Some(Created), _) => Some(Created),
+            (Some(_), Removed) => Some(Removed),
+            // Deleted and written again, as editors save.
+            (Some(Removed), _) => Some(Modified),
+            (Some(Modified), _) => Some(Modified),
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn finish(self) -> Vec<FileChangeEvent> {
+        self.events
+            .into_iter()
+            .filter_map(|(path, kind)| Some(FileChangeEvent { path, kind: kind? }))
+            .collect()
+    }
+}
+
+struct Subscriber {
+    /// `None` for every path.
+    filter: Option<GlobSet>,
+    tx: mpsc::UnboundedSender<FileChangeEvent>,
+}
+
+struct Subscribers {
+    root: PathBuf,
+    list: Mutex<Vec<Subscriber>>,
+}
+
+impl Subscribers {
+    fn dispatch(&self, events: &[FileChangeEvent]) {
+        let Ok(mut list) = self.list.lock() else {
+            return;
+        };
+        list.retain(|sub| !sub.tx.is_closed());
+        for event in events {
+            let relative = event.path.strip_prefix(&self.root).unwrap_or(&event.path);
+            for sub in list.iter() {
+                if sub.filter.as_ref().is_none_or(|f| f.is_match(relative)) {
+                    let _ = sub.tx.send(event.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Watches a directory tree and hands its coalesced changes to subscribers.
 pub struct FileWatcher {
-    _watcher: RecommendedWatcher,
+    subscribers: Arc<Subscribers>,
+    polling: bool,
+    // Behind a mutex so the watcher can be shared between threads.
+    _watcher: Mutex<Box<dyn Watcher + Send>>,
 }
 
+static SHARED: LazyLock<Mutex<HashMap<PathBuf, Weak<FileWatcher>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 impl FileWatcher {
-    /// Start watching a directory. Returns a receiver for file change events.
+    /// Start watching a directory with the default options. Returns a
+    /// receiver for all of its file change events.
     pub fn watch(
         path: &Path,
-    ) -> Result<(Self, mpsc::UnboundedReceiver<FileChangeEvent>), crate::error::PhazeError> {
-        let (tx, rx) = mpsc::unbounded_channel();
+    ) -> Result<(Self, mpsc::UnboundedReceiver<FileChangeEvent>), PhazeError> {
+        let watcher = Self::with_options(path, &WatchOptions::default())?;
+        let rx = watcher.subscribe(&[])?;
+        Ok((watcher, rx))
+    }
 
-        let event_tx = tx.clone();
-        let mut watcher = RecommendedWatcher::new(
+    /// Start watching a directory; nothing is handed out until something
+    /// [`subscribe`](Self::subscribe)s.
+    pub fn with_options(path: &Path, options: &WatchOptions) -> Result<Self, PhazeError> {
+        let root = path.to_path_buf();
+        let polling = match options.poll {
+            PollMode::Always => true,
+            PollMode::Never => false,
+            PollMode::Auto => network_fs(&root).is_some(),
+        };
+
+        let (raw_tx, raw_rx) = std_mpsc::channel::<FileChangeEvent>();
+        let handler = {
+            let root = root.clone();
+            let follow_symlinks = options.follow_symlinks;
             move |result: Result<Event, notify::Error>| {
-                if let Ok(event) = result {
-                    let kind = match &event.kind {
-                        notify::EventKind::Create(_) => FileChangeKind::Created,
-                        notify::EventKind::Modify(_) => FileChangeKind::Modified,
-                        notify::EventKind::Remove(_) => FileChangeKind::Removed,
-                        _ => return,
-                    };
-
-                    for path in event.paths {
-                        let _ = event_tx.send(FileChangeEvent {
-                            path,
-                            kind: kind.clone(),
-                        });
+                let Ok(event) = result else {
+                    return;
+                };
+                let kind = match &event.kind {
+                    notify::EventKind::Create(_) => FileChangeKind::Created,
+                    notify::EventKind::Modify(_) => FileChangeKind::Modified,
+                    notify::EventKind::Remove(_) => FileChangeKind::Removed,
+                    _ => return,
+                };
+                for path in event.paths {
+                    if !follow_symlinks && under_symlink(&root, &path) {
+                        continue;
                     }
+                    let _ = raw_tx.send(FileChangeEvent {
+                        path,
+                        kind: kind.clone(),
+                    });
                 }
-            },
-            Config::default(),
-        )
-        .map_err(|e| crate::error::PhazeError::Other(format!("Failed to create watcher: {e}")))?;
-
+            }
+        };
+        let mut watcher: Box<dyn Watcher + Send> = if polling {
+            let config = Config::default()
+                .with_poll_interval(Duration::from_millis(options.poll_interval_ms));
+            Box::new(
+                PollWatcher::new(handler, config)
+                    .map_err(|e| PhazeError::Other(format!("Failed to create watcher: {e}")))?,
+            )
+        } else {
+            Box::new(
+                RecommendedWatcher::new(handler, Config::default())
+                    .map_err(|e| PhazeError::Other(format!("Failed to create watcher: {e}")))?,
+            )
+        };
         watcher
-            .watch(path, RecursiveMode::Recursive)
-            .map_err(|e| crate::error::PhazeError::Other(format!("Failed to watch path: {e}")))?;
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| PhazeError::Other(format!("Failed to watch path: {e}")))?;
+
+        let subscribers = Arc::new(Subscribers {
+            root,
+            list: Mutex::new(Vec::new()),
+        });
+        let coalesce = Duration::from_millis(options.coalesce_ms);
+        let max_delay = Duration::from_millis(options.max_delay_ms);
+        let weak = Arc::downgrade(&subscribers);
+        // Ends when the watcher, and with it `raw_tx`, is dropped.
+        std::thread::spawn(move || {
+            while let Ok(first) = raw_rx.recv() {
+                let mut burst = Coalescer::default();
+                burst.push(first);
+                let deadline = Instant::now() + max_delay;
+                loop {
+                    let wait = coalesce.min(deadline.saturating_duration_since(Instant::now()));
+                    match raw_rx.recv_timeout(wait) {
+                        Ok(event) => burst.push(event),
+                        Err(_) => break,
+                    }
+                }
+                let Some(subscribers) = weak.upgrade() else {
+                    return;
+                };
+                subscribers.dispatch(&burst.finish());
+            }
+        });
+
+        Ok(Self {
+            subscribers,
+            polling,
+            _watcher: Mutex::new(watcher),
+        })
+    }
 
-        Ok((Self { _watcher: watcher }, rx))
+    /// The watcher of `path` that other subsystems already use, or a new one
+    /// with the options of the settings' `[watcher]` table. It stops once
+    /// the last `Arc` to it is dropped.
+    pub fn shared(path: &Path) -> Result<Arc<Self>, PhazeError> {
+        let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut shared = SHARED
+            .lock()
+            .map_err(|e| PhazeError::Other(e.to_string()))?;
+        if let Some(watcher) = shared.get(&key).and_then(Weak::upgrade) {
+            return Ok(watcher);
+        }
+        let watcher = Arc::new(Self::with_options(path, &Settings::load().watcher)?);
+        shared.retain(|_, w| w.strong_count() > 0);
+        shared.insert(key, Arc::downgrade(&watcher));
+        Ok(watcher)
+    }
+
+    /// A receiver for the changes to paths matching any of `globs`, which
+    /// are relative to the watched directory (`**/*.rs`, `.phazeai/board.md`);
+    /// no globs receives every change. Dropping the receiver unsubscribes.
+    pub fn subscribe(
+        &self,
+        globs: &[&str],
+    ) -> Result<mpsc::UnboundedReceiver<FileChangeEvent>, PhazeError> {
+        let filter = if globs.is_empty() {
+            None
+        } else {
+            let mut set = GlobSetBuilder::new();
+            for glob in globs {
+                let glob = GlobBuilder::new(glob)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| PhazeError::Other(format!("Bad watch pattern {glob}: {e}")))?;
+                set.add(glob);
+            }
+            Some(set.build().map_err(|e| PhazeError::Other(e.to_string()))?)
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        if let Ok(mut list) = self.subscribers.list.lock() {
+            list.push(Subscriber { filter, tx });
+        }
+        Ok(rx)
+    }
+
+    /// Whether the tree is polled rather than natively watched.
+    pub fn is_polling(&self) -> bool {
+        self.polling
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.subscribers.root
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct FileChangeEvent {
-    pub path: std::path::PathBuf,
-    pub kind: FileChangeKind,
+/// Whether a directory between `root` and `path` is a symlink.
+fn under_symlink(root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let mut dir = root.to_path_buf();
+    let mut components = relative.components().peekable();
+    while let Some(component) = components.next() {
+        // The changed path itself may be a symlink.
+        if components.peek().is_none() {
+            break;
+        }
+        dir.push(component);
+        if dir
+            .symlink_metadata()
+            .is_ok_and(|m| m.file_type().is_symlink())
+        {
+            return true;
+        }
+    }
+    false
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum FileChangeKind {
-    Created,
-    Modified,
-    Removed,
+/// One line of a mount table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    pub point: PathBuf,
+    pub fs_type: String,
+}
+
+/// The mounts of Linux's `/proc/self/mounts`.
+pub fn parse_proc_mounts(text: &str) -> Vec<Mount> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let point = fields.next()?;
+            let fs_type = fields.next()?;
+            Some(Mount {
+                point: PathBuf::from(unescape_octal(point)),
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The mounts listed by macOS's and the BSDs' `mount`:
+/// `//me@nas/share on /Volumes/share (smbfs, nodev, nosuid)`.
+pub fn parse_mount_output(text: &str) -> Vec<Mount> {
+    text.lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?.trim();
+            Some(Mount {
+                point: PathBuf::from(point),
+                fs_type: fs_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `\040`-style escapes, as the kernel writes spaces in mount points.
+fn unescape_octal(text: &str) -> String {
+    let mut out = Vec::with_capacity(text.len());
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let code = (bytes[i] == b'\\')
+            .then(|| text.get(i + 1..i + 4))
+            .flatten()
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match code {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The filesystem type of the mount `path` is on.
+pub fn fs_type_of<'a>(mounts: &'a [Mount], path: &Path) -> Option<&'a str> {
+    mounts
+        .iter()
+        .filter(|m| path.starts_with(&m.point))
+        .max_by_key(|m| m.point.components().count())
+        .map(|m| m.fs_type.as_str())
+}
+
+pub fn is_network_fs_type(fs_type: &str) -> bool {
+    NETWORK_FS_TYPES.contains(&fs_type) || fs_type.starts_with("nfs")
+}
+
+/// The network filesystem `path` is on, if it is on one.
+pub fn network_fs(path: &Path) -> Option<String> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mounts = if cfg!(target_os = "linux") {
+        parse_proc_mounts(&std::fs::read_to_string("/proc/self/mounts").ok()?)
+    } else if cfg!(windows) {
+        // A UNC path, `\\server\share` (`\\?\UNC\server\share` canonicalized).
+        let text = path.to_string_lossy();
+        let unc = text.starts_with(r"\\?\UNC\")
+            || (!text.starts_with(r"\\?\") && text.starts_with(r"\\"));
+        return unc.then(|| "smb".to_string());
+    } else {
+        let output = std::process::Command::new("mount").output().ok()?;
+        parse_mount_output(&String::from_utf8_lossy(&output.stdout))
+    };
+    fs_type_of(&mounts, &path)
+        .filter(|t| is_network_fs_type(t))
+        .map(String::from)
 }
@@ -0,0 +1,95 @@
+//! Environments for `.http` files, kept in `.phazeai/http-env.toml`.
+//!
+//! ```toml
+//! default = "dev"
+//!
+//! [shared]            # in every environment
+//! api = "v2"
+//!
+//! [dev]
+//! host = "localhost:3000"
+//!
+//! [prod]
+//! host = "api.example.com"
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::PhazeError;
+
+/// The environments file, relative to the workspace root.
+pub const HTTP_ENV_FILE: &str = ".phazeai/http-env.toml";
+
+/// Variables every environment has; an environment's own override them.
+const SHARED: &str = "shared";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpEnv {
+    /// The environment used when none is chosen.
+    pub default: Option<String>,
+    pub environments: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl HttpEnv {
+    pub fn parse(text: &str) -> Result<Self, PhazeError> {
+        let table: toml::Table = toml::from_str(text)
+            .map_err(|e| PhazeError::Config(format!("{HTTP_ENV_FILE}: {e}")))?;
+        let mut env = HttpEnv::default();
+        for (key, value) in table {
+            match value {
+                toml::Value::String(name) if key == "default" => env.default = Some(name),
+                toml::Value::Table(vars) => {
+                    let vars = vars
+                        .into_iter()
+                        .map(|(name, value)| {
+                            let value = match value {
+                                toml::Value::String(s) => s,
+                                other => other.to_string(),
+                            };
+                            (name, value)
+                        })
+                        .collect();
+                    env.environments.insert(key, vars);
+                }
+                _ => {}
+            }
+        }
+        Ok(env)
+    }
+
+    /// The workspace's environments; none when it has no file.
+    pub fn load(root: &Path) -> Result<Self, PhazeError> {
+        match std::fs::read_to_string(root.join(HTTP_ENV_FILE)) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Names of the environments to choose from.
+    pub fn names(&self) -> Vec<&str> {
+        self.environments
+            .keys()
+            .map(String::as_str)
+            .filter(|name| *name != SHARED)
+            .collect()
+    }
+
+    /// The shared variables overlaid with those of `name`, or of the
+    /// default environment.
+    pub fn variables(&self, name: Option<&str>) -> Result<BTreeMap<String, String>, PhazeError> {
+        let mut vars = self.environments.get(SHARED).cloned().unwrap_or_default();
+        let Some(name) = name.or(self.default.as_deref()) else {
+            return Ok(vars);
+        };
+        let env = self.environments.get(name).ok_or_else(|| {
+            PhazeError::Config(format!(
+                "no environment \"{name}\" in {HTTP_ENV_FILE}; it has: {}",
+                self.names().join(", ")
+            ))
+        })?;
+        vars.extend(env.clone());
+        Ok(vars)
+    }
+}
@@ -0,0 +1,257 @@
+//! REST client for `.http` / `.rest` files.
+//!
+//! A file holds requests separated by `###` lines, each a request line
+//! (`POST {{host}}/users HTTP/1.1`), headers, a blank line and a body, with
+//! `# @name` comments naming them and `@var = value` lines defining
+//! variables. [`resolve`] substitutes `{{variables}}` from the file and from
+//! the chosen environment of [`HttpEnv`], plus the system variables
+//! `{{$processEnv NAME}}`, `{{$timestamp}}`, `{{$datetime}}` and `{{$uuid}}`;
+//! [`send`] runs the result on the shared HTTP client. The IDE's HTTP tab
+//! and the agent's `http_request` tool both go through here.
+
+pub mod env;
+pub mod parse;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+pub use env::{HttpEnv, HTTP_ENV_FILE};
+pub use parse::{HttpFile, HttpRequest};
+
+use crate::error::PhazeError;
+
+/// Extensions of request files.
+pub const HTTP_EXTENSIONS: &[&str] = &["http", "rest"];
+
+/// Bytes of a response body kept; the rest is cut off.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .user_agent("PhazeAI/1.0 (REST client)")
+        .build()
+        .unwrap_or_default()
+});
+
+/// The HTTP client every request shares, so connections are reused.
+pub fn client() -> &'static reqwest::Client {
+    &CLIENT
+}
+
+pub fn is_http_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| HTTP_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// The value of a `{{$…}}` system variable.
+fn system_variable(expr: &str) -> Option<String> {
+    let (name, arg) = expr.split_once(char::is_whitespace).unwrap_or((expr, ""));
+    match name {
+        "$processEnv" | "$env" => std::env::var(arg.trim()).ok(),
+        "$timestamp" => Some(chrono::Utc::now().timestamp().to_string()),
+        "$datetime" => Some(chrono::Utc::now().to_rfc3339()),
+        "$uuid" | "$guid" => Some(uuid::Uuid::new_v4().to_string()),
+        _ => None,
+    }
+}
+
+/// `text` with its `{{name}}`s replaced, or the names that have no value.
+fn substitute(text: &str, vars: &BTreeMap<String, String>) -> Result<String, Vec<String>> {
+    let mut out = String::with_capacity(text.len());
+    let mut missing = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        let expr = rest[open + 2..open + close].trim();
+        let value = if expr.starts_with('$') {
+            system_variable(expr)
+        } else {
+            vars.get(expr).cloned()
+        };
+        match value {
+            Some(value) => out.push_str(&value),
+            None => missing.push(expr.to_string()),
+        }
+        rest = &rest[open + close + 2..];
+    }
+    out.push_str(rest);
+    if missing.is_empty() {
+        Ok(out)
+    } else {
+        Err(missing)
+    }
+}
+
+/// The variables for a file's requests: `env`'s, then the file's own in
+/// order, each of which may use those before it.
+pub fn variables(
+    file: &HttpFile,
+    env: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, PhazeError> {
+    let mut vars = env;
+    for (name, value) in &file.variables {
+        let value = substitute(value, &vars).map_err(|missing| undefined(&missing))?;
+        vars.insert(name.clone(), value);
+    }
+    Ok(vars)
+}
+
+fn undefined(missing: &[String]) -> PhazeError {
+    PhazeError::Config(format!(
+        "undefined variable{} {}; define {} with `@name = value` or in {HTTP_ENV_FILE}",
+        if missing.len() == 1 { "" } else { "s" },
+        missing.join(", "),
+        if missing.len() == 1 { "it" } else { "them" },
+    ))
+}
+
+/// `request` with its variables substituted and a `< path` body read from
+/// the file, relative to `dir`.
+pub fn resolve(
+    request: &HttpRequest,
+    vars: &BTreeMap<String, String>,
+    dir: &Path,
+) -> Result<HttpRequest, PhazeError> {
+    let mut missing = Vec::new();
+    let mut sub = |text: &str| {
+        substitute(text, vars).unwrap_or_else(|names| {
+            missing.extend(names);
+            String::new()
+        })
+    };
+    let url = sub(&request.url);
+    let headers = request
+        .headers
+        .iter()
+        .map(|(k, v)| (k.clone(), sub(v)))
+        .collect();
+    let body = match request.body.trim().strip_prefix("< ") {
+        Some(path) => std::fs::read_to_string(dir.join(path.trim()))?,
+        None => sub(&request.body),
+    };
+    if !missing.is_empty() {
+        missing.sort();
+        missing.dedup();
+        return Err(undefined(&missing));
+    }
+    Ok(HttpRequest {
+        url,
+        headers,
+        body,
+        ..request.clone()
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// `OK`, `Not Found`, …
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// Bytes in the whole body, including any cut off.
+    pub size: usize,
+    pub elapsed: Duration,
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `200 OK · 84 ms · 1.2 KB`.
+    pub fn summary(&self) -> String {
+        let size = if self.size < 1024 {
+            format!("{} B", self.size)
+        } else {
+            format!("{:.1} KB", self.size as f64 / 1024.0)
+        };
+        format!(
+            "{} {} · {} ms · {size}",
+            self.status,
+            self.reason,
+            self.elapsed.as_millis()
+        )
+    }
+
+    /// The body, indented when it is JSON.
+    pub fn pretty_body(&self) -> String {
+        let json_type = self
+            .header("content-type")
+            .is_some_and(|t| t.contains("json"));
+        let looks_json = matches!(self.body.trim_start().chars().next(), Some('{' | '['));
+        if json_type || looks_json {
+            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&self.body) {
+                if let Ok(pretty) = serde_json::to_string_pretty(&value) {
+                    return pretty;
+                }
+            }
+        }
+        self.body.clone()
+    }
+}
+
+/// Send a resolved request.
+pub async fn send(request: &HttpRequest) -> Result<HttpResponse, PhazeError> {
+    let method = reqwest::Method::from_bytes(request.method.as_bytes())
+        .map_err(|_| PhazeError::Other(format!("bad method {}", request.method)))?;
+    let mut builder = client().request(method, &request.url);
+    for (key, value) in &request.headers {
+        builder = builder.header(key, value);
+    }
+    if !request.body.is_empty() {
+        builder = builder.body(request.body.clone());
+    }
+    let started = Instant::now();
+    let response = builder.send().await?;
+    let status = response.status();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                String::from_utf8_lossy(v.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+    let bytes = response.bytes().await?;
+    let elapsed = started.elapsed();
+    let kept = &bytes[..bytes.len().min(MAX_BODY_BYTES)];
+    Ok(HttpResponse {
+        status: status.as_u16(),
+        reason: status.canonical_reason().unwrap_or("").to_string(),
+        headers,
+        body: String::from_utf8_lossy(kept).into_owned(),
+        size: bytes.len(),
+        elapsed,
+    })
+}
+
+/// Resolve and send the request of `file` (the file at `path`) with the
+/// variables of environment `env` of the workspace at `root`.
+pub async fn run(
+    root: &Path,
+    path: &Path,
+    file: &HttpFile,
+    request: &HttpRequest,
+    env: Option<&str>,
+) -> Result<(HttpRequest, HttpResponse), PhazeError> {
+    let vars = variables(file, HttpEnv::load(root)?.variables(env)?)?;
+    let dir = path.parent().unwrap_or(root);
+    let resolved = resolve(request, &vars, dir)?;
+    let response = send(&resolved).await?;
+    Ok((resolved, response))
+}
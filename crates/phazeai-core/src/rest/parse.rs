@@ -0,0 +1,196 @@
+//! Parsing `.http` / `.rest` files.
+
+use std::path::Path;
+
+use crate::error::PhazeError;
+
+const METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+];
+
+/// One request of a file, as written — `{{variables}}` not yet substituted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// From a `# @name` comment, else the text after `###`.
+    pub name: Option<String>,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// Empty when there is none. A body of `< path` is read from that file,
+    /// relative to the `.http` file.
+    pub body: String,
+    /// 0-based line of the request line.
+    pub line: u32,
+    /// 0-based lines the request's block spans, both inclusive.
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+impl HttpRequest {
+    /// The name, or `METHOD url`.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("{} {}", self.method, self.url),
+        }
+    }
+}
+
+/// A parsed `.http` file: its `@name = value` variables and its requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpFile {
+    pub variables: Vec<(String, String)>,
+    pub requests: Vec<HttpRequest>,
+}
+
+/// `name` from `@name = value`.
+fn variable(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.strip_prefix('@')?.split_once('=')?;
+    let name = name.trim();
+    (!name.is_empty() && !name.contains(char::is_whitespace))
+        .then(|| (name.to_string(), value.trim().to_string()))
+}
+
+/// The text of a `#` or `//` comment.
+fn comment(line: &str) -> Option<&str> {
+    line.strip_prefix('#')
+        .or_else(|| line.strip_prefix("//"))
+        .map(str::trim)
+}
+
+/// `(method, url)` of a request line; a bare URL is a GET.
+fn request_line(line: &str) -> (String, String) {
+    let (first, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let (method, target) = if METHODS.contains(&first.to_ascii_uppercase().as_str()) {
+        (first.to_ascii_uppercase(), rest.trim())
+    } else {
+        ("GET".to_string(), line)
+    };
+    let url = match target.rsplit_once(char::is_whitespace) {
+        Some((url, version)) if version.starts_with("HTTP/") => url.trim_end(),
+        _ => target,
+    };
+    (method, url.to_string())
+}
+
+impl HttpFile {
+    pub fn parse(text: &str) -> Self {
+        let mut file = HttpFile::default();
+        let lines: Vec<&str> = text.lines().collect();
+        let mut start = 0;
+        while start < lines.len() {
+            let end = (start + 1..lines.len())
+                .find(|&i| lines[i].trim_start().starts_with("###"))
+                .unwrap_or(lines.len());
+            file.parse_block(&lines, start, end);
+            start = end;
+        }
+        file
+    }
+
+    /// The block of `lines[start..end]`, which may open with a `###` line.
+    fn parse_block(&mut self, lines: &[&str], start: usize, end: usize) {
+        let mut name = lines[start]
+            .trim()
+            .strip_prefix("###")
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty());
+        let mut i = start;
+        if lines[start].trim_start().starts_with("###") {
+            i += 1;
+        }
+
+        // Comments, `@name`s and variables before the request line.
+        let request_at = loop {
+            if i >= end {
+                return;
+            }
+            let line = lines[i].trim();
+            if let Some(text) = comment(line) {
+                if let Some(n) = text.strip_prefix("@name") {
+                    let n = n.trim();
+                    if !n.is_empty() {
+                        name = Some(n.to_string());
+                    }
+                }
+            } else if let Some(var) = variable(line) {
+                self.variables.push(var);
+            } else if !line.is_empty() {
+                break i;
+            }
+            i += 1;
+        };
+
+        let (method, mut url) = request_line(lines[request_at].trim());
+        i = request_at + 1;
+        // Query parameters continued on the next lines.
+        while i < end {
+            let line = lines[i].trim();
+            if !(line.starts_with('?') || line.starts_with('&')) {
+                break;
+            }
+            url.push_str(line);
+            i += 1;
+        }
+
+        let mut headers = Vec::new();
+        while i < end {
+            let line = lines[i].trim();
+            i += 1;
+            if line.is_empty() {
+                break;
+            }
+            if comment(line).is_some() {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                headers.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        let mut body: Vec<&str> = lines[i.min(end)..end].to_vec();
+        while body.last().is_some_and(|l| l.trim().is_empty()) {
+            body.pop();
+        }
+
+        self.requests.push(HttpRequest {
+            name,
+            method,
+            url,
+            headers,
+            body: body.join("\n"),
+            line: request_at as u32,
+            start_line: start as u32,
+            end_line: end.saturating_sub(1) as u32,
+        });
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PhazeError> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// The request whose block holds 0-based `line`.
+    pub fn request_at(&self, line: u32) -> Option<&HttpRequest> {
+        self.requests
+            .iter()
+            .find(|r| r.start_line <= line && line <= r.end_line)
+    }
+
+    /// The request named `name`, ignoring case; a request without a name
+    /// matches on its `METHOD url`.
+    pub fn find(&self, name: &str) -> Option<&HttpRequest> {
+        let name = name.trim();
+        self.requests
+            .iter()
+            .find(|r| {
+                r.name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            })
+            .or_else(|| {
+                self.requests
+                    .iter()
+                    .find(|r| r.label().eq_ignore_ascii_case(name))
+            })
+    }
+}
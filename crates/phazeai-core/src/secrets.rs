@@ -0,0 +1,38 @@
+//! Secrets in the OS keychain — macOS Keychain, Windows Credential Manager
+//! or the Secret Service — filed by service and account.
+//!
+//! Every call may block: on Linux it goes over D-Bus, and on macOS it can
+//! ask the user to unlock the keychain. Keep it off the UI thread.
+
+use crate::error::PhazeError;
+
+fn entry(service: &str, account: &str) -> Result<keyring::Entry, PhazeError> {
+    keyring::Entry::new(service, account).map_err(keychain_error)
+}
+
+fn keychain_error(e: keyring::Error) -> PhazeError {
+    PhazeError::Config(format!("keychain: {e}"))
+}
+
+pub fn store_secret(service: &str, account: &str, secret: &str) -> Result<(), PhazeError> {
+    entry(service, account)?
+        .set_password(secret)
+        .map_err(keychain_error)
+}
+
+/// The secret filed under `service` and `account`; `None` when there's
+/// none.
+pub fn secret(service: &str, account: &str) -> Result<Option<String>, PhazeError> {
+    match entry(service, account)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keychain_error(e)),
+    }
+}
+
+pub fn forget_secret(service: &str, account: &str) -> Result<(), PhazeError> {
+    match entry(service, account)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(keychain_error(e)),
+    }
+}
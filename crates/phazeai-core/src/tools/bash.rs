@@ -7,6 +7,10 @@ use tokio::sync::Mutex;
 
 const MAX_OUTPUT_CHARS: usize = 30000;
 
+/// Fence languages treated as runnable shell commands.
+pub(crate) const SHELL_FENCES: &[&str] =
+    &["bash", "sh", "shell", "zsh", "console", "shell-session"];
+
 pub struct BashTool {
     cwd: Arc<Mutex<PathBuf>>,
 }
@@ -70,7 +74,11 @@ impl Tool for BashTool {
         let wrapped_command = format!("{command} && echo \"PWD:$(pwd)\"");
 
         let mut cmd = tokio::process::Command::new("bash");
-        cmd.arg("-c").arg(&wrapped_command).current_dir(&cwd);
+        // Stopping the agent mid-command drops this future; take the shell with it.
+        cmd.arg("-c")
+            .arg(&wrapped_command)
+            .current_dir(&cwd)
+            .kill_on_drop(true);
 
         let output =
             tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), cmd.output())
@@ -127,3 +135,46 @@ impl Tool for BashTool {
         }))
     }
 }
+
+/// The shell commands in a Markdown reply's fenced code blocks tagged `bash`,
+/// `sh`, `console` and the like, one entry per block. In `console` blocks, and
+/// in any block that uses `$ ` prompts, only the prompt lines are kept
+/// (without the prompt); the rest is sample output.
+pub fn shell_code_blocks(markdown: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, Vec<&str>)> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        match current.take() {
+            None => {
+                if let Some(info) = trimmed.strip_prefix("```") {
+                    let lang = info.split_whitespace().next().unwrap_or("");
+                    current = Some((lang.to_ascii_lowercase(), Vec::new()));
+                }
+            }
+            Some((lang, body)) if trimmed.starts_with("```") => {
+                if SHELL_FENCES.contains(&lang.as_str()) {
+                    let command = block_command(&lang, &body);
+                    if !command.is_empty() {
+                        blocks.push(command);
+                    }
+                }
+            }
+            Some((lang, mut body)) => {
+                body.push(line);
+                current = Some((lang, body));
+            }
+        }
+    }
+    blocks
+}
+
+fn block_command(lang: &str, body: &[&str]) -> String {
+    let prompt = |l: &str| l.trim_start().strip_prefix("$ ").map(str::to_string);
+    let lines: Vec<String> = if lang == "console" || body.iter().any(|l| prompt(l).is_some()) {
+        body.iter().filter_map(|l| prompt(l)).collect()
+    } else {
+        body.iter().map(|l| l.to_string()).collect()
+    };
+    lines.join("\n").trim().to_string()
+}
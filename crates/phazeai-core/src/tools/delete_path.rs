@@ -1,7 +1,9 @@
 use crate::error::PhazeError;
+use crate::project::file_ops::FileJournal;
+use crate::tools::file_history::operation_json;
 use crate::tools::traits::{Tool, ToolResult};
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Critical paths that must never be deleted
 const PROTECTED_PATHS: &[&str] = &[
@@ -9,7 +11,59 @@ const PROTECTED_PATHS: &[&str] = &[
     "/sys", "/lib", "/lib64", "/opt",
 ];
 
-pub struct DeletePathTool;
+/// Moves files and directories to the workspace trash, where
+/// `file_history` can restore them.
+pub struct DeletePathTool {
+    journal: FileJournal,
+}
+
+impl DeletePathTool {
+    pub fn new(journal: FileJournal) -> Self {
+        Self { journal }
+    }
+}
+
+impl Default for DeletePathTool {
+    fn default() -> Self {
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::new(FileJournal::for_workspace(&root))
+    }
+}
+
+fn check_not_protected(path: &Path, path_str: &str) -> Result<(), PhazeError> {
+    if !path.exists() && !path.is_symlink() {
+        return Err(PhazeError::tool(
+            "delete_path",
+            format!("Path does not exist: {path_str}"),
+        ));
+    }
+
+    // Safety: refuse to delete critical paths
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| PhazeError::tool("delete_path", format!("Cannot resolve path: {e}")))?;
+    let canonical_str = canonical.to_string_lossy();
+
+    for protected in PROTECTED_PATHS {
+        if canonical_str.as_ref() == *protected {
+            return Err(PhazeError::tool(
+                "delete_path",
+                format!("REFUSED: Cannot delete protected path: {protected}"),
+            ));
+        }
+    }
+
+    // Also protect home directory itself
+    if let Some(home) = dirs::home_dir() {
+        if canonical == home {
+            return Err(PhazeError::tool(
+                "delete_path",
+                "REFUSED: Cannot delete home directory",
+            ));
+        }
+    }
+    Ok(())
+}
 
 #[async_trait::async_trait]
 impl Tool for DeletePathTool {
@@ -18,7 +72,9 @@ impl Tool for DeletePathTool {
     }
 
     fn description(&self) -> &str {
-        "Delete a file or directory. Refuses to delete critical system paths. For directories, deletes recursively."
+        "Delete files or directories by moving them to the workspace trash (.phazeai/trash). \
+         Refuses to delete critical system paths. Several paths given together are one \
+         operation, which `file_history` can undo."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -28,77 +84,42 @@ impl Tool for DeletePathTool {
                 "path": {
                     "type": "string",
                     "description": "Path to delete"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Several paths to delete as one operation, instead of 'path'"
                 }
-            },
-            "required": ["path"]
+            }
         })
     }
 
     async fn execute(&self, params: Value) -> ToolResult {
-        let path_str = params
-            .get("path")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| PhazeError::tool("delete_path", "Missing required parameter: path"))?;
-
-        let path = Path::new(path_str);
-
-        if !path.exists() {
+        let mut paths: Vec<&str> = params
+            .get("paths")
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+        paths.extend(params.get("path").and_then(|v| v.as_str()));
+        if paths.is_empty() {
             return Err(PhazeError::tool(
                 "delete_path",
-                format!("Path does not exist: {path_str}"),
+                "Missing required parameter: path",
             ));
         }
 
-        // Safety: refuse to delete critical paths
-        let canonical = path
-            .canonicalize()
-            .map_err(|e| PhazeError::tool("delete_path", format!("Cannot resolve path: {e}")))?;
-        let canonical_str = canonical.to_string_lossy();
-
-        for protected in PROTECTED_PATHS {
-            if canonical_str.as_ref() == *protected {
-                return Err(PhazeError::tool(
-                    "delete_path",
-                    format!("REFUSED: Cannot delete protected path: {protected}"),
-                ));
-            }
-        }
-
-        // Also protect home directory itself
-        if let Some(home) = dirs::home_dir() {
-            if canonical == home {
-                return Err(PhazeError::tool(
-                    "delete_path",
-                    "REFUSED: Cannot delete home directory",
-                ));
-            }
+        for path_str in &paths {
+            check_not_protected(Path::new(path_str), path_str)?;
         }
 
-        if path.is_file() || path.is_symlink() {
-            tokio::fs::remove_file(path).await.map_err(|e| {
-                PhazeError::tool("delete_path", format!("Failed to delete file: {e}"))
-            })?;
-
-            Ok(serde_json::json!({
-                "success": true,
-                "type": "file",
-                "deleted": path_str,
-            }))
-        } else if path.is_dir() {
-            tokio::fs::remove_dir_all(path).await.map_err(|e| {
-                PhazeError::tool("delete_path", format!("Failed to delete directory: {e}"))
-            })?;
+        let paths: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        let operation = self
+            .journal
+            .delete(&paths)
+            .map_err(|e| PhazeError::tool("delete_path", e.to_string()))?;
 
-            Ok(serde_json::json!({
-                "success": true,
-                "type": "directory",
-                "deleted": path_str,
-            }))
-        } else {
-            Err(PhazeError::tool(
-                "delete_path",
-                format!("Unsupported file type: {path_str}"),
-            ))
-        }
+        let mut result = operation_json(&operation);
+        result["success"] = Value::Bool(true);
+        Ok(result)
     }
 }
@@ -117,9 +117,11 @@ impl Tool for EditTool {
             ));
         };
 
-        tokio::fs::write(path, &new_content).await.map_err(|e| {
-            PhazeError::tool("edit_file", format!("Failed to write '{}': {}", path, e))
-        })?;
+        super::file::write_file(path, new_content)
+            .await
+            .map_err(|e| {
+                PhazeError::tool("edit_file", format!("Failed to write '{}': {}", path, e))
+            })?;
 
         Ok(serde_json::json!({
             "path": path,
@@ -1,8 +1,18 @@
+use crate::editing::write_atomic;
 use crate::error::PhazeError;
 use crate::tools::traits::{Tool, ToolResult};
 use serde_json::Value;
 use std::path::Path;
 
+/// Write `content` to `path` atomically, so an editor or a crash never
+/// sees the file half-written, off the async runtime's threads.
+pub(crate) async fn write_file(path: &str, content: String) -> std::io::Result<()> {
+    let path = Path::new(path).to_path_buf();
+    tokio::task::spawn_blocking(move || write_atomic(&path, content.as_bytes()))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
 pub struct ReadFileTool;
 
 #[async_trait::async_trait]
@@ -128,7 +138,7 @@ impl Tool for WriteFileTool {
             }
         }
 
-        tokio::fs::write(path, content).await.map_err(|e| {
+        write_file(path, content.to_string()).await.map_err(|e| {
             PhazeError::tool("write_file", format!("Failed to write '{}': {}", path, e))
         })?;
 
@@ -0,0 +1,109 @@
+use crate::error::PhazeError;
+use crate::project::file_ops::{FileJournal, FileOperation, FileStep};
+use crate::tools::traits::{Tool, ToolResult};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Lists the deletes and moves made through `delete_path`, `move_path` and
+/// the explorer, and undoes them.
+pub struct FileHistoryTool {
+    journal: FileJournal,
+}
+
+impl FileHistoryTool {
+    pub fn new(journal: FileJournal) -> Self {
+        Self { journal }
+    }
+}
+
+impl Default for FileHistoryTool {
+    fn default() -> Self {
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::new(FileJournal::for_workspace(&root))
+    }
+}
+
+/// An operation as the file tools report it.
+pub(crate) fn operation_json(operation: &FileOperation) -> Value {
+    let steps: Vec<Value> = operation
+        .steps
+        .iter()
+        .map(|step| match step {
+            FileStep::Moved { from, to } => serde_json::json!({
+                "moved": from.to_string_lossy(),
+                "to": to.to_string_lossy(),
+            }),
+            FileStep::Trashed { from, .. } => serde_json::json!({
+                "trashed": from.to_string_lossy(),
+            }),
+        })
+        .collect();
+    serde_json::json!({
+        "operation": operation.id,
+        "summary": operation.summary,
+        "at": operation.at,
+        "steps": steps,
+    })
+}
+
+#[async_trait::async_trait]
+impl Tool for FileHistoryTool {
+    fn name(&self) -> &str {
+        "file_history"
+    }
+
+    fn description(&self) -> &str {
+        "Deletes and moves made with delete_path and move_path (and in the explorer), newest \
+         last. 'list' shows them; 'undo' puts back everything one operation moved — the last \
+         one, or the one with the given id — restoring deleted files from the trash."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "description": "The action to perform",
+                    "enum": ["list", "undo"]
+                },
+                "id": {
+                    "type": "integer",
+                    "description": "Operation id from 'list' to undo. Default: the last operation."
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                PhazeError::tool("file_history", "Missing required parameter: action")
+            })?;
+        match action {
+            "list" => {
+                let operations: Vec<Value> =
+                    self.journal.history()?.iter().map(operation_json).collect();
+                Ok(serde_json::json!({ "operations": operations }))
+            }
+            "undo" => {
+                let id = params.get("id").and_then(|v| v.as_u64());
+                let operation = self
+                    .journal
+                    .undo(id)
+                    .map_err(|e| PhazeError::tool("file_history", e.to_string()))?;
+                Ok(serde_json::json!({
+                    "success": true,
+                    "undone": operation_json(&operation),
+                }))
+            }
+            other => Err(PhazeError::tool(
+                "file_history",
+                format!("Unknown action '{other}'; use 'list' or 'undo'"),
+            )),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::error::PhazeError;
+use crate::project::walk_workspace;
+use crate::rest::{self, HttpFile};
+use crate::tools::traits::{Tool, ToolResult};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Response body characters returned to the model.
+const MAX_BODY_CHARS: usize = 20_000;
+
+/// Runs a named request from the workspace's `.http` / `.rest` files.
+pub struct HttpRequestTool {
+    root: PathBuf,
+}
+
+impl HttpRequestTool {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The file holding the request named `name`: `file` when given, else
+    /// the first request file in the workspace that has one.
+    fn locate(&self, name: &str, file: Option<&str>) -> Result<(PathBuf, HttpFile), PhazeError> {
+        if let Some(file) = file {
+            let path = self.root.join(file);
+            return Ok((path.clone(), HttpFile::load(&path)?));
+        }
+        walk_workspace(&self.root, None)
+            .into_iter()
+            .filter(|path| rest::is_http_file(path))
+            .filter_map(|path| HttpFile::load(&path).ok().map(|f| (path, f)))
+            .find(|(_, f)| f.find(name).is_some())
+            .ok_or_else(|| {
+                PhazeError::tool(
+                    "http_request",
+                    format!("No request named \"{name}\" in the workspace's .http/.rest files"),
+                )
+            })
+    }
+}
+
+impl Default for HttpRequestTool {
+    fn default() -> Self {
+        Self::new(std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[async_trait::async_trait]
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Run a request saved in the project's .http / .rest files, by its name (from a \
+         '# @name' comment or the '###' heading), with {{variables}} filled in from the file \
+         and the environment in .phazeai/http-env.toml. Returns the status, headers and body. \
+         Use it to check an API endpoint the project defines; use 'fetch' for arbitrary URLs."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Name of the request"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Workspace-relative .http file to look in (default: all of them)"
+                },
+                "env": {
+                    "type": "string",
+                    "description": "Environment from .phazeai/http-env.toml (default: its 'default')"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let name = params
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| PhazeError::tool("http_request", "Missing required parameter: name"))?;
+        let file = params.get("file").and_then(|v| v.as_str());
+        let env = params.get("env").and_then(|v| v.as_str());
+
+        let (path, http_file) = self.locate(name, file)?;
+        let request = http_file.find(name).ok_or_else(|| {
+            let names: Vec<String> = http_file.requests.iter().map(|r| r.label()).collect();
+            PhazeError::tool(
+                "http_request",
+                format!(
+                    "No request named \"{name}\" in {}; it has: {}",
+                    relative(&self.root, &path),
+                    names.join(", ")
+                ),
+            )
+        })?;
+        let (sent, response) = rest::run(&self.root, &path, &http_file, request, env)
+            .await
+            .map_err(|e| PhazeError::tool("http_request", e.to_string()))?;
+
+        let body = response.pretty_body();
+        let truncated = body.chars().count() > MAX_BODY_CHARS;
+        let body: String = body.chars().take(MAX_BODY_CHARS).collect();
+        let headers: serde_json::Map<String, Value> = response
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::from(v.as_str())))
+            .collect();
+        Ok(serde_json::json!({
+            "request": format!("{} {}", sent.method, sent.url),
+            "file": relative(&self.root, &path),
+            "status": response.status,
+            "reason": response.reason,
+            "elapsed_ms": response.elapsed.as_millis() as u64,
+            "headers": headers,
+            "body": body,
+            "truncated": truncated,
+        }))
+    }
+}
@@ -1,16 +1,30 @@
+use crate::context::memory::{self, MemoryStore};
 use crate::error::PhazeError;
 use crate::tools::traits::{Tool, ToolResult};
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
 use std::path::PathBuf;
 
-#[derive(Serialize, Deserialize, Default)]
-struct MemoryStore {
-    items: HashMap<String, String>,
+/// Saves, searches and forgets facts in the project's `.phazeai/memory.md`.
+pub struct MemoryTool {
+    store: MemoryStore,
 }
 
-pub struct MemoryTool;
+impl MemoryTool {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+impl Default for MemoryTool {
+    fn default() -> Self {
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::new(MemoryStore::for_workspace(&root))
+    }
+}
+
+fn memory_json(m: &memory::Memory) -> Value {
+    serde_json::json!({ "id": m.id, "text": m.text })
+}
 
 #[async_trait::async_trait]
 impl Tool for MemoryTool {
@@ -19,7 +33,11 @@ impl Tool for MemoryTool {
     }
 
     fn description(&self) -> &str {
-        "Auto-memory storage. Use this to persist facts, project knowledge, patterns, and contextual memory permanently across sessions. Action can be 'store', 'search', or 'list'."
+        "Long-term project memory. Save durable facts about this project that future sessions \
+         should know (conventions, chosen libraries and versions, decisions, gotchas), e.g. \
+         \"we use sqlx 0.8, never diesel\". Relevant memories are added to your instructions \
+         automatically. Actions: 'save', 'search', 'list', 'forget'. Don't save secrets or \
+         facts that only matter to the current task."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -28,16 +46,20 @@ impl Tool for MemoryTool {
             "properties": {
                 "action": {
                     "type": "string",
-                    "description": "The action to perform: 'store', 'search', or 'list'",
-                    "enum": ["store", "search", "list"]
+                    "description": "The action to perform",
+                    "enum": ["save", "search", "list", "forget"]
                 },
-                "key": {
+                "content": {
                     "type": "string",
-                    "description": "The memory key to store under or search for (e.g., 'auth_architecture', 'db_schema_notes'). Required for 'store' and 'search'."
+                    "description": "The fact to save, as one short self-contained sentence. Required for 'save'."
                 },
-                "content": {
+                "query": {
                     "type": "string",
-                    "description": "The content to permanently save. Required for 'store'."
+                    "description": "Words to look for. Required for 'search'."
+                },
+                "id": {
+                    "type": "integer",
+                    "description": "Memory id from 'list' or 'search'. Required for 'forget'."
                 }
             },
             "required": ["action"]
@@ -49,76 +71,50 @@ impl Tool for MemoryTool {
             .get("action")
             .and_then(|v| v.as_str())
             .ok_or_else(|| PhazeError::tool("memory", "Missing required parameter: action"))?;
-
-        let memory_dir = if let Some(home) = dirs::home_dir() {
-            home.join(".phazeai")
-        } else {
-            std::env::current_dir()
-                .unwrap_or_else(|_| PathBuf::from("."))
-                .join(".phazeai")
-        };
-        let memory_file = memory_dir.join("memory.json");
-
-        if !memory_dir.exists() {
-            let _ = tokio::fs::create_dir_all(&memory_dir).await;
-        }
-
-        let mut store: MemoryStore = if memory_file.exists() {
-            let data = tokio::fs::read_to_string(&memory_file).await.map_err(|e| {
-                PhazeError::tool("memory", format!("Failed to read memory file: {}", e))
-            })?;
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            MemoryStore::default()
-        };
+        let str_param = |name: &str| params.get(name).and_then(|v| v.as_str());
 
         match action {
-            "store" => {
-                let key = params
-                    .get("key")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| PhazeError::tool("memory", "Missing 'key' for store action"))?;
-                let content = params
-                    .get("content")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| {
-                        PhazeError::tool("memory", "Missing 'content' for store action")
-                    })?;
-
-                store.items.insert(key.to_string(), content.to_string());
-
-                let serialized = serde_json::to_string_pretty(&store)
-                    .map_err(|e| PhazeError::tool("memory", format!("Failed to serialize: {e}")))?;
-                tokio::fs::write(&memory_file, serialized)
-                    .await
-                    .map_err(|e| {
-                        PhazeError::tool("memory", format!("Failed to save memory file: {}", e))
-                    })?;
-
+            // "store" was the action name before memories became per-project.
+            "save" | "store" => {
+                let content = str_param("content")
+                    .ok_or_else(|| PhazeError::tool("memory", "Missing 'content' for save"))?;
+                let added = self.store.add(content)?;
                 Ok(serde_json::json!({
                     "success": true,
-                    "message": format!("Stored memory under key: {}", key),
+                    "message": if added { "Saved to project memory" } else { "Already remembered" },
                 }))
             }
             "search" => {
-                let key_query = params.get("key").and_then(|v| v.as_str()).unwrap_or("");
-
-                let mut results = HashMap::new();
-                for (k, v) in &store.items {
-                    if k.contains(key_query) || key_query.is_empty() {
-                        results.insert(k.clone(), v.clone());
-                    }
-                }
-
-                Ok(serde_json::json!({
-                    "results": results
-                }))
+                let query = str_param("query")
+                    .or_else(|| str_param("key"))
+                    .unwrap_or("");
+                let results: Vec<Value> = self
+                    .store
+                    .relevant(query, 10)?
+                    .iter()
+                    .map(memory_json)
+                    .collect();
+                Ok(serde_json::json!({ "results": results }))
             }
             "list" => {
-                let keys: Vec<String> = store.items.keys().cloned().collect();
-                Ok(serde_json::json!({
-                    "memory_keys": keys
-                }))
+                let memories: Vec<Value> = self.store.list()?.iter().map(memory_json).collect();
+                Ok(serde_json::json!({ "memories": memories }))
+            }
+            "forget" => {
+                let id = params
+                    .get("id")
+                    .and_then(|v| v.as_u64())
+                    .ok_or_else(|| PhazeError::tool("memory", "Missing 'id' for forget"))?;
+                match self.store.remove(id as usize)? {
+                    Some(removed) => Ok(serde_json::json!({
+                        "success": true,
+                        "message": format!("Forgot: {}", removed.text),
+                    })),
+                    None => Err(PhazeError::tool(
+                        "memory",
+                        format!("No memory with id {id}"),
+                    )),
+                }
             }
             _ => Err(PhazeError::tool(
                 "memory",
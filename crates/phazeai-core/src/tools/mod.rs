@@ -9,9 +9,11 @@ mod download;
 mod edit;
 mod fetch;
 mod file;
+mod file_history;
 mod find_path;
 mod glob;
 mod grep;
+mod http_request;
 mod list;
 pub mod mcp_bridge;
 mod memory;
@@ -19,11 +21,14 @@ mod move_path;
 mod now;
 mod open;
 mod screenshot;
+mod sql_query;
+mod task_board;
 mod traits;
 mod web_search;
 
 pub use approval::{ToolApprovalManager, ToolApprovalMode, ToolPermission};
-pub use bash::BashTool;
+pub(crate) use bash::SHELL_FENCES;
+pub use bash::{shell_code_blocks, BashTool};
 pub use browse::BrowseTool;
 pub use copy_path::CopyPathTool;
 pub use create_directory::CreateDirectoryTool;
@@ -33,14 +38,18 @@ pub use download::DownloadTool;
 pub use edit::EditTool;
 pub use fetch::FetchTool;
 pub use file::{ReadFileTool, WriteFileTool};
+pub use file_history::FileHistoryTool;
 pub use find_path::FindPathTool;
 pub use glob::GlobTool;
 pub use grep::GrepTool;
+pub use http_request::HttpRequestTool;
 pub use list::ListFilesTool;
 pub use memory::MemoryTool;
 pub use move_path::MovePathTool;
 pub use now::NowTool;
 pub use open::OpenTool;
 pub use screenshot::ScreenshotTool;
+pub use sql_query::SqlQueryTool;
+pub use task_board::TaskBoardTool;
 pub use traits::*;
 pub use web_search::WebSearchTool;
@@ -1,9 +1,34 @@
 use crate::error::PhazeError;
+use crate::project::file_ops::FileJournal;
+use crate::tools::file_history::operation_json;
 use crate::tools::traits::{Tool, ToolResult};
 use serde_json::Value;
-use std::path::Path;
+use std::path::PathBuf;
 
-pub struct MovePathTool;
+/// Moves and renames paths, one or many at a time, as operations
+/// `file_history` can undo.
+pub struct MovePathTool {
+    journal: FileJournal,
+}
+
+impl MovePathTool {
+    pub fn new(journal: FileJournal) -> Self {
+        Self { journal }
+    }
+}
+
+impl Default for MovePathTool {
+    fn default() -> Self {
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::new(FileJournal::for_workspace(&root))
+    }
+}
+
+fn pair(value: &Value) -> Option<(PathBuf, PathBuf)> {
+    let source = value.get("source")?.as_str()?;
+    let destination = value.get("destination")?.as_str()?;
+    Some((PathBuf::from(source), PathBuf::from(destination)))
+}
 
 #[async_trait::async_trait]
 impl Tool for MovePathTool {
@@ -12,7 +37,9 @@ impl Tool for MovePathTool {
     }
 
     fn description(&self) -> &str {
-        "Move or rename a file or directory. Works across filesystems by falling back to copy+delete."
+        "Move or rename a file or directory, or many at once with 'moves'. Works across \
+         filesystems by falling back to copy+delete. An existing destination is replaced and \
+         kept in the trash. Each call is one operation that `file_history` can undo."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -26,78 +53,58 @@ impl Tool for MovePathTool {
                 "destination": {
                     "type": "string",
                     "description": "Destination path"
+                },
+                "moves": {
+                    "type": "array",
+                    "description": "Several moves to make as one operation, instead of 'source' and 'destination'",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "source": { "type": "string" },
+                            "destination": { "type": "string" }
+                        },
+                        "required": ["source", "destination"]
+                    }
                 }
-            },
-            "required": ["source", "destination"]
+            }
         })
     }
 
     async fn execute(&self, params: Value) -> ToolResult {
-        let source = params
-            .get("source")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| PhazeError::tool("move_path", "Missing required parameter: source"))?;
-
-        let destination = params
-            .get("destination")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| {
-                PhazeError::tool("move_path", "Missing required parameter: destination")
-            })?;
-
-        let source_path = Path::new(source);
-        let dest_path = Path::new(destination);
-
-        if !source_path.exists() {
-            return Err(PhazeError::tool(
-                "move_path",
-                format!("Source path does not exist: {source}"),
-            ));
+        let mut moves = Vec::new();
+        if let Some(list) = params.get("moves").and_then(|v| v.as_array()) {
+            for value in list {
+                moves.push(pair(value).ok_or_else(|| {
+                    PhazeError::tool(
+                        "move_path",
+                        "Each entry of 'moves' needs 'source' and 'destination'",
+                    )
+                })?);
+            }
         }
-
-        // Ensure parent directory exists
-        if let Some(parent) = dest_path.parent() {
-            tokio::fs::create_dir_all(parent).await.map_err(|e| {
-                PhazeError::tool("move_path", format!("Failed to create parent dirs: {e}"))
-            })?;
+        if params.get("source").is_some() || moves.is_empty() {
+            let source = params
+                .get("source")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    PhazeError::tool("move_path", "Missing required parameter: source")
+                })?;
+            let destination = params
+                .get("destination")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| {
+                    PhazeError::tool("move_path", "Missing required parameter: destination")
+                })?;
+            moves.push((PathBuf::from(source), PathBuf::from(destination)));
         }
 
-        // Try rename first (fast, same filesystem)
-        match tokio::fs::rename(source_path, dest_path).await {
-            Ok(()) => Ok(serde_json::json!({
-                "success": true,
-                "source": source,
-                "destination": destination,
-                "method": "rename",
-            })),
-            Err(_) => {
-                // Fallback: copy then delete (cross-filesystem)
-                if source_path.is_file() {
-                    tokio::fs::copy(source_path, dest_path).await.map_err(|e| {
-                        PhazeError::tool("move_path", format!("Failed to copy: {e}"))
-                    })?;
-                    tokio::fs::remove_file(source_path).await.map_err(|e| {
-                        PhazeError::tool("move_path", format!("Failed to remove source: {e}"))
-                    })?;
-                } else {
-                    // For directories, use recursive copy then remove
-                    super::copy_path::copy_dir_recursive(source_path, dest_path)
-                        .await
-                        .map_err(|e| {
-                            PhazeError::tool("move_path", format!("Failed to copy dir: {e}"))
-                        })?;
-                    tokio::fs::remove_dir_all(source_path).await.map_err(|e| {
-                        PhazeError::tool("move_path", format!("Failed to remove source dir: {e}"))
-                    })?;
-                }
+        let operation = self
+            .journal
+            .move_paths(&moves)
+            .map_err(|e| PhazeError::tool("move_path", e.to_string()))?;
 
-                Ok(serde_json::json!({
-                    "success": true,
-                    "source": source,
-                    "destination": destination,
-                    "method": "copy_and_delete",
-                }))
-            }
-        }
+        let mut result = operation_json(&operation);
+        result["success"] = Value::Bool(true);
+        Ok(result)
     }
 }
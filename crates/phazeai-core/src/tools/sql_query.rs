@@ -0,0 +1,183 @@
+use crate::config::Settings;
+use crate::db::{Database, DatabaseProfile, TableInfo};
+use crate::error::PhazeError;
+use crate::tools::traits::{Tool, ToolResult};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Rows returned to the model.
+const MAX_TOOL_ROWS: usize = 200;
+
+/// Queries the databases saved in the settings. Only read-only statements
+/// run unless a profile sets `agent_writes`.
+pub struct SqlQueryTool {
+    profiles: Vec<DatabaseProfile>,
+    /// SQLite paths are relative to it.
+    root: PathBuf,
+}
+
+impl SqlQueryTool {
+    pub fn new(profiles: Vec<DatabaseProfile>, root: impl Into<PathBuf>) -> Self {
+        Self {
+            profiles,
+            root: root.into(),
+        }
+    }
+
+    /// The profile named `name`, or the only one there is.
+    fn profile(&self, name: Option<&str>) -> Result<&DatabaseProfile, PhazeError> {
+        let names = || {
+            let names: Vec<&str> = self.profiles.iter().map(|p| p.name.as_str()).collect();
+            names.join(", ")
+        };
+        match name {
+            Some(name) => self
+                .profiles
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    PhazeError::tool(
+                        "sql_query",
+                        format!("No database named \"{name}\"; there are: {}", names()),
+                    )
+                }),
+            None if self.profiles.len() == 1 => Ok(&self.profiles[0]),
+            None if self.profiles.is_empty() => Err(PhazeError::tool(
+                "sql_query",
+                "No databases are configured; the user can add one in the DB tab",
+            )),
+            None => Err(PhazeError::tool(
+                "sql_query",
+                format!("Say which database: {}", names()),
+            )),
+        }
+    }
+}
+
+impl Default for SqlQueryTool {
+    fn default() -> Self {
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::new(Settings::load().databases, root)
+    }
+}
+
+fn table_json(table: &TableInfo) -> Value {
+    serde_json::json!({
+        "name": table.qualified_name(),
+        "view": table.is_view,
+    })
+}
+
+#[async_trait::async_trait]
+impl Tool for SqlQueryTool {
+    fn name(&self) -> &str {
+        "sql_query"
+    }
+
+    fn description(&self) -> &str {
+        "Query a database the user has connected (SQLite, PostgreSQL or MySQL). \
+         Actions: 'tables' lists its tables and views, 'describe' gives a table's columns, \
+         'query' runs one SQL statement and returns up to 200 rows. Statements must be \
+         read-only (SELECT, WITH, EXPLAIN, SHOW, PRAGMA) unless the user allowed writes \
+         for that database."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        let names: Vec<&str> = self.profiles.iter().map(|p| p.name.as_str()).collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "description": "The action to perform (default: query)",
+                    "enum": ["query", "tables", "describe"]
+                },
+                "database": {
+                    "type": "string",
+                    "description": format!(
+                        "The database to use: {} (may be left out when there is one)",
+                        if names.is_empty() { "none configured".to_string() } else { names.join(", ") }
+                    )
+                },
+                "sql": {
+                    "type": "string",
+                    "description": "The statement to run (for 'query')"
+                },
+                "table": {
+                    "type": "string",
+                    "description": "The table, as listed by 'tables' (for 'describe')"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let tool_error = |e: PhazeError| PhazeError::tool("sql_query", e.to_string());
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .unwrap_or("query");
+        let profile = self.profile(params.get("database").and_then(|v| v.as_str()))?;
+        let mut db = Database::open(profile, &self.root).map_err(tool_error)?;
+        if !profile.agent_writes {
+            db = db.read_only();
+        }
+
+        match action {
+            "tables" => {
+                let tables = db.tables().await.map_err(tool_error)?;
+                let tables: Vec<Value> = tables.iter().map(table_json).collect();
+                Ok(serde_json::json!({ "database": db.name, "tables": tables }))
+            }
+            "describe" => {
+                let name = params
+                    .get("table")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| PhazeError::tool("sql_query", "'describe' needs a table"))?;
+                let tables = db.tables().await.map_err(tool_error)?;
+                let table = tables
+                    .iter()
+                    .find(|t| t.qualified_name() == name || t.name == name)
+                    .ok_or_else(|| {
+                        PhazeError::tool("sql_query", format!("No table \"{name}\" in {}", db.name))
+                    })?;
+                let columns: Vec<Value> = db
+                    .columns(table)
+                    .await
+                    .map_err(tool_error)?
+                    .iter()
+                    .map(|c| {
+                        serde_json::json!({
+                            "name": c.name,
+                            "type": c.data_type,
+                            "nullable": c.nullable,
+                            "primary_key": c.primary_key,
+                        })
+                    })
+                    .collect();
+                Ok(serde_json::json!({
+                    "table": table.qualified_name(),
+                    "columns": columns,
+                }))
+            }
+            "query" => {
+                let sql = params
+                    .get("sql")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| PhazeError::tool("sql_query", "'query' needs sql"))?;
+                let result = db.query(sql, MAX_TOOL_ROWS).await.map_err(tool_error)?;
+                Ok(serde_json::json!({
+                    "database": db.name,
+                    "columns": result.columns,
+                    "rows": result.rows,
+                    "affected": result.affected,
+                    "truncated": result.truncated,
+                }))
+            }
+            other => Err(PhazeError::tool(
+                "sql_query",
+                format!("Unknown action: {other}"),
+            )),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::error::PhazeError;
+use crate::project::board::{Board, BoardStore, Card, CardLink};
+use crate::tools::traits::{Tool, ToolResult};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// Creates, updates and closes cards on the project's task board in
+/// `.phazeai/tasks.json`.
+pub struct TaskBoardTool {
+    store: BoardStore,
+    /// The conversation the agent is running in; cards it creates link to it.
+    conversation: Option<String>,
+}
+
+impl TaskBoardTool {
+    pub fn new(store: BoardStore) -> Self {
+        Self {
+            store,
+            conversation: None,
+        }
+    }
+
+    pub fn with_conversation(mut self, id: impl Into<String>) -> Self {
+        self.conversation = Some(id.into());
+        self
+    }
+}
+
+impl Default for TaskBoardTool {
+    fn default() -> Self {
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::new(BoardStore::for_workspace(&root))
+    }
+}
+
+fn card_json(card: &Card) -> Value {
+    let links: Vec<String> = card.links.iter().map(CardLink::label).collect();
+    serde_json::json!({
+        "id": card.id,
+        "title": card.title,
+        "description": card.description,
+        "column": card.column,
+        "links": links,
+        "last_note": card.notes.last().map(|n| n.text.clone()),
+    })
+}
+
+fn board_json(board: &Board) -> Value {
+    let columns: Vec<Value> = board
+        .columns
+        .iter()
+        .map(|column| {
+            let cards: Vec<Value> = board.cards_in(column).map(card_json).collect();
+            serde_json::json!({ "name": column, "cards": cards })
+        })
+        .collect();
+    serde_json::json!({ "columns": columns })
+}
+
+#[async_trait::async_trait]
+impl Tool for TaskBoardTool {
+    fn name(&self) -> &str {
+        "task_board"
+    }
+
+    fn description(&self) -> &str {
+        "The project's task board, shared with the user. Use it to track multi-step work: \
+         'create' a card per task, 'move' it to \"In Progress\" when you start, add a 'note' \
+         as you make progress, and 'close' it when done. Cards can point at a file and line. \
+         Actions: 'list', 'create', 'update', 'move', 'note', 'close'."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "description": "The action to perform",
+                    "enum": ["list", "create", "update", "move", "note", "close"]
+                },
+                "id": {
+                    "type": "integer",
+                    "description": "Card id from 'list' or 'create'. Required except for 'list' and 'create'."
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Short imperative title. Required for 'create'."
+                },
+                "description": {
+                    "type": "string",
+                    "description": "What the task involves."
+                },
+                "column": {
+                    "type": "string",
+                    "description": "Column to put the card in, e.g. \"To Do\", \"In Progress\", \"Done\". Required for 'move'."
+                },
+                "file": {
+                    "type": "string",
+                    "description": "Workspace-relative file to link the card to."
+                },
+                "line": {
+                    "type": "integer",
+                    "description": "1-based line in 'file'."
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Progress to record on the card. Required for 'note'; optional for the others."
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let action = params
+            .get("action")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| PhazeError::tool("task_board", "Missing required parameter: action"))?;
+        let str_param = |name: &str| params.get(name).and_then(|v| v.as_str());
+        let id = || {
+            params
+                .get("id")
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| PhazeError::tool("task_board", format!("Missing 'id' for {action}")))
+        };
+        let file_link = str_param("file").map(|path| CardLink::File {
+            path: path.to_string(),
+            line: params
+                .get("line")
+                .and_then(|v| v.as_u64())
+                .map(|l| l as u32),
+        });
+        let note = str_param("note").unwrap_or("");
+
+        let card = match action {
+            "list" => return Ok(board_json(&self.store.load()?)),
+            "create" => {
+                let title = str_param("title")
+                    .ok_or_else(|| PhazeError::tool("task_board", "Missing 'title' for create"))?;
+                self.store.update(|board| {
+                    let card = board.add_card(
+                        title,
+                        str_param("description").unwrap_or(""),
+                        str_param("column"),
+                    )?;
+                    if let Some(link) = file_link {
+                        card.link(link);
+                    }
+                    if let Some(id) = &self.conversation {
+                        card.link(CardLink::Conversation { id: id.clone() });
+                    }
+                    let id = card.id;
+                    board.add_note(id, note)?;
+                    Ok(id)
+                })?
+            }
+            "update" => {
+                let id = id()?;
+                self.store.update(|board| {
+                    let card = board.card_mut(id)?;
+                    if let Some(title) = str_param("title").filter(|t| !t.trim().is_empty()) {
+                        card.title = title.trim().to_string();
+                    }
+                    if let Some(description) = str_param("description") {
+                        card.description = description.trim().to_string();
+                    }
+                    if let Some(link) = file_link {
+                        card.link(link);
+                    }
+                    let moved =
+                        str_param("column").filter(|c| !card.column.eq_ignore_ascii_case(c.trim()));
+                    if let Some(column) = moved {
+                        board.move_card(id, column, None)?;
+                    }
+                    board.add_note(id, note)?;
+                    Ok(id)
+                })?
+            }
+            "move" => {
+                let id = id()?;
+                let column = str_param("column")
+                    .ok_or_else(|| PhazeError::tool("task_board", "Missing 'column' for move"))?;
+                self.store.update(|board| {
+                    board.move_card(id, column, None)?;
+                    board.add_note(id, note)?;
+                    Ok(id)
+                })?
+            }
+            "note" => {
+                let id = id()?;
+                if note.trim().is_empty() {
+                    return Err(PhazeError::tool("task_board", "Missing 'note' for note"));
+                }
+                self.store
+                    .update(|board| board.add_note(id, note).map(|_| id))?
+            }
+            "close" => {
+                let id = id()?;
+                self.store.update(|board| {
+                    board.add_note(id, note)?;
+                    board.close_card(id)?;
+                    Ok(id)
+                })?
+            }
+            _ => {
+                return Err(PhazeError::tool(
+                    "task_board",
+                    format!("Unknown action: {}", action),
+                ))
+            }
+        };
+        let board = self.store.load()?;
+        let card = board
+            .card(card)
+            .ok_or_else(|| PhazeError::tool("task_board", "The card disappeared"))?;
+        Ok(serde_json::json!({ "success": true, "card": card_json(card) }))
+    }
+}
@@ -55,6 +55,20 @@ impl ToolRegistry {
     pub fn definitions(&self) -> Vec<ToolDefinition> {
         self.tools.values().map(|t| t.to_definition()).collect()
     }
+
+    /// Keep only the tools `keep` accepts, e.g. to honour the tools a
+    /// conversation has switched off.
+    pub fn with_filter(mut self, keep: impl Fn(&dyn Tool) -> bool) -> Self {
+        self.tools.retain(|_, tool| keep(tool.as_ref()));
+        self
+    }
+
+    /// Names of the registered tools, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.tools.keys().cloned().collect();
+        names.sort();
+        names
+    }
 }
 
 impl Default for ToolRegistry {
@@ -71,15 +85,19 @@ impl Default for ToolRegistry {
         // New tools
         registry.register(Box::new(super::FindPathTool));
         registry.register(Box::new(super::FetchTool));
+        registry.register(Box::new(super::HttpRequestTool::default()));
+        registry.register(Box::new(super::SqlQueryTool::default()));
         registry.register(Box::new(super::WebSearchTool));
         registry.register(Box::new(super::CopyPathTool));
-        registry.register(Box::new(super::MovePathTool));
-        registry.register(Box::new(super::DeletePathTool));
+        registry.register(Box::new(super::MovePathTool::default()));
+        registry.register(Box::new(super::DeletePathTool::default()));
+        registry.register(Box::new(super::FileHistoryTool::default()));
         registry.register(Box::new(super::CreateDirectoryTool));
         registry.register(Box::new(super::NowTool));
         registry.register(Box::new(super::OpenTool));
         registry.register(Box::new(super::DiagnosticsTool));
-        registry.register(Box::new(super::MemoryTool));
+        registry.register(Box::new(super::MemoryTool::default()));
+        registry.register(Box::new(super::TaskBoardTool::default()));
         registry.register(Box::new(super::BrowseTool));
         registry.register(Box::new(super::DownloadTool));
         registry.register(Box::new(super::ScreenshotTool));
@@ -98,7 +116,7 @@ impl ToolRegistry {
         registry.register(Box::new(super::ListFilesTool));
         registry.register(Box::new(super::FindPathTool));
         registry.register(Box::new(super::NowTool));
-        registry.register(Box::new(super::MemoryTool));
+        registry.register(Box::new(super::MemoryTool::default()));
         registry
     }
 
@@ -113,9 +131,12 @@ impl ToolRegistry {
         registry.register(Box::new(super::FindPathTool));
         registry.register(Box::new(super::NowTool));
         registry.register(Box::new(super::FetchTool));
+        registry.register(Box::new(super::HttpRequestTool::default()));
+        registry.register(Box::new(super::SqlQueryTool::default()));
         registry.register(Box::new(super::WebSearchTool));
         registry.register(Box::new(super::DiagnosticsTool));
-        registry.register(Box::new(super::MemoryTool));
+        registry.register(Box::new(super::MemoryTool::default()));
+        registry.register(Box::new(super::TaskBoardTool::default()));
         registry.register(Box::new(super::BashTool::default()));
         registry.register(Box::new(super::BrowseTool));
         registry.register(Box::new(super::DownloadTool));
@@ -0,0 +1,406 @@
+//! Self-update: which release the configured channel is offered, fetching
+//! and verifying its binary, and staging it to be swapped in at the next
+//! launch.
+//!
+//! The release feed lists each release's version and channel, with one
+//! asset per binary and platform. Each asset is signed with Ed25519 over a
+//! [`manifest`] of its version, channel, binary, platform and SHA-256, so a
+//! signed binary can't be replayed as another release, channel or
+//! platform. An asset is only staged once its checksum matches, the
+//! signature checks out against the release key compiled in from
+//! `PHAZEAI_RELEASE_KEY` and it's newer than the running build; builds
+//! without a key can check for updates but won't install them. The binary
+//! stays under [`updates_dir`] until [`apply_staged`] verifies it again and
+//! replaces the installed one, first thing at the next launch.
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::audit::compare_versions;
+use crate::constants::endpoints;
+use crate::error::PhazeError;
+
+/// The version of this build.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The Ed25519 public key release binaries are signed with, base64; set
+/// when building a release.
+const RELEASE_KEY: Option<&str> = option_env!("PHAZEAI_RELEASE_KEY");
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Stable,
+    /// Pre-releases, as well as everything on stable.
+    Beta,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 2] = [Self::Stable, Self::Beta];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.label().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Whether a release on `channel` is offered to this one.
+    pub fn includes(self, channel: Channel) -> bool {
+        self == Self::Beta || channel == Self::Stable
+    }
+}
+
+/// `[updates]` in the config.
+///
+/// ```toml
+/// [updates]
+/// channel = "beta"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateSettings {
+    pub channel: Channel,
+    /// Look for a newer release when the IDE starts.
+    pub check_on_launch: bool,
+    pub feed_url: String,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            channel: Channel::Stable,
+            check_on_launch: true,
+            feed_url: endpoints::RELEASE_FEED_URL.to_string(),
+        }
+    }
+}
+
+/// The release feed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseFeed {
+    #[serde(default)]
+    pub releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub version: String,
+    pub channel: Channel,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+/// One binary of a release, for one platform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Asset {
+    /// `phazeai` or `phazeai-ide`.
+    pub binary: String,
+    /// As [`target`] names it.
+    pub target: String,
+    pub url: String,
+    /// Hex SHA-256 of the binary.
+    pub sha256: String,
+    /// Base64 Ed25519 signature of the asset's [`manifest`].
+    pub signature: String,
+}
+
+impl ReleaseFeed {
+    /// The newest release offered to `channel` that's newer than `current`.
+    pub fn latest(&self, channel: Channel, current: &str) -> Option<&Release> {
+        self.releases
+            .iter()
+            .filter(|r| channel.includes(r.channel))
+            .filter(|r| compare_versions(&r.version, current) == Ordering::Greater)
+            .max_by(|a, b| compare_versions(&a.version, &b.version))
+    }
+}
+
+impl Release {
+    pub fn asset(&self, binary: &str, target: &str) -> Option<&Asset> {
+        self.assets
+            .iter()
+            .find(|a| a.binary == binary && a.target == target)
+    }
+}
+
+/// This platform as assets name it, e.g. `x86_64-linux`.
+pub fn target() -> String {
+    format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
+
+fn http_client() -> Result<reqwest::Client, PhazeError> {
+    Ok(reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .user_agent(concat!("PhazeAI/", env!("CARGO_PKG_VERSION"), " (updates)"))
+        .build()?)
+}
+
+pub async fn fetch_feed(url: &str) -> Result<ReleaseFeed, PhazeError> {
+    let response = http_client()?.get(url).send().await?.error_for_status()?;
+    Ok(response.json().await?)
+}
+
+/// The release `settings` would update this build to, if there is one.
+pub async fn check(settings: &UpdateSettings) -> Result<Option<Release>, PhazeError> {
+    let feed = fetch_feed(&settings.feed_url).await?;
+    Ok(feed.latest(settings.channel, CURRENT_VERSION).cloned())
+}
+
+/// The release key of this build, decoded.
+pub fn release_key() -> Option<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(RELEASE_KEY?.trim())
+        .ok()
+}
+
+/// What the signature of `asset`, in release `version` on `channel`,
+/// covers: one `key value` line each for the version, channel, binary,
+/// target and checksum.
+pub fn manifest(version: &str, channel: Channel, asset: &Asset) -> String {
+    format!(
+        "phazeai-release\nversion {version}\nchannel {}\nbinary {}\ntarget {}\nsha256 {}\n",
+        channel.label(),
+        asset.binary,
+        asset.target,
+        asset.sha256.trim().to_ascii_lowercase()
+    )
+}
+
+/// Check `bytes`, `asset` of release `version` on `channel`, before
+/// installing it over `current`: the checksum must match, the manifest
+/// signature must check out with the Ed25519 public `key`, the asset must
+/// be for this platform and the version newer than `current`.
+pub fn verify(
+    bytes: &[u8],
+    version: &str,
+    channel: Channel,
+    asset: &Asset,
+    key: &[u8],
+    current: &str,
+) -> Result<(), PhazeError> {
+    let digest = hex::encode(Sha256::digest(bytes));
+    if !digest.eq_ignore_ascii_case(asset.sha256.trim()) {
+        return Err(PhazeError::Other(format!(
+            "checksum mismatch for {}: expected {}, got {digest}",
+            asset.url, asset.sha256
+        )));
+    }
+    let signature = base64::engine::general_purpose::STANDARD
+        .decode(asset.signature.trim())
+        .map_err(|e| PhazeError::Other(format!("malformed signature for {}: {e}", asset.url)))?;
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, key)
+        .verify(manifest(version, channel, asset).as_bytes(), &signature)
+        .map_err(|_| PhazeError::Other(format!("bad signature for {}", asset.url)))?;
+    if asset.target != target() {
+        return Err(PhazeError::Other(format!(
+            "{} is for {}, not {}",
+            asset.url,
+            asset.target,
+            target()
+        )));
+    }
+    if compare_versions(version, current) != Ordering::Greater {
+        return Err(PhazeError::Other(format!(
+            "{} is version {version}, which isn't newer than {current}",
+            asset.url
+        )));
+    }
+    Ok(())
+}
+
+/// The release key, or why there isn't one.
+fn require_release_key() -> Result<Vec<u8>, PhazeError> {
+    release_key().ok_or_else(|| {
+        PhazeError::Other(
+            "this build has no release key to verify updates with; \
+             install new versions from the release page or your package manager"
+                .into(),
+        )
+    })
+}
+
+/// Fetch `asset` of `release` and verify it against the release key.
+pub async fn download(release: &Release, asset: &Asset) -> Result<Vec<u8>, PhazeError> {
+    let key = require_release_key()?;
+    let response = http_client()?
+        .get(&asset.url)
+        .send()
+        .await?
+        .error_for_status()?;
+    let bytes = response.bytes().await?;
+    verify(
+        &bytes,
+        &release.version,
+        release.channel,
+        asset,
+        &key,
+        CURRENT_VERSION,
+    )?;
+    Ok(bytes.to_vec())
+}
+
+/// A verified binary waiting for the next launch, with what it was
+/// verified against so it can be checked again before it's installed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Staged {
+    pub version: String,
+    pub channel: Channel,
+    pub asset: Asset,
+    pub path: PathBuf,
+}
+
+/// Where updates are staged.
+pub fn updates_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("phazeai")
+        .join("updates")
+}
+
+fn record_path(dir: &Path, binary: &str) -> PathBuf {
+    dir.join(format!("{binary}.json"))
+}
+
+/// Whether `name`, from the feed, is safe to put in a file name: letters,
+/// digits, `.`, `-`, `+` and `_`, with no `..`.
+fn safe_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains("..")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_'))
+}
+
+/// Keep `bytes`, `asset` of release `version` on `channel`, in `dir` for
+/// the next launch, replacing whatever was staged for its binary before.
+pub fn stage(
+    dir: &Path,
+    version: &str,
+    channel: Channel,
+    asset: &Asset,
+    bytes: &[u8],
+) -> Result<Staged, PhazeError> {
+    let binary = &asset.binary;
+    if !safe_file_name(binary) || !safe_file_name(version) {
+        return Err(PhazeError::Other(format!(
+            "refusing to stage {binary} {version}: not a valid binary name and version"
+        )));
+    }
+    std::fs::create_dir_all(dir)?;
+    if let Some(previous) = staged(dir, binary) {
+        let _ = std::fs::remove_file(previous.path);
+    }
+    let staged = Staged {
+        version: version.to_string(),
+        channel,
+        asset: asset.clone(),
+        path: dir.join(format!("{binary}-{version}")),
+    };
+    std::fs::write(&staged.path, bytes)?;
+    std::fs::write(
+        record_path(dir, binary),
+        serde_json::to_string_pretty(&staged)?,
+    )?;
+    Ok(staged)
+}
+
+/// What's staged for `binary` in `dir`.
+pub fn staged(dir: &Path, binary: &str) -> Option<Staged> {
+    let record = std::fs::read_to_string(record_path(dir, binary)).ok()?;
+    serde_json::from_str(&record).ok()
+}
+
+/// Replace `exe` with what's staged for `binary` in `dir`, once it
+/// verifies against `key` again and is newer than `current`. The staged
+/// copy is gone afterwards either way, so a failed swap isn't retried
+/// every launch.
+pub fn apply_staged(
+    dir: &Path,
+    binary: &str,
+    exe: &Path,
+    current: &str,
+    key: &[u8],
+) -> Result<Option<Staged>, PhazeError> {
+    let Some(staged) = staged(dir, binary) else {
+        return Ok(None);
+    };
+    let _ = std::fs::remove_file(record_path(dir, binary));
+    let result = if compare_versions(&staged.version, current) == Ordering::Greater {
+        std::fs::read(&staged.path)
+            .map_err(PhazeError::from)
+            .and_then(|bytes| {
+                verify(
+                    &bytes,
+                    &staged.version,
+                    staged.channel,
+                    &staged.asset,
+                    key,
+                    current,
+                )?;
+                swap_in(&bytes, exe)
+            })
+            .map(|()| Some(staged.clone()))
+    } else {
+        Ok(None)
+    };
+    let _ = std::fs::remove_file(&staged.path);
+    result
+}
+
+/// Write `bytes` next to `exe` and rename them over `exe`; the running
+/// process keeps the file it was started from.
+fn swap_in(bytes: &[u8], exe: &Path) -> Result<(), PhazeError> {
+    let next = exe.with_extension("new");
+    std::fs::write(&next, bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&next, std::fs::Permissions::from_mode(0o755))?;
+    }
+    // Windows won't replace a running executable, but will rename it; put
+    // it back if the new one can't take its place.
+    #[cfg(windows)]
+    let old = exe.with_extension("old");
+    #[cfg(windows)]
+    {
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(exe, &old)?;
+    }
+    std::fs::rename(&next, exe).map_err(|e| {
+        let _ = std::fs::remove_file(&next);
+        #[cfg(windows)]
+        let _ = std::fs::rename(&old, exe);
+        PhazeError::Other(format!("couldn't replace {}: {e}", exe.display()))
+    })
+}
+
+/// Start `exe` again with this process's arguments, in place of this
+/// process where the platform allows. Only returns on failure.
+pub fn restart(exe: &Path) -> std::io::Error {
+    let mut command = std::process::Command::new(exe);
+    command.args(std::env::args_os().skip(1));
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.exec()
+    }
+    #[cfg(not(unix))]
+    match command.spawn() {
+        Ok(_) => std::process::exit(0),
+        Err(e) => e,
+    }
+}
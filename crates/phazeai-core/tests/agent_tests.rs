@@ -1,21 +1,29 @@
-use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use phazeai_core::agent::schedule::{run_scheduled, unattended_approval};
+use phazeai_core::agent::{CronSchedule, Job, JobId, JobQueue, JobStatus, ScheduledJob};
+use phazeai_core::llm::{ReasoningItem, ReasoningKind};
 use phazeai_core::{
-    Agent, AgentEvent, LlmClient, LlmResponse, Message, PhazeError, Role, StreamEvent, Tool,
-    ToolDefinition, ToolRegistry, ToolResult,
+    Agent, AgentEvent, ConversationStore, LlmClient, LlmResponse, Message, PhazeError, Role,
+    StreamEvent, Tool, ToolDefinition, ToolRegistry, ToolResult,
 };
 use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::mpsc::unbounded_channel;
 
 /// Mock LLM that returns pre-programmed stream event sequences.
 struct MockLlm {
     responses: Arc<Mutex<Vec<Vec<StreamEvent>>>>,
+    /// Every request's messages, in order.
+    requests: Arc<Mutex<Vec<Vec<Message>>>>,
 }
 
 impl MockLlm {
     fn new(responses: Vec<Vec<StreamEvent>>) -> Self {
         Self {
             responses: Arc::new(Mutex::new(responses)),
+            requests: Arc::default(),
         }
     }
 }
@@ -36,9 +44,10 @@ impl LlmClient for MockLlm {
 
     async fn chat_stream(
         &self,
-        _messages: &[Message],
+        messages: &[Message],
         _tools: &[ToolDefinition],
     ) -> Result<UnboundedReceiver<StreamEvent>, PhazeError> {
+        self.requests.lock().unwrap().push(messages.to_vec());
         let mut responses = self.responses.lock().unwrap();
         let events = responses.pop().unwrap_or_else(|| vec![StreamEvent::Done]);
 
@@ -53,6 +62,58 @@ impl LlmClient for MockLlm {
     }
 }
 
+/// Mock LLM whose stream sends one token and then stalls, like a dead connection.
+#[derive(Default)]
+struct StallingLlm {
+    open: Mutex<Vec<UnboundedSender<StreamEvent>>>,
+}
+
+#[async_trait::async_trait]
+impl LlmClient for StallingLlm {
+    async fn chat(
+        &self,
+        _messages: &[Message],
+        _tools: &[ToolDefinition],
+    ) -> Result<LlmResponse, PhazeError> {
+        unreachable!("Agent streams")
+    }
+
+    async fn chat_stream(
+        &self,
+        _messages: &[Message],
+        _tools: &[ToolDefinition],
+    ) -> Result<UnboundedReceiver<StreamEvent>, PhazeError> {
+        let (mut tx, rx) = unbounded();
+        tx.start_send(StreamEvent::TextDelta("Thinking".to_string()))
+            .unwrap();
+        self.open.lock().unwrap().push(tx);
+        Ok(rx)
+    }
+}
+
+/// Tool that never finishes in time.
+struct SlowTool;
+
+#[async_trait::async_trait]
+impl Tool for SlowTool {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    fn description(&self) -> &str {
+        "Sleeps for a minute"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({"type": "object", "properties": {}})
+    }
+
+    async fn execute(&self, _params: Value) -> ToolResult {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(Value::Null)
+    }
+}
+
 /// Simple echo tool for testing.
 struct EchoTool;
 
@@ -176,6 +237,59 @@ async fn test_tool_call_execution() {
     assert!(response.tool_calls[0].result_summary.contains("hello"));
 }
 
+#[tokio::test]
+async fn test_reasoning_is_sent_back_with_the_tool_call_it_led_to() {
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(EchoTool));
+    let item = ReasoningItem {
+        id: "rs_1".to_string(),
+        kind: ReasoningKind::OpenAi,
+        summary: vec!["Echo first.".to_string()],
+        encrypted_content: Some("gAAA".to_string()),
+    };
+    let mock = MockLlm::new(vec![
+        vec![
+            StreamEvent::TextDelta("Done!".to_string()),
+            StreamEvent::Done,
+        ],
+        vec![
+            StreamEvent::ReasoningDelta("Echo first.".to_string()),
+            StreamEvent::Reasoning(item.clone()),
+            StreamEvent::ToolCallStart {
+                id: "call_1".to_string(),
+                name: "echo".to_string(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "call_1".to_string(),
+                arguments_delta: r#"{"text": "hi"}"#.to_string(),
+            },
+            StreamEvent::ToolCallEnd {
+                id: "call_1".to_string(),
+            },
+            StreamEvent::Done,
+        ],
+    ]);
+    let requests = mock.requests.clone();
+    let agent = Agent::new(Box::new(mock)).with_tools(registry);
+    let (tx, mut rx) = unbounded_channel();
+    agent.run_with_events("Echo hi", tx).await.unwrap();
+
+    let mut reasoning = String::new();
+    while let Ok(event) = rx.try_recv() {
+        if let AgentEvent::ReasoningDelta(text) = event {
+            reasoning.push_str(&text);
+        }
+    }
+    assert_eq!(reasoning, "Echo first.");
+
+    let requests = requests.lock().unwrap();
+    let call = requests[1]
+        .iter()
+        .find(|m| m.tool_calls.is_some())
+        .expect("the tool call is in the second request");
+    assert_eq!(call.reasoning, vec![item]);
+}
+
 #[tokio::test]
 async fn test_max_iterations_reached() {
     // Always return a tool call, causing infinite loop
@@ -293,12 +407,62 @@ async fn test_event_channel_receives_all_events() {
 
     handle.await.unwrap();
 
-    // Check we got: Thinking, TextDelta, TextDelta, TokenUsage, Complete
+    // Check we got: Thinking, FirstToken, TextDelta, TextDelta, TokenUsage, Complete
     assert!(matches!(events[0], AgentEvent::Thinking { iteration: 1 }));
-    assert!(matches!(events[1], AgentEvent::TextDelta(_)));
+    assert!(matches!(events[1], AgentEvent::FirstToken { .. }));
     assert!(matches!(events[2], AgentEvent::TextDelta(_)));
-    assert!(matches!(events[3], AgentEvent::TokenUsage { .. }));
-    assert!(matches!(events[4], AgentEvent::Complete { iterations: 1 }));
+    assert!(matches!(events[3], AgentEvent::TextDelta(_)));
+    assert!(matches!(events[4], AgentEvent::TokenUsage { .. }));
+    assert!(matches!(events[5], AgentEvent::Complete { iterations: 1 }));
+}
+
+#[tokio::test]
+async fn test_citations_report_the_ranges_tools_read() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("lib.rs");
+    std::fs::write(&file, "a\nb\nc\nd\ne\nf\n").unwrap();
+    let args = serde_json::json!({ "path": file, "offset": 2, "limit": 3 }).to_string();
+
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(phazeai_core::tools::ReadFileTool));
+    let mock = MockLlm::new(vec![
+        vec![
+            StreamEvent::TextDelta("Lines b to d.".to_string()),
+            StreamEvent::Done,
+        ],
+        vec![
+            StreamEvent::ToolCallStart {
+                id: "call_1".to_string(),
+                name: "read_file".to_string(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "call_1".to_string(),
+                arguments_delta: args,
+            },
+            StreamEvent::ToolCallEnd {
+                id: "call_1".to_string(),
+            },
+            StreamEvent::Done,
+        ],
+    ]);
+
+    let agent = Agent::new(Box::new(mock)).with_tools(registry);
+    let (tx, mut rx) = unbounded_channel();
+    agent
+        .run_with_events("What's in lib.rs?", tx)
+        .await
+        .unwrap();
+
+    let mut citations = None;
+    while let Ok(event) = rx.try_recv() {
+        if let AgentEvent::Citations(c) = event {
+            citations = Some(c);
+        }
+    }
+    let citations = citations.expect("a Citations event");
+    assert_eq!(citations.len(), 1);
+    assert_eq!(citations[0].path, file.to_string_lossy());
+    assert_eq!((citations[0].start_line, citations[0].end_line), (2, 4));
 }
 
 #[tokio::test]
@@ -317,6 +481,122 @@ async fn test_llm_error_propagation() {
     }
 }
 
+#[tokio::test]
+async fn test_cancel_aborts_stalled_stream() {
+    let token = Arc::new(AtomicBool::new(false));
+    let agent = Agent::new(Box::new(StallingLlm::default())).with_cancel_token(token.clone());
+
+    let cancel = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        token.store(true, Ordering::Relaxed);
+    });
+    let result = tokio::time::timeout(Duration::from_secs(5), agent.run("Test"))
+        .await
+        .expect("cancel should interrupt the stream");
+    cancel.await.unwrap();
+
+    assert!(matches!(result, Err(PhazeError::Cancelled)));
+}
+
+#[tokio::test]
+async fn test_turn_timeout_stops_stalled_stream() {
+    let agent = Agent::new(Box::new(StallingLlm::default()))
+        .with_turn_timeout(Some(Duration::from_millis(150)));
+    let (tx, mut rx) = unbounded_channel();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), agent.run_with_events("Test", tx))
+        .await
+        .expect("the turn timeout should fire");
+
+    assert!(matches!(result, Err(PhazeError::Timeout(_))));
+    let mut reported = false;
+    while let Ok(event) = rx.try_recv() {
+        reported |= matches!(event, AgentEvent::Error(e) if e.contains("Timed out"));
+    }
+    assert!(reported);
+}
+
+#[tokio::test]
+async fn test_tool_timeout_fails_call_and_continues() {
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(SlowTool));
+    let mock = MockLlm::new(vec![
+        vec![
+            StreamEvent::TextDelta("Gave up on it".to_string()),
+            StreamEvent::Done,
+        ],
+        vec![
+            StreamEvent::ToolCallStart {
+                id: "call_1".to_string(),
+                name: "slow".to_string(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "call_1".to_string(),
+                arguments_delta: "{}".to_string(),
+            },
+            StreamEvent::ToolCallEnd {
+                id: "call_1".to_string(),
+            },
+            StreamEvent::Done,
+        ],
+    ]);
+
+    let agent = Agent::new(Box::new(mock))
+        .with_tools(registry)
+        .with_tool_timeout(Some(Duration::from_millis(100)));
+    let response = tokio::time::timeout(Duration::from_secs(5), agent.run("Go"))
+        .await
+        .expect("the tool timeout should fire")
+        .unwrap();
+
+    assert_eq!(response.content, "Gave up on it");
+    assert!(!response.tool_calls[0].success);
+    assert!(response.tool_calls[0].result_summary.contains("timed out"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dropped_stream_is_resumed_from_partial_reply() {
+    let mock = MockLlm::new(vec![
+        vec![StreamEvent::TextDelta("lo!".to_string()), StreamEvent::Done],
+        // No Done: the connection closed early.
+        vec![StreamEvent::TextDelta("Hel".to_string())],
+    ]);
+    let requests = mock.requests.clone();
+    let agent = Agent::new(Box::new(mock));
+    let (tx, mut rx) = unbounded_channel();
+
+    let response = agent.run_with_events("Greet", tx).await.unwrap();
+
+    assert_eq!(response.content, "Hello!");
+    assert_eq!(response.resumed, 1);
+    assert_eq!(response.iterations, 1);
+    let requests = requests.lock().unwrap();
+    let resume = &requests[1];
+    assert_eq!(resume[resume.len() - 2].role, Role::Assistant);
+    assert_eq!(resume[resume.len() - 2].content, "Hel");
+    assert_eq!(resume[resume.len() - 1].role, Role::User);
+    let mut resumed_events = 0;
+    while let Ok(event) = rx.try_recv() {
+        if let AgentEvent::StreamResumed { attempt, .. } = event {
+            resumed_events += 1;
+            assert_eq!(attempt, 1);
+        }
+    }
+    assert_eq!(resumed_events, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_stream_that_keeps_dropping_fails() {
+    let dropped = || vec![StreamEvent::Dropped("connection reset".to_string())];
+    let mock = MockLlm::new(vec![dropped(), dropped(), dropped(), dropped()]);
+    let agent = Agent::new(Box::new(mock));
+
+    match agent.run("Test").await {
+        Err(PhazeError::Llm(msg)) => assert!(msg.contains("connection reset")),
+        other => panic!("expected a dropped-stream error, got {other:?}"),
+    }
+}
+
 #[tokio::test]
 async fn test_tool_not_found() {
     // Return a tool call for a tool that doesn't exist
@@ -771,3 +1051,394 @@ async fn test_no_approval_callback_executes_normally() {
     assert_eq!(response.tool_calls.len(), 1);
     assert!(response.tool_calls[0].success);
 }
+
+// ── Recording & replay ──────────────────────────────────────────────────
+
+fn echo_then_done() -> Vec<Vec<StreamEvent>> {
+    vec![
+        vec![
+            StreamEvent::TextDelta("Done!".to_string()),
+            StreamEvent::Done,
+        ],
+        vec![
+            StreamEvent::ToolCallStart {
+                id: "call_1".to_string(),
+                name: "echo".to_string(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "call_1".to_string(),
+                arguments_delta: r#"{"text": "hello"}"#.to_string(),
+            },
+            StreamEvent::ToolCallEnd {
+                id: "call_1".to_string(),
+            },
+            StreamEvent::Done,
+        ],
+    ]
+}
+
+#[tokio::test]
+async fn test_recorder_captures_turns_and_tools() {
+    use phazeai_core::agent::recording::{RunRecorder, RunRecording};
+
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(EchoTool));
+    let recorder = RunRecorder::new("Echo hello", std::env::temp_dir());
+    let agent = Agent::new(Box::new(MockLlm::new(echo_then_done())))
+        .with_tools(registry)
+        .with_system_prompt("sys")
+        .with_recorder(recorder.clone());
+    agent.run("Echo hello").await.unwrap();
+
+    let rec = recorder.snapshot();
+    assert_eq!(rec.model_turns.len(), 2);
+    assert_eq!(rec.system_prompt(), Some("sys"));
+    assert_eq!(rec.tool_calls.len(), 1);
+    assert_eq!(rec.tool_calls[0].name, "echo");
+    assert!(rec.tool_calls[0].success);
+    assert!(rec.tool_calls[0].output.contains("hello"));
+
+    // Bundles survive a JSON round trip.
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("run.json");
+    rec.save(&path).unwrap();
+    let loaded = RunRecording::load(&path).unwrap();
+    assert_eq!(loaded.model_turns[1].events, rec.model_turns[1].events);
+    assert_eq!(loaded.tool_calls[0].output, rec.tool_calls[0].output);
+}
+
+#[tokio::test]
+async fn test_replay_llm_reproduces_run() {
+    use phazeai_core::agent::recording::{compare_tool_calls, ReplayLlm, RunRecorder};
+
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(EchoTool));
+    let recorder = RunRecorder::new("Echo hello", std::env::temp_dir());
+    Agent::new(Box::new(MockLlm::new(echo_then_done())))
+        .with_tools(registry)
+        .with_recorder(recorder.clone())
+        .run("Echo hello")
+        .await
+        .unwrap();
+    let original = recorder.snapshot();
+
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(EchoTool));
+    let replay_recorder = RunRecorder::new("Echo hello", std::env::temp_dir());
+    let response = Agent::new(Box::new(ReplayLlm::new(&original, None)))
+        .with_tools(registry)
+        .with_recorder(replay_recorder.clone())
+        .run("Echo hello")
+        .await
+        .unwrap();
+    assert_eq!(response.content, "Done!");
+
+    let replayed = replay_recorder.snapshot();
+    let tmp = std::env::temp_dir();
+    assert!(compare_tool_calls(&original.tool_calls, &replayed.tool_calls, &tmp, &tmp).is_empty());
+
+    // A tool that now behaves differently shows up as a divergence.
+    let mut changed = replayed.tool_calls.clone();
+    changed[0].output = "something else".into();
+    let diffs = compare_tool_calls(&original.tool_calls, &changed, &tmp, &tmp);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].what, "output");
+}
+
+#[tokio::test]
+async fn test_replay_llm_errors_when_exhausted() {
+    use phazeai_core::agent::recording::{ReplayLlm, RunRecorder};
+
+    let empty = RunRecorder::new("x", std::env::temp_dir()).snapshot();
+    let agent = Agent::new(Box::new(ReplayLlm::new(&empty, None)));
+    assert!(agent.run("x").await.is_err());
+}
+
+#[tokio::test]
+async fn test_memory_is_injected_and_saved_by_tool() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let store = phazeai_core::MemoryStore::for_workspace(dir.path());
+    store.add("We use sqlx 0.8, never diesel").unwrap();
+
+    let mock = MockLlm::new(vec![
+        vec![
+            StreamEvent::TextDelta("Noted.".to_string()),
+            StreamEvent::Done,
+        ],
+        vec![
+            StreamEvent::ToolCallStart {
+                id: "call_1".to_string(),
+                name: "memory".to_string(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "call_1".to_string(),
+                arguments_delta: r#"{"action": "save", "content": "Migrations live in db/"}"#
+                    .to_string(),
+            },
+            StreamEvent::ToolCallEnd {
+                id: "call_1".to_string(),
+            },
+            StreamEvent::Done,
+        ],
+    ]);
+
+    let agent = Agent::new(Box::new(mock))
+        .with_system_prompt("System prompt")
+        .with_memory(store.clone());
+    let response = agent.run("Add a users table").await.unwrap();
+    assert!(response.tool_calls[0].success);
+
+    let history = agent.get_conversation_history().await;
+    assert!(history[0].content.starts_with("System prompt"));
+    assert!(history[0]
+        .content
+        .contains("- We use sqlx 0.8, never diesel"));
+
+    let saved: Vec<String> = store.list().unwrap().into_iter().map(|m| m.text).collect();
+    assert_eq!(
+        saved,
+        vec!["We use sqlx 0.8, never diesel", "Migrations live in db/"]
+    );
+}
+
+// ── Background jobs ─────────────────────────────────────────────────────────
+
+/// An echo call, then "Done!" — the echo needs approval in a job.
+fn echo_job_agent() -> Agent {
+    let mut registry = ToolRegistry::new();
+    registry.register(Box::new(EchoTool));
+    let mock = MockLlm::new(vec![
+        vec![
+            StreamEvent::TextDelta("Done!".to_string()),
+            StreamEvent::Done,
+        ],
+        vec![
+            StreamEvent::ToolCallStart {
+                id: "call_1".to_string(),
+                name: "echo".to_string(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "call_1".to_string(),
+                arguments_delta: r#"{"text": "hi"}"#.to_string(),
+            },
+            StreamEvent::ToolCallEnd {
+                id: "call_1".to_string(),
+            },
+            StreamEvent::Done,
+        ],
+    ]);
+    Agent::new(Box::new(mock)).with_tools(registry)
+}
+
+fn echo_job_queue(max_concurrent: usize) -> JobQueue {
+    JobQueue::new(
+        max_concurrent,
+        Arc::new(|_dir: &std::path::Path| Ok(echo_job_agent())),
+        Arc::new(|| {}),
+    )
+}
+
+fn wait_for_job(queue: &JobQueue, id: JobId, done: impl Fn(&JobStatus) -> bool) -> Job {
+    for _ in 0..500 {
+        if let Some(job) = queue.jobs().into_iter().find(|j| j.id == id) {
+            if done(&job.status) {
+                return job;
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    panic!(
+        "job {id} never reached the expected status: {:?}",
+        queue.jobs()
+    );
+}
+
+#[test]
+fn test_job_waits_for_approval_then_finishes() {
+    let queue = echo_job_queue(2);
+    let workspace = std::env::temp_dir();
+    let id = queue.submit("Say hi", workspace.clone(), false);
+
+    let job = wait_for_job(
+        &queue,
+        id,
+        |s| matches!(s, JobStatus::AwaitingApproval { tool, .. } if tool == "echo"),
+    );
+    assert_eq!(job.workdir.as_deref(), Some(workspace.as_path()));
+
+    queue.respond(id, true);
+    let job = wait_for_job(&queue, id, JobStatus::is_finished);
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(job.output, "Done!");
+    assert!(job.log.iter().any(|l| l == "Approved echo"));
+    assert!(job.log.iter().any(|l| l.starts_with("✓ echo")));
+
+    queue.clear_finished();
+    assert!(queue.jobs().is_empty());
+}
+
+#[test]
+fn test_cancel_queued_and_parked_jobs() {
+    let queue = echo_job_queue(1);
+    let first = queue.submit("One", std::env::temp_dir(), false);
+    let second = queue.submit("Two", std::env::temp_dir(), false);
+
+    // The first job holds the only slot while it waits for approval.
+    wait_for_job(&queue, first, |s| {
+        matches!(s, JobStatus::AwaitingApproval { .. })
+    });
+    assert_eq!(
+        wait_for_job(&queue, second, |_| true).status,
+        JobStatus::Queued
+    );
+
+    queue.cancel(second);
+    queue.cancel(first);
+    assert_eq!(
+        wait_for_job(&queue, first, JobStatus::is_finished).status,
+        JobStatus::Cancelled
+    );
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    let second = wait_for_job(&queue, second, JobStatus::is_finished);
+    assert_eq!(second.status, JobStatus::Cancelled);
+    assert!(second.log.is_empty());
+}
+
+fn at(s: &str) -> chrono::NaiveDateTime {
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+}
+
+#[test]
+fn test_cron_next_after() {
+    let every_15 = CronSchedule::parse("*/15 9-17 * * mon-fri").unwrap();
+    // Friday evening rolls over to Monday morning.
+    assert_eq!(
+        every_15.next_after(at("2026-10-16 17:50")),
+        Some(at("2026-10-19 09:00"))
+    );
+    assert_eq!(
+        every_15.next_after(at("2026-10-19 09:00")),
+        Some(at("2026-10-19 09:15"))
+    );
+
+    let nightly = CronSchedule::parse("@nightly").unwrap();
+    assert_eq!(
+        nightly.next_after(at("2026-12-31 23:59")),
+        Some(at("2027-01-01 00:00"))
+    );
+
+    // With both day fields restricted, either one matching is enough.
+    let first_or_sunday = CronSchedule::parse("30 2 1 * 7").unwrap();
+    assert!(first_or_sunday.matches(at("2026-10-18 02:30")));
+    assert!(first_or_sunday.matches(at("2026-12-01 02:30")));
+    assert!(!first_or_sunday.matches(at("2026-10-19 02:30")));
+
+    assert_eq!(
+        CronSchedule::parse("0 0 29 feb *")
+            .unwrap()
+            .next_after(at("2026-03-01 00:00")),
+        Some(at("2028-02-29 00:00"))
+    );
+    assert_eq!(
+        CronSchedule::parse("0 0 31 feb *")
+            .unwrap()
+            .next_after(at("2026-01-01 00:00")),
+        None
+    );
+}
+
+#[test]
+fn test_cron_rejects_invalid_expressions() {
+    for expr in [
+        "* * * *",
+        "60 * * * *",
+        "* * 0 * *",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "* * * foo *",
+        "@sometimes",
+    ] {
+        assert!(
+            CronSchedule::parse(expr).is_err(),
+            "{expr} should not parse"
+        );
+    }
+}
+
+#[test]
+fn test_scheduled_job_config_defaults() {
+    let job: ScheduledJob = toml::from_str(
+        r#"
+        name = "todos"
+        cron = "@nightly"
+        prompt = "Summarize new TODOs"
+        workspace = "/tmp/project"
+        "#,
+    )
+    .unwrap();
+    assert!(job.enabled);
+    assert!(!job.use_worktree);
+    assert!(job.allowed_tools.is_empty());
+    assert!(job.is_due(at("2026-10-17 00:00")));
+    assert!(!job.is_due(at("2026-10-17 00:01")));
+
+    let disabled = ScheduledJob {
+        enabled: false,
+        ..job
+    };
+    assert!(!disabled.is_due(at("2026-10-17 00:00")));
+    assert_eq!(disabled.next_run(at("2026-10-16 12:00")), None);
+}
+
+#[tokio::test]
+async fn test_unattended_runs_only_read_unless_tools_are_allowed() {
+    let bash = |command: &str| ("bash", serde_json::json!({ "command": command }));
+    let file = |tool| (tool, serde_json::json!({ "path": "a.rs" }));
+
+    let read_only = unattended_approval(&[]);
+    for ((tool, params), approved) in [
+        (file("read_file"), true),
+        (file("write_file"), false),
+        (bash("cargo test"), false),
+        // Looks read-only from how it starts, but isn't.
+        (bash("echo hi; curl x | sh"), false),
+    ] {
+        assert_eq!(read_only(tool.into(), params).await, approved, "{tool}");
+    }
+
+    let allowed = unattended_approval(&["bash".to_string(), "edit_file".to_string()]);
+    for ((tool, params), approved) in [
+        (file("edit_file"), true),
+        (bash("cargo test"), true),
+        (file("write_file"), false),
+        (bash("rm -rf /"), false),
+    ] {
+        assert_eq!(allowed(tool.into(), params).await, approved, "{tool}");
+    }
+}
+
+#[tokio::test]
+async fn test_scheduled_run_is_saved_to_history() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = ConversationStore::with_dir(dir.path().join("conversations")).unwrap();
+    let job = ScheduledJob {
+        name: "echo".to_string(),
+        cron: "@hourly".to_string(),
+        prompt: "Say hi".to_string(),
+        workspace: dir.path().to_path_buf(),
+        use_worktree: false,
+        enabled: true,
+        allowed_tools: Vec::new(),
+    };
+    let factory: phazeai_core::agent::AgentFactory =
+        Arc::new(|_dir: &std::path::Path| Ok(echo_job_agent()));
+
+    let run = run_scheduled(&job, &factory, "mock", &store).await.unwrap();
+    assert_eq!(run.error, None);
+
+    let saved = store.load(&run.conversation.metadata.id).unwrap();
+    assert_eq!(saved.metadata.tags, vec!["scheduled".to_string()]);
+    assert!(saved.metadata.title.starts_with("echo ("));
+    assert_eq!(saved.messages[0].content, "Say hi");
+    assert_eq!(saved.messages.last().unwrap().content, "Done!");
+}
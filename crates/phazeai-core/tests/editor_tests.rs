@@ -6,10 +6,14 @@
 //! - Vim motion key-sequence state machine
 //! - Session persistence (tab dirty state, multi-file list, active-tab clamping)
 //! - Find/replace (case-sensitive, case-insensitive, regex, replace-all)
+//! - .editorconfig resolution and indentation detection
+//! - Typing assists, the navigation jump list and line bookmarks
 //!
 //! Run: `cargo test --test editor_tests`
 
-use phazeai_core::analysis::{extract_symbols_generic, symbols_to_repo_map, Severity, SymbolKind};
+use phazeai_core::analysis::{
+    extract_symbols_generic, is_public_declaration, symbols_to_repo_map, Severity, SymbolKind,
+};
 
 // ── 1. Rope / text operation helpers ─────────────────────────────────────────
 //
@@ -605,6 +609,41 @@ fn repo_map_empty_source_shows_only_filename() {
     assert_eq!(lines.len(), 1, "expected only filename line; got: {map}");
 }
 
+// ── is_public_declaration ─────────────────────────────────────────────────────
+
+/// `(extension, line, name, parent, public)`
+const DECLARATIONS: &[(&str, &str, &str, Option<&str>, bool)] = &[
+    ("rs", "pub fn run() {", "run", None, true),
+    ("rs", "pub async fn run() {", "run", None, true),
+    ("rs", "fn run() {", "run", None, false),
+    ("rs", "pub(crate) struct Cache {", "Cache", None, false),
+    ("rs", "fn fmt() {", "fmt", Some("impl Debug for A {"), true),
+    ("rs", "fn len();", "len", Some("pub trait Len {"), true),
+    ("rs", "Red,", "Red", Some("pub enum Color {"), true),
+    ("rs", "fn helper() {", "helper", Some("impl A {"), false),
+    ("ts", "export function load() {", "load", None, true),
+    ("ts", "function load() {", "load", None, false),
+    ("ts", "private cache;", "cache", Some("class A {"), false),
+    ("ts", "get(key) {", "get", Some("export class A {"), true),
+    ("go", "func Serve() {", "Serve", None, true),
+    ("go", "func serve() {", "serve", None, false),
+    ("py", "def __init__():", "__init__", Some("class A:"), true),
+    ("py", "def _helper():", "_helper", None, false),
+    ("java", "public void run()", "run", Some("class A {"), true),
+    ("java", "void run() {", "run", Some("class A {"), false),
+];
+
+#[test]
+fn public_declaration_follows_each_languages_rules() {
+    for &(ext, line, name, parent, public) in DECLARATIONS {
+        assert_eq!(
+            is_public_declaration(ext, line, name, parent),
+            public,
+            "{ext}: {line:?} in {parent:?}"
+        );
+    }
+}
+
 // ── 3. Linter analysis ────────────────────────────────────────────────────────
 //
 // The Linter::analyze API requires a Language enum value, but Language is not
@@ -1844,3 +1883,747 @@ fn replace_regex_case_insensitive() {
     let result = replace_all_regex(text, r"(?i)hello", "hi");
     assert_eq!(result, "hi hi hi");
 }
+
+// ── 7. EditorConfig and indentation detection ─────────────────────────────────
+
+use phazeai_core::project::editorconfig::{
+    detect_indent, EditorConfig, Indent, IndentSource, IndentStyle, LineEnding,
+};
+
+#[test]
+fn detect_indent_two_spaces() {
+    let text = "fn main() {\n  if x {\n    y();\n  }\n}\n";
+    assert_eq!(detect_indent(text, 4), Some(Indent::spaces(2)));
+}
+
+#[test]
+fn detect_indent_tabs_use_tab_size() {
+    let text = "func main() {\n\tif x {\n\t\ty()\n\t}\n}\n";
+    assert_eq!(detect_indent(text, 8), Some(Indent::tabs(8)));
+}
+
+#[test]
+fn detect_indent_ignores_block_comment_stars() {
+    let text = "/**\n * Docs\n */\nint f() {\n    return 1;\n}\n";
+    assert_eq!(detect_indent(text, 2), Some(Indent::spaces(4)));
+}
+
+#[test]
+fn detect_indent_none_without_indented_lines() {
+    assert_eq!(detect_indent("a\nb\n\nc\n", 4), None);
+}
+
+#[test]
+fn editorconfig_sections_and_overrides() {
+    let mut config = EditorConfig::default();
+    let content = "root = true\n\n[*]\nindent_style = space\nindent_size = 4\ntrim_trailing_whitespace = true\n\n[*.{js,ts}]\nindent_size = 2\n\n[Makefile]\nindent_style = tab\n";
+    config.apply(content, "src/app.ts");
+    assert_eq!(config.indent_style, Some(IndentStyle::Spaces));
+    assert_eq!(config.indent_size, Some(2));
+    assert_eq!(config.trim_trailing_whitespace, Some(true));
+
+    let mut config = EditorConfig::default();
+    config.apply(content, "build/Makefile");
+    assert_eq!(config.indent_style, Some(IndentStyle::Tabs));
+}
+
+#[test]
+fn editorconfig_max_line_length() {
+    let content = "[*]\nmax_line_length = 100\n\n[*.md]\nmax_line_length = off\n";
+    let mut config = EditorConfig::default();
+    config.apply(content, "src/main.rs");
+    assert_eq!(config.max_line_length, Some(100));
+
+    let mut config = EditorConfig::default();
+    config.apply(content, "README.md");
+    assert_eq!(config.max_line_length, None);
+}
+
+#[test]
+fn editorconfig_closer_file_wins_and_root_stops_search() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let root = dir.path().join("repo");
+    std::fs::create_dir_all(root.join("web")).unwrap();
+    std::fs::write(
+        dir.path().join(".editorconfig"),
+        "[*]\nend_of_line = crlf\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join(".editorconfig"),
+        "root = true\n[*]\nindent_size = 4\ninsert_final_newline = true\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("web/.editorconfig"), "[*.css]\nindent_size = 2\n").unwrap();
+
+    let config = EditorConfig::for_file(&root.join("web/site.css"));
+    assert_eq!(config.indent_size, Some(2));
+    assert_eq!(config.insert_final_newline, Some(true));
+    assert_eq!(config.end_of_line, None);
+}
+
+#[test]
+fn editorconfig_indent_beats_detection() {
+    let text = "a {\n  b;\n}\n";
+    let default = Indent::spaces(4);
+
+    let effective = EditorConfig::default().indent(text, default);
+    assert_eq!(effective.indent, Indent::spaces(2));
+    assert_eq!(effective.source, IndentSource::Detected);
+
+    let config = EditorConfig {
+        indent_size: Some(8),
+        ..Default::default()
+    };
+    let effective = config.indent(text, default);
+    assert_eq!(effective.indent, Indent::spaces(8));
+    assert_eq!(effective.source, IndentSource::EditorConfig);
+
+    let effective = EditorConfig::default().indent("plain\n", default);
+    assert_eq!(effective.source, IndentSource::Default);
+}
+
+#[test]
+fn editorconfig_whitespace_fixes() {
+    let config = EditorConfig {
+        trim_trailing_whitespace: Some(true),
+        insert_final_newline: Some(true),
+        end_of_line: Some(LineEnding::Crlf),
+        ..Default::default()
+    };
+    let mut text = "a  \r\nb\t\nc ".to_string();
+    for (range, replacement) in config.whitespace_fixes(&text).into_iter().rev() {
+        text.replace_range(range, replacement);
+    }
+    assert_eq!(text, "a\r\nb\nc\r\n");
+    assert!(EditorConfig::default().whitespace_fixes("x  ").is_empty());
+}
+
+// ── 8. Typing assists: auto-close, Enter indentation, surround ───────────────
+
+use phazeai_core::editing::{EnterIndent, TypingRules};
+
+#[test]
+fn auto_close_brackets_and_quotes() {
+    let rust = TypingRules::for_extension("rs");
+    assert_eq!(rust.auto_close('(', "foo", None), Some(')'));
+    assert_eq!(rust.auto_close('{', "fn f() ", Some(' ')), Some('}'));
+    assert_eq!(rust.auto_close('[', "let v = ", Some(')')), Some(']'));
+    // Not right before a word.
+    assert_eq!(rust.auto_close('(', "", Some('x')), None);
+    assert_eq!(rust.auto_close('"', "let s = ", None), Some('"'));
+    // Ending a string, escaped, or after a word.
+    assert_eq!(rust.auto_close('"', "let s = \"abc", None), None);
+    assert_eq!(rust.auto_close('"', "\"a\\", None), None);
+    assert_eq!(rust.auto_close('"', "r", None), None);
+    // In a comment.
+    assert_eq!(rust.auto_close('"', "x(); // say ", None), None);
+    assert_eq!(rust.auto_close('"', "let s = \"//\"; f(", None), Some('"'));
+}
+
+#[test]
+fn auto_close_language_exceptions() {
+    let rust = TypingRules::for_extension("rs");
+    assert_eq!(rust.auto_close('\'', "let c = ", None), Some('\''));
+    // Lifetimes.
+    assert_eq!(rust.auto_close('\'', "fn f(x: &", None), None);
+    assert_eq!(rust.auto_close('\'', "struct S<", None), None);
+    assert_eq!(rust.auto_close('\'', "impl<'a, ", None), None);
+    assert_eq!(rust.auto_close('`', "", None), None);
+
+    let md = TypingRules::for_extension("md");
+    assert_eq!(md.auto_close('\'', "it", None), None);
+    assert_eq!(md.auto_close('\'', "say ", None), None);
+    assert_eq!(md.auto_close('`', "run ", None), Some('`'));
+
+    let py = TypingRules::for_extension("py");
+    assert_eq!(py.auto_close('\'', "x = ", None), Some('\''));
+    assert_eq!(py.auto_close('\'', "x = 1  # it", None), None);
+    assert_eq!(py.auto_close('\'', "x = 1  # a ", None), None);
+}
+
+#[test]
+fn type_over_and_pair_delete() {
+    let rules = TypingRules::for_extension("ts");
+    assert!(rules.types_over(')', Some(')')));
+    assert!(rules.types_over('"', Some('"')));
+    assert!(!rules.types_over(')', Some(']')));
+    assert!(!rules.types_over('(', Some('(')));
+    assert!(rules.deletes_pair('(', ')'));
+    assert!(rules.deletes_pair('\'', '\''));
+    assert!(!rules.deletes_pair('(', ']'));
+    assert!(!rules.deletes_pair('a', 'a'));
+}
+
+#[test]
+fn surround_selection_pairs() {
+    let rust = TypingRules::for_extension("rs");
+    assert_eq!(rust.surround('('), Some(')'));
+    assert_eq!(rust.surround('{'), Some('}'));
+    assert_eq!(rust.surround('"'), Some('"'));
+    assert_eq!(rust.surround('`'), Some('`'));
+    assert_eq!(rust.surround('*'), None);
+    let md = TypingRules::for_extension("md");
+    assert_eq!(md.surround('*'), Some('*'));
+    assert_eq!(md.surround('_'), Some('_'));
+}
+
+fn enter(ext: &str, previous: &str, rest: &str) -> EnterIndent {
+    TypingRules::for_extension(ext).enter(previous, rest, "    ")
+}
+
+#[test]
+fn enter_indent_continues_and_opens_blocks() {
+    assert_eq!(enter("rs", "    let x = 1;", "").indent, "    ");
+    assert_eq!(enter("rs", "fn main() {", "").indent, "    ");
+    assert_eq!(enter("rs", "    foo(  // args", "").indent, "        ");
+    // Rust match arm without a body.
+    assert_eq!(enter("rs", "        Some(x) =>", "").indent, "            ");
+    assert_eq!(enter("rs", "        Some(x) => x,", "").indent, "        ");
+    // `:` only opens a block in Python.
+    assert_eq!(enter("rs", "    label:", "").indent, "    ");
+    assert_eq!(enter("py", "def f(x):", "").indent, "    ");
+    assert_eq!(enter("py", "    if x:  # check", "").indent, "        ");
+}
+
+#[test]
+fn enter_between_pair_and_python_dedent() {
+    assert_eq!(
+        enter("rs", "    fn f() {", "}"),
+        EnterIndent {
+            indent: "        ".into(),
+            closing: Some("    ".into()),
+        }
+    );
+    assert_eq!(enter("js", "call(", ");").closing, Some(String::new()));
+    assert_eq!(enter("rs", "fn f() {", "x }").closing, None);
+
+    assert_eq!(enter("py", "        return x", "").indent, "    ");
+    assert_eq!(enter("py", "\t\tpass", "").indent, "\t");
+    assert_eq!(enter("py", "returned = 1", "").indent, "");
+    assert_eq!(enter("rs", "    return x;", "").indent, "    ");
+}
+
+// ── 9. Jump list ──────────────────────────────────────────────────────────────
+
+use phazeai_core::editing::{JumpList, Location};
+
+#[test]
+fn jump_list_records_only_jumps() {
+    let mut jumps = JumpList::new();
+    let a = Location::new("a.rs", 5, 0);
+    jumps.observe(&a, &Location::new("a.rs", 6, 0));
+    jumps.observe(&a, &Location::new("a.rs", 14, 3));
+    assert!(!jumps.can_go_back());
+    jumps.observe(&a, &Location::new("a.rs", 40, 0));
+    jumps.observe(&Location::new("a.rs", 40, 0), &Location::new("b.rs", 0, 0));
+    assert!(jumps.can_go_back());
+    let recent = jumps.recent(10);
+    assert_eq!(recent, vec![Location::new("a.rs", 40, 0), a]);
+}
+
+#[test]
+fn jump_list_back_and_forward() {
+    let mut jumps = JumpList::new();
+    let a = Location::new("a.rs", 1, 0);
+    let b = Location::new("b.rs", 20, 4);
+    let c = Location::new("c.rs", 3, 0);
+    jumps.observe(&a, &b);
+    jumps.observe(&b, &c);
+
+    assert_eq!(jumps.back(c.clone()), Some(b.clone()));
+    // The moves `back` causes, including opening the file elsewhere first,
+    // aren't recorded.
+    jumps.observe(&c, &Location::new("b.rs", 0, 0));
+    jumps.observe(&Location::new("b.rs", 0, 0), &b);
+    assert_eq!(jumps.back(b.clone()), Some(a.clone()));
+    jumps.observe(&b, &a);
+    assert_eq!(jumps.back(a.clone()), None);
+
+    assert_eq!(jumps.forward(), Some(b.clone()));
+    jumps.observe(&a, &b);
+    assert_eq!(jumps.forward(), Some(c.clone()));
+    jumps.observe(&b, &c);
+    assert_eq!(jumps.forward(), None);
+    assert!(!jumps.can_go_forward());
+}
+
+#[test]
+fn jump_list_new_jump_drops_forward_history() {
+    let mut jumps = JumpList::new();
+    let a = Location::new("a.rs", 1, 0);
+    let b = Location::new("b.rs", 1, 0);
+    let c = Location::new("c.rs", 1, 0);
+    jumps.observe(&a, &b);
+    jumps.observe(&b, &c);
+    assert_eq!(jumps.back(c.clone()), Some(b.clone()));
+    jumps.observe(&c, &b);
+    let d = Location::new("d.rs", 1, 0);
+    jumps.observe(&b, &d);
+    assert!(!jumps.can_go_forward());
+    assert_eq!(jumps.back(d.clone()), Some(b.clone()));
+    jumps.observe(&d, &b);
+    assert_eq!(jumps.back(b.clone()), Some(a));
+}
+
+// ── 10. Bookmarks ─────────────────────────────────────────────────────────────
+
+use phazeai_core::editing::{Bookmark, Bookmarks};
+use std::path::Path;
+
+#[test]
+fn bookmarks_toggle_and_notes() {
+    let mut marks = Bookmarks::new();
+    let a = Path::new("src/a.rs");
+    assert!(marks.toggle(a, 12));
+    assert!(marks.toggle(a, 3));
+    assert_eq!(marks.lines_in(a), vec![3, 12]);
+
+    assert!(marks.set_note(a, 12, "  check this  "));
+    assert_eq!(marks.get(a, 12).unwrap().note, "check this");
+    assert!(!marks.set_note(a, 4, "nope"));
+
+    assert!(!marks.toggle(a, 12));
+    assert_eq!(marks.lines_in(a), vec![3]);
+    assert!(marks.remove(a, 3));
+    assert!(marks.is_empty());
+}
+
+#[test]
+fn bookmarks_grouped_by_file() {
+    let mut marks = Bookmarks::new();
+    marks.toggle(Path::new("b.rs"), 1);
+    marks.toggle(Path::new("a.rs"), 9);
+    marks.toggle(Path::new("a.rs"), 2);
+    let groups = marks.by_file();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].0, Path::new("a.rs"));
+    let lines: Vec<u32> = groups[0].1.iter().map(|b| b.line).collect();
+    assert_eq!(lines, vec![2, 9]);
+    assert_eq!(groups[1].0, Path::new("b.rs"));
+}
+
+#[test]
+fn bookmarks_next_and_previous_wrap_across_files() {
+    let mut marks = Bookmarks::new();
+    let (a, b) = (Path::new("a.rs"), Path::new("b.rs"));
+    marks.toggle(a, 5);
+    marks.toggle(a, 20);
+    marks.toggle(b, 1);
+
+    let at = |m: Option<&Bookmark>| m.map(|m| (m.path.clone(), m.line));
+    assert_eq!(at(marks.next(a, 5)), Some((a.into(), 20)));
+    assert_eq!(at(marks.next(a, 20)), Some((b.into(), 1)));
+    assert_eq!(at(marks.next(b, 1)), Some((a.into(), 5)));
+    assert_eq!(at(marks.previous(a, 5)), Some((b.into(), 1)));
+    assert_eq!(at(marks.previous(b, 0)), Some((a.into(), 20)));
+    assert_eq!(Bookmarks::new().next(a, 0), None);
+}
+
+#[test]
+fn bookmarks_deserialize_sorted_without_duplicates() {
+    let json = r#"[
+        {"path": "b.rs", "line": 4},
+        {"path": "a.rs", "line": 7, "note": "first"},
+        {"path": "a.rs", "line": 7, "note": "second"}
+    ]"#;
+    let marks: Bookmarks = serde_json::from_str(json).unwrap();
+    assert_eq!(marks.len(), 2);
+    assert_eq!(marks.get(Path::new("a.rs"), 7).unwrap().note, "first");
+    let out = serde_json::to_string(&marks).unwrap();
+    assert!(out.starts_with(r#"[{"path":"a.rs","line":7,"note":"first"}"#));
+    assert!(out.contains(r#"{"path":"b.rs","line":4}"#));
+}
+
+// ── 11. Derived state: folds, bracket pairs, diagnostics ─────────────────────
+
+use phazeai_core::editing::derived::{self, Derived, DerivedCache};
+
+const DERIVED_SRC: &str =
+    "fn main() {\n    let v = vec![1, (2)];\n    if x {\n        y(\"}\");\n    }\n}\n";
+
+#[test]
+fn derived_finds_folds_pairs_and_guides() {
+    let derived = Derived::compute(7, DERIVED_SRC, "rs");
+    assert_eq!(derived.revision, 7);
+    assert_eq!(derived.fold_ranges, vec![(0, 5), (2, 4)]);
+    // The `}` inside the string isn't a bracket.
+    let depths: Vec<usize> = derived.bracket_pairs.iter().map(|p| p.2).collect();
+    assert_eq!(depths, vec![0, 2, 1, 2, 1, 0]);
+    // Only `fn main() { … }` and `if x { … }` span more than a line apart.
+    assert_eq!(derived.bracket_guides, vec![(2, 9, 4, 1), (0, 10, 5, 0)]);
+}
+
+#[test]
+fn derived_cache_computes_each_revision_once() {
+    let mut cache = DerivedCache::default();
+    let mut passes = 0;
+    let first = cache.get(1, || {
+        passes += 1;
+        DERIVED_SRC.to_string()
+    });
+    let again = cache.get(1, || {
+        passes += 1;
+        String::new()
+    });
+    assert_eq!(passes, 1);
+    assert_eq!(first, again);
+
+    let next = cache.get(2, || {
+        passes += 1;
+        "{}".to_string()
+    });
+    assert_eq!(passes, 2);
+    assert_eq!(next.bracket_pairs, vec![(0, 1, 0)]);
+    assert_eq!(cache.latest().map(|d| d.revision), Some(2));
+}
+
+#[test]
+fn derived_diag_lines_are_zero_based_and_sorted() {
+    let lines = derived::diag_lines([(12, 'w'), (3, 'e'), (0, 'h')]);
+    assert_eq!(lines, vec![(0, 'h'), (2, 'e'), (11, 'w')]);
+}
+
+// ── 12. Folding: comments, regions, indentation, imports ─────────────────────
+
+use phazeai_core::editing::folding::{self, fold_regions, FoldMemory};
+
+#[test]
+fn folds_comments_regions_and_imports_in_rust() {
+    let src = "\
+use std::fs;
+use std::{
+    io,
+    path::Path,
+};
+
+// #region helpers
+/// Reads it.
+/// Twice.
+fn read() {
+    /* one
+       two */
+}
+// #endregion
+";
+    assert_eq!(
+        fold_regions(src, "rs"),
+        vec![(0, 4), (1, 4), (6, 13), (7, 8), (9, 12), (10, 11)]
+    );
+}
+
+#[test]
+fn folds_python_blocks_and_hash_regions() {
+    let src = "\
+import os
+from sys import (
+    argv,
+)
+
+# region main
+def main():
+    if argv:
+
+        print(1)
+    return 0
+# endregion
+";
+    assert_eq!(
+        fold_regions(src, "py"),
+        vec![(0, 3), (1, 2), (5, 11), (6, 10), (7, 9)]
+    );
+    // `# regional` is an ordinary comment.
+    assert!(fold_regions("# regional\nx = 1\n# endregion\n", "py").is_empty());
+}
+
+#[test]
+fn fold_levels_count_nesting() {
+    // `fn a() {` holding `if {` and `} else {`, then `fn b() {`.
+    let ranges = [(0, 6), (1, 3), (3, 5), (7, 9)];
+    assert_eq!(folding::fold_levels(&ranges), vec![1, 2, 2, 1]);
+    assert_eq!(folding::starts_at_level(&ranges, 1), vec![0, 7]);
+    assert_eq!(folding::starts_at_level(&ranges, 2), vec![1, 3]);
+    assert!(folding::starts_at_level(&ranges, 3).is_empty());
+}
+
+#[test]
+fn fold_memory_remembers_folded_lines_per_file() {
+    let mut folds = FoldMemory::new();
+    let a = Path::new("/w/a.rs");
+    assert!(folds.set(a, [9, 2, 9]));
+    assert!(!folds.set(a, [2, 9]));
+    assert_eq!(folds.get(a), &[2, 9]);
+    assert!(folds.get(Path::new("/w/b.rs")).is_empty());
+
+    let text = toml::to_string(&folds).unwrap();
+    assert_eq!(toml::from_str::<FoldMemory>(&text).unwrap(), folds);
+
+    assert!(folds.set(a, []));
+    assert!(folds.is_empty());
+}
+
+// ── 13. Compare: changes, rows, intra-line words, copying ─────────────────────
+
+use phazeai_core::editing::compare::{self, Change, Row, Side};
+
+const COMPARE_LEFT: &str = "one\ntwo\nthree\nfour\n";
+const COMPARE_RIGHT: &str = "one\n2\nthree\nfour\nfive\n";
+
+#[test]
+fn compare_finds_changed_runs_of_lines() {
+    let changes = compare::changes(COMPARE_LEFT, COMPARE_RIGHT);
+    assert_eq!(
+        changes,
+        vec![
+            Change {
+                left: 1..2,
+                right: 1..2
+            },
+            Change {
+                left: 4..4,
+                right: 4..5
+            },
+        ]
+    );
+    assert!(compare::changes("same\n", "same\n").is_empty());
+}
+
+#[test]
+fn compare_rows_side_by_side_and_inline() {
+    let changes = vec![Change {
+        left: 1..3,
+        right: 1..2,
+    }];
+    let row = |left, right, change| Row {
+        left,
+        right,
+        change,
+    };
+    assert_eq!(
+        compare::side_by_side(&changes, 4, 3),
+        vec![
+            row(Some(0), Some(0), None),
+            row(Some(1), Some(1), Some(0)),
+            row(Some(2), None, Some(0)),
+            row(Some(3), Some(2), None),
+        ]
+    );
+    assert_eq!(
+        compare::inline(&changes, 4, 3),
+        vec![
+            row(Some(0), Some(0), None),
+            row(Some(1), None, Some(0)),
+            row(Some(2), None, Some(0)),
+            row(None, Some(1), Some(0)),
+            row(Some(3), Some(2), None),
+        ]
+    );
+}
+
+#[test]
+fn compare_intraline_marks_changed_words() {
+    let (old, new) = compare::intraline("let total = a + b;\n", "let sum = a + b + c;\n");
+    assert_eq!(old, vec![4..9]);
+    assert_eq!(new, vec![4..7, 15..19]);
+}
+
+#[test]
+fn compare_copies_a_change_either_way() {
+    let changes = compare::changes(COMPARE_LEFT, COMPARE_RIGHT);
+    let right = compare::copy_change(COMPARE_LEFT, COMPARE_RIGHT, &changes[0], Side::Right);
+    assert_eq!(right, "one\ntwo\nthree\nfour\nfive\n");
+    let left = compare::copy_change(COMPARE_LEFT, COMPARE_RIGHT, &changes[1], Side::Left);
+    assert_eq!(left, "one\ntwo\nthree\nfour\nfive\n");
+    assert_eq!(Side::Left.other(), Side::Right);
+}
+
+// ── 14. Applying chat code blocks: paths, targets, placement ──────────────────
+
+use phazeai_core::editing::apply::{self, CodeBlock};
+
+const APPLY_FILE: &str = "\
+use std::fmt;
+
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    fn bump(&mut self) {
+        self.count += 1;
+    }
+
+    fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+";
+
+#[test]
+fn apply_finds_code_blocks_and_their_paths() {
+    let reply = "\
+Change `src/counter.rs`:
+
+```rust
+fn bump() {}
+```
+
+```rust:src/lib.rs
+mod counter;
+```
+
+```python
+# file: tools/run.py
+print(1)
+```
+
+```sh
+cargo test
+```
+";
+    let blocks = apply::code_blocks(reply);
+    assert_eq!(
+        blocks,
+        vec![
+            CodeBlock {
+                lang: "rust".into(),
+                path: Some("src/counter.rs".into()),
+                body: "fn bump() {}".into(),
+            },
+            CodeBlock {
+                lang: "rust".into(),
+                path: Some("src/lib.rs".into()),
+                body: "mod counter;".into(),
+            },
+            CodeBlock {
+                lang: "python".into(),
+                path: Some("tools/run.py".into()),
+                body: "print(1)".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn apply_resolves_a_path_hint_in_the_workspace() {
+    let root = std::path::Path::new("/ws");
+    let files = vec![
+        root.join("crates/app/src/main.rs"),
+        root.join("src/main.rs"),
+        root.join("src/lib.rs"),
+    ];
+    assert_eq!(
+        apply::resolve_hint("src/lib.rs", root, &files),
+        Some(root.join("src/lib.rs"))
+    );
+    assert_eq!(
+        apply::resolve_hint("main.rs", root, &files),
+        Some(root.join("src/main.rs"))
+    );
+    assert_eq!(
+        apply::resolve_hint("./src/new.rs", root, &files),
+        Some(root.join("src/new.rs"))
+    );
+}
+
+#[test]
+fn apply_ignores_a_path_hint_outside_the_workspace() {
+    let root = std::path::Path::new("/ws");
+    let files = vec![root.join("src/main.rs")];
+    for hint in [
+        "/etc/passwd",
+        "../outside.rs",
+        "src/../../outside.rs",
+        "..\\outside.rs",
+    ] {
+        assert_eq!(apply::resolve_hint(hint, root, &files), None, "{hint}");
+    }
+
+    // A block whose hint escapes is matched by its contents instead.
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("counter.rs");
+    std::fs::write(&counter, APPLY_FILE).unwrap();
+    let block = CodeBlock {
+        lang: "rust".into(),
+        path: Some("../../home/me/.bashrc".into()),
+        body: "    fn reset(&mut self) {\n        self.count = 10;\n    }".into(),
+    };
+    assert_eq!(
+        apply::find_target(&block, dir.path(), std::slice::from_ref(&counter), &[]),
+        Some(counter)
+    );
+    let unrelated = CodeBlock {
+        body: "export PATH=/tmp/evil:$PATH".into(),
+        ..block
+    };
+    assert_eq!(apply::find_target(&unrelated, dir.path(), &[], &[]), None);
+}
+
+#[test]
+fn apply_finds_the_file_a_block_is_most_like() {
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("counter.rs");
+    let other = dir.path().join("other.rs");
+    std::fs::write(&counter, APPLY_FILE).unwrap();
+    std::fs::write(&other, "fn main() {}\n").unwrap();
+    let files = vec![counter.clone(), other];
+    let block = CodeBlock {
+        lang: "rust".into(),
+        path: None,
+        body: "    fn reset(&mut self) {\n        self.count = 10;\n    }".into(),
+    };
+    assert_eq!(
+        apply::find_target(&block, dir.path(), &files, &[]),
+        Some(counter)
+    );
+    let unrelated = CodeBlock {
+        body: "fn parse(input: &str) -> Vec<u8> {\n    todo!()\n}".into(),
+        ..block
+    };
+    assert_eq!(
+        apply::find_target(&unrelated, dir.path(), &files, &[]),
+        None
+    );
+}
+
+#[test]
+fn apply_replaces_the_block_it_overlaps() {
+    // Shown without the impl's indentation, with its last line changed.
+    let block = "fn bump(&mut self) {\n    self.count += 2;\n}";
+    let applied = apply::apply_block(APPLY_FILE, block, "rs").unwrap();
+    assert_eq!(applied, APPLY_FILE.replace("count += 1", "count += 2"));
+
+    // Parts between elisions are placed one by one.
+    let block = "\
+struct Counter {
+    count: u64,
+}
+// ...
+    fn reset(&mut self) {
+        self.count = u64::MAX;
+    }";
+    let applied = apply::apply_block(APPLY_FILE, block, "rs").unwrap();
+    assert_eq!(
+        applied,
+        APPLY_FILE
+            .replace("count: u32", "count: u64")
+            .replace("count = 0", "count = u64::MAX")
+    );
+
+    // Nothing in the block appears once in the file.
+    assert_eq!(apply::apply_block(APPLY_FILE, "}\n", "rs"), None);
+}
+
+#[test]
+fn apply_takes_a_block_as_the_whole_file_when_it_is_one() {
+    let whole = APPLY_FILE.replace("use std::fmt;\n\n", "");
+    let applied = apply::apply_block(APPLY_FILE, &whole, "rs").unwrap();
+    assert_eq!(applied, whole);
+    assert_eq!(
+        apply::apply_block("", "fn main() {}", "rs").as_deref(),
+        Some("fn main() {}\n")
+    );
+    let crlf = APPLY_FILE.replace('\n', "\r\n");
+    let applied = apply::apply_block(&crlf, "fn bump(&mut self) {\n    self.count += 2;\n}", "rs");
+    assert_eq!(applied, Some(crlf.replace("count += 1", "count += 2")));
+}
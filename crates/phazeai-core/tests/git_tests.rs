@@ -1,8 +1,17 @@
-use phazeai_core::git::{FileState, GitOps};
-use phazeai_core::project::{FileChangeKind, FileWatcher};
+use phazeai_core::analysis::todos;
+use phazeai_core::git::{
+    parse_blame_porcelain, parse_plan_suggestion, parse_worktree_list, relative_time,
+    task_branch_name, todo_list, validate_plan, worktree_path, CommitSummary, ConflictSide,
+    FileState, GitOps, RebaseAction, RebaseState, RebaseStep,
+};
+use phazeai_core::project::watcher::{
+    fs_type_of, is_network_fs_type, parse_mount_output, parse_proc_mounts, Coalescer,
+};
+use phazeai_core::project::{FileChangeEvent, FileChangeKind, FileWatcher, WatchOptions};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
 use tempfile::TempDir;
 use tokio::time::{sleep, timeout, Duration};
 
@@ -283,6 +292,382 @@ async fn test_log_empty_repo() {
     assert!(result.is_err() || result.unwrap().is_empty());
 }
 
+// ============================================================================
+// GitOps::blame() Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_blame_attributes_lines_to_commits() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+
+    init_git_repo(repo_path);
+
+    let git_ops = GitOps::new(repo_path);
+
+    create_file(repo_path, "lib.rs", "one\ntwo\n");
+    git_ops.add(&["lib.rs"]).await.unwrap();
+    git_ops.commit("Add lib").await.unwrap();
+
+    create_file(repo_path, "lib.rs", "one\nTWO\nthree\n");
+    git_ops.add(&["lib.rs"]).await.unwrap();
+    git_ops.commit("Shout two").await.unwrap();
+
+    let blame = git_ops.blame(&repo_path.join("lib.rs")).await.unwrap();
+    assert_eq!(blame.len(), 3);
+    assert_eq!(blame[0].summary, "Add lib");
+    assert_eq!(blame[1].summary, "Shout two");
+    assert_eq!(blame[1].commit, blame[2].commit);
+    assert_eq!(blame[0].author, "Test");
+    assert!(!blame[0].is_uncommitted());
+}
+
+#[tokio::test]
+async fn test_todo_comments_are_attributed_with_blame() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_git_repo(repo_path);
+    let git_ops = GitOps::new(repo_path);
+
+    create_file(repo_path, "lib.rs", "// TODO: committed\n");
+    git_ops.add(&["lib.rs"]).await.unwrap();
+    git_ops.commit("Add lib").await.unwrap();
+    create_file(
+        repo_path,
+        "lib.rs",
+        "// TODO: committed\n// FIXME: not yet\n",
+    );
+
+    let mut found = todos::scan_workspace(repo_path);
+    todos::attribute_authors(repo_path, &mut found).await;
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].author.as_deref(), Some("Test"));
+    assert_eq!(found[1].author, None);
+}
+
+#[test]
+fn test_parse_blame_porcelain_reuses_commit_headers() {
+    let a = "a".repeat(40);
+    let zero = "0".repeat(40);
+    let porcelain = format!(
+        "{a} 1 1 2\nauthor Ada\nauthor-time 1700000000\nsummary Initial\nfilename x.rs\n\tfn main() {{\n\
+         {a} 2 2\n\t}}\n\
+         {zero} 3 3 1\nauthor Not Committed Yet\nauthor-time 1700000500\nsummary Version of x.rs from x.rs\nfilename x.rs\n\t// wip\n"
+    );
+    let blame = parse_blame_porcelain(&porcelain);
+    assert_eq!(blame.len(), 3);
+    assert_eq!(blame[1].author, "Ada");
+    assert_eq!(blame[1].summary, "Initial");
+    assert_eq!(blame[1].short_commit(), "aaaaaaaa");
+    assert!(blame[2].is_uncommitted());
+}
+
+#[test]
+fn test_relative_time() {
+    let now = 1_700_000_000;
+    assert_eq!(relative_time(now - 5, now), "just now");
+    assert_eq!(relative_time(now - 60, now), "1 minute ago");
+    assert_eq!(relative_time(now - 3 * 86_400, now), "3 days ago");
+    assert_eq!(relative_time(now - 400 * 86_400, now), "1 year ago");
+}
+
+// ============================================================================
+// GitOps::history() Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_history_for_repo_and_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+
+    init_git_repo(repo_path);
+
+    let git_ops = GitOps::new(repo_path);
+
+    create_file(repo_path, "a.txt", "a\n");
+    git_ops.add(&["a.txt"]).await.unwrap();
+    git_ops.commit("Add a | with a pipe").await.unwrap();
+
+    create_file(repo_path, "b.txt", "b\n");
+    git_ops.add(&["b.txt"]).await.unwrap();
+    git_ops.commit("Add b").await.unwrap();
+
+    let all = git_ops.history(10, None).await.unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].subject, "Add b");
+    assert_eq!(all[1].subject, "Add a | with a pipe");
+    assert_eq!(all[1].author, "Test");
+    assert!(all[0].hash.starts_with(&all[0].short_hash));
+
+    let for_a = git_ops.history(10, Some(Path::new("a.txt"))).await.unwrap();
+    assert_eq!(for_a.len(), 1);
+    assert_eq!(for_a[0].hash, all[1].hash);
+
+    let shown = git_ops.show_commit(&all[0].hash, None).await.unwrap();
+    assert!(shown.contains("Add b"));
+    assert!(shown.contains("+b"));
+}
+
+#[tokio::test]
+async fn test_revert_and_restore_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+
+    init_git_repo(repo_path);
+
+    let git_ops = GitOps::new(repo_path);
+
+    create_file(repo_path, "a.txt", "one\n");
+    git_ops.add(&["a.txt"]).await.unwrap();
+    git_ops.commit("One").await.unwrap();
+
+    create_file(repo_path, "a.txt", "two\n");
+    git_ops.add(&["a.txt"]).await.unwrap();
+    git_ops.commit("Two").await.unwrap();
+
+    let history = git_ops.history(10, None).await.unwrap();
+    git_ops.revert(&history[0].hash).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(repo_path.join("a.txt")).unwrap(),
+        "one\n"
+    );
+    assert_eq!(git_ops.history(10, None).await.unwrap().len(), 3);
+
+    git_ops
+        .restore_file(&history[0].hash, Path::new("a.txt"))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(repo_path.join("a.txt")).unwrap(),
+        "two\n"
+    );
+}
+
+// ============================================================================
+// Interactive Rebase Tests
+// ============================================================================
+
+async fn commit_file(git_ops: &GitOps, dir: &Path, name: &str, content: &str, message: &str) {
+    create_file(dir, name, content);
+    git_ops.add(&[name]).await.unwrap();
+    git_ops.commit(message).await.unwrap();
+}
+
+fn step(action: RebaseAction, hash: &str, message: Option<&str>) -> RebaseStep {
+    RebaseStep {
+        action,
+        hash: hash.to_string(),
+        subject: format!("subject {hash}"),
+        message: message.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_todo_list_amends_after_each_reworded_group() {
+    let steps = [
+        step(RebaseAction::Pick, "aaaa", None),
+        step(RebaseAction::Squash, "bbbb", Some("Combined")),
+        step(RebaseAction::Drop, "cccc", None),
+        step(RebaseAction::Fixup, "dddd", None),
+        step(RebaseAction::Reword, "eeee", Some("Better")),
+    ];
+    let todo = todo_list(&steps, |i| Path::new("/tmp").join(format!("msg {i}")));
+    assert_eq!(
+        todo,
+        "pick aaaa subject aaaa\n\
+         squash bbbb subject bbbb\n\
+         drop cccc subject cccc\n\
+         fixup dddd subject dddd\n\
+         exec git commit --amend --only --allow-empty --quiet -F '/tmp/msg 3'\n\
+         pick eeee subject eeee\n\
+         exec git commit --amend --only --allow-empty --quiet -F '/tmp/msg 4'\n"
+    );
+    assert!(validate_plan(&steps).is_ok());
+    assert!(validate_plan(&[step(RebaseAction::Fixup, "aaaa", None)]).is_err());
+    assert!(validate_plan(&[step(RebaseAction::Drop, "aaaa", None)]).is_err());
+}
+
+#[test]
+fn test_parse_plan_suggestion_keeps_every_commit() {
+    let commit = |hash: &str, subject: &str| CommitSummary {
+        hash: hash.repeat(40 / hash.len()),
+        short_hash: hash.repeat(7 / hash.len()),
+        author: "Test".into(),
+        author_time: 0,
+        subject: subject.into(),
+    };
+    let commits = [
+        commit("a", "Add parser"),
+        commit("b", "wip"),
+        commit("c", "fix"),
+    ];
+    let reply = r#"Here you go:
+        [{"hash": "aaaaaaa", "action": "reword", "message": "Add the config parser"},
+         {"hash": "ccccccc", "action": "fixup", "message": "ignored"},
+         {"hash": "zzzzzzz", "action": "pick"}]"#;
+    let steps = parse_plan_suggestion(reply, &commits);
+    let plan: Vec<_> = steps
+        .iter()
+        .map(|s| (&s.hash[..1], s.action, s.message.as_deref()))
+        .collect();
+    assert_eq!(
+        plan,
+        [
+            ("a", RebaseAction::Reword, Some("Add the config parser")),
+            ("b", RebaseAction::Pick, None),
+            ("c", RebaseAction::Fixup, None),
+        ]
+    );
+
+    // A plan that starts with a squash is unusable; everything is picked.
+    let bad = r#"[{"hash": "bbbbbbb", "action": "squash"}]"#;
+    let steps = parse_plan_suggestion(bad, &commits[1..]);
+    assert!(steps.iter().all(|s| s.action == RebaseAction::Pick));
+}
+
+#[tokio::test]
+async fn test_rebase_squashes_rewords_and_drops() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_git_repo(repo_path);
+    let git_ops = GitOps::new(repo_path);
+
+    commit_file(&git_ops, repo_path, "base.txt", "base\n", "Base").await;
+    let base = git_ops.history(1, None).await.unwrap()[0].hash.clone();
+    commit_file(&git_ops, repo_path, "a.txt", "a\n", "Add a").await;
+    commit_file(&git_ops, repo_path, "a.txt", "a2\n", "wip").await;
+    commit_file(&git_ops, repo_path, "junk.txt", "junk\n", "Add junk").await;
+
+    let commits = git_ops.rebase_commits(Some(&base)).await.unwrap();
+    assert_eq!(commits.len(), 3);
+    assert_eq!(commits[0].subject, "Add a");
+
+    let mut steps: Vec<RebaseStep> = commits.iter().map(RebaseStep::pick).collect();
+    steps[0].message = Some("Add a, finished".into());
+    steps[1].action = RebaseAction::Fixup;
+    steps[2].action = RebaseAction::Drop;
+    let state = git_ops.start_rebase(Some(&base), &steps).await.unwrap();
+    assert_eq!(state, RebaseState::Idle);
+
+    let history = git_ops.history(10, None).await.unwrap();
+    let subjects: Vec<_> = history.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, ["Add a, finished", "Base"]);
+    assert_eq!(fs::read_to_string(repo_path.join("a.txt")).unwrap(), "a2\n");
+    assert!(!repo_path.join("junk.txt").exists());
+}
+
+#[tokio::test]
+async fn test_rebase_stops_on_conflict_and_aborts() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path();
+    init_git_repo(repo_path);
+    let git_ops = GitOps::new(repo_path);
+
+    commit_file(&git_ops, repo_path, "x.txt", "1\n", "One").await;
+    let base = git_ops.history(1, None).await.unwrap()[0].hash.clone();
+    commit_file(&git_ops, repo_path, "x.txt", "2\n", "Two").await;
+    commit_file(&git_ops, repo_path, "x.txt", "3\n", "Three").await;
+    let head = git_ops.history(1, None).await.unwrap()[0].hash.clone();
+
+    // Dropping "Two" replays "Three" onto "One": x.txt conflicts.
+    let commits = git_ops.rebase_commits(Some(&base)).await.unwrap();
+    let mut steps: Vec<RebaseStep> = commits.iter().map(RebaseStep::pick).collect();
+    steps[0].action = RebaseAction::Drop;
+    let state = git_ops.start_rebase(Some(&base), &steps).await.unwrap();
+    assert_eq!(
+        state,
+        RebaseState::Stopped {
+            conflicts: vec!["x.txt".to_string()]
+        }
+    );
+
+    git_ops.rebase_abort().await.unwrap();
+    assert_eq!(git_ops.rebase_state().await, RebaseState::Idle);
+    assert_eq!(git_ops.history(1, None).await.unwrap()[0].hash, head);
+
+    // Keeping the replayed commit's side finishes the rebase.
+    git_ops.start_rebase(Some(&base), &steps).await.unwrap();
+    git_ops
+        .resolve_conflict("x.txt", ConflictSide::Commit)
+        .await
+        .unwrap();
+    assert_eq!(git_ops.rebase_continue().await.unwrap(), RebaseState::Idle);
+    assert_eq!(fs::read_to_string(repo_path.join("x.txt")).unwrap(), "3\n");
+    assert_eq!(git_ops.history(10, None).await.unwrap().len(), 2);
+}
+
+// ============================================================================
+// Worktree Tests
+// ============================================================================
+
+#[test]
+fn test_parse_worktree_list() {
+    let output = "worktree /repo\n\
+                  HEAD 1111111111111111111111111111111111111111\n\
+                  branch refs/heads/main\n\
+                  \n\
+                  worktree /repo-worktrees/phaze-fix\n\
+                  HEAD 2222222222222222222222222222222222222222\n\
+                  detached\n\
+                  locked agent running\n";
+    let worktrees = parse_worktree_list(output);
+    assert_eq!(worktrees.len(), 2);
+    assert!(worktrees[0].is_main);
+    assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+    assert_eq!(worktrees[1].path, Path::new("/repo-worktrees/phaze-fix"));
+    assert_eq!(worktrees[1].branch, None);
+    assert!(worktrees[1].locked && !worktrees[1].is_main);
+}
+
+#[test]
+fn test_task_branch_name() {
+    assert_eq!(
+        task_branch_name("Add retry to the HTTP client, then update docs and tests"),
+        "phaze/add-retry-to-the-http-client"
+    );
+    assert_eq!(task_branch_name("  ?? "), "phaze/task");
+    assert_eq!(
+        worktree_path(Path::new("/src/app"), "phaze/fix-it"),
+        Path::new("/src/app-worktrees/phaze-fix-it")
+    );
+}
+
+#[tokio::test]
+async fn test_task_worktree_leaves_main_checkout_alone() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo_path = temp_dir.path().join("app");
+    fs::create_dir(&repo_path).unwrap();
+    init_git_repo(&repo_path);
+    let git_ops = GitOps::new(&repo_path);
+    commit_file(&git_ops, &repo_path, "a.txt", "a\n", "Initial").await;
+
+    let first = git_ops.create_task_worktree("Fix the bug").await.unwrap();
+    let second = git_ops.create_task_worktree("Fix the bug").await.unwrap();
+    assert_eq!(first.branch.as_deref(), Some("phaze/fix-the-bug"));
+    assert_eq!(second.branch.as_deref(), Some("phaze/fix-the-bug-2"));
+    assert_eq!(
+        first.path,
+        temp_dir.path().join("app-worktrees/phaze-fix-the-bug")
+    );
+
+    // Work committed in the task worktree doesn't show up in the main one.
+    let task_git = GitOps::new(&first.path);
+    commit_file(&task_git, &first.path, "b.txt", "b\n", "Task work").await;
+    assert!(!repo_path.join("b.txt").exists());
+    assert!(git_ops.status().await.unwrap().is_clean);
+
+    let listed = git_ops.worktrees().await.unwrap();
+    assert_eq!(listed.len(), 3);
+    assert!(listed[0].is_main);
+
+    git_ops.remove_worktree(&first.path, false).await.unwrap();
+    assert!(!first.path.exists());
+    assert_eq!(git_ops.worktrees().await.unwrap().len(), 2);
+    // The task branch survives its worktree.
+    let history = GitOps::new(&second.path);
+    assert!(history.checkout("phaze/fix-the-bug").await.is_ok());
+}
+
 // ============================================================================
 // GitOps::diff() Tests
 // ============================================================================
@@ -514,6 +899,160 @@ async fn test_watcher_multiple_events() {
     );
 }
 
+#[tokio::test]
+async fn test_watcher_coalesces_bursts() {
+    let temp_dir = TempDir::new().unwrap();
+    let watch_path = temp_dir.path();
+
+    let (_watcher, mut rx) = FileWatcher::watch(watch_path).unwrap();
+    sleep(Duration::from_millis(100)).await;
+
+    // One file written over and over, like a build rewriting its outputs
+    for i in 0..20 {
+        create_file(watch_path, "burst.txt", &format!("Content {i}"));
+    }
+
+    let event = timeout(Duration::from_millis(500), rx.recv())
+        .await
+        .expect("Timeout waiting for event")
+        .expect("No event received");
+    assert_eq!(event.kind, FileChangeKind::Created);
+    assert!(event.path.ends_with("burst.txt"));
+
+    // The rest of the burst was folded into that one event
+    let more = timeout(Duration::from_millis(200), rx.recv()).await;
+    assert!(more.is_err(), "unexpected event: {more:?}");
+}
+
+#[test]
+fn test_coalescer_merges_per_path() {
+    let event = |name: &str, kind| FileChangeEvent {
+        path: PathBuf::from(name),
+        kind,
+    };
+    let mut burst = Coalescer::default();
+    burst.push(event("a", FileChangeKind::Created));
+    burst.push(event("b", FileChangeKind::Modified));
+    burst.push(event("a", FileChangeKind::Modified));
+    burst.push(event("tmp", FileChangeKind::Created));
+    burst.push(event("b", FileChangeKind::Removed));
+    burst.push(event("tmp", FileChangeKind::Removed));
+    // Deleted and written again, as some editors save
+    burst.push(event("c", FileChangeKind::Removed));
+    burst.push(event("c", FileChangeKind::Created));
+
+    assert_eq!(
+        burst.finish(),
+        vec![
+            event("a", FileChangeKind::Created),
+            event("b", FileChangeKind::Removed),
+            event("c", FileChangeKind::Modified),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_watcher_subscriptions_filter_by_glob() {
+    let temp_dir = TempDir::new().unwrap();
+    let watch_path = temp_dir.path();
+    fs::create_dir(watch_path.join("src")).unwrap();
+
+    let watcher = FileWatcher::with_options(watch_path, &WatchOptions::default()).unwrap();
+    let mut rust = watcher.subscribe(&["**/*.rs"]).unwrap();
+    let mut board = watcher.subscribe(&[".phazeai/board.md"]).unwrap();
+    let mut all = watcher.subscribe(&[]).unwrap();
+    sleep(Duration::from_millis(100)).await;
+
+    create_file(watch_path, "notes.txt", "Hello");
+    sleep(Duration::from_millis(100)).await;
+    create_file(&watch_path.join("src"), "main.rs", "fn main() {}");
+
+    let event = timeout(Duration::from_millis(500), rust.recv())
+        .await
+        .expect("Timeout waiting for event")
+        .expect("No event received");
+    assert!(event.path.ends_with("src/main.rs"));
+
+    let first = timeout(Duration::from_millis(500), all.recv())
+        .await
+        .expect("Timeout waiting for event")
+        .expect("No event received");
+    assert!(first.path.ends_with("notes.txt"));
+
+    assert!(timeout(Duration::from_millis(200), board.recv())
+        .await
+        .is_err());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_watcher_can_ignore_symlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let watch_path = temp_dir.path();
+    std::os::unix::fs::symlink(outside.path(), watch_path.join("linked")).unwrap();
+
+    let options = WatchOptions {
+        follow_symlinks: false,
+        ..WatchOptions::default()
+    };
+    let watcher = FileWatcher::with_options(watch_path, &options).unwrap();
+    let mut rx = watcher.subscribe(&[]).unwrap();
+    sleep(Duration::from_millis(100)).await;
+
+    create_file(&watch_path.join("linked"), "through_link.txt", "Hidden");
+    sleep(Duration::from_millis(100)).await;
+    create_file(watch_path, "direct.txt", "Seen");
+
+    let event = timeout(Duration::from_millis(500), rx.recv())
+        .await
+        .expect("Timeout waiting for event")
+        .expect("No event received");
+    assert!(event.path.ends_with("direct.txt"));
+}
+
+#[tokio::test]
+async fn test_shared_watcher_is_reused() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let first = FileWatcher::shared(temp_dir.path()).unwrap();
+    let second = FileWatcher::shared(temp_dir.path()).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first.root(), temp_dir.path());
+}
+
+#[test]
+fn test_network_filesystem_detection() {
+    let proc_mounts = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+nas:/export/home /home/me nfs4 rw,vers=4.2 0 0
+//nas/team\\040share /mnt/team\\040share cifs rw 0 0
+me@box:/srv /home/me/box fuse.sshfs rw 0 0
+";
+    let mounts = parse_proc_mounts(proc_mounts);
+    assert_eq!(mounts.len(), 4);
+    assert_eq!(mounts[2].point, PathBuf::from("/mnt/team share"));
+
+    let fs_type = |path: &str| fs_type_of(&mounts, Path::new(path));
+    assert_eq!(fs_type("/home/me/project"), Some("nfs4"));
+    assert_eq!(fs_type("/home/me/box/src"), Some("fuse.sshfs"));
+    assert_eq!(fs_type("/home/other"), Some("ext4"));
+    assert!(is_network_fs_type("nfs4"));
+    assert!(is_network_fs_type("cifs"));
+    assert!(!is_network_fs_type("ext4"));
+
+    let mount_output = "\
+/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)
+//me@nas/share on /Volumes/share (smbfs, nodev, nosuid, mounted by me)
+";
+    let mounts = parse_mount_output(mount_output);
+    assert_eq!(
+        fs_type_of(&mounts, Path::new("/Volumes/share/repo")),
+        Some("smbfs")
+    );
+    assert_eq!(fs_type_of(&mounts, Path::new("/Users/me")), Some("apfs"));
+}
+
 // ============================================================================
 // FileChangeKind Enum Tests
 // ============================================================================
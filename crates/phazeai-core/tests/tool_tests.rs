@@ -1,5 +1,7 @@
+use phazeai_core::project::FileJournal;
 use phazeai_core::tools::{
-    BashTool, EditTool, GlobTool, GrepTool, ListFilesTool, ReadFileTool, Tool, WriteFileTool,
+    shell_code_blocks, BashTool, DeletePathTool, EditTool, FileHistoryTool, GlobTool, GrepTool,
+    ListFilesTool, MovePathTool, ReadFileTool, Tool, ToolRegistry, WriteFileTool,
 };
 use serde_json::json;
 use std::path::PathBuf;
@@ -457,6 +459,38 @@ async fn test_bash_error_command() {
     assert_ne!(result["exit_code"], 0);
 }
 
+#[test]
+fn test_shell_code_blocks() {
+    let reply = "Build it:\n\n```bash\ncargo build --release\n```\n\n\
+                 ```rust\nfn main() {}\n```\n\n\
+                 ```console\n$ ls -la\ntotal 0\n$ git status\n```\n\n```sh\n\n```\n";
+    assert_eq!(
+        shell_code_blocks(reply),
+        vec!["cargo build --release", "ls -la\ngit status"]
+    );
+}
+
+#[test]
+fn test_registry_with_filter() {
+    let all = ToolRegistry::default();
+    assert!(all.names().contains(&"bash".to_string()));
+
+    let disabled = ["bash", "write_file", "edit_file"];
+    let filtered = ToolRegistry::default().with_filter(|t| !disabled.contains(&t.name()));
+    assert_eq!(filtered.tool_count(), all.tool_count() - disabled.len());
+    assert!(filtered.get("bash").is_none());
+    assert!(filtered.get("read_file").is_some());
+    assert!(filtered
+        .definitions()
+        .iter()
+        .all(|d| !disabled.contains(&d.name.as_str())));
+
+    let names = filtered.names();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+}
+
 // ============================================================================
 // GrepTool Tests
 // ============================================================================
@@ -755,3 +789,82 @@ async fn test_list_files_default_recursive() {
     // Should not include nested files with default recursive=false
     assert!(!file_names.iter().any(|n| n.contains("nested.txt")));
 }
+
+// ============================================================================
+// DeletePathTool / MovePathTool / FileHistoryTool Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_delete_path_goes_to_trash_and_undoes() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_dir = create_test_files(&temp_dir).await;
+    let journal = FileJournal::for_workspace(&test_dir);
+    let delete = DeletePathTool::new(journal.clone());
+    let history = FileHistoryTool::new(journal.clone());
+
+    let result = delete
+        .execute(json!({
+            "paths": [
+                test_dir.join("test.txt").to_string_lossy(),
+                test_dir.join("subdir").to_string_lossy(),
+            ]
+        }))
+        .await
+        .unwrap();
+    assert_eq!(result["success"], true);
+    assert_eq!(result["steps"].as_array().unwrap().len(), 2);
+    assert!(!test_dir.join("test.txt").exists());
+    assert!(!test_dir.join("subdir").exists());
+
+    let listed = history.execute(json!({ "action": "list" })).await.unwrap();
+    assert_eq!(listed["operations"].as_array().unwrap().len(), 1);
+
+    history.execute(json!({ "action": "undo" })).await.unwrap();
+    assert!(test_dir.join("test.txt").exists());
+    assert_eq!(
+        tokio::fs::read_to_string(test_dir.join("subdir/nested.txt"))
+            .await
+            .unwrap(),
+        "nested content\nmore lines"
+    );
+    assert!(history.execute(json!({ "action": "undo" })).await.is_err());
+}
+
+#[tokio::test]
+async fn test_move_path_bulk_moves_undo_together() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_dir = create_test_files(&temp_dir).await;
+    let journal = FileJournal::for_workspace(&test_dir);
+    let tool = MovePathTool::new(journal.clone());
+    let path = |name: &str| test_dir.join(name).to_string_lossy().to_string();
+
+    tool.execute(json!({
+        "moves": [
+            { "source": path("test.txt"), "destination": path("docs/test.txt") },
+            { "source": path("test.rs"), "destination": path("src/main.rs") },
+            // Replaces hello.txt, which goes to the trash.
+            { "source": path("subdir/nested.txt"), "destination": path("hello.txt") },
+        ]
+    }))
+    .await
+    .unwrap();
+    assert!(test_dir.join("src/main.rs").exists());
+    assert_eq!(
+        tokio::fs::read_to_string(test_dir.join("hello.txt"))
+            .await
+            .unwrap(),
+        "nested content\nmore lines"
+    );
+
+    journal.undo(None).unwrap();
+    assert!(test_dir.join("test.txt").exists());
+    assert!(test_dir.join("test.rs").exists());
+    assert!(test_dir.join("subdir/nested.txt").exists());
+    assert!(!test_dir.join("src/main.rs").exists());
+    assert_eq!(
+        tokio::fs::read_to_string(test_dir.join("hello.txt"))
+            .await
+            .unwrap(),
+        "Hello World\nFoo Bar\nHello Again"
+    );
+}
@@ -1,6 +1,8 @@
-use crate::protocol::{JsonRpcRequest, JsonRpcResponse};
+use crate::protocol::{Handshake, JsonRpcRequest, JsonRpcResponse, PROTOCOL_VERSION};
+use phazeai_core::ToolDefinition;
+use serde::Deserialize;
 use serde_json::Value;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Child;
 use tokio::sync::Mutex;
@@ -12,6 +14,24 @@ pub struct SidecarClient {
     stdout: Mutex<BufReader<tokio::process::ChildStdout>>,
     process: Mutex<Child>,
     next_id: AtomicU64,
+    /// Calls waiting for their response.
+    in_flight: AtomicUsize,
+}
+
+/// Counts a call as in flight until dropped, however the call ends.
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn new(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self(count)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl SidecarClient {
@@ -30,9 +50,22 @@ impl SidecarClient {
             stdout: Mutex::new(BufReader::new(stdout)),
             process: Mutex::new(process),
             next_id: AtomicU64::new(1),
+            in_flight: AtomicUsize::new(0),
         })
     }
 
+    /// Whether a call is waiting for its response. Calls are answered in
+    /// order, so a ping sent now would wait behind it.
+    pub fn is_busy(&self) -> bool {
+        self.in_flight.load(Ordering::SeqCst) > 0
+    }
+
+    /// Whether the sidecar process has ended.
+    pub async fn has_exited(&self) -> bool {
+        let mut process = self.process.lock().await;
+        !matches!(process.try_wait(), Ok(None))
+    }
+
     pub async fn shutdown(&self) -> Result<(), String> {
         let mut process = self.process.lock().await;
         match process.kill().await {
@@ -43,6 +76,7 @@ impl SidecarClient {
     }
 
     pub async fn call(&self, method: &str, params: Option<Value>) -> Result<Value, String> {
+        let _in_flight = InFlight::new(&self.in_flight);
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         let request = JsonRpcRequest::new(id, method, params);
 
@@ -67,10 +101,13 @@ impl SidecarClient {
         let mut line = String::new();
         {
             let mut stdout = self.stdout.lock().await;
-            stdout
+            let read = stdout
                 .read_line(&mut line)
                 .await
                 .map_err(|e| format!("Read error: {e}"))?;
+            if read == 0 {
+                return Err("Sidecar closed its output".to_string());
+            }
         }
 
         let response: JsonRpcResponse =
@@ -90,6 +127,30 @@ impl SidecarClient {
         .await
     }
 
+    /// Snippets like `code` from the index, near-duplicates first. When the
+    /// code comes from the index itself, `path` and its 1-based `lines` keep
+    /// it out of its own results.
+    pub async fn find_similar(
+        &self,
+        code: &str,
+        path: Option<&str>,
+        lines: Option<(u32, u32)>,
+        top_k: usize,
+    ) -> Result<Value, String> {
+        let mut params = serde_json::json!({
+            "code": code,
+            "top_k": top_k,
+        });
+        if let Some(path) = path {
+            params["path"] = path.into();
+        }
+        if let Some((start, end)) = lines {
+            params["start_line"] = start.into();
+            params["end_line"] = end.into();
+        }
+        self.call("find_similar", Some(params)).await
+    }
+
     pub async fn build_index(&self, paths: &[String]) -> Result<Value, String> {
         self.call(
             "build_index",
@@ -114,6 +175,46 @@ impl SidecarClient {
     pub async fn health_check(&self) -> bool {
         self.call("ping", None).await.is_ok()
     }
+
+    /// Tell the sidecar which protocol we speak, and ask who it is, which
+    /// protocol it speaks and what it offers.
+    pub async fn handshake(&self) -> Result<Handshake, String> {
+        let params = serde_json::json!({
+            "protocol": PROTOCOL_VERSION,
+            "client": { "name": "phazeai", "version": env!("CARGO_PKG_VERSION") },
+        });
+        let value = self.call("handshake", Some(params)).await.map_err(|e| {
+            if e.starts_with("Method not found") {
+                "sidecar predates the handshake; update it".to_string()
+            } else {
+                e
+            }
+        })?;
+        serde_json::from_value(value).map_err(|e| format!("Invalid handshake: {e}"))
+    }
+
+    /// The tools a sidecar with the `tools` capability provides.
+    pub async fn list_tools(&self) -> Result<Vec<ToolDefinition>, String> {
+        #[derive(Deserialize)]
+        struct ToolList {
+            tools: Vec<ToolDefinition>,
+        }
+        let value = self.call("tools/list", None).await?;
+        serde_json::from_value::<ToolList>(value)
+            .map(|list| list.tools)
+            .map_err(|e| format!("Invalid tool list: {e}"))
+    }
+
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, String> {
+        self.call(
+            "tools/call",
+            Some(serde_json::json!({
+                "name": name,
+                "arguments": arguments,
+            })),
+        )
+        .await
+    }
 }
 
 impl Drop for SidecarClient {
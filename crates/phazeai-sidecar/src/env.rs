@@ -0,0 +1,250 @@
+//! The sidecar's Python environment.
+//!
+//! The sidecar runs in a virtualenv of its own under
+//! `~/.local/share/phazeai/sidecar/venv`, so it never depends on what is
+//! installed in the system Python. [`SidecarEnv::prepare`] creates it on
+//! first start from an interpreter found by [`find_python`], and installs
+//! the pinned packages of the `requirements.txt` next to the sidecar script
+//! into it. The requirements last installed are recorded in the venv: an
+//! unchanged environment is used as is, and changed requirements are
+//! installed on the next start. [`SidecarEnv::remove`] wipes the venv so the
+//! next start rebuilds it from scratch.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// Oldest Python the sidecar runs on.
+pub const MIN_PYTHON: (u32, u32) = (3, 8);
+
+/// Prints the interpreter's version, and fails if it can't make venvs.
+const PROBE: &str = "import sys, venv; print('%d.%d.%d' % sys.version_info[:3])";
+
+/// The requirements installed into the venv, as last installed.
+const STAMP: &str = "phazeai-requirements.txt";
+
+/// A Python interpreter the sidecar can run on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonInfo {
+    pub path: String,
+    pub version: (u32, u32, u32),
+}
+
+/// Parse a `major.minor.patch` version as printed by the probe.
+pub fn parse_python_version(text: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = text.trim().split('.').map(|p| p.parse::<u32>().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+/// Whether `requirements` lists any package, i.e. has a line that isn't
+/// blank or a comment.
+pub fn has_packages(requirements: &str) -> bool {
+    requirements
+        .lines()
+        .map(str::trim)
+        .any(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Check that `python` runs, is at least [`MIN_PYTHON`] and can create
+/// virtualenvs.
+pub async fn probe_python(python: &str) -> Result<PythonInfo, String> {
+    let output = Command::new(python)
+        .args(["-c", PROBE])
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("{python}: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().last().unwrap_or("failed to run");
+        return Err(format!("{python}: {reason}"));
+    }
+    let version = parse_python_version(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| format!("{python}: unrecognized version"))?;
+    if (version.0, version.1) < MIN_PYTHON {
+        return Err(format!(
+            "{python}: Python {}.{}.{} is older than {}.{}",
+            version.0, version.1, version.2, MIN_PYTHON.0, MIN_PYTHON.1
+        ));
+    }
+    Ok(PythonInfo {
+        path: python.to_string(),
+        version,
+    })
+}
+
+/// The first usable interpreter: `preferred` (the one in the settings), then
+/// the usual names on `PATH`. The error says why each was passed over.
+pub async fn find_python(preferred: &str) -> Result<PythonInfo, String> {
+    let mut candidates = vec![preferred];
+    for name in ["python3", "python"] {
+        if !candidates.contains(&name) {
+            candidates.push(name);
+        }
+    }
+    let mut reasons = Vec::new();
+    for candidate in candidates {
+        match probe_python(candidate).await {
+            Ok(info) => return Ok(info),
+            Err(e) => reasons.push(e),
+        }
+    }
+    Err(format!("no usable Python found ({})", reasons.join("; ")))
+}
+
+/// The virtualenv the sidecar runs in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SidecarEnv {
+    root: PathBuf,
+}
+
+impl SidecarEnv {
+    /// An environment kept under `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `~/.local/share/phazeai/sidecar`, or the platform's equivalent.
+    pub fn default_root() -> Option<PathBuf> {
+        dirs::data_local_dir().map(|dir| dir.join("phazeai").join("sidecar"))
+    }
+
+    pub fn venv_dir(&self) -> PathBuf {
+        self.root.join("venv")
+    }
+
+    /// The venv's interpreter, whether or not the venv exists yet.
+    pub fn python(&self) -> PathBuf {
+        if cfg!(windows) {
+            self.venv_dir().join("Scripts").join("python.exe")
+        } else {
+            self.venv_dir().join("bin").join("python")
+        }
+    }
+
+    /// Whether the venv exists with exactly `requirements` installed.
+    pub fn is_current(&self, requirements: &str) -> bool {
+        self.python().exists()
+            && std::fs::read_to_string(self.venv_dir().join(STAMP))
+                .ok()
+                .as_deref()
+                == Some(requirements)
+    }
+
+    /// Make sure the venv exists with `requirements` installed, creating it
+    /// from `base_python` (or another interpreter if that one won't do) when
+    /// needed, and return its interpreter. Each step, and the output of the
+    /// tools run, is reported to `progress` a line at a time.
+    pub async fn prepare(
+        &self,
+        base_python: &str,
+        requirements: Option<&Path>,
+        mut progress: impl FnMut(String),
+    ) -> Result<PathBuf, String> {
+        let wanted = match requirements {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Cannot read {}: {e}", path.display()))?,
+            None => String::new(),
+        };
+        if self.is_current(&wanted) {
+            return Ok(self.python());
+        }
+
+        let python = self.python();
+        let venv_works = python.exists() && probe_python(&python.to_string_lossy()).await.is_ok();
+        if !venv_works {
+            let base = find_python(base_python).await?;
+            let (major, minor, patch) = base.version;
+            progress(format!(
+                "Creating Python {major}.{minor}.{patch} virtualenv in {}",
+                self.venv_dir().display()
+            ));
+            // Left behind by a failed attempt or a moved interpreter.
+            self.remove()?;
+            std::fs::create_dir_all(&self.root)
+                .map_err(|e| format!("Cannot create {}: {e}", self.root.display()))?;
+            let mut venv = Command::new(&base.path);
+            venv.arg("-m").arg("venv").arg(self.venv_dir());
+            run(venv, &mut progress)
+                .await
+                .map_err(|e| format!("Creating the virtualenv failed: {e}"))?;
+        }
+
+        if let Some(path) = requirements.filter(|_| has_packages(&wanted)) {
+            progress(format!("Installing {}", path.display()));
+            let mut pip = Command::new(&python);
+            pip.args([
+                "-m",
+                "pip",
+                "install",
+                "--disable-pip-version-check",
+                "--no-input",
+                "-r",
+            ])
+            .arg(path);
+            run(pip, &mut progress)
+                .await
+                .map_err(|e| format!("Installing requirements failed: {e}"))?;
+        }
+
+        std::fs::write(self.venv_dir().join(STAMP), &wanted)
+            .map_err(|e| format!("Cannot record installed requirements: {e}"))?;
+        Ok(python)
+    }
+
+    /// Delete the venv; the next [`prepare`](Self::prepare) rebuilds it.
+    pub fn remove(&self) -> Result<(), String> {
+        let dir = self.venv_dir();
+        if !dir.exists() {
+            return Ok(());
+        }
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("Cannot remove {}: {e}", dir.display()))
+    }
+}
+
+/// Run `command` to completion, passing each line it prints on stdout or
+/// stderr to `progress`. Fails with its last line when it exits non-zero.
+async fn run(mut command: Command, progress: &mut impl FnMut(String)) -> Result<(), String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(forward_lines(stdout, tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(forward_lines(stderr, tx));
+    }
+    let mut last = None;
+    while let Some(line) = rx.recv().await {
+        last = Some(line.clone());
+        progress(line);
+    }
+
+    let status = child.wait().await.map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(last.unwrap_or_else(|| status.to_string()))
+    }
+}
+
+async fn forward_lines(stream: impl AsyncRead + Unpin, tx: mpsc::UnboundedSender<String>) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if tx.send(line).is_err() {
+            break;
+        }
+    }
+}
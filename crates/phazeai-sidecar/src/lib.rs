@@ -1,9 +1,18 @@
 mod client;
+mod env;
 mod manager;
+mod plugin;
 mod protocol;
+mod supervisor;
 mod tool;
 
 pub use client::SidecarClient;
+pub use env::{
+    find_python, has_packages, parse_python_version, probe_python, PythonInfo, SidecarEnv,
+    MIN_PYTHON,
+};
 pub use manager::SidecarManager;
-pub use protocol::{JsonRpcRequest, JsonRpcResponse};
-pub use tool::{BuildIndexTool, SemanticSearchTool};
+pub use plugin::{plugin_sidecar_tools, PluginSidecar, PluginTool};
+pub use protocol::{Capabilities, Handshake, JsonRpcRequest, JsonRpcResponse, PROTOCOL_VERSION};
+pub use supervisor::{ClientSlot, SidecarEvent, SidecarHandle, SidecarState, SidecarSupervisor};
+pub use tool::{BuildIndexTool, FindSimilarCodeTool, SemanticSearchTool};
@@ -1,24 +1,48 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Stdio;
 use tokio::process::{Child, Command};
 use tracing::{info, warn};
 
-/// Manages the Python sidecar process lifecycle.
+/// Manages a sidecar process's lifecycle: the Python sidecar, or any other
+/// command speaking the same protocol.
 pub struct SidecarManager {
-    python_path: String,
-    script_path: PathBuf,
+    program: String,
+    args: Vec<String>,
+    envs: HashMap<String, String>,
+    /// The Python script, checked for before starting.
+    script_path: Option<PathBuf>,
     process: Option<Child>,
 }
 
 impl SidecarManager {
     pub fn new(python_path: impl Into<String>, script_path: impl Into<PathBuf>) -> Self {
         Self {
-            python_path: python_path.into(),
-            script_path: script_path.into(),
+            program: python_path.into(),
+            args: Vec::new(),
+            envs: HashMap::new(),
+            script_path: Some(script_path.into()),
             process: None,
         }
     }
 
+    /// A sidecar started as `program args…`, e.g. `node helper.js`.
+    pub fn command(program: impl Into<String>, args: Vec<String>) -> Self {
+        Self {
+            program: program.into(),
+            args,
+            envs: HashMap::new(),
+            script_path: None,
+            process: None,
+        }
+    }
+
+    /// Set environment variables for the process, on top of the IDE's own.
+    pub fn envs(mut self, envs: HashMap<String, String>) -> Self {
+        self.envs = envs;
+        self
+    }
+
     pub fn is_running(&self) -> bool {
         self.process.is_some()
     }
@@ -28,21 +52,27 @@ impl SidecarManager {
             return Ok(());
         }
 
-        if !self.script_path.exists() {
-            return Err(format!(
-                "Sidecar script not found: {}",
-                self.script_path.display()
-            ));
+        let mut command = Command::new(&self.program);
+        if let Some(script_path) = &self.script_path {
+            if !script_path.exists() {
+                return Err(format!(
+                    "Sidecar script not found: {}",
+                    script_path.display()
+                ));
+            }
+            info!(
+                "Starting Python sidecar: {} {}",
+                self.program,
+                script_path.display()
+            );
+            command.arg(script_path);
+        } else {
+            info!("Starting sidecar: {} {}", self.program, self.args.join(" "));
         }
 
-        info!(
-            "Starting Python sidecar: {} {}",
-            self.python_path,
-            self.script_path.display()
-        );
-
-        let child = Command::new(&self.python_path)
-            .arg(&self.script_path)
+        let child = command
+            .args(&self.args)
+            .envs(&self.envs)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
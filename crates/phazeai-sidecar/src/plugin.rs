@@ -0,0 +1,168 @@
+//! Plugin sidecars: helper processes in any language whose tools the agent
+//! can call.
+//!
+//! A plugin is any executable, configured under `[[plugin_sidecars]]`, that
+//! speaks the sidecar's JSON-RPC protocol over stdio. It is started for an
+//! agent run, must pass the `handshake` and declare the `tools` capability,
+//! and its tools, as listed by `tools/list`, are registered with the agent as
+//! `sidecar__<plugin>__<tool>` so they can't shadow built-in tools or each
+//! other. The process ends when the last of its tools is dropped.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use phazeai_core::config::PluginSidecarConfig;
+use phazeai_core::{PhazeError, Tool, ToolDefinition, ToolResult};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tracing::{debug, info, warn};
+
+use crate::{Handshake, SidecarClient, SidecarManager};
+
+/// How long a plugin may take to start and answer the handshake.
+const START_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A running plugin sidecar and the tools it provides.
+pub struct PluginSidecar {
+    name: String,
+    handshake: Handshake,
+    definitions: Vec<ToolDefinition>,
+    client: Arc<SidecarClient>,
+}
+
+impl PluginSidecar {
+    /// Start the plugin and negotiate with it: check its protocol, that it
+    /// offers tools, and that it has every tool `config` asks for.
+    pub async fn start(config: &PluginSidecarConfig) -> Result<Self, String> {
+        let mut manager =
+            SidecarManager::command(&config.command, config.args.clone()).envs(config.env.clone());
+        manager.start().await?;
+        let mut process = manager
+            .take_process()
+            .ok_or("Sidecar process handle missing")?;
+        // Drained into the log so a chatty plugin can't fill the pipe and stall.
+        if let Some(stderr) = process.stderr.take() {
+            let name = config.name.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!("[{name}] {line}");
+                }
+            });
+        }
+        let client = Arc::new(SidecarClient::from_process(process)?);
+
+        let (handshake, mut definitions) = tokio::time::timeout(START_TIMEOUT, async {
+            let handshake = client.handshake().await?;
+            handshake.check()?;
+            if !handshake.capabilities.tools {
+                return Err("does not offer tools".to_string());
+            }
+            let definitions = client.list_tools().await?;
+            Ok::<_, String>((handshake, definitions))
+        })
+        .await
+        .map_err(|_| "did not answer the handshake in time".to_string())??;
+
+        if !config.tools.is_empty() {
+            if let Some(missing) = config
+                .tools
+                .iter()
+                .find(|name| !definitions.iter().any(|d| d.name == **name))
+            {
+                return Err(format!("does not provide the tool `{missing}`"));
+            }
+            definitions.retain(|d| config.tools.contains(&d.name));
+        }
+
+        info!(
+            "Plugin sidecar {} ({} {}) provides {} tools",
+            config.name,
+            handshake.name,
+            handshake.version,
+            definitions.len()
+        );
+        Ok(Self {
+            name: config.name.clone(),
+            handshake,
+            definitions,
+            client,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn handshake(&self) -> &Handshake {
+        &self.handshake
+    }
+
+    /// Its tools, under the names it gave them.
+    pub fn definitions(&self) -> &[ToolDefinition] {
+        &self.definitions
+    }
+
+    /// Its tools, ready to register with an agent.
+    pub fn tools(&self) -> Vec<Box<dyn Tool>> {
+        self.definitions
+            .iter()
+            .map(|definition| {
+                Box::new(PluginTool {
+                    tool_name: format!("sidecar__{}__{}", self.name, definition.name),
+                    remote_name: definition.name.clone(),
+                    description: definition.description.clone(),
+                    parameters: definition.parameters.clone(),
+                    client: self.client.clone(),
+                }) as Box<dyn Tool>
+            })
+            .collect()
+    }
+}
+
+/// Start every configured plugin and collect their tools. A plugin that
+/// fails to start is logged and left out, so one broken helper doesn't stop
+/// the agent from running.
+pub async fn plugin_sidecar_tools(configs: &[PluginSidecarConfig]) -> Vec<Box<dyn Tool>> {
+    let mut tools = Vec::new();
+    for config in configs {
+        match PluginSidecar::start(config).await {
+            Ok(plugin) => tools.extend(plugin.tools()),
+            Err(e) => warn!("Plugin sidecar {} unavailable: {e}", config.name),
+        }
+    }
+    tools
+}
+
+/// One tool of a plugin sidecar, called over its JSON-RPC connection.
+pub struct PluginTool {
+    /// Name as seen by the agent, e.g. `sidecar__jira__create_issue`.
+    tool_name: String,
+    /// Name the plugin knows it by, e.g. `create_issue`.
+    remote_name: String,
+    description: String,
+    parameters: Value,
+    client: Arc<SidecarClient>,
+}
+
+#[async_trait::async_trait]
+impl Tool for PluginTool {
+    fn name(&self) -> &str {
+        &self.tool_name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        self.client
+            .call_tool(&self.remote_name, params)
+            .await
+            .map_err(|e| PhazeError::tool(&self.tool_name, e))
+    }
+}
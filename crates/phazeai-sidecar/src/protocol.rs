@@ -52,3 +52,41 @@ impl JsonRpcResponse {
         }
     }
 }
+
+/// Version of the request/response contract between the IDE and the sidecar.
+/// Bumped when a method changes shape; a sidecar speaking another version is
+/// refused at startup instead of failing on its first real request.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The sidecar's reply to `handshake`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handshake {
+    pub name: String,
+    pub version: String,
+    pub protocol: u32,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+/// What a sidecar offers beyond `ping` and `handshake`, as it declares in
+/// its handshake. Anything left out is taken as not offered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Answers `tools/list` with `{"tools": [{name, description,
+    /// parameters}]}` and runs them on `tools/call` with `{name, arguments}`.
+    #[serde(default)]
+    pub tools: bool,
+}
+
+impl Handshake {
+    /// Whether the IDE can talk to this sidecar.
+    pub fn check(&self) -> Result<(), String> {
+        if self.protocol != PROTOCOL_VERSION {
+            return Err(format!(
+                "{} {} speaks protocol {}, expected {}",
+                self.name, self.version, self.protocol, PROTOCOL_VERSION
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+//! Keeps the Python sidecar running.
+//!
+//! [`SidecarSupervisor`] starts the sidecar, checks it speaks our protocol,
+//! pings it while it is idle, and restarts it with exponential backoff when
+//! it crashes or stops answering, giving up after a few consecutive failures
+//! until it is restarted by hand. Given a [`SidecarEnv`], it runs the sidecar
+//! in that virtualenv, setting it up first when needed. The sidecar's stderr
+//! and the setup's output are forwarded line by line so the IDE can show them
+//! in the Output panel, with the secrets of the workspace's [`EnvVars`]
+//! masked.
+
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use phazeai_core::lsp::Backoff;
+use phazeai_core::progress;
+use phazeai_core::project::EnvVars;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::mpsc as tokio_mpsc;
+use tracing::{info, warn};
+
+use crate::{SidecarClient, SidecarEnv, SidecarManager};
+
+/// How often an idle sidecar is pinged.
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+/// How long a ping or the handshake may take.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// A sidecar that ran this long before failing starts its backoff over.
+const STABLE_AFTER: Duration = Duration::from_secs(120);
+
+/// The slot the current client is published in while the sidecar is ready.
+pub type ClientSlot = Arc<Mutex<Option<Arc<SidecarClient>>>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SidecarState {
+    Stopped,
+    Starting,
+    /// Up and answering; `version` is what it reported in the handshake.
+    Ready {
+        version: String,
+    },
+    /// Down; the `attempt`th restart happens after `delay`.
+    Restarting {
+        attempt: u32,
+        delay: Duration,
+    },
+    /// Gave up restarting; restart by hand.
+    Failed(String),
+}
+
+impl SidecarState {
+    pub fn label(&self) -> String {
+        match self {
+            SidecarState::Stopped => "stopped".to_string(),
+            SidecarState::Starting => "starting".to_string(),
+            SidecarState::Ready { version } => format!("running (v{version})"),
+            SidecarState::Restarting { attempt, delay } => {
+                format!("restarting in {}s (attempt {attempt})", delay.as_secs())
+            }
+            SidecarState::Failed(reason) => format!("failed: {reason}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SidecarEvent {
+    State(SidecarState),
+    /// A line the sidecar wrote to stderr.
+    Stderr(String),
+    /// A step of setting up its environment, or a line of output from it.
+    Setup(String),
+}
+
+enum Command {
+    Restart,
+    Repair,
+    Stop,
+}
+
+/// Controls a running [`SidecarSupervisor`]. Dropping every clone stops the
+/// sidecar.
+#[derive(Clone)]
+pub struct SidecarHandle {
+    commands: tokio_mpsc::UnboundedSender<Command>,
+}
+
+impl SidecarHandle {
+    /// Restart the sidecar now, with a fresh backoff, even if it was given up on.
+    pub fn restart(&self) {
+        let _ = self.commands.send(Command::Restart);
+    }
+
+    /// Rebuild the sidecar's environment from scratch and restart it.
+    pub fn repair(&self) {
+        let _ = self.commands.send(Command::Repair);
+    }
+
+    pub fn stop(&self) {
+        let _ = self.commands.send(Command::Stop);
+    }
+
+    /// Whether the supervisor is still running, i.e. hasn't been stopped.
+    pub fn is_running(&self) -> bool {
+        !self.commands.is_closed()
+    }
+}
+
+pub struct SidecarSupervisor {
+    python_path: String,
+    script_path: PathBuf,
+    backoff: Backoff,
+    ping_interval: Duration,
+    client: ClientSlot,
+    env: Option<SidecarEnv>,
+    rebuild_env: bool,
+    variables: EnvVars,
+}
+
+/// Why the supervisor stopped watching a sidecar.
+enum Outcome {
+    Failed(String),
+    Restart,
+    Repair,
+    Stop,
+}
+
+impl SidecarSupervisor {
+    pub fn new(python_path: impl Into<String>, script_path: impl Into<PathBuf>) -> Self {
+        Self {
+            python_path: python_path.into(),
+            script_path: script_path.into(),
+            backoff: Backoff::default(),
+            ping_interval: PING_INTERVAL,
+            client: Arc::new(Mutex::new(None)),
+            env: None,
+            rebuild_env: false,
+            variables: EnvVars::default(),
+        }
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Publish the client in `slot` (and clear it while the sidecar is down)
+    /// instead of a slot of the supervisor's own.
+    pub fn client_slot(mut self, slot: ClientSlot) -> Self {
+        self.client = slot;
+        self
+    }
+
+    /// Run the sidecar in `env`'s virtualenv, created from the configured
+    /// interpreter, instead of on that interpreter directly.
+    pub fn environment(mut self, env: SidecarEnv) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    /// Start the sidecar with `variables` set, and mask their secrets in its
+    /// stderr.
+    pub fn variables(mut self, variables: EnvVars) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Delete the environment before the first launch so it is rebuilt.
+    pub fn rebuild_environment(mut self) -> Self {
+        self.rebuild_env = true;
+        self
+    }
+
+    /// Run the supervisor on a thread of its own, sending state changes and
+    /// stderr lines to `events`.
+    pub fn spawn(self, events: mpsc::Sender<SidecarEvent>) -> SidecarHandle {
+        let (tx, rx) = tokio_mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt.block_on(self.run(events, rx)),
+                Err(e) => {
+                    let _ = events.send(SidecarEvent::State(SidecarState::Failed(format!(
+                        "runtime error: {e}"
+                    ))));
+                }
+            }
+        });
+        SidecarHandle { commands: tx }
+    }
+
+    async fn run(
+        mut self,
+        events: mpsc::Sender<SidecarEvent>,
+        mut commands: tokio_mpsc::UnboundedReceiver<Command>,
+    ) {
+        let state = |s: SidecarState| {
+            let _ = events.send(SidecarEvent::State(s));
+        };
+        if self.rebuild_env {
+            self.repair_or_report(&events);
+        }
+        loop {
+            state(SidecarState::Starting);
+            let started = Instant::now();
+            let outcome = match self.launch(&events).await {
+                Ok((client, version)) => {
+                    info!("Sidecar {version} ready");
+                    self.publish(Some(client.clone()));
+                    state(SidecarState::Ready { version });
+                    let outcome = self.watch(&client, &mut commands).await;
+                    self.publish(None);
+                    let _ = client.shutdown().await;
+                    outcome
+                }
+                Err(e) => Outcome::Failed(e),
+            };
+
+            let reason = match outcome {
+                Outcome::Stop => {
+                    state(SidecarState::Stopped);
+                    return;
+                }
+                Outcome::Restart => {
+                    self.backoff.reset();
+                    continue;
+                }
+                Outcome::Repair => match self.repair() {
+                    Ok(()) => {
+                        self.backoff.reset();
+                        continue;
+                    }
+                    Err(reason) => reason,
+                },
+                Outcome::Failed(reason) => reason,
+            };
+            warn!("Sidecar failed: {reason}");
+            if started.elapsed() >= STABLE_AFTER {
+                self.backoff.reset();
+            }
+            match self.backoff.next_delay() {
+                Some(delay) => {
+                    state(SidecarState::Restarting {
+                        attempt: self.backoff.attempt(),
+                        delay,
+                    });
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        cmd = commands.recv() => match cmd {
+                            Some(Command::Restart) => self.backoff.reset(),
+                            Some(Command::Repair) => self.repair_or_report(&events),
+                            Some(Command::Stop) | None => {
+                                state(SidecarState::Stopped);
+                                return;
+                            }
+                        },
+                    }
+                }
+                None => {
+                    state(SidecarState::Failed(reason));
+                    match commands.recv().await {
+                        Some(Command::Restart) => self.backoff.reset(),
+                        Some(Command::Repair) => self.repair_or_report(&events),
+                        Some(Command::Stop) | None => {
+                            state(SidecarState::Stopped);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Delete the environment so the next launch rebuilds it.
+    fn repair(&self) -> Result<(), String> {
+        match &self.env {
+            Some(env) => env.remove(),
+            None => Ok(()),
+        }
+    }
+
+    /// [`repair`](Self::repair) between launches, reporting a failure to
+    /// delete the environment as setup output.
+    fn repair_or_report(&mut self, events: &mpsc::Sender<SidecarEvent>) {
+        if let Err(e) = self.repair() {
+            let _ = events.send(SidecarEvent::Setup(e));
+        }
+        self.backoff.reset();
+    }
+
+    fn publish(&self, client: Option<Arc<SidecarClient>>) {
+        if let Ok(mut slot) = self.client.lock() {
+            *slot = client;
+        }
+    }
+
+    /// Set up the environment, start the sidecar, forward its stderr, and
+    /// check its handshake.
+    async fn launch(
+        &self,
+        events: &mpsc::Sender<SidecarEvent>,
+    ) -> Result<(Arc<SidecarClient>, String), String> {
+        let python = match &self.env {
+            Some(env) => self.prepare(env, events).await?,
+            None => self.python_path.clone(),
+        };
+        let mut manager = SidecarManager::new(python, self.script_path.clone())
+            .envs(self.variables.pairs().into_iter().collect());
+        manager.start().await?;
+        let mut process = manager
+            .take_process()
+            .ok_or("Sidecar process handle missing")?;
+        if let Some(stderr) = process.stderr.take() {
+            let events = events.clone();
+            let variables = self.variables.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if events
+                        .send(SidecarEvent::Stderr(variables.redact(&line)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+        let client = Arc::new(SidecarClient::from_process(process)?);
+        let handshake = tokio::time::timeout(RESPONSE_TIMEOUT, client.handshake())
+            .await
+            .map_err(|_| "Sidecar did not answer the handshake".to_string())??;
+        handshake.check()?;
+        Ok((client, handshake.version))
+    }
+
+    /// Make sure `env` is ready, reporting any work in the background-task
+    /// status, and return its interpreter.
+    async fn prepare(
+        &self,
+        env: &SidecarEnv,
+        events: &mpsc::Sender<SidecarEvent>,
+    ) -> Result<String, String> {
+        // Pinned packages, if any, sit next to the script.
+        let requirements = self.script_path.with_file_name("requirements.txt");
+        let requirements = requirements.exists().then_some(requirements);
+        let mut task = None;
+        let result = env
+            .prepare(&self.python_path, requirements.as_deref(), |line| {
+                task.get_or_insert_with(|| {
+                    progress::global()
+                        .task("Setting up sidecar environment")
+                        .start()
+                })
+                .set_message(line.clone());
+                let _ = events.send(SidecarEvent::Setup(line));
+            })
+            .await;
+        match (result, task) {
+            (Err(e), Some(task)) => {
+                task.fail(e.clone());
+                Err(e)
+            }
+            (result, _) => result.map(|python| python.to_string_lossy().into_owned()),
+        }
+    }
+
+    /// Ping the sidecar while it is idle until it fails or a command arrives.
+    async fn watch(
+        &self,
+        client: &SidecarClient,
+        commands: &mut tokio_mpsc::UnboundedReceiver<Command>,
+    ) -> Outcome {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.ping_interval) => {
+                    if client.has_exited().await {
+                        return Outcome::Failed("process exited".to_string());
+                    }
+                    // A long call (index build) holds the line; its own
+                    // result will tell if the sidecar died meanwhile.
+                    if client.is_busy() {
+                        continue;
+                    }
+                    let answered = tokio::time::timeout(RESPONSE_TIMEOUT, client.health_check())
+                        .await
+                        .unwrap_or(false);
+                    if !answered {
+                        return Outcome::Failed("stopped answering pings".to_string());
+                    }
+                }
+                cmd = commands.recv() => match cmd {
+                    Some(Command::Restart) => return Outcome::Restart,
+                    Some(Command::Repair) => return Outcome::Repair,
+                    Some(Command::Stop) | None => return Outcome::Stop,
+                },
+            }
+        }
+    }
+}
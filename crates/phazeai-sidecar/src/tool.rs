@@ -69,6 +69,85 @@ impl Tool for SemanticSearchTool {
     }
 }
 
+/// Finds code like a snippet across the project, so the agent reuses a
+/// helper that already exists instead of writing it again.
+pub struct FindSimilarCodeTool {
+    client: Arc<SidecarClient>,
+}
+
+impl FindSimilarCodeTool {
+    pub fn new(client: Arc<SidecarClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for FindSimilarCodeTool {
+    fn name(&self) -> &str {
+        "find_similar_code"
+    }
+
+    fn description(&self) -> &str {
+        "Find code in the project similar to a snippet: near-duplicates (kind \"duplicate\") \
+         and related code, with file and line range. Call this before writing a new helper \
+         to check whether one already exists, and reuse it if so. Pass path, start_line and \
+         end_line when the snippet comes from a file, so it is not returned itself."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "description": "The code to find similar code for, e.g. a draft of the helper"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "File the code comes from, if any"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "First line (1-based) of the code in that file"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "Last line (1-based) of the code in that file"
+                },
+                "top_k": {
+                    "type": "integer",
+                    "description": "Number of results to return (default: 5, max: 20)"
+                }
+            },
+            "required": ["code"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> ToolResult {
+        let code = params.get("code").and_then(|v| v.as_str()).ok_or_else(|| {
+            PhazeError::tool("find_similar_code", "Missing required parameter: code")
+        })?;
+
+        let path = params.get("path").and_then(|v| v.as_str());
+        let line = |key: &str| params.get(key).and_then(|v| v.as_u64()).map(|n| n as u32);
+        let lines = line("start_line").zip(line("end_line"));
+
+        let top_k = params
+            .get("top_k")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5)
+            .min(20) as usize;
+
+        let result = self
+            .client
+            .find_similar(code, path, lines, top_k)
+            .await
+            .map_err(|e| PhazeError::tool("find_similar_code", format!("Sidecar error: {e}")))?;
+
+        Ok(result)
+    }
+}
+
 /// Tool to build the semantic search index for the project.
 pub struct BuildIndexTool {
     client: Arc<SidecarClient>,
@@ -88,7 +167,7 @@ impl Tool for BuildIndexTool {
 
     fn description(&self) -> &str {
         "Build or rebuild the semantic search index for the project. \
-         Call this before using semantic_search if search returns no results, \
+         Call this before using semantic_search or find_similar_code if they return no results, \
          or after significant code changes."
     }
 
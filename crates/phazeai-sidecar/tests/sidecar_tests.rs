@@ -1,6 +1,15 @@
-use phazeai_sidecar::{JsonRpcRequest, JsonRpcResponse, SidecarManager};
+use phazeai_core::config::PluginSidecarConfig;
+use phazeai_core::lsp::Backoff;
+use phazeai_core::Tool;
+use phazeai_sidecar::{
+    find_python, has_packages, parse_python_version, plugin_sidecar_tools, probe_python,
+    FindSimilarCodeTool, Handshake, JsonRpcRequest, JsonRpcResponse, PluginSidecar, SidecarClient,
+    SidecarEnv, SidecarEvent, SidecarManager, SidecarState, SidecarSupervisor, PROTOCOL_VERSION,
+};
 use serde_json::{json, Value};
 use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 use tempfile::NamedTempFile;
 
 // ============================================================================
@@ -386,3 +395,508 @@ async fn test_sidecar_manager_start_when_already_running() {
 
     manager.stop().await;
 }
+
+// ============================================================================
+// Handshake Tests
+// ============================================================================
+
+#[test]
+fn test_handshake_check_accepts_current_protocol() {
+    let handshake: Handshake = serde_json::from_value(json!({
+        "name": "phazeai-sidecar",
+        "version": "0.1.0",
+        "protocol": PROTOCOL_VERSION,
+    }))
+    .unwrap();
+
+    assert!(handshake.check().is_ok());
+    assert!(!handshake.capabilities.tools);
+}
+
+#[test]
+fn test_handshake_check_rejects_other_protocol() {
+    let handshake = Handshake {
+        name: "phazeai-sidecar".to_string(),
+        version: "0.0.1".to_string(),
+        protocol: PROTOCOL_VERSION + 1,
+        capabilities: Default::default(),
+    };
+
+    let err = handshake.check().unwrap_err();
+    assert!(err.contains("0.0.1"));
+    assert!(err.contains(&format!("expected {PROTOCOL_VERSION}")));
+}
+
+// ============================================================================
+// Supervisor Tests
+// ============================================================================
+
+/// A sidecar answering `handshake` with `protocol` and everything else with
+/// "pong", after saying hello on stderr.
+fn protocol_script(protocol: u32) -> String {
+    format!(
+        r#"import json, sys
+print("sidecar says hello", file=sys.stderr, flush=True)
+for line in sys.stdin:
+    req = json.loads(line)
+    if req["method"] == "handshake":
+        result = {{"name": "test-sidecar", "version": "9.9", "protocol": {protocol}}}
+    else:
+        result = "pong"
+    print(json.dumps({{"jsonrpc": "2.0", "id": req["id"], "result": result}}), flush=True)
+"#
+    )
+}
+
+/// Collect events until `done` matches one, failing after a few seconds.
+fn events_until(
+    rx: &mpsc::Receiver<SidecarEvent>,
+    done: impl Fn(&SidecarEvent) -> bool,
+) -> Vec<SidecarEvent> {
+    let mut events = Vec::new();
+    loop {
+        let event = rx
+            .recv_timeout(Duration::from_secs(10))
+            .unwrap_or_else(|_| panic!("timed out; got {events:?}"));
+        let finished = done(&event);
+        events.push(event);
+        if finished {
+            return events;
+        }
+    }
+}
+
+fn quick_backoff(max_attempts: u32) -> Backoff {
+    Backoff::new(
+        Duration::from_millis(10),
+        Duration::from_millis(10),
+        max_attempts,
+    )
+}
+
+#[tokio::test]
+async fn test_supervisor_becomes_ready_and_publishes_client() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), protocol_script(PROTOCOL_VERSION)).unwrap();
+
+    let slot = Arc::new(Mutex::new(None));
+    let (tx, rx) = mpsc::channel();
+    let handle = SidecarSupervisor::new("python3", temp_file.path())
+        .ping_interval(Duration::from_millis(50))
+        .client_slot(slot.clone())
+        .spawn(tx);
+
+    let mut events = events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Ready { .. }))
+    });
+    assert!(matches!(
+        events.last(),
+        Some(SidecarEvent::State(SidecarState::Ready { version })) if version == "9.9"
+    ));
+    assert!(slot.lock().unwrap().is_some());
+
+    // Its stderr comes through, before or after it is ready.
+    let is_hello =
+        |e: &SidecarEvent| matches!(e, SidecarEvent::Stderr(line) if line == "sidecar says hello");
+    if !events.iter().any(is_hello) {
+        events = events_until(&rx, is_hello);
+    }
+    assert!(events.iter().any(is_hello));
+
+    // Pings keep it up.
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(slot.lock().unwrap().is_some());
+
+    handle.stop();
+    events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Stopped))
+    });
+    assert!(slot.lock().unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_supervisor_restarts_crashing_sidecar_then_gives_up() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), "import sys\nsys.exit(1)\n").unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let handle = SidecarSupervisor::new("python3", temp_file.path())
+        .backoff(quick_backoff(2))
+        .spawn(tx);
+
+    let events = events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Failed(_)))
+    });
+    let restarts: Vec<u32> = events
+        .iter()
+        .filter_map(|e| match e {
+            SidecarEvent::State(SidecarState::Restarting { attempt, .. }) => Some(*attempt),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(restarts, vec![1, 2]);
+    assert!(handle.is_running());
+
+    // A manual restart starts over.
+    handle.restart();
+    events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Starting))
+    });
+    handle.stop();
+    events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Stopped))
+    });
+}
+
+#[tokio::test]
+async fn test_supervisor_refuses_sidecar_with_other_protocol() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), protocol_script(PROTOCOL_VERSION + 1)).unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let _handle = SidecarSupervisor::new("python3", temp_file.path())
+        .backoff(quick_backoff(0))
+        .spawn(tx);
+
+    let events = events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Failed(_)))
+    });
+    let Some(SidecarEvent::State(SidecarState::Failed(reason))) = events.last() else {
+        unreachable!();
+    };
+    assert!(reason.contains("speaks protocol"), "{reason}");
+}
+
+// ============================================================================
+// Environment Tests
+// ============================================================================
+
+#[test]
+fn test_parse_python_version() {
+    assert_eq!(parse_python_version("3.11.4\n"), Some((3, 11, 4)));
+    assert_eq!(parse_python_version("3.11"), None);
+    assert_eq!(parse_python_version("3.11.4.1"), None);
+    assert_eq!(parse_python_version("Python 3.11.4"), None);
+}
+
+#[test]
+fn test_has_packages_ignores_comments_and_blank_lines() {
+    assert!(!has_packages(""));
+    assert!(!has_packages("# nothing yet\n\n   # still nothing\n"));
+    assert!(has_packages("# pinned\nnumpy==1.26.4\n"));
+}
+
+#[test]
+fn test_sidecar_env_missing_venv_is_not_current() {
+    let dir = tempfile::tempdir().unwrap();
+    let env = SidecarEnv::new(dir.path());
+
+    assert!(env.python().starts_with(dir.path().join("venv")));
+    assert!(!env.is_current(""));
+    assert!(env.remove().is_ok());
+}
+
+#[tokio::test]
+async fn test_probe_python_rejects_missing_interpreter() {
+    let err = probe_python("definitely-not-a-python").await.unwrap_err();
+    assert!(err.starts_with("definitely-not-a-python:"));
+}
+
+#[tokio::test]
+async fn test_find_python_falls_back_from_missing_interpreter() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+
+    let python = find_python("definitely-not-a-python").await.unwrap();
+    assert_eq!(python.path, "python3");
+    assert!(python.version >= (3, 8, 0));
+}
+
+#[tokio::test]
+async fn test_sidecar_env_prepare_creates_venv_once() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let requirements = dir.path().join("requirements.txt");
+    std::fs::write(&requirements, "# nothing to install\n").unwrap();
+    let env = SidecarEnv::new(dir.path().join("env"));
+
+    let mut steps = Vec::new();
+    let python = env
+        .prepare("python3", Some(&requirements), |line| steps.push(line))
+        .await
+        .unwrap();
+    assert_eq!(python, env.python());
+    assert!(python.exists());
+    assert!(steps[0].starts_with("Creating Python 3."));
+    assert!(env.is_current("# nothing to install\n"));
+
+    // Nothing to do the second time.
+    let mut steps = Vec::new();
+    env.prepare("python3", Some(&requirements), |line| steps.push(line))
+        .await
+        .unwrap();
+    assert!(steps.is_empty());
+
+    // Changed requirements are taken up without rebuilding the venv.
+    std::fs::write(&requirements, "# still nothing\n").unwrap();
+    let mut steps = Vec::new();
+    env.prepare("python3", Some(&requirements), |line| steps.push(line))
+        .await
+        .unwrap();
+    assert!(steps.is_empty());
+    assert!(env.is_current("# still nothing\n"));
+
+    env.remove().unwrap();
+    assert!(!env.python().exists());
+}
+
+#[tokio::test]
+async fn test_supervisor_runs_sidecar_in_its_environment() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("server.py");
+    std::fs::write(&script, protocol_script(PROTOCOL_VERSION)).unwrap();
+    let env = SidecarEnv::new(dir.path().join("env"));
+
+    let (tx, rx) = mpsc::channel();
+    let handle = SidecarSupervisor::new("python3", &script)
+        .environment(env.clone())
+        .spawn(tx);
+
+    let events = events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Ready { .. }))
+    });
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, SidecarEvent::Setup(line) if line.starts_with("Creating Python"))));
+    assert!(env.python().exists());
+
+    // Repair rebuilds it.
+    handle.repair();
+    let events = events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Ready { .. }))
+    });
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, SidecarEvent::Setup(line) if line.starts_with("Creating Python"))));
+
+    handle.stop();
+    events_until(&rx, |e| {
+        matches!(e, SidecarEvent::State(SidecarState::Stopped))
+    });
+}
+
+// ============================================================================
+// Plugin Sidecar Tests
+// ============================================================================
+
+/// A plugin offering an `echo` tool, or no tools at all without `tools`.
+fn plugin_script(tools: bool) -> String {
+    let tools = if tools { "True" } else { "False" };
+    format!(
+        r#"import json, sys
+for line in sys.stdin:
+    req = json.loads(line)
+    if req["method"] == "handshake":
+        result = {{"name": "echo-plugin", "version": "1.0", "protocol": {PROTOCOL_VERSION},
+                   "capabilities": {{"tools": {tools}}}}}
+    elif req["method"] == "tools/list":
+        result = {{"tools": [{{"name": "echo", "description": "Echo the arguments",
+                               "parameters": {{"type": "object"}}}}]}}
+    elif req["method"] == "tools/call":
+        result = {{"tool": req["params"]["name"], "echoed": req["params"]["arguments"]}}
+    else:
+        result = None
+    print(json.dumps({{"jsonrpc": "2.0", "id": req["id"], "result": result}}), flush=True)
+"#
+    )
+}
+
+fn plugin_config(script: &NamedTempFile, tools: &[&str]) -> PluginSidecarConfig {
+    PluginSidecarConfig {
+        name: "echo".to_string(),
+        command: "python3".to_string(),
+        args: vec![script.path().to_string_lossy().into_owned()],
+        env: Default::default(),
+        tools: tools.iter().map(|t| t.to_string()).collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_plugin_sidecar_registers_prefixed_tools() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let script = NamedTempFile::new().unwrap();
+    std::fs::write(script.path(), plugin_script(true)).unwrap();
+
+    let plugin = PluginSidecar::start(&plugin_config(&script, &[]))
+        .await
+        .unwrap();
+    assert_eq!(plugin.handshake().name, "echo-plugin");
+    assert_eq!(plugin.definitions().len(), 1);
+
+    let tools = plugin.tools();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name(), "sidecar__echo__echo");
+    assert_eq!(tools[0].description(), "Echo the arguments");
+
+    let result = tools[0].execute(json!({ "text": "hi" })).await.unwrap();
+    assert_eq!(
+        result,
+        json!({ "tool": "echo", "echoed": { "text": "hi" } })
+    );
+}
+
+#[tokio::test]
+async fn test_plugin_sidecar_rejects_missing_configured_tool() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let script = NamedTempFile::new().unwrap();
+    std::fs::write(script.path(), plugin_script(true)).unwrap();
+
+    let err = PluginSidecar::start(&plugin_config(&script, &["echo", "shout"]))
+        .await
+        .err()
+        .unwrap();
+    assert!(err.contains("shout"));
+}
+
+#[tokio::test]
+async fn test_plugin_sidecar_without_tools_capability_is_refused() {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return;
+    }
+    let script = NamedTempFile::new().unwrap();
+    std::fs::write(script.path(), plugin_script(false)).unwrap();
+
+    let err = PluginSidecar::start(&plugin_config(&script, &[]))
+        .await
+        .err()
+        .unwrap();
+    assert!(err.contains("does not offer tools"));
+}
+
+#[tokio::test]
+async fn test_plugin_sidecar_tools_skips_plugins_that_fail() {
+    let config = PluginSidecarConfig {
+        name: "missing".to_string(),
+        command: "nonexistent_plugin_binary_12345".to_string(),
+        args: Vec::new(),
+        env: Default::default(),
+        tools: Vec::new(),
+    };
+
+    assert!(plugin_sidecar_tools(&[config]).await.is_empty());
+}
+
+// ============================================================================
+// Similar Code Tests
+// ============================================================================
+
+/// The bundled sidecar, started on a workspace holding `files`.
+async fn bundled_sidecar(
+    files: &[(&str, &str)],
+) -> Option<(tempfile::TempDir, Arc<SidecarClient>)> {
+    if !SidecarManager::check_python("python3").await {
+        eprintln!("Python3 not available, skipping test");
+        return None;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    for (name, content) in files {
+        std::fs::write(dir.path().join(name), content).unwrap();
+    }
+    let script = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../sidecar/server.py");
+    let mut manager = SidecarManager::new("python3", script);
+    manager.start().await.unwrap();
+    let client = Arc::new(SidecarClient::from_process(manager.take_process().unwrap()).unwrap());
+    let root = dir.path().to_string_lossy().to_string();
+    client.build_index(&[root]).await.unwrap();
+    Some((dir, client))
+}
+
+const PARSE_PORT: &str = "fn parse_port(value: &str) -> Option<u16> {
+    let trimmed = value.trim();
+    trimmed.parse::<u16>().ok().filter(|p| *p > 0)
+}
+
+fn greet(name: &str) -> String {
+    let mut out = String::from(\"hello \");
+    out.push_str(name);
+    out
+}
+";
+
+const PORT_FROM: &str = "fn port_from(input: &str) -> Option<u16> {
+    let trimmed = input.trim();
+    trimmed.parse::<u16>().ok().filter(|p| *p > 0)
+}
+";
+
+#[tokio::test]
+async fn test_find_similar_reports_renamed_copy_as_duplicate() {
+    let Some((dir, client)) =
+        bundled_sidecar(&[("config.rs", PARSE_PORT), ("net.rs", PORT_FROM)]).await
+    else {
+        return;
+    };
+    let own = dir.path().join("net.rs").to_string_lossy().to_string();
+
+    let result = client
+        .find_similar(PORT_FROM, Some(&own), Some((1, 4)), 5)
+        .await
+        .unwrap();
+    let matches = result["matches"].as_array().unwrap();
+
+    assert!(matches.iter().all(|m| m["file"] != own.as_str()));
+    let first = &matches[0];
+    assert!(first["file"].as_str().unwrap().ends_with("config.rs"));
+    assert_eq!(first["kind"], "duplicate");
+    assert_eq!(
+        (first["start_line"].clone(), first["end_line"].clone()),
+        (json!(1), json!(5))
+    );
+    assert!(first["snippet"].as_str().unwrap().contains("fn parse_port"));
+}
+
+#[tokio::test]
+async fn test_find_similar_code_tool_requires_code() {
+    let Some((_dir, client)) = bundled_sidecar(&[("config.rs", PARSE_PORT)]).await else {
+        return;
+    };
+    let tool = FindSimilarCodeTool::new(client);
+
+    assert!(tool.execute(json!({ "path": "config.rs" })).await.is_err());
+    let result = tool
+        .execute(json!({ "code": "fn greet(name: &str) -> String { String::new() }" }))
+        .await
+        .unwrap();
+    assert!(result["matches"][0]["snippet"]
+        .as_str()
+        .unwrap()
+        .contains("fn greet"));
+}
@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -14,26 +16,79 @@ use floem::{
     window::WindowConfig,
     Application, IntoView, Renderer,
 };
+use phazeai_core::analysis::{PlanTask, Removal, TodoComment, SECURITY_SOURCE};
 use phazeai_core::config::LlmProvider;
 use phazeai_core::constants::ui as ui_const;
+use phazeai_core::editing::{Bookmarks, FoldMemory, JumpList, Location, TextEncoding};
+use phazeai_core::lsp::LensAction;
+use phazeai_core::lsp::{DiagnosticCounts, ServerState, ServerStatus};
+use phazeai_core::memory::{self, Subsystem};
+use phazeai_core::notifications::{NotificationCenter, Progress, Severity};
+use phazeai_core::output::{self, OutputLog};
+use phazeai_core::progress::{self, TaskSnapshot};
+use phazeai_core::project::editorconfig::{
+    EffectiveIndent, Indent, IndentSource, IndentStyle, LineEnding,
+};
+use phazeai_core::project::run_config::{RunConfigs, RUN_CONFIG_FILE};
+use phazeai_core::project::{EnvVars, FileIndex, FileWatcher};
 use phazeai_core::{Agent, AgentEvent, Settings};
-use phazeai_sidecar::{SidecarClient, SidecarManager};
+use phazeai_sidecar::{
+    ClientSlot, SidecarEnv, SidecarEvent, SidecarHandle, SidecarState, SidecarSupervisor,
+};
 
 use crate::lsp_bridge::{
     start_lsp_bridge, CodeAction, CodeLensEntry, CompletionEntry, DefinitionResult, DiagEntry,
-    DiagSeverity, LspCommand, ReferenceEntry, SymbolEntry,
+    DiagSeverity, DocumentHighlights, LspCommand, PeekLocation, ReferenceEntry, SymbolEntry,
 };
 
+#[cfg(feature = "webview")]
+use crate::panels::preview::preview_panel;
 use crate::{
-    commands::{execute_command, match_global_shortcut},
+    commands::{execute_command, match_emacs_key, match_global_shortcut, EmacsCommand},
     components::icon::{icons, phaze_icon},
+    components::links::LinkOpener,
+    components::virtual_list::virtual_list,
+    i18n::tr,
+    notifications::{
+        finish_progress, notification_bell, notification_drawer, notify, record_toasts,
+        start_progress, toast_stack, toggle_notifications, update_progress,
+    },
     panels::{
-        chat::chat_panel, editor::editor_panel, explorer::explorer_panel,
-        extensions::extensions_panel, git::git_panel, github_actions::github_actions_panel, search,
-        settings::settings_panel, terminal::terminal_panel,
+        audit::audit_panel,
+        bench::bench_panel,
+        board::board_panel,
+        bookmarks::bookmarks_panel,
+        chat::chat_panel,
+        compare::{compare_panel, Comparison},
+        database::database_panel,
+        editor::{editor_panel, BufferCommand, FileFormatCommand},
+        env::env_panel,
+        explorer::explorer_panel,
+        extensions::extensions_panel,
+        git::git_panel,
+        git_history::git_history_view,
+        git_rebase::git_rebase_view,
+        github_actions::github_actions_panel,
+        http::http_panel,
+        jobs::{jobs_panel, jobs_tab_label, start_job_queue},
+        language_servers::{language_servers_panel, language_servers_tab_label},
+        memory::memory_panel,
+        metrics::metrics_panel,
+        module_graph::module_graph_panel,
+        output::{
+            append_output, output_panel, show_output_channel, start_build_runner,
+            start_config_runner, RunConfigSignals,
+        },
+        profile::profile_panel,
+        search,
+        settings::settings_panel,
+        tasks::{tasks_panel, tasks_tab_label},
+        terminal::terminal_panel,
+        todos::{start_todo_scanner, todos_panel, todos_tab_label},
     },
+    startup,
     theme::{PhazeTheme, ThemeVariant},
-    util::safe_get,
+    util::{focus_ring, list_nav_key, list_nav_key_opt, safe_get, ListNav},
 };
 
 /// Vim normal-mode motions dispatched to the active editor.
@@ -91,14 +146,55 @@ pub enum VimMotion {
     YankVisualSelection,
     /// Change (delete + enter insert) the current visual selection.
     ChangeVisualSelection,
+    // Emacs kill ring
+    /// Kill to the end of the line (C-k); `true` appends to the last kill.
+    KillLine(bool),
+    /// Insert the last kill (C-y).
+    Yank,
+    /// Copy the selection to the kill ring (M-w).
+    CopySelection,
+}
+
+/// A selection to find similar code for, from the main editor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarCodeQuery {
+    pub path: PathBuf,
+    /// 1-based lines the selection spans.
+    pub start_line: u32,
+    pub end_line: u32,
+    pub code: String,
+}
+
+/// The inline peek widget, open below a line of the main editor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Peek {
+    pub path: PathBuf,
+    /// 0-based line the widget opens below.
+    pub line: u32,
+    pub content: PeekContent,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PeekContent {
+    FindingDefinition,
+    Definition(PeekLocation),
+    NoDefinition,
+    FindingReferences,
+    References(Vec<ReferenceEntry>),
 }
 
 /// Global IDE state shared across all panels via Floem reactive system.
 #[derive(Clone, Debug, PartialEq)]
 pub struct SearchResult {
     pub path: std::path::PathBuf,
+    /// 1-based line number.
     pub line: usize,
     pub content: String,
+    /// Byte ranges of the matches in `content`.
+    pub ranges: Vec<std::ops::Range<usize>>,
+    /// Context lines around the match, when the panel asks for them.
+    pub before: Vec<String>,
+    pub after: Vec<String>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -124,11 +220,37 @@ pub enum Tab {
     References,
     Symbols,
     GitDiff,
+    GitHistory,
+    Rebase,
+    Jobs,
+    Bookmarks,
+    Todos,
+    Tasks,
+    Board,
+    Preview,
+    Http,
+    Database,
+    Env,
+    LanguageServers,
+    ModuleGraph,
+    Metrics,
+    Audit,
+    Profile,
+    Bench,
+    Memory,
+    Compare,
 }
 
 #[derive(Clone)]
 pub struct IdeState {
     pub theme: RwSignal<PhazeTheme>,
+    /// Syntect theme used for editor highlighting — derived from `theme` and
+    /// `syntax_theme_overrides`, so switching either re-highlights open files.
+    pub syntax_theme: RwSignal<String>,
+    /// User-chosen syntax theme per UI theme (UI theme name → syntect theme).
+    pub syntax_theme_overrides: RwSignal<std::collections::HashMap<String, String>>,
+    /// When true, the UI theme tracks the OS light/dark appearance.
+    pub follow_system_theme: RwSignal<bool>,
     pub left_panel_tab: RwSignal<Tab>,
     pub bottom_panel_tab: RwSignal<Tab>,
     pub show_left_panel: RwSignal<bool>,
@@ -155,12 +277,19 @@ pub struct IdeState {
     pub file_picker_query: RwSignal<String>,
     /// All workspace files, populated async when picker opens.
     pub file_picker_files: RwSignal<Vec<std::path::PathBuf>>,
+    /// Top-level folders the file index let go of to stay within its memory
+    /// budget: the picker says they aren't listed, and Apply walks them.
+    pub file_picker_unindexed: RwSignal<Vec<std::path::PathBuf>>,
     // Search
     pub search_query: RwSignal<String>,
     pub search_results: RwSignal<Vec<SearchResult>>,
     // LSP — populated async by start_lsp_bridge()
     pub diagnostics: RwSignal<Vec<DiagEntry>>,
+    /// Errors and warnings per file and workspace folder, for the explorer.
+    pub diagnostic_counts: RwSignal<std::collections::HashMap<PathBuf, DiagnosticCounts>>,
     pub lsp_cmd: tokio::sync::mpsc::UnboundedSender<LspCommand>,
+    /// Each language server started so far and its health, for the status bar.
+    pub lsp_status: RwSignal<Vec<ServerStatus>>,
     /// Latest completion list from the LSP server (set after RequestCompletions).
     pub completions: RwSignal<Vec<CompletionEntry>>,
     /// Whether the completion popup is visible.
@@ -170,6 +299,17 @@ pub struct IdeState {
     /// Active cursor position: (path, 0-based line, 0-based col).
     /// Written by the editor; read by Ctrl+Space handler to know where to request.
     pub active_cursor: RwSignal<Option<(PathBuf, u32, u32)>>,
+    /// Navigation history fed by `active_cursor`: file switches and long
+    /// cursor jumps (Alt+Left / Alt+Right to walk it).
+    pub jump_list: RwSignal<JumpList>,
+    /// Line bookmarks for the open workspace (Ctrl+Alt+K to toggle).
+    pub bookmarks: RwSignal<Bookmarks>,
+    /// Bookmarks of every workspace, keyed by workspace path, as persisted in
+    /// the session file.
+    pub bookmark_store: RwSignal<BTreeMap<String, Bookmarks>>,
+    /// Folded regions of every file, by start line, as persisted in the
+    /// session file.
+    pub saved_folds: RwSignal<FoldMemory>,
     // Panel resize drag state (used by the divider + overlay)
     pub panel_drag_active: RwSignal<bool>,
     pub panel_drag_start_x: RwSignal<f64>,
@@ -187,8 +327,27 @@ pub struct IdeState {
     pub completion_filter_text: RwSignal<String>,
     /// Whether vim keybindings are enabled (persisted to settings).
     pub vim_mode: RwSignal<bool>,
+    /// Whether the Emacs keymap preset is enabled (persisted); exclusive
+    /// with `vim_mode`.
+    pub emacs_mode: RwSignal<bool>,
+    /// True after C-x while waiting for the rest of the sequence.
+    pub emacs_prefix: RwSignal<bool>,
+    /// True when the last key was C-k, so the next kill appends to it.
+    pub emacs_last_kill: RwSignal<bool>,
     /// Editor font size (persisted to config.toml).
     pub font_size: RwSignal<u32>,
+    /// Editor font family (persisted to config.toml).
+    pub font_family: RwSignal<String>,
+    /// Whether programming ligatures are rendered in the editor and chat.
+    pub font_ligatures: RwSignal<bool>,
+    /// Editor line height as a multiple of the font size.
+    pub line_height: RwSignal<f32>,
+    /// Terminal font family and size — independent of the editor font.
+    pub terminal_font_family: RwSignal<String>,
+    pub terminal_font_size: RwSignal<u32>,
+    /// AI chat font family and size.
+    pub chat_font_family: RwSignal<String>,
+    pub chat_font_size: RwSignal<u32>,
     /// Editor tab size (persisted to config.toml).
     pub tab_size: RwSignal<u32>,
     /// Set by the LSP bridge when a go-to-definition result arrives.
@@ -196,9 +355,16 @@ pub struct IdeState {
     pub goto_definition: RwSignal<Option<DefinitionResult>>,
     /// Hover documentation text from the LSP server (None when idle).
     pub hover_text: RwSignal<Option<String>>,
+    /// Explain shell commands hovered in the terminal or chat (persisted).
+    pub explain_commands_on_hover: RwSignal<bool>,
+    /// Puts explanations of hovered shell commands into `hover_text`.
+    pub command_hover: crate::command_hover::CommandHover,
     /// Non-zero when the editor should jump to this 1-based line in the active file.
     /// Cleared to 0 by the editor after it performs the scroll/jump.
     pub goto_line: RwSignal<u32>,
+    /// With `goto_line`, selects through this 1-based line instead of only
+    /// placing the cursor. Cleared to 0 by the editor with `goto_line`.
+    pub goto_line_end: RwSignal<u32>,
     /// Incremented to trigger comment-toggle on the current line in the active editor.
     pub comment_toggle_nonce: RwSignal<u64>,
     /// All currently open editor tabs (written by editor_panel, read for session save).
@@ -207,6 +373,8 @@ pub struct IdeState {
     pub initial_tabs: Vec<PathBuf>,
     /// Active AI provider display name (e.g. "Claude (Anthropic)", "Ollama (Local)").
     pub ai_provider: RwSignal<String>,
+    /// Email of the signed-in PhazeAI Cloud account; `None` when signed out.
+    pub cloud_account: RwSignal<Option<String>>,
     /// Active AI model identifier (e.g. "claude-sonnet-4-6", "llama3.2").
     pub ai_model: RwSignal<String>,
     /// True when vim mode is in Normal (command) mode; false = Insert mode.
@@ -217,8 +385,18 @@ pub struct IdeState {
     pub vim_motion: RwSignal<Option<VimMotion>>,
     /// Ghost text (FIM) suggestion — shown inline after cursor, Tab to accept.
     pub ghost_text: RwSignal<Option<String>>,
-    /// Output panel log lines (build/run output, LSP messages, etc.)
-    pub output_log: RwSignal<Vec<String>>,
+    /// Output panel channels (Build, LSP, Agent, Extensions, …).
+    pub output: RwSignal<OutputLog>,
+    /// Channel shown in the Output panel.
+    pub output_channel: RwSignal<String>,
+    /// Bump to run the workspace's build command into the Build channel.
+    pub build_run: RwSignal<u64>,
+    /// True while a build started from the Output panel is running.
+    pub build_running: RwSignal<bool>,
+    /// The run configuration picked in the top bar.
+    pub run_config: RwSignal<Option<String>>,
+    /// Running, re-running (Ctrl+F5) and stopping run configurations.
+    pub run_configs: RunConfigSignals,
     /// Find-all-references results (Shift+F12).
     pub references: RwSignal<Vec<ReferenceEntry>>,
     /// Whether the References tab in the bottom panel is the active view.
@@ -239,10 +417,50 @@ pub struct IdeState {
     pub doc_symbols: RwSignal<Vec<SymbolEntry>>,
     /// Toast notification text — auto-cleared after 3 s.
     pub status_toast: RwSignal<Option<String>>,
+    /// Every notification shown, for the toast stack, bell and history drawer.
+    pub notifications: RwSignal<NotificationCenter>,
+    /// Whether the notification history drawer is open.
+    pub notifications_open: RwSignal<bool>,
+    /// Background work running on the core progress bus (indexing, scans,
+    /// language-server warm-up), oldest first.
+    pub background_tasks: RwSignal<Vec<TaskSnapshot>>,
+    /// Background agent jobs (JOBS tab).
+    pub job_queue: phazeai_core::agent::JobQueue,
+    /// Latest snapshot of `job_queue`'s jobs.
+    pub jobs: RwSignal<Vec<phazeai_core::agent::Job>>,
+    /// TODO / FIXME / HACK comments across the workspace (TODOS tab).
+    pub todos: RwSignal<Vec<TodoComment>>,
+    /// True while the workspace is being scanned for `todos`.
+    pub todos_scanning: RwSignal<bool>,
+    /// Bump to rescan the workspace for `todos`.
+    pub todos_rescan: RwSignal<u64>,
+    /// The checklist in the workspace's `plan.md` (TASKS tab).
+    pub plan_tasks: RwSignal<Vec<PlanTask>>,
+    /// Text to pull action items from into `plan.md`; set by the chat
+    /// header and by "Extract Tasks from Selection".
+    pub extract_tasks_from: RwSignal<Option<String>>,
+    /// Incremented to extract tasks from the selection in the main editor.
+    pub extract_tasks_nonce: RwSignal<u64>,
+    /// A saved conversation for the chat panel to open, from the task board.
+    pub open_conversation: RwSignal<Option<String>>,
+    /// Images attached to the next chat message, such as a web preview
+    /// screenshot.
+    pub chat_images: RwSignal<Vec<PathBuf>>,
+    /// A request for the HTTP tab to send: the request file and a 0-based
+    /// line inside the request.
+    pub http_send_request: RwSignal<Option<(PathBuf, u32)>>,
     /// Zen mode — when true, hides all panels for distraction-free editing (Ctrl+Shift+Z).
     pub zen_mode: RwSignal<bool>,
     /// Line ending style of the active file ("LF", "CRLF", or "Mixed").
     pub line_ending: RwSignal<&'static str>,
+    /// Encoding the active file is read and saved in.
+    pub active_encoding: RwSignal<TextEncoding>,
+    /// Change the active file's line endings or encoding; the editor resets
+    /// it to None once applied.
+    pub file_format_cmd: RwSignal<Option<FileFormatCommand>>,
+    /// Indentation of the active file, from .editorconfig or detected from
+    /// its content; set from the status bar to change it for that file.
+    pub active_indent: RwSignal<Option<EffectiveIndent>>,
     /// Whether the workspace symbols overlay (Ctrl+T) is visible.
     pub ws_syms_open: RwSignal<bool>,
     /// Filter query typed in the workspace symbols overlay.
@@ -273,6 +491,12 @@ pub struct IdeState {
     pub delete_line_nonce: RwSignal<u64>,
     /// Inline blame annotation for the current cursor line (shown in status bar).
     pub active_blame: RwSignal<String>,
+    /// File whose history the GIT HISTORY tab shows; `None` for the whole repository.
+    pub git_history_file: RwSignal<Option<PathBuf>>,
+    /// Show the blame annotation at the end of the current line (persisted).
+    pub inline_blame: RwSignal<bool>,
+    /// Show the blame gutter beside every line (persisted).
+    pub blame_gutter: RwSignal<bool>,
     /// Whether the split editor pane is visible (Ctrl+Alt+\).
     pub split_editor: RwSignal<bool>,
     /// Active file in the split editor pane (independent of primary pane).
@@ -281,6 +505,8 @@ pub struct IdeState {
     pub split_open_tabs: RwSignal<Vec<PathBuf>>,
     /// Cursor position in the split editor pane.
     pub split_active_cursor: RwSignal<Option<(PathBuf, u32, u32)>>,
+    /// Set to a 1-based line to jump there in the split editor pane.
+    pub split_goto_line: RwSignal<u32>,
     /// Column cursor up nonce — Ctrl+Alt+Up adds cursor on line above at same column.
     pub col_cursor_up_nonce: RwSignal<u64>,
     /// Column cursor down nonce — Ctrl+Alt+Down adds cursor on line below at same column.
@@ -339,19 +565,31 @@ pub struct IdeState {
     pub bottom_panel_maximized: RwSignal<bool>,
     /// LSP progress message (e.g. "indexing 45%") — None when idle.
     pub lsp_progress: RwSignal<Option<String>>,
-    /// Peek definition source lines (Alt+F12) — set when a peek result arrives.
-    pub peek_def_lines: RwSignal<Vec<String>>,
-    /// Whether the peek definition popup is visible.
-    pub peek_def_open: RwSignal<bool>,
+    /// The inline peek widget (Alt+F12 definition, Shift+F12 references),
+    /// or `None` when closed.
+    pub peek: RwSignal<Option<Peek>>,
     /// Code lens entries for the active file.
     pub code_lens: RwSignal<Vec<CodeLensEntry>>,
+    /// Occurrences of the symbol under the cursor, from the language server.
+    pub document_highlights: RwSignal<Option<DocumentHighlights>>,
     /// LSP folding ranges for the active file: (start_line, end_line) pairs (0-based).
     pub folding_ranges: RwSignal<Vec<(u32, u32)>>,
     /// When true, automatically send OrganizeImports after saving the active file.
     pub organize_imports_on_save: RwSignal<bool>,
+    /// When true, saving a file with mixed line endings gives it one kind.
+    pub normalize_line_endings_on_save: RwSignal<bool>,
+    /// Columns the editor draws vertical rulers at.
+    pub rulers: RwSignal<Vec<u32>>,
+    /// When true, lines over the `.editorconfig` `max_line_length` are
+    /// reported in the Problems panel.
+    pub line_length_lint: RwSignal<bool>,
     /// Text to send to the active terminal PTY (Run in Terminal / Run File).
     /// Set by editor context menu; terminal_panel watches and resets to None after writing.
     pub run_in_terminal_text: RwSignal<Option<String>>,
+    /// Command from a chat code block's Run button; its output is captured.
+    pub chat_run_request: RwSignal<Option<String>>,
+    /// Output of the last `chat_run_request` command, for the chat panel.
+    pub chat_run_output: RwSignal<Option<crate::panels::terminal::TerminalRun>>,
     /// Incremented to title-case the current selection in the active editor.
     pub transform_title_nonce: RwSignal<u64>,
     /// Incremented to format only the current selection (rustfmt/prettier on selection).
@@ -362,28 +600,51 @@ pub struct IdeState {
     pub fold_all_nonce: RwSignal<u64>,
     /// Incremented to unfold all ranges in the active editor.
     pub unfold_all_nonce: RwSignal<u64>,
-    /// Code-lens entries for the active file (shown as inline gutter labels).
+    /// Set to N to fold the ranges nested N deep in the active editor, which
+    /// clears it.
+    pub fold_level: RwSignal<Option<usize>>,
+    /// The two texts in the COMPARE tab; setting a new pair brings it up.
+    pub comparison: RwSignal<Option<Comparison>>,
+    /// Compare the active file with its saved copy or the clipboard, review
+    /// a chat code block against the file it's for, or replace an open
+    /// file's text from the COMPARE tab; the main editor resets it to None
+    /// once applied.
+    pub buffer_cmd: RwSignal<Option<BufferCommand>>,
+    /// Whether code lenses are shown above functions in the editor.
     pub code_lens_visible: RwSignal<bool>,
     /// Whether LSP inlay hints are shown in the editor.
     pub inlay_hints_toggle: RwSignal<bool>,
     /// Inlay hint entries from LSP or regex fallback for the active file.
     pub inlay_hints_sig: RwSignal<Vec<crate::lsp_bridge::InlayHintEntry>>,
     /// Shared handle to the sidecar client for explicit shutdown on IDE exit.
-    pub sidecar_client: Arc<std::sync::Mutex<Option<Arc<SidecarClient>>>>,
+    pub sidecar_client: ClientSlot,
+    /// The supervisor restarting the sidecar when it crashes, once started.
+    pub sidecar_supervisor: Arc<std::sync::Mutex<Option<SidecarHandle>>>,
+    /// Sidecar process state; `None` when semantic search is disabled.
+    pub sidecar_state: RwSignal<Option<SidecarState>>,
+    /// Increment to restart the sidecar, or start it if it never was.
+    pub sidecar_restart_nonce: RwSignal<u64>,
+    /// Increment to rebuild the sidecar's virtualenv and restart it.
+    pub sidecar_repair_nonce: RwSignal<u64>,
     /// Whether the semantic search sidecar is running.
     pub sidecar_ready: RwSignal<bool>,
     /// Human-readable sidecar/indexing status shown in the UI.
     pub sidecar_status: RwSignal<String>,
     /// True while the semantic index is being built or rebuilt.
     pub sidecar_building: RwSignal<bool>,
-    /// Semantic search results (file path + snippet pairs).
-    pub sidecar_results: RwSignal<Vec<(String, String)>>,
+    /// Semantic search results: file path, 1-based line to open at, snippet.
+    pub sidecar_results: RwSignal<Vec<(String, u32, String)>>,
     /// Semantic index rebuild nonce — increment to trigger a rebuild.
     pub sidecar_build_nonce: RwSignal<u64>,
     /// Semantic search query nonce — increment to trigger a search.
     pub sidecar_search_nonce: RwSignal<u64>,
     /// Current semantic search query text.
     pub sidecar_query: RwSignal<String>,
+    /// Incremented to look for code similar to the selection in the main editor.
+    pub find_similar_nonce: RwSignal<u64>,
+    /// The selection the main editor was asked to find similar code for;
+    /// the results replace the semantic search results.
+    pub similar_code_query: RwSignal<Option<SimilarCodeQuery>>,
 
     /// Text to inject into the chat panel input and auto-send.
     /// Set by context menu "Explain Selection" / "Generate Tests" / "Fix with AI".
@@ -462,9 +723,14 @@ struct SessionState {
     split_editor: bool,
     split_editor_down: bool,
     vim_mode: bool,
+    emacs_mode: bool,
     theme: String,
     /// Zen mode — hides all chrome for distraction-free editing.
     zen_mode: bool,
+    /// Line bookmarks per workspace, keyed by workspace path.
+    bookmarks: BTreeMap<String, Bookmarks>,
+    /// Folded start lines per file.
+    folds: FoldMemory,
 }
 
 impl Default for SessionState {
@@ -479,8 +745,11 @@ impl Default for SessionState {
             split_editor: false,
             split_editor_down: false,
             vim_mode: false,
+            emacs_mode: false,
             theme: "Midnight Blue".to_string(),
             zen_mode: false,
+            bookmarks: BTreeMap::new(),
+            folds: FoldMemory::new(),
         }
     }
 }
@@ -504,8 +773,9 @@ fn load_session() -> SessionState {
         return SessionState::default();
     };
     let mut state: SessionState = toml::from_str(&text).unwrap_or_default();
-    // Drop tabs for files that no longer exist on disk.
+    // Drop tabs and folds for files that no longer exist on disk.
     state.open_tabs.retain(|p| p.exists());
+    state.folds.retain(|p| p.exists());
     // Clamp active_tab_index to the surviving tab list.
     if let Some(idx) = state.active_tab_index {
         if state.open_tabs.is_empty() {
@@ -561,8 +831,11 @@ fn session_commit(
     split_editor: bool,
     split_editor_down: bool,
     vim_mode: bool,
+    emacs_mode: bool,
     theme: String,
     zen_mode: bool,
+    bookmarks: BTreeMap<String, Bookmarks>,
+    folds: FoldMemory,
 ) {
     let active_tab_index = active_file
         .as_ref()
@@ -577,13 +850,16 @@ fn session_commit(
         split_editor,
         split_editor_down,
         vim_mode,
+        emacs_mode,
         theme,
         zen_mode,
+        bookmarks,
+        folds,
     };
     session_save_debounced(gen, ss);
 }
 
-fn dirs_next_config() -> Option<PathBuf> {
+pub(crate) fn dirs_next_config() -> Option<PathBuf> {
     let home = std::env::var("HOME")
         .ok()
         .map(PathBuf::from)
@@ -592,34 +868,42 @@ fn dirs_next_config() -> Option<PathBuf> {
 }
 
 /// Convert a provider display name back to LlmProvider enum.
-fn provider_name_to_llm_provider(name: &str) -> Option<LlmProvider> {
+pub(crate) fn provider_name_to_llm_provider(name: &str) -> Option<LlmProvider> {
     match name {
         "Claude (Anthropic)" => Some(LlmProvider::Claude),
         "OpenAI" => Some(LlmProvider::OpenAI),
         "Google Gemini" => Some(LlmProvider::Gemini),
+        "Google Vertex AI" => Some(LlmProvider::Vertex),
         "Groq" => Some(LlmProvider::Groq),
         "Together.ai" => Some(LlmProvider::Together),
         "OpenRouter" => Some(LlmProvider::OpenRouter),
+        "xAI Grok" => Some(LlmProvider::Xai),
+        "Mistral" => Some(LlmProvider::Mistral),
+        "Hugging Face" => Some(LlmProvider::HuggingFace),
+        "AWS Bedrock" => Some(LlmProvider::Bedrock),
         "LM Studio (Local)" => Some(LlmProvider::LmStudio),
+        "llama.cpp (Local)" => Some(LlmProvider::LlamaCpp),
+        "vLLM (Local)" => Some(LlmProvider::Vllm),
         "Ollama (Local)" => Some(LlmProvider::Ollama),
+        "PhazeAI Cloud" => Some(LlmProvider::PhazeCloud),
         _ => None,
     }
 }
 
-/// Save a single editor setting by loading the full Settings, mutating, and writing back.
-/// This preserves all other settings (LLM, sidecar, providers, etc.).
+/// Save editor settings through the config store, which writes back only
+/// what `mutate` changed and leaves everything else (LLM, sidecar, providers,
+/// other writers' changes) alone.
 pub fn save_editor_settings(mutate: impl FnOnce(&mut phazeai_core::config::EditorSettings)) {
-    let mut settings = Settings::load();
-    mutate(&mut settings.editor);
-    let _ = settings.save();
+    let _ = Settings::update(|settings| mutate(&mut settings.editor));
 }
 
 /// Show a toast notification that auto-dismisses after 3 seconds.
 /// Safe to call from any code that has access to `IdeState`.
+/// Static messages are translated; formatted ones pass through unchanged.
 pub fn show_toast(toast: RwSignal<Option<String>>, msg: impl Into<String>) {
     use floem::ext_event::create_ext_action;
     use floem::reactive::Scope;
-    toast.set(Some(msg.into()));
+    toast.set(Some(tr(&msg.into())));
     // Use Scope::current() to reuse the caller's scope — no leak.
     let dismiss = create_ext_action(Scope::current(), move |_: ()| {
         toast.set(None);
@@ -635,111 +919,30 @@ fn load_editor_settings() -> phazeai_core::config::EditorSettings {
     Settings::load().editor
 }
 
-#[allow(clippy::too_many_arguments)]
-fn spawn_sidecar_start(
-    python_path: String,
-    script: PathBuf,
-    workspace_root: PathBuf,
-    shared_client: Arc<std::sync::Mutex<Option<Arc<SidecarClient>>>>,
-    ready_tx: std::sync::mpsc::SyncSender<bool>,
-    status_tx: std::sync::mpsc::SyncSender<String>,
-    build_tx: std::sync::mpsc::SyncSender<bool>,
-    build_after_start: bool,
-) {
-    std::thread::spawn(move || {
-        if shared_client.lock().ok().and_then(|g| g.clone()).is_some() {
-            let _ = ready_tx.send(true);
-            if build_after_start {
-                let _ = build_tx.send(true);
-            }
-            return;
-        }
-
-        let _ = status_tx.send(format!(
-            "Starting semantic search sidecar with {}...",
-            python_path
-        ));
-
-        let rt = match tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-        {
-            Ok(rt) => rt,
-            Err(e) => {
-                let _ = status_tx.send(format!("Semantic search runtime error: {e}"));
-                let _ = ready_tx.send(false);
-                return;
-            }
-        };
-
-        rt.block_on(async move {
-            let mut mgr = SidecarManager::new(python_path.clone(), script.clone());
-            match mgr.start().await {
-                Ok(()) => {
-                    if let Some(process) = mgr.into_process() {
-                        match SidecarClient::from_process(process) {
-                            Ok(client) => {
-                                if client.health_check().await {
-                                    let client = Arc::new(client);
-                                    if let Ok(mut slot) = shared_client.lock() {
-                                        *slot = Some(client);
-                                    }
-                                    let _ = ready_tx.send(true);
-                                    let _ = status_tx.send(format!(
-                                        "Semantic search ready for {}",
-                                        workspace_root.display()
-                                    ));
-                                    if build_after_start {
-                                        let _ = build_tx.send(true);
-                                    }
-                                } else {
-                                    let _ = ready_tx.send(false);
-                                    let _ = status_tx
-                                        .send("Semantic search sidecar failed health check".into());
-                                }
-                            }
-                            Err(e) => {
-                                let _ = ready_tx.send(false);
-                                let _ = status_tx
-                                    .send(format!("Semantic search connection error: {e}"));
-                            }
-                        }
-                    } else {
-                        let _ = ready_tx.send(false);
-                        let _ = status_tx.send("Semantic search process handle missing".into());
-                    }
-                }
-                Err(e) => {
-                    let _ = ready_tx.send(false);
-                    let _ = status_tx.send(format!("Semantic search failed to start: {e}"));
-                }
-            }
-        });
-    });
-}
-
 impl IdeState {
     pub fn new(settings: &Settings) -> Self {
         let _theme = PhazeTheme::from_name(&settings.editor.theme);
         // Use the git repository root as the workspace, so all git operations
         // are correctly scoped to the project root even when launched from a
         // subdirectory. Fall back to current_dir if not inside a git repo.
-        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-        let workspace = std::process::Command::new("git")
-            .args(["rev-parse", "--show-toplevel"])
-            .current_dir(&cwd)
-            .output()
-            .ok()
-            .and_then(|out| {
-                if out.status.success() {
-                    String::from_utf8(out.stdout)
-                        .ok()
-                        .map(|s| PathBuf::from(s.trim()))
-                } else {
-                    None
-                }
-            })
-            .unwrap_or(cwd);
+        let workspace = startup::phase("workspace", || {
+            let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            std::process::Command::new("git")
+                .args(["rev-parse", "--show-toplevel"])
+                .current_dir(&cwd)
+                .output()
+                .ok()
+                .and_then(|out| {
+                    if out.status.success() {
+                        String::from_utf8(out.stdout)
+                            .ok()
+                            .map(|s| PathBuf::from(s.trim()))
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(cwd)
+        });
 
         let git_branch = create_rw_signal("main".to_string());
 
@@ -788,9 +991,11 @@ impl IdeState {
 
         // Start LSP bridge — background tokio thread running LspManager.
         // Must be called in a Floem reactive scope (we're inside the window callback).
-        let lsp = start_lsp_bridge(workspace.clone());
+        let lsp = startup::phase("lsp", || start_lsp_bridge(workspace.clone()));
         let lsp_cmd = lsp.cmd_tx;
         let diagnostics = lsp.diagnostics;
+        let diagnostic_counts = lsp.diagnostic_counts;
+        let lsp_status = lsp.server_status;
         let completions = lsp.completions;
         let goto_definition = lsp.goto_definition;
         let hover_text = lsp.hover_text;
@@ -800,22 +1005,47 @@ impl IdeState {
         let doc_symbols = lsp.doc_symbols;
         let workspace_symbols = lsp.workspace_symbols;
         let lsp_progress = lsp.lsp_progress;
-        let peek_def_lines = lsp.peek_def_lines;
+        let peek_definition = lsp.peek_definition;
         let code_lens = lsp.code_lens;
+        let document_highlights = lsp.document_highlights;
         let folding_ranges = lsp.folding_ranges;
         let inlay_hints_lsp = lsp.inlay_hints;
+        let lsp_log = lsp.log;
 
-        // Watch peek_def_lines: when it becomes non-empty, open the peek popup.
-        let peek_def_open_sig: RwSignal<bool> = create_rw_signal(false);
-        {
-            let peek_lines = peek_def_lines;
-            let peek_open = peek_def_open_sig;
-            create_effect(move |_| {
-                if !peek_lines.get().is_empty() {
-                    peek_open.set(true);
-                }
-            });
-        }
+        // Fill an open peek in with the definition or references it is
+        // waiting for once they arrive.
+        let peek_sig: RwSignal<Option<Peek>> = create_rw_signal(None);
+        create_effect(move |_| {
+            let Some(found) = peek_definition.get() else {
+                return;
+            };
+            let waiting = peek_sig.with_untracked(
+                |p| matches!(p, Some(p) if p.content == PeekContent::FindingDefinition),
+            );
+            if waiting {
+                peek_sig.update(|p| {
+                    if let Some(p) = p {
+                        p.content = match found {
+                            Some(location) => PeekContent::Definition(location),
+                            None => PeekContent::NoDefinition,
+                        };
+                    }
+                });
+            }
+        });
+        create_effect(move |_| {
+            let refs = references.get();
+            let waiting = peek_sig.with_untracked(
+                |p| matches!(p, Some(p) if p.content == PeekContent::FindingReferences),
+            );
+            if waiting {
+                peek_sig.update(|p| {
+                    if let Some(p) = p {
+                        p.content = PeekContent::References(refs);
+                    }
+                });
+            }
+        });
 
         // When a definition result arrives, navigate to the target file + line.
         let goto_line_sig: RwSignal<u32> = create_rw_signal(0u32);
@@ -892,18 +1122,17 @@ impl IdeState {
             });
         }
 
-        // Detect read-only status + line-ending style in background thread.
+        // Detect read-only status in a background thread. Line endings and
+        // encoding come from the editor, which knows the buffer's.
         let active_readonly_sig: RwSignal<bool> = create_rw_signal(false);
         let line_ending_sig: RwSignal<&'static str> = create_rw_signal("LF");
         {
             use floem::ext_event::create_signal_from_channel;
-            let (file_info_tx, file_info_rx) =
-                std::sync::mpsc::sync_channel::<(bool, &'static str)>(1);
+            let (file_info_tx, file_info_rx) = std::sync::mpsc::sync_channel::<bool>(1);
             let file_info_sig = create_signal_from_channel(file_info_rx);
             create_effect(move |_| {
-                if let Some((readonly, ending)) = file_info_sig.get() {
+                if let Some(readonly) = file_info_sig.get() {
                     active_readonly_sig.set(readonly);
-                    line_ending_sig.set(ending);
                 }
             });
             create_effect(move |_| {
@@ -913,21 +1142,7 @@ impl IdeState {
                         let readonly = std::fs::metadata(&path)
                             .map(|m| m.permissions().readonly())
                             .unwrap_or(false);
-                        let style = std::fs::read(&path)
-                            .ok()
-                            .map(|bytes| {
-                                let crlf_count = bytes.windows(2).filter(|w| *w == b"\r\n").count();
-                                let lf_count = bytes.iter().filter(|&&b| b == b'\n').count();
-                                if crlf_count > 0 && lf_count > crlf_count {
-                                    "Mixed"
-                                } else if crlf_count > 0 {
-                                    "CRLF"
-                                } else {
-                                    "LF"
-                                }
-                            })
-                            .unwrap_or("LF");
-                        let _ = tx.try_send((readonly, style));
+                        let _ = tx.try_send(readonly);
                     });
                 } else {
                     active_readonly_sig.set(false);
@@ -939,6 +1154,13 @@ impl IdeState {
         // Create persistent settings signals before Self so we can wire save effects.
         let theme_signal = create_rw_signal(PhazeTheme::from_name(&session.theme));
         let font_size_signal = create_rw_signal(editor_cfg.font_size as u32);
+        let font_family_signal = create_rw_signal(editor_cfg.font_family.clone());
+        let font_ligatures_signal = create_rw_signal(editor_cfg.font_ligatures);
+        let line_height_signal = create_rw_signal(editor_cfg.line_height.clamp(1.0, 2.5));
+        let terminal_font_family_signal = create_rw_signal(editor_cfg.terminal_font_family.clone());
+        let terminal_font_size_signal = create_rw_signal(editor_cfg.terminal_font_size as u32);
+        let chat_font_family_signal = create_rw_signal(editor_cfg.chat_font_family.clone());
+        let chat_font_size_signal = create_rw_signal(editor_cfg.chat_font_size as u32);
         let tab_size_signal = create_rw_signal(editor_cfg.tab_size);
         let auto_save_signal = create_rw_signal(editor_cfg.auto_save);
         let word_wrap_signal = create_rw_signal(editor_cfg.word_wrap);
@@ -946,12 +1168,97 @@ impl IdeState {
         let inlay_hints_toggle_signal = create_rw_signal(editor_cfg.inlay_hints);
         let code_lens_visible_signal = create_rw_signal(editor_cfg.code_lens);
         let organize_imports_signal = create_rw_signal(editor_cfg.organize_imports_on_save);
+        let normalize_eol_signal = create_rw_signal(editor_cfg.normalize_line_endings_on_save);
+        let rulers_signal = create_rw_signal(editor_cfg.rulers.clone());
+        let line_length_lint_signal = create_rw_signal(editor_cfg.line_length_lint);
+        {
+            let lsp_tx = lsp_cmd.clone();
+            create_effect(move |_| {
+                let on = line_length_lint_signal.get();
+                let _ = lsp_tx.send(LspCommand::SetLineLengthLint(on));
+                if let Some(path) = open_file.get_untracked().filter(|_| on) {
+                    let _ = lsp_tx.send(LspCommand::CheckLineLength { path });
+                }
+            });
+        }
+        let explain_commands_signal = create_rw_signal(editor_cfg.explain_commands_on_hover);
+        let inline_blame_signal = create_rw_signal(editor_cfg.inline_blame);
+        let blame_gutter_signal = create_rw_signal(editor_cfg.blame_gutter);
+        let follow_system_theme_signal = create_rw_signal(editor_cfg.follow_system_theme);
+        let syntax_overrides_signal = create_rw_signal(editor_cfg.syntax_themes.clone());
+
+        // Resolve the syntect theme whenever the UI theme or overrides change.
+        let syntax_theme_signal = create_rw_signal(String::new());
+        create_effect(move |_| {
+            let variant = theme_signal.get().variant;
+            let name = syntax_overrides_signal
+                .get()
+                .get(variant.name())
+                .cloned()
+                .unwrap_or_else(|| variant.default_syntax_theme().to_string());
+            syntax_theme_signal.set(name);
+        });
+
+        // ── Follow OS appearance ──────────────────────────────────────────────
+        // A background thread polls the OS preference and only reports changes.
+        // The dark theme to return to is the last dark theme the user picked.
+        {
+            let (sys_tx, sys_rx) = std::sync::mpsc::sync_channel::<bool>(1);
+            let sys_dark_sig = create_signal_from_channel(sys_rx);
+            let last_dark = create_rw_signal(if theme_signal.get_untracked().is_dark() {
+                theme_signal.get_untracked().variant
+            } else {
+                ThemeVariant::from_name(phazeai_core::constants::defaults::THEME)
+            });
+            create_effect(move |_| {
+                let t = theme_signal.get();
+                if t.is_dark() {
+                    last_dark.set(t.variant);
+                }
+            });
+            create_effect(move |_| {
+                let follow = follow_system_theme_signal.get();
+                let Some(dark) = sys_dark_sig.get() else {
+                    return;
+                };
+                if !follow || theme_signal.get_untracked().is_dark() == dark {
+                    return;
+                }
+                let variant = if dark {
+                    last_dark.get_untracked()
+                } else {
+                    ThemeVariant::Light
+                };
+                theme_signal.set(PhazeTheme::from_variant(variant));
+            });
+            std::thread::spawn(move || {
+                let mut last: Option<bool> = None;
+                loop {
+                    if let Some(dark) = crate::theme::detect_system_dark() {
+                        if last != Some(dark) {
+                            last = Some(dark);
+                            if sys_tx.send(dark).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    std::thread::sleep(Duration::from_secs(5));
+                }
+            });
+        }
 
-        // Whenever theme, font_size, or tab_size changes, persist to config.toml.
+        // Whenever theme, fonts, or tab_size changes, persist to config.toml.
         // Done in a background thread to avoid blocking the UI.
         create_effect(move |_| {
             let theme_name = theme_signal.get().variant.name().to_string();
             let fs = font_size_signal.get();
+            let family = font_family_signal.get();
+            let ligatures = font_ligatures_signal.get();
+            let line_height = line_height_signal.get();
+            let term_family = terminal_font_family_signal.get();
+            let term_fs = terminal_font_size_signal.get();
+            let chat_family = chat_font_family_signal.get();
+            let chat_fs = chat_font_size_signal.get();
             let ts = tab_size_signal.get();
             let auto_save = auto_save_signal.get();
             let word_wrap = word_wrap_signal.get();
@@ -959,10 +1266,25 @@ impl IdeState {
             let inlay = inlay_hints_toggle_signal.get();
             let code_lens = code_lens_visible_signal.get();
             let organize = organize_imports_signal.get();
+            let normalize_eol = normalize_eol_signal.get();
+            let rulers = rulers_signal.get();
+            let line_length_lint = line_length_lint_signal.get();
+            let explain_commands = explain_commands_signal.get();
+            let inline_blame = inline_blame_signal.get();
+            let blame_gutter = blame_gutter_signal.get();
+            let follow_system = follow_system_theme_signal.get();
+            let syntax_themes = syntax_overrides_signal.get();
             std::thread::spawn(move || {
                 save_editor_settings(|e| {
                     e.theme = theme_name;
                     e.font_size = fs as f32;
+                    e.font_family = family;
+                    e.font_ligatures = ligatures;
+                    e.line_height = line_height;
+                    e.terminal_font_family = term_family;
+                    e.terminal_font_size = term_fs as f32;
+                    e.chat_font_family = chat_family;
+                    e.chat_font_size = chat_fs as f32;
                     e.tab_size = ts;
                     e.auto_save = auto_save;
                     e.word_wrap = word_wrap;
@@ -970,6 +1292,14 @@ impl IdeState {
                     e.inlay_hints = inlay;
                     e.code_lens = code_lens;
                     e.organize_imports_on_save = organize;
+                    e.normalize_line_endings_on_save = normalize_eol;
+                    e.rulers = rulers;
+                    e.line_length_lint = line_length_lint;
+                    e.explain_commands_on_hover = explain_commands;
+                    e.inline_blame = inline_blame;
+                    e.blame_gutter = blame_gutter;
+                    e.follow_system_theme = follow_system;
+                    e.syntax_themes = syntax_themes;
                 });
             });
         });
@@ -984,10 +1314,12 @@ impl IdeState {
             "Semantic search not started.".to_string()
         });
         let sidecar_building_sig = create_rw_signal(false);
-        let sidecar_results_sig: RwSignal<Vec<(String, String)>> = create_rw_signal(Vec::new());
+        let sidecar_results_sig: RwSignal<Vec<(String, u32, String)>> =
+            create_rw_signal(Vec::new());
         let sidecar_build_nonce_sig = create_rw_signal(0u64);
         let sidecar_search_nonce_sig = create_rw_signal(0u64);
         let sidecar_query_sig = create_rw_signal(String::new());
+        let similar_code_query_sig = create_rw_signal(None::<SimilarCodeQuery>);
 
         let script_candidates: Vec<PathBuf> = {
             let exe_dir = std::env::current_exe()
@@ -1010,9 +1342,19 @@ impl IdeState {
         let sidecar_script = script_candidates.into_iter().find(|p| p.exists());
 
         // Shared sidecar client — always created so IdeState can reference it
-        // for clean shutdown. Will be None when sidecar is disabled or unavailable.
-        let shared_client: Arc<std::sync::Mutex<Option<Arc<SidecarClient>>>> =
+        // for clean shutdown. The supervisor fills it while the sidecar is up
+        // and empties it while the sidecar is down.
+        let shared_client: ClientSlot = Arc::new(std::sync::Mutex::new(None));
+        let sidecar_supervisor: Arc<std::sync::Mutex<Option<SidecarHandle>>> =
             Arc::new(std::sync::Mutex::new(None));
+        let sidecar_state_sig: RwSignal<Option<SidecarState>> = create_rw_signal(None);
+        let sidecar_restart_nonce_sig = create_rw_signal(0u64);
+        let sidecar_repair_nonce_sig = create_rw_signal(0u64);
+        // Supervisor events. The sidecar's stderr and the output of setting up
+        // its virtualenv go to the Output panel, wired up once the panel's log
+        // exists below.
+        let (sidecar_event_tx, sidecar_event_rx) = std::sync::mpsc::channel::<SidecarEvent>();
+        let sidecar_events = create_signal_from_channel(sidecar_event_rx);
 
         if !settings.sidecar.enabled {
             sidecar_ready_sig.set(false);
@@ -1024,20 +1366,13 @@ impl IdeState {
             let sidecar_build_nonce = sidecar_build_nonce_sig;
             let sidecar_nonce = sidecar_search_nonce_sig;
             let sidecar_query2 = sidecar_query_sig;
-            let (sc_tx, sc_rx) = std::sync::mpsc::sync_channel::<Vec<(String, String)>>(4);
+            let (sc_tx, sc_rx) = std::sync::mpsc::sync_channel::<Vec<(String, u32, String)>>(4);
             let sc_signal = create_signal_from_channel(sc_rx);
             create_effect(move |_| {
                 if let Some(results) = sc_signal.get() {
                     sidecar_results2.set(results);
                 }
             });
-            let (ready_tx, ready_rx) = std::sync::mpsc::sync_channel::<bool>(1);
-            let ready_signal = create_signal_from_channel(ready_rx);
-            create_effect(move |_| {
-                if let Some(ok) = ready_signal.get() {
-                    sidecar_ready2.set(ok);
-                }
-            });
             let (status_tx, status_rx) = std::sync::mpsc::sync_channel::<String>(8);
             let status_signal = create_signal_from_channel(status_rx);
             create_effect(move |_| {
@@ -1054,13 +1389,83 @@ impl IdeState {
             });
             let (build_tx, build_rx) = std::sync::mpsc::sync_channel::<bool>(8);
             let build_signal = create_signal_from_channel(build_rx);
+            // Set when a build is asked for before the sidecar is ready; the
+            // build runs as soon as it is.
+            let build_when_ready = create_rw_signal(false);
+
+            // Start the supervisor, or restart the sidecar it is watching;
+            // `repair` rebuilds the sidecar's virtualenv first.
+            let start_sidecar = {
+                let python_path = settings.sidecar.python_path.clone();
+                let script = script.clone();
+                let env = SidecarEnv::default_root().map(SidecarEnv::new);
+                let workspace_root = workspace.clone();
+                let client = shared_client.clone();
+                let supervisor = sidecar_supervisor.clone();
+                let events = sidecar_event_tx.clone();
+                move |repair: bool| {
+                    let Ok(mut handle) = supervisor.lock() else {
+                        return;
+                    };
+                    match handle.as_ref() {
+                        Some(running) if running.is_running() && repair => running.repair(),
+                        Some(running) if running.is_running() => running.restart(),
+                        _ => {
+                            let mut supervisor =
+                                SidecarSupervisor::new(python_path.clone(), script.clone())
+                                    .client_slot(client.clone())
+                                    .variables(EnvVars::for_workspace(&workspace_root));
+                            if let Some(env) = env.clone() {
+                                supervisor = supervisor.environment(env);
+                                if repair {
+                                    supervisor = supervisor.rebuild_environment();
+                                }
+                            }
+                            *handle = Some(supervisor.spawn(events.clone()));
+                        }
+                    }
+                }
+            };
+
             let workspace_root = workspace.clone();
             let python_path = settings.sidecar.python_path.clone();
-            let script_for_build = script.clone();
+            let build_tx_for_ready = build_tx.clone();
+            create_effect(move |_| {
+                let Some(SidecarEvent::State(sidecar)) = sidecar_events.get() else {
+                    return;
+                };
+                sidecar_ready2.set(matches!(sidecar, SidecarState::Ready { .. }));
+                sidecar_status2.set(match &sidecar {
+                    SidecarState::Starting => {
+                        format!("Starting semantic search sidecar with {python_path}...")
+                    }
+                    SidecarState::Ready { .. } => {
+                        format!("Semantic search ready for {}", workspace_root.display())
+                    }
+                    other => format!("Semantic search sidecar {}", other.label()),
+                });
+                match &sidecar {
+                    SidecarState::Ready { .. } => {
+                        if build_when_ready.get_untracked() {
+                            build_when_ready.set(false);
+                            let _ = build_tx_for_ready.send(true);
+                        }
+                    }
+                    SidecarState::Failed(_) | SidecarState::Stopped => {
+                        if build_when_ready.get_untracked() {
+                            build_when_ready.set(false);
+                            sidecar_building2.set(false);
+                        }
+                    }
+                    _ => {}
+                }
+                sidecar_state_sig.set(Some(sidecar));
+            });
+
+            let workspace_root = workspace.clone();
             let shared_client_for_build = shared_client.clone();
-            let ready_tx_for_build = ready_tx.clone();
             let status_tx_for_build = status_tx.clone();
-            let build_tx_for_build = build_tx.clone();
+            let start_for_build = start_sidecar.clone();
             create_effect(move |_| {
                 if build_signal.get().is_none() {
                     return;
@@ -1070,16 +1475,14 @@ impl IdeState {
                 let Some(client) = shared_client_for_build.lock().ok().and_then(|g| g.clone())
                 else {
                     let _ = building_tx.send(true);
-                    spawn_sidecar_start(
-                        python_path.clone(),
-                        script_for_build.clone(),
-                        root,
-                        shared_client_for_build.clone(),
-                        ready_tx_for_build.clone(),
-                        status_tx_for_build.clone(),
-                        build_tx_for_build.clone(),
-                        true,
-                    );
+                    build_when_ready.set(true);
+                    // Already on its way up otherwise.
+                    if matches!(
+                        sidecar_state_sig.get_untracked(),
+                        None | Some(SidecarState::Failed(_) | SidecarState::Stopped)
+                    ) {
+                        start_for_build(false);
+                    }
                     return;
                 };
                 let tx = status_tx_for_build.clone();
@@ -1088,6 +1491,8 @@ impl IdeState {
                 std::thread::spawn(move || {
                     let _ = building_tx2.send(true);
                     let _ = tx.send(format!("Building semantic index for {root_str}..."));
+                    // Finished when the thread ends, whichever way it ends.
+                    let _task = progress::global().task("Semantic indexing").start();
                     let rt = match tokio::runtime::Builder::new_current_thread()
                         .enable_all()
                         .build()
@@ -1142,6 +1547,7 @@ impl IdeState {
                             );
                             let _ = tx.send(vec![(
                                 "sidecar unavailable".to_string(),
+                                1,
                                 "semantic search is not connected".to_string(),
                             )]);
                             return;
@@ -1155,6 +1561,7 @@ impl IdeState {
                             Err(e) => {
                                 let _ = tx.send(vec![(
                                     "sidecar error".to_string(),
+                                    1,
                                     format!("failed to create runtime: {e}"),
                                 )]);
                                 return;
@@ -1180,13 +1587,14 @@ impl IdeState {
                                                     .and_then(|v| v.as_str())
                                                     .unwrap_or("")
                                                     .to_string();
-                                                (file, snippet)
+                                                (file, 1, snippet)
                                             })
                                             .collect::<Vec<_>>()
                                     })
                                     .unwrap_or_default(),
                                 Err(e) => vec![(
                                     "sidecar error".to_string(),
+                                    1,
                                     if e.contains("Index not built") {
                                         "semantic index not built yet — click Reindex".to_string()
                                     } else {
@@ -1200,6 +1608,88 @@ impl IdeState {
                     });
                 }
             });
+            // Find code like a selection; the results take the place of the
+            // semantic search results, opening at the snippet's first line.
+            let sc_tx3 = sc_tx.clone();
+            let shared_client_for_similar = shared_client.clone();
+            let status_tx_for_similar = status_tx.clone();
+            create_effect(move |_| {
+                let Some(query) = similar_code_query_sig.get() else {
+                    return;
+                };
+                let tx = sc_tx3.clone();
+                let client_cell = shared_client_for_similar.clone();
+                let status_tx3 = status_tx_for_similar.clone();
+                std::thread::spawn(move || {
+                    let client = client_cell.lock().ok().and_then(|g| g.clone());
+                    let Some(client) = client else {
+                        let _ = status_tx3.send(
+                            "Semantic search unavailable. Build the index to start the sidecar."
+                                .to_string(),
+                        );
+                        return;
+                    };
+                    let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                    else {
+                        return;
+                    };
+                    let name = query
+                        .path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let path = query.path.to_string_lossy().to_string();
+                    let lines = (query.start_line, query.end_line);
+                    let result =
+                        rt.block_on(client.find_similar(&query.code, Some(&path), Some(lines), 8));
+                    let matches = match result {
+                        Ok(value) => value
+                            .get("matches")
+                            .and_then(|v| v.as_array())
+                            .cloned()
+                            .unwrap_or_default(),
+                        Err(e) => {
+                            let _ = status_tx3.send(if e.contains("Index not built") {
+                                "Semantic index not built yet — click Reindex".to_string()
+                            } else {
+                                format!("Find similar code failed: {e}")
+                            });
+                            return;
+                        }
+                    };
+                    let results: Vec<(String, u32, String)> = matches
+                        .iter()
+                        .map(|m| {
+                            let file = m.get("file").and_then(|v| v.as_str()).unwrap_or("unknown");
+                            let line = |key: &str| m.get(key).and_then(|v| v.as_u64()).unwrap_or(1);
+                            let kind = m.get("kind").and_then(|v| v.as_str()).unwrap_or("related");
+                            let first = m
+                                .get("snippet")
+                                .and_then(|v| v.as_str())
+                                .and_then(|s| s.lines().find(|l| !l.trim().is_empty()))
+                                .unwrap_or("")
+                                .trim();
+                            (
+                                file.to_string(),
+                                line("start_line") as u32,
+                                format!(
+                                    "{kind} · lines {}–{} · {first}",
+                                    line("start_line"),
+                                    line("end_line")
+                                ),
+                            )
+                        })
+                        .collect();
+                    let _ = status_tx3.send(if results.is_empty() {
+                        format!("No code like lines {}–{} of {name}", lines.0, lines.1)
+                    } else {
+                        format!("Code like lines {}–{} of {name}:", lines.0, lines.1)
+                    });
+                    let _ = tx.send(results);
+                });
+            });
             let build_tx_for_nonce = build_tx.clone();
             create_effect(move |_| {
                 let nonce = sidecar_build_nonce.get();
@@ -1209,18 +1699,26 @@ impl IdeState {
                 let _ = build_tx_for_nonce.send(true);
             });
 
+            let start_for_restart = start_sidecar.clone();
+            create_effect(move |_| {
+                if sidecar_restart_nonce_sig.get() == 0 {
+                    return;
+                }
+                start_for_restart(false);
+            });
+            let start_for_repair = start_sidecar.clone();
+            create_effect(move |_| {
+                if sidecar_repair_nonce_sig.get() == 0 {
+                    return;
+                }
+                start_for_repair(true);
+            });
+
             if settings.sidecar.auto_start {
                 sidecar_status_sig.set("Starting semantic search...".to_string());
-                spawn_sidecar_start(
-                    settings.sidecar.python_path.clone(),
-                    script,
-                    workspace.clone(),
-                    shared_client.clone(),
-                    ready_tx.clone(),
-                    status_tx.clone(),
-                    build_tx.clone(),
-                    true,
-                );
+                build_when_ready.set(true);
+                // Spawning Python can wait until the window is up.
+                startup::after_first_frame(move || start_sidecar(false));
             } else {
                 sidecar_status_sig.set(
                     "Semantic search idle. Click Reindex to start and build the index.".into(),
@@ -1228,6 +1726,9 @@ impl IdeState {
             }
         } else {
             sidecar_status_sig.set("Semantic search sidecar script not found.".to_string());
+            sidecar_state_sig.set(Some(SidecarState::Failed(
+                "sidecar/server.py not found".to_string(),
+            )));
         }
 
         // AI provider / model signals — initialized from current settings file.
@@ -1236,6 +1737,50 @@ impl IdeState {
         let ai_model_sig = create_rw_signal(settings.llm.model.clone());
 
         let status_toast_sig = create_rw_signal(None);
+        let notifications_sig = create_rw_signal(NotificationCenter::new());
+        record_toasts(status_toast_sig, notifications_sig);
+
+        // Semantic index builds show as a progress notification, finished
+        // with the sidecar's last status.
+        let index_note = create_rw_signal(None::<u64>);
+        create_effect(move |_| {
+            let building = sidecar_building_sig.get();
+            let status = sidecar_status_sig.get();
+            match index_note.get_untracked() {
+                None if building => index_note.set(Some(start_progress(
+                    notifications_sig,
+                    "Building semantic index",
+                    None,
+                ))),
+                Some(id) if building => update_progress(
+                    notifications_sig,
+                    id,
+                    Progress {
+                        done: 0,
+                        total: None,
+                    },
+                    Some(&status),
+                ),
+                Some(id) => {
+                    index_note.set(None);
+                    finish_progress(notifications_sig, id, Severity::of_message(&status), status);
+                }
+                None => {}
+            }
+        });
+
+        let background_tasks_sig = create_rw_signal(progress::global().tasks());
+        {
+            let events = create_signal_from_channel(progress::global().subscribe());
+            create_effect(move |_| {
+                if events.get().is_some() {
+                    background_tasks_sig.set(progress::global().tasks());
+                }
+            });
+        }
+
+        let jobs_sig = create_rw_signal(Vec::new());
+        let job_queue = start_job_queue(jobs_sig, status_toast_sig);
 
         // Extension Manager — native plugin system
         let ext_manager = Arc::new(std::sync::Mutex::new(
@@ -1247,16 +1792,33 @@ impl IdeState {
             let provider_name = ai_provider_sig.get();
             let model = ai_model_sig.get();
             std::thread::spawn(move || {
-                let mut s = Settings::load();
                 let Some(provider) = provider_name_to_llm_provider(&provider_name) else {
                     return;
                 };
-                s.llm.provider = provider;
-                s.llm.model = model;
-                let _ = s.save();
+                let _ = Settings::update(|s| {
+                    s.llm.provider = provider;
+                    s.llm.model = model;
+                });
             });
         });
 
+        // Follow provider / model changes made outside this window: by the
+        // CLI, another instance or a hand edit of the config.
+        let config_changes = create_signal_from_channel(phazeai_core::config::store::subscribe());
+        create_effect(move |_| {
+            let Some(change) = config_changes.get().filter(|c| c.external) else {
+                return;
+            };
+            let llm = change.settings.llm;
+            let provider = llm.provider.to_provider_id().name().to_string();
+            if ai_provider_sig.get_untracked() != provider {
+                ai_provider_sig.set(provider);
+            }
+            if ai_model_sig.get_untracked() != llm.model {
+                ai_model_sig.set(llm.model);
+            }
+        });
+
         // ── Session-persisted layout signals ─────────────────────────────────
         // Create these before `Self {}` so the debounced-save effect can capture them.
         let show_left_panel_sig = create_rw_signal(session.show_left_panel);
@@ -1265,8 +1827,101 @@ impl IdeState {
         let split_editor_sig = create_rw_signal(session.split_editor);
         let split_editor_down_sig = create_rw_signal(session.split_editor_down);
         let vim_mode_sig = create_rw_signal(session.vim_mode);
+        let emacs_mode_sig = create_rw_signal(session.emacs_mode && !session.vim_mode);
         let zen_mode_sig = create_rw_signal(session.zen_mode);
         let left_panel_width_sig = create_rw_signal(session.left_panel_width);
+        let workspace_root_sig = create_rw_signal(workspace.clone());
+
+        let todos_sig = create_rw_signal(Vec::new());
+        let todos_scanning_sig = create_rw_signal(false);
+        let todos_rescan_sig = create_rw_signal(0u64);
+        start_todo_scanner(
+            todos_sig,
+            todos_scanning_sig,
+            workspace_root_sig,
+            todos_rescan_sig,
+        );
+
+        let file_picker_files_sig = create_rw_signal(Vec::new());
+        let file_picker_unindexed_sig = create_rw_signal(Vec::new());
+        start_file_index(
+            file_picker_files_sig,
+            file_picker_unindexed_sig,
+            workspace_root_sig,
+        );
+
+        let output_sig = create_rw_signal(OutputLog::new());
+        let build_run_sig = create_rw_signal(0u64);
+        let build_running_sig = create_rw_signal(false);
+        start_build_runner(
+            output_sig,
+            build_running_sig,
+            workspace_root_sig,
+            build_run_sig,
+            notifications_sig,
+        );
+        let run_configs = RunConfigSignals {
+            request: create_rw_signal(None),
+            running: create_rw_signal(None),
+            last: create_rw_signal(None),
+            stop: create_rw_signal(0),
+        };
+        start_config_runner(
+            output_sig,
+            workspace_root_sig,
+            run_configs,
+            notifications_sig,
+        );
+        create_effect(move |_| {
+            if let Some(line) = lsp_log.get() {
+                append_output(output_sig, output::LSP, &line);
+            }
+        });
+        create_effect(move |_| {
+            if let Some(SidecarEvent::Stderr(line) | SidecarEvent::Setup(line)) =
+                sidecar_events.get()
+            {
+                append_output(output_sig, output::SIDECAR, &line);
+            }
+        });
+
+        // Bookmarks: the store holds every workspace's; `bookmarks_sig` is the
+        // open workspace's, reloaded when the workspace changes and written
+        // back into the store on every edit.
+        let bookmark_store_sig = create_rw_signal(session.bookmarks.clone());
+        let saved_folds_sig = create_rw_signal(session.folds.clone());
+        let bookmarks_sig = create_rw_signal(Bookmarks::new());
+        create_effect(move |_| {
+            let key = workspace_root_sig.get().to_string_lossy().into_owned();
+            let marks = bookmark_store_sig
+                .get_untracked()
+                .get(&key)
+                .cloned()
+                .unwrap_or_default();
+            bookmarks_sig.set(marks);
+        });
+        create_effect(move |_| {
+            let marks = bookmarks_sig.get();
+            let key = workspace_root_sig
+                .get_untracked()
+                .to_string_lossy()
+                .into_owned();
+            let stored = bookmark_store_sig
+                .get_untracked()
+                .get(&key)
+                .cloned()
+                .unwrap_or_default();
+            if stored == marks {
+                return;
+            }
+            bookmark_store_sig.update(|store| {
+                if marks.is_empty() {
+                    store.remove(&key);
+                } else {
+                    store.insert(key, marks);
+                }
+            });
+        });
 
         // Debounce cancel token: shared between the effect and spawned threads.
         let session_gen = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
@@ -1286,8 +1941,11 @@ impl IdeState {
                 let split_editor = split_editor_sig.get();
                 let split_editor_down = split_editor_down_sig.get();
                 let vim_mode = vim_mode_sig.get();
+                let emacs_mode = emacs_mode_sig.get();
                 let theme = theme_signal.get().variant.name().to_string();
                 let zen_mode = zen_mode_sig.get();
+                let bookmarks = bookmark_store_sig.get();
+                let folds = saved_folds_sig.get();
                 session_commit(
                     gen.clone(),
                     open_tabs,
@@ -1299,12 +1957,30 @@ impl IdeState {
                     split_editor,
                     split_editor_down,
                     vim_mode,
+                    emacs_mode,
                     theme,
                     zen_mode,
+                    bookmarks,
+                    folds,
                 );
             });
         }
 
+        // Jump list: report every cursor move; file switches and long moves
+        // (go-to-definition, goto-line, search results…) remember where the
+        // cursor came from.
+        let active_cursor_sig: RwSignal<Option<(PathBuf, u32, u32)>> = create_rw_signal(None);
+        let jump_list_sig = create_rw_signal(JumpList::new());
+        create_effect(move |last: Option<Option<Location>>| {
+            let to = active_cursor_sig
+                .get()
+                .map(|(path, line, col)| Location::new(path, line, col));
+            if let (Some(Some(from)), Some(to)) = (&last, &to) {
+                jump_list_sig.update(|j| j.observe(from, to));
+            }
+            to
+        });
+
         Self {
             theme: theme_signal,
             left_panel_tab: create_rw_signal(Tab::Explorer),
@@ -1313,7 +1989,7 @@ impl IdeState {
             show_right_panel: show_right_panel_sig,
             show_bottom_panel: show_bottom_panel_sig,
             open_file,
-            workspace_root: create_rw_signal(workspace),
+            workspace_root: workspace_root_sig,
             ai_thinking: create_rw_signal(false),
             left_panel_width: left_panel_width_sig,
             git_branch,
@@ -1321,15 +1997,22 @@ impl IdeState {
             command_palette_query: create_rw_signal(String::new()),
             file_picker_open: create_rw_signal(false),
             file_picker_query: create_rw_signal(String::new()),
-            file_picker_files: create_rw_signal(Vec::new()),
+            file_picker_files: file_picker_files_sig,
+            file_picker_unindexed: file_picker_unindexed_sig,
             search_query: create_rw_signal("".to_string()),
             search_results: create_rw_signal(Vec::new()),
             diagnostics,
+            diagnostic_counts,
             lsp_cmd,
+            lsp_status,
             completions,
             completion_open: create_rw_signal(false),
             completion_selected: create_rw_signal(0usize),
-            active_cursor: create_rw_signal(None),
+            active_cursor: active_cursor_sig,
+            jump_list: jump_list_sig,
+            bookmarks: bookmarks_sig,
+            bookmark_store: bookmark_store_sig,
+            saved_folds: saved_folds_sig,
             panel_drag_active: create_rw_signal(false),
             panel_drag_start_x: create_rw_signal(0.0),
             panel_drag_start_width: left_panel_width_sig,
@@ -1338,21 +2021,43 @@ impl IdeState {
             inline_edit_query: create_rw_signal(String::new()),
             completion_filter_text: create_rw_signal(String::new()),
             vim_mode: vim_mode_sig,
+            emacs_mode: emacs_mode_sig,
+            emacs_prefix: create_rw_signal(false),
+            emacs_last_kill: create_rw_signal(false),
             font_size: font_size_signal,
+            font_family: font_family_signal,
+            font_ligatures: font_ligatures_signal,
+            line_height: line_height_signal,
+            terminal_font_family: terminal_font_family_signal,
+            terminal_font_size: terminal_font_size_signal,
+            chat_font_family: chat_font_family_signal,
+            chat_font_size: chat_font_size_signal,
             tab_size: tab_size_signal,
             goto_definition,
             hover_text,
             goto_line: goto_line_sig,
+            goto_line_end: create_rw_signal(0u32),
             comment_toggle_nonce: create_rw_signal(0u64),
             open_tabs: open_tabs_sig,
             initial_tabs,
             ai_provider: ai_provider_sig,
+            cloud_account: create_rw_signal({
+                let creds = phazeai_cloud::CloudCredentials::load();
+                creds
+                    .is_authenticated()
+                    .then(|| creds.email.unwrap_or_default())
+            }),
             ai_model: ai_model_sig,
             vim_normal_mode: create_rw_signal(false),
             vim_pending_key: create_rw_signal(None),
             vim_motion: create_rw_signal(None),
             ghost_text: create_rw_signal(None),
-            output_log: create_rw_signal(vec!["[PhazeAI] Output panel ready.".to_string()]),
+            output: output_sig,
+            output_channel: create_rw_signal(output::BUILD.to_string()),
+            build_run: build_run_sig,
+            build_running: build_running_sig,
+            run_config: create_rw_signal(None),
+            run_configs,
             references,
             references_visible: create_rw_signal(false),
             code_actions,
@@ -1363,8 +2068,25 @@ impl IdeState {
             sig_help,
             doc_symbols,
             status_toast: status_toast_sig,
+            notifications: notifications_sig,
+            notifications_open: create_rw_signal(false),
+            background_tasks: background_tasks_sig,
+            job_queue,
+            jobs: jobs_sig,
+            todos: todos_sig,
+            todos_scanning: todos_scanning_sig,
+            todos_rescan: todos_rescan_sig,
+            plan_tasks: create_rw_signal(Vec::new()),
+            extract_tasks_from: create_rw_signal(None),
+            extract_tasks_nonce: create_rw_signal(0u64),
+            open_conversation: create_rw_signal(None),
+            chat_images: create_rw_signal(Vec::new()),
+            http_send_request: create_rw_signal(None),
             zen_mode: zen_mode_sig,
             line_ending: line_ending_sig,
+            active_encoding: create_rw_signal(TextEncoding::default()),
+            file_format_cmd: create_rw_signal(None),
+            active_indent: create_rw_signal(None),
             ws_syms_open: create_rw_signal(false),
             ws_syms_query: create_rw_signal(String::new()),
             workspace_symbols,
@@ -1380,10 +2102,12 @@ impl IdeState {
             duplicate_line_nonce: create_rw_signal(0u64),
             delete_line_nonce: create_rw_signal(0u64),
             active_blame: create_rw_signal(String::new()),
+            git_history_file: create_rw_signal(None),
             split_editor: split_editor_sig,
             split_open_file: create_rw_signal(None),
             split_open_tabs: create_rw_signal(Vec::new()),
             split_active_cursor: create_rw_signal(None),
+            split_goto_line: create_rw_signal(0u32),
             col_cursor_up_nonce: create_rw_signal(0u64),
             col_cursor_down_nonce: create_rw_signal(0u64),
             sticky_lines: create_rw_signal(Vec::new()),
@@ -1413,21 +2137,43 @@ impl IdeState {
             goto_overlay_input: create_rw_signal(String::new()),
             bottom_panel_maximized: create_rw_signal(false),
             lsp_progress,
-            peek_def_lines,
-            peek_def_open: peek_def_open_sig,
+            peek: peek_sig,
             code_lens,
+            document_highlights,
             folding_ranges,
             organize_imports_on_save: organize_imports_signal,
+            normalize_line_endings_on_save: normalize_eol_signal,
+            rulers: rulers_signal,
+            line_length_lint: line_length_lint_signal,
+            explain_commands_on_hover: explain_commands_signal,
+            inline_blame: inline_blame_signal,
+            blame_gutter: blame_gutter_signal,
+            command_hover: crate::command_hover::CommandHover::new(
+                explain_commands_signal,
+                hover_text,
+            ),
+            syntax_theme: syntax_theme_signal,
+            syntax_theme_overrides: syntax_overrides_signal,
+            follow_system_theme: follow_system_theme_signal,
             run_in_terminal_text: create_rw_signal(None),
+            chat_run_request: create_rw_signal(None),
+            chat_run_output: create_rw_signal(None),
             transform_title_nonce: create_rw_signal(0u64),
             format_selection_nonce: create_rw_signal(0u64),
             save_no_format_nonce: create_rw_signal(0u64),
             fold_all_nonce: create_rw_signal(0u64),
             unfold_all_nonce: create_rw_signal(0u64),
+            fold_level: create_rw_signal(None),
+            comparison: create_rw_signal(None),
+            buffer_cmd: create_rw_signal(None),
             code_lens_visible: code_lens_visible_signal,
             inlay_hints_toggle: inlay_hints_toggle_signal,
             inlay_hints_sig: inlay_hints_lsp,
             sidecar_client: shared_client.clone(),
+            sidecar_supervisor,
+            sidecar_state: sidecar_state_sig,
+            sidecar_restart_nonce: sidecar_restart_nonce_sig,
+            sidecar_repair_nonce: sidecar_repair_nonce_sig,
             sidecar_ready: sidecar_ready_sig,
             sidecar_status: sidecar_status_sig,
             sidecar_building: sidecar_building_sig,
@@ -1435,6 +2181,8 @@ impl IdeState {
             sidecar_build_nonce: sidecar_build_nonce_sig,
             sidecar_search_nonce: sidecar_search_nonce_sig,
             sidecar_query: sidecar_query_sig,
+            find_similar_nonce: create_rw_signal(0u64),
+            similar_code_query: similar_code_query_sig,
             pending_chat_inject: create_rw_signal(None),
             ext_manager,
             ext_loading: create_rw_signal(false),
@@ -1444,42 +2192,314 @@ impl IdeState {
     }
 }
 
-// ── Command palette commands ──────────────────────────────────────────────────
+/// Walk the jump list back (or forward) and open that location.
+pub(crate) fn navigate_jump(state: &IdeState, forward: bool) {
+    let current = state
+        .active_cursor
+        .get_untracked()
+        .map(|(path, line, col)| Location::new(path, line, col));
+    let mut target = None;
+    state.jump_list.update(|j| {
+        target = if forward {
+            j.forward()
+        } else {
+            current.and_then(|c| j.back(c))
+        };
+    });
+    match target {
+        Some(location) => go_to_location(state, &location),
+        None if forward => show_toast(state.status_toast, "No later location"),
+        None => show_toast(state.status_toast, "No earlier location"),
+    }
+}
 
-#[derive(Clone)]
-struct PaletteCommand {
-    label: &'static str,
-    action: fn(IdeState),
+/// Open `location`'s file and move the cursor to its line.
+pub(crate) fn go_to_location(state: &IdeState, location: &Location) {
+    state.open_file.set(Some(location.path.clone()));
+    state.goto_line.set(location.line + 1);
 }
 
-fn all_commands() -> Vec<PaletteCommand> {
-    vec![
-        PaletteCommand {
-            label: "Open File…",
-            action: |s| {
-                if let Some(path) = rfd::FileDialog::new().pick_file() {
-                    s.open_file.set(Some(path));
-                    s.show_left_panel.set(true);
-                    s.left_panel_width.set(260.0);
-                }
-            },
-        },
-        PaletteCommand {
-            label: "Open Folder…",
-            action: |s| {
-                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
-                    s.workspace_root.set(folder);
-                    // Clear file picker cache so it re-walks on next open
-                    s.file_picker_files.set(Vec::new());
-                    s.show_left_panel.set(true);
-                    s.left_panel_width.set(300.0);
-                    s.left_panel_tab.set(crate::app::Tab::Explorer);
-                }
-            },
-        },
-        PaletteCommand {
-            label: "Toggle Terminal",
-            action: |s| {
+/// Look for code like the main editor's selection; the matches show in the
+/// Search panel under the semantic search bar.
+fn find_similar_code(state: IdeState) {
+    if !state.sidecar_ready.get_untracked() {
+        show_toast(
+            state.status_toast,
+            "Semantic search is not running; build the index from the Search panel",
+        );
+        return;
+    }
+    state.left_panel_tab.set(Tab::Search);
+    state.show_left_panel.set(true);
+    state.find_similar_nonce.update(|n| *n += 1);
+}
+
+/// Pull the action items in the main editor's selection into `plan.md`.
+fn extract_tasks_from_selection(state: IdeState) {
+    state.bottom_panel_tab.set(Tab::Tasks);
+    state.show_bottom_panel.set(true);
+    state.extract_tasks_nonce.update(|n| *n += 1);
+}
+
+/// Pick two files and compare them in the COMPARE tab.
+fn compare_files(state: IdeState) {
+    let root = state.workspace_root.get_untracked();
+    let Some(left) = rfd::FileDialog::new()
+        .set_title("Compare: first file")
+        .set_directory(&root)
+        .pick_file()
+    else {
+        return;
+    };
+    let Some(right) = rfd::FileDialog::new()
+        .set_title("Compare: second file")
+        .set_directory(&root)
+        .pick_file()
+    else {
+        return;
+    };
+    match Comparison::files(left, right) {
+        Some(comparison) => state.comparison.set(Some(comparison)),
+        None => show_toast(state.status_toast, "Couldn't read the files to compare"),
+    }
+}
+
+/// Pick from recently visited locations in a popup menu.
+fn show_recent_locations(state: IdeState) {
+    let recent = state.jump_list.get_untracked().recent(20);
+    if recent.is_empty() {
+        show_toast(state.status_toast, "No recent locations");
+        return;
+    }
+    let root = state.workspace_root.get_untracked();
+    let mut menu = Menu::new("");
+    for location in recent {
+        let name = location
+            .path
+            .strip_prefix(&root)
+            .unwrap_or(&location.path)
+            .display()
+            .to_string();
+        let s = state.clone();
+        menu = menu.entry(
+            MenuItem::new(format!("{name}:{}", location.line + 1))
+                .action(move || go_to_location(&s, &location)),
+        );
+    }
+    show_context_menu(menu, None);
+}
+
+/// Toggle a bookmark on the cursor line of the active editor.
+pub(crate) fn toggle_bookmark(state: &IdeState) {
+    let Some((path, line, _)) = state.active_cursor.get_untracked() else {
+        return;
+    };
+    let mut added = false;
+    state.bookmarks.update(|b| added = b.toggle(&path, line));
+    let msg = if added {
+        format!("Bookmark added on line {}", line + 1)
+    } else {
+        format!("Bookmark removed from line {}", line + 1)
+    };
+    show_toast(state.status_toast, msg);
+}
+
+/// Send the request under the cursor of a `.http` / `.rest` file from the
+/// HTTP tab.
+pub(crate) fn send_http_request_at_cursor(state: IdeState) {
+    match state.active_cursor.get_untracked() {
+        Some((path, line, _)) if phazeai_core::rest::is_http_file(&path) => {
+            state.http_send_request.set(Some((path, line)));
+            state.bottom_panel_tab.set(Tab::Http);
+            state.show_bottom_panel.set(true);
+        }
+        _ => show_toast(
+            state.status_toast,
+            "Put the cursor in a .http or .rest file",
+        ),
+    }
+}
+
+/// Commented-out example written to a new `launch.toml`.
+const RUN_CONFIG_TEMPLATE: &str = "\
+# Run configurations, started from the top bar or with `phazeai run-config <name>`.
+#
+# [[configurations]]
+# name = \"server\"
+# command = \"cargo\"
+# args = [\"run\", \"--bin\", \"server\"]
+# cwd = \"crates/server\"          # relative to the workspace root
+# env = { RUST_LOG = \"debug\" }   # on top of the active .env profile
+# pre_task = \"migrate\"           # another configuration that must succeed first
+";
+
+/// Open the workspace's `launch.toml`, creating it from a template first.
+pub(crate) fn edit_run_configs(state: IdeState) {
+    let path = state.workspace_root.get_untracked().join(RUN_CONFIG_FILE);
+    if !path.exists() {
+        let created = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, RUN_CONFIG_TEMPLATE));
+        if let Err(e) = created {
+            show_toast(
+                state.status_toast,
+                format!("Couldn't create {RUN_CONFIG_FILE}: {e}"),
+            );
+            return;
+        }
+    }
+    state.open_file.set(Some(path));
+}
+
+/// Run the configuration picked in the top bar, or the first one there is.
+pub(crate) fn run_selected_config(state: IdeState) {
+    let name = state.run_config.get_untracked().or_else(|| {
+        let root = state.workspace_root.get_untracked();
+        RunConfigs::load(&root)
+            .ok()?
+            .configurations
+            .first()
+            .map(|c| c.name.clone())
+    });
+    let Some(name) = name else {
+        show_toast(state.status_toast, "Add a run configuration first");
+        return edit_run_configs(state);
+    };
+    state.run_config.set(Some(name.clone()));
+    show_output_channel(&state, output::RUN);
+    state.run_configs.request.set(Some(name));
+}
+
+/// Run the last run configuration again, stopping it first if it is still
+/// running (Ctrl+F5).
+pub(crate) fn rerun_last_config(state: IdeState) {
+    match state.run_configs.last.get_untracked() {
+        Some(name) => {
+            show_output_channel(&state, output::RUN);
+            state.run_configs.request.set(Some(name));
+        }
+        None => run_selected_config(state),
+    }
+}
+
+/// Move to the next (or previous) bookmark, continuing across files and
+/// wrapping around at either end.
+pub(crate) fn navigate_bookmark(state: &IdeState, forward: bool) {
+    let (path, line) = match state.active_cursor.get_untracked() {
+        Some((path, line, _)) => (path, line),
+        None => (state.open_file.get_untracked().unwrap_or_default(), 0),
+    };
+    let marks = state.bookmarks.get_untracked();
+    let target = if forward {
+        marks.next(&path, line)
+    } else {
+        marks.previous(&path, line)
+    };
+    match target {
+        Some(b) => go_to_location(state, &Location::new(b.path.clone(), b.line, 0)),
+        None => show_toast(state.status_toast, "No bookmarks"),
+    }
+}
+
+/// Set (or clear, with `None`) the syntax theme override for the current UI theme.
+pub(crate) fn set_syntax_theme(state: &IdeState, name: Option<&str>) {
+    let ui_theme = state.theme.get_untracked().variant.name().to_string();
+    state.syntax_theme_overrides.update(|m| match name {
+        Some(n) => {
+            m.insert(ui_theme, n.to_string());
+        }
+        None => {
+            m.remove(&ui_theme);
+        }
+    });
+}
+
+/// Write or update ARCHITECTURE.md in the background, then open it. Only
+/// sections whose files changed since the last run are rewritten.
+pub(crate) fn generate_architecture_summary(state: &IdeState) {
+    use floem::ext_event::create_ext_action;
+    use floem::reactive::Scope;
+    use phazeai_core::analysis::onboard;
+
+    show_toast(state.status_toast, "Writing architecture summary…");
+    let root = state.workspace_root.get_untracked();
+    let toast = state.status_toast;
+    let open_file = state.open_file;
+    let on_done = create_ext_action(
+        Scope::current(),
+        move |result: Result<onboard::OnboardReport, String>| match result {
+            Ok(report) => {
+                let message = if report.regenerated.is_empty() {
+                    "Architecture summary is up to date".to_string()
+                } else {
+                    format!(
+                        "Architecture summary: updated {} of {} sections",
+                        report.regenerated.len(),
+                        onboard::Section::ALL.len()
+                    )
+                };
+                open_file.set(Some(report.path));
+                show_toast(toast, message);
+            }
+            Err(e) => show_toast(toast, format!("Architecture summary failed: {e}")),
+        },
+    );
+    std::thread::spawn(move || {
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())
+            .and_then(|rt| {
+                rt.block_on(async {
+                    let llm = Settings::load()
+                        .build_llm_client()
+                        .map_err(|e| e.to_string())?;
+                    onboard::onboard(llm.as_ref(), &root, false)
+                        .await
+                        .map_err(|e| e.to_string())
+                })
+            });
+        on_done(result);
+    });
+}
+
+// ── Command palette commands ──────────────────────────────────────────────────
+
+#[derive(Clone)]
+pub(crate) struct PaletteCommand {
+    pub(crate) label: &'static str,
+    pub(crate) action: fn(IdeState),
+}
+
+pub(crate) fn all_commands() -> Vec<PaletteCommand> {
+    vec![
+        PaletteCommand {
+            label: "Open File…",
+            action: |s| {
+                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    s.open_file.set(Some(path));
+                    s.show_left_panel.set(true);
+                    s.left_panel_width.set(260.0);
+                }
+            },
+        },
+        PaletteCommand {
+            label: "Open Folder…",
+            action: |s| {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    s.workspace_root.set(folder);
+                    // Drop the old workspace's files until the new one is indexed
+                    s.file_picker_files.set(Vec::new());
+                    s.show_left_panel.set(true);
+                    s.left_panel_width.set(300.0);
+                    s.left_panel_tab.set(crate::app::Tab::Explorer);
+                }
+            },
+        },
+        PaletteCommand {
+            label: "Toggle Terminal",
+            action: |s| {
                 s.show_bottom_panel.update(|v| *v = !*v);
             },
         },
@@ -1497,7 +2517,7 @@ fn all_commands() -> Vec<PaletteCommand> {
                 s.show_right_panel.update(|v| *v = !*v);
             },
         },
-        // ── All 12 themes ────────────────────────────────────────────────────
+        // ── All 13 themes ────────────────────────────────────────────────────
         PaletteCommand {
             label: "Theme: Midnight Blue",
             action: |s| {
@@ -1578,6 +2598,64 @@ fn all_commands() -> Vec<PaletteCommand> {
                 s.theme.set(PhazeTheme::from_variant(ThemeVariant::Light));
             },
         },
+        PaletteCommand {
+            label: "Theme: High Contrast",
+            action: |s| {
+                s.theme
+                    .set(PhazeTheme::from_variant(ThemeVariant::HighContrast));
+            },
+        },
+        PaletteCommand {
+            label: "Theme: High Contrast Light",
+            action: |s| {
+                s.theme
+                    .set(PhazeTheme::from_variant(ThemeVariant::HighContrastLight));
+            },
+        },
+        PaletteCommand {
+            label: "Theme: Toggle Follow System Appearance",
+            action: |s| {
+                s.follow_system_theme.update(|v| *v = !*v);
+                let msg = if s.follow_system_theme.get() {
+                    "Following system light/dark theme"
+                } else {
+                    "Stopped following system theme"
+                };
+                show_toast(s.status_toast, msg);
+            },
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Ocean Dark",
+            action: |s| set_syntax_theme(&s, Some("base16-ocean.dark")),
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Eighties Dark",
+            action: |s| set_syntax_theme(&s, Some("base16-eighties.dark")),
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Mocha Dark",
+            action: |s| set_syntax_theme(&s, Some("base16-mocha.dark")),
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Ocean Light",
+            action: |s| set_syntax_theme(&s, Some("base16-ocean.light")),
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Inspired GitHub",
+            action: |s| set_syntax_theme(&s, Some("InspiredGitHub")),
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Solarized Dark",
+            action: |s| set_syntax_theme(&s, Some("Solarized (dark)")),
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Solarized Light",
+            action: |s| set_syntax_theme(&s, Some("Solarized (light)")),
+        },
+        PaletteCommand {
+            label: "Syntax Theme: Reset to Default",
+            action: |s| set_syntax_theme(&s, None),
+        },
         PaletteCommand {
             label: "Transform: To Uppercase",
             action: |s| s.transform_upper_nonce.update(|v| *v += 1),
@@ -1615,10 +2693,83 @@ fn all_commands() -> Vec<PaletteCommand> {
                 s.goto_overlay_input.set(String::new());
             },
         },
+        PaletteCommand {
+            label: "Go Back",
+            action: |s| navigate_jump(&s, false),
+        },
+        PaletteCommand {
+            label: "Go Forward",
+            action: |s| navigate_jump(&s, true),
+        },
+        PaletteCommand {
+            label: "Go to Recently Visited Location…",
+            action: show_recent_locations,
+        },
+        PaletteCommand {
+            label: "Bookmarks: Toggle Bookmark",
+            action: |s| toggle_bookmark(&s),
+        },
+        PaletteCommand {
+            label: "Bookmarks: Next Bookmark",
+            action: |s| navigate_bookmark(&s, true),
+        },
+        PaletteCommand {
+            label: "Bookmarks: Previous Bookmark",
+            action: |s| navigate_bookmark(&s, false),
+        },
+        PaletteCommand {
+            label: "Bookmarks: Show Bookmarks",
+            action: |s| {
+                s.left_panel_tab.set(Tab::Bookmarks);
+                s.show_left_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Bookmarks: Clear All Bookmarks",
+            action: |s| s.bookmarks.set(Bookmarks::new()),
+        },
         PaletteCommand {
             label: "Toggle Organize Imports on Save",
             action: |s| s.organize_imports_on_save.update(|v| *v = !*v),
         },
+        PaletteCommand {
+            label: "Toggle Normalize Line Endings on Save",
+            action: |s| s.normalize_line_endings_on_save.update(|v| *v = !*v),
+        },
+        PaletteCommand {
+            label: "Toggle Max Line Length Check",
+            action: |s| s.line_length_lint.update(|v| *v = !*v),
+        },
+        PaletteCommand {
+            label: "Change End of Line Sequence: LF",
+            action: |s| {
+                s.file_format_cmd
+                    .set(Some(FileFormatCommand::LineEndings(LineEnding::Lf)))
+            },
+        },
+        PaletteCommand {
+            label: "Change End of Line Sequence: CRLF",
+            action: |s| {
+                s.file_format_cmd
+                    .set(Some(FileFormatCommand::LineEndings(LineEnding::Crlf)))
+            },
+        },
+        PaletteCommand {
+            label: "Reopen or Save with Encoding…",
+            action: |s| show_file_format_menu(&s),
+        },
+        PaletteCommand {
+            label: "Compare Active File with Saved",
+            action: |s| s.buffer_cmd.set(Some(BufferCommand::CompareWithSaved)),
+        },
+        PaletteCommand {
+            label: "Compare Active File with Clipboard",
+            action: |s| s.buffer_cmd.set(Some(BufferCommand::CompareWithClipboard)),
+        },
+        PaletteCommand {
+            label: "Compare Files…",
+            action: compare_files,
+        },
         PaletteCommand {
             label: "Transform: To Title Case",
             action: |s| s.transform_title_nonce.update(|v| *v += 1),
@@ -1627,6 +2778,10 @@ fn all_commands() -> Vec<PaletteCommand> {
             label: "Format Selection",
             action: |s| s.format_selection_nonce.update(|v| *v += 1),
         },
+        PaletteCommand {
+            label: "Find Similar Code",
+            action: find_similar_code,
+        },
         PaletteCommand {
             label: "Save Without Formatting",
             action: |s| s.save_no_format_nonce.update(|v| *v += 1),
@@ -1639,59 +2794,359 @@ fn all_commands() -> Vec<PaletteCommand> {
             label: "Unfold All",
             action: |s| s.unfold_all_nonce.update(|v| *v += 1),
         },
+        PaletteCommand {
+            label: "Fold Level 1",
+            action: |s| s.fold_level.set(Some(1)),
+        },
+        PaletteCommand {
+            label: "Fold Level 2",
+            action: |s| s.fold_level.set(Some(2)),
+        },
+        PaletteCommand {
+            label: "Fold Level 3",
+            action: |s| s.fold_level.set(Some(3)),
+        },
+        PaletteCommand {
+            label: "Fold Level 4",
+            action: |s| s.fold_level.set(Some(4)),
+        },
+        PaletteCommand {
+            label: "Fold Level 5",
+            action: |s| s.fold_level.set(Some(5)),
+        },
+        PaletteCommand {
+            label: "Fold Level 6",
+            action: |s| s.fold_level.set(Some(6)),
+        },
+        PaletteCommand {
+            label: "Fold Level 7",
+            action: |s| s.fold_level.set(Some(7)),
+        },
         PaletteCommand {
             label: "Toggle Code Lens",
             action: |s| s.code_lens_visible.update(|v| *v = !*v),
         },
+        PaletteCommand {
+            label: "Toggle Explain Commands on Hover",
+            action: |s| s.explain_commands_on_hover.update(|v| *v = !*v),
+        },
+        PaletteCommand {
+            label: "Git: Show History",
+            action: |s| {
+                s.git_history_file.set(None);
+                s.bottom_panel_tab.set(Tab::GitHistory);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Git: Show File History",
+            action: |s| {
+                if let Some(file) = s.open_file.get_untracked() {
+                    s.git_history_file.set(Some(file));
+                    s.bottom_panel_tab.set(Tab::GitHistory);
+                    s.show_bottom_panel.set(true);
+                }
+            },
+        },
+        PaletteCommand {
+            label: "Agent: Background Jobs",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Jobs);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show TODO Comments",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Todos);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show Tasks (plan.md)",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Tasks);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show Task Board",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Board);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        #[cfg(feature = "webview")]
+        PaletteCommand {
+            label: "Show Web Preview",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Preview);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show HTTP Client",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Http);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Send HTTP Request at Cursor",
+            action: send_http_request_at_cursor,
+        },
+        PaletteCommand {
+            label: "Show Database Explorer",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Database);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Run Configuration",
+            action: run_selected_config,
+        },
+        PaletteCommand {
+            label: "Re-run Last Run Configuration",
+            action: rerun_last_config,
+        },
+        PaletteCommand {
+            label: "Stop Run Configuration",
+            action: |s| s.run_configs.stop.update(|n| *n += 1),
+        },
+        PaletteCommand {
+            label: "Edit Run Configurations",
+            action: edit_run_configs,
+        },
+        PaletteCommand {
+            label: "Show Environment Variables",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Env);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show Memory Usage",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Memory);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Extract Tasks from Selection",
+            action: extract_tasks_from_selection,
+        },
+        PaletteCommand {
+            label: "Generate Architecture Summary",
+            action: |s| generate_architecture_summary(&s),
+        },
+        PaletteCommand {
+            label: "Run Workspace Analysis",
+            action: |s| {
+                let _ = s.lsp_cmd.send(LspCommand::AnalyzeWorkspace);
+                s.bottom_panel_tab.set(Tab::Problems);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Run Security Scan",
+            action: |s| {
+                let _ = s.lsp_cmd.send(LspCommand::ScanSecurity);
+                s.bottom_panel_tab.set(Tab::Problems);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show Module Graph",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::ModuleGraph);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show Code Metrics",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Metrics);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show Dependency Audit",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Audit);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show CPU Profile",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Profile);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Show Benchmarks",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Bench);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Run Build",
+            action: |s| {
+                show_output_channel(&s, output::BUILD);
+                s.build_run.update(|n| *n += 1);
+            },
+        },
+        PaletteCommand {
+            label: "Output: Show LSP Log",
+            action: |s| show_output_channel(&s, output::LSP),
+        },
+        PaletteCommand {
+            label: "LSP: Show Language Servers",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::LanguageServers);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Sidecar: Restart",
+            action: |s| s.sidecar_restart_nonce.update(|n| *n += 1),
+        },
+        PaletteCommand {
+            label: "Update PhazeAI",
+            action: |s| crate::update::download_and_stage(s.status_toast),
+        },
+        PaletteCommand {
+            label: "Sidecar: Repair Environment",
+            action: |s| {
+                s.sidecar_repair_nonce.update(|n| *n += 1);
+                show_output_channel(&s, output::SIDECAR);
+            },
+        },
+        PaletteCommand {
+            label: "LSP: Restart Language Servers",
+            action: |s| {
+                let _ = s.lsp_cmd.send(LspCommand::RestartServers);
+            },
+        },
+        PaletteCommand {
+            label: "Output: Show Agent Log",
+            action: |s| show_output_channel(&s, output::AGENT),
+        },
+        PaletteCommand {
+            label: "Notifications: Show History",
+            action: |s| {
+                if !s.notifications_open.get_untracked() {
+                    toggle_notifications(&s);
+                }
+            },
+        },
+        PaletteCommand {
+            label: "Notifications: Clear All",
+            action: |s| s.notifications.update(|c| c.clear()),
+        },
+        PaletteCommand {
+            label: "Git: Interactive Rebase",
+            action: |s| {
+                s.bottom_panel_tab.set(Tab::Rebase);
+                s.show_bottom_panel.set(true);
+            },
+        },
+        PaletteCommand {
+            label: "Toggle Inline Blame",
+            action: |s| s.inline_blame.update(|v| *v = !*v),
+        },
+        PaletteCommand {
+            label: "Toggle Blame Gutter",
+            action: |s| s.blame_gutter.update(|v| *v = !*v),
+        },
     ]
 }
 
 // ── File picker overlay (Ctrl+P) ──────────────────────────────────────────────
 
-fn file_picker(state: IdeState) -> impl IntoView {
-    let query = state.file_picker_query;
-    let all_files = state.file_picker_files;
-    let hovered: RwSignal<Option<usize>> = create_rw_signal(None);
+/// How often the file index takes in file watcher events.
+const FILE_INDEX_POLL: Duration = Duration::from_millis(200);
+
+/// Keep `files` listing the workspace for the file picker: index it with the
+/// core [`FileIndex`] when the root changes, then keep the index current from
+/// [`FileWatcher`] events until the root changes again. `unindexed` gets the
+/// folders evicted to stay within the memory budget.
+fn start_file_index(
+    files: RwSignal<Vec<PathBuf>>,
+    unindexed: RwSignal<Vec<PathBuf>>,
+    workspace_root: RwSignal<PathBuf>,
+) {
+    type Snapshot = (u64, Vec<PathBuf>, Vec<PathBuf>);
+    let (tx, rx) = std::sync::mpsc::sync_channel::<Snapshot>(2);
+    let snapshots = floem::ext_event::create_signal_from_channel(rx);
+    let generation = Arc::new(AtomicU64::new(0));
+
+    {
+        let generation = generation.clone();
+        create_effect(move |_| {
+            let Some((gen, list, evicted)) = snapshots.get() else {
+                return;
+            };
+            // The workspace changed; its index will follow.
+            if gen == generation.load(Ordering::Relaxed) {
+                files.set(list);
+                unindexed.set(evicted);
+            }
+        });
+    }
 
-    // When picker opens, walk workspace asynchronously (re-walk when root changes)
-    let last_root: RwSignal<Option<std::path::PathBuf>> = create_rw_signal(None);
-    let (files_tx, files_rx) = std::sync::mpsc::sync_channel::<Vec<std::path::PathBuf>>(1);
-    let files_sig = floem::ext_event::create_signal_from_channel(files_rx);
-    create_effect(move |_| {
-        if let Some(files) = files_sig.get() {
-            all_files.set(files);
-        }
-    });
     create_effect(move |_| {
-        if !state.file_picker_open.get() {
-            return;
-        }
-        let root = state.workspace_root.get();
-        if last_root.get().as_ref() == Some(&root) {
-            return;
-        }
-        last_root.set(Some(root.clone()));
-        let tx = files_tx.clone();
+        let root = workspace_root.get();
+        unindexed.set(Vec::new());
+        let gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let generation = generation.clone();
+        let tx = tx.clone();
         std::thread::spawn(move || {
-            let files: Vec<std::path::PathBuf> = walkdir::WalkDir::new(&root)
-                .max_depth(10)
-                .into_iter()
-                .flatten()
-                .filter(|e| e.file_type().is_file())
-                .filter(|e| {
-                    let p = e.path().to_string_lossy();
-                    !p.contains("/target/")
-                        && !p.contains("/.git/")
-                        && !p.contains("/node_modules/")
-                        && !p.contains("/.cache/")
-                })
-                .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
-                .map(|e| e.into_path())
-                .take(2000)
-                .collect();
-            let _ = tx.send(files);
+            // Watch before walking so files created meanwhile aren't missed.
+            let watcher = FileWatcher::shared(&root).ok();
+            let events = watcher.as_ref().and_then(|w| w.subscribe(&[]).ok());
+            let task = progress::global()
+                .task("Indexing files")
+                .unit("files")
+                .cancellable()
+                .start();
+            let mut index = FileIndex::build(&root, Some(&task));
+            drop(task);
+            let snapshot = |index: &mut FileIndex| {
+                // The index and the picker's copy of it.
+                let ledger = memory::global();
+                let evicted = index.evict_to(ledger.budget(Subsystem::FileIndex) / 2);
+                ledger.evicted(Subsystem::FileIndex, evicted);
+                ledger.report(Subsystem::FileIndex, 2 * index.bytes(), index.len());
+                let (files, evicted) = (index.files(), index.evicted());
+                (gen, files.cloned().collect(), evicted.cloned().collect())
+            };
+            if tx.send(snapshot(&mut index)).is_err() {
+                return;
+            }
+            let Some(mut events) = events else {
+                return;
+            };
+            while generation.load(Ordering::Relaxed) == gen {
+                std::thread::sleep(FILE_INDEX_POLL);
+                let mut changed = false;
+                while let Ok(event) = events.try_recv() {
+                    changed |= index.apply(&event);
+                }
+                if changed && tx.send(snapshot(&mut index)).is_err() {
+                    return;
+                }
+            }
         });
     });
+}
+
+fn file_picker(state: IdeState) -> impl IntoView {
+    let query = state.file_picker_query;
+    let all_files = state.file_picker_files;
+    let hovered: RwSignal<Option<usize>> = create_rw_signal(None);
 
     let filtered = move || -> Vec<(usize, std::path::PathBuf)> {
         let q = query.get().to_lowercase();
@@ -1713,6 +3168,13 @@ fn file_picker(state: IdeState) -> impl IntoView {
             .collect()
     };
 
+    // Keyboard selection (↑/↓ + Enter) — reset whenever the query changes.
+    let selected: RwSignal<usize> = create_rw_signal(0);
+    create_effect(move |_| {
+        let _ = query.get();
+        selected.set(0);
+    });
+
     let search_box = text_input(query).style(move |s| {
         let t = state.theme.get();
         let p = &t.palette;
@@ -1725,7 +3187,17 @@ fn file_picker(state: IdeState) -> impl IntoView {
             .border_color(p.border_focus)
             .border_radius(6.0)
             .margin_bottom(8.0)
+            .apply(focus_ring(p))
     });
+    {
+        let input_id = search_box.id();
+        let open = state.file_picker_open;
+        create_effect(move |_| {
+            if open.get() {
+                input_id.request_focus();
+            }
+        });
+    }
 
     let items_view = scroll(
         dyn_stack(filtered, |(idx, _)| *idx, {
@@ -1741,7 +3213,7 @@ fn file_picker(state: IdeState) -> impl IntoView {
                 let display2 = display.clone();
                 let hov = hovered;
                 let state = state.clone();
-                container(
+                let row = container(
                     stack((
                         label(move || {
                             path_clone
@@ -1776,7 +3248,7 @@ fn file_picker(state: IdeState) -> impl IntoView {
                             .padding_horiz(12.0)
                             .padding_vert(7.0)
                             .border_radius(4.0)
-                            .background(if hov.get() == Some(idx) {
+                            .background(if hov.get() == Some(idx) || selected.get() == idx {
                                 p.bg_elevated
                             } else {
                                 floem::peniko::Color::TRANSPARENT
@@ -1798,7 +3270,14 @@ fn file_picker(state: IdeState) -> impl IntoView {
                 })
                 .on_event_stop(EventListener::PointerLeave, move |_| {
                     hov.set(None);
-                })
+                });
+                let row_id = row.id();
+                create_effect(move |_| {
+                    if selected.get() == idx {
+                        row_id.scroll_to(None);
+                    }
+                });
+                row
             }
         })
         .style(|s| s.flex_col().width_full()),
@@ -1818,7 +3297,39 @@ fn file_picker(state: IdeState) -> impl IntoView {
             .apply_if(!empty, |s| s.display(floem::style::Display::None))
     });
 
-    let picker_box = stack((search_box, empty_hint, items_view))
+    // Folders evicted from the index aren't listed until something in them
+    // changes; say so rather than let their files silently go missing.
+    let unindexed = state.file_picker_unindexed;
+    let unindexed_hint = label(move || {
+        let root = state.workspace_root.get();
+        let folders = unindexed.get();
+        let names: Vec<String> = folders
+            .iter()
+            .take(3)
+            .map(|f| f.strip_prefix(&root).unwrap_or(f).display().to_string())
+            .collect();
+        let more = if folders.len() > names.len() {
+            ", …"
+        } else {
+            ""
+        };
+        format!(
+            "{} folders not indexed (memory budget): {}{more}",
+            folders.len(),
+            names.join(", ")
+        )
+    })
+    .style(move |s| {
+        s.font_size(11.0)
+            .padding_horiz(12.0)
+            .margin_bottom(6.0)
+            .color(state.theme.get().palette.text_muted)
+            .apply_if(unindexed.get().is_empty(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let picker_box = stack((search_box, empty_hint, unindexed_hint, items_view))
         .style({
             let state = state.clone();
             move |s| {
@@ -1841,11 +3352,27 @@ fn file_picker(state: IdeState) -> impl IntoView {
         .on_event_stop(EventListener::KeyDown, {
             let state = state.clone();
             move |event| {
-                if let Event::KeyDown(e) = event {
-                    if e.key.logical_key == Key::Named(NamedKey::Escape) {
+                let Event::KeyDown(e) = event else {
+                    return;
+                };
+                let Key::Named(ref named) = e.key.logical_key else {
+                    return;
+                };
+                let rows = filtered();
+                match list_nav_key(named, selected.get_untracked(), rows.len()) {
+                    ListNav::Close => {
                         state.file_picker_open.set(false);
                         state.file_picker_query.set(String::new());
                     }
+                    ListNav::Move(i) => selected.set(i),
+                    ListNav::Accept(i) => {
+                        if let Some((_, path)) = rows.into_iter().nth(i) {
+                            state.open_file.set(Some(path));
+                            state.file_picker_open.set(false);
+                            state.file_picker_query.set(String::new());
+                        }
+                    }
+                    ListNav::None => {}
                 }
             }
         });
@@ -1880,18 +3407,29 @@ fn command_palette(state: IdeState) -> impl IntoView {
     let query = state.command_palette_query;
 
     // Build a filtered list of matching commands driven by the query signal.
+    // Items are `(position in filtered list, label, action)`.
     #[allow(clippy::type_complexity)]
     let commands_list = move || -> Vec<(usize, &'static str, fn(IdeState))> {
         let q = query.get().to_lowercase();
         all_commands()
             .into_iter()
+            .filter(|cmd| {
+                q.is_empty()
+                    || cmd.label.to_lowercase().contains(&q)
+                    || tr(cmd.label).to_lowercase().contains(&q)
+            })
             .enumerate()
-            .filter(|(_, cmd)| q.is_empty() || cmd.label.to_lowercase().contains(&q))
-            .map(|(idx, cmd)| (idx, cmd.label, cmd.action))
+            .map(|(pos, cmd)| (pos, cmd.label, cmd.action))
             .collect()
     };
 
     let row_hovered: RwSignal<Option<usize>> = create_rw_signal(None);
+    // Keyboard selection (↑/↓ + Enter) — reset whenever the query changes.
+    let selected: RwSignal<usize> = create_rw_signal(0);
+    create_effect(move |_| {
+        let _ = query.get();
+        selected.set(0);
+    });
 
     let search_box = text_input(query).style(move |s| {
         let t = state.theme.get();
@@ -1905,7 +3443,18 @@ fn command_palette(state: IdeState) -> impl IntoView {
             .border_color(p.border_focus)
             .border_radius(6.0)
             .margin_bottom(8.0)
+            .apply(focus_ring(p))
     });
+    // Focus the query box whenever the palette opens so it is usable without a mouse.
+    {
+        let input_id = search_box.id();
+        let open = state.command_palette_open;
+        create_effect(move |_| {
+            if open.get() {
+                input_id.request_focus();
+            }
+        });
+    }
 
     let items_view = scroll(
         dyn_stack(commands_list, |(idx, lbl, _action)| (*idx, *lbl), {
@@ -1913,7 +3462,7 @@ fn command_palette(state: IdeState) -> impl IntoView {
             move |(idx, cmd_label, cmd_action)| {
                 let hovered = row_hovered;
                 let state = state.clone();
-                container(label(move || cmd_label).style({
+                let row = container(label(move || tr(cmd_label)).style({
                     let state = state.clone();
                     move |s| {
                         s.font_size(13.0)
@@ -1925,7 +3474,7 @@ fn command_palette(state: IdeState) -> impl IntoView {
                     move |s| {
                         let t = state.theme.get();
                         let p = &t.palette;
-                        let is_hov = hovered.get() == Some(idx);
+                        let is_hov = hovered.get() == Some(idx) || selected.get() == idx;
                         s.width_full()
                             .padding_horiz(12.0)
                             .padding_vert(8.0)
@@ -1951,7 +3500,15 @@ fn command_palette(state: IdeState) -> impl IntoView {
                 })
                 .on_event_stop(EventListener::PointerLeave, move |_| {
                     hovered.set(None);
-                })
+                });
+                // Keep the keyboard-selected row scrolled into view.
+                let row_id = row.id();
+                create_effect(move |_| {
+                    if selected.get() == idx {
+                        row_id.scroll_to(None);
+                    }
+                });
+                row
             }
         })
         .style(|s| s.flex_col().width_full()),
@@ -1978,15 +3535,31 @@ fn command_palette(state: IdeState) -> impl IntoView {
                     .box_shadow_spread(0.0)
             }
         })
-        // Escape key closes the palette when it has focus
+        // Escape closes the palette; ↑/↓ move the selection; Enter runs it.
         .on_event_stop(EventListener::KeyDown, {
             let state = state.clone();
             move |event| {
-                if let Event::KeyDown(e) = event {
-                    if e.key.logical_key == Key::Named(floem::keyboard::NamedKey::Escape) {
+                let Event::KeyDown(e) = event else {
+                    return;
+                };
+                let Key::Named(ref named) = e.key.logical_key else {
+                    return;
+                };
+                let count = commands_list().len();
+                match list_nav_key(named, selected.get_untracked(), count) {
+                    ListNav::Close => {
                         state.command_palette_open.set(false);
                         state.command_palette_query.set(String::new());
                     }
+                    ListNav::Move(i) => selected.set(i),
+                    ListNav::Accept(i) => {
+                        if let Some((_, _, action)) = commands_list().into_iter().nth(i) {
+                            state.command_palette_open.set(false);
+                            state.command_palette_query.set(String::new());
+                            action(state.clone());
+                        }
+                    }
+                    ListNav::None => {}
                 }
             }
         });
@@ -2123,6 +3696,7 @@ fn activity_bar(state: IdeState) -> impl IntoView {
         activity_bar_btn(icons::SEARCH, Tab::Search, state.clone()),
         activity_bar_btn(icons::SOURCE_CONTROL, Tab::Git, state.clone()),
         activity_bar_btn(icons::LIST_CHECKS, Tab::Symbols, state.clone()),
+        activity_bar_btn(icons::BOOKMARK, Tab::Bookmarks, state.clone()),
         activity_bar_btn(icons::COMPOSE, Tab::Composer, state.clone()),
         activity_bar_btn(icons::DEBUG, Tab::Debug, state.clone()),
         activity_bar_btn(icons::REMOTE, Tab::Remote, state.clone()),
@@ -2232,6 +3806,8 @@ fn left_panel(state: IdeState) -> impl IntoView {
         state.open_file,
         state.theme,
         state.open_tabs,
+        state.diagnostic_counts,
+        state.comparison,
     );
 
     let explorer_wrap = container(explorer).style({
@@ -2364,6 +3940,17 @@ fn left_panel(state: IdeState) -> impl IntoView {
         }
     });
 
+    let bookmarks_wrap = container(bookmarks_panel(state.clone())).style({
+        let state = state.clone();
+        move |s| {
+            s.width_full()
+                .height_full()
+                .apply_if(state.left_panel_tab.get() != Tab::Bookmarks, |s| {
+                    s.display(floem::style::Display::None)
+                })
+        }
+    });
+
     let composer_wrap = container(crate::panels::composer::composer_panel(state.clone())).style({
         let state = state.clone();
         move |s| {
@@ -2386,12 +3973,7 @@ fn left_panel(state: IdeState) -> impl IntoView {
         }
     });
 
-    let account_wrap = container(coming_soon_panel(
-        "Account",
-        "Sign in to sync settings, manage PhazeAI Cloud features, and collaborate with your team.",
-        state.theme,
-    ))
-    .style({
+    let account_wrap = container(crate::panels::account::account_panel(state.clone())).style({
         let state = state.clone();
         move |s| {
             s.width_full()
@@ -2408,6 +3990,7 @@ fn left_panel(state: IdeState) -> impl IntoView {
             search_wrap,
             git_wrap,
             symbols_wrap,
+            bookmarks_wrap,
             debug_wrap,
             extensions_wrap,
             remote_wrap,
@@ -2515,38 +4098,69 @@ where
         })
 }
 
+/// The active file's line endings, and the encodings to reopen or save it in.
+fn show_file_format_menu(state: &IdeState) {
+    let cmd = state.file_format_cmd;
+    let line_ending = state.line_ending.get_untracked();
+    let current = state.active_encoding.get_untracked();
+    let mark = |on: bool| if on { "✓ " } else { "   " };
+    let mut menu = Menu::new("");
+    for ending in [LineEnding::Lf, LineEnding::Crlf] {
+        menu = menu.entry(
+            MenuItem::new(format!(
+                "{}{}",
+                mark(ending.label() == line_ending),
+                ending.label()
+            ))
+            .action(move || cmd.set(Some(FileFormatCommand::LineEndings(ending)))),
+        );
+    }
+    menu = menu.separator();
+    for encoding in TextEncoding::ALL {
+        menu = menu.entry(
+            MenuItem::new(format!(
+                "{}Reopen with {encoding}",
+                mark(encoding == current)
+            ))
+            .action(move || cmd.set(Some(FileFormatCommand::Reopen(encoding)))),
+        );
+    }
+    menu = menu.separator();
+    for encoding in TextEncoding::ALL {
+        menu = menu.entry(
+            MenuItem::new(format!("   Save with {encoding}"))
+                .action(move || cmd.set(Some(FileFormatCommand::SaveAs(encoding)))),
+        );
+    }
+    show_context_menu(menu, None);
+}
+
 fn status_bar(state: IdeState) -> impl IntoView {
-    // Cloud sign-in indicator (left-most element)
-    // let cloud_btn = container(label(|| "☁ Sign in"))
-    //     .style(move |s| {
-    //         let p = state.theme.get().palette;
-    //         s.font_size(10.0)
-    //             .padding_horiz(8.0)
-    //             .padding_vert(2.0)
-    //             .margin_right(8.0)
-    //             .border_radius(3.0)
-    //             .cursor(floem::style::CursorStyle::Pointer)
-    //             .color(p.accent)
-    //             .background(p.accent_dim)
-    //     })
-    //     .on_click_stop(|_| {
-    //         // Open PhazeAI cloud sign-in in the system browser.
-    //         let url = phazeai_cloud::auth::login_url();
-    //         let opener = if cfg!(target_os = "macos") {
-    //             "open"
-    //         } else if cfg!(target_os = "windows") {
-    //             "cmd"
-    //         } else {
-    //             "xdg-open"
-    //         };
-    //         let mut cmd = std::process::Command::new(opener);
-    //         if cfg!(target_os = "windows") {
-    //             cmd.args(["/C", "start", "", url]);
-    //         } else {
-    //             cmd.arg(url);
-    //         }
-    //         let _ = cmd.spawn();
-    //     });
+    // Cloud account indicator (left-most element): opens the Account tab.
+    let cloud_btn = {
+        let s = state.clone();
+        let cloud_account = state.cloud_account;
+        container(label(move || match cloud_account.get() {
+            Some(email) if !email.is_empty() => format!("☁ {email}"),
+            Some(_) => "☁ Signed in".to_string(),
+            None => "☁ Sign in".to_string(),
+        }))
+        .style(move |s| {
+            let p = state.theme.get().palette;
+            s.font_size(10.0)
+                .padding_horiz(8.0)
+                .padding_vert(2.0)
+                .margin_right(8.0)
+                .border_radius(3.0)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .color(p.accent)
+                .background(p.accent_dim)
+        })
+        .on_click_stop(move |_| {
+            s.left_panel_tab.set(Tab::Account);
+            s.show_left_panel.set(true);
+        })
+    };
 
     // Branch clickable button — click to open branch picker overlay
     let branch_btn = {
@@ -2613,6 +4227,7 @@ fn status_bar(state: IdeState) -> impl IntoView {
     };
 
     let left = stack((
+        cloud_btn,
         branch_btn,
         label(|| "   ").style(|s| s.font_size(11.0)),
         phaze_icon(icons::BRANCH, 12.0, move |p| p.accent, state.theme),
@@ -2623,11 +4238,19 @@ fn status_bar(state: IdeState) -> impl IntoView {
     ))
     .style(|s| s.items_center().padding_horiz(8.0));
 
-    // VIM mode toggle button — shows INSERT/NORMAL when active
+    // Keymap button — shows INSERT/NORMAL in vim, EMACS (and a pending C-x)
+    // in Emacs; click to pick the default keys, vim or Emacs.
     let vim_btn = {
         let s = state.clone();
         let s_label = state.clone();
         container(label(move || {
+            if s_label.emacs_mode.get() {
+                return if s_label.emacs_prefix.get() {
+                    "EMACS  C-x-".to_string()
+                } else {
+                    "EMACS".to_string()
+                };
+            }
             if !s_label.vim_mode.get() {
                 return "NORMAL".to_string();
             }
@@ -2639,35 +4262,49 @@ fn status_bar(state: IdeState) -> impl IntoView {
         }))
         .style(move |s2| {
             let p = state.theme.get().palette;
-            let vim = state.vim_mode.get();
-            let normal = state.vim_normal_mode.get();
+            let keymap = state.vim_mode.get() || state.emacs_mode.get();
+            let normal = state.vim_mode.get() && state.vim_normal_mode.get();
             s2.font_size(10.0)
                 .padding_horiz(6.0)
                 .padding_vert(2.0)
                 .margin_right(6.0)
                 .border_radius(3.0)
                 .cursor(floem::style::CursorStyle::Pointer)
-                .color(if vim { p.bg_base } else { p.text_muted })
-                .background(if vim && normal {
+                .color(if keymap { p.bg_base } else { p.text_muted })
+                .background(if keymap && normal {
                     p.warning
-                } else if vim {
+                } else if keymap {
                     p.accent
                 } else {
                     p.bg_elevated
                 })
                 .border(1.0)
-                .border_color(if vim { p.accent } else { p.border })
+                .border_color(if keymap { p.accent } else { p.border })
         })
         .on_click_stop(move |_| {
-            let s2 = s.clone();
-            s2.vim_mode.update(|v| *v = !*v);
-            // When enabling vim mode, start in Normal mode.
-            if s2.vim_mode.get() {
-                s2.vim_normal_mode.set(true);
-            } else {
-                s2.vim_normal_mode.set(false);
-            }
-            // Session is persisted by the unified debounced effect watching vim_mode.
+            let (vim_mode, emacs_mode) = (s.vim_mode, s.emacs_mode);
+            let (vim_normal_mode, emacs_prefix) = (s.vim_normal_mode, s.emacs_prefix);
+            let pick = move |vim: bool, emacs: bool| {
+                move || {
+                    vim_mode.set(vim);
+                    // When enabling vim mode, start in Normal mode.
+                    vim_normal_mode.set(vim);
+                    emacs_mode.set(emacs);
+                    emacs_prefix.set(false);
+                    // Session is persisted by the unified debounced effect.
+                }
+            };
+            let vim = vim_mode.get_untracked();
+            let emacs = emacs_mode.get_untracked();
+            let mark = |on: bool| if on { "✓ " } else { "   " };
+            let menu = Menu::new("")
+                .entry(
+                    MenuItem::new(format!("{}Default Keymap", mark(!vim && !emacs)))
+                        .action(pick(false, false)),
+                )
+                .entry(MenuItem::new(format!("{}Vim", mark(vim))).action(pick(true, false)))
+                .entry(MenuItem::new(format!("{}Emacs", mark(emacs))).action(pick(false, true)));
+            show_context_menu(menu, None);
         })
     };
 
@@ -2753,28 +4390,118 @@ fn status_bar(state: IdeState) -> impl IntoView {
             s.font_size(10.0)
                 .color(if has_err { p.error } else { p.warning })
         }),
-        // LSP progress indicator — shown while indexing, hidden when idle.
-        label(move || {
-            state
-                .lsp_progress
-                .get()
-                .map(|msg| {
-                    if msg.len() > 40 {
-                        let end = msg.floor_char_boundary(40);
-                        format!("{}…  ", &msg[..end])
-                    } else {
-                        format!("{msg}  ")
+        // Language server health — the server most in trouble is shown;
+        // click for every server's state and to restart them.
+        {
+            let lsp_status = state.lsp_status;
+            let lsp_cmd = state.lsp_cmd.clone();
+            let lsp_state = state.clone();
+            let lsp_theme = state.theme;
+            // The server to show: failed over restarting over the rest.
+            let worst = move || {
+                lsp_status
+                    .get()
+                    .into_iter()
+                    .max_by_key(|st| match st.state {
+                        ServerState::Failed(_) => 3,
+                        ServerState::Restarting { .. } => 2,
+                        ServerState::Starting => 1,
+                        ServerState::Running => 0,
+                    })
+            };
+            container(label(move || match worst() {
+                Some(st) => match st.state {
+                    ServerState::Running => match lsp_status.get().len() {
+                        1 => format!("● {}  ", st.server),
+                        n => format!("● {n} language servers  "),
+                    },
+                    ServerState::Starting => format!("◌ {} starting  ", st.server),
+                    ServerState::Restarting { .. } => format!("↻ {} restarting  ", st.server),
+                    ServerState::Failed(_) => format!("⊘ {} stopped  ", st.server),
+                },
+                None => String::new(),
+            }))
+            .style(move |s| {
+                let p = lsp_theme.get().palette;
+                let color = match worst().map(|st| st.state) {
+                    Some(ServerState::Failed(_)) => p.error,
+                    Some(ServerState::Restarting { .. }) | Some(ServerState::Starting) => p.warning,
+                    _ => p.success,
+                };
+                s.color(color)
+                    .font_size(10.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(lsp_status.get().is_empty(), |s| {
+                        s.display(floem::style::Display::None)
+                    })
+            })
+            .on_click_stop(move |_| {
+                let mut menu = Menu::new("");
+                for st in lsp_status.get_untracked() {
+                    let lsp_cmd = lsp_cmd.clone();
+                    let server = st.server.clone();
+                    menu = menu.entry(
+                        MenuItem::new(format!("Restart {} — {}", st.server, st.state.label()))
+                            .action(move || {
+                                let _ = lsp_cmd.send(LspCommand::RestartServer(server.clone()));
+                            }),
+                    );
+                }
+                let lsp_cmd = lsp_cmd.clone();
+                let s = lsp_state.clone();
+                menu = menu
+                    .separator()
+                    .entry(MenuItem::new("Show Language Servers").action(move || {
+                        s.bottom_panel_tab.set(Tab::LanguageServers);
+                        s.show_bottom_panel.set(true);
+                    }))
+                    .entry(
+                        MenuItem::new("Restart All Language Servers").action(move || {
+                            let _ = lsp_cmd.send(LspCommand::RestartServers);
+                        }),
+                    );
+                show_context_menu(menu, None);
+            })
+        },
+        // Background work (indexing, scans, LSP warm-up) from the core
+        // progress bus — hidden when idle; click to cancel a task.
+        {
+            let tasks = state.background_tasks;
+            let bg_theme = state.theme;
+            container(label(move || {
+                progress::summary(&tasks.get())
+                    .map(|text| format!("⟳ {text}  "))
+                    .unwrap_or_default()
+            }))
+            .style(move |s| {
+                s.color(bg_theme.get().palette.text_muted)
+                    .font_size(10.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(tasks.get().is_empty(), |s| {
+                        s.display(floem::style::Display::None)
+                    })
+            })
+            .on_click_stop(move |_| {
+                let mut menu = Menu::new("");
+                for task in tasks.get_untracked() {
+                    let id = task.id;
+                    let mut text = task.label();
+                    if let Some(message) = &task.message {
+                        text = format!("{text} — {message}");
                     }
-                })
-                .unwrap_or_default()
-        })
-        .style(move |s| {
-            s.color(state.theme.get().palette.text_muted)
-                .font_size(10.0)
-                .apply_if(state.lsp_progress.get().is_none(), |s| {
-                    s.display(floem::style::Display::None)
-                })
-        }),
+                    menu = menu.entry(if task.cancel_requested {
+                        MenuItem::new(format!("{text} (cancelling)"))
+                    } else if task.cancellable {
+                        MenuItem::new(format!("Cancel {text}")).action(move || {
+                            progress::global().cancel(id);
+                        })
+                    } else {
+                        MenuItem::new(text)
+                    });
+                }
+                show_context_menu(menu, None);
+            })
+        },
         label(|| "AI Ready  ")
             .style(move |s| s.color(state.theme.get().palette.success).font_size(11.0)),
         // Git blame for current cursor line
@@ -2794,54 +4521,91 @@ fn status_bar(state: IdeState) -> impl IntoView {
                     s.display(floem::style::Display::None)
                 })
         }),
-        // Dynamic encoding + line ending indicator — clickable to toggle CRLF/LF
+        // Indentation of the active file — click to change it for this file
+        {
+            let indent_sig = state.active_indent;
+            let indent_theme = state.theme;
+            let indent_hov = create_rw_signal(false);
+            container(
+                label(move || {
+                    indent_sig
+                        .get()
+                        .map(|i| format!("{}  ", i.label()))
+                        .unwrap_or_default()
+                })
+                .style(move |s| {
+                    let p = indent_theme.get().palette;
+                    s.color(if indent_hov.get() {
+                        p.accent
+                    } else {
+                        p.text_muted
+                    })
+                    .font_size(11.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                }),
+            )
+            .on_click_stop(move |_| {
+                let Some(current) = indent_sig.get_untracked() else {
+                    return;
+                };
+                let choose = move |indent: Indent| {
+                    move || {
+                        indent_sig.set(Some(EffectiveIndent {
+                            indent,
+                            source: IndentSource::User,
+                        }))
+                    }
+                };
+                let mark = |on: bool| if on { "✓ " } else { "   " };
+                let size = current.indent.size;
+                let spaces = current.indent.style == IndentStyle::Spaces;
+                let mut menu = Menu::new("")
+                    .entry(
+                        MenuItem::new(format!("{}Indent Using Spaces", mark(spaces)))
+                            .action(choose(Indent::spaces(size))),
+                    )
+                    .entry(
+                        MenuItem::new(format!("{}Indent Using Tabs", mark(!spaces)))
+                            .action(choose(Indent::tabs(size))),
+                    )
+                    .separator();
+                for n in [2, 3, 4, 8] {
+                    let indent = Indent {
+                        style: current.indent.style,
+                        size: n,
+                    };
+                    menu = menu.entry(
+                        MenuItem::new(format!("{}Size: {n}", mark(n == size)))
+                            .action(choose(indent)),
+                    );
+                }
+                show_context_menu(menu, None);
+            })
+            .on_event_stop(EventListener::PointerEnter, move |_| indent_hov.set(true))
+            .on_event_stop(EventListener::PointerLeave, move |_| indent_hov.set(false))
+        },
+        // Encoding + line ending indicator — opens the file format menu
         {
             let le_state = state.clone();
+            let menu_state = state.clone();
             let le_theme = state.theme;
             let le_hov = create_rw_signal(false);
             container(
-                label(move || format!("UTF-8 {}  ", le_state.line_ending.get())).style(move |s| {
+                label(move || {
+                    format!(
+                        "{} {}  ",
+                        le_state.active_encoding.get(),
+                        le_state.line_ending.get()
+                    )
+                })
+                .style(move |s| {
                     let p = le_theme.get().palette;
                     s.color(if le_hov.get() { p.accent } else { p.text_muted })
                         .font_size(11.0)
                         .cursor(floem::style::CursorStyle::Pointer)
                 }),
             )
-            .on_click_stop(move |_| {
-                // Toggle line ending and convert file bytes
-                let current = state.line_ending.get();
-                let new_le: &'static str = if current == "CRLF" { "LF" } else { "CRLF" };
-                state.line_ending.set(new_le);
-                // Convert open file bytes
-                if let Some(path) = state.open_file.get_untracked() {
-                    if path.exists() {
-                        let toast = state.status_toast;
-                        if let Ok(bytes) = std::fs::read(&path) {
-                            let converted = if new_le == "LF" {
-                                // Remove all \r
-                                bytes
-                                    .into_iter()
-                                    .filter(|&b| b != b'\r')
-                                    .collect::<Vec<_>>()
-                            } else {
-                                // Add \r before each \n that isn't already preceded by \r
-                                let mut out = Vec::with_capacity(bytes.len() + bytes.len() / 20);
-                                let mut prev = 0u8;
-                                for b in bytes {
-                                    if b == b'\n' && prev != b'\r' {
-                                        out.push(b'\r');
-                                    }
-                                    out.push(b);
-                                    prev = b;
-                                }
-                                out
-                            };
-                            let _ = std::fs::write(&path, &converted);
-                            show_toast(toast, format!("Converted to {new_le}"));
-                        }
-                    }
-                }
-            })
+            .on_click_stop(move |_| show_file_format_menu(&menu_state))
             .on_event_stop(EventListener::PointerEnter, move |_| le_hov.set(true))
             .on_event_stop(EventListener::PointerLeave, move |_| le_hov.set(false))
         },
@@ -2877,6 +4641,7 @@ fn status_bar(state: IdeState) -> impl IntoView {
                     .apply_if(!ro_sig.get(), |s| s.display(floem::style::Display::None))
             })
         },
+        notification_bell(state.clone()),
     ))
     .style(|s| s.items_center().padding_horiz(8.0));
 
@@ -2899,6 +4664,9 @@ fn status_bar(state: IdeState) -> impl IntoView {
     })
 }
 
+/// A Problems row's height before it is laid out.
+const PROBLEM_ROW_HEIGHT: f64 = 26.0;
+
 fn problems_view(state: IdeState) -> impl IntoView {
     use floem::reactive::create_rw_signal as crws;
     let diags = state.diagnostics;
@@ -2909,6 +4677,19 @@ fn problems_view(state: IdeState) -> impl IntoView {
     // Filter toggles
     let show_errors = crws(true);
     let show_warnings = crws(true);
+    // Row chosen with the keyboard; Enter jumps to it like a click.
+    let selected = crws(None::<usize>);
+    let visible = move || {
+        safe_get(diags, Vec::new())
+            .into_iter()
+            .filter(|d| match d.severity {
+                DiagSeverity::Error => show_errors.get(),
+                DiagSeverity::Warning => show_warnings.get(),
+                _ => true,
+            })
+            .enumerate()
+            .collect::<Vec<_>>()
+    };
 
     let err_btn = container(label(move || {
         let n = diags
@@ -2956,7 +4737,40 @@ fn problems_view(state: IdeState) -> impl IntoView {
         show_warnings.update(|v| *v = !*v);
     });
 
-    let filter_bar = stack((err_btn, warn_btn)).style(move |s| {
+    let lsp_cmd = state.lsp_cmd.clone();
+    let analyze_btn = container(label(|| "Analyze Workspace".to_string()))
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(11.0)
+                .padding_horiz(8.0)
+                .padding_vert(3.0)
+                .border_radius(4.0)
+                .margin_left(floem::unit::PxPctAuto::Auto)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .color(p.text_secondary)
+                .background(p.bg_elevated)
+        })
+        .on_click_stop(move |_| {
+            let _ = lsp_cmd.send(LspCommand::AnalyzeWorkspace);
+        });
+
+    let lsp_cmd = state.lsp_cmd.clone();
+    let security_btn = container(label(|| "Security Scan".to_string()))
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(11.0)
+                .padding_horiz(8.0)
+                .padding_vert(3.0)
+                .border_radius(4.0)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .color(p.text_secondary)
+                .background(p.bg_elevated)
+        })
+        .on_click_stop(move |_| {
+            let _ = lsp_cmd.send(LspCommand::ScanSecurity);
+        });
+
+    let filter_bar = stack((err_btn, warn_btn, analyze_btn, security_btn)).style(move |s| {
         let p = theme.get().palette;
         s.flex_row()
             .gap(6.0)
@@ -2986,110 +4800,238 @@ fn problems_view(state: IdeState) -> impl IntoView {
             })
     });
 
-    let list = scroll(
-        dyn_stack(
-            move || {
-                safe_get(diags, Vec::new())
-                    .into_iter()
-                    .filter(|d| match d.severity {
-                        DiagSeverity::Error => show_errors.get(),
-                        DiagSeverity::Warning => show_warnings.get(),
-                        _ => true,
-                    })
-                    .enumerate()
-                    .collect::<Vec<_>>()
-            },
-            |(idx, _)| *idx,
-            {
-                let theme = state.theme;
-                move |(_, entry): (usize, DiagEntry)| {
-                    let sev = entry.severity;
-                    let icon = match sev {
-                        DiagSeverity::Error => "⊗",
-                        DiagSeverity::Warning => "⚠",
-                        DiagSeverity::Info => "ℹ",
-                        DiagSeverity::Hint => "○",
-                    };
-                    let filename = entry
-                        .path
-                        .file_name()
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_else(|| "?".to_string());
-                    let loc = format!("{}:{}", entry.line, entry.col);
-                    let msg = entry.message.clone();
-                    let path = entry.path.clone();
-                    let line_no = entry.line;
-                    let hovered = crws(false);
+    let list = virtual_list(
+        visible,
+        |(i, d)| {
+            (
+                *i,
+                d.path.clone(),
+                d.line,
+                d.col,
+                d.message.clone(),
+                d.source.clone(),
+            )
+        },
+        {
+            let theme = state.theme;
+            move |(idx, entry): (usize, DiagEntry)| {
+                let sev = entry.severity;
+                let icon = match sev {
+                    DiagSeverity::Error => "⊗",
+                    DiagSeverity::Warning => "⚠",
+                    DiagSeverity::Info => "ℹ",
+                    DiagSeverity::Hint => "○",
+                };
+                let filename = entry
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "?".to_string());
+                let loc = format!("{}:{}", entry.line, entry.col);
+                let msg = entry.message.clone();
+                let path = entry.path.clone();
+                let line_no = entry.line;
+                let hovered = crws(false);
+                let source = entry.source.clone().unwrap_or_default();
+                let has_source = !source.is_empty();
+                let fix_title = entry
+                    .fix
+                    .as_ref()
+                    .map(|f| f.title.clone())
+                    .unwrap_or_default();
+                let has_fix = entry.fix.is_some();
+                let fix = entry.fix.clone();
+                let fix_path = entry.path.clone();
+                let fix_state = state.clone();
+                let is_security = source == SECURITY_SOURCE;
+                let propose = {
+                    let state = state.clone();
+                    let (path, line, message) =
+                        (entry.path.clone(), entry.line, entry.message.clone());
+                    move || propose_security_fix(&state, &path, line, &message)
+                };
 
-                    container(
-                        stack((
-                            label(move || icon.to_string()).style(move |s| {
+                container(
+                    stack((
+                        label(move || icon.to_string()).style(move |s| {
+                            let p = theme.get().palette;
+                            let c = match sev {
+                                DiagSeverity::Error => p.error,
+                                DiagSeverity::Warning => p.warning,
+                                DiagSeverity::Info => p.accent,
+                                _ => p.text_muted,
+                            };
+                            s.font_size(13.0).color(c).margin_right(8.0)
+                        }),
+                        label(move || msg.clone()).style(move |s| {
+                            s.font_size(12.0)
+                                .color(theme.get().palette.text_primary)
+                                .flex_grow(1.0)
+                        }),
+                        container(label(move || fix_title.clone()))
+                            .style(move |s| {
                                 let p = theme.get().palette;
-                                let c = match sev {
-                                    DiagSeverity::Error => p.error,
-                                    DiagSeverity::Warning => p.warning,
-                                    DiagSeverity::Info => p.accent,
-                                    _ => p.text_muted,
-                                };
-                                s.font_size(13.0).color(c).margin_right(8.0)
-                            }),
-                            label(move || msg.clone()).style(move |s| {
-                                s.font_size(12.0)
-                                    .color(theme.get().palette.text_primary)
-                                    .flex_grow(1.0)
-                            }),
-                            label(move || filename.clone()).style(move |s| {
-                                s.font_size(11.0)
-                                    .color(theme.get().palette.accent)
+                                s.font_size(10.0)
+                                    .padding_horiz(6.0)
+                                    .padding_vert(1.0)
                                     .margin_left(8.0)
+                                    .border_radius(3.0)
+                                    .cursor(floem::style::CursorStyle::Pointer)
+                                    .color(p.accent)
+                                    .background(p.bg_elevated)
+                                    .apply_if(!has_fix, |s| s.display(floem::style::Display::None))
+                            })
+                            .on_click_stop(move |_| {
+                                if let Some(fix) = &fix {
+                                    apply_analysis_fix(&fix_state, &fix_path, fix);
+                                }
                             }),
-                            label(move || loc.clone()).style(move |s| {
+                        container(label(|| "Propose Fix".to_string()))
+                            .style(move |s| {
+                                let p = theme.get().palette;
                                 s.font_size(10.0)
-                                    .color(theme.get().palette.text_muted)
-                                    .margin_left(6.0)
-                            }),
-                        ))
-                        .style(|s| s.flex_row().items_center().width_full()),
-                    )
-                    .style(move |s| {
-                        let p = theme.get().palette;
-                        s.width_full()
-                            .padding_horiz(12.0)
-                            .padding_vert(5.0)
-                            .cursor(floem::style::CursorStyle::Pointer)
-                            .background(if hovered.get() {
-                                p.bg_elevated
-                            } else {
-                                floem::peniko::Color::TRANSPARENT
+                                    .padding_horiz(6.0)
+                                    .padding_vert(1.0)
+                                    .margin_left(8.0)
+                                    .border_radius(3.0)
+                                    .cursor(floem::style::CursorStyle::Pointer)
+                                    .color(p.accent)
+                                    .background(p.bg_elevated)
+                                    .apply_if(!is_security, |s| {
+                                        s.display(floem::style::Display::None)
+                                    })
                             })
-                    })
-                    .on_click_stop(move |_| {
-                        open_file.set(Some(path.clone()));
-                        goto_line.set(line_no);
-                    })
-                    .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
-                        hovered.set(true);
-                    })
-                    .on_event_stop(
-                        floem::event::EventListener::PointerLeave,
-                        move |_| {
-                            hovered.set(false);
-                        },
-                    )
-                }
-            },
-        )
-        .style(|s| s.flex_col().width_full()),
+                            .on_click_stop(move |_| propose()),
+                        label(move || source.clone()).style(move |s| {
+                            s.font_size(10.0)
+                                .color(theme.get().palette.text_muted)
+                                .margin_left(8.0)
+                                .apply_if(!has_source, |s| s.display(floem::style::Display::None))
+                        }),
+                        label(move || filename.clone()).style(move |s| {
+                            s.font_size(11.0)
+                                .color(theme.get().palette.accent)
+                                .margin_left(8.0)
+                        }),
+                        label(move || loc.clone()).style(move |s| {
+                            s.font_size(10.0)
+                                .color(theme.get().palette.text_muted)
+                                .margin_left(6.0)
+                        }),
+                    ))
+                    .style(|s| s.flex_row().items_center().width_full()),
+                )
+                .style(move |s| {
+                    let p = theme.get().palette;
+                    s.width_full()
+                        .padding_horiz(12.0)
+                        .padding_vert(5.0)
+                        .cursor(floem::style::CursorStyle::Pointer)
+                        .background(if selected.get() == Some(idx) {
+                            p.selection
+                        } else if hovered.get() {
+                            p.bg_elevated
+                        } else {
+                            floem::peniko::Color::TRANSPARENT
+                        })
+                })
+                .on_click_stop(move |_| {
+                    selected.set(Some(idx));
+                    open_file.set(Some(path.clone()));
+                    goto_line.set(line_no);
+                })
+                .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                    hovered.set(true);
+                })
+                .on_event_stop(
+                    floem::event::EventListener::PointerLeave,
+                    move |_| {
+                        hovered.set(false);
+                    },
+                )
+            }
+        },
+        PROBLEM_ROW_HEIGHT,
+        move || selected.get(),
     )
-    .style(move |s| {
-        s.width_full()
-            .flex_grow(1.0)
-            .apply_if(diags.get().is_empty(), |s| {
-                s.display(floem::style::Display::None)
-            })
+    .style(|s| s.width_full().height_full());
+
+    let list = container(list);
+    let list_id = list.id();
+    let list = list
+        .on_event_cont(floem::event::EventListener::PointerDown, move |_| {
+            list_id.request_focus()
+        })
+        .on_event_stop(floem::event::EventListener::KeyDown, move |event| {
+            let floem::event::Event::KeyDown(e) = event else {
+                return;
+            };
+            let floem::keyboard::Key::Named(ref named) = e.key.logical_key else {
+                return;
+            };
+            let rows = visible();
+            match list_nav_key_opt(named, selected.get(), rows.len()) {
+                ListNav::Move(i) => selected.set(Some(i)),
+                ListNav::Accept(i) => {
+                    if let Some((_, d)) = rows.get(i) {
+                        open_file.set(Some(d.path.clone()));
+                        goto_line.set(d.line);
+                    }
+                }
+                ListNav::Close => selected.set(None),
+                ListNav::None => {}
+            }
+        })
+        .keyboard_navigable()
+        .style(move |s| {
+            s.width_full()
+                .flex_grow(1.0)
+                .min_height(0.0)
+                .apply(focus_ring(&theme.get().palette))
+                .apply_if(diags.get().is_empty(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        });
+
+    stack((filter_bar, empty_msg, list)).style(|s| s.flex_col().width_full().height_full())
+}
+
+/// Apply a workspace analysis or line length quick fix to the file on disk,
+/// then check again so the Problems panel drops what it fixed.
+fn apply_analysis_fix(state: &IdeState, path: &std::path::Path, fix: &Removal) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            show_toast(state.status_toast, format!("{}: {e}", path.display()));
+            return;
+        }
+    };
+    if let Err(e) = std::fs::write(path, fix.apply(&content)) {
+        show_toast(state.status_toast, format!("{}: {e}", path.display()));
+        return;
+    }
+    // Re-open in editor to reflect changes
+    if state.open_file.get().as_deref() == Some(path) {
+        state.open_file.set(Some(path.to_path_buf()));
+    }
+    let _ = state.lsp_cmd.send(LspCommand::AnalyzeWorkspace);
+    let _ = state.lsp_cmd.send(LspCommand::CheckLineLength {
+        path: path.to_path_buf(),
     });
+    show_toast(state.status_toast, fix.title.clone());
+}
 
-    stack((filter_bar, empty_msg, list)).style(|s| s.flex_col().width_full().height_full())
+/// Ask the agent to fix a security scan finding. Only the location goes in
+/// the prompt, so a committed secret isn't sent along with it.
+fn propose_security_fix(state: &IdeState, path: &std::path::Path, line: u32, message: &str) {
+    state.pending_chat_inject.set(Some(format!(
+        "The security scan reports at {}:{line}: {message}\n\nRead the code around that \
+         line and propose a minimal fix (bound query parameters, arguments passed without a \
+         shell, handled errors, or reading the secret from the environment). Don't repeat any \
+         secret value in your reply, and say if the finding is a false positive.",
+        path.display()
+    )));
+    state.show_right_panel.set(true);
 }
 
 fn references_view(state: IdeState) -> impl IntoView {
@@ -3230,45 +5172,6 @@ fn references_view(state: IdeState) -> impl IntoView {
     stack((count_label, empty_msg, list)).style(|s| s.flex_col().width_full().height_full())
 }
 
-fn output_view(state: IdeState) -> impl IntoView {
-    let log = state.output_log;
-    let theme = state.theme;
-    scroll(
-        dyn_stack(
-            move || {
-                safe_get(log, Vec::new())
-                    .into_iter()
-                    .enumerate()
-                    .collect::<Vec<_>>()
-            },
-            |(idx, _)| *idx,
-            move |(_, line): (usize, String)| {
-                let line2 = line.clone();
-                label(move || line.clone()).style(move |s| {
-                    let p = theme.get().palette;
-                    let color = if line2.starts_with("[error]")
-                        || line2.contains("error") && !line2.contains("0 errors")
-                    {
-                        p.error
-                    } else if line2.starts_with("[warn]") || line2.contains("warning") {
-                        p.warning
-                    } else {
-                        p.text_secondary
-                    };
-                    s.font_size(11.5)
-                        .color(color)
-                        .font_family("JetBrains Mono, Fira Code, monospace".to_string())
-                        .padding_horiz(12.0)
-                        .padding_vert(1.0)
-                        .width_full()
-                })
-            },
-        )
-        .style(|s| s.flex_col().width_full()),
-    )
-    .style(|s| s.width_full().height_full())
-}
-
 fn debug_console_view(state: IdeState) -> impl IntoView {
     let theme = state.theme;
     container(
@@ -3305,15 +5208,143 @@ fn ports_view(state: IdeState) -> impl IntoView {
     .style(|s| s.width_full().height_full().items_center().justify_center())
 }
 
+/// Order of the rows in the outline panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutlineSort {
+    /// File order, nested as in the source.
+    Position,
+    Name,
+    /// Grouped by kind, then by name.
+    Kind,
+}
+
+/// The rows the outline shows: `(index in symbols, symbol, matched)`.
+///
+/// In file order a row is kept when it passes the filter and, with
+/// `public_only`, is public along with everything it is nested in; the
+/// symbols enclosing a kept row stay too, as unmatched context. Sorted by
+/// name or kind, only matching rows are listed, flat.
+fn outline_rows(
+    symbols: &[SymbolEntry],
+    filter: &str,
+    sort: OutlineSort,
+    public_only: bool,
+) -> Vec<(usize, SymbolEntry, bool)> {
+    let filter = filter.trim().to_lowercase();
+    let mut parents: Vec<Option<usize>> = Vec::with_capacity(symbols.len());
+    let mut open: Vec<usize> = Vec::new();
+    for sym in symbols {
+        while open.last().is_some_and(|&p| symbols[p].depth >= sym.depth) {
+            open.pop();
+        }
+        parents.push(open.last().copied());
+        open.push(parents.len() - 1);
+    }
+    let mut api: Vec<bool> = Vec::with_capacity(symbols.len());
+    for (i, sym) in symbols.iter().enumerate() {
+        api.push(sym.public && parents[i].is_none_or(|p| api[p]));
+    }
+    let matches = |i: usize| {
+        (!public_only || api[i])
+            && (filter.is_empty() || symbols[i].name.to_lowercase().contains(&filter))
+    };
+
+    if sort == OutlineSort::Position {
+        let mut keep = vec![false; symbols.len()];
+        for i in (0..symbols.len()).filter(|&i| matches(i)) {
+            keep[i] = true;
+            let mut parent = parents[i];
+            while let Some(p) = parent.filter(|&p| !keep[p]) {
+                keep[p] = true;
+                parent = parents[p];
+            }
+        }
+        return symbols
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| keep[*i])
+            .map(|(i, sym)| (i, sym.clone(), matches(i)))
+            .collect();
+    }
+    let mut rows: Vec<(usize, SymbolEntry, bool)> = symbols
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| matches(*i))
+        .map(|(i, sym)| {
+            (
+                i,
+                SymbolEntry {
+                    depth: 0,
+                    ..sym.clone()
+                },
+                true,
+            )
+        })
+        .collect();
+    rows.sort_by_cached_key(|(_, sym, _)| {
+        let name = sym.name.to_lowercase();
+        match sort {
+            OutlineSort::Kind => (sym.kind.clone(), name),
+            _ => (String::new(), name),
+        }
+    });
+    rows
+}
+
+/// A toggle button in the outline panel's toolbar.
+fn outline_toggle(
+    text: &'static str,
+    theme: RwSignal<PhazeTheme>,
+    active: impl Fn() -> bool + 'static,
+    on_click: impl Fn() + 'static,
+) -> impl IntoView {
+    container(label(move || text.to_string()))
+        .style(move |s| {
+            let p = theme.get().palette;
+            let on = active();
+            s.font_size(11.0)
+                .padding_horiz(6.0)
+                .padding_vert(2.0)
+                .border_radius(3.0)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .color(if on { p.bg_base } else { p.text_muted })
+                .background(if on { p.accent } else { p.bg_elevated })
+                .border(1.0)
+                .border_color(p.border)
+        })
+        .on_click_stop(move |_| on_click())
+}
+
 /// Symbol outline panel — displayed in the left sidebar under the "Symbols" tab.
+///
+/// The symbols can be filtered by name, sorted by position, name or kind,
+/// and narrowed to the public API. The symbol around the cursor is
+/// highlighted and kept in view as the cursor moves.
 fn symbol_outline_panel(state: IdeState) -> impl IntoView {
     use floem::reactive::create_rw_signal as crws;
     let symbols = state.doc_symbols;
     let theme = state.theme;
     let open_file = state.open_file;
     let goto_line = state.goto_line;
+    let active_cursor = state.active_cursor;
     let lsp_cmd = state.lsp_cmd.clone();
 
+    let filter = crws(String::new());
+    let sort = crws(OutlineSort::Position);
+    let public_only = crws(false);
+
+    // The innermost symbol whose body holds the cursor.
+    let current = floem::reactive::create_memo(move |_| {
+        let (path, line, _) = active_cursor.get()?;
+        if open_file.get().as_ref() != Some(&path) {
+            return None;
+        }
+        let line = line + 1;
+        safe_get(symbols, Vec::new())
+            .iter()
+            .rposition(|sym| sym.line <= line && line <= sym.end_line)
+    });
+
     // Refresh button
     let refresh_btn = container(label(|| " ↺ ".to_string()).style(move |s| {
         s.font_size(13.0)
@@ -3338,10 +5369,67 @@ fn symbol_outline_panel(state: IdeState) -> impl IntoView {
     ))
     .style(|s| s.flex_row().items_center().padding_vert(6.0).width_full());
 
+    let sort_toggle = |text, mode| {
+        outline_toggle(
+            text,
+            theme,
+            move || sort.get() == mode,
+            move || sort.set(mode),
+        )
+    };
+    let toolbar = stack((
+        text_input(filter)
+            .placeholder("Filter symbols")
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.flex_grow(1.0)
+                    .min_width(0.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(4.0)
+                    .color(p.text_primary)
+                    .padding_horiz(6.0)
+                    .padding_vert(3.0)
+                    .font_size(12.0)
+            })
+            .on_event_stop(EventListener::KeyDown, move |event| {
+                if let Event::KeyDown(ke) = event {
+                    if ke.key.logical_key == Key::Named(NamedKey::Escape) {
+                        filter.set(String::new());
+                    }
+                }
+            }),
+        sort_toggle("≡", OutlineSort::Position),
+        sort_toggle("A–Z", OutlineSort::Name),
+        sort_toggle("Kind", OutlineSort::Kind),
+        outline_toggle(
+            "pub",
+            theme,
+            move || public_only.get(),
+            move || public_only.update(|v| *v = !*v),
+        ),
+    ))
+    .style(|s| {
+        s.flex_row()
+            .items_center()
+            .gap(4.0)
+            .width_full()
+            .padding_horiz(8.0)
+            .padding_bottom(4.0)
+    });
+
+    let rows = floem::reactive::create_memo(move |_| {
+        let syms = safe_get(symbols, Vec::new());
+        outline_rows(&syms, &filter.get(), sort.get(), public_only.get())
+    });
+
     let empty_msg = container(
         label(move || {
             if symbols.get().is_empty() {
                 "No symbols found in file.".to_string()
+            } else if rows.get().is_empty() {
+                "No matching symbols.".to_string()
             } else {
                 String::new()
             }
@@ -3353,23 +5441,18 @@ fn symbol_outline_panel(state: IdeState) -> impl IntoView {
         }),
     )
     .style(move |s| {
-        s.apply_if(!symbols.get().is_empty(), |s| {
+        s.apply_if(!rows.get().is_empty(), |s| {
             s.display(floem::style::Display::None)
         })
     });
 
     let list = scroll(
         dyn_stack(
-            move || {
-                safe_get(symbols, Vec::new())
-                    .into_iter()
-                    .enumerate()
-                    .collect::<Vec<_>>()
-            },
-            |(i, _)| *i,
+            move || rows.get(),
+            |(i, sym, matched)| (*i, sym.name.clone(), sym.line, sym.depth, *matched),
             {
                 let theme = state.theme;
-                move |(_, sym): (usize, SymbolEntry)| {
+                move |(idx, sym, matched): (usize, SymbolEntry, bool)| {
                     let hovered = crws(false);
                     let name = sym.name.clone();
                     let kind = sym.kind.clone();
@@ -3387,7 +5470,7 @@ fn symbol_outline_panel(state: IdeState) -> impl IntoView {
                         _ => pal.text_muted,
                     };
 
-                    container(
+                    let row = container(
                         stack((
                             label(move || format!("{kind} ")).style(move |s| {
                                 s.font_size(11.0)
@@ -3395,8 +5478,13 @@ fn symbol_outline_panel(state: IdeState) -> impl IntoView {
                                     .font_family("JetBrains Mono, monospace".to_string())
                             }),
                             label(move || name.clone()).style(move |s| {
+                                let p = theme.get().palette;
                                 s.font_size(12.0)
-                                    .color(theme.get().palette.text_primary)
+                                    .color(if matched {
+                                        p.text_primary
+                                    } else {
+                                        p.text_muted
+                                    })
                                     .font_family("JetBrains Mono, monospace".to_string())
                             }),
                         ))
@@ -3410,6 +5498,8 @@ fn symbol_outline_panel(state: IdeState) -> impl IntoView {
                             .cursor(floem::style::CursorStyle::Pointer)
                             .background(if hovered.get() {
                                 p.bg_elevated
+                            } else if current.get() == Some(idx) {
+                                p.accent_dim
                             } else {
                                 floem::peniko::Color::TRANSPARENT
                             })
@@ -3425,7 +5515,15 @@ fn symbol_outline_panel(state: IdeState) -> impl IntoView {
                         move |_| {
                             hovered.set(false);
                         },
-                    )
+                    );
+                    // Follow the cursor: keep its symbol scrolled into view.
+                    let row_id = row.id();
+                    create_effect(move |_| {
+                        if current.get() == Some(idx) {
+                            row_id.scroll_to(None);
+                        }
+                    });
+                    row
                 }
             },
         )
@@ -3433,7 +5531,7 @@ fn symbol_outline_panel(state: IdeState) -> impl IntoView {
     )
     .style(move |s| s.flex_grow(1.0).width_full());
 
-    stack((header, empty_msg, list)).style(|s| s.flex_col().width_full().height_full())
+    stack((header, toolbar, empty_msg, list)).style(|s| s.flex_col().width_full().height_full())
 }
 
 /// Git diff viewer — shown in the bottom panel "GIT DIFF" tab.
@@ -3489,45 +5587,7 @@ fn git_diff_view(state: IdeState) -> impl IntoView {
         })
     });
 
-    let diff_scroll = scroll(
-        dyn_stack(
-            move || {
-                safe_get(diff_lines, Vec::new())
-                    .into_iter()
-                    .enumerate()
-                    .collect::<Vec<_>>()
-            },
-            |(i, _)| *i,
-            move |(_, (text, kind)): (usize, (String, u8))| {
-                let pal = &theme.get().palette;
-                let color = match kind {
-                    1 => pal.diff_added_fg,
-                    2 => pal.diff_removed_fg,
-                    3 => pal.diff_header_fg,
-                    _ => pal.text_secondary,
-                };
-                let bg = match kind {
-                    1 => pal.diff_added_bg,
-                    2 => pal.diff_removed_bg,
-                    3 => pal.diff_header_bg,
-                    _ => floem::peniko::Color::TRANSPARENT,
-                };
-                container(label(move || text.clone()).style(move |s| {
-                    s.font_size(12.0)
-                        .color(color)
-                        .font_family("JetBrains Mono, Fira Code, monospace".to_string())
-                }))
-                .style(move |s| {
-                    s.width_full()
-                        .padding_horiz(8.0)
-                        .padding_vert(1.0)
-                        .background(bg)
-                })
-            },
-        )
-        .style(|s| s.flex_col().width_full()),
-    )
-    .style(move |s| {
+    let diff_scroll = scroll(diff_lines_view(theme, diff_lines)).style(move |s| {
         s.flex_grow(1.0)
             .width_full()
             .apply_if(diff_lines.get().is_empty(), |s| {
@@ -3538,6 +5598,50 @@ fn git_diff_view(state: IdeState) -> impl IntoView {
     stack((empty_msg, diff_scroll)).style(|s| s.flex_col().width_full().height_full())
 }
 
+/// Diff lines colored by kind (see [`parse_diff_output`]), one row each.
+/// Shared by the GIT DIFF tab and the commit detail pane of GIT HISTORY.
+pub(crate) fn diff_lines_view(
+    theme: RwSignal<PhazeTheme>,
+    diff_lines: RwSignal<Vec<(String, u8)>>,
+) -> impl IntoView {
+    dyn_stack(
+        move || {
+            safe_get(diff_lines, Vec::new())
+                .into_iter()
+                .enumerate()
+                .collect::<Vec<_>>()
+        },
+        |(i, _)| *i,
+        move |(_, (text, kind)): (usize, (String, u8))| {
+            let pal = &theme.get().palette;
+            let color = match kind {
+                1 => pal.diff_added_fg,
+                2 => pal.diff_removed_fg,
+                3 => pal.diff_header_fg,
+                _ => pal.text_secondary,
+            };
+            let bg = match kind {
+                1 => pal.diff_added_bg,
+                2 => pal.diff_removed_bg,
+                3 => pal.diff_header_bg,
+                _ => floem::peniko::Color::TRANSPARENT,
+            };
+            container(label(move || text.clone()).style(move |s| {
+                s.font_size(12.0)
+                    .color(color)
+                    .font_family("JetBrains Mono, Fira Code, monospace".to_string())
+            }))
+            .style(move |s| {
+                s.width_full()
+                    .padding_horiz(8.0)
+                    .padding_vert(1.0)
+                    .background(bg)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full())
+}
+
 /// Run `git diff HEAD -- <path>` and return colored diff lines.
 fn run_git_diff(path: &std::path::Path) -> Vec<(String, u8)> {
     let dir = path.parent().unwrap_or(path);
@@ -3569,7 +5673,8 @@ fn run_git_diff(path: &std::path::Path) -> Vec<(String, u8)> {
     }
 }
 
-fn parse_diff_output(text: &str) -> Vec<(String, u8)> {
+/// Split a diff into lines tagged 0=context, 1=added, 2=removed, 3=header.
+pub(crate) fn parse_diff_output(text: &str) -> Vec<(String, u8)> {
     text.lines()
         .map(|line| {
             let kind = if line.starts_with('+') && !line.starts_with("+++") {
@@ -3589,32 +5694,123 @@ fn parse_diff_output(text: &str) -> Vec<(String, u8)> {
 fn bottom_panel(state: IdeState) -> impl IntoView {
     let current_tab = state.bottom_panel_tab;
     let maximized = state.bottom_panel_maximized;
+    // Floem stacks hold at most 16 views, so the tabs and panels are split in
+    // groups; a group of panels is hidden unless it has the current tab.
+    const FIRST_PANELS: [Tab; 11] = [
+        Tab::Terminal,
+        Tab::Problems,
+        Tab::References,
+        Tab::GitDiff,
+        Tab::GitHistory,
+        Tab::Rebase,
+        Tab::Jobs,
+        Tab::Todos,
+        Tab::Tasks,
+        Tab::Board,
+        Tab::Compare,
+    ];
+    const LATER_PANELS: [Tab; 14] = [
+        Tab::Preview,
+        Tab::Http,
+        Tab::Database,
+        Tab::Env,
+        Tab::LanguageServers,
+        Tab::ModuleGraph,
+        Tab::Metrics,
+        Tab::Audit,
+        Tab::Profile,
+        Tab::Bench,
+        Tab::Memory,
+        Tab::Output,
+        Tab::DebugConsole,
+        Tab::Ports,
+    ];
+    let panel_group = move |s: floem::style::Style, tabs: &[Tab]| {
+        s.width_full()
+            .height_full()
+            .apply_if(!tabs.contains(&current_tab.get()), |s| {
+                s.display(floem::style::Display::None)
+            })
+    };
 
     container(
         stack((
             // Tab bar — double-click to maximize/restore
             stack((
-                bottom_panel_tab("TERMINAL", Tab::Terminal, state.clone()),
-                bottom_panel_tab_dyn(
-                    {
-                        let diags = state.diagnostics;
-                        move || {
-                            let n = diags.get().len();
-                            if n == 0 {
-                                "PROBLEMS".to_string()
-                            } else {
-                                format!("PROBLEMS ({})", n)
+                stack((
+                    bottom_panel_tab("TERMINAL", Tab::Terminal, state.clone()),
+                    bottom_panel_tab_dyn(
+                        {
+                            let diags = state.diagnostics;
+                            move || {
+                                let n = diags.get().len();
+                                if n == 0 {
+                                    "PROBLEMS".to_string()
+                                } else {
+                                    format!("PROBLEMS ({})", n)
+                                }
                             }
-                        }
-                    },
-                    Tab::Problems,
-                    state.clone(),
-                ),
-                bottom_panel_tab("REFERENCES", Tab::References, state.clone()),
-                bottom_panel_tab("GIT DIFF", Tab::GitDiff, state.clone()),
-                bottom_panel_tab("OUTPUT", Tab::Output, state.clone()),
-                bottom_panel_tab("DEBUG CONSOLE", Tab::DebugConsole, state.clone()),
-                bottom_panel_tab("PORTS", Tab::Ports, state.clone()),
+                        },
+                        Tab::Problems,
+                        state.clone(),
+                    ),
+                    bottom_panel_tab("REFERENCES", Tab::References, state.clone()),
+                    bottom_panel_tab("GIT DIFF", Tab::GitDiff, state.clone()),
+                    bottom_panel_tab("GIT HISTORY", Tab::GitHistory, state.clone()),
+                    bottom_panel_tab("REBASE", Tab::Rebase, state.clone()),
+                    bottom_panel_tab_dyn(
+                        {
+                            let jobs = state.jobs;
+                            move || jobs_tab_label(jobs)
+                        },
+                        Tab::Jobs,
+                        state.clone(),
+                    ),
+                    bottom_panel_tab_dyn(
+                        {
+                            let todos = state.todos;
+                            move || todos_tab_label(todos)
+                        },
+                        Tab::Todos,
+                        state.clone(),
+                    ),
+                    bottom_panel_tab_dyn(
+                        {
+                            let plan = state.plan_tasks;
+                            move || tasks_tab_label(plan)
+                        },
+                        Tab::Tasks,
+                        state.clone(),
+                    ),
+                    bottom_panel_tab("COMPARE", Tab::Compare, state.clone()),
+                ))
+                .style(|s| s.items_center().gap(16.0)),
+                stack((
+                    bottom_panel_tab("BOARD", Tab::Board, state.clone()),
+                    #[cfg(feature = "webview")]
+                    bottom_panel_tab("PREVIEW", Tab::Preview, state.clone()),
+                    bottom_panel_tab("HTTP", Tab::Http, state.clone()),
+                    bottom_panel_tab("DB", Tab::Database, state.clone()),
+                    bottom_panel_tab("ENV", Tab::Env, state.clone()),
+                    bottom_panel_tab_dyn(
+                        {
+                            let status = state.lsp_status;
+                            move || language_servers_tab_label(status)
+                        },
+                        Tab::LanguageServers,
+                        state.clone(),
+                    ),
+                    bottom_panel_tab("MODULE GRAPH", Tab::ModuleGraph, state.clone()),
+                    bottom_panel_tab("METRICS", Tab::Metrics, state.clone()),
+                    bottom_panel_tab("AUDIT", Tab::Audit, state.clone()),
+                    bottom_panel_tab("PROFILE", Tab::Profile, state.clone()),
+                    bottom_panel_tab("BENCH", Tab::Bench, state.clone()),
+                    bottom_panel_tab("MEMORY", Tab::Memory, state.clone()),
+                    bottom_panel_tab("OUTPUT", Tab::Output, state.clone()),
+                    bottom_panel_tab("DEBUG CONSOLE", Tab::DebugConsole, state.clone()),
+                    bottom_panel_tab("PORTS", Tab::Ports, state.clone()),
+                ))
+                .style(|s| s.items_center().gap(16.0)),
                 // Close button
                 phaze_icon(icons::CLOSE, 12.0, move |p| p.text_muted, state.theme)
                     .style(move |s| {
@@ -3647,60 +5843,200 @@ fn bottom_panel(state: IdeState) -> impl IntoView {
             }),
             // Content
             stack((
-                container(terminal_panel(
-                    state.theme,
-                    state.as_global_command_state(),
-                    state.run_in_terminal_text,
+                stack((
+                    container(terminal_panel(
+                        state.theme,
+                        state.as_global_command_state(),
+                        state.run_in_terminal_text,
+                        state.chat_run_request,
+                        state.chat_run_output,
+                        state.command_hover.clone(),
+                        state.terminal_font_family,
+                        state.terminal_font_size,
+                        LinkOpener::new(state.open_file, state.goto_line, state.workspace_root),
+                        state.workspace_root,
+                    ))
+                    .style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Terminal, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(problems_view(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Problems, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(references_view(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::References, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(git_diff_view(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::GitDiff, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(git_history_view(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::GitHistory, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(git_rebase_view(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Rebase, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(jobs_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Jobs, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(todos_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Todos, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(tasks_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Tasks, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(board_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Board, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(compare_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Compare, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
                 ))
-                .style(move |s| {
-                    s.width_full()
-                        .height_full()
-                        .apply_if(current_tab.get() != Tab::Terminal, |s| {
-                            s.display(floem::style::Display::None)
-                        })
-                }),
-                container(problems_view(state.clone())).style(move |s| {
-                    s.width_full()
-                        .height_full()
-                        .apply_if(current_tab.get() != Tab::Problems, |s| {
-                            s.display(floem::style::Display::None)
-                        })
-                }),
-                container(references_view(state.clone())).style(move |s| {
-                    s.width_full()
-                        .height_full()
-                        .apply_if(current_tab.get() != Tab::References, |s| {
-                            s.display(floem::style::Display::None)
-                        })
-                }),
-                container(git_diff_view(state.clone())).style(move |s| {
-                    s.width_full()
-                        .height_full()
-                        .apply_if(current_tab.get() != Tab::GitDiff, |s| {
-                            s.display(floem::style::Display::None)
-                        })
-                }),
-                container(output_view(state.clone())).style(move |s| {
-                    s.width_full()
-                        .height_full()
-                        .apply_if(current_tab.get() != Tab::Output, |s| {
-                            s.display(floem::style::Display::None)
-                        })
-                }),
-                container(debug_console_view(state.clone())).style(move |s| {
-                    s.width_full()
-                        .height_full()
-                        .apply_if(current_tab.get() != Tab::DebugConsole, |s| {
-                            s.display(floem::style::Display::None)
-                        })
-                }),
-                container(ports_view(state.clone())).style(move |s| {
-                    s.width_full()
-                        .height_full()
-                        .apply_if(current_tab.get() != Tab::Ports, |s| {
-                            s.display(floem::style::Display::None)
-                        })
-                }),
+                .style(move |s| panel_group(s, &FIRST_PANELS)),
+                stack((
+                    #[cfg(feature = "webview")]
+                    container(preview_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Preview, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(http_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Http, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(database_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Database, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(env_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Env, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(language_servers_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::LanguageServers, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(module_graph_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::ModuleGraph, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(metrics_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Metrics, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(audit_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Audit, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(profile_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Profile, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(bench_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Bench, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(memory_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Memory, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(output_panel(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Output, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(debug_console_view(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::DebugConsole, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                    container(ports_view(state.clone())).style(move |s| {
+                        s.width_full()
+                            .height_full()
+                            .apply_if(current_tab.get() != Tab::Ports, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                ))
+                .style(move |s| panel_group(s, &LATER_PANELS)),
             ))
             .style(|s| s.flex_grow(1.0).width_full()),
         ))
@@ -4461,40 +6797,6 @@ fn sig_help_overlay(state: IdeState) -> impl IntoView {
         .on_click_stop(move |_| sig_help.set(None))
 }
 
-// ── Toast notification overlay ────────────────────────────────────────────────
-
-fn toast_overlay(state: IdeState) -> impl IntoView {
-    let toast = state.status_toast;
-    let theme = state.theme;
-
-    container(
-        label(move || toast.get().unwrap_or_default()).style(move |s| {
-            let p = theme.get().palette;
-            s.font_size(13.0).color(p.text_primary)
-        }),
-    )
-    .style(move |s| {
-        let shown = toast.get().is_some();
-        let p = theme.get().palette;
-        s.absolute()
-            .inset_bottom(40.0)
-            .inset_left(320.0)
-            .z_index(ui_const::Z_TOAST)
-            .padding_horiz(18.0)
-            .padding_vert(10.0)
-            .background(p.bg_elevated)
-            .border_radius(8.0)
-            .border(1.0)
-            .border_color(p.border)
-            .box_shadow_h_offset(0.0)
-            .box_shadow_v_offset(4.0)
-            .box_shadow_blur(20.0)
-            .box_shadow_color(p.glow)
-            .box_shadow_spread(0.0)
-            .apply_if(!shown, |s| s.display(floem::style::Display::None))
-    })
-}
-
 // ── Workspace Symbols overlay (Ctrl+T) ───────────────────────────────────────
 // Shows a fuzzy-searchable list of symbols across the whole workspace,
 // provided by the LSP workspace/symbol request or a ripgrep fallback.
@@ -4983,113 +7285,81 @@ fn goto_overlay(state: IdeState) -> impl IntoView {
         .on_click_stop(move |_| open.set(false))
 }
 
-// ── Peek Definition overlay (Alt+F12) ────────────────────────────────────────
-
-fn peek_def_overlay(state: IdeState) -> impl IntoView {
-    let open = state.peek_def_open;
-    let lines = state.peek_def_lines;
-    let theme = state.theme;
-
-    let close_btn = container(label(|| "  Close  "))
-        .style(move |s| {
-            let p = theme.get().palette;
-            s.padding_horiz(10.0)
-                .padding_vert(4.0)
-                .border_radius(4.0)
-                .background(p.bg_elevated)
-                .color(p.text_secondary)
-                .font_size(11.0)
-                .cursor(floem::style::CursorStyle::Pointer)
-                .border(1.0)
-                .border_color(p.glass_border)
-        })
-        .on_click_stop(move |_| {
-            open.set(false);
-            lines.set(vec![]);
+fn ide_root(state: IdeState) -> impl IntoView {
+    // Clicking a code lens runs or debugs in the terminal, lists references
+    // or implementations, or asks the AI to explain the function.
+    let code_lens_clicked = create_rw_signal(None::<CodeLensEntry>);
+    {
+        let state = state.clone();
+        create_effect(move |_| {
+            let Some(entry) = code_lens_clicked.get() else {
+                return;
+            };
+            match entry.action {
+                LensAction::Run(command) | LensAction::Debug(command) => {
+                    state.run_in_terminal_text.set(Some(command));
+                    state.show_bottom_panel.set(true);
+                    state.bottom_panel_tab.set(Tab::Terminal);
+                }
+                LensAction::References(at) => {
+                    let _ = state.lsp_cmd.send(LspCommand::RequestReferences {
+                        path: entry.path,
+                        line: at.line,
+                        col: at.character,
+                    });
+                    state.references_visible.set(true);
+                    state.show_bottom_panel.set(true);
+                    state.bottom_panel_tab.set(Tab::References);
+                }
+                LensAction::Implementations(at) => {
+                    let _ = state.lsp_cmd.send(LspCommand::RequestImplementation {
+                        path: entry.path,
+                        line: at.line,
+                        col: at.character,
+                    });
+                }
+                LensAction::Explain {
+                    symbol,
+                    start_line,
+                    end_line,
+                } => {
+                    let fname = entry
+                        .path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "file".to_string());
+                    state.pending_chat_inject.set(Some(format!(
+                        "Explain the function `{symbol}` at lines {}-{} in {fname}",
+                        start_line + 1,
+                        end_line + 1
+                    )));
+                    state.show_right_panel.set(true);
+                }
+                LensAction::None => {}
+            }
         });
+    }
 
-    let header = stack((
-        label(|| "Peek Definition").style(move |s| {
-            s.font_size(12.0)
-                .color(theme.get().palette.text_primary)
-                .flex_grow(1.0)
-        }),
-        close_btn,
-    ))
-    .style(move |s| {
-        let p = theme.get().palette;
-        s.flex_row()
-            .items_center()
-            .padding_horiz(12.0)
-            .padding_vert(6.0)
-            .border_bottom(1.0)
-            .border_color(p.glass_border)
-    });
-
-    let content = scroll(
-        dyn_stack(
-            move || {
-                safe_get(lines, Vec::new())
-                    .into_iter()
-                    .enumerate()
-                    .collect::<Vec<_>>()
-            },
-            |(i, _)| *i,
-            move |(_, line_text)| {
-                let is_highlight = line_text.starts_with('>');
-                let text_val = line_text.clone();
-                container(label(move || text_val.clone())).style(move |s| {
-                    let p = theme.get().palette;
-                    s.font_family("monospace".to_string())
-                        .font_size(12.0)
-                        .color(if is_highlight {
-                            p.text_primary
-                        } else {
-                            p.text_secondary
-                        })
-                        .background(if is_highlight {
-                            p.accent_dim
-                        } else {
-                            floem::peniko::Color::TRANSPARENT
-                        })
-                        .padding_horiz(12.0)
-                        .padding_vert(1.0)
-                        .width_full()
-                })
-            },
-        )
-        .style(|s| s.flex_col().width_full()),
-    )
-    .style(|s| s.max_height(280.0).width_full());
-
-    let popup = stack((header, content)).style(move |s| {
-        let p = theme.get().palette;
-        s.flex_col()
-            .width(560.0)
-            .background(p.bg_panel)
-            .border(1.5)
-            .border_color(p.glass_border)
-            .border_radius(8.0)
-    });
-
-    container(popup)
-        .style(move |s| {
-            let shown = open.get() && !lines.get().is_empty();
-            s.absolute()
-                .inset(0)
-                .items_center()
-                .justify_center()
-                .z_index(ui_const::Z_PEEK_DEF)
-                .background(state.theme.get().palette.overlay_bg_light)
-                .apply_if(!shown, |s| s.display(floem::style::Display::None))
-        })
-        .on_click_stop(move |_| open.set(false))
-}
+    // "Open to the Side" in the peek widget opens its location in the split
+    // pane (1-based line) and closes the peek.
+    let peek_open_to_side = create_rw_signal(None::<(PathBuf, u32)>);
+    {
+        let state = state.clone();
+        create_effect(move |_| {
+            let Some((path, line)) = peek_open_to_side.get() else {
+                return;
+            };
+            state.split_editor.set(true);
+            state.split_open_file.set(Some(path));
+            state.split_goto_line.set(line);
+            state.peek.set(None);
+        });
+    }
 
-fn ide_root(state: IdeState) -> impl IntoView {
     let raw_editor = editor_panel(
         state.open_file,
         state.theme,
+        state.syntax_theme,
         state.ai_thinking,
         state.lsp_cmd.clone(),
         state.active_cursor,
@@ -5104,6 +7374,9 @@ fn ide_root(state: IdeState) -> impl IntoView {
         state.auto_save,
         state.workspace_root.get_untracked(),
         state.font_size,
+        state.font_family,
+        state.font_ligatures,
+        state.line_height,
         state.word_wrap,
         state.ctrl_d_nonce,
         state.fold_nonce,
@@ -5129,6 +7402,8 @@ fn ide_root(state: IdeState) -> impl IntoView {
         state.yank_ring,
         state.tab_size,
         state.line_ending,
+        state.active_indent,
+        state.bookmarks,
         state.folding_ranges,
         state.transform_title_nonce,
         state.format_selection_nonce,
@@ -5137,21 +7412,41 @@ fn ide_root(state: IdeState) -> impl IntoView {
         state.unfold_all_nonce,
         state.code_lens,
         state.code_lens_visible,
+        code_lens_clicked,
+        state.document_highlights,
+        state.peek,
+        peek_open_to_side,
         state.organize_imports_on_save,
         state.inlay_hints_sig,
         state.inlay_hints_toggle,
+        state.inline_blame,
+        state.blame_gutter,
+        state.find_similar_nonce,
+        state.similar_code_query,
+        state.goto_line_end,
+        state.extract_tasks_nonce,
+        state.extract_tasks_from,
+        state.normalize_line_endings_on_save,
+        state.active_encoding,
+        state.file_format_cmd,
+        state.rulers,
+        state.fold_level,
+        state.saved_folds,
+        state.buffer_cmd,
+        state.comparison,
     );
 
     // ── Split editor (Ctrl+Alt+\) — second independent editor pane ──────────
     let split_raw = editor_panel(
         state.split_open_file,
         state.theme,
+        state.syntax_theme,
         state.ai_thinking,
         state.lsp_cmd.clone(),
         state.split_active_cursor,
         state.pending_completion,
         state.diagnostics,
-        create_rw_signal(0u32), // independent goto_line for split pane
+        state.split_goto_line,  // independent goto_line for split pane
         create_rw_signal(0u64), // independent comment nonce
         vec![],                 // no session restore for split pane
         state.split_open_tabs,
@@ -5160,6 +7455,9 @@ fn ide_root(state: IdeState) -> impl IntoView {
         state.auto_save,
         state.workspace_root.get_untracked(),
         state.font_size,
+        state.font_family,
+        state.font_ligatures,
+        state.line_height,
         state.word_wrap,
         create_rw_signal(0u64),          // ctrl_d
         create_rw_signal(0u64),          // fold
@@ -5185,6 +7483,8 @@ fn ide_root(state: IdeState) -> impl IntoView {
         create_rw_signal(Vec::<String>::new()),     // yank_ring
         state.tab_size,                             // tab_size
         state.line_ending,                          // line_ending_out
+        create_rw_signal(None),                     // indent_out (split pane)
+        state.bookmarks,                            // bookmarks
         create_rw_signal(Vec::<(u32, u32)>::new()), // lsp_folding_ranges (split pane)
         create_rw_signal(0u64),                     // transform_title_nonce
         create_rw_signal(0u64),                     // format_selection_nonce
@@ -5193,9 +7493,28 @@ fn ide_root(state: IdeState) -> impl IntoView {
         create_rw_signal(0u64),                     // unfold_all_nonce
         create_rw_signal(vec![]),                   // code_lens_sig
         create_rw_signal(true),                     // code_lens_visible
+        create_rw_signal(None),                     // code_lens_clicked
+        state.document_highlights,                  // shared; answers carry their path
+        create_rw_signal(None),                     // peek (main pane only)
+        create_rw_signal(None),                     // peek_open_to_side
         create_rw_signal(false),                    // organize_imports_on_save
         create_rw_signal(vec![]),                   // inlay_hints_sig
         create_rw_signal(false),                    // inlay_hints_toggle
+        state.inline_blame,
+        state.blame_gutter,
+        create_rw_signal(0u64), // find_similar_nonce (main pane only)
+        create_rw_signal(None), // similar_code_out
+        create_rw_signal(0u32), // goto_line_end
+        create_rw_signal(0u64), // extract_tasks_nonce (main pane only)
+        create_rw_signal(None), // extract_tasks_out
+        state.normalize_line_endings_on_save,
+        state.active_encoding,
+        create_rw_signal(None), // file_format_cmd (main pane only)
+        state.rulers,
+        create_rw_signal(None), // fold_level (main pane only)
+        state.saved_folds,
+        create_rw_signal(None), // buffer_cmd (main pane only)
+        state.comparison,
     );
     let split_pane = container(split_raw).style(move |s| {
         s.flex_grow(1.0)
@@ -5230,11 +7549,11 @@ fn ide_root(state: IdeState) -> impl IntoView {
                         let s6 = s.clone();
                         let s7 = s.clone();
                         let menu = Menu::new("")
-                            .entry(MenuItem::new("Copy").action(move || {
+                            .entry(MenuItem::new(tr("Copy")).action(move || {
                                 // Trigger system copy (editor handles it internally on Ctrl+C)
                                 // Best effort: nothing to do here without editor handle
                             }))
-                            .entry(MenuItem::new("Paste").action(move || {
+                            .entry(MenuItem::new(tr("Paste")).action(move || {
                                 // Paste from clipboard into editor
                                 if let Ok(mut cb) = arboard::Clipboard::new() {
                                     if let Ok(text) = cb.get_text() {
@@ -5243,7 +7562,7 @@ fn ide_root(state: IdeState) -> impl IntoView {
                                 }
                             }))
                             .separator()
-                            .entry(MenuItem::new("Go to Definition\tF12").action(move || {
+                            .entry(MenuItem::new(tr("Go to Definition\tF12")).action(move || {
                                 if let Some((path, line, col)) = s3.active_cursor.get() {
                                     let _ = s3.lsp_cmd.send(LspCommand::RequestDefinition {
                                         path,
@@ -5252,23 +7571,21 @@ fn ide_root(state: IdeState) -> impl IntoView {
                                     });
                                 }
                             }))
-                            .entry(MenuItem::new("Find All References\tShift+F12").action(
-                                move || {
-                                    if let Some((path, line, col)) = s4.active_cursor.get() {
-                                        let _ = s4.lsp_cmd.send(LspCommand::RequestReferences {
-                                            path,
-                                            line,
-                                            col,
-                                        });
-                                        s4.show_bottom_panel.set(true);
-                                        s4.bottom_panel_tab.set(Tab::References);
-                                    }
-                                },
-                            ))
-                            .entry(MenuItem::new("Rename Symbol\tF2").action(move || {
+                            .entry(MenuItem::new(tr("Find All References")).action(move || {
+                                if let Some((path, line, col)) = s4.active_cursor.get() {
+                                    let _ = s4.lsp_cmd.send(LspCommand::RequestReferences {
+                                        path,
+                                        line,
+                                        col,
+                                    });
+                                    s4.show_bottom_panel.set(true);
+                                    s4.bottom_panel_tab.set(Tab::References);
+                                }
+                            }))
+                            .entry(MenuItem::new(tr("Rename Symbol\tF2")).action(move || {
                                 s5.rename_open.set(true);
                             }))
-                            .entry(MenuItem::new("Code Actions\tCtrl+.").action(move || {
+                            .entry(MenuItem::new(tr("Code Actions\tCtrl+.")).action(move || {
                                 if let Some((path, line, col)) = s6.active_cursor.get() {
                                     let _ = s6.lsp_cmd.send(LspCommand::RequestCodeActions {
                                         path,
@@ -5278,18 +7595,20 @@ fn ide_root(state: IdeState) -> impl IntoView {
                                 }
                             }))
                             .separator()
-                            .entry(MenuItem::new("Toggle Comment\tCtrl+/").action(move || {
+                            .entry(MenuItem::new(tr("Toggle Comment\tCtrl+/")).action(move || {
                                 s7.comment_toggle_nonce.update(|v| *v += 1);
                             }));
                         // AI-powered context menu items
                         let s_explain = s.clone();
                         let s_tests = s.clone();
                         let s_fix = s.clone();
+                        let s_similar = s.clone();
+                        let s_tasks = s.clone();
                         let s_run = s.clone();
                         let s_run_file = s.clone();
                         let menu = menu
                             .separator()
-                            .entry(MenuItem::new("🤖 Explain Selection").action(move || {
+                            .entry(MenuItem::new(tr("🤖 Explain Selection")).action(move || {
                                 if let Some((ref path, line, _)) = s_explain.active_cursor.get() {
                                     let fname = path
                                         .file_name()
@@ -5303,7 +7622,7 @@ fn ide_root(state: IdeState) -> impl IntoView {
                                     s_explain.show_right_panel.set(true);
                                 }
                             }))
-                            .entry(MenuItem::new("🧪 Generate Tests").action(move || {
+                            .entry(MenuItem::new(tr("🧪 Generate Tests")).action(move || {
                                 if let Some((ref path, line, _)) = s_tests.active_cursor.get() {
                                     let fname = path
                                         .file_name()
@@ -5317,7 +7636,7 @@ fn ide_root(state: IdeState) -> impl IntoView {
                                     s_tests.show_right_panel.set(true);
                                 }
                             }))
-                            .entry(MenuItem::new("🔧 Fix with AI").action(move || {
+                            .entry(MenuItem::new(tr("🔧 Fix with AI")).action(move || {
                                 if let Some((ref path, line, _)) = s_fix.active_cursor.get() {
                                     let diags = s_fix.diagnostics.get();
                                     let cur_diag = diags
@@ -5335,11 +7654,19 @@ fn ide_root(state: IdeState) -> impl IntoView {
                                         );
                                     }
                                 }
-                            }));
+                            }))
+                            .entry(MenuItem::new(tr("🔍 Find Similar Code")).action(move || {
+                                find_similar_code(s_similar.clone());
+                            }))
+                            .entry(MenuItem::new(tr("📋 Extract Tasks from Selection")).action(
+                                move || {
+                                    extract_tasks_from_selection(s_tasks.clone());
+                                },
+                            ));
                         // Run in Terminal / Run File entries
                         let menu = menu
                             .separator()
-                            .entry(MenuItem::new("Run in Terminal").action(move || {
+                            .entry(MenuItem::new(tr("Run in Terminal")).action(move || {
                                 // Send selected text (from clipboard) to the active terminal.
                                 // If nothing is in the clipboard, send a placeholder.
                                 let text = if let Ok(mut cb) = arboard::Clipboard::new() {
@@ -5355,7 +7682,7 @@ fn ide_root(state: IdeState) -> impl IntoView {
                                     s_run.bottom_panel_tab.set(Tab::Terminal);
                                 }
                             }))
-                            .entry(MenuItem::new("Run File").action(move || {
+                            .entry(MenuItem::new(tr("Run File")).action(move || {
                                 // Build a shell command based on the active file's extension.
                                 if let Some(ref path) = s_run_file.open_file.get() {
                                     let ext =
@@ -5387,6 +7714,28 @@ fn ide_root(state: IdeState) -> impl IntoView {
         state.ai_thinking,
         state.pending_chat_inject,
         state.workspace_root,
+        state.chat_font_family,
+        state.chat_font_size,
+        state.font_ligatures,
+        state.chat_run_request,
+        state.chat_run_output,
+        state.show_bottom_panel,
+        state.bottom_panel_tab,
+        state.command_hover.clone(),
+        state.ai_provider,
+        state.ai_model,
+        state.todos,
+        state.output,
+        state.open_file,
+        state.goto_line,
+        state.goto_line_end,
+        state.extract_tasks_from,
+        state.open_conversation,
+        state.chat_images,
+        state.file_picker_files,
+        state.file_picker_unindexed,
+        state.open_tabs,
+        state.buffer_cmd,
     );
 
     let chat_wrap = container(chat).style(move |s| {
@@ -5453,6 +7802,7 @@ fn ide_root(state: IdeState) -> impl IntoView {
     let down_raw = editor_panel(
         state.split_down_file,
         state.theme,
+        state.syntax_theme,
         state.ai_thinking,
         state.lsp_cmd.clone(),
         state.split_down_cursor,
@@ -5467,6 +7817,9 @@ fn ide_root(state: IdeState) -> impl IntoView {
         state.auto_save,
         state.workspace_root.get_untracked(),
         state.font_size,
+        state.font_family,
+        state.font_ligatures,
+        state.line_height,
         state.word_wrap,
         create_rw_signal(0u64),
         create_rw_signal(0u64),
@@ -5492,6 +7845,8 @@ fn ide_root(state: IdeState) -> impl IntoView {
         create_rw_signal(Vec::<String>::new()),     // yank_ring
         state.tab_size,                             // tab_size
         state.line_ending,                          // line_ending_out
+        create_rw_signal(None),                     // indent_out (split pane)
+        state.bookmarks,                            // bookmarks
         create_rw_signal(Vec::<(u32, u32)>::new()), // lsp_folding_ranges (down pane)
         create_rw_signal(0u64),                     // transform_title_nonce
         create_rw_signal(0u64),                     // format_selection_nonce
@@ -5500,9 +7855,28 @@ fn ide_root(state: IdeState) -> impl IntoView {
         create_rw_signal(0u64),                     // unfold_all_nonce
         create_rw_signal(vec![]),                   // code_lens_sig
         create_rw_signal(true),                     // code_lens_visible
+        create_rw_signal(None),                     // code_lens_clicked
+        state.document_highlights,                  // shared; answers carry their path
+        create_rw_signal(None),                     // peek (main pane only)
+        create_rw_signal(None),                     // peek_open_to_side
         create_rw_signal(false),                    // organize_imports_on_save
         create_rw_signal(vec![]),                   // inlay_hints_sig
         create_rw_signal(false),                    // inlay_hints_toggle
+        state.inline_blame,
+        state.blame_gutter,
+        create_rw_signal(0u64), // find_similar_nonce (main pane only)
+        create_rw_signal(None), // similar_code_out
+        create_rw_signal(0u32), // goto_line_end
+        create_rw_signal(0u64), // extract_tasks_nonce (main pane only)
+        create_rw_signal(None), // extract_tasks_out
+        state.normalize_line_endings_on_save,
+        state.active_encoding,
+        create_rw_signal(None), // file_format_cmd (main pane only)
+        state.rulers,
+        create_rw_signal(None), // fold_level (main pane only)
+        state.saved_folds,
+        create_rw_signal(None), // buffer_cmd (main pane only)
+        state.comparison,
     );
     let down_pane = container(down_raw).style(move |s| {
         s.flex_grow(1.0)
@@ -5572,13 +7946,101 @@ fn ide_root(state: IdeState) -> impl IntoView {
 
 // ─── Menu Bar ─────────────────────────────────────────────────────────────────
 
+/// The run configuration dropdown and the button that runs or stops it, at
+/// the right end of the menu bar.
+fn run_config_picker(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let selected = state.run_config;
+    let running = state.run_configs.running;
+    let hovered = create_rw_signal(false);
+
+    let s = state.clone();
+    let dropdown = label(move || match selected.get() {
+        Some(name) => format!("{name} ▾"),
+        None => format!("{} ▾", tr("Run Configuration")),
+    })
+    .style(move |st| {
+        let p = theme.get().palette;
+        st.font_size(12.0)
+            .padding_horiz(8.0)
+            .height(20.0)
+            .items_center()
+            .border(1.0)
+            .border_radius(3.0)
+            .border_color(p.border)
+            .cursor(floem::style::CursorStyle::Pointer)
+            .color(p.text_secondary)
+    })
+    .on_click_stop(move |_| {
+        let configs = match RunConfigs::load(&s.workspace_root.get_untracked()) {
+            Ok(configs) => configs,
+            Err(e) => return notify(s.notifications, Severity::Error, e.to_string()),
+        };
+        let mut menu = Menu::new(tr("Run Configuration"));
+        for name in configs.names() {
+            let name = name.to_string();
+            menu = menu.entry(MenuItem::new(name.clone()).action(move || {
+                selected.set(Some(name.clone()));
+            }));
+        }
+        let s_edit = s.clone();
+        menu = menu
+            .separator()
+            .entry(
+                MenuItem::new(tr("Edit Run Configurations…")).action(move || {
+                    edit_run_configs(s_edit.clone());
+                }),
+            );
+        show_context_menu(menu, None);
+    });
+
+    let s = state.clone();
+    let button = label(move || {
+        String::from(if running.get().is_some() {
+            "■"
+        } else {
+            "▶"
+        })
+    })
+    .style(move |st| {
+        let p = theme.get().palette;
+        st.font_size(12.0)
+            .padding_horiz(8.0)
+            .height(20.0)
+            .items_center()
+            .border_radius(3.0)
+            .cursor(floem::style::CursorStyle::Pointer)
+            .color(if running.get().is_some() {
+                p.error
+            } else {
+                p.success
+            })
+            .apply_if(hovered.get(), |st| st.background(p.bg_elevated))
+    })
+    .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+        hovered.set(true);
+    })
+    .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+        hovered.set(false);
+    })
+    .on_click_stop(move |_| {
+        if running.get_untracked().is_some() {
+            s.run_configs.stop.update(|n| *n += 1);
+        } else {
+            run_selected_config(s.clone());
+        }
+    });
+
+    stack((dropdown, button)).style(|s| s.items_center().gap(4.0).padding_right(8.0))
+}
+
 /// Custom in-app menu bar (Floem's native `.window_menu()` is Linux-unsupported).
 /// Each label opens a context menu via `show_context_menu` on click.
 fn menu_bar(state: IdeState) -> impl IntoView {
     // Helper: a hoverable menu-bar label.
     let make_item = |label_text: &'static str, theme: RwSignal<PhazeTheme>| {
         let hovered = create_rw_signal(false);
-        container(label(move || label_text))
+        container(label(move || tr(label_text)))
             .style(move |sty| {
                 let t = theme.get();
                 let p = &t.palette;
@@ -5608,13 +8070,13 @@ fn menu_bar(state: IdeState) -> impl IntoView {
             let s2 = s.clone();
             let s3 = s.clone();
             let s4 = s.clone();
-            let menu = Menu::new("File")
-                .entry(MenuItem::new("Open File…\tCtrl+O").action(move || {
+            let menu = Menu::new(tr("File"))
+                .entry(MenuItem::new(tr("Open File…\tCtrl+O")).action(move || {
                     if let Some(path) = rfd::FileDialog::new().pick_file() {
                         s2.open_file.set(Some(path));
                     }
                 }))
-                .entry(MenuItem::new("Open Folder…").action(move || {
+                .entry(MenuItem::new(tr("Open Folder…")).action(move || {
                     if let Some(folder) = rfd::FileDialog::new().pick_folder() {
                         s3.workspace_root.set(folder);
                         s3.file_picker_files.set(Vec::new());
@@ -5623,7 +8085,7 @@ fn menu_bar(state: IdeState) -> impl IntoView {
                     }
                 }))
                 .separator()
-                .entry(MenuItem::new("Exit").action(move || {
+                .entry(MenuItem::new(tr("Exit")).action(move || {
                     let _ = s4.clone();
                     std::process::exit(0);
                 }));
@@ -5638,18 +8100,18 @@ fn menu_bar(state: IdeState) -> impl IntoView {
             let s2 = s.clone();
             let s3 = s.clone();
             let s4 = s.clone();
-            let menu = Menu::new("Edit")
-                .entry(MenuItem::new("Toggle Comment\tCtrl+/").action(move || {
+            let menu = Menu::new(tr("Edit"))
+                .entry(MenuItem::new(tr("Toggle Comment\tCtrl+/")).action(move || {
                     s2.comment_toggle_nonce.update(|v| *v += 1);
                 }))
                 .separator()
-                .entry(MenuItem::new("Inline AI Edit\tCtrl+K").action(move || {
+                .entry(MenuItem::new(tr("Inline AI Edit\tCtrl+K")).action(move || {
                     s3.inline_edit_open.set(true);
                     s3.inline_edit_query.set(String::new());
                 }))
                 .separator()
                 .entry(
-                    MenuItem::new("Command Palette\tCtrl+Shift+P").action(move || {
+                    MenuItem::new(tr("Command Palette\tCtrl+Shift+P")).action(move || {
                         s4.command_palette_open.set(true);
                     }),
                 );
@@ -5668,109 +8130,123 @@ fn menu_bar(state: IdeState) -> impl IntoView {
             let s_zin = s.clone();
             let s_zout = s.clone();
             // Theme submenu
-            let theme_menu = Menu::new("Theme")
-                .entry(MenuItem::new("Midnight Blue").action({
+            let theme_menu = Menu::new(tr("Theme"))
+                .entry(MenuItem::new(tr("Midnight Blue")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::MidnightBlue));
                     }
                 }))
-                .entry(MenuItem::new("Cyberpunk 2077").action({
+                .entry(MenuItem::new(tr("Cyberpunk 2077")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::Cyberpunk));
                     }
                 }))
-                .entry(MenuItem::new("Synthwave '84").action({
+                .entry(MenuItem::new(tr("Synthwave '84")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::Synthwave84));
                     }
                 }))
-                .entry(MenuItem::new("Andromeda").action({
+                .entry(MenuItem::new(tr("Andromeda")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::Andromeda));
                     }
                 }))
-                .entry(MenuItem::new("Dark").action({
+                .entry(MenuItem::new(tr("Dark")).action({
                     let s = s.clone();
                     move || {
                         s.theme.set(PhazeTheme::from_variant(ThemeVariant::Dark));
                     }
                 }))
-                .entry(MenuItem::new("Dracula").action({
+                .entry(MenuItem::new(tr("Dracula")).action({
                     let s = s.clone();
                     move || {
                         s.theme.set(PhazeTheme::from_variant(ThemeVariant::Dracula));
                     }
                 }))
-                .entry(MenuItem::new("Tokyo Night").action({
+                .entry(MenuItem::new(tr("Tokyo Night")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::TokyoNight));
                     }
                 }))
-                .entry(MenuItem::new("Monokai").action({
+                .entry(MenuItem::new(tr("Monokai")).action({
                     let s = s.clone();
                     move || {
                         s.theme.set(PhazeTheme::from_variant(ThemeVariant::Monokai));
                     }
                 }))
-                .entry(MenuItem::new("Nord Dark").action({
+                .entry(MenuItem::new(tr("Nord Dark")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::NordDark));
                     }
                 }))
-                .entry(MenuItem::new("Matrix Green").action({
+                .entry(MenuItem::new(tr("Matrix Green")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::MatrixGreen));
                     }
                 }))
-                .entry(MenuItem::new("Root Shell").action({
+                .entry(MenuItem::new(tr("Root Shell")).action({
                     let s = s.clone();
                     move || {
                         s.theme
                             .set(PhazeTheme::from_variant(ThemeVariant::RootShell));
                     }
                 }))
-                .entry(MenuItem::new("Light").action({
+                .entry(MenuItem::new(tr("Light")).action({
                     let s = s.clone();
                     move || {
                         s.theme.set(PhazeTheme::from_variant(ThemeVariant::Light));
                     }
+                }))
+                .entry(MenuItem::new(tr("High Contrast")).action({
+                    let s = s.clone();
+                    move || {
+                        s.theme
+                            .set(PhazeTheme::from_variant(ThemeVariant::HighContrast));
+                    }
+                }))
+                .entry(MenuItem::new(tr("High Contrast Light")).action({
+                    let s = s.clone();
+                    move || {
+                        s.theme
+                            .set(PhazeTheme::from_variant(ThemeVariant::HighContrastLight));
+                    }
                 }));
 
-            let menu = Menu::new("View")
-                .entry(MenuItem::new("Explorer\tCtrl+B").action(move || {
+            let menu = Menu::new(tr("View"))
+                .entry(MenuItem::new(tr("Explorer\tCtrl+B")).action(move || {
                     s_exp.show_left_panel.update(|v| *v = !*v);
                     let open = s_exp.show_left_panel.get();
                     s_exp.left_panel_width.set(if open { 260.0 } else { 0.0 });
                 }))
-                .entry(MenuItem::new("Terminal\tCtrl+J").action(move || {
+                .entry(MenuItem::new(tr("Terminal\tCtrl+J")).action(move || {
                     s_term.show_bottom_panel.update(|v| *v = !*v);
                 }))
-                .entry(MenuItem::new("AI Chat\tCtrl+\\").action(move || {
+                .entry(MenuItem::new(tr("AI Chat\tCtrl+\\")).action(move || {
                     s_chat.show_right_panel.update(|v| *v = !*v);
                 }))
                 .separator()
-                .entry(MenuItem::new("Zoom In\tCtrl+=").action(move || {
+                .entry(MenuItem::new(tr("Zoom In\tCtrl+=")).action(move || {
                     s_zin.font_size.update(|v| *v = (*v + 1).min(32));
                 }))
-                .entry(MenuItem::new("Zoom Out\tCtrl+-").action(move || {
+                .entry(MenuItem::new(tr("Zoom Out\tCtrl+-")).action(move || {
                     s_zout.font_size.update(|v| *v = v.saturating_sub(1).max(8));
                 }))
                 .separator()
-                .entry(MenuItem::new("Zen Mode\tCtrl+Shift+Z").action(move || {
+                .entry(MenuItem::new(tr("Zen Mode\tCtrl+Shift+Z")).action(move || {
                     s_zen.zen_mode.update(|v| *v = !*v);
                 }))
                 .separator()
@@ -5786,8 +8262,8 @@ fn menu_bar(state: IdeState) -> impl IntoView {
             let s_def = s.clone();
             let s_sym = s.clone();
             let s_fp = s.clone();
-            let menu = Menu::new("Go")
-                .entry(MenuItem::new("Go to Definition\tF12").action(move || {
+            let menu = Menu::new(tr("Go"))
+                .entry(MenuItem::new(tr("Go to Definition\tF12")).action(move || {
                     if let Some((path, line, col)) = s_def.active_cursor.get() {
                         let _ =
                             s_def
@@ -5795,27 +8271,26 @@ fn menu_bar(state: IdeState) -> impl IntoView {
                                 .send(LspCommand::RequestDefinition { path, line, col });
                     }
                 }))
+                .entry(MenuItem::new(tr("Find All References")).action(move || {
+                    if let Some((path, line, col)) = s_sym.active_cursor.get() {
+                        let _ =
+                            s_sym
+                                .lsp_cmd
+                                .send(LspCommand::RequestReferences { path, line, col });
+                        s_sym.references_visible.set(true);
+                        s_sym.show_bottom_panel.set(true);
+                        s_sym.bottom_panel_tab.set(Tab::References);
+                    }
+                }))
                 .entry(
-                    MenuItem::new("Find All References\tShift+F12").action(move || {
-                        if let Some((path, line, col)) = s_sym.active_cursor.get() {
-                            let _ = s_sym.lsp_cmd.send(LspCommand::RequestReferences {
-                                path,
-                                line,
-                                col,
-                            });
-                            s_sym.references_visible.set(true);
-                            s_sym.show_bottom_panel.set(true);
-                            s_sym.bottom_panel_tab.set(Tab::References);
-                        }
+                    MenuItem::new(tr("Workspace Symbols\tCtrl+T")).action(move || {
+                        s_fp.ws_syms_open.set(true);
+                        s_fp.ws_syms_query.set(String::new());
+                        let _ = s_fp.lsp_cmd.send(LspCommand::RequestWorkspaceSymbols {
+                            query: String::new(),
+                        });
                     }),
-                )
-                .entry(MenuItem::new("Workspace Symbols\tCtrl+T").action(move || {
-                    s_fp.ws_syms_open.set(true);
-                    s_fp.ws_syms_query.set(String::new());
-                    let _ = s_fp.lsp_cmd.send(LspCommand::RequestWorkspaceSymbols {
-                        query: String::new(),
-                    });
-                }));
+                );
             show_context_menu(menu, None);
         })
     };
@@ -5825,20 +8300,40 @@ fn menu_bar(state: IdeState) -> impl IntoView {
         let s = state.clone();
         make_item("Run", state.theme).on_click_stop(move |_| {
             let s_run = s.clone();
+            let s_run_build = s.clone();
+            let s_run_config = s.clone();
+            let s_rerun = s.clone();
+            let s_edit_configs = s.clone();
             let s_build = s.clone();
             let s_test = s.clone();
-            let menu = Menu::new("Run")
-                .entry(MenuItem::new("Open Terminal\tCtrl+J").action(move || {
+            let menu = Menu::new(tr("Run"))
+                .entry(MenuItem::new(tr("Open Terminal\tCtrl+J")).action(move || {
                     s_run.show_bottom_panel.set(true);
                     s_run.bottom_panel_tab.set(Tab::Terminal);
                 }))
                 .separator()
-                .entry(MenuItem::new("Show Build Output").action(move || {
-                    s_build.show_bottom_panel.set(true);
-                    s_build.bottom_panel_tab.set(Tab::Output);
+                .entry(MenuItem::new(tr("Run Build")).action(move || {
+                    show_output_channel(&s_run_build, output::BUILD);
+                    s_run_build.build_run.update(|n| *n += 1);
+                }))
+                .entry(MenuItem::new(tr("Run Configuration")).action(move || {
+                    run_selected_config(s_run_config.clone());
+                }))
+                .entry(
+                    MenuItem::new(tr("Re-run Last Configuration\tCtrl+F5")).action(move || {
+                        rerun_last_config(s_rerun.clone());
+                    }),
+                )
+                .entry(
+                    MenuItem::new(tr("Edit Run Configurations…")).action(move || {
+                        edit_run_configs(s_edit_configs.clone());
+                    }),
+                )
+                .entry(MenuItem::new(tr("Show Build Output")).action(move || {
+                    show_output_channel(&s_build, output::BUILD);
                 }))
                 .entry(
-                    MenuItem::new("Show Problems\tCtrl+Shift+M").action(move || {
+                    MenuItem::new(tr("Show Problems\tCtrl+Shift+M")).action(move || {
                         s_test.show_bottom_panel.set(true);
                         s_test.bottom_panel_tab.set(Tab::Problems);
                     }),
@@ -5852,14 +8347,14 @@ fn menu_bar(state: IdeState) -> impl IntoView {
         let s = state.clone();
         make_item("Help", state.theme).on_click_stop(move |_| {
             let s2 = s.clone();
-            let menu = Menu::new("Help")
+            let menu = Menu::new(tr("Help"))
                 .entry(
-                    MenuItem::new("Command Palette\tCtrl+Shift+P").action(move || {
+                    MenuItem::new(tr("Command Palette\tCtrl+Shift+P")).action(move || {
                         s2.command_palette_open.set(true);
                     }),
                 )
                 .separator()
-                .entry(MenuItem::new("About PhazeAI IDE").action(|| {
+                .entry(MenuItem::new(tr("About PhazeAI IDE")).action(|| {
                     rfd::MessageDialog::new()
                         .set_title("About PhazeAI IDE")
                         .set_description(format!(
@@ -5879,7 +8374,14 @@ fn menu_bar(state: IdeState) -> impl IntoView {
     // ── Bar layout ───────────────────────────────────────────────────────────
     let bar_state = state.clone();
     stack((
-        file_item, edit_item, view_item, go_item, run_item, help_item,
+        file_item,
+        edit_item,
+        view_item,
+        go_item,
+        run_item,
+        help_item,
+        empty().style(|s| s.flex_grow(1.0)),
+        run_config_picker(state.clone()),
     ))
     .style(move |s| {
         let t = bar_state.theme.get();
@@ -5899,15 +8401,53 @@ fn menu_bar(state: IdeState) -> impl IntoView {
 
 /// Launch the PhazeAI IDE.
 pub fn launch_phaze_ide() {
+    launch_phaze_ide_with(Vec::new());
+}
+
+/// Launch the PhazeAI IDE, opening `files` once the window is up.
+pub fn launch_phaze_ide_with(files: Vec<crate::instance::Target>) {
     // Anonymous telemetry — single fire-and-forget ping, no personal data
     phazeai_core::telemetry::report_launch(phazeai_core::telemetry::AppKind::Ide);
 
-    let settings = Settings::load();
+    // Nothing shown at first needs these; have them ready for the first file
+    // opened and the font picker without holding up the window.
+    startup::background("syntaxes", crate::panels::editor::preload_syntaxes);
+    startup::background("fonts", || {
+        crate::fonts::system_font_families();
+    });
+
+    let settings = startup::phase("settings", Settings::load);
+    memory::global().set_budgets(settings.memory.clone());
+    startup::phase("locale", || crate::i18n::init(&settings.editor.locale));
+    // Offer PhazeAI Cloud hosted models when signed in; apply the org policy.
+    startup::phase("cloud", phazeai_cloud::init);
 
     Application::new()
         .window(
             move |_| {
-                let state = IdeState::new(&settings);
+                let state = startup::phase("state", || IdeState::new(&settings));
+                startup::after_first_frame(startup::first_frame);
+                {
+                    let (updates, toast) = (settings.updates.clone(), state.status_toast);
+                    startup::after_first_frame(move || {
+                        crate::update::check_on_launch(updates, toast)
+                    });
+                }
+                {
+                    let (open_file, goto_line) = (state.open_file, state.goto_line);
+                    let files = files.clone();
+                    startup::after_first_frame(move || {
+                        for file in files {
+                            open_file.set(Some(file.path));
+                            if let Some(line) = file.line {
+                                goto_line.set(line.saturating_sub(1));
+                            }
+                        }
+                    });
+                }
+                if settings.editor.control_socket {
+                    crate::control::start(&state);
+                }
 
                 // Overlay layers — rendered after IDE content so they paint on top.
                 let palette = command_palette(state.clone());
@@ -5918,12 +8458,12 @@ pub fn launch_phaze_ide() {
                 let code_actions_popup = code_actions_overlay(state.clone());
                 let rename_popup = rename_overlay(state.clone());
                 let sig_help_popup = sig_help_overlay(state.clone());
-                let toast_popup = toast_overlay(state.clone());
+                let toast_popup = toast_stack(state.clone());
+                let notifications_popup = notification_drawer(state.clone());
                 let ws_syms_popup = workspace_symbols_overlay(state.clone());
                 let branch_picker_popup = branch_picker_overlay(state.clone());
                 let vim_ex_popup = vim_ex_overlay(state.clone());
                 let goto_popup = goto_overlay(state.clone());
-                let peek_def_popup = peek_def_overlay(state.clone());
 
                 // Full-window drag capture overlay — only visible while a panel
                 // resize is in progress (panel_drag_active == true).  By covering
@@ -5957,15 +8497,17 @@ pub fn launch_phaze_ide() {
                 };
 
                 // Root: cosmic canvas + menu bar + IDE + overlays (overlays use z_index)
-                let ide_with_menu = stack((menu_bar(state.clone()), ide_root(state.clone())))
-                    .style(|s| s.flex_col().width_full().height_full().padding(16.0));
+                let ide_with_menu = startup::phase("views", || {
+                    stack((menu_bar(state.clone()), ide_root(state.clone())))
+                        .style(|s| s.flex_col().width_full().height_full().padding(16.0))
+                });
 
                 // Floem stack() supports up to 16 children; nest into two groups.
                 let overlays_b = stack((
-                    peek_def_popup, // Z_PEEK_DEF(485) — peek definition (Alt+F12)
-                    vim_ex_popup,   // Z_VIM_EX(490) — vim ex command bar
-                    goto_popup,     // Z_GOTO(495) — goto line/col (Ctrl+G)
-                    drag_overlay,   // Z_DRAG_OVERLAY(50) — only shown during resize
+                    notifications_popup, // Z_NOTIFICATIONS(455) — notification history
+                    vim_ex_popup,        // Z_VIM_EX(490) — vim ex command bar
+                    goto_popup,          // Z_GOTO(495) — goto line/col (Ctrl+G)
+                    drag_overlay,        // Z_DRAG_OVERLAY(50) — only shown during resize
                 ))
                 .style(|s| {
                     s.absolute()
@@ -6003,6 +8545,62 @@ pub fn launch_phaze_ide() {
                             let shift = key_event.modifiers.contains(Modifiers::SHIFT);
                             let alt = key_event.modifiers.contains(Modifiers::ALT);
 
+                            // ── Emacs keymap ───────────────────────────────────
+                            // Runs first so its C- keys win over the defaults.
+                            if state.emacs_mode.get() {
+                                let cmd = match_emacs_key(key_event, state.emacs_prefix.get());
+                                let modifier_only = matches!(
+                                    key_event.key.logical_key,
+                                    Key::Named(
+                                        NamedKey::Control
+                                            | NamedKey::Alt
+                                            | NamedKey::Shift
+                                            | NamedKey::Meta
+                                    )
+                                );
+                                let kill_run = state.emacs_last_kill.get();
+                                if !modifier_only {
+                                    state
+                                        .emacs_last_kill
+                                        .set(cmd == Some(EmacsCommand::KillLine));
+                                }
+                                if let Some(cmd) = cmd {
+                                    state.emacs_prefix.set(cmd == EmacsCommand::Prefix);
+                                    let motion = match cmd {
+                                        EmacsCommand::Prefix => None,
+                                        EmacsCommand::Quit => {
+                                            show_toast(state.status_toast, "Quit");
+                                            None
+                                        }
+                                        EmacsCommand::LineStart => Some(VimMotion::LineStart),
+                                        EmacsCommand::LineEnd => Some(VimMotion::LineEnd),
+                                        EmacsCommand::NextLine => Some(VimMotion::Down),
+                                        EmacsCommand::PreviousLine => Some(VimMotion::Up),
+                                        EmacsCommand::ForwardWord => Some(VimMotion::WordForward),
+                                        EmacsCommand::BackwardWord => Some(VimMotion::WordBackward),
+                                        EmacsCommand::BufferStart => Some(VimMotion::GotoFileTop),
+                                        EmacsCommand::BufferEnd => Some(VimMotion::GotoFileBottom),
+                                        EmacsCommand::KillLine => {
+                                            Some(VimMotion::KillLine(kill_run))
+                                        }
+                                        EmacsCommand::Yank => Some(VimMotion::Yank),
+                                        EmacsCommand::CopyRegion => Some(VimMotion::CopySelection),
+                                        // C-s itself reached the editor's Ctrl+S handler
+                                        // first, which saved; only the prefix is consumed.
+                                        EmacsCommand::Save => None,
+                                        EmacsCommand::SwitchBuffer => {
+                                            state.file_picker_query.set(String::new());
+                                            state.file_picker_open.set(true);
+                                            None
+                                        }
+                                    };
+                                    if motion.is_some() {
+                                        state.vim_motion.set(motion);
+                                    }
+                                    return;
+                                }
+                            }
+
                             // ── Global shortcut dispatch (unified via execute_command) ──
                             if let Some(cmd) = match_global_shortcut(key_event) {
                                 execute_command(cmd, &state.as_global_command_state());
@@ -6013,9 +8611,8 @@ pub fn launch_phaze_ide() {
                             if let Key::Named(ref named) = key_event.key.logical_key {
                                 match named {
                                     floem::keyboard::NamedKey::Escape => {
-                                        if state.peek_def_open.get() {
-                                            state.peek_def_open.set(false);
-                                            state.peek_def_lines.set(vec![]);
+                                        if state.peek.get().is_some() {
+                                            state.peek.set(None);
                                             return;
                                         }
                                         if state.branch_picker_open.get() {
@@ -6116,7 +8713,7 @@ pub fn launch_phaze_ide() {
                                             return;
                                         }
                                     }
-                                    // F12 — go to definition; Shift+F12 — find all references; Alt+F12 — peek definition; Ctrl+F12 — go to implementation
+                                    // F12 — go to definition; Shift+F12 — peek references; Alt+F12 — peek definition; Ctrl+F12 — go to implementation
                                     floem::keyboard::NamedKey::F12 => {
                                         if let Some((path, line, col)) = state.active_cursor.get() {
                                             if ctrl {
@@ -6129,7 +8726,13 @@ pub fn launch_phaze_ide() {
                                                     },
                                                 );
                                             } else if shift {
-                                                // Shift+F12: find all references
+                                                // Shift+F12: peek all references; they
+                                                // also fill the References panel.
+                                                state.peek.set(Some(Peek {
+                                                    path: path.clone(),
+                                                    line,
+                                                    content: PeekContent::FindingReferences,
+                                                }));
                                                 let _ = state.lsp_cmd.send(
                                                     LspCommand::RequestReferences {
                                                         path,
@@ -6137,13 +8740,13 @@ pub fn launch_phaze_ide() {
                                                         col,
                                                     },
                                                 );
-                                                state.references_visible.set(true);
-                                                state.show_bottom_panel.set(true);
-                                                state.bottom_panel_tab.set(Tab::References);
                                             } else if alt {
                                                 // Alt+F12: peek definition
-                                                state.peek_def_lines.set(vec![]);
-                                                state.peek_def_open.set(false);
+                                                state.peek.set(Some(Peek {
+                                                    path: path.clone(),
+                                                    line,
+                                                    content: PeekContent::FindingDefinition,
+                                                }));
                                                 let _ = state.lsp_cmd.send(
                                                     LspCommand::RequestPeekDefinition {
                                                         path,
@@ -6164,6 +8767,11 @@ pub fn launch_phaze_ide() {
                                         }
                                         return;
                                     }
+                                    // Ctrl+F5 — re-run the last run configuration
+                                    floem::keyboard::NamedKey::F5 if ctrl => {
+                                        rerun_last_config(state.clone());
+                                        return;
+                                    }
                                     // F1 with Ctrl — show hover documentation
                                     floem::keyboard::NamedKey::F1 => {
                                         if ctrl {
@@ -6223,6 +8831,19 @@ pub fn launch_phaze_ide() {
                                         }
                                         return;
                                     }
+                                    // Alt+Left/Right — back / forward through the jump list
+                                    floem::keyboard::NamedKey::ArrowLeft
+                                        if alt && !ctrl && !shift =>
+                                    {
+                                        navigate_jump(&state, false);
+                                        return;
+                                    }
+                                    floem::keyboard::NamedKey::ArrowRight
+                                        if alt && !ctrl && !shift =>
+                                    {
+                                        navigate_jump(&state, true);
+                                        return;
+                                    }
                                     // Alt+Up/Down — move or duplicate line
                                     floem::keyboard::NamedKey::ArrowUp
                                         if alt && !ctrl && !shift =>
@@ -6503,6 +9124,25 @@ pub fn launch_phaze_ide() {
                                     }
                                 }
 
+                                // Ctrl+Alt+K / L / J → toggle, next, previous bookmark
+                                if ctrl && alt && !shift {
+                                    match ch.as_str() {
+                                        "k" => {
+                                            toggle_bookmark(&state);
+                                            return;
+                                        }
+                                        "l" => {
+                                            navigate_bookmark(&state, true);
+                                            return;
+                                        }
+                                        "j" => {
+                                            navigate_bookmark(&state, false);
+                                            return;
+                                        }
+                                        _ => {}
+                                    }
+                                }
+
                                 // Ctrl+Alt+Shift+D → split editor down toggle
                                 if ctrl && alt && shift && ch.as_str() == "d" {
                                     state.split_editor_down.update(|v| *v = !*v);
@@ -6739,7 +9379,13 @@ pub fn launch_phaze_ide() {
                 .on_event_stop(EventListener::WindowClosed, {
                     let state = state.clone();
                     move |_| {
-                        // Kill sidecar process cleanly on IDE exit.
+                        // Kill sidecar process cleanly on IDE exit, stopping
+                        // its supervisor first so it isn't restarted.
+                        if let Ok(guard) = state.sidecar_supervisor.lock() {
+                            if let Some(supervisor) = guard.as_ref() {
+                                supervisor.stop();
+                            }
+                        }
                         if let Ok(guard) = state.sidecar_client.lock() {
                             if let Some(client) = guard.as_ref() {
                                 // Build a small runtime just for the shutdown call.
@@ -6769,8 +9415,11 @@ pub fn launch_phaze_ide() {
                             split_editor: state.split_editor.get_untracked(),
                             split_editor_down: state.split_editor_down.get_untracked(),
                             vim_mode: state.vim_mode.get_untracked(),
+                            emacs_mode: state.emacs_mode.get_untracked(),
                             theme: state.theme.get_untracked().variant.name().to_string(),
                             zen_mode: state.zen_mode.get_untracked(),
+                            bookmarks: state.bookmark_store.get_untracked(),
+                            folds: state.saved_folds.get_untracked(),
                         });
                     }
                 })
@@ -0,0 +1,111 @@
+//! The web preview window opened by the IDE's PREVIEW tab.
+//!
+//! `phazeai-preview --url <url> [--width <w>] [--height <h>]` shows the page
+//! in a native webview and takes `PreviewCommand`s, one per line, on stdin.
+//! It quits when stdin closes, so it never outlives the IDE.
+
+use std::io::BufRead;
+
+use phazeai_ui::panels::preview::PreviewCommand;
+use tao::{
+    dpi::LogicalSize,
+    event::{Event, WindowEvent},
+    event_loop::{ControlFlow, EventLoopBuilder},
+    window::WindowBuilder,
+};
+use wry::WebViewBuilder;
+
+struct Args {
+    url: String,
+    width: u32,
+    height: u32,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        url: String::new(),
+        width: 1280,
+        height: 800,
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        let value = it.next().ok_or_else(|| format!("{flag} needs a value"))?;
+        match flag.as_str() {
+            "--url" => args.url = value,
+            "--width" => args.width = value.parse().map_err(|_| "bad --width".to_string())?,
+            "--height" => args.height = value.parse().map_err(|_| "bad --height".to_string())?,
+            _ => return Err(format!("unknown flag {flag}")),
+        }
+    }
+    if args.url.is_empty() {
+        return Err("usage: phazeai-preview --url <url> [--width <w>] [--height <h>]".into());
+    }
+    Ok(args)
+}
+
+fn title(url: &str, width: u32, height: u32) -> String {
+    format!("Preview — {url} ({width}×{height})")
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = parse_args()?;
+    let event_loop = EventLoopBuilder::<PreviewCommand>::with_user_event().build();
+    let window = WindowBuilder::new()
+        .with_title(title(&args.url, args.width, args.height))
+        .with_inner_size(LogicalSize::new(args.width, args.height))
+        .build(&event_loop)?;
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    let builder = WebViewBuilder::new(&window);
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let builder = {
+        use tao::platform::unix::WindowExtUnix;
+        use wry::WebViewBuilderExtUnix;
+        let vbox = window
+            .default_vbox()
+            .ok_or("no GTK container for the webview")?;
+        WebViewBuilder::new_gtk(vbox)
+    };
+    let webview = builder.with_url(&args.url).build()?;
+
+    let proxy = event_loop.create_proxy();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(command) = PreviewCommand::parse(&line) {
+                if proxy.send_event(command).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = proxy.send_event(PreviewCommand::Quit);
+    });
+
+    let mut url = args.url;
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Wait;
+        match event {
+            Event::UserEvent(PreviewCommand::Navigate(to)) => {
+                let _ = webview.load_url(&to);
+                url = to;
+                let size = window.inner_size().to_logical::<u32>(window.scale_factor());
+                window.set_title(&title(&url, size.width, size.height));
+            }
+            Event::UserEvent(PreviewCommand::Reload) => {
+                let _ = webview.evaluate_script("location.reload()");
+            }
+            Event::UserEvent(PreviewCommand::Resize(width, height)) => {
+                window.set_inner_size(LogicalSize::new(width, height));
+                window.set_title(&title(&url, width, height));
+            }
+            Event::UserEvent(PreviewCommand::Quit)
+            | Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => *control_flow = ControlFlow::Exit,
+            _ => {}
+        }
+    });
+}
@@ -1,4 +1,43 @@
+use phazeai_ui::instance;
+
 fn main() {
+    // Install an update staged from the IDE before anything else.
+    phazeai_ui::update::apply_staged();
+
+    let args = instance::parse_args(std::env::args().skip(1));
+    // `--profile-startup` prints how long each part of launch took.
+    phazeai_ui::startup::begin(args.profile_startup);
+
+    // Open the files in the IDE already running, unless asked for a window
+    // of its own.
+    let socket = phazeai_core::ipc::socket_path();
+    if !args.new_window && instance::running(&socket) && instance::forward(&socket, &args.targets) {
+        if args.targets.is_empty() {
+            eprintln!("PhazeAI is already running; pass --new-window for another window.");
+        }
+        return;
+    }
+    // A folder is the workspace; the rest are files to open. One named by a
+    // link is only opened once the user agrees.
+    let (folders, mut files): (Vec<_>, Vec<_>) =
+        args.targets.into_iter().partition(|t| t.path.is_dir());
+    let linked = folders.iter().any(|f| f.from_link);
+    let folders: Vec<_> = folders
+        .into_iter()
+        .filter(|f| !f.from_link || instance::confirm_linked_folder(&f.path))
+        .collect();
+    if linked && folders.is_empty() && files.is_empty() {
+        return;
+    }
+    for file in &mut files {
+        file.path = instance::absolute(&file.path);
+    }
+    if let Some(folder) = folders.first() {
+        if let Err(e) = std::env::set_current_dir(&folder.path) {
+            eprintln!("Can't open {}: {e}", folder.path.display());
+        }
+    }
+
     // The Floem view tree (~6000 lines of nested panels) exceeds the default
     // OS main thread stack. Set RLIMIT_STACK to unlimited before constructing
     // any views. This is safe on Linux — the kernel grows the stack on demand
@@ -14,5 +53,5 @@ fn main() {
             libc::setrlimit(libc::RLIMIT_STACK, &unlimited);
         }
     }
-    phazeai_ui::launch_phaze_ide();
+    phazeai_ui::launch_phaze_ide_with(files);
 }
@@ -0,0 +1,114 @@
+//! Shell command explanations in the hover tooltip.
+//!
+//! When "Explain Commands on Hover" is on, resting the pointer on a command in
+//! the terminal or in chat asks the quick-answer model what it does and shows
+//! the answer in the same tooltip overlay as LSP hovers.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, RwSignal, SignalGet, SignalUpdate},
+};
+use phazeai_core::{analysis::explain, llm::TaskType, Settings};
+
+/// How long the pointer rests on a command before it is explained.
+const HOVER_DELAY: Duration = Duration::from_millis(600);
+/// Shown while the model answers.
+const EXPLAINING: &str = "Explaining…";
+
+/// Explains hovered commands into `hover_text`. Create it inside a reactive
+/// scope; clones share the pending request.
+#[derive(Clone)]
+pub struct CommandHover {
+    enabled: RwSignal<bool>,
+    hover_text: RwSignal<Option<String>>,
+    /// Bumped on every enter/leave so stale requests are dropped.
+    generation: Arc<AtomicU64>,
+    tx: std::sync::mpsc::SyncSender<(u64, String)>,
+}
+
+impl CommandHover {
+    pub fn new(enabled: RwSignal<bool>, hover_text: RwSignal<Option<String>>) -> Self {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<(u64, String)>(8);
+        let result = create_signal_from_channel(rx);
+        let generation = Arc::new(AtomicU64::new(0));
+        {
+            let generation = Arc::clone(&generation);
+            create_effect(move |_| {
+                if let Some((gen, text)) = result.get() {
+                    if gen == generation.load(Ordering::SeqCst) {
+                        hover_text.set(Some(text));
+                    }
+                }
+            });
+        }
+        Self {
+            enabled,
+            hover_text,
+            generation,
+            tx,
+        }
+    }
+
+    /// The pointer entered `command`: explain it after a short rest.
+    pub fn enter(&self, command: &str) {
+        let gen = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        if !self.enabled.get_untracked() {
+            return;
+        }
+        let Some(command) = explain::normalize_command(command) else {
+            return;
+        };
+        if let Some(cached) = explain::cached_explanation(&command) {
+            self.hover_text
+                .set(Some(format_explanation(&command, &cached)));
+            return;
+        }
+        let generation = Arc::clone(&self.generation);
+        let tx = self.tx.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(HOVER_DELAY);
+            if generation.load(Ordering::SeqCst) != gen {
+                return;
+            }
+            let _ = tx.send((gen, format!("$ {command}\n\n{EXPLAINING}")));
+            let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            else {
+                return;
+            };
+            let result = rt.block_on(async {
+                let llm = Settings::load().build_client_for_task(TaskType::QuickAnswer)?;
+                explain::explain_command(llm.as_ref(), &command).await
+            });
+            let text = match result {
+                Ok(explanation) => format_explanation(&command, &explanation),
+                Err(e) => {
+                    eprintln!("[PhazeAI] failed to explain `{command}`: {e}");
+                    format!("$ {command}\n\nCouldn't explain this command: {e}")
+                }
+            };
+            let _ = tx.send((gen, text));
+        });
+    }
+
+    /// The pointer left the command; a pending explanation is dropped.
+    pub fn leave(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let pending = self
+            .hover_text
+            .get_untracked()
+            .is_some_and(|t| t.ends_with(EXPLAINING));
+        if pending {
+            self.hover_text.set(None);
+        }
+    }
+}
+
+fn format_explanation(command: &str, explanation: &str) -> String {
+    format!("$ {command}\n\n{explanation}")
+}
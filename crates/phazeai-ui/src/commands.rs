@@ -93,6 +93,95 @@ pub fn match_global_shortcut(ke: &floem::keyboard::KeyEvent) -> Option<IdeComman
     None
 }
 
+// ── Emacs keymap ──────────────────────────────────────────────────────────────
+
+/// An action from the Emacs keymap preset, active while it's picked in the
+/// status bar instead of the default keys or vim.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmacsCommand {
+    /// C-x — wait for the second key of a `C-x` sequence.
+    Prefix,
+    /// C-g, or an unbound key after C-x — cancel.
+    Quit,
+    /// C-a
+    LineStart,
+    /// C-e
+    LineEnd,
+    /// C-n
+    NextLine,
+    /// C-p
+    PreviousLine,
+    /// M-f
+    ForwardWord,
+    /// M-b
+    BackwardWord,
+    /// M-<
+    BufferStart,
+    /// M->
+    BufferEnd,
+    /// C-k — kill to the end of the line, or the line break when already there.
+    KillLine,
+    /// C-y — insert the last kill.
+    Yank,
+    /// M-w — copy the selection to the kill ring.
+    CopyRegion,
+    /// C-x C-s
+    Save,
+    /// C-x b — switch to another file through the file picker.
+    SwitchBuffer,
+}
+
+/// Map a key to its Emacs command; `after_prefix` is true when C-x was the
+/// previous key. Pure, like `match_global_shortcut`. Modifier-only presses
+/// return `None` so a pending C-x survives them. C-f and C-b are left to
+/// Find and the explorer toggle; the arrow keys move by character.
+pub fn match_emacs_key(ke: &floem::keyboard::KeyEvent, after_prefix: bool) -> Option<EmacsCommand> {
+    let ctrl = ke.modifiers.contains(Modifiers::CONTROL);
+    let shift = ke.modifiers.contains(Modifiers::SHIFT);
+    let alt = ke.modifiers.contains(Modifiers::ALT);
+
+    let Key::Character(ref ch) = ke.key.logical_key else {
+        return None;
+    };
+    let s = ch.as_str().to_lowercase();
+
+    if after_prefix {
+        return Some(match (ctrl, s.as_str()) {
+            (true, "s") => EmacsCommand::Save,
+            (false, "b") => EmacsCommand::SwitchBuffer,
+            _ => EmacsCommand::Quit,
+        });
+    }
+
+    if ctrl && !alt && !shift {
+        return match s.as_str() {
+            "a" => Some(EmacsCommand::LineStart),
+            "e" => Some(EmacsCommand::LineEnd),
+            "n" => Some(EmacsCommand::NextLine),
+            "p" => Some(EmacsCommand::PreviousLine),
+            "k" => Some(EmacsCommand::KillLine),
+            "y" => Some(EmacsCommand::Yank),
+            "x" => Some(EmacsCommand::Prefix),
+            "g" => Some(EmacsCommand::Quit),
+            _ => None,
+        };
+    }
+
+    // Shift is allowed so M-< and M-> work on layouts that need it.
+    if alt && !ctrl {
+        return match s.as_str() {
+            "w" => Some(EmacsCommand::CopyRegion),
+            "f" => Some(EmacsCommand::ForwardWord),
+            "b" => Some(EmacsCommand::BackwardWord),
+            "<" => Some(EmacsCommand::BufferStart),
+            ">" => Some(EmacsCommand::BufferEnd),
+            _ => None,
+        };
+    }
+
+    None
+}
+
 // ── GlobalCommandState ────────────────────────────────────────────────────────
 
 /// Minimal reactive state needed to execute any `IdeCommand`.
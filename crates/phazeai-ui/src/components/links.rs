@@ -0,0 +1,151 @@
+//! Ctrl+Click links in lines of terminal and Output panel text.
+//!
+//! [`linked_line`] draws a line's text layout with the links the core's
+//! `find_links` found in it. The link under the pointer is underlined when it
+//! leads somewhere, and Ctrl+Click (Cmd+Click on macOS) opens a file location
+//! in the editor or a web address in the browser.
+
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use floem::{
+    event::{Event, EventListener},
+    keyboard::Modifiers,
+    reactive::{create_rw_signal, RwSignal, SignalGet, SignalUpdate, SignalWith},
+    style::{CursorStyle, Display},
+    text::TextLayout,
+    views::{empty, rich_text, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::output::Link;
+
+use crate::components::open_url;
+use crate::theme::PhazeTheme;
+
+/// Opens links: files in the editor at the linked line, URLs in the browser.
+#[derive(Clone, Copy)]
+pub struct LinkOpener {
+    open_file: RwSignal<Option<PathBuf>>,
+    goto_line: RwSignal<u32>,
+    workspace_root: RwSignal<PathBuf>,
+}
+
+impl LinkOpener {
+    pub fn new(
+        open_file: RwSignal<Option<PathBuf>>,
+        goto_line: RwSignal<u32>,
+        workspace_root: RwSignal<PathBuf>,
+    ) -> Self {
+        Self {
+            open_file,
+            goto_line,
+            workspace_root,
+        }
+    }
+
+    /// The file a file link points at, relative paths resolved against `cwd`
+    /// first and then the workspace root.
+    fn file(&self, link: &Link, cwd: Option<&Path>) -> Option<(PathBuf, u32)> {
+        let Link::File(file) = link else {
+            return None;
+        };
+        let path = cwd
+            .and_then(|cwd| file.resolve(cwd))
+            .or_else(|| file.resolve(&self.workspace_root.get_untracked()))?;
+        Some((path, file.line))
+    }
+
+    /// Whether `link` leads anywhere: always for a URL, and for a file link
+    /// when the file exists.
+    pub fn can_open(&self, link: &Link, cwd: Option<&Path>) -> bool {
+        matches!(link, Link::Url { .. }) || self.file(link, cwd).is_some()
+    }
+
+    pub fn open(&self, link: &Link, cwd: Option<&Path>) {
+        match link {
+            Link::Url { url, .. } => open_url(url),
+            Link::File(_) => {
+                if let Some((path, line)) = self.file(link, cwd) {
+                    self.open_file.set(Some(path));
+                    self.goto_line.set(line);
+                }
+            }
+        }
+    }
+}
+
+/// `layout` drawn with its `links` clickable. `cwd` is the directory relative
+/// file links are resolved against, if it isn't the workspace root.
+pub fn linked_line(
+    layout: RwSignal<TextLayout>,
+    links: Vec<Link>,
+    opener: LinkOpener,
+    cwd: impl Fn() -> Option<PathBuf> + 'static,
+    theme: RwSignal<PhazeTheme>,
+) -> impl IntoView {
+    // The hovered link and its x extent in the layout.
+    let hovered: RwSignal<Option<(usize, f64, f64)>> = create_rw_signal(None);
+    // The link last under the pointer, openable or not, so the file is only
+    // looked up when the pointer moves onto another link.
+    let probed: Rc<Cell<Option<usize>>> = Rc::new(Cell::new(None));
+    let links = Rc::new(links);
+    let cwd = Rc::new(cwd);
+
+    let underline = empty().style(move |s| match hovered.get() {
+        Some((_, x0, x1)) => s
+            .absolute()
+            .inset_left(x0)
+            .inset_bottom(0.0)
+            .width(x1 - x0)
+            .height(1.0)
+            .background(theme.get().palette.accent)
+            .pointer_events_none(),
+        None => s.display(Display::None),
+    });
+
+    let links_move = links.clone();
+    let cwd_move = cwd.clone();
+    let probed_leave = probed.clone();
+    stack((rich_text(move || layout.get()), underline))
+        .style(move |s| {
+            s.width_full()
+                .apply_if(hovered.get().is_some(), |s| s.cursor(CursorStyle::Pointer))
+        })
+        .on_event_cont(EventListener::PointerMove, move |e| {
+            let Event::PointerMove(pe) = e else {
+                return;
+            };
+            let x = pe.pos.x;
+            let hit = layout.with_untracked(|l| {
+                links_move.iter().enumerate().find_map(|(i, link)| {
+                    let range = link.range();
+                    let x0 = l.hit_position(range.start).point.x;
+                    let x1 = l.hit_position(range.end).point.x;
+                    (x >= x0 && x < x1).then_some((i, x0, x1))
+                })
+            });
+            if probed.replace(hit.map(|(i, ..)| i)) == hit.map(|(i, ..)| i) {
+                return;
+            }
+            let cwd = cwd_move();
+            hovered.set(hit.filter(|&(i, ..)| opener.can_open(&links_move[i], cwd.as_deref())));
+        })
+        .on_event_cont(EventListener::PointerLeave, move |_| {
+            probed_leave.set(None);
+            hovered.set(None);
+        })
+        .on_event_cont(EventListener::PointerDown, move |e| {
+            let Event::PointerDown(pe) = e else {
+                return;
+            };
+            if !(pe.modifiers.contains(Modifiers::CONTROL)
+                || pe.modifiers.contains(Modifiers::META))
+            {
+                return;
+            }
+            if let Some((i, ..)) = hovered.get_untracked() {
+                opener.open(&links[i], cwd().as_deref());
+            }
+        })
+}
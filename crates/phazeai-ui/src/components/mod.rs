@@ -1,9 +1,12 @@
 pub mod button;
 pub mod icon;
 pub mod input;
+pub mod links;
 pub mod panel;
 pub mod scroll;
 pub mod tabs;
+pub mod upgrade;
+pub mod virtual_list;
 
 pub use button::{phaze_button, phaze_icon_button, ButtonVariant};
 pub use icon::phaze_icon;
@@ -11,3 +14,5 @@ pub use input::phaze_input;
 pub use panel::{phaze_glass_panel, phaze_panel};
 pub use scroll::phaze_scroll;
 pub use tabs::{phaze_tabs, TabItem};
+pub use upgrade::{open_upgrade_page, open_url, upgrade_prompt};
+pub use virtual_list::virtual_list;
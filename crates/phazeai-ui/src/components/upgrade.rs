@@ -0,0 +1,62 @@
+use floem::{
+    reactive::{RwSignal, SignalGet},
+    views::{label, stack, Decorators},
+    IntoView,
+};
+use phazeai_cloud::{entitlements::UPGRADE_URL, Feature};
+
+use super::button::{phaze_button, ButtonVariant};
+use crate::theme::PhazeTheme;
+
+/// Open `url` in the system browser.
+pub fn open_url(url: &str) {
+    #[cfg(target_os = "linux")]
+    let _ = std::process::Command::new("xdg-open").arg(url).spawn();
+    #[cfg(target_os = "macos")]
+    let _ = std::process::Command::new("open").arg(url).spawn();
+    #[cfg(target_os = "windows")]
+    let _ = std::process::Command::new("cmd")
+        .args(["/c", "start", "", url])
+        .spawn();
+}
+
+/// Open the PhazeAI Cloud billing page in the system browser.
+pub fn open_upgrade_page() {
+    open_url(UPGRADE_URL);
+}
+
+/// A card explaining that `feature` needs a higher plan, with an Upgrade
+/// button. Hidden while `visible` is false.
+pub fn upgrade_prompt(
+    feature: Feature,
+    visible: RwSignal<bool>,
+    theme: RwSignal<PhazeTheme>,
+) -> impl IntoView {
+    let text = format!(
+        "{} is included in the {} plan.",
+        feature.display_name(),
+        feature.min_tier().display_name()
+    );
+    stack((
+        label(move || text.clone()).style(move |s| {
+            s.font_size(11.0)
+                .flex_grow(1.0)
+                .min_width(0.0)
+                .color(theme.get().palette.text_secondary)
+        }),
+        phaze_button("Upgrade", ButtonVariant::Primary, theme, open_upgrade_page),
+    ))
+    .style(move |s| {
+        let t = theme.get();
+        let p = &t.palette;
+        s.items_center()
+            .width_full()
+            .margin(8.0)
+            .padding(10.0)
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(p.accent)
+            .background(p.accent_dim)
+            .apply_if(!visible.get(), |s| s.display(floem::style::Display::None))
+    })
+}
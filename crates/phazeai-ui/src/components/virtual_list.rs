@@ -0,0 +1,141 @@
+//! A scrolling list that only builds the rows in view.
+//!
+//! `dyn_stack` makes a view for every item, which crawls once a list runs into
+//! the thousands. [`virtual_list`] stands a spacer in for the rows above the
+//! viewport and builds just the rows in it, plus a few either side, keyed so
+//! rows that stay in view keep their views while scrolling. Rows may differ in
+//! height: each is measured once laid out, and rows not seen yet are assumed
+//! to be `row_height` tall.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::Range;
+
+use floem::{
+    kurbo::Rect,
+    reactive::{create_effect, create_memo, create_rw_signal, SignalGet, SignalUpdate, SignalWith},
+    views::{container, dyn_stack, empty, scroll, stack, Decorators, Scroll},
+    IntoView,
+};
+
+/// Rows built past each edge of the viewport, so a short scroll doesn't show
+/// a gap before the next layout.
+const OVERSCAN: usize = 6;
+
+/// The top of each row followed by the total height, from the rows' measured
+/// heights and `estimate` for the rest.
+pub fn row_offsets(heights: impl IntoIterator<Item = Option<f64>>, estimate: f64) -> Vec<f64> {
+    let mut offsets = vec![0.0];
+    let mut top = 0.0;
+    for height in heights {
+        top += height.unwrap_or(estimate);
+        offsets.push(top);
+    }
+    offsets
+}
+
+/// The rows of [`row_offsets`] showing between `top` and `bottom`, widened by
+/// `overscan` rows each way.
+pub fn visible_rows(offsets: &[f64], top: f64, bottom: f64, overscan: usize) -> Range<usize> {
+    let len = offsets.len().saturating_sub(1);
+    // The first row ending below `top`, and the first starting at `bottom`.
+    let first = offsets[1..].partition_point(|&end| end <= top);
+    let last = offsets[..len].partition_point(|&start| start < bottom);
+    first.saturating_sub(overscan).min(len)..(last + overscan).min(len)
+}
+
+/// A scrolling column of `items`, building only the rows in view.
+///
+/// `key_fn` must tell rows apart and change when a row's content does, like
+/// `dyn_stack`'s. `reveal` names a row to scroll to whenever it changes, e.g.
+/// the keyboard selection; `|| None` for lists without one.
+pub fn virtual_list<T, K, V>(
+    items: impl Fn() -> Vec<T> + 'static,
+    key_fn: impl Fn(&T) -> K + 'static,
+    view_fn: impl Fn(T) -> V + 'static,
+    row_height: f64,
+    reveal: impl Fn() -> Option<usize> + 'static,
+) -> Scroll
+where
+    T: Clone + 'static,
+    K: Clone + Eq + Hash + 'static,
+    V: IntoView + 'static,
+{
+    let rows = create_rw_signal(Vec::<(K, T)>::new());
+    // Measured heights outlive rows scrolled away, so coming back to them
+    // doesn't jump.
+    let heights = create_rw_signal(HashMap::<K, f64>::new());
+    create_effect(move |_| {
+        let list: Vec<(K, T)> = items()
+            .into_iter()
+            .map(|item| (key_fn(&item), item))
+            .collect();
+        // Forget the rows that are gone once they outnumber the rest.
+        if heights.with_untracked(|h| h.len() > 2 * list.len().max(64)) {
+            let keys: HashSet<&K> = list.iter().map(|(k, _)| k).collect();
+            heights.update(|h| h.retain(|k, _| keys.contains(k)));
+        }
+        rows.set(list);
+    });
+    let offsets = create_memo(move |_| {
+        rows.with(|rows| {
+            heights.with(|h| row_offsets(rows.iter().map(|(k, _)| h.get(k).copied()), row_height))
+        })
+    });
+    let viewport = create_rw_signal(Rect::ZERO);
+    let range = create_memo(move |_| {
+        let view = viewport.get();
+        offsets.with(|o| visible_rows(o, view.y0, view.y1, OVERSCAN))
+    });
+
+    let shown = dyn_stack(
+        move || {
+            let range = range.get();
+            rows.with(|rows| rows.get(range).map(<[_]>::to_vec).unwrap_or_default())
+        },
+        |(key, _)| key.clone(),
+        move |(key, item)| {
+            container(view_fn(item))
+                .on_resize(move |rect| {
+                    let height = rect.height();
+                    let known = heights.with_untracked(|h| h.get(&key).copied());
+                    if known.is_none_or(|known| (known - height).abs() > 0.5) {
+                        heights.update(|h| {
+                            h.insert(key.clone(), height);
+                        });
+                    }
+                })
+                .style(|s| s.width_full())
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let column = stack((
+        empty().style(move |s| {
+            let top = offsets.with(|o| o[range.get().start]);
+            s.height(top).width_full()
+        }),
+        shown,
+    ))
+    .style(move |s| {
+        let total = offsets.with(|o| o.last().copied().unwrap_or_default());
+        s.flex_col().width_full().min_height(total)
+    });
+    let column_id = column.id();
+    create_effect(move |_| {
+        let Some(i) = reveal() else {
+            return;
+        };
+        let bounds = offsets.with_untracked(|o| (i + 1 < o.len()).then(|| (o[i], o[i + 1])));
+        if let Some((top, bottom)) = bounds {
+            let width = viewport.get_untracked().width();
+            column_id.scroll_to(Some(Rect::new(0.0, top, width, bottom)));
+        }
+    });
+
+    scroll(column)
+        .on_scroll(move |rect| viewport.set(rect))
+        .on_resize(move |rect| {
+            viewport.update(|v| *v = v.with_size(rect.size()));
+        })
+}
@@ -0,0 +1,156 @@
+//! IDE side of the local control socket (`phazeai ctl`).
+//!
+//! Connection threads forward each request to the UI thread through a
+//! channel and block until the reactive effect below posts the reply.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, SignalGet, SignalUpdate},
+};
+use phazeai_core::ipc::{self, methods, RpcError, RpcHandler, RpcRequest};
+use serde_json::{json, Value};
+
+use crate::app::{all_commands, IdeState};
+use crate::lsp_bridge::DiagSeverity;
+
+type Reply = SyncSender<Result<Value, RpcError>>;
+
+/// How long a connection waits for the UI thread before giving up.
+const UI_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Start the control server. Must be called inside the window's reactive scope.
+pub fn start(state: &IdeState) {
+    let (req_tx, req_rx) = sync_channel::<(u64, RpcRequest, Reply)>(16);
+    let next_seq = AtomicU64::new(1);
+
+    let handler: RpcHandler = Arc::new(move |req: &RpcRequest| {
+        if req.method == methods::PING {
+            return Ok(json!("pong"));
+        }
+        let (reply_tx, reply_rx) = sync_channel(1);
+        let seq = next_seq.fetch_add(1, Ordering::Relaxed);
+        req_tx
+            .send((seq, req.clone(), reply_tx))
+            .map_err(|_| RpcError::new(ipc::INTERNAL_ERROR, "IDE is shutting down"))?;
+        reply_rx
+            .recv_timeout(UI_TIMEOUT)
+            .unwrap_or_else(|_| Err(RpcError::new(ipc::INTERNAL_ERROR, "IDE did not respond")))
+    });
+
+    let path = ipc::socket_path();
+    if let Err(e) = ipc::serve(path.clone(), handler) {
+        eprintln!(
+            "[PhazeAI] control socket {} unavailable: {e}",
+            path.display()
+        );
+        return;
+    }
+
+    let incoming = create_signal_from_channel(req_rx);
+    let state = state.clone();
+    // Actions may read other signals and re-trigger this effect; the
+    // sequence number makes sure each request is dispatched exactly once.
+    create_effect(move |last: Option<u64>| {
+        let last = last.unwrap_or(0);
+        match incoming.get() {
+            Some((seq, req, reply)) if seq > last => {
+                let _ = reply.send(dispatch(&state, &req));
+                seq
+            }
+            _ => last,
+        }
+    });
+}
+
+fn str_param<'a>(req: &'a RpcRequest, key: &str) -> Result<&'a str, RpcError> {
+    req.params
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params(format!("missing string param '{key}'")))
+}
+
+fn dispatch(state: &IdeState, req: &RpcRequest) -> Result<Value, RpcError> {
+    match req.method.as_str() {
+        methods::OPEN_FILE => {
+            // A path that doesn't exist yet opens as a new file.
+            let path = PathBuf::from(str_param(req, "path")?);
+            if path.is_dir() {
+                return Err(RpcError::invalid_params(format!(
+                    "not a file: {}",
+                    path.display()
+                )));
+            }
+            state.open_file.set(Some(path));
+            // `line` is 1-based on the wire; goto_line is 0-based.
+            if let Some(line) = req.params.get("line").and_then(Value::as_u64) {
+                state.goto_line.set((line as u32).saturating_sub(1));
+            }
+            Ok(json!(true))
+        }
+        methods::LIST_COMMANDS => Ok(json!(all_commands()
+            .iter()
+            .map(|c| c.label)
+            .collect::<Vec<_>>())),
+        methods::RUN_COMMAND => {
+            let label = str_param(req, "label")?;
+            let cmd = all_commands()
+                .into_iter()
+                .find(|c| c.label.eq_ignore_ascii_case(label))
+                .ok_or_else(|| RpcError::invalid_params(format!("unknown command '{label}'")))?;
+            (cmd.action)(state.clone());
+            Ok(json!(true))
+        }
+        methods::FOCUS_WINDOW => {
+            floem::action::focus_window();
+            Ok(json!(true))
+        }
+        methods::SEND_CHAT => {
+            let prompt = str_param(req, "prompt")?;
+            state.show_right_panel.set(true);
+            state.pending_chat_inject.set(Some(prompt.to_string()));
+            Ok(json!(true))
+        }
+        methods::GET_DIAGNOSTICS => {
+            let filter = req
+                .params
+                .get("path")
+                .and_then(Value::as_str)
+                .map(PathBuf::from);
+            let diags: Vec<Value> = state
+                .diagnostics
+                .get_untracked()
+                .iter()
+                .filter(|d| filter.as_ref().map_or(true, |p| &d.path == p))
+                .map(|d| {
+                    json!({
+                        "path": d.path,
+                        "line": d.line,
+                        "col": d.col,
+                        "severity": severity_name(&d.severity),
+                        "message": d.message,
+                    })
+                })
+                .collect();
+            Ok(Value::Array(diags))
+        }
+        other => Err(RpcError::new(
+            ipc::METHOD_NOT_FOUND,
+            format!("unknown method '{other}'"),
+        )),
+    }
+}
+
+fn severity_name(s: &DiagSeverity) -> &'static str {
+    match s {
+        DiagSeverity::Error => "error",
+        DiagSeverity::Warning => "warning",
+        DiagSeverity::Info => "info",
+        DiagSeverity::Hint => "hint",
+    }
+}
@@ -0,0 +1,107 @@
+//! Font family helpers: system font enumeration and fallback stacks.
+//!
+//! Text layout has no per-feature control over OpenType ligatures, so turning
+//! ligatures off swaps in the ligature-free sibling that most coding fonts ship
+//! (e.g. "JetBrains Mono NL", "Cascadia Mono") ahead of the chosen family.
+
+use floem::text::FamilyOwned;
+use lazy_static::lazy_static;
+
+/// Monospace families tried after the user's choice, in order.
+pub const FALLBACK_MONO_FAMILIES: &[&str] = &["JetBrains Mono", "Fira Code", "Cascadia Code"];
+
+/// Shown in the picker when the system font list cannot be read.
+const COMMON_FAMILIES: &[&str] = &[
+    "JetBrains Mono",
+    "Fira Code",
+    "Cascadia Code",
+    "Source Code Pro",
+    "Hack",
+    "Iosevka",
+    "Ubuntu Mono",
+    "DejaVu Sans Mono",
+    "Menlo",
+    "Consolas",
+];
+
+lazy_static! {
+    static ref SYSTEM_FAMILIES: Vec<String> = load_system_families();
+}
+
+/// Installed font family names, sorted and de-duplicated.
+/// Enumerated once per process via fontconfig (`fc-list`).
+pub fn system_font_families() -> &'static [String] {
+    &SYSTEM_FAMILIES
+}
+
+fn load_system_families() -> Vec<String> {
+    let out = std::process::Command::new("fc-list")
+        .args([":", "family"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success());
+    let mut families: Vec<String> = match out {
+        Some(o) => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            // Multi-name entries look like "DejaVu Sans,DejaVu Sans Condensed".
+            .filter_map(|l| l.split(',').next())
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect(),
+        None => Vec::new(),
+    };
+    if families.is_empty() {
+        families = COMMON_FAMILIES.iter().map(|s| s.to_string()).collect();
+    }
+    families.sort_by_key(|f| f.to_lowercase());
+    families.dedup();
+    families
+}
+
+/// The ligature-free variant of a coding font, if it has a well-known one.
+pub fn ligature_free_variant(family: &str) -> Option<&'static str> {
+    match family {
+        "JetBrains Mono" => Some("JetBrains Mono NL"),
+        "Cascadia Code" => Some("Cascadia Mono"),
+        "Fira Code" => Some("Fira Mono"),
+        "Iosevka" => Some("Iosevka Fixed"),
+        "Monaspace Neon" => Some("Monaspace Neon Frozen"),
+        _ => None,
+    }
+}
+
+fn family_names(family: &str, ligatures: bool) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let family = family.trim();
+    if !family.is_empty() {
+        if !ligatures {
+            if let Some(nl) = ligature_free_variant(family) {
+                names.push(nl.to_string());
+            }
+        }
+        names.push(family.to_string());
+    }
+    for f in FALLBACK_MONO_FAMILIES {
+        if !names.iter().any(|n| n == f) {
+            names.push(f.to_string());
+        }
+    }
+    names
+}
+
+/// Family fallback list for text layouts (`Attrs::family`, editor styling).
+pub fn font_stack(family: &str, ligatures: bool) -> Vec<FamilyOwned> {
+    let mut stack: Vec<FamilyOwned> = family_names(family, ligatures)
+        .into_iter()
+        .map(FamilyOwned::Name)
+        .collect();
+    stack.push(FamilyOwned::Monospace);
+    stack
+}
+
+/// Comma-separated family list for view styles (`Style::font_family`).
+pub fn font_stack_css(family: &str, ligatures: bool) -> String {
+    let mut names = family_names(family, ligatures);
+    names.push("monospace".to_string());
+    names.join(", ")
+}
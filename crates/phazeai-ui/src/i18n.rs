@@ -0,0 +1,192 @@
+//! UI string translation (gettext-style).
+//!
+//! Message ids are the English source strings, so untranslated text falls
+//! through unchanged and call sites stay readable: `tr("Open File…")`.
+//! Catalogs are standard `.po` files; the template of all extracted strings
+//! lives in `crates/phazeai-ui/locales/phazeai.pot`.
+//!
+//! Lookup order for locale `pt_BR`:
+//! 1. `~/.config/phazeai/locales/pt_BR.po` (community / user overrides)
+//! 2. bundled `locales/pt_BR.po`
+//! 3. the same two for the bare language `pt`
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+/// Catalogs compiled into the binary: `(locale, .po source)`.
+const BUNDLED: &[(&str, &str)] = &[];
+
+lazy_static! {
+    static ref CATALOG: RwLock<Catalog> = RwLock::new(Catalog::default());
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Catalog {
+    pub locale: String,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parse `.po` source. Only `msgid`/`msgstr` pairs are used; comments,
+    /// contexts and plural forms are ignored. Empty translations are skipped.
+    pub fn parse_po(locale: &str, src: &str) -> Self {
+        enum Field {
+            None,
+            Id,
+            Str,
+        }
+        let mut messages = HashMap::new();
+        let mut id = String::new();
+        let mut text = String::new();
+        let mut field = Field::None;
+
+        let mut flush = |id: &mut String, text: &mut String| {
+            if !id.is_empty() && !text.is_empty() {
+                messages.insert(std::mem::take(id), std::mem::take(text));
+            }
+            id.clear();
+            text.clear();
+        };
+
+        for line in src.lines() {
+            let line = line.trim();
+            if let Some(rest) = line.strip_prefix("msgid ") {
+                flush(&mut id, &mut text);
+                id = unquote(rest);
+                field = Field::Id;
+            } else if let Some(rest) = line.strip_prefix("msgstr ") {
+                text = unquote(rest);
+                field = Field::Str;
+            } else if line.starts_with('"') {
+                match field {
+                    Field::Id => id.push_str(&unquote(line)),
+                    Field::Str => text.push_str(&unquote(line)),
+                    Field::None => {}
+                }
+            } else if line.is_empty() || line.starts_with('#') || line.starts_with("msg") {
+                field = Field::None;
+            }
+        }
+        flush(&mut id, &mut text);
+
+        Self {
+            locale: locale.to_string(),
+            messages,
+        }
+    }
+
+    pub fn get(&self, msgid: &str) -> Option<&str> {
+        self.messages.get(msgid).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+/// Strip surrounding quotes and resolve C-style escapes.
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    let inner = s
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s);
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Locale from the environment (`LC_ALL`, `LC_MESSAGES`, `LANG`), e.g. `de_DE`.
+/// Returns `en` when unset or set to `C`/`POSIX`.
+pub fn detect_system_locale() -> String {
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(v) = std::env::var(var) {
+            let v = v.split(['.', '@']).next().unwrap_or("").trim();
+            if !v.is_empty() && v != "C" && v != "POSIX" {
+                return v.replace('-', "_");
+            }
+        }
+    }
+    "en".to_string()
+}
+
+fn user_locale_dir() -> Option<PathBuf> {
+    crate::app::dirs_next_config().map(|d| d.join("locales"))
+}
+
+fn load_source(locale: &str) -> Option<String> {
+    if let Some(dir) = user_locale_dir() {
+        if let Ok(src) = std::fs::read_to_string(dir.join(format!("{locale}.po"))) {
+            return Some(src);
+        }
+    }
+    BUNDLED
+        .iter()
+        .find(|(l, _)| *l == locale)
+        .map(|(_, src)| src.to_string())
+}
+
+/// Load the catalog for `locale` (empty = detect from the environment).
+/// English, or a locale with no catalog, leaves strings untranslated.
+pub fn init(locale: &str) {
+    let locale = if locale.trim().is_empty() {
+        detect_system_locale()
+    } else {
+        locale.trim().replace('-', "_")
+    };
+    let lang = locale.split('_').next().unwrap_or(&locale).to_string();
+
+    let catalog = load_source(&locale)
+        .map(|src| Catalog::parse_po(&locale, &src))
+        .or_else(|| load_source(&lang).map(|src| Catalog::parse_po(&lang, &src)))
+        .unwrap_or_else(|| Catalog {
+            locale: locale.clone(),
+            messages: HashMap::new(),
+        });
+
+    if let Ok(mut guard) = CATALOG.write() {
+        *guard = catalog;
+    }
+}
+
+/// The active locale code (e.g. `de_DE`).
+pub fn current_locale() -> String {
+    CATALOG.read().map(|c| c.locale.clone()).unwrap_or_default()
+}
+
+/// Translate a UI string. Returns the input unchanged if no translation exists.
+pub fn tr(msgid: &str) -> String {
+    CATALOG
+        .read()
+        .ok()
+        .and_then(|c| c.get(msgid).map(str::to_string))
+        .unwrap_or_else(|| msgid.to_string())
+}
+
+/// Translate and substitute `{name}` placeholders.
+pub fn tr_args(msgid: &str, args: &[(&str, &str)]) -> String {
+    let mut out = tr(msgid);
+    for (name, value) in args {
+        out = out.replace(&format!("{{{name}}}"), value);
+    }
+    out
+}
@@ -0,0 +1,218 @@
+//! One IDE per user: a launch that finds another answering on the control
+//! socket hands it the files it was given and exits, so `phazeai-ui <file>`
+//! (or `phazeai-ui open <file>`) opens a tab in the window already up rather
+//! than a second window with state of its own. `--new-window` launches one
+//! regardless.
+//!
+//! The control socket doubles as the lock: the first instance to bind it is
+//! the one others defer to, and with `control_socket = false` in the config
+//! every launch gets its own window.
+//!
+//! Links work the same way: the desktop file and the Windows installer
+//! register the `phazeai://` scheme, so `phazeai://open?file=…&line=…` from
+//! a terminal, browser or error tracker, and `file://` URLs from a file
+//! manager, arrive here as arguments. Any web page can make such a link,
+//! so links never name network paths, and a folder one names becomes the
+//! workspace only once the user agrees.
+
+use std::path::{Path, PathBuf};
+
+use phazeai_core::ipc::{self, methods};
+use serde_json::json;
+
+/// The scheme of links that open a file in the IDE.
+pub const URL_SCHEME: &str = "phazeai";
+
+/// A file or folder named on the command line, with the line to go to when
+/// given as `path:line` or by a link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub path: PathBuf,
+    /// 1-based.
+    pub line: Option<u32>,
+    /// Named by a link rather than typed on the command line.
+    pub from_link: bool,
+}
+
+/// What the command line asked for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchArgs {
+    pub targets: Vec<Target>,
+    /// `--new-window`: don't defer to a running instance.
+    pub new_window: bool,
+    /// `--profile-startup`
+    pub profile_startup: bool,
+}
+
+/// Parse the arguments after the program name. A leading `open` is the
+/// same as none; unknown flags and links are ignored. Everything after
+/// `--` is a path, links included.
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> LaunchArgs {
+    let mut parsed = LaunchArgs::default();
+    let mut args = args.into_iter().peekable();
+    if args.peek().is_some_and(|a| a == "open") {
+        args.next();
+    }
+    let mut only_paths = false;
+    for arg in args {
+        match arg.as_str() {
+            "--" if !only_paths => only_paths = true,
+            "--new-window" | "-n" if !only_paths => parsed.new_window = true,
+            "--profile-startup" if !only_paths => parsed.profile_startup = true,
+            flag if flag.starts_with('-') && !only_paths => {}
+            link if !only_paths && is_link(link) => match parse_url(link) {
+                Some(target) => parsed.targets.push(target),
+                None => eprintln!("[PhazeAI] not a link to a file: {link}"),
+            },
+            _ => parsed.targets.push(parse_target(&arg)),
+        }
+    }
+    parsed
+}
+
+/// `src/main.rs:42` → `src/main.rs` at line 42. A suffix that isn't a
+/// positive number stays part of the path.
+pub fn parse_target(arg: &str) -> Target {
+    let line_suffix = arg
+        .rsplit_once(':')
+        .filter(|(path, _)| !path.is_empty())
+        .and_then(|(path, line)| Some((path, line.parse::<u32>().ok()?)))
+        .filter(|&(_, line)| line > 0);
+    match line_suffix {
+        Some((path, line)) => Target {
+            path: PathBuf::from(path),
+            line: Some(line),
+            from_link: false,
+        },
+        None => Target {
+            path: PathBuf::from(arg),
+            line: None,
+            from_link: false,
+        },
+    }
+}
+
+fn is_link(arg: &str) -> bool {
+    arg.starts_with("file://")
+        || arg
+            .strip_prefix(URL_SCHEME)
+            .is_some_and(|rest| rest.starts_with("://"))
+}
+
+/// The file a `phazeai://open?file=/src/main.rs&line=42` link or a
+/// `file:///src/main.rs` URL points at, the path percent-decoded. `None`
+/// for any other link, for `file://` URLs naming another host, and for
+/// network paths such as `\\host\share`, which would reach out to that host.
+pub fn parse_url(url: &str) -> Option<Target> {
+    if let Some(rest) = url.strip_prefix("file://") {
+        let path = rest.strip_prefix("localhost").unwrap_or(rest);
+        if !path.starts_with('/') {
+            return None;
+        }
+        let path = urlencoding::decode(path).ok()?;
+        let path = without_slash_before_drive(&path);
+        if is_network_path(path) {
+            return None;
+        }
+        return Some(Target {
+            path: PathBuf::from(path),
+            line: None,
+            from_link: true,
+        });
+    }
+    let rest = url.strip_prefix(URL_SCHEME)?.strip_prefix("://")?;
+    let (action, query) = rest.split_once('?')?;
+    if action.trim_end_matches('/') != "open" {
+        return None;
+    }
+    let (mut path, mut line) = (None, None);
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = urlencoding::decode(value).ok()?;
+        match key {
+            "file" if is_network_path(&value) => return None,
+            "file" if !value.is_empty() => path = Some(PathBuf::from(value.into_owned())),
+            "line" => line = value.parse::<u32>().ok().filter(|&l| l > 0),
+            _ => {}
+        }
+    }
+    Some(Target {
+        path: path?,
+        line,
+        from_link: true,
+    })
+}
+
+/// `\\host\share`, `//host/share` or `\\?\UNC\…`, which Windows reads as a
+/// path on another machine.
+fn is_network_path(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    path.starts_with("//")
+}
+
+/// `/C:/src/main.rs` → `C:/src/main.rs` on Windows, where a `file://` URL
+/// has a slash before the drive letter.
+fn without_slash_before_drive(path: &str) -> &str {
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 3 && bytes[1].is_ascii_alphabetic() && bytes[2] == b':';
+    if cfg!(windows) && drive {
+        &path[1..]
+    } else {
+        path
+    }
+}
+
+/// Whether to open `folder`, which a link named, as the workspace. Anything
+/// can hand the IDE a link, so this asks first.
+pub fn confirm_linked_folder(folder: &Path) -> bool {
+    let answer = rfd::MessageDialog::new()
+        .set_title("Open Folder from Link")
+        .set_description(format!(
+            "A link asks PhazeAI to open {} as the workspace. Open it?",
+            folder.display()
+        ))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show();
+    answer == rfd::MessageDialogResult::Yes
+}
+
+/// Whether another instance is answering on `socket`.
+pub fn running(socket: &Path) -> bool {
+    ipc::call(socket, methods::PING, json!({})).is_ok()
+}
+
+/// `path` made absolute against this process's directory, resolving
+/// symlinks when it exists; the running instance has a directory of its
+/// own.
+pub fn absolute(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Hand `targets` to the instance on `socket` and bring its window to the
+/// front. Files that don't exist yet are sent too, to open as new files,
+/// but a folder is a workspace of its own: `false` when any target is one
+/// and this launch should open a window after all.
+pub fn forward(socket: &Path, targets: &[Target]) -> bool {
+    if targets.iter().any(|t| t.path.is_dir()) {
+        return false;
+    }
+    for target in targets {
+        let path = absolute(&target.path);
+        let mut params = json!({ "path": path });
+        if let Some(line) = target.line {
+            params["line"] = json!(line);
+        }
+        if let Err(e) = ipc::call(socket, methods::OPEN_FILE, params) {
+            eprintln!(
+                "[PhazeAI] couldn't open {} in the running IDE: {e}",
+                path.display()
+            );
+            return false;
+        }
+    }
+    // Instances too old to know the method have the files all the same.
+    let _ = ipc::call(socket, methods::FOCUS_WINDOW, json!({}));
+    true
+}
@@ -1,10 +1,18 @@
 pub mod app;
+pub mod command_hover;
 pub mod commands;
 pub mod components;
+pub mod control;
+pub mod fonts;
+pub mod i18n;
+pub mod instance;
 pub mod lsp_bridge;
+pub mod notifications;
 pub mod panels;
+pub mod startup;
 pub mod theme;
+pub mod update;
 pub mod util;
 
-pub use app::launch_phaze_ide;
+pub use app::{launch_phaze_ide, launch_phaze_ide_with};
 pub use theme::{PhazePalette, PhazeTheme, ThemeVariant};
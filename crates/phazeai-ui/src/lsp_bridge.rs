@@ -1,16 +1,31 @@
 //! LSP bridge — runs LspManager in a background tokio thread and exposes:
 //! - a command sender (UI → LSP, sync-safe)
-//! - a reactive diagnostics signal (LSP → UI, all open files merged)
+//! - a reactive diagnostics signal (LSP → UI, every file any server reported
+//!   on, open or not) with per-file and per-directory counts
 //! - a reactive completions signal (LSP → UI, latest completion list)
+//! - a reactive server status signal (LSP → UI, each server's health, as
+//!   servers that die are restarted with backoff)
 //!
 //! **Must be started from within a Floem reactive scope** (window callback),
 //! because `create_signal_from_channel` and `create_effect` are reactive.
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 use floem::ext_event::create_signal_from_channel;
-use floem::reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate};
+use floem::reactive::{
+    create_effect, create_rw_signal, ReadSignal, RwSignal, SignalGet, SignalUpdate,
+};
+use floem_editor_core::xi_rope::{Rope, RopeDelta};
+use phazeai_core::analysis::{
+    long_lines, FindingKind, Linter, Removal, SecurityFinding, Severity, WorkspaceFinding,
+    ANALYSIS_SOURCE, LINE_LENGTH_SOURCE, SECURITY_SOURCE,
+};
+use phazeai_core::lsp::{
+    lens, uri_to_path, utf16_column, AppliedEdits, DiagnosticCounts, DiagnosticStore, LensAction,
+    ServerStatus,
+};
+use phazeai_core::project::editorconfig::EditorConfig;
 use phazeai_core::{LspEvent, LspManager};
 use tokio::sync::mpsc;
 
@@ -23,10 +38,12 @@ pub enum LspCommand {
     /// File was opened / active tab changed — send textDocument/didOpen.
     OpenFile { path: PathBuf, text: String },
     /// File content changed — debounced 300 ms before forwarding did_change.
+    /// `edits` are the edits that made the change, when the editor knows them.
     ChangeFile {
         path: PathBuf,
         text: String,
         version: i32,
+        edits: Option<AppliedEdits>,
     },
     /// Request completions at a cursor position — triggers Completions event.
     RequestCompletions { path: PathBuf, line: u32, col: u32 },
@@ -54,10 +71,14 @@ pub enum LspCommand {
     SaveFile { path: PathBuf },
     /// Request workspace-wide symbol search (Ctrl+T). Query is the filter string.
     RequestWorkspaceSymbols { query: String },
-    /// Request peek definition (Alt+F12) — like go-to-def but returns source lines.
+    /// Request peek definition (Alt+F12) — like go-to-def but returns an
+    /// excerpt of the definition's file instead of jumping there.
     RequestPeekDefinition { path: PathBuf, line: u32, col: u32 },
     /// Request code lens for the current file (textDocument/codeLens).
     RequestCodeLens { path: PathBuf },
+    /// Request the occurrences of the symbol at a position, column in UTF-16
+    /// units (textDocument/documentHighlight).
+    RequestDocumentHighlight { path: PathBuf, line: u32, col: u32 },
     /// Request go-to-implementation at cursor position (Ctrl+F12).
     RequestImplementation { path: PathBuf, line: u32, col: u32 },
     /// Request LSP folding ranges for the current file (textDocument/foldingRange).
@@ -70,6 +91,21 @@ pub enum LspCommand {
         start_line: u32,
         end_line: u32,
     },
+    /// Run the workspace analysis (unused items and dependencies, copied
+    /// code) and replace its results in the Problems panel.
+    AnalyzeWorkspace,
+    /// Scan the workspace for committed secrets and injection-prone code
+    /// and replace the last scan's results in the Problems panel.
+    ScanSecurity,
+    /// Turn the max-line-length check on or off. While on, files are checked
+    /// against their `.editorconfig` `max_line_length` when opened or saved.
+    SetLineLengthLint(bool),
+    /// Check a file on disk again after something other than a save changed it.
+    CheckLineLength { path: PathBuf },
+    /// Restart the named language server now, even if it was given up on.
+    RestartServer(String),
+    /// Restart every language server now, including ones given up on.
+    RestartServers,
     /// Graceful shutdown.
     Shutdown,
 }
@@ -88,21 +124,52 @@ pub struct InlayHintEntry {
 /// A code lens entry attached to a specific line.
 #[derive(Debug, Clone)]
 pub struct CodeLensEntry {
-    /// 1-based line number the lens appears on.
+    /// File the lens is in.
+    pub path: PathBuf,
+    /// 1-based line number the lens appears above.
     pub line: u32,
-    /// Display label (e.g. "2 references", "Run test").
+    /// Display label (e.g. "2 references", "Run").
     pub label: String,
+    /// What clicking the lens does.
+    pub action: LensAction,
 }
 
-/// A symbol entry from the document symbol outline.
+/// The occurrences of the symbol at a position in a file.
 #[derive(Debug, Clone)]
+pub struct DocumentHighlights {
+    pub path: PathBuf,
+    /// 0-based line they were requested at.
+    pub line: u32,
+    /// UTF-16 column they were requested at.
+    pub col: u32,
+    /// `None` when the file's server can't find occurrences, so the editor
+    /// matches the word as text instead.
+    pub occurrences: Option<Vec<Occurrence>>,
+}
+
+/// Where a symbol occurs, and whether it is written or read there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    /// 0-based `(line, UTF-16 column)` of the start.
+    pub start: (u32, u32),
+    /// 0-based `(line, UTF-16 column)` of the end.
+    pub end: (u32, u32),
+    pub write: bool,
+}
+
+/// A symbol entry from the document symbol outline.
+#[derive(Debug, Clone, PartialEq)]
 pub struct SymbolEntry {
     pub name: String,
     pub kind: String, // "fn", "struct", "impl", "trait", "mod", etc.
     /// 1-based line number.
     pub line: u32,
+    /// 1-based last line of the symbol's body (`line` when unknown).
+    pub end_line: u32,
     /// Nesting depth (0 = top-level).
     pub depth: u32,
+    /// Part of the file's public API, as far as its declaration tells.
+    pub public: bool,
 }
 
 /// Parsed signature help result returned by the LSP server.
@@ -126,8 +193,39 @@ pub struct DefinitionResult {
     pub col: u32,
 }
 
+/// Lines of context a peek shows above and below its target line.
+const PEEK_CONTEXT: usize = 100;
+
+/// An excerpt of a file around a line, shown by the inline peek widget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeekLocation {
+    pub path: PathBuf,
+    /// 0-based line the excerpt is around.
+    pub line: u32,
+    /// 0-based line of `lines[0]`.
+    pub first_line: u32,
+    pub lines: Vec<String>,
+}
+
+impl PeekLocation {
+    /// Read the excerpt around 0-based `line` of `path` from disk.
+    pub fn read(path: &Path, line: u32) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let all: Vec<&str> = content.lines().collect();
+        let line = (line as usize).min(all.len().saturating_sub(1));
+        let start = line.saturating_sub(PEEK_CONTEXT);
+        let end = (line + PEEK_CONTEXT + 1).min(all.len());
+        Some(Self {
+            path: path.to_path_buf(),
+            line: line as u32,
+            first_line: start as u32,
+            lines: all[start..end].iter().map(|l| l.to_string()).collect(),
+        })
+    }
+}
+
 /// A single find-references result entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ReferenceEntry {
     pub path: PathBuf,
     /// 1-based line number.
@@ -165,6 +263,10 @@ pub struct DiagEntry {
     pub col: u32,
     pub message: String,
     pub severity: DiagSeverity,
+    /// What reported it, e.g. "rustc" or "Analysis".
+    pub source: Option<String>,
+    /// The workspace analysis's quick fix, if it has one.
+    pub fix: Option<Removal>,
 }
 
 /// A single completion item, simplified from lsp_types::CompletionItem.
@@ -184,6 +286,8 @@ pub struct CompletionEntry {
 pub struct LspBridgeSignals {
     pub cmd_tx: mpsc::UnboundedSender<LspCommand>,
     pub diagnostics: RwSignal<Vec<DiagEntry>>,
+    /// Errors and warnings per file and per workspace directory.
+    pub diagnostic_counts: RwSignal<HashMap<PathBuf, DiagnosticCounts>>,
     pub completions: RwSignal<Vec<CompletionEntry>>,
     pub goto_definition: RwSignal<Option<DefinitionResult>>,
     pub hover_text: RwSignal<Option<String>>,
@@ -193,10 +297,19 @@ pub struct LspBridgeSignals {
     pub doc_symbols: RwSignal<Vec<SymbolEntry>>,
     pub workspace_symbols: RwSignal<Vec<SymbolEntry>>,
     pub lsp_progress: RwSignal<Option<String>>,
-    pub peek_def_lines: RwSignal<Vec<String>>,
+    /// The latest answer to `RequestPeekDefinition`; `Some(None)` when no
+    /// definition was found.
+    pub peek_definition: ReadSignal<Option<Option<PeekLocation>>>,
     pub code_lens: RwSignal<Vec<CodeLensEntry>>,
+    /// The latest answer to `RequestDocumentHighlight`.
+    pub document_highlights: RwSignal<Option<DocumentHighlights>>,
     pub folding_ranges: RwSignal<Vec<(u32, u32)>>,
     pub inlay_hints: RwSignal<Vec<InlayHintEntry>>,
+    /// `window/logMessage` lines from the language servers, one at a time.
+    pub log: ReadSignal<Option<String>>,
+    /// Each started language server and whether it is running, restarting
+    /// or given up on.
+    pub server_status: RwSignal<Vec<ServerStatus>>,
 }
 
 // ── Bridge entry point ────────────────────────────────────────────────────────
@@ -210,7 +323,7 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
     let (lsp_cmd_tx, mut lsp_cmd_rx) = mpsc::unbounded_channel::<LspCommand>();
 
     // Diagnostics: bridge → Floem (sync_channel consumed by create_signal_from_channel)
-    let (diag_tx, diag_rx) = std::sync::mpsc::sync_channel::<Vec<DiagEntry>>(16);
+    let (diag_tx, diag_rx) = std::sync::mpsc::sync_channel::<DiagSnapshot>(4);
     // Completions: bridge → Floem
     let (comp_tx, comp_rx) = std::sync::mpsc::sync_channel::<Vec<CompletionEntry>>(8);
     // Definition: bridge → Floem
@@ -229,14 +342,20 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
     let (ws_syms_tx, ws_syms_rx) = std::sync::mpsc::sync_channel::<Vec<SymbolEntry>>(4);
     // LSP progress: bridge → Floem (None = idle, Some("msg") = in progress)
     let (progress_tx, progress_rx) = std::sync::mpsc::sync_channel::<Option<String>>(8);
-    // Peek definition lines: bridge → Floem
-    let (peek_tx, peek_rx) = std::sync::mpsc::sync_channel::<Vec<String>>(4);
+    // Peek definition: bridge → Floem
+    let (peek_tx, peek_rx) = std::sync::mpsc::sync_channel::<Option<PeekLocation>>(4);
     // Code lens entries: bridge → Floem
     let (code_lens_tx, code_lens_rx) = std::sync::mpsc::sync_channel::<Vec<CodeLensEntry>>(4);
+    // Document highlights: bridge → Floem
+    let (doc_hl_tx, doc_hl_rx) = std::sync::mpsc::sync_channel::<DocumentHighlights>(8);
     // Folding ranges: bridge → Floem (start_line, end_line pairs, 0-based)
     let (fold_ranges_tx, fold_ranges_rx) = std::sync::mpsc::sync_channel::<Vec<(u32, u32)>>(4);
     // Inlay hints: bridge → Floem
     let (inlay_tx, inlay_rx) = std::sync::mpsc::sync_channel::<Vec<InlayHintEntry>>(4);
+    // Server log messages: bridge → Floem (Output panel's LSP channel)
+    let (log_tx, log_rx) = std::sync::mpsc::sync_channel::<String>(256);
+    // Server health: bridge → Floem
+    let (status_tx, status_rx) = std::sync::mpsc::sync_channel::<Vec<ServerStatus>>(4);
 
     std::thread::spawn(move || {
         let rt = match tokio::runtime::Builder::new_current_thread()
@@ -254,17 +373,37 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
             let (event_tx, mut event_rx) =
                 tokio::sync::mpsc::unbounded_channel::<LspEvent>();
             let ws_root_for_refs = workspace_root.clone();
+            let ws_root_for_diags = workspace_root.clone();
             let mut manager = LspManager::new(workspace_root, event_tx.clone());
 
-            // uri → diagnostics (merged across all open files)
-            let mut all_diags: HashMap<String, Vec<DiagEntry>> = HashMap::new();
+            // Every file's diagnostics, by server, kept until the server
+            // replaces them or exits. Changes are sent to the UI in one
+            // snapshot per DIAG_FLUSH, retried while the channel is full.
+            let mut all_diags = DiagnosticStore::new();
+            let mut diags_dirty = false;
+            let mut line_length_lint = false;
+
+            // Workspace analysis and security scans: the scan runs off this
+            // loop, the analysis's unused items are checked with the file's
+            // server, then the results replace the last run's under their
+            // source ("Analysis" or "Security").
+            let (scanned_tx, mut scanned_rx) = mpsc::unbounded_channel::<Vec<WorkspaceFinding>>();
+            let (analyzed_tx, mut analyzed_rx) =
+                mpsc::unbounded_channel::<(&'static str, Vec<(PathBuf, lsp_types::Diagnostic)>)>();
 
             // Debounce state for ChangeFile: latest pending change + deadline.
             // The `sleep_until` arm only fires when `pending_change.is_some()`.
             let debounce_ms = tokio::time::Duration::from_millis(300);
             let far_future   = tokio::time::Instant::now() + tokio::time::Duration::from_secs(86400);
-            let mut pending_change: Option<(PathBuf, String, i32)> = None;
+            let mut pending_change: Option<(PathBuf, String, i32, Option<AppliedEdits>)> = None;
             let mut change_deadline = far_future;
+            let mut diag_deadline = far_future;
+
+            // Dead or hung servers are found and restarted on this tick; the
+            // status bar is sent the servers' states whenever they change.
+            let mut health_tick = tokio::time::interval(HEALTH_CHECK);
+            health_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut sent_status: Vec<ServerStatus> = Vec::new();
 
             loop {
                 tokio::select! {
@@ -272,15 +411,41 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
                     cmd = lsp_cmd_rx.recv() => {
                         match cmd {
                             Some(LspCommand::OpenFile { path, text }) => {
+                                // The server is about to get this text whole, so
+                                // pending edits for it no longer apply.
+                                if let Some((pending, _, _, edits)) = &mut pending_change {
+                                    if *pending == path {
+                                        *edits = None;
+                                    }
+                                }
+                                if line_length_lint
+                                    && all_diags.publish(LINE_LENGTH_SOURCE, path.clone(), line_length_diagnostics(&path, &text))
+                                    && !diags_dirty
+                                {
+                                    diags_dirty = true;
+                                    diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
+                                }
                                 if let Err(e) = manager.ensure_server_for_file(&path).await {
                                     eprintln!("[LSP] no server for {}: {e}", path.display());
                                 } else {
                                     manager.did_open(&path, &text);
                                 }
                             }
-                            Some(LspCommand::ChangeFile { path, text, version }) => {
+                            Some(LspCommand::ChangeFile { path, text, version, edits }) => {
                                 // Buffer and debounce — reset deadline on every keystroke.
-                                pending_change = Some((path, text, version));
+                                // Edits made since the last flush are sent together; a
+                                // change to another file flushes the pending one first.
+                                let edits = match pending_change.take() {
+                                    Some((pending, _, _, earlier)) if pending == path => {
+                                        earlier.zip(edits).map(|(a, b)| a.then(b))
+                                    }
+                                    Some((pending, text, version, earlier)) => {
+                                        manager.did_change(&pending, version, &text, earlier);
+                                        edits
+                                    }
+                                    None => edits,
+                                };
+                                pending_change = Some((path, text, version, edits));
                                 change_deadline = tokio::time::Instant::now() + debounce_ms;
                             }
                             Some(LspCommand::RequestCompletions { path, line, col }) => {
@@ -527,7 +692,8 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
                                     if let Some(client) = client_opt {
                                         match client.document_symbols(&path2).await {
                                             Ok(syms) if !syms.is_empty() => {
-                                                let entries = flatten_symbols(&syms, 0);
+                                                let mut entries = flatten_symbols(&syms, 0);
+                                                mark_public_symbols(&mut entries, &path2);
                                                 let _ = syms_tx2.send(entries);
                                                 return;
                                             }
@@ -535,105 +701,118 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
                                         }
                                     }
                                     // Fallback: regex-based symbol scan
-                                    let entries = parse_symbols_from_file(&path2);
+                                    let mut entries = parse_symbols_from_file(&path2);
+                                    mark_public_symbols(&mut entries, &path2);
                                     let _ = syms_tx2.send(entries);
                                 });
                             }
                             Some(LspCommand::SaveFile { path }) => {
+                                if line_length_lint {
+                                    let text = std::fs::read_to_string(&path).unwrap_or_default();
+                                    if all_diags.publish(LINE_LENGTH_SOURCE, path.clone(), line_length_diagnostics(&path, &text))
+                                        && !diags_dirty
+                                    {
+                                        diags_dirty = true;
+                                        diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
+                                    }
+                                }
                                 manager.did_save(&path);
+                                // Lines may have moved; place the lenses anew.
+                                let code_lens_tx2 = code_lens_tx.clone();
+                                let client_opt = manager.client_for_file(&path).cloned();
+                                tokio::spawn(async move {
+                                    let _ = code_lens_tx2.try_send(code_lenses(client_opt, path).await);
+                                });
                             }
                             Some(LspCommand::RequestPeekDefinition { path, line, col }) => {
-                                if let Some(client) = manager.client_for_file(&path).cloned() {
-                                    let path2  = path.clone();
-                                    let evt_tx = event_tx.clone();
-                                    let peek_tx2 = peek_tx.clone();
-                                    tokio::spawn(async move {
-                                        match client.goto_definition(&path2, line, col).await {
-                                            Ok(locs) => {
-                                                if let Some(loc) = locs.into_iter().next() {
-                                                    let uri_str = loc.uri.to_string();
-                                                    let target_path = uri_str
-                                                        .strip_prefix("file://")
-                                                        .map(std::path::PathBuf::from)
-                                                        .unwrap_or_else(|| std::path::PathBuf::from(&uri_str));
-                                                    let target_line = loc.range.start.line as usize; // 0-based
-                                                    // Read 15 lines centered on the target
-                                                    let lines_snapshot: Vec<String> =
-                                                        std::fs::read_to_string(&target_path)
-                                                            .map(|content| {
-                                                                let all: Vec<&str> = content.lines().collect();
-                                                                let total = all.len();
-                                                                let start = target_line.saturating_sub(7);
-                                                                let end = (target_line + 8).min(total);
-                                                                all[start..end]
-                                                                    .iter()
-                                                                    .enumerate()
-                                                                    .map(|(i, l)| {
-                                                                        let ln = start + i + 1; // 1-based
-                                                                        let marker = if start + i == target_line { ">" } else { " " };
-                                                                        format!("{marker}{ln:>4}  {l}")
-                                                                    })
-                                                                    .collect()
-                                                            })
-                                                            .unwrap_or_default();
-                                                    if !lines_snapshot.is_empty() {
-                                                        let _ = peek_tx2.try_send(lines_snapshot);
-                                                    }
-                                                    // Also fire a Definition event so goto_definition signal updates
-                                                    let _ = evt_tx.send(LspEvent::Definition(vec![loc]));
-                                                }
+                                let peek_tx2 = peek_tx.clone();
+                                let client_opt = manager.client_for_file(&path).cloned();
+                                tokio::spawn(async move {
+                                    let location = match client_opt {
+                                        Some(client) => match client.goto_definition(&path, line, col).await {
+                                            Ok(locs) => locs.into_iter().next(),
+                                            Err(e) => {
+                                                eprintln!("[LSP] peek definition error: {e}");
+                                                None
                                             }
-                                            Err(e) => eprintln!("[LSP] peek definition error: {e}"),
-                                        }
+                                        },
+                                        None => None,
+                                    };
+                                    let excerpt = location.and_then(|loc| {
+                                        PeekLocation::read(&uri_to_path(&loc.uri), loc.range.start.line)
                                     });
-                                }
+                                    let _ = peek_tx2.try_send(excerpt);
+                                });
                             }
                             Some(LspCommand::RequestCodeLens { path }) => {
                                 let code_lens_tx2 = code_lens_tx.clone();
-                                let path2 = path.clone();
                                 let client_opt = manager.client_for_file(&path).cloned();
                                 tokio::spawn(async move {
-                                    let entries = {
-                                        // LspClient doesn't yet expose textDocument/codeLens,
-                                        // so we use the file-scan fallback in all cases.
-                                        // client_opt is available for future extension.
-                                        let _client_opt = client_opt;
-                                        code_lens_from_file(&path2)
+                                    let _ = code_lens_tx2.try_send(code_lenses(client_opt, path).await);
+                                });
+                            }
+                            Some(LspCommand::RequestDocumentHighlight { path, line, col }) => {
+                                let doc_hl_tx2 = doc_hl_tx.clone();
+                                let client_opt = manager
+                                    .client_for_file(&path)
+                                    .filter(|c| c.supports_document_highlight())
+                                    .cloned();
+                                tokio::spawn(async move {
+                                    let occurrences = match client_opt {
+                                        Some(client) => match client.document_highlight(&path, line, col).await {
+                                            Ok(highlights) => Some(
+                                                highlights
+                                                    .into_iter()
+                                                    .map(|h| Occurrence {
+                                                        start: (h.range.start.line, h.range.start.character),
+                                                        end: (h.range.end.line, h.range.end.character),
+                                                        write: h.kind == Some(lsp_types::DocumentHighlightKind::WRITE),
+                                                    })
+                                                    .collect(),
+                                            ),
+                                            Err(e) => {
+                                                eprintln!("[LSP] document highlight error: {e}");
+                                                None
+                                            }
+                                        },
+                                        None => None,
                                     };
-                                    let _ = code_lens_tx2.try_send(entries);
+                                    let _ = doc_hl_tx2.try_send(DocumentHighlights { path, line, col, occurrences });
                                 });
                             }
                             Some(LspCommand::RequestWorkspaceSymbols { query }) => {
-                                // Try each active LSP client for workspace symbols.
-                                // Fall back to a ripgrep-based symbol scan if no LSP client handles it.
+                                // Ask every running server and merge their symbols.
+                                // Fall back to a ripgrep-based symbol scan if none finds any.
                                 let ws_syms_tx2 = ws_syms_tx.clone();
                                 let query2      = query.clone();
                                 let ws_root2    = ws_root_for_refs.clone();
-                                // Collect any available client (prefer rust-analyzer or ts-server).
-                                let client_opt: Option<std::sync::Arc<phazeai_core::LspClient>> =
-                                    manager.client_for_language("rust")
-                                    .or_else(|| manager.client_for_language("typescript"))
-                                    .or_else(|| manager.client_for_language("python"))
-                                    .cloned();
+                                let clients: Vec<std::sync::Arc<phazeai_core::LspClient>> =
+                                    manager.clients().cloned().collect();
                                 tokio::spawn(async move {
-                                    if let Some(client) = client_opt {
-                                        match client.workspace_symbol(&query2).await {
-                                            Ok(syms) if !syms.is_empty() => {
-                                                let entries = syms.into_iter().map(|si| {
-                                                    let kind_str = symbol_kind_str(si.kind);
-                                                    SymbolEntry {
-                                                        name: si.name,
-                                                        kind: kind_str,
-                                                        line: si.location.range.start.line + 1,
-                                                        depth: 0,
-                                                    }
-                                                }).collect::<Vec<_>>();
-                                                let _ = ws_syms_tx2.try_send(entries);
-                                                return;
-                                            }
-                                            _ => {}
+                                    let mut requests = tokio::task::JoinSet::new();
+                                    for client in clients {
+                                        let query3 = query2.clone();
+                                        requests.spawn(async move {
+                                            client.workspace_symbol(&query3).await
+                                        });
+                                    }
+                                    let mut entries = Vec::new();
+                                    while let Some(result) = requests.join_next().await {
+                                        if let Ok(Ok(syms)) = result {
+                                            entries.extend(syms.into_iter().map(|si| SymbolEntry {
+                                                name: si.name,
+                                                kind: symbol_kind_str(si.kind),
+                                                line: si.location.range.start.line + 1,
+                                                end_line: si.location.range.end.line + 1,
+                                                depth: 0,
+                                                public: true,
+                                            }));
                                         }
                                     }
+                                    if !entries.is_empty() {
+                                        let _ = ws_syms_tx2.try_send(entries);
+                                        return;
+                                    }
                                     // Fallback: ripgrep-based workspace symbol scan
                                     let entries = ripgrep_workspace_symbols(&query2, &ws_root2);
                                     let _ = ws_syms_tx2.try_send(entries);
@@ -751,44 +930,146 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
                                     let _ = inlay_tx2.try_send(hints);
                                 });
                             }
+                            Some(LspCommand::AnalyzeWorkspace) => {
+                                let scanned_tx2 = scanned_tx.clone();
+                                let ws_root2 = ws_root_for_diags.clone();
+                                tokio::spawn(async move {
+                                    let findings = tokio::task::spawn_blocking(move || {
+                                        Linter::analyze_workspace(&ws_root2)
+                                    })
+                                    .await
+                                    .unwrap_or_default();
+                                    let _ = scanned_tx2.send(findings);
+                                });
+                            }
+                            Some(LspCommand::ScanSecurity) => {
+                                let analyzed_tx2 = analyzed_tx.clone();
+                                let ws_root2 = ws_root_for_diags.clone();
+                                tokio::spawn(async move {
+                                    let root = ws_root2.clone();
+                                    let findings = tokio::task::spawn_blocking(move || {
+                                        Linter::security_scan(&root)
+                                    })
+                                    .await
+                                    .unwrap_or_default();
+                                    let diagnostics = findings
+                                        .iter()
+                                        .map(|f| (ws_root2.join(&f.path), security_diagnostic(f)))
+                                        .collect();
+                                    let _ = analyzed_tx2.send((SECURITY_SOURCE, diagnostics));
+                                });
+                            }
+                            Some(LspCommand::SetLineLengthLint(on)) => {
+                                line_length_lint = on;
+                                if !on && all_diags.clear_server(LINE_LENGTH_SOURCE) && !diags_dirty {
+                                    diags_dirty = true;
+                                    diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
+                                }
+                            }
+                            Some(LspCommand::CheckLineLength { path }) => {
+                                let text = std::fs::read_to_string(&path).unwrap_or_default();
+                                if line_length_lint
+                                    && all_diags.publish(LINE_LENGTH_SOURCE, path.clone(), line_length_diagnostics(&path, &text))
+                                    && !diags_dirty
+                                {
+                                    diags_dirty = true;
+                                    diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
+                                }
+                            }
+                            Some(LspCommand::RestartServer(server)) => {
+                                manager.restart_server(&server).await;
+                            }
+                            Some(LspCommand::RestartServers) => {
+                                manager.restart_all().await;
+                            }
                             Some(LspCommand::Shutdown) | None => break,
                         }
                     }
 
+                    // ── Health check: restart servers that died or hang ──────
+                    _ = health_tick.tick() => {
+                        manager.check_health();
+                        manager.poll_restarts().await;
+                    }
+
                     // ── Debounce flush: forward buffered ChangeFile ──────────
                     _ = tokio::time::sleep_until(change_deadline), if pending_change.is_some() => {
-                        if let Some((path, text, version)) = pending_change.take() {
-                            manager.did_change(&path, version, &text);
+                        if let Some((path, text, version, edits)) = pending_change.take() {
+                            manager.did_change(&path, version, &text, edits);
                         }
                         change_deadline = far_future; // reset timer to idle
                     }
 
+                    // ── Diagnostics flush: send the store's latest snapshot ──
+                    _ = tokio::time::sleep_until(diag_deadline), if diags_dirty => {
+                        let snapshot = diag_snapshot(&all_diags, &ws_root_for_diags);
+                        if diag_tx.try_send(snapshot).is_ok() {
+                            diags_dirty = false;
+                            diag_deadline = far_future;
+                        } else {
+                            diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
+                        }
+                    }
+
+                    // ── Workspace analysis: confirm, then publish ────────────
+                    Some(findings) = scanned_rx.recv() => {
+                        let findings: Vec<_> = findings
+                            .into_iter()
+                            .map(|f| {
+                                let client = (f.kind == FindingKind::UnusedItem)
+                                    .then(|| manager.client_for_file(&ws_root_for_diags.join(&f.path)).cloned())
+                                    .flatten();
+                                (f, client)
+                            })
+                            .collect();
+                        let analyzed_tx2 = analyzed_tx.clone();
+                        let ws_root2 = ws_root_for_diags.clone();
+                        tokio::spawn(async move {
+                            let diagnostics = confirm_unused(findings, &ws_root2)
+                                .await
+                                .iter()
+                                .map(|f| (ws_root2.join(&f.path), analysis_diagnostic(f)))
+                                .collect();
+                            let _ = analyzed_tx2.send((ANALYSIS_SOURCE, diagnostics));
+                        });
+                    }
+                    Some((source, diagnostics)) = analyzed_rx.recv() => {
+                        let mut by_file: BTreeMap<PathBuf, Vec<lsp_types::Diagnostic>> = BTreeMap::new();
+                        for (path, diagnostic) in diagnostics {
+                            by_file.entry(path).or_default().push(diagnostic);
+                        }
+                        let mut changed = all_diags.clear_server(source);
+                        for (path, diagnostics) in by_file {
+                            changed |= all_diags.publish(source, path, diagnostics);
+                        }
+                        if changed && !diags_dirty {
+                            diags_dirty = true;
+                            diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
+                        }
+                    }
+
                     // ── LSP server event ─────────────────────────────────────
                     event = event_rx.recv() => {
                         match event {
-                            Some(LspEvent::Diagnostics { uri, diagnostics }) => {
-                                let uri_str = uri.to_string();
-                                let path = uri_str
-                                    .strip_prefix("file://")
-                                    .map(PathBuf::from)
-                                    .unwrap_or_else(|| PathBuf::from(&uri_str));
-
-                                if diagnostics.is_empty() {
-                                    all_diags.remove(&uri_str);
-                                } else {
-                                    let entries = diagnostics.iter().map(|d| DiagEntry {
-                                        path: path.clone(),
-                                        line: d.range.start.line + 1,
-                                        col:  d.range.start.character + 1,
-                                        message:  d.message.clone(),
-                                        severity: severity_from_lsp(d.severity),
-                                    }).collect();
-                                    all_diags.insert(uri_str, entries);
+                            Some(LspEvent::Diagnostics { server, uri, diagnostics }) => {
+                                let changed = all_diags.publish(&server, uri_to_path(&uri), diagnostics);
+                                if changed && !diags_dirty {
+                                    diags_dirty = true;
+                                    diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
+                                }
+                            }
+                            // A server that (re)starts or exits republishes
+                            // from scratch; what it reported before is stale.
+                            Some(LspEvent::Initialized(server)) | Some(LspEvent::Exited(server)) => {
+                                // Schedules the restart if the server died,
+                                // leaving any work it reported unfinished.
+                                if manager.server_exited(&server) {
+                                    let _ = progress_tx.try_send(None);
+                                }
+                                if all_diags.clear_server(&server) && !diags_dirty {
+                                    diags_dirty = true;
+                                    diag_deadline = tokio::time::Instant::now() + DIAG_FLUSH;
                                 }
-
-                                let flat: Vec<DiagEntry> =
-                                    all_diags.values().flatten().cloned().collect();
-                                let _ = diag_tx.try_send(flat);
                             }
 
                             Some(LspEvent::Completions(items)) => {
@@ -862,14 +1143,20 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
                                         text.to_string()
                                     };
                                     let _ = progress_tx.try_send(Some(label));
+                                } else if msg != "__progress_create__" {
+                                    let _ = log_tx.try_send(msg);
                                 }
-                                // __progress_create__ and other Log messages are silently ignored.
                             }
                             Some(_) => {} // other events ignored
                             None => break, // event channel closed
                         }
                     }
                 }
+
+                let status = manager.statuses();
+                if status != sent_status && status_tx.try_send(status.clone()).is_ok() {
+                    sent_status = status;
+                }
             }
         });
     });
@@ -887,10 +1174,15 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
     let progress_chan = create_signal_from_channel(progress_rx);
     let peek_chan = create_signal_from_channel(peek_rx);
     let code_lens_chan = create_signal_from_channel(code_lens_rx);
+    let doc_hl_chan = create_signal_from_channel(doc_hl_rx);
     let fold_ranges_chan = create_signal_from_channel(fold_ranges_rx);
     let inlay_chan = create_signal_from_channel(inlay_rx);
+    let log_chan = create_signal_from_channel(log_rx);
+    let status_chan = create_signal_from_channel(status_rx);
 
     let diag_sig: RwSignal<Vec<DiagEntry>> = create_rw_signal(vec![]);
+    let diag_counts_sig: RwSignal<HashMap<PathBuf, DiagnosticCounts>> =
+        create_rw_signal(HashMap::new());
     let comp_sig: RwSignal<Vec<CompletionEntry>> = create_rw_signal(vec![]);
     let def_sig: RwSignal<Option<DefinitionResult>> = create_rw_signal(None);
     let hover_sig: RwSignal<Option<String>> = create_rw_signal(None);
@@ -900,14 +1192,16 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
     let syms_sig: RwSignal<Vec<SymbolEntry>> = create_rw_signal(vec![]);
     let ws_syms_sig: RwSignal<Vec<SymbolEntry>> = create_rw_signal(vec![]);
     let lsp_progress_sig: RwSignal<Option<String>> = create_rw_signal(None);
-    let peek_def_lines_sig: RwSignal<Vec<String>> = create_rw_signal(vec![]);
     let code_lens_sig: RwSignal<Vec<CodeLensEntry>> = create_rw_signal(vec![]);
+    let doc_hl_sig: RwSignal<Option<DocumentHighlights>> = create_rw_signal(None);
     let folding_ranges_sig: RwSignal<Vec<(u32, u32)>> = create_rw_signal(vec![]);
     let inlay_hints_sig: RwSignal<Vec<InlayHintEntry>> = create_rw_signal(vec![]);
+    let status_sig: RwSignal<Vec<ServerStatus>> = create_rw_signal(vec![]);
 
     create_effect(move |_| {
-        if let Some(entries) = diag_chan.get() {
-            diag_sig.set(entries);
+        if let Some(snapshot) = diag_chan.get() {
+            diag_sig.set(snapshot.entries);
+            diag_counts_sig.set(snapshot.counts);
         }
     });
     create_effect(move |_| {
@@ -956,13 +1250,13 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
         }
     });
     create_effect(move |_| {
-        if let Some(lines) = peek_chan.get() {
-            peek_def_lines_sig.set(lines);
+        if let Some(entries) = code_lens_chan.get() {
+            code_lens_sig.set(entries);
         }
     });
     create_effect(move |_| {
-        if let Some(entries) = code_lens_chan.get() {
-            code_lens_sig.set(entries);
+        if let Some(highlights) = doc_hl_chan.get() {
+            doc_hl_sig.set(Some(highlights));
         }
     });
     create_effect(move |_| {
@@ -975,10 +1269,16 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
             inlay_hints_sig.set(hints);
         }
     });
+    create_effect(move |_| {
+        if let Some(status) = status_chan.get() {
+            status_sig.set(status);
+        }
+    });
 
     LspBridgeSignals {
         cmd_tx: lsp_cmd_tx,
         diagnostics: diag_sig,
+        diagnostic_counts: diag_counts_sig,
         completions: comp_sig,
         goto_definition: def_sig,
         hover_text: hover_sig,
@@ -988,15 +1288,104 @@ pub fn start_lsp_bridge(workspace_root: PathBuf) -> LspBridgeSignals {
         doc_symbols: syms_sig,
         workspace_symbols: ws_syms_sig,
         lsp_progress: lsp_progress_sig,
-        peek_def_lines: peek_def_lines_sig,
+        peek_definition: peek_chan,
         code_lens: code_lens_sig,
+        document_highlights: doc_hl_sig,
         folding_ranges: folding_ranges_sig,
         inlay_hints: inlay_hints_sig,
+        log: log_chan,
+        server_status: status_sig,
     }
 }
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
+/// How often language servers are checked for having died or hung.
+const HEALTH_CHECK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Diagnostics changes are batched and sent to the UI this often.
+const DIAG_FLUSH: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Every diagnostic in the store, flattened for display, with the counts the
+/// explorer badges show.
+#[derive(Clone)]
+struct DiagSnapshot {
+    entries: Vec<DiagEntry>,
+    counts: HashMap<PathBuf, DiagnosticCounts>,
+}
+
+/// The editor's deltas, each with the text it was applied to, as `didChange`
+/// ranges; `None` when there were none.
+pub fn applied_edits<'a>(
+    deltas: impl IntoIterator<Item = (&'a Rope, &'a RopeDelta)>,
+) -> Option<AppliedEdits> {
+    let mut base_len = None;
+    let mut changes = Vec::new();
+    for (old_text, delta) in deltas {
+        base_len.get_or_insert(old_text.len());
+        // One span covering everything the delta changed, so several cursors'
+        // edits go as one change.
+        let (changed, new_len) = delta.summary();
+        if changed.start == changed.end && new_len == 0 {
+            continue;
+        }
+        let new_text = delta.apply(old_text);
+        changes.push(lsp_types::TextDocumentContentChangeEvent {
+            range: Some(lsp_types::Range {
+                start: rope_position(old_text, changed.start),
+                end: rope_position(old_text, changed.end),
+            }),
+            range_length: None,
+            text: new_text
+                .slice_to_cow(changed.start..changed.start + new_len)
+                .into_owned(),
+        });
+    }
+    Some(AppliedEdits {
+        base_len: base_len?,
+        changes,
+    })
+}
+
+/// The position of byte `offset` in `text`, with the column in UTF-16 code
+/// units.
+fn rope_position(text: &Rope, offset: usize) -> lsp_types::Position {
+    let line = text.line_of_offset(offset);
+    let before = text.slice_to_cow(text.offset_of_line(line)..offset);
+    lsp_types::Position {
+        line: line as u32,
+        character: utf16_column(&before, before.len()),
+    }
+}
+
+fn diag_snapshot(store: &DiagnosticStore, workspace_root: &std::path::Path) -> DiagSnapshot {
+    let entries = store
+        .iter()
+        .map(|(path, d)| DiagEntry {
+            path: path.to_path_buf(),
+            line: d.range.start.line + 1,
+            col: d.range.start.character + 1,
+            message: d.message.clone(),
+            severity: severity_from_lsp(d.severity),
+            source: d.source.clone(),
+            fix: d
+                .data
+                .as_ref()
+                .filter(|_| {
+                    matches!(
+                        d.source.as_deref(),
+                        Some(ANALYSIS_SOURCE | LINE_LENGTH_SOURCE)
+                    )
+                })
+                .and_then(|data| serde_json::from_value(data.clone()).ok()),
+        })
+        .collect();
+    DiagSnapshot {
+        entries,
+        counts: store.counts(workspace_root),
+    }
+}
+
 /// Extract plain text from an `lsp_types::Hover` value.
 fn hover_to_string(hover: lsp_types::Hover) -> String {
     use lsp_types::HoverContents;
@@ -1409,7 +1798,9 @@ fn flatten_symbols(syms: &[lsp_types::DocumentSymbol], depth: u32) -> Vec<Symbol
             name: sym.name.clone(),
             kind: kind.to_string(),
             line: sym.selection_range.start.line + 1,
+            end_line: sym.range.end.line + 1,
             depth,
+            public: true,
         });
         if let Some(children) = &sym.children {
             out.extend(flatten_symbols(children, depth + 1));
@@ -1501,14 +1892,57 @@ fn parse_symbols_from_file(path: &PathBuf) -> Vec<SymbolEntry> {
                     name,
                     kind: kind.to_string(),
                     line: (i as u32) + 1,
+                    end_line: (i as u32) + 1,
                     depth,
+                    public: true,
                 });
             }
         }
     }
+    // A symbol runs until the next one that isn't nested in it.
+    let last_line = content.lines().count() as u32;
+    for i in 0..symbols.len() {
+        let depth = symbols[i].depth;
+        symbols[i].end_line = symbols[i + 1..]
+            .iter()
+            .find(|next| next.depth <= depth)
+            .map_or(last_line, |next| next.line - 1)
+            .max(symbols[i].line);
+    }
     symbols
 }
 
+/// Judge each symbol's visibility from its declaration line and its parent's.
+fn mark_public_symbols(symbols: &mut [SymbolEntry], path: &Path) {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return;
+    };
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let lines: Vec<&str> = content.lines().collect();
+    let line_of = |line: u32| {
+        (line as usize)
+            .checked_sub(1)
+            .and_then(|i| lines.get(i))
+            .copied()
+            .unwrap_or("")
+    };
+    // Declaration lines of the enclosing symbols, by depth.
+    let mut parents: Vec<&str> = Vec::new();
+    for sym in symbols.iter_mut() {
+        parents.truncate(sym.depth as usize);
+        let line = line_of(sym.line);
+        sym.public = phazeai_core::analysis::is_public_declaration(
+            ext,
+            line,
+            &sym.name,
+            parents.last().copied(),
+        );
+        if parents.len() == sym.depth as usize {
+            parents.push(line);
+        }
+    }
+}
+
 /// Convert an LSP `SymbolKind` to a short human-readable string.
 fn symbol_kind_str(kind: lsp_types::SymbolKind) -> String {
     use lsp_types::SymbolKind;
@@ -1605,7 +2039,9 @@ fn ripgrep_workspace_symbols(query: &str, workspace: &std::path::Path) -> Vec<Sy
                 name,
                 kind: kind_str.to_string(),
                 line: line_num,
+                end_line: line_num,
                 depth: 0,
+                public: true,
             })
         })();
 
@@ -1617,69 +2053,240 @@ fn ripgrep_workspace_symbols(query: &str, workspace: &std::path::Path) -> Vec<Sy
     entries
 }
 
-/// Fallback code lens generation from a source file.
-/// Scans for function/method definitions and attaches a "Run" or "Test" label.
-fn code_lens_from_file(path: &PathBuf) -> Vec<CodeLensEntry> {
-    let content = match std::fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return vec![],
-    };
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+/// Most symbols a file's reference counts are looked up for.
+const MAX_REFERENCE_LENSES: usize = 100;
+
+/// The code lenses of `path`: its server's, plus Run/Debug lenses, reference
+/// counts and "Explain" lenses for whatever the server doesn't provide. With
+/// no server, only the Run/Debug lenses found by scanning the file.
+async fn code_lenses(
+    client: Option<std::sync::Arc<phazeai_core::LspClient>>,
+    path: PathBuf,
+) -> Vec<CodeLensEntry> {
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
     let mut lenses = Vec::new();
 
-    for (i, line) in content.lines().enumerate() {
-        let trimmed = line.trim();
-        let line_num = (i as u32) + 1; // 1-based
-
-        let label = if ext == "rs" {
-            if trimmed.starts_with("#[test]") || trimmed.starts_with("#[tokio::test]") {
-                Some("Run Test".to_string())
-            } else if trimmed.starts_with("pub fn ")
-                || trimmed.starts_with("fn ")
-                || trimmed.starts_with("pub async fn ")
-                || trimmed.starts_with("async fn ")
-            {
-                let rest = trimmed
-                    .trim_start_matches("pub async fn ")
-                    .trim_start_matches("async fn ")
-                    .trim_start_matches("pub fn ")
-                    .trim_start_matches("fn ");
-                let name = rest.split(['(', '<', ' ']).next().unwrap_or("").to_string();
-                if !name.is_empty() {
-                    Some(format!("fn {name}"))
+    if let Some(client) = &client {
+        if let Some(resolve) = client.code_lens_support() {
+            let mut resolving = tokio::task::JoinSet::new();
+            for server_lens in client.code_lens(&path).await.unwrap_or_default() {
+                if server_lens.command.is_none() && resolve {
+                    let client = client.clone();
+                    resolving.spawn(async move { client.resolve_code_lens(server_lens).await });
                 } else {
-                    None
+                    lenses.extend(lens::from_server(&server_lens));
                 }
-            } else {
-                None
-            }
-        } else if matches!(ext, "js" | "ts" | "jsx" | "tsx") {
-            if trimmed.starts_with("function ") || trimmed.starts_with("export function ") {
-                Some("Run".to_string())
-            } else {
-                None
             }
-        } else if ext == "py" {
-            if trimmed.starts_with("def test_") {
-                Some("Run Test".to_string())
-            } else if trimmed.starts_with("def ") {
-                Some("Run".to_string())
-            } else {
-                None
+            while let Some(result) = resolving.join_next().await {
+                if let Ok(Ok(server_lens)) = result {
+                    lenses.extend(lens::from_server(&server_lens));
+                }
             }
-        } else {
-            None
-        };
+        }
+    }
 
-        if let Some(lbl) = label {
-            lenses.push(CodeLensEntry {
-                line: line_num,
-                label: lbl,
+    if !lenses
+        .iter()
+        .any(|l| matches!(l.action, LensAction::Run(_)))
+    {
+        lenses.extend(lens::runnables(&path, &text));
+    }
+
+    if let Some(client) = &client {
+        let symbols = client.document_symbols(&path).await.unwrap_or_default();
+        let mut symbols = lens_symbols(&symbols);
+        symbols.truncate(MAX_REFERENCE_LENSES);
+        let counted = lenses
+            .iter()
+            .any(|l| matches!(l.action, LensAction::References(_)));
+        let mut counting = tokio::task::JoinSet::new();
+        for sym in symbols {
+            let line = sym.range.start.line;
+            if matches!(
+                sym.kind,
+                lsp_types::SymbolKind::FUNCTION
+                    | lsp_types::SymbolKind::METHOD
+                    | lsp_types::SymbolKind::CONSTRUCTOR
+            ) {
+                lenses.extend(lens::explain_lens(
+                    &sym.name,
+                    line,
+                    sym.range.end.line,
+                    &text,
+                ));
+            }
+            if counted {
+                continue;
+            }
+            let client = client.clone();
+            let path = path.clone();
+            let at = sym.selection_range.start;
+            counting.spawn(async move {
+                let locations = client.find_references(&path, at.line, at.character).await;
+                let count = locations
+                    .ok()?
+                    .iter()
+                    // The declaration itself isn't a reference.
+                    .filter(|l| l.range.start != at || uri_to_path(&l.uri) != path)
+                    .count();
+                Some(lens::Lens {
+                    line,
+                    title: lens::reference_title(count),
+                    action: LensAction::References(at),
+                })
             });
         }
+        while let Some(result) = counting.join_next().await {
+            if let Ok(Some(count)) = result {
+                lenses.push(count);
+            }
+        }
     }
 
+    lenses.sort_by_key(|l| (l.line, l.action.order()));
     lenses
+        .into_iter()
+        .map(|l| CodeLensEntry {
+            path: path.clone(),
+            line: l.line + 1,
+            label: l.title,
+            action: l.action,
+        })
+        .collect()
+}
+
+/// Most unused items a workspace analysis asks the language server about;
+/// ones past this are reported on the text search alone.
+const MAX_CONFIRMED_ITEMS: usize = 100;
+
+/// Drop the unused items the file's language server finds references to,
+/// which the analysis's identifier count can't see through (macros, paths
+/// spelled differently, generated code). Without a server the finding stays.
+async fn confirm_unused(
+    findings: Vec<(
+        WorkspaceFinding,
+        Option<std::sync::Arc<phazeai_core::LspClient>>,
+    )>,
+    workspace_root: &Path,
+) -> Vec<WorkspaceFinding> {
+    let mut kept = Vec::new();
+    let mut checking = tokio::task::JoinSet::new();
+    for (finding, client) in findings {
+        let Some(client) = client.filter(|_| checking.len() < MAX_CONFIRMED_ITEMS) else {
+            kept.push(finding);
+            continue;
+        };
+        let path = workspace_root.join(&finding.path);
+        checking.spawn(async move {
+            let at = lsp_types::Position::new(finding.line as u32 - 1, finding.column as u32);
+            let referenced = match client.find_references(&path, at.line, at.character).await {
+                Ok(locations) => locations
+                    .iter()
+                    .any(|l| l.range.start != at || uri_to_path(&l.uri) != path),
+                Err(_) => false,
+            };
+            (!referenced).then_some(finding)
+        });
+    }
+    while let Some(result) = checking.join_next().await {
+        if let Ok(Some(finding)) = result {
+            kept.push(finding);
+        }
+    }
+    kept
+}
+
+/// A workspace analysis finding as a diagnostic; its quick fix rides in `data`.
+fn analysis_diagnostic(f: &WorkspaceFinding) -> lsp_types::Diagnostic {
+    let start = lsp_types::Position::new(f.line as u32 - 1, f.column as u32);
+    let end = lsp_types::Position::new(f.end_line as u32, 0).max(start);
+    lsp_types::Diagnostic {
+        range: lsp_types::Range::new(start, end),
+        severity: Some(lsp_severity(f.severity)),
+        source: Some(ANALYSIS_SOURCE.to_string()),
+        message: f.message.clone(),
+        data: f
+            .fix
+            .as_ref()
+            .and_then(|fix| serde_json::to_value(fix).ok()),
+        ..Default::default()
+    }
+}
+
+/// The lines of `path`, holding `content`, over its `.editorconfig`
+/// `max_line_length`, as diagnostics with their quick fixes in `data`.
+fn line_length_diagnostics(path: &Path, content: &str) -> Vec<lsp_types::Diagnostic> {
+    let config = EditorConfig::for_file(path);
+    let Some(max) = config.max_line_length else {
+        return Vec::new();
+    };
+    let tab_width = config.tab_width.or(config.indent_size).unwrap_or(4);
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    long_lines(content, max as usize, tab_width as usize, ext)
+        .into_iter()
+        .map(|long| {
+            let line = long.line as u32 - 1;
+            lsp_types::Diagnostic {
+                range: lsp_types::Range::new(
+                    lsp_types::Position::new(line, max),
+                    lsp_types::Position::new(line + 1, 0),
+                ),
+                severity: Some(lsp_types::DiagnosticSeverity::INFORMATION),
+                source: Some(LINE_LENGTH_SOURCE.to_string()),
+                message: format!("Line is {} columns, over the limit of {max}", long.width),
+                data: long
+                    .fix
+                    .as_ref()
+                    .and_then(|fix| serde_json::to_value(fix).ok()),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// A security scan finding as a diagnostic, coded with its rule.
+fn security_diagnostic(f: &SecurityFinding) -> lsp_types::Diagnostic {
+    let start = lsp_types::Position::new(f.line as u32 - 1, f.column as u32);
+    lsp_types::Diagnostic {
+        range: lsp_types::Range::new(start, lsp_types::Position::new(f.line as u32, 0)),
+        severity: Some(lsp_severity(f.severity)),
+        code: Some(lsp_types::NumberOrString::String(f.rule.id().to_string())),
+        source: Some(SECURITY_SOURCE.to_string()),
+        message: f.message.clone(),
+        ..Default::default()
+    }
+}
+
+fn lsp_severity(severity: Severity) -> lsp_types::DiagnosticSeverity {
+    match severity {
+        Severity::Error => lsp_types::DiagnosticSeverity::ERROR,
+        Severity::Warning => lsp_types::DiagnosticSeverity::WARNING,
+        Severity::Info => lsp_types::DiagnosticSeverity::INFORMATION,
+    }
+}
+
+/// The symbols, nested ones included, that get reference-count lenses.
+fn lens_symbols(syms: &[lsp_types::DocumentSymbol]) -> Vec<lsp_types::DocumentSymbol> {
+    use lsp_types::SymbolKind;
+    let mut out = Vec::new();
+    for sym in syms {
+        if matches!(
+            sym.kind,
+            SymbolKind::FUNCTION
+                | SymbolKind::METHOD
+                | SymbolKind::CONSTRUCTOR
+                | SymbolKind::STRUCT
+                | SymbolKind::CLASS
+                | SymbolKind::ENUM
+                | SymbolKind::INTERFACE
+        ) {
+            out.push(sym.clone());
+        }
+        if let Some(children) = &sym.children {
+            out.extend(lens_symbols(children));
+        }
+    }
+    out
 }
 
 /// Regex-based inlay hint generator for common patterns when no LSP server is available.
@@ -0,0 +1,377 @@
+//! Notification center — stacked toasts, the status-bar bell and the history
+//! drawer.
+//!
+//! Every [`show_toast`](crate::app::show_toast) message is recorded in the
+//! core [`NotificationCenter`] with a severity guessed from its wording; code
+//! that knows the severity calls [`notify`]. Long operations (semantic
+//! indexing, builds, conversation sync) call [`start_progress`] and
+//! [`finish_progress`] so their toast stays up with a progress bar until they
+//! end.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use floem::{
+    ext_event::create_ext_action,
+    peniko::Color,
+    reactive::{create_effect, RwSignal, Scope, SignalGet, SignalUpdate, SignalWith},
+    style::{CursorStyle, Display},
+    views::{container, dyn_stack, empty, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::constants::ui as ui_const;
+use phazeai_core::git::relative_time;
+use phazeai_core::notifications::{Notification, NotificationCenter, Progress, Severity};
+
+use crate::app::IdeState;
+use crate::components::icon::{icons, phaze_icon};
+use crate::i18n::tr;
+use crate::panels::git_history::action_button;
+use crate::theme::{PhazePalette, PhazeTheme};
+
+/// How long a notification stays on screen once it has nothing left to do.
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Take notification `id` off screen after [`TOAST_DURATION`].
+fn dismiss_later(center: RwSignal<NotificationCenter>, id: u64) {
+    // Use Scope::current() to reuse the caller's scope, as show_toast does.
+    let dismiss = create_ext_action(Scope::current(), move |_: ()| {
+        center.update(|c| c.dismiss(id));
+    });
+    std::thread::spawn(move || {
+        std::thread::sleep(TOAST_DURATION);
+        dismiss(());
+    });
+}
+
+/// Show `message` as a toast and keep it in the history.
+pub(crate) fn notify(
+    center: RwSignal<NotificationCenter>,
+    severity: Severity,
+    message: impl Into<String>,
+) {
+    let message = tr(&message.into());
+    let mut id = 0;
+    center.update(|c| id = c.notify(severity, message));
+    dismiss_later(center, id);
+}
+
+/// Show a toast for a long operation until [`finish_progress`] ends it.
+/// `total` is the amount of work, when it is known up front.
+pub(crate) fn start_progress(
+    center: RwSignal<NotificationCenter>,
+    message: impl Into<String>,
+    total: Option<u64>,
+) -> u64 {
+    let message = tr(&message.into());
+    let mut id = 0;
+    center.update(|c| id = c.start_progress(message, total));
+    id
+}
+
+pub(crate) fn update_progress(
+    center: RwSignal<NotificationCenter>,
+    id: u64,
+    progress: Progress,
+    message: Option<&str>,
+) {
+    center.update(|c| c.update_progress(id, progress, message));
+}
+
+/// End the operation `id` with its outcome, which stays on screen for a few
+/// seconds like any other toast.
+pub(crate) fn finish_progress(
+    center: RwSignal<NotificationCenter>,
+    id: u64,
+    severity: Severity,
+    message: impl Into<String>,
+) {
+    let message = tr(&message.into());
+    center.update(|c| c.finish(id, severity, message));
+    dismiss_later(center, id);
+}
+
+/// Record every message shown through `show_toast` in `center`.
+pub(crate) fn record_toasts(toast: RwSignal<Option<String>>, center: RwSignal<NotificationCenter>) {
+    create_effect(move |_| {
+        if let Some(msg) = toast.get() {
+            notify(center, Severity::of_message(&msg), msg);
+        }
+    });
+}
+
+fn severity_glyph(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "ℹ",
+        Severity::Success => "✓",
+        Severity::Warning => "⚠",
+        Severity::Error => "✕",
+    }
+}
+
+fn severity_color(severity: Severity, p: &PhazePalette) -> Color {
+    match severity {
+        Severity::Info => p.accent,
+        Severity::Success => p.success,
+        Severity::Warning => p.warning,
+        Severity::Error => p.error,
+    }
+}
+
+/// The message, with the share done for a running operation.
+fn message_text(n: &Notification) -> String {
+    match n.progress {
+        Some(progress) => match progress.fraction() {
+            Some(f) => format!("{}  {:.0}%", n.message, f * 100.0),
+            None if progress.done > 0 => format!("{}  ({})", n.message, progress.done),
+            None => format!("{}…", n.message.trim_end_matches('…')),
+        },
+        None => n.message.clone(),
+    }
+}
+
+/// A notification's icon, message and progress bar, read live from `center`.
+fn notification_body(
+    center: RwSignal<NotificationCenter>,
+    id: u64,
+    theme: RwSignal<PhazeTheme>,
+    trailing: impl IntoView + 'static,
+) -> impl IntoView {
+    let progress = move || center.with(|c| c.get(id).and_then(|n| n.progress));
+    let severity = move || {
+        center
+            .with(|c| c.get(id).map(|n| n.severity))
+            .unwrap_or(Severity::Info)
+    };
+
+    let bar = container(empty().style(move |s| {
+        let p = theme.get().palette;
+        match progress().and_then(|pr| pr.fraction()) {
+            Some(f) => s.height_full().width_pct(f * 100.0).background(p.accent),
+            // Unknown amount of work: a dim full-width bar.
+            None => s
+                .height_full()
+                .width_full()
+                .background(p.accent.with_alpha(0.35)),
+        }
+    }))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.width_full()
+            .height(3.0)
+            .margin_top(6.0)
+            .border_radius(2.0)
+            .background(p.bg_base)
+            .apply_if(progress().is_none(), |s| s.display(Display::None))
+    });
+
+    stack((
+        stack((
+            label(move || severity_glyph(severity())).style(move |s| {
+                let p = theme.get().palette;
+                s.font_size(12.0)
+                    .width(16.0)
+                    .color(severity_color(severity(), &p))
+            }),
+            label(move || center.with(|c| c.get(id).map(message_text).unwrap_or_default())).style(
+                move |s| {
+                    s.font_size(12.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_primary)
+                },
+            ),
+            trailing,
+        ))
+        .style(|s| s.items_center().gap(6.0).width_full()),
+        bar,
+    ))
+    .style(|s| s.flex_col().width_full())
+}
+
+/// Toasts stacked above the status bar, newest at the bottom. Hidden while
+/// the history drawer is open, which lists the same notifications.
+pub(crate) fn toast_stack(state: IdeState) -> impl IntoView {
+    let center = state.notifications;
+    let open = state.notifications_open;
+    let theme = state.theme;
+
+    dyn_stack(
+        move || center.with(|c| c.toasts().iter().rev().map(|n| n.id).collect::<Vec<_>>()),
+        |id| *id,
+        move |id| {
+            let close = label(|| "×")
+                .style(move |s| {
+                    s.font_size(13.0)
+                        .padding_horiz(4.0)
+                        .cursor(CursorStyle::Pointer)
+                        .color(theme.get().palette.text_muted)
+                })
+                .on_click_stop(move |_| center.update(|c| c.dismiss(id)));
+            container(notification_body(center, id, theme, close)).style(move |s| {
+                let p = theme.get().palette;
+                s.width(360.0)
+                    .padding_horiz(14.0)
+                    .padding_vert(10.0)
+                    .background(p.bg_elevated)
+                    .border_radius(8.0)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .box_shadow_h_offset(0.0)
+                    .box_shadow_v_offset(4.0)
+                    .box_shadow_blur(20.0)
+                    .box_shadow_color(p.glow)
+                    .box_shadow_spread(0.0)
+            })
+        },
+    )
+    .style(move |s| {
+        s.absolute()
+            .inset_bottom(40.0)
+            .inset_right(24.0)
+            .z_index(ui_const::Z_TOAST)
+            .flex_col()
+            .gap(8.0)
+            .apply_if(open.get(), |s| s.display(Display::None))
+    })
+}
+
+/// Open or close the history drawer; everything in it counts as read.
+pub(crate) fn toggle_notifications(state: &IdeState) {
+    state.notifications_open.update(|open| *open = !*open);
+    state.notifications.update(|c| c.mark_all_read());
+}
+
+/// Status-bar bell with the unread count; opens the history drawer.
+pub(crate) fn notification_bell(state: IdeState) -> impl IntoView {
+    let center = state.notifications;
+    let theme = state.theme;
+    let unread = move || center.with(|c| c.unread());
+
+    container(
+        stack((
+            phaze_icon(
+                icons::BELL,
+                12.0,
+                move |p| {
+                    if unread() > 0 {
+                        p.accent
+                    } else {
+                        p.text_muted
+                    }
+                },
+                theme,
+            ),
+            label(move || match unread() {
+                0 => String::new(),
+                n if n > 99 => " 99+".to_string(),
+                n => format!(" {n}"),
+            })
+            .style(move |s| {
+                s.font_size(10.0)
+                    .color(theme.get().palette.accent)
+                    .apply_if(unread() == 0, |s| s.display(Display::None))
+            }),
+        ))
+        .style(|s| s.items_center()),
+    )
+    .style(|s| {
+        s.padding_horiz(6.0)
+            .padding_vert(2.0)
+            .cursor(CursorStyle::Pointer)
+    })
+    .on_click_stop(move |_| toggle_notifications(&state))
+}
+
+/// The notification history, opened from the bell.
+pub(crate) fn notification_drawer(state: IdeState) -> impl IntoView {
+    let center = state.notifications;
+    let open = state.notifications_open;
+    let theme = state.theme;
+
+    let header = stack((
+        label(|| "NOTIFICATIONS").style(move |s| {
+            s.font_size(11.0)
+                .font_weight(floem::text::Weight::BOLD)
+                .color(theme.get().palette.text_muted)
+                .flex_grow(1.0)
+        }),
+        action_button(
+            || "Clear All".to_string(),
+            theme,
+            move || center.update(|c| c.clear()),
+        ),
+        action_button(|| "Close".to_string(), theme, move || open.set(false)),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.items_center()
+            .gap(6.0)
+            .width_full()
+            .padding_horiz(12.0)
+            .padding_vert(8.0)
+            .border_bottom(1.0)
+            .border_color(p.border)
+    });
+
+    let rows = dyn_stack(
+        move || center.with(|c| c.history().map(|n| n.id).collect::<Vec<_>>()),
+        |id| *id,
+        move |id| {
+            let when = label(move || {
+                let secs = |t: SystemTime| {
+                    t.duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0)
+                };
+                center.with(|c| {
+                    c.get(id)
+                        .map(|n| relative_time(secs(n.time), secs(SystemTime::now())))
+                        .unwrap_or_default()
+                })
+            })
+            .style(move |s| s.font_size(10.0).color(theme.get().palette.text_muted));
+            container(notification_body(center, id, theme, when)).style(move |s| {
+                let p = theme.get().palette;
+                s.width_full()
+                    .padding_horiz(12.0)
+                    .padding_vert(8.0)
+                    .border_bottom(1.0)
+                    .border_color(p.border)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty_msg = label(|| "No notifications yet.").style(move |s| {
+        s.font_size(12.0)
+            .padding(16.0)
+            .color(theme.get().palette.text_muted)
+            .apply_if(!center.with(|c| c.is_empty()), |s| s.display(Display::None))
+    });
+
+    stack((
+        header,
+        empty_msg,
+        scroll(rows).style(|s| s.flex_grow(1.0).min_height(0.0).width_full()),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.absolute()
+            .inset_bottom(30.0)
+            .inset_right(16.0)
+            .width(380.0)
+            .max_height(440.0)
+            .flex_col()
+            .z_index(ui_const::Z_NOTIFICATIONS)
+            .background(p.bg_elevated)
+            .border_radius(8.0)
+            .border(1.0)
+            .border_color(p.border)
+            .box_shadow_h_offset(0.0)
+            .box_shadow_v_offset(4.0)
+            .box_shadow_blur(20.0)
+            .box_shadow_color(p.glow)
+            .box_shadow_spread(0.0)
+            .apply_if(!open.get(), |s| s.display(Display::None))
+    })
+}
@@ -0,0 +1,831 @@
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, img, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_cloud::{
+    entitlements, login::LOGIN_TIMEOUT, sync, CloudClient, CloudCredentials, Feature, LoginFlow,
+    OrgPolicy, Privacy, Seat, TeamRole,
+};
+use phazeai_core::context::ConversationStore;
+use phazeai_core::notifications::Severity;
+
+use crate::{
+    app::IdeState,
+    components::{
+        button::{phaze_button, ButtonVariant},
+        input::phaze_input,
+        upgrade::{open_upgrade_page, open_url},
+    },
+    notifications::{finish_progress, start_progress},
+};
+
+/// What the Account tab shows for a signed-in account.
+#[derive(Clone)]
+struct AccountView {
+    email: String,
+    plan: String,
+    features: Vec<&'static str>,
+    /// Credits remaining and the period's limit, when the API answered.
+    credits: Option<(u64, u64)>,
+    avatar: Option<Vec<u8>>,
+}
+
+/// The organization section, shown to Team plan admins.
+#[derive(Clone)]
+struct OrgView {
+    name: String,
+    seats_total: u32,
+    seats_used: u32,
+    seats: Vec<Seat>,
+    policy: OrgPolicy,
+}
+
+#[derive(Clone)]
+enum AccountUpdate {
+    Status(String),
+    SignedIn(AccountView),
+    Org(Option<OrgView>),
+    SignedOut,
+    /// An organization change finished.
+    Done,
+    /// A conversation sync action finished; this machine's mode now.
+    Synced(Privacy),
+    Failed(String),
+}
+
+/// Run `work` on a background runtime, reporting through `tx`.
+fn spawn_account_task<F, Fut>(tx: std::sync::mpsc::SyncSender<AccountUpdate>, work: F)
+where
+    F: FnOnce(std::sync::mpsc::SyncSender<AccountUpdate>) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = ()>,
+{
+    std::thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                eprintln!("[PhazeAI] runtime error: {e}");
+                return;
+            }
+        };
+        rt.block_on(work(tx));
+    });
+}
+
+/// Load the signed-in account, renewing the access token if needed. Falls
+/// back to the cached plan when the API can't be reached.
+async fn load_account(tx: std::sync::mpsc::SyncSender<AccountUpdate>) {
+    let client = match phazeai_cloud::session_client("").await {
+        Ok(client) => client,
+        Err(_) => {
+            let _ = tx.send(AccountUpdate::SignedOut);
+            return;
+        }
+    };
+    let account = client.validate().await;
+    if let Err(e) = entitlements::refresh(&client).await {
+        eprintln!("[PhazeAI] entitlement check failed: {e}");
+    }
+    let current = entitlements::current();
+    let mut view = AccountView {
+        email: CloudCredentials::load().email.unwrap_or_default(),
+        plan: current.tier.display_name().to_string(),
+        features: current
+            .enabled()
+            .into_iter()
+            .map(|f| f.display_name())
+            .collect(),
+        credits: None,
+        avatar: None,
+    };
+    match account {
+        Ok(info) => {
+            view.email = info.email;
+            view.credits = Some((info.credits_remaining, info.credits_limit));
+            if let Some(url) = info.avatar_url {
+                view.avatar = fetch_avatar(&url).await;
+            }
+        }
+        Err(e) => {
+            let _ = tx.send(AccountUpdate::Status(format!(
+                "Offline — showing your cached plan ({e})"
+            )));
+        }
+    }
+    let _ = tx.send(AccountUpdate::SignedIn(view));
+    if Feature::Organization.is_enabled() {
+        load_org(&client, &tx).await;
+    }
+}
+
+/// The organization section for admins, or `None` for other members. The
+/// org policy is applied either way.
+async fn fetch_org(client: &CloudClient) -> anyhow::Result<Option<OrgView>> {
+    let org = client.organization().await?;
+    org.policy.save()?;
+    org.policy.apply()?;
+    if org.role != TeamRole::Admin {
+        return Ok(None);
+    }
+    Ok(Some(OrgView {
+        seats: client.seats().await?,
+        name: org.name,
+        seats_total: org.seats_total,
+        seats_used: org.seats_used,
+        policy: org.policy,
+    }))
+}
+
+async fn load_org(client: &CloudClient, tx: &std::sync::mpsc::SyncSender<AccountUpdate>) {
+    match fetch_org(client).await {
+        Ok(view) => {
+            let _ = tx.send(AccountUpdate::Org(view));
+        }
+        Err(e) => eprintln!("[PhazeAI] organization load failed: {e}"),
+    }
+}
+
+/// Make an organization change as the signed-in admin, report the outcome
+/// and reload the organization.
+async fn change_org<F, Fut>(tx: std::sync::mpsc::SyncSender<AccountUpdate>, change: F)
+where
+    F: FnOnce(CloudClient) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<String>>,
+{
+    let client = match phazeai_cloud::session_client("").await {
+        Ok(client) => client,
+        Err(e) => {
+            let _ = tx.send(AccountUpdate::Failed(e.to_string()));
+            return;
+        }
+    };
+    match change(client.clone()).await {
+        Ok(msg) => {
+            let _ = tx.send(AccountUpdate::Status(msg));
+            load_org(&client, &tx).await;
+            let _ = tx.send(AccountUpdate::Done);
+        }
+        Err(e) => {
+            let _ = tx.send(AccountUpdate::Failed(e.to_string()));
+        }
+    }
+}
+
+/// What the conversation sync section asked for.
+enum SyncAction {
+    Now,
+    /// Turn on end-to-end encryption, or unlock it on this machine.
+    Encrypt(String),
+    RotateKey(String),
+    Decrypt,
+}
+
+async fn run_sync(tx: std::sync::mpsc::SyncSender<AccountUpdate>, action: SyncAction) {
+    let result = async {
+        let client = phazeai_cloud::session_client("").await?;
+        let store = ConversationStore::new()?;
+        anyhow::Ok(match action {
+            SyncAction::Now => {
+                let report = sync::sync_conversations(&client, &store).await?;
+                format!("Synced: {report}.")
+            }
+            SyncAction::Encrypt(passphrase) => {
+                let (key, report) = sync::enable_encryption(&client, &store, &passphrase).await?;
+                format!(
+                    "Encrypted with key {} ({report}). PhazeAI can't recover a lost passphrase.",
+                    key.id()
+                )
+            }
+            SyncAction::RotateKey(passphrase) => {
+                let (key, report) = sync::rotate_key(&client, &store, &passphrase).await?;
+                format!("Re-encrypted with key {} ({report}).", key.id())
+            }
+            SyncAction::Decrypt => {
+                let report = sync::disable_encryption(&client, &store).await?;
+                format!("Synced conversations are now server-readable ({report}).")
+            }
+        })
+    }
+    .await;
+    let _ = tx.send(AccountUpdate::Synced(Privacy::current()));
+    let _ = tx.send(match result {
+        Ok(msg) => AccountUpdate::Status(msg),
+        Err(e) => AccountUpdate::Failed(format!("Sync failed: {e}")),
+    });
+    let _ = tx.send(AccountUpdate::Done);
+}
+
+/// Parse the policy form: comma-separated providers (blank allows all) and
+/// retention days (blank keeps history).
+fn parse_policy(providers: &str, retention: &str) -> Result<OrgPolicy, String> {
+    let retention = retention.trim();
+    Ok(OrgPolicy {
+        allowed_providers: providers
+            .split(',')
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect(),
+        retention_days: if retention.is_empty() {
+            None
+        } else {
+            Some(
+                retention.parse().map_err(|_| {
+                    format!("Retention must be a number of days, not '{retention}'")
+                })?,
+            )
+        },
+    })
+}
+
+async fn fetch_avatar(url: &str) -> Option<Vec<u8>> {
+    let resp = reqwest::get(url).await.ok()?.error_for_status().ok()?;
+    resp.bytes().await.ok().map(|b| b.to_vec())
+}
+
+/// Browser sign-in: open the sign-in page, wait for the callback, then load
+/// the account.
+async fn sign_in(tx: std::sync::mpsc::SyncSender<AccountUpdate>) {
+    let flow = match LoginFlow::start().await {
+        Ok(flow) => flow,
+        Err(e) => {
+            let _ = tx.send(AccountUpdate::Failed(e.to_string()));
+            return;
+        }
+    };
+    let url = flow.url();
+    open_url(&url);
+    let _ = tx.send(AccountUpdate::Status(format!(
+        "Finish signing in in your browser. If it didn't open, visit:\n{url}"
+    )));
+    match flow.finish(LOGIN_TIMEOUT).await {
+        Ok(_) => load_account(tx).await,
+        Err(e) => {
+            let _ = tx.send(AccountUpdate::Failed(format!("Sign-in failed: {e}")));
+        }
+    }
+}
+
+fn initials(email: &str) -> String {
+    email
+        .chars()
+        .next()
+        .map(|c| c.to_uppercase().to_string())
+        .unwrap_or_else(|| "?".into())
+}
+
+/// The Account tab: browser sign-in, the signed-in account's avatar, plan,
+/// credits and features, and sign-out. Team admins also manage the
+/// organization's seats and policy here.
+pub fn account_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let cloud_account = state.cloud_account;
+    let account: RwSignal<Option<AccountView>> = create_rw_signal(None);
+    let status: RwSignal<String> = create_rw_signal(String::new());
+    let busy = create_rw_signal(false);
+    let org: RwSignal<Option<OrgView>> = create_rw_signal(None);
+    let invite_email = create_rw_signal(String::new());
+    let policy_providers = create_rw_signal(String::new());
+    let policy_retention = create_rw_signal(String::new());
+    let privacy = create_rw_signal(Privacy::current());
+    let passphrase = create_rw_signal(String::new());
+    let notifications = state.notifications;
+    // The progress notification of the running conversation sync.
+    let sync_note: RwSignal<Option<u64>> = create_rw_signal(None);
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<AccountUpdate>(8);
+    let updates = create_signal_from_channel(rx);
+    create_effect(move |_| match updates.get() {
+        Some(AccountUpdate::Status(msg)) => {
+            if let Some(id) = sync_note.get_untracked() {
+                sync_note.set(None);
+                finish_progress(notifications, id, Severity::Success, msg.clone());
+            }
+            status.set(msg);
+        }
+        Some(AccountUpdate::SignedIn(view)) => {
+            cloud_account.set(Some(view.email.clone()));
+            account.set(Some(view));
+            busy.set(false);
+        }
+        Some(AccountUpdate::Org(view)) => {
+            if let Some(view) = &view {
+                policy_providers.set(view.policy.allowed_providers.join(", "));
+                policy_retention.set(
+                    view.policy
+                        .retention_days
+                        .map(|d| d.to_string())
+                        .unwrap_or_default(),
+                );
+            }
+            org.set(view);
+        }
+        Some(AccountUpdate::SignedOut) => {
+            cloud_account.set(None);
+            account.set(None);
+            org.set(None);
+            busy.set(false);
+        }
+        Some(AccountUpdate::Done) => busy.set(false),
+        Some(AccountUpdate::Synced(mode)) => privacy.set(mode),
+        Some(AccountUpdate::Failed(msg)) => {
+            if let Some(id) = sync_note.get_untracked() {
+                sync_note.set(None);
+                finish_progress(notifications, id, Severity::Error, msg.clone());
+            }
+            status.set(msg);
+            busy.set(false);
+        }
+        None => {}
+    });
+
+    if CloudCredentials::load().is_authenticated() {
+        busy.set(true);
+        spawn_account_task(tx.clone(), load_account);
+    }
+
+    let header = container(label(|| "ACCOUNT").style(move |s| {
+        let p = theme.get().palette;
+        s.font_size(11.0)
+            .font_weight(floem::text::Weight::BOLD)
+            .color(p.text_muted)
+            .padding_horiz(12.0)
+            .padding_vert(8.0)
+    }))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.width_full()
+            .border_bottom(1.0)
+            .border_color(p.glass_border)
+    });
+
+    // ── Signed out ─────────────────────────────────────────────────────────
+    let sign_in_tx = tx.clone();
+    let signed_out = stack((
+        label(|| "PhazeAI Cloud").style(move |s| {
+            s.font_size(14.0)
+                .font_weight(floem::text::Weight::BOLD)
+                .color(theme.get().palette.text_primary)
+                .margin_bottom(6.0)
+        }),
+        label(|| {
+            "Sign in for hosted models without an API key, cloud sync and team features."
+                .to_string()
+        })
+        .style(move |s| {
+            s.font_size(11.5)
+                .color(theme.get().palette.text_secondary)
+                .margin_bottom(12.0)
+        }),
+        phaze_button(
+            "Sign in with browser",
+            ButtonVariant::Primary,
+            theme,
+            move || {
+                if busy.get_untracked() {
+                    return;
+                }
+                busy.set(true);
+                status.set("Opening your browser…".into());
+                spawn_account_task(sign_in_tx.clone(), sign_in);
+            },
+        ),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .padding(16.0)
+            .width_full()
+            .apply_if(account.get().is_some(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    // ── Signed in ──────────────────────────────────────────────────────────
+    let avatar = stack((
+        img(move || account.get().and_then(|a| a.avatar).unwrap_or_default()).style(move |s| {
+            s.width(40.0)
+                .height(40.0)
+                .border_radius(20.0)
+                .apply_if(account.get().and_then(|a| a.avatar).is_none(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        }),
+        label(move || {
+            account
+                .get()
+                .map(|a| initials(&a.email))
+                .unwrap_or_default()
+        })
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.width(40.0)
+                .height(40.0)
+                .border_radius(20.0)
+                .font_size(18.0)
+                .font_weight(floem::text::Weight::BOLD)
+                .justify_center()
+                .items_center()
+                .color(p.accent)
+                .background(p.accent_dim)
+                .apply_if(account.get().and_then(|a| a.avatar).is_some(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        }),
+    ));
+
+    let identity = stack((
+        avatar,
+        stack((
+            label(move || account.get().map(|a| a.email).unwrap_or_default()).style(move |s| {
+                s.font_size(13.0)
+                    .font_weight(floem::text::Weight::BOLD)
+                    .color(theme.get().palette.text_primary)
+            }),
+            label(move || account.get().map(|a| a.plan).unwrap_or_default()).style(move |s| {
+                let p = theme.get().palette;
+                s.font_size(10.5)
+                    .margin_top(4.0)
+                    .padding_horiz(6.0)
+                    .padding_vert(2.0)
+                    .border_radius(3.0)
+                    .color(p.accent)
+                    .background(p.accent_dim)
+            }),
+        ))
+        .style(|s| s.flex_col().margin_left(10.0)),
+    ))
+    .style(|s| s.items_center().margin_bottom(12.0));
+
+    let credits = label(move || match account.get().and_then(|a| a.credits) {
+        Some((left, limit)) => format!("Credits: {left} of {limit} left this period"),
+        None => String::new(),
+    })
+    .style(move |s| {
+        s.font_size(11.5)
+            .color(theme.get().palette.text_secondary)
+            .margin_bottom(8.0)
+    });
+
+    let features = dyn_stack(
+        move || account.get().map(|a| a.features).unwrap_or_default(),
+        |f| *f,
+        move |f| {
+            label(move || format!("✓ {f}")).style(move |s| {
+                s.font_size(11.5)
+                    .color(theme.get().palette.text_secondary)
+                    .margin_bottom(3.0)
+            })
+        },
+    )
+    .style(|s| s.flex_col().margin_bottom(12.0));
+
+    let refresh_tx = tx.clone();
+    let actions = stack((
+        phaze_button("Refresh", ButtonVariant::Secondary, theme, move || {
+            busy.set(true);
+            status.set(String::new());
+            spawn_account_task(refresh_tx.clone(), load_account);
+        }),
+        phaze_button(
+            "Manage plan",
+            ButtonVariant::Ghost,
+            theme,
+            open_upgrade_page,
+        ),
+        phaze_button("Sign out", ButtonVariant::Danger, theme, move || {
+            let mut creds = CloudCredentials::load();
+            match creds.logout() {
+                Ok(()) => {
+                    account.set(None);
+                    org.set(None);
+                    cloud_account.set(None);
+                    status.set("Signed out.".into());
+                }
+                Err(e) => status.set(format!("Sign-out failed: {e}")),
+            }
+        }),
+    ))
+    .style(|s| s.gap(6.0).flex_wrap(floem::style::FlexWrap::Wrap));
+
+    // ── Organization (admins) ──────────────────────────────────────────────
+    let section_label = move |text: &'static str| {
+        label(move || text).style(move |s| {
+            s.font_size(11.0)
+                .font_weight(floem::text::Weight::BOLD)
+                .color(theme.get().palette.text_muted)
+                .margin_top(10.0)
+                .margin_bottom(4.0)
+        })
+    };
+
+    let revoke_tx = tx.clone();
+    let seats = dyn_stack(
+        move || org.get().map(|o| o.seats).unwrap_or_default(),
+        |seat| {
+            (
+                seat.email.clone(),
+                seat.status,
+                seat.requests_used,
+                seat.tokens_used,
+            )
+        },
+        move |seat| {
+            let tx = revoke_tx.clone();
+            let email = seat.email.clone();
+            let usage = format!(
+                "{} · {} · {} requests, {} tokens",
+                seat.role.name(),
+                seat.status.name(),
+                seat.requests_used,
+                seat.tokens_used
+            );
+            stack((
+                stack((
+                    label(move || seat.email.clone())
+                        .style(move |s| s.font_size(12.0).color(theme.get().palette.text_primary)),
+                    label(move || usage.clone())
+                        .style(move |s| s.font_size(10.5).color(theme.get().palette.text_muted)),
+                ))
+                .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0)),
+                phaze_button("Revoke", ButtonVariant::Ghost, theme, move || {
+                    if busy.get_untracked() {
+                        return;
+                    }
+                    busy.set(true);
+                    let email = email.clone();
+                    spawn_account_task(tx.clone(), move |tx| {
+                        change_org(tx, move |client| async move {
+                            client.revoke_seat(&email).await?;
+                            Ok(format!("Revoked access for {email}."))
+                        })
+                    });
+                }),
+            ))
+            .style(move |s| {
+                s.items_center()
+                    .width_full()
+                    .padding_vert(4.0)
+                    .border_bottom(1.0)
+                    .border_color(theme.get().palette.glass_border)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let invite_tx = tx.clone();
+    let invite = stack((
+        container(phaze_input(invite_email, "name@company.com", theme))
+            .style(|s| s.flex_grow(1.0).margin_right(6.0)),
+        phaze_button("Invite", ButtonVariant::Secondary, theme, move || {
+            let email = invite_email.get_untracked().trim().to_string();
+            if email.is_empty() || busy.get_untracked() {
+                return;
+            }
+            busy.set(true);
+            invite_email.set(String::new());
+            spawn_account_task(invite_tx.clone(), move |tx| {
+                change_org(tx, move |client| async move {
+                    let seat = client.invite_member(&email, TeamRole::Member).await?;
+                    Ok(format!("Invited {}.", seat.email))
+                })
+            });
+        }),
+    ))
+    .style(|s| s.items_center().width_full().margin_top(6.0));
+
+    let policy_tx = tx.clone();
+    let policy = stack((
+        label(|| "Allowed providers (comma-separated, blank for all)").style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.text_secondary)
+                .margin_bottom(3.0)
+        }),
+        phaze_input(policy_providers, "claude, phazecloud", theme),
+        label(|| "Delete chat history after (days, blank to keep)").style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.text_secondary)
+                .margin_top(6.0)
+                .margin_bottom(3.0)
+        }),
+        phaze_input(policy_retention, "30", theme),
+        container(phaze_button(
+            "Save policy",
+            ButtonVariant::Primary,
+            theme,
+            move || {
+                if busy.get_untracked() {
+                    return;
+                }
+                let policy = match parse_policy(
+                    &policy_providers.get_untracked(),
+                    &policy_retention.get_untracked(),
+                ) {
+                    Ok(policy) => policy,
+                    Err(msg) => {
+                        status.set(msg);
+                        return;
+                    }
+                };
+                busy.set(true);
+                spawn_account_task(policy_tx.clone(), move |tx| {
+                    change_org(tx, move |client| async move {
+                        client.set_org_policy(&policy).await?;
+                        Ok("Saved the organization policy.".to_string())
+                    })
+                });
+            },
+        ))
+        .style(|s| s.margin_top(8.0)),
+    ))
+    .style(|s| s.flex_col().width_full());
+
+    let organization = stack((
+        label(move || org.get().map(|o| o.name).unwrap_or_default()).style(move |s| {
+            s.font_size(13.0)
+                .font_weight(floem::text::Weight::BOLD)
+                .color(theme.get().palette.text_primary)
+        }),
+        label(move || match org.get() {
+            Some(o) => format!("{} of {} seats used", o.seats_used, o.seats_total),
+            None => String::new(),
+        })
+        .style(move |s| s.font_size(11.5).color(theme.get().palette.text_secondary)),
+        section_label("SEATS"),
+        seats,
+        invite,
+        section_label("POLICY"),
+        policy,
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.flex_col()
+            .width_full()
+            .margin_top(16.0)
+            .padding_top(12.0)
+            .border_top(1.0)
+            .border_color(p.glass_border)
+            .apply_if(org.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    // ── Conversation sync ──────────────────────────────────────────────────
+    let start_sync = {
+        let tx = tx.clone();
+        move |action: SyncAction| {
+            if busy.get_untracked() {
+                return;
+            }
+            busy.set(true);
+            status.set("Syncing conversations…".into());
+            sync_note.set(Some(start_progress(
+                notifications,
+                "Syncing conversations",
+                None,
+            )));
+            spawn_account_task(tx.clone(), move |tx| run_sync(tx, action));
+        }
+    };
+    // Take the typed passphrase, clearing the field.
+    let take_passphrase = move || {
+        let value = passphrase.get_untracked();
+        passphrase.set(String::new());
+        if value.is_empty() {
+            status.set("Enter a passphrase first.".into());
+            None
+        } else {
+            Some(value)
+        }
+    };
+
+    let privacy_line = label(move || match privacy.get() {
+        Privacy::EndToEnd { key_id } => format!(
+            "🔒 Conversations: end-to-end encrypted (key {key_id}). PhazeAI Cloud stores only ciphertext."
+        ),
+        Privacy::ServerReadable => {
+            "Conversations: server-readable. PhazeAI Cloud can read synced chats.".to_string()
+        }
+    })
+    .style(move |s| {
+        let p = theme.get().palette;
+        let encrypted = privacy.get().is_encrypted();
+        s.font_size(11.5)
+            .color(if encrypted { p.success } else { p.warning })
+            .margin_bottom(3.0)
+    });
+
+    let sync_now = start_sync.clone();
+    let encrypt = start_sync.clone();
+    let rotate = start_sync.clone();
+    let decrypt = start_sync;
+    let conversation_sync = stack((
+        section_label("CONVERSATION SYNC"),
+        privacy_line,
+        label(|| "Account, plan, team context and org policy are always server-readable.").style(
+            move |s| {
+                s.font_size(10.5)
+                    .color(theme.get().palette.text_muted)
+                    .margin_bottom(6.0)
+            },
+        ),
+        phaze_input(passphrase, "Encryption passphrase", theme),
+        stack((
+            phaze_button("Sync now", ButtonVariant::Secondary, theme, move || {
+                sync_now(SyncAction::Now)
+            }),
+            phaze_button(
+                "Encrypt / unlock",
+                ButtonVariant::Primary,
+                theme,
+                move || {
+                    if let Some(p) = take_passphrase() {
+                        encrypt(SyncAction::Encrypt(p));
+                    }
+                },
+            ),
+            container(
+                stack((
+                    phaze_button("Rotate key", ButtonVariant::Ghost, theme, move || {
+                        if let Some(p) = take_passphrase() {
+                            rotate(SyncAction::RotateKey(p));
+                        }
+                    }),
+                    phaze_button(
+                        "Turn off encryption",
+                        ButtonVariant::Danger,
+                        theme,
+                        move || decrypt(SyncAction::Decrypt),
+                    ),
+                ))
+                .style(|s| s.gap(6.0)),
+            )
+            .style(move |s| {
+                s.apply_if(!privacy.get().is_encrypted(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+            }),
+        ))
+        .style(|s| {
+            s.gap(6.0)
+                .margin_top(6.0)
+                .flex_wrap(floem::style::FlexWrap::Wrap)
+        }),
+    ))
+    .style(move |s| {
+        let synced = account
+            .get()
+            .is_some_and(|a| a.features.contains(&Feature::CloudSync.display_name()));
+        s.flex_col()
+            .width_full()
+            .margin_top(8.0)
+            .apply_if(!synced, |s| s.display(floem::style::Display::None))
+    });
+
+    let signed_in = stack((
+        identity,
+        credits,
+        features,
+        actions,
+        conversation_sync,
+        organization,
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .padding(16.0)
+            .width_full()
+            .apply_if(account.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let status_line = label(move || {
+        if busy.get() && status.get().is_empty() {
+            "Loading…".to_string()
+        } else {
+            status.get()
+        }
+    })
+    .style(move |s| {
+        s.font_size(11.0)
+            .padding_horiz(16.0)
+            .color(theme.get().palette.text_muted)
+            .apply_if(status.get().is_empty() && !busy.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    stack((
+        header,
+        scroll(stack((signed_out, signed_in, status_line)).style(|s| s.flex_col().width_full()))
+            .style(|s| s.flex_grow(1.0).width_full()),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .width_full()
+            .height_full()
+            .background(theme.get().palette.bg_panel)
+    })
+}
@@ -0,0 +1,415 @@
+//! Dependency audit — the bottom-panel "AUDIT" tab.
+//!
+//! The packages the workspace's lockfiles pin, checked against the local
+//! advisory database, and grouped by license. Audits read only the local
+//! database, so the tab works offline; "Refresh Advisories" updates it from
+//! OSV for the workspace's packages, and "Import Advisories…" merges an OSV
+//! export (an ecosystem's `all.zip` or a record) for machines without a
+//! network. "Propose Upgrade Plan" hands the vulnerable packages to the
+//! agent.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use floem::{
+    ext_event::{create_ext_action, create_signal_from_channel},
+    reactive::{create_effect, create_rw_signal, RwSignal, Scope, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::audit::{
+    self, AdvisoryDb, AuditReport, LicenseGroup, LicenseKind, Vulnerability,
+};
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::panels::git_history::action_button;
+use crate::theme::PhazeTheme;
+
+/// Section heading between the vulnerability and license lists.
+fn heading(text: impl Fn() -> String + 'static, theme: RwSignal<PhazeTheme>) -> impl IntoView {
+    label(text).style(move |s| {
+        let p = theme.get().palette;
+        s.font_size(11.0)
+            .color(p.text_secondary)
+            .padding_horiz(10.0)
+            .padding_top(8.0)
+            .padding_bottom(3.0)
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(p.border)
+    })
+}
+
+/// "3 days ago" for the database's last refresh.
+fn age(updated: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    let Some(updated) = updated else {
+        return "never refreshed".to_string();
+    };
+    let days = (chrono::Utc::now() - updated).num_days();
+    match days {
+        0 => "refreshed today".to_string(),
+        1 => "refreshed yesterday".to_string(),
+        n => format!("refreshed {n} days ago"),
+    }
+}
+
+pub fn audit_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let pending_chat_inject = state.pending_chat_inject;
+    let show_right_panel = state.show_right_panel;
+    let toast = state.status_toast;
+
+    let report = create_rw_signal(AuditReport::default());
+    let scanning = create_rw_signal(false);
+    let updating = create_rw_signal(false);
+    let rescan = create_rw_signal(0u64);
+
+    // ── Auditing ───────────────────────────────────────────────────────────
+    {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<(u64, AuditReport)>(1);
+        let results = create_signal_from_channel(rx);
+        let generation = Arc::new(AtomicU64::new(0));
+        {
+            let generation = generation.clone();
+            create_effect(move |_| {
+                let Some((gen, audited)) = results.get() else {
+                    return;
+                };
+                if gen != generation.load(Ordering::Relaxed) {
+                    return;
+                }
+                report.set(audited);
+                scanning.set(false);
+            });
+        }
+        create_effect(move |_| {
+            if rescan.get() == 0 {
+                return;
+            }
+            let root = workspace_root.get_untracked();
+            let gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
+            scanning.set(true);
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let db = AdvisoryDb::load(&AdvisoryDb::default_path());
+                let _ = tx.send((gen, audit::audit(&root, &db)));
+            });
+        });
+    }
+    // Audited when the tab is first opened; lockfiles change rarely.
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |_| {
+            if shown.get() && tab.get() == Tab::Audit && rescan.get_untracked() == 0 {
+                rescan.set(1);
+            }
+        });
+    }
+    create_effect(move |_| {
+        let _ = workspace_root.get();
+        if rescan.get_untracked() > 0 {
+            rescan.update(|n| *n += 1);
+        }
+    });
+
+    // ── Updating the advisory database ─────────────────────────────────────
+    let updated = move |what: &'static str| {
+        create_ext_action(Scope::current(), move |result: Result<usize, String>| {
+            updating.set(false);
+            match result {
+                Ok(added) => {
+                    show_toast(toast, format!("{what}: {added} new advisories"));
+                    rescan.update(|n| *n += 1);
+                }
+                Err(e) => show_toast(toast, format!("{what} failed: {e}")),
+            }
+        })
+    };
+    let refresh = move || {
+        if updating.get_untracked() {
+            return;
+        }
+        updating.set(true);
+        let root = workspace_root.get_untracked();
+        let on_done = updated("Advisories refreshed");
+        std::thread::spawn(move || {
+            let path = AdvisoryDb::default_path();
+            let mut db = AdvisoryDb::load(&path);
+            let packages = audit::workspace_packages(&root);
+            let result = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| e.to_string())
+                .and_then(|rt| {
+                    rt.block_on(db.refresh(&packages))
+                        .map_err(|e| e.to_string())
+                })
+                .and_then(|added| db.save(&path).map(|_| added).map_err(|e| e.to_string()));
+            on_done(result);
+        });
+    };
+    let import = move || {
+        if updating.get_untracked() {
+            return;
+        }
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter("OSV advisories", &["zip", "json"])
+            .pick_file()
+        else {
+            return;
+        };
+        updating.set(true);
+        let on_done = updated("Advisories imported");
+        std::thread::spawn(move || {
+            let path = AdvisoryDb::default_path();
+            let mut db = AdvisoryDb::load(&path);
+            let result = db
+                .import(&file)
+                .and_then(|added| db.save(&path).map(|_| added))
+                .map_err(|e| e.to_string());
+            on_done(result);
+        });
+    };
+
+    let propose_upgrades = move || {
+        let vulnerable = report.get_untracked().vulnerabilities;
+        if vulnerable.is_empty() {
+            show_toast(toast, "No vulnerable dependencies");
+            return;
+        }
+        pending_chat_inject.set(Some(format!(
+            "These dependencies have known vulnerabilities. Propose an upgrade plan: for each, \
+             the version to move to (the lowest fixed version that the manifest's requirement \
+             allows, or the change to the requirement if it doesn't), which ones are pulled in \
+             by another dependency that has to be upgraded instead, and the order to do them in \
+             with the commands to run. Say which have no fix and what to do about them.\n\n{}",
+            audit::describe_vulnerabilities(&vulnerable)
+        )));
+        show_right_panel.set(true);
+    };
+
+    // ── Header ─────────────────────────────────────────────────────────────
+    let header = stack((
+        action_button(
+            move || {
+                if updating.get() {
+                    "Updating…".to_string()
+                } else {
+                    "Refresh Advisories".to_string()
+                }
+            },
+            theme,
+            refresh,
+        ),
+        action_button(|| "Import Advisories…".to_string(), theme, import),
+        action_button(
+            || "Propose Upgrade Plan".to_string(),
+            theme,
+            propose_upgrades,
+        ),
+        action_button(
+            || "Re-audit".to_string(),
+            theme,
+            move || rescan.update(|n| *n += 1),
+        ),
+        label(move || {
+            let r = report.get();
+            let status = if scanning.get() {
+                "  ·  auditing…"
+            } else {
+                ""
+            };
+            format!(
+                "{} packages, {} vulnerabilities  ·  {} advisories, {}{status}",
+                r.packages.len(),
+                r.vulnerabilities.len(),
+                r.db_advisories,
+                age(r.db_updated)
+            )
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    // ── Vulnerabilities ────────────────────────────────────────────────────
+    let vulnerabilities = dyn_stack(
+        move || report.get().vulnerabilities,
+        |v| {
+            (
+                v.package.lockfile.clone(),
+                v.package.name.clone(),
+                v.package.version.clone(),
+                v.advisory.id.clone(),
+            )
+        },
+        move |v: Vulnerability| {
+            let hovered = create_rw_signal(false);
+            let rank = v.severity_rank();
+            let package = format!("{} {}", v.package.name, v.package.version);
+            let severity = v
+                .advisory
+                .severity
+                .clone()
+                .unwrap_or_else(|| "unrated".to_string())
+                .to_lowercase();
+            let id = v.advisory.id.clone();
+            let summary = v.advisory.summary.clone();
+            let fix = match v.fixed_in() {
+                Some(version) => format!("→ {version}"),
+                None => "no fix".to_string(),
+            };
+            let lockfile = v.package.lockfile.clone();
+            let lockfile_text = lockfile.display().to_string();
+            stack((
+                label(move || package.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .width(200.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                label(move || severity.clone()).style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(11.0).width(80.0).color(match rank {
+                        3.. => p.error,
+                        2 => p.warning,
+                        _ => p.text_secondary,
+                    })
+                }),
+                label(move || id.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .width(170.0)
+                        .color(theme.get().palette.accent)
+                }),
+                label(move || summary.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_secondary)
+                }),
+                label(move || fix.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .width(90.0)
+                        .color(theme.get().palette.success)
+                }),
+                label(move || lockfile_text.clone())
+                    .style(move |s| s.font_size(10.0).color(theme.get().palette.text_muted)),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(hovered.get(), |s| s.background(p.bg_elevated))
+            })
+            .on_click_stop(move |_| {
+                open_file.set(Some(workspace_root.get_untracked().join(&lockfile)));
+            })
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let no_vulnerabilities = label(move || {
+        let r = report.get();
+        if scanning.get() {
+            "Auditing the workspace's lockfiles…".to_string()
+        } else if r.packages.is_empty() {
+            "No Cargo.lock or package-lock.json in the workspace.".to_string()
+        } else if r.db_advisories == 0 {
+            "The advisory database is empty; refresh or import it to audit offline.".to_string()
+        } else {
+            "No known vulnerabilities ✓".to_string()
+        }
+    })
+    .style(move |s| {
+        s.font_size(12.0)
+            .color(theme.get().palette.text_muted)
+            .padding(10.0)
+            .apply_if(!report.get().vulnerabilities.is_empty(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    // ── Licenses ───────────────────────────────────────────────────────────
+    let licenses = dyn_stack(
+        move || report.get().licenses,
+        |g| g.license.clone(),
+        move |g: LicenseGroup| {
+            let kind = g.kind;
+            let title = format!("{}  ·  {}", g.license, kind.label());
+            let count = g.packages.len();
+            // The few packages of an unusual license are the ones to check.
+            let names = if count <= 6 {
+                g.packages.join(", ")
+            } else {
+                format!("{}, …", g.packages[..6].join(", "))
+            };
+            stack((
+                label(move || title.clone()).style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(12.0).width(320.0).color(match kind {
+                        LicenseKind::Copyleft => p.error,
+                        LicenseKind::Unknown | LicenseKind::WeakCopyleft => p.warning,
+                        LicenseKind::Permissive => p.text_primary,
+                    })
+                }),
+                label(move || count.to_string()).style(move |s| {
+                    s.font_size(12.0)
+                        .width(50.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                label(move || names.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_muted)
+                }),
+            ))
+            .style(|s| {
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let body = stack((
+        heading(
+            move || format!("VULNERABILITIES ({})", report.get().vulnerabilities.len()),
+            theme,
+        ),
+        no_vulnerabilities,
+        vulnerabilities,
+        heading(
+            move || format!("LICENSES ({})", report.get().licenses.len()),
+            theme,
+        ),
+        licenses,
+    ))
+    .style(|s| s.flex_col().width_full());
+
+    stack((
+        header,
+        container(scroll(body).style(|s| s.width_full().height_full()))
+            .style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
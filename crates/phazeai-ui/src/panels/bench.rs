@@ -0,0 +1,441 @@
+//! Benchmarks — the bottom-panel "BENCH" tab.
+//!
+//! The workspace's criterion and pytest-benchmark suites, each runnable on
+//! its own or all together, and every benchmark's recent means on the
+//! current branch as a sparkline. A run is compared with the previous one on
+//! the branch (or on `main` for a new branch) before it's recorded, and a
+//! benchmark that slowed past the threshold is highlighted and raises a
+//! warning notification. Clicking a benchmark opens its suite.
+
+use std::collections::HashMap;
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{
+        create_effect, create_rw_signal, RwSignal, Scope, SignalGet, SignalUpdate, SignalWith,
+    },
+    views::{dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::bench::{self, BenchHistory, BenchSuite, Comparison, Trend};
+use phazeai_core::notifications::Severity;
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::notifications::notify;
+use crate::panels::git_history::action_button;
+use crate::theme::PhazeTheme;
+
+/// Samples drawn in each sparkline.
+const TREND_LENGTH: usize = 20;
+
+/// What the tab shows: the suites and the branch's history.
+#[derive(Clone, Default)]
+struct Snapshot {
+    suites: Vec<BenchSuite>,
+    branch: String,
+    trends: Vec<Trend>,
+}
+
+fn load(root: &std::path::Path) -> Result<Snapshot, String> {
+    let (branch, _) = bench::git_head(root);
+    let history = BenchHistory::open_workspace(root).map_err(|e| e.to_string())?;
+    Ok(Snapshot {
+        suites: bench::discover(root),
+        trends: history
+            .trends(&branch, TREND_LENGTH)
+            .map_err(|e| e.to_string())?,
+        branch,
+    })
+}
+
+/// Run `suites` in order, comparing and recording each one.
+fn run_suites(root: &std::path::Path, suites: &[BenchSuite]) -> Result<Vec<Comparison>, String> {
+    let (branch, commit) = bench::git_head(root);
+    let mut history = BenchHistory::open_workspace(root).map_err(|e| e.to_string())?;
+    let mut comparisons = Vec::new();
+    for suite in suites {
+        let results = bench::run_suite(root, suite).map_err(|e| e.to_string())?;
+        comparisons.extend(
+            history
+                .compare(&branch, &suite.name, &results)
+                .map_err(|e| e.to_string())?,
+        );
+        history
+            .record(&branch, commit.as_deref(), &suite.name, &results)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(comparisons)
+}
+
+/// Means as block characters, lowest to highest.
+fn sparkline(trend: &Trend) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let values: Vec<f64> = trend.samples.iter().map(|s| s.mean_ns).collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            let level = if max > min {
+                ((v - min) / (max - min) * 7.0).round() as usize
+            } else {
+                3
+            };
+            BLOCKS[level.min(7)]
+        })
+        .collect()
+}
+
+fn small_label(
+    text: impl Fn() -> String + 'static,
+    width: f64,
+    theme: RwSignal<PhazeTheme>,
+) -> impl IntoView {
+    label(text).style(move |s| {
+        s.font_size(11.0)
+            .width(width)
+            .color(theme.get().palette.text_secondary)
+    })
+}
+
+pub fn bench_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let notifications = state.notifications;
+    let toast = state.status_toast;
+
+    let snapshot = create_rw_signal(Snapshot::default());
+    let loaded = create_rw_signal(false);
+    // The suite being run, or "all".
+    let running = create_rw_signal(None::<String>);
+    let threshold_text = create_rw_signal(format!("{:.0}", bench::DEFAULT_THRESHOLD * 100.0));
+    // The last run's comparisons, by suite and benchmark.
+    let last_run = create_rw_signal(HashMap::<(String, String), Comparison>::new());
+
+    let threshold = move || {
+        threshold_text
+            .get()
+            .trim()
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .map(|pct| pct / 100.0)
+            .unwrap_or(bench::DEFAULT_THRESHOLD)
+    };
+
+    // ── Loading ────────────────────────────────────────────────────────────
+    let refresh = move || {
+        let root = workspace_root.get_untracked();
+        let on_done = create_ext_action(
+            Scope::current(),
+            move |result: Result<Snapshot, String>| match result {
+                Ok(s) => snapshot.set(s),
+                Err(e) => show_toast(toast, format!("Benchmark history unavailable: {e}")),
+            },
+        );
+        std::thread::spawn(move || on_done(load(&root)));
+    };
+    // Discovered when the tab is first opened, and again for a new workspace.
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |_| {
+            if shown.get() && tab.get() == Tab::Bench && !loaded.get_untracked() {
+                loaded.set(true);
+                refresh();
+            }
+        });
+    }
+    create_effect(move |_| {
+        let _ = workspace_root.get();
+        last_run.set(HashMap::new());
+        if loaded.get_untracked() {
+            refresh();
+        }
+    });
+
+    // ── Running ────────────────────────────────────────────────────────────
+    let run = move |suites: Vec<BenchSuite>, what: String| {
+        if running.get_untracked().is_some() || suites.is_empty() {
+            return;
+        }
+        running.set(Some(what));
+        let root = workspace_root.get_untracked();
+        let on_done = create_ext_action(
+            Scope::current(),
+            move |result: Result<Vec<Comparison>, String>| {
+                running.set(None);
+                refresh();
+                let comparisons = match result {
+                    Ok(c) => c,
+                    Err(e) => {
+                        notify(
+                            notifications,
+                            Severity::Error,
+                            format!("Benchmarks failed: {e}"),
+                        );
+                        return;
+                    }
+                };
+                let limit = threshold();
+                let regressed: Vec<String> = comparisons
+                    .iter()
+                    .filter(|c| c.is_regression(limit))
+                    .map(|c| {
+                        format!(
+                            "{} ({:+.0}%)",
+                            c.result.benchmark,
+                            c.change().unwrap_or(0.0) * 100.0
+                        )
+                    })
+                    .collect();
+                if regressed.is_empty() {
+                    show_toast(
+                        toast,
+                        format!("{} benchmark(s) ran, no regressions", comparisons.len()),
+                    );
+                } else {
+                    notify(
+                        notifications,
+                        Severity::Warning,
+                        format!(
+                            "{} benchmark(s) regressed by more than {:.0}%: {}",
+                            regressed.len(),
+                            limit * 100.0,
+                            regressed.join(", ")
+                        ),
+                    );
+                }
+                last_run.update(|runs| {
+                    for c in comparisons {
+                        runs.insert((c.suite.clone(), c.result.benchmark.clone()), c);
+                    }
+                });
+            },
+        );
+        std::thread::spawn(move || on_done(run_suites(&root, &suites)));
+    };
+    let open_suite = move |name: &str| {
+        let file = snapshot.with_untracked(|s| {
+            s.suites
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| s.file.clone())
+        });
+        if let Some(file) = file {
+            open_file.set(Some(workspace_root.get_untracked().join(file)));
+        }
+    };
+
+    // ── Header ─────────────────────────────────────────────────────────────
+    let header = stack((
+        action_button(
+            move || match running.get() {
+                Some(what) => format!("Running {what}…"),
+                None => "Run All".to_string(),
+            },
+            theme,
+            move || run(snapshot.get_untracked().suites, "all".to_string()),
+        ),
+        action_button(|| "Refresh".to_string(), theme, refresh),
+        label(|| "Regression threshold %".to_string())
+            .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+        text_input(threshold_text).style(move |s| {
+            let p = theme.get().palette;
+            s.width(48.0)
+                .font_size(12.0)
+                .background(p.bg_elevated)
+                .border(1.0)
+                .border_color(p.border)
+                .border_radius(3.0)
+                .padding_horiz(6.0)
+                .color(p.text_primary)
+        }),
+        label(move || {
+            let s = snapshot.get();
+            if s.branch.is_empty() {
+                String::new()
+            } else {
+                format!("{} suite(s)  ·  history on {}", s.suites.len(), s.branch)
+            }
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    // ── Suites ─────────────────────────────────────────────────────────────
+    let suites = dyn_stack(
+        move || snapshot.get().suites,
+        |s| s.name.clone(),
+        move |suite: BenchSuite| {
+            let name = suite.name.clone();
+            let kind = suite.kind.label();
+            stack((
+                label(move || name.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .text_ellipsis()
+                        .color(theme.get().palette.text_primary)
+                }),
+                label(move || kind.to_string())
+                    .style(move |s| s.font_size(10.0).color(theme.get().palette.text_muted)),
+                action_button(
+                    || "Run".to_string(),
+                    theme,
+                    move || run(vec![suite.clone()], suite.name.clone()),
+                ),
+            ))
+            .style(|s| {
+                s.items_center()
+                    .gap(6.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let suites_column = stack((
+        label(|| "SUITES".to_string()).style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(11.0)
+                .color(p.text_secondary)
+                .padding_horiz(10.0)
+                .padding_vert(3.0)
+                .width_full()
+                .border_bottom(1.0)
+                .border_color(p.border)
+        }),
+        label(|| "No criterion benches or pytest-benchmark tests found.".to_string()).style(
+            move |s| {
+                s.font_size(11.0)
+                    .padding(10.0)
+                    .color(theme.get().palette.text_muted)
+                    .apply_if(!snapshot.get().suites.is_empty(), |s| {
+                        s.display(floem::style::Display::None)
+                    })
+            },
+        ),
+        scroll(suites).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.flex_col()
+            .width(300.0)
+            .height_full()
+            .border_right(1.0)
+            .border_color(p.border)
+    });
+
+    // ── Trends ─────────────────────────────────────────────────────────────
+    let rows = dyn_stack(
+        move || snapshot.get().trends,
+        |t| (t.suite.clone(), t.benchmark.clone(), t.samples.len()),
+        move |trend: Trend| {
+            let hovered = create_rw_signal(false);
+            let key = (trend.suite.clone(), trend.benchmark.clone());
+            // The last run's baseline when there is one, else the previous
+            // sample on the branch.
+            let change = {
+                let trend = trend.clone();
+                move || {
+                    last_run
+                        .with(|runs| runs.get(&key).map(|c| c.change()))
+                        .unwrap_or_else(|| trend.change())
+                }
+            };
+            let regressed = {
+                let change = change.clone();
+                move || change().is_some_and(|c| c > threshold())
+            };
+            let latest = trend
+                .latest()
+                .map(|s| bench::format_duration(s.mean_ns))
+                .unwrap_or_default();
+            let line = sparkline(&trend);
+            let runs = trend.samples.len();
+            let name = format!("{}  ·  {}", trend.benchmark, trend.suite);
+            let suite = trend.suite.clone();
+            let regressed_style = regressed.clone();
+            stack((
+                label(move || name.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .text_ellipsis()
+                        .color(theme.get().palette.text_primary)
+                }),
+                small_label(move || latest.clone(), 90.0, theme),
+                label(move || match change() {
+                    Some(c) => format!("{:+.1}%", c * 100.0),
+                    None => "—".to_string(),
+                })
+                .style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(11.0).width(64.0).color(if regressed() {
+                        p.error
+                    } else {
+                        p.text_secondary
+                    })
+                }),
+                label(move || line.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .font_family("JetBrains Mono, monospace".to_string())
+                        .width(170.0)
+                        .color(theme.get().palette.accent)
+                }),
+                small_label(move || format!("{runs} run(s)"), 60.0, theme),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(hovered.get(), |s| s.background(p.bg_elevated))
+                    .apply_if(regressed_style(), |s| {
+                        s.border_left(3.0).border_color(p.error)
+                    })
+            })
+            .on_click_stop(move |_| open_suite(&suite))
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty_msg = label(|| "No recorded runs on this branch yet — run a suite.".to_string())
+        .style(move |s| {
+            s.font_size(12.0)
+                .color(theme.get().palette.text_muted)
+                .padding(12.0)
+                .apply_if(!snapshot.get().trends.is_empty(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        });
+
+    let trends = stack((
+        empty_msg,
+        scroll(rows).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0).height_full());
+
+    stack((
+        header,
+        stack((suites_column, trends)).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
@@ -0,0 +1,411 @@
+//! The project task board — the bottom-panel "BOARD" tab.
+//!
+//! Shows `.phazeai/tasks.json` as columns of cards. Cards are dragged
+//! between and within columns; right-click one to move it, link it to the
+//! current editor line or delete it. Link chips open the file at the line
+//! or the conversation in the chat panel. The agent edits the same file
+//! through its `task_board` tool, so the board reloads when the file changes.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::TrySendError;
+use std::sync::Arc;
+use std::time::Duration;
+
+use floem::{
+    action::show_context_menu,
+    event::{Event, EventListener},
+    ext_event::create_signal_from_channel,
+    keyboard::{Key, NamedKey},
+    menu::{Menu, MenuItem},
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::project::board::{Board, BoardStore, Card, CardLink, BOARD_FILE};
+use phazeai_core::project::FileWatcher;
+use phazeai_core::PhazeError;
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::panels::git_history::action_button;
+
+/// How often the board file's watcher is drained.
+const BOARD_POLL: Duration = Duration::from_millis(300);
+
+const COLUMN_WIDTH: f64 = 260.0;
+
+/// Reload `board` whenever the workspace's board file changes on disk.
+fn watch_board(board: RwSignal<Board>, workspace_root: RwSignal<PathBuf>) {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<()>(1);
+    let changed = create_signal_from_channel(rx);
+    create_effect(move |_| {
+        if changed.get().is_some() {
+            let store = BoardStore::for_workspace(&workspace_root.get_untracked());
+            if let Ok(loaded) = store.load() {
+                board.set(loaded);
+            }
+        }
+    });
+
+    let generation = Arc::new(AtomicU64::new(0));
+    create_effect(move |_| {
+        let root = workspace_root.get();
+        board.set(BoardStore::for_workspace(&root).load().unwrap_or_default());
+        let gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let generation = generation.clone();
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let Ok(watcher) = FileWatcher::shared(&root) else {
+                return;
+            };
+            let Ok(mut events) = watcher.subscribe(&[BOARD_FILE]) else {
+                return;
+            };
+            while generation.load(Ordering::Relaxed) == gen {
+                std::thread::sleep(BOARD_POLL);
+                let mut changed = false;
+                while events.try_recv().is_ok() {
+                    changed = true;
+                }
+                // A full channel already has a reload pending.
+                if changed && matches!(tx.try_send(()), Err(TrySendError::Disconnected(_))) {
+                    return;
+                }
+            }
+        });
+    });
+}
+
+pub fn board_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let goto_line = state.goto_line;
+    let toast = state.status_toast;
+    let active_cursor = state.active_cursor;
+    let open_conversation = state.open_conversation;
+    let show_right_panel = state.show_right_panel;
+
+    let board: RwSignal<Board> = create_rw_signal(Board::default());
+    let new_title = create_rw_signal(String::new());
+    // The card being dragged, and the column it's over.
+    let dragging: RwSignal<Option<u64>> = create_rw_signal(None);
+    let drop_column: RwSignal<Option<String>> = create_rw_signal(None);
+
+    watch_board(board, workspace_root);
+
+    // Re-read on opening the tab, in case the watcher missed a change.
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |was_open: Option<bool>| {
+            let open = shown.get() && tab.get() == Tab::Board;
+            if open && was_open == Some(false) {
+                if let Ok(loaded) =
+                    BoardStore::for_workspace(&workspace_root.get_untracked()).load()
+                {
+                    board.set(loaded);
+                }
+            }
+            open
+        });
+    }
+
+    // Apply a change to the file and show the result.
+    let change = move |f: &dyn Fn(&mut Board) -> Result<(), PhazeError>| {
+        let store = BoardStore::for_workspace(&workspace_root.get_untracked());
+        match store.update(|b| {
+            f(b)?;
+            Ok(b.clone())
+        }) {
+            Ok(updated) => board.set(updated),
+            Err(e) => show_toast(toast, format!("Board: {e}")),
+        }
+    };
+
+    let open_link = move |link: &CardLink| match link {
+        CardLink::File { path, line } => {
+            open_file.set(Some(workspace_root.get_untracked().join(path)));
+            if let Some(line) = line {
+                goto_line.set(*line);
+            }
+        }
+        CardLink::Conversation { id } => {
+            open_conversation.set(Some(id.clone()));
+            show_right_panel.set(true);
+        }
+    };
+
+    let add_card = move || {
+        let title = new_title.get_untracked();
+        if title.trim().is_empty() {
+            return;
+        }
+        change(&|b| b.add_card(&title, "", None).map(|_| ()));
+        new_title.set(String::new());
+    };
+
+    let card_menu = move |card: Card| {
+        let columns = board.get_untracked().columns;
+        let mut menu = Menu::new("");
+        for column in columns.into_iter().filter(|c| *c != card.column) {
+            let id = card.id;
+            menu = menu.entry(
+                MenuItem::new(format!("Move to {column}"))
+                    .action(move || change(&|b| b.move_card(id, &column, None))),
+            );
+        }
+        let id = card.id;
+        let here = active_cursor.get_untracked().map(|(path, line, _)| {
+            let root = workspace_root.get_untracked();
+            let path = path.strip_prefix(&root).unwrap_or(&path).to_path_buf();
+            CardLink::File {
+                path: path.to_string_lossy().to_string(),
+                line: Some(line + 1),
+            }
+        });
+        if let Some(link) = here {
+            menu = menu
+                .separator()
+                .entry(
+                    MenuItem::new(format!("Link {}", link.label())).action(move || {
+                        change(&|b| {
+                            b.card_mut(id)?.link(link.clone());
+                            Ok(())
+                        })
+                    }),
+                );
+        }
+        if !card.links.is_empty() {
+            menu = menu.entry(MenuItem::new("Clear Links").action(move || {
+                change(&|b| {
+                    b.card_mut(id)?.links.clear();
+                    Ok(())
+                })
+            }));
+        }
+        menu = menu.separator().entry(
+            MenuItem::new("Delete Card").action(move || change(&|b| b.remove_card(id).map(|_| ()))),
+        );
+        show_context_menu(menu, None);
+    };
+
+    let card_view = move |card: Card| {
+        let id = card.id;
+        let column = card.column.clone();
+        let title = card.title.clone();
+        let description = card.description.clone();
+        let last_note = card.notes.last().map(|n| n.text.clone());
+        let has_note = last_note.is_some();
+        let has_description = !description.is_empty();
+        let links = card.links.clone();
+        let hovered = create_rw_signal(false);
+
+        let chips = dyn_stack(
+            move || links.clone(),
+            |l| l.label(),
+            move |link: CardLink| {
+                let text = match &link {
+                    CardLink::File { .. } => link.label(),
+                    CardLink::Conversation { .. } => "💬 conversation".to_string(),
+                };
+                container(
+                    label(move || text.clone())
+                        .style(move |s| s.font_size(10.0).color(theme.get().palette.accent)),
+                )
+                .style(move |s| {
+                    let p = theme.get().palette;
+                    s.padding_horiz(5.0)
+                        .padding_vert(1.0)
+                        .border(1.0)
+                        .border_radius(3.0)
+                        .border_color(p.border)
+                        .cursor(floem::style::CursorStyle::Pointer)
+                        .hover(|s| s.background(p.bg_elevated))
+                })
+                .on_click_stop(move |_| open_link(&link))
+            },
+        )
+        .style(|s| {
+            s.flex_row()
+                .flex_wrap(floem::style::FlexWrap::Wrap)
+                .gap(4.0)
+        });
+
+        stack((
+            label(move || format!("#{id}  {title}")).style(move |s| {
+                s.font_size(12.0)
+                    .color(theme.get().palette.text_primary)
+                    .font_weight(floem::text::Weight::SEMIBOLD)
+            }),
+            label(move || description.clone()).style(move |s| {
+                s.font_size(11.0)
+                    .color(theme.get().palette.text_secondary)
+                    .apply_if(!has_description, |s| s.display(floem::style::Display::None))
+            }),
+            chips,
+            label(move || format!("↳ {}", last_note.clone().unwrap_or_default())).style(move |s| {
+                s.font_size(10.0)
+                    .color(theme.get().palette.text_muted)
+                    .apply_if(!has_note, |s| s.display(floem::style::Display::None))
+            }),
+        ))
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.flex_col()
+                .gap(4.0)
+                .width_full()
+                .padding(8.0)
+                .border(1.0)
+                .border_radius(4.0)
+                .border_color(if hovered.get() { p.accent } else { p.border })
+                .background(p.bg_elevated)
+                .cursor(floem::style::CursorStyle::Pointer)
+        })
+        .draggable()
+        .on_event_stop(EventListener::DragStart, move |_| dragging.set(Some(id)))
+        .on_event_stop(EventListener::DragEnd, move |_| drop_column.set(None))
+        .on_event_stop(EventListener::Drop, move |_| {
+            drop_column.set(None);
+            if let Some(dragged) = dragging.get_untracked() {
+                dragging.set(None);
+                let column = column.clone();
+                change(&|b| b.move_card(dragged, &column, Some(id)));
+            }
+        })
+        .on_event_stop(EventListener::PointerEnter, move |_| hovered.set(true))
+        .on_event_stop(EventListener::PointerLeave, move |_| hovered.set(false))
+        .on_event_cont(EventListener::PointerDown, move |event| {
+            if let Event::PointerDown(pointer) = event {
+                if pointer.button.is_secondary() {
+                    if let Some(card) = board.get_untracked().card(id).cloned() {
+                        card_menu(card);
+                    }
+                }
+            }
+        })
+    };
+
+    let column_view = move |name: String| {
+        let (n_count, n_cards, n_drop, n_menu, n_over) = (
+            name.clone(),
+            name.clone(),
+            name.clone(),
+            name.clone(),
+            name.clone(),
+        );
+        let is_target = move || drop_column.get().as_deref() == Some(n_over.as_str());
+        let header = label(move || {
+            let n = board.get().cards_in(&n_count).count();
+            format!("{n_count}  ({n})")
+        })
+        .style(move |s| {
+            s.font_size(11.0)
+                .padding_bottom(4.0)
+                .color(theme.get().palette.text_secondary)
+                .font_weight(floem::text::Weight::BOLD)
+        })
+        .on_event_stop(EventListener::PointerDown, move |event| {
+            if let Event::PointerDown(pointer) = event {
+                if pointer.button.is_secondary() {
+                    let column = n_menu.clone();
+                    let menu = Menu::new("").entry(
+                        MenuItem::new(format!("Remove Column \"{column}\""))
+                            .action(move || change(&|b| b.remove_column(&column))),
+                    );
+                    show_context_menu(menu, None);
+                }
+            }
+        });
+        let cards = dyn_stack(
+            move || board.get().cards_in(&n_cards).cloned().collect::<Vec<_>>(),
+            |c| format!("{}:{}", c.id, c.updated_at),
+            card_view,
+        )
+        .style(|s| s.flex_col().gap(6.0).width_full());
+        stack((
+            header,
+            scroll(cards).style(|s| s.flex_grow(1.0).width_full()),
+        ))
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.flex_col()
+                .width(COLUMN_WIDTH)
+                .min_width(COLUMN_WIDTH)
+                .height_full()
+                .padding(8.0)
+                .border(1.0)
+                .border_radius(4.0)
+                .border_color(if is_target() { p.accent } else { p.border })
+        })
+        .on_event_cont(EventListener::DragOver, move |_| {
+            if drop_column.get_untracked().as_deref() != Some(name.as_str()) {
+                drop_column.set(Some(name.clone()));
+            }
+        })
+        .on_event_stop(EventListener::Drop, move |_| {
+            drop_column.set(None);
+            if let Some(dragged) = dragging.get_untracked() {
+                dragging.set(None);
+                change(&|b| b.move_card(dragged, &n_drop, None));
+            }
+        })
+    };
+
+    let header = stack((
+        text_input(new_title)
+            .placeholder("New card or column name")
+            .on_event_cont(EventListener::KeyDown, move |event| {
+                if let Event::KeyDown(e) = event {
+                    if e.key.logical_key == Key::Named(NamedKey::Enter) {
+                        add_card();
+                    }
+                }
+            })
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width(260.0)
+                    .font_size(12.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(3.0)
+                    .padding_horiz(6.0)
+                    .color(p.text_primary)
+            }),
+        action_button(|| "Add Card".to_string(), theme, add_card),
+        action_button(
+            || "Add Column".to_string(),
+            theme,
+            move || {
+                let name = new_title.get_untracked();
+                if name.trim().is_empty() {
+                    return;
+                }
+                change(&|b| b.add_column(&name));
+                new_title.set(String::new());
+            },
+        ),
+        label(move || {
+            let b = board.get();
+            let done = b.columns.last().map(|c| b.cards_in(c).count()).unwrap_or(0);
+            format!("{} cards · {done} done · {BOARD_FILE}", b.cards.len())
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let columns = dyn_stack(move || board.get().columns, |c| c.clone(), column_view)
+        .style(|s| s.flex_row().gap(8.0).height_full().padding(8.0));
+
+    stack((
+        header,
+        scroll(columns).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
@@ -0,0 +1,211 @@
+//! Line bookmarks — the left-panel "BOOKMARKS" view.
+//!
+//! Lists the open workspace's bookmarks grouped by file. Clicking a line
+//! opens it; each bookmark's note can be edited in place (Enter or leaving
+//! the field saves it). Bookmarks are toggled with Ctrl+Alt+K and walked
+//! with Ctrl+Alt+L / Ctrl+Alt+J.
+
+use std::path::PathBuf;
+
+use floem::{
+    event::{Event, EventListener},
+    keyboard::{Key, NamedKey},
+    reactive::{create_rw_signal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::editing::{Bookmark, Bookmarks, Location};
+
+use crate::app::{go_to_location, IdeState};
+use crate::panels::git_history::action_button;
+
+/// One row of the list: a file heading or a bookmark under it.
+#[derive(Clone)]
+enum Row {
+    File { path: PathBuf, count: usize },
+    Mark(Bookmark),
+}
+
+fn rows(bookmarks: &Bookmarks) -> Vec<Row> {
+    let mut out = Vec::new();
+    for (path, marks) in bookmarks.by_file() {
+        out.push(Row::File {
+            path,
+            count: marks.len(),
+        });
+        out.extend(marks.into_iter().map(Row::Mark));
+    }
+    out
+}
+
+pub fn bookmarks_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let bookmarks = state.bookmarks;
+    let workspace_root = state.workspace_root;
+
+    let header = stack((
+        label(move || format!("BOOKMARKS ({})", bookmarks.get().len())).style(move |s| {
+            s.font_size(11.0)
+                .font_weight(floem::text::Weight::BOLD)
+                .color(theme.get().palette.text_muted)
+                .flex_grow(1.0)
+                .padding_left(12.0)
+        }),
+        action_button(
+            || "Clear All".to_string(),
+            theme,
+            move || bookmarks.set(Bookmarks::new()),
+        ),
+    ))
+    .style(|s| {
+        s.flex_row()
+            .items_center()
+            .padding_vert(6.0)
+            .padding_right(8.0)
+            .width_full()
+    });
+
+    let empty_msg = container(
+        label(|| "No bookmarks. Press Ctrl+Alt+K to bookmark the cursor line.".to_string()).style(
+            move |s| {
+                s.font_size(12.0)
+                    .color(theme.get().palette.text_muted)
+                    .padding(12.0)
+            },
+        ),
+    )
+    .style(move |s| {
+        s.apply_if(!bookmarks.get().is_empty(), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    let list = scroll(
+        dyn_stack(
+            move || rows(&bookmarks.get()),
+            |row| match row {
+                Row::File { path, count } => format!("{}#{count}", path.display()),
+                Row::Mark(b) => format!("{}:{}:{}", b.path.display(), b.line, b.note),
+            },
+            move |row| match row {
+                Row::File { path, count } => {
+                    let root = workspace_root.get_untracked();
+                    let name = path
+                        .strip_prefix(&root)
+                        .unwrap_or(&path)
+                        .display()
+                        .to_string();
+                    label(move || format!("{name}  ({count})"))
+                        .style(move |s| {
+                            let p = theme.get().palette;
+                            s.font_size(12.0)
+                                .color(p.text_secondary)
+                                .padding_horiz(12.0)
+                                .padding_top(6.0)
+                                .padding_bottom(2.0)
+                                .width_full()
+                        })
+                        .into_any()
+                }
+                Row::Mark(mark) => bookmark_row(&state, mark).into_any(),
+            },
+        )
+        .style(|s| s.flex_col().width_full()),
+    )
+    .style(|s| s.flex_grow(1.0).width_full());
+
+    stack((header, empty_msg, list)).style(|s| s.flex_col().width_full().height_full())
+}
+
+/// A bookmark: its line (click to open), an editable note and a remove button.
+fn bookmark_row(state: &IdeState, mark: Bookmark) -> impl IntoView {
+    let theme = state.theme;
+    let bookmarks = state.bookmarks;
+    let hovered = create_rw_signal(false);
+    let note = create_rw_signal(mark.note.clone());
+    let Bookmark { path, line, .. } = mark;
+
+    let save_note = {
+        let path = path.clone();
+        move || {
+            let text = note.get_untracked();
+            if bookmarks
+                .get_untracked()
+                .get(&path, line)
+                .map(|b| b.note.as_str())
+                != Some(text.trim())
+            {
+                bookmarks.update(|b| {
+                    b.set_note(&path, line, &text);
+                });
+            }
+        }
+    };
+
+    let line_label = label(move || format!("{:>5}", line + 1))
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(12.0)
+                .min_width(44.0)
+                .color(if hovered.get() {
+                    p.accent
+                } else {
+                    p.text_muted
+                })
+                .font_family("JetBrains Mono, monospace".to_string())
+                .cursor(floem::style::CursorStyle::Pointer)
+        })
+        .on_click_stop({
+            let state = state.clone();
+            let path = path.clone();
+            move |_| go_to_location(&state, &Location::new(path.clone(), line, 0))
+        })
+        .on_event_stop(EventListener::PointerEnter, move |_| hovered.set(true))
+        .on_event_stop(EventListener::PointerLeave, move |_| hovered.set(false));
+
+    let note_input = text_input(note)
+        .placeholder("Add a note…")
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.flex_grow(1.0)
+                .min_width(0.0)
+                .font_size(11.0)
+                .background(floem::peniko::Color::TRANSPARENT)
+                .border(0.0)
+                .padding_horiz(4.0)
+                .color(p.text_primary)
+        })
+        .on_event_cont(EventListener::KeyDown, {
+            let save_note = save_note.clone();
+            move |event| {
+                if let Event::KeyDown(e) = event {
+                    if e.key.logical_key == Key::Named(NamedKey::Enter) {
+                        save_note();
+                    }
+                }
+            }
+        })
+        .on_event_cont(EventListener::FocusLost, move |_| save_note());
+
+    let remove = label(|| "×".to_string())
+        .style(move |s| {
+            s.font_size(13.0)
+                .padding_horiz(6.0)
+                .color(theme.get().palette.text_muted)
+                .cursor(floem::style::CursorStyle::Pointer)
+        })
+        .on_click_stop(move |_| {
+            bookmarks.update(|b| {
+                b.remove(&path, line);
+            })
+        });
+
+    stack((line_label, note_input, remove)).style(|s| {
+        s.flex_row()
+            .items_center()
+            .width_full()
+            .padding_left(8.0)
+            .padding_right(4.0)
+            .padding_vert(1.0)
+    })
+}
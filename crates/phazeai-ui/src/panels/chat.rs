@@ -1,23 +1,43 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::rc::Rc;
 use std::sync::Arc;
 
 use floem::{
     event::{Event, EventListener},
     ext_event::create_signal_from_channel,
-    keyboard::{Key, Modifiers},
+    keyboard::{Key, Modifiers, NamedKey},
     reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
-    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    views::{container, dyn_stack, img, label, scroll, stack, text_input, Decorators},
     IntoView,
 };
 use phazeai_core::{
-    Agent, AgentEvent, ConversationMetadata, ConversationStore, SavedConversation, SavedMessage,
-    Settings,
+    analysis::{todos, TodoComment},
+    context::{
+        budget, docs, titling, Citation, ContextBudget, ContextOverflow, ContextSource, DocsIndex,
+        Memory,
+    },
+    editing::apply::{self, CodeBlock},
+    llm::{ImageAttachment, ModelInfo, ProviderId, StreamMeter, TaskType, UsageTracker},
+    output::{self, OutputLog},
+    project::{walk_workspace, BoardStore},
+    tools::{shell_code_blocks, ToolApprovalManager, ToolPermission},
+    Agent, AgentEvent, ConversationMetadata, ConversationStore, MemoryStore, SavedConversation,
+    SavedMessage, SearchQuery, Settings, ToolRegistry,
 };
 
+use phazeai_cloud::{team, CloudCredentials, Feature, TeamCache, TeamPromptContext};
+
 use crate::{
-    components::icon::{icons, phaze_icon},
+    app::Tab,
+    command_hover::CommandHover,
+    components::{
+        icon::{icons, phaze_icon},
+        upgrade_prompt,
+        virtual_list::virtual_list,
+    },
+    panels::{editor::BufferCommand, output::append_output, terminal::TerminalRun},
     theme::PhazeTheme,
-    util::safe_get,
+    util::{list_nav_key_opt, safe_get, ListNav},
 };
 
 // ── AI Mode ───────────────────────────────────────────────────────────────────
@@ -75,6 +95,28 @@ pub struct ChatMessage {
     /// True while AI is still generating this message.
     pub loading: bool,
     pub is_error: bool,
+    /// A reasoning model's summarized reasoning, or Claude's thinking, for
+    /// this reply, shown collapsed above it. Not saved with the conversation.
+    pub reasoning: String,
+    /// The file ranges this reply is grounded in, shown as chips under it.
+    pub citations: Vec<Citation>,
+}
+
+impl ChatMessage {
+    /// Changes whenever what the transcript shows for the message does, so
+    /// its row is rebuilt while it streams.
+    fn render_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            &self.content,
+            &self.reasoning,
+            self.loading,
+            self.is_error,
+            self.citations.len(),
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
 }
 
 /// What the background AI thread sends to the Floem UI thread.
@@ -82,6 +124,10 @@ pub struct ChatMessage {
 enum ChatUpdate {
     /// Partial streamed text — contains the FULL accumulated text so far.
     Partial(String),
+    /// Summarized reasoning so far (also the full text).
+    Reasoning(String),
+    /// The file ranges the reply is grounded in; sent just before `Done`.
+    Citations(Vec<Citation>),
     /// Generation complete — final text.
     Done(String),
     /// Tool execution started.
@@ -92,6 +138,15 @@ enum ChatUpdate {
     Err(String),
     /// The user cancelled generation via the Stop button.
     Cancelled(String),
+    /// Live readout of the response so far: elapsed time, tokens, tokens/sec.
+    Stats(String),
+    /// The reply was resumed this many times after its stream dropped.
+    Recovered(u32),
+    /// Time from sending a request to its first streamed token.
+    FirstToken {
+        provider: String,
+        latency: std::time::Duration,
+    },
 }
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
@@ -129,6 +184,7 @@ fn save_conversation(
     conversation_id: &str,
     model_name: &str,
     workspace_root: &std::path::Path,
+    disabled_tools: &[String],
 ) {
     let store = ConversationStore::new().unwrap_or_else(|_| ConversationStore::default());
 
@@ -143,6 +199,7 @@ fn save_conversation(
             content: m.content.clone(),
             timestamp: now_str(),
             tool_name: None,
+            citations: m.citations.clone(),
         })
         .collect();
 
@@ -172,6 +229,9 @@ fn save_conversation(
         message_count: saved_messages.len(),
         model: model_name.to_string(),
         project_dir: cwd,
+        tags: Vec::new(),
+        title_source: Default::default(),
+        disabled_tools: disabled_tools.to_vec(),
     };
 
     let conversation = SavedConversation {
@@ -180,14 +240,187 @@ fn save_conversation(
         system_prompt: None,
     };
 
-    let _ = store.save(&conversation);
+    if store.save(&conversation).is_ok() && titling::wants_auto_title(&conversation) {
+        spawn_auto_title(conversation.metadata.id);
+    }
+}
+
+/// Title and tag a conversation in the background with the quick-answer model.
+fn spawn_auto_title(id: String) {
+    std::thread::spawn(move || {
+        let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        let result = rt.block_on(async {
+            let llm = Settings::load().build_client_for_task(TaskType::QuickAnswer)?;
+            let store = ConversationStore::new()?;
+            titling::auto_title(&store, llm.as_ref(), &id).await
+        });
+        if let Err(e) = result {
+            eprintln!("[PhazeAI] failed to title conversation {id}: {e}");
+        }
+    });
+}
+
+fn chat_messages_from_saved(conv: SavedConversation) -> Vec<ChatMessage> {
+    conv.messages
+        .into_iter()
+        .map(|m| {
+            #[allow(clippy::wildcard_in_or_patterns)]
+            let role = match m.role.as_str() {
+                "user" => ChatRole::User,
+                "assistant" => ChatRole::Assistant,
+                "tool" | "system" | _ => ChatRole::Tool,
+            };
+            ChatMessage {
+                role,
+                content: m.content,
+                loading: false,
+                is_error: false,
+                reasoning: String::new(),
+                citations: m.citations,
+            }
+        })
+        .collect()
+}
+
+/// One row in the History tab.
+#[derive(Clone, Debug)]
+struct HistoryRow {
+    id: String,
+    title: String,
+    tags: Vec<String>,
+    /// `date · model · #tags`
+    detail: String,
+    /// Matching excerpt; empty when listing recent conversations.
+    snippet: String,
+}
+
+impl HistoryRow {
+    fn from_meta(meta: &ConversationMetadata, snippet: String) -> Self {
+        let date = meta.updated_at.get(..10).unwrap_or(&meta.updated_at);
+        let tags: String = meta.tags.iter().map(|t| format!(" #{t}")).collect();
+        Self {
+            id: meta.id.clone(),
+            title: meta.title.clone(),
+            tags: meta.tags.clone(),
+            detail: format!("{date} · {}{tags}", meta.model),
+            snippet,
+        }
+    }
+}
+
+/// A shared team memory or pinned document in the Memory tab.
+#[derive(Clone, Debug)]
+struct TeamRow {
+    id: String,
+    text: String,
+    /// `pinned · author` or `author`
+    detail: String,
+    document: bool,
+    /// The member's role lets them delete it.
+    removable: bool,
+}
+
+fn team_rows(cache: &TeamCache) -> Vec<TeamRow> {
+    let ctx = &cache.context;
+    let memories = ctx.memories.iter().map(|m| TeamRow {
+        id: m.id.clone(),
+        text: m.text.clone(),
+        detail: m.author.clone(),
+        document: false,
+        removable: ctx.role.can_remove(&m.author, &ctx.email),
+    });
+    let documents = ctx.documents.iter().map(|d| TeamRow {
+        id: d.id.clone(),
+        text: d.title.clone(),
+        detail: format!("pinned document · {}", d.pinned_by),
+        document: true,
+        removable: ctx.role.can_remove(&d.pinned_by, &ctx.email),
+    });
+    memories.chain(documents).collect()
+}
+
+/// Delete a team memory or unpin a document off the UI thread, then re-sync
+/// the cached team context and signal `done`.
+fn remove_team_item(row: TeamRow, done: std::sync::mpsc::SyncSender<()>) {
+    std::thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                eprintln!("[PhazeAI] runtime error: {e}");
+                return;
+            }
+        };
+        let result = rt.block_on(async {
+            let client = phazeai_cloud::session_client("").await?;
+            if row.document {
+                client.unpin_document(&row.id).await?;
+            } else {
+                client.delete_team_memory(&row.id).await?;
+            }
+            team::sync(&client).await
+        });
+        if let Err(e) = result {
+            eprintln!("[PhazeAI] failed to remove team item {}: {e}", row.id);
+        }
+        let _ = done.send(());
+    });
+}
+
+/// Search saved conversations off the UI thread. An empty query lists the
+/// most recent ones; `#tag` words filter by tag. Results are tagged with the
+/// query they answer.
+fn load_history(
+    query: String,
+    workspace: Option<String>,
+    tx: std::sync::mpsc::SyncSender<(String, Vec<HistoryRow>)>,
+) {
+    std::thread::spawn(move || {
+        let store = ConversationStore::new().unwrap_or_else(|_| ConversationStore::default());
+        let rows = if query.trim().is_empty() {
+            store
+                .list_recent(100)
+                .unwrap_or_default()
+                .iter()
+                .filter(|m| workspace.is_none() || m.project_dir == workspace)
+                .map(|m| HistoryRow::from_meta(m, String::new()))
+                .collect()
+        } else {
+            let search = SearchQuery {
+                workspace,
+                limit: 50,
+                ..SearchQuery::from_input(&query)
+            };
+            match store.search_messages(&search) {
+                Ok(hits) => hits
+                    .iter()
+                    .map(|h| HistoryRow::from_meta(&h.metadata, h.snippet.replace('\n', " ")))
+                    .collect(),
+                Err(e) => {
+                    eprintln!("[PhazeAI] history search failed: {e}");
+                    Vec::new()
+                }
+            }
+        };
+        let _ = tx.send((query, rows));
+    });
 }
 
 fn send_to_ai(
     user_message: String,
+    images: Vec<ImageAttachment>,
     settings: Settings,
     workspace_root: std::path::PathBuf,
     mode_hint: &'static str,
+    conversation_id: String,
+    disabled_tools: Vec<String>,
     update_tx: std::sync::mpsc::SyncSender<ChatUpdate>,
     cancel_token: Arc<std::sync::atomic::AtomicBool>,
 ) {
@@ -211,7 +444,36 @@ fn send_to_ai(
                     return;
                 }
             };
-            let mut agent = Agent::new(client).with_cancel_token(cancel_token);
+            let mut agent = Agent::new(client)
+                .with_cancel_token(cancel_token)
+                .with_limits(&settings.agent)
+                .with_memory(MemoryStore::for_workspace(&workspace_root))
+                .with_task_board(
+                    BoardStore::for_workspace(&workspace_root),
+                    Some(conversation_id),
+                )
+                .with_http_requests(&workspace_root)
+                .with_file_journal(&workspace_root)
+                .with_databases(&settings.databases, &workspace_root);
+            if let Some(index) = DocsIndex::for_workspace(&workspace_root, &settings.docs) {
+                agent = agent.with_docs(Arc::new(index));
+            }
+
+            // Shared team context (Team plan): use the cached snapshot and
+            // refresh entitlements and the snapshot alongside this run.
+            let creds = CloudCredentials::load();
+            if creds.is_authenticated() {
+                tokio::spawn(async move {
+                    let result = match phazeai_cloud::session_client("").await {
+                        Ok(cloud) => phazeai_cloud::sync_account(&cloud).await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = result {
+                        eprintln!("[PhazeAI] PhazeAI Cloud sync failed: {e}");
+                    }
+                });
+                agent = agent.with_prompt_context(Arc::new(TeamPromptContext));
+            }
 
             // Connect to MCP servers
             let mcp_configs = phazeai_core::mcp::McpManager::load_config(&workspace_root);
@@ -221,6 +483,16 @@ fn send_to_ai(
                 agent.register_mcp_tools(std::sync::Arc::new(std::sync::Mutex::new(mcp_manager)));
             }
 
+            // Tools from plugin sidecars (Node, Go, … helpers) in the settings.
+            for tool in phazeai_sidecar::plugin_sidecar_tools(&settings.plugin_sidecars).await {
+                agent.register_tool(tool);
+            }
+
+            // Tools switched off for this conversation.
+            if !disabled_tools.is_empty() {
+                agent = agent.with_tool_filter(|t| !disabled_tools.iter().any(|d| d == t.name()));
+            }
+
             let (agent_tx, mut agent_rx) = tokio::sync::mpsc::unbounded_channel::<AgentEvent>();
 
             // Prepend the mode system hint (empty for default Chat mode).
@@ -229,23 +501,77 @@ fn send_to_ai(
             } else {
                 format!("{}{}", mode_hint, user_message)
             };
-            let run_fut = agent.run_with_events(&full_prompt, agent_tx);
+            let provider = settings.llm.provider.to_provider_id().name().to_string();
+            let run_fut = agent.run_with_images(&full_prompt, images, agent_tx);
             let drain_fut = async {
                 let mut accumulated = String::new();
-                while let Some(event) = agent_rx.recv().await {
+                let mut reasoning = String::new();
+                let mut new_step = false;
+                let mut meter = StreamMeter::start();
+                let mut resumes = 0;
+                let mut doc_sources = Vec::new();
+                // Refresh the header readout even while no tokens arrive.
+                let mut tick = tokio::time::interval(std::time::Duration::from_millis(250));
+                loop {
+                    let event = tokio::select! {
+                        event = agent_rx.recv() => match event {
+                            Some(event) => event,
+                            None => break,
+                        },
+                        _ = tick.tick() => {
+                            let mut stats = meter.summary();
+                            if resumes > 0 {
+                                stats.push_str(&format!(" · reconnected ×{resumes}"));
+                            }
+                            let _ = update_tx.try_send(ChatUpdate::Stats(stats));
+                            continue;
+                        }
+                    };
                     match event {
+                        AgentEvent::Thinking { .. } => new_step = true,
+                        AgentEvent::ReasoningDelta(text) => {
+                            // Each agent step reasons anew; keep the steps apart.
+                            if std::mem::take(&mut new_step) && !reasoning.is_empty() {
+                                reasoning.push_str("\n\n");
+                            }
+                            reasoning.push_str(&text);
+                            let _ = update_tx.send(ChatUpdate::Reasoning(reasoning.clone()));
+                        }
                         AgentEvent::TextDelta(text) => {
+                            meter.on_text(&text);
                             accumulated.push_str(&text);
                             let _ = update_tx.send(ChatUpdate::Partial(accumulated.clone()));
                         }
+                        AgentEvent::FirstToken { latency } => {
+                            let _ = update_tx.send(ChatUpdate::FirstToken {
+                                provider: provider.clone(),
+                                latency,
+                            });
+                        }
+                        AgentEvent::TokenUsage { output_tokens, .. } => {
+                            meter.set_output_tokens(output_tokens);
+                        }
                         AgentEvent::ToolStart { name } => {
                             let _ = update_tx.send(ChatUpdate::ToolStart { name });
                         }
                         AgentEvent::ToolResult { name, summary, .. } => {
                             let _ = update_tx.send(ChatUpdate::ToolResult { name, summary });
                         }
+                        AgentEvent::StreamResumed { .. } => resumes += 1,
+                        AgentEvent::DocSources(chunks) => doc_sources = chunks,
+                        AgentEvent::Citations(citations) => {
+                            let _ = update_tx.send(ChatUpdate::Citations(citations));
+                        }
                         AgentEvent::Complete { .. } => {
+                            // List the documentation the reply cites under it.
+                            if let Some(footer) = docs::sources_footer(&accumulated, &doc_sources) {
+                                accumulated.push_str(&footer);
+                            }
+                            let _ = update_tx.send(ChatUpdate::Stats(meter.summary()));
                             let _ = update_tx.send(ChatUpdate::Done(accumulated.clone()));
+                            if resumes > 0 {
+                                let _ = update_tx.send(ChatUpdate::Recovered(resumes));
+                            }
                             break;
                         }
                         AgentEvent::Error(e) => {
@@ -276,24 +602,21 @@ fn send_to_ai(
 ///
 /// Settings are re-loaded from disk on each send so model/provider changes in
 /// the settings panel take effect immediately without restarting.
-/// Expand `@filename` mentions in a chat message into file context blocks.
-///
-/// Scans for `@path/to/file` tokens, resolves each relative to `root`,
-/// reads file contents, and prepends them as context. Returns the expanded prompt.
-fn expand_file_mentions(message: &str, root: &std::path::Path) -> String {
+/// `@path/to/file` mentions in a chat message that resolve to files under
+/// `root`, with their contents (very large files truncated).
+fn mentioned_files(message: &str, root: &std::path::Path) -> Vec<(String, Option<String>)> {
     static RE: std::sync::LazyLock<regex::Regex> =
         std::sync::LazyLock::new(|| regex::Regex::new(r"@([\w./\-]+\.\w+)").expect("valid regex"));
-    let re = &*RE;
-    let mut context_blocks = Vec::new();
-    let mut clean_msg = message.to_string();
-
-    for cap in re.captures_iter(message) {
-        let mention = &cap[1];
-        let file_path = root.join(mention);
-        if file_path.is_file() {
-            if let Ok(contents) = std::fs::read_to_string(&file_path) {
+    RE.captures_iter(message)
+        .filter_map(|cap| {
+            let mention = cap[1].to_string();
+            let file_path = root.join(&mention);
+            if !file_path.is_file() {
+                return None;
+            }
+            let contents = std::fs::read_to_string(&file_path).ok().map(|contents| {
                 // Truncate very large files
-                let truncated = if contents.len() > 30_000 {
+                if contents.len() > 30_000 {
                     let end = contents.floor_char_boundary(30_000);
                     format!(
                         "{}...\n[truncated — {} bytes total]",
@@ -302,15 +625,31 @@ fn expand_file_mentions(message: &str, root: &std::path::Path) -> String {
                     )
                 } else {
                     contents
-                };
-                context_blocks.push(format!(
-                    "<file path=\"{}\">\n{}\n</file>",
-                    mention, truncated
-                ));
-            }
-            // Remove the @mention from the visible message
-            clean_msg = clean_msg.replace(&format!("@{mention}"), &format!("`{mention}`"));
+                }
+            });
+            Some((mention, contents))
+        })
+        .collect()
+}
+
+/// Expand `@filename` mentions in a chat message into file context blocks.
+///
+/// Resolves each mention relative to `root`, reads file contents, and
+/// prepends them as context, except for mentions in `dropped`. Returns the
+/// expanded prompt.
+fn expand_file_mentions(message: &str, root: &std::path::Path, dropped: &[String]) -> String {
+    let mut context_blocks = Vec::new();
+    let mut clean_msg = message.to_string();
+
+    for (mention, contents) in mentioned_files(message, root) {
+        if let Some(contents) = contents.filter(|_| !dropped.contains(&mention)) {
+            context_blocks.push(format!(
+                "<file path=\"{}\">\n{}\n</file>",
+                mention, contents
+            ));
         }
+        // Remove the @mention from the visible message
+        clean_msg = clean_msg.replace(&format!("@{mention}"), &format!("`{mention}`"));
     }
 
     if context_blocks.is_empty() {
@@ -324,52 +663,459 @@ fn expand_file_mentions(message: &str, root: &std::path::Path) -> String {
     }
 }
 
+/// A shell command from a reply with a Run button; hovering the command
+/// explains it. The command is classified with the session's tool approval
+/// policy first: clicking Run approves it, except that destructive commands
+/// the policy asks about need a second, explicit "Run anyway" click.
+#[allow(clippy::too_many_arguments)]
+fn run_card(
+    command: String,
+    approvals: RwSignal<ToolApprovalManager>,
+    theme: RwSignal<PhazeTheme>,
+    messages: RwSignal<Vec<ChatMessage>>,
+    chat_run_request: RwSignal<Option<String>>,
+    show_bottom_panel: RwSignal<bool>,
+    bottom_panel_tab: RwSignal<Tab>,
+    command_hover: CommandHover,
+) -> impl IntoView {
+    let destructive =
+        approvals.get_untracked().classify_bash_command(&command) == ToolPermission::Destructive;
+    let confirming = create_rw_signal(false);
+    let preview = command.clone();
+    let hovered_command = command.clone();
+    let hover_leave = command_hover.clone();
+
+    let button = container(label(move || {
+        if confirming.get() {
+            "Run anyway"
+        } else {
+            "▶ Run"
+        }
+    }))
+    .style(move |s| {
+        let t = theme.get();
+        let p = &t.palette;
+        let color = if confirming.get() { p.error } else { p.accent };
+        s.font_size(11.0)
+            .padding_horiz(8.0)
+            .padding_vert(3.0)
+            .border(1.0)
+            .border_radius(4.0)
+            .border_color(color.with_alpha(0.5))
+            .color(color)
+            .cursor(floem::style::CursorStyle::Pointer)
+            .hover(|s| s.background(color.with_alpha(0.15)))
+    })
+    .on_click_stop(move |_| {
+        let params = serde_json::json!({ "command": command });
+        let asks = approvals.get_untracked().needs_approval("bash", &params);
+        if destructive && asks && !confirming.get_untracked() {
+            confirming.set(true);
+            return;
+        }
+        confirming.set(false);
+        approvals.update(|m| m.record_approval("bash"));
+        messages.update(|list| {
+            list.push(ChatMessage {
+                role: ChatRole::Tool,
+                content: format!("$ {command}"),
+                loading: true,
+                is_error: false,
+                reasoning: String::new(),
+                citations: Vec::new(),
+            });
+        });
+        chat_run_request.set(Some(command.clone()));
+        show_bottom_panel.set(true);
+        bottom_panel_tab.set(Tab::Terminal);
+    });
+
+    let warning = label(|| "Destructive command. Check it before running.").style(move |s| {
+        s.font_size(11.0)
+            .color(theme.get().palette.error)
+            .margin_top(4.0)
+            .apply_if(!confirming.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    stack((
+        stack((
+            label(move || {
+                let first = preview.lines().next().unwrap_or("");
+                if preview.lines().count() > 1 {
+                    format!("$ {first} …")
+                } else {
+                    format!("$ {first}")
+                }
+            })
+            .style(move |s| {
+                s.font_size(11.0)
+                    .color(theme.get().palette.text_secondary)
+                    .flex_grow(1.0)
+                    .min_width(0.0)
+            })
+            .on_event_cont(EventListener::PointerEnter, move |_| {
+                command_hover.enter(&hovered_command)
+            })
+            .on_event_cont(EventListener::PointerLeave, move |_| hover_leave.leave()),
+            button,
+        ))
+        .style(|s| s.items_center().gap(8.0).width_full()),
+        warning,
+    ))
+    .style(move |s| {
+        let t = theme.get();
+        let p = &t.palette;
+        s.flex_col()
+            .width_full()
+            .margin_top(6.0)
+            .padding_horiz(8.0)
+            .padding_vert(5.0)
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(p.glass_border)
+            .background(p.bg_deep.with_alpha(0.6))
+    })
+}
+
+/// A code block from a reply with an Apply button, which works out the
+/// file the block is for and opens the edit for review in the COMPARE tab.
+/// Folders the file index evicted are walked again for the search.
+fn apply_card(
+    block: CodeBlock,
+    theme: RwSignal<PhazeTheme>,
+    workspace_root: RwSignal<std::path::PathBuf>,
+    workspace_files: RwSignal<Vec<std::path::PathBuf>>,
+    unindexed_folders: RwSignal<Vec<std::path::PathBuf>>,
+    open_tabs: RwSignal<Vec<std::path::PathBuf>>,
+    buffer_cmd: RwSignal<Option<BufferCommand>>,
+) -> impl IntoView {
+    // What happened to the last Apply, when it didn't open a review.
+    let status: RwSignal<Option<String>> = create_rw_signal(None);
+    let (target_tx, target_rx) = std::sync::mpsc::sync_channel::<Option<std::path::PathBuf>>(1);
+    let target = create_signal_from_channel(target_rx);
+    let body = block.body.clone();
+    create_effect(move |_| match target.get() {
+        Some(Some(path)) => {
+            status.set(None);
+            buffer_cmd.set(Some(BufferCommand::Review {
+                path,
+                body: body.clone(),
+            }));
+        }
+        Some(None) => status.set(Some(
+            "No file in the workspace looks like this code; ask for it with the file's path."
+                .to_string(),
+        )),
+        None => {}
+    });
+
+    let summary = match &block.path {
+        Some(path) => path.clone(),
+        None => {
+            let first = block.body.lines().map(str::trim).find(|l| !l.is_empty());
+            format!("{} …", first.unwrap_or(""))
+        }
+    };
+    let button = container(label(|| "Apply")).style(move |s| {
+        let t = theme.get();
+        let p = &t.palette;
+        s.font_size(11.0)
+            .padding_horiz(8.0)
+            .padding_vert(3.0)
+            .border(1.0)
+            .border_radius(4.0)
+            .border_color(p.accent.with_alpha(0.5))
+            .color(p.accent)
+            .cursor(floem::style::CursorStyle::Pointer)
+            .hover(|s| s.background(p.accent.with_alpha(0.15)))
+    });
+    let button = button.on_click_stop(move |_| {
+        status.set(Some("Finding the file…".to_string()));
+        let block = block.clone();
+        let root = workspace_root.get_untracked();
+        let mut files = workspace_files.get_untracked();
+        let unindexed = unindexed_folders.get_untracked();
+        let open = open_tabs.get_untracked();
+        let tx = target_tx.clone();
+        std::thread::spawn(move || {
+            for folder in &unindexed {
+                files.extend(walk_workspace(folder, None));
+            }
+            let _ = tx.send(apply::find_target(&block, &root, &files, &open));
+        });
+    });
+
+    stack((
+        stack((
+            label(move || summary.clone()).style(move |s| {
+                s.font_size(11.0)
+                    .color(theme.get().palette.text_secondary)
+                    .flex_grow(1.0)
+                    .min_width(0.0)
+            }),
+            button,
+        ))
+        .style(|s| s.items_center().gap(8.0).width_full()),
+        label(move || status.get().unwrap_or_default()).style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.text_muted)
+                .margin_top(4.0)
+                .apply_if(status.get().is_none(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        }),
+    ))
+    .style(move |s| {
+        let t = theme.get();
+        let p = &t.palette;
+        s.flex_col()
+            .width_full()
+            .margin_top(6.0)
+            .padding_horiz(8.0)
+            .padding_vert(5.0)
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(p.glass_border)
+            .background(p.bg_deep.with_alpha(0.6))
+    })
+}
+
+/// Attachment key for the terminal run at an index in `terminal_runs`.
+const TERMINAL_RUN_KEY: &str = "terminal:";
+
+/// Mentioning `@todos` attaches the workspace's TODO comments (TODOS tab).
+const TODOS_MENTION: &str = "@todos";
+/// Attachment key for the `@todos` list.
+const TODOS_KEY: &str = "todos";
+/// TODO comments listed for the model; the rest are only counted.
+const MAX_TODOS_IN_CONTEXT: usize = 200;
+/// A message's height before it is laid out.
+const MESSAGE_HEIGHT: f64 = 60.0;
+
+fn mentions_todos(message: &str) -> bool {
+    message
+        .split_whitespace()
+        .any(|w| w.trim_end_matches(['.', ',', ':', ';', '?', '!']) == TODOS_MENTION)
+}
+
+/// Prefix the prompt with the workspace's TODO comments when the message
+/// mentions `@todos` and the list wasn't dropped.
+fn with_todos(
+    prompt: String,
+    message: &str,
+    todo_list: &[TodoComment],
+    dropped: &[String],
+) -> String {
+    if !mentions_todos(message) || dropped.iter().any(|d| d == TODOS_KEY) {
+        return prompt;
+    }
+    format!(
+        "{}\n{prompt}",
+        todos::todos_context(todo_list, MAX_TODOS_IN_CONTEXT)
+    )
+}
+
+/// A message held back because it doesn't fit the model's context window.
+#[derive(Clone)]
+struct HeldMessage {
+    overflow: ContextOverflow,
+    model: String,
+    /// Available models whose window fits it, smallest first.
+    larger: Vec<(ProviderId, ModelInfo)>,
+}
+
+/// Estimate what sending `message` will cost against the configured model's
+/// context window; `Some` when it doesn't fit. Models whose window isn't
+/// known are not checked.
+fn check_context(
+    settings: &Settings,
+    hint: &str,
+    message: &str,
+    root: &std::path::Path,
+    dropped: &[String],
+    runs: &[TerminalRun],
+    todo_list: &[TodoComment],
+) -> Option<HeldMessage> {
+    let window =
+        budget::context_window(&settings.llm.provider.to_provider_id(), &settings.llm.model)?;
+    let mut parts = ContextBudget::new();
+    parts.add(ContextSource::SystemPrompt, "Mode instructions", hint);
+    for (mention, contents) in mentioned_files(message, root) {
+        if let Some(contents) = contents.filter(|_| !dropped.contains(&mention)) {
+            parts.add(
+                ContextSource::Attachment(mention.clone()),
+                format!("@{mention}"),
+                &contents,
+            );
+        }
+    }
+    if mentions_todos(message) && !dropped.iter().any(|d| d == TODOS_KEY) {
+        parts.add(
+            ContextSource::Attachment(TODOS_KEY.to_string()),
+            TODOS_MENTION,
+            &todos::todos_context(todo_list, MAX_TODOS_IN_CONTEXT),
+        );
+    }
+    for (i, run) in runs.iter().enumerate() {
+        parts.add(
+            ContextSource::Attachment(format!("{TERMINAL_RUN_KEY}{i}")),
+            format!("$ {}", run.command),
+            &run.output,
+        );
+    }
+    parts.add(ContextSource::Prompt, "Your message", message);
+    let overflow = parts
+        .check(window, settings.llm.max_tokens as usize)
+        .err()?;
+    let larger = budget::larger_models(&settings.build_provider_registry(), overflow.estimated);
+    Some(HeldMessage {
+        overflow,
+        model: settings.llm.model.clone(),
+        larger,
+    })
+}
+
+/// Prefix the next prompt with the output of commands run from chat code
+/// blocks, so the model sees what happened.
+fn with_terminal_runs(prompt: String, runs: &[TerminalRun]) -> String {
+    if runs.is_empty() {
+        return prompt;
+    }
+    let outputs: Vec<String> = runs
+        .iter()
+        .map(|r| format!("$ {}\n```\n{}\n```", r.command, r.output))
+        .collect();
+    format!(
+        "I ran these commands in the terminal:\n\n{}\n\n{}",
+        outputs.join("\n\n"),
+        prompt
+    )
+}
+
+/// `chat_run_request` / `chat_run_output`: shell code blocks in replies get a
+/// Run button that sends the command to the integrated terminal; its output
+/// comes back as a tool message and is included with the next prompt.
+/// `command_hover`: explains those commands when they are hovered.
+/// `ai_provider` / `ai_model`: the configured model, switched from here when
+/// a message doesn't fit its context window.
+/// `todos`: the workspace's TODO comments, sent along when a message
+/// mentions `@todos`.
+/// `output`: tool calls and errors are logged to its Agent channel.
+/// `open_file` / `goto_line` / `goto_line_end`: a reply's citation chips open
+/// the cited range in the editor.
+/// `workspace_files` / `unindexed_folders` / `open_tabs` / `buffer_cmd`: a
+/// reply's other code blocks get an Apply button that finds the file each
+/// is for among them and sends the edit to the COMPARE tab for review.
+#[allow(clippy::too_many_arguments)]
 pub fn chat_panel(
     theme: RwSignal<PhazeTheme>,
     ai_thinking: RwSignal<bool>,
     chat_inject: RwSignal<Option<String>>,
     workspace_root: RwSignal<std::path::PathBuf>,
+    font_family: RwSignal<String>,
+    font_size: RwSignal<u32>,
+    font_ligatures: RwSignal<bool>,
+    chat_run_request: RwSignal<Option<String>>,
+    chat_run_output: RwSignal<Option<TerminalRun>>,
+    show_bottom_panel: RwSignal<bool>,
+    bottom_panel_tab: RwSignal<Tab>,
+    command_hover: CommandHover,
+    ai_provider: RwSignal<String>,
+    ai_model: RwSignal<String>,
+    todos: RwSignal<Vec<TodoComment>>,
+    output: RwSignal<OutputLog>,
+    open_file: RwSignal<Option<std::path::PathBuf>>,
+    goto_line: RwSignal<u32>,
+    goto_line_end: RwSignal<u32>,
+    extract_tasks_from: RwSignal<Option<String>>,
+    open_conversation: RwSignal<Option<String>>,
+    chat_images: RwSignal<Vec<std::path::PathBuf>>,
+    workspace_files: RwSignal<Vec<std::path::PathBuf>>,
+    unindexed_folders: RwSignal<Vec<std::path::PathBuf>>,
+    open_tabs: RwSignal<Vec<std::path::PathBuf>>,
+    buffer_cmd: RwSignal<Option<BufferCommand>>,
 ) -> impl IntoView {
     let mut initial_messages = vec![ChatMessage {
         role: ChatRole::Assistant,
         content: "Welcome to PhazeAI. How can I help you?".to_string(),
         loading: false,
         is_error: false,
+        reasoning: String::new(),
+        citations: Vec::new(),
     }];
     let mut initial_id = ConversationStore::generate_id();
+    let mut initial_disabled_tools = Vec::new();
 
     if let Ok(store) = ConversationStore::new() {
         if let Ok(recent) = store.list_recent(1) {
             if let Some(meta) = recent.first() {
                 if let Ok(conv) = store.load(&meta.id) {
                     initial_id = meta.id.clone();
-                    initial_messages.clear();
-                    for m in conv.messages {
-                        #[allow(clippy::wildcard_in_or_patterns)]
-                        let role = match m.role.as_str() {
-                            "user" => ChatRole::User,
-                            "assistant" => ChatRole::Assistant,
-                            "tool" | "system" | _ => ChatRole::Tool,
-                        };
-                        initial_messages.push(ChatMessage {
-                            role,
-                            content: m.content,
-                            loading: false,
-                            is_error: false,
-                        });
-                    }
+                    initial_disabled_tools = conv.metadata.disabled_tools.clone();
+                    initial_messages = chat_messages_from_saved(conv);
                 }
             }
         }
     }
 
     let conversation_id = create_rw_signal(initial_id);
+    let disabled_tools: RwSignal<Vec<String>> = create_rw_signal(initial_disabled_tools);
     let messages: RwSignal<Vec<ChatMessage>> = create_rw_signal(initial_messages);
     let input_text = create_rw_signal(String::new());
     let is_loading = create_rw_signal(false);
     let mode = create_rw_signal(AiMode::Chat);
     let current_cancel_token: RwSignal<Option<Arc<std::sync::atomic::AtomicBool>>> =
         create_rw_signal(None);
+    // Terminal output not yet shown to the model.
+    let terminal_runs: RwSignal<Vec<TerminalRun>> = create_rw_signal(Vec::new());
+    // Approval policy for those commands, kept for the whole session.
+    let approvals = create_rw_signal(ToolApprovalManager::default());
+    // Header readout for the current (or last) response.
+    let stream_stats = create_rw_signal(String::new());
+    // Time to first token per provider this session, and the latest provider.
+    let usage: RwSignal<UsageTracker> = create_rw_signal(UsageTracker::default());
+    let last_provider = create_rw_signal(String::new());
+    // A message too big for the model's context window, waiting for the user
+    // to trim it, switch model or send it anyway; and the @file attachments
+    // removed from it so far.
+    let held: RwSignal<Option<HeldMessage>> = create_rw_signal(None);
+    let dropped_mentions: RwSignal<Vec<String>> = create_rw_signal(Vec::new());
+    let send_anyway = create_rw_signal(false);
+
+    // ── Output of commands run from code blocks ───────────────────────────────
+    create_effect(move |_| {
+        let Some(run) = chat_run_output.get() else {
+            return;
+        };
+        chat_run_output.set(None);
+        let started = format!("$ {}", run.command);
+        let output = if run.output.trim().is_empty() {
+            "(no output)".to_string()
+        } else {
+            run.output.clone()
+        };
+        messages.update(|list| {
+            let pending = list
+                .iter_mut()
+                .rev()
+                .find(|m| m.role == ChatRole::Tool && m.loading && m.content == started);
+            if let Some(msg) = pending {
+                msg.content = format!("{started}\n{output}");
+                msg.loading = false;
+            }
+        });
+        terminal_runs.update(|runs| runs.push(run));
+        let msgs = messages.get_untracked();
+        save_conversation(
+            &msgs,
+            &conversation_id.get_untracked(),
+            &Settings::load().llm.model,
+            &workspace_root.get_untracked(),
+            &disabled_tools.get_untracked(),
+        );
+    });
 
     let (update_tx, update_rx) = std::sync::mpsc::sync_channel::<ChatUpdate>(256);
     let update_signal = create_signal_from_channel(update_rx);
@@ -386,17 +1132,45 @@ pub fn chat_panel(
                         }
                     });
                 }
+                ChatUpdate::Reasoning(text) => {
+                    messages.update(|list| {
+                        if let Some(last) = list
+                            .iter_mut()
+                            .rev()
+                            .find(|m| m.role == ChatRole::Assistant)
+                            .filter(|m| m.loading)
+                        {
+                            last.reasoning = text;
+                        }
+                    });
+                }
+                ChatUpdate::Citations(citations) => {
+                    messages.update(|list| {
+                        if let Some(last) = list
+                            .iter_mut()
+                            .rev()
+                            .find(|m| m.role == ChatRole::Assistant)
+                            .filter(|m| m.loading)
+                        {
+                            last.citations = citations;
+                        }
+                    });
+                }
                 ChatUpdate::ToolStart { name } => {
+                    append_output(output, output::AGENT, &format!("[tool] {name}"));
                     messages.update(|list| {
                         list.push(ChatMessage {
                             role: ChatRole::Tool,
                             content: format!("Running tool: {}...", name),
                             loading: true,
                             is_error: false,
+                            reasoning: String::new(),
+                            citations: Vec::new(),
                         });
                     });
                 }
                 ChatUpdate::ToolResult { name, summary } => {
+                    append_output(output, output::AGENT, &format!("[tool] {name}: {summary}"));
                     messages.update(|list| {
                         if let Some(last) = list.last_mut() {
                             if last.role == ChatRole::Tool && last.loading {
@@ -411,6 +1185,7 @@ pub fn chat_panel(
                         &conversation_id.get_untracked(),
                         &Settings::load().llm.model,
                         &workspace_root.get_untracked(),
+                        &disabled_tools.get_untracked(),
                     );
                 }
                 ChatUpdate::Done(text) => {
@@ -435,9 +1210,11 @@ pub fn chat_panel(
                         &conversation_id.get_untracked(),
                         &Settings::load().llm.model,
                         &workspace_root.get_untracked(),
+                        &disabled_tools.get_untracked(),
                     );
                 }
                 ChatUpdate::Err(e) => {
+                    append_output(output, output::AGENT, &format!("[error] {e}"));
                     messages.update(|list| {
                         // Remove any in-flight loading assistant message so the
                         // error bubble appears cleanly (no empty ghost bubble).
@@ -451,6 +1228,8 @@ pub fn chat_panel(
                             content: format!("Error: {}", e),
                             loading: false,
                             is_error: true,
+                            reasoning: String::new(),
+                            citations: Vec::new(),
                         });
                     });
                     is_loading.set(false);
@@ -461,6 +1240,7 @@ pub fn chat_panel(
                         &conversation_id.get_untracked(),
                         &Settings::load().llm.model,
                         &workspace_root.get_untracked(),
+                        &disabled_tools.get_untracked(),
                     );
                 }
                 ChatUpdate::Cancelled(partial) => {
@@ -487,8 +1267,40 @@ pub fn chat_panel(
                         &conversation_id.get_untracked(),
                         &Settings::load().llm.model,
                         &workspace_root.get_untracked(),
+                        &disabled_tools.get_untracked(),
+                    );
+                }
+                ChatUpdate::Stats(summary) => stream_stats.set(summary),
+                ChatUpdate::Recovered(times) => {
+                    let times = match times {
+                        1 => "once".to_string(),
+                        n => format!("{n} times"),
+                    };
+                    messages.update(|list| {
+                        list.push(ChatMessage {
+                            role: ChatRole::Tool,
+                            content: format!(
+                                "↻ Recovered: the connection dropped {times}; the reply was resumed where it stopped."
+                            ),
+                            loading: false,
+                            is_error: false,
+                            reasoning: String::new(),
+                            citations: Vec::new(),
+                        });
+                    });
+                    let msgs = messages.get_untracked();
+                    save_conversation(
+                        &msgs,
+                        &conversation_id.get_untracked(),
+                        &Settings::load().llm.model,
+                        &workspace_root.get_untracked(),
+                        &disabled_tools.get_untracked(),
                     );
                 }
+                ChatUpdate::FirstToken { provider, latency } => {
+                    usage.update(|u| u.track_first_token(&provider, latency));
+                    last_provider.set(provider);
+                }
             }
         }
     });
@@ -506,40 +1318,88 @@ pub fn chat_panel(
                 return;
             }
 
-            // Expand @file mentions into context blocks before sending to AI
+            // Re-read settings on every send so model/provider changes in the
+            // settings panel take effect immediately (no restart needed).
+            let live_settings = Settings::load();
+            let hint = mode.get_untracked().system_hint();
             let root = workspace_root.get_untracked();
-            let prompt = expand_file_mentions(&trimmed, &root);
+            let dropped = dropped_mentions.get_untracked();
+            if !send_anyway.get_untracked() {
+                if let Some(message) = check_context(
+                    &live_settings,
+                    hint,
+                    &trimmed,
+                    &root,
+                    &dropped,
+                    &terminal_runs.get_untracked(),
+                    &todos.get_untracked(),
+                ) {
+                    held.set(Some(message));
+                    return;
+                }
+            }
+            held.set(None);
+            send_anyway.set(false);
+            dropped_mentions.set(Vec::new());
+
+            // Expand @file mentions into context blocks before sending to AI
+            let prompt = with_terminal_runs(
+                with_todos(
+                    expand_file_mentions(&trimmed, &root, &dropped),
+                    &trimmed,
+                    &todos.get_untracked(),
+                    &dropped,
+                ),
+                &terminal_runs.get_untracked(),
+            );
+            terminal_runs.set(Vec::new());
+            // Attached images go with this message only.
+            let image_paths = chat_images.get_untracked();
+            chat_images.set(Vec::new());
+            let images: Vec<ImageAttachment> = image_paths
+                .iter()
+                .filter_map(|path| ImageAttachment::from_file(path).ok())
+                .collect();
+            let mut shown = trimmed.clone();
+            for path in &image_paths {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                shown.push_str(&format!("\n🖼 {name}"));
+            }
 
             messages.update(|list| {
                 list.push(ChatMessage {
                     role: ChatRole::User,
-                    content: trimmed.clone(),
+                    content: shown,
                     loading: false,
                     is_error: false,
+                    reasoning: String::new(),
+                    citations: Vec::new(),
                 });
                 list.push(ChatMessage {
                     role: ChatRole::Assistant,
                     content: String::new(),
                     loading: true,
                     is_error: false,
+                    reasoning: String::new(),
+                    citations: Vec::new(),
                 });
             });
             input_text.set(String::new());
             is_loading.set(true);
             ai_thinking.set(true);
+            stream_stats.set(String::new());
 
             let token = Arc::new(std::sync::atomic::AtomicBool::new(false));
             current_cancel_token.set(Some(token.clone()));
 
-            // Re-read settings on every send so model/provider changes in the
-            // settings panel take effect immediately (no restart needed).
-            let live_settings = Settings::load();
-            let hint = mode.get_untracked().system_hint();
             send_to_ai(
                 prompt,
+                images,
                 live_settings,
                 root,
                 hint,
+                conversation_id.get_untracked(),
+                disabled_tools.get_untracked(),
                 (*update_tx).clone(),
                 token,
             );
@@ -558,6 +1418,26 @@ pub fn chat_panel(
         });
     }
 
+    // ── Open a conversation linked from the task board ───────────────────────
+    create_effect(move |_| {
+        let Some(id) = open_conversation.get() else {
+            return;
+        };
+        open_conversation.set(None);
+        if is_loading.get_untracked() || conversation_id.get_untracked() == id {
+            return;
+        }
+        let store = ConversationStore::new().unwrap_or_else(|_| ConversationStore::default());
+        match store.load(&id) {
+            Ok(conv) => {
+                conversation_id.set(id);
+                disabled_tools.set(conv.metadata.disabled_tools.clone());
+                messages.set(chat_messages_from_saved(conv));
+            }
+            Err(e) => eprintln!("[PhazeAI] failed to open conversation {id}: {e}"),
+        }
+    });
+
     // ── Header — neon strip + title ───────────────────────────────────────────
 
     // 2px accent-colored top strip (the "neon line" on top of the panel)
@@ -567,16 +1447,94 @@ pub fn chat_panel(
             .background(theme.get().palette.accent)
     });
 
-    let header_content = container(
-        stack((
-            phaze_icon(icons::AI, 14.0, move |p| p.accent, theme),
-            label(|| "  PHAZEAI").style(move |s| {
-                s.font_size(11.0)
-                    .color(theme.get().palette.accent)
-                    .font_weight(floem::text::Weight::BOLD)
-            }),
+    let show_history = create_rw_signal(false);
+    let show_memory = create_rw_signal(false);
+
+    // "History" / "Memory" header buttons; the open one reads "Back to chat".
+    let tab_toggle = move |name: &'static str, open: RwSignal<bool>, other: RwSignal<bool>| {
+        container(label(
+            move || if open.get() { "Back to chat" } else { name },
+        ))
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            s.font_size(11.0)
+                .padding_horiz(8.0)
+                .padding_vert(3.0)
+                .border_radius(4.0)
+                .color(if open.get() { p.accent } else { p.text_muted })
+                .cursor(floem::style::CursorStyle::Pointer)
+                .hover(|s| s.background(p.bg_elevated))
+                .apply_if(other.get(), |s| s.display(floem::style::Display::None))
+        })
+        .on_click_stop(move |_| open.update(|v| *v = !*v))
+    };
+    let history_toggle = tab_toggle("History", show_history, show_memory);
+    let memory_toggle = tab_toggle("Memory", show_memory, show_history);
+
+    // "Tasks" pulls the conversation's action items into plan.md.
+    let tasks_button = container(label(|| "Tasks"))
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            s.font_size(11.0)
+                .padding_horiz(8.0)
+                .padding_vert(3.0)
+                .border_radius(4.0)
+                .color(p.text_muted)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .hover(|s| s.background(p.bg_elevated))
+                .apply_if(show_history.get() || show_memory.get(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        })
+        .on_click_stop(move |_| {
+            let transcript = messages
+                .get_untracked()
+                .iter()
+                .filter(|m| !m.loading && !m.is_error && m.role != ChatRole::Tool)
+                .map(|m| {
+                    let who = if m.role == ChatRole::User {
+                        "User"
+                    } else {
+                        "Assistant"
+                    };
+                    format!("{who}: {}", m.content)
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            extract_tasks_from.set(Some(transcript));
+            bottom_panel_tab.set(Tab::Tasks);
+            show_bottom_panel.set(true);
+        });
+
+    let header_content = container(
+        stack((
+            stack((
+                phaze_icon(icons::AI, 14.0, move |p| p.accent, theme),
+                label(|| "  PHAZEAI").style(move |s| {
+                    s.font_size(11.0)
+                        .color(theme.get().palette.accent)
+                        .font_weight(floem::text::Weight::BOLD)
+                }),
+                label(move || {
+                    let stats = stream_stats.get();
+                    if stats.is_empty() {
+                        return String::new();
+                    }
+                    // Average first-token latency for comparing providers.
+                    let provider = last_provider.get();
+                    match usage.get().first_token.get(&provider) {
+                        Some(avg) => format!("  {stats} · {provider} avg {}ms", avg.average_ms()),
+                        None => format!("  {stats}"),
+                    }
+                })
+                .style(move |s| s.font_size(10.0).color(theme.get().palette.text_muted)),
+            ))
+            .style(|s| s.items_center()),
+            stack((tasks_button, memory_toggle, history_toggle)).style(|s| s.items_center()),
         ))
-        .style(|s| s.items_center()),
+        .style(|s| s.items_center().justify_between().width_full()),
     )
     .style(move |s| {
         let t = theme.get();
@@ -653,6 +1611,163 @@ pub fn chat_panel(
             .padding_vert(4.0)
     });
 
+    // ── Tool toggles (saved with the conversation) ────────────────────────────
+
+    let show_tools = create_rw_signal(false);
+    let all_tools = ToolRegistry::default().names();
+    let read_only_tools = ToolRegistry::read_only().names();
+    let set_disabled = move |list: Vec<String>| {
+        disabled_tools.set(list);
+        // Persist right away once there's a conversation to attach it to.
+        let msgs = messages.get_untracked();
+        if msgs.iter().any(|m| m.role == ChatRole::User) {
+            save_conversation(
+                &msgs,
+                &conversation_id.get_untracked(),
+                &Settings::load().llm.model,
+                &workspace_root.get_untracked(),
+                &disabled_tools.get_untracked(),
+            );
+        }
+    };
+
+    let strip_button = move |text: &'static str, on_click: Box<dyn Fn()>| {
+        container(label(move || text))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.font_size(10.0)
+                    .padding_horiz(6.0)
+                    .padding_vert(2.0)
+                    .border_radius(3.0)
+                    .color(p.text_muted)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .hover(|s| s.background(p.bg_elevated).color(p.text_primary))
+            })
+            .on_click_stop(move |_| on_click())
+    };
+
+    let tools_summary = container(label(move || {
+        let off = disabled_tools.get().len();
+        let arrow = if show_tools.get() { "▾" } else { "▸" };
+        match off {
+            0 => format!("{arrow} Tools: all on"),
+            n => format!("{arrow} Tools: {n} off"),
+        }
+    }))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.font_size(10.0)
+            .flex_grow(1.0)
+            .padding_vert(2.0)
+            .color(if disabled_tools.get().is_empty() {
+                p.text_muted
+            } else {
+                p.warning
+            })
+            .cursor(floem::style::CursorStyle::Pointer)
+    })
+    .on_click_stop(move |_| show_tools.update(|v| *v = !*v));
+
+    let tool_presets = stack((
+        strip_button(
+            "Read-only",
+            Box::new({
+                let all_tools = all_tools.clone();
+                move || {
+                    set_disabled(
+                        all_tools
+                            .iter()
+                            .filter(|t| !read_only_tools.contains(t))
+                            .cloned()
+                            .collect(),
+                    )
+                }
+            }),
+        ),
+        strip_button("All on", Box::new(move || set_disabled(Vec::new()))),
+    ))
+    .style(move |s| {
+        s.items_center().gap(2.0).apply_if(!show_tools.get(), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    let tool_chips = dyn_stack(
+        move || {
+            if show_tools.get() {
+                all_tools.clone()
+            } else {
+                Vec::new()
+            }
+        },
+        |name| name.clone(),
+        move |name: String| {
+            let enabled = {
+                let name = name.clone();
+                move || !disabled_tools.get().contains(&name)
+            };
+            let toggle = {
+                let name = name.clone();
+                move || {
+                    let mut list = disabled_tools.get_untracked();
+                    if let Some(i) = list.iter().position(|d| *d == name) {
+                        list.remove(i);
+                    } else {
+                        list.push(name.clone());
+                    }
+                    set_disabled(list);
+                }
+            };
+            container(label({
+                let enabled = enabled.clone();
+                move || {
+                    let mark = if enabled() { "✓" } else { "✗" };
+                    format!("{mark} {name}")
+                }
+            }))
+            .style(move |s| {
+                let p = theme.get().palette;
+                let on = enabled();
+                s.font_size(10.0)
+                    .padding_horiz(6.0)
+                    .padding_vert(2.0)
+                    .border(1.0)
+                    .border_radius(3.0)
+                    .border_color(if on { p.border } else { p.error })
+                    .color(if on {
+                        p.text_secondary
+                    } else {
+                        p.text_disabled
+                    })
+                    .cursor(floem::style::CursorStyle::Pointer)
+            })
+            .on_click_stop(move |_| toggle())
+        },
+    )
+    .style(move |s| {
+        s.width_full()
+            .gap(4.0)
+            .padding_top(4.0)
+            .flex_wrap(floem::style::FlexWrap::Wrap)
+            .apply_if(!show_tools.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let tools_strip = stack((
+        stack((tools_summary, tool_presets)).style(|s| s.items_center().width_full()),
+        tool_chips,
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.flex_col()
+            .width_full()
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .border_bottom(1.0)
+            .border_color(p.glass_border)
+    });
+
     let do_retry: Rc<dyn Fn()> = Rc::new({
         let update_tx = update_tx.clone();
         move || {
@@ -683,24 +1798,35 @@ pub fn chat_panel(
                         content: String::new(),
                         loading: true,
                         is_error: false,
+                        reasoning: String::new(),
+                        citations: Vec::new(),
                     });
                 });
 
                 is_loading.set(true);
                 ai_thinking.set(true);
+                stream_stats.set(String::new());
 
                 let token = Arc::new(std::sync::atomic::AtomicBool::new(false));
                 current_cancel_token.set(Some(token.clone()));
 
                 let root = workspace_root.get_untracked();
-                let prompt = expand_file_mentions(&user_msg, &root);
+                let prompt = with_todos(
+                    expand_file_mentions(&user_msg, &root, &[]),
+                    &user_msg,
+                    &todos.get_untracked(),
+                    &[],
+                );
                 let live_settings = Settings::load();
                 let hint = mode.get_untracked().system_hint();
                 send_to_ai(
                     prompt,
+                    Vec::new(),
                     live_settings,
                     root,
                     hint,
+                    conversation_id.get_untracked(),
+                    disabled_tools.get_untracked(),
                     (*update_tx).clone(),
                     token,
                 );
@@ -710,181 +1836,342 @@ pub fn chat_panel(
 
     // ── Message bubbles ───────────────────────────────────────────────────────
 
-    let msg_list = dyn_stack(
-        move || {
-            let list = safe_get(messages, Vec::new());
-            let len = list.len();
-            list.into_iter()
-                .enumerate()
-                .map(|(i, msg)| (i, msg, i == len - 1))
-                .collect::<Vec<_>>()
-        },
-        |(i, _, _)| *i,
-        move |(_, msg, is_last)| {
-            let is_user = msg.role == ChatRole::User;
-            let content = msg.content.clone();
-            let loading = msg.loading;
-            let is_error = msg.is_error;
-
-            let text_content = if loading && content.is_empty() {
-                "●●●".to_string()
-            } else {
-                content
-            };
-            let is_typing = loading && text_content.starts_with('●');
-            let is_tool = msg.role == ChatRole::Tool;
-            // Error messages always show retry; other AI messages only on the last one.
-            // Use a signal read inside the style closure so it stays reactive.
-            let show_retry_for_error = is_error && !is_user;
-            let show_retry_for_last = !is_user && is_last && !is_tool;
-            let do_retry_btn = do_retry.clone();
-            let do_retry_btn2 = do_retry.clone();
-
-            // Icon-only retry button shown at the trailing edge of normal AI messages.
-            let icon_retry_btn = container(phaze_icon(
-                icons::REFRESH,
-                12.0,
-                move |p| p.text_secondary,
-                theme,
+    let message_view = move |(i, msg, is_last): (usize, ChatMessage, bool)| {
+        let is_user = msg.role == ChatRole::User;
+        let content = msg.content.clone();
+        let loading = msg.loading;
+        let is_error = msg.is_error;
+
+        let text_content = if loading && content.is_empty() {
+            "●●●".to_string()
+        } else {
+            content
+        };
+        let is_typing = loading && text_content.starts_with('●');
+        let is_tool = msg.role == ChatRole::Tool;
+        // Error messages always show retry; other AI messages only on the last one.
+        // Use a signal read inside the style closure so it stays reactive.
+        let show_retry_for_error = is_error && !is_user;
+        let show_retry_for_last = !is_user && is_last && !is_tool;
+        let do_retry_btn = do_retry.clone();
+        let do_retry_btn2 = do_retry.clone();
+
+        // Icon-only retry button shown at the trailing edge of normal AI messages.
+        let icon_retry_btn = container(phaze_icon(
+            icons::REFRESH,
+            12.0,
+            move |p| p.text_secondary,
+            theme,
+        ))
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            // Reactive: re-evaluate is_loading every render pass.
+            let should_show = show_retry_for_last && !is_loading.get() && !is_error;
+            s.padding(4.0)
+                .border_radius(4.0)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .hover(|s| s.background(p.bg_elevated))
+                .apply_if(!should_show, |s| s.display(floem::style::Display::None))
+        })
+        .on_click_stop(move |_| {
+            (do_retry_btn)();
+        });
+
+        // "Retry" text button shown inside error bubbles.
+        let error_retry_btn = container(
+            stack((
+                phaze_icon(icons::REFRESH, 11.0, move |p| p.error, theme),
+                label(|| " Retry")
+                    .style(move |s| s.font_size(11.0).color(theme.get().palette.error)),
             ))
-            .style(move |s| {
-                let t = theme.get();
-                let p = &t.palette;
-                // Reactive: re-evaluate is_loading every render pass.
-                let should_show = show_retry_for_last && !is_loading.get() && !is_error;
-                s.padding(4.0)
-                    .border_radius(4.0)
-                    .cursor(floem::style::CursorStyle::Pointer)
-                    .hover(|s| s.background(p.bg_elevated))
-                    .apply_if(!should_show, |s| s.display(floem::style::Display::None))
-            })
-            .on_click_stop(move |_| {
-                (do_retry_btn)();
-            });
+            .style(|s| s.items_center()),
+        )
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            s.padding_horiz(8.0)
+                .padding_vert(4.0)
+                .border_radius(6.0)
+                .border(1.0)
+                .border_color(p.error.with_alpha(0.4))
+                .cursor(floem::style::CursorStyle::Pointer)
+                .margin_top(8.0)
+                .hover(|s| s.background(p.error.with_alpha(0.15)))
+                .apply_if(!show_retry_for_error, |s| {
+                    s.display(floem::style::Display::None)
+                })
+        })
+        .on_click_stop(move |_| {
+            (do_retry_btn2)();
+        });
 
-            // "Retry" text button shown inside error bubbles.
-            let error_retry_btn = container(
-                stack((
-                    phaze_icon(icons::REFRESH, 11.0, move |p| p.error, theme),
-                    label(|| " Retry")
-                        .style(move |s| s.font_size(11.0).color(theme.get().palette.error)),
-                ))
-                .style(|s| s.items_center()),
+        // One Run card per shell code block in a finished assistant reply.
+        let run_cards = dyn_stack(
+            move || {
+                let list = safe_get(messages, Vec::new());
+                list.get(i)
+                    .filter(|m| m.role == ChatRole::Assistant && !m.loading && !m.is_error)
+                    .map(|m| shell_code_blocks(&m.content))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .enumerate()
+                    .collect::<Vec<_>>()
+            },
+            |(j, command)| (*j, command.clone()),
+            {
+                let command_hover = command_hover.clone();
+                move |(_, command)| {
+                    run_card(
+                        command,
+                        approvals,
+                        theme,
+                        messages,
+                        chat_run_request,
+                        show_bottom_panel,
+                        bottom_panel_tab,
+                        command_hover.clone(),
+                    )
+                }
+            },
+        )
+        .style(|s| s.flex_col().width_full());
+
+        // One Apply card per other code block in a finished assistant reply.
+        let apply_cards = dyn_stack(
+            move || {
+                let list = safe_get(messages, Vec::new());
+                list.get(i)
+                    .filter(|m| m.role == ChatRole::Assistant && !m.loading && !m.is_error)
+                    .map(|m| apply::code_blocks(&m.content))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .enumerate()
+                    .collect::<Vec<_>>()
+            },
+            |(j, block)| (*j, block.body.clone()),
+            move |(_, block)| {
+                apply_card(
+                    block,
+                    theme,
+                    workspace_root,
+                    workspace_files,
+                    unindexed_folders,
+                    open_tabs,
+                    buffer_cmd,
+                )
+            },
+        )
+        .style(|s| s.flex_col().width_full());
+
+        // Chips for the file ranges a finished reply is grounded in; each
+        // opens its range in the editor.
+        let citation_chips =
+            dyn_stack(
+                move || {
+                    let list = safe_get(messages, Vec::new());
+                    list.get(i)
+                        .filter(|m| m.role == ChatRole::Assistant && !m.loading)
+                        .map(|m| m.citations.clone())
+                        .unwrap_or_default()
+                },
+                |c| c.location(),
+                move |c| {
+                    let text = c.label();
+                    container(label(move || text.clone()).style(move |s| {
+                        s.font_size(11.0).color(theme.get().palette.text_secondary)
+                    }))
+                    .style(move |s| {
+                        let p = &theme.get().palette;
+                        s.padding_horiz(8.0)
+                            .padding_vert(3.0)
+                            .margin_right(6.0)
+                            .margin_top(6.0)
+                            .border(1.0)
+                            .border_color(p.glass_border)
+                            .border_radius(10.0)
+                            .cursor(floem::style::CursorStyle::Pointer)
+                            .hover(|s| s.background(p.bg_elevated))
+                    })
+                    .on_click_stop(move |_| {
+                        open_file.set(Some(c.resolve(&workspace_root.get_untracked())));
+                        goto_line_end.set(c.end_line);
+                        goto_line.set(c.start_line);
+                    })
+                },
             )
+            .style(|s| {
+                s.flex_row()
+                    .flex_wrap(floem::style::FlexWrap::Wrap)
+                    .width_full()
+            });
+
+        // The model's reasoning or thinking, collapsed above the reply.
+        let reasoning_open = create_rw_signal(false);
+        let reasoning = move || {
+            safe_get(messages, Vec::new())
+                .get(i)
+                .map(|m| m.reasoning.clone())
+                .unwrap_or_default()
+        };
+        let reasoning_section = stack((
+            label(move || {
+                let arrow = if reasoning_open.get() { "▾" } else { "▸" };
+                let answering = safe_get(messages, Vec::new())
+                    .get(i)
+                    .is_some_and(|m| m.loading && m.content.is_empty());
+                let title = if answering { "Thinking…" } else { "Thinking" };
+                format!("{arrow} {title}")
+            })
             .style(move |s| {
-                let t = theme.get();
-                let p = &t.palette;
-                s.padding_horiz(8.0)
-                    .padding_vert(4.0)
-                    .border_radius(6.0)
-                    .border(1.0)
-                    .border_color(p.error.with_alpha(0.4))
+                let p = &theme.get().palette;
+                s.font_size(11.0)
+                    .color(p.text_muted)
                     .cursor(floem::style::CursorStyle::Pointer)
-                    .margin_top(8.0)
-                    .hover(|s| s.background(p.error.with_alpha(0.15)))
-                    .apply_if(!show_retry_for_error, |s| {
+                    .hover(|s| s.color(p.text_secondary))
+            })
+            .on_click_stop(move |_| reasoning_open.update(|open| *open = !*open)),
+            label(reasoning).style(move |s| {
+                let p = &theme.get().palette;
+                s.font_size(font_size.get() as f32 - 2.0)
+                    .color(p.text_muted)
+                    .line_height(1.5)
+                    .max_width_pct(100.0)
+                    .margin_top(4.0)
+                    .padding_left(8.0)
+                    .border_left(2.0)
+                    .border_color(p.glass_border)
+                    .apply_if(!reasoning_open.get(), |s| {
                         s.display(floem::style::Display::None)
                     })
-            })
-            .on_click_stop(move |_| {
-                (do_retry_btn2)();
-            });
+            }),
+        ))
+        .style(move |s| {
+            s.flex_col()
+                .width_full()
+                .margin_bottom(6.0)
+                .apply_if(reasoning().is_empty(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        });
 
-            container(
+        container(
+            stack((
+                reasoning_section,
+                // Row: tool-chip + message text + icon retry button (non-error AI messages)
                 stack((
-                    // Row: tool-chip + message text + icon retry button (non-error AI messages)
                     stack((
-                        stack((
-                            phaze_icon(icons::CHIP, 11.0, move |p| p.accent, theme).style(
-                                move |s: floem::style::Style| {
-                                    s.apply_if(!is_tool, |s| s.display(floem::style::Display::None))
-                                },
-                            ),
-                            label(move || text_content.clone()).style(move |s| {
-                                let t = theme.get();
-                                let p = &t.palette;
-                                s.font_size(if is_tool { 11.0 } else { 13.0 })
-                                    .color(if is_user {
-                                        p.text_primary
-                                    } else if is_error {
-                                        p.error
-                                    } else if is_typing || is_tool {
-                                        p.accent
-                                    } else {
-                                        p.text_secondary
-                                    })
-                                    .max_width_pct(100.0)
-                                    .line_height(1.5)
-                                    .apply_if(is_tool, |s| {
-                                        s.font_weight(floem::text::Weight::MEDIUM)
-                                    })
-                            }),
-                        ))
-                        .style(|s| s.items_center().flex_grow(1.0)),
-                        icon_retry_btn,
+                        phaze_icon(icons::CHIP, 11.0, move |p| p.accent, theme).style(
+                            move |s: floem::style::Style| {
+                                s.apply_if(!is_tool, |s| s.display(floem::style::Display::None))
+                            },
+                        ),
+                        label(move || text_content.clone()).style(move |s| {
+                            let t = theme.get();
+                            let p = &t.palette;
+                            let fs = font_size.get() as f32;
+                            let family = font_family.get();
+                            s.font_size(if is_tool { fs - 2.0 } else { fs })
+                                .apply_if(!family.trim().is_empty(), |s| {
+                                    s.font_family(crate::fonts::font_stack_css(
+                                        &family,
+                                        font_ligatures.get(),
+                                    ))
+                                })
+                                .color(if is_user {
+                                    p.text_primary
+                                } else if is_error {
+                                    p.error
+                                } else if is_typing || is_tool {
+                                    p.accent
+                                } else {
+                                    p.text_secondary
+                                })
+                                .max_width_pct(100.0)
+                                .line_height(1.5)
+                                .apply_if(is_tool, |s| s.font_weight(floem::text::Weight::MEDIUM))
+                        }),
                     ))
-                    .style(|s| s.items_center().justify_between().width_full()),
-                    // Error retry button below the error text (only for error bubbles)
-                    error_retry_btn,
+                    .style(|s| s.items_center().flex_grow(1.0)),
+                    icon_retry_btn,
                 ))
-                .style(|s| s.flex_col().width_full()),
-            )
-            .style(move |s| {
-                let t = theme.get();
-                let p = &t.palette;
-                if is_user {
-                    // User bubble: accent tinted glass
-                    s.width_full()
-                        .padding_horiz(14.0)
-                        .padding_vert(10.0)
-                        .background(p.accent_dim)
-                        .border(1.0)
-                        .border_color(p.glass_border)
-                        .border_radius(12.0)
-                        .margin_bottom(8.0)
-                        // Subtle inner glow
-                        .box_shadow_blur(12.0)
-                        .box_shadow_color(p.glow)
-                        .box_shadow_spread(0.0)
-                        .box_shadow_h_offset(0.0)
-                        .box_shadow_v_offset(0.0)
-                } else if is_tool {
-                    // Tool card: specialized micro-bubble
-                    s.width_full()
-                        .padding_horiz(10.0)
-                        .padding_vert(6.0)
-                        .background(p.bg_deep.with_alpha(0.6))
-                        .border(1.0)
-                        .border_color(p.glass_border)
-                        .border_radius(6.0)
-                        .margin_bottom(6.0)
-                        .margin_horiz(20.0) // Indent tool calls
-                } else if is_error {
-                    s.width_full()
-                        .padding_horiz(14.0)
-                        .padding_vert(10.0)
-                        .background(p.error.with_alpha(0.1))
-                        .border(1.0)
-                        .border_color(p.error.with_alpha(0.3))
-                        .border_radius(10.0)
-                        .margin_bottom(8.0)
-                } else {
-                    // Assistant bubble: darker glass for better readability
-                    s.width_full()
-                        .padding_horiz(14.0)
-                        .padding_vert(10.0)
-                        .background(p.bg_panel)
-                        .border(1.0)
-                        .border_color(p.glass_border)
-                        .border_radius(10.0)
-                        .margin_bottom(8.0)
-                }
-            })
+                .style(|s| s.items_center().justify_between().width_full()),
+                run_cards,
+                apply_cards,
+                citation_chips,
+                // Error retry button below the error text (only for error bubbles)
+                error_retry_btn,
+            ))
+            .style(|s| s.flex_col().width_full()),
+        )
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            if is_user {
+                // User bubble: accent tinted glass
+                s.width_full()
+                    .padding_horiz(14.0)
+                    .padding_vert(10.0)
+                    .background(p.accent_dim)
+                    .border(1.0)
+                    .border_color(p.glass_border)
+                    .border_radius(12.0)
+                    .margin_bottom(8.0)
+                    // Subtle inner glow
+                    .box_shadow_blur(12.0)
+                    .box_shadow_color(p.glow)
+                    .box_shadow_spread(0.0)
+                    .box_shadow_h_offset(0.0)
+                    .box_shadow_v_offset(0.0)
+            } else if is_tool {
+                // Tool card: specialized micro-bubble
+                s.width_full()
+                    .padding_horiz(10.0)
+                    .padding_vert(6.0)
+                    .background(p.bg_deep.with_alpha(0.6))
+                    .border(1.0)
+                    .border_color(p.glass_border)
+                    .border_radius(6.0)
+                    .margin_bottom(6.0)
+                    .margin_horiz(20.0) // Indent tool calls
+            } else if is_error {
+                s.width_full()
+                    .padding_horiz(14.0)
+                    .padding_vert(10.0)
+                    .background(p.error.with_alpha(0.1))
+                    .border(1.0)
+                    .border_color(p.error.with_alpha(0.3))
+                    .border_radius(10.0)
+                    .margin_bottom(8.0)
+            } else {
+                // Assistant bubble: darker glass for better readability
+                s.width_full()
+                    .padding_horiz(14.0)
+                    .padding_vert(10.0)
+                    .background(p.bg_panel)
+                    .border(1.0)
+                    .border_color(p.glass_border)
+                    .border_radius(10.0)
+                    .margin_bottom(8.0)
+            }
+        })
+    };
+
+    let messages_scroll = virtual_list(
+        move || {
+            let list = safe_get(messages, Vec::new());
+            let len = list.len();
+            list.into_iter()
+                .enumerate()
+                .map(|(i, msg)| (i, msg, i == len - 1))
+                .collect::<Vec<_>>()
         },
+        |(i, msg, is_last)| (*i, *is_last, msg.render_key()),
+        move |row| container(message_view(row)).style(|s| s.padding_horiz(10.0).width_full()),
+        MESSAGE_HEIGHT,
+        || None,
     )
-    .style(|s| s.flex_col().padding(10.0).gap(0.0).width_full());
-
-    let messages_scroll = scroll(msg_list).style(|s| s.flex_grow(1.0).min_height(0.0).width_full());
+    .style(|s| s.flex_grow(1.0).min_height(0.0).width_full());
 
     // ── Input bar ─────────────────────────────────────────────────────────────
 
@@ -955,6 +2242,7 @@ pub fn chat_panel(
         .style(move |s| {
             let t = theme.get();
             let p = &t.palette;
+            let family = font_family.get();
             s.flex_grow(1.0)
                 .background(p.glass_bg)
                 .border(1.0)
@@ -963,7 +2251,10 @@ pub fn chat_panel(
                 .color(p.text_primary)
                 .padding_horiz(12.0)
                 .padding_vert(8.0)
-                .font_size(13.0)
+                .font_size(font_size.get() as f32)
+                .apply_if(!family.trim().is_empty(), |s| {
+                    s.font_family(crate::fonts::font_stack_css(&family, font_ligatures.get()))
+                })
                 .min_width(0.0)
         })
         .on_event_stop(EventListener::KeyDown, move |event| {
@@ -992,9 +2283,812 @@ pub fn chat_panel(
             .background(p.glass_bg)
     });
 
+    // ── History tab ───────────────────────────────────────────────────────────
+
+    let history_query = create_rw_signal(String::new());
+    let history_this_workspace = create_rw_signal(false);
+    let history_rows: RwSignal<Vec<HistoryRow>> = create_rw_signal(Vec::new());
+    let (history_tx, history_rx) = std::sync::mpsc::sync_channel::<(String, Vec<HistoryRow>)>(8);
+    let history_results = create_signal_from_channel(history_rx);
+    // Bumped after an edit so the list reloads.
+    let history_refresh = create_rw_signal(0u64);
+    let editing_id: RwSignal<Option<String>> = create_rw_signal(None);
+    let edit_title = create_rw_signal(String::new());
+    let edit_tags = create_rw_signal(String::new());
+    // Row picked with ↑/↓ from the search box; Enter opens it.
+    let history_selected: RwSignal<Option<usize>> = create_rw_signal(None);
+
+    create_effect(move |_| {
+        let _ = history_refresh.get();
+        if !show_history.get() {
+            return;
+        }
+        let workspace = history_this_workspace
+            .get()
+            .then(|| workspace_root.get_untracked().display().to_string());
+        load_history(history_query.get(), workspace, history_tx.clone());
+    });
+    create_effect(move |_| {
+        if let Some((query, rows)) = history_results.get() {
+            // Drop answers to queries the user has already typed past.
+            if query == history_query.get_untracked() {
+                history_rows.set(rows);
+                history_selected.set(None);
+            }
+        }
+    });
+
+    let open_conversation = move |id: &str| {
+        if is_loading.get_untracked() {
+            return;
+        }
+        let store = ConversationStore::new().unwrap_or_else(|_| ConversationStore::default());
+        match store.load(id) {
+            Ok(conv) => {
+                conversation_id.set(id.to_string());
+                disabled_tools.set(conv.metadata.disabled_tools.clone());
+                messages.set(chat_messages_from_saved(conv));
+                show_history.set(false);
+            }
+            Err(e) => eprintln!("[PhazeAI] failed to open conversation {id}: {e}"),
+        }
+    };
+
+    let history_search = text_input(history_query)
+        .placeholder("Search conversations…")
+        .on_event_cont(EventListener::KeyDown, move |event| {
+            let Event::KeyDown(e) = event else {
+                return;
+            };
+            // Home/End stay with the text cursor.
+            let Key::Named(
+                ref named @ (NamedKey::ArrowUp
+                | NamedKey::ArrowDown
+                | NamedKey::PageUp
+                | NamedKey::PageDown
+                | NamedKey::Enter
+                | NamedKey::Escape),
+            ) = e.key.logical_key
+            else {
+                return;
+            };
+            let rows = history_rows.get_untracked();
+            match list_nav_key_opt(named, history_selected.get_untracked(), rows.len()) {
+                ListNav::Move(i) => history_selected.set(Some(i)),
+                ListNav::Accept(i) => {
+                    if let Some(row) = rows.get(i) {
+                        open_conversation(&row.id);
+                    }
+                }
+                ListNav::Close => history_selected.set(None),
+                ListNav::None => {}
+            }
+        })
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            s.flex_grow(1.0)
+                .min_width(0.0)
+                .background(p.glass_bg)
+                .border(1.0)
+                .border_color(p.border_focus)
+                .border_radius(6.0)
+                .color(p.text_primary)
+                .padding_horiz(10.0)
+                .padding_vert(6.0)
+                .font_size(12.0)
+        });
+
+    let workspace_chip = container(label(|| "This workspace"))
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            let on = history_this_workspace.get();
+            s.font_size(11.0)
+                .padding_horiz(8.0)
+                .padding_vert(5.0)
+                .margin_left(6.0)
+                .border_radius(4.0)
+                .border(1.0)
+                .border_color(if on { p.accent } else { p.glass_border })
+                .color(if on { p.accent } else { p.text_muted })
+                .background(if on {
+                    p.accent_dim
+                } else {
+                    floem::peniko::Color::TRANSPARENT
+                })
+                .cursor(floem::style::CursorStyle::Pointer)
+        })
+        .on_click_stop(move |_| history_this_workspace.update(|v| *v = !*v));
+
+    let history_bar = stack((history_search, workspace_chip)).style(move |s| {
+        let t = theme.get();
+        s.items_center()
+            .width_full()
+            .padding(8.0)
+            .border_bottom(1.0)
+            .border_color(t.palette.glass_border)
+    });
+
+    // Title / tags editor shown above the list while a row is being edited.
+    let save_edit = move || {
+        let Some(id) = editing_id.get_untracked() else {
+            return;
+        };
+        let store = ConversationStore::new().unwrap_or_else(|_| ConversationStore::default());
+        let title = edit_title.get_untracked();
+        let tags: Vec<String> = edit_tags
+            .get_untracked()
+            .split([',', ' '])
+            .filter(|t| !t.trim().is_empty())
+            .map(str::to_string)
+            .collect();
+        let unchanged_title = history_rows
+            .get_untracked()
+            .iter()
+            .any(|r| r.id == id && r.title == title.trim());
+        let result = if title.trim().is_empty() || unchanged_title {
+            Ok(())
+        } else {
+            store.rename(&id, &title)
+        }
+        .and_then(|_| store.set_tags(&id, &tags));
+        if let Err(e) = result {
+            eprintln!("[PhazeAI] failed to update conversation {id}: {e}");
+        }
+        editing_id.set(None);
+        history_refresh.update(|n| *n += 1);
+    };
+
+    let edit_input = move |value: RwSignal<String>, placeholder: &'static str| {
+        text_input(value)
+            .placeholder(placeholder)
+            .style(move |s| {
+                let t = theme.get();
+                let p = &t.palette;
+                s.width_full()
+                    .margin_bottom(6.0)
+                    .background(p.glass_bg)
+                    .border(1.0)
+                    .border_color(p.border_focus)
+                    .border_radius(6.0)
+                    .color(p.text_primary)
+                    .padding_horiz(8.0)
+                    .padding_vert(5.0)
+                    .font_size(12.0)
+            })
+            .on_event_stop(EventListener::KeyDown, move |event| {
+                if let Event::KeyDown(e) = event {
+                    match &e.key.logical_key {
+                        Key::Named(floem::keyboard::NamedKey::Enter) => save_edit(),
+                        Key::Named(floem::keyboard::NamedKey::Escape) => editing_id.set(None),
+                        _ => {}
+                    }
+                }
+            })
+    };
+
+    let edit_button = move |text: &'static str, on_click: Rc<dyn Fn()>| {
+        container(label(move || text))
+            .style(move |s| {
+                let t = theme.get();
+                let p = &t.palette;
+                s.font_size(11.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(4.0)
+                    .margin_right(6.0)
+                    .border_radius(4.0)
+                    .border(1.0)
+                    .border_color(p.glass_border)
+                    .color(p.text_secondary)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .hover(|s| s.background(p.bg_elevated))
+            })
+            .on_click_stop(move |_| on_click())
+    };
+
+    let history_editor = stack((
+        edit_input(edit_title, "Title"),
+        edit_input(edit_tags, "Tags, comma separated"),
+        stack((
+            edit_button("Save", Rc::new(save_edit)),
+            edit_button("Cancel", Rc::new(move || editing_id.set(None))),
+        )),
+    ))
+    .style(move |s| {
+        let t = theme.get();
+        s.flex_col()
+            .width_full()
+            .padding(8.0)
+            .border_bottom(1.0)
+            .border_color(t.palette.glass_border)
+            .background(t.palette.bg_elevated)
+            .apply_if(editing_id.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let history_list = dyn_stack(
+        move || {
+            history_rows
+                .get()
+                .into_iter()
+                .enumerate()
+                .collect::<Vec<_>>()
+        },
+        |(i, row)| (*i, row.id.clone(), row.detail.clone(), row.title.clone()),
+        move |(idx, row)| {
+            let id = row.id.clone();
+            let has_snippet = !row.snippet.is_empty();
+            let title = row.title.clone();
+            let detail = row.detail.clone();
+            let snippet = row.snippet.clone();
+            let edit_row = row.clone();
+            let edit = container(label(|| "✎"))
+                .style(move |s| {
+                    let t = theme.get();
+                    let p = &t.palette;
+                    s.font_size(11.0)
+                        .padding_horiz(5.0)
+                        .border_radius(4.0)
+                        .color(p.text_muted)
+                        .cursor(floem::style::CursorStyle::Pointer)
+                        .hover(|s| s.background(p.bg_panel).color(p.accent))
+                })
+                .on_click_stop(move |_| {
+                    edit_title.set(edit_row.title.clone());
+                    edit_tags.set(edit_row.tags.join(", "));
+                    editing_id.set(Some(edit_row.id.clone()));
+                });
+            let row = stack((
+                stack((
+                    label(move || title.clone()).style(move |s| {
+                        s.font_size(12.0)
+                            .flex_grow(1.0)
+                            .min_width(0.0)
+                            .color(theme.get().palette.text_primary)
+                            .font_weight(floem::text::Weight::MEDIUM)
+                    }),
+                    edit,
+                ))
+                .style(|s| s.items_center().width_full()),
+                label(move || detail.clone())
+                    .style(move |s| s.font_size(10.0).color(theme.get().palette.text_muted)),
+                label(move || snippet.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .margin_top(3.0)
+                        .color(theme.get().palette.text_secondary)
+                        .apply_if(!has_snippet, |s| s.display(floem::style::Display::None))
+                }),
+            ))
+            .style(move |s| {
+                let t = theme.get();
+                let p = &t.palette;
+                s.flex_col()
+                    .width_full()
+                    .padding_horiz(12.0)
+                    .padding_vert(8.0)
+                    .border_bottom(1.0)
+                    .border_color(p.glass_border)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(history_selected.get() == Some(idx), |s| {
+                        s.background(p.selection)
+                    })
+                    .hover(|s| s.background(p.bg_elevated))
+            })
+            .on_click_stop(move |_| open_conversation(&id));
+            let row_id = row.id();
+            create_effect(move |_| {
+                if history_selected.get() == Some(idx) {
+                    row_id.scroll_to(None);
+                }
+            });
+            row
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let history_empty = label(move || {
+        if history_query.get().trim().is_empty() {
+            "No saved conversations yet."
+        } else {
+            "No matching conversations."
+        }
+    })
+    .style(move |s| {
+        s.font_size(11.0)
+            .padding(14.0)
+            .color(theme.get().palette.text_muted)
+            .apply_if(!history_rows.get().is_empty(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let history_view = stack((
+        history_bar,
+        history_editor,
+        scroll(stack((history_list, history_empty)).style(|s| s.flex_col().width_full()))
+            .style(|s| s.flex_grow(1.0).min_height(0.0).width_full()),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .width_full()
+            .flex_grow(1.0)
+            .min_height(0.0)
+            .apply_if(!show_history.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    // ── Memory tab ────────────────────────────────────────────────────────────
+
+    let memory_rows: RwSignal<Vec<Memory>> = create_rw_signal(Vec::new());
+    let memory_input = create_rw_signal(String::new());
+    // Bumped after an add or delete so the list reloads.
+    let memory_refresh = create_rw_signal(0u64);
+    let memory_store = move || MemoryStore::for_workspace(&workspace_root.get_untracked());
+
+    // Shared team context, from the cached snapshot (Team plan only).
+    let team_heading: RwSignal<Option<String>> = create_rw_signal(None);
+    let team_items: RwSignal<Vec<TeamRow>> = create_rw_signal(Vec::new());
+    let team_upsell = create_rw_signal(false);
+    let (team_tx, team_rx) = std::sync::mpsc::sync_channel::<()>(4);
+    let team_removed = create_signal_from_channel(team_rx);
+
+    create_effect(move |_| {
+        let _ = memory_refresh.get();
+        if !show_memory.get() {
+            return;
+        }
+        match memory_store().list() {
+            Ok(rows) => memory_rows.set(rows),
+            Err(e) => eprintln!("[PhazeAI] failed to read project memory: {e}"),
+        }
+        let signed_in = CloudCredentials::load().is_authenticated();
+        let team_enabled = Feature::TeamContext.is_enabled();
+        // Signed-in accounts without the Team plan get an upgrade prompt instead.
+        team_upsell.set(signed_in && !team_enabled);
+        let cache = (signed_in && team_enabled).then(TeamCache::load).flatten();
+        team_heading.set(
+            cache
+                .as_ref()
+                .map(|c| format!("TEAM · {} ({})", c.context.team_name, c.context.role.name())),
+        );
+        team_items.set(cache.as_ref().map(team_rows).unwrap_or_default());
+    });
+    create_effect(move |_| {
+        if team_removed.get().is_some() {
+            memory_refresh.update(|n| *n += 1);
+        }
+    });
+
+    let add_memory = move || {
+        let text = memory_input.get_untracked();
+        if text.trim().is_empty() {
+            return;
+        }
+        if let Err(e) = memory_store().add(&text) {
+            eprintln!("[PhazeAI] failed to save memory: {e}");
+        }
+        memory_input.set(String::new());
+        memory_refresh.update(|n| *n += 1);
+    };
+
+    let memory_bar = text_input(memory_input)
+        .placeholder("Remember a fact about this project…")
+        .style(move |s| {
+            let t = theme.get();
+            let p = &t.palette;
+            s.width_full()
+                .background(p.glass_bg)
+                .border(1.0)
+                .border_color(p.border_focus)
+                .border_radius(6.0)
+                .color(p.text_primary)
+                .padding_horiz(10.0)
+                .padding_vert(6.0)
+                .font_size(12.0)
+        })
+        .on_event_stop(EventListener::KeyDown, move |event| {
+            if let Event::KeyDown(e) = event {
+                if e.key.logical_key == Key::Named(floem::keyboard::NamedKey::Enter) {
+                    add_memory();
+                }
+            }
+        });
+    let memory_bar = container(memory_bar).style(move |s| {
+        s.width_full()
+            .padding(8.0)
+            .border_bottom(1.0)
+            .border_color(theme.get().palette.glass_border)
+    });
+
+    let memory_list = dyn_stack(
+        move || memory_rows.get(),
+        |m| (m.id, m.text.clone()),
+        move |m| {
+            let text = m.text.clone();
+            let delete = container(label(|| "✕"))
+                .style(move |s| {
+                    let t = theme.get();
+                    let p = &t.palette;
+                    s.font_size(11.0)
+                        .padding_horiz(5.0)
+                        .margin_left(6.0)
+                        .border_radius(4.0)
+                        .color(p.text_muted)
+                        .cursor(floem::style::CursorStyle::Pointer)
+                        .hover(|s| s.background(p.bg_panel).color(p.error))
+                })
+                .on_click_stop(move |_| {
+                    if let Err(e) = memory_store().remove(m.id) {
+                        eprintln!("[PhazeAI] failed to delete memory: {e}");
+                    }
+                    memory_refresh.update(|n| *n += 1);
+                });
+            stack((
+                label(move || text.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                delete,
+            ))
+            .style(move |s| {
+                let t = theme.get();
+                let p = &t.palette;
+                s.items_center()
+                    .width_full()
+                    .padding_horiz(12.0)
+                    .padding_vert(8.0)
+                    .border_bottom(1.0)
+                    .border_color(p.glass_border)
+                    .hover(|s| s.background(p.bg_elevated))
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let memory_empty = label(|| {
+        "Nothing remembered for this project yet. The agent saves durable facts \
+         here as it works; they are stored in .phazeai/memory.md."
+    })
+    .style(move |s| {
+        s.font_size(11.0)
+            .padding(14.0)
+            .color(theme.get().palette.text_muted)
+            .apply_if(!memory_rows.get().is_empty(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let team_header = label(move || team_heading.get().unwrap_or_default()).style(move |s| {
+        let t = theme.get();
+        s.font_size(10.0)
+            .width_full()
+            .padding_horiz(12.0)
+            .padding_top(12.0)
+            .padding_bottom(4.0)
+            .color(t.palette.text_muted)
+            .font_weight(floem::text::Weight::BOLD)
+            .apply_if(team_heading.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let team_list = dyn_stack(
+        move || team_items.get(),
+        |row| (row.id.clone(), row.text.clone()),
+        move |row| {
+            let text = row.text.clone();
+            let detail = row.detail.clone();
+            let removable = row.removable;
+            let team_tx = team_tx.clone();
+            let delete = container(label(|| "✕"))
+                .style(move |s| {
+                    let t = theme.get();
+                    let p = &t.palette;
+                    s.font_size(11.0)
+                        .padding_horiz(5.0)
+                        .margin_left(6.0)
+                        .border_radius(4.0)
+                        .color(p.text_muted)
+                        .cursor(floem::style::CursorStyle::Pointer)
+                        .hover(|s| s.background(p.bg_panel).color(p.error))
+                        .apply_if(!removable, |s| s.display(floem::style::Display::None))
+                })
+                .on_click_stop(move |_| remove_team_item(row.clone(), team_tx.clone()));
+            stack((
+                stack((
+                    label(move || text.clone())
+                        .style(move |s| s.font_size(12.0).color(theme.get().palette.text_primary)),
+                    label(move || detail.clone())
+                        .style(move |s| s.font_size(10.0).color(theme.get().palette.text_muted)),
+                ))
+                .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0)),
+                delete,
+            ))
+            .style(move |s| {
+                let t = theme.get();
+                let p = &t.palette;
+                s.items_center()
+                    .width_full()
+                    .padding_horiz(12.0)
+                    .padding_vert(8.0)
+                    .border_bottom(1.0)
+                    .border_color(p.glass_border)
+                    .hover(|s| s.background(p.bg_elevated))
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let memory_view = stack((
+        memory_bar,
+        scroll(
+            stack((
+                memory_list,
+                memory_empty,
+                team_header,
+                team_list,
+                upgrade_prompt(Feature::TeamContext, team_upsell, theme),
+            ))
+            .style(|s| s.flex_col().width_full()),
+        )
+        .style(|s| s.flex_grow(1.0).min_height(0.0).width_full()),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .width_full()
+            .flex_grow(1.0)
+            .min_height(0.0)
+            .apply_if(!show_memory.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    // ── Context window overflow ───────────────────────────────────────────────
+
+    let held_contributors = dyn_stack(
+        move || {
+            held.get()
+                .map(|m| m.overflow.contributors)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|part| part.tokens > 0)
+                .take(5)
+                .enumerate()
+                .collect::<Vec<_>>()
+        },
+        |(i, part)| format!("{i}:{}:{}", part.label, part.tokens),
+        {
+            let do_send = do_send.clone();
+            move |(_, part)| {
+                let remove: Option<Box<dyn Fn()>> = match part.source {
+                    ContextSource::Attachment(key) => {
+                        let do_send = do_send.clone();
+                        Some(Box::new(move || {
+                            match key
+                                .strip_prefix(TERMINAL_RUN_KEY)
+                                .and_then(|i| i.parse::<usize>().ok())
+                            {
+                                Some(i) => terminal_runs.update(|runs| {
+                                    if i < runs.len() {
+                                        runs.remove(i);
+                                    }
+                                }),
+                                None => dropped_mentions.update(|d| d.push(key.clone())),
+                            }
+                            do_send();
+                        }))
+                    }
+                    _ => None,
+                };
+                let removable = remove.is_some();
+                let label_text = part.label.clone();
+                stack((
+                    label(move || label_text.clone()).style(move |s| {
+                        s.font_size(11.0)
+                            .color(theme.get().palette.text_primary)
+                            .flex_grow(1.0)
+                            .min_width(0.0)
+                    }),
+                    label(move || format!("~{} tok", part.tokens)).style(move |s| {
+                        s.font_size(10.0)
+                            .color(theme.get().palette.text_muted)
+                            .margin_horiz(6.0)
+                    }),
+                    container(crate::panels::git_history::action_button(
+                        || "Remove".to_string(),
+                        theme,
+                        move || {
+                            if let Some(remove) = &remove {
+                                remove();
+                            }
+                        },
+                    ))
+                    .style(move |s| {
+                        s.apply_if(!removable, |s| s.display(floem::style::Display::None))
+                    }),
+                ))
+                .style(|s| s.items_center().width_full().padding_vert(2.0))
+            }
+        },
+    )
+    .style(|s| s.flex_col().width_full().margin_vert(4.0));
+
+    let held_models = dyn_stack(
+        move || {
+            held.get()
+                .map(|m| m.larger)
+                .unwrap_or_default()
+                .into_iter()
+                .take(3)
+                .collect::<Vec<_>>()
+        },
+        |(provider, model)| format!("{}:{}", provider.name(), model.id),
+        {
+            let do_send = do_send.clone();
+            move |(provider, model): (ProviderId, ModelInfo)| {
+                let do_send = do_send.clone();
+                let text = format!(
+                    "Switch to {} ({}K)",
+                    model.name,
+                    model.context_window / 1000
+                );
+                crate::panels::git_history::action_button(
+                    move || text.clone(),
+                    theme,
+                    move || {
+                        let Some(llm_provider) =
+                            crate::app::provider_name_to_llm_provider(provider.name())
+                        else {
+                            return;
+                        };
+                        // Save before re-sending, which reads the settings file.
+                        let _ = Settings::update(|s| {
+                            s.llm.provider = llm_provider;
+                            s.llm.model = model.id.clone();
+                        });
+                        ai_provider.set(provider.name().to_string());
+                        ai_model.set(model.id.clone());
+                        do_send();
+                    },
+                )
+            }
+        },
+    )
+    .style(|s| {
+        s.width_full()
+            .gap(4.0)
+            .flex_wrap(floem::style::FlexWrap::Wrap)
+    });
+
+    let held_actions = stack((
+        crate::panels::git_history::action_button(|| "Send anyway".to_string(), theme, {
+            let do_send = do_send.clone();
+            move || {
+                send_anyway.set(true);
+                do_send();
+            }
+        }),
+        crate::panels::git_history::action_button(
+            || "Cancel".to_string(),
+            theme,
+            move || {
+                held.set(None);
+                dropped_mentions.set(Vec::new());
+            },
+        ),
+    ))
+    .style(|s| s.gap(4.0).margin_top(6.0));
+
+    let overflow_card = stack((
+        label(move || {
+            held.get()
+                .map(|m| {
+                    format!(
+                        "This message is ~{} tokens, but {} leaves room for ~{}. Biggest parts:",
+                        m.overflow.estimated, m.model, m.overflow.limit
+                    )
+                })
+                .unwrap_or_default()
+        })
+        .style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.warning)
+                .width_full()
+        }),
+        held_contributors,
+        held_models,
+        held_actions,
+    ))
+    .style(move |s| {
+        let t = theme.get();
+        let p = &t.palette;
+        s.flex_col()
+            .margin_horiz(10.0)
+            .margin_bottom(6.0)
+            .padding(8.0)
+            .border(1.0)
+            .border_radius(6.0)
+            .border_color(p.warning.with_alpha(0.5))
+            .background(p.bg_deep.with_alpha(0.6))
+            .apply_if(held.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    // ── Attached images (preview screenshots) ────────────────────────────────
+
+    let image_bar = dyn_stack(
+        move || chat_images.get(),
+        |path| path.clone(),
+        move |path: std::path::PathBuf| {
+            let bytes = std::fs::read(&path).unwrap_or_default();
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            stack((
+                img(move || bytes.clone()).style(|s| s.height(36.0).border_radius(3.0)),
+                label(move || name.clone()).style(move |s| {
+                    s.font_size(10.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_muted)
+                }),
+                crate::panels::git_history::action_button(
+                    || "✕".to_string(),
+                    theme,
+                    move || chat_images.update(|list| list.retain(|p| *p != path)),
+                ),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(6.0)
+                    .width_full()
+                    .padding(4.0)
+                    .border(1.0)
+                    .border_radius(4.0)
+                    .border_color(p.glass_border)
+            })
+        },
+    )
+    .style(move |s| {
+        s.flex_col()
+            .gap(4.0)
+            .width_full()
+            .padding_horiz(8.0)
+            .apply_if(chat_images.get().is_empty(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let chat_view = stack((
+        mode_tabs,
+        tools_strip,
+        messages_scroll,
+        overflow_card,
+        image_bar,
+        input_bar,
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .width_full()
+            .flex_grow(1.0)
+            .min_height(0.0)
+            .apply_if(show_history.get() || show_memory.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
     // ── Full panel ────────────────────────────────────────────────────────────
 
-    stack((header, mode_tabs, messages_scroll, input_bar)).style(move |s| {
+    stack((header, chat_view, history_view, memory_view)).style(move |s| {
         let t = theme.get();
         let p = &t.palette;
         s.flex_col()
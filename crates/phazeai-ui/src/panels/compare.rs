@@ -0,0 +1,562 @@
+//! Compare — the bottom-panel "COMPARE" tab.
+//!
+//! Two texts side by side or inline: two files picked in the explorer or
+//! from the palette, a file or buffer and the clipboard, an editor's
+//! unsaved buffer and the file on disk, or a file and a chat code block
+//! applied to it. Changed lines are tinted and the words that differ within
+//! them picked out; Previous and Next walk the changes. The current change
+//! can be copied to either side, or all of a suggested edit at once, which
+//! writes a file side to disk, edits a buffer side in the editor (so it can
+//! be undone there) and only changes the text held here for the clipboard
+//! or a suggestion.
+
+use std::ops::Range;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use floem::{
+    reactive::{
+        create_effect, create_memo, create_rw_signal, RwSignal, SignalGet, SignalUpdate, SignalWith,
+    },
+    text::{Attrs, AttrsList, FamilyOwned, TextLayout, Weight},
+    views::{container, empty, label, rich_text, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::editing::compare::{self, Change, Row, Side};
+use phazeai_core::editing::{decode_text, write_atomic, TextEncoding};
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::components::virtual_list::virtual_list;
+use crate::panels::git_history::action_button;
+use crate::theme::{PhazePalette, PhazeTheme};
+
+const ROW_HEIGHT: f64 = 18.0;
+const FONT_SIZE: f32 = 12.0;
+
+/// Where one side of a comparison comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    File(PathBuf),
+    /// An open file's text in the editor, saved or not.
+    Buffer(PathBuf),
+    Clipboard,
+    /// Text proposed for the other side, such as a chat code block.
+    Suggestion,
+}
+
+impl Source {
+    fn label(&self) -> String {
+        let name = |path: &PathBuf| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string())
+        };
+        match self {
+            Source::File(path) => name(path),
+            Source::Buffer(path) => format!("{} (editor)", name(path)),
+            Source::Clipboard => "Clipboard".to_string(),
+            Source::Suggestion => "Suggested edit".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompareSide {
+    pub source: Source,
+    pub text: String,
+    /// How a file side is written back when a change is copied into it.
+    pub encoding: TextEncoding,
+}
+
+impl CompareSide {
+    /// The file at `path`, in the encoding it's detected to be in.
+    pub fn file(path: PathBuf) -> Option<Self> {
+        let bytes = std::fs::read(&path).ok()?;
+        let (text, encoding) = decode_text(&bytes);
+        Some(Self {
+            source: Source::File(path),
+            text,
+            encoding,
+        })
+    }
+
+    pub fn clipboard() -> Option<Self> {
+        let text = arboard::Clipboard::new().ok()?.get_text().ok()?;
+        Some(Self {
+            source: Source::Clipboard,
+            text,
+            encoding: TextEncoding::default(),
+        })
+    }
+}
+
+/// What the COMPARE tab shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub left: CompareSide,
+    pub right: CompareSide,
+}
+
+impl Comparison {
+    /// The comparison of two files, `None` if either can't be read.
+    pub fn files(left: PathBuf, right: PathBuf) -> Option<Self> {
+        Some(Self {
+            left: CompareSide::file(left)?,
+            right: CompareSide::file(right)?,
+        })
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut CompareSide {
+        match side {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        }
+    }
+}
+
+/// A comparison's lines and changes, worked out once per comparison.
+#[derive(Debug, PartialEq)]
+struct Diffed {
+    left: Vec<String>,
+    right: Vec<String>,
+    changes: Vec<Change>,
+}
+
+impl Diffed {
+    fn new(comparison: &Comparison) -> Self {
+        let own = |text: &str| compare::lines(text).into_iter().map(String::from).collect();
+        Self {
+            left: own(&comparison.left.text),
+            right: own(&comparison.right.text),
+            changes: compare::changes(&comparison.left.text, &comparison.right.text),
+        }
+    }
+
+    fn line(&self, side: Side, line: usize) -> &str {
+        let lines = match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        };
+        lines.get(line).map_or("", String::as_str)
+    }
+
+    /// The words of `line` on `side` that differ from the line it's paired
+    /// with in its change; none for a line the other side has nothing for.
+    fn word_ranges(&self, row: &Row, side: Side) -> Vec<Range<usize>> {
+        let Some(change) = row.change.and_then(|k| self.changes.get(k)) else {
+            return Vec::new();
+        };
+        let (own, other, line) = match side {
+            Side::Left => (&change.left, &change.right, row.left),
+            Side::Right => (&change.right, &change.left, row.right),
+        };
+        let Some(line) = line else {
+            return Vec::new();
+        };
+        let partner = other.start + (line - own.start);
+        if !other.contains(&partner) {
+            return Vec::new();
+        }
+        let (left, right) = match side {
+            Side::Left => (line, partner),
+            Side::Right => (partner, line),
+        };
+        let (old, new) =
+            compare::intraline(self.line(Side::Left, left), self.line(Side::Right, right));
+        match side {
+            Side::Left => old,
+            Side::Right => new,
+        }
+    }
+}
+
+pub fn compare_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let comparison = state.comparison;
+    let font_family = state.font_family;
+    let inline_view = create_rw_signal(false);
+    // The change Previous/Next are on, and copying applies to.
+    let current: RwSignal<Option<usize>> = create_rw_signal(None);
+    // Bumped with every comparison, so rows of the last one aren't reused.
+    let generation = create_rw_signal(0u64);
+
+    // Any comparison set brings up the tab. Those made in the tab itself,
+    // by copying, swapping or refreshing, happen with it showing anyway.
+    let (tab, show_panel) = (state.bottom_panel_tab, state.show_bottom_panel);
+    create_effect(move |_| {
+        if comparison.with(|c| c.is_some()) {
+            tab.set(Tab::Compare);
+            show_panel.set(true);
+        }
+    });
+    let diffed =
+        create_memo(move |_| comparison.with(|c| c.as_ref().map(|c| Rc::new(Diffed::new(c)))));
+    create_effect(move |_| {
+        let count = diffed.with(|d| d.as_ref().map_or(0, |d| d.changes.len()));
+        generation.update(|g| *g += 1);
+        let at = current.get_untracked();
+        current.set(match at {
+            _ if count == 0 => None,
+            Some(k) => Some(k.min(count - 1)),
+            None => Some(0),
+        });
+    });
+    let rows = create_memo(move |_| {
+        let inline = inline_view.get();
+        diffed.with(|d| {
+            d.as_ref().map_or_else(Vec::new, |d| {
+                let (l, r) = (d.left.len(), d.right.len());
+                if inline {
+                    compare::inline(&d.changes, l, r)
+                } else {
+                    compare::side_by_side(&d.changes, l, r)
+                }
+            })
+        })
+    });
+
+    let step = move |forward: bool| {
+        let count = diffed.with_untracked(|d| d.as_ref().map_or(0, |d| d.changes.len()));
+        if count == 0 {
+            return;
+        }
+        current.update(|at| {
+            let k = at.unwrap_or(0);
+            *at = Some(if forward {
+                (k + 1) % count
+            } else {
+                (k + count - 1) % count
+            });
+        });
+    };
+    let copy_to = {
+        let state = state.clone();
+        move |to: Side| {
+            let change = diffed.with_untracked(|d| {
+                let k = current.get_untracked()?;
+                d.as_ref()?.changes.get(k).cloned()
+            });
+            if let Some(change) = change {
+                copy_change(&state, &change, to);
+            }
+        }
+    };
+    let copy_left = copy_to.clone();
+
+    let title = label(move || {
+        comparison.with(|c| match c {
+            Some(c) => format!("{}  ↔  {}", c.left.source.label(), c.right.source.label()),
+            None => String::new(),
+        })
+    })
+    .style(move |s| s.font_size(12.0).color(theme.get().palette.text_primary));
+    let position = label(move || {
+        let count = diffed.with(|d| d.as_ref().map_or(0, |d| d.changes.len()));
+        match current.get() {
+            Some(k) if count > 0 => format!("Change {} of {count}", k + 1),
+            _ => "No differences".to_string(),
+        }
+    })
+    .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted));
+
+    let header = stack((
+        title,
+        position,
+        action_button(|| "Previous".to_string(), theme, move || step(false)),
+        action_button(|| "Next".to_string(), theme, move || step(true)),
+        action_button(
+            || "Copy to Right →".to_string(),
+            theme,
+            move || copy_to(Side::Right),
+        ),
+        action_button(
+            || "← Copy to Left".to_string(),
+            theme,
+            move || copy_left(Side::Left),
+        ),
+        container(action_button(|| "← Apply All".to_string(), theme, {
+            let state = state.clone();
+            move || apply_all(&state)
+        }))
+        .style(move |s| {
+            let suggested = comparison.with(|c| {
+                c.as_ref()
+                    .is_some_and(|c| c.right.source == Source::Suggestion)
+            });
+            s.apply_if(!suggested, |s| s.display(floem::style::Display::None))
+        }),
+        action_button(
+            move || {
+                if inline_view.get() {
+                    "Side by Side".to_string()
+                } else {
+                    "Inline".to_string()
+                }
+            },
+            theme,
+            move || inline_view.update(|v| *v = !*v),
+        ),
+        action_button(
+            || "Swap".to_string(),
+            theme,
+            move || {
+                comparison.update(|c| {
+                    if let Some(c) = c {
+                        std::mem::swap(&mut c.left, &mut c.right);
+                    }
+                })
+            },
+        ),
+        action_button(|| "Refresh".to_string(), theme, move || refresh(comparison)),
+    ))
+    .style(move |s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(theme.get().palette.border)
+    });
+
+    let list = virtual_list(
+        move || {
+            let g = generation.get();
+            let inline = inline_view.get();
+            rows.get()
+                .into_iter()
+                .enumerate()
+                .map(|(i, row)| (g, inline, i, row))
+                .collect::<Vec<_>>()
+        },
+        |&(g, inline, i, row)| (g, inline, i, row.left, row.right, row.change),
+        move |(_, inline, _, row): (u64, bool, usize, Row)| {
+            let Some(d) = diffed.get_untracked() else {
+                return empty().into_any();
+            };
+            let fonts = crate::fonts::font_stack(&font_family.get_untracked(), false);
+            let view = if inline {
+                let side = if row.left.is_some() && row.change.is_some() {
+                    Side::Left
+                } else {
+                    Side::Right
+                };
+                cell(&d, row, side, true, fonts, theme).into_any()
+            } else {
+                stack((
+                    cell(&d, row, Side::Left, false, fonts.clone(), theme),
+                    cell(&d, row, Side::Right, false, fonts, theme),
+                ))
+                .style(|s| s.width_full())
+                .into_any()
+            };
+            container(view)
+                .style(move |s| {
+                    let p = theme.get().palette;
+                    let here = row.change.is_some() && row.change == current.get();
+                    s.width_full().border_left(2.0).border_color(if here {
+                        p.accent
+                    } else {
+                        floem::peniko::Color::TRANSPARENT
+                    })
+                })
+                .on_click_stop(move |_| {
+                    if row.change.is_some() {
+                        current.set(row.change);
+                    }
+                })
+                .into_any()
+        },
+        ROW_HEIGHT,
+        move || {
+            let k = current.get()?;
+            rows.with(|rows| rows.iter().position(|row| row.change == Some(k)))
+        },
+    )
+    .style(|s| s.flex_grow(1.0).width_full());
+
+    let empty_msg = container(
+        label(|| {
+            "Nothing to compare. Pick two files with Select for Compare in the explorer, or \
+             run Compare Active File with Saved or with Clipboard from the command palette."
+                .to_string()
+        })
+        .style(move |s| s.font_size(12.0).color(theme.get().palette.text_muted)),
+    )
+    .style(move |s| {
+        s.padding(12.0)
+            .apply_if(comparison.with(|c| c.is_some()), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    stack((header, empty_msg, list)).style(|s| s.flex_col().width_full().height_full())
+}
+
+/// One side's line of `row`: its line number and text, tinted when it's
+/// changed, with the words that differ in bold. Inline, a line of both
+/// sides shows both numbers.
+fn cell(
+    d: &Diffed,
+    row: Row,
+    side: Side,
+    inline: bool,
+    fonts: Vec<FamilyOwned>,
+    theme: RwSignal<PhazeTheme>,
+) -> impl IntoView {
+    let line = match side {
+        Side::Left => row.left,
+        Side::Right => row.right,
+    };
+    let text = line.map(|l| d.line(side, l).trim_end_matches(['\n', '\r']).to_string());
+    let words = d.word_ranges(&row, side);
+    let number = |n: Option<usize>| n.map(|n| (n + 1).to_string()).unwrap_or_default();
+    let gutter = if inline {
+        format!(
+            "{:>5} {:>5} {}",
+            number(row.left),
+            number(row.right),
+            match (row.change, side) {
+                (None, _) => ' ',
+                (Some(_), Side::Left) => '-',
+                (Some(_), Side::Right) => '+',
+            }
+        )
+    } else {
+        format!("{:>5}", number(line))
+    };
+    let changed = row.change.is_some();
+
+    stack((
+        label(move || gutter.clone()).style(move |s| {
+            s.font_size(FONT_SIZE)
+                .font_family("JetBrains Mono, Fira Code, monospace".to_string())
+                .color(theme.get().palette.text_muted)
+                .margin_right(8.0)
+        }),
+        rich_text(move || {
+            line_layout(
+                text.as_deref().unwrap_or(""),
+                &words,
+                side,
+                &theme.get().palette,
+                &fonts,
+            )
+        })
+        .style(|s| s.flex_grow(1.0).min_width(0.0)),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        let background = match (changed, line, side) {
+            (false, _, _) => floem::peniko::Color::TRANSPARENT,
+            (true, None, _) => p.bg_elevated,
+            (true, Some(_), Side::Left) => p.diff_removed_bg,
+            (true, Some(_), Side::Right) => p.diff_added_bg,
+        };
+        s.width_pct(if inline { 100.0 } else { 50.0 })
+            .min_width(0.0)
+            .height(ROW_HEIGHT)
+            .items_center()
+            .padding_horiz(6.0)
+            .background(background)
+    })
+}
+
+/// `text` with `words` in bold, in the removed color on the left and the
+/// added color on the right.
+fn line_layout(
+    text: &str,
+    words: &[Range<usize>],
+    side: Side,
+    p: &PhazePalette,
+    fonts: &[FamilyOwned],
+) -> TextLayout {
+    let base = Attrs::new()
+        .font_size(FONT_SIZE)
+        .color(p.text_primary)
+        .family(fonts);
+    let color = match side {
+        Side::Left => p.diff_removed_fg,
+        Side::Right => p.diff_added_fg,
+    };
+    let mut attrs = AttrsList::new(base.clone());
+    for range in words {
+        let range = range.start.min(text.len())..range.end.min(text.len());
+        if !range.is_empty() {
+            attrs.add_span(range, base.clone().color(color).weight(Weight::BOLD));
+        }
+    }
+    let mut layout = TextLayout::new();
+    layout.set_text(text, attrs, None);
+    layout
+}
+
+/// Copy `change` of the comparison to side `to`, writing it where that
+/// side comes from.
+fn copy_change(state: &IdeState, change: &Change, to: Side) {
+    let Some(c) = state.comparison.get_untracked() else {
+        return;
+    };
+    let text = compare::copy_change(&c.left.text, &c.right.text, change, to);
+    set_side(state, c, to, text);
+}
+
+/// Take all of a suggested edit on the right into the left side.
+fn apply_all(state: &IdeState) {
+    let Some(c) = state.comparison.get_untracked() else {
+        return;
+    };
+    let text = c.right.text.clone();
+    set_side(state, c, Side::Left, text);
+}
+
+/// Make `text` side `to` of `c`, writing it where that side comes from.
+fn set_side(state: &IdeState, mut c: Comparison, to: Side, text: String) {
+    let target = c.side_mut(to);
+    match &target.source {
+        Source::File(path) => {
+            // A suggestion can be for a file that doesn't exist yet.
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            let written = target
+                .encoding
+                .encode(&text)
+                .and_then(|bytes| write_atomic(path, &bytes).map_err(|e| e.to_string()));
+            if let Err(e) = written {
+                show_toast(
+                    state.status_toast,
+                    format!("Couldn't write {}: {e}", path.display()),
+                );
+                return;
+            }
+        }
+        Source::Buffer(path) => {
+            state
+                .buffer_cmd
+                .set(Some(crate::panels::editor::BufferCommand::Replace {
+                    path: path.clone(),
+                    text: text.clone(),
+                }));
+        }
+        Source::Clipboard | Source::Suggestion => {}
+    }
+    target.text = text;
+    state.comparison.set(Some(c));
+}
+
+/// Read the file sides of the comparison again.
+fn refresh(comparison: RwSignal<Option<Comparison>>) {
+    let Some(mut c) = comparison.get_untracked() else {
+        return;
+    };
+    for side in [Side::Left, Side::Right] {
+        let side = c.side_mut(side);
+        if let Source::File(path) = &side.source {
+            if let Some(fresh) = CompareSide::file(path.clone()) {
+                *side = fresh;
+            }
+        }
+    }
+    comparison.set(Some(c));
+}
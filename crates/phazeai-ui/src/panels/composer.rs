@@ -356,7 +356,8 @@ pub fn composer_panel(state: IdeState) -> impl IntoView {
                     tools.register(Box::new(BashTool::new(ws.clone())));
                     let mut agent = Agent::new(client)
                         .with_tools(tools)
-                        .with_cancel_token(token);
+                        .with_cancel_token(token)
+                        .with_limits(&settings.agent);
 
                     // Connect MCP servers
                     let mcp_configs = phazeai_core::mcp::McpManager::load_config(&ws);
@@ -366,6 +367,13 @@ pub fn composer_panel(state: IdeState) -> impl IntoView {
                         agent.register_mcp_tools(Arc::new(std::sync::Mutex::new(mcp_manager)));
                     }
 
+                    // Tools from plugin sidecars in the settings.
+                    for tool in
+                        phazeai_sidecar::plugin_sidecar_tools(&settings.plugin_sidecars).await
+                    {
+                        agent.register_tool(tool);
+                    }
+
                     // Wire approval function when mode is not AutoAll.
                     if mode != ComposerApprovalMode::AutoAll {
                         let tx_appr = tx.clone();
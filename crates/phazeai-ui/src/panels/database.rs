@@ -0,0 +1,559 @@
+//! Database explorer — the bottom-panel "DB" tab.
+//!
+//! The left side lists the connection profiles saved in the settings, a
+//! form to add one (its password goes to the OS keychain, not the settings
+//! file), and the tables and views of the selected database; click a table
+//! to see its columns, or "Preview" to query its first rows. The right side
+//! runs SQL against the selected database and shows the result as a grid.
+//! Profiles marked read-only only run statements that change nothing.
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use floem::{
+    event::{Event, EventListener},
+    ext_event::create_ext_action,
+    keyboard::{Key, NamedKey},
+    reactive::{
+        create_effect, create_rw_signal, RwSignal, Scope, SignalGet, SignalUpdate, SignalWith,
+    },
+    views::{dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::db::{
+    keychain, ColumnInfo, Database, DatabaseProfile, DbKind, QueryResult, TableInfo, MAX_ROWS,
+};
+use phazeai_core::{PhazeError, Settings};
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::panels::git_history::action_button;
+use crate::theme::PhazeTheme;
+
+/// Width of each result column.
+const CELL_WIDTH: f64 = 160.0;
+
+/// Run `work` on a thread of its own, handing its result to `on_done` on
+/// the UI thread.
+fn spawn_db<T, F>(
+    work: impl FnOnce() -> F + Send + 'static,
+    on_done: impl FnOnce(Result<T, String>) + 'static,
+) where
+    T: Send + 'static,
+    F: Future<Output = Result<T, PhazeError>>,
+{
+    let on_done = create_ext_action(Scope::current(), on_done);
+    std::thread::spawn(move || {
+        let result = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt.block_on(work()).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        on_done(result);
+    });
+}
+
+fn open(profile: &DatabaseProfile, root: &std::path::Path) -> Result<Database, String> {
+    Database::open(profile, root).map_err(|e| e.to_string())
+}
+
+fn muted(text: impl Fn() -> String + 'static, theme: RwSignal<PhazeTheme>) -> impl IntoView {
+    label(text).style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted))
+}
+
+pub fn database_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let toast = state.status_toast;
+
+    let profiles = create_rw_signal(Vec::<DatabaseProfile>::new());
+    let selected = create_rw_signal(None::<String>);
+    let tables = create_rw_signal(Vec::<TableInfo>::new());
+    // Columns of the tables opened in the tree, by qualified name.
+    let columns = create_rw_signal(HashMap::<String, Vec<ColumnInfo>>::new());
+    let sql = create_rw_signal(String::new());
+    let running = create_rw_signal(false);
+    let result = create_rw_signal(None::<Result<QueryResult, String>>);
+
+    // The add-connection form.
+    let adding = create_rw_signal(false);
+    let new_name = create_rw_signal(String::new());
+    let new_url = create_rw_signal(String::new());
+    let new_password = create_rw_signal(String::new());
+    let new_read_only = create_rw_signal(false);
+
+    let selected_profile = move || {
+        let name = selected.get_untracked()?;
+        profiles.with_untracked(|ps| ps.iter().find(|p| p.name == name).cloned())
+    };
+
+    // ── Loading ────────────────────────────────────────────────────────────
+    let load_tables = move || {
+        let Some(profile) = selected_profile() else {
+            tables.set(Vec::new());
+            return;
+        };
+        let db = match open(&profile, &workspace_root.get_untracked()) {
+            Ok(db) => db,
+            Err(e) => return show_toast(toast, format!("{}: {e}", profile.name)),
+        };
+        columns.set(HashMap::new());
+        spawn_db(
+            move || async move { db.tables().await },
+            move |loaded| match loaded {
+                Ok(t) => tables.set(t),
+                Err(e) => {
+                    tables.set(Vec::new());
+                    show_toast(toast, format!("{}: {e}", profile.name));
+                }
+            },
+        );
+    };
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        let loaded = create_rw_signal(false);
+        create_effect(move |_| {
+            if shown.get() && tab.get() == Tab::Database && !loaded.get_untracked() {
+                loaded.set(true);
+                let saved = Settings::load().databases;
+                if selected.get_untracked().is_none() {
+                    selected.set(saved.first().map(|p| p.name.clone()));
+                }
+                profiles.set(saved);
+            }
+        });
+    }
+    create_effect(move |_| {
+        let _ = selected.get();
+        let _ = workspace_root.get();
+        result.set(None);
+        load_tables();
+    });
+
+    let toggle_columns = move |table: TableInfo| {
+        let key = table.qualified_name();
+        if columns.with_untracked(|c| c.contains_key(&key)) {
+            columns.update(|c| {
+                c.remove(&key);
+            });
+            return;
+        }
+        let Some(profile) = selected_profile() else {
+            return;
+        };
+        let Ok(db) = open(&profile, &workspace_root.get_untracked()) else {
+            return;
+        };
+        spawn_db(
+            move || async move { db.columns(&table).await },
+            move |loaded| match loaded {
+                Ok(cols) => columns.update(|c| {
+                    c.insert(key, cols);
+                }),
+                Err(e) => show_toast(toast, e),
+            },
+        );
+    };
+
+    // ── Running ────────────────────────────────────────────────────────────
+    let run = move || {
+        let text = sql.get_untracked();
+        if running.get_untracked() || text.trim().is_empty() {
+            return;
+        }
+        let Some(profile) = selected_profile() else {
+            show_toast(toast, "Pick a database first");
+            return;
+        };
+        let db = match open(&profile, &workspace_root.get_untracked()) {
+            Ok(db) => db,
+            Err(e) => return result.set(Some(Err(e))),
+        };
+        running.set(true);
+        spawn_db(
+            move || async move { db.query(&text, MAX_ROWS).await },
+            move |outcome| {
+                running.set(false);
+                let changed = matches!(&outcome, Ok(r) if r.columns.is_empty());
+                result.set(Some(outcome));
+                // A statement may have created or dropped a table.
+                if changed {
+                    load_tables();
+                }
+            },
+        );
+    };
+    let preview = move |table: &TableInfo| {
+        let Some(profile) = selected_profile() else {
+            return;
+        };
+        if let Ok(db) = open(&profile, &workspace_root.get_untracked()) {
+            sql.set(db.preview_sql(table));
+            run();
+        }
+    };
+
+    // ── Profiles ───────────────────────────────────────────────────────────
+    let save_profile = move || {
+        let name = new_name.get_untracked().trim().to_string();
+        let url = new_url.get_untracked().trim().to_string();
+        if name.is_empty() || url.is_empty() {
+            show_toast(toast, "A connection needs a name and a URL or SQLite file");
+            return;
+        }
+        let password = new_password.get_untracked();
+        if !password.is_empty() {
+            if let Err(e) = keychain::store_password(&name, &password) {
+                show_toast(toast, format!("Couldn't save the password: {e}"));
+                return;
+            }
+        }
+        let profile = DatabaseProfile {
+            name: name.clone(),
+            url,
+            read_only: new_read_only.get_untracked(),
+            agent_writes: false,
+        };
+        let settings = match Settings::update(|s| {
+            s.databases.retain(|p| p.name != name);
+            s.databases.push(profile);
+        }) {
+            Ok(settings) => settings,
+            Err(e) => {
+                show_toast(toast, format!("Couldn't save the settings: {e}"));
+                return;
+            }
+        };
+        profiles.set(settings.databases);
+        selected.set(Some(name));
+        new_name.set(String::new());
+        new_url.set(String::new());
+        new_password.set(String::new());
+        new_read_only.set(false);
+        adding.set(false);
+    };
+    let remove_profile = move || {
+        let Some(name) = selected.get_untracked() else {
+            return;
+        };
+        let settings = match Settings::update(|s| s.databases.retain(|p| p.name != name)) {
+            Ok(settings) => settings,
+            Err(e) => {
+                show_toast(toast, format!("Couldn't save the settings: {e}"));
+                return;
+            }
+        };
+        let _ = keychain::forget_password(&name);
+        selected.set(settings.databases.first().map(|p| p.name.clone()));
+        profiles.set(settings.databases);
+        show_toast(toast, format!("Removed {name}"));
+    };
+
+    let input_style = move |s: floem::style::Style| {
+        let p = theme.get().palette;
+        s.width_full()
+            .font_size(12.0)
+            .background(p.bg_elevated)
+            .border(1.0)
+            .border_color(p.border)
+            .border_radius(3.0)
+            .padding_horiz(6.0)
+            .color(p.text_primary)
+    };
+
+    let profile_list = dyn_stack(
+        move || profiles.get(),
+        |p| p.name.clone(),
+        move |profile: DatabaseProfile| {
+            let name = profile.name.clone();
+            let kind = DbKind::of(&profile.url).label();
+            let detail = if profile.read_only {
+                format!("{kind} · read-only")
+            } else {
+                kind.to_string()
+            };
+            let is_selected = {
+                let name = name.clone();
+                move || selected.get().as_deref() == Some(name.as_str())
+            };
+            stack((
+                label(move || profile.name.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .text_ellipsis()
+                        .color(theme.get().palette.text_primary)
+                }),
+                muted(move || detail.clone(), theme),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(6.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(3.0)
+                    .width_full()
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(is_selected(), |s| s.background(p.bg_elevated))
+            })
+            .on_click_stop(move |_| selected.set(Some(name.clone())))
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let add_form = stack((
+        text_input(new_name)
+            .placeholder("Name")
+            .style(move |s| input_style(s)),
+        text_input(new_url)
+            .placeholder("postgres://user@host/db, mysql://…, or app.sqlite3")
+            .style(move |s| input_style(s)),
+        text_input(new_password)
+            .placeholder("Password (kept in the OS keychain)")
+            .style(move |s| input_style(s)),
+        stack((
+            action_button(
+                move || {
+                    format!(
+                        "Read-only: {}",
+                        if new_read_only.get() { "on" } else { "off" }
+                    )
+                },
+                theme,
+                move || new_read_only.update(|on| *on = !*on),
+            ),
+            action_button(|| "Save".to_string(), theme, save_profile),
+            action_button(|| "Cancel".to_string(), theme, move || adding.set(false)),
+        ))
+        .style(|s| s.items_center().gap(6.0)),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .gap(4.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+            .apply_if(!adding.get(), |s| s.display(floem::style::Display::None))
+    });
+
+    let table_tree = dyn_stack(
+        move || tables.get(),
+        |t| t.qualified_name(),
+        move |table: TableInfo| {
+            let key = table.qualified_name();
+            let title = format!(
+                "{} {}",
+                if table.is_view { "◇" } else { "▦" },
+                table.qualified_name()
+            );
+            let for_columns = table.clone();
+            let column_lines = dyn_stack(
+                move || columns.with(|c| c.get(&key).cloned().unwrap_or_default()),
+                |c| c.name.clone(),
+                move |c: ColumnInfo| {
+                    let text = format!(
+                        "{}{}  {}{}",
+                        if c.primary_key { "🔑 " } else { "" },
+                        c.name,
+                        c.data_type,
+                        if c.nullable { "" } else { " not null" }
+                    );
+                    label(move || text.clone()).style(move |s| {
+                        s.font_size(11.0)
+                            .padding_left(28.0)
+                            .color(theme.get().palette.text_muted)
+                    })
+                },
+            )
+            .style(|s| s.flex_col().width_full());
+            stack((
+                stack((
+                    label(move || title.clone())
+                        .style(move |s| {
+                            s.font_size(12.0)
+                                .flex_grow(1.0)
+                                .min_width(0.0)
+                                .text_ellipsis()
+                                .cursor(floem::style::CursorStyle::Pointer)
+                                .color(theme.get().palette.text_primary)
+                        })
+                        .on_click_stop(move |_| toggle_columns(for_columns.clone())),
+                    action_button(|| "Preview".to_string(), theme, move || preview(&table)),
+                ))
+                .style(|s| {
+                    s.items_center()
+                        .gap(6.0)
+                        .padding_horiz(10.0)
+                        .padding_vert(1.0)
+                        .width_full()
+                }),
+                column_lines,
+            ))
+            .style(|s| s.flex_col().width_full())
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let section = move |title: &'static str| {
+        label(move || title.to_string()).style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(11.0)
+                .color(p.text_secondary)
+                .padding_horiz(10.0)
+                .padding_vert(3.0)
+                .width_full()
+                .border_bottom(1.0)
+                .border_color(p.border)
+        })
+    };
+
+    let sidebar = stack((
+        stack((
+            section("CONNECTIONS"),
+            action_button(|| "Add".to_string(), theme, move || adding.set(true)),
+            action_button(|| "Remove".to_string(), theme, remove_profile),
+            action_button(|| "Refresh".to_string(), theme, load_tables),
+        ))
+        .style(|s| s.items_center().gap(4.0).width_full()),
+        add_form,
+        label(|| "No connections yet — add a SQLite file or a connection string.".to_string())
+            .style(move |s| {
+                s.font_size(11.0)
+                    .padding(10.0)
+                    .color(theme.get().palette.text_muted)
+                    .apply_if(!profiles.get().is_empty(), |s| {
+                        s.display(floem::style::Display::None)
+                    })
+            }),
+        profile_list,
+        section("TABLES"),
+        scroll(table_tree).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.flex_col()
+            .width(300.0)
+            .height_full()
+            .border_right(1.0)
+            .border_color(p.border)
+    });
+
+    // ── Query and results ──────────────────────────────────────────────────
+    let query_bar = stack((
+        text_input(sql)
+            .placeholder("SQL — Enter to run")
+            .on_event_cont(EventListener::KeyDown, move |event| {
+                if let Event::KeyDown(e) = event {
+                    if e.key.logical_key == Key::Named(NamedKey::Enter) {
+                        run();
+                    }
+                }
+            })
+            .style(move |s| {
+                input_style(s)
+                    .flex_grow(1.0)
+                    .font_family("JetBrains Mono, monospace".to_string())
+            }),
+        action_button(
+            move || String::from(if running.get() { "Running…" } else { "Run" }),
+            theme,
+            run,
+        ),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let status = label(move || {
+        let db = selected.get().unwrap_or_default();
+        match result.get() {
+            None if db.is_empty() => "No database selected".to_string(),
+            None => format!("Connected to {db}"),
+            Some(Ok(r)) => r.summary(),
+            Some(Err(e)) => e,
+        }
+    })
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.font_size(11.0)
+            .padding_horiz(10.0)
+            .padding_vert(2.0)
+            .color(if matches!(result.get(), Some(Err(_))) {
+                p.error
+            } else {
+                p.text_muted
+            })
+    });
+
+    let cell = move |text: String, header: bool, null: bool| {
+        label(move || text.clone()).style(move |s| {
+            let p = theme.get().palette;
+            s.width(CELL_WIDTH)
+                .font_size(12.0)
+                .padding_horiz(6.0)
+                .padding_vert(2.0)
+                .text_ellipsis()
+                .border_right(1.0)
+                .border_color(p.border)
+                .color(if header {
+                    p.text_secondary
+                } else if null {
+                    p.text_muted
+                } else {
+                    p.text_primary
+                })
+        })
+    };
+    let grid_rows = move || -> Vec<(usize, Vec<Option<String>>)> {
+        match result.get() {
+            Some(Ok(r)) => r.rows.into_iter().enumerate().collect(),
+            _ => Vec::new(),
+        }
+    };
+    let header = dyn_stack(
+        move || match result.get() {
+            Some(Ok(r)) => r.columns.into_iter().enumerate().collect(),
+            _ => Vec::new(),
+        },
+        |(i, name): &(usize, String)| (*i, name.clone()),
+        move |(_, name)| cell(name, true, false),
+    )
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.background(p.bg_elevated)
+            .border_bottom(1.0)
+            .border_color(p.border)
+    });
+    let body = dyn_stack(
+        grid_rows,
+        |(i, _)| *i,
+        move |(_, values)| {
+            dyn_stack(
+                move || values.clone().into_iter().enumerate().collect::<Vec<_>>(),
+                |(i, _)| *i,
+                move |(_, value): (usize, Option<String>)| {
+                    let null = value.is_none();
+                    cell(value.unwrap_or_else(|| "NULL".to_string()), false, null)
+                },
+            )
+            .style(move |s| {
+                s.border_bottom(1.0)
+                    .border_color(theme.get().palette.border)
+            })
+        },
+    )
+    .style(|s| s.flex_col());
+
+    let grid = scroll(stack((header, body)).style(|s| s.flex_col()))
+        .style(|s| s.flex_grow(1.0).width_full().min_height(0.0));
+
+    let results = stack((query_bar, status, grid))
+        .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0).height_full());
+
+    stack((sidebar, results)).style(|s| s.width_full().height_full())
+}
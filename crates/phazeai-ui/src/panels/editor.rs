@@ -1,12 +1,12 @@
 use std::{
     borrow::Cow,
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
-    path::PathBuf,
-    rc::Rc,
+    path::{Path, PathBuf},
+    rc::{Rc, Weak},
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
 };
 
@@ -15,8 +15,10 @@ use floem::{
     ext_event::create_signal_from_channel,
     keyboard::{Key, Modifiers},
     kurbo::{Circle, Point},
-    reactive::{create_effect, create_memo, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
-    text::{Attrs, AttrsList, FamilyOwned, Stretch, Style as TextStyle, Weight},
+    reactive::{
+        create_effect, create_memo, create_rw_signal, RwSignal, SignalGet, SignalUpdate, SignalWith,
+    },
+    text::{Attrs, AttrsList, FamilyOwned, Stretch, Style as TextStyle, TextLayout, Weight},
     views::{
         canvas, container, dyn_stack,
         editor::{
@@ -31,7 +33,7 @@ use floem::{
             text::{default_dark_color, Document, SimpleStylingBuilder, Styling, WrapMethod},
             EditorStyle,
         },
-        label, scroll, stack, text_editor, text_input, Decorators,
+        label, rich_text, scroll, stack, text_editor, text_input, Decorators,
     },
     IntoView, Renderer,
 };
@@ -40,14 +42,39 @@ use crate::lsp_bridge::DiagSeverity;
 use crate::util::safe_get;
 use lazy_static::lazy_static;
 use syntect::{
-    highlighting::{FontStyle, HighlightState, Highlighter, RangedHighlightIterator, ThemeSet},
-    parsing::{ParseState, ScopeStack, SyntaxSet},
+    highlighting::{
+        FontStyle, HighlightState, Highlighter, RangedHighlightIterator, Theme, ThemeSet,
+    },
+    parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet},
 };
 
-use phazeai_core::{llm::Message, Settings};
+use phazeai_core::{
+    analysis::{
+        metrics::{self, Heat},
+        Language,
+    },
+    constants::ui as ui_const,
+    editing::{
+        apply::apply_block,
+        convert_line_endings, decode_text,
+        derived::{self, Derived, DerivedCache},
+        folding::{self, FoldMemory},
+        merge::{MARKER_OURS, MARKER_THEIRS},
+        merge3, write_atomic, Bookmarks, DiskChange, DiskStamp, LineEndings, TextEncoding,
+        TypingRules,
+    },
+    git::{relative_time, BlameLine, GitOps},
+    llm::{Message, TaskType},
+    lsp::{byte_column, utf16_column},
+    memory::{self, BudgetLru, Subsystem},
+    project::editorconfig::{EditorConfig, EffectiveIndent, Indent, IndentSource, LineEnding},
+    project::watcher::{FileChangeKind, FileWatcher},
+    Settings,
+};
 
 use crate::{
     components::icon::{icons, phaze_icon},
+    panels::compare::{CompareSide, Comparison, Source as CompareSource},
     theme::PhazeTheme,
 };
 
@@ -58,8 +85,122 @@ lazy_static! {
     static ref THEME_SET: ThemeSet = ThemeSet::load_defaults();
 }
 
+/// Load the syntax and theme sets now rather than when the first file opens;
+/// the launch calls this off the UI thread.
+pub fn preload_syntaxes() {
+    lazy_static::initialize(&SYNTAX_SET);
+    lazy_static::initialize(&THEME_SET);
+}
+
 // ── Syntax Highlighting Styling ───────────────────────────────────────────────
 
+/// Roughly what one highlighting checkpoint, a parser and a highlighter
+/// state, takes up.
+const CHECKPOINT_BYTES: usize = 1024;
+
+thread_local! {
+    /// Every highlighter's checkpoints by id, so those of the tabs drawn
+    /// least recently can be let go of once over the highlighting budget.
+    static CHECKPOINTS: RefCell<BudgetLru<u64, Weak<RefCell<Checkpoints>>>> =
+        RefCell::new(BudgetLru::new());
+    static NEXT_CHECKPOINTS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A highlighter's incremental states, one per 16-line block, counted in the
+/// memory ledger while they are held.
+struct Checkpoints {
+    id: u64,
+    this: Weak<RefCell<Checkpoints>>,
+    states: Vec<(ParseState, HighlightState)>,
+}
+
+impl Checkpoints {
+    fn shared() -> Rc<RefCell<Self>> {
+        let id = NEXT_CHECKPOINTS.with(|next| next.replace(next.get() + 1));
+        Rc::new_cyclic(|this| {
+            RefCell::new(Self {
+                id,
+                this: this.clone(),
+                states: Vec::new(),
+            })
+        })
+    }
+
+    /// Note `added` checkpoints in the ledger, and these as the most
+    /// recently used.
+    fn account(&self, added: isize) {
+        memory::global().adjust(
+            Subsystem::Highlighting,
+            added * CHECKPOINT_BYTES as isize,
+            added,
+        );
+        let bytes = self.states.len() * CHECKPOINT_BYTES;
+        CHECKPOINTS.with(|all| all.borrow_mut().insert(self.id, self.this.clone(), bytes));
+    }
+
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    fn last(&self) -> Option<&(ParseState, HighlightState)> {
+        self.states.last()
+    }
+
+    fn push(&mut self, state: (ParseState, HighlightState)) {
+        self.states.push(state);
+        self.account(1);
+        self.evict_others();
+    }
+
+    /// Clear the checkpoints of the tabs drawn least recently until they're
+    /// back within budget; those tabs are parsed again when next drawn.
+    /// These, being drawn, stay.
+    fn evict_others(&self) {
+        let ledger = memory::global();
+        let budget = ledger.budget(Subsystem::Highlighting);
+        let evicted = CHECKPOINTS.with(|all| all.borrow_mut().evict_to(budget));
+        let mut cleared = 0;
+        for (id, other) in evicted {
+            if id == self.id {
+                let bytes = self.states.len() * CHECKPOINT_BYTES;
+                CHECKPOINTS.with(|all| all.borrow_mut().insert(id, other, bytes));
+            } else if let Some(other) = other.upgrade() {
+                match other.try_borrow_mut() {
+                    Ok(mut other) if other.len() > 0 => {
+                        other.clear();
+                        cleared += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        ledger.evicted(Subsystem::Highlighting, cleared);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        let dropped = self.states.len().saturating_sub(len);
+        self.states.truncate(len);
+        self.account(-(dropped as isize));
+    }
+
+    fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl Drop for Checkpoints {
+    fn drop(&mut self) {
+        let held = self.states.len() as isize;
+        memory::global().adjust(
+            Subsystem::Highlighting,
+            -held * CHECKPOINT_BYTES as isize,
+            -held,
+        );
+        // The registry may already be gone when the thread exits.
+        let _ = CHECKPOINTS.try_with(|all| all.borrow_mut().remove(&self.id));
+    }
+}
+
 /// A `Styling` implementation that uses syntect for per-line syntax highlighting.
 /// Wraps an inner `Rc<dyn Styling>` (the SimpleStylingBuilder output) for
 /// font/layout settings and adds color spans from syntect on top.
@@ -69,11 +210,14 @@ struct SyntaxStyle {
     parse_state_proto: ParseState,
     doc: Option<Rc<dyn Document>>,
     /// Cached incremental states, one entry per 16-line block.
-    states: RefCell<Vec<(ParseState, HighlightState)>>,
+    states: Rc<RefCell<Checkpoints>>,
     /// Diagnostic lines for this file: (0-based line index, severity).
     diag_lines: Vec<(usize, DiagSeverity)>,
-    /// Word highlight ranges as byte offsets into the document.
-    highlight_ranges: Vec<(usize, usize)>,
+    /// Occurrences of the symbol under the cursor: (start byte, end byte,
+    /// whether it is written there).
+    highlight_ranges: Vec<(usize, usize, bool)>,
+    highlight_read_color: floem::peniko::Color,
+    highlight_write_color: floem::peniko::Color,
     /// Git gutter lines: (0-based line index, status).
     /// Status: 0 = added, 1 = modified, 2 = deleted.
     git_lines: Vec<(usize, u8)>,
@@ -81,8 +225,14 @@ struct SyntaxStyle {
     git_color_added: floem::peniko::Color,
     git_color_modified: floem::peniko::Color,
     git_color_deleted: floem::peniko::Color,
+    /// Complex functions: (first line, last line, 0-based) and the color of
+    /// the heat bar drawn beside the git gutter down their lines.
+    heat_ranges: Vec<(usize, usize, floem::peniko::Color)>,
     /// 0-based index of the currently active (cursor) line — receives a subtle background highlight.
     current_line: usize,
+    /// Bookmarked lines (0-based) and the tint drawn behind them.
+    bookmark_lines: Vec<usize>,
+    bookmark_color: floem::peniko::Color,
     /// Foldable regions: `(start_line, end_line)` pairs detected from braces/brackets.
     foldable_ranges: Vec<(usize, usize)>,
     /// Set of fold-start lines that are currently collapsed (hidden).
@@ -95,52 +245,25 @@ struct SyntaxStyle {
     bracket_pairs: Vec<(usize, usize, usize)>,
     /// Character width in pixels (approximated from font_size) for indent guide placement.
     char_width_px: f64,
-    /// Inline git blame: `(0-based line, "author • date")` — only for the current cursor line.
-    blame_line: Option<(usize, String)>,
+    /// Columns per indent level, for indent guide placement.
+    indent_width: usize,
     /// Bracket pair guides: (open_line, open_col_chars, close_line, depth) for vertical lines.
     bracket_pair_guides: Vec<(usize, usize, usize, usize)>,
+    /// Columns to draw vertical rulers at.
+    rulers: Vec<usize>,
+    ruler_color: floem::peniko::Color,
     /// Last known rope length for cache invalidation. If rope length changes,
     /// the entire states cache is cleared to prevent stale highlighting.
     last_rope_len: std::cell::Cell<usize>,
 }
 
 impl SyntaxStyle {
-    /// Create a `SyntaxStyle` for the given file extension.
-    /// Falls back to plain-text if no matching grammar is found.
-    fn for_extension(ext: &str, inner: Rc<dyn Styling>) -> Self {
-        let theme = &THEME_SET.themes["base16-ocean.dark"];
-        let highlighter = Highlighter::new(theme);
-
-        // Map common extensions → syntect scope names
-        let syntax = match ext {
-            "rs" => SYNTAX_SET.find_syntax_by_extension("rs"),
-            "py" | "pyw" => SYNTAX_SET.find_syntax_by_extension("py"),
-            "js" | "mjs" | "cjs" => SYNTAX_SET.find_syntax_by_extension("js"),
-            "ts" | "tsx" => SYNTAX_SET.find_syntax_by_extension("ts"),
-            "jsx" => SYNTAX_SET.find_syntax_by_extension("jsx"),
-            "json" | "jsonc" => SYNTAX_SET.find_syntax_by_extension("json"),
-            "toml" => SYNTAX_SET.find_syntax_by_extension("toml"),
-            "md" | "mdx" | "markdown" => SYNTAX_SET.find_syntax_by_extension("md"),
-            "html" | "htm" => SYNTAX_SET.find_syntax_by_extension("html"),
-            "css" => SYNTAX_SET.find_syntax_by_extension("css"),
-            "scss" | "sass" => SYNTAX_SET.find_syntax_by_extension("scss"),
-            "c" | "h" => SYNTAX_SET.find_syntax_by_extension("c"),
-            "cpp" | "cc" | "cxx" | "hpp" => SYNTAX_SET.find_syntax_by_extension("cpp"),
-            "go" => SYNTAX_SET.find_syntax_by_extension("go"),
-            "sh" | "bash" | "zsh" => SYNTAX_SET.find_syntax_by_extension("sh"),
-            "yaml" | "yml" => SYNTAX_SET.find_syntax_by_extension("yaml"),
-            "xml" => SYNTAX_SET.find_syntax_by_extension("xml"),
-            "sql" => SYNTAX_SET.find_syntax_by_extension("sql"),
-            "lua" => SYNTAX_SET.find_syntax_by_extension("lua"),
-            "rb" => SYNTAX_SET.find_syntax_by_extension("rb"),
-            "java" => SYNTAX_SET.find_syntax_by_extension("java"),
-            "kt" | "kts" => SYNTAX_SET.find_syntax_by_extension("kt"),
-            "swift" => SYNTAX_SET.find_syntax_by_extension("swift"),
-            "cs" => SYNTAX_SET.find_syntax_by_extension("cs"),
-            _ => None,
-        }
-        .or_else(|| SYNTAX_SET.find_syntax_plain_text().into())
-        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    /// Create a `SyntaxStyle` for the given file extension and syntect theme.
+    /// Falls back to plain-text if no matching grammar is found, and to
+    /// `base16-ocean.dark` if the theme name is unknown.
+    fn for_extension(ext: &str, theme_name: &str, inner: Rc<dyn Styling>) -> Self {
+        let highlighter = Highlighter::new(syntect_theme(theme_name));
+        let syntax = syntax_for_extension(ext);
 
         let parse_state_proto = ParseState::new(syntax);
 
@@ -149,22 +272,29 @@ impl SyntaxStyle {
             highlighter,
             parse_state_proto,
             doc: None,
-            states: RefCell::new(Vec::new()),
+            states: Checkpoints::shared(),
             diag_lines: Vec::new(),
             highlight_ranges: Vec::new(),
+            highlight_read_color: floem::peniko::Color::from_rgba8(100, 160, 255, 50),
+            highlight_write_color: floem::peniko::Color::from_rgba8(255, 150, 60, 60),
             git_lines: Vec::new(),
             git_color_added: floem::peniko::Color::from_rgba8(80, 200, 80, 220),
             git_color_modified: floem::peniko::Color::from_rgba8(80, 160, 255, 220),
             git_color_deleted: floem::peniko::Color::from_rgba8(220, 60, 60, 220),
+            heat_ranges: Vec::new(),
             current_line: 0,
+            bookmark_lines: Vec::new(),
+            bookmark_color: floem::peniko::Color::from_rgba8(80, 160, 255, 36),
             foldable_ranges: Vec::new(),
             folded_starts: HashSet::new(),
             find_match_ranges: Vec::new(),
             matching_bracket: None,
             bracket_pairs: Vec::new(),
             char_width_px: 8.4,
-            blame_line: None,
+            indent_width: 4,
             bracket_pair_guides: Vec::new(),
+            rulers: Vec::new(),
+            ruler_color: floem::peniko::Color::from_rgba8(120, 120, 140, 60),
             last_rope_len: std::cell::Cell::new(0),
         }
     }
@@ -174,6 +304,101 @@ impl SyntaxStyle {
     }
 }
 
+/// The syntect theme called `name`, or `base16-ocean.dark` if there is none.
+fn syntect_theme(name: &str) -> &'static Theme {
+    THEME_SET
+        .themes
+        .get(name)
+        .unwrap_or(&THEME_SET.themes["base16-ocean.dark"])
+}
+
+/// The grammar for files with extension `ext`, or plain text.
+fn syntax_for_extension(ext: &str) -> &'static SyntaxReference {
+    // Map common extensions → syntect scope names
+    match ext {
+        "rs" => SYNTAX_SET.find_syntax_by_extension("rs"),
+        "py" | "pyw" => SYNTAX_SET.find_syntax_by_extension("py"),
+        "js" | "mjs" | "cjs" => SYNTAX_SET.find_syntax_by_extension("js"),
+        "ts" | "tsx" => SYNTAX_SET.find_syntax_by_extension("ts"),
+        "jsx" => SYNTAX_SET.find_syntax_by_extension("jsx"),
+        "json" | "jsonc" => SYNTAX_SET.find_syntax_by_extension("json"),
+        "toml" => SYNTAX_SET.find_syntax_by_extension("toml"),
+        "md" | "mdx" | "markdown" => SYNTAX_SET.find_syntax_by_extension("md"),
+        "html" | "htm" => SYNTAX_SET.find_syntax_by_extension("html"),
+        "css" => SYNTAX_SET.find_syntax_by_extension("css"),
+        "scss" | "sass" => SYNTAX_SET.find_syntax_by_extension("scss"),
+        "c" | "h" => SYNTAX_SET.find_syntax_by_extension("c"),
+        "cpp" | "cc" | "cxx" | "hpp" => SYNTAX_SET.find_syntax_by_extension("cpp"),
+        "go" => SYNTAX_SET.find_syntax_by_extension("go"),
+        "sh" | "bash" | "zsh" => SYNTAX_SET.find_syntax_by_extension("sh"),
+        "yaml" | "yml" => SYNTAX_SET.find_syntax_by_extension("yaml"),
+        "xml" => SYNTAX_SET.find_syntax_by_extension("xml"),
+        "sql" => SYNTAX_SET.find_syntax_by_extension("sql"),
+        "lua" => SYNTAX_SET.find_syntax_by_extension("lua"),
+        "rb" => SYNTAX_SET.find_syntax_by_extension("rb"),
+        "java" => SYNTAX_SET.find_syntax_by_extension("java"),
+        "kt" | "kts" => SYNTAX_SET.find_syntax_by_extension("kt"),
+        "swift" => SYNTAX_SET.find_syntax_by_extension("swift"),
+        "cs" => SYNTAX_SET.find_syntax_by_extension("cs"),
+        "diff" | "patch" => SYNTAX_SET.find_syntax_by_extension("diff"),
+        _ => None,
+    }
+    .or_else(|| SYNTAX_SET.find_syntax_plain_text().into())
+    .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text())
+}
+
+/// Syntax colors for consecutive `lines` of a file with extension `ext`:
+/// for each line, the byte ranges of its spans and their colors.
+fn highlight_excerpt(
+    ext: &str,
+    theme_name: &str,
+    lines: &[String],
+) -> Vec<Vec<(std::ops::Range<usize>, floem::peniko::Color)>> {
+    let highlighter = Highlighter::new(syntect_theme(theme_name));
+    let mut parse_state = ParseState::new(syntax_for_extension(ext));
+    let mut highlight_state = HighlightState::new(&highlighter, ScopeStack::new());
+    lines
+        .iter()
+        .map(|line| {
+            // The grammars expect every line to end in its newline.
+            let text = format!("{line}\n");
+            let Ok(ops) = parse_state.parse_line(&text, &SYNTAX_SET) else {
+                return Vec::new();
+            };
+            RangedHighlightIterator::new(&mut highlight_state, &ops, &text, &highlighter)
+                .filter(|(_, _, range)| range.start < line.len())
+                .map(|(style, _, range)| {
+                    let c = style.foreground;
+                    (
+                        range.start..range.end.min(line.len()),
+                        floem::peniko::Color::from_rgba8(c.r, c.g, c.b, c.a),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A small text button in the peek widget's header.
+fn peek_button(
+    text: &'static str,
+    theme: RwSignal<PhazeTheme>,
+    on_click: impl Fn() + 'static,
+) -> impl IntoView {
+    label(move || text)
+        .on_click_stop(move |_| on_click())
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(11.0)
+                .padding_horiz(8.0)
+                .padding_vert(2.0)
+                .border_radius(4.0)
+                .color(p.text_secondary)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .hover(|s| s.background(p.bg_elevated).color(p.text_primary))
+        })
+}
+
 // ── Word-highlight helpers ─────────────────────────────────────────────────
 
 /// Returns (start_byte, end_byte, word) for the identifier/word under `offset`,
@@ -244,6 +469,22 @@ fn find_word_occurrences(text: &str, word: &str) -> Vec<(usize, usize)> {
     ranges
 }
 
+// ── Complexity heat ────────────────────────────────────────────────────────
+
+/// The functions of the file at `path` complex enough for a heat bar:
+/// `(first line, last line, heat)`, lines 0-based.
+fn complexity_heat(path: &std::path::Path) -> Vec<(usize, usize, Heat)> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let Ok(code) = std::fs::read_to_string(path) else {
+        return vec![];
+    };
+    metrics::measure(&code, Language::from_extension(ext))
+        .into_iter()
+        .filter(|f| f.heat() != Heat::Cool)
+        .map(|f| (f.line - 1, f.end_line - 1, f.heat()))
+        .collect()
+}
+
 // ── Git diff parser ────────────────────────────────────────────────────────
 
 /// Run `git diff HEAD -- <path>` and parse changed lines for the new file.
@@ -487,6 +728,20 @@ impl Styling for SyntaxStyle {
             });
         }
 
+        // Tinted background on bookmarked lines.
+        if self.bookmark_lines.contains(&line) {
+            let line_h = self.inner.line_height(edid, line) as f64;
+            layout_line.extra_style.push(LineExtraStyle {
+                x: 0.0,
+                y: 0.0,
+                width: Some(10000.0),
+                height: line_h,
+                bg_color: Some(self.bookmark_color),
+                under_line: None,
+                wave_line: None,
+            });
+        }
+
         // Draw wave_line (error) or under_line (warning/info) for diagnostic lines.
         for &(diag_line, severity) in &self.diag_lines {
             if diag_line != line {
@@ -542,6 +797,23 @@ impl Styling for SyntaxStyle {
             });
         }
 
+        // Heat bar beside the git bar on the lines of a complex function.
+        for &(start, end, color) in &self.heat_ranges {
+            if line < start || line > end {
+                continue;
+            }
+            let line_h = self.inner.line_height(edid, line) as f64;
+            layout_line.extra_style.push(LineExtraStyle {
+                x: 4.0,
+                y: 0.0,
+                width: Some(2.0),
+                height: line_h,
+                bg_color: Some(color),
+                under_line: None,
+                wave_line: None,
+            });
+        }
+
         // Draw fold indicator (bright = collapsed, dim = expanded) in gutter.
         self.paint_fold_indicator(edid, line, layout_line);
 
@@ -560,10 +832,11 @@ impl Styling for SyntaxStyle {
                 .chars()
                 .take_while(|c| *c == ' ' || *c == '\t')
                 .count();
-            // Draw guide at each 4-space indent level
-            if leading_spaces >= 4 {
+            // Draw guide at each indent level
+            let step = self.indent_width.max(1);
+            if leading_spaces >= step {
                 let line_h = self.inner.line_height(edid, line) as f64;
-                let mut indent = 4usize;
+                let mut indent = step;
                 while indent <= leading_spaces {
                     let x = (indent as f64) * self.char_width_px;
                     layout_line.extra_style.push(LineExtraStyle {
@@ -575,11 +848,27 @@ impl Styling for SyntaxStyle {
                         under_line: None,
                         wave_line: None,
                     });
-                    indent += 4;
+                    indent += step;
                 }
             }
         }
 
+        // ── Rulers ────────────────────────────────────────────────────────────
+        if !self.rulers.is_empty() {
+            let line_h = self.inner.line_height(edid, line) as f64;
+            for &column in &self.rulers {
+                layout_line.extra_style.push(LineExtraStyle {
+                    x: column as f64 * self.char_width_px,
+                    y: 0.0,
+                    width: Some(1.0),
+                    height: line_h,
+                    bg_color: Some(self.ruler_color),
+                    under_line: None,
+                    wave_line: None,
+                });
+            }
+        }
+
         // ── Bracket pair guides ───────────────────────────────────────────────
         // Draw 1px vertical lines at the open-bracket column from open_line+1
         // to close_line, connecting matching `{}`/`()`/`[]` pairs across lines.
@@ -684,7 +973,7 @@ impl Styling for SyntaxStyle {
                 };
                 let line_h = self.inner.line_height(edid, line) as f64;
 
-                for &(hl_start, hl_end) in &self.highlight_ranges {
+                for &(hl_start, hl_end, write) in &self.highlight_ranges {
                     if hl_end <= line_start || hl_start >= line_end {
                         continue;
                     }
@@ -699,7 +988,11 @@ impl Styling for SyntaxStyle {
                         y: 0.0,
                         width: Some((x1 - x0).max(2.0)),
                         height: line_h,
-                        bg_color: Some(floem::peniko::Color::from_rgba8(100, 160, 255, 50)),
+                        bg_color: Some(if write {
+                            self.highlight_write_color
+                        } else {
+                            self.highlight_read_color
+                        }),
                         under_line: None,
                         wave_line: None,
                     });
@@ -746,107 +1039,7 @@ impl SyntaxStyle {
     }
 }
 
-// ── Fold range detection ──────────────────────────────────────────────────────
-
-/// Detects foldable regions from source text using brace/bracket matching.
-/// Returns `Vec<(start_line, end_line)>` where `start_line` has the `{`
-/// and `end_line` has the matching `}`.  Ranges with start==end are excluded.
-fn detect_fold_ranges(text: &str) -> Vec<(usize, usize)> {
-    let mut stack: Vec<usize> = Vec::new();
-    let mut ranges: Vec<(usize, usize)> = Vec::new();
-    let mut in_string = false;
-    let mut string_char = '"';
-    let mut prev_char = '\0';
-
-    for (line_idx, line) in text.lines().enumerate() {
-        for ch in line.chars() {
-            if in_string {
-                if ch == string_char && prev_char != '\\' {
-                    in_string = false;
-                }
-            } else {
-                match ch {
-                    '"' | '\'' => {
-                        in_string = true;
-                        string_char = ch;
-                    }
-                    '{' => stack.push(line_idx),
-                    '}' => {
-                        if let Some(start) = stack.pop() {
-                            if line_idx > start {
-                                ranges.push((start, line_idx));
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            prev_char = ch;
-        }
-        // Strings don't span lines in this simplified parser.
-        if in_string {
-            in_string = false;
-        }
-        prev_char = '\0';
-    }
-    // Sort by start line for consistent ordering.
-    ranges.sort_by_key(|&(s, _)| s);
-    ranges
-}
-
-// ── Bracket pair detection ────────────────────────────────────────────────────
-
-/// Detects all bracket pairs in `text` and returns `(open_byte, close_byte, depth)`.
-/// Only `{`, `(`, `[` pairs are tracked; depth is 0-based nesting level.
-fn compute_bracket_pairs(text: &str) -> Vec<(usize, usize, usize)> {
-    let mut stack: Vec<(usize, usize)> = Vec::new(); // (byte_pos, depth)
-    let mut result: Vec<(usize, usize, usize)> = Vec::new();
-    let mut depth = 0usize;
-    let mut in_string = false;
-    let mut string_char = '"';
-    let mut in_line_comment = false;
-    let mut prev = '\0';
-    let mut byte_pos = 0usize;
-
-    for ch in text.chars() {
-        let ch_len = ch.len_utf8();
-
-        if in_line_comment {
-            if ch == '\n' {
-                in_line_comment = false;
-            }
-        } else if in_string {
-            if ch == string_char && prev != '\\' {
-                in_string = false;
-            }
-        } else {
-            match ch {
-                '/' if prev == '/' => {
-                    in_line_comment = true;
-                }
-                '"' | '\'' => {
-                    in_string = true;
-                    string_char = ch;
-                }
-                '(' | '[' | '{' => {
-                    stack.push((byte_pos, depth));
-                    depth += 1;
-                }
-                ')' | ']' | '}' => {
-                    if let Some((open_pos, open_depth)) = stack.pop() {
-                        result.push((open_pos, byte_pos, open_depth));
-                        depth = open_depth;
-                    }
-                }
-                _ => {}
-            }
-        }
-
-        prev = ch;
-        byte_pos += ch_len;
-    }
-    result
-}
+// ── Matching bracket ──────────────────────────────────────────────────────────
 
 /// Given text and a byte offset, return `(open_byte, close_byte)` for the bracket at
 /// that offset (or `None` if no bracket is there).
@@ -872,6 +1065,34 @@ fn find_bracket_match(
     None
 }
 
+/// Width of the blame gutter, in pixels.
+const BLAME_GUTTER_WIDTH: f64 = 190.0;
+
+/// Fold ranges and bracket pairs are worked out once edits pause this long.
+const DERIVE_IDLE: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// "author, 3 days ago · summary" for the status bar and end-of-line annotation.
+fn blame_summary(line: &BlameLine) -> String {
+    if line.is_uncommitted() {
+        return "Not committed yet".to_string();
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let summary = if line.summary.chars().count() > 60 {
+        let cut: String = line.summary.chars().take(59).collect();
+        format!("{cut}…")
+    } else {
+        line.summary.clone()
+    };
+    format!(
+        "{}, {} · {summary}",
+        line.author,
+        relative_time(line.author_time, now)
+    )
+}
+
 // ── Tab state ─────────────────────────────────────────────────────────────────
 
 #[derive(Clone)]
@@ -881,6 +1102,145 @@ struct TabState {
     dirty: RwSignal<bool>,
 }
 
+// ── Changes on disk ───────────────────────────────────────────────────────────
+
+/// What an open file held on disk when it was read or last saved: the stamp
+/// that tells someone else's changes from ours, and the text a merge starts
+/// from.
+struct OnDisk {
+    stamp: DiskStamp,
+    text: String,
+}
+
+type DiskRegistry = Rc<RefCell<HashMap<String, OnDisk>>>;
+
+/// The encoding each open file is read and saved in.
+type EncodingRegistry = Rc<RefCell<HashMap<String, TextEncoding>>>;
+
+/// How often the editor picks up what the watcher saw change.
+const DISK_POLL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Note that `path`, registered as `key`, now holds `bytes` on disk, which
+/// decode to `text`.
+fn remember_on_disk(registry: &DiskRegistry, key: &str, path: &Path, text: String, bytes: &[u8]) {
+    match DiskStamp::written(path, bytes) {
+        Ok(stamp) => {
+            registry
+                .borrow_mut()
+                .insert(key.to_string(), OnDisk { stamp, text });
+        }
+        Err(_) => {
+            registry.borrow_mut().remove(key);
+        }
+    }
+}
+
+/// Whether `path` changed on disk since it was read or saved.
+fn changed_on_disk(registry: &DiskRegistry, key: &str, path: &Path) -> bool {
+    registry
+        .borrow()
+        .get(key)
+        .is_some_and(|d| d.stamp.check(path) == DiskChange::Modified)
+}
+
+/// The file at `path` as its bytes and their text, decoded in `encoding`
+/// when they're valid in it and in the detected encoding otherwise.
+fn read_file_text(
+    path: &Path,
+    encoding: Option<TextEncoding>,
+) -> Option<(Vec<u8>, String, TextEncoding)> {
+    let bytes = std::fs::read(path).ok()?;
+    let (text, encoding) = match encoding.and_then(|e| e.decode(&bytes).map(|t| (t, e))) {
+        Some(decoded) => decoded,
+        None => decode_text(&bytes),
+    };
+    Some((bytes, text, encoding))
+}
+
+fn replace_text(doc: &Rc<dyn Document>, text: &str) {
+    let len = doc.text().len();
+    doc.edit_single(Selection::region(0, len), text, EditType::InsertChars);
+}
+
+/// Send batches of the files under `root` that were written, one per poll,
+/// until the receiver goes away.
+fn watch_disk(root: PathBuf, tx: std::sync::mpsc::SyncSender<Vec<PathBuf>>) {
+    std::thread::spawn(move || {
+        let Ok(watcher) = FileWatcher::shared(&root) else {
+            return;
+        };
+        let Ok(mut events) = watcher.subscribe(&[]) else {
+            return;
+        };
+        loop {
+            std::thread::sleep(DISK_POLL);
+            let mut written = Vec::new();
+            while let Ok(event) = events.try_recv() {
+                if event.kind != FileChangeKind::Removed && !written.contains(&event.path) {
+                    written.push(event.path);
+                }
+            }
+            if !written.is_empty() && tx.send(written).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+fn disk_bar_button(
+    text: &'static str,
+    theme: RwSignal<PhazeTheme>,
+    visible: impl Fn() -> bool + 'static,
+    on_click: impl Fn() + 'static,
+) -> impl IntoView {
+    container(label(move || text))
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.padding_horiz(8.0)
+                .padding_vert(3.0)
+                .margin_left(6.0)
+                .font_size(12.0)
+                .color(p.text_primary)
+                .border(1.0)
+                .border_color(p.border)
+                .border_radius(4.0)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .hover(|s| s.background(p.bg_elevated))
+                .apply_if(!visible(), |s| s.display(floem::style::Display::None))
+        })
+        .on_click_stop(move |_| on_click())
+}
+
+// ── File format ───────────────────────────────────────────────────────────────
+
+/// A change to how the active file is stored, from the status bar or palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormatCommand {
+    /// Convert the buffer's line endings; saved with the next save.
+    LineEndings(LineEnding),
+    /// Read the file again in this encoding. Refused while it has unsaved
+    /// changes.
+    Reopen(TextEncoding),
+    /// Save the buffer in this encoding from now on.
+    SaveAs(TextEncoding),
+}
+
+// ── Compare ───────────────────────────────────────────────────────────────────
+
+/// A request from the palette or the COMPARE tab about an editor buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferCommand {
+    /// Compare the active file's buffer with the file on disk.
+    CompareWithSaved,
+    /// Compare the active file's buffer with the clipboard.
+    CompareWithClipboard,
+    /// Replace the buffer of the open file `path` with `text`, as one edit.
+    Replace { path: PathBuf, text: String },
+    /// Compare `path`, its buffer if it's open, with `body` put in place,
+    /// for a chat code block to be reviewed before it's applied.
+    Review { path: PathBuf, body: String },
+}
+
 // ── Editor panel ──────────────────────────────────────────────────────────────
 
 /// Full multi-tab code editor with syntect syntax highlighting.
@@ -889,10 +1249,17 @@ struct TabState {
 /// `lsp_cmd` notifies the LSP server on every edit (did_change).
 /// `active_cursor` is written with (path, 0-based line, 0-based col) whenever
 ///   the active editor's cursor moves — read by the completion popup.
+/// `peek` is shown inline below its line while its file is the active tab;
+///   "Open to the Side" writes the peeked location to `peek_open_to_side`.
+/// `line_ending_out` and `encoding_out` describe the active file for the
+///   status bar; `file_format_cmd` changes them.
+/// `rulers` are drawn in every file, along with its `.editorconfig`
+///   `max_line_length` as a print margin.
 #[allow(clippy::too_many_arguments)]
 pub fn editor_panel(
     open_file: RwSignal<Option<PathBuf>>,
     theme: RwSignal<PhazeTheme>,
+    syntax_theme: RwSignal<String>,
     ai_thinking: RwSignal<bool>,
     lsp_cmd: tokio::sync::mpsc::UnboundedSender<crate::lsp_bridge::LspCommand>,
     active_cursor: RwSignal<Option<(PathBuf, u32, u32)>>,
@@ -907,6 +1274,9 @@ pub fn editor_panel(
     auto_save: RwSignal<bool>,
     workspace_root: std::path::PathBuf,
     font_size: RwSignal<u32>,
+    font_family: RwSignal<String>,
+    font_ligatures: RwSignal<bool>,
+    line_height: RwSignal<f32>,
     word_wrap: RwSignal<bool>,
     ctrl_d_nonce: RwSignal<u64>,
     fold_nonce: RwSignal<u64>,
@@ -932,6 +1302,8 @@ pub fn editor_panel(
     yank_ring: RwSignal<Vec<String>>,
     tab_size: RwSignal<u32>,
     line_ending_out: RwSignal<&'static str>,
+    indent_out: RwSignal<Option<EffectiveIndent>>,
+    bookmarks: RwSignal<Bookmarks>,
     lsp_folding_ranges: RwSignal<Vec<(u32, u32)>>,
     transform_title_nonce: RwSignal<u64>,
     format_selection_nonce: RwSignal<u64>,
@@ -940,13 +1312,44 @@ pub fn editor_panel(
     unfold_all_nonce: RwSignal<u64>,
     code_lens_sig: RwSignal<Vec<crate::lsp_bridge::CodeLensEntry>>,
     code_lens_visible: RwSignal<bool>,
+    code_lens_clicked: RwSignal<Option<crate::lsp_bridge::CodeLensEntry>>,
+    document_highlights: RwSignal<Option<crate::lsp_bridge::DocumentHighlights>>,
+    peek: RwSignal<Option<crate::app::Peek>>,
+    peek_open_to_side: RwSignal<Option<(PathBuf, u32)>>,
     organize_imports_on_save: RwSignal<bool>,
     inlay_hints: RwSignal<Vec<crate::lsp_bridge::InlayHintEntry>>,
     inlay_hints_toggle: RwSignal<bool>,
+    inline_blame: RwSignal<bool>,
+    blame_gutter: RwSignal<bool>,
+    find_similar_nonce: RwSignal<u64>,
+    similar_code_out: RwSignal<Option<crate::app::SimilarCodeQuery>>,
+    ext_goto_line_end: RwSignal<u32>,
+    extract_tasks_nonce: RwSignal<u64>,
+    extract_tasks_out: RwSignal<Option<String>>,
+    normalize_line_endings_on_save: RwSignal<bool>,
+    encoding_out: RwSignal<TextEncoding>,
+    file_format_cmd: RwSignal<Option<FileFormatCommand>>,
+    rulers: RwSignal<Vec<u32>>,
+    fold_level: RwSignal<Option<usize>>,
+    saved_folds: RwSignal<FoldMemory>,
+    buffer_cmd: RwSignal<Option<BufferCommand>>,
+    comparison_out: RwSignal<Option<Comparison>>,
 ) -> impl IntoView {
     let tabs: RwSignal<Vec<TabState>> = create_rw_signal(vec![]);
     let active_idx: RwSignal<Option<usize>> = create_rw_signal(None);
 
+    // Clicking a blame annotation writes the commit to a `.diff` file in a
+    // background thread; it arrives here and opens as a new tab.
+    let (commit_tab_tx, commit_tab_rx) = std::sync::mpsc::sync_channel::<PathBuf>(4);
+    {
+        let commit_tab_sig = create_signal_from_channel(commit_tab_rx);
+        create_effect(move |_| {
+            if let Some(path) = commit_tab_sig.get() {
+                open_file.set(Some(path));
+            }
+        });
+    }
+
     // ── Restore session tabs on first mount ──────────────────────────────────
     // Open all paths from the previous session as background tabs before the
     // file-open memo runs. Guard with a one-shot flag so this only fires once.
@@ -1010,6 +1413,55 @@ pub fn editor_panel(
     let docs_for_stack = docs.clone();
     let docs_for_save = docs.clone();
     let docs_for_find = docs.clone();
+    let on_disk: DiskRegistry = Rc::new(RefCell::new(HashMap::new()));
+    let encodings: EncodingRegistry = Rc::new(RefCell::new(HashMap::new()));
+    // Bumped when the active file's encoding or line endings may have changed.
+    let format_rev = create_rw_signal(0u64);
+
+    // Closed tabs' documents stay registered so reopening one keeps its
+    // state; the least recently closed are dropped once the documents are
+    // over their memory budget. Open tabs count against it but always stay.
+    let closed_docs: Rc<RefCell<BudgetLru<String, ()>>> = Rc::new(RefCell::new(BudgetLru::new()));
+    {
+        let docs = docs.clone();
+        let on_disk = on_disk.clone();
+        let encodings = encodings.clone();
+        create_effect(move |_| {
+            let open: HashSet<String> = tabs.with(|tabs| {
+                tabs.iter()
+                    .map(|t| t.path.to_string_lossy().to_string())
+                    .collect()
+            });
+            let mut registry = docs.borrow_mut();
+            let mut closed = closed_docs.borrow_mut();
+            let mut open_bytes = 0;
+            for (key, doc) in registry.iter() {
+                let bytes = doc.text().len();
+                if open.contains(key) {
+                    closed.remove(key);
+                    open_bytes += bytes;
+                } else if !closed.contains(key) {
+                    closed.insert(key.clone(), (), bytes);
+                }
+            }
+            let ledger = memory::global();
+            let budget = ledger
+                .budget(Subsystem::Documents)
+                .saturating_sub(open_bytes);
+            let evicted = closed.evict_to(budget);
+            for (key, ()) in &evicted {
+                registry.remove(key);
+                on_disk.borrow_mut().remove(key);
+                encodings.borrow_mut().remove(key);
+            }
+            ledger.evicted(Subsystem::Documents, evicted.len());
+            ledger.report(
+                Subsystem::Documents,
+                open_bytes + closed.bytes(),
+                registry.len(),
+            );
+        });
+    }
 
     // ── Find in file (Ctrl+F) ────────────────────────────────────────────────
     let find_open: RwSignal<bool> = create_rw_signal(false);
@@ -1106,6 +1558,8 @@ pub fn editor_panel(
     // Nonce — when incremented, the active editor recreates at `goto_line`.
     let goto_nonce: RwSignal<u64> = create_rw_signal(0u64);
     let goto_line: RwSignal<usize> = create_rw_signal(1usize);
+    // Last line of the range to select at `goto_line`; 0 places only the cursor.
+    let goto_line_end: RwSignal<usize> = create_rw_signal(0usize);
 
     // Wire external goto_line (from LSP go-to-definition) into the local mechanism.
     // When IdeState.goto_line becomes nonzero we jump to that line, then reset to 0.
@@ -1113,8 +1567,10 @@ pub fn editor_panel(
         let line = ext_goto_line.get();
         if line > 0 {
             goto_line.set(line as usize);
+            goto_line_end.set(ext_goto_line_end.get_untracked() as usize);
             goto_nonce.update(|v| *v += 1);
             ext_goto_line.set(0);
+            ext_goto_line_end.set(0);
         }
     });
 
@@ -1122,8 +1578,10 @@ pub fn editor_panel(
     let _ = create_memo(move |_| {
         let path = open_file.get();
         if let Some(p) = path {
-            // Don't open tabs for files that no longer exist
-            if !p.exists() {
+            // Folders aren't opened as tabs. A file that doesn't exist opens
+            // empty and is created when it's saved, as long as its folder
+            // is there.
+            if p.is_dir() || (!p.exists() && !p.parent().is_some_and(|dir| dir.is_dir())) {
                 return;
             }
             let existing = tabs.get().iter().position(|t| t.path == p);
@@ -1149,8 +1607,407 @@ pub fn editor_panel(
         }
     });
 
+    // ── Changes on disk ─────────────────────────────────────────────────────
+    // Saves don't write over a file that changed since it was read; its tab
+    // gets the Reload / Overwrite / Merge bar instead. The watcher reloads
+    // clean tabs whose file changed and raises the bar for dirty ones.
+    let disk_conflict: RwSignal<Option<PathBuf>> = create_rw_signal(None);
+    let disk_notice: RwSignal<Option<String>> = create_rw_signal(None);
+    let save_to_disk = {
+        let on_disk = on_disk.clone();
+        let encodings = encodings.clone();
+        Rc::new(move |path: &Path, content: String, force: bool| -> bool {
+            let key = path.to_string_lossy().to_string();
+            if !force && changed_on_disk(&on_disk, &key, path) {
+                disk_conflict.set(Some(path.to_path_buf()));
+                return false;
+            }
+            let encoding = encodings.borrow().get(&key).copied().unwrap_or_default();
+            let bytes = match encoding.encode(&content) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    disk_notice.set(Some(format!(
+                        "Not saved: {e}. Save with Encoding to pick one that fits."
+                    )));
+                    return false;
+                }
+            };
+            if write_atomic(path, &bytes).is_err() {
+                return false;
+            }
+            remember_on_disk(&on_disk, &key, path, content, &bytes);
+            format_rev.update(|n| *n += 1);
+            true
+        })
+    };
+    let reload_from_disk = {
+        let docs = docs.clone();
+        let on_disk = on_disk.clone();
+        let encodings = encodings.clone();
+        Rc::new(move |path: &Path| {
+            let key = path.to_string_lossy().to_string();
+            let current = encodings.borrow().get(&key).copied();
+            let Some((bytes, text, encoding)) = read_file_text(path, current) else {
+                return;
+            };
+            let Some(doc) = docs.borrow().get(&key).cloned() else {
+                return;
+            };
+            replace_text(&doc, &text);
+            encodings.borrow_mut().insert(key.clone(), encoding);
+            remember_on_disk(&on_disk, &key, path, text, &bytes);
+            if let Some(tab) = tabs.get_untracked().iter().find(|t| t.path == path) {
+                tab.dirty.set(false);
+            }
+            format_rev.update(|n| *n += 1);
+        })
+    };
+    {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<Vec<PathBuf>>(4);
+        watch_disk(workspace_root.clone(), tx);
+        let written = create_signal_from_channel(rx);
+        let on_disk = on_disk.clone();
+        let reload = reload_from_disk.clone();
+        create_effect(move |_| {
+            let Some(paths) = written.get() else {
+                return;
+            };
+            let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+            let written: HashSet<PathBuf> = paths.iter().map(|p| canonical(p)).collect();
+            for tab in tabs.get_untracked() {
+                let key = tab.path.to_string_lossy().to_string();
+                if !written.contains(&canonical(&tab.path))
+                    || !changed_on_disk(&on_disk, &key, &tab.path)
+                {
+                    continue;
+                }
+                if tab.dirty.get_untracked() {
+                    disk_conflict.set(Some(tab.path.clone()));
+                } else {
+                    reload(&tab.path);
+                }
+            }
+        });
+    }
+
+    // ── File format ─────────────────────────────────────────────────────────
+    // The status bar shows the active file's encoding and line endings as
+    // its buffer has them; its menu and the palette send FileFormatCommands.
+    {
+        let docs = docs.clone();
+        let encodings = encodings.clone();
+        create_effect(move |_| {
+            let _ = format_rev.get();
+            let Some(key) = active_idx.get().and_then(|i| {
+                tabs.with(|t| t.get(i).map(|t| t.path.to_string_lossy().to_string()))
+            }) else {
+                return;
+            };
+            encoding_out.set(encodings.borrow().get(&key).copied().unwrap_or_default());
+            if let Some(doc) = docs.borrow().get(&key) {
+                line_ending_out.set(LineEndings::detect(&doc.text().to_string()).label());
+            }
+        });
+    }
+    {
+        let docs = docs.clone();
+        let encodings = encodings.clone();
+        let on_disk = on_disk.clone();
+        let save = save_to_disk.clone();
+        let lsp_cmd = lsp_cmd.clone();
+        create_effect(move |_| {
+            let Some(command) = file_format_cmd.get() else {
+                return;
+            };
+            file_format_cmd.set(None);
+            let Some(tab) = active_idx
+                .get_untracked()
+                .and_then(|i| tabs.get_untracked().get(i).cloned())
+            else {
+                return;
+            };
+            let key = tab.path.to_string_lossy().to_string();
+            let Some(doc) = docs.borrow().get(&key).cloned() else {
+                return;
+            };
+            match command {
+                FileFormatCommand::LineEndings(ending) => {
+                    let text = doc.text().to_string();
+                    let converted = convert_line_endings(&text, ending);
+                    if converted != text {
+                        replace_text(&doc, &converted);
+                        tab.dirty.set(true);
+                    }
+                }
+                FileFormatCommand::Reopen(encoding) => {
+                    if tab.dirty.get_untracked() {
+                        disk_notice.set(Some(format!(
+                            "Save or undo the changes to {} before reopening it.",
+                            tab.name
+                        )));
+                        return;
+                    }
+                    let Ok(bytes) = std::fs::read(&tab.path) else {
+                        return;
+                    };
+                    let Some(text) = encoding.decode(&bytes) else {
+                        disk_notice.set(Some(format!("{} isn't valid {encoding}.", tab.name)));
+                        return;
+                    };
+                    encodings.borrow_mut().insert(key.clone(), encoding);
+                    replace_text(&doc, &text);
+                    remember_on_disk(&on_disk, &key, &tab.path, text, &bytes);
+                    tab.dirty.set(false);
+                }
+                FileFormatCommand::SaveAs(encoding) => {
+                    if let Err(e) = encoding.encode(&doc.text().to_string()) {
+                        disk_notice.set(Some(format!("Can't save as {encoding}: {e}.")));
+                        return;
+                    }
+                    let previous = encodings.borrow_mut().insert(key.clone(), encoding);
+                    let content = apply_editorconfig_on_save(
+                        &doc,
+                        &tab.path,
+                        normalize_line_endings_on_save.get_untracked(),
+                    );
+                    if save(&tab.path, content, false) {
+                        tab.dirty.set(false);
+                        let _ = lsp_cmd.send(crate::lsp_bridge::LspCommand::SaveFile {
+                            path: tab.path.clone(),
+                        });
+                    } else if let Some(previous) = previous {
+                        encodings.borrow_mut().insert(key, previous);
+                    }
+                }
+            }
+            format_rev.update(|n| *n += 1);
+        });
+    }
+
+    // ── Compare ─────────────────────────────────────────────────────────────
+    // Puts the active buffer next to the file on disk or the clipboard in the
+    // COMPARE tab, and a file next to a chat code block applied to it; takes
+    // back the changes it copies into a buffer.
+    {
+        let docs = docs.clone();
+        let encodings = encodings.clone();
+        create_effect(move |_| {
+            let Some(command) = buffer_cmd.get() else {
+                return;
+            };
+            buffer_cmd.set(None);
+            let with_saved = match command {
+                BufferCommand::Replace { path, text } => {
+                    let key = path.to_string_lossy().to_string();
+                    let Some(doc) = docs.borrow().get(&key).cloned() else {
+                        return;
+                    };
+                    replace_text(&doc, &text);
+                    if let Some(tab) = tabs.get_untracked().iter().find(|t| t.path == path) {
+                        tab.dirty.set(true);
+                    }
+                    return;
+                }
+                BufferCommand::Review { path, body } => {
+                    let key = path.to_string_lossy().to_string();
+                    let name = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let open = tabs.get_untracked().iter().any(|t| t.path == path);
+                    let doc = docs.borrow().get(&key).cloned().filter(|_| open);
+                    let current = match doc {
+                        Some(doc) => CompareSide {
+                            source: CompareSource::Buffer(path.clone()),
+                            text: doc.text().to_string(),
+                            encoding: encodings.borrow().get(&key).copied().unwrap_or_default(),
+                        },
+                        None => match read_file_text(&path, None) {
+                            Some((_, text, encoding)) => CompareSide {
+                                source: CompareSource::File(path.clone()),
+                                text,
+                                encoding,
+                            },
+                            None if path.exists() => {
+                                disk_notice.set(Some(format!("Couldn't read {name}.")));
+                                return;
+                            }
+                            // A new file.
+                            None => CompareSide {
+                                source: CompareSource::File(path.clone()),
+                                text: String::new(),
+                                encoding: TextEncoding::default(),
+                            },
+                        },
+                    };
+                    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                    match apply_block(&current.text, &body, ext) {
+                        Some(text) => comparison_out.set(Some(Comparison {
+                            right: CompareSide {
+                                source: CompareSource::Suggestion,
+                                text,
+                                encoding: current.encoding,
+                            },
+                            left: current,
+                        })),
+                        None => disk_notice.set(Some(format!(
+                            "Couldn't tell where the code block goes in {name}."
+                        ))),
+                    }
+                    return;
+                }
+                BufferCommand::CompareWithSaved => true,
+                BufferCommand::CompareWithClipboard => false,
+            };
+            let Some(tab) = active_idx
+                .get_untracked()
+                .and_then(|i| tabs.get_untracked().get(i).cloned())
+            else {
+                return;
+            };
+            let key = tab.path.to_string_lossy().to_string();
+            let Some(doc) = docs.borrow().get(&key).cloned() else {
+                return;
+            };
+            let encoding = encodings.borrow().get(&key).copied().unwrap_or_default();
+            let buffer = CompareSide {
+                source: CompareSource::Buffer(tab.path.clone()),
+                text: doc.text().to_string(),
+                encoding,
+            };
+            let other = if with_saved {
+                read_file_text(&tab.path, Some(encoding)).map(|(_, text, encoding)| CompareSide {
+                    source: CompareSource::File(tab.path.clone()),
+                    text,
+                    encoding,
+                })
+            } else {
+                CompareSide::clipboard()
+            };
+            match other {
+                Some(other) => comparison_out.set(Some(Comparison {
+                    left: other,
+                    right: buffer,
+                })),
+                None => disk_notice.set(Some(format!("Nothing to compare {} with.", tab.name))),
+            }
+        });
+    }
+
+    let disk_bar = {
+        let has_conflict = move || disk_conflict.get().is_some();
+        let message = label(move || match (disk_conflict.get(), disk_notice.get()) {
+            (Some(path), _) => format!(
+                "{} changed on disk since it was opened. Keep which version?",
+                path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            ),
+            (None, Some(notice)) => notice,
+            (None, None) => String::new(),
+        })
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.flex_grow(1.0).font_size(12.0).color(p.text_primary)
+        });
+        let reload = {
+            let reload = reload_from_disk.clone();
+            disk_bar_button("Reload", theme, has_conflict, move || {
+                if let Some(path) = disk_conflict.get_untracked() {
+                    reload(&path);
+                }
+                disk_conflict.set(None);
+            })
+        };
+        let overwrite = {
+            let save = save_to_disk.clone();
+            let lsp_cmd = lsp_cmd.clone();
+            let docs = docs.clone();
+            disk_bar_button("Overwrite", theme, has_conflict, move || {
+                let Some(path) = disk_conflict.get_untracked() else {
+                    return;
+                };
+                disk_conflict.set(None);
+                let key = path.to_string_lossy().to_string();
+                let Some(doc) = docs.borrow().get(&key).cloned() else {
+                    return;
+                };
+                let content = apply_editorconfig_on_save(
+                    &doc,
+                    &path,
+                    normalize_line_endings_on_save.get_untracked(),
+                );
+                if save(&path, content, true) {
+                    if let Some(tab) = tabs.get_untracked().iter().find(|t| t.path == path) {
+                        tab.dirty.set(false);
+                    }
+                    let _ = lsp_cmd.send(crate::lsp_bridge::LspCommand::SaveFile { path });
+                }
+            })
+        };
+        let merge = {
+            let docs = docs.clone();
+            let on_disk = on_disk.clone();
+            let encodings = encodings.clone();
+            disk_bar_button("Merge", theme, has_conflict, move || {
+                let Some(path) = disk_conflict.get_untracked() else {
+                    return;
+                };
+                disk_conflict.set(None);
+                let key = path.to_string_lossy().to_string();
+                let Some(doc) = docs.borrow().get(&key).cloned() else {
+                    return;
+                };
+                let current = encodings.borrow().get(&key).copied();
+                let Some((bytes, theirs, _)) = read_file_text(&path, current) else {
+                    return;
+                };
+                let base = on_disk
+                    .borrow()
+                    .get(&key)
+                    .map(|d| d.text.clone())
+                    .unwrap_or_default();
+                let merged = merge3(&base, &doc.text().to_string(), &theirs);
+                replace_text(&doc, &merged.text);
+                remember_on_disk(&on_disk, &key, &path, theirs, &bytes);
+                if let Some(tab) = tabs.get_untracked().iter().find(|t| t.path == path) {
+                    tab.dirty.set(true);
+                }
+                if merged.conflicts > 0 {
+                    disk_notice.set(Some(format!(
+                        "Merged with {} conflict(s), marked between {MARKER_OURS} and {MARKER_THEIRS}. Save when resolved.",
+                        merged.conflicts
+                    )));
+                }
+            })
+        };
+        let dismiss = disk_bar_button(
+            "Dismiss",
+            theme,
+            || true,
+            move || {
+                disk_conflict.set(None);
+                disk_notice.set(None);
+            },
+        );
+        stack((message, reload, overwrite, merge, dismiss)).style(move |s| {
+            let p = theme.get().palette;
+            let visible = disk_conflict.get().is_some() || disk_notice.get().is_some();
+            s.flex_row()
+                .items_center()
+                .width_full()
+                .padding_horiz(12.0)
+                .padding_vert(4.0)
+                .background(p.warning.with_alpha(0.12))
+                .border_bottom(1.0)
+                .border_color(p.border)
+                .apply_if(!visible, |s| s.display(floem::style::Display::None))
+        })
+    };
+
     // Ctrl+S save handler
     let lsp_cmd_for_save = lsp_cmd.clone();
+    let save_to_disk_for_save = save_to_disk.clone();
     let save_fn = Rc::new(move || {
         let Some(idx) = active_idx.get() else { return };
         let tab_list = tabs.get();
@@ -1160,8 +2017,9 @@ pub fn editor_panel(
         let Some(doc) = registry.get(&key) else {
             return;
         };
-        let content = doc.text().to_string();
-        if std::fs::write(&tab.path, content).is_ok() {
+        let content =
+            apply_editorconfig_on_save(doc, &tab.path, normalize_line_endings_on_save.get());
+        if save_to_disk_for_save(&tab.path, content, false) {
             tab.dirty.set(false);
             // Send textDocument/didSave so LSP servers that rely on it (e.g. rust-analyzer
             // doesn't need it, but gopls, pylsp, etc. do) get the save notification.
@@ -1322,6 +2180,9 @@ pub fn editor_panel(
     // changes.  Font-size updates call editor.update_styling() reactively.
     // Goto-line uses the same nonce-effect pattern as find-cursor-jump.
     // This preserves the undo/redo stack across zoom and navigation.
+    let on_disk_for_stack = on_disk.clone();
+    let encodings_for_stack = encodings.clone();
+    let save_to_disk_for_stack = save_to_disk.clone();
     let editor_body = dyn_stack(
         move || tabs.get().into_iter().enumerate().collect::<Vec<_>>(),
         |(_i, tab)| format!("{}", tab.path.to_string_lossy()),
@@ -1338,56 +2199,44 @@ pub fn editor_panel(
                 let reg = docs_for_stack.borrow();
                 reg.get(&key)
                     .map(|d| d.text().to_string())
-                    .unwrap_or_else(|| std::fs::read_to_string(&tab.path).unwrap_or_default())
+                    .unwrap_or_else(|| {
+                        let (bytes, text, encoding) =
+                            read_file_text(&tab.path, None).unwrap_or_default();
+                        encodings_for_stack
+                            .borrow_mut()
+                            .insert(key.clone(), encoding);
+                        remember_on_disk(&on_disk_for_stack, &key, &tab.path, text.clone(), &bytes);
+                        text
+                    })
             };
 
-            // ── .editorconfig: read and apply for this tab ────────────────
-            {
-                let ec = read_editorconfig(&tab.path, &workspace_root);
-                if active_idx.get_untracked() == Some(i) {
-                    if let Some(size) = ec.indent_size {
-                        tab_size.set(size);
-                    }
-                    if let Some(eol) = ec.end_of_line {
-                        line_ending_out.set(eol);
-                    }
+            // ── Indentation: .editorconfig, else detected from the content ─
+            // Kept per tab so a detected indent never changes the global
+            // tab_size; the status bar shows (and changes) the active tab's.
+            let editorconfig = EditorConfig::for_file(&tab.path);
+            let tab_indent = create_rw_signal(
+                editorconfig.indent(&content, Indent::spaces(tab_size.get_untracked())),
+            );
+            // Files without a configured or detected indent follow tab_size.
+            create_effect(move |_| {
+                let size = tab_size.get();
+                if tab_indent.get_untracked().source == IndentSource::Default {
+                    tab_indent.update(|t| t.indent.size = size);
                 }
-            }
-
-            // ── Auto-detect indentation from first 2000 bytes ─────────────
-            {
-                let sample_len = content.len().min(2000);
-                let sample_end = content.floor_char_boundary(sample_len);
-                let mut tab_count = 0usize;
-                let mut space2 = 0usize;
-                let mut space4 = 0usize;
-                let mut space8 = 0usize;
-                for line in content[..sample_end].lines() {
-                    if line.starts_with('\t') {
-                        tab_count += 1;
-                    } else if line.starts_with("        ") {
-                        space8 += 1;
-                    } else if line.starts_with("    ") {
-                        space4 += 1;
-                    } else if line.starts_with("  ") {
-                        space2 += 1;
-                    }
+            });
+            create_effect(move |_| {
+                if active_idx.get() == Some(i) {
+                    indent_out.set(Some(tab_indent.get()));
                 }
-                let detected = if tab_count > space2 + space4 + space8 {
-                    4u32 // tabs → treat as 4
-                } else if space2 > space4 && space2 > space8 {
-                    2
-                } else if space8 > space4 {
-                    8
-                } else if space4 > 0 {
-                    4
-                } else {
-                    0 // no evidence — keep current
+            });
+            create_effect(move |_| {
+                let Some(chosen) = indent_out.get() else {
+                    return;
                 };
-                if detected > 0 {
-                    tab_size.set(detected);
+                if active_idx.get_untracked() == Some(i) && chosen != tab_indent.get_untracked() {
+                    tab_indent.set(chosen);
                 }
-            }
+            });
 
             let tab_ext = tab
                 .path
@@ -1395,9 +2244,16 @@ pub fn editor_panel(
                 .and_then(|e| e.to_str())
                 .unwrap_or("")
                 .to_string();
-
-            // `use_wrap` passed at call time so this closure doesn't capture `word_wrap`.
-            let make_base_styling = |fs: usize, use_wrap: bool| -> Rc<dyn Styling> {
+            let typing = TypingRules::for_extension(&tab_ext);
+
+            // Font settings are passed at call time so this closure doesn't capture signals.
+            let make_base_styling = |fs: usize,
+                                     use_wrap: bool,
+                                     family: &str,
+                                     ligatures: bool,
+                                     line_h: f32,
+                                     indent: Indent|
+             -> Rc<dyn Styling> {
                 let wrap = if use_wrap {
                     WrapMethod::EditorWidth
                 } else {
@@ -1407,12 +2263,9 @@ pub fn editor_panel(
                     SimpleStylingBuilder::default()
                         .wrap(wrap)
                         .font_size(fs)
-                        .font_family(vec![
-                            FamilyOwned::Name("JetBrains Mono".to_string()),
-                            FamilyOwned::Name("Fira Code".to_string()),
-                            FamilyOwned::Name("Cascadia Code".to_string()),
-                            FamilyOwned::Monospace,
-                        ])
+                        .line_height(line_h)
+                        .font_family(crate::fonts::font_stack(family, ligatures))
+                        .tab_width(indent.size as usize)
                         .build(),
                 )
             };
@@ -1445,11 +2298,19 @@ pub fn editor_panel(
                     let line_0 = goto_line.get().saturating_sub(1);
                     let max_line = rope.num_lines().saturating_sub(1);
                     let offset = rope.offset_of_line(line_0.min(max_line));
-                    cursor_sig.set(Cursor::new(
-                        CursorMode::Insert(Selection::caret(offset)),
-                        None,
-                        None,
-                    ));
+                    // A range selects its lines whole.
+                    let end_0 = goto_line_end.get_untracked().saturating_sub(1);
+                    let selection = if end_0 > line_0 {
+                        let end = if end_0 < max_line {
+                            rope.offset_of_line(end_0 + 1)
+                        } else {
+                            rope.len()
+                        };
+                        Selection::region(offset, end)
+                    } else {
+                        Selection::caret(offset)
+                    };
+                    cursor_sig.set(Cursor::new(CursorMode::Insert(selection), None, None));
                 });
             }
 
@@ -1478,11 +2339,13 @@ pub fn editor_panel(
 
             // ── Cursor position tracking → active_cursor signal ──────────
             // ── Word/symbol highlight under cursor ───────────────────────
-            // On every cursor move in the active tab, find the word under
-            // the cursor and all whole-word occurrences in the document.
-            // Stores byte-offset ranges in `word_hl`; the styling effect
-            // below picks them up and draws soft highlight boxes.
-            let word_hl: RwSignal<Vec<(usize, usize)>> = create_rw_signal(vec![]);
+            // On every cursor move in the active tab, ask the language
+            // server for the occurrences of the symbol under the cursor.
+            // Its answer — or, when it has no server that can tell, every
+            // whole-word match of the word — is stored as byte-offset ranges
+            // in `word_hl`; the styling effect below picks them up and draws
+            // soft highlight boxes, in another color where it is written.
+            let word_hl: RwSignal<Vec<(usize, usize, bool)>> = create_rw_signal(vec![]);
             // Tracks the 0-based line index of the cursor for current-line highlighting.
             let current_line_sig: RwSignal<usize> = create_rw_signal(0usize);
 
@@ -1491,9 +2354,18 @@ pub fn editor_panel(
             #[allow(clippy::type_complexity)]
             let fold_state: RwSignal<(Vec<(usize, usize)>, HashSet<usize>)> =
                 create_rw_signal((Vec::new(), HashSet::new()));
+            // Set once the folds saved for the file are back, after the
+            // first fold ranges arrive; until then there's nothing to save.
+            let folds_restored = create_rw_signal(false);
 
             // Bracket pairs for colorization: (open_byte, close_byte, depth)
             let bracket_pairs_sig: RwSignal<Vec<(usize, usize, usize)>> = create_rw_signal(vec![]);
+            // Guides between brackets on distant lines: (open_line, open_col, close_line, depth)
+            #[allow(clippy::type_complexity)]
+            let bracket_guides_sig: RwSignal<Vec<(usize, usize, usize, usize)>> =
+                create_rw_signal(vec![]);
+            // Bumped on every edit; fold ranges and bracket pairs follow it.
+            let revision: RwSignal<u64> = create_rw_signal(0u64);
             // Matching bracket for the bracket under cursor: (open_byte, close_byte)
             let matching_bracket_sig: RwSignal<Option<(usize, usize)>> = create_rw_signal(None);
 
@@ -1519,31 +2391,86 @@ pub fn editor_panel(
             }
             {
                 let doc_for_hl = doc.clone();
+                let hl_path = tab.path.clone();
+                let hl_tx = lsp_cmd.clone();
                 create_effect(move |_| {
                     if active_idx.get() != Some(i) {
                         return;
                     }
                     let offset = cursor_sig.get().offset();
-                    let rope = doc_for_hl.rope_text();
-                    let len = rope.len();
-                    if len == 0 {
+                    // Moving within an occurrence keeps the boxes until the
+                    // answer arrives; moving off them clears them at once.
+                    let on_occurrence = word_hl.with_untracked(|ranges| {
+                        ranges
+                            .iter()
+                            .any(|&(start, end, _)| start <= offset && offset <= end)
+                    });
+                    if !on_occurrence {
                         word_hl.set(vec![]);
+                    }
+                    let rope = doc_for_hl.rope_text();
+                    let line = rope.line_of_offset(offset);
+                    let line_start = rope.offset_of_line(line);
+                    let before = rope.slice_to_cow(line_start..offset);
+                    let _ = hl_tx.send(crate::lsp_bridge::LspCommand::RequestDocumentHighlight {
+                        path: hl_path.clone(),
+                        line: line as u32,
+                        col: utf16_column(&before, before.len()),
+                    });
+                });
+            }
+            {
+                let doc_for_hl = doc.clone();
+                let hl_path = tab.path.clone();
+                create_effect(move |_| {
+                    let Some(highlights) = document_highlights.get() else {
+                        return;
+                    };
+                    if active_idx.get_untracked() != Some(i) || highlights.path != hl_path {
                         return;
                     }
-                    // Avoid searching huge files (> 2 MB) on every keystroke.
-                    let ranges = if len < 2_000_000 {
-                        let text = rope.slice_to_cow(0..len).to_string();
-                        if let Some((_, _, word)) = word_at_offset(&text, offset) {
-                            if word.len() >= 2 {
-                                find_word_occurrences(&text, &word)
-                            } else {
-                                vec![]
+                    let offset = cursor_sig.get_untracked().offset();
+                    let rope = doc_for_hl.rope_text();
+                    let line = rope.line_of_offset(offset);
+                    let line_start = rope.offset_of_line(line);
+                    let before = rope.slice_to_cow(line_start..offset);
+                    // An answer for where the cursor was before it moved.
+                    if (highlights.line, highlights.col)
+                        != (line as u32, utf16_column(&before, before.len()))
+                    {
+                        return;
+                    }
+                    let len = rope.len();
+                    let ranges = match highlights.occurrences {
+                        Some(occurrences) => {
+                            let offset_of = |(line, col): (u32, u32)| {
+                                let line = line as usize;
+                                if line >= rope.num_lines() {
+                                    return len;
+                                }
+                                rope.offset_of_line(line)
+                                    + byte_column(&rope.line_content(line), col)
+                            };
+                            occurrences
+                                .iter()
+                                .map(|o| (offset_of(o.start), offset_of(o.end), o.write))
+                                .filter(|&(start, end, _)| start < end)
+                                .collect()
+                        }
+                        // Avoid searching huge files (> 2 MB) on every keystroke.
+                        None if len > 0 && len < 2_000_000 => {
+                            let text = rope.slice_to_cow(0..len).to_string();
+                            match word_at_offset(&text, offset) {
+                                Some((_, _, word)) if word.len() >= 2 => {
+                                    find_word_occurrences(&text, &word)
+                                        .into_iter()
+                                        .map(|(start, end)| (start, end, false))
+                                        .collect()
+                                }
+                                _ => vec![],
                             }
-                        } else {
-                            vec![]
                         }
-                    } else {
-                        vec![]
+                        None => vec![],
                     };
                     word_hl.set(ranges);
                 });
@@ -1622,49 +2549,82 @@ pub fn editor_panel(
                 });
             }
 
-            // ── Fold range detection (on load + after each save) ──────────
-            // Text is extracted on the UI thread (Rc<Document> is not Send);
-            // brace-matching runs in a background thread.
+            // ── Derived state: fold ranges, bracket pairs and guides ──────
+            // Worked out off the UI thread once edits pause for DERIVE_IDLE,
+            // for the revision current then. The cache keeps a revision from
+            // being worked out twice; results for a revision the tab has
+            // since left behind are dropped, as a newer pass is on its way.
             {
-                let doc_for_fold = doc.clone();
+                let doc_for_derive = doc.clone();
+                let cache = Arc::new(Mutex::new(DerivedCache::new(&tab_ext)));
+                let restore_path = tab.path.clone();
+                let latest = Arc::new(AtomicU64::new(0));
                 // Use sync_channel + create_signal_from_channel to avoid Scope leak.
-                let (fold_tx, fold_rx) = std::sync::mpsc::sync_channel::<Vec<(usize, usize)>>(1);
-                let fold_result_sig = create_signal_from_channel(fold_rx);
-                // Receive effect: merges brace-based and LSP ranges when result arrives.
+                let (derive_tx, derive_rx) = std::sync::mpsc::sync_channel::<Arc<Derived>>(1);
+                let derived_sig = create_signal_from_channel(derive_rx);
+                // Receive effect: merges brace-based and LSP fold ranges and
+                // hands the bracket pairs to colorization and matching.
                 create_effect(move |_| {
-                    if let Some(brace_ranges) = fold_result_sig.get() {
-                        let lsp_ranges: Vec<(usize, usize)> = lsp_folding_ranges
-                            .get_untracked()
-                            .into_iter()
-                            .map(|(s, e)| (s as usize, e as usize))
-                            .collect();
-                        let mut merged = brace_ranges;
-                        for lsp_r in &lsp_ranges {
-                            if !merged.iter().any(|b| b.0 == lsp_r.0) {
-                                merged.push(*lsp_r);
-                            }
+                    let Some(derived) = derived_sig.get() else {
+                        return;
+                    };
+                    if derived.revision != revision.get_untracked() {
+                        return;
+                    }
+                    let lsp_ranges: Vec<(usize, usize)> = lsp_folding_ranges
+                        .get_untracked()
+                        .into_iter()
+                        .map(|(s, e)| (s as usize, e as usize))
+                        .collect();
+                    let mut merged = derived.fold_ranges.clone();
+                    for lsp_r in &lsp_ranges {
+                        if !merged.iter().any(|b| b.0 == lsp_r.0) {
+                            merged.push(*lsp_r);
                         }
-                        merged.sort_by_key(|r| r.0);
-                        fold_state.update(|(r, _f)| *r = merged);
                     }
+                    merged.sort_by_key(|r| r.0);
+                    fold_state.update(|(r, _f)| *r = merged);
+                    if !folds_restored.get_untracked() {
+                        folds_restored.set(true);
+                        let saved = saved_folds.with_untracked(|f| f.get(&restore_path).to_vec());
+                        fold_state.update(|(ranges, folded)| {
+                            folded.extend(
+                                ranges
+                                    .iter()
+                                    .map(|r| r.0)
+                                    .filter(|start| saved.contains(&(*start as u32))),
+                            );
+                        });
+                    }
+                    bracket_pairs_sig.set(derived.bracket_pairs.clone());
+                    bracket_guides_sig.set(derived.bracket_guides.clone());
                 });
-                // Trigger effect: re-runs on every save, spawns background detection.
+                // Trigger effect: re-runs on every edit.
                 create_effect(move |_| {
-                    let _dirty = safe_get(dirty, false); // re-runs when file is saved
-                    let rope = doc_for_fold.rope_text();
-                    let len = rope.len();
-                    let text = if len == 0 || len > 500_000 {
-                        String::new()
-                    } else {
-                        rope.slice_to_cow(0..len).to_string()
-                    };
-                    let tx = fold_tx.clone();
-                    if text.is_empty() {
-                        let _ = tx.send(vec![]);
-                        return;
-                    }
+                    let rev = revision.get();
+                    latest.store(rev, Ordering::Relaxed);
+                    // Shares the document's rope; it becomes a string only if
+                    // no edit follows within DERIVE_IDLE.
+                    let rope = doc_for_derive.text();
+                    let (cache, latest, tx) = (cache.clone(), latest.clone(), derive_tx.clone());
                     std::thread::spawn(move || {
-                        let _ = tx.send(detect_fold_ranges(&text));
+                        if rev > 0 {
+                            std::thread::sleep(DERIVE_IDLE);
+                        }
+                        if latest.load(Ordering::Relaxed) != rev {
+                            return;
+                        }
+                        let text = || {
+                            if rope.len() > derived::MAX_FOLD_BYTES {
+                                String::new()
+                            } else {
+                                rope.to_string()
+                            }
+                        };
+                        let derived = cache.lock().map(|mut cache| cache.get(rev, text));
+                        if let Ok(derived) = derived {
+                            let _ = tx.send(derived);
+                        }
                     });
                 });
             }
@@ -1716,42 +2676,6 @@ pub fn editor_panel(
                 });
             }
 
-            // ── Bracket pair detection ────────────────────────────────────
-            // Runs compute_bracket_pairs on file content changes in a background
-            // thread. Updates bracket_pairs_sig, which drives both colorization
-            // and matching-bracket highlight via the styling effect.
-            {
-                let doc_for_bp = doc.clone();
-                // Use sync_channel + create_signal_from_channel to avoid Scope leak.
-                let (bp_tx, bp_rx) = std::sync::mpsc::sync_channel::<Vec<(usize, usize, usize)>>(1);
-                let bp_result_sig = create_signal_from_channel(bp_rx);
-                // Receive effect: applies bracket pairs when result arrives.
-                create_effect(move |_| {
-                    if let Some(pairs) = bp_result_sig.get() {
-                        bracket_pairs_sig.set(pairs);
-                    }
-                });
-                // Trigger effect: re-runs on dirty, spawns background detection.
-                create_effect(move |_| {
-                    let _dirty = safe_get(dirty, false); // re-runs on every save
-                    let rope = doc_for_bp.rope_text();
-                    let len = rope.len();
-                    let text = if len == 0 || len > 300_000 {
-                        String::new()
-                    } else {
-                        rope.slice_to_cow(0..len).to_string()
-                    };
-                    let tx = bp_tx.clone();
-                    if text.is_empty() {
-                        let _ = tx.send(vec![]);
-                        return;
-                    }
-                    std::thread::spawn(move || {
-                        let _ = tx.send(compute_bracket_pairs(&text));
-                    });
-                });
-            }
-
             // ── Matching bracket detection ────────────────────────────────
             // On every cursor move for the active tab, checks if the cursor
             // is adjacent to a bracket and highlights both it and its pair.
@@ -1768,96 +2692,91 @@ pub fn editor_panel(
                 });
             }
 
-            // ── Auto-close bracket insertion ─────────────────────────────
-            // When the user types an opening bracket, automatically insert the
-            // matching closing bracket and keep the cursor between the pair.
-            // Uses a suppress flag so our own insert doesn't re-trigger.
+            // ── Auto-close pairs ─────────────────────────────────────────
+            // Typing an opening bracket or quote inserts its closer and keeps
+            // the cursor between the pair; typing that closer steps over it and
+            // Backspace between an empty pair deletes both. `TypingRules`
+            // holds the per-language exceptions (strings, comments, lifetimes).
+            // Uses a suppress flag so our own edit doesn't re-trigger.
             {
                 let doc_for_ac = doc.clone();
                 // Suppress re-entry after our own edit.
                 let ac_suppress: RwSignal<bool> = create_rw_signal(false);
-                // Previous cursor offset for delta detection.
-                let ac_prev: RwSignal<usize> = create_rw_signal(0usize);
+                // Previous cursor offset, document length and the characters
+                // either side of the cursor, to tell typing and Backspace apart.
+                let ac_prev: RwSignal<(usize, usize, Option<char>, Option<char>)> =
+                    create_rw_signal((0, 0, None, None));
                 create_effect(move |_| {
                     let cur_pos = cursor_sig.get().offset();
-                    // Skip if this was triggered by our own bracket insert.
+                    let rope = doc_for_ac.rope_text();
+                    let len = rope.len();
+                    if cur_pos > len {
+                        return;
+                    }
+                    let line = rope.line_of_offset(cur_pos);
+                    let line_start = rope.offset_of_line(line);
+                    let line_end = rope.offset_of_line(line + 1).min(len);
+                    let line_text = rope.slice_to_cow(line_start..line_end);
+                    let (before, after) = split_at_cursor(&line_text, cur_pos - line_start);
+                    let (prev, prev_len, prev_before, prev_after) = ac_prev.get_untracked();
+                    ac_prev.set((cur_pos, len, before.chars().last(), after));
+                    // Skip if this was triggered by our own edit.
                     if ac_suppress.get_untracked() {
                         ac_suppress.set(false);
                         return;
                     }
-                    let prev = ac_prev.get_untracked();
-                    ac_prev.set(cur_pos);
-                    // Only act when cursor advanced by exactly 1 (character typed).
                     if active_idx.get() != Some(i) {
                         return;
                     }
-                    if cur_pos == 0 || cur_pos != prev + 1 {
-                        return;
-                    }
-                    let rope = doc_for_ac.rope_text();
-                    if cur_pos > rope.len() {
-                        return;
-                    }
-                    let prefix = rope.slice_to_cow(0..cur_pos);
-                    if let Some(open_ch) = prefix.chars().last() {
-                        let close = match open_ch {
-                            '(' => Some(')'),
-                            '[' => Some(']'),
-                            '{' => Some('}'),
-                            // Auto-close quotes only when the next char is whitespace/EOF
-                            // and the char before the quote is not a backslash (escape).
-                            '"' => {
-                                let prev_is_escape = cur_pos >= 2 && {
-                                    let s = rope.slice_to_cow((cur_pos - 2)..(cur_pos - 1));
-                                    s.starts_with('\\')
-                                };
-                                if prev_is_escape {
-                                    None
-                                } else {
-                                    Some('"')
-                                }
-                            }
-                            '\'' => {
-                                // Don't close in Rust lifetime positions: `'a` at word boundary
-                                let prev_is_alpha = cur_pos >= 2 && {
-                                    let s = rope.slice_to_cow((cur_pos - 2)..(cur_pos - 1));
-                                    s.chars()
-                                        .next()
-                                        .map(|c| c.is_alphanumeric() || c == '_')
-                                        .unwrap_or(false)
-                                };
-                                if prev_is_alpha {
-                                    None
-                                } else {
-                                    Some('\'')
-                                }
-                            }
-                            _ => None,
+                    // One character typed.
+                    if cur_pos == prev + 1 && len == prev_len + 1 {
+                        let Some(typed) = before.chars().last() else {
+                            return;
                         };
-                        if let Some(close_ch) = close {
-                            // Only auto-close when next char is whitespace or end-of-file.
-                            let next_is_ok = cur_pos >= rope.len() || {
-                                let nc = rope
-                                    .slice_to_cow(cur_pos..(cur_pos + 1).min(rope.len()))
-                                    .chars()
-                                    .next()
-                                    .unwrap_or('\0');
-                                nc.is_whitespace()
-                            };
-                            if next_is_ok {
-                                ac_suppress.set(true);
-                                doc_for_ac.edit_single(
-                                    Selection::caret(cur_pos),
-                                    &close_ch.to_string(),
-                                    EditType::InsertChars,
-                                );
-                                // Keep cursor between the pair (don't advance past closing bracket).
-                                cursor_sig.set(Cursor::new(
-                                    CursorMode::Insert(Selection::caret(cur_pos)),
-                                    None,
-                                    None,
-                                ));
-                            }
+                        let before = &before[..before.len() - typed.len_utf8()];
+                        if typing.types_over(typed, after) {
+                            ac_suppress.set(true);
+                            doc_for_ac.edit_single(
+                                Selection::region(cur_pos, cur_pos + typed.len_utf8()),
+                                "",
+                                EditType::Delete,
+                            );
+                            cursor_sig.set(Cursor::new(
+                                CursorMode::Insert(Selection::caret(cur_pos)),
+                                None,
+                                None,
+                            ));
+                        } else if let Some(close_ch) = typing.auto_close(typed, before, after) {
+                            ac_suppress.set(true);
+                            doc_for_ac.edit_single(
+                                Selection::caret(cur_pos),
+                                &close_ch.to_string(),
+                                EditType::InsertChars,
+                            );
+                            // Keep cursor between the pair (don't advance past closing bracket).
+                            cursor_sig.set(Cursor::new(
+                                CursorMode::Insert(Selection::caret(cur_pos)),
+                                None,
+                                None,
+                            ));
+                        }
+                    // One character deleted with Backspace.
+                    } else if cur_pos + 1 == prev && len + 1 == prev_len {
+                        let (Some(open_ch), Some(close_ch)) = (prev_before, after) else {
+                            return;
+                        };
+                        if prev_after == Some(close_ch) && typing.deletes_pair(open_ch, close_ch) {
+                            ac_suppress.set(true);
+                            doc_for_ac.edit_single(
+                                Selection::region(cur_pos, cur_pos + close_ch.len_utf8()),
+                                "",
+                                EditType::Delete,
+                            );
+                            cursor_sig.set(Cursor::new(
+                                CursorMode::Insert(Selection::caret(cur_pos)),
+                                None,
+                                None,
+                            ));
                         }
                     }
                 });
@@ -1866,7 +2785,8 @@ pub fn editor_panel(
             // ── Auto-surround (wrap selection with bracket / quote) ───────
             // When the user has a non-empty selection and types an opening
             // bracket or quote, the selection is WRAPPED rather than replaced.
-            // Works for ( [ { " and ' — skips if selection is > 50 KB.
+            // Works for ( [ { " ' and ` (plus * and _ in Markdown) — skips if
+            // selection is > 50 KB.
             {
                 let doc_for_surr = doc.clone();
                 let surr_suppress: RwSignal<bool> = create_rw_signal(false);
@@ -1899,14 +2819,7 @@ pub fn editor_panel(
                             let rope = doc_for_surr.rope_text();
                             if cur_pos <= rope.len() {
                                 let typed_ch = rope.slice_to_cow(sel_start..cur_pos).chars().next();
-                                let close_opt = typed_ch.and_then(|c| match c {
-                                    '(' => Some(')'),
-                                    '[' => Some(']'),
-                                    '{' => Some('}'),
-                                    '"' => Some('"'),
-                                    '\'' => Some('\''),
-                                    _ => None,
-                                });
+                                let close_opt = typed_ch.and_then(|c| typing.surround(c));
                                 if let Some(close_ch) = close_opt {
                                     surr_suppress.set(true);
                                     let insert_text = format!("{sel_text}{close_ch}");
@@ -1953,7 +2866,10 @@ pub fn editor_panel(
 
             // ── Smart indent on Enter ────────────────────────────────────
             // After pressing Enter, indents the new line to match the previous
-            // line's indentation (plus extra indent after `{`, `(`, `[`, `:`).
+            // line's indentation, one level deeper after an opener (`{`, `(`,
+            // `[`, a Python `:`, a Rust `=>` arm) or shallower after Python's
+            // `return`/`pass`. Enter between a pair moves the closer to its own
+            // line below the cursor.
             {
                 let doc_for_si = doc.clone();
                 let si_suppress: RwSignal<bool> = create_rw_signal(false);
@@ -2002,29 +2918,25 @@ pub fn editor_panel(
                     let pl_end = rope.offset_of_line(cur_line);
                     let pl_text = rope.slice_to_cow(pl_start..pl_end).to_string();
                     let pl_trim = pl_text.trim_end_matches(['\n', '\r']);
-                    let ws_len = pl_trim.len() - pl_trim.trim_start().len();
-                    let indent = pl_trim[..ws_len].to_string();
-                    let extra = if pl_trim.trim_end().ends_with('{')
-                        || pl_trim.trim_end().ends_with('(')
-                        || pl_trim.trim_end().ends_with('[')
-                        || pl_trim.trim_end().ends_with(':')
-                    {
-                        "    " // 4-space extra indent after block-opening tokens
-                    } else {
-                        ""
+                    let rest_end = rope.offset_of_line(cur_line + 1).min(len);
+                    let rest = rope.slice_to_cow(cur_pos..rest_end);
+                    let unit = tab_indent.get_untracked().indent.unit();
+                    let enter = typing.enter(pl_trim, &rest, &unit);
+                    let insert = match &enter.closing {
+                        Some(closing) => format!("{}\n{closing}", enter.indent),
+                        None => enter.indent.clone(),
                     };
-                    let full_indent = format!("{indent}{extra}");
-                    if full_indent.is_empty() {
+                    if insert.is_empty() {
                         return;
                     }
                     si_suppress.set(true);
                     doc_for_si.edit_single(
                         Selection::caret(cur_pos),
-                        &full_indent,
+                        &insert,
                         EditType::InsertChars,
                     );
                     cursor_sig.set(Cursor::new(
-                        CursorMode::Insert(Selection::caret(cur_pos + full_indent.len())),
+                        CursorMode::Insert(Selection::caret(cur_pos + enter.indent.len())),
                         None,
                         None,
                     ));
@@ -2077,9 +2989,12 @@ pub fn editor_panel(
                     if ws_len == 0 {
                         return;
                     } // already at column 0 — nothing to do
-                      // Remove one indent level: prefer 4 spaces, then 2, then 1 tab.
+                      // Remove one indent level: the file's unit, else 4 spaces, 2, 1 tab.
                     let ws_prefix = &line_text[..ws_len];
-                    let remove = if ws_prefix.ends_with("    ") {
+                    let unit = tab_indent.get_untracked().indent.unit();
+                    let remove = if ws_prefix.ends_with(unit.as_str()) {
+                        unit.len()
+                    } else if ws_prefix.ends_with("    ") {
                         4
                     } else if ws_prefix.ends_with("  ") {
                         2
@@ -3164,6 +4079,85 @@ pub fn editor_panel(
                         // ── Expand / Shrink selection ─────────────────
                         // These are triggered via nonces, handled in separate effects below.
                         VimMotion::ExpandSelection | VimMotion::ShrinkSelection => cur_offset,
+                        // ── Emacs kill ring ───────────────────────────
+                        // Kills and copies go to the yank ring and the system
+                        // clipboard; consecutive C-k kills build one entry.
+                        VimMotion::KillLine(append) => {
+                            let rope = doc_for_vim.rope_text();
+                            let len = rope.len();
+                            let line = rope.line_of_offset(cur_offset);
+                            let line_end = if line + 1 < rope.num_lines() {
+                                rope.offset_of_line(line + 1).saturating_sub(1)
+                            } else {
+                                len
+                            };
+                            // At the end of the line, kill the line break instead.
+                            let end = if cur_offset >= line_end {
+                                (line_end + 1).min(len)
+                            } else {
+                                line_end
+                            };
+                            if end > cur_offset {
+                                let killed = rope.slice_to_cow(cur_offset..end).to_string();
+                                doc_for_vim.edit_single(
+                                    Selection::region(cur_offset, end),
+                                    "",
+                                    EditType::Delete,
+                                );
+                                yank_ring.update(|ring| match ring.first_mut() {
+                                    Some(last) if append => last.push_str(&killed),
+                                    _ => {
+                                        ring.insert(0, killed);
+                                        ring.truncate(5);
+                                    }
+                                });
+                                if let (Some(text), Ok(mut cb)) =
+                                    (yank_ring.get_untracked().first(), arboard::Clipboard::new())
+                                {
+                                    let _ = cb.set_text(text.clone());
+                                }
+                            }
+                            cur_offset
+                        }
+                        VimMotion::Yank => {
+                            // The clipboard holds the last kill unless something
+                            // newer was copied elsewhere.
+                            let text = arboard::Clipboard::new()
+                                .ok()
+                                .and_then(|mut cb| cb.get_text().ok())
+                                .filter(|t| !t.is_empty())
+                                .or_else(|| yank_ring.get_untracked().first().cloned())
+                                .unwrap_or_default();
+                            if text.is_empty() {
+                                cur_offset
+                            } else {
+                                doc_for_vim.edit_single(
+                                    Selection::caret(cur_offset),
+                                    &text,
+                                    EditType::InsertChars,
+                                );
+                                cur_offset + text.len()
+                            }
+                        }
+                        VimMotion::CopySelection => {
+                            let region = match &cursor_sig.get_untracked().mode {
+                                CursorMode::Insert(sel) => sel.regions().first().copied(),
+                                _ => None,
+                            };
+                            if let Some(r) = region.filter(|r| r.start != r.end) {
+                                let rope = doc_for_vim.rope_text();
+                                let end = r.start.max(r.end).min(rope.len());
+                                let text = rope.slice_to_cow(r.start.min(r.end)..end).to_string();
+                                yank_ring.update(|ring| {
+                                    ring.insert(0, text.clone());
+                                    ring.truncate(5);
+                                });
+                                if let Ok(mut cb) = arboard::Clipboard::new() {
+                                    let _ = cb.set_text(text);
+                                }
+                            }
+                            cur_offset
+                        }
                     };
 
                     // Apply visual mode selection if active
@@ -3571,12 +4565,88 @@ pub fn editor_panel(
                 });
             }
 
+            // ── Find similar code ─────────────────────────────────────────
+            {
+                let doc_sim = doc.clone();
+                let tab_path_sim = tab.path.clone();
+                let last_sim = create_rw_signal(0u64);
+                create_effect(move |_| {
+                    let n = find_similar_nonce.get();
+                    if n == 0 || n == last_sim.get_untracked() {
+                        return;
+                    }
+                    if active_idx.get() != Some(i) {
+                        return;
+                    }
+                    last_sim.set(n);
+                    let cur = cursor_sig.get_untracked();
+                    let Some(r) = (match cur.mode {
+                        CursorMode::Insert(ref s) => s.regions().first().copied(),
+                        _ => None,
+                    }) else {
+                        return;
+                    };
+                    let (sel_start, sel_end) = (r.start.min(r.end), r.start.max(r.end));
+                    let rope = doc_sim.rope_text();
+                    let code = rope.slice_to_cow(sel_start..sel_end).to_string();
+                    if code.trim().is_empty() {
+                        return;
+                    }
+                    similar_code_out.set(Some(crate::app::SimilarCodeQuery {
+                        path: tab_path_sim.clone(),
+                        start_line: rope.line_of_offset(sel_start) as u32 + 1,
+                        end_line: rope.line_of_offset(sel_end) as u32 + 1,
+                        code,
+                    }));
+                });
+            }
+
+            // ── Extract tasks from the selection ──────────────────────────
+            {
+                let doc_tasks = doc.clone();
+                let tab_path_tasks = tab.path.clone();
+                let last_tasks = create_rw_signal(0u64);
+                create_effect(move |_| {
+                    let n = extract_tasks_nonce.get();
+                    if n == 0 || n == last_tasks.get_untracked() {
+                        return;
+                    }
+                    if active_idx.get() != Some(i) {
+                        return;
+                    }
+                    last_tasks.set(n);
+                    let cur = cursor_sig.get_untracked();
+                    let Some(r) = (match cur.mode {
+                        CursorMode::Insert(ref s) => s.regions().first().copied(),
+                        _ => None,
+                    }) else {
+                        return;
+                    };
+                    let (sel_start, sel_end) = (r.start.min(r.end), r.start.max(r.end));
+                    let rope = doc_tasks.rope_text();
+                    let text = rope.slice_to_cow(sel_start..sel_end).to_string();
+                    if text.trim().is_empty() {
+                        return;
+                    }
+                    let name = tab_path_tasks
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    extract_tasks_out.set(Some(format!(
+                        "{name}, lines {}-{}:\n{text}",
+                        rope.line_of_offset(sel_start) + 1,
+                        rope.line_of_offset(sel_end) + 1,
+                    )));
+                });
+            }
+
             // ── Save without formatting ───────────────────────────────────
             {
                 let doc_snf = doc.clone();
                 let tab_path_snf = tab.path.clone();
                 let tab_dirty_snf = tab.dirty;
                 let lsp_cmd_snf = lsp_cmd.clone();
+                let save_snf = save_to_disk_for_stack.clone();
                 let last_snf = create_rw_signal(0u64);
                 create_effect(move |_| {
                     let n = save_no_format_nonce.get();
@@ -3587,8 +4657,12 @@ pub fn editor_panel(
                         return;
                     }
                     last_snf.set(n);
-                    let content = doc_snf.text().to_string();
-                    if std::fs::write(&tab_path_snf, content).is_ok() {
+                    let content = apply_editorconfig_on_save(
+                        &doc_snf,
+                        &tab_path_snf,
+                        normalize_line_endings_on_save.get_untracked(),
+                    );
+                    if save_snf(&tab_path_snf, content, false) {
                         tab_dirty_snf.set(false);
                         let _ = lsp_cmd_snf.send(crate::lsp_bridge::LspCommand::SaveFile {
                             path: tab_path_snf.clone(),
@@ -3615,11 +4689,46 @@ pub fn editor_panel(
                             }
                         });
                     }
-                    if ua > 0 && ua != last_ua.get_untracked() {
-                        last_ua.set(ua);
-                        fold_state.update(|(_ranges, folded)| {
-                            folded.clear();
-                        });
+                    if ua > 0 && ua != last_ua.get_untracked() {
+                        last_ua.set(ua);
+                        fold_state.update(|(_ranges, folded)| {
+                            folded.clear();
+                        });
+                    }
+                });
+            }
+
+            // ── Fold level N ──────────────────────────────────────────────
+            // Folds the ranges nested N deep, leaving the others as they are.
+            create_effect(move |_| {
+                let Some(level) = fold_level.get() else {
+                    return;
+                };
+                if active_idx.get() != Some(i) {
+                    return;
+                }
+                fold_level.set(None);
+                fold_state.update(|(ranges, folded)| {
+                    folded.extend(folding::starts_at_level(ranges, level));
+                });
+            });
+
+            // ── Remember folds ────────────────────────────────────────────
+            // Keeps the file's folded ranges in the session, by start line.
+            {
+                let path = tab.path.clone();
+                create_effect(move |_| {
+                    let (ranges, folded) = fold_state.get();
+                    if !folds_restored.get_untracked() {
+                        return;
+                    }
+                    let starts = ranges
+                        .iter()
+                        .filter(|r| folded.contains(&r.0))
+                        .map(|r| r.0 as u32);
+                    let mut folds = saved_folds.get_untracked();
+                    if folds.set(&path, starts) {
+                        saved_folds.set(folds);
                     }
                 });
             }
@@ -3682,11 +4791,6 @@ pub fn editor_panel(
                         };
 
                         let suggestion = rt.block_on(async move {
-                            let client = match settings.build_llm_client() {
-                                Ok(c) => c,
-                                Err(_) => return String::new(),
-                            };
-
                             // Trim to reasonable context window sizes.
                             let pre = if prefix.len() > 1500 {
                                 let start = prefix.ceil_char_boundary(prefix.len() - 1500);
@@ -3701,6 +4805,23 @@ pub fn editor_panel(
                                 suffix
                             };
 
+                            // llama.cpp and vLLM take a raw FIM prompt: no
+                            // instructions to follow, nothing to strip.
+                            if let Some(fim) = settings.build_fim_client() {
+                                return match fim.complete(&pre, &suf).await {
+                                    Ok(text) if text.lines().count() <= 6 => {
+                                        text.trim_end().to_string()
+                                    }
+                                    _ => String::new(),
+                                };
+                            }
+
+                            let client = match settings.build_client_for_task(TaskType::Completion)
+                            {
+                                Ok(c) => c,
+                                Err(_) => return String::new(),
+                            };
+
                             let prompt = format!(
                                 "You are a code completion engine. \
                                  Complete the code at the <CURSOR> marker. \
@@ -3748,9 +4869,19 @@ pub fn editor_panel(
                 .unwrap_or(false);
 
             // Build initial syntect-based styling for this file's language
-            let base_styling = make_base_styling(initial_fs, word_wrap.get_untracked());
-            let mut syn_style =
-                SyntaxStyle::for_extension(if is_large_file { "" } else { &tab_ext }, base_styling);
+            let base_styling = make_base_styling(
+                initial_fs,
+                word_wrap.get_untracked(),
+                &font_family.get_untracked(),
+                font_ligatures.get_untracked(),
+                line_height.get_untracked(),
+                tab_indent.get_untracked().indent,
+            );
+            let mut syn_style = SyntaxStyle::for_extension(
+                if is_large_file { "" } else { &tab_ext },
+                &syntax_theme.get_untracked(),
+                base_styling,
+            );
             syn_style.set_doc(doc.clone());
 
             // ── Git gutter decorations ────────────────────────────────────
@@ -3779,64 +4910,80 @@ pub fn editor_panel(
                 });
             }
 
-            // ── Git blame data (per-line author/date) ────────────────────
-            // Fetched once on save; only the current cursor line's blame
-            // is shown inline (to avoid visual clutter).
-            let blame_data: RwSignal<Vec<(String, String)>> = create_rw_signal(vec![]);
+            // ── Complexity heat ───────────────────────────────────────────
+            // Measured from the file on disk on first mount and on every
+            // save, like the git gutter.
+            let heat: RwSignal<Vec<(usize, usize, Heat)>> = create_rw_signal(vec![]);
+            if !is_large_file {
+                let heat_path = tab.path.clone();
+                let (heat_tx, heat_rx) =
+                    std::sync::mpsc::sync_channel::<Vec<(usize, usize, Heat)>>(1);
+                let heat_sig = create_signal_from_channel(heat_rx);
+                create_effect(move |_| {
+                    if let Some(ranges) = heat_sig.get() {
+                        heat.set(ranges);
+                    }
+                });
+                create_effect(move |_| {
+                    let _dirty = safe_get(dirty, false); // tracked — re-runs on save
+                    let p = heat_path.clone();
+                    let tx = heat_tx.clone();
+                    std::thread::spawn(move || {
+                        let _ = tx.send(complexity_heat(&p));
+                    });
+                });
+            }
+
+            // ── Git blame (lazy, per line) ────────────────────────────────
+            // Loaded when the tab is shown with inline blame or the blame
+            // gutter on, and again after each save. `GitOps::blame` caches by
+            // modification time, so switching back to a tab doesn't re-run git.
+            let blame: RwSignal<Option<Arc<Vec<BlameLine>>>> = create_rw_signal(None);
             {
                 let blame_path = tab.path.clone();
-                let (blame_tx, blame_rx) =
-                    std::sync::mpsc::sync_channel::<Vec<(String, String)>>(1);
+                let (blame_tx, blame_rx) = std::sync::mpsc::sync_channel::<Arc<Vec<BlameLine>>>(1);
                 let blame_result_sig = create_signal_from_channel(blame_rx);
-                // Receive effect: applies blame entries when result arrives.
                 create_effect(move |_| {
-                    if let Some(data) = blame_result_sig.get() {
-                        blame_data.set(data);
+                    if let Some(lines) = blame_result_sig.get() {
+                        blame.set(Some(lines));
                     }
                 });
-                // Trigger effect: re-runs on save, spawns background git blame.
                 create_effect(move |_| {
-                    let _dirty = safe_get(dirty, false);
+                    let saved = !safe_get(dirty, false);
+                    let wanted = inline_blame.get() || blame_gutter.get();
+                    if !saved || !wanted || !is_active() || !blame_path.is_absolute() {
+                        return;
+                    }
+                    let Some(root) = blame_path.parent().and_then(GitOps::find_root) else {
+                        return;
+                    };
                     let p = blame_path.clone();
                     let tx = blame_tx.clone();
                     std::thread::spawn(move || {
-                        let dir = p.parent().unwrap_or(&p);
-                        let out = std::process::Command::new("git")
-                            .args(["blame", "--date=short", "--porcelain"])
-                            .arg(&p)
-                            .current_dir(dir)
-                            .output();
-                        let mut entries: Vec<(String, String)> = vec![];
-                        if let Ok(output) = out {
-                            if output.status.success() {
-                                let text = String::from_utf8_lossy(&output.stdout);
-                                let mut cur_author = String::new();
-                                let mut cur_date = String::new();
-                                for line in text.lines() {
-                                    if let Some(rest) = line.strip_prefix("author ") {
-                                        cur_author = rest.to_string();
-                                    } else if let Some(rest) = line.strip_prefix("author-time ") {
-                                        // porcelain gives unix timestamp; convert to date
-                                        if let Ok(ts) = rest.parse::<i64>() {
-                                            let secs_per_day = 86400;
-                                            let days = ts / secs_per_day;
-                                            let y = 1970 + (days / 365);
-                                            cur_date = format!("{y}");
-                                        }
-                                    } else if let Some(rest) = line.strip_prefix("committer-time ")
-                                    {
-                                        // Use committer date as fallback
-                                        let _ = rest;
-                                    } else if line.starts_with('\t') {
-                                        // Content line = one blame entry complete
-                                        entries.push((cur_author.clone(), cur_date.clone()));
-                                    }
-                                }
-                            }
+                        let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+                            .enable_all()
+                            .build()
+                        else {
+                            return;
+                        };
+                        // Untracked files have no blame; nothing to show.
+                        if let Ok(lines) = rt.block_on(GitOps::new(root).blame(&p)) {
+                            let _ = tx.send(lines);
                         }
-                        let _ = tx.send(entries);
                     });
                 });
+                // Status bar text for the current line.
+                create_effect(move |_| {
+                    if !is_active() {
+                        return;
+                    }
+                    let line = current_line_sig.get();
+                    let text = blame
+                        .get()
+                        .and_then(|b| b.get(line).map(blame_summary))
+                        .unwrap_or_default();
+                    active_blame.set(text);
+                });
             }
 
             // ── Reactive styling update: font-size + diagnostics + word highlights + folds ──
@@ -3847,77 +4994,84 @@ pub fn editor_panel(
                 let ext_for_style = tab_ext.clone();
                 let editor_for_style = editor_ref.clone();
                 let path_for_diag = tab.path.clone();
+                let print_margin = editorconfig.max_line_length;
+                // Only this file's lines, and only again when diagnostics
+                // change rather than on every restyle.
+                let my_diags = {
+                    let path = tab.path.clone();
+                    create_memo(move |_| {
+                        diagnostics.with(|all| {
+                            derived::diag_lines(
+                                all.iter()
+                                    .filter(|d| d.path == path)
+                                    .map(|d| (d.line, d.severity)),
+                            )
+                        })
+                    })
+                };
                 create_effect(move |_| {
                     let fs = font_size.get() as usize;
                     let use_wrap = word_wrap.get(); // tracked — triggers rebuild when toggled
-                    let all_diags = diagnostics.get();
+                    let family = font_family.get();
+                    let ligatures = font_ligatures.get();
+                    let line_h = line_height.get();
+                    let indent = tab_indent.get().indent;
                     let hl_ranges = word_hl.get();
                     let git_chgs = git_changes.get();
+                    let heat_fns = heat.get();
                     let cur_line = current_line_sig.get();
+                    let marks = bookmarks.get().lines_in(&path_for_diag);
                     let (fold_ranges, folded) = fold_state.get();
                     let bp_pairs = bracket_pairs_sig.get();
+                    let bp_guides = bracket_guides_sig.get();
                     let match_brkt = matching_bracket_sig.get();
                     let find_offs = find_match_offsets.get();
                     let find_q = find_query.get();
-                    let blame_entries = blame_data.get();
-                    let my_diags: Vec<(usize, DiagSeverity)> = all_diags
-                        .iter()
-                        .filter(|d| d.path == path_for_diag)
-                        .map(|d| (d.line.saturating_sub(1) as usize, d.severity))
-                        .collect();
-                    let new_base = make_base_styling(fs, use_wrap);
+                    let syn_theme = syntax_theme.get(); // tracked — re-highlights on theme switch
+                    let mut columns = rulers.get();
+                    columns.extend(print_margin);
+                    columns.sort_unstable();
+                    columns.dedup();
+                    let new_base =
+                        make_base_styling(fs, use_wrap, &family, ligatures, line_h, indent);
                     let mut new_style = SyntaxStyle::for_extension(
                         if is_large_file { "" } else { &ext_for_style },
+                        &syn_theme,
                         new_base,
                     );
                     new_style.set_doc(doc_for_style.clone());
-                    new_style.diag_lines = my_diags;
+                    new_style.indent_width = indent.size as usize;
+                    new_style.char_width_px = fs as f64 * 0.6;
+                    new_style.rulers = columns.into_iter().map(|c| c as usize).collect();
+                    new_style.diag_lines = my_diags.get();
                     new_style.highlight_ranges = hl_ranges;
                     new_style.git_lines = git_chgs;
                     let pal = &theme.get().palette;
+                    new_style.highlight_read_color = pal.word_highlight_read;
+                    new_style.highlight_write_color = pal.word_highlight_write;
                     new_style.git_color_added = pal.git_added.with_alpha(0.86);
                     new_style.git_color_modified = pal.git_modified.with_alpha(0.86);
                     new_style.git_color_deleted = pal.git_deleted.with_alpha(0.86);
+                    new_style.heat_ranges = heat_fns
+                        .into_iter()
+                        .map(|(start, end, h)| {
+                            let color = if h == Heat::Hot {
+                                pal.error
+                            } else {
+                                pal.warning
+                            };
+                            (start, end, color.with_alpha(0.55))
+                        })
+                        .collect();
                     new_style.current_line = cur_line;
+                    new_style.bookmark_lines = marks.into_iter().map(|l| l as usize).collect();
+                    new_style.bookmark_color = pal.accent.with_alpha(0.14);
+                    new_style.ruler_color = pal.border.with_alpha(0.7);
                     new_style.foldable_ranges = fold_ranges;
                     new_style.folded_starts = folded;
-                    new_style.bracket_pairs = bp_pairs.clone();
-                    // Build bracket pair guides from pairs that span multiple lines
-                    if let Some(ref rope_doc) = new_style.doc {
-                        let rope = rope_doc.rope_text();
-                        let guides: Vec<(usize, usize, usize, usize)> = bp_pairs
-                            .iter()
-                            .filter_map(|&(open_b, close_b, depth)| {
-                                let open_line = rope.line_of_offset(open_b);
-                                let close_line = rope.line_of_offset(close_b);
-                                if close_line <= open_line + 1 {
-                                    return None; // same or adjacent line, no guide needed
-                                }
-                                let line_start = rope.offset_of_line(open_line);
-                                let open_col = open_b.saturating_sub(line_start);
-                                Some((open_line, open_col, close_line, depth))
-                            })
-                            .collect();
-                        new_style.bracket_pair_guides = guides;
-                    }
+                    new_style.bracket_pairs = bp_pairs;
+                    new_style.bracket_pair_guides = bp_guides;
                     new_style.matching_bracket = match_brkt;
-                    // Inline blame for the current cursor line
-                    new_style.blame_line = if cur_line < blame_entries.len() {
-                        let (ref author, ref date) = blame_entries[cur_line];
-                        if author.is_empty() || author == "Not Committed Yet" {
-                            None
-                        } else {
-                            Some((cur_line, format!("{author} • {date}")))
-                        }
-                    } else {
-                        None
-                    };
-                    // Push blame to IdeState for status bar display
-                    if let Some((_, ref text)) = new_style.blame_line {
-                        active_blame.set(text.clone());
-                    } else {
-                        active_blame.set(String::new());
-                    }
                     // Convert start offsets \u2192 (start, end) ranges using query length.
                     new_style.find_match_ranges = if find_q.is_empty() {
                         vec![]
@@ -3930,8 +5084,470 @@ pub fn editor_panel(
 
             // Store in registry for save + find
             docs_for_stack.borrow_mut().insert(key, doc);
+            format_rev.update(|n| *n += 1);
+
+            // ── Blame views: end-of-line annotation and gutter ────────────
+            // Both are placed per logical line from the editor's viewport, so
+            // they are hidden while word wrap is on.
+            let viewport = editor_ref.viewport;
+            let line_px = move || (font_size.get() as f32 * line_height.get()).round() as f64;
+            let open_commit = {
+                let root = tab.path.parent().and_then(GitOps::find_root);
+                let tx = commit_tab_tx.clone();
+                move |commit: String| {
+                    let Some(root) = root.clone() else {
+                        return;
+                    };
+                    let tx = tx.clone();
+                    std::thread::spawn(move || {
+                        if let Some(path) = crate::panels::git::write_commit_diff(&root, &commit) {
+                            let _ = tx.send(path);
+                        }
+                    });
+                }
+            };
+            let current_blame = move || {
+                blame
+                    .get()
+                    .and_then(|b| b.get(current_line_sig.get()).cloned())
+            };
+            let inline_view = container(
+                label(move || {
+                    current_blame()
+                        .map(|b| blame_summary(&b))
+                        .unwrap_or_default()
+                })
+                .style(move |s| {
+                    s.font_size((font_size.get() as f32 * 0.9).max(9.0))
+                        .color(theme.get().palette.text_muted)
+                }),
+            )
+            .on_click_stop({
+                let open_commit = open_commit.clone();
+                move |_| {
+                    if let Some(b) = current_blame().filter(|b| !b.is_uncommitted()) {
+                        open_commit(b.commit);
+                    }
+                }
+            })
+            .style(move |s| {
+                let p = theme.get().palette;
+                let vp = viewport.get();
+                let lh = line_px();
+                let y = current_line_sig.get() as f64 * lh - vp.y0;
+                let hidden = !inline_blame.get()
+                    || word_wrap.get()
+                    || current_blame().is_none()
+                    || y < 0.0
+                    || y + lh > vp.height();
+                s.absolute()
+                    .inset_top(y)
+                    .inset_right(18.0)
+                    .height(lh)
+                    .items_center()
+                    .padding_horiz(8.0)
+                    .background(p.bg_base.with_alpha(0.85))
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(hidden, |s| s.display(floem::style::Display::None))
+            });
+            // ── Code lenses: a row of actions above each lensed line ──────
+            // The row sits on the line before, usually blank, a comment or an
+            // attribute, and steps aside while the cursor is on that line.
+            let lens_path = tab.path.clone();
+            let lens_rows = move || {
+                if !code_lens_visible.get() || word_wrap.get() {
+                    return Vec::new();
+                }
+                let mut rows: Vec<(u32, Vec<crate::lsp_bridge::CodeLensEntry>)> = Vec::new();
+                for entry in code_lens_sig.get() {
+                    if entry.path != lens_path {
+                        continue;
+                    }
+                    match rows.last_mut() {
+                        Some((line, entries)) if *line == entry.line => entries.push(entry),
+                        _ => rows.push((entry.line, vec![entry])),
+                    }
+                }
+                rows
+            };
+            let lens_view = dyn_stack(
+                lens_rows,
+                |(line, entries)| {
+                    let labels: Vec<&str> = entries.iter().map(|e| e.label.as_str()).collect();
+                    (*line, labels.join("|"))
+                },
+                move |(line, entries)| {
+                    let row = line.saturating_sub(2) as usize;
+                    let items: Vec<_> = entries.into_iter().enumerate().collect();
+                    dyn_stack(
+                        move || items.clone(),
+                        |(i, entry)| (*i, entry.label.clone()),
+                        move |(i, entry)| {
+                            let text = if i == 0 {
+                                entry.label.clone()
+                            } else {
+                                format!("|  {}", entry.label)
+                            };
+                            let clickable = entry.action != phazeai_core::lsp::LensAction::None;
+                            label(move || text.clone())
+                                .on_click_stop(move |_| {
+                                    if clickable {
+                                        code_lens_clicked.set(Some(entry.clone()));
+                                    }
+                                })
+                                .style(move |s| {
+                                    let p = theme.get().palette;
+                                    s.font_size((font_size.get() as f32 * 0.85).max(9.0))
+                                        .color(p.text_muted)
+                                        .apply_if(clickable, |s| {
+                                            s.cursor(floem::style::CursorStyle::Pointer)
+                                                .hover(|s| s.color(p.accent))
+                                        })
+                                })
+                        },
+                    )
+                    .style(move |s| {
+                        let p = theme.get().palette;
+                        let vp = viewport.get();
+                        let lh = line_px();
+                        let y = row as f64 * lh - vp.y0;
+                        let hidden =
+                            current_line_sig.get() == row || y < 0.0 || y + lh > vp.height();
+                        s.absolute()
+                            .inset_top(y)
+                            .inset_right(18.0)
+                            .height(lh)
+                            .flex_row()
+                            .items_center()
+                            .gap(8.0)
+                            .padding_horiz(8.0)
+                            .background(p.bg_base.with_alpha(0.85))
+                            .apply_if(hidden, |s| s.display(floem::style::Display::None))
+                    })
+                },
+            )
+            .style(|s| s.absolute().inset_top(0.0).width_full().height(0.0));
+            // ── Peek: a definition or references, inline below a line ──────
+            // A read-only excerpt with syntax colors, scrolled to its target
+            // line; for references, the list beside it picks which is shown.
+            let peek_path = tab.path.clone();
+            let peek_here = create_memo(move |_| peek.get().filter(|p| p.path == peek_path));
+            let peek_selected: RwSignal<usize> = create_rw_signal(0);
+            create_effect(move |_| {
+                let _ = peek_here.get();
+                peek_selected.set(0);
+            });
+            let peek_location = create_memo(move |_| match peek_here.get()?.content {
+                crate::app::PeekContent::Definition(location) => Some(location),
+                crate::app::PeekContent::References(refs) => {
+                    let r = refs.get(peek_selected.get())?;
+                    crate::lsp_bridge::PeekLocation::read(&r.path, r.line.saturating_sub(1))
+                }
+                _ => None,
+            });
+            let peek_title = move || {
+                use crate::app::PeekContent;
+                match peek_here.get().map(|p| p.content) {
+                    Some(PeekContent::FindingDefinition) => "Finding definition…".to_string(),
+                    Some(PeekContent::NoDefinition) => "No definition found".to_string(),
+                    Some(PeekContent::FindingReferences) => "Finding references…".to_string(),
+                    Some(PeekContent::References(refs)) if refs.is_empty() => {
+                        "No references found".to_string()
+                    }
+                    Some(PeekContent::References(refs)) => format!(
+                        "{} reference{}",
+                        refs.len(),
+                        if refs.len() == 1 { "" } else { "s" }
+                    ),
+                    Some(PeekContent::Definition(location)) => {
+                        format!("{}:{}", location.path.display(), location.line + 1)
+                    }
+                    None => String::new(),
+                }
+            };
+            let peek_header = stack((
+                label(peek_title).style(move |s| {
+                    s.font_size(12.0)
+                        .color(theme.get().palette.text_primary)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                }),
+                container(peek_button("Open", theme, move || {
+                    if let Some(location) = peek_location.get_untracked() {
+                        open_file.set(Some(location.path));
+                        ext_goto_line.set(location.line + 1);
+                        peek.set(None);
+                    }
+                }))
+                .style(move |s| {
+                    s.apply_if(peek_location.get().is_none(), |s| {
+                        s.display(floem::style::Display::None)
+                    })
+                }),
+                container(peek_button("Open to the Side", theme, move || {
+                    if let Some(location) = peek_location.get_untracked() {
+                        peek_open_to_side.set(Some((location.path, location.line + 1)));
+                    }
+                }))
+                .style(move |s| {
+                    s.apply_if(peek_location.get().is_none(), |s| {
+                        s.display(floem::style::Display::None)
+                    })
+                }),
+                peek_button("✕", theme, move || peek.set(None)),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.flex_row()
+                    .items_center()
+                    .gap(4.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(4.0)
+                    .border_bottom(1.0)
+                    .border_color(p.glass_border)
+            });
+            let peek_code = scroll(
+                dyn_stack(
+                    move || {
+                        let Some(location) = peek_location.get() else {
+                            return Vec::new();
+                        };
+                        let ext = location
+                            .path
+                            .extension()
+                            .and_then(|e| e.to_str())
+                            .unwrap_or("");
+                        let spans = highlight_excerpt(ext, &syntax_theme.get(), &location.lines);
+                        location
+                            .lines
+                            .into_iter()
+                            .zip(spans)
+                            .enumerate()
+                            .map(|(i, (text, spans))| {
+                                let line = location.first_line + i as u32;
+                                (line, text, spans, line == location.line)
+                            })
+                            .collect::<Vec<_>>()
+                    },
+                    |(line, text, _, target)| (*line, text.clone(), *target),
+                    move |(line, text, spans, target)| {
+                        let row = stack((
+                            label(move || format!("{:>5}", line + 1)).style(move |s| {
+                                s.font_family(crate::fonts::font_stack_css(
+                                    &font_family.get(),
+                                    font_ligatures.get(),
+                                ))
+                                .font_size(font_size.get() as f32 * 0.9)
+                                .color(theme.get().palette.text_muted)
+                                .margin_right(12.0)
+                            }),
+                            rich_text(move || {
+                                let p = theme.get().palette;
+                                let family = crate::fonts::font_stack(
+                                    &font_family.get(),
+                                    font_ligatures.get(),
+                                );
+                                let base = Attrs::new()
+                                    .family(&family)
+                                    .font_size(font_size.get() as f32 * 0.9)
+                                    .color(p.text_primary);
+                                let mut attrs = AttrsList::new(base.clone());
+                                for (range, color) in &spans {
+                                    attrs.add_span(range.clone(), base.clone().color(*color));
+                                }
+                                let mut layout = TextLayout::new();
+                                layout.set_text(&text, attrs, None);
+                                layout
+                            }),
+                        ))
+                        .style(move |s| {
+                            let p = theme.get().palette;
+                            s.flex_row()
+                                .items_center()
+                                .padding_horiz(8.0)
+                                .min_width_full()
+                                .apply_if(target, |s| s.background(p.accent_dim))
+                        });
+                        if target {
+                            let row_id = row.id();
+                            create_effect(move |_| row_id.scroll_to(None));
+                        }
+                        row
+                    },
+                )
+                .style(|s| s.flex_col().min_width_full()),
+            )
+            .style(|s| s.flex_grow(1.0).min_width(0.0).height_full());
+            let peek_refs = scroll(
+                dyn_stack(
+                    move || match peek_here.get().map(|p| p.content) {
+                        Some(crate::app::PeekContent::References(refs)) => {
+                            refs.into_iter().enumerate().collect::<Vec<_>>()
+                        }
+                        _ => Vec::new(),
+                    },
+                    |(i, r)| (*i, r.path.clone(), r.line),
+                    move |(i, r)| {
+                        let name = r
+                            .path
+                            .file_name()
+                            .map(|n| n.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        let text = format!("{name}:{}", r.line);
+                        label(move || text.clone())
+                            .on_click_stop(move |_| peek_selected.set(i))
+                            .style(move |s| {
+                                let p = theme.get().palette;
+                                let selected = peek_selected.get() == i;
+                                s.font_size(11.5)
+                                    .width_full()
+                                    .padding_horiz(10.0)
+                                    .padding_vert(3.0)
+                                    .color(if selected {
+                                        p.text_primary
+                                    } else {
+                                        p.text_secondary
+                                    })
+                                    .cursor(floem::style::CursorStyle::Pointer)
+                                    .apply_if(selected, |s| s.background(p.accent_dim))
+                                    .hover(|s| s.background(p.bg_elevated))
+                            })
+                    },
+                )
+                .style(|s| s.flex_col().width_full()),
+            )
+            .style(move |s| {
+                let p = theme.get().palette;
+                let has_refs = matches!(
+                    peek_here.get().map(|p| p.content),
+                    Some(crate::app::PeekContent::References(refs)) if !refs.is_empty()
+                );
+                s.width(240.0)
+                    .height_full()
+                    .flex_shrink(0.0)
+                    .border_left(1.0)
+                    .border_color(p.glass_border)
+                    .background(p.bg_surface)
+                    .apply_if(!has_refs, |s| s.display(floem::style::Display::None))
+            });
+            let peek_view = stack((
+                peek_header,
+                stack((peek_code, peek_refs))
+                    .style(|s| s.flex_row().flex_grow(1.0).min_height(0.0).width_full()),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                let vp = viewport.get();
+                let lh = line_px();
+                let height = (lh * 14.0).min(vp.height() * 0.8).max(120.0);
+                let below = peek_here
+                    .get()
+                    .map_or(0.0, |peek| (peek.line + 1) as f64 * lh - vp.y0);
+                let y = below.min(vp.height() - height).max(0.0);
+                s.absolute()
+                    .inset_top(y)
+                    .inset_left(8.0)
+                    .inset_right(18.0)
+                    .height(height)
+                    .flex_col()
+                    .z_index(ui_const::Z_PEEK_DEF)
+                    .background(p.bg_panel)
+                    .border(1.0)
+                    .border_top(2.0)
+                    .border_color(p.accent)
+                    .border_radius(4.0)
+                    .apply_if(peek_here.get().is_none(), |s| {
+                        s.display(floem::style::Display::None)
+                    })
+            });
+            let gutter_view = canvas(move |cx, size| {
+                let p = theme.get().palette;
+                cx.fill(&floem::kurbo::Rect::ZERO.with_size(size), p.bg_panel, 0.0);
+                cx.fill(
+                    &floem::kurbo::Rect::new(size.width - 1.0, 0.0, size.width, size.height),
+                    p.border,
+                    0.0,
+                );
+                let Some(lines) = blame.get() else {
+                    return;
+                };
+                let lh = line_px();
+                let vp = viewport.get();
+                let fs = (font_size.get() as f32 * 0.85).max(9.0);
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or_default();
+                let max_chars = (size.width / (fs as f64 * 0.6)) as usize;
+                let first = (vp.y0 / lh).floor().max(0.0) as usize;
+                let last = ((vp.y1 / lh).ceil() as usize).min(lines.len());
+                for line in first..last {
+                    let entry = &lines[line];
+                    let y = line as f64 * lh - vp.y0;
+                    // Only the first line of each run of one commit is labelled.
+                    if line > first && lines[line - 1].commit == entry.commit {
+                        continue;
+                    }
+                    if line > first {
+                        cx.fill(
+                            &floem::kurbo::Rect::new(0.0, y, size.width - 1.0, y + 1.0),
+                            p.border.with_alpha(0.5),
+                            0.0,
+                        );
+                    }
+                    let text = if entry.is_uncommitted() {
+                        "Not committed yet".to_string()
+                    } else {
+                        format!(
+                            "{}  {}",
+                            relative_time(entry.author_time, now),
+                            entry.author
+                        )
+                    };
+                    let text = if text.chars().count() > max_chars {
+                        let cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+                        format!("{cut}…")
+                    } else {
+                        text
+                    };
+                    let attrs = Attrs::new().font_size(fs).color(if entry.is_uncommitted() {
+                        p.git_modified
+                    } else {
+                        p.text_muted
+                    });
+                    let mut layout = TextLayout::new();
+                    layout.set_text(&text, AttrsList::new(attrs), None);
+                    let text_y = y + (lh - fs as f64 * 1.3).max(0.0) / 2.0;
+                    cx.draw_text(&layout, Point::new(8.0, text_y));
+                }
+            })
+            .on_event_stop(EventListener::PointerDown, {
+                let open_commit = open_commit.clone();
+                move |e| {
+                    if let Event::PointerDown(pe) = e {
+                        let y = pe.pos.y + viewport.get_untracked().y0;
+                        let line = (y / line_px()).max(0.0) as usize;
+                        let commit = blame.get_untracked().and_then(|b| {
+                            b.get(line)
+                                .filter(|b| !b.is_uncommitted())
+                                .map(|b| b.commit.clone())
+                        });
+                        if let Some(commit) = commit {
+                            open_commit(commit);
+                        }
+                    }
+                }
+            })
+            .style(move |s| {
+                let hidden = !blame_gutter.get() || word_wrap.get() || blame.get().is_none();
+                s.width(BLAME_GUTTER_WIDTH)
+                    .height_full()
+                    .flex_shrink(0.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(hidden, |s| s.display(floem::style::Display::None))
+            });
 
-            raw_editor
+            let editor_view = raw_editor
                 .styling(syn_style)
                 .editor_style(move |style| {
                     let t = theme.get();
@@ -3947,16 +5563,21 @@ pub fn editor_panel(
                 .update({
                     let as_gen = Arc::clone(&auto_save_gen);
                     let as_tx = auto_save_tx.clone();
-                    move |_| {
+                    move |update| {
                         dirty.set(true);
+                        revision.update(|r| *r += 1);
                         // Notify LSP server of content change (textDocument/didChange).
                         let text = doc_for_lsp.text().to_string();
+                        let edits = crate::lsp_bridge::applied_edits(
+                            update.deltas().map(|d| (d.old_text, d.delta)),
+                        );
                         let ver = lsp_ver.get();
                         lsp_ver.update(|v| *v += 1);
                         let _ = lsp_tx.send(crate::lsp_bridge::LspCommand::ChangeFile {
                             path: lsp_path.clone(),
                             text,
                             version: ver,
+                            edits,
                         });
                         // Auto-save: debounce 1.5 s — each edit cancels the previous timer.
                         if auto_save.get_untracked() {
@@ -3972,10 +5593,17 @@ pub fn editor_panel(
                         }
                     }
                 })
-                .style(move |s| {
-                    s.size_full()
-                        .apply_if(!is_active(), |s| s.display(floem::style::Display::None))
-                })
+                .style(|s| s.size_full());
+
+            stack((
+                gutter_view,
+                stack((editor_view, inline_view, lens_view, peek_view))
+                    .style(|s| s.flex_grow(1.0).min_width(0.0).height_full()),
+            ))
+            .style(move |s| {
+                s.size_full()
+                    .apply_if(!is_active(), |s| s.display(floem::style::Display::None))
+            })
         },
     )
     .style(|s| s.flex_grow(1.0).min_height(0.0).min_width(0.0).width_full());
@@ -4579,48 +6207,6 @@ pub fn editor_panel(
         })
     };
 
-    // ── Code lens bar (shown above editor when there are entries) ────────────
-    let code_lens_bar = {
-        let cl_theme = theme;
-        dyn_stack(
-            move || {
-                if !code_lens_visible.get() {
-                    return vec![];
-                }
-                code_lens_sig.get()
-            },
-            |entry| entry.line,
-            move |entry| {
-                let t = cl_theme.get();
-                let _p = &t.palette;
-                label(move || format!("● {} ─ line {}", entry.label, entry.line + 1))
-                    .style(move |s| {
-                        let t2 = cl_theme.get();
-                        s.padding_horiz(10.0)
-                            .padding_vert(2.0)
-                            .font_size(11.0)
-                            .color(t2.palette.accent)
-                            .cursor(floem::style::CursorStyle::Pointer)
-                            .hover(|s| s.color(t2.palette.text_primary))
-                    })
-                    .on_click_stop(move |_| {
-                        // Jump to the line containing this lens entry
-                        ext_goto_line.set(entry.line + 1);
-                    })
-            },
-        )
-        .style(move |s| {
-            let visible = code_lens_visible.get() && !code_lens_sig.get().is_empty();
-            let p = theme.get().palette;
-            s.flex_row()
-                .flex_wrap(floem::style::FlexWrap::Wrap)
-                .width_full()
-                .background(p.overlay_bg_light.with_alpha(40.0 / 255.0))
-                .border_bottom(1.0)
-                .apply_if(!visible, |s| s.display(floem::style::Display::None))
-        })
-    };
-
     // ── Inlay hints bar — shows type annotations for the current cursor line ──
     let inlay_bar = {
         let ih_theme = theme;
@@ -4673,9 +6259,9 @@ pub fn editor_panel(
         tab_bar,
         breadcrumbs,
         sticky_bar,
-        code_lens_bar,
         inlay_bar,
         find_bar,
+        disk_bar,
         editor_row,
         ghost_strip,
         goto_overlay,
@@ -5057,106 +6643,54 @@ fn disambiguate_tab_names(list: &mut [TabState]) {
     }
 }
 
-// ── .editorconfig support ─────────────────────────────────────────────────────
-
-#[derive(Default)]
-pub struct EditorConfigSettings {
-    pub indent_size: Option<u32>,
-    pub use_tabs: Option<bool>,
-    pub end_of_line: Option<&'static str>,
-}
-
-/// Walk up from `file_path`'s parent toward `workspace_root`, reading `.editorconfig`
-/// files (innermost wins for each key).  Parses `[*]` and extension-specific sections.
-pub fn read_editorconfig(
-    file_path: &std::path::Path,
-    workspace_root: &std::path::Path,
-) -> EditorConfigSettings {
-    let ext = file_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_string();
-
-    let mut indent_size: Option<u32> = None;
-    let mut use_tabs: Option<bool> = None;
-    let mut end_of_line: Option<&'static str> = None;
-
-    // Collect directories from file's parent up to workspace_root (inclusive).
-    let mut dirs: Vec<std::path::PathBuf> = Vec::new();
-    let mut cur = file_path.parent().map(|p| p.to_path_buf());
-    while let Some(dir) = cur {
-        dirs.push(dir.clone());
-        if dir == workspace_root {
-            break;
-        }
-        // Stop at filesystem root
-        let parent = dir.parent().map(|p| p.to_path_buf());
-        if parent.as_deref() == Some(&dir) {
-            break;
-        }
-        cur = parent;
+/// Split a line at byte column `col`: the text before the cursor and the
+/// character after it, if any (line breaks don't count).
+fn split_at_cursor(line: &str, col: usize) -> (&str, Option<char>) {
+    if !line.is_char_boundary(col) {
+        return ("", None);
     }
+    let (before, rest) = line.split_at(col);
+    (
+        before,
+        rest.chars().next().filter(|c| *c != '\n' && *c != '\r'),
+    )
+}
 
-    // Process from outermost (workspace_root) to innermost (file's dir) so inner wins.
-    dirs.reverse();
-
-    for dir in &dirs {
-        let ec_path = dir.join(".editorconfig");
-        let Ok(content) = std::fs::read_to_string(&ec_path) else {
-            continue;
-        };
+// ── .editorconfig support ─────────────────────────────────────────────────────
 
-        let mut in_matching_section = false;
-        for line in content.lines() {
-            let line = line.trim();
-            if line.starts_with('#') || line.starts_with(';') || line.is_empty() {
-                continue;
-            }
-            if line.starts_with('[') && line.ends_with(']') {
-                let glob = &line[1..line.len() - 1];
-                // Match [*], [*.ext], [*.{ext1,ext2}], or [**]
-                in_matching_section = glob == "*"
-                    || glob == "**"
-                    || glob == format!("*.{ext}")
-                    || glob.starts_with("*.{")
-                        && glob.ends_with('}')
-                        && glob[3..glob.len() - 1].split(',').any(|e| e.trim() == ext);
-                continue;
-            }
-            if !in_matching_section {
-                continue;
-            }
-            // Parse key = value
-            if let Some(eq) = line.find('=') {
-                let key = line[..eq].trim().to_lowercase();
-                let val = line[eq + 1..].trim().to_lowercase();
-                match key.as_str() {
-                    "indent_style" => {
-                        use_tabs = Some(val == "tab");
-                    }
-                    "indent_size" | "tab_width" => {
-                        if let Ok(n) = val.parse::<u32>() {
-                            indent_size = Some(n);
-                        }
-                    }
-                    "end_of_line" => {
-                        end_of_line = match val.as_str() {
-                            "crlf" => Some("CRLF"),
-                            "lf" => Some("LF"),
-                            "cr" => Some("LF"), // treat CR as LF for display
-                            _ => None,
-                        };
-                    }
-                    _ => {}
-                }
-            }
+/// Trim trailing whitespace and add a final newline where the file's
+/// `.editorconfig` asks for them, and with `normalize_eol` give mixed line
+/// endings its `end_of_line` (else the most common one). The buffer is
+/// edited too, so it matches what's written; returns the text to save.
+fn apply_editorconfig_on_save(
+    doc: &Rc<dyn Document>,
+    path: &std::path::Path,
+    normalize_eol: bool,
+) -> String {
+    let editorconfig = EditorConfig::for_file(path);
+    if normalize_eol {
+        let text = doc.text().to_string();
+        if let LineEndings::Mixed { most } = LineEndings::detect(&text) {
+            let ending = editorconfig.end_of_line.unwrap_or(most);
+            replace_text(doc, &convert_line_endings(&text, ending));
         }
     }
-
-    EditorConfigSettings {
-        indent_size,
-        use_tabs,
-        end_of_line,
+    let text = doc.text().to_string();
+    let fixes = editorconfig.whitespace_fixes(&text);
+    if fixes.is_empty() {
+        return text;
+    }
+    for (range, replacement) in fixes.into_iter().rev() {
+        let edit_type = if replacement.is_empty() {
+            EditType::Delete
+        } else {
+            EditType::InsertChars
+        };
+        doc.edit_single(
+            Selection::region(range.start, range.end),
+            replacement,
+            edit_type,
+        );
     }
+    doc.text().to_string()
 }
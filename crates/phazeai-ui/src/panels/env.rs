@@ -0,0 +1,324 @@
+//! Environment variables — the bottom-panel "ENV" tab.
+//!
+//! Picks the workspace's active profile from its `.env.<profile>` files and
+//! lists the variables that profile resolves to, with the file each comes
+//! from. Values that look like secrets are masked until revealed. Variables
+//! are added, changed and removed in place in their `.env` file. Terminals,
+//! build tasks and the sidecar started afterwards get the new values.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use floem::{
+    reactive::{create_effect, create_rw_signal, SignalGet, SignalUpdate, SignalWith},
+    views::{container, dyn_stack, empty, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::project::env_vars::{self, EnvSettings, EnvVar, EnvVars, MASK};
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::panels::git_history::action_button;
+
+/// The file new variables of `profile` are written to.
+fn target_file(root: &Path, profile: Option<&str>) -> PathBuf {
+    root.join(match profile {
+        Some(profile) => format!(".env.{profile}"),
+        None => ".env".to_string(),
+    })
+}
+
+pub fn env_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let toast = state.status_toast;
+
+    let profiles = create_rw_signal(Vec::<String>::new());
+    let vars = create_rw_signal(EnvVars::default());
+    // Secrets shown in the clear, by key.
+    let revealed = create_rw_signal(HashSet::<String>::new());
+    let key = create_rw_signal(String::new());
+    let value = create_rw_signal(String::new());
+
+    let reload = move || {
+        let root = workspace_root.get_untracked();
+        let profile = EnvSettings::load(&root).profile;
+        profiles.set(env_vars::profiles(&root));
+        match EnvVars::resolve(&root, profile.as_deref()) {
+            Ok(resolved) => vars.set(resolved),
+            Err(e) => {
+                show_toast(toast, format!("Couldn't read the .env files: {e}"));
+                vars.set(EnvVars {
+                    profile,
+                    vars: Vec::new(),
+                });
+            }
+        }
+    };
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        let loaded = create_rw_signal(false);
+        create_effect(move |_| {
+            if shown.get() && tab.get() == Tab::Env && !loaded.get_untracked() {
+                loaded.set(true);
+                reload();
+            }
+        });
+    }
+    create_effect(move |prev: Option<()>| {
+        let _ = workspace_root.get();
+        if prev.is_some() {
+            revealed.set(HashSet::new());
+            reload();
+        }
+    });
+
+    let switch_profile = move |profile: Option<String>| {
+        let settings = EnvSettings { profile };
+        if let Err(e) = settings.save(&workspace_root.get_untracked()) {
+            show_toast(toast, format!("Couldn't save the profile: {e}"));
+            return;
+        }
+        reload();
+    };
+    let save_var = move || {
+        let name = key.get_untracked().trim().to_string();
+        if name.is_empty() {
+            show_toast(toast, "A variable needs a name");
+            return;
+        }
+        let root = workspace_root.get_untracked();
+        let profile = vars.with_untracked(|v| v.profile.clone());
+        let path = target_file(&root, profile.as_deref());
+        if let Err(e) = env_vars::set_var(&path, &name, &value.get_untracked()) {
+            show_toast(toast, format!("Couldn't save {name}: {e}"));
+            return;
+        }
+        key.set(String::new());
+        value.set(String::new());
+        reload();
+    };
+    let remove = move |var: EnvVar| {
+        if let Err(e) = env_vars::remove_var(&var.source, &var.key) {
+            show_toast(toast, format!("Couldn't remove {}: {e}", var.key));
+            return;
+        }
+        reload();
+        show_toast(toast, format!("Removed {}", var.key));
+    };
+
+    let chip = move |text: String, active: Box<dyn Fn() -> bool>, profile: Option<String>| {
+        label(move || text.clone())
+            .style(move |s| {
+                let p = theme.get().palette;
+                let active = active();
+                s.font_size(11.0)
+                    .padding_horiz(8.0)
+                    .padding_vert(2.0)
+                    .border(1.0)
+                    .border_radius(3.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .border_color(if active { p.accent } else { p.border })
+                    .color(if active { p.accent } else { p.text_secondary })
+            })
+            .on_click_stop(move |_| switch_profile(profile.clone()))
+    };
+
+    // ── Profiles ───────────────────────────────────────────────────────────
+    let profile_bar = stack((
+        label(|| "Profile".to_string())
+            .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+        chip(
+            ".env only".to_string(),
+            Box::new(move || vars.with(|v| v.profile.is_none())),
+            None,
+        ),
+        dyn_stack(
+            move || profiles.get(),
+            |name| name.clone(),
+            move |name: String| {
+                let active = {
+                    let name = name.clone();
+                    move || vars.with(|v| v.profile.as_deref() == Some(name.as_str()))
+                };
+                chip(name.clone(), Box::new(active), Some(name))
+            },
+        )
+        .style(|s| s.items_center().gap(6.0)),
+        label(|| "add .env.dev, .env.test or .env.prod for more".to_string()).style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.text_muted)
+                .apply_if(!profiles.with(|p| p.is_empty()), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        }),
+        empty().style(|s| s.flex_grow(1.0)),
+        action_button(|| "Reload".to_string(), theme, reload),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(p.border)
+    });
+
+    // ── Variables ──────────────────────────────────────────────────────────
+    let var_rows = dyn_stack(
+        move || vars.get().vars,
+        |v| (v.key.clone(), v.value.clone(), v.source.clone()),
+        move |var: EnvVar| {
+            let root = workspace_root.get_untracked();
+            let source = var
+                .source
+                .strip_prefix(&root)
+                .unwrap_or(&var.source)
+                .display()
+                .to_string();
+            let is_revealed = {
+                let key = var.key.clone();
+                move || revealed.with(|r| r.contains(&key))
+            };
+            let shown = {
+                let var = var.clone();
+                let is_revealed = is_revealed.clone();
+                move || {
+                    if is_revealed() {
+                        var.value.clone()
+                    } else {
+                        var.display_value().to_string()
+                    }
+                }
+            };
+            let secret = var.secret;
+            let name = var.key.clone();
+            let for_reveal = var.key.clone();
+            let for_edit = var.clone();
+            stack((
+                label(move || name.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .width(220.0)
+                        .text_ellipsis()
+                        .color(theme.get().palette.text_primary)
+                }),
+                label(shown).style(move |s| {
+                    s.font_size(12.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .text_ellipsis()
+                        .font_family("JetBrains Mono, monospace".to_string())
+                        .color(theme.get().palette.text_secondary)
+                }),
+                label(move || source.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .width(120.0)
+                        .text_ellipsis()
+                        .color(theme.get().palette.text_muted)
+                }),
+                container(action_button(
+                    move || String::from(if is_revealed() { "Hide" } else { "Reveal" }),
+                    theme,
+                    move || {
+                        revealed.update(|r| {
+                            if !r.remove(&for_reveal) {
+                                r.insert(for_reveal.clone());
+                            }
+                        })
+                    },
+                ))
+                .style(move |s| s.apply_if(!secret, |s| s.display(floem::style::Display::None))),
+                action_button(
+                    || "Edit".to_string(),
+                    theme,
+                    move || {
+                        key.set(for_edit.key.clone());
+                        value.set(for_edit.value.clone());
+                    },
+                ),
+                action_button(|| "Delete".to_string(), theme, move || remove(var.clone())),
+            ))
+            .style(|s| {
+                s.items_center()
+                    .gap(6.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty_hint = label(|| "No variables — add one below or create a .env file.".to_string())
+        .style(move |s| {
+            s.font_size(11.0)
+                .padding(10.0)
+                .color(theme.get().palette.text_muted)
+                .apply_if(!vars.with(|v| v.vars.is_empty()), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        });
+
+    // ── Edit form ──────────────────────────────────────────────────────────
+    let input_style = move |s: floem::style::Style| {
+        let p = theme.get().palette;
+        s.font_size(12.0)
+            .background(p.bg_elevated)
+            .border(1.0)
+            .border_color(p.border)
+            .border_radius(3.0)
+            .padding_horiz(6.0)
+            .color(p.text_primary)
+    };
+    let form = stack((
+        text_input(key)
+            .placeholder("NAME")
+            .style(move |s| input_style(s).width(220.0)),
+        text_input(value)
+            .placeholder("value")
+            .style(move |s| input_style(s).flex_grow(1.0).min_width(0.0)),
+        action_button(|| "Set".to_string(), theme, save_var),
+        label(move || {
+            let root = workspace_root.get();
+            let path = target_file(&root, vars.with(|v| v.profile.clone()).as_deref());
+            let file = path.file_name().unwrap_or_default().to_string_lossy();
+            format!("in {file}")
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+            .border_top(1.0)
+            .border_color(p.border)
+    });
+
+    let footer = label(move || {
+        let secrets = vars.with(|v| v.vars.iter().filter(|v| v.secret).count());
+        format!(
+            "Secrets show as {MASK} here and in the Build and sidecar output ({secrets} masked). \
+             Terminals, builds and the sidecar started from now on get these variables."
+        )
+    })
+    .style(move |s| {
+        s.font_size(11.0)
+            .padding_horiz(10.0)
+            .padding_vert(3.0)
+            .width_full()
+            .color(theme.get().palette.text_muted)
+    });
+
+    stack((
+        profile_bar,
+        scroll(stack((var_rows, empty_hint)).style(|s| s.flex_col().width_full()))
+            .style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+        form,
+        footer,
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
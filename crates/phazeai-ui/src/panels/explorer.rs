@@ -1,4 +1,8 @@
-use std::{collections::HashMap, path::PathBuf, sync::mpsc::channel};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+};
 
 use floem::{
     action::show_context_menu,
@@ -6,16 +10,21 @@ use floem::{
     ext_event::create_signal_from_channel,
     keyboard::{Key, NamedKey},
     menu::{Menu, MenuItem},
-    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    reactive::{
+        create_effect, create_memo, create_rw_signal, RwSignal, SignalGet, SignalUpdate, SignalWith,
+    },
     views::{container, dyn_stack, label, scroll, stack, Decorators},
     IntoView,
 };
 use notify::{EventKind, RecursiveMode, Watcher};
+use phazeai_core::{lsp::DiagnosticCounts, project::FileJournal};
 
 use crate::{
     components::icon::{icons, phaze_icon},
+    i18n::{tr, tr_args},
+    panels::compare::{CompareSide, Comparison},
     theme::PhazeTheme,
-    util::safe_get,
+    util::{focus_ring, list_nav_key_opt, safe_get, ListNav},
 };
 
 /// A single visible row in the file tree.
@@ -138,12 +147,13 @@ fn fs_create_dir(path: &PathBuf) -> Result<(), String> {
     std::fs::create_dir_all(path).map_err(|e| e.to_string())
 }
 
-fn fs_delete(path: &PathBuf) -> Result<(), String> {
-    if path.is_dir() {
-        std::fs::remove_dir_all(path).map_err(|e| e.to_string())
-    } else {
-        std::fs::remove_file(path).map_err(|e| e.to_string())
-    }
+/// Move `path` to the workspace trash, where Undo File Operation (or the
+/// agent's `file_history` tool) can restore it.
+fn fs_delete(root: &Path, path: &Path) -> Result<(), String> {
+    FileJournal::for_workspace(root)
+        .delete(&[path.to_path_buf()])
+        .map(|_| ())
+        .map_err(|e| e.to_string())
 }
 
 /// The file-tree explorer panel.
@@ -152,7 +162,11 @@ pub fn explorer_panel(
     open_file: RwSignal<Option<PathBuf>>,
     theme: RwSignal<PhazeTheme>,
     open_tabs: RwSignal<Vec<PathBuf>>,
+    diagnostic_counts: RwSignal<HashMap<PathBuf, DiagnosticCounts>>,
+    comparison: RwSignal<Option<Comparison>>,
 ) -> impl IntoView {
+    // The file picked with "Select for Compare", until it's compared.
+    let compare_first: RwSignal<Option<PathBuf>> = create_rw_signal(None);
     // ── Open Editors section state ─────────────────────────────────────────
     let open_editors_expanded: RwSignal<bool> = create_rw_signal(true);
     let entries: RwSignal<Vec<FileEntry>> = create_rw_signal(vec![]);
@@ -401,7 +415,30 @@ pub fn explorer_panel(
                 }
             });
 
-            container(
+            // Problems badge: the error count, else the warning count, of the
+            // file or of everything under the folder.
+            let diag_path = entry.path.clone();
+            let counts = create_memo(move |_| {
+                diagnostic_counts.with(|c| c.get(&diag_path).copied().unwrap_or_default())
+            });
+            let diag_badge = label(move || {
+                let c = counts.get();
+                match (c.errors, c.warnings) {
+                    (0, 0) => String::new(),
+                    (0, w) => w.to_string(),
+                    (e, _) => e.to_string(),
+                }
+            })
+            .style(move |s| {
+                let p = theme.get().palette;
+                let c = counts.get();
+                s.font_size(10.0)
+                    .margin_left(4.0)
+                    .color(if c.errors > 0 { p.error } else { p.warning })
+                    .apply_if(c.is_empty(), |s| s.display(floem::style::Display::None))
+            });
+
+            let row = container(
                 stack((
                     // Indent spacer
                     container(label(|| "")).style(move |s| s.width(indent).height_full()),
@@ -416,8 +453,17 @@ pub fn explorer_panel(
                     label(move || name.clone()).style(move |s| {
                         let t = theme.get();
                         let p = &t.palette;
-                        s.font_size(13.0).color(p.text_primary).flex_grow(1.0)
+                        let c = counts.get();
+                        let color = if c.errors > 0 {
+                            p.error
+                        } else if c.warnings > 0 {
+                            p.warning
+                        } else {
+                            p.text_primary
+                        };
+                        s.font_size(13.0).color(color).flex_grow(1.0)
                     }),
+                    diag_badge,
                     // Git status badge
                     git_badge,
                 ))
@@ -487,8 +533,8 @@ pub fn explorer_panel(
 
                             // ── New File ──────────────────────────────────────
                             let pdir = parent_dir.clone();
-                            let menu =
-                                Menu::new("").entry(MenuItem::new("New File").action(move || {
+                            let menu = Menu::new("").entry(MenuItem::new(tr("New File")).action(
+                                move || {
                                     // Create an untitled file in parent dir
                                     let new_path = find_unique_path(&pdir, "untitled", "");
                                     let _ = fs_create_file(&new_path);
@@ -496,46 +542,60 @@ pub fn explorer_panel(
                                         let root = root_ref.get();
                                         *list = rebuild_tree(&root, list);
                                     });
-                                }));
+                                },
+                            ));
 
                             // ── New Folder ────────────────────────────────────
                             let pdir2 = parent_dir.clone();
-                            let menu = menu.entry(MenuItem::new("New Folder").action(move || {
-                                let new_path = find_unique_path(&pdir2, "new_folder", "");
-                                let _ = fs_create_dir(&new_path);
-                                entries_ref.update(|list| {
-                                    let root = root_ref.get();
-                                    *list = rebuild_tree(&root, list);
-                                });
-                            }));
+                            let menu =
+                                menu.entry(MenuItem::new(tr("New Folder")).action(move || {
+                                    let new_path = find_unique_path(&pdir2, "new_folder", "");
+                                    let _ = fs_create_dir(&new_path);
+                                    entries_ref.update(|list| {
+                                        let root = root_ref.get();
+                                        *list = rebuild_tree(&root, list);
+                                    });
+                                }));
 
                             let menu = menu.separator();
 
                             // ── Delete ────────────────────────────────────────
                             let del_path = entry_path3.clone();
-                            let menu = menu.entry(MenuItem::new("Delete").action(move || {
-                                let _ = fs_delete(&del_path);
+                            let menu = menu.entry(MenuItem::new(tr("Delete")).action(move || {
+                                let _ = fs_delete(&root_ref.get(), &del_path);
                                 entries_ref.update(|list| {
                                     let root = root_ref.get();
                                     *list = rebuild_tree(&root, list);
                                 });
                             }));
 
+                            // ── Undo the last delete or move ─────────────────
+                            let menu = menu.entry(MenuItem::new(tr("Undo File Operation")).action(
+                                move || {
+                                    let root = root_ref.get();
+                                    let _ = FileJournal::for_workspace(&root).undo(None);
+                                    entries_ref.update(|list| {
+                                        *list = rebuild_tree(&root, list);
+                                    });
+                                },
+                            ));
+
                             let menu = menu.separator();
 
                             // ── Copy Path ─────────────────────────────────────
                             let cp_path = entry_path3.clone();
-                            let menu = menu.entry(MenuItem::new("Copy Path").action(move || {
-                                let path_str = cp_path.to_string_lossy().to_string();
-                                if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                                    let _ = clipboard.set_text(path_str);
-                                }
-                            }));
+                            let menu =
+                                menu.entry(MenuItem::new(tr("Copy Path")).action(move || {
+                                    let path_str = cp_path.to_string_lossy().to_string();
+                                    if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                                        let _ = clipboard.set_text(path_str);
+                                    }
+                                }));
 
                             // ── Copy Absolute Path ────────────────────────────
                             let abs_path = entry_path3.clone();
-                            let menu =
-                                menu.entry(MenuItem::new("Copy Absolute Path").action(move || {
+                            let menu = menu.entry(MenuItem::new(tr("Copy Absolute Path")).action(
+                                move || {
                                     let path_str = if let Ok(abs) = abs_path.canonicalize() {
                                         abs.to_string_lossy().to_string()
                                     } else {
@@ -544,12 +604,13 @@ pub fn explorer_panel(
                                     if let Ok(mut clipboard) = arboard::Clipboard::new() {
                                         let _ = clipboard.set_text(path_str);
                                     }
-                                }));
+                                },
+                            ));
 
                             // ── Copy Relative Path ────────────────────────────
                             let rel_path_entry = entry_path3.clone();
-                            let menu =
-                                menu.entry(MenuItem::new("Copy Relative Path").action(move || {
+                            let menu = menu.entry(MenuItem::new(tr("Copy Relative Path")).action(
+                                move || {
                                     let root = root_ref.get();
                                     let rel = rel_path_entry
                                         .strip_prefix(&root)
@@ -560,7 +621,8 @@ pub fn explorer_panel(
                                     if let Ok(mut clipboard) = arboard::Clipboard::new() {
                                         let _ = clipboard.set_text(rel);
                                     }
-                                }));
+                                },
+                            ));
 
                             let menu = menu.separator();
 
@@ -579,7 +641,7 @@ pub fn explorer_panel(
                                     .parent()
                                     .map(|p| p.to_path_buf())
                                     .unwrap_or_else(|| root_ref.get());
-                                menu.entry(MenuItem::new("Duplicate").action(move || {
+                                menu.entry(MenuItem::new(tr("Duplicate")).action(move || {
                                     let new_path = find_unique_path(
                                         &dup_dir,
                                         &format!("{}_copy", stem),
@@ -595,10 +657,17 @@ pub fn explorer_panel(
                                 menu
                             };
 
+                            // ── Compare (files only) ──────────────────────────
+                            let menu = if !is_dir3 {
+                                compare_entries(menu, &entry_path3, compare_first, comparison)
+                            } else {
+                                menu
+                            };
+
                             // ── Reveal in File Manager ────────────────────────
                             let reveal_path = entry_path3.clone();
-                            let menu = menu.entry(MenuItem::new("Reveal in File Manager").action(
-                                move || {
+                            let menu = menu.entry(
+                                MenuItem::new(tr("Reveal in File Manager")).action(move || {
                                     let dir = if reveal_path.is_dir() {
                                         reveal_path.clone()
                                     } else {
@@ -615,8 +684,8 @@ pub fn explorer_panel(
                                     #[cfg(target_os = "windows")]
                                     let _ =
                                         std::process::Command::new("explorer").arg(&dir).spawn();
-                                },
-                            ));
+                                }),
+                            );
 
                             show_context_menu(menu, None);
                         }
@@ -628,7 +697,15 @@ pub fn explorer_panel(
             })
             .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
                 is_hovered.set(false);
-            })
+            });
+            // Keep the keyboard-focused row scrolled into view.
+            let row_id = row.id();
+            create_effect(move |_| {
+                if focused_idx.get() == Some(this_idx) {
+                    row_id.scroll_to(None);
+                }
+            });
+            row
         },
     )
     .style(|s| s.flex_col().padding(4.0).gap(1.0));
@@ -702,9 +779,20 @@ pub fn explorer_panel(
     // sized by content width (not clamped to viewport) so deep paths scroll.
     let tree_scroll = scroll(tree).style(|s| s.flex_grow(1.0).min_height(0.0).min_width(0.0));
 
-    // Outer container handles keyboard navigation for the whole panel
-    let panel_body = container(tree_scroll)
-        .style(|s| s.flex_grow(1.0).min_height(0.0).width_full())
+    // Outer container takes keyboard focus (Tab, or a click on a row) and
+    // handles navigation for the whole panel.
+    let panel_body = container(tree_scroll);
+    let body_id = panel_body.id();
+    let panel_body = panel_body
+        .keyboard_navigable()
+        .on_event_cont(EventListener::PointerDown, move |_| body_id.request_focus())
+        .style(move |s| {
+            let t = theme.get();
+            s.flex_grow(1.0)
+                .min_height(0.0)
+                .width_full()
+                .apply(focus_ring(&t.palette))
+        })
         .on_event_stop(EventListener::KeyDown, move |event| {
             if let Event::KeyDown(key_event) = event {
                 let list_len = entries.get().len();
@@ -732,6 +820,18 @@ pub fn explorer_panel(
                             });
                         });
                     }
+                    // ── Home/End/PageUp/PageDown — jump; Escape — unfocus ────
+                    Key::Named(
+                        named @ (NamedKey::Home
+                        | NamedKey::End
+                        | NamedKey::PageUp
+                        | NamedKey::PageDown
+                        | NamedKey::Escape),
+                    ) => match list_nav_key_opt(named, focused_idx.get(), list_len) {
+                        ListNav::Move(i) => focused_idx.set(Some(i)),
+                        ListNav::Close => focused_idx.set(None),
+                        _ => {}
+                    },
                     // ── Arrow Right — expand dir ──────────────────────────────
                     Key::Named(NamedKey::ArrowRight) => {
                         if let Some(idx) = focused_idx.get() {
@@ -1044,3 +1144,43 @@ fn file_icon(name: &str) -> &'static str {
         _ => icons::FILE,
     }
 }
+
+/// The context menu's compare entries for the file at `path`: pick it as
+/// the first file, compare it with the one picked, or with the clipboard.
+fn compare_entries(
+    menu: Menu,
+    path: &Path,
+    compare_first: RwSignal<Option<PathBuf>>,
+    comparison: RwSignal<Option<Comparison>>,
+) -> Menu {
+    let select_path = path.to_path_buf();
+    let mut menu = menu
+        .separator()
+        .entry(MenuItem::new(tr("Select for Compare")).action(move || {
+            compare_first.set(Some(select_path.clone()));
+        }));
+    if let Some(first) = compare_first.get_untracked().filter(|first| first != path) {
+        let name = first
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let second = path.to_path_buf();
+        menu = menu.entry(
+            MenuItem::new(tr_args("Compare with '{name}'", &[("name", &name)])).action(move || {
+                let pair = Comparison::files(first.clone(), second.clone());
+                if pair.is_some() {
+                    compare_first.set(None);
+                    comparison.set(pair);
+                }
+            }),
+        );
+    }
+    let clip_path = path.to_path_buf();
+    menu.entry(MenuItem::new(tr("Compare with Clipboard")).action(move || {
+        let file = CompareSide::file(clip_path.clone());
+        if let (Some(left), Some(right)) = (file, CompareSide::clipboard()) {
+            comparison.set(Some(Comparison { left, right }));
+        }
+    }))
+    .separator()
+}
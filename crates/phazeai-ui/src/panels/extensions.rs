@@ -1,6 +1,7 @@
 use crate::app::IdeState;
 use crate::components::button::{phaze_button, ButtonVariant};
 use crate::components::input::phaze_input;
+use crate::panels::output::append_output;
 use crate::util::safe_get;
 use floem::{
     ext_event::create_signal_from_channel,
@@ -8,8 +9,26 @@ use floem::{
     views::{container, dyn_stack, h_stack, label, scroll, v_stack, Decorators},
     IntoView,
 };
+use phazeai_core::ext_host::IdeDelegate;
+use phazeai_core::output;
 use rfd::FileDialog;
 
+/// Sends what plugins log or show while they load to the Output panel's
+/// Extensions channel.
+struct OutputDelegate(std::sync::mpsc::SyncSender<String>);
+
+impl IdeDelegate for OutputDelegate {
+    fn log(&self, msg: &str) {
+        let _ = self.0.try_send(msg.to_string());
+    }
+    fn show_message(&self, msg: &str) {
+        let _ = self.0.try_send(msg.to_string());
+    }
+    fn get_active_text(&self) -> String {
+        String::new()
+    }
+}
+
 /// Combined extension manager panel.
 ///
 /// Supports two extension types:
@@ -24,6 +43,16 @@ pub fn extensions_panel(state: IdeState) -> impl IntoView {
 
     let (result_tx, result_rx) = std::sync::mpsc::sync_channel::<Result<Vec<String>, String>>(4);
     let result_signal = create_signal_from_channel(result_rx);
+    let (log_tx, log_rx) = std::sync::mpsc::sync_channel::<String>(64);
+    let log_signal = create_signal_from_channel(log_rx);
+    {
+        let out = state.output;
+        create_effect(move |_| {
+            if let Some(line) = log_signal.get() {
+                append_output(out, output::EXTENSIONS, &line);
+            }
+        });
+    }
     {
         let state = state.clone();
         create_effect(move |_| {
@@ -34,6 +63,7 @@ pub fn extensions_panel(state: IdeState) -> impl IntoView {
                         state.extensions.set(names);
                     }
                     Err(e) => {
+                        append_output(state.output, output::EXTENSIONS, &format!("[error] {e}"));
                         crate::app::show_toast(
                             state.status_toast,
                             format!("Extension error: {}", e),
@@ -49,16 +79,18 @@ pub fn extensions_panel(state: IdeState) -> impl IntoView {
     let scan_action = {
         let state = state.clone();
         let tx = result_tx.clone();
+        let log_tx = log_tx.clone();
         move |_: ()| {
             state.ext_loading.set(true);
             let manager = state.ext_manager.clone();
             let tx = tx.clone();
+            let log_tx = log_tx.clone();
             std::thread::spawn(move || {
                 let mut all_names: Vec<String> = Vec::new();
 
                 // 1. Scan native Rust plugins
                 if let Ok(mut mgr) = manager.lock() {
-                    let host = phazeai_core::ext_host::DummyDelegate;
+                    let host = OutputDelegate(log_tx);
                     let host =
                         phazeai_core::ext_host::IdeDelegateHost::new(std::sync::Arc::new(host));
                     mgr.scan_plugins(&host);
@@ -100,6 +132,7 @@ pub fn extensions_panel(state: IdeState) -> impl IntoView {
         let state = state.clone();
         let tx = result_tx.clone();
         let toast_tx = toast_tx.clone();
+        let log_tx = log_tx.clone();
         move |_: ()| {
             let Some(path) = FileDialog::new()
                 .add_filter("VSCode Extension", &["vsix"])
@@ -112,6 +145,7 @@ pub fn extensions_panel(state: IdeState) -> impl IntoView {
             state.ext_loading.set(true);
             let tx = tx.clone();
             let toast_tx = toast_tx.clone();
+            let log_tx = log_tx.clone();
             std::thread::spawn(move || {
                 match phazeai_core::ext_host::asset_loader::install_vsix(&path) {
                     Ok(ext) => {
@@ -126,10 +160,12 @@ pub fn extensions_panel(state: IdeState) -> impl IntoView {
                         let grammars = contributes.map(|c| c.grammars.len()).unwrap_or(0);
                         let snippets = contributes.map(|c| c.snippets.len()).unwrap_or(0);
 
-                        let _ = toast_tx.send(format!(
+                        let installed = format!(
                             "Installed {} — {} themes, {} grammars, {} snippets",
                             name, themes, grammars, snippets
-                        ));
+                        );
+                        let _ = log_tx.send(installed.clone());
+                        let _ = toast_tx.send(installed);
 
                         // Rescan all extensions
                         let mut registry =
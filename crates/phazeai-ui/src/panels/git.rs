@@ -15,6 +15,7 @@ use phazeai_core::{constants::ui as ui_const, Agent, AgentEvent, Settings};
 use crate::{
     app::{show_toast, IdeState},
     components::icon::{icons, phaze_icon},
+    panels::git_worktrees::worktree_section,
     theme::PhazeTheme,
     util::safe_get,
 };
@@ -615,6 +616,16 @@ fn run_git_show_diff(root: &std::path::Path, hash: &str) -> String {
     }
 }
 
+/// Writes the message and patch of `hash` to a temp `.diff` file, so the
+/// commit can be opened in its own editor tab with diff highlighting.
+pub(crate) fn write_commit_diff(root: &std::path::Path, hash: &str) -> Option<std::path::PathBuf> {
+    let dir = std::env::temp_dir().join("phazeai-commits");
+    std::fs::create_dir_all(&dir).ok()?;
+    let path = dir.join(format!("{}.diff", &hash[..hash.len().min(12)]));
+    std::fs::write(&path, run_git_show_diff(root, hash)).ok()?;
+    Some(path)
+}
+
 /// Loads the 50 most recent commits via `git log`.
 fn run_git_log(root: &std::path::Path) -> Vec<CommitEntry> {
     let out = std::process::Command::new("git")
@@ -3980,6 +3991,8 @@ pub fn git_panel(state: IdeState) -> impl IntoView {
 
     let commit_log_section = stack((log_header, log_scroll)).style(|s| s.flex_col().width_full());
 
+    let worktrees_section = worktree_section(state.clone());
+
     // ── Full scrollable body ──────────────────────────────────────────────────
     let body = scroll(
         stack((
@@ -3987,6 +4000,7 @@ pub fn git_panel(state: IdeState) -> impl IntoView {
             commit_history,
             blame_section,
             stash_list_section,
+            worktrees_section,
             merge_section,
             tag_section,
             diff_section,
@@ -0,0 +1,410 @@
+//! Git history — the bottom-panel "GIT HISTORY" tab.
+//!
+//! Lists the commits of the repository, or of one file ("Git: Show File
+//! History"), with the selected commit's message and diff beside the list
+//! and actions to check it out, cherry-pick it, revert it or copy its hash.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::mpsc::SyncSender;
+
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::git::{relative_time, CommitSummary, GitOps};
+
+use crate::app::{diff_lines_view, parse_diff_output, show_toast, IdeState, Tab};
+use crate::theme::PhazeTheme;
+use crate::util::safe_get;
+
+/// Commits listed at most.
+const HISTORY_LIMIT: usize = 300;
+
+/// Run a [`GitOps`] call for `root` on a background thread and send its result.
+pub(crate) fn spawn_git<T, F, Fut>(root: PathBuf, tx: SyncSender<T>, f: F)
+where
+    T: Send + 'static,
+    F: FnOnce(GitOps) -> Fut + Send + 'static,
+    Fut: Future<Output = T>,
+{
+    std::thread::spawn(move || {
+        let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        let _ = tx.send(rt.block_on(f(GitOps::new(root))));
+    });
+}
+
+#[derive(Clone, Copy)]
+enum Action {
+    /// Check out the commit, or restore the scoped file to it.
+    Checkout,
+    CherryPick,
+    Revert,
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// A small bordered text button, as in the detail pane's action bar.
+pub(crate) fn action_button(
+    text: impl Fn() -> String + 'static,
+    theme: RwSignal<PhazeTheme>,
+    on_click: impl Fn() + 'static,
+) -> impl IntoView {
+    let hov = create_rw_signal(false);
+    container(label(text).style(move |s| {
+        let p = theme.get().palette;
+        s.font_size(11.0).color(if hov.get() {
+            p.accent_hover
+        } else {
+            p.text_secondary
+        })
+    }))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.padding_horiz(8.0)
+            .padding_vert(2.0)
+            .border(1.0)
+            .border_radius(3.0)
+            .border_color(p.border)
+            .cursor(floem::style::CursorStyle::Pointer)
+            .background(if hov.get() {
+                p.bg_elevated
+            } else {
+                floem::peniko::Color::TRANSPARENT
+            })
+    })
+    .on_click_stop(move |_| on_click())
+    .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+        hov.set(true)
+    })
+    .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+        hov.set(false)
+    })
+}
+
+pub fn git_history_view(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let scope = state.git_history_file;
+    let workspace_root = state.workspace_root;
+    let toast = state.status_toast;
+    let visible = {
+        let tab = state.bottom_panel_tab;
+        let shown = state.show_bottom_panel;
+        move || shown.get() && tab.get() == Tab::GitHistory
+    };
+
+    let commits: RwSignal<Vec<CommitSummary>> = create_rw_signal(Vec::new());
+    let selected: RwSignal<Option<CommitSummary>> = create_rw_signal(None);
+    let detail: RwSignal<Vec<(String, u8)>> = create_rw_signal(Vec::new());
+    let message: RwSignal<String> = create_rw_signal(String::new());
+    let refresh = create_rw_signal(0u64);
+
+    // Repository that holds the scoped file, or the workspace's.
+    let repo_root = move || {
+        let start = scope
+            .get()
+            .and_then(|f| f.parent().map(|p| p.to_path_buf()))
+            .unwrap_or_else(|| workspace_root.get());
+        GitOps::find_root(&start)
+    };
+
+    // ── Commit list: loaded while the tab is visible ─────────────────────────
+    {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<Result<Vec<CommitSummary>, String>>(1);
+        let result = create_signal_from_channel(rx);
+        create_effect(move |_| match result.get() {
+            Some(Ok(list)) => {
+                message.set(if list.is_empty() {
+                    "No commits.".to_string()
+                } else {
+                    String::new()
+                });
+                commits.set(list);
+            }
+            Some(Err(e)) => {
+                message.set(e);
+                commits.set(Vec::new());
+            }
+            None => {}
+        });
+        create_effect(move |_| {
+            let _ = refresh.get();
+            let file = scope.get();
+            if !visible() {
+                return;
+            }
+            selected.set(None);
+            let Some(root) = repo_root() else {
+                message.set("Not a git repository.".to_string());
+                commits.set(Vec::new());
+                return;
+            };
+            message.set("Loading history…".to_string());
+            spawn_git(root, tx.clone(), move |git| async move {
+                git.history(HISTORY_LIMIT, file.as_deref()).await
+            });
+        });
+    }
+
+    // ── Detail: message and diff of the selected commit ──────────────────────
+    {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<Result<String, String>>(1);
+        let result = create_signal_from_channel(rx);
+        create_effect(move |_| {
+            if let Some(shown) = result.get() {
+                detail.set(parse_diff_output(&shown.unwrap_or_else(|e| e)));
+            }
+        });
+        create_effect(move |_| {
+            let Some(commit) = selected.get() else {
+                detail.set(Vec::new());
+                return;
+            };
+            let Some(root) = repo_root() else {
+                return;
+            };
+            let file = scope.get_untracked();
+            spawn_git(root, tx.clone(), move |git| async move {
+                git.show_commit(&commit.hash, file.as_deref()).await
+            });
+        });
+    }
+
+    // ── Actions on the selected commit ───────────────────────────────────────
+    let (action_tx, action_rx) = std::sync::mpsc::sync_channel::<Result<String, String>>(1);
+    {
+        let result = create_signal_from_channel(action_rx);
+        create_effect(move |_| match result.get() {
+            Some(Ok(msg)) => {
+                show_toast(toast, msg);
+                refresh.update(|n| *n += 1);
+            }
+            Some(Err(e)) => show_toast(toast, format!("git: {e}")),
+            None => {}
+        });
+    }
+    let run_action = move |action: Action| {
+        let (Some(commit), Some(root)) = (selected.get_untracked(), repo_root()) else {
+            return;
+        };
+        let file = scope.get_untracked();
+        spawn_git(root, action_tx.clone(), move |git| async move {
+            let short = &commit.short_hash;
+            match (action, file) {
+                (Action::Checkout, Some(file)) => {
+                    git.restore_file(&commit.hash, &file).await?;
+                    Ok(format!("Restored {} from {short}", file.display()))
+                }
+                (Action::Checkout, None) => {
+                    git.checkout(&commit.hash).await?;
+                    Ok(format!("Checked out {short} (detached HEAD)"))
+                }
+                (Action::CherryPick, _) => {
+                    git.cherry_pick(&commit.hash).await?;
+                    Ok(format!("Cherry-picked {short}"))
+                }
+                (Action::Revert, _) => {
+                    git.revert(&commit.hash).await?;
+                    Ok(format!("Reverted {short}"))
+                }
+            }
+        });
+    };
+
+    // ── Header: scope and refresh ────────────────────────────────────────────
+    let header = stack((
+        label(move || match scope.get() {
+            Some(file) => format!(
+                "History of {}",
+                file.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            None => "Repository history".to_string(),
+        })
+        .style(move |s| {
+            s.font_size(11.0)
+                .font_weight(floem::text::Weight::BOLD)
+                .color(theme.get().palette.text_primary)
+        }),
+        container(action_button(
+            || "Repository".to_string(),
+            theme,
+            move || scope.set(None),
+        ))
+        .style(move |s| {
+            s.apply_if(scope.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+        }),
+        action_button(|| "Current File".to_string(), theme, {
+            let open_file = state.open_file;
+            move || {
+                if let Some(file) = open_file.get_untracked() {
+                    scope.set(Some(file));
+                }
+            }
+        }),
+        action_button(
+            || "↻".to_string(),
+            theme,
+            move || refresh.update(|n| *n += 1),
+        ),
+        label(move || message.get()).style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.text_muted)
+                .flex_grow(1.0)
+        }),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(8.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    // ── Commit list ──────────────────────────────────────────────────────────
+    let rows = dyn_stack(
+        move || safe_get(commits, Vec::new()),
+        |c| c.hash.clone(),
+        move |commit: CommitSummary| {
+            let hov = create_rw_signal(false);
+            let hash = commit.hash.clone();
+            let is_selected = move || selected.get().is_some_and(|s| s.hash == hash);
+            let when = relative_time(commit.author_time, now());
+            let short = commit.short_hash.clone();
+            let subject = commit.subject.clone();
+            let meta = format!("{} · {when}", commit.author);
+            container(
+                stack((
+                    label(move || short.clone()).style(move |s| {
+                        s.font_size(11.0)
+                            .min_width(60.0)
+                            .color(theme.get().palette.accent)
+                            .font_family("JetBrains Mono, Fira Code, monospace".to_string())
+                    }),
+                    label(move || subject.clone()).style(move |s| {
+                        s.font_size(11.0)
+                            .flex_grow(1.0)
+                            .min_width(0.0)
+                            .color(theme.get().palette.text_primary)
+                    }),
+                    label(move || meta.clone()).style(move |s| {
+                        s.font_size(10.0)
+                            .margin_left(8.0)
+                            .color(theme.get().palette.text_muted)
+                    }),
+                ))
+                .style(|s| s.items_center().width_full().min_width(0.0)),
+            )
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width_full()
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .background(if is_selected() {
+                        p.selection
+                    } else if hov.get() {
+                        p.bg_elevated
+                    } else {
+                        floem::peniko::Color::TRANSPARENT
+                    })
+            })
+            .on_click_stop(move |_| selected.set(Some(commit.clone())))
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hov.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hov.set(false)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+    let list = scroll(rows).style(move |s| {
+        s.width_pct(45.0)
+            .height_full()
+            .border_right(1.0)
+            .border_color(theme.get().palette.border)
+    });
+
+    // ── Detail pane ──────────────────────────────────────────────────────────
+    let actions = stack((
+        action_button(
+            move || {
+                if scope.get().is_some() {
+                    "Restore File".to_string()
+                } else {
+                    "Checkout".to_string()
+                }
+            },
+            theme,
+            {
+                let run_action = run_action.clone();
+                move || run_action(Action::Checkout)
+            },
+        ),
+        action_button(|| "Cherry-pick".to_string(), theme, {
+            let run_action = run_action.clone();
+            move || run_action(Action::CherryPick)
+        }),
+        action_button(
+            || "Revert".to_string(),
+            theme,
+            move || run_action(Action::Revert),
+        ),
+        action_button(
+            || "Copy Hash".to_string(),
+            theme,
+            move || {
+                if let Some(commit) = selected.get_untracked() {
+                    if let Ok(mut cb) = arboard::Clipboard::new() {
+                        let _ = cb.set_text(commit.hash.clone());
+                    }
+                    show_toast(toast, format!("Copied {}", commit.short_hash));
+                }
+            },
+        ),
+    ))
+    .style(|s| s.gap(6.0).padding(6.0).width_full());
+    let detail_pane = stack((
+        actions,
+        scroll(diff_lines_view(theme, detail))
+            .style(|s| s.flex_grow(1.0).min_height(0.0).width_full()),
+    ))
+    .style(move |s| {
+        s.flex_col()
+            .flex_grow(1.0)
+            .min_width(0.0)
+            .height_full()
+            .apply_if(selected.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+    let placeholder = label(|| "Select a commit to see its changes.".to_string()).style(move |s| {
+        s.font_size(12.0)
+            .padding(16.0)
+            .color(theme.get().palette.text_muted)
+            .apply_if(selected.get().is_some(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    stack((
+        header,
+        stack((list, detail_pane, placeholder))
+            .style(|s| s.flex_grow(1.0).min_height(0.0).width_full()),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
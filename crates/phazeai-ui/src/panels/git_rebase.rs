@@ -0,0 +1,537 @@
+//! Interactive rebase assistant — the bottom-panel "REBASE" tab.
+//!
+//! Lists the commits after a base as an editable plan: drag the handle to
+//! reorder, click the action to cycle pick/reword/squash/fixup/drop, and type
+//! new messages inline. "Suggest" asks the model for squash groupings and
+//! better messages. When git stops on conflicts the conflicted files are
+//! listed with open / keep-side / resolved actions, and Abort is always one
+//! click away while a rebase is in progress.
+
+use floem::{
+    event::EventListener,
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::{
+    git::{
+        suggest_plan, CommitSummary, ConflictSide, GitOps, RebaseAction, RebaseState, RebaseStep,
+    },
+    llm::TaskType,
+    Settings,
+};
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::panels::git_history::{action_button, spawn_git};
+use crate::util::safe_get;
+
+/// Commits listed when there is no upstream to rebase onto.
+const MAX_PLAN: usize = 100;
+
+/// Results from background git work, applied on the UI thread.
+enum RebaseMsg {
+    Loaded {
+        base: String,
+        commits: Vec<CommitSummary>,
+        state: RebaseState,
+    },
+    Suggested(Vec<RebaseStep>),
+    State(RebaseState, String),
+    Error(String),
+}
+
+fn action_color(action: RebaseAction, p: &crate::theme::PhazePalette) -> floem::peniko::Color {
+    match action {
+        RebaseAction::Pick => p.text_secondary,
+        RebaseAction::Reword => p.accent,
+        RebaseAction::Squash | RebaseAction::Fixup => p.warning,
+        RebaseAction::Drop => p.error,
+    }
+}
+
+fn next_action(action: RebaseAction) -> RebaseAction {
+    let all = RebaseAction::ALL;
+    let i = all.iter().position(|a| *a == action).unwrap_or(0);
+    all[(i + 1) % all.len()]
+}
+
+pub fn git_rebase_view(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let toast = state.status_toast;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let visible = {
+        let tab = state.bottom_panel_tab;
+        let shown = state.show_bottom_panel;
+        move || shown.get() && tab.get() == Tab::Rebase
+    };
+
+    // Base commit (empty for the root), the plan and the rebase's state.
+    let base = create_rw_signal(String::new());
+    let plan: RwSignal<Vec<RebaseStep>> = create_rw_signal(Vec::new());
+    // Bumped whenever the plan is replaced so rows (and their message
+    // inputs) are rebuilt rather than reused by hash.
+    let plan_gen = create_rw_signal(0u64);
+    let rebase_state = create_rw_signal(RebaseState::Idle);
+    let status = create_rw_signal(String::new());
+    let busy = create_rw_signal(false);
+    let dragging: RwSignal<Option<String>> = create_rw_signal(None);
+    let loaded_once = create_rw_signal(false);
+
+    let repo_root = move || GitOps::find_root(&workspace_root.get_untracked());
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<RebaseMsg>(4);
+    {
+        let result = create_signal_from_channel(rx);
+        create_effect(move |_| {
+            let Some(msg) = result.get() else {
+                return;
+            };
+            busy.set(false);
+            match msg {
+                RebaseMsg::Loaded {
+                    base: b,
+                    commits,
+                    state,
+                } => {
+                    base.set(b);
+                    status.set(match commits.len() {
+                        0 => "Nothing to rebase after this base.".to_string(),
+                        n => format!("{n} commits, oldest first."),
+                    });
+                    plan.set(commits.iter().map(RebaseStep::pick).collect());
+                    plan_gen.update(|g| *g += 1);
+                    rebase_state.set(state);
+                }
+                RebaseMsg::Suggested(steps) => {
+                    status.set("Suggested plan loaded; review it before starting.".to_string());
+                    plan.set(steps);
+                    plan_gen.update(|g| *g += 1);
+                }
+                RebaseMsg::State(state, done) => {
+                    let finished = state == RebaseState::Idle;
+                    rebase_state.set(state);
+                    if finished {
+                        show_toast(toast, done);
+                        status.set(String::new());
+                        plan.set(Vec::new());
+                        plan_gen.update(|g| *g += 1);
+                    } else {
+                        status.set("Rebase stopped — resolve, then Continue.".to_string());
+                    }
+                }
+                RebaseMsg::Error(e) => {
+                    status.set(e.clone());
+                    show_toast(toast, format!("git: {e}"));
+                }
+            }
+        });
+    }
+
+    // Load the plan for `base` (the default base when `None`).
+    let load = {
+        let tx = tx.clone();
+        move |requested: Option<String>| {
+            let Some(root) = repo_root() else {
+                status.set("Not a git repository.".to_string());
+                return;
+            };
+            busy.set(true);
+            status.set("Loading commits…".to_string());
+            spawn_git(root, tx.clone(), move |git| async move {
+                let state = git.rebase_state().await;
+                let base = match requested {
+                    Some(b) => b,
+                    None => git.default_rebase_base().await.unwrap_or_default(),
+                };
+                let on = (!base.trim().is_empty()).then_some(base.trim());
+                match git.rebase_commits(on).await {
+                    Ok(mut commits) => {
+                        let skip = commits.len().saturating_sub(MAX_PLAN);
+                        commits.drain(..skip);
+                        RebaseMsg::Loaded {
+                            base,
+                            commits,
+                            state,
+                        }
+                    }
+                    Err(e) => RebaseMsg::Error(e),
+                }
+            });
+        }
+    };
+    {
+        let load = load.clone();
+        create_effect(move |_| {
+            if visible() && !loaded_once.get_untracked() {
+                loaded_once.set(true);
+                load(None);
+            }
+        });
+    }
+
+    // Run a state-changing rebase command and report where it left off.
+    let run = {
+        let tx = tx.clone();
+        move |what: &'static str, done: &'static str| {
+            let Some(root) = repo_root() else {
+                return;
+            };
+            let steps = plan.get_untracked();
+            let b = base.get_untracked();
+            busy.set(true);
+            spawn_git(root, tx.clone(), move |git| async move {
+                let on = (!b.trim().is_empty()).then_some(b.trim());
+                let result = match what {
+                    "start" => git.start_rebase(on, &steps).await,
+                    "continue" => git.rebase_continue().await,
+                    "skip" => git.rebase_skip().await,
+                    _ => git.rebase_abort().await.map(|_| RebaseState::Idle),
+                };
+                match result {
+                    Ok(state) => RebaseMsg::State(state, done.to_string()),
+                    Err(e) => RebaseMsg::Error(e),
+                }
+            });
+        }
+    };
+
+    let suggest = {
+        let tx = tx.clone();
+        move || {
+            let commits: Vec<CommitSummary> = plan
+                .get_untracked()
+                .iter()
+                .map(|s| CommitSummary {
+                    hash: s.hash.clone(),
+                    short_hash: s.hash[..s.hash.len().min(8)].to_string(),
+                    author: String::new(),
+                    author_time: 0,
+                    subject: s.subject.clone(),
+                })
+                .collect();
+            if commits.len() < 2 {
+                return;
+            }
+            busy.set(true);
+            status.set("Asking the model for a plan…".to_string());
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                else {
+                    return;
+                };
+                let result = rt.block_on(async {
+                    let llm = Settings::load().build_client_for_task(TaskType::CodeReview)?;
+                    suggest_plan(llm.as_ref(), &commits).await
+                });
+                let _ = tx.send(match result {
+                    Ok(steps) => RebaseMsg::Suggested(steps),
+                    Err(e) => RebaseMsg::Error(format!("couldn't suggest a plan: {e}")),
+                });
+            });
+        }
+    };
+
+    // ── Header ───────────────────────────────────────────────────────────────
+    let stopped = move || matches!(rebase_state.get(), RebaseState::Stopped { .. });
+    let hide_when = |hidden: bool, s: floem::style::Style| {
+        s.apply_if(hidden, |s| s.display(floem::style::Display::None))
+    };
+    let header = stack((
+        label(|| "Rebase commits after".to_string())
+            .style(move |s| s.font_size(11.0).color(theme.get().palette.text_secondary)),
+        text_input(base).placeholder("root").style(move |s| {
+            let p = theme.get().palette;
+            s.width(160.0)
+                .font_size(11.0)
+                .background(p.bg_elevated)
+                .border(1.0)
+                .border_color(p.border)
+                .border_radius(3.0)
+                .padding_horiz(6.0)
+                .color(p.text_primary)
+        }),
+        container(action_button(|| "Load".to_string(), theme, {
+            let load = load.clone();
+            move || load(Some(base.get_untracked()))
+        }))
+        .style(move |s| hide_when(stopped(), s)),
+        container(action_button(|| "✨ Suggest".to_string(), theme, suggest))
+            .style(move |s| hide_when(stopped() || plan.get().len() < 2, s)),
+        container(action_button(|| "Start Rebase".to_string(), theme, {
+            let run = run.clone();
+            move || {
+                if !busy.get_untracked() {
+                    run("start", "Rebase finished");
+                }
+            }
+        }))
+        .style(move |s| hide_when(stopped() || plan.get().is_empty(), s)),
+        container(action_button(|| "Continue".to_string(), theme, {
+            let run = run.clone();
+            move || run("continue", "Rebase finished")
+        }))
+        .style(move |s| hide_when(!stopped(), s)),
+        container(action_button(|| "Skip Commit".to_string(), theme, {
+            let run = run.clone();
+            move || run("skip", "Rebase finished")
+        }))
+        .style(move |s| hide_when(!stopped(), s)),
+        container(action_button(|| "Abort".to_string(), theme, {
+            let run = run.clone();
+            move || run("abort", "Rebase aborted; the branch is back where it was")
+        }))
+        .style(move |s| hide_when(!stopped(), s)),
+        label(move || status.get()).style(move |s| {
+            s.font_size(11.0)
+                .flex_grow(1.0)
+                .min_width(0.0)
+                .color(theme.get().palette.text_muted)
+        }),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(8.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    // ── Conflicts (while stopped) ────────────────────────────────────────────
+    let conflict_rows = dyn_stack(
+        move || match rebase_state.get() {
+            RebaseState::Stopped { conflicts } => conflicts,
+            RebaseState::Idle => Vec::new(),
+        },
+        |path| path.clone(),
+        move |path: String| {
+            let resolve = {
+                let tx = tx.clone();
+                move |path: String, side: Option<ConflictSide>| {
+                    let Some(root) = repo_root() else {
+                        return;
+                    };
+                    spawn_git(root, tx.clone(), move |git| async move {
+                        let result = match side {
+                            Some(side) => git.resolve_conflict(&path, side).await,
+                            None => git.mark_resolved(&path).await,
+                        };
+                        match result {
+                            Ok(()) => RebaseMsg::State(git.rebase_state().await, String::new()),
+                            Err(e) => RebaseMsg::Error(e),
+                        }
+                    });
+                }
+            };
+            let abs = repo_root().map(|root| root.join(&path));
+            stack((
+                label(move || "⚠".to_string())
+                    .style(move |s| s.color(theme.get().palette.warning).font_size(11.0)),
+                label({
+                    let path = path.clone();
+                    move || path.clone()
+                })
+                .style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                action_button(
+                    || "Open".to_string(),
+                    theme,
+                    move || {
+                        if let Some(abs) = &abs {
+                            open_file.set(Some(abs.clone()));
+                        }
+                    },
+                ),
+                action_button(|| "Keep Upstream".to_string(), theme, {
+                    let (resolve, path) = (resolve.clone(), path.clone());
+                    move || resolve(path.clone(), Some(ConflictSide::Upstream))
+                }),
+                action_button(|| "Keep Commit".to_string(), theme, {
+                    let (resolve, path) = (resolve.clone(), path.clone());
+                    move || resolve(path.clone(), Some(ConflictSide::Commit))
+                }),
+                action_button(
+                    || "Mark Resolved".to_string(),
+                    theme,
+                    move || resolve(path.clone(), None),
+                ),
+            ))
+            .style(|s| {
+                s.items_center()
+                    .gap(6.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    // ── Plan rows ────────────────────────────────────────────────────────────
+    let move_to = move |hash: &str, target: &str| {
+        plan.update(|steps| {
+            let (Some(from), Some(to)) = (
+                steps.iter().position(|s| s.hash == hash),
+                steps.iter().position(|s| s.hash == target),
+            ) else {
+                return;
+            };
+            let step = steps.remove(from);
+            steps.insert(to, step);
+        });
+    };
+    let step_of = move |hash: &str| plan.get().into_iter().find(|s| s.hash == hash);
+    let plan_rows = dyn_stack(
+        move || {
+            let gen = plan_gen.get();
+            safe_get(plan, Vec::new())
+                .into_iter()
+                .map(|s| (gen, s))
+                .collect::<Vec<_>>()
+        },
+        |(gen, s)| format!("{gen}-{}", s.hash),
+        move |(_, step): (u64, RebaseStep)| {
+            let hash = step.hash.clone();
+            let message = create_rw_signal(step.message.clone().unwrap_or_default());
+            {
+                let hash = hash.clone();
+                create_effect(move |_| {
+                    let text = message.get();
+                    plan.update(|steps| {
+                        if let Some(s) = steps.iter_mut().find(|s| s.hash == hash) {
+                            s.message = (!text.trim().is_empty()).then(|| text.trim().to_string());
+                        }
+                    });
+                });
+            }
+            let action = {
+                let hash = hash.clone();
+                move || step_of(&hash).map_or(RebaseAction::Pick, |s| s.action)
+            };
+            let show_message = {
+                let action = action.clone();
+                move || matches!(action(), RebaseAction::Reword | RebaseAction::Squash)
+            };
+            let short = hash[..hash.len().min(8)].to_string();
+            let subject = step.subject.clone();
+            stack((
+                label(|| "⠿".to_string())
+                    .style(move |s| {
+                        s.font_size(13.0)
+                            .padding_horiz(4.0)
+                            .color(theme.get().palette.text_muted)
+                            .cursor(floem::style::CursorStyle::Pointer)
+                    })
+                    .on_event_stop(EventListener::PointerDown, {
+                        let hash = hash.clone();
+                        move |_| dragging.set(Some(hash.clone()))
+                    }),
+                container(label({
+                    let action = action.clone();
+                    move || action().as_str().to_string()
+                }))
+                .style({
+                    let action = action.clone();
+                    move |s| {
+                        let p = theme.get().palette;
+                        s.width(58.0)
+                            .font_size(11.0)
+                            .padding_horiz(6.0)
+                            .border(1.0)
+                            .border_radius(3.0)
+                            .border_color(p.border)
+                            .color(action_color(action(), &p))
+                            .cursor(floem::style::CursorStyle::Pointer)
+                    }
+                })
+                .on_click_stop({
+                    let hash = hash.clone();
+                    move |_| {
+                        plan.update(|steps| {
+                            if let Some(s) = steps.iter_mut().find(|s| s.hash == hash) {
+                                s.action = next_action(s.action);
+                            }
+                        })
+                    }
+                }),
+                label(move || short.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .min_width(64.0)
+                        .color(theme.get().palette.accent)
+                        .font_family("JetBrains Mono, Fira Code, monospace".to_string())
+                }),
+                label(move || subject.clone()).style(move |s| {
+                    let p = theme.get().palette;
+                    let dropped = action() == RebaseAction::Drop;
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(if dropped {
+                            p.text_disabled
+                        } else {
+                            p.text_primary
+                        })
+                }),
+                text_input(message)
+                    .placeholder("New message")
+                    .style(move |s| {
+                        let p = theme.get().palette;
+                        s.width_pct(40.0)
+                            .font_size(11.0)
+                            .background(p.bg_elevated)
+                            .border(1.0)
+                            .border_color(p.border)
+                            .border_radius(3.0)
+                            .padding_horiz(6.0)
+                            .color(p.text_primary)
+                            .apply_if(!show_message(), |s| s.display(floem::style::Display::None))
+                    }),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                let dragged = dragging.get().is_some_and(|h| h == hash);
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+                    .background(if dragged {
+                        p.selection
+                    } else {
+                        floem::peniko::Color::TRANSPARENT
+                    })
+            })
+            .on_event_cont(EventListener::PointerEnter, {
+                let target = step.hash.clone();
+                move |_| {
+                    if let Some(dragged) = dragging.get_untracked() {
+                        if dragged != target {
+                            move_to(&dragged, &target);
+                        }
+                    }
+                }
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full())
+    .on_event_cont(EventListener::PointerUp, move |_| dragging.set(None))
+    .on_event_cont(EventListener::PointerLeave, move |_| dragging.set(None));
+
+    let body = scroll(
+        stack((
+            conflict_rows,
+            container(plan_rows).style(move |s| hide_when(stopped(), s.width_full())),
+        ))
+        .style(|s| s.flex_col().width_full()),
+    )
+    .style(|s| s.flex_grow(1.0).min_height(0.0).width_full());
+
+    stack((header, body)).style(|s| s.flex_col().width_full().height_full())
+}
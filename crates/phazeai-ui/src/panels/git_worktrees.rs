@@ -0,0 +1,328 @@
+//! The Git panel's WORKTREES section: list the repository's worktrees, open
+//! one as the workspace, remove finished ones, and create a checkout on a new
+//! task branch for agent work.
+
+use floem::{
+    event::{Event, EventListener},
+    ext_event::create_signal_from_channel,
+    keyboard::{Key, NamedKey},
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::{
+    constants::ui as ui_const,
+    git::{GitOps, Worktree},
+};
+
+use crate::app::{show_toast, IdeState};
+use crate::panels::git_history::{action_button, spawn_git};
+use crate::util::safe_get;
+
+enum WorktreeMsg {
+    Listed(Vec<Worktree>),
+    Created(Worktree),
+    Error(String),
+}
+
+pub(crate) fn worktree_section(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let toast = state.status_toast;
+    let workspace_root = state.workspace_root;
+    let file_picker_files = state.file_picker_files;
+
+    let worktrees: RwSignal<Vec<Worktree>> = create_rw_signal(Vec::new());
+    let expanded = create_rw_signal(false);
+    let creating = create_rw_signal(false);
+    let task = create_rw_signal(String::new());
+    let status = create_rw_signal(String::new());
+    let hdr_hov = create_rw_signal(false);
+
+    let repo_root = move || GitOps::find_root(&workspace_root.get_untracked());
+    // Open `path` as the workspace, as "Open Folder…" does.
+    let switch_to = move |path: std::path::PathBuf| {
+        workspace_root.set(path);
+        file_picker_files.set(Vec::new());
+    };
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<WorktreeMsg>(2);
+    let reload = {
+        let tx = tx.clone();
+        move || {
+            if let Some(root) = repo_root() {
+                spawn_git(root, tx.clone(), |git| async move {
+                    match git.worktrees().await {
+                        Ok(list) => WorktreeMsg::Listed(list),
+                        Err(e) => WorktreeMsg::Error(e),
+                    }
+                });
+            }
+        }
+    };
+    {
+        let reload = reload.clone();
+        let result = create_signal_from_channel(rx);
+        create_effect(move |_| match result.get() {
+            Some(WorktreeMsg::Listed(list)) => worktrees.set(list),
+            Some(WorktreeMsg::Created(wt)) => {
+                let branch = wt.branch.unwrap_or_default();
+                status.set(format!("Created {branch} at {}", wt.path.display()));
+                task.set(String::new());
+                creating.set(false);
+                reload();
+            }
+            Some(WorktreeMsg::Error(e)) => {
+                status.set(e.lines().next().unwrap_or("?").to_string());
+                reload();
+            }
+            None => {}
+        });
+    }
+    {
+        let reload = reload.clone();
+        create_effect(move |_| {
+            let _ = workspace_root.get();
+            if expanded.get() {
+                reload();
+            }
+        });
+    }
+
+    let create = {
+        let tx = tx.clone();
+        move || {
+            let description = task.get_untracked().trim().to_string();
+            let Some(root) = repo_root() else {
+                status.set("Not a git repository.".to_string());
+                return;
+            };
+            if description.is_empty() {
+                return;
+            }
+            spawn_git(root, tx.clone(), move |git| async move {
+                match git.create_task_worktree(&description).await {
+                    Ok(wt) => WorktreeMsg::Created(wt),
+                    Err(e) => WorktreeMsg::Error(e),
+                }
+            });
+        }
+    };
+
+    let header = container(
+        stack((
+            label(move || if expanded.get() { "▾ " } else { "▸ " }).style(move |s| {
+                s.font_size(10.0)
+                    .color(theme.get().palette.text_muted)
+                    .margin_right(2.0)
+            }),
+            label(move || format!("WORKTREES ({})", worktrees.get().len())).style(move |s| {
+                s.font_size(11.0)
+                    .color(theme.get().palette.text_muted)
+                    .font_weight(floem::text::Weight::BOLD)
+                    .flex_grow(1.0)
+            }),
+            action_button(
+                || "+ Task".to_string(),
+                theme,
+                move || {
+                    expanded.set(true);
+                    creating.update(|v| *v = !*v);
+                },
+            ),
+        ))
+        .style(|s| s.items_center().width_full()),
+    )
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.padding_horiz(10.0)
+            .padding_vert(5.0)
+            .width_full()
+            .cursor(floem::style::CursorStyle::Pointer)
+            .border_top(1.0)
+            .border_color(p.border)
+            .background(if hdr_hov.get() {
+                p.bg_elevated
+            } else {
+                floem::peniko::Color::TRANSPARENT
+            })
+    })
+    .on_click_stop(move |_| expanded.update(|v| *v = !*v))
+    .on_event_stop(EventListener::PointerEnter, move |_| hdr_hov.set(true))
+    .on_event_stop(EventListener::PointerLeave, move |_| hdr_hov.set(false));
+
+    let new_task_row = stack((
+        text_input(task)
+            .placeholder("Task description (becomes phaze/<branch>)")
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.flex_grow(1.0)
+                    .min_width(0.0)
+                    .font_size(11.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(3.0)
+                    .padding_horiz(6.0)
+                    .color(p.text_primary)
+            })
+            .on_event_cont(EventListener::KeyDown, {
+                let create = create.clone();
+                move |event| {
+                    if let Event::KeyDown(e) = event {
+                        if e.key.logical_key == Key::Named(NamedKey::Enter) {
+                            create();
+                        }
+                    }
+                }
+            }),
+        action_button(|| "Create".to_string(), theme, create),
+    ))
+    .style(move |s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(12.0)
+            .padding_vert(3.0)
+            .width_full()
+            .apply_if(!expanded.get() || !creating.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let status_label = label(move || status.get()).style(move |s| {
+        s.font_size(10.0)
+            .color(theme.get().palette.text_muted)
+            .padding_horiz(12.0)
+            .padding_vert(2.0)
+            .width_full()
+            .apply_if(status.get().is_empty(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let rows = dyn_stack(
+        move || {
+            if !expanded.get() {
+                return vec![];
+            }
+            worktrees.get()
+        },
+        |wt| wt.path.clone(),
+        move |wt: Worktree| {
+            let row_hov = create_rw_signal(false);
+            let path = wt.path.clone();
+            let is_current = {
+                let path = path.clone();
+                move || GitOps::find_root(&workspace_root.get()).is_some_and(|r| r == path)
+            };
+            let name = match &wt.branch {
+                Some(branch) => branch.clone(),
+                None => format!("({} detached)", &wt.head[..wt.head.len().min(8)]),
+            };
+            let location = if wt.prunable {
+                "missing — prune to forget".to_string()
+            } else {
+                wt.path.display().to_string()
+            };
+            let removable = !wt.is_main;
+
+            let remove = {
+                let tx = tx.clone();
+                let path = path.clone();
+                move || {
+                    let Some(root) = repo_root() else {
+                        return;
+                    };
+                    let path = path.clone();
+                    status.set(format!("Removing {}…", path.display()));
+                    spawn_git(root, tx.clone(), move |git| async move {
+                        let result = if path.exists() {
+                            git.remove_worktree(&path, false).await
+                        } else {
+                            git.prune_worktrees().await
+                        };
+                        match result {
+                            Ok(()) => match git.worktrees().await {
+                                Ok(list) => WorktreeMsg::Listed(list),
+                                Err(e) => WorktreeMsg::Error(e),
+                            },
+                            Err(e) => WorktreeMsg::Error(e),
+                        }
+                    });
+                }
+            };
+
+            container(
+                stack((
+                    label({
+                        let is_current = is_current.clone();
+                        move || if is_current() { "● " } else { "  " }
+                    })
+                    .style(move |s| s.font_size(10.0).color(theme.get().palette.accent)),
+                    stack((
+                        label(move || name.clone()).style(move |s| {
+                            s.font_size(11.0).color(theme.get().palette.text_primary)
+                        }),
+                        label(move || location.clone()).style(move |s| {
+                            s.font_size(10.0).color(theme.get().palette.text_muted)
+                        }),
+                    ))
+                    .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0)),
+                    container(action_button(|| "Open".to_string(), theme, {
+                        let path = path.clone();
+                        move || switch_to(path.clone())
+                    }))
+                    .style({
+                        let is_current = is_current.clone();
+                        move |s| {
+                            s.apply_if(
+                                !safe_get(row_hov, false) || is_current() || wt.prunable,
+                                |s| s.display(floem::style::Display::None),
+                            )
+                        }
+                    }),
+                    container(action_button(|| "Remove".to_string(), theme, {
+                        move || {
+                            if is_current() {
+                                show_toast(toast, "Open another worktree before removing this one");
+                            } else {
+                                remove();
+                            }
+                        }
+                    }))
+                    .style(move |s| {
+                        s.margin_left(4.0)
+                            .apply_if(!safe_get(row_hov, false) || !removable, |s| {
+                                s.display(floem::style::Display::None)
+                            })
+                    }),
+                ))
+                .style(|s| s.items_center().width_full().min_width(0.0)),
+            )
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width_full()
+                    .padding_horiz(12.0)
+                    .padding_vert(3.0)
+                    .border_radius(3.0)
+                    .background(if safe_get(row_hov, false) {
+                        p.bg_elevated
+                    } else {
+                        floem::peniko::Color::TRANSPARENT
+                    })
+            })
+            .on_event_stop(EventListener::PointerEnter, move |_| row_hov.set(true))
+            .on_event_stop(EventListener::PointerLeave, move |_| row_hov.set(false))
+        },
+    )
+    .style(|s: floem::style::Style| s.flex_col().width_full());
+
+    let body =
+        scroll(stack((new_task_row, rows, status_label)).style(|s| s.flex_col().width_full()))
+            .style(move |s| {
+                s.max_height(ui_const::MAX_LIST_HEIGHT)
+                    .width_full()
+                    .apply_if(!expanded.get(), |s| s.display(floem::style::Display::None))
+            });
+
+    stack((header, body)).style(|s| s.flex_col().width_full())
+}
@@ -0,0 +1,347 @@
+//! REST client for `.http` / `.rest` files — the bottom-panel "HTTP" tab.
+//!
+//! The left side lists the requests of the request file open in the editor,
+//! each with a Send button, and the environments of
+//! `.phazeai/http-env.toml` to fill `{{variables}}` from; the right side shows
+//! the last response: its status line, headers and body, with JSON indented.
+//! "Send HTTP Request at Cursor" sends the request under the editor cursor.
+
+use std::path::{Path, PathBuf};
+
+use floem::{
+    ext_event::create_ext_action,
+    reactive::{create_effect, create_rw_signal, RwSignal, Scope, SignalGet, SignalUpdate},
+    views::{dyn_stack, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::rest::{self, HttpEnv, HttpFile, HttpRequest, HttpResponse};
+
+use crate::app::{show_toast, IdeState};
+use crate::panels::git_history::action_button;
+
+/// The request file's requests and the workspace's environments.
+#[derive(Clone, Default)]
+struct Loaded {
+    file: Option<PathBuf>,
+    requests: Vec<HttpRequest>,
+    environments: Vec<String>,
+    default_env: Option<String>,
+}
+
+fn load(root: &Path, file: Option<PathBuf>) -> Result<Loaded, String> {
+    let env = HttpEnv::load(root).map_err(|e| e.to_string())?;
+    let requests = match &file {
+        Some(path) => HttpFile::load(path).map_err(|e| e.to_string())?.requests,
+        None => Vec::new(),
+    };
+    Ok(Loaded {
+        file,
+        requests,
+        environments: env.names().into_iter().map(String::from).collect(),
+        default_env: env.default,
+    })
+}
+
+/// Parse `path` afresh and send its request at 0-based `line`.
+fn send_at(
+    root: &Path,
+    path: &Path,
+    line: u32,
+    env: Option<&str>,
+) -> Result<(HttpRequest, HttpResponse), String> {
+    let file = HttpFile::load(path).map_err(|e| e.to_string())?;
+    let request = file
+        .request_at(line)
+        .ok_or_else(|| "No request at the cursor".to_string())?;
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    rt.block_on(rest::run(root, path, &file, request, env))
+        .map_err(|e| e.to_string())
+}
+
+pub fn http_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let toast = state.status_toast;
+    let send_request = state.http_send_request;
+
+    let loaded = create_rw_signal(Loaded::default());
+    let env = create_rw_signal(None::<String>);
+    // The label of the request in flight.
+    let sending = create_rw_signal(None::<String>);
+    let response = create_rw_signal(None::<Result<(HttpRequest, HttpResponse), String>>);
+
+    let refresh = move |file: Option<PathBuf>| {
+        let root = workspace_root.get_untracked();
+        let on_done =
+            create_ext_action(
+                Scope::current(),
+                move |result: Result<Loaded, String>| match result {
+                    Ok(l) => {
+                        let keep = env
+                            .get_untracked()
+                            .is_some_and(|e| l.environments.contains(&e));
+                        if !keep {
+                            env.set(l.default_env.clone());
+                        }
+                        loaded.set(l);
+                    }
+                    Err(e) => show_toast(toast, format!("HTTP client: {e}")),
+                },
+            );
+        std::thread::spawn(move || on_done(load(&root, file)));
+    };
+    // Follows the editor to the last request file opened in it.
+    create_effect(move |_| {
+        let _ = workspace_root.get();
+        let file = open_file
+            .get()
+            .filter(|p| rest::is_http_file(p))
+            .or_else(|| loaded.get_untracked().file);
+        refresh(file);
+    });
+
+    let send = move |path: PathBuf, line: u32, what: String| {
+        if sending.get_untracked().is_some() {
+            show_toast(toast, "A request is already in flight");
+            return;
+        }
+        sending.set(Some(what));
+        let root = workspace_root.get_untracked();
+        let env_name = env.get_untracked();
+        let on_done = create_ext_action(
+            Scope::current(),
+            move |result: Result<(HttpRequest, HttpResponse), String>| {
+                sending.set(None);
+                response.set(Some(result));
+            },
+        );
+        std::thread::spawn(move || on_done(send_at(&root, &path, line, env_name.as_deref())));
+    };
+    create_effect(move |_| {
+        if let Some((path, line)) = send_request.get() {
+            send_request.set(None);
+            refresh(Some(path.clone()));
+            send(path, line, format!("line {}", line + 1));
+        }
+    });
+
+    // ── Requests ───────────────────────────────────────────────────────────
+    let environments = dyn_stack(
+        move || loaded.get().environments,
+        |name| name.clone(),
+        move |name: String| {
+            let shown = name.clone();
+            let active = {
+                let name = name.clone();
+                move || env.get().as_deref() == Some(name.as_str())
+            };
+            label(move || shown.clone())
+                .style(move |s| {
+                    let p = theme.get().palette;
+                    let active = active();
+                    s.font_size(11.0)
+                        .padding_horiz(8.0)
+                        .padding_vert(2.0)
+                        .border(1.0)
+                        .border_radius(3.0)
+                        .cursor(floem::style::CursorStyle::Pointer)
+                        .border_color(if active { p.accent } else { p.border })
+                        .color(if active { p.accent } else { p.text_secondary })
+                })
+                .on_click_stop(move |_| env.set(Some(name.clone())))
+        },
+    )
+    .style(|s| s.items_center().gap(6.0));
+
+    let header = stack((
+        label(move || match loaded.get().file {
+            Some(path) => path
+                .strip_prefix(workspace_root.get())
+                .unwrap_or(&path)
+                .display()
+                .to_string(),
+            None => "Open a .http or .rest file".to_string(),
+        })
+        .style(move |s| {
+            s.font_size(11.0)
+                .flex_grow(1.0)
+                .min_width(0.0)
+                .text_ellipsis()
+                .color(theme.get().palette.text_secondary)
+        }),
+        action_button(
+            || "Reload".to_string(),
+            theme,
+            move || refresh(loaded.get_untracked().file),
+        ),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(3.0)
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(p.border)
+    });
+
+    let env_bar = stack((
+        label(|| "Environment".to_string())
+            .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+        environments,
+        label(|| format!("none — add {}", rest::HTTP_ENV_FILE)).style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.text_muted)
+                .apply_if(!loaded.get().environments.is_empty(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        }),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let requests = dyn_stack(
+        move || loaded.get().requests,
+        |r| (r.line, r.label()),
+        move |request: HttpRequest| {
+            let title = request.label();
+            let target = format!("{} {}", request.method, request.url);
+            let line = request.line;
+            stack((
+                stack((
+                    label(move || title.clone()).style(move |s| {
+                        s.font_size(12.0)
+                            .text_ellipsis()
+                            .color(theme.get().palette.text_primary)
+                    }),
+                    label(move || target.clone()).style(move |s| {
+                        s.font_size(10.0)
+                            .text_ellipsis()
+                            .color(theme.get().palette.text_muted)
+                    }),
+                ))
+                .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0)),
+                action_button(
+                    move || {
+                        String::from(if sending.get().is_some() {
+                            "…"
+                        } else {
+                            "Send"
+                        })
+                    },
+                    theme,
+                    move || {
+                        if let Some(path) = loaded.get_untracked().file {
+                            send(path, line, request.label());
+                        }
+                    },
+                ),
+            ))
+            .style(|s| {
+                s.items_center()
+                    .gap(6.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(3.0)
+                    .width_full()
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let requests_column = stack((
+        header,
+        env_bar,
+        scroll(requests).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.flex_col()
+            .width(340.0)
+            .height_full()
+            .border_right(1.0)
+            .border_color(p.border)
+    });
+
+    // ── Response ───────────────────────────────────────────────────────────
+    let status = label(move || {
+        if let Some(what) = sending.get() {
+            return format!("Sending {what}…");
+        }
+        match response.get() {
+            None => "Send a request to see its response.".to_string(),
+            Some(Ok((sent, r))) => format!("{}  ·  {} {}", r.summary(), sent.method, sent.url),
+            Some(Err(e)) => e,
+        }
+    })
+    .style(move |s| {
+        let p = theme.get().palette;
+        let color = match response.get() {
+            Some(Ok((_, r))) if r.status >= 400 => p.error,
+            Some(Ok(_)) => p.success,
+            Some(Err(_)) => p.error,
+            None => p.text_muted,
+        };
+        s.font_size(12.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(p.border)
+            .color(color)
+    });
+
+    let mono = |s: floem::style::Style| {
+        s.font_size(12.0)
+            .font_family("JetBrains Mono, monospace".to_string())
+    };
+    let headers = label(move || match response.get() {
+        Some(Ok((_, r))) => r
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{k}: {v}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    })
+    .style(move |s| {
+        let p = theme.get().palette;
+        mono(s)
+            .padding(10.0)
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(p.border)
+            .color(p.text_secondary)
+            .apply_if(!matches!(response.get(), Some(Ok(_))), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+    let body = label(move || match response.get() {
+        Some(Ok((_, r))) => r.pretty_body(),
+        _ => String::new(),
+    })
+    .style(move |s| {
+        mono(s)
+            .padding(10.0)
+            .width_full()
+            .color(theme.get().palette.text_primary)
+    });
+
+    let response_column = stack((
+        status,
+        scroll(stack((headers, body)).style(|s| s.flex_col().width_full()))
+            .style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0).height_full());
+
+    stack((requests_column, response_column)).style(|s| s.width_full().height_full())
+}
@@ -0,0 +1,687 @@
+//! Background agent jobs — the bottom-panel "JOBS" tab.
+//!
+//! Jobs run on the core [`JobQueue`] while the user keeps editing. The panel
+//! queues new jobs (optionally each in its own git worktree), shows their
+//! progress, and answers approval requests; a toast announces when a job
+//! needs approval or finishes.
+//!
+//! The SCHEDULES strip manages recurring jobs stored in the config, which
+//! `phazeai daemon` runs on their cron schedules.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use floem::{
+    event::{Event, EventListener},
+    ext_event::create_signal_from_channel,
+    keyboard::{Key, NamedKey},
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::{
+    agent::{CronSchedule, Job, JobId, JobQueue, JobStatus, ScheduledJob},
+    tools::BashTool,
+    Agent, Settings, ToolRegistry,
+};
+
+use crate::app::{show_toast, IdeState};
+use crate::panels::git_history::action_button;
+use crate::util::{focus_ring, list_nav_key_opt, safe_get, ListNav};
+
+/// Jobs that run at once; the rest wait their turn.
+const MAX_CONCURRENT_JOBS: usize = 2;
+
+/// Start the job queue, mirroring its jobs into `jobs` and toasting when one
+/// needs approval or finishes.
+pub(crate) fn start_job_queue(
+    jobs: RwSignal<Vec<Job>>,
+    toast: RwSignal<Option<String>>,
+) -> JobQueue {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<()>(1);
+    let queue = JobQueue::new(
+        MAX_CONCURRENT_JOBS,
+        Arc::new(|dir: &Path| {
+            let settings = Settings::load();
+            let client = settings.build_llm_client()?;
+            let mut tools = ToolRegistry::default();
+            tools.register(Box::new(BashTool::new(dir.to_path_buf())));
+            Ok(Agent::new(client)
+                .with_tools(tools)
+                .with_limits(&settings.agent))
+        }),
+        // A full channel already has a refresh pending.
+        Arc::new(move || {
+            let _ = tx.try_send(());
+        }),
+    );
+    let changed = create_signal_from_channel(rx);
+    let snapshot = queue.clone();
+    create_effect(move |_| {
+        if changed.get().is_none() {
+            return;
+        }
+        let latest = snapshot.jobs();
+        let previous = jobs.get_untracked();
+        for job in &latest {
+            let before = previous.iter().find(|j| j.id == job.id).map(|j| &j.status);
+            if before == Some(&job.status) {
+                continue;
+            }
+            let msg = match &job.status {
+                JobStatus::AwaitingApproval { tool, .. } => {
+                    format!("Job #{} needs approval to run {tool}", job.id)
+                }
+                JobStatus::Succeeded => format!("Job #{} finished", job.id),
+                JobStatus::Failed(e) => format!("Job #{} failed: {e}", job.id),
+                _ => continue,
+            };
+            show_toast(toast, msg);
+        }
+        jobs.set(latest);
+    });
+    queue
+}
+
+/// Tools the schedule editor can allow; runs may always use read-only ones.
+/// Other tools allowed with `phazeai schedule add --allow` are kept on edit.
+pub const SCHEDULE_TOOLS: [&str; 3] = ["write_file", "edit_file", "bash"];
+
+/// Allow `tool` in `tools`, or stop allowing it if it already is.
+pub fn toggle_tool(tools: &mut Vec<String>, tool: &str) {
+    match tools.iter().position(|t| t == tool) {
+        Some(i) => {
+            tools.remove(i);
+        }
+        None => tools.push(tool.to_string()),
+    }
+}
+
+/// Save `job` into `list`, replacing the schedule named `original` when it's
+/// an edit. Schedule names stay unique.
+pub fn save_schedule(
+    list: &mut Vec<ScheduledJob>,
+    original: Option<&str>,
+    job: ScheduledJob,
+) -> Result<(), String> {
+    if list
+        .iter()
+        .any(|j| j.name == job.name && Some(j.name.as_str()) != original)
+    {
+        return Err(format!("A schedule named '{}' already exists", job.name));
+    }
+    match original {
+        Some(name) => match list.iter_mut().find(|j| j.name == name) {
+            Some(slot) => *slot = job,
+            None => return Err(format!("The schedule '{name}' no longer exists")),
+        },
+        None => list.push(job),
+    }
+    Ok(())
+}
+
+/// Change the config's schedules with `f` and mirror the result into
+/// `schedules`.
+fn edit_schedules(schedules: RwSignal<Vec<ScheduledJob>>, f: impl FnOnce(&mut Vec<ScheduledJob>)) {
+    if let Ok(settings) = Settings::update(|s| f(&mut s.schedules)) {
+        schedules.set(settings.schedules);
+    }
+}
+
+fn status_color(status: &JobStatus, p: &crate::theme::PhazePalette) -> floem::peniko::Color {
+    match status {
+        JobStatus::Queued | JobStatus::Cancelled => p.text_muted,
+        JobStatus::Running => p.accent,
+        JobStatus::AwaitingApproval { .. } => p.warning,
+        JobStatus::Succeeded => p.success,
+        JobStatus::Failed(_) => p.error,
+    }
+}
+
+/// Tab label with the number of unfinished jobs.
+pub(crate) fn jobs_tab_label(jobs: RwSignal<Vec<Job>>) -> String {
+    let active = jobs
+        .get()
+        .iter()
+        .filter(|j| !j.status.is_finished())
+        .count();
+    if active == 0 {
+        "JOBS".to_string()
+    } else {
+        format!("JOBS ({active})")
+    }
+}
+
+pub fn jobs_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let queue = state.job_queue.clone();
+    let jobs = state.jobs;
+    let workspace_root = state.workspace_root;
+    let toast = state.status_toast;
+
+    let prompt = create_rw_signal(String::new());
+    let use_worktree = create_rw_signal(true);
+    let selected: RwSignal<Option<JobId>> = create_rw_signal(None);
+
+    let submit = {
+        let queue = queue.clone();
+        move || {
+            let text = prompt.get_untracked().trim().to_string();
+            if text.is_empty() {
+                return;
+            }
+            let id = queue.submit(
+                text,
+                workspace_root.get_untracked(),
+                use_worktree.get_untracked(),
+            );
+            prompt.set(String::new());
+            selected.set(Some(id));
+        }
+    };
+
+    let schedules = create_rw_signal(Settings::load().schedules);
+    let show_schedules = create_rw_signal(false);
+    let schedule_name = create_rw_signal(String::new());
+    let schedule_cron = create_rw_signal("@nightly".to_string());
+    let schedule_tools: RwSignal<Vec<String>> = create_rw_signal(Vec::new());
+    // The schedule being edited; fields the editor doesn't show keep its values.
+    let editing: RwSignal<Option<ScheduledJob>> = create_rw_signal(None);
+
+    let reset_editor = move || {
+        editing.set(None);
+        prompt.set(String::new());
+        schedule_name.set(String::new());
+        schedule_tools.set(Vec::new());
+    };
+
+    // Save the prompt above as a recurring job for `phazeai daemon`.
+    let save = move || {
+        let name = schedule_name.get_untracked().trim().to_string();
+        let cron = schedule_cron.get_untracked().trim().to_string();
+        let text = prompt.get_untracked().trim().to_string();
+        if name.is_empty() || text.is_empty() {
+            show_toast(toast, "Enter a prompt and a schedule name");
+            return;
+        }
+        if let Err(e) = CronSchedule::parse(&cron) {
+            show_toast(toast, e);
+            return;
+        }
+        let original = editing.get_untracked();
+        let job = ScheduledJob {
+            name,
+            cron,
+            prompt: text,
+            use_worktree: use_worktree.get_untracked(),
+            allowed_tools: schedule_tools.get_untracked(),
+            ..original.clone().unwrap_or_else(|| ScheduledJob {
+                name: String::new(),
+                cron: String::new(),
+                prompt: String::new(),
+                workspace: workspace_root.get_untracked(),
+                use_worktree: false,
+                enabled: true,
+                allowed_tools: Vec::new(),
+            })
+        };
+        let original = original.map(|j| j.name);
+        let mut result = Ok(());
+        edit_schedules(schedules, |list| {
+            result = save_schedule(list, original.as_deref(), job)
+        });
+        match result {
+            Ok(()) => reset_editor(),
+            Err(e) => show_toast(toast, e),
+        }
+    };
+
+    let header = stack((
+        text_input(prompt)
+            .placeholder("Describe a job, e.g. \"write tests for the parser module\"")
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.flex_grow(1.0)
+                    .min_width(0.0)
+                    .font_size(12.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(3.0)
+                    .padding_horiz(6.0)
+                    .color(p.text_primary)
+            })
+            .on_event_cont(EventListener::KeyDown, {
+                let submit = submit.clone();
+                move |event| {
+                    if let Event::KeyDown(e) = event {
+                        if e.key.logical_key == Key::Named(NamedKey::Enter) {
+                            submit();
+                        }
+                    }
+                }
+            }),
+        action_button(
+            move || {
+                let mark = if use_worktree.get() { "☑" } else { "☐" };
+                format!("{mark} Own worktree")
+            },
+            theme,
+            move || use_worktree.update(|v| *v = !*v),
+        ),
+        action_button(|| "Queue Job".to_string(), theme, submit),
+        action_button(|| "Clear Finished".to_string(), theme, {
+            let queue = queue.clone();
+            move || queue.clear_finished()
+        }),
+        action_button(
+            move || {
+                let mark = if show_schedules.get() { "▾" } else { "▸" };
+                format!("{mark} Schedules ({})", schedules.get().len())
+            },
+            theme,
+            move || show_schedules.update(|v| *v = !*v),
+        ),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let small_input = move |s: floem::style::Style| {
+        let p = theme.get().palette;
+        s.font_size(11.0)
+            .background(p.bg_elevated)
+            .border(1.0)
+            .border_color(p.border)
+            .border_radius(3.0)
+            .padding_horiz(6.0)
+            .color(p.text_primary)
+    };
+    let new_schedule_row = stack((
+        text_input(schedule_name)
+            .placeholder("Schedule name")
+            .style(move |s| small_input(s).width(140.0)),
+        text_input(schedule_cron)
+            .placeholder("Cron, e.g. 0 2 * * 1-5")
+            .style(move |s| small_input(s).width(140.0)),
+        action_button(
+            move || {
+                if editing.get().is_some() {
+                    "Save Schedule"
+                } else {
+                    "Schedule Prompt"
+                }
+                .to_string()
+            },
+            theme,
+            save,
+        ),
+        container(action_button(
+            || "Cancel Edit".to_string(),
+            theme,
+            reset_editor,
+        ))
+        .style(move |s| {
+            s.apply_if(editing.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+        }),
+        label(|| "Runs while `phazeai daemon` is running; results go to history.")
+            .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(3.0)
+            .width_full()
+    });
+
+    // Unattended runs can't ask, so the editor says up front what they may do.
+    let tool_row = stack((
+        label(|| "Allow without asking:")
+            .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+        dyn_stack(
+            || SCHEDULE_TOOLS,
+            |tool| *tool,
+            move |tool| {
+                action_button(
+                    move || {
+                        let on = schedule_tools.get().iter().any(|t| t == tool);
+                        format!("{} {tool}", if on { "☑" } else { "☐" })
+                    },
+                    theme,
+                    move || schedule_tools.update(|tools| toggle_tool(tools, tool)),
+                )
+            },
+        )
+        .style(|s| s.gap(4.0).items_center()),
+        label(move || {
+            let others: Vec<String> = schedule_tools
+                .get()
+                .into_iter()
+                .filter(|t| !SCHEDULE_TOOLS.contains(&t.as_str()))
+                .collect();
+            if others.is_empty() {
+                "Read-only tools are always allowed; destructive commands never are.".to_string()
+            } else {
+                format!("Also allows {}", others.join(", "))
+            }
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(3.0)
+            .width_full()
+    });
+
+    let schedule_rows = dyn_stack(
+        move || schedules.get(),
+        |job| {
+            format!(
+                "{}-{}-{}-{}-{:?}",
+                job.name, job.enabled, job.cron, job.prompt, job.allowed_tools
+            )
+        },
+        {
+            let queue = queue.clone();
+            move |job: ScheduledJob| {
+                let now = chrono::Local::now().naive_local();
+                let next = match job.schedule() {
+                    Err(e) => format!("invalid: {e}"),
+                    Ok(_) if !job.enabled => "disabled".to_string(),
+                    Ok(_) => job
+                        .next_run(now)
+                        .map(|t| format!("next {}", t.format("%a %Y-%m-%d %H:%M")))
+                        .unwrap_or_else(|| "never".to_string()),
+                };
+                let allows = if job.allowed_tools.is_empty() {
+                    "read-only".to_string()
+                } else {
+                    format!("allows {}", job.allowed_tools.join(", "))
+                };
+                let summary = format!("{}  ·  {}  ·  {next}  ·  {allows}", job.name, job.cron);
+                let prompt_text = job.prompt.clone();
+                let enabled = job.enabled;
+                let name = job.name.clone();
+                stack((
+                    label(move || summary.clone()).style(move |s| {
+                        s.font_size(11.0)
+                            .min_width(260.0)
+                            .color(theme.get().palette.text_secondary)
+                    }),
+                    label(move || prompt_text.clone()).style(move |s| {
+                        s.font_size(11.0)
+                            .flex_grow(1.0)
+                            .min_width(0.0)
+                            .color(theme.get().palette.text_primary)
+                    }),
+                    action_button(|| "Run Now".to_string(), theme, {
+                        let queue = queue.clone();
+                        let job = job.clone();
+                        move || {
+                            queue.submit(
+                                job.prompt.clone(),
+                                job.workspace.clone(),
+                                job.use_worktree,
+                            );
+                        }
+                    }),
+                    action_button(|| "Edit".to_string(), theme, {
+                        let job = job.clone();
+                        move || {
+                            prompt.set(job.prompt.clone());
+                            schedule_name.set(job.name.clone());
+                            schedule_cron.set(job.cron.clone());
+                            use_worktree.set(job.use_worktree);
+                            schedule_tools.set(job.allowed_tools.clone());
+                            editing.set(Some(job.clone()));
+                        }
+                    }),
+                    action_button(
+                        move || if enabled { "Disable" } else { "Enable" }.to_string(),
+                        theme,
+                        {
+                            let name = name.clone();
+                            move || {
+                                edit_schedules(schedules, |list| {
+                                    for j in list.iter_mut().filter(|j| j.name == name) {
+                                        j.enabled = !enabled;
+                                    }
+                                })
+                            }
+                        },
+                    ),
+                    action_button(
+                        || "Remove".to_string(),
+                        theme,
+                        move || edit_schedules(schedules, |list| list.retain(|j| j.name != name)),
+                    ),
+                ))
+                .style(|s| {
+                    s.items_center()
+                        .gap(6.0)
+                        .padding_horiz(10.0)
+                        .padding_vert(2.0)
+                        .width_full()
+                })
+            }
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let schedule_section = stack((new_schedule_row, tool_row, schedule_rows)).style(move |s| {
+        let p = theme.get().palette;
+        s.flex_col()
+            .width_full()
+            .padding_bottom(4.0)
+            .border_bottom(1.0)
+            .border_color(p.border)
+            .apply_if(!show_schedules.get(), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    let rows = dyn_stack(
+        move || safe_get(jobs, Vec::new()),
+        |job| format!("{}-{:?}", job.id, job.status),
+        {
+            let queue = queue.clone();
+            move |job: Job| {
+                let id = job.id;
+                let status = job.status.clone();
+                let awaiting = matches!(status, JobStatus::AwaitingApproval { .. });
+                let finished = status.is_finished();
+                let place = match &job.branch {
+                    Some(branch) => branch.clone(),
+                    None if job.use_worktree && !finished => "worktree pending".to_string(),
+                    None => String::new(),
+                };
+                let prompt_text = job.prompt.clone();
+                let status_text = status.label().to_string();
+                let buttons = stack((
+                    container(action_button(|| "Approve".to_string(), theme, {
+                        let queue = queue.clone();
+                        move || queue.respond(id, true)
+                    }))
+                    .style(move |s| {
+                        s.apply_if(!awaiting, |s| s.display(floem::style::Display::None))
+                    }),
+                    container(action_button(|| "Deny".to_string(), theme, {
+                        let queue = queue.clone();
+                        move || queue.respond(id, false)
+                    }))
+                    .style(move |s| {
+                        s.apply_if(!awaiting, |s| s.display(floem::style::Display::None))
+                    }),
+                    container(action_button(|| "Cancel".to_string(), theme, {
+                        let queue = queue.clone();
+                        move || queue.cancel(id)
+                    }))
+                    .style(move |s| {
+                        s.apply_if(finished, |s| s.display(floem::style::Display::None))
+                    }),
+                ))
+                .style(|s| s.gap(4.0).items_center());
+                let row = stack((
+                    label(move || format!("#{id}")).style(move |s| {
+                        s.font_size(11.0)
+                            .min_width(28.0)
+                            .color(theme.get().palette.text_muted)
+                    }),
+                    label(move || status_text.clone()).style({
+                        let status = status.clone();
+                        move |s| {
+                            let p = theme.get().palette;
+                            s.font_size(11.0)
+                                .min_width(96.0)
+                                .color(status_color(&status, &p))
+                        }
+                    }),
+                    label(move || prompt_text.clone()).style(move |s| {
+                        s.font_size(12.0)
+                            .flex_grow(1.0)
+                            .min_width(0.0)
+                            .color(theme.get().palette.text_primary)
+                    }),
+                    label(move || place.clone()).style(move |s| {
+                        s.font_size(11.0)
+                            .margin_right(6.0)
+                            .color(theme.get().palette.text_muted)
+                    }),
+                    buttons,
+                ))
+                .style(move |s| {
+                    let p = theme.get().palette;
+                    s.items_center()
+                        .gap(6.0)
+                        .padding_horiz(10.0)
+                        .padding_vert(3.0)
+                        .width_full()
+                        .cursor(floem::style::CursorStyle::Pointer)
+                        .background(if selected.get() == Some(id) {
+                            p.selection
+                        } else {
+                            floem::peniko::Color::TRANSPARENT
+                        })
+                })
+                .on_click_stop(move |_| selected.set(Some(id)));
+                let row_id = row.id();
+                create_effect(move |_| {
+                    if selected.get() == Some(id) {
+                        row_id.scroll_to(None);
+                    }
+                });
+                row
+            }
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty = label(|| "No jobs yet. Queued jobs run in the background while you edit.").style(
+        move |s| {
+            s.font_size(12.0)
+                .padding(12.0)
+                .color(theme.get().palette.text_muted)
+                .apply_if(!jobs.get().is_empty(), |s| {
+                    s.display(floem::style::Display::None)
+                })
+        },
+    );
+
+    // Details of the selected job: what it's waiting on, its log and reply.
+    let selected_job = move || {
+        let id = selected.get()?;
+        jobs.get().into_iter().find(|j| j.id == id)
+    };
+    let detail = label(move || {
+        let Some(job) = selected_job() else {
+            return String::new();
+        };
+        let mut text = String::new();
+        if let Some(dir) = &job.workdir {
+            text.push_str(&format!("Working in {}\n", dir.display()));
+        }
+        match &job.status {
+            JobStatus::AwaitingApproval { detail, .. } => {
+                text.push_str(&format!("\nWaiting for approval:\n{detail}\n"));
+            }
+            JobStatus::Failed(e) => text.push_str(&format!("\nFailed: {e}\n")),
+            _ => {}
+        }
+        if !job.log.is_empty() {
+            text.push('\n');
+            text.push_str(&job.log.join("\n"));
+            text.push('\n');
+        }
+        if !job.output.is_empty() {
+            text.push('\n');
+            text.push_str(&job.output);
+        }
+        text
+    })
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.font_size(12.0)
+            .padding(10.0)
+            .width_full()
+            .color(p.text_secondary)
+            .font_family("JetBrains Mono, Fira Code, monospace".to_string())
+    });
+
+    // ↑/↓ walk the job list once it has focus; Escape clears the selection.
+    let list = container(
+        scroll(stack((rows, empty)).style(|s| s.flex_col().width_full()))
+            .style(|s| s.width_full().height_full()),
+    );
+    let list_id = list.id();
+    let list = list
+        .on_event_cont(EventListener::PointerDown, move |_| list_id.request_focus())
+        .on_event_stop(EventListener::KeyDown, move |event| {
+            let Event::KeyDown(e) = event else {
+                return;
+            };
+            let Key::Named(ref named) = e.key.logical_key else {
+                return;
+            };
+            let ids: Vec<JobId> = jobs.get_untracked().iter().map(|j| j.id).collect();
+            let current = selected
+                .get_untracked()
+                .and_then(|id| ids.iter().position(|&j| j == id));
+            match list_nav_key_opt(named, current, ids.len()) {
+                ListNav::Move(i) => selected.set(Some(ids[i])),
+                ListNav::Close => selected.set(None),
+                ListNav::Accept(_) | ListNav::None => {}
+            }
+        })
+        .keyboard_navigable()
+        .style(move |s| {
+            s.width_pct(50.0)
+                .height_full()
+                .apply(focus_ring(&theme.get().palette))
+        });
+
+    stack((
+        header,
+        schedule_section,
+        stack((
+            list,
+            scroll(detail).style(move |s| {
+                let p = theme.get().palette;
+                s.flex_grow(1.0)
+                    .height_full()
+                    .border_left(1.0)
+                    .border_color(p.border)
+            }),
+        ))
+        .style(|s| s.flex_grow(1.0).min_height(0.0).width_full()),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
@@ -0,0 +1,163 @@
+//! Language servers — the bottom-panel "LANGUAGE SERVERS" tab.
+//!
+//! One row per server started in the workspace (rust-analyzer, pyright,
+//! typescript-language-server, …): the languages it serves, how many open
+//! files it has, and whether it is running, restarting after a crash or
+//! given up on. Each row can be restarted on its own, or all at once.
+
+use floem::{
+    reactive::{create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::lsp::{ServerState, ServerStatus};
+
+use crate::app::IdeState;
+use crate::lsp_bridge::LspCommand;
+use crate::panels::git_history::action_button;
+
+pub(crate) fn language_servers_tab_label(status: RwSignal<Vec<ServerStatus>>) -> String {
+    let list = status.get();
+    let down = list
+        .iter()
+        .filter(|st| st.state != ServerState::Running)
+        .count();
+    if down == 0 {
+        "LANGUAGE SERVERS".to_string()
+    } else {
+        format!("LANGUAGE SERVERS ({down})")
+    }
+}
+
+pub fn language_servers_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let status = state.lsp_status;
+
+    let header = stack((
+        label(move || {
+            let list = status.get();
+            let running = list
+                .iter()
+                .filter(|st| st.state == ServerState::Running)
+                .count();
+            format!("{running} of {} servers running", list.len())
+        })
+        .style(move |s| {
+            s.font_size(11.0)
+                .color(theme.get().palette.text_muted)
+                .flex_grow(1.0)
+        }),
+        {
+            let lsp_cmd = state.lsp_cmd.clone();
+            action_button(
+                || "Restart All".to_string(),
+                theme,
+                move || {
+                    let _ = lsp_cmd.send(LspCommand::RestartServers);
+                },
+            )
+        },
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let lsp_cmd = state.lsp_cmd.clone();
+    let rows = dyn_stack(
+        move || status.get(),
+        |st| format!("{}:{:?}:{}", st.server, st.state, st.open_files),
+        move |st: ServerStatus| {
+            let hovered = create_rw_signal(false);
+            let state_color = st.state.clone();
+            let state_text = st.state.label();
+            let name = st.server.clone();
+            let languages = st.languages.join(", ");
+            let files = match st.open_files {
+                1 => "1 open file".to_string(),
+                n => format!("{n} open files"),
+            };
+            let lsp_cmd = lsp_cmd.clone();
+            let server = st.server.clone();
+            stack((
+                label(|| "●".to_string()).style(move |s| {
+                    let p = theme.get().palette;
+                    let color = match state_color {
+                        ServerState::Running => p.success,
+                        ServerState::Starting | ServerState::Restarting { .. } => p.warning,
+                        ServerState::Failed(_) => p.error,
+                    };
+                    s.font_size(11.0).color(color)
+                }),
+                label(move || name.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .min_width(200.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                label(move || languages.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_secondary)
+                }),
+                label(move || files.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .min_width(90.0)
+                        .color(theme.get().palette.text_muted)
+                }),
+                label(move || state_text.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .min_width(200.0)
+                        .color(theme.get().palette.text_muted)
+                }),
+                action_button(
+                    || "Restart".to_string(),
+                    theme,
+                    move || {
+                        let _ = lsp_cmd.send(LspCommand::RestartServer(server.clone()));
+                    },
+                ),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(3.0)
+                    .width_full()
+                    .apply_if(hovered.get(), |s| s.background(p.bg_elevated))
+            })
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty_msg = container(
+        label(|| "No language servers running. Open a source file to start one.".to_string())
+            .style(move |s| {
+                s.font_size(12.0)
+                    .color(theme.get().palette.text_muted)
+                    .padding(12.0)
+            }),
+    )
+    .style(move |s| {
+        s.apply_if(!status.get().is_empty(), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    stack((
+        header,
+        empty_msg,
+        scroll(rows).style(|s| s.flex_grow(1.0).width_full()),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
@@ -0,0 +1,174 @@
+//! Memory use — the bottom-panel "MEMORY" tab.
+//!
+//! One row per subsystem of the memory ledger: what it holds, its budget
+//! from `[memory]` in the config, and how many entries it has evicted to
+//! stay within it. Refreshed every second while the tab is showing.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, SignalGet},
+    views::{container, dyn_stack, empty, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::memory::{self, format_bytes, UsageRow};
+
+use crate::app::{IdeState, Tab};
+
+/// How often the figures are read while the tab is showing.
+const REFRESH: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    rows: Vec<UsageRow>,
+    resident: Option<usize>,
+}
+
+fn snapshot() -> Snapshot {
+    Snapshot {
+        rows: memory::global().rows(),
+        resident: memory::resident_bytes(),
+    }
+}
+
+pub fn memory_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+
+    let showing = Arc::new(AtomicBool::new(false));
+    {
+        let showing = showing.clone();
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |_| {
+            showing.store(shown.get() && tab.get() == Tab::Memory, Ordering::Relaxed);
+        });
+    }
+    let (tx, rx) = std::sync::mpsc::sync_channel::<Snapshot>(1);
+    std::thread::spawn(move || loop {
+        if showing.load(Ordering::Relaxed) && tx.send(snapshot()).is_err() {
+            return;
+        }
+        std::thread::sleep(REFRESH);
+    });
+    let latest = create_signal_from_channel(rx);
+    let current = move || latest.get().unwrap_or_else(snapshot);
+
+    let header = label(move || {
+        let snap = current();
+        let tracked: usize = snap.rows.iter().map(|r| r.usage.bytes).sum();
+        match snap.resident {
+            Some(resident) => format!(
+                "{} tracked of {} resident",
+                format_bytes(tracked),
+                format_bytes(resident)
+            ),
+            None => format!("{} tracked", format_bytes(tracked)),
+        }
+    })
+    .style(move |s| {
+        s.font_size(11.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+            .color(theme.get().palette.text_muted)
+    });
+
+    let rows = dyn_stack(
+        move || current().rows,
+        |row| {
+            (
+                row.subsystem,
+                row.usage.bytes,
+                row.usage.entries,
+                row.usage.evicted,
+                row.budget,
+            )
+        },
+        move |row: UsageRow| {
+            let over = row.over_budget();
+            let fill = row.fill().min(1.0);
+            let name = row.subsystem.label();
+            let used = format!(
+                "{} / {}",
+                format_bytes(row.usage.bytes),
+                format_bytes(row.budget)
+            );
+            let entries = match row.usage.evicted {
+                0 => format!("{} entries", row.usage.entries),
+                n => format!("{} entries, {n} evicted", row.usage.entries),
+            };
+            let policy = row.subsystem.policy();
+            stack((
+                label(move || name.to_string()).style(move |s| {
+                    s.font_size(12.0)
+                        .width(160.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                // Budget gauge
+                container(empty().style(move |s| {
+                    let p = theme.get().palette;
+                    s.height_full()
+                        .width_pct(fill * 100.0)
+                        .border_radius(2.0)
+                        .background(if over { p.error } else { p.accent })
+                }))
+                .style(move |s| {
+                    s.width(120.0)
+                        .height(6.0)
+                        .border_radius(2.0)
+                        .background(theme.get().palette.bg_elevated)
+                }),
+                label(move || used.clone()).style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(11.0).width(150.0).color(if over {
+                        p.error
+                    } else {
+                        p.text_secondary
+                    })
+                }),
+                label(move || entries.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .width(170.0)
+                        .color(theme.get().palette.text_muted)
+                }),
+                label(move || policy.to_string()).style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .text_ellipsis()
+                        .color(theme.get().palette.text_muted)
+                }),
+            ))
+            .style(|s| {
+                s.items_center()
+                    .gap(10.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(3.0)
+                    .width_full()
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let footer = label(|| {
+        "Sizes are estimates of the text and state held. Budgets are set under [memory] \
+         in the config (documents_mb, highlighting_mb, file_index_mb, blame_mb)."
+            .to_string()
+    })
+    .style(move |s| {
+        s.font_size(11.0)
+            .padding_horiz(10.0)
+            .padding_vert(3.0)
+            .width_full()
+            .color(theme.get().palette.text_muted)
+    });
+
+    stack((
+        header,
+        scroll(rows).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+        footer,
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
@@ -0,0 +1,379 @@
+//! Code metrics — the bottom-panel "METRICS" tab.
+//!
+//! A table of the workspace's functions with their cyclomatic complexity,
+//! length and nesting depth from the core analysis. The workspace is
+//! measured when the tab is first opened and from the Refresh button.
+//! Clicking a column header sorts by it (again to reverse), the list can be
+//! filtered by name or path or narrowed to hotspots, and "Review Hotspots"
+//! asks the agent to review the worst functions.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, create_memo, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::analysis::metrics::{self, FileMetrics, FunctionMetrics, Heat};
+
+use crate::app::{IdeState, Tab};
+use crate::panels::git_history::action_button;
+use crate::theme::PhazeTheme;
+
+/// Rows shown at once; narrow the filter to see the rest.
+const MAX_ROWS: usize = 500;
+/// Hotspots sent to the agent by "Review Hotspots".
+const MAX_REVIEW_HOTSPOTS: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Function,
+    File,
+    Complexity,
+    Length,
+    Nesting,
+}
+
+/// One row of the table: a function and the file it is in.
+#[derive(Clone, PartialEq)]
+struct Row {
+    path: PathBuf,
+    function: FunctionMetrics,
+}
+
+fn sort_rows(rows: &mut [Row], column: Column, descending: bool) {
+    rows.sort_by(|a, b| {
+        let (fa, fb) = (&a.function, &b.function);
+        let order = match column {
+            Column::Function => fa.name.cmp(&fb.name),
+            Column::File => (&a.path, fa.line).cmp(&(&b.path, fb.line)),
+            Column::Complexity => fa.complexity.cmp(&fb.complexity),
+            Column::Length => fa.length().cmp(&fb.length()),
+            Column::Nesting => fa.nesting.cmp(&fb.nesting),
+        };
+        let order = if descending { order.reverse() } else { order };
+        order.then_with(|| (&a.path, fa.line).cmp(&(&b.path, fb.line)))
+    });
+}
+
+/// A clickable column header showing the sort direction when it is sorted by.
+fn column_header(
+    title: &'static str,
+    column: Column,
+    width: Option<f64>,
+    sort: RwSignal<(Column, bool)>,
+    theme: RwSignal<PhazeTheme>,
+) -> impl IntoView {
+    label(move || {
+        let (by, descending) = sort.get();
+        match (by == column, descending) {
+            (true, true) => format!("{title} ▾"),
+            (true, false) => format!("{title} ▴"),
+            (false, _) => title.to_string(),
+        }
+    })
+    .style(move |s| {
+        let p = theme.get().palette;
+        let s = s
+            .font_size(11.0)
+            .color(p.text_secondary)
+            .cursor(floem::style::CursorStyle::Pointer);
+        match width {
+            Some(w) => s.width(w),
+            None => s.flex_grow(1.0).min_width(0.0),
+        }
+    })
+    .on_click_stop(move |_| {
+        sort.update(|(by, descending)| {
+            if *by == column {
+                *descending = !*descending;
+            } else {
+                // Numbers read best largest first, names alphabetically.
+                *descending = !matches!(column, Column::Function | Column::File);
+                *by = column;
+            }
+        })
+    })
+}
+
+pub fn metrics_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let goto_line = state.goto_line;
+    let pending_chat_inject = state.pending_chat_inject;
+    let show_right_panel = state.show_right_panel;
+
+    let report = create_rw_signal(Vec::<FileMetrics>::new());
+    let scanning = create_rw_signal(false);
+    let rescan = create_rw_signal(0u64);
+    let filter_text = create_rw_signal(String::new());
+    let hotspots_only = create_rw_signal(false);
+    let sort = create_rw_signal((Column::Complexity, true));
+
+    // ── Measuring ──────────────────────────────────────────────────────────
+    {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<(u64, Vec<FileMetrics>)>(1);
+        let results = create_signal_from_channel(rx);
+        let generation = Arc::new(AtomicU64::new(0));
+        {
+            let generation = generation.clone();
+            create_effect(move |_| {
+                let Some((gen, files)) = results.get() else {
+                    return;
+                };
+                if gen != generation.load(Ordering::Relaxed) {
+                    return;
+                }
+                report.set(files);
+                scanning.set(false);
+            });
+        }
+        create_effect(move |_| {
+            if rescan.get() == 0 {
+                return;
+            }
+            let root = workspace_root.get_untracked();
+            let gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
+            scanning.set(true);
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send((gen, metrics::measure_workspace(&root)));
+            });
+        });
+    }
+    // Measured when the tab is first opened; Refresh picks up later changes.
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |_| {
+            if shown.get() && tab.get() == Tab::Metrics && rescan.get_untracked() == 0 {
+                rescan.set(1);
+            }
+        });
+    }
+    // A different workspace has different functions.
+    create_effect(move |_| {
+        let _ = workspace_root.get();
+        if rescan.get_untracked() > 0 {
+            rescan.update(|n| *n += 1);
+        }
+    });
+
+    let rows = create_memo(move |_| {
+        let needle = filter_text.get().to_lowercase();
+        let only_hot = hotspots_only.get();
+        let mut rows: Vec<Row> = report
+            .get()
+            .into_iter()
+            .flat_map(|file| {
+                let path = file.path;
+                file.functions.into_iter().map(move |function| Row {
+                    path: path.clone(),
+                    function,
+                })
+            })
+            .filter(|r| !only_hot || r.function.is_hotspot())
+            .filter(|r| {
+                needle.is_empty()
+                    || r.function.name.to_lowercase().contains(&needle)
+                    || r.path.to_string_lossy().to_lowercase().contains(&needle)
+            })
+            .collect();
+        let (column, descending) = sort.get();
+        sort_rows(&mut rows, column, descending);
+        rows
+    });
+
+    let review = move || {
+        let hotspots = metrics::hotspots(&report.get_untracked(), MAX_REVIEW_HOTSPOTS);
+        if hotspots.is_empty() {
+            return;
+        }
+        pending_chat_inject.set(Some(format!(
+            "Review these complexity hotspots of the workspace, worst first. For each, \
+             read the function and say whether it is worth simplifying and how (functions \
+             to extract, branches to merge or replace with a table, early returns):\n\n{}",
+            metrics::describe_hotspots(&hotspots)
+        )));
+        show_right_panel.set(true);
+    };
+
+    // ── Header ─────────────────────────────────────────────────────────────
+    let header = stack((
+        text_input(filter_text)
+            .placeholder("Filter by function or path")
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width(220.0)
+                    .font_size(12.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(3.0)
+                    .padding_horiz(6.0)
+                    .color(p.text_primary)
+            }),
+        action_button(
+            move || {
+                let mark = if hotspots_only.get() { "✓ " } else { "" };
+                format!("{mark}Hotspots Only")
+            },
+            theme,
+            move || hotspots_only.update(|on| *on = !*on),
+        ),
+        action_button(|| "Review Hotspots".to_string(), theme, review),
+        action_button(
+            || "Refresh".to_string(),
+            theme,
+            move || rescan.update(|n| *n += 1),
+        ),
+        label(move || {
+            let files = report.get();
+            let functions: usize = files.iter().map(|f| f.functions.len()).sum();
+            let lines: usize = files.iter().map(|f| f.lines_of_code).sum();
+            let hot = files
+                .iter()
+                .flat_map(|f| &f.functions)
+                .filter(|f| f.is_hotspot())
+                .count();
+            let status = if scanning.get() {
+                "  ·  measuring…"
+            } else {
+                ""
+            };
+            format!("{functions} functions, {lines} lines of code, {hot} hotspots{status}")
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let columns = stack((
+        column_header("Function", Column::Function, Some(220.0), sort, theme),
+        column_header("File", Column::File, None, sort, theme),
+        column_header("Complexity", Column::Complexity, Some(80.0), sort, theme),
+        column_header("Lines", Column::Length, Some(60.0), sort, theme),
+        column_header("Nesting", Column::Nesting, Some(60.0), sort, theme),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.items_center()
+            .gap(8.0)
+            .padding_horiz(10.0)
+            .padding_vert(3.0)
+            .width_full()
+            .border_bottom(1.0)
+            .border_color(p.border)
+    });
+
+    // ── Table ──────────────────────────────────────────────────────────────
+    let table = dyn_stack(
+        move || rows.get().into_iter().take(MAX_ROWS).collect::<Vec<_>>(),
+        |r| (r.path.clone(), r.function.clone()),
+        move |row: Row| {
+            let hovered = create_rw_signal(false);
+            let f = row.function.clone();
+            let name = f.name.clone();
+            let location = format!("{}:{}", row.path.display(), f.line);
+            let heat = f.heat();
+            let (complexity, length, nesting) = (f.complexity, f.length(), f.nesting);
+            let long = length >= metrics::LONG_FUNCTION;
+            let deep = nesting >= metrics::DEEP_NESTING;
+            let (path, line) = (row.path.clone(), f.line);
+            stack((
+                label(move || name.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .width(220.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                label(move || location.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_secondary)
+                }),
+                label(move || complexity.to_string()).style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(12.0).width(80.0).color(match heat {
+                        Heat::Hot => p.error,
+                        Heat::Warm => p.warning,
+                        Heat::Cool => p.text_primary,
+                    })
+                }),
+                label(move || length.to_string()).style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(12.0).width(60.0).color(if long {
+                        p.warning
+                    } else {
+                        p.text_primary
+                    })
+                }),
+                label(move || nesting.to_string()).style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(12.0).width(60.0).color(if deep {
+                        p.warning
+                    } else {
+                        p.text_primary
+                    })
+                }),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(hovered.get(), |s| s.background(p.bg_elevated))
+            })
+            .on_click_stop(move |_| {
+                open_file.set(Some(workspace_root.get_untracked().join(&path)));
+                goto_line.set(line as u32);
+            })
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty_msg = container(
+        label(move || {
+            if scanning.get() {
+                "Measuring the workspace…".to_string()
+            } else {
+                "No functions found.".to_string()
+            }
+        })
+        .style(move |s| {
+            s.font_size(12.0)
+                .color(theme.get().palette.text_muted)
+                .padding(12.0)
+        }),
+    )
+    .style(move |s| {
+        s.apply_if(!report.get().is_empty(), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    stack((
+        header,
+        columns,
+        empty_msg,
+        scroll(table).style(|s| s.flex_grow(1.0).width_full()),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
@@ -1,10 +1,33 @@
+pub mod account;
+pub mod audit;
+pub mod bench;
+pub mod board;
+pub mod bookmarks;
 pub mod chat;
+pub mod compare;
 pub mod composer;
+pub mod database;
 pub mod editor;
+pub mod env;
 pub mod explorer;
 pub mod extensions;
 pub mod git;
+pub mod git_history;
+pub mod git_rebase;
+pub mod git_worktrees;
 pub mod github_actions;
+pub mod http;
+pub mod jobs;
+pub mod language_servers;
+pub mod memory;
+pub mod metrics;
+pub mod module_graph;
+pub mod output;
+#[cfg(feature = "webview")]
+pub mod preview;
+pub mod profile;
 pub mod search;
 pub mod settings;
+pub mod tasks;
 pub mod terminal;
+pub mod todos;
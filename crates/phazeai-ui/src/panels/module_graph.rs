@@ -0,0 +1,482 @@
+//! Module graph — the bottom-panel "MODULE GRAPH" tab.
+//!
+//! Draws the workspace's import graph from the core analysis: one box per
+//! source file, an arrow from each file to the files it imports, imports to
+//! the left of their importers. The graph is scanned when the tab is first
+//! opened and from the Refresh button. It can be narrowed to one package
+//! and zoomed; clicking a module opens it and highlights its imports and
+//! importers, and "Explain Subsystem" asks the agent about the selected
+//! module's neighborhood, or the whole graph shown.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use floem::{
+    action::show_context_menu,
+    ext_event::create_signal_from_channel,
+    kurbo::{BezPath, Point, Rect, Size, Vec2},
+    menu::{Menu, MenuItem},
+    reactive::{create_effect, create_memo, create_rw_signal, SignalGet, SignalUpdate, SignalWith},
+    views::{canvas, container, dyn_stack, label, scroll, stack, Decorators},
+    IntoView, Renderer,
+};
+use phazeai_core::analysis::imports::{self, ImportGraph, Module};
+
+use crate::app::{IdeState, Tab};
+use crate::panels::git_history::action_button;
+
+/// Width of a column of the layout, and of a module's box, at zoom 1.
+const COLUMN_WIDTH: f64 = 240.0;
+const NODE_WIDTH: f64 = 190.0;
+/// Height of a row of the layout, and of a module's box, at zoom 1.
+const ROW_HEIGHT: f64 = 34.0;
+const NODE_HEIGHT: f64 = 24.0;
+/// Space around the graph.
+const MARGIN: f64 = 16.0;
+
+const MIN_ZOOM: f64 = 0.2;
+const MAX_ZOOM: f64 = 2.5;
+const ZOOM_STEP: f64 = 1.25;
+
+/// Modules sent to the agent at most; the rest are summarized as a count.
+const MAX_EXPLAIN_MODULES: usize = 150;
+
+/// A module's label: its file name, with the directory for files named
+/// after it (`net/mod.rs`, `ui/index.ts`, `app/__init__.py`).
+fn module_label(module: &Module) -> String {
+    let name = module
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = module.path.file_stem().and_then(|s| s.to_str());
+    match (stem, module.path.parent().and_then(|p| p.file_name())) {
+        (Some("mod" | "lib" | "main" | "index" | "__init__"), Some(dir)) => {
+            format!("{}/{name}", dir.to_string_lossy())
+        }
+        _ => name,
+    }
+}
+
+/// A filled arrow from `from` to `to`, `width` thick with a head at `to`.
+fn arrow(from: Point, to: Point, width: f64, head: f64) -> BezPath {
+    let dir = to - from;
+    let len = dir.hypot().max(1.0);
+    let unit = dir / len;
+    let normal = Vec2::new(-unit.y, unit.x);
+    let base = to - unit * head;
+    let mut path = BezPath::new();
+    path.move_to(from + normal * (width / 2.0));
+    path.line_to(base + normal * (width / 2.0));
+    path.line_to(base + normal * (head / 2.0));
+    path.line_to(to);
+    path.line_to(base - normal * (head / 2.0));
+    path.line_to(base - normal * (width / 2.0));
+    path.line_to(from - normal * (width / 2.0));
+    path.close_path();
+    path
+}
+
+pub fn module_graph_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let pending_chat_inject = state.pending_chat_inject;
+    let show_right_panel = state.show_right_panel;
+
+    let graph = create_rw_signal(ImportGraph::default());
+    let scanning = create_rw_signal(false);
+    let rescan = create_rw_signal(0u64);
+    let package = create_rw_signal(None::<String>);
+    let selected = create_rw_signal(None::<PathBuf>);
+    let zoom = create_rw_signal(1.0f64);
+    let viewport = create_rw_signal(Size::ZERO);
+
+    // ── Scanning ───────────────────────────────────────────────────────────
+    {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<(u64, ImportGraph)>(1);
+        let results = create_signal_from_channel(rx);
+        let generation = Arc::new(AtomicU64::new(0));
+        {
+            let generation = generation.clone();
+            create_effect(move |_| {
+                let Some((gen, scanned)) = results.get() else {
+                    return;
+                };
+                if gen != generation.load(Ordering::Relaxed) {
+                    return;
+                }
+                graph.set(scanned);
+                scanning.set(false);
+            });
+        }
+        create_effect(move |_| {
+            if rescan.get() == 0 {
+                return;
+            }
+            let root = workspace_root.get_untracked();
+            let gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
+            scanning.set(true);
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send((gen, imports::scan_workspace(&root)));
+            });
+        });
+    }
+    // Scanned when the tab is first opened; Refresh picks up later changes.
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |_| {
+            if shown.get() && tab.get() == Tab::ModuleGraph && rescan.get_untracked() == 0 {
+                rescan.set(1);
+            }
+        });
+    }
+    // A different workspace has a different graph.
+    create_effect(move |_| {
+        let _ = workspace_root.get();
+        package.set(None);
+        selected.set(None);
+        if rescan.get_untracked() > 0 {
+            rescan.update(|n| *n += 1);
+        }
+    });
+
+    let visible = create_memo(move |_| {
+        let graph = graph.get();
+        match package.get() {
+            Some(name) => graph.subgraph(|m| m.package == name),
+            None => graph,
+        }
+    });
+    let cells = create_memo(move |_| visible.with(|g| g.layout()));
+    let selected_index = create_memo(move |_| {
+        let path = selected.get()?;
+        visible.with(|g| g.modules.iter().position(|m| m.path == path))
+    });
+    // Extent of the layout, in cells.
+    let extent = create_memo(move |_| {
+        cells.with(|cells| {
+            let cols = cells.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+            let rows = cells.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+            (cols, rows)
+        })
+    });
+    let graph_size = move || {
+        let (cols, rows) = extent.get();
+        let z = zoom.get();
+        Size::new(
+            (cols as f64 * COLUMN_WIDTH + 2.0 * MARGIN) * z,
+            (rows as f64 * ROW_HEIGHT + 2.0 * MARGIN) * z,
+        )
+    };
+    let fit = move || {
+        let (cols, rows) = extent.get_untracked();
+        let view = viewport.get_untracked();
+        if cols == 0 || view.is_empty() {
+            zoom.set(1.0);
+            return;
+        }
+        let w = cols as f64 * COLUMN_WIDTH + 2.0 * MARGIN;
+        let h = rows as f64 * ROW_HEIGHT + 2.0 * MARGIN;
+        zoom.set(
+            (view.width / w)
+                .min(view.height / h)
+                .clamp(MIN_ZOOM, MAX_ZOOM),
+        );
+    };
+
+    // Each package gets a color of its own, in package order.
+    let package_names = create_memo(move |_| graph.with(|g| g.packages()));
+    let package_color = move |name: &str| {
+        let p = theme.get().palette;
+        let colors = [
+            p.syn_keyword,
+            p.syn_type,
+            p.syn_function,
+            p.syn_string,
+            p.syn_number,
+            p.syn_macro,
+            p.accent,
+        ];
+        let i = package_names.with(|names| names.iter().position(|n| n == name).unwrap_or(0));
+        colors[i % colors.len()]
+    };
+
+    // ── Header ─────────────────────────────────────────────────────────────
+    let pick_package = move || {
+        let current = package.get_untracked();
+        let counts: Vec<(String, usize)> = graph.with_untracked(|g| {
+            g.packages()
+                .into_iter()
+                .map(|name| {
+                    let n = g.modules.iter().filter(|m| m.package == name).count();
+                    (name, n)
+                })
+                .collect()
+        });
+        let mark = |on: bool| if on { "✓ " } else { "   " };
+        let mut menu = Menu::new("").entry(
+            MenuItem::new(format!("{}All Packages", mark(current.is_none())))
+                .action(move || package.set(None)),
+        );
+        for (name, count) in counts {
+            let label = format!("{}{name} ({count})", mark(current.as_ref() == Some(&name)));
+            menu = menu.entry(MenuItem::new(label).action(move || package.set(Some(name.clone()))));
+        }
+        show_context_menu(menu, None);
+    };
+
+    let explain = move || {
+        let subsystem = visible.with_untracked(|g| match selected_index.get_untracked() {
+            Some(i) => g.neighborhood(i),
+            None => g.clone(),
+        });
+        if subsystem.modules.is_empty() {
+            return;
+        }
+        let total = subsystem.modules.len();
+        let shown = if total > MAX_EXPLAIN_MODULES {
+            let keep: std::collections::HashSet<PathBuf> = subsystem
+                .modules
+                .iter()
+                .take(MAX_EXPLAIN_MODULES)
+                .map(|m| m.path.clone())
+                .collect();
+            subsystem.subgraph(|m| keep.contains(&m.path))
+        } else {
+            subsystem
+        };
+        let mut graph_text = shown.describe();
+        if total > MAX_EXPLAIN_MODULES {
+            graph_text.push_str(&format!(
+                "… and {} more modules\n",
+                total - MAX_EXPLAIN_MODULES
+            ));
+        }
+        let scope = match (selected.get_untracked(), package.get_untracked()) {
+            (Some(path), _) => format!("around `{}`", path.display()),
+            (None, Some(name)) => format!("of the package `{name}`"),
+            (None, None) => "of the workspace".to_string(),
+        };
+        pending_chat_inject.set(Some(format!(
+            "Explain this subsystem {scope}: what each module is for, how they depend on \
+             each other, and where to start reading. Its import graph (`a -> b` means a \
+             imports b):\n\n```\n{graph_text}```"
+        )));
+        show_right_panel.set(true);
+    };
+
+    let header = stack((
+        action_button(
+            move || {
+                format!(
+                    "Package: {} ▾",
+                    package.get().unwrap_or_else(|| "All".to_string())
+                )
+            },
+            theme,
+            pick_package,
+        ),
+        action_button(|| "Explain Subsystem".to_string(), theme, explain),
+        action_button(
+            || "−".to_string(),
+            theme,
+            move || zoom.update(|z| *z = (*z / ZOOM_STEP).max(MIN_ZOOM)),
+        ),
+        action_button(
+            move || format!("{:.0}%", zoom.get() * 100.0),
+            theme,
+            move || zoom.set(1.0),
+        ),
+        action_button(
+            || "+".to_string(),
+            theme,
+            move || zoom.update(|z| *z = (*z * ZOOM_STEP).min(MAX_ZOOM)),
+        ),
+        action_button(|| "Fit".to_string(), theme, fit),
+        action_button(
+            || "Refresh".to_string(),
+            theme,
+            move || rescan.update(|n| *n += 1),
+        ),
+        label(move || {
+            let (modules, edges) = visible.with(|g| (g.modules.len(), g.edges.len()));
+            let status = if scanning.get() {
+                "  ·  scanning…"
+            } else {
+                ""
+            };
+            let focus = match selected.get() {
+                Some(path) => format!("  ·  {}", path.display()),
+                None => String::new(),
+            };
+            format!("{modules} modules, {edges} imports{focus}{status}")
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    // ── Graph ──────────────────────────────────────────────────────────────
+    let node_origin = move |(col, row): (u32, u32), z: f64| {
+        Point::new(
+            (MARGIN + col as f64 * COLUMN_WIDTH) * z,
+            (MARGIN + row as f64 * ROW_HEIGHT) * z,
+        )
+    };
+
+    let edges = canvas(move |cx, _size| {
+        let p = theme.get().palette;
+        let z = zoom.get();
+        let focus = selected_index.get();
+        let cells = cells.get();
+        visible.with(|g| {
+            // Dim edges first so highlighted ones are drawn on top.
+            let mut ordered: Vec<&(usize, usize)> = g.edges.iter().collect();
+            ordered.sort_by_key(|(a, b)| focus.is_some_and(|f| f == *a || f == *b));
+            for &(a, b) in ordered {
+                let (Some(&from), Some(&to)) = (cells.get(a), cells.get(b)) else {
+                    continue;
+                };
+                let start = node_origin(from, z) + Vec2::new(0.0, NODE_HEIGHT * z / 2.0);
+                let end = node_origin(to, z) + Vec2::new(NODE_WIDTH * z, NODE_HEIGHT * z / 2.0);
+                let color = match focus {
+                    Some(f) if f == a => p.accent,
+                    Some(f) if f == b => p.warning,
+                    Some(_) => p.border.with_alpha(0.25),
+                    None => p.text_muted.with_alpha(0.45),
+                };
+                let width = (1.2 * z).max(0.6);
+                cx.fill(&arrow(start, end, width, (7.0 * z).max(3.0)), color, 0.0);
+            }
+        });
+    })
+    .style(move |s| {
+        let size = graph_size();
+        s.absolute().width(size.width).height(size.height)
+    });
+
+    let nodes = dyn_stack(
+        move || {
+            let cells = cells.get();
+            visible.with(|g| {
+                g.modules
+                    .iter()
+                    .cloned()
+                    .enumerate()
+                    .map(|(i, m)| (i, m, cells.get(i).copied().unwrap_or_default()))
+                    .collect::<Vec<_>>()
+            })
+        },
+        |(i, module, cell)| (*i, module.path.clone(), *cell),
+        move |(index, module, cell): (usize, Module, (u32, u32))| {
+            let hovered = create_rw_signal(false);
+            let text = module_label(&module);
+            let color_of = module.package.clone();
+            let path = module.path.clone();
+            // Modules unrelated to the selected one fade.
+            let dimmed = create_memo(move |_| {
+                let Some(f) = selected_index.get() else {
+                    return false;
+                };
+                f != index
+                    && visible.with(|g| {
+                        !g.edges
+                            .iter()
+                            .any(|&edge| edge == (f, index) || edge == (index, f))
+                    })
+            });
+            container(label(move || text.clone()).style(move |s| {
+                let p = theme.get().palette;
+                let z = zoom.get();
+                s.font_size((11.0 * z).max(6.0)).color(if dimmed.get() {
+                    p.text_muted
+                } else {
+                    p.text_primary
+                })
+            }))
+            .style(move |s| {
+                let p = theme.get().palette;
+                let z = zoom.get();
+                let origin = node_origin(cell, z);
+                let focus = selected_index.get();
+                s.absolute()
+                    .inset_left(origin.x)
+                    .inset_top(origin.y)
+                    .width(NODE_WIDTH * z)
+                    .height(NODE_HEIGHT * z)
+                    .items_center()
+                    .padding_horiz(6.0 * z)
+                    .border_radius(4.0 * z)
+                    .border((if focus == Some(index) { 2.0 } else { 1.0 }) * z.max(0.5))
+                    .border_color(package_color(&color_of))
+                    .background(if focus == Some(index) {
+                        p.accent_dim
+                    } else if hovered.get() {
+                        p.bg_elevated
+                    } else {
+                        p.bg_panel
+                    })
+                    .cursor(floem::style::CursorStyle::Pointer)
+            })
+            .on_click_stop(move |_| {
+                selected.set(Some(path.clone()));
+                open_file.set(Some(workspace_root.get_untracked().join(&path)));
+            })
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(move |s| {
+        let size = graph_size();
+        s.absolute().width(size.width).height(size.height)
+    });
+
+    let surface = stack((edges, nodes))
+        .style(move |s| {
+            let size = graph_size();
+            s.width(size.width).height(size.height)
+        })
+        // Clicking the background clears the selection.
+        .on_click_stop(move |_| selected.set(None));
+
+    let empty_msg = container(
+        label(move || {
+            if scanning.get() {
+                "Building the import graph…".to_string()
+            } else {
+                "No imports between source files found.".to_string()
+            }
+        })
+        .style(move |s| {
+            s.font_size(12.0)
+                .color(theme.get().palette.text_muted)
+                .padding(12.0)
+        }),
+    )
+    .style(move |s| {
+        s.apply_if(visible.with(|g| !g.modules.is_empty()), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    stack((
+        header,
+        empty_msg,
+        scroll(surface)
+            .style(|s| s.flex_grow(1.0).width_full().min_height(0.0))
+            .on_resize(move |rect: Rect| viewport.set(rect.size())),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
@@ -0,0 +1,529 @@
+//! Output panel — the bottom-panel "OUTPUT" tab.
+//!
+//! Shows one channel of the core [`OutputLog`] at a time, picked from the
+//! channel dropdown: Build, LSP, Agent, Extensions and any channel a producer
+//! has written to since. Lines keep the colors of their ANSI escape codes,
+//! and file locations, compiler errors and URLs in them open on Ctrl+Click.
+//! Run Build streams the workspace's build command into Build, and the run
+//! configurations started from the top bar stream into Run.
+
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+use std::time::Duration;
+
+use floem::{
+    action::show_context_menu,
+    ext_event::create_signal_from_channel,
+    menu::{Menu, MenuItem},
+    peniko::Color,
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate, SignalWith},
+    text::{Attrs, AttrsList, FamilyOwned, Style as TextStyle, TextLayout, Weight},
+    views::{container, label, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::notifications::{NotificationCenter, Severity};
+use phazeai_core::output::{self, AnsiColor, Link, OutputLine, OutputLog};
+use phazeai_core::project::{EnvVars, RunConfig, RunConfigs};
+
+use crate::app::{IdeState, Tab};
+use crate::components::links::{linked_line, LinkOpener};
+use crate::components::virtual_list::virtual_list;
+use crate::notifications::{finish_progress, notify, start_progress};
+use crate::panels::git_history::action_button;
+use crate::panels::terminal::indexed_to_color;
+use crate::theme::PhazePalette;
+
+/// A line's height before it is laid out.
+const LINE_HEIGHT: f64 = 18.0;
+
+/// Append `text` to `channel`, one entry per line.
+pub(crate) fn append_output(output: RwSignal<OutputLog>, channel: &str, text: &str) {
+    output.update(|log| log.append(channel, text));
+}
+
+#[derive(Clone)]
+enum BuildEvent {
+    Line(String),
+    Exited(Option<i32>),
+}
+
+/// Run the workspace's build command whenever `trigger` is bumped, streaming
+/// stdout and stderr into the Build channel. `running` is true until the
+/// process exits; a bump while a build is running is ignored. The build shows
+/// as a progress notification until it ends.
+pub(crate) fn start_build_runner(
+    output: RwSignal<OutputLog>,
+    running: RwSignal<bool>,
+    workspace_root: RwSignal<PathBuf>,
+    trigger: RwSignal<u64>,
+    notifications: RwSignal<NotificationCenter>,
+) {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<BuildEvent>(256);
+    let events = create_signal_from_channel(rx);
+    let note = create_rw_signal(None::<u64>);
+
+    create_effect(move |_| match events.get() {
+        Some(BuildEvent::Line(line)) => append_output(output, output::BUILD, &line),
+        Some(BuildEvent::Exited(code)) => {
+            running.set(false);
+            let (severity, message) = match code {
+                Some(0) => (Severity::Success, "Build finished".to_string()),
+                Some(code) => (
+                    Severity::Error,
+                    format!("Build failed with exit code {code}"),
+                ),
+                None => (Severity::Warning, "Build terminated".to_string()),
+            };
+            append_output(
+                output,
+                output::BUILD,
+                &format!("[{}]", message.to_lowercase()),
+            );
+            if let Some(id) = note.get_untracked() {
+                note.set(None);
+                finish_progress(notifications, id, severity, message);
+            }
+        }
+        None => {}
+    });
+
+    create_effect(move |prev: Option<u64>| {
+        let n = trigger.get();
+        // Only bumps run a build, not the first run of the effect.
+        if prev.is_none() || running.get_untracked() {
+            return n;
+        }
+        let root = workspace_root.get_untracked();
+        let Some(command) = output::build_command(&root) else {
+            notify(
+                notifications,
+                Severity::Warning,
+                "No Cargo.toml, package.json, go.mod or Makefile to build",
+            );
+            return n;
+        };
+        output.update(|log| {
+            log.clear(output::BUILD);
+            log.append(output::BUILD, &format!("> {}", command.join(" ")));
+        });
+        let env = EnvVars::for_workspace(&root);
+        let child = Command::new(&command[0])
+            .args(&command[1..])
+            .current_dir(&root)
+            .envs(env.pairs())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                append_output(
+                    output,
+                    output::BUILD,
+                    &format!("[error] could not run {}: {e}", command[0]),
+                );
+                notify(
+                    notifications,
+                    Severity::Error,
+                    format!("Could not run {}: {e}", command[0]),
+                );
+                return n;
+            }
+        };
+        running.set(true);
+        note.set(Some(start_progress(
+            notifications,
+            format!("Running {}", command.join(" ")),
+            None,
+        )));
+        let stdout = child
+            .stdout
+            .take()
+            .map(|s| forward_lines(s, tx.clone(), env.clone()));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|s| forward_lines(s, tx.clone(), env.clone()));
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            for reader in [stdout, stderr].into_iter().flatten() {
+                let _ = reader.join();
+            }
+            let code = child.wait().ok().and_then(|status| status.code());
+            let _ = tx.send(BuildEvent::Exited(code));
+        });
+        n
+    });
+}
+
+/// The run configuration signals of [`IdeState`].
+#[derive(Clone, Copy)]
+pub struct RunConfigSignals {
+    /// Set to a configuration's name to run it after its pre-tasks,
+    /// stopping the one running first.
+    pub request: RwSignal<Option<String>>,
+    /// The configuration running, if one is.
+    pub running: RwSignal<Option<String>>,
+    /// The configuration run last.
+    pub last: RwSignal<Option<String>>,
+    /// Bump to stop the configuration running.
+    pub stop: RwSignal<u64>,
+}
+
+/// Run the configurations of `.phazeai/launch.toml` asked for in
+/// `signals.request`, streaming their output into the Run channel. Each
+/// pre-task has to exit successfully before the next step starts.
+pub(crate) fn start_config_runner(
+    output: RwSignal<OutputLog>,
+    workspace_root: RwSignal<PathBuf>,
+    signals: RunConfigSignals,
+    notifications: RwSignal<NotificationCenter>,
+) {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<BuildEvent>(256);
+    let events = create_signal_from_channel(rx);
+    let stop_flag = create_rw_signal(None::<Arc<AtomicBool>>);
+    // Asked for while another configuration was still stopping.
+    let pending = create_rw_signal(None::<String>);
+
+    let start = move |name: String| {
+        let root = workspace_root.get_untracked();
+        let steps = RunConfigs::load(&root).and_then(|configs| {
+            configs
+                .plan(&name)
+                .map(|plan| plan.into_iter().cloned().collect::<Vec<_>>())
+        });
+        let steps = match steps {
+            Ok(steps) => steps,
+            Err(e) => return notify(notifications, Severity::Error, e.to_string()),
+        };
+        let name = steps.last().map_or(name, |c| c.name.clone());
+        output.update(|log| log.clear(output::RUN));
+        signals.running.set(Some(name.clone()));
+        signals.last.set(Some(name));
+        let stop = Arc::new(AtomicBool::new(false));
+        stop_flag.set(Some(stop.clone()));
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let code = run_steps(&root, &steps, &tx, &stop);
+            let _ = tx.send(BuildEvent::Exited(code));
+        });
+    };
+
+    let start_pending = start.clone();
+    create_effect(move |_| match events.get() {
+        Some(BuildEvent::Line(line)) => append_output(output, output::RUN, &line),
+        Some(BuildEvent::Exited(code)) => {
+            let name = signals.running.get_untracked().unwrap_or_default();
+            signals.running.set(None);
+            stop_flag.set(None);
+            let (severity, message) = match code {
+                Some(0) => (Severity::Success, format!("{name} finished")),
+                Some(code) => (
+                    Severity::Error,
+                    format!("{name} failed with exit code {code}"),
+                ),
+                None => (Severity::Warning, format!("{name} stopped")),
+            };
+            append_output(output, output::RUN, &format!("[{message}]"));
+            match pending.get_untracked() {
+                Some(next) => {
+                    pending.set(None);
+                    start_pending(next);
+                }
+                None => notify(notifications, severity, message),
+            }
+        }
+        None => {}
+    });
+
+    create_effect(move |_| {
+        let Some(name) = signals.request.get() else {
+            return;
+        };
+        signals.request.set(None);
+        match stop_flag.get_untracked() {
+            Some(stop) => {
+                pending.set(Some(name));
+                stop.store(true, Ordering::Relaxed);
+            }
+            None => start(name),
+        }
+    });
+
+    create_effect(move |prev: Option<u64>| {
+        let n = signals.stop.get();
+        if prev.is_some() {
+            pending.set(None);
+            if let Some(stop) = stop_flag.get_untracked() {
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+        n
+    });
+}
+
+/// Run `steps` one after the other until one fails or `stop` is set,
+/// returning the exit code of the last one run (`None` when it was killed).
+fn run_steps(
+    root: &std::path::Path,
+    steps: &[RunConfig],
+    tx: &SyncSender<BuildEvent>,
+    stop: &AtomicBool,
+) -> Option<i32> {
+    let env = EnvVars::for_workspace(root);
+    let mut code = Some(0);
+    for config in steps {
+        let _ = tx.send(BuildEvent::Line(format!(
+            "> [{}] {}",
+            config.name,
+            env.redact(&config.command_line())
+        )));
+        let child = config
+            .command(root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                let _ = tx.send(BuildEvent::Line(format!(
+                    "[error] could not run {}: {e}",
+                    config.command
+                )));
+                return Some(127);
+            }
+        };
+        let stdout = child
+            .stdout
+            .take()
+            .map(|s| forward_lines(s, tx.clone(), env.clone()));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|s| forward_lines(s, tx.clone(), env.clone()));
+        code = loop {
+            if stop.load(Ordering::Relaxed) {
+                let _ = child.kill();
+            }
+            match child.try_wait() {
+                Ok(Some(status)) => break status.code(),
+                Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+                Err(_) => break None,
+            }
+        };
+        // A killed process's children may hold its output open; don't wait
+        // for them.
+        if !stop.load(Ordering::Relaxed) {
+            for reader in [stdout, stderr].into_iter().flatten() {
+                let _ = reader.join();
+            }
+        }
+        if code != Some(0) {
+            break;
+        }
+    }
+    code
+}
+
+/// Send each line read from `stream` until it closes, with the secrets of
+/// `env` masked.
+fn forward_lines(
+    stream: impl Read + Send + 'static,
+    tx: SyncSender<BuildEvent>,
+    env: EnvVars,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if tx.send(BuildEvent::Line(env.redact(&line))).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn ansi_color(color: AnsiColor) -> Color {
+    match color {
+        AnsiColor::Indexed(idx) => indexed_to_color(idx),
+        AnsiColor::Rgb(r, g, b) => Color::from_rgb8(r, g, b),
+    }
+}
+
+/// Default color for lines without escape codes, from the words rustc,
+/// cargo and most tools put in their diagnostics.
+fn line_color(line: &OutputLine, p: &PhazePalette) -> Color {
+    let text = line.text.trim_start();
+    if text.starts_with("[error]") || text.starts_with("error") {
+        p.error
+    } else if text.starts_with("[warn]") || text.starts_with("warning") {
+        p.warning
+    } else {
+        p.text_secondary
+    }
+}
+
+fn line_layout(
+    line: &OutputLine,
+    links: &[Link],
+    p: &PhazePalette,
+    font_size: f32,
+    fonts: &[FamilyOwned],
+) -> TextLayout {
+    let base = Attrs::new()
+        .font_size(font_size)
+        .color(line_color(line, p))
+        .family(fonts);
+    let mut attrs = AttrsList::new(base.clone());
+    for span in &line.spans {
+        let mut span_attrs = base.clone();
+        if let Some(fg) = span.style.fg {
+            span_attrs = span_attrs.color(ansi_color(fg));
+        }
+        if span.style.bold {
+            span_attrs = span_attrs.weight(Weight::BOLD);
+        }
+        if span.style.italic {
+            span_attrs = span_attrs.style(TextStyle::Italic);
+        }
+        attrs.add_span(span.range.clone(), span_attrs);
+    }
+    for link in links {
+        attrs.add_span(link.range().clone(), base.clone().color(p.accent));
+    }
+    // An empty layout has no height; keep blank lines one row tall.
+    let text = if line.text.is_empty() {
+        " "
+    } else {
+        &line.text
+    };
+    let mut layout = TextLayout::new();
+    layout.set_text(text, attrs, None);
+    layout
+}
+
+pub fn output_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let output = state.output;
+    let channel = state.output_channel;
+    let building = state.build_running;
+    let build_run = state.build_run;
+    let opener = LinkOpener::new(state.open_file, state.goto_line, state.workspace_root);
+    let font_family = state.terminal_font_family;
+    let font_size = state.terminal_font_size;
+
+    let pick_channel = move || {
+        let current = channel.get_untracked();
+        let mark = |on: bool| if on { "✓ " } else { "   " };
+        let mut menu = Menu::new("");
+        for name in output.with_untracked(|log| log.channel_names()) {
+            let label = format!("{}{name}", mark(name == current));
+            menu = menu.entry(MenuItem::new(label).action(move || channel.set(name.clone())));
+        }
+        show_context_menu(menu, None);
+    };
+
+    let header = stack((
+        action_button(move || format!("{} ▾", channel.get()), theme, pick_channel),
+        action_button(
+            || "Clear".to_string(),
+            theme,
+            move || output.update(|log| log.clear(&channel.get_untracked())),
+        ),
+        action_button(
+            move || {
+                if building.get() {
+                    "Building…".to_string()
+                } else {
+                    "Run Build".to_string()
+                }
+            },
+            theme,
+            move || {
+                channel.set(output::BUILD.to_string());
+                build_run.update(|n| *n += 1);
+            },
+        ),
+        label(move || {
+            output.with(|log| match log.channel(&channel.get()) {
+                Some(c) if c.dropped() > 0 => {
+                    format!(
+                        "{} lines  ·  {} earlier lines dropped",
+                        c.len(),
+                        c.dropped()
+                    )
+                }
+                Some(c) => format!("{} lines", c.len()),
+                None => String::new(),
+            })
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let rows = virtual_list(
+        move || {
+            let name = channel.get();
+            output.with(|log| {
+                log.channel(&name)
+                    .map(|c| c.lines().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default()
+            })
+        },
+        |line| line.id,
+        move |line: OutputLine| {
+            let links = output::find_links(&line.text);
+            let layout = create_rw_signal(TextLayout::new());
+            {
+                let links = links.clone();
+                create_effect(move |_| {
+                    let p = theme.get().palette;
+                    let fonts = crate::fonts::font_stack(&font_family.get(), false);
+                    layout.set(line_layout(
+                        &line,
+                        &links,
+                        &p,
+                        font_size.get() as f32,
+                        &fonts,
+                    ));
+                });
+            }
+            container(linked_line(layout, links, opener, || None, theme))
+                .style(|s| s.width_full().padding_horiz(12.0).padding_vert(1.0))
+        },
+        LINE_HEIGHT,
+        || None,
+    )
+    .style(|s| s.flex_grow(1.0).width_full());
+
+    let empty_msg = container(
+        label(move || format!("No output in {} yet.", channel.get())).style(move |s| {
+            s.font_size(12.0)
+                .color(theme.get().palette.text_muted)
+                .padding(12.0)
+        }),
+    )
+    .style(move |s| {
+        let empty = output.with(|log| log.channel(&channel.get()).is_none_or(|c| c.is_empty()));
+        s.apply_if(!empty, |s| s.display(floem::style::Display::None))
+    });
+
+    stack((header, empty_msg, rows)).style(|s| s.flex_col().width_full().height_full())
+}
+
+/// Show the Output tab on `channel`.
+pub(crate) fn show_output_channel(state: &IdeState, channel: &str) {
+    state.output_channel.set(channel.to_string());
+    state.bottom_panel_tab.set(Tab::Output);
+    state.show_bottom_panel.set(true);
+}
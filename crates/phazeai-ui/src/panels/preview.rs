@@ -0,0 +1,489 @@
+//! Web preview of a frontend dev server — the bottom-panel "PREVIEW" tab.
+//!
+//! "Open Preview" starts the `phazeai-preview` helper, a webview window
+//! showing the dev server's page at the chosen device size, and drives it
+//! with [`PreviewCommand`]s written to its stdin. With auto-reload on, saving
+//! a file the page is built from reloads it. "Send Screenshot to AI" captures
+//! the page with a headless Chrome at the same size and attaches it to the
+//! next chat message, asking the question typed beside it if there is one.
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::TrySendError;
+use std::sync::Arc;
+use std::time::Duration;
+
+use floem::{
+    event::{Event, EventListener},
+    ext_event::create_signal_from_channel,
+    keyboard::{Key, NamedKey},
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, img, label, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::preview::{self, DevicePreset, COMMON_DEV_PORTS, DEVICE_PRESETS};
+use phazeai_core::project::FileWatcher;
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::panels::git_history::action_button;
+
+/// How often the workspace watcher is drained; saves within one poll
+/// reload the page once.
+const RELOAD_POLL: Duration = Duration::from_millis(300);
+
+/// A command to the preview window, one per line on its stdin. The window
+/// closes when its stdin does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewCommand {
+    Navigate(String),
+    Reload,
+    /// Logical width and height of the page.
+    Resize(u32, u32),
+    Quit,
+}
+
+impl PreviewCommand {
+    pub fn to_line(&self) -> String {
+        match self {
+            PreviewCommand::Navigate(url) => format!("navigate {url}"),
+            PreviewCommand::Reload => "reload".to_string(),
+            PreviewCommand::Resize(w, h) => format!("resize {w} {h}"),
+            PreviewCommand::Quit => "quit".to_string(),
+        }
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (verb, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match verb {
+            "navigate" if !rest.trim().is_empty() => {
+                Some(PreviewCommand::Navigate(rest.trim().to_string()))
+            }
+            "reload" => Some(PreviewCommand::Reload),
+            "resize" => {
+                let (w, h) = rest.trim().split_once(' ')?;
+                Some(PreviewCommand::Resize(
+                    w.parse().ok()?,
+                    h.trim().parse().ok()?,
+                ))
+            }
+            "quit" => Some(PreviewCommand::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// The running helper.
+struct PreviewWindow {
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl PreviewWindow {
+    /// Send `command`; false once the window is gone.
+    fn send(&mut self, command: &PreviewCommand) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+            && writeln!(self.stdin, "{}", command.to_line()).is_ok()
+            && self.stdin.flush().is_ok()
+    }
+}
+
+/// The helper binary, next to the IDE's own when installed together.
+fn helper_path() -> PathBuf {
+    let name = format!("phazeai-preview{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .map(|exe| exe.with_file_name(&name))
+        .filter(|path| path.is_file())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+fn open_window(url: &str, preset: DevicePreset) -> std::io::Result<PreviewWindow> {
+    let mut child = Command::new(helper_path())
+        .args(["--url", url])
+        .args(["--width", &preset.width.to_string()])
+        .args(["--height", &preset.height.to_string()])
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| std::io::Error::other("no stdin for the preview window"))?;
+    Ok(PreviewWindow { child, stdin })
+}
+
+/// Signal `reload` whenever a file the page is built from changes in the
+/// workspace.
+fn watch_sources(reload: std::sync::mpsc::SyncSender<()>, workspace_root: RwSignal<PathBuf>) {
+    let generation = Arc::new(AtomicU64::new(0));
+    create_effect(move |_| {
+        let root = workspace_root.get();
+        let gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
+        let generation = generation.clone();
+        let reload = reload.clone();
+        std::thread::spawn(move || {
+            let Ok(watcher) = FileWatcher::shared(&root) else {
+                return;
+            };
+            let Ok(mut events) = watcher.subscribe(&[]) else {
+                return;
+            };
+            while generation.load(Ordering::Relaxed) == gen {
+                std::thread::sleep(RELOAD_POLL);
+                let mut changed = false;
+                while let Ok(event) = events.try_recv() {
+                    changed |= preview::reloads_preview(&event.path);
+                }
+                // A full channel already has a reload pending.
+                if changed && matches!(reload.try_send(()), Err(TrySendError::Disconnected(_))) {
+                    return;
+                }
+            }
+        });
+    });
+}
+
+pub fn preview_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let toast = state.status_toast;
+    let chat_images = state.chat_images;
+    let chat_inject = state.pending_chat_inject;
+    let show_right_panel = state.show_right_panel;
+
+    let target = create_rw_signal(String::new());
+    let question = create_rw_signal(String::new());
+    let preset = create_rw_signal(0usize);
+    let auto_reload = create_rw_signal(true);
+    let detected: RwSignal<Vec<u16>> = create_rw_signal(Vec::new());
+    let is_open = create_rw_signal(false);
+    let capturing = create_rw_signal(false);
+    let last_shot: RwSignal<Option<PathBuf>> = create_rw_signal(None);
+    let window: Rc<RefCell<Option<PreviewWindow>>> = Rc::new(RefCell::new(None));
+
+    let send = {
+        let window = window.clone();
+        move |command: PreviewCommand| {
+            let mut slot = window.borrow_mut();
+            let sent = slot.as_mut().is_some_and(|w| w.send(&command));
+            if !sent {
+                *slot = None;
+            }
+            is_open.set(sent);
+            sent
+        }
+    };
+
+    // ── Dev server ports, probed whenever the tab opens ─────────────────────
+    let (ports_tx, ports_rx) = std::sync::mpsc::sync_channel::<Vec<u16>>(1);
+    let ports = create_signal_from_channel(ports_rx);
+    create_effect(move |_| {
+        if let Some(found) = ports.get() {
+            if target.get_untracked().trim().is_empty() {
+                if let Some(port) = found.first() {
+                    target.set(port.to_string());
+                }
+            }
+            detected.set(found);
+        }
+    });
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |_| {
+            if shown.get() && tab.get() == Tab::Preview {
+                let tx = ports_tx.clone();
+                std::thread::spawn(move || {
+                    let _ = tx.try_send(preview::listening_ports(COMMON_DEV_PORTS));
+                });
+            }
+        });
+    }
+
+    // ── Auto-reload on save ─────────────────────────────────────────────────
+    let (reload_tx, reload_rx) = std::sync::mpsc::sync_channel::<()>(1);
+    watch_sources(reload_tx, state.workspace_root);
+    let reloads = create_signal_from_channel(reload_rx);
+    {
+        let send = send.clone();
+        create_effect(move |_| {
+            if reloads.get().is_some() && auto_reload.get_untracked() && is_open.get_untracked() {
+                send(PreviewCommand::Reload);
+            }
+        });
+    }
+
+    // ── Screenshots ─────────────────────────────────────────────────────────
+    let (shot_tx, shot_rx) = std::sync::mpsc::sync_channel::<Result<PathBuf, String>>(1);
+    let shots = create_signal_from_channel(shot_rx);
+    create_effect(move |_| {
+        let Some(result) = shots.get() else {
+            return;
+        };
+        capturing.set(false);
+        match result {
+            Ok(path) => {
+                last_shot.set(Some(path.clone()));
+                chat_images.update(|list| list.push(path));
+                show_right_panel.set(true);
+                let ask = question.get_untracked();
+                if ask.trim().is_empty() {
+                    show_toast(toast, "Screenshot attached to your next chat message");
+                } else {
+                    let p = DEVICE_PRESETS[preset.get_untracked()];
+                    chat_inject.set(Some(format!(
+                        "The attached screenshot shows {} at {} size ({}×{}). {}",
+                        preview::preview_url(&target.get_untracked()).unwrap_or_default(),
+                        p.name,
+                        p.width,
+                        p.height,
+                        ask.trim()
+                    )));
+                    question.set(String::new());
+                }
+            }
+            Err(e) => show_toast(toast, format!("Couldn't capture the preview: {e}")),
+        }
+    });
+
+    let capture = move || {
+        if capturing.get_untracked() {
+            return;
+        }
+        let Some(url) = preview::preview_url(&target.get_untracked()) else {
+            show_toast(toast, "Enter a port or URL to preview");
+            return;
+        };
+        let p = DEVICE_PRESETS[preset.get_untracked()];
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let out = std::env::temp_dir().join(format!(
+            "phazeai-preview-{}-{stamp}.png",
+            p.name.to_lowercase()
+        ));
+        capturing.set(true);
+        let tx = shot_tx.clone();
+        std::thread::spawn(move || {
+            let result = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(rt) => rt
+                    .block_on(preview::capture(&url, p, &out))
+                    .map(|_| out)
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(result);
+        });
+    };
+
+    let open = {
+        let window = window.clone();
+        let send = send.clone();
+        move || {
+            let Some(url) = preview::preview_url(&target.get_untracked()) else {
+                show_toast(toast, "Enter a port or URL to preview");
+                return;
+            };
+            let p = DEVICE_PRESETS[preset.get_untracked()];
+            if is_open.get_untracked()
+                && send(PreviewCommand::Navigate(url.clone()))
+                && send(PreviewCommand::Resize(p.width, p.height))
+            {
+                return;
+            }
+            match open_window(&url, p) {
+                Ok(w) => {
+                    *window.borrow_mut() = Some(w);
+                    is_open.set(true);
+                }
+                Err(e) => show_toast(toast, format!("Couldn't open the preview window: {e}")),
+            }
+        }
+    };
+
+    let input_style = move |s: floem::style::Style, width: f64| {
+        let p = theme.get().palette;
+        s.width(width)
+            .font_size(12.0)
+            .background(p.bg_elevated)
+            .border(1.0)
+            .border_color(p.border)
+            .border_radius(3.0)
+            .padding_horiz(6.0)
+            .color(p.text_primary)
+    };
+
+    let address_bar = stack((
+        text_input(target)
+            .placeholder("Port or URL, e.g. 5173")
+            .on_event_cont(EventListener::KeyDown, {
+                let open = open.clone();
+                move |event| {
+                    if let Event::KeyDown(e) = event {
+                        if e.key.logical_key == Key::Named(NamedKey::Enter) {
+                            open();
+                        }
+                    }
+                }
+            })
+            .style(move |s| input_style(s, 220.0)),
+        action_button(
+            move || String::from(if is_open.get() { "Go" } else { "Open Preview" }),
+            theme,
+            open,
+        ),
+        {
+            let send = send.clone();
+            action_button(
+                || "Reload".to_string(),
+                theme,
+                move || {
+                    if !send(PreviewCommand::Reload) {
+                        show_toast(toast, "The preview window isn't open");
+                    }
+                },
+            )
+        },
+        {
+            let window = window.clone();
+            action_button(
+                || "Close".to_string(),
+                theme,
+                move || {
+                    if let Some(mut w) = window.borrow_mut().take() {
+                        w.send(&PreviewCommand::Quit);
+                    }
+                    is_open.set(false);
+                },
+            )
+        },
+        action_button(
+            move || {
+                format!(
+                    "Auto-reload on save: {}",
+                    if auto_reload.get() { "on" } else { "off" }
+                )
+            },
+            theme,
+            move || auto_reload.update(|on| *on = !*on),
+        ),
+        label(move || {
+            let ports = detected.get();
+            if ports.is_empty() {
+                "No dev server found on the usual ports".to_string()
+            } else {
+                let list: Vec<String> = ports.iter().map(|p| p.to_string()).collect();
+                format!("Listening: {}", list.join(", "))
+            }
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| s.items_center().gap(6.0).width_full());
+
+    let presets = dyn_stack(
+        || {
+            DEVICE_PRESETS
+                .iter()
+                .copied()
+                .enumerate()
+                .collect::<Vec<_>>()
+        },
+        |(i, _)| *i,
+        {
+            let send = send.clone();
+            move |(i, p): (usize, DevicePreset)| {
+                let send = send.clone();
+                label(move || format!("{} {}×{}", p.name, p.width, p.height))
+                    .style(move |s| {
+                        let pal = theme.get().palette;
+                        let active = preset.get() == i;
+                        s.font_size(11.0)
+                            .padding_horiz(8.0)
+                            .padding_vert(2.0)
+                            .border(1.0)
+                            .border_radius(3.0)
+                            .cursor(floem::style::CursorStyle::Pointer)
+                            .border_color(if active { pal.accent } else { pal.border })
+                            .color(if active {
+                                pal.accent
+                            } else {
+                                pal.text_secondary
+                            })
+                    })
+                    .on_click_stop(move |_| {
+                        preset.set(i);
+                        if is_open.get_untracked() {
+                            send(PreviewCommand::Resize(p.width, p.height));
+                        }
+                    })
+            }
+        },
+    )
+    .style(|s| s.items_center().gap(6.0));
+
+    let screenshot_bar = stack((
+        text_input(question)
+            .placeholder("What looks wrong? (optional)")
+            .on_event_cont(EventListener::KeyDown, {
+                let capture = capture.clone();
+                move |event| {
+                    if let Event::KeyDown(e) = event {
+                        if e.key.logical_key == Key::Named(NamedKey::Enter) {
+                            capture();
+                        }
+                    }
+                }
+            })
+            .style(move |s| input_style(s, 320.0)),
+        action_button(
+            move || {
+                String::from(if capturing.get() {
+                    "Capturing…"
+                } else {
+                    "Send Screenshot to AI"
+                })
+            },
+            theme,
+            capture,
+        ),
+    ))
+    .style(|s| s.items_center().gap(6.0).width_full());
+
+    let thumbnail = container(img(move || {
+        last_shot
+            .get()
+            .and_then(|path| std::fs::read(path).ok())
+            .unwrap_or_default()
+    }))
+    .style(move |s| {
+        s.height(160.0).apply_if(last_shot.get().is_none(), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    stack((
+        address_bar,
+        presets,
+        screenshot_bar,
+        label(move || {
+            if is_open.get() {
+                "The preview is open in its own window.".to_string()
+            } else {
+                "Start your dev server, then open its port here.".to_string()
+            }
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+        thumbnail,
+    ))
+    .style(|s| {
+        s.flex_col()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(6.0)
+            .width_full()
+            .height_full()
+    })
+}
@@ -0,0 +1,498 @@
+//! CPU profiles — the bottom-panel "PROFILE" tab.
+//!
+//! Runs `cargo flamegraph`, `perf` or `py-spy` on a target from the
+//! workspace root (or opens saved collapsed stacks / `perf script` output)
+//! and draws the samples as a flame graph, callers above callees. Clicking a
+//! frame selects it and looks up its source; "Zoom In" makes it the graph's
+//! root, and "Optimize This" hands the frame, its share of the samples, its
+//! call path and its source to the agent.
+
+use std::sync::Arc;
+
+use floem::{
+    ext_event::create_ext_action,
+    peniko::Color,
+    reactive::{
+        create_memo, create_rw_signal, RwSignal, Scope, SignalGet, SignalUpdate, SignalWith,
+    },
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::profiling::{self, FrameBox, Profile, Profiler, SourceLocation};
+
+use crate::app::{show_toast, IdeState};
+use crate::panels::git_history::action_button;
+use crate::theme::PhazeTheme;
+
+/// Height of a row of frames.
+const ROW_HEIGHT: f64 = 18.0;
+/// Frames narrower than this share of the graph's root aren't drawn.
+const MIN_FRACTION: f64 = 0.002;
+/// Functions listed under "Hottest".
+const MAX_HOTTEST: usize = 15;
+
+/// The classic flame graph warm palette: a stable red-to-yellow color per
+/// function name, so a function keeps its color wherever it's called.
+fn frame_color(name: &str) -> Color {
+    let hash = name
+        .bytes()
+        .fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+    let v = (hash % 1000) as f64 / 1000.0;
+    Color::from_rgb8(
+        205 + (50.0 * v) as u8,
+        (230.0 * ((hash >> 10) % 1000) as f64 / 1000.0) as u8,
+        (55.0 * ((hash >> 20) % 1000) as f64 / 1000.0) as u8,
+    )
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    part as f64 * 100.0 / whole.max(1) as f64
+}
+
+/// A line of the selected frame's details.
+fn detail(text: impl Fn() -> String + 'static, theme: RwSignal<PhazeTheme>) -> impl IntoView {
+    label(text).style(move |s| {
+        s.font_size(11.0)
+            .color(theme.get().palette.text_secondary)
+            .width_full()
+            .min_width(0.0)
+    })
+}
+
+pub fn profile_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let goto_line = state.goto_line;
+    let pending_chat_inject = state.pending_chat_inject;
+    let show_right_panel = state.show_right_panel;
+    let toast = state.status_toast;
+
+    let profiler = create_rw_signal(Profiler::CargoFlamegraph);
+    let target = create_rw_signal(String::new());
+    let profile = create_rw_signal(Arc::new(Profile::default()));
+    // Where the samples came from: the command or the opened file.
+    let origin = create_rw_signal(String::new());
+    let running = create_rw_signal(false);
+    let focus = create_rw_signal(Vec::<usize>::new());
+    let selected = create_rw_signal(None::<Vec<usize>>);
+    // The selected frame's source, once looked up, with the frame it's for.
+    let source = create_rw_signal(None::<(Vec<usize>, Option<SourceLocation>)>);
+
+    // ── Loading ────────────────────────────────────────────────────────────
+    let loaded = move |from: String| {
+        create_ext_action(Scope::current(), move |result: Result<Profile, String>| {
+            running.set(false);
+            match result {
+                Ok(p) => {
+                    profile.set(Arc::new(p));
+                    origin.set(from);
+                    focus.set(Vec::new());
+                    selected.set(None);
+                    source.set(None);
+                }
+                Err(e) => show_toast(toast, format!("Profiling failed: {e}")),
+            }
+        })
+    };
+    let run = move || {
+        let command = target.get_untracked().trim().to_string();
+        if running.get_untracked() {
+            return;
+        }
+        if command.is_empty() {
+            show_toast(toast, "Enter what to profile first");
+            return;
+        }
+        let tool = profiler.get_untracked();
+        running.set(true);
+        let root = workspace_root.get_untracked();
+        let on_done = loaded(format!("{} {command}", tool.label()));
+        std::thread::spawn(move || {
+            on_done(profiling::run_profile(&root, tool, &command).map_err(|e| e.to_string()));
+        });
+    };
+    let open = move || {
+        if running.get_untracked() {
+            return;
+        }
+        let Some(file) = rfd::FileDialog::new()
+            .add_filter(
+                "Collapsed stacks or perf script",
+                &["folded", "txt", "collapsed", "perf"],
+            )
+            .add_filter("All files", &["*"])
+            .pick_file()
+        else {
+            return;
+        };
+        running.set(true);
+        let name = file
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let on_done = loaded(name);
+        std::thread::spawn(move || {
+            on_done(profiling::load_profile(&file).map_err(|e| e.to_string()));
+        });
+    };
+
+    // Selecting a frame looks its source up off the UI thread.
+    let select = move |path: Vec<usize>| {
+        selected.set(Some(path.clone()));
+        let Some(name) = profile.with_untracked(|p| p.node(&path).map(|n| n.name.clone())) else {
+            return;
+        };
+        let root = workspace_root.get_untracked();
+        let on_found = create_ext_action(Scope::current(), move |found: Option<SourceLocation>| {
+            if selected.get_untracked().as_ref() == Some(&path) {
+                source.set(Some((path, found)));
+            }
+        });
+        std::thread::spawn(move || on_found(profiling::locate_frame(&root, &name)));
+    };
+    let open_source = move |location: &SourceLocation| {
+        open_file.set(Some(workspace_root.get_untracked().join(&location.path)));
+        goto_line.set(location.line as u32);
+    };
+    let optimize = move || {
+        let Some(path) = selected.get_untracked() else {
+            show_toast(toast, "Select a frame to optimize");
+            return;
+        };
+        let found = source
+            .get_untracked()
+            .filter(|(p, _)| *p == path)
+            .and_then(|(_, found)| found);
+        let prompt =
+            profile.with_untracked(|p| profiling::optimize_prompt(p, &path, found.as_ref()));
+        if let Some(prompt) = prompt {
+            pending_chat_inject.set(Some(prompt));
+            show_right_panel.set(true);
+        }
+    };
+
+    // ── Header ─────────────────────────────────────────────────────────────
+    let profilers = Profiler::ALL.map(|tool| {
+        action_button(
+            move || {
+                let mark = if profiler.get() == tool { "✓ " } else { "" };
+                format!("{mark}{}", tool.label())
+            },
+            theme,
+            move || profiler.set(tool),
+        )
+    });
+    let [cargo_flamegraph, perf, py_spy] = profilers;
+    let header = stack((
+        cargo_flamegraph,
+        perf,
+        py_spy,
+        text_input(target)
+            .placeholder("What to profile")
+            .on_event_stop(floem::event::EventListener::KeyDown, move |event| {
+                if let floem::event::Event::KeyDown(ke) = event {
+                    use floem::keyboard::{Key, NamedKey};
+                    if ke.key.logical_key == Key::Named(NamedKey::Enter) {
+                        run();
+                    }
+                }
+            })
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width(260.0)
+                    .font_size(12.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(3.0)
+                    .padding_horiz(6.0)
+                    .color(p.text_primary)
+            }),
+        action_button(
+            move || {
+                if running.get() {
+                    "Profiling…".to_string()
+                } else {
+                    "Run Profile".to_string()
+                }
+            },
+            theme,
+            run,
+        ),
+        label(move || format!("e.g. {}", profiler.get().target_hint()))
+            .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+        action_button(|| "Open Profile…".to_string(), theme, open),
+        label(move || {
+            let total = profile.with(|p| p.total());
+            if total == 0 {
+                String::new()
+            } else {
+                format!("{total} samples  ·  {}", origin.get())
+            }
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    // ── Flame graph ────────────────────────────────────────────────────────
+    let layout = create_memo(move |_| {
+        let focus = focus.get();
+        profile.with(|p| {
+            let total = p.node(&focus).map_or(0, |n| n.total);
+            (p.layout(&focus, MIN_FRACTION), total)
+        })
+    });
+    let graph_height = move || {
+        layout.with(|(boxes, _)| boxes.iter().map(|b| b.depth + 1).max().unwrap_or(0)) as f64
+            * ROW_HEIGHT
+    };
+    let frames = dyn_stack(
+        move || layout.get().0,
+        |b| b.path.clone(),
+        move |frame: FrameBox| {
+            let hovered = create_rw_signal(false);
+            let whole = layout.with_untracked(|(_, total)| *total);
+            let left = percent(frame.start, whole);
+            let width = percent(frame.total, whole);
+            let color = frame_color(&frame.name);
+            let name = frame.name.clone();
+            let path = frame.path.clone();
+            let is_selected = {
+                let path = path.clone();
+                create_memo(move |_| selected.get().as_ref() == Some(&path))
+            };
+            container(label(move || name.clone()).style(|s| {
+                s.font_size(10.0)
+                    .color(Color::from_rgb8(30, 20, 10))
+                    .min_width(0.0)
+                    .text_ellipsis()
+            }))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.absolute()
+                    .inset_left_pct(left)
+                    .inset_top(frame.depth as f64 * ROW_HEIGHT)
+                    .width_pct(width)
+                    .height(ROW_HEIGHT - 1.0)
+                    .items_center()
+                    .padding_horiz(3.0)
+                    .border_radius(2.0)
+                    .background(if hovered.get() {
+                        color.with_alpha(0.75)
+                    } else {
+                        color
+                    })
+                    .apply_if(is_selected.get(), |s| s.border(2.0).border_color(p.accent))
+                    .cursor(floem::style::CursorStyle::Pointer)
+            })
+            .on_click_stop(move |_| select(path.clone()))
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(move |s| s.width_full().height(graph_height()).margin_horiz(10.0));
+
+    let empty_msg = label(move || {
+        if running.get() {
+            "Profiling… the graph appears when the target exits.".to_string()
+        } else {
+            "Pick a profiler, enter the target and Run Profile, or open saved collapsed stacks \
+             or perf script output."
+                .to_string()
+        }
+    })
+    .style(move |s| {
+        s.font_size(12.0)
+            .color(theme.get().palette.text_muted)
+            .padding(12.0)
+            .apply_if(profile.with(|p| p.total() > 0), |s| {
+                s.display(floem::style::Display::None)
+            })
+    });
+
+    // ── Selected frame ─────────────────────────────────────────────────────
+    let selected_node = move || {
+        let path = selected.get()?;
+        profile.with(|p| {
+            p.node(&path)
+                .map(|n| (n.name.clone(), n.total, n.self_samples, p.total()))
+        })
+    };
+    let selected_source = move || {
+        let path = selected.get()?;
+        source.get().filter(|(p, _)| *p == path)
+    };
+    let details = stack((
+        label(move || {
+            selected_node()
+                .map(|(name, ..)| name)
+                .unwrap_or_else(|| "Click a frame to select it.".to_string())
+        })
+        .style(move |s| {
+            s.font_size(12.0)
+                .color(theme.get().palette.text_primary)
+                .width_full()
+                .min_width(0.0)
+        }),
+        detail(
+            move || match selected_node() {
+                Some((_, total, own, all)) => format!(
+                    "{:.1}% of samples, {:.1}% in the frame itself",
+                    percent(total, all),
+                    percent(own, all)
+                ),
+                None => String::new(),
+            },
+            theme,
+        ),
+        detail(
+            move || match (selected.get(), selected_source()) {
+                (None, _) => String::new(),
+                (Some(_), None) => "Looking up the source…".to_string(),
+                (Some(_), Some((_, None))) => "Not in the workspace".to_string(),
+                (Some(_), Some((_, Some(l)))) => {
+                    format!("{}:{}", l.path.display(), l.line)
+                }
+            },
+            theme,
+        ),
+        stack((
+            action_button(
+                || "Zoom In".to_string(),
+                theme,
+                move || {
+                    if let Some(path) = selected.get_untracked() {
+                        focus.set(path);
+                    }
+                },
+            ),
+            action_button(
+                || "Reset Zoom".to_string(),
+                theme,
+                move || focus.set(Vec::new()),
+            ),
+            action_button(
+                || "Open Source".to_string(),
+                theme,
+                move || match selected_source() {
+                    Some((_, Some(location))) => open_source(&location),
+                    _ => show_toast(toast, "No workspace source for this frame"),
+                },
+            ),
+            action_button(|| "Optimize This".to_string(), theme, optimize),
+        ))
+        .style(|s| {
+            s.gap(6.0)
+                .padding_top(4.0)
+                .flex_wrap(floem::style::FlexWrap::Wrap)
+        }),
+    ))
+    .style(|s| s.flex_col().gap(2.0).padding(10.0).width_full());
+
+    let hottest = dyn_stack(
+        move || {
+            profile.with(|p| {
+                let total = p.total();
+                p.hottest(MAX_HOTTEST)
+                    .into_iter()
+                    .map(move |f| (f, total))
+                    .collect::<Vec<_>>()
+            })
+        },
+        |(f, _)| f.name.clone(),
+        move |(f, total)| {
+            let hovered = create_rw_signal(false);
+            let share = format!("{:.1}%", percent(f.self_samples, total));
+            let name = f.name.clone();
+            stack((
+                label(move || share.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .width(48.0)
+                        .color(theme.get().palette.warning)
+                }),
+                label(move || name.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .text_ellipsis()
+                        .color(theme.get().palette.text_primary)
+                }),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(6.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(1.0)
+                    .width_full()
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(hovered.get(), |s| s.background(p.bg_elevated))
+            })
+            .on_click_stop(move |_| {
+                let root = workspace_root.get_untracked();
+                let frame = f.name.clone();
+                let on_found =
+                    create_ext_action(Scope::current(), move |found: Option<SourceLocation>| {
+                        match found {
+                            Some(location) => open_source(&location),
+                            None => show_toast(toast, "No workspace source for this function"),
+                        }
+                    });
+                std::thread::spawn(move || on_found(profiling::locate_frame(&root, &frame)));
+            })
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let sidebar = stack((
+        details,
+        label(|| "HOTTEST (SELF TIME)".to_string()).style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(11.0)
+                .color(p.text_secondary)
+                .padding_horiz(10.0)
+                .padding_vert(3.0)
+                .width_full()
+                .border_bottom(1.0)
+                .border_color(p.border)
+        }),
+        scroll(hottest).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(move |s| {
+        let p = theme.get().palette;
+        s.flex_col()
+            .width(320.0)
+            .height_full()
+            .border_left(1.0)
+            .border_color(p.border)
+    });
+
+    let graph = stack((
+        empty_msg,
+        scroll(frames).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0).height_full());
+
+    stack((
+        header,
+        stack((graph, sidebar)).style(|s| s.flex_grow(1.0).width_full().min_height(0.0)),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
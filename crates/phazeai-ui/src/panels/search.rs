@@ -1,12 +1,46 @@
+//! The Search panel — workspace text search, multi-file replace and
+//! semantic search.
+//!
+//! Text search runs the core workspace search on a worker thread as the query
+//! or options change, after a short pause in typing. Results stream into
+//! `search_results` in batches while the search runs, and a newer query
+//! cancels the search still running for the old one. Matches are highlighted
+//! within their lines, and the context toggle shows the lines around them.
+
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, SyncSender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use floem::{
-    ext_event::create_ext_action,
-    reactive::{create_memo, create_rw_signal, RwSignal, Scope, SignalGet, SignalUpdate},
-    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    ext_event::{create_ext_action, create_signal_from_channel},
+    reactive::{
+        create_effect, create_memo, create_rw_signal, RwSignal, Scope, SignalGet, SignalUpdate,
+    },
+    text::{Attrs, AttrsList, TextLayout, Weight},
+    views::{container, dyn_stack, label, rich_text, stack, text_input, Decorators},
     IntoView,
 };
+use phazeai_core::project::search::{self, FileMatches, SearchOptions, MAX_MATCHES};
+use regex::Regex;
 
 use crate::app::{IdeState, SearchResult};
-use crate::util::{safe_get, safe_get_memo};
+use crate::components::virtual_list::virtual_list;
+use crate::theme::{PhazePalette, PhazeTheme};
+use crate::util::{focus_ring, list_nav_key_opt, safe_get, safe_get_memo, ListNav};
+
+/// Lines shown before and after each match when context is on.
+const CONTEXT_LINES: usize = 2;
+/// A search starts once typing has paused this long.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(250);
+/// Results found are handed to the panel at most this often.
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+/// A result row's height before it is laid out.
+const RESULT_ROW_HEIGHT: f64 = 22.0;
 
 /// The search panel — workspace search + multi-file replace.
 pub fn search_panel(state: IdeState) -> impl IntoView {
@@ -27,6 +61,30 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
     let open_editors_only = create_rw_signal(false);
     let include_glob = create_rw_signal(String::new());
     let exclude_glob = create_rw_signal(String::new());
+    let show_context = create_rw_signal(false);
+    let search_error: RwSignal<Option<String>> = create_rw_signal(None);
+    // Bumped by Enter to search again with an unchanged query.
+    let search_nonce = create_rw_signal(0u64);
+
+    {
+        let state = state.clone();
+        start_search(results, is_searching, search_error, move || {
+            let _ = search_nonce.get();
+            SearchRequest {
+                root: state.workspace_root.get(),
+                query: query.get(),
+                options: SearchOptions {
+                    regex: use_regex.get(),
+                    case_sensitive: case_sensitive.get(),
+                    whole_word: whole_word.get(),
+                    context: if show_context.get() { CONTEXT_LINES } else { 0 },
+                    include: SearchOptions::globs(&include_glob.get()),
+                    exclude: SearchOptions::globs(&exclude_glob.get()),
+                },
+                open_only: open_editors_only.get().then(|| state.open_tabs.get()),
+            }
+        });
+    }
 
     // Tree view toggle and keyboard selection state
     let tree_view: RwSignal<bool> = create_rw_signal(false);
@@ -106,7 +164,8 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
             String::new()
         } else {
             let files: std::collections::HashSet<_> = r.iter().map(|x| &x.path).collect();
-            format!("{} results in {} files", r.len(), files.len())
+            let more = if r.len() >= MAX_MATCHES { "+" } else { "" };
+            format!("{}{more} results in {} files", r.len(), files.len())
         }
     });
     let count_label = label(move || match_count.get()).style(move |s| {
@@ -233,6 +292,34 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
             })
     };
 
+    let opt_context = {
+        let t = theme;
+        container(label(|| "≡"))
+            .style(move |s| {
+                let p = t.get().palette;
+                s.font_size(11.0)
+                    .padding_horiz(6.0)
+                    .padding_vert(2.0)
+                    .border_radius(3.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .color(if show_context.get() {
+                        p.bg_base
+                    } else {
+                        p.text_muted
+                    })
+                    .background(if show_context.get() {
+                        p.accent
+                    } else {
+                        p.bg_elevated
+                    })
+                    .border(1.0)
+                    .border_color(p.border)
+            })
+            .on_click_stop(move |_| {
+                show_context.update(|v| *v = !*v);
+            })
+    };
+
     // ── Search input ──────────────────────────────────────────────────────────
     let search_bar = {
         let state2 = state.clone();
@@ -268,18 +355,7 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                                         });
                                         history_idx.set(None);
                                     }
-                                    let root = state2.workspace_root.get();
-                                    perform_search(
-                                        state2.clone(),
-                                        is_searching,
-                                        root,
-                                        use_regex,
-                                        case_sensitive,
-                                        whole_word,
-                                        open_editors_only,
-                                        include_glob,
-                                        exclude_glob,
-                                    );
+                                    search_nonce.update(|n| *n += 1);
                                     if !q.trim().is_empty() {
                                         state2.sidecar_query.set(q.trim().to_string());
                                         state2.sidecar_search_nonce.update(|n| *n += 1);
@@ -324,6 +400,7 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                 opt_case,
                 opt_word,
                 opt_open_only,
+                opt_context,
             ))
             .style(|s| s.flex_row().items_center().gap(4.0).width_full()),
         )
@@ -452,7 +529,7 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                         .collect::<Vec<_>>()
                 },
                 |(i, _)| *i,
-                move |(_, (file, snippet))| {
+                move |(_, (file, line, snippet))| {
                     let file_path = std::path::PathBuf::from(file.clone());
                     let file_label = file_path
                         .file_name()
@@ -487,7 +564,7 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                     })
                     .on_click_stop(move |_| {
                         semantic_state2.open_file.set(Some(file_path.clone()));
-                        semantic_state2.goto_line.set(1);
+                        semantic_state2.goto_line.set(line);
                     })
                     .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
                         hovered.set(true);
@@ -584,14 +661,14 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
     // ── Flat results list (with keyboard selection highlighting) ──────────────
     let flat_results_view = {
         let state_flat = state.clone();
-        dyn_stack(
+        virtual_list(
             move || {
                 safe_get(results, Vec::new())
                     .into_iter()
                     .enumerate()
                     .collect::<Vec<_>>()
             },
-            |(i, _)| *i,
+            |(i, r)| (*i, r.path.clone(), r.line),
             move |(i, r)| {
                 let path_str = r
                     .path
@@ -601,20 +678,21 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                 let is_selected = move || selected_idx.get() == Some(i);
                 let path = r.path.clone();
                 let line = r.line;
-                let content_text = r.content.trim().to_string();
                 let s = state_flat.clone();
                 container(
                     stack((
-                        label(move || format!("{}:{}", path_str, r.line + 1)).style(move |s| {
-                            let p = theme.get().palette;
-                            s.font_size(10.0).color(p.accent).padding_right(6.0)
-                        }),
-                        label(move || content_text.clone()).style(move |s| {
-                            let p = theme.get().palette;
-                            s.font_size(11.0).color(p.text_primary).flex_grow(1.0)
-                        }),
+                        context_label(&r.before, line - r.before.len(), theme),
+                        stack((
+                            label(move || format!("{}:{}", path_str, line)).style(move |s| {
+                                let p = theme.get().palette;
+                                s.font_size(10.0).color(p.accent).padding_right(6.0)
+                            }),
+                            match_line(r.content.clone(), r.ranges.clone(), 11.0, theme),
+                        ))
+                        .style(|s| s.flex_row().items_center()),
+                        context_label(&r.after, line + 1, theme),
                     ))
-                    .style(|s| s.flex_row().items_center()),
+                    .style(|s| s.flex_col()),
                 )
                 .style(move |s| {
                     let p = theme.get().palette;
@@ -631,8 +709,15 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                     s.goto_line.set(line as u32);
                 })
             },
+            RESULT_ROW_HEIGHT,
+            move || selected_idx.get(),
         )
-        .style(|s: floem::style::Style| s.flex_col().width_full())
+        .style(move |s| {
+            s.flex_grow(1.0)
+                .min_height(0.0)
+                .width_full()
+                .apply_if(tree_view.get(), |s| s.display(floem::style::Display::None))
+        })
     };
 
     // ── Tree results list (grouped by file) ───────────────────────────────────
@@ -649,7 +734,7 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
 
     let tree_results_view = {
         let state4 = state.clone();
-        dyn_stack(
+        virtual_list(
             move || {
                 // Flatten: for each file group, emit a header item then each match item.
                 let mut items: Vec<(String, Option<SearchResult>)> = Vec::new();
@@ -709,24 +794,24 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                         // Match result row (indented)
                         let path = res.path.clone();
                         let line = res.line;
-                        let content = res.content.trim().to_string();
                         let hovered = create_rw_signal(false);
                         let s = state5.clone();
 
                         container(
                             stack((
-                                label(move || format!("  L{line}: ")).style(move |s| {
-                                    s.font_size(11.0)
-                                        .color(theme.get().palette.text_muted)
-                                        .min_width(50.0)
-                                }),
-                                label(move || content.clone()).style(move |s| {
-                                    s.font_size(12.0)
-                                        .color(theme.get().palette.text_primary)
-                                        .flex_grow(1.0)
-                                }),
+                                context_label(&res.before, line - res.before.len(), theme),
+                                stack((
+                                    label(move || format!("  L{line}: ")).style(move |s| {
+                                        s.font_size(11.0)
+                                            .color(theme.get().palette.text_muted)
+                                            .min_width(50.0)
+                                    }),
+                                    match_line(res.content, res.ranges, 12.0, theme),
+                                ))
+                                .style(|s| s.items_center()),
+                                context_label(&res.after, line + 1, theme),
                             ))
-                            .style(|s| s.items_center()),
+                            .style(|s| s.flex_col()),
                         )
                         .style(move |_s| {
                             let p = theme.get().palette;
@@ -757,13 +842,22 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
                     }
                 }
             },
+            RESULT_ROW_HEIGHT,
+            || None,
         )
-        .style(|s: floem::style::Style| s.flex_col().width_full())
+        .style(move |s| {
+            s.flex_grow(1.0)
+                .min_height(0.0)
+                .width_full()
+                .apply_if(!tree_view.get(), |s| s.display(floem::style::Display::None))
+        })
     };
 
     // ── Status / searching label ──────────────────────────────────────────────
     let searching_label = label(move || {
-        if is_searching.get() {
+        if let Some(err) = search_error.get() {
+            err
+        } else if is_searching.get() && results.get().is_empty() {
             "Searching...".to_string()
         } else if results.get().is_empty() && !query.get().is_empty() {
             "No results found.".to_string()
@@ -777,69 +871,50 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
         s.font_size(12.0)
             .color(theme.get().palette.text_muted)
             .padding(12.0)
-            .apply_if(!results.get().is_empty() && !is_searching.get(), |s| {
+            .apply_if(!results.get().is_empty(), |s| {
                 s.display(floem::style::Display::None)
             })
     });
 
-    // ── Combine flat + tree into a conditional container ─────────────────────
-    // We wrap both in a container and show/hide based on tree_view signal.
-    let flat_container =
-        container(stack((flat_results_view, searching_label)).style(|s| s.flex_col().width_full()))
-            .style(move |s| {
-                s.flex_col()
-                    .width_full()
-                    .apply_if(tree_view.get(), |s| s.display(floem::style::Display::None))
-            });
-
-    let tree_container = container(tree_results_view).style(move |s| {
-        s.flex_col()
-            .width_full()
-            .apply_if(!tree_view.get(), |s| s.display(floem::style::Display::None))
-    });
-
-    let results_inner =
-        scroll(stack((flat_container, tree_container)).style(|s| s.flex_col().width_full()))
-            .style(|s| s.flex_grow(1.0).min_height(0.0).width_full())
-            .keyboard_navigable();
+    // ── Flat or tree results, whichever tree_view picks ──────────────────────
+    let results_inner = stack((searching_label, flat_results_view, tree_results_view))
+        .style(|s| s.flex_col().flex_grow(1.0).min_height(0.0).width_full());
 
     // ── Keyboard navigation wrapper ───────────────────────────────────────────
-    let results_area = container(results_inner)
+    let results_area = container(results_inner);
+    let results_area_id = results_area.id();
+    let results_area = results_area
+        .on_event_cont(floem::event::EventListener::PointerDown, move |_| {
+            results_area_id.request_focus()
+        })
         .on_event_stop(floem::event::EventListener::KeyDown, move |event| {
-            if let floem::event::Event::KeyDown(e) = event {
-                use floem::keyboard::NamedKey;
-                let total = results.get().len();
-                match e.key.logical_key {
-                    floem::keyboard::Key::Named(NamedKey::ArrowDown) => {
-                        selected_idx.update(|i| {
-                            *i = Some(match *i {
-                                None => 0,
-                                Some(n) => (n + 1).min(total.saturating_sub(1)),
-                            });
-                        });
-                    }
-                    floem::keyboard::Key::Named(NamedKey::ArrowUp) => {
-                        selected_idx.update(|i| {
-                            *i = Some(match *i {
-                                None => 0,
-                                Some(n) => n.saturating_sub(1),
-                            });
-                        });
-                    }
-                    floem::keyboard::Key::Named(NamedKey::Enter) => {
-                        if let Some(idx) = selected_idx.get() {
-                            if let Some(r) = results.get().get(idx).cloned() {
-                                state.open_file.set(Some(r.path.clone()));
-                                state.goto_line.set(r.line as u32 + 1);
-                            }
-                        }
+            let floem::event::Event::KeyDown(e) = event else {
+                return;
+            };
+            let floem::keyboard::Key::Named(ref named) = e.key.logical_key else {
+                return;
+            };
+            let total = results.get().len();
+            match list_nav_key_opt(named, selected_idx.get(), total) {
+                ListNav::Move(i) => selected_idx.set(Some(i)),
+                ListNav::Accept(i) => {
+                    if let Some(r) = results.get().get(i).cloned() {
+                        state.open_file.set(Some(r.path.clone()));
+                        state.goto_line.set(r.line as u32);
                     }
-                    _ => {}
                 }
+                ListNav::Close => selected_idx.set(None),
+                ListNav::None => {}
             }
         })
         .keyboard_navigable()
-        .style(|s| s.flex_grow(1.0).min_height(0.0).width_full());
+        .style(move |s| {
+            let t = theme.get();
+            s.flex_grow(1.0)
+                .min_height(0.0)
+                .width_full()
+                .apply(focus_ring(&t.palette))
+        });
 
     stack((
         header,
@@ -861,151 +936,194 @@ pub fn search_panel(state: IdeState) -> impl IntoView {
     })
 }
 
-#[allow(clippy::too_many_arguments)]
-fn perform_search(
-    state: IdeState,
-    is_searching: RwSignal<bool>,
-    root: std::path::PathBuf,
-    use_regex: RwSignal<bool>,
-    case_sensitive: RwSignal<bool>,
-    whole_word: RwSignal<bool>,
-    open_editors_only: RwSignal<bool>,
-    include_glob: RwSignal<String>,
-    exclude_glob: RwSignal<String>,
-) {
-    let query = state.search_query.get();
-    if query.is_empty() {
-        return;
-    }
+/// What a search looks for and where.
+struct SearchRequest {
+    root: PathBuf,
+    query: String,
+    options: SearchOptions,
+    /// Only files open in the editor, when that filter is on.
+    open_only: Option<Vec<PathBuf>>,
+}
 
-    is_searching.set(true);
-    state.search_results.set(vec![]);
+/// Results of search `gen` found since the last batch; `done` on the last.
+#[derive(Clone)]
+struct SearchBatch {
+    gen: u64,
+    files: Vec<FileMatches>,
+    done: bool,
+}
 
-    let regex = use_regex.get();
-    let case_sens = case_sensitive.get();
-    let word = whole_word.get();
-    let open_only = open_editors_only.get();
-    let include = include_glob.get();
-    let exclude = exclude_glob.get();
-    // Capture open tabs for filtering (only if open_only is enabled)
-    let open_tab_paths: Vec<std::path::PathBuf> = if open_only {
-        state.open_tabs.get()
-    } else {
-        vec![]
-    };
-    let (tx, rx) = std::sync::mpsc::channel();
+/// Search again whenever `request` changes, cancelling the search still
+/// running, and stream what is found into `results`.
+fn start_search(
+    results: RwSignal<Vec<SearchResult>>,
+    is_searching: RwSignal<bool>,
+    error: RwSignal<Option<String>>,
+    request: impl Fn() -> SearchRequest + 'static,
+) {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<SearchBatch>(16);
+    let batches = create_signal_from_channel(rx);
+    let generation = Rc::new(Cell::new(0u64));
+    let cancel = Rc::new(RefCell::new(Arc::new(AtomicBool::new(false))));
 
-    std::thread::spawn(move || {
-        let mut found = Vec::new();
+    {
+        let generation = generation.clone();
+        create_effect(move |_| {
+            let Some(batch) = batches.get() else {
+                return;
+            };
+            // Results of a cancelled search still in flight.
+            if batch.gen != generation.get() {
+                return;
+            }
+            if !batch.files.is_empty() {
+                results.update(|list| {
+                    for file in batch.files {
+                        list.extend(file.matches.into_iter().map(|m| SearchResult {
+                            path: file.path.clone(),
+                            line: m.line,
+                            content: m.text,
+                            ranges: m.ranges,
+                            before: m.before,
+                            after: m.after,
+                        }));
+                    }
+                    list.truncate(MAX_MATCHES);
+                });
+            }
+            if batch.done {
+                is_searching.set(false);
+            }
+        });
+    }
 
-        let mut rg_args = vec![
-            "--line-number".to_string(),
-            "--no-heading".to_string(),
-            "--color=never".to_string(),
-        ];
-        if !case_sens {
-            rg_args.push("--ignore-case".to_string());
-        }
-        if word {
-            rg_args.push("--word-regexp".to_string());
-        }
-        if !regex {
-            rg_args.push("--fixed-strings".to_string());
-        }
-        // Apply glob filters
-        for part in include
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-        {
-            rg_args.push("--glob".to_string());
-            rg_args.push(part.to_string());
-        }
-        for part in exclude
-            .split(',')
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty())
-        {
-            rg_args.push("--glob".to_string());
-            rg_args.push(format!("!{part}"));
+    create_effect(move |_| {
+        let request = request();
+        cancel.borrow().store(true, Ordering::Relaxed);
+        let token = Arc::new(AtomicBool::new(false));
+        *cancel.borrow_mut() = token.clone();
+        generation.set(generation.get() + 1);
+        results.set(Vec::new());
+        error.set(None);
+        is_searching.set(false);
+        if request.query.is_empty() {
+            return;
         }
-        rg_args.push("-e".to_string());
-        rg_args.push(query.clone());
+        let re = match request.options.matcher(&request.query) {
+            Ok(re) => re,
+            Err(e) => {
+                error.set(Some(format!("Invalid regex: {e}")));
+                return;
+            }
+        };
+        is_searching.set(true);
+        let (gen, tx) = (generation.get(), tx.clone());
+        std::thread::spawn(move || run_search(request, re, token, gen, tx));
+    });
+}
 
-        let rg_result = std::process::Command::new("rg")
-            .args(&rg_args)
-            .current_dir(&root)
-            .output();
+/// Run one search, sending its results in batches of [`BATCH_INTERVAL`].
+/// Gives up without a word once `cancel` is set.
+fn run_search(
+    request: SearchRequest,
+    re: Regex,
+    cancel: Arc<AtomicBool>,
+    gen: u64,
+    tx: SyncSender<SearchBatch>,
+) {
+    std::thread::sleep(SEARCH_DEBOUNCE);
+    if cancel.load(Ordering::Relaxed) {
+        return;
+    }
+    let (file_tx, file_rx) = std::sync::mpsc::channel();
+    {
+        let cancel = cancel.clone();
+        let (root, options) = (request.root, request.options);
+        std::thread::spawn(move || {
+            search::search_workspace(&root, &re, &options, &cancel, file_tx);
+        });
+    }
 
-        if let Ok(output) = rg_result {
-            let text = String::from_utf8_lossy(&output.stdout);
-            for line in text.lines().take(500) {
-                let parts: Vec<&str> = line.splitn(3, ':').collect();
-                if parts.len() == 3 {
-                    if let Ok(line_num) = parts[1].parse::<usize>() {
-                        found.push(SearchResult {
-                            path: root.join(parts[0]),
-                            line: line_num,
-                            content: parts[2].to_string(),
-                        });
-                    }
-                }
-            }
-        } else {
-            // Fallback: walkdir + string search
-            for entry in walkdir::WalkDir::new(&root)
-                .into_iter()
-                .filter_entry(|e| {
-                    let n = e.file_name().to_string_lossy();
-                    !n.starts_with('.') && n != "target" && n != "node_modules" && n != ".git"
-                })
-                .flatten()
-            {
-                if !entry.file_type().is_file() {
-                    continue;
-                }
-                if let Ok(content) = std::fs::read_to_string(entry.path()) {
-                    for (i, line_text) in content.lines().enumerate() {
-                        let matches = if case_sens {
-                            line_text.contains(&query)
-                        } else {
-                            line_text.to_lowercase().contains(&query.to_lowercase())
-                        };
-                        if matches {
-                            found.push(SearchResult {
-                                path: entry.path().to_path_buf(),
-                                line: i + 1,
-                                content: line_text.to_string(),
-                            });
-                            if found.len() >= 500 {
-                                break;
-                            }
-                        }
-                    }
-                }
-                if found.len() >= 500 {
-                    break;
+    let mut files = Vec::new();
+    let mut last_sent = Instant::now();
+    loop {
+        match file_rx.recv_timeout(BATCH_INTERVAL) {
+            Ok(file) => {
+                let wanted = request
+                    .open_only
+                    .as_ref()
+                    .is_none_or(|open| open.is_empty() || open.contains(&file.path));
+                if wanted {
+                    files.push(file);
                 }
             }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if cancel.load(Ordering::Relaxed) {
+            return;
         }
-        // Filter to open editors only if requested
-        if open_only && !open_tab_paths.is_empty() {
-            found.retain(|r| open_tab_paths.contains(&r.path));
+        if !files.is_empty() && last_sent.elapsed() >= BATCH_INTERVAL {
+            let batch = SearchBatch {
+                gen,
+                files: std::mem::take(&mut files),
+                done: false,
+            };
+            if tx.send(batch).is_err() {
+                return;
+            }
+            last_sent = Instant::now();
         }
-        let _ = tx.send(found);
+    }
+    let _ = tx.send(SearchBatch {
+        gen,
+        files,
+        done: true,
     });
+}
 
-    let results_sig = state.search_results;
-    // create_ext_action avoids leaking a new signal+effect on every search invocation
-    let on_results = create_ext_action(Scope::current(), move |found: Vec<SearchResult>| {
-        results_sig.set(found);
-        is_searching.set(false);
-    });
-    std::thread::spawn(move || {
-        if let Ok(found) = rx.recv() {
-            on_results(found);
+/// A result line, trimmed, with its matches in bold accent.
+fn match_layout(text: &str, ranges: &[Range<usize>], p: &PhazePalette, size: f32) -> TextLayout {
+    let start = text.len() - text.trim_start().len();
+    let trimmed = text.trim();
+    let base = Attrs::new().font_size(size).color(p.text_primary);
+    let mut attrs = AttrsList::new(base.clone());
+    for range in ranges {
+        let from = range.start.saturating_sub(start).min(trimmed.len());
+        let to = range.end.saturating_sub(start).min(trimmed.len());
+        if from < to {
+            attrs.add_span(from..to, base.clone().color(p.accent).weight(Weight::BOLD));
         }
-    });
+    }
+    let mut layout = TextLayout::new();
+    layout.set_text(trimmed, attrs, None);
+    layout
+}
+
+fn match_line(
+    text: String,
+    ranges: Vec<Range<usize>>,
+    size: f32,
+    theme: RwSignal<PhazeTheme>,
+) -> impl IntoView {
+    rich_text(move || match_layout(&text, &ranges, &theme.get().palette, size))
+        .style(|s| s.flex_grow(1.0).min_width(0.0))
+}
+
+/// Context lines numbered from `first`, dimmed; nothing when there are none.
+fn context_label(lines: &[String], first: usize, theme: RwSignal<PhazeTheme>) -> impl IntoView {
+    let text = lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| format!("{:>5}  {}", first + i, l.trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let empty = text.is_empty();
+    label(move || text.clone()).style(move |s| {
+        s.font_size(10.0)
+            .color(theme.get().palette.text_muted)
+            .apply_if(empty, |s| s.display(floem::style::Display::None))
+    })
 }
 
 fn perform_replace_all(
@@ -1,13 +1,25 @@
+use std::sync::mpsc::TrySendError;
+use std::time::Duration;
+
 use floem::{
-    reactive::{SignalGet, SignalUpdate},
+    ext_event::{create_ext_action, create_signal_from_channel},
+    reactive::{
+        create_effect, create_memo, create_rw_signal, Scope, SignalGet, SignalUpdate, SignalWith,
+    },
     views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
     IntoView,
 };
-use phazeai_core::{llm::provider::ProviderId, Settings};
+use phazeai_cloud::{entitlements::UPGRADE_URL, Feature};
+use phazeai_core::llm::{bedrock, vertex, BuildStatus, ModelStore, OllamaManager, ProviderConfig};
+use phazeai_core::project::FileWatcher;
+use phazeai_core::update::{Channel, CURRENT_VERSION};
+use phazeai_core::{llm::provider::ProviderId, output, Settings};
+use phazeai_sidecar::SidecarState;
 
 use crate::{
-    app::IdeState,
+    app::{show_toast, IdeState},
     components::icon::{icons, phaze_icon},
+    panels::{git_history::action_button, output::show_output_channel},
     theme::{PhazeTheme, ThemeVariant},
 };
 
@@ -23,11 +35,19 @@ fn provider_name_to_id(name: &str) -> Option<ProviderId> {
         "Claude (Anthropic)" => Some(ProviderId::Claude),
         "OpenAI" => Some(ProviderId::OpenAI),
         "Google Gemini" => Some(ProviderId::Gemini),
+        "Google Vertex AI" => Some(ProviderId::Vertex),
         "Groq" => Some(ProviderId::Groq),
         "Together.ai" => Some(ProviderId::Together),
         "OpenRouter" => Some(ProviderId::OpenRouter),
+        "xAI Grok" => Some(ProviderId::Xai),
+        "Mistral" => Some(ProviderId::Mistral),
+        "Hugging Face" => Some(ProviderId::HuggingFace),
+        "AWS Bedrock" => Some(ProviderId::Bedrock),
         "Ollama (Local)" => Some(ProviderId::Ollama),
         "LM Studio (Local)" => Some(ProviderId::LmStudio),
+        "llama.cpp (Local)" => Some(ProviderId::LlamaCpp),
+        "vLLM (Local)" => Some(ProviderId::Vllm),
+        "PhazeAI Cloud" => Some(ProviderId::PhazeCloud),
         _ => None,
     }
 }
@@ -43,6 +63,9 @@ fn provider_status(name: &str) -> ProviderUiStatus {
 
     let settings = Settings::load();
     let registry = settings.build_provider_registry();
+    if provider_id == ProviderId::PhazeCloud {
+        return cloud_provider_status(registry.get_config(&provider_id).is_some());
+    }
     let Some(config) = registry.get_config(&provider_id) else {
         return ProviderUiStatus {
             available: false,
@@ -59,6 +82,12 @@ fn provider_status(name: &str) -> ProviderUiStatus {
         };
     }
 
+    match provider_id {
+        ProviderId::Bedrock => return bedrock_provider_status(config),
+        ProviderId::Vertex => return vertex_provider_status(config),
+        _ => {}
+    }
+
     if provider_id.needs_api_key() {
         if config.api_key().is_some() {
             return ProviderUiStatus {
@@ -81,6 +110,82 @@ fn provider_status(name: &str) -> ProviderUiStatus {
     }
 }
 
+/// PhazeAI Cloud is registered only while signed in and is metered by quota
+/// rather than an API key.
+fn cloud_provider_status(signed_in: bool) -> ProviderUiStatus {
+    if !signed_in {
+        return ProviderUiStatus {
+            available: false,
+            summary: "Not signed in".into(),
+            detail: "Add an API token to ~/.config/phazeai/cloud.toml.".into(),
+        };
+    }
+    if !Feature::HostedModels.is_enabled() {
+        return ProviderUiStatus {
+            available: false,
+            summary: "Upgrade required".into(),
+            detail: format!("Hosted models need the Cloud plan: {UPGRADE_URL}"),
+        };
+    }
+    let detail = match phazeai_cloud::hosted::usage().quota {
+        Some(q) => format!("{} of {} requests left this period", q.remaining, q.limit),
+        None => "Hosted models, no API key needed".into(),
+    };
+    ProviderUiStatus {
+        available: true,
+        summary: "Ready".into(),
+        detail,
+    }
+}
+
+/// Bedrock signs requests with AWS credentials from the environment, the
+/// shared profile or the instance role, in the configured region.
+fn bedrock_provider_status(config: &ProviderConfig) -> ProviderUiStatus {
+    let (region, _) = bedrock::endpoint_for(&config.base_url);
+    if config.is_available() {
+        return ProviderUiStatus {
+            available: true,
+            summary: "Ready".into(),
+            detail: format!("AWS credentials found; region {region}"),
+        };
+    }
+    ProviderUiStatus {
+        available: false,
+        summary: "No AWS credentials".into(),
+        detail: format!(
+            "Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, add an AWS profile, or use an instance role ({region})."
+        ),
+    }
+}
+
+/// Vertex AI uses Application Default Credentials and bills the project set
+/// under `[vertex]` (or found from the environment or the credentials).
+fn vertex_provider_status(config: &ProviderConfig) -> ProviderUiStatus {
+    if !config.is_available() {
+        return ProviderUiStatus {
+            available: false,
+            summary: "No Google Cloud credentials".into(),
+            detail:
+                "Set GOOGLE_APPLICATION_CREDENTIALS or run `gcloud auth application-default login`."
+                    .into(),
+        };
+    }
+    let project = vertex::GoogleCredentials::find()
+        .and_then(|creds| vertex::VertexProject::resolve(&config.base_url, &creds));
+    match project {
+        Ok(p) => ProviderUiStatus {
+            available: true,
+            summary: "Ready".into(),
+            detail: format!("Project {} in {}", p.project, p.location),
+        },
+        Err(e) => ProviderUiStatus {
+            available: false,
+            summary: "No project".into(),
+            detail: e.to_string(),
+        },
+    }
+}
+
 // ─── helpers ────────────────────────────────────────────────────────────────
 
 /// A thin horizontal rule used to separate sections.
@@ -258,6 +363,49 @@ fn theme_tile(name: &'static str, state: IdeState) -> impl IntoView {
     })
 }
 
+/// A clickable syntax-highlighting theme option; applies to the current UI theme.
+fn syntax_tile(name: &'static str, state: IdeState) -> impl IntoView {
+    use floem::reactive::create_rw_signal;
+    let theme = state.theme;
+    let syntax_theme = state.syntax_theme;
+    let is_hovered = create_rw_signal(false);
+
+    container(label(move || name).style(move |s| {
+        let p = theme.get().palette;
+        let active = syntax_theme.get() == name;
+        let color = if active { p.accent } else { p.text_secondary };
+        s.font_size(11.0).color(color)
+    }))
+    .style(move |s| {
+        let p = theme.get().palette;
+        let active = syntax_theme.get() == name;
+        let hovered = is_hovered.get();
+        let border_color = if active {
+            p.accent
+        } else if hovered {
+            p.border_focus
+        } else {
+            p.border
+        };
+        s.padding_vert(5.0)
+            .padding_horiz(8.0)
+            .background(if active { p.accent_dim } else { p.bg_surface })
+            .border(1.0)
+            .border_color(border_color)
+            .border_radius(6.0)
+            .cursor(floem::style::CursorStyle::Pointer)
+    })
+    .on_click_stop(move |_| {
+        crate::app::set_syntax_theme(&state, Some(name));
+    })
+    .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+        is_hovered.set(true);
+    })
+    .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+        is_hovered.set(false);
+    })
+}
+
 // ─── sections ────────────────────────────────────────────────────────────────
 
 fn theme_section(state: IdeState) -> impl IntoView {
@@ -283,7 +431,66 @@ fn theme_section(state: IdeState) -> impl IntoView {
             .width_full()
     });
 
-    stack((section_header("THEME", state.clone()), tiles)).style(|s| s.flex_col().width_full())
+    let syntax_tiles = dyn_stack(
+        move || {
+            crate::theme::SYNTAX_THEMES
+                .iter()
+                .copied()
+                .enumerate()
+                .collect::<Vec<_>>()
+        },
+        |(i, _name)| *i,
+        {
+            let state = state.clone();
+            move |(_i, name)| syntax_tile(name, state.clone())
+        },
+    )
+    .style(|s| {
+        s.flex_row()
+            .flex_wrap(floem::style::FlexWrap::Wrap)
+            .gap(6.0)
+            .width_full()
+    });
+
+    let theme = state.theme;
+    let follow = state.follow_system_theme;
+    let follow_row = stack((
+        label(|| "Follow System Light/Dark").style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(12.0).color(p.text_primary).flex_grow(1.0)
+        }),
+        container(label(move || if follow.get() { "ON" } else { "OFF" }))
+            .style(move |s| {
+                let p = theme.get().palette;
+                let on = follow.get();
+                s.font_size(11.0)
+                    .padding_horiz(8.0)
+                    .padding_vert(3.0)
+                    .border_radius(4.0)
+                    .color(p.bg_base)
+                    .background(if on { p.success } else { p.bg_elevated })
+                    .border(1.0)
+                    .border_color(if on { p.success } else { p.border })
+                    .cursor(floem::style::CursorStyle::Pointer)
+            })
+            .on_click_stop(move |_| follow.update(|v| *v = !*v)),
+    ))
+    .style(|s| s.flex_row().items_center().width_full().padding_vert(6.0));
+
+    let syntax_label = label(move || format!("Syntax colors for {}", theme.get().variant.name()))
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(11.0).color(p.text_muted).padding_vert(6.0)
+        });
+
+    stack((
+        section_header("THEME", state.clone()),
+        tiles,
+        follow_row,
+        syntax_label,
+        syntax_tiles,
+    ))
+    .style(|s| s.flex_col().width_full())
 }
 
 fn editor_section(state: IdeState) -> impl IntoView {
@@ -294,16 +501,28 @@ fn editor_section(state: IdeState) -> impl IntoView {
     let auto_save = state.auto_save;
     let word_wrap = state.word_wrap;
     let organize_imports = state.organize_imports_on_save;
+    let normalize_eol = state.normalize_line_endings_on_save;
+    let line_length_lint = state.line_length_lint;
     let code_lens_vis = state.code_lens_visible;
     let inlay_hints = state.inlay_hints_toggle;
     let relative_ln = state.relative_line_numbers;
+    let ligatures = state.font_ligatures;
+    let explain_commands = state.explain_commands_on_hover;
+    let inline_blame = state.inline_blame;
+    let blame_gutter = state.blame_gutter;
     let theme_as = state.theme;
     let as_hov = floem::reactive::create_rw_signal(false);
     let ww_hov = floem::reactive::create_rw_signal(false);
     let oi_hov = floem::reactive::create_rw_signal(false);
+    let eol_hov = floem::reactive::create_rw_signal(false);
+    let ll_hov = floem::reactive::create_rw_signal(false);
     let cl_hov = floem::reactive::create_rw_signal(false);
     let ih_hov = floem::reactive::create_rw_signal(false);
     let rln_hov = floem::reactive::create_rw_signal(false);
+    let lig_hov = floem::reactive::create_rw_signal(false);
+    let ec_hov = floem::reactive::create_rw_signal(false);
+    let ib_hov = floem::reactive::create_rw_signal(false);
+    let bg_hov = floem::reactive::create_rw_signal(false);
 
     let toggle_row =
         |label_text: &'static str,
@@ -358,9 +577,213 @@ fn editor_section(state: IdeState) -> impl IntoView {
             oi_hov,
             theme_as,
         ),
+        toggle_row(
+            "Normalize Line Endings on Save",
+            normalize_eol,
+            eol_hov,
+            theme_as,
+        ),
+        stack((
+            rulers_row(state.clone()),
+            toggle_row(
+                "Flag Lines Over max_line_length",
+                line_length_lint,
+                ll_hov,
+                theme_as,
+            ),
+        ))
+        .style(|s| s.flex_col().width_full()),
         toggle_row("Show Code Lens", code_lens_vis, cl_hov, theme_as),
         toggle_row("Inlay Hints  (Ctrl+Alt+I)", inlay_hints, ih_hov, theme_as),
         toggle_row("Relative Line Numbers", relative_ln, rln_hov, theme_as),
+        toggle_row("Font Ligatures", ligatures, lig_hov, theme_as),
+        toggle_row("Inline Git Blame", inline_blame, ib_hov, theme_as),
+        toggle_row("Git Blame Gutter", blame_gutter, bg_hov, theme_as),
+        toggle_row(
+            "Explain Commands on Hover (AI)",
+            explain_commands,
+            ec_hov,
+            theme_as,
+        ),
+    ))
+    .style(|s| s.flex_col().width_full())
+}
+
+/// Ruler columns as a comma-separated list, e.g. `80, 100, 120`. Anything
+/// that isn't a column is dropped when the list is read.
+fn rulers_row(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let rulers = state.rulers;
+    let format = |columns: &[u32]| {
+        columns
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let text = create_rw_signal(format(&rulers.get_untracked()));
+    create_effect(move |_| {
+        let mut columns: Vec<u32> = text.with(|t| {
+            t.split([',', ' '])
+                .filter_map(|c| c.trim().parse().ok())
+                .filter(|&c| c > 0)
+                .collect()
+        });
+        columns.sort_unstable();
+        columns.dedup();
+        if rulers.with_untracked(|r| *r != columns) {
+            rulers.set(columns);
+        }
+    });
+
+    stack((
+        label(|| "Rulers").style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(12.0).color(p.text_primary).flex_grow(1.0)
+        }),
+        text_input(text)
+            .placeholder("e.g. 80, 120")
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width(120.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border_focus)
+                    .border_radius(4.0)
+                    .color(p.text_primary)
+                    .padding_horiz(8.0)
+                    .padding_vert(4.0)
+                    .font_size(12.0)
+                    .min_width(0.0)
+            }),
+    ))
+    .style(|s| {
+        s.flex_row()
+            .items_center()
+            .width_full()
+            .padding_vert(4.0)
+            .padding_horiz(4.0)
+    })
+}
+
+/// A font family row: free-text input plus suggestions from installed fonts.
+/// Suggestions are hidden once the input names an installed family exactly.
+fn font_family_row(
+    row_label: &'static str,
+    family: floem::reactive::RwSignal<String>,
+    state: IdeState,
+) -> impl IntoView {
+    let theme = state.theme;
+
+    let input_row = stack((
+        label(move || row_label).style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(13.0).color(p.text_primary).flex_grow(1.0)
+        }),
+        text_input(family)
+            .placeholder("e.g. JetBrains Mono")
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width(160.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border_focus)
+                    .border_radius(4.0)
+                    .color(p.text_primary)
+                    .padding_horiz(8.0)
+                    .padding_vert(4.0)
+                    .font_size(12.0)
+                    .min_width(0.0)
+            }),
+    ))
+    .style(|s| s.flex_row().items_center().width_full().padding_vert(4.0));
+
+    let suggestions = dyn_stack(
+        move || {
+            let query = family.get().trim().to_lowercase();
+            let all = crate::fonts::system_font_families();
+            if all.iter().any(|f| f.to_lowercase() == query) {
+                return Vec::new();
+            }
+            all.iter()
+                .filter(|f| query.is_empty() || f.to_lowercase().contains(&query))
+                .take(6)
+                .cloned()
+                .collect::<Vec<_>>()
+        },
+        |name| name.clone(),
+        move |name| {
+            let pick = name.clone();
+            container(label(move || name.clone()).style(move |s| {
+                let p = theme.get().palette;
+                s.font_size(11.0).color(p.text_secondary)
+            }))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.padding_vert(3.0)
+                    .padding_horiz(6.0)
+                    .background(p.bg_surface)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(4.0)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .hover(|s| s.border_color(p.border_focus))
+            })
+            .on_click_stop(move |_| family.set(pick.clone()))
+        },
+    )
+    .style(|s| {
+        s.flex_row()
+            .flex_wrap(floem::style::FlexWrap::Wrap)
+            .gap(4.0)
+            .width_full()
+    });
+
+    stack((input_row, suggestions)).style(|s| s.flex_col().width_full().padding_bottom(4.0))
+}
+
+/// Editor line height (multiple of font size), stepped by 0.1 between 1.0 and 2.5.
+fn line_height_row(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let line_height = state.line_height;
+    let step = move |delta: f32| {
+        line_height.update(|v| *v = ((*v + delta) * 10.0).round().clamp(10.0, 25.0) / 10.0);
+    };
+    let dec = stepper_btn("-", state.clone(), move || step(-0.1));
+    let inc = stepper_btn("+", state.clone(), move || step(0.1));
+    let value_display = container(label(move || format!("{:.1}", line_height.get())).style(
+        move |s| {
+            let p = theme.get().palette;
+            s.font_size(13.0).color(p.text_primary)
+        },
+    ))
+    .style(|s| s.width(28.0).items_center().justify_center());
+
+    stack((
+        label(|| "Line Height").style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(13.0).color(p.text_primary).flex_grow(1.0)
+        }),
+        stack((dec, value_display, inc)).style(|s| s.flex_row().items_center().gap(2.0)),
+    ))
+    .style(|s| s.flex_row().items_center().width_full().padding_vert(4.0))
+}
+
+fn fonts_section(state: IdeState) -> impl IntoView {
+    stack((
+        section_header("FONTS", state.clone()),
+        font_family_row("Editor Font", state.font_family, state.clone()),
+        line_height_row(state.clone()),
+        font_family_row("Terminal Font", state.terminal_font_family, state.clone()),
+        stepper_row(
+            "Terminal Font Size",
+            state.terminal_font_size,
+            8,
+            32,
+            state.clone(),
+        ),
+        font_family_row("Chat Font", state.chat_font_family, state.clone()),
+        stepper_row("Chat Font Size", state.chat_font_size, 9, 24, state.clone()),
     ))
     .style(|s| s.flex_col().width_full())
 }
@@ -458,11 +881,19 @@ fn ai_section(state: IdeState) -> impl IntoView {
         "Claude (Anthropic)",
         "OpenAI",
         "Google Gemini",
+        "Google Vertex AI",
         "Groq",
         "Together.ai",
         "OpenRouter",
+        "xAI Grok",
+        "Mistral",
+        "Hugging Face",
+        "AWS Bedrock",
         "Ollama (Local)",
         "LM Studio (Local)",
+        "llama.cpp (Local)",
+        "vLLM (Local)",
+        "PhazeAI Cloud",
     ];
 
     let provider_tiles = dyn_stack(
@@ -549,6 +980,320 @@ fn ai_section(state: IdeState) -> impl IntoView {
     .style(|s| s.flex_col().width_full())
 }
 
+/// How often the Modelfile directory is checked for saved edits.
+const MODELFILE_POLL: Duration = Duration::from_millis(500);
+
+/// A custom model as listed: its name, then its base model and build state,
+/// or why its Modelfile doesn't parse.
+#[derive(Clone, PartialEq)]
+struct LocalModelRow {
+    name: String,
+    summary: Result<(String, BuildStatus), String>,
+}
+
+fn local_model_rows(store: &ModelStore) -> Vec<LocalModelRow> {
+    store
+        .names()
+        .into_iter()
+        .map(|name| LocalModelRow {
+            summary: store
+                .load(&name)
+                .map(|m| (m.base.clone(), store.status(&m)))
+                .map_err(|e| e.to_string()),
+            name,
+        })
+        .collect()
+}
+
+/// Custom Ollama models built from Modelfiles in the config directory,
+/// `phaze-beast` among them. Edit opens a model's Modelfile; saving it
+/// rebuilds a model that was built before, and Build (re)creates one on the
+/// Ollama server, with its progress in the status bar.
+fn local_models_section(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let open_file = state.open_file;
+    let toast = state.status_toast;
+    let store = ModelStore::open_default();
+
+    let reload = create_rw_signal(0u64);
+    let rows = {
+        let store = store.clone();
+        create_memo(move |_| {
+            reload.get();
+            local_model_rows(&store)
+        })
+    };
+    let building = create_rw_signal(None::<String>);
+    let new_name = create_rw_signal(String::new());
+
+    let build = {
+        let store = store.clone();
+        move |name: String, force: bool| {
+            if building.get_untracked().is_some() {
+                return;
+            }
+            building.set(Some(name.clone()));
+            let store = store.clone();
+            let on_done = create_ext_action(
+                Scope::current(),
+                move |result: Result<(String, bool), String>| {
+                    building.set(None);
+                    reload.update(|n| *n += 1);
+                    match result {
+                        Ok((name, true)) => show_toast(toast, format!("Built {name}")),
+                        Ok((name, false)) => show_toast(toast, format!("{name} is up to date")),
+                        Err(e) => show_toast(toast, format!("Model build failed: {e}")),
+                    }
+                },
+            );
+            std::thread::spawn(move || {
+                let result = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| e.to_string())
+                    .and_then(|rt| {
+                        rt.block_on(async {
+                            let manager = OllamaManager::new(&Settings::load().ollama_base_url())
+                                .map_err(|e| e.to_string())?;
+                            let model = store.load(&name).map_err(|e| e.to_string())?;
+                            manager
+                                .build_custom_model(&store, &model, force)
+                                .await
+                                .map(|built| (name, built))
+                                .map_err(|e| e.to_string())
+                        })
+                    });
+                on_done(result);
+            });
+        }
+    };
+    let edit = {
+        let store = store.clone();
+        move |name: &str| match store.edit(name, None) {
+            Ok(path) => open_file.set(Some(path)),
+            Err(e) => show_toast(toast, format!("Couldn't open the Modelfile: {e}")),
+        }
+    };
+
+    // Saved Modelfile edits rebuild models that were built before; new ones
+    // wait for Build.
+    {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<()>(1);
+        let saved = create_signal_from_channel(rx);
+        let dir = ModelStore::default_dir();
+        std::thread::spawn(move || {
+            if std::fs::create_dir_all(&dir).is_err() {
+                return;
+            }
+            let Ok(watcher) = FileWatcher::shared(&dir) else {
+                return;
+            };
+            let Ok(mut events) = watcher.subscribe(&["**/*.Modelfile"]) else {
+                return;
+            };
+            loop {
+                std::thread::sleep(MODELFILE_POLL);
+                let mut changed = false;
+                while events.try_recv().is_ok() {
+                    changed = true;
+                }
+                // A full channel already has a reload pending.
+                if changed && matches!(tx.try_send(()), Err(TrySendError::Disconnected(_))) {
+                    return;
+                }
+            }
+        });
+        let build = build.clone();
+        create_effect(move |_| {
+            if saved.get().is_none() {
+                return;
+            }
+            reload.update(|n| *n += 1);
+            let changed = rows
+                .get_untracked()
+                .into_iter()
+                .find(|r| matches!(r.summary, Ok((_, BuildStatus::Changed))));
+            if let Some(row) = changed {
+                build(row.name, false);
+            }
+        });
+    }
+
+    let model_rows = dyn_stack(move || rows.get(), |row| row.name.clone(), {
+        let build = build.clone();
+        let edit = edit.clone();
+        move |row: LocalModelRow| {
+            let name = row.name.clone();
+            let detail = match &row.summary {
+                Ok((base, status)) => format!("FROM {base}  ·  {}", status.label()),
+                Err(e) => e.clone(),
+            };
+            let invalid = row.summary.is_err();
+            let (build, edit) = (build.clone(), edit.clone());
+            let (build_name, edit_name) = (row.name.clone(), row.name.clone());
+            stack((
+                stack((
+                    label(move || name.clone())
+                        .style(move |s| s.font_size(12.0).color(theme.get().palette.text_primary)),
+                    label(move || detail.clone()).style(move |s| {
+                        let p = theme.get().palette;
+                        s.font_size(11.0)
+                            .color(if invalid { p.warning } else { p.text_muted })
+                            .min_width(0.0)
+                            .text_ellipsis()
+                    }),
+                ))
+                .style(|s| s.flex_col().flex_grow(1.0).min_width(0.0)),
+                action_button(|| "Edit".to_string(), theme, move || edit(&edit_name)),
+                action_button(
+                    move || match building.get() {
+                        Some(b) if b == build_name => "Building…".to_string(),
+                        _ => "Build".to_string(),
+                    },
+                    theme,
+                    {
+                        let name = row.name.clone();
+                        move || build(name.clone(), true)
+                    },
+                ),
+            ))
+            .style(|s| {
+                s.flex_row()
+                    .items_center()
+                    .gap(6.0)
+                    .width_full()
+                    .padding_vert(3.0)
+            })
+        }
+    })
+    .style(|s| s.flex_col().width_full());
+
+    let create = move || {
+        let name = new_name.get_untracked().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        new_name.set(String::new());
+        edit(&name);
+        reload.update(|n| *n += 1);
+    };
+    let new_row = stack((
+        text_input(new_name)
+            .placeholder("new-model-name")
+            .on_event_stop(floem::event::EventListener::KeyDown, {
+                let create = create.clone();
+                move |event| {
+                    if let floem::event::Event::KeyDown(ke) = event {
+                        use floem::keyboard::{Key, NamedKey};
+                        if ke.key.logical_key == Key::Named(NamedKey::Enter) {
+                            create();
+                        }
+                    }
+                }
+            })
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.flex_grow(1.0)
+                    .min_width(0.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(4.0)
+                    .color(p.text_primary)
+                    .padding_horiz(8.0)
+                    .padding_vert(4.0)
+                    .font_size(12.0)
+            }),
+        action_button(|| "New Model".to_string(), theme, create),
+    ))
+    .style(|s| {
+        s.flex_row()
+            .items_center()
+            .gap(6.0)
+            .width_full()
+            .padding_top(4.0)
+    });
+
+    stack((
+        section_header("LOCAL MODELS", state.clone()),
+        model_rows,
+        new_row,
+    ))
+    .style(|s| s.flex_col().width_full())
+}
+
+/// The semantic search sidecar: whether it is up, a manual restart for when
+/// the supervisor has given up on it, and a repair that rebuilds its
+/// virtualenv.
+fn sidecar_section(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let sidecar = state.sidecar_state;
+    let restart = state.sidecar_restart_nonce;
+    let repair = state.sidecar_repair_nonce;
+
+    let status_row = stack((
+        label(|| "●".to_string()).style(move |s| {
+            let p = theme.get().palette;
+            let color = match sidecar.get() {
+                Some(SidecarState::Ready { .. }) => p.success,
+                Some(SidecarState::Starting | SidecarState::Restarting { .. }) => p.warning,
+                Some(SidecarState::Failed(_)) => p.error,
+                Some(SidecarState::Stopped) | None => p.text_muted,
+            };
+            s.font_size(11.0).color(color)
+        }),
+        label(move || match sidecar.get() {
+            Some(st) => format!("Python sidecar {}", st.label()),
+            None => "Python sidecar disabled".to_string(),
+        })
+        .style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(12.0)
+                .color(p.text_primary)
+                .flex_grow(1.0)
+                .min_width(0.0)
+        }),
+        container(action_button(
+            || "Restart".to_string(),
+            theme,
+            move || restart.update(|n| *n += 1),
+        ))
+        .style(move |s| {
+            s.apply_if(sidecar.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+        }),
+        container(action_button(
+            || "Repair".to_string(),
+            theme,
+            move || repair.update(|n| *n += 1),
+        ))
+        .style(move |s| {
+            s.apply_if(sidecar.get().is_none(), |s| {
+                s.display(floem::style::Display::None)
+            })
+        }),
+        {
+            let state = state.clone();
+            action_button(
+                || "Show Log".to_string(),
+                theme,
+                move || show_output_channel(&state, output::SIDECAR),
+            )
+        },
+    ))
+    .style(|s| {
+        s.flex_row()
+            .items_center()
+            .gap(6.0)
+            .width_full()
+            .padding_vert(4.0)
+    });
+
+    stack((section_header("SIDECAR", state.clone()), status_row))
+        .style(|s| s.flex_col().width_full())
+}
+
 fn about_section(state: IdeState) -> impl IntoView {
     use floem::reactive::create_rw_signal;
     let theme = state.theme;
@@ -565,7 +1310,7 @@ fn about_section(state: IdeState) -> impl IntoView {
                     .color(p.text_primary)
                     .font_weight(floem::text::Weight::BOLD)
             }),
-            label(|| "Version 0.1.0").style(move |s| {
+            label(|| format!("Version {CURRENT_VERSION}")).style(move |s| {
                 let t = theme.get();
                 let p = &t.palette;
                 s.font_size(11.0).color(p.text_muted)
@@ -610,8 +1355,76 @@ fn about_section(state: IdeState) -> impl IntoView {
             .spawn();
     });
 
-    stack((section_header("ABOUT", state.clone()), icon_row, link))
-        .style(|s| s.flex_col().width_full())
+    stack((
+        section_header("ABOUT", state.clone()),
+        icon_row,
+        link,
+        updates_row(state.clone()),
+    ))
+    .style(|s| s.flex_col().width_full())
+}
+
+/// One choice of release channel, highlighted while it's the configured one.
+fn channel_chip(
+    channel: Channel,
+    current: floem::reactive::RwSignal<Channel>,
+    state: IdeState,
+) -> impl IntoView {
+    let theme = state.theme;
+    container(label(move || channel.label().to_string()).style(move |s| {
+        let p = theme.get().palette;
+        let color = if current.get() == channel {
+            p.accent
+        } else {
+            p.text_secondary
+        };
+        s.font_size(12.0).color(color)
+    }))
+    .style(move |s| {
+        let p = theme.get().palette;
+        let selected = current.get() == channel;
+        s.padding_horiz(10.0)
+            .padding_vert(3.0)
+            .border(1.0)
+            .border_radius(4.0)
+            .border_color(if selected { p.accent } else { p.border })
+            .background(if selected {
+                p.bg_elevated
+            } else {
+                p.bg_surface
+            })
+            .cursor(floem::style::CursorStyle::Pointer)
+    })
+    .on_click_stop(move |_| crate::update::set_channel(current, channel))
+}
+
+/// The release channel, saved as `[updates] channel`, and a button that
+/// stages the newest release on it.
+fn updates_row(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let toast = state.status_toast;
+    let channel = create_rw_signal(Settings::load().updates.channel);
+
+    stack((
+        label(|| "Update channel").style(move |s| {
+            let p = theme.get().palette;
+            s.font_size(13.0).color(p.text_primary).flex_grow(1.0)
+        }),
+        channel_chip(Channel::Stable, channel, state.clone()),
+        channel_chip(Channel::Beta, channel, state.clone()),
+        action_button(
+            || "Update PhazeAI".to_string(),
+            theme,
+            move || crate::update::download_and_stage(toast),
+        ),
+    ))
+    .style(|s| {
+        s.flex_row()
+            .items_center()
+            .gap(6.0)
+            .width_full()
+            .padding_vert(4.0)
+    })
 }
 
 // ─── keybindings reference ───────────────────────────────────────────────────────
@@ -628,9 +1441,15 @@ fn keybindings_section(state: IdeState) -> impl IntoView {
         // Navigation
         ("Ctrl+G", "Go to Line"),
         ("F12", "Go to Definition"),
-        ("Shift+F12", "Find All References"),
+        ("Shift+F12", "Peek References"),
+        ("Alt+F12", "Peek Definition"),
         ("F2", "Rename Symbol"),
         ("Ctrl+T", "Workspace Symbols"),
+        ("Alt+Left", "Go Back"),
+        ("Alt+Right", "Go Forward"),
+        ("Ctrl+Alt+K", "Toggle Bookmark"),
+        ("Ctrl+Alt+L", "Next Bookmark"),
+        ("Ctrl+Alt+J", "Previous Bookmark"),
         // Editing
         ("Ctrl+/", "Toggle Comment"),
         ("Ctrl+D", "Select Next Occurrence"),
@@ -738,8 +1557,14 @@ pub fn settings_panel(state: IdeState) -> impl IntoView {
         divider(state.clone()),
         editor_section(state.clone()),
         divider(state.clone()),
+        fonts_section(state.clone()),
+        divider(state.clone()),
         ai_section(state.clone()),
         divider(state.clone()),
+        local_models_section(state.clone()),
+        divider(state.clone()),
+        sidecar_section(state.clone()),
+        divider(state.clone()),
         keybindings_section(state.clone()),
         divider(state.clone()),
         about_section(state.clone()),
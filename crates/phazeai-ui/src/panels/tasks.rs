@@ -0,0 +1,294 @@
+//! The workspace plan — the bottom-panel "TASKS" tab.
+//!
+//! Lists the checklist in `plan.md`. "Extract Tasks" in the chat header and
+//! "Extract Tasks from Selection" in the editor send text through
+//! `IdeState::extract_tasks_from`; the model's action items are appended to
+//! the plan here. Tasks can be checked off and filed as GitHub issues on
+//! `origin`, which records the issue's URL in the plan.
+
+use floem::{
+    ext_event::create_signal_from_channel,
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, Decorators},
+    IntoView,
+};
+use phazeai_core::{
+    analysis::tasks::{self, PlanTask, PLAN_FILE},
+    llm::TaskType,
+    PhazeError, Settings,
+};
+
+use crate::app::{show_toast, IdeState, Tab};
+use crate::components::open_url;
+use crate::panels::git_history::{action_button, spawn_git};
+use crate::util::safe_get;
+
+enum TasksMsg {
+    Extracted(Result<usize, String>),
+    Filed(String, Result<String, String>),
+}
+
+pub(crate) fn tasks_tab_label(tasks: RwSignal<Vec<PlanTask>>) -> String {
+    let open = tasks.get().iter().filter(|t| !t.done).count();
+    if open == 0 {
+        "TASKS".to_string()
+    } else {
+        format!("TASKS ({open})")
+    }
+}
+
+pub fn tasks_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let plan = state.plan_tasks;
+    let extract_from = state.extract_tasks_from;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let toast = state.status_toast;
+
+    let status = create_rw_signal(String::new());
+    let busy = create_rw_signal(false);
+    // Titles of tasks being filed as issues.
+    let filing: RwSignal<Vec<String>> = create_rw_signal(Vec::new());
+
+    let reload = move || plan.set(tasks::read_plan(&workspace_root.get_untracked()));
+
+    // The plan of the current workspace, re-read whenever the tab opens.
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |_| {
+            let root = workspace_root.get();
+            let open = shown.get() && tab.get() == Tab::Tasks;
+            if open {
+                plan.set(tasks::read_plan(&root));
+            }
+        });
+    }
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<TasksMsg>(8);
+    let msg = create_signal_from_channel(rx);
+    create_effect(move |_| {
+        let Some(m) = msg.get() else {
+            return;
+        };
+        match m {
+            TasksMsg::Extracted(Ok(0)) => {
+                busy.set(false);
+                status.set("No new action items found".to_string());
+            }
+            TasksMsg::Extracted(Ok(n)) => {
+                busy.set(false);
+                status.set(format!(
+                    "Added {n} task{} to {PLAN_FILE}",
+                    if n == 1 { "" } else { "s" }
+                ));
+            }
+            TasksMsg::Extracted(Err(e)) => {
+                busy.set(false);
+                status.set(format!("Couldn't extract tasks: {e}"));
+            }
+            TasksMsg::Filed(title, Ok(url)) => {
+                filing.update(|f| f.retain(|t| *t != title));
+                show_toast(toast, format!("Filed {url}"));
+            }
+            TasksMsg::Filed(title, Err(e)) => {
+                filing.update(|f| f.retain(|t| *t != title));
+                show_toast(toast, format!("Couldn't file the issue: {e}"));
+            }
+        }
+        reload();
+    });
+
+    // ── Extraction ──────────────────────────────────────────────────────────
+    {
+        let tx = tx.clone();
+        create_effect(move |_| {
+            let Some(text) = extract_from.get() else {
+                return;
+            };
+            extract_from.set(None);
+            busy.set(true);
+            status.set("Looking for action items…".to_string());
+            spawn_git(
+                workspace_root.get_untracked(),
+                tx.clone(),
+                move |git| async move {
+                    let result: Result<usize, PhazeError> = async {
+                        let llm = Settings::load().build_client_for_task(TaskType::Reasoning)?;
+                        let found = tasks::extract_tasks(llm.as_ref(), &text).await?;
+                        tasks::add_to_plan(git.repo_root(), &found)
+                    }
+                    .await;
+                    TasksMsg::Extracted(result.map_err(|e| e.to_string()))
+                },
+            );
+        });
+    }
+
+    let toggle_done = move |task: PlanTask| {
+        let root = workspace_root.get_untracked();
+        let updated = PlanTask {
+            done: !task.done,
+            ..task.clone()
+        };
+        if let Err(e) = tasks::update_in_plan(&root, &task.title, &updated) {
+            show_toast(toast, format!("Couldn't update {PLAN_FILE}: {e}"));
+        }
+        reload();
+    };
+
+    let file_issue = move |task: PlanTask| {
+        filing.update(|f| f.push(task.title.clone()));
+        spawn_git(
+            workspace_root.get_untracked(),
+            tx.clone(),
+            move |git| async move {
+                let result = async {
+                    let url = git
+                        .create_github_issue(&task.title, &task.issue_body())
+                        .await?;
+                    let filed = PlanTask {
+                        issue: Some(url.clone()),
+                        ..task.clone()
+                    };
+                    tasks::update_in_plan(git.repo_root(), &task.title, &filed)?;
+                    Ok::<_, PhazeError>(url)
+                }
+                .await;
+                TasksMsg::Filed(task.title, result.map_err(|e| e.to_string()))
+            },
+        );
+    };
+
+    let header = stack((
+        action_button(
+            || "Open plan.md".to_string(),
+            theme,
+            move || {
+                let path = workspace_root.get_untracked().join(PLAN_FILE);
+                if path.exists() {
+                    open_file.set(Some(path));
+                } else {
+                    show_toast(toast, format!("No {PLAN_FILE} yet"));
+                }
+            },
+        ),
+        action_button(|| "Reload".to_string(), theme, reload),
+        label(move || {
+            let list = plan.get();
+            let done = list.iter().filter(|t| t.done).count();
+            let summary = format!("{done} of {} done", list.len());
+            match status.get() {
+                s if s.is_empty() => summary,
+                s => format!("{summary}  ·  {s}"),
+            }
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let rows = dyn_stack(
+        move || safe_get(plan, Vec::new()),
+        |t| format!("{}:{}:{}:{:?}", t.title, t.detail, t.done, t.issue),
+        move |task: PlanTask| {
+            let done = task.done;
+            let title = task.title.clone();
+            let detail = task.detail.clone();
+            let issue = task.issue.clone();
+            let (t_toggle, t_file) = (task.clone(), task.clone());
+            let filing_title = task.title.clone();
+            let file_issue = file_issue.clone();
+            stack((
+                label(move || String::from(if done { "☑" } else { "☐" }))
+                    .style(move |s| {
+                        s.font_size(13.0)
+                            .min_width(18.0)
+                            .color(theme.get().palette.accent)
+                            .cursor(floem::style::CursorStyle::Pointer)
+                    })
+                    .on_click_stop(move |_| toggle_done(t_toggle.clone())),
+                label(move || title.clone()).style(move |s| {
+                    let p = theme.get().palette;
+                    s.font_size(12.0)
+                        .color(if done { p.text_muted } else { p.text_primary })
+                }),
+                label(move || detail.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_muted)
+                }),
+                match issue {
+                    Some(url) => {
+                        let number = url.rsplit('/').next().unwrap_or("").to_string();
+                        action_button(
+                            move || format!("#{number} ↗"),
+                            theme,
+                            move || open_url(&url),
+                        )
+                        .into_any()
+                    }
+                    None => action_button(
+                        move || {
+                            if filing.get().contains(&filing_title) {
+                                "Filing…".to_string()
+                            } else {
+                                "File Issue".to_string()
+                            }
+                        },
+                        theme,
+                        move || {
+                            if !filing.get_untracked().contains(&t_file.title) {
+                                file_issue(t_file.clone());
+                            }
+                        },
+                    )
+                    .into_any(),
+                },
+            ))
+            .style(move |s| {
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty_msg = container(
+        label(move || {
+            if busy.get() {
+                "Looking for action items…".to_string()
+            } else {
+                format!(
+                    "No tasks in {PLAN_FILE}. Use \"Tasks\" in the chat header or \
+                     \"Extract Tasks from Selection\" in the editor to add some."
+                )
+            }
+        })
+        .style(move |s| {
+            s.font_size(12.0)
+                .color(theme.get().palette.text_muted)
+                .padding(12.0)
+        }),
+    )
+    .style(move |s| {
+        s.apply_if(!plan.get().is_empty(), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    stack((
+        header,
+        empty_msg,
+        scroll(rows).style(|s| s.flex_grow(1.0).width_full()),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
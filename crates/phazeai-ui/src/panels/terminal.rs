@@ -1,5 +1,6 @@
 use arboard;
 use std::io::{Read, Write};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
@@ -17,9 +18,14 @@ use floem::{
 use portable_pty::{CommandBuilder, MasterPty, NativePtySystem, PtySize, PtySystem};
 use vte::{Params, Perform};
 
+use crate::command_hover::CommandHover;
 use crate::commands::{execute_command, match_global_shortcut, GlobalCommandState};
+use crate::components::links::{linked_line, LinkOpener};
 use crate::util::safe_get;
+use phazeai_core::analysis::explain::command_from_prompt_line;
 use phazeai_core::constants::terminal as term_consts;
+use phazeai_core::output::{find_links, Link};
+use phazeai_core::project::EnvVars;
 
 use crate::theme::PhazeTheme;
 
@@ -43,7 +49,7 @@ impl TermColor {
 }
 
 /// Convert a 256-color xterm index to a floem Color.
-fn indexed_to_color(idx: u8) -> Color {
+pub(crate) fn indexed_to_color(idx: u8) -> Color {
     const BASIC: [(u8, u8, u8); 16] = [
         (0, 0, 0),       // 0  Black
         (194, 54, 33),   // 1  Red
@@ -154,6 +160,10 @@ struct TermState {
     pub cwd: String,
     /// Line indices (into `lines`) where OSC 133;A (prompt start) was seen.
     pub prompt_line_positions: Vec<usize>,
+    /// Line index of the prompt a captured command was typed at, while it runs.
+    capture_from: Option<usize>,
+    /// Output of the last captured command, once the next prompt appears.
+    captured: Option<String>,
 }
 
 impl TermState {
@@ -167,14 +177,25 @@ impl TermState {
             cursor_col: 0,
             cwd: String::new(),
             prompt_line_positions: Vec::new(),
+            capture_from: None,
+            captured: None,
         }
     }
 
-    fn commit_line(&mut self) {
-        let line = std::mem::replace(&mut self.current_line, TermLine::new());
-        self.lines.push(line);
-        // Enforce scrollback cap — trim oldest lines when exceeded.
-        // MAX_SCROLLBACK is the configurable limit (see const above).
+    /// Start capturing the output of the command about to be typed at the
+    /// current prompt; it's available from [`take_captured`](Self::take_captured)
+    /// once the shell reports its next prompt (OSC 133;A).
+    fn start_capture(&mut self) {
+        self.capture_from = Some(self.lines.len());
+        self.captured = None;
+    }
+
+    fn take_captured(&mut self) -> Option<String> {
+        self.captured.take()
+    }
+
+    /// Drop the oldest lines beyond MAX_SCROLLBACK, shifting recorded positions.
+    fn trim_scrollback(&mut self) {
         if self.lines.len() > MAX_SCROLLBACK {
             let drain_count = self.lines.len() - MAX_SCROLLBACK;
             self.lines.drain(0..drain_count);
@@ -187,7 +208,18 @@ impl TermState {
                     true
                 }
             });
+            if let Some(start) = self.capture_from.as_mut() {
+                *start = start.saturating_sub(drain_count);
+            }
         }
+    }
+
+    fn commit_line(&mut self) {
+        let line = std::mem::replace(&mut self.current_line, TermLine::new());
+        self.lines.push(line);
+        // Enforce scrollback cap — trim oldest lines when exceeded.
+        // MAX_SCROLLBACK is the configurable limit (see const above).
+        self.trim_scrollback();
         self.cursor_col = 0;
     }
 
@@ -395,24 +427,19 @@ impl Perform for VtePerformer {
                 if let Ok(mut s) = self.state.lock() {
                     // Commit current line so the position is accurate
                     if !s.current_line.is_empty() {
-                        let line = std::mem::replace(&mut s.current_line, TermLine::new());
-                        s.lines.push(line);
-                        if s.lines.len() > MAX_SCROLLBACK {
-                            let drain_count = s.lines.len() - MAX_SCROLLBACK;
-                            s.lines.drain(0..drain_count);
-                            s.prompt_line_positions.retain_mut(|pos| {
-                                if *pos < drain_count {
-                                    false
-                                } else {
-                                    *pos -= drain_count;
-                                    true
-                                }
-                            });
-                        }
-                        s.cursor_col = 0;
+                        s.commit_line();
                     }
                     let pos = s.lines.len();
                     s.prompt_line_positions.push(pos);
+                    // A captured command has finished: everything after the
+                    // line it was typed on is its output.
+                    if let Some(start) = s.capture_from.take() {
+                        let output: Vec<String> = s.lines[(start + 1).min(pos)..pos]
+                            .iter()
+                            .map(TermLine::plain_text)
+                            .collect();
+                        s.captured = Some(output.join("\n"));
+                    }
                 }
             }
         }
@@ -562,23 +589,21 @@ const SHELLS: &[&str] = &["bash", "zsh", "fish", "sh"];
 /// Maximum lines rendered at once — keeps the dyn_stack fast.
 const MAX_RENDER_LINES: usize = 500;
 
+/// `links` are drawn in `link_fg` over the line's own colors.
 fn build_line_layout(
     line: &TermLine,
     default_fg: Color,
     _default_bg: Color,
     font_size: f32,
+    fonts: &[FamilyOwned],
+    links: &[Link],
+    link_fg: Color,
 ) -> TextLayout {
     let plain = line.plain_text();
-    let fonts = [
-        FamilyOwned::Name("JetBrains Mono".to_string()),
-        FamilyOwned::Name("Fira Code".to_string()),
-        FamilyOwned::Name("Cascadia Code".to_string()),
-        FamilyOwned::Monospace,
-    ];
     let default_attrs = Attrs::new()
         .font_size(font_size)
         .color(default_fg)
-        .family(&fonts);
+        .family(fonts);
     let mut attrs_list = AttrsList::new(default_attrs);
 
     let mut byte_offset: usize = 0;
@@ -592,12 +617,19 @@ fn build_line_layout(
         byte_offset = end;
 
         let fg = seg.fg.to_floem_color(default_fg);
-        let mut span_attrs = Attrs::new().font_size(font_size).color(fg).family(&fonts);
+        let mut span_attrs = Attrs::new().font_size(font_size).color(fg).family(fonts);
         if seg.bold {
             span_attrs = span_attrs.weight(Weight::BOLD);
         }
         attrs_list.add_span(start..end, span_attrs);
     }
+    for link in links {
+        let link_attrs = Attrs::new()
+            .font_size(font_size)
+            .color(link_fg)
+            .family(fonts);
+        attrs_list.add_span(link.range().clone(), link_attrs);
+    }
 
     let mut layout = TextLayout::new();
     layout.set_text(&plain, attrs_list, None);
@@ -610,10 +642,13 @@ fn build_line_layout(
 /// `shell`: the shell binary name or path to launch (e.g. "bash", "zsh").
 /// `cwd_out`: signal that receives the current working directory via OSC 7.
 /// `term_font_size`: reactive font size (8..32).
+/// `term_font_family`: reactive font family; ligatures are never used so cells stay aligned.
 /// `find_open`: whether the find bar is visible.
 /// `find_query`: current find query string.
 /// Shared PTY writer type — Arc so it can be cloned and shared with callers.
 type SharedPtyWriter = Arc<Mutex<Option<Box<dyn Write + Send>>>>;
+/// Shared terminal state, exposed so callers can capture a command's output.
+type SharedTermState = Arc<Mutex<TermState>>;
 
 /// One independent PTY terminal session.
 /// `pty_writer_out`: if `Some`, will be set to `Arc::clone` of this terminal's PTY
@@ -621,6 +656,11 @@ type SharedPtyWriter = Arc<Mutex<Option<Box<dyn Write + Send>>>>;
 /// (Feature 3 — Run in Terminal).
 /// `prompt_positions_out`: if `Some`, receives live-updated prompt line positions from
 /// OSC 133;A markers so the caller can implement prev/next command navigation (Feature 2).
+/// `term_state_out`: if `Some`, is set to this terminal's state so the caller can
+/// capture the output of commands it runs.
+/// `command_hover`: explains the command on a prompt line when it is hovered.
+/// `link_opener`: opens file locations and URLs in the output on Ctrl+Click.
+/// `workspace_root`: the shell gets the variables of its `.env` files.
 #[allow(clippy::too_many_arguments)]
 fn single_terminal(
     theme: RwSignal<PhazeTheme>,
@@ -631,13 +671,18 @@ fn single_terminal(
     // identically from inside the terminal regardless of which other widget has focus.
     cmd_state: GlobalCommandState,
     term_font_size: RwSignal<u32>,
+    term_font_family: RwSignal<String>,
     find_open: RwSignal<bool>,
     find_query: RwSignal<String>,
     pty_writer_out: Option<RwSignal<Option<SharedPtyWriter>>>,
     prompt_positions_out: Option<RwSignal<Vec<usize>>>,
+    term_state_out: Option<RwSignal<Option<SharedTermState>>>,
+    command_hover: CommandHover,
+    link_opener: LinkOpener,
+    workspace_root: RwSignal<PathBuf>,
 ) -> impl IntoView {
     // ── Shared VTE state ──────────────────────────────────────────────────
-    let term_state: Arc<Mutex<TermState>> = Arc::new(Mutex::new(TermState::new()));
+    let term_state: SharedTermState = Arc::new(Mutex::new(TermState::new()));
     let pty_writer: SharedPtyWriter = Arc::new(Mutex::new(None));
     // Keep the master PTY handle alive so we can resize it when the view dimensions change.
     let pty_master: Arc<Mutex<Option<Box<dyn MasterPty + Send>>>> = Arc::new(Mutex::new(None));
@@ -648,6 +693,9 @@ fn single_terminal(
     if let Some(out_sig) = pty_writer_out {
         out_sig.set(Some(Arc::clone(&pty_writer)));
     }
+    if let Some(out_sig) = term_state_out {
+        out_sig.set(Some(Arc::clone(&term_state)));
+    }
 
     // ── Update channel: reader thread → reactive signal ───────────────────
     let (update_tx, update_rx) = std::sync::mpsc::channel::<()>();
@@ -662,6 +710,7 @@ fn single_terminal(
 
     // ── Spawn PTY thread ──────────────────────────────────────────────────
     {
+        let root = workspace_root.get_untracked();
         let term_state_t = Arc::clone(&term_state);
         let pty_writer_t = Arc::clone(&pty_writer);
         let pty_master_t = Arc::clone(&pty_master);
@@ -689,6 +738,9 @@ fn single_terminal(
             };
 
             let mut cmd = CommandBuilder::new(&shell);
+            for (key, value) in EnvVars::for_workspace(&root).pairs() {
+                cmd.env(key, value);
+            }
             cmd.env("TERM", term_consts::TERM_TYPE);
             cmd.env("COLORTERM", term_consts::COLOR_TERM);
 
@@ -848,6 +900,7 @@ fn single_terminal(
     let is_focused = create_rw_signal(false);
 
     // ── Output list ───────────────────────────────────────────────────────
+    let term_state_hover = Arc::clone(&term_state);
     let output_list = dyn_stack(
         move || {
             let all = safe_get(lines, Vec::new());
@@ -856,53 +909,98 @@ fn single_terminal(
             all.into_iter().enumerate().skip(start).collect::<Vec<_>>()
         },
         |(i, _)| *i,
-        move |(_, line)| {
+        move |(i, line)| {
             let segments = line.segments.clone();
+            let hover_enter = command_hover.clone();
+            let hover_leave = command_hover.clone();
+            let term_state_h = Arc::clone(&term_state_hover);
+            let links = find_links(&line.plain_text());
 
             let initial_layout = {
                 let t = theme.get_untracked();
                 let p = &t.palette;
                 let fs = term_font_size.get_untracked() as f32;
+                let fonts = crate::fonts::font_stack(&term_font_family.get_untracked(), false);
                 if segments.is_empty() {
                     let mut layout = TextLayout::new();
                     let attrs = Attrs::new()
                         .font_size(fs)
                         .color(p.text_primary)
-                        .family(&[FamilyOwned::Monospace]);
+                        .family(&fonts);
                     layout.set_text(" ", AttrsList::new(attrs), None);
                     layout
                 } else {
-                    build_line_layout(&line, p.text_primary, p.bg_base, fs)
+                    build_line_layout(
+                        &line,
+                        p.text_primary,
+                        p.bg_base,
+                        fs,
+                        &fonts,
+                        &links,
+                        p.accent,
+                    )
                 }
             };
 
             let layout_signal: RwSignal<TextLayout> = create_rw_signal(initial_layout);
 
+            let links_layout = links.clone();
             create_effect(move |_| {
                 let t = theme.get();
                 let p = &t.palette;
                 let fs = term_font_size.get() as f32;
+                let fonts = crate::fonts::font_stack(&term_font_family.get(), false);
                 let new_layout = if segments.is_empty() {
                     let mut layout = TextLayout::new();
                     let attrs = Attrs::new()
                         .font_size(fs)
                         .color(p.text_primary)
-                        .family(&[FamilyOwned::Monospace]);
+                        .family(&fonts);
                     layout.set_text(" ", AttrsList::new(attrs), None);
                     layout
                 } else {
                     let reconstructed = TermLine {
                         segments: segments.clone(),
                     };
-                    build_line_layout(&reconstructed, p.text_primary, p.bg_base, fs)
+                    build_line_layout(
+                        &reconstructed,
+                        p.text_primary,
+                        p.bg_base,
+                        fs,
+                        &fonts,
+                        &links_layout,
+                        p.accent,
+                    )
                 };
                 layout_signal.set(new_layout);
             });
 
-            container(
-                floem::views::rich_text(move || layout_signal.get()).style(|s| s.width_full()),
-            )
+            // Relative paths in the output are relative to the shell's directory.
+            let link_cwd = move || {
+                let cwd = cwd_out.get_untracked();
+                (!cwd.is_empty()).then(|| PathBuf::from(cwd))
+            };
+            container(linked_line(
+                layout_signal,
+                links,
+                link_opener,
+                link_cwd,
+                theme,
+            ))
             .style(|s| s.padding_horiz(8.0).padding_vert(1.0).width_full())
+            // Prompt lines (OSC 133;A) hold a command that can be explained.
+            .on_event_cont(EventListener::PointerEnter, move |_| {
+                let command = term_state_h.lock().ok().and_then(|s| {
+                    if !s.prompt_line_positions.contains(&i) {
+                        return None;
+                    }
+                    command_from_prompt_line(&s.lines.get(i)?.plain_text())
+                });
+                if let Some(command) = command {
+                    hover_enter.enter(&command);
+                }
+            })
+            .on_event_cont(EventListener::PointerLeave, move |_| hover_leave.leave())
         },
     )
     .style(|s| s.flex_col().width_full().padding_vert(4.0));
@@ -1200,6 +1298,25 @@ fn single_terminal(
 
 // ── Multi-tab terminal panel ───────────────────────────────────────────────────
 
+/// Most output (in chars) fed back to the chat from one command; the tail is kept.
+const MAX_CHAT_RUN_OUTPUT: usize = 8_000;
+
+/// A command run from the chat panel and the output it printed.
+#[derive(Clone, Debug)]
+pub struct TerminalRun {
+    pub command: String,
+    pub output: String,
+}
+
+fn tail_chars(text: &str, max: usize) -> String {
+    let count = text.chars().count();
+    if count <= max {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - max).collect();
+    format!("[… {} earlier chars omitted]\n{tail}", count - max)
+}
+
 /// Real PTY terminal panel with multiple tab support.
 ///
 /// A "+" button spawns a new shell session. Each session is independent —
@@ -1209,6 +1326,14 @@ fn single_terminal(
 /// `run_in_terminal_text`: when set to `Some(text)` from outside (e.g. editor
 /// right-click → "Run in Terminal"), writes `text\n` to the active PTY and
 /// resets the signal to `None`.
+///
+/// `chat_run_request`: like `run_in_terminal_text`, but the command's output is
+/// captured and published to `chat_run_output` once the shell prints its next
+/// prompt (needs the OSC 133 shell integration, which is injected into bash).
+///
+/// `link_opener`: opens file locations and URLs in terminal output on
+/// Ctrl+Click.
+#[allow(clippy::too_many_arguments)]
 pub fn terminal_panel(
     theme: RwSignal<PhazeTheme>,
     // Unified global command state carrying all signals needed to dispatch
@@ -1216,13 +1341,18 @@ pub fn terminal_panel(
     // Ctrl+Alt+\ from inside the terminal PTY canvas.
     cmd_state: GlobalCommandState,
     run_in_terminal_text: RwSignal<Option<String>>,
+    chat_run_request: RwSignal<Option<String>>,
+    chat_run_output: RwSignal<Option<TerminalRun>>,
+    command_hover: CommandHover,
+    // Terminal font settings, shared with IdeState so zoom is persisted.
+    term_font_family: RwSignal<String>,
+    term_font_size: RwSignal<u32>,
+    link_opener: LinkOpener,
+    workspace_root: RwSignal<PathBuf>,
 ) -> impl IntoView {
     // Shell selector index (cycles through SHELLS)
     let shell_idx: RwSignal<usize> = create_rw_signal(0usize);
 
-    // Terminal zoom — independent font size, clamped 8..32
-    let term_font_size: RwSignal<u32> = create_rw_signal(13u32);
-
     // Terminal split — show two PTY panes side by side
     let term_split: RwSignal<bool> = create_rw_signal(false);
 
@@ -1233,6 +1363,8 @@ pub fn terminal_panel(
     // Feature 3: active terminal's PTY writer — set by single_terminal once PTY is ready.
     // Writing bytes to this sends them directly to the active shell.
     let active_pty_writer: RwSignal<Option<SharedPtyWriter>> = create_rw_signal(None);
+    // The active terminal's state, for capturing the output of chat-run commands.
+    let active_term_state: RwSignal<Option<SharedTermState>> = create_rw_signal(None);
 
     // Feature 2: command marker positions from OSC 133;A in the active terminal.
     // Each entry is a line index where a new prompt started.
@@ -1263,10 +1395,56 @@ pub fn terminal_panel(
         });
     }
 
+    // Commands run from chat code blocks: remember which terminal runs the
+    // command and publish its output when the next prompt appears.
+    let chat_run_pending: RwSignal<Option<(String, SharedTermState)>> = create_rw_signal(None);
+    create_effect(move |_| {
+        let Some(command) = chat_run_request.get() else {
+            return;
+        };
+        chat_run_request.set(None);
+        let (Some(writer_arc), Some(state_arc)) = (
+            active_pty_writer.get_untracked(),
+            active_term_state.get_untracked(),
+        ) else {
+            chat_run_output.set(Some(TerminalRun {
+                command,
+                output: "The terminal isn't ready yet; try again in a moment.".to_string(),
+            }));
+            return;
+        };
+        if let Ok(mut state) = state_arc.lock() {
+            state.start_capture();
+        }
+        if let Ok(mut guard) = writer_arc.lock() {
+            if let Some(ref mut w) = *guard {
+                let _ = w.write_all(format!("{command}\n").as_bytes());
+                let _ = w.flush();
+            }
+        }
+        chat_run_pending.set(Some((command, state_arc)));
+    });
+    create_effect(move |_| {
+        // Re-runs on every output update of the active terminal.
+        let _ = prompt_positions.get();
+        let Some((command, state_arc)) = chat_run_pending.get_untracked() else {
+            return;
+        };
+        let captured = state_arc.lock().ok().and_then(|mut s| s.take_captured());
+        if let Some(output) = captured {
+            chat_run_pending.set(None);
+            chat_run_output.set(Some(TerminalRun {
+                command,
+                output: tail_chars(&output, MAX_CHAT_RUN_OUTPUT),
+            }));
+        }
+    });
+
     // Each entry: (id, name_signal, clear_nonce_signal, shell_str, cwd_signal,
-    //              pty_writer_signal, prompt_positions_signal)
+    //              pty_writer_signal, prompt_positions_signal, term_state_signal)
     // pty_writer_signal: receives the PTY writer Arc once single_terminal initialises
     // prompt_positions_signal: receives OSC 133;A positions from that terminal
+    // term_state_signal: receives the terminal's state, for capturing command output
     type TabEntry = (
         usize,
         RwSignal<String>,
@@ -1275,6 +1453,7 @@ pub fn terminal_panel(
         RwSignal<String>,
         RwSignal<Option<SharedPtyWriter>>,
         RwSignal<Vec<usize>>,
+        RwSignal<Option<SharedTermState>>,
     );
     let tab_data: RwSignal<Vec<TabEntry>> = create_rw_signal(vec![(
         1usize,
@@ -1284,6 +1463,7 @@ pub fn terminal_panel(
         create_rw_signal(String::new()),
         create_rw_signal(None::<SharedPtyWriter>),
         create_rw_signal(Vec::<usize>::new()),
+        create_rw_signal(None::<SharedTermState>),
     )]);
     let active_tab: RwSignal<usize> = create_rw_signal(1);
     let next_id: RwSignal<usize> = create_rw_signal(2);
@@ -1293,10 +1473,12 @@ pub fn terminal_panel(
         create_effect(move |_| {
             let id = active_tab.get();
             let data = tab_data.get();
-            if let Some((_, _, _, _, _, pw_sig, pp_sig)) = data.iter().find(|(tid, ..)| *tid == id)
+            if let Some((_, _, _, _, _, pw_sig, pp_sig, ts_sig)) =
+                data.iter().find(|(tid, ..)| *tid == id)
             {
                 active_pty_writer.set(pw_sig.get());
                 prompt_positions.set(pp_sig.get());
+                active_term_state.set(ts_sig.get());
             }
         });
     }
@@ -1312,7 +1494,7 @@ pub fn terminal_panel(
                 tab_data
                     .get()
                     .into_iter()
-                    .map(|(id, ns, cs, _sh, cwd, _pw, _pp)| (id, ns, cs, cwd))
+                    .map(|(id, ns, cs, _sh, cwd, ..)| (id, ns, cs, cwd))
                     .collect::<Vec<_>>()
             },
             |(id, _, _, _)| *id,
@@ -1420,10 +1602,9 @@ pub fn terminal_panel(
                             .apply_if(!show, |s| s.display(Display::None))
                     })
                     .on_click_stop(move |_| {
-                        tab_data.update(|data| data.retain(|(tid, _, _, _, _, _, _)| *tid != id));
+                        tab_data.update(|data| data.retain(|(tid, ..)| *tid != id));
                         if active_tab.get_untracked() == id {
-                            if let Some((last, _, _, _, _, _, _)) = tab_data.get_untracked().last()
-                            {
+                            if let Some((last, ..)) = tab_data.get_untracked().last() {
                                 active_tab.set(*last);
                             }
                         }
@@ -1650,6 +1831,7 @@ pub fn terminal_panel(
                         create_rw_signal(String::new()),
                         create_rw_signal(None::<SharedPtyWriter>),
                         create_rw_signal(Vec::<usize>::new()),
+                        create_rw_signal(None::<SharedTermState>),
                     ))
                 });
                 active_tab.set(id);
@@ -1679,10 +1861,15 @@ pub fn terminal_panel(
         split_cwd,
         cmd_state.clone(),
         term_font_size,
+        term_font_family,
         term_find_open,
         term_find_query,
         None,
         None,
+        None,
+        command_hover.clone(),
+        link_opener,
+        workspace_root,
     );
 
     // ── Terminal instances (one per tab, hidden when not active) ──────────
@@ -1690,11 +1877,13 @@ pub fn terminal_panel(
         move || {
             safe_get(tab_data, Vec::new())
                 .into_iter()
-                .map(|(id, _, cs, shell, cwd, pw_sig, pp_sig)| (id, cs, shell, cwd, pw_sig, pp_sig))
+                .map(|(id, _, cs, shell, cwd, pw_sig, pp_sig, ts_sig)| {
+                    (id, cs, shell, cwd, pw_sig, pp_sig, ts_sig)
+                })
                 .collect::<Vec<_>>()
         },
-        |(id, _, _, _, _, _)| *id,
-        move |(id, clear_sig, shell, cwd_sig, pw_sig, pp_sig)| {
+        |(id, ..)| *id,
+        move |(id, clear_sig, shell, cwd_sig, pw_sig, pp_sig, ts_sig)| {
             single_terminal(
                 theme,
                 clear_sig,
@@ -1702,10 +1891,15 @@ pub fn terminal_panel(
                 cwd_sig,
                 cmd_state.clone(),
                 term_font_size,
+                term_font_family,
                 term_find_open,
                 term_find_query,
                 Some(pw_sig),
                 Some(pp_sig),
+                Some(ts_sig),
+                command_hover.clone(),
+                link_opener,
+                workspace_root,
             )
             .style(move |s| {
                 s.size_full()
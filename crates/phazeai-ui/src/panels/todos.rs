@@ -0,0 +1,310 @@
+//! TODO / FIXME / HACK comments — the bottom-panel "TODOS" tab.
+//!
+//! A background thread scans the workspace with the core scanner (honoring
+//! `.gitignore`), publishes the list, then fills in authors from `git blame`.
+//! The workspace is rescanned when it changes, when the tab is opened and
+//! from the Refresh button. The list can be filtered by tag, author and
+//! text; `@todos` in a chat message sends it to the agent.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use floem::{
+    action::show_context_menu,
+    ext_event::create_signal_from_channel,
+    menu::{Menu, MenuItem},
+    reactive::{create_effect, create_rw_signal, RwSignal, SignalGet, SignalUpdate},
+    views::{container, dyn_stack, label, scroll, stack, text_input, Decorators},
+    IntoView,
+};
+use phazeai_core::analysis::todos::{self, TodoComment, TodoFilter, TODO_TAGS};
+
+use crate::app::{IdeState, Tab};
+use crate::panels::git_history::action_button;
+use crate::util::safe_get;
+
+/// Rows shown at once; narrow the filter to see the rest.
+const MAX_ROWS: usize = 500;
+
+/// Rescan the workspace whenever it or `rescan` changes, publishing results
+/// into `todos`. `scanning` is true until authors have been filled in.
+pub(crate) fn start_todo_scanner(
+    todos: RwSignal<Vec<TodoComment>>,
+    scanning: RwSignal<bool>,
+    workspace_root: RwSignal<PathBuf>,
+    rescan: RwSignal<u64>,
+) {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<(u64, Vec<TodoComment>, bool)>(2);
+    let results = create_signal_from_channel(rx);
+    let generation = Arc::new(AtomicU64::new(0));
+
+    {
+        let generation = generation.clone();
+        create_effect(move |_| {
+            let Some((gen, list, done)) = results.get() else {
+                return;
+            };
+            // A newer scan has started; its results will follow.
+            if gen != generation.load(Ordering::Relaxed) {
+                return;
+            }
+            todos.set(list);
+            scanning.set(!done);
+        });
+    }
+
+    create_effect(move |_| {
+        let root = workspace_root.get();
+        let _ = rescan.get();
+        let gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
+        scanning.set(true);
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let mut found = todos::scan_workspace(&root);
+            let _ = tx.send((gen, found.clone(), false));
+            let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            else {
+                let _ = tx.send((gen, found, true));
+                return;
+            };
+            rt.block_on(todos::attribute_authors(&root, &mut found));
+            let _ = tx.send((gen, found, true));
+        });
+    });
+}
+
+pub(crate) fn todos_tab_label(todos: RwSignal<Vec<TodoComment>>) -> String {
+    let n = todos.get().len();
+    if n == 0 {
+        "TODOS".to_string()
+    } else {
+        format!("TODOS ({n})")
+    }
+}
+
+pub fn todos_panel(state: IdeState) -> impl IntoView {
+    let theme = state.theme;
+    let todos = state.todos;
+    let scanning = state.todos_scanning;
+    let rescan = state.todos_rescan;
+    let workspace_root = state.workspace_root;
+    let open_file = state.open_file;
+    let goto_line = state.goto_line;
+
+    let filter_text = create_rw_signal(String::new());
+    let tag = create_rw_signal(None::<String>);
+    let author = create_rw_signal(None::<String>);
+
+    // Opening the tab picks up comments added since the last scan.
+    {
+        let (tab, shown) = (state.bottom_panel_tab, state.show_bottom_panel);
+        create_effect(move |was_open: Option<bool>| {
+            let open = shown.get() && tab.get() == Tab::Todos;
+            if open && was_open == Some(false) {
+                rescan.update(|n| *n += 1);
+            }
+            open
+        });
+    }
+
+    let filtered = move || {
+        let filter = TodoFilter {
+            tag: tag.get(),
+            author: author.get(),
+            text: filter_text.get(),
+        };
+        safe_get(todos, Vec::new())
+            .into_iter()
+            .filter(|t| filter.matches(t))
+            .collect::<Vec<_>>()
+    };
+
+    let pick_tag = move || {
+        let list = todos.get_untracked();
+        let current = tag.get_untracked();
+        let mark = |on: bool| if on { "✓ " } else { "   " };
+        let mut menu = Menu::new("").entry(
+            MenuItem::new(format!("{}All Tags", mark(current.is_none())))
+                .action(move || tag.set(None)),
+        );
+        for &t in TODO_TAGS {
+            let count = list.iter().filter(|c| c.tag == t).count();
+            menu = menu.entry(
+                MenuItem::new(format!(
+                    "{}{t} ({count})",
+                    mark(current.as_deref() == Some(t))
+                ))
+                .action(move || tag.set(Some(t.to_string()))),
+            );
+        }
+        show_context_menu(menu, None);
+    };
+
+    let pick_author = move || {
+        let list = todos.get_untracked();
+        let current = author.get_untracked();
+        let mark = |on: bool| if on { "✓ " } else { "   " };
+        let mut menu = Menu::new("").entry(
+            MenuItem::new(format!("{}All Authors", mark(current.is_none())))
+                .action(move || author.set(None)),
+        );
+        for name in todos::todo_authors(&list) {
+            let count = list
+                .iter()
+                .filter(|c| c.author.as_ref() == Some(&name))
+                .count();
+            let label = format!("{}{name} ({count})", mark(current.as_ref() == Some(&name)));
+            menu = menu.entry(MenuItem::new(label).action(move || author.set(Some(name.clone()))));
+        }
+        show_context_menu(menu, None);
+    };
+
+    let header = stack((
+        text_input(filter_text)
+            .placeholder("Filter by text or path")
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.width(220.0)
+                    .font_size(12.0)
+                    .background(p.bg_elevated)
+                    .border(1.0)
+                    .border_color(p.border)
+                    .border_radius(3.0)
+                    .padding_horiz(6.0)
+                    .color(p.text_primary)
+            }),
+        action_button(
+            move || format!("Tag: {} ▾", tag.get().unwrap_or_else(|| "All".to_string())),
+            theme,
+            pick_tag,
+        ),
+        action_button(
+            move || {
+                format!(
+                    "Author: {} ▾",
+                    author.get().unwrap_or_else(|| "All".to_string())
+                )
+            },
+            theme,
+            pick_author,
+        ),
+        action_button(
+            || "Refresh".to_string(),
+            theme,
+            move || rescan.update(|n| *n += 1),
+        ),
+        label(move || {
+            let shown = filtered().len();
+            let total = todos.get().len();
+            let status = if scanning.get() {
+                "  ·  scanning…"
+            } else {
+                ""
+            };
+            if shown == total {
+                format!("{total} comments{status}")
+            } else {
+                format!("{shown} of {total} comments{status}")
+            }
+        })
+        .style(move |s| s.font_size(11.0).color(theme.get().palette.text_muted)),
+    ))
+    .style(|s| {
+        s.items_center()
+            .gap(6.0)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .width_full()
+    });
+
+    let rows = dyn_stack(
+        move || filtered().into_iter().take(MAX_ROWS).collect::<Vec<_>>(),
+        |t| format!("{}:{}:{}:{:?}", t.path.display(), t.line, t.text, t.author),
+        move |todo: TodoComment| {
+            let hovered = create_rw_signal(false);
+            let tag_name = todo.tag;
+            let location = format!("{}:{}", todo.path.display(), todo.line + 1);
+            let text = todo.text.clone();
+            let who = todo.author.clone().unwrap_or_default();
+            let (path, line) = (todo.path.clone(), todo.line);
+            stack((
+                label(move || tag_name.to_string()).style(move |s| {
+                    let p = theme.get().palette;
+                    let color = match tag_name {
+                        "FIXME" => p.error,
+                        "HACK" | "XXX" => p.warning,
+                        _ => p.accent,
+                    };
+                    s.font_size(11.0).min_width(44.0).color(color)
+                }),
+                label(move || text.clone()).style(move |s| {
+                    s.font_size(12.0)
+                        .flex_grow(1.0)
+                        .min_width(0.0)
+                        .color(theme.get().palette.text_primary)
+                }),
+                label(move || who.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .min_width(100.0)
+                        .color(theme.get().palette.text_muted)
+                }),
+                label(move || location.clone()).style(move |s| {
+                    s.font_size(11.0)
+                        .min_width(200.0)
+                        .color(theme.get().palette.text_secondary)
+                }),
+            ))
+            .style(move |s| {
+                let p = theme.get().palette;
+                s.items_center()
+                    .gap(8.0)
+                    .padding_horiz(10.0)
+                    .padding_vert(2.0)
+                    .width_full()
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .apply_if(hovered.get(), |s| s.background(p.bg_elevated))
+            })
+            .on_click_stop(move |_| {
+                open_file.set(Some(workspace_root.get_untracked().join(&path)));
+                goto_line.set(line + 1);
+            })
+            .on_event_stop(floem::event::EventListener::PointerEnter, move |_| {
+                hovered.set(true)
+            })
+            .on_event_stop(floem::event::EventListener::PointerLeave, move |_| {
+                hovered.set(false)
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let empty_msg = container(
+        label(move || {
+            if scanning.get() {
+                "Scanning the workspace…".to_string()
+            } else {
+                "No TODO, FIXME, HACK or XXX comments found.".to_string()
+            }
+        })
+        .style(move |s| {
+            s.font_size(12.0)
+                .color(theme.get().palette.text_muted)
+                .padding(12.0)
+        }),
+    )
+    .style(move |s| {
+        s.apply_if(!todos.get().is_empty(), |s| {
+            s.display(floem::style::Display::None)
+        })
+    });
+
+    stack((
+        header,
+        empty_msg,
+        scroll(rows).style(|s| s.flex_grow(1.0).width_full()),
+    ))
+    .style(|s| s.flex_col().width_full().height_full())
+}
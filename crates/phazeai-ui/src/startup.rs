@@ -0,0 +1,202 @@
+//! Startup timing, and the work left until the window is up.
+//!
+//! Launch goes through a handful of named phases: settings, the workspace,
+//! building the views. Heavy work that nothing on screen needs yet — the
+//! syntax and theme sets, the system font list — runs on [`background`]
+//! threads alongside them, and work that can wait, like starting the
+//! sidecar, runs [`after_first_frame`]. `phazeai-ui --profile-startup`
+//! prints each phase's timing once the window is up and the background
+//! phases are done, flagging a first frame slower than [`STARTUP_BUDGET`].
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long launch may take to show the window.
+pub const STARTUP_BUDGET: Duration = Duration::from_millis(400);
+
+/// One timed piece of startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Phase {
+    pub name: &'static str,
+    /// Since launch.
+    pub start: Duration,
+    pub took: Duration,
+    /// Ran on its own thread, off the path to the first frame.
+    pub background: bool,
+}
+
+/// The phases of one launch and when the first frame came.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StartupProfile {
+    pub phases: Vec<Phase>,
+    /// Since launch.
+    pub first_frame: Option<Duration>,
+}
+
+impl StartupProfile {
+    pub fn record(&mut self, phase: Phase) {
+        self.phases.push(phase);
+    }
+
+    /// Whether the first frame came later than `budget`; not while it
+    /// hasn't come yet.
+    pub fn over_budget(&self, budget: Duration) -> bool {
+        self.first_frame.is_some_and(|t| t > budget)
+    }
+
+    /// The phases in the order they started, as a table.
+    pub fn report(&self, budget: Duration) -> String {
+        let mut phases: Vec<&Phase> = self.phases.iter().collect();
+        phases.sort_by_key(|p| p.start);
+        let width = phases.iter().map(|p| p.name.len()).max().unwrap_or(0);
+        let mut out = String::from("Startup profile:\n");
+        for phase in phases {
+            let _ = writeln!(
+                out,
+                "  {:<width$}  {:>7.1} ms  at {:>7.1} ms{}",
+                phase.name,
+                millis(phase.took),
+                millis(phase.start),
+                if phase.background {
+                    "  (background)"
+                } else {
+                    ""
+                },
+            );
+        }
+        match self.first_frame {
+            Some(t) => {
+                let _ = write!(
+                    out,
+                    "First frame at {:.1} ms, budget {:.0} ms{}",
+                    millis(t),
+                    millis(budget),
+                    if self.over_budget(budget) {
+                        " — over budget"
+                    } else {
+                        ""
+                    },
+                );
+            }
+            None => out.push_str("No frame yet"),
+        }
+        out
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+struct Launch {
+    at: Instant,
+    print: bool,
+    /// Background phases still running.
+    pending: AtomicUsize,
+    profile: Mutex<StartupProfile>,
+    printed: Mutex<bool>,
+}
+
+impl Launch {
+    fn new(print: bool) -> Self {
+        Self {
+            at: Instant::now(),
+            print,
+            pending: AtomicUsize::new(0),
+            profile: Mutex::new(StartupProfile::default()),
+            printed: Mutex::new(false),
+        }
+    }
+
+    /// Print the report if asked to, once there's a frame and nothing is
+    /// left running.
+    fn print_when_done(&self) {
+        if !self.print || self.pending.load(Ordering::SeqCst) > 0 {
+            return;
+        }
+        let Ok(profile) = self.profile.lock() else {
+            return;
+        };
+        if profile.first_frame.is_none() {
+            return;
+        }
+        let Ok(mut printed) = self.printed.lock() else {
+            return;
+        };
+        if !*printed {
+            *printed = true;
+            eprintln!("{}", profile.report(STARTUP_BUDGET));
+        }
+    }
+}
+
+static LAUNCH: OnceLock<Launch> = OnceLock::new();
+
+fn launch() -> &'static Launch {
+    LAUNCH.get_or_init(|| Launch::new(false))
+}
+
+/// Start the clock; `print` the report once the window is up. Call first
+/// thing in `main` — without it the clock starts at the first phase.
+pub fn begin(print: bool) {
+    let _ = LAUNCH.set(Launch::new(print));
+}
+
+fn record(name: &'static str, started: Instant, background: bool) {
+    let launch = launch();
+    let phase = Phase {
+        name,
+        start: started.saturating_duration_since(launch.at),
+        took: started.elapsed(),
+        background,
+    };
+    if let Ok(mut profile) = launch.profile.lock() {
+        profile.record(phase);
+    }
+}
+
+/// Run `f` as the phase `name`.
+pub fn phase<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    launch();
+    let started = Instant::now();
+    let result = f();
+    record(name, started, false);
+    result
+}
+
+/// Run `f` as the phase `name` on a thread of its own.
+pub fn background(name: &'static str, f: impl FnOnce() + Send + 'static) {
+    let launch = launch();
+    launch.pending.fetch_add(1, Ordering::SeqCst);
+    let spawned = std::thread::Builder::new()
+        .name(format!("startup-{name}"))
+        .spawn(move || {
+            let started = Instant::now();
+            f();
+            record(name, started, true);
+            launch.pending.fetch_sub(1, Ordering::SeqCst);
+            launch.print_when_done();
+        });
+    if let Err(e) = spawned {
+        launch.pending.fetch_sub(1, Ordering::SeqCst);
+        eprintln!("Couldn't start {name} in the background: {e}");
+    }
+}
+
+/// Run `f` once the event loop has shown the window.
+pub fn after_first_frame(f: impl FnOnce() + 'static) {
+    floem::action::exec_after(Duration::ZERO, move |_| f());
+}
+
+/// Note that the window is up. Later calls do nothing.
+pub fn first_frame() {
+    let launch = launch();
+    if let Ok(mut profile) = launch.profile.lock() {
+        if profile.first_frame.is_none() {
+            profile.first_frame = Some(launch.at.elapsed());
+        }
+    }
+    launch.print_when_done();
+}
@@ -19,6 +19,9 @@ pub enum ThemeVariant {
     RootShell,
     // Light
     Light,
+    // Accessibility
+    HighContrast,
+    HighContrastLight,
 }
 
 impl ThemeVariant {
@@ -36,6 +39,8 @@ impl ThemeVariant {
             ThemeVariant::MatrixGreen,
             ThemeVariant::RootShell,
             ThemeVariant::Light,
+            ThemeVariant::HighContrast,
+            ThemeVariant::HighContrastLight,
         ]
     }
 
@@ -52,6 +57,8 @@ impl ThemeVariant {
             "matrixgreen" | "matrix" => Self::MatrixGreen,
             "rootshell" | "root" => Self::RootShell,
             "light" => Self::Light,
+            "highcontrast" | "contrast" | "hc" => Self::HighContrast,
+            "highcontrastlight" | "hclight" => Self::HighContrastLight,
             _ => Self::Dark,
         }
     }
@@ -70,10 +77,103 @@ impl ThemeVariant {
             Self::MatrixGreen => "Matrix Green",
             Self::RootShell => "Root Shell",
             Self::Light => "Light",
+            Self::HighContrast => "High Contrast",
+            Self::HighContrastLight => "High Contrast Light",
+        }
+    }
+
+    /// True for variants with a light background.
+    pub fn is_light(&self) -> bool {
+        matches!(self, Self::Light | Self::HighContrastLight)
+    }
+
+    /// The syntect theme used for code highlighting when the user has not
+    /// picked one for this UI theme.
+    pub fn default_syntax_theme(&self) -> &'static str {
+        match self {
+            Self::Light | Self::HighContrastLight => "InspiredGitHub",
+            Self::Dracula | Self::TokyoNight | Self::Synthwave84 => "base16-mocha.dark",
+            Self::Monokai => "base16-eighties.dark",
+            _ => "base16-ocean.dark",
         }
     }
 }
 
+/// Syntax highlighting themes bundled with syntect (`ThemeSet::load_defaults`).
+pub const SYNTAX_THEMES: &[&str] = &[
+    "base16-ocean.dark",
+    "base16-eighties.dark",
+    "base16-mocha.dark",
+    "base16-ocean.light",
+    "InspiredGitHub",
+    "Solarized (dark)",
+    "Solarized (light)",
+];
+
+/// Ask the OS whether it prefers a dark appearance.
+///
+/// Returns `None` when the preference cannot be determined (no desktop
+/// settings daemon, unsupported platform, command missing).
+pub fn detect_system_dark() -> Option<bool> {
+    if let Ok(gtk) = std::env::var("GTK_THEME") {
+        if !gtk.is_empty() {
+            return Some(gtk.to_lowercase().contains("dark"));
+        }
+    }
+
+    let run = |cmd: &str, args: &[&str]| -> Option<(bool, String)> {
+        let out = std::process::Command::new(cmd).args(args).output().ok()?;
+        Some((
+            out.status.success(),
+            String::from_utf8_lossy(&out.stdout).trim().to_lowercase(),
+        ))
+    };
+
+    if cfg!(target_os = "macos") {
+        // `AppleInterfaceStyle` only exists while Dark Mode is on.
+        let (ok, out) = run("defaults", &["read", "-g", "AppleInterfaceStyle"])?;
+        return Some(ok && out.contains("dark"));
+    }
+
+    if cfg!(target_os = "windows") {
+        let (ok, out) = run(
+            "reg",
+            &[
+                "query",
+                r"HKCU\Software\Microsoft\Windows\CurrentVersion\Themes\Personalize",
+                "/v",
+                "AppsUseLightTheme",
+            ],
+        )?;
+        if !ok {
+            return None;
+        }
+        return Some(out.contains("0x0"));
+    }
+
+    // Linux / BSD: freedesktop color-scheme first, then the GTK theme name.
+    if let Some((true, out)) = run(
+        "gsettings",
+        &["get", "org.gnome.desktop.interface", "color-scheme"],
+    ) {
+        if out.contains("prefer-dark") {
+            return Some(true);
+        }
+        if out.contains("prefer-light") {
+            return Some(false);
+        }
+    }
+    if let Some((true, out)) = run(
+        "gsettings",
+        &["get", "org.gnome.desktop.interface", "gtk-theme"],
+    ) {
+        if !out.is_empty() {
+            return Some(out.contains("dark"));
+        }
+    }
+    None
+}
+
 /// Raw brand palette — all literal color values live here.
 #[derive(Debug, Clone)]
 pub struct PhazePalette {
@@ -150,6 +250,10 @@ pub struct PhazePalette {
     pub indent_guide: Color,
     pub inlay_hint: Color,
     pub matching_bracket_bg: Color,
+    /// Occurrences of the symbol under the cursor where it is read.
+    pub word_highlight_read: Color,
+    /// Occurrences of the symbol under the cursor where it is written.
+    pub word_highlight_write: Color,
     pub cursor_line_bg: Color,
     pub minimap_bar: Color,
 
@@ -196,6 +300,8 @@ impl PhazePalette {
         self.indent_guide = Color::from_rgba8(255, 255, 255, 12);
         self.inlay_hint = Color::from_rgba8(100, 180, 255, 220);
         self.matching_bracket_bg = Color::from_rgba8(120, 120, 140, 35);
+        self.word_highlight_read = Color::from_rgba8(100, 160, 255, 50);
+        self.word_highlight_write = Color::from_rgba8(255, 150, 60, 60);
         self.cursor_line_bg = Color::from_rgba8(255, 255, 255, 12);
         self.minimap_bar = Color::from_rgba8(180, 190, 210, 60);
 
@@ -275,6 +381,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -351,6 +459,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -427,6 +537,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -442,6 +554,191 @@ impl PhazePalette {
         .with_derived_defaults()
     }
 
+    /// WCAG AAA oriented: pure black background, white text, bright yellow
+    /// focus/accent and no translucent glass so every edge stays visible.
+    pub fn high_contrast() -> Self {
+        Self {
+            bg_deep: Color::from_rgb8(0, 0, 0),
+            bg_base: Color::from_rgb8(0, 0, 0),
+            bg_surface: Color::from_rgb8(0, 0, 0),
+            bg_panel: Color::from_rgb8(0, 0, 0),
+            bg_elevated: Color::from_rgb8(20, 20, 20),
+
+            text_primary: Color::from_rgb8(255, 255, 255),
+            text_secondary: Color::from_rgb8(255, 255, 255),
+            text_muted: Color::from_rgb8(215, 215, 215),
+            text_disabled: Color::from_rgb8(160, 160, 160),
+
+            accent: Color::from_rgb8(255, 215, 0),
+            accent_hover: Color::from_rgb8(255, 235, 90),
+            accent_dim: Color::from_rgba8(255, 215, 0, 70),
+
+            success: Color::from_rgb8(0, 255, 120),
+            warning: Color::from_rgb8(255, 200, 0),
+            error: Color::from_rgb8(255, 90, 90),
+            info: Color::from_rgb8(110, 200, 255),
+
+            git_added: Color::from_rgb8(0, 255, 120),
+            git_modified: Color::from_rgb8(110, 200, 255),
+            git_deleted: Color::from_rgb8(255, 90, 90),
+            git_untracked: Color::from_rgb8(215, 215, 215),
+
+            border: Color::from_rgb8(255, 255, 255),
+            border_focus: Color::from_rgb8(255, 215, 0),
+
+            selection: Color::from_rgba8(255, 215, 0, 90),
+
+            syn_keyword: Color::from_rgb8(110, 200, 255),
+            syn_string: Color::from_rgb8(255, 180, 120),
+            syn_comment: Color::from_rgb8(120, 255, 120),
+            syn_function: Color::from_rgb8(255, 255, 140),
+            syn_number: Color::from_rgb8(180, 255, 200),
+            syn_type: Color::from_rgb8(100, 255, 255),
+            syn_operator: Color::from_rgb8(255, 255, 255),
+            syn_macro: Color::from_rgb8(255, 140, 255),
+
+            // Derived — overwritten by with_derived_defaults()
+            diag_error: Color::TRANSPARENT,
+            diag_warning: Color::TRANSPARENT,
+            diag_info: Color::TRANSPARENT,
+            diag_hint: Color::TRANSPARENT,
+            diff_added_fg: Color::TRANSPARENT,
+            diff_added_bg: Color::TRANSPARENT,
+            diff_removed_fg: Color::TRANSPARENT,
+            diff_removed_bg: Color::TRANSPARENT,
+            diff_header_fg: Color::TRANSPARENT,
+            diff_header_bg: Color::TRANSPARENT,
+            bracket_1: Color::TRANSPARENT,
+            bracket_2: Color::TRANSPARENT,
+            bracket_3: Color::TRANSPARENT,
+            bracket_4: Color::TRANSPARENT,
+            find_match_bg: Color::TRANSPARENT,
+            find_match_border: Color::TRANSPARENT,
+            indent_guide: Color::TRANSPARENT,
+            inlay_hint: Color::TRANSPARENT,
+            matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
+            cursor_line_bg: Color::TRANSPARENT,
+            minimap_bar: Color::TRANSPARENT,
+            overlay_bg: Color::TRANSPARENT,
+            overlay_bg_light: Color::TRANSPARENT,
+            button_primary_bg: Color::TRANSPARENT,
+            button_primary_fg: Color::TRANSPARENT,
+            button_hover_bg: Color::TRANSPARENT,
+
+            glass_bg: Color::from_rgb8(0, 0, 0),
+            glass_border: Color::from_rgb8(255, 255, 255),
+            glow: Color::TRANSPARENT,
+        }
+        .with_derived_defaults()
+        .with_high_contrast_overrides()
+    }
+
+    fn with_high_contrast_overrides(mut self) -> Self {
+        self.button_primary_fg = Color::BLACK;
+        self.cursor_line_bg = Color::from_rgba8(255, 255, 255, 30);
+        self.indent_guide = Color::from_rgba8(255, 255, 255, 60);
+        self.find_match_border = Color::from_rgb8(255, 215, 0);
+        self.overlay_bg = Color::from_rgba8(0, 0, 0, 220);
+        self
+    }
+
+    /// Light counterpart of [`Self::high_contrast`]: white background, black
+    /// text and borders, dark blue focus/accent for bright rooms and low vision.
+    pub fn high_contrast_light() -> Self {
+        Self {
+            bg_deep: Color::from_rgb8(255, 255, 255),
+            bg_base: Color::from_rgb8(255, 255, 255),
+            bg_surface: Color::from_rgb8(255, 255, 255),
+            bg_panel: Color::from_rgb8(255, 255, 255),
+            bg_elevated: Color::from_rgb8(235, 235, 235),
+
+            text_primary: Color::from_rgb8(0, 0, 0),
+            text_secondary: Color::from_rgb8(0, 0, 0),
+            text_muted: Color::from_rgb8(45, 45, 45),
+            text_disabled: Color::from_rgb8(100, 100, 100),
+
+            accent: Color::from_rgb8(0, 50, 160),
+            accent_hover: Color::from_rgb8(0, 30, 110),
+            accent_dim: Color::from_rgba8(0, 50, 160, 45),
+
+            success: Color::from_rgb8(0, 100, 0),
+            warning: Color::from_rgb8(130, 70, 0),
+            error: Color::from_rgb8(180, 0, 0),
+            info: Color::from_rgb8(0, 70, 150),
+
+            git_added: Color::from_rgb8(0, 100, 0),
+            git_modified: Color::from_rgb8(0, 70, 150),
+            git_deleted: Color::from_rgb8(180, 0, 0),
+            git_untracked: Color::from_rgb8(45, 45, 45),
+
+            border: Color::from_rgb8(0, 0, 0),
+            border_focus: Color::from_rgb8(0, 50, 160),
+
+            selection: Color::from_rgba8(0, 50, 160, 60),
+
+            syn_keyword: Color::from_rgb8(0, 0, 170),
+            syn_string: Color::from_rgb8(140, 30, 0),
+            syn_comment: Color::from_rgb8(0, 95, 0),
+            syn_function: Color::from_rgb8(100, 0, 140),
+            syn_number: Color::from_rgb8(0, 85, 85),
+            syn_type: Color::from_rgb8(0, 70, 130),
+            syn_operator: Color::from_rgb8(0, 0, 0),
+            syn_macro: Color::from_rgb8(150, 0, 100),
+
+            // Derived — overwritten by with_derived_defaults()
+            diag_error: Color::TRANSPARENT,
+            diag_warning: Color::TRANSPARENT,
+            diag_info: Color::TRANSPARENT,
+            diag_hint: Color::TRANSPARENT,
+            diff_added_fg: Color::TRANSPARENT,
+            diff_added_bg: Color::TRANSPARENT,
+            diff_removed_fg: Color::TRANSPARENT,
+            diff_removed_bg: Color::TRANSPARENT,
+            diff_header_fg: Color::TRANSPARENT,
+            diff_header_bg: Color::TRANSPARENT,
+            bracket_1: Color::TRANSPARENT,
+            bracket_2: Color::TRANSPARENT,
+            bracket_3: Color::TRANSPARENT,
+            bracket_4: Color::TRANSPARENT,
+            find_match_bg: Color::TRANSPARENT,
+            find_match_border: Color::TRANSPARENT,
+            indent_guide: Color::TRANSPARENT,
+            inlay_hint: Color::TRANSPARENT,
+            matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
+            cursor_line_bg: Color::TRANSPARENT,
+            minimap_bar: Color::TRANSPARENT,
+            overlay_bg: Color::TRANSPARENT,
+            overlay_bg_light: Color::TRANSPARENT,
+            button_primary_bg: Color::TRANSPARENT,
+            button_primary_fg: Color::TRANSPARENT,
+            button_hover_bg: Color::TRANSPARENT,
+
+            glass_bg: Color::from_rgb8(255, 255, 255),
+            glass_border: Color::from_rgb8(0, 0, 0),
+            glow: Color::TRANSPARENT,
+        }
+        .with_derived_defaults()
+        .with_high_contrast_light_overrides()
+    }
+
+    fn with_high_contrast_light_overrides(mut self) -> Self {
+        self.diag_error = self.error;
+        self.diag_warning = self.warning;
+        self.diag_info = self.info;
+        self.diag_hint = self.success;
+        self.cursor_line_bg = Color::from_rgba8(0, 0, 0, 20);
+        self.indent_guide = Color::from_rgba8(0, 0, 0, 70);
+        self.find_match_bg = Color::from_rgba8(255, 220, 0, 140);
+        self.find_match_border = Color::from_rgb8(0, 0, 0);
+        self.button_hover_bg = Color::from_rgba8(0, 0, 0, 25);
+        self.overlay_bg = Color::from_rgba8(255, 255, 255, 220);
+        self
+    }
+
     // ── Cyberpunk 2077 ───────────────────────────────────────────────────────
     pub fn cyberpunk() -> Self {
         Self {
@@ -504,6 +801,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -581,6 +880,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -658,6 +959,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -735,6 +1038,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -812,6 +1117,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -889,6 +1196,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -966,6 +1275,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -1043,6 +1354,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -1120,6 +1433,8 @@ impl PhazePalette {
             indent_guide: Color::TRANSPARENT,
             inlay_hint: Color::TRANSPARENT,
             matching_bracket_bg: Color::TRANSPARENT,
+            word_highlight_read: Color::TRANSPARENT,
+            word_highlight_write: Color::TRANSPARENT,
             cursor_line_bg: Color::TRANSPARENT,
             minimap_bar: Color::TRANSPARENT,
             overlay_bg: Color::TRANSPARENT,
@@ -1185,6 +1500,8 @@ impl PhazeTheme {
             ThemeVariant::MatrixGreen => PhazePalette::matrix_green(),
             ThemeVariant::RootShell => PhazePalette::root_shell(),
             ThemeVariant::Light => PhazePalette::light(),
+            ThemeVariant::HighContrast => PhazePalette::high_contrast(),
+            ThemeVariant::HighContrastLight => PhazePalette::high_contrast_light(),
         };
         Self {
             variant: v,
@@ -1197,7 +1514,7 @@ impl PhazeTheme {
     }
 
     pub fn is_dark(&self) -> bool {
-        self.variant != ThemeVariant::Light
+        !self.variant.is_light()
     }
 
     /// True if this theme uses the cosmic glass look (animated nebula canvas).
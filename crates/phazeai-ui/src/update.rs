@@ -0,0 +1,132 @@
+//! Updates for the IDE: a check for a newer release once the window is up,
+//! the "Update PhazeAI" command that downloads and stages it, and putting a
+//! staged release in place at the next launch.
+
+use floem::ext_event::create_ext_action;
+use floem::reactive::{RwSignal, Scope, SignalGet, SignalUpdate};
+use phazeai_core::update::{self, Channel, UpdateSettings, CURRENT_VERSION};
+use phazeai_core::Settings;
+
+use crate::app::show_toast;
+
+/// The name of the IDE's assets in the release feed.
+pub const BINARY: &str = "phazeai-ide";
+
+/// Install a release staged by "Update PhazeAI" and start it in place of
+/// this process. Call first thing in `main`.
+pub fn apply_staged() {
+    let (Ok(exe), Some(key)) = (std::env::current_exe(), update::release_key()) else {
+        return;
+    };
+    match update::apply_staged(&update::updates_dir(), BINARY, &exe, CURRENT_VERSION, &key) {
+        Ok(Some(staged)) => {
+            eprintln!("[PhazeAI] updated to {}", staged.version);
+            let e = update::restart(&exe);
+            eprintln!("[PhazeAI] couldn't restart after updating: {e}");
+            std::process::exit(1);
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("[PhazeAI] couldn't install the staged update: {e}"),
+    }
+}
+
+/// Run `f` on a thread of its own with a runtime, handing the result to
+/// `done` on the UI thread.
+fn in_background<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+    done: impl FnOnce(Result<T, String>) + 'static,
+) {
+    let done = create_ext_action(Scope::current(), done);
+    std::thread::spawn(move || done(f()));
+}
+
+fn block_on<T>(
+    f: impl std::future::Future<Output = Result<T, phazeai_core::PhazeError>>,
+) -> Result<T, String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?
+        .block_on(f)
+        .map_err(|e| e.to_string())
+}
+
+/// Say so when there's a newer release; quiet otherwise, and when the feed
+/// can't be reached.
+pub fn check_on_launch(settings: UpdateSettings, toast: RwSignal<Option<String>>) {
+    if !settings.check_on_launch {
+        return;
+    }
+    let channel = settings.channel;
+    in_background(
+        move || block_on(update::check(&settings)),
+        move |result| {
+            if let Ok(Some(release)) = result {
+                show_toast(
+                    toast,
+                    format!(
+                        "PhazeAI {} is available on the {} channel; run Update PhazeAI to install it",
+                        release.version,
+                        channel.label()
+                    ),
+                );
+            }
+        },
+    );
+}
+
+/// Download the newest release on the configured channel and stage it for
+/// the next launch, reporting how it went.
+pub fn download_and_stage(toast: RwSignal<Option<String>>) {
+    let settings = Settings::load().updates;
+    let channel = settings.channel;
+    show_toast(toast, "Checking for updates…");
+    in_background(
+        move || {
+            block_on(async {
+                let Some(release) = update::check(&settings).await? else {
+                    return Ok(None);
+                };
+                let target = update::target();
+                let asset = release.asset(BINARY, &target).ok_or_else(|| {
+                    phazeai_core::PhazeError::Other(format!(
+                        "PhazeAI {} has no build for {target}",
+                        release.version
+                    ))
+                })?;
+                let bytes = update::download(&release, asset).await?;
+                update::stage(
+                    &update::updates_dir(),
+                    &release.version,
+                    release.channel,
+                    asset,
+                    &bytes,
+                )?;
+                Ok(Some(release.version))
+            })
+        },
+        move |result| {
+            let message = match result {
+                Ok(Some(version)) => {
+                    format!("PhazeAI {version} is ready and will be installed when you restart")
+                }
+                Ok(None) => format!(
+                    "PhazeAI {CURRENT_VERSION} is the newest release on the {} channel",
+                    channel.label()
+                ),
+                Err(e) => format!("Update failed: {e}"),
+            };
+            show_toast(toast, message);
+        },
+    );
+}
+
+/// Switch to `channel`, saving it to the config.
+pub fn set_channel(channel: RwSignal<Channel>, to: Channel) {
+    if channel.get_untracked() == to {
+        return;
+    }
+    if Settings::update(|s| s.updates.channel = to).is_ok() {
+        channel.set(to);
+    }
+}
@@ -1,4 +1,10 @@
-use floem::reactive::{Memo, RwSignal, SignalGet};
+use floem::{
+    keyboard::NamedKey,
+    reactive::{Memo, RwSignal, SignalGet},
+    style::Style,
+};
+
+use crate::theme::PhazePalette;
 
 /// Safely read a reactive signal that may have been disposed (e.g. from a
 /// removed `dyn_stack` item).  Returns `default` if the signal's scope is gone.
@@ -15,3 +21,46 @@ pub fn safe_get<T: Clone + 'static>(sig: RwSignal<T>, default: T) -> T {
 pub fn safe_get_memo<T: Clone + 'static>(memo: Memo<T>, default: T) -> T {
     std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| memo.get())).unwrap_or(default)
 }
+
+/// Outcome of a navigation key pressed inside a keyboard-driven list overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListNav {
+    None,
+    Close,
+    Move(usize),
+    Accept(usize),
+}
+
+/// Shared ↑/↓/Home/End/Enter/Escape handling for list overlays (palette,
+/// file picker). `selected` is the current row, `count` the number of rows.
+pub fn list_nav_key(key: &NamedKey, selected: usize, count: usize) -> ListNav {
+    match key {
+        NamedKey::Escape => ListNav::Close,
+        NamedKey::ArrowDown if count > 0 => ListNav::Move((selected + 1).min(count - 1)),
+        NamedKey::ArrowUp if count > 0 => ListNav::Move(selected.saturating_sub(1)),
+        NamedKey::PageDown if count > 0 => ListNav::Move((selected + 10).min(count - 1)),
+        NamedKey::PageUp if count > 0 => ListNav::Move(selected.saturating_sub(10)),
+        NamedKey::Home if count > 0 => ListNav::Move(0),
+        NamedKey::End if count > 0 => ListNav::Move(count - 1),
+        NamedKey::Enter if selected < count => ListNav::Accept(selected),
+        _ => ListNav::None,
+    }
+}
+
+/// [`list_nav_key`] for panel lists that may have no row selected yet: the
+/// first move selects the top row (End the bottom one) and Enter does nothing.
+pub fn list_nav_key_opt(key: &NamedKey, selected: Option<usize>, count: usize) -> ListNav {
+    match (selected, list_nav_key(key, selected.unwrap_or(0), count)) {
+        (Some(_), nav) => nav,
+        (None, ListNav::Move(_)) if *key != NamedKey::End => ListNav::Move(0),
+        (None, ListNav::Accept(_)) => ListNav::None,
+        (None, nav) => nav,
+    }
+}
+
+/// Visible outline drawn around a view while it has keyboard focus.
+/// Apply with `.apply(focus_ring(p))` so focus is never conveyed by colour alone.
+pub fn focus_ring(p: &PhazePalette) -> Style {
+    let ring = p.border_focus;
+    Style::new().focus_visible(move |s| s.outline(2.0).outline_color(ring))
+}
@@ -797,3 +797,267 @@ fn tab_stays_clean_after_content_returns_to_original() {
     tab.edit("fn main() {}"); // content back to original
     assert!(!tab.is_dirty());
 }
+
+// ── Virtual list rows (components/virtual_list.rs) ────────────────────────────
+
+use phazeai_ui::components::virtual_list::{row_offsets, visible_rows};
+
+#[test]
+fn virtual_list_offsets_use_measured_heights() {
+    let offsets = row_offsets([Some(40.0), None, Some(10.0)], 20.0);
+    assert_eq!(offsets, vec![0.0, 40.0, 60.0, 70.0]);
+}
+
+#[test]
+fn virtual_list_builds_only_rows_in_view() {
+    let offsets = row_offsets(std::iter::repeat_n(None, 1000), 20.0);
+    assert_eq!(visible_rows(&offsets, 0.0, 100.0, 0), 0..5);
+    assert_eq!(visible_rows(&offsets, 210.0, 310.0, 0), 10..16);
+    // Overscan, clamped at the end of the list
+    assert_eq!(visible_rows(&offsets, 210.0, 310.0, 3), 7..19);
+    assert_eq!(visible_rows(&offsets, 19_950.0, 20_100.0, 3), 994..1000);
+    assert_eq!(visible_rows(&row_offsets([], 20.0), 0.0, 100.0, 3), 0..0);
+}
+
+// ── List keyboard navigation (util.rs) ────────────────────────────────────────
+
+use floem::keyboard::NamedKey;
+use phazeai_ui::util::{list_nav_key_opt, ListNav};
+
+#[test]
+fn list_nav_without_a_selection_starts_at_the_ends() {
+    let nav = |key: NamedKey, selected, count| list_nav_key_opt(&key, selected, count);
+    assert_eq!(nav(NamedKey::ArrowDown, None, 5), ListNav::Move(0));
+    assert_eq!(nav(NamedKey::PageDown, None, 5), ListNav::Move(0));
+    assert_eq!(nav(NamedKey::End, None, 5), ListNav::Move(4));
+    assert_eq!(nav(NamedKey::Enter, None, 5), ListNav::None);
+    assert_eq!(nav(NamedKey::ArrowDown, None, 0), ListNav::None);
+    assert_eq!(nav(NamedKey::ArrowDown, Some(1), 5), ListNav::Move(2));
+    assert_eq!(nav(NamedKey::Enter, Some(3), 5), ListNav::Accept(3));
+    assert_eq!(nav(NamedKey::Escape, Some(3), 5), ListNav::Close);
+}
+
+// ── Startup profile (startup.rs) ──────────────────────────────────────────────
+
+use phazeai_ui::startup::{Phase, StartupProfile};
+use std::time::Duration;
+
+fn startup_phase(name: &'static str, start: u64, took: u64, background: bool) -> Phase {
+    Phase {
+        name,
+        start: Duration::from_millis(start),
+        took: Duration::from_millis(took),
+        background,
+    }
+}
+
+#[test]
+fn startup_report_lists_phases_in_start_order() {
+    let mut profile = StartupProfile::default();
+    profile.record(startup_phase("state", 30, 120, false));
+    profile.record(startup_phase("syntaxes", 1, 80, true));
+    profile.record(startup_phase("settings", 2, 25, false));
+    profile.first_frame = Some(Duration::from_millis(210));
+
+    let report = profile.report(Duration::from_millis(400));
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[1].trim_start().starts_with("syntaxes"));
+    assert!(lines[1].ends_with("(background)"));
+    assert!(lines[2].trim_start().starts_with("settings"));
+    assert!(lines[3].contains("120.0 ms"));
+    assert_eq!(lines[4], "First frame at 210.0 ms, budget 400 ms");
+}
+
+#[test]
+fn startup_over_budget_only_once_framed_late() {
+    let mut profile = StartupProfile::default();
+    assert!(!profile.over_budget(Duration::from_millis(400)));
+    assert!(profile
+        .report(Duration::from_millis(400))
+        .ends_with("No frame yet"));
+
+    profile.first_frame = Some(Duration::from_millis(650));
+    assert!(profile.over_budget(Duration::from_millis(400)));
+    assert!(profile
+        .report(Duration::from_millis(400))
+        .ends_with("— over budget"));
+    assert!(!profile.over_budget(Duration::from_secs(1)));
+}
+
+// ── Single instance (instance.rs) ─────────────────────────────────────────────
+
+use phazeai_ui::instance::{parse_args, parse_target, Target};
+use std::path::PathBuf;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn instance_targets_take_an_optional_line() {
+    assert_eq!(
+        parse_target("src/main.rs:42"),
+        Target {
+            path: PathBuf::from("src/main.rs"),
+            line: Some(42),
+            from_link: false,
+        }
+    );
+    assert_eq!(parse_target("notes:draft").line, None);
+    assert_eq!(parse_target("a.rs:0").path, PathBuf::from("a.rs:0"));
+    assert_eq!(parse_target(":7").path, PathBuf::from(":7"));
+}
+
+#[test]
+fn instance_args_accept_open_and_new_window() {
+    let parsed = parse_args(args(&["open", "a.rs", "--new-window", "b.rs:3"]));
+    assert!(parsed.new_window);
+    assert!(!parsed.profile_startup);
+    assert_eq!(parsed.targets.len(), 2);
+    assert_eq!(parsed.targets[1].line, Some(3));
+
+    let parsed = parse_args(args(&["--profile-startup", "--", "--new-window"]));
+    assert!(parsed.profile_startup && !parsed.new_window);
+    assert_eq!(parsed.targets[0].path, PathBuf::from("--new-window"));
+    assert!(parse_args(args(&[])).targets.is_empty());
+}
+
+#[test]
+fn instance_forwards_files_that_do_not_exist_yet_by_absolute_path() {
+    let path = phazeai_ui::instance::absolute(std::path::Path::new("not-created-yet.rs"));
+    assert!(path.is_absolute());
+    assert!(path.ends_with("not-created-yet.rs"));
+}
+
+#[test]
+fn instance_links_name_a_file_and_line() {
+    use phazeai_ui::instance::parse_url;
+
+    let target = parse_url("phazeai://open?file=%2Fsrc%2Fmy%20app%2Fmain.rs&line=42").unwrap();
+    assert_eq!(target.path, PathBuf::from("/src/my app/main.rs"));
+    assert_eq!(target.line, Some(42));
+    assert!(target.from_link);
+    assert_eq!(
+        parse_url("phazeai://open/?line=0&file=/a.rs").unwrap().line,
+        None
+    );
+    assert!(parse_url("phazeai://open?line=3").is_none());
+    assert!(parse_url("phazeai://settings?file=/a.rs").is_none());
+
+    assert_eq!(
+        parse_url("file:///home/me/a%23b.rs").unwrap().path,
+        PathBuf::from("/home/me/a#b.rs")
+    );
+    assert!(parse_url("file://other-host/a.rs").is_none());
+    // Network paths would reach out to another machine.
+    for link in [
+        "file:////host/share/a.rs",
+        "file:///%5C%5Chost%5Cshare%5Ca.rs",
+        "phazeai://open?file=%5C%5Chost%5Cshare%5Ca.rs",
+        "phazeai://open?file=//host/share/a.rs",
+    ] {
+        assert!(parse_url(link).is_none(), "{link}");
+    }
+    #[cfg(windows)]
+    assert_eq!(
+        parse_url("file:///C:/Users/me/a%20b.rs").unwrap().path,
+        PathBuf::from("C:/Users/me/a b.rs")
+    );
+
+    let parsed = parse_args(args(&[
+        "phazeai://open?file=/x.rs&line=7",
+        "phazeai://settings",
+    ]));
+    assert_eq!(parsed.targets.len(), 1);
+    assert_eq!(parsed.targets[0].line, Some(7));
+
+    // After `--`, a link is a path like any other argument.
+    let parsed = parse_args(args(&["--", "phazeai://open?file=/x.rs"]));
+    assert_eq!(
+        parsed.targets[0].path,
+        PathBuf::from("phazeai://open?file=/x.rs")
+    );
+}
+
+// ── Scheduled job editor ─────────────────────────────────────────────────
+
+#[test]
+fn editing_a_schedule_keeps_its_allowed_tools() {
+    use phazeai_core::agent::ScheduledJob;
+    use phazeai_ui::panels::jobs::{save_schedule, toggle_tool};
+
+    let job = ScheduledJob {
+        name: "nightly".into(),
+        cron: "@nightly".into(),
+        prompt: "summarize new TODOs".into(),
+        workspace: PathBuf::from("/work"),
+        use_worktree: false,
+        enabled: false,
+        allowed_tools: vec!["write_file".into(), "mcp_issue_tracker".into()],
+    };
+    let mut list = vec![job.clone()];
+
+    // An edit that only changes the prompt leaves the tools as they were.
+    let edited = ScheduledJob {
+        prompt: "summarize new FIXMEs".into(),
+        ..job.clone()
+    };
+    save_schedule(&mut list, Some("nightly"), edited).unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].prompt, "summarize new FIXMEs");
+    assert_eq!(list[0].allowed_tools, job.allowed_tools);
+    assert!(!list[0].enabled);
+
+    // Toggling in the picker adds and removes only the tool clicked.
+    let mut tools = list[0].allowed_tools.clone();
+    toggle_tool(&mut tools, "bash");
+    toggle_tool(&mut tools, "write_file");
+    assert_eq!(tools, ["mcp_issue_tracker", "bash"]);
+    let renamed = ScheduledJob {
+        name: "weekly".into(),
+        allowed_tools: tools.clone(),
+        ..list[0].clone()
+    };
+    save_schedule(&mut list, Some("nightly"), renamed).unwrap();
+    assert_eq!(list[0].name, "weekly");
+    assert_eq!(list[0].allowed_tools, tools);
+
+    // New schedules and renames can't take another schedule's name.
+    assert!(save_schedule(&mut list, None, job.clone()).is_ok());
+    assert!(save_schedule(&mut list, None, job.clone()).is_err());
+    assert!(save_schedule(&mut list, Some("weekly"), job).is_err());
+    assert_eq!(list.len(), 2);
+}
+
+// ── LSP sync from editor edits ───────────────────────────────────────────
+
+#[test]
+fn editor_edits_become_utf16_change_ranges() {
+    use floem_editor_core::xi_rope::{Rope, RopeDelta};
+    use phazeai_core::lsp::incremental_change;
+    use phazeai_ui::lsp_bridge::applied_edits;
+
+    assert_eq!(applied_edits(std::iter::empty()), None);
+
+    // Replacing 'é' after a crab (two UTF-16 units) on the second line.
+    let old = "fn main() {\n    \"🦀é\";\n}\n";
+    let rope = Rope::from(old);
+    let start = old.find('é').unwrap();
+    let delta = RopeDelta::simple_edit(start..start + 'é'.len_utf8(), Rope::from("è"), old.len());
+    let edits = applied_edits([(&rope, &delta)]).unwrap();
+    assert_eq!(edits.base_len, old.len());
+    let new = old.replace('é', "è");
+    assert_eq!(edits.changes, vec![incremental_change(old, &new).unwrap()]);
+
+    // Several deltas in one update are sent in order, each against the text
+    // the one before it left.
+    let second = Rope::from(new.as_str());
+    let joined = RopeDelta::simple_edit(11..13, Rope::from(""), new.len());
+    let edits = applied_edits([(&rope, &delta), (&second, &joined)]).unwrap();
+    assert_eq!(edits.changes.len(), 2);
+    let range = edits.changes[1].range.unwrap();
+    assert_eq!((range.start.line, range.start.character), (0, 11));
+    assert_eq!((range.end.line, range.end.character), (1, 1));
+    assert_eq!(edits.changes[1].text, "");
+}
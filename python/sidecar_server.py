@@ -7,6 +7,7 @@ via JSON-RPC 2.0 messages over stdin/stdout (one JSON object per line).
 
 Methods:
   - ping: Health check
+  - handshake: Server name, version and protocol version
   - search: Semantic search over codebase embeddings
   - build_index: Build embedding index from project paths
   - analyze: Analyze a source file
@@ -17,6 +18,11 @@ import sys
 import traceback
 from typing import Any, Dict, Optional
 
+SERVER_NAME = "phazeai-sidecar"
+SERVER_VERSION = "0.1.0"
+# Must match PROTOCOL_VERSION in crates/phazeai-sidecar/src/protocol.rs.
+PROTOCOL_VERSION = 1
+
 # Lazy imports for optional dependencies
 embedding_system = None
 analyzer = None
@@ -48,6 +54,14 @@ def handle_ping(params: Optional[Dict]) -> Any:
     return {"status": "ok"}
 
 
+def handle_handshake(params: Optional[Dict]) -> Any:
+    return {
+        "name": SERVER_NAME,
+        "version": SERVER_VERSION,
+        "protocol": PROTOCOL_VERSION,
+    }
+
+
 def handle_search(params: Dict) -> Any:
     es = get_embedding_system()
     if es is None:
@@ -83,6 +97,7 @@ def handle_analyze(params: Dict) -> Any:
 
 METHODS = {
     "ping": handle_ping,
+    "handshake": handle_handshake,
     "search": handle_search,
     "build_index": handle_build_index,
     "analyze": handle_analyze,
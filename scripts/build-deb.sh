@@ -43,13 +43,18 @@ Type=Application
 Name=PhazeAI IDE
 GenericName=AI-Powered IDE
 Comment=Local-first AI-native IDE with multi-model support
-Exec=phazeai-ide %F
+Exec=phazeai-ide %U
 Icon=phazeai-ide
 Terminal=false
 Categories=Development;IDE;TextEditor;
-MimeType=text/plain;
+MimeType=text/plain;inode/directory;x-scheme-handler/phazeai;
 Keywords=ide;editor;ai;rust;python;coding;
 StartupWMClass=phazeai-ide
+Actions=new-window;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=phazeai-ide --new-window
 EOF
 
 # Changelog
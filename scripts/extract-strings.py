@@ -0,0 +1,86 @@
+#!/usr/bin/env python3
+"""Extract translatable UI strings into crates/phazeai-ui/locales/phazeai.pot.
+
+Usage: python3 scripts/extract-strings.py [--check]
+
+Picks up string literals passed to `tr(...)`, `tr_args(...)`, palette command
+labels and static `show_toast(..., "...")` messages. Re-run after adding UI
+text, then merge into existing catalogs with `msgmerge -U <lang>.po phazeai.pot`.
+
+With `--check` nothing is written; the script exits non-zero when the msgids in
+the template differ from the ones in the source (CI runs this). Reference
+comments are ignored so unrelated line shifts don't fail the check.
+"""
+
+import pathlib
+import re
+import sys
+
+ROOT = pathlib.Path(__file__).resolve().parent.parent
+SRC = ROOT / "crates" / "phazeai-ui" / "src"
+OUT = ROOT / "crates" / "phazeai-ui" / "locales" / "phazeai.pot"
+
+LITERAL = r'"((?:[^"\\]|\\.)*)"'
+PATTERNS = [
+    re.compile(r"\btr(?:_args)?\(\s*" + LITERAL),
+    re.compile(r"\blabel:\s*" + LITERAL),
+    re.compile(r"\bshow_toast\([^,]+,\s*" + LITERAL + r"(?:\.to_string\(\))?\s*\)"),
+    re.compile(r"\bmake_item\(\s*" + LITERAL),
+]
+
+
+def template_msgids(text: str) -> set[str]:
+    ids = set(re.findall(r'^msgid "((?:[^"\\]|\\.)*)"$', text, re.MULTILINE))
+    ids.discard("")
+    return ids
+
+
+def main() -> None:
+    check = "--check" in sys.argv[1:]
+    found: dict[str, list[str]] = {}
+    for path in sorted(SRC.rglob("*.rs")):
+        text = path.read_text(encoding="utf-8")
+        rel = path.relative_to(ROOT)
+        for pattern in PATTERNS:
+            for m in pattern.finditer(text):
+                msgid = m.group(1)
+                if not msgid.strip():
+                    continue
+                line = text.count("\n", 0, m.start()) + 1
+                found.setdefault(msgid, []).append(f"{rel}:{line}")
+
+    out = [
+        "# PhazeAI IDE UI strings.",
+        "# Generated by scripts/extract-strings.py — do not edit by hand.",
+        'msgid ""',
+        'msgstr ""',
+        '"Content-Type: text/plain; charset=UTF-8\\n"',
+        "",
+    ]
+    for msgid in sorted(found):
+        out.append("#: " + " ".join(found[msgid][:3]))
+        out.append(f'msgid "{msgid}"')
+        out.append('msgstr ""')
+        out.append("")
+
+    if check:
+        current = template_msgids(OUT.read_text(encoding="utf-8")) if OUT.exists() else set()
+        missing = sorted(set(found) - current)
+        stale = sorted(current - set(found))
+        for msgid in missing:
+            print(f'missing: "{msgid}"')
+        for msgid in stale:
+            print(f'stale:   "{msgid}"')
+        if missing or stale:
+            print(f"{OUT.relative_to(ROOT)} is out of date; run python3 scripts/extract-strings.py")
+            sys.exit(1)
+        print(f"{OUT.relative_to(ROOT)} is up to date ({len(found)} strings)")
+        return
+
+    OUT.parent.mkdir(parents=True, exist_ok=True)
+    OUT.write_text("\n".join(out), encoding="utf-8")
+    print(f"wrote {len(found)} strings to {OUT.relative_to(ROOT)}")
+
+
+if __name__ == "__main__":
+    main()
@@ -11,6 +11,7 @@ import re
 from pathlib import Path
 from typing import Dict, List, Any, Optional, Set
 from collections import defaultdict
+from difflib import SequenceMatcher
 from math import log, sqrt
 
 
@@ -36,6 +37,22 @@ SKIP_DIRS = {
     'build', '.next', '.venv', 'venv', 'vendor'
 }
 
+# Snippets for find_similar: files are cut where a definition starts, and
+# into pieces of at most CHUNK_MAX_LINES lines
+CHUNK_MAX_LINES = 60
+CHUNK_MIN_LINES = 3
+DEFINITION_START = re.compile(
+    r'^\s*(?:(?:pub(?:\([^)]*\))?|export|default|async|static|public|private|protected)\s+)*'
+    r'(?:fn|def|class|struct|enum|trait|impl|func|function|interface|type)\b'
+)
+# Attributes, decorators and doc comments go with the definition below them
+DEFINITION_PREFIXES = ('#[', '@', '///')
+# Candidates compared token by token with the query, among the best by TF-IDF
+SIMILAR_CANDIDATES = 50
+# Snippets whose tokens match the query's this closely, names aside, are
+# near-duplicates
+DUPLICATE_RATIO = 0.9
+
 
 class TfidfIndex:
     """Simple TF-IDF based search index using only stdlib."""
@@ -116,6 +133,22 @@ class TfidfIndex:
         # Invalidate IDF cache
         self.idf_cache.clear()
 
+    def remove_documents(self, path: str) -> None:
+        """Remove every document indexed for a path, before indexing it again."""
+        kept_docs = []
+        kept_tfs = []
+        for doc, tf in zip(self.documents, self.doc_term_freq):
+            if doc['metadata']['path'] == path:
+                for term in tf:
+                    self.term_doc_freq[term] -= 1
+            else:
+                kept_docs.append(doc)
+                kept_tfs.append(tf)
+        if len(kept_docs) != len(self.documents):
+            self.documents = kept_docs
+            self.doc_term_freq = kept_tfs
+            self.idf_cache.clear()
+
     def search(self, query: str, top_k: int = 5) -> List[Dict[str, Any]]:
         """Search the index and return top_k results."""
         if not self.documents:
@@ -227,6 +260,8 @@ class CodeIndex:
 
     def __init__(self):
         self.index = TfidfIndex()
+        # The same files cut into snippets, for find_similar
+        self.chunks = TfidfIndex()
         self.indexed_files: Set[str] = set()
         self.doc_counter = 0
 
@@ -275,6 +310,51 @@ class CodeIndex:
 
         return snippet or content[:max_length]
 
+    def split_chunks(self, content: str) -> List[Dict[str, Any]]:
+        """Cut a file into snippets at definitions, with 1-based line ranges.
+        A snippet too short to stand alone runs on into the next definition."""
+        lines = content.split('\n')
+        chunks = []
+        start = 0
+        for i in range(1, len(lines) + 1):
+            if i < len(lines) and DEFINITION_START.match(lines[i]):
+                # Cut above the definition's attributes and doc comments
+                cut = i
+                while cut > start and lines[cut - 1].strip().startswith(DEFINITION_PREFIXES):
+                    cut -= 1
+            elif i == len(lines) or i - start >= CHUNK_MAX_LINES:
+                cut = i
+            else:
+                continue
+
+            body = lines[start:cut]
+            if sum(1 for line in body if line.strip()) < CHUNK_MIN_LINES:
+                if i < len(lines) and i - start < CHUNK_MAX_LINES:
+                    continue
+            else:
+                chunks.append({
+                    'start_line': start + 1,
+                    'end_line': cut,
+                    'text': '\n'.join(body)
+                })
+            start = cut
+        return chunks
+
+    def add_chunks(self, path: str, content: str) -> None:
+        """Index a file's snippets, replacing those of an earlier build."""
+        self.chunks.remove_documents(path)
+        for chunk in self.split_chunks(content):
+            self.doc_counter += 1
+            self.chunks.add_document(
+                doc_id=self.doc_counter,
+                text=chunk['text'],
+                metadata={
+                    'path': path,
+                    'start_line': chunk['start_line'],
+                    'end_line': chunk['end_line']
+                }
+            )
+
     def build_index(self, paths: List[str]) -> Dict[str, Any]:
         """Build index from given paths."""
         indexed_count = 0
@@ -313,7 +393,8 @@ class CodeIndex:
                     skipped_count += 1
                     continue
 
-                # Index the file
+                # Index the file, replacing it if it was indexed before
+                self.index.remove_documents(str(file_path))
                 self.doc_counter += 1
                 self.index.add_document(
                     doc_id=self.doc_counter,
@@ -324,6 +405,7 @@ class CodeIndex:
                         'ext': file_path.suffix
                     }
                 )
+                self.add_chunks(str(file_path), content)
                 self.indexed_files.add(str(file_path))
                 indexed_count += 1
 
@@ -353,6 +435,56 @@ class CodeIndex:
 
         return matches
 
+    @staticmethod
+    def code_shape(code: str) -> List[str]:
+        """Tokens with each name replaced by the order it first appears in,
+        so a copy with renamed variables has the same shape."""
+        names: Dict[str, str] = {}
+        shape = []
+        for token in re.findall(r'\w+|[^\w\s]', code):
+            if re.match(r'\w', token) and not token.isdigit():
+                token = names.setdefault(token, f'#{len(names)}')
+            shape.append(token)
+        return shape
+
+    def find_similar(self, code: str, top_k: int = 5, path: Optional[str] = None,
+                     start_line: Optional[int] = None,
+                     end_line: Optional[int] = None) -> List[Dict[str, Any]]:
+        """Find snippets like the given code, leaving out the code's own lines."""
+        own_path = str(Path(path).resolve()) if path else None
+        results = self.chunks.search(code, len(self.chunks.documents))
+        shape = self.code_shape(code)
+
+        matches = []
+        for result in results:
+            meta = result['doc']['metadata']
+            if own_path == meta['path'] and start_line is not None and end_line is not None \
+                    and meta['start_line'] <= end_line and start_line <= meta['end_line']:
+                continue
+
+            text = result['doc']['text']
+            ratio = SequenceMatcher(None, shape, self.code_shape(text), autojunk=False).ratio()
+            matches.append({
+                'file': meta['path'],
+                'start_line': meta['start_line'],
+                'end_line': meta['end_line'],
+                'score': round(result['score'], 4),
+                'kind': 'duplicate' if ratio >= DUPLICATE_RATIO else 'related',
+                'snippet': text
+            })
+            if len(matches) >= SIMILAR_CANDIDATES:
+                break
+
+        # Near-duplicates first, each group best first
+        matches.sort(key=lambda m: m['kind'] != 'duplicate')
+        return matches[:top_k]
+
+
+SERVER_NAME = 'phazeai-sidecar'
+SERVER_VERSION = '0.1.0'
+# Must match PROTOCOL_VERSION in crates/phazeai-sidecar/src/protocol.rs.
+PROTOCOL_VERSION = 1
+
 
 class JsonRpcServer:
     """JSON-RPC 2.0 server over stdio."""
@@ -364,6 +496,14 @@ class JsonRpcServer:
         """Handle ping request."""
         return "pong"
 
+    def handle_handshake(self, params: Optional[Dict]) -> Dict[str, Any]:
+        """Handle handshake request: identify the server and its protocol."""
+        return {
+            'name': SERVER_NAME,
+            'version': SERVER_VERSION,
+            'protocol': PROTOCOL_VERSION
+        }
+
     def handle_build_index(self, params: Dict) -> Dict[str, Any]:
         """Handle build_index request."""
         paths = params.get('paths', [])
@@ -387,6 +527,25 @@ class JsonRpcServer:
 
         return {'matches': matches}
 
+    def handle_find_similar(self, params: Dict) -> Dict[str, Any]:
+        """Handle find_similar request."""
+        code = params.get('code')
+        if not code or not code.strip():
+            raise ValueError("Missing 'code' parameter")
+
+        if not self.code_index.indexed_files:
+            raise RuntimeError("Index not built")
+
+        matches = self.code_index.find_similar(
+            code,
+            params.get('top_k', 5),
+            params.get('path'),
+            params.get('start_line'),
+            params.get('end_line')
+        )
+
+        return {'matches': matches}
+
     def handle_analyze(self, params: Dict) -> Dict[str, Any]:
         """Handle analyze request."""
         content = params.get('content')
@@ -417,10 +576,14 @@ class JsonRpcServer:
         try:
             if method == 'ping':
                 result = self.handle_ping(params)
+            elif method == 'handshake':
+                result = self.handle_handshake(params)
             elif method == 'build_index':
                 result = self.handle_build_index(params)
             elif method == 'search':
                 result = self.handle_search(params)
+            elif method == 'find_similar':
+                result = self.handle_find_similar(params)
             elif method == 'analyze':
                 result = self.handle_analyze(params)
             else:
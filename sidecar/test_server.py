@@ -85,9 +85,24 @@ def test_server():
         assert 'matches' in resp['result'], "No matches in search result"
         print("✓ Search test passed\n")
 
-        # Test 5: Invalid method
-        print("Test 5: Invalid method")
-        req = {"jsonrpc": "2.0", "id": 5, "method": "invalid"}
+        # Test 5: Find similar
+        print("Test 5: Find similar")
+        req = {
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "find_similar",
+            "params": {"code": "pub fn new() -> Self {\n    Self::default()\n}\n", "top_k": 3}
+        }
+        resp = send_request(req)
+        print(f"Request: {json.dumps(req, indent=2)}")
+        print(f"Response: {json.dumps(resp, indent=2)}")
+        assert 'result' in resp, "Find similar failed"
+        assert all('start_line' in m for m in resp['result']['matches']), "Matches lack line ranges"
+        print("✓ Find similar test passed\n")
+
+        # Test 6: Invalid method
+        print("Test 6: Invalid method")
+        req = {"jsonrpc": "2.0", "id": 6, "method": "invalid"}
         resp = send_request(req)
         print(f"Request: {req}")
         print(f"Response: {resp}")